@@ -1,12 +1,22 @@
+use std::path::Path;
+
 use anyhow::Context as _;
 use clap::{Parser, Subcommand};
 use tokio::io::{self, AsyncReadExt};
-use zksync_config::{ContractsConfig, DBConfig, ETHClientConfig, ETHSenderConfig, PostgresConfig};
-use zksync_core::block_reverter::{
-    BlockReverter, BlockReverterEthConfig, BlockReverterFlags, L1ExecutedBatchesRevert,
+use zksync_config::{
+    configs::chain::NetworkConfig, ContractsConfig, DBConfig, ETHClientConfig, ETHSenderConfig,
+    ObjectStoreConfig, PostgresConfig,
+};
+use zksync_core::{
+    block_reverter::{
+        replay_l1_batch, BlockReverter, BlockReverterEthConfig, BlockReverterFlags,
+        L1ExecutedBatchesRevert,
+    },
+    witness_bundle_exporter::WitnessBundleExporter,
 };
 use zksync_dal::ConnectionPool;
 use zksync_env_config::FromEnv;
+use zksync_object_store::ObjectStoreFactory;
 use zksync_types::{L1BatchNumber, U256};
 
 #[derive(Debug, Parser)]
@@ -64,6 +74,25 @@ enum Command {
     /// Clears failed L1 transactions.
     #[command(name = "clear-failed-transactions")]
     ClearFailedL1Transactions,
+
+    /// Re-executes a sealed L1 batch and compares the results with the data persisted in Postgres.
+    #[command(name = "replay-l1-batch")]
+    ReplayL1Batch {
+        /// L1 batch number to replay.
+        #[arg(long)]
+        l1_batch_number: u32,
+        /// Outputs the replay report as a JSON object, so that it is machine-readable.
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Exports a self-contained witness bundle for a sealed L1 batch to the object store.
+    #[command(name = "export-witness-bundle")]
+    ExportWitnessBundle {
+        /// L1 batch number to export the witness bundle for.
+        #[arg(long)]
+        l1_batch_number: u32,
+    },
 }
 
 #[tokio::main]
@@ -102,9 +131,9 @@ async fn main() -> anyhow::Result<()> {
     .context("failed to build a connection pool")?;
     let mut block_reverter = BlockReverter::new(
         db_config.state_keeper_db_path,
-        db_config.merkle_tree.path,
+        db_config.merkle_tree.path.clone(),
         Some(config),
-        connection_pool,
+        connection_pool.clone(),
         L1ExecutedBatchesRevert::Disallowed,
     );
 
@@ -184,6 +213,38 @@ async fn main() -> anyhow::Result<()> {
                 .await
         }
         Command::ClearFailedL1Transactions => block_reverter.clear_failed_l1_transactions().await,
+        Command::ReplayL1Batch {
+            l1_batch_number,
+            json,
+        } => {
+            let network = NetworkConfig::from_env().context("NetworkConfig::from_env()")?;
+            let report = replay_l1_batch(
+                &connection_pool,
+                Path::new(&db_config.merkle_tree.path),
+                L1BatchNumber(l1_batch_number),
+                network.zksync_network_id,
+            )
+            .await?;
+            if json {
+                println!("{}", serde_json::to_string(&report).unwrap());
+            } else {
+                println!("Replay report: {report:#?}");
+            }
+            anyhow::ensure!(
+                report.is_consistent(),
+                "replayed L1 batch #{l1_batch_number} diverges from the persisted data"
+            );
+        }
+        Command::ExportWitnessBundle { l1_batch_number } => {
+            let object_store_config =
+                ObjectStoreConfig::from_env().context("ObjectStoreConfig::from_env()")?;
+            let object_store = ObjectStoreFactory::new(object_store_config)
+                .create_store()
+                .await;
+            let exporter = WitnessBundleExporter::new(connection_pool, object_store);
+            let path = exporter.export(L1BatchNumber(l1_batch_number)).await?;
+            println!("Exported witness bundle for L1 batch #{l1_batch_number} to `{path}`");
+        }
     }
     Ok(())
 }
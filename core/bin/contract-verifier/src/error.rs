@@ -22,4 +22,12 @@ pub enum ContractVerifierError {
     AbstractContract(String),
     #[error("Failed to deserialize standard JSON input")]
     FailedToDeserializeInput,
+    #[error("Unsupported standard JSON input language: {0}")]
+    UnsupportedLanguage(String),
+    #[error("Library {0} has invalid address {1}")]
+    InvalidLibraryAddress(String, String),
+    #[error(
+        "Contract with {0} name is present in several source files; specify it as `<file>:{0}`"
+    )]
+    AmbiguousContractName(String),
 }
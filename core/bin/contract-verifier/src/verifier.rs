@@ -102,16 +102,19 @@ impl ContractVerifier {
     ) -> Result<CompilationArtifacts, ContractVerifierError> {
         // Users may provide either just contract name or
         // source file name and contract name joined with ":".
+        // For multi-file standard JSON input, the file name may be omitted if the contract name is unique.
         let (file_name, contract_name) =
             if let Some((file_name, contract_name)) = request.req.contract_name.rsplit_once(':') {
-                (file_name.to_string(), contract_name.to_string())
+                (Some(file_name.to_string()), contract_name.to_string())
+            } else if let SourceCodeData::StandardJsonInput(_) = &request.req.source_code_data {
+                (None, request.req.contract_name.clone())
             } else {
                 (
-                    format!("{}.sol", request.req.contract_name),
+                    Some(format!("{}.sol", request.req.contract_name)),
                     request.req.contract_name.clone(),
                 )
             };
-        let input = Self::build_zksolc_input(request.clone(), file_name.clone())?;
+        let input = Self::build_zksolc_input(request.clone(), file_name.as_deref())?;
 
         let zksync_home = env::var("ZKSYNC_HOME").unwrap_or_else(|_| ".".into());
         let zksolc_path = Path::new(&zksync_home)
@@ -162,14 +165,11 @@ impl ContractVerifier {
                     }
                 }
 
-                let contracts = output["contracts"]
-                    .get(file_name.as_str())
-                    .cloned()
-                    .ok_or(ContractVerifierError::MissingSource(file_name))?;
-                let contract = contracts
-                    .get(&contract_name)
-                    .cloned()
-                    .ok_or(ContractVerifierError::MissingContract(contract_name))?;
+                let contract = Self::find_zksolc_contract(
+                    &output["contracts"],
+                    file_name.as_deref(),
+                    &contract_name,
+                )?;
                 let bytecode_str = contract["evm"]["bytecode"]["object"].as_str().ok_or(
                     ContractVerifierError::AbstractContract(request.req.contract_name),
                 )?;
@@ -198,17 +198,50 @@ impl ContractVerifier {
         }
     }
 
+    /// Finds the compiled contract in the standard JSON output. If `file_name` is not specified,
+    /// the contract name must be unique among all source files.
+    fn find_zksolc_contract(
+        contracts: &serde_json::Value,
+        file_name: Option<&str>,
+        contract_name: &str,
+    ) -> Result<serde_json::Value, ContractVerifierError> {
+        if let Some(file_name) = file_name {
+            let file_contracts = contracts
+                .get(file_name)
+                .ok_or_else(|| ContractVerifierError::MissingSource(file_name.to_owned()))?;
+            return file_contracts
+                .get(contract_name)
+                .cloned()
+                .ok_or_else(|| ContractVerifierError::MissingContract(contract_name.to_owned()));
+        }
+
+        let mut matching_contracts = contracts
+            .as_object()
+            .into_iter()
+            .flat_map(|files| files.values())
+            .filter_map(|file_contracts| file_contracts.get(contract_name));
+        let contract = matching_contracts
+            .next()
+            .ok_or_else(|| ContractVerifierError::MissingContract(contract_name.to_owned()))?;
+        if matching_contracts.next().is_some() {
+            return Err(ContractVerifierError::AmbiguousContractName(
+                contract_name.to_owned(),
+            ));
+        }
+        Ok(contract.clone())
+    }
+
     async fn compile_zkvyper(
         request: VerificationRequest,
         config: ContractVerifierConfig,
     ) -> Result<CompilationArtifacts, ContractVerifierError> {
         // Users may provide either just contract name or
         // source file name and contract name joined with ":".
-        let contract_name =
-            if let Some((_file_name, contract_name)) = request.req.contract_name.rsplit_once(':') {
-                contract_name.to_string()
+        let (file_name, contract_name) =
+            if let Some((file_name, contract_name)) = request.req.contract_name.rsplit_once(':') {
+                (Some(file_name.to_string()), contract_name.to_string())
             } else {
-                request.req.contract_name.clone()
+                (None, request.req.contract_name.clone())
             };
         let input = Self::build_zkvyper_input(request.clone())?;
 
@@ -243,26 +276,45 @@ impl ContractVerifier {
             .await
             .map_err(|_| ContractVerifierError::CompilationTimeout)??;
 
-        let file_name = format!("{contract_name}.vy");
+        let artifact = Self::find_zkvyper_contract(&output, file_name.as_deref(), &contract_name)?;
+        let bytecode_str = artifact["bytecode"]
+            .as_str()
+            .ok_or(ContractVerifierError::InternalError)?;
+        let bytecode = hex::decode(bytecode_str).unwrap();
+        Ok(CompilationArtifacts {
+            abi: artifact["abi"].clone(),
+            bytecode,
+        })
+    }
+
+    /// Finds the compiled contract in the `combined_json` output of `zkvyper`. If the source file is specified,
+    /// it's matched by the path suffix, which allows to disambiguate contracts with the same name
+    /// in different directories.
+    fn find_zkvyper_contract(
+        output: &serde_json::Value,
+        file_name: Option<&str>,
+        contract_name: &str,
+    ) -> Result<serde_json::Value, ContractVerifierError> {
+        let file_name = match file_name {
+            Some(name) if name.ends_with(".vy") => name.to_owned(),
+            Some(name) => format!("{name}.vy"),
+            None => format!("{contract_name}.vy"),
+        };
         let object = output
             .as_object()
-            .cloned()
             .ok_or(ContractVerifierError::InternalError)?;
         for (path, artifact) in object {
-            let path = Path::new(&path);
-            if path.file_name().unwrap().to_str().unwrap() == file_name {
-                let bytecode_str = artifact["bytecode"]
-                    .as_str()
-                    .ok_or(ContractVerifierError::InternalError)?;
-                let bytecode = hex::decode(bytecode_str).unwrap();
-                return Ok(CompilationArtifacts {
-                    abi: artifact["abi"].clone(),
-                    bytecode,
-                });
+            let path = Path::new(path);
+            let is_matching = path.ends_with(&file_name)
+                && path.file_stem().and_then(|stem| stem.to_str()) == Some(contract_name);
+            if is_matching {
+                return Ok(artifact.clone());
             }
         }
 
-        Err(ContractVerifierError::MissingContract(contract_name))
+        Err(ContractVerifierError::MissingContract(
+            contract_name.to_owned(),
+        ))
     }
 
     async fn compile(
@@ -277,7 +329,7 @@ impl ContractVerifier {
 
     fn build_zksolc_input(
         request: VerificationRequest,
-        file_name: String,
+        file_name: Option<&str>,
     ) -> Result<ZkSolcInput, ContractVerifierError> {
         let default_output_selection = serde_json::json!(
             {
@@ -293,11 +345,13 @@ impl ContractVerifier {
                 let source = Source {
                     content: source_code,
                 };
+                let file_name = file_name.expect("file name is always set for single-file input");
                 let sources: HashMap<String, Source> =
-                    vec![(file_name, source)].into_iter().collect();
+                    vec![(file_name.to_owned(), source)].into_iter().collect();
                 let optimizer = Optimizer::new(request.req.optimization_used);
 
                 let settings = Settings {
+                    remappings: None,
                     libraries: None,
                     output_selection: Some(default_output_selection),
                     optimizer,
@@ -315,6 +369,7 @@ impl ContractVerifier {
                 let mut compiler_input: StandardJson =
                     serde_json::from_value(serde_json::Value::Object(map))
                         .map_err(|_| ContractVerifierError::FailedToDeserializeInput)?;
+                Self::validate_standard_json(&compiler_input, file_name)?;
                 // Set default output selection even if it is different in request.
                 compiler_input.settings.output_selection = Some(default_output_selection);
                 Ok(ZkSolcInput::StandardJson(compiler_input))
@@ -326,11 +381,43 @@ impl ContractVerifier {
         }
     }
 
+    /// Checks standard JSON input provided by the user so that obviously invalid inputs are rejected
+    /// with a descriptive error instead of a compiler failure.
+    fn validate_standard_json(
+        input: &StandardJson,
+        file_name: Option<&str>,
+    ) -> Result<(), ContractVerifierError> {
+        if input.language != "Solidity" {
+            return Err(ContractVerifierError::UnsupportedLanguage(
+                input.language.clone(),
+            ));
+        }
+        if let Some(file_name) = file_name {
+            if !input.sources.contains_key(file_name) {
+                return Err(ContractVerifierError::MissingSource(file_name.to_owned()));
+            }
+        }
+
+        let libraries = input.settings.libraries.iter().flatten();
+        for (library_file, file_libraries) in libraries {
+            for (library_name, address) in file_libraries {
+                if address.parse::<Address>().is_err() {
+                    return Err(ContractVerifierError::InvalidLibraryAddress(
+                        format!("{library_file}:{library_name}"),
+                        address.clone(),
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
     fn build_zkvyper_input(
         request: VerificationRequest,
     ) -> Result<ZkVyperInput, ContractVerifierError> {
         let sources = match request.req.source_code_data {
             SourceCodeData::VyperMultiFile(s) => s,
+            SourceCodeData::StandardJsonInput(input) => Self::vyper_standard_json_sources(input)?,
             _ => panic!("Unexpected SourceCode variant"),
         };
         Ok(ZkVyperInput {
@@ -339,6 +426,26 @@ impl ContractVerifier {
         })
     }
 
+    /// Extracts source files from Vyper standard JSON input, so that they can be compiled in the same way
+    /// as multi-file Vyper input.
+    fn vyper_standard_json_sources(
+        input: serde_json::Map<String, serde_json::Value>,
+    ) -> Result<HashMap<String, String>, ContractVerifierError> {
+        let sources = input
+            .get("sources")
+            .and_then(serde_json::Value::as_object)
+            .ok_or(ContractVerifierError::FailedToDeserializeInput)?;
+        sources
+            .iter()
+            .map(|(path, source)| {
+                let content = source["content"]
+                    .as_str()
+                    .ok_or(ContractVerifierError::FailedToDeserializeInput)?;
+                Ok((path.clone(), content.to_owned()))
+            })
+            .collect()
+    }
+
     fn decode_constructor_arguments_from_calldata(
         calldata: DeployContractCalldata,
         contract_address_to_verify: Address,
@@ -534,3 +641,149 @@ impl JobProcessor for ContractVerifier {
         Ok(1)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn standard_json(value: serde_json::Value) -> StandardJson {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn finding_zksolc_contract() {
+        let contracts = json!({
+            "contracts/Token.sol": { "Token": { "id": 0 }, "Ownable": { "id": 1 } },
+            "contracts/Vault.sol": { "Vault": { "id": 2 }, "Ownable": { "id": 3 } },
+        });
+
+        let contract = ContractVerifier::find_zksolc_contract(
+            &contracts,
+            Some("contracts/Vault.sol"),
+            "Ownable",
+        )
+        .unwrap();
+        assert_eq!(contract, json!({ "id": 3 }));
+        let contract = ContractVerifier::find_zksolc_contract(&contracts, None, "Vault").unwrap();
+        assert_eq!(contract, json!({ "id": 2 }));
+
+        let err = ContractVerifier::find_zksolc_contract(&contracts, None, "Ownable").unwrap_err();
+        assert!(
+            matches!(err, ContractVerifierError::AmbiguousContractName(name) if name == "Ownable")
+        );
+        let err = ContractVerifier::find_zksolc_contract(&contracts, None, "Missing").unwrap_err();
+        assert!(matches!(err, ContractVerifierError::MissingContract(name) if name == "Missing"));
+        let err = ContractVerifier::find_zksolc_contract(
+            &contracts,
+            Some("contracts/Token.sol"),
+            "Vault",
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractVerifierError::MissingContract(name) if name == "Vault"));
+        let err = ContractVerifier::find_zksolc_contract(&contracts, Some("Vault.sol"), "Vault")
+            .unwrap_err();
+        assert!(matches!(err, ContractVerifierError::MissingSource(name) if name == "Vault.sol"));
+    }
+
+    #[test]
+    fn validating_standard_json() {
+        let input = standard_json(json!({
+            "language": "Solidity",
+            "sources": { "contracts/Token.sol": { "content": "contract Token {}" } },
+            "settings": {
+                "outputSelection": null,
+                "libraries": {
+                    "contracts/Math.sol": { "Math": "0x0000000000000000000000000000000000000001" },
+                },
+            },
+        }));
+        ContractVerifier::validate_standard_json(&input, Some("contracts/Token.sol")).unwrap();
+        ContractVerifier::validate_standard_json(&input, None).unwrap();
+        let err = ContractVerifier::validate_standard_json(&input, Some("Token.sol")).unwrap_err();
+        assert!(matches!(err, ContractVerifierError::MissingSource(name) if name == "Token.sol"));
+
+        let input = standard_json(json!({
+            "language": "Yul",
+            "sources": { "Token.yul": { "content": "object \"Token\" {}" } },
+            "settings": { "outputSelection": null },
+        }));
+        let err = ContractVerifier::validate_standard_json(&input, None).unwrap_err();
+        assert!(matches!(err, ContractVerifierError::UnsupportedLanguage(lang) if lang == "Yul"));
+
+        let input = standard_json(json!({
+            "language": "Solidity",
+            "sources": { "Token.sol": { "content": "contract Token {}" } },
+            "settings": {
+                "outputSelection": null,
+                "libraries": { "Math.sol": { "Math": "0x01" } },
+            },
+        }));
+        let err = ContractVerifier::validate_standard_json(&input, None).unwrap_err();
+        assert!(matches!(
+            err,
+            ContractVerifierError::InvalidLibraryAddress(library, address)
+                if library == "Math.sol:Math" && address == "0x01"
+        ));
+    }
+
+    #[test]
+    fn finding_zkvyper_contract() {
+        let output = json!({
+            "/tmp/dir/contracts/Token.vy": { "bytecode": "00" },
+            "/tmp/dir/interfaces/Token.vy": { "bytecode": "01" },
+            "/tmp/dir/contracts/Vault.vy": { "bytecode": "02" },
+        });
+
+        let artifact =
+            ContractVerifier::find_zkvyper_contract(&output, Some("contracts/Token.vy"), "Token")
+                .unwrap();
+        assert_eq!(artifact, json!({ "bytecode": "00" }));
+        // The `.vy` extension may be omitted.
+        let artifact =
+            ContractVerifier::find_zkvyper_contract(&output, Some("interfaces/Token"), "Token")
+                .unwrap();
+        assert_eq!(artifact, json!({ "bytecode": "01" }));
+        let artifact = ContractVerifier::find_zkvyper_contract(&output, None, "Vault").unwrap();
+        assert_eq!(artifact, json!({ "bytecode": "02" }));
+
+        // Path components must match exactly, not just as string suffixes.
+        let err = ContractVerifier::find_zkvyper_contract(&output, Some("acts/Token.vy"), "Token")
+            .unwrap_err();
+        assert!(matches!(err, ContractVerifierError::MissingContract(name) if name == "Token"));
+        // The contract name must match the file name.
+        let err =
+            ContractVerifier::find_zkvyper_contract(&output, Some("contracts/Vault.vy"), "Token")
+                .unwrap_err();
+        assert!(matches!(err, ContractVerifierError::MissingContract(name) if name == "Token"));
+    }
+
+    #[test]
+    fn extracting_vyper_standard_json_sources() {
+        let input = json!({
+            "language": "Vyper",
+            "sources": {
+                "contracts/Token.vy": { "content": "# @version 0.3.3" },
+                "interfaces/IToken.vy": { "content": "# @version 0.3.3\n" },
+            },
+            "settings": {},
+        });
+        let serde_json::Value::Object(input) = input else {
+            unreachable!();
+        };
+        let sources = ContractVerifier::vyper_standard_json_sources(input).unwrap();
+        assert_eq!(sources.len(), 2);
+        assert_eq!(sources["contracts/Token.vy"], "# @version 0.3.3");
+
+        let input = json!({ "language": "Vyper", "sources": { "Token.vy": "# @version 0.3.3" } });
+        let serde_json::Value::Object(input) = input else {
+            unreachable!();
+        };
+        let err = ContractVerifier::vyper_standard_json_sources(input).unwrap_err();
+        assert!(matches!(
+            err,
+            ContractVerifierError::FailedToDeserializeInput
+        ));
+    }
+}
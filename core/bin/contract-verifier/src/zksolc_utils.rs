@@ -39,6 +39,9 @@ pub enum MetadataHash {
     /// Do not include bytecode hash.
     #[serde(rename = "none")]
     None,
+    /// Append the `keccak256` hash of the metadata to the bytecode.
+    #[serde(rename = "keccak256")]
+    Keccak256,
 }
 
 #[derive(Debug, Default, Serialize, Deserialize)]
@@ -52,6 +55,9 @@ pub struct Metadata {
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Settings {
+    /// The import path remappings.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remappings: Option<Vec<String>>,
     /// The linker library addresses.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub libraries: Option<HashMap<String, HashMap<String, String>>>,
@@ -0,0 +1,206 @@
+//! Exporting Postgres data to the object store.
+
+use std::{sync::Arc, time::Instant};
+
+use anyhow::Context as _;
+use zksync_dal::{
+    db_export_dal::{ExportChunkRange, ExportedTable},
+    ConnectionPool, StorageProcessor,
+};
+use zksync_object_store::ObjectStore;
+use zksync_types::{L1BatchNumber, MiniblockNumber};
+
+use crate::objects::{
+    DbExportChunk, DbExportChunkKey, DbExportManifest, ExportedChunkInfo, ExportedTableInfo,
+};
+
+/// Exports a consistent subset of Postgres data up to a certain L1 batch to the object store.
+#[derive(Debug)]
+pub(crate) struct DbExporter {
+    pool: ConnectionPool,
+    blob_store: Arc<dyn ObjectStore>,
+    l1_batches_per_chunk: u32,
+}
+
+impl DbExporter {
+    pub fn new(
+        pool: ConnectionPool,
+        blob_store: Arc<dyn ObjectStore>,
+        l1_batches_per_chunk: u32,
+    ) -> Self {
+        assert!(l1_batches_per_chunk > 0, "chunk size must be positive");
+        Self {
+            pool,
+            blob_store,
+            l1_batches_per_chunk,
+        }
+    }
+
+    /// Exports data up to and including the specified L1 batch, or the last sealed L1 batch if it's not specified.
+    /// Returns the number of the last exported L1 batch, which is used as the export ID.
+    pub async fn export(
+        &self,
+        l1_batch_number: Option<L1BatchNumber>,
+    ) -> anyhow::Result<L1BatchNumber> {
+        let started_at = Instant::now();
+        let mut storage = self.pool.access_storage_tagged("db_export").await?;
+        // All data is read in a single repeatable-read transaction, so the export is consistent
+        // even if the node writes to the DB concurrently.
+        let mut transaction = storage.start_transaction().await?;
+        transaction
+            .db_export_dal()
+            .start_consistent_export()
+            .await
+            .context("start_consistent_export()")?;
+
+        let sealed_l1_batch = transaction
+            .blocks_dal()
+            .get_sealed_l1_batch_number()
+            .await
+            .context("get_sealed_l1_batch_number()")?
+            .context("Postgres contains no L1 batches")?;
+        let l1_batch_number = l1_batch_number.unwrap_or(sealed_l1_batch);
+        anyhow::ensure!(
+            l1_batch_number <= sealed_l1_batch,
+            "Cannot export data up to L1 batch #{l1_batch_number}: last sealed L1 batch is #{sealed_l1_batch}"
+        );
+        let earliest_l1_batch = transaction
+            .blocks_dal()
+            .get_earliest_l1_batch_number()
+            .await
+            .context("get_earliest_l1_batch_number()")?
+            .context("Postgres contains no L1 batches")?;
+        anyhow::ensure!(
+            earliest_l1_batch == L1BatchNumber(0),
+            "Postgres doesn't contain the genesis L1 batch (it was probably recovered from a snapshot); \
+             exporting such a DB is not supported"
+        );
+
+        let migration_version = transaction
+            .db_export_dal()
+            .get_latest_migration_version()
+            .await
+            .context("get_latest_migration_version()")?;
+        let mut tables = Vec::with_capacity(ExportedTable::ALL.len());
+        for table in ExportedTable::ALL {
+            let columns = transaction
+                .db_export_dal()
+                .get_columns(table)
+                .await
+                .with_context(|| format!("get_columns({table:?})"))?;
+            tables.push((table, columns));
+        }
+
+        tracing::info!(
+            "Exporting Postgres data up to L1 batch #{l1_batch_number} (migration version: {migration_version:?}) \
+             in chunks of {} L1 batches",
+            self.l1_batches_per_chunk
+        );
+        let (_, miniblock_number) = transaction
+            .blocks_dal()
+            .get_miniblock_range_of_l1_batch(l1_batch_number)
+            .await
+            .with_context(|| format!("get_miniblock_range_of_l1_batch({l1_batch_number})"))?
+            .with_context(|| format!("L1 batch #{l1_batch_number} doesn't have miniblocks"))?;
+        let mut chunks = vec![];
+        let mut chunk_start = L1BatchNumber(0);
+        while chunk_start <= l1_batch_number {
+            let chunk_end = l1_batch_number.min(chunk_start + (self.l1_batches_per_chunk - 1));
+            let chunk =
+                Self::chunk_range(&mut transaction, chunk_start, chunk_end, miniblock_number)
+                    .await?;
+            let chunk_id = chunks.len();
+            self.export_chunk(&mut transaction, &tables, l1_batch_number, chunk_id, &chunk)
+                .await?;
+            chunks.push(ExportedChunkInfo::from(&chunk));
+            chunk_start = chunk_end + 1;
+        }
+
+        let manifest = DbExportManifest {
+            l1_batch_number,
+            miniblock_number,
+            migration_version,
+            tables: tables
+                .into_iter()
+                .map(|(table, columns)| ExportedTableInfo {
+                    name: table.name().to_owned(),
+                    columns,
+                })
+                .collect(),
+            chunks,
+        };
+        let manifest_key = self
+            .blob_store
+            .put(l1_batch_number, &manifest)
+            .await
+            .context("failed persisting export manifest")?;
+        tracing::info!(
+            "Finished exporting Postgres data up to L1 batch #{l1_batch_number} / miniblock #{miniblock_number} \
+             in {:?}; manifest is saved to `{manifest_key}`",
+            started_at.elapsed()
+        );
+        Ok(l1_batch_number)
+    }
+
+    async fn chunk_range(
+        storage: &mut StorageProcessor<'_>,
+        start: L1BatchNumber,
+        end: L1BatchNumber,
+        last_exported_miniblock: MiniblockNumber,
+    ) -> anyhow::Result<ExportChunkRange> {
+        let (first_miniblock, _) = storage
+            .blocks_dal()
+            .get_miniblock_range_of_l1_batch(start)
+            .await
+            .with_context(|| format!("get_miniblock_range_of_l1_batch({start})"))?
+            .with_context(|| format!("L1 batch #{start} doesn't have miniblocks"))?;
+        let (_, last_miniblock) = storage
+            .blocks_dal()
+            .get_miniblock_range_of_l1_batch(end)
+            .await
+            .with_context(|| format!("get_miniblock_range_of_l1_batch({end})"))?
+            .with_context(|| format!("L1 batch #{end} doesn't have miniblocks"))?;
+        Ok(ExportChunkRange {
+            l1_batches: start..=end,
+            miniblocks: first_miniblock..=last_miniblock,
+            last_exported_miniblock,
+        })
+    }
+
+    async fn export_chunk(
+        &self,
+        storage: &mut StorageProcessor<'_>,
+        tables: &[(ExportedTable, Vec<String>)],
+        l1_batch_number: L1BatchNumber,
+        chunk_id: usize,
+        chunk: &ExportChunkRange,
+    ) -> anyhow::Result<()> {
+        let started_at = Instant::now();
+        let mut output = DbExportChunk::default();
+        for (table, columns) in tables {
+            let rows = storage
+                .db_export_dal()
+                .export_chunk(*table, columns, chunk)
+                .await
+                .with_context(|| format!("failed exporting {table:?} for {chunk:?}"))?;
+            output
+                .push_table(table.name(), &rows)
+                .context("failed compressing exported rows")?;
+        }
+
+        let key = DbExportChunkKey {
+            l1_batch_number,
+            chunk_id,
+        };
+        let object_key = self
+            .blob_store
+            .put(key, &output)
+            .await
+            .with_context(|| format!("failed persisting chunk {chunk_id}"))?;
+        tracing::info!(
+            "Exported chunk {chunk_id} ({chunk:?}) to `{object_key}` in {:?}",
+            started_at.elapsed()
+        );
+        Ok(())
+    }
+}
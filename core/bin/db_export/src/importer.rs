@@ -0,0 +1,141 @@
+//! Importing Postgres data exported by [`DbExporter`](crate::exporter::DbExporter).
+
+use std::{collections::HashMap, sync::Arc, time::Instant};
+
+use anyhow::Context as _;
+use zksync_dal::{db_export_dal::ExportedTable, ConnectionPool};
+use zksync_object_store::ObjectStore;
+use zksync_types::L1BatchNumber;
+
+use crate::objects::{DbExportChunk, DbExportChunkKey, DbExportManifest};
+
+/// Imports a previously created DB export into an empty Postgres instance.
+#[derive(Debug)]
+pub(crate) struct DbImporter {
+    pool: ConnectionPool,
+    blob_store: Arc<dyn ObjectStore>,
+}
+
+impl DbImporter {
+    pub fn new(pool: ConnectionPool, blob_store: Arc<dyn ObjectStore>) -> Self {
+        Self { pool, blob_store }
+    }
+
+    /// Imports the export with the specified ID (i.e., the number of the last exported L1 batch).
+    /// Each chunk is imported in a separate DB transaction, so that the import doesn't hold a transaction
+    /// for the entire DB. If the import is interrupted, it can be resumed by calling this method again;
+    /// chunks that are already imported are skipped.
+    pub async fn import(&self, l1_batch_number: L1BatchNumber) -> anyhow::Result<()> {
+        self.import_chunks(l1_batch_number, usize::MAX).await
+    }
+
+    /// Returns the ID of the first chunk that should be imported based on the last sealed L1 batch in Postgres.
+    fn first_chunk_to_import(
+        manifest: &DbExportManifest,
+        sealed_l1_batch: Option<L1BatchNumber>,
+    ) -> anyhow::Result<usize> {
+        let Some(sealed_l1_batch) = sealed_l1_batch else {
+            return Ok(0);
+        };
+        anyhow::ensure!(
+            sealed_l1_batch != manifest.l1_batch_number,
+            "Export up to L1 batch #{sealed_l1_batch} is already imported"
+        );
+        let imported_chunk_count = manifest
+            .chunks
+            .iter()
+            .position(|chunk| *chunk.l1_batches.end() == sealed_l1_batch)
+            .with_context(|| {
+                format!(
+                    "Cannot import data into non-empty Postgres (last sealed L1 batch: #{sealed_l1_batch}); \
+                     it doesn't correspond to a partially imported export"
+                )
+            })?
+            + 1;
+        Ok(imported_chunk_count)
+    }
+
+    /// Imports at most `max_chunk_count` chunks of the export. Used in tests to emulate interrupted imports.
+    pub(crate) async fn import_chunks(
+        &self,
+        l1_batch_number: L1BatchNumber,
+        max_chunk_count: usize,
+    ) -> anyhow::Result<()> {
+        let started_at = Instant::now();
+        let manifest: DbExportManifest = self
+            .blob_store
+            .get(l1_batch_number)
+            .await
+            .with_context(|| format!("failed fetching manifest for export #{l1_batch_number}"))?;
+
+        let mut storage = self.pool.access_storage_tagged("db_export").await?;
+        let migration_version = storage
+            .db_export_dal()
+            .get_latest_migration_version()
+            .await
+            .context("get_latest_migration_version()")?;
+        anyhow::ensure!(
+            migration_version == manifest.migration_version,
+            "Migration version of Postgres ({migration_version:?}) differs from the exported one ({:?})",
+            manifest.migration_version
+        );
+        let sealed_l1_batch = storage
+            .blocks_dal()
+            .get_sealed_l1_batch_number()
+            .await
+            .context("get_sealed_l1_batch_number()")?;
+        let first_chunk_id = Self::first_chunk_to_import(&manifest, sealed_l1_batch)?;
+
+        let mut tables = HashMap::with_capacity(manifest.tables.len());
+        for table_info in &manifest.tables {
+            let table = ExportedTable::from_name(&table_info.name)
+                .with_context(|| format!("unknown exported table `{}`", table_info.name))?;
+            tables.insert(table_info.name.as_str(), (table, &table_info.columns));
+        }
+
+        tracing::info!(
+            "Importing Postgres data up to L1 batch #{l1_batch_number} / miniblock #{} in {} chunks, \
+             starting from chunk {first_chunk_id}",
+            manifest.miniblock_number,
+            manifest.chunks.len()
+        );
+        let chunks = manifest.chunks.iter().enumerate().skip(first_chunk_id);
+        for (chunk_id, chunk_info) in chunks.take(max_chunk_count) {
+            let chunk_started_at = Instant::now();
+            let key = DbExportChunkKey {
+                l1_batch_number,
+                chunk_id,
+            };
+            let chunk: DbExportChunk = self
+                .blob_store
+                .get(key)
+                .await
+                .with_context(|| format!("failed fetching chunk {chunk_id}"))?;
+
+            let mut transaction = storage.start_transaction().await?;
+            for (table_name, compressed_rows) in &chunk.tables {
+                let (table, columns) = tables
+                    .get(table_name.as_str())
+                    .with_context(|| format!("table `{table_name}` is not in the manifest"))?;
+                let rows = DbExportChunk::decompress_rows(compressed_rows)
+                    .context("failed decompressing exported rows")?;
+                transaction
+                    .db_export_dal()
+                    .import_chunk(*table, columns, &rows)
+                    .await
+                    .with_context(|| format!("failed importing {table:?} for chunk {chunk_id}"))?;
+            }
+            transaction.commit().await?;
+            tracing::info!(
+                "Imported chunk {chunk_id} ({chunk_info:?}) in {:?}",
+                chunk_started_at.elapsed()
+            );
+        }
+
+        tracing::info!(
+            "Finished importing Postgres data up to L1 batch #{l1_batch_number} in {:?}",
+            started_at.elapsed()
+        );
+        Ok(())
+    }
+}
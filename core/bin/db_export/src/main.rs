@@ -0,0 +1,94 @@
+//! Utility exporting a logically consistent subset of Postgres data (protocol versions, L1 batches, miniblocks
+//! including consensus certificates, transactions, events, L2-to-L1 logs, storage logs, the latest storage state,
+//! initial writes and factory deps) to the object store, and importing it back. Unlike `pg_dump`, the export
+//! can be created from a running node and restricted to a certain L1 batch, which allows to quickly bootstrap
+//! external nodes or restore a node after a disaster.
+//!
+//! # Assumptions
+//!
+//! The import target must be an empty Postgres instance with all migrations applied; the set of applied migrations
+//! must match the one of the exported DB. Data not included into the export (e.g., L1 transactions sent
+//! by the operator or mempool transactions) is not restored. Chunks are imported in separate DB transactions;
+//! an interrupted import can be resumed by running it again.
+
+use anyhow::Context as _;
+use clap::{Parser, Subcommand};
+use zksync_config::PostgresConfig;
+use zksync_dal::ConnectionPool;
+use zksync_env_config::{object_store::SnapshotsObjectStoreConfig, FromEnv};
+use zksync_object_store::ObjectStoreFactory;
+use zksync_types::L1BatchNumber;
+
+use crate::{exporter::DbExporter, importer::DbImporter};
+
+mod exporter;
+mod importer;
+mod objects;
+#[cfg(test)]
+mod tests;
+
+#[derive(Debug, Parser)]
+#[command(author = "Matter Labs", version, about = "Postgres export / import utility", long_about = None)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Exports Postgres data to the object store.
+    #[command(name = "export")]
+    Export {
+        /// Last L1 batch to export. If not specified, data up to the last sealed L1 batch is exported.
+        #[arg(long)]
+        l1_batch_number: Option<u32>,
+        /// Number of L1 batches exported in a single chunk.
+        #[arg(long, default_value_t = 100)]
+        l1_batches_per_chunk: u32,
+    },
+    /// Imports previously exported data into an empty Postgres instance.
+    #[command(name = "import")]
+    Import {
+        /// Last L1 batch in the imported export.
+        #[arg(long)]
+        l1_batch_number: u32,
+    },
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    #[allow(deprecated)] // TODO (QIT-21): Use centralized configuration approach.
+    let log_format = vlog::log_format_from_env();
+    let _guard = vlog::ObservabilityBuilder::new()
+        .with_log_format(log_format)
+        .build();
+
+    let object_store_config =
+        SnapshotsObjectStoreConfig::from_env().context("SnapshotsObjectStoreConfig::from_env()")?;
+    let blob_store = ObjectStoreFactory::new(object_store_config.0)
+        .create_store()
+        .await;
+    let postgres_config = PostgresConfig::from_env().context("PostgresConfig::from_env()")?;
+    let pool = ConnectionPool::singleton(postgres_config.master_url()?)
+        .build()
+        .await
+        .context("failed to build a connection pool")?;
+
+    match Cli::parse().command {
+        Command::Export {
+            l1_batch_number,
+            l1_batches_per_chunk,
+        } => {
+            let exporter = DbExporter::new(pool, blob_store, l1_batches_per_chunk);
+            let l1_batch_number = exporter.export(l1_batch_number.map(L1BatchNumber)).await?;
+            println!("Exported Postgres data up to L1 batch #{l1_batch_number}");
+        }
+        Command::Import { l1_batch_number } => {
+            DbImporter::new(pool, blob_store)
+                .import(L1BatchNumber(l1_batch_number))
+                .await?;
+            println!("Imported Postgres data up to L1 batch #{l1_batch_number}");
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,104 @@
+//! Objects persisted in the object store by the export utility.
+
+use std::{
+    io::{self, Read, Write},
+    ops,
+};
+
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use serde::{Deserialize, Serialize};
+use zksync_dal::db_export_dal::ExportChunkRange;
+use zksync_object_store::{serialize_using_bincode, Bucket, StoredObject};
+use zksync_types::{L1BatchNumber, MiniblockNumber};
+
+/// Manifest of a DB export. The manifest is uploaded after all chunks, so its presence signals
+/// that the export is complete.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct DbExportManifest {
+    /// Last L1 batch included into the export.
+    pub l1_batch_number: L1BatchNumber,
+    /// Last miniblock included into the export.
+    pub miniblock_number: MiniblockNumber,
+    /// Version of the latest DB migration applied to the exported DB.
+    pub migration_version: Option<i64>,
+    /// Exported tables together with the exported columns.
+    pub tables: Vec<ExportedTableInfo>,
+    pub chunks: Vec<ExportedChunkInfo>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct ExportedTableInfo {
+    pub name: String,
+    pub columns: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct ExportedChunkInfo {
+    pub l1_batches: ops::RangeInclusive<L1BatchNumber>,
+    pub miniblocks: ops::RangeInclusive<MiniblockNumber>,
+}
+
+impl From<&ExportChunkRange> for ExportedChunkInfo {
+    fn from(range: &ExportChunkRange) -> Self {
+        Self {
+            l1_batches: range.l1_batches.clone(),
+            miniblocks: range.miniblocks.clone(),
+        }
+    }
+}
+
+impl StoredObject for DbExportManifest {
+    const BUCKET: Bucket = Bucket::DbExports;
+    type Key<'a> = L1BatchNumber;
+
+    fn encode_key(key: Self::Key<'_>) -> String {
+        format!("db_export_l1_batch_{key}_manifest.bin")
+    }
+
+    serialize_using_bincode!();
+}
+
+/// Key of a [`DbExportChunk`].
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct DbExportChunkKey {
+    pub l1_batch_number: L1BatchNumber,
+    pub chunk_id: usize,
+}
+
+/// Rows of all exported tables for a range of L1 batches.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub(crate) struct DbExportChunk {
+    /// Table name and the corresponding rows in the import order. Rows are in the text `COPY` format
+    /// compressed with gzip.
+    pub tables: Vec<(String, Vec<u8>)>,
+}
+
+impl DbExportChunk {
+    pub fn push_table(&mut self, name: &str, rows: &[u8]) -> io::Result<()> {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(rows)?;
+        self.tables.push((name.to_owned(), encoder.finish()?));
+        Ok(())
+    }
+
+    pub fn decompress_rows(compressed_rows: &[u8]) -> io::Result<Vec<u8>> {
+        let mut decoder = GzDecoder::new(compressed_rows);
+        let mut rows = Vec::new();
+        decoder.read_to_end(&mut rows)?;
+        Ok(rows)
+    }
+}
+
+impl StoredObject for DbExportChunk {
+    const BUCKET: Bucket = Bucket::DbExports;
+    type Key<'a> = DbExportChunkKey;
+
+    fn encode_key(key: Self::Key<'_>) -> String {
+        format!(
+            "db_export_l1_batch_{}_chunk_{:0>4}.bin",
+            key.l1_batch_number, key.chunk_id
+        )
+    }
+
+    serialize_using_bincode!();
+}
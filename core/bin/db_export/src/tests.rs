@@ -0,0 +1,176 @@
+//! Tests for the DB export utility.
+
+use zksync_dal::StorageProcessor;
+use zksync_object_store::ObjectStoreFactory;
+use zksync_types::{
+    block::{BlockGasCount, L1BatchHeader, MiniblockHeader},
+    AccountTreeId, Address, MiniblockNumber, ProtocolVersion, StorageKey, StorageLog, H256,
+};
+
+use super::*;
+use crate::objects::DbExportManifest;
+
+fn storage_key(index: u64) -> StorageKey {
+    StorageKey::new(
+        AccountTreeId::new(Address::repeat_byte(1)),
+        H256::from_low_u64_be(index),
+    )
+}
+
+async fn create_l1_batch(storage: &mut StorageProcessor<'_>, number: u32) {
+    let miniblock_header = MiniblockHeader {
+        number: MiniblockNumber(number),
+        timestamp: number.into(),
+        hash: H256::from_low_u64_be(number.into()),
+        l1_tx_count: 0,
+        l2_tx_count: 0,
+        base_fee_per_gas: 0,
+        batch_fee_input: Default::default(),
+        base_system_contracts_hashes: Default::default(),
+        protocol_version: Some(Default::default()),
+        virtual_blocks: 0,
+    };
+    storage
+        .blocks_dal()
+        .insert_miniblock(&miniblock_header)
+        .await
+        .unwrap();
+    let key = storage_key(number.into());
+    let logs = vec![StorageLog::new_write_log(
+        key,
+        H256::repeat_byte(number as u8 + 1),
+    )];
+    storage
+        .storage_logs_dal()
+        .insert_storage_logs(MiniblockNumber(number), &[(H256::zero(), logs)])
+        .await;
+
+    let mut header = L1BatchHeader::new(
+        L1BatchNumber(number),
+        number.into(),
+        Address::default(),
+        Default::default(),
+        Default::default(),
+    );
+    header.is_finished = true;
+    storage
+        .blocks_dal()
+        .insert_l1_batch(&header, &[], BlockGasCount::default(), &[], &[], 0)
+        .await
+        .unwrap();
+    storage
+        .blocks_dal()
+        .mark_miniblocks_as_executed_in_l1_batch(L1BatchNumber(number))
+        .await
+        .unwrap();
+    storage
+        .storage_logs_dedup_dal()
+        .insert_initial_writes(L1BatchNumber(number), &[key])
+        .await;
+}
+
+#[tokio::test]
+async fn exporting_and_importing_db() {
+    let source_pool = ConnectionPool::test_pool().await;
+    let mut storage = source_pool.access_storage().await.unwrap();
+    storage
+        .protocol_versions_dal()
+        .save_protocol_version_with_tx(ProtocolVersion::default())
+        .await;
+    for number in 0..5 {
+        create_l1_batch(&mut storage, number).await;
+    }
+    drop(storage);
+
+    let blob_store = ObjectStoreFactory::mock().create_store().await;
+    let exporter = DbExporter::new(source_pool, blob_store.clone(), 2);
+    let exported_l1_batch = exporter.export(Some(L1BatchNumber(3))).await.unwrap();
+    assert_eq!(exported_l1_batch, L1BatchNumber(3));
+
+    let manifest: DbExportManifest = blob_store.get(exported_l1_batch).await.unwrap();
+    assert_eq!(manifest.miniblock_number, MiniblockNumber(3));
+    assert_eq!(manifest.chunks.len(), 2);
+    assert_eq!(
+        manifest.chunks[1].l1_batches,
+        L1BatchNumber(2)..=L1BatchNumber(3)
+    );
+
+    let target_pool = ConnectionPool::test_pool().await;
+    let importer = DbImporter::new(target_pool.clone(), blob_store.clone());
+    importer.import(exported_l1_batch).await.unwrap();
+
+    let mut storage = target_pool.access_storage().await.unwrap();
+    let sealed_l1_batch = storage
+        .blocks_dal()
+        .get_sealed_l1_batch_number()
+        .await
+        .unwrap();
+    assert_eq!(sealed_l1_batch, Some(L1BatchNumber(3)));
+    let sealed_miniblock = storage
+        .blocks_dal()
+        .get_sealed_miniblock_number()
+        .await
+        .unwrap();
+    assert_eq!(sealed_miniblock, MiniblockNumber(3));
+
+    let hashed_keys: Vec<_> = (0..5).map(|i| storage_key(i).hashed_key()).collect();
+    let values = storage
+        .storage_logs_dal()
+        .get_storage_values(&hashed_keys, MiniblockNumber(3))
+        .await;
+    for (i, hashed_key) in hashed_keys.iter().enumerate() {
+        let expected_value = (i < 4).then(|| H256::repeat_byte(i as u8 + 1));
+        assert_eq!(values[hashed_key], expected_value, "{i}");
+        let latest_value = storage
+            .storage_dal()
+            .get_by_key(&storage_key(i as u64))
+            .await;
+        assert_eq!(latest_value, expected_value, "{i}");
+    }
+
+    // Repeated import should fail.
+    let err = importer.import(exported_l1_batch).await.unwrap_err();
+    assert!(format!("{err:#}").contains("already imported"), "{err:#}");
+}
+
+#[tokio::test]
+async fn resuming_interrupted_import() {
+    let source_pool = ConnectionPool::test_pool().await;
+    let mut storage = source_pool.access_storage().await.unwrap();
+    storage
+        .protocol_versions_dal()
+        .save_protocol_version_with_tx(ProtocolVersion::default())
+        .await;
+    for number in 0..5 {
+        create_l1_batch(&mut storage, number).await;
+    }
+    drop(storage);
+
+    let blob_store = ObjectStoreFactory::mock().create_store().await;
+    let exporter = DbExporter::new(source_pool, blob_store.clone(), 2);
+    let exported_l1_batch = exporter.export(None).await.unwrap();
+    assert_eq!(exported_l1_batch, L1BatchNumber(4));
+
+    let target_pool = ConnectionPool::test_pool().await;
+    let importer = DbImporter::new(target_pool.clone(), blob_store.clone());
+    importer.import_chunks(exported_l1_batch, 1).await.unwrap();
+    let mut storage = target_pool.access_storage().await.unwrap();
+    let sealed_l1_batch = storage
+        .blocks_dal()
+        .get_sealed_l1_batch_number()
+        .await
+        .unwrap();
+    assert_eq!(sealed_l1_batch, Some(L1BatchNumber(1)));
+
+    importer.import(exported_l1_batch).await.unwrap();
+    let sealed_l1_batch = storage
+        .blocks_dal()
+        .get_sealed_l1_batch_number()
+        .await
+        .unwrap();
+    assert_eq!(sealed_l1_batch, Some(exported_l1_batch));
+    for i in 0..5 {
+        let value = storage.storage_dal().get_by_key(&storage_key(i)).await;
+        assert_eq!(value, Some(H256::repeat_byte(i as u8 + 1)), "{i}");
+    }
+}
@@ -3,7 +3,9 @@ use std::{env, time::Duration};
 use anyhow::Context;
 use serde::Deserialize;
 use url::Url;
-use zksync_basic_types::{Address, L1ChainId, L2ChainId, MiniblockNumber};
+use zksync_basic_types::{
+    commitment::L1BatchCommitmentMode, Address, L1ChainId, L2ChainId, MiniblockNumber,
+};
 use zksync_core::api_server::{
     tx_sender::TxSenderConfig,
     web3::{state::InternalApiConfig, Namespace},
@@ -99,6 +101,15 @@ pub struct OptionalENConfig {
     /// Max possible limit of subscriptions to be in the API state at once.
     #[serde(default = "OptionalENConfig::default_subscriptions_limit")]
     pub subscriptions_limit: usize,
+    /// Maximum number of active subscriptions per WebSocket connection. Default is 1024.
+    #[serde(default = "OptionalENConfig::default_websocket_subscriptions_per_connection_limit")]
+    pub websocket_subscriptions_per_connection_limit: u32,
+    /// Timeout (in seconds) after which unresponsive WebSocket connections are closed. If not set, connections
+    /// are never closed because of inactivity.
+    websocket_idle_timeout: Option<u64>,
+    /// Timeout (in ms) for sending a notification to a subscriber. Slow subscribers are dropped after this timeout.
+    #[serde(default = "OptionalENConfig::default_pubsub_send_timeout_ms")]
+    pubsub_send_timeout_ms: u64,
     /// Max possible limit of entities to be requested via API at once.
     #[serde(default = "OptionalENConfig::default_req_entities_limit")]
     pub req_entities_limit: usize,
@@ -113,9 +124,21 @@ pub struct OptionalENConfig {
     /// Limit for fee history block range.
     #[serde(default = "OptionalENConfig::default_fee_history_limit")]
     pub fee_history_limit: u64,
+    /// Maximum block range for `eth_getLogs` and event filters. If not set, the block range is not limited.
+    pub get_logs_max_block_range: Option<u32>,
+    /// Maximum number of logs returned by a single `eth_getLogs` call. If not set, `req_entities_limit` is used.
+    pub get_logs_max_results: Option<usize>,
+    /// Number of latest miniblocks for which historical state queries (`eth_call`, `eth_getBalance`
+    /// and `eth_getStorageAt`) are served. If not set, the node runs in archival mode, i.e., serves state
+    /// for all miniblocks it stores.
+    pub historical_state_depth: Option<u32>,
     /// Maximum number of requests in a single batch JSON RPC request. Default is 500.
     #[serde(default = "OptionalENConfig::default_max_batch_request_size")]
     pub max_batch_request_size: usize,
+    /// Maximum number of calls from a single batch request executed concurrently by the HTTP server.
+    /// Default is 1, i.e., calls in a batch are executed sequentially.
+    #[serde(default = "OptionalENConfig::default_batch_request_concurrency")]
+    pub batch_request_concurrency: usize,
     /// Maximum response body size in MiBs. Default is 10 MiB.
     #[serde(default = "OptionalENConfig::default_max_response_body_size_mb")]
     pub max_response_body_size_mb: usize,
@@ -144,6 +167,17 @@ pub struct OptionalENConfig {
     /// values cache will be disabled.
     #[serde(default = "OptionalENConfig::default_latest_values_cache_size_mb")]
     latest_values_cache_size_mb: usize,
+    /// Size of the in-memory cache of storage values kept warm across L1 batches executed by the state keeper,
+    /// in MiBs. The default value is 32 MiB. If set to 0, the cache will be disabled.
+    #[serde(default = "OptionalENConfig::default_state_keeper_overlay_cache_size_mb")]
+    state_keeper_overlay_cache_size_mb: usize,
+    /// Size of the cache for immutable JSON-RPC responses shared among HTTP and WS servers, in MiBs.
+    /// The cache is disabled by default (or if set to 0).
+    #[serde(default)]
+    response_cache_size_mb: usize,
+    /// Time-to-live for entries in the response cache, in seconds.
+    #[serde(default = "OptionalENConfig::default_response_cache_ttl_sec")]
+    response_cache_ttl_sec: u64,
     /// Enabled JSON RPC API namespaces.
     api_namespaces: Option<Vec<Namespace>>,
 
@@ -183,10 +217,40 @@ pub struct OptionalENConfig {
     /// Timeout to wait for the Merkle tree database to run compaction on stalled writes.
     #[serde(default = "OptionalENConfig::default_merkle_tree_stalled_writes_timeout_sec")]
     merkle_tree_stalled_writes_timeout_sec: u64,
+    /// Number of threads in a dedicated thread pool used to compute Merkle tree hashes. Subtrees are hashed
+    /// in parallel, so a larger pool can reduce tree update latency for large L1 batches. If not specified,
+    /// the global `rayon` thread pool will be used.
+    pub merkle_tree_hashing_thread_count: Option<usize>,
+    /// Number of past Merkle tree versions (i.e., L1 batches) to keep when pruning the tree. If not specified,
+    /// the tree is not pruned. Versions for L1 batches not executed on L1 are never pruned.
+    pub merkle_tree_pruning_past_versions_to_keep: Option<u64>,
+    /// If set, an empty Merkle tree will be recovered from the storage logs in Postgres rather than built
+    /// by processing all L1 batches starting from genesis.
+    #[serde(default)]
+    pub merkle_tree_recover_from_storage_logs: bool,
+    /// L1 batch to recover the Merkle tree to if `merkle_tree_recover_from_storage_logs` is set. If not specified,
+    /// the latest L1 batch with metadata will be used.
+    pub merkle_tree_storage_logs_recovery_l1_batch: Option<u32>,
+    /// If set, Merkle proofs (e.g., for `zks_getProof`) are served by a read-only follower tree lagging behind
+    /// the main tree by the specified number of L1 batches, so that proof queries don't slow down tree updates.
+    pub merkle_tree_follower_version_lag: Option<u64>,
+    /// If set, consistency of the latest Merkle tree version is periodically verified in RocksDB, and its root hash
+    /// is compared to the stored L1 batch metadata. The check traverses the entire tree, so it is I/O-intensive.
+    pub merkle_tree_consistency_check_interval_sec: Option<u64>,
+    /// Interval between backups of the Merkle tree and the state keeper cache to the object store
+    /// (configured via `OBJECT_STORE_*` env vars). If set, the instances are also restored from the latest backup
+    /// on startup if their local data is missing or corrupted. If not set, backups are disabled.
+    pub rocksdb_backup_interval_sec: Option<u64>,
 
     // Other config settings
     /// Port on which the Prometheus exporter server is listening.
     pub prometheus_port: Option<u16>,
+    /// Port of the admin server allowing to change log directives at runtime. The server only listens
+    /// on the loopback interface. If not set, the admin server is not started.
+    pub admin_port: Option<u16>,
+    /// Threshold in milliseconds for slow DB queries and connection acquisitions, which are logged and counted
+    /// in metrics. If not set, the default threshold (100ms) is used.
+    slow_query_threshold_ms: Option<u64>,
     /// Number of keys that is processed by enum_index migration in State Keeper each L1 batch.
     #[serde(default = "OptionalENConfig::default_enum_index_migration_chunk_size")]
     pub enum_index_migration_chunk_size: usize,
@@ -195,6 +259,50 @@ pub struct OptionalENConfig {
     /// 0 means that sealing is synchronous; this is mostly useful for performance comparison, testing etc.
     #[serde(default = "OptionalENConfig::default_miniblock_seal_queue_capacity")]
     pub miniblock_seal_queue_capacity: usize,
+    /// Path to the directory used to persist miniblocks fetched from the main node, but not yet applied
+    /// by the state keeper. If set, such miniblocks are replayed on node restart instead of being fetched again.
+    /// If not set, fetched miniblocks are only kept in memory.
+    pub fetched_blocks_journal_path: Option<String>,
+    /// Additional JSON-RPC endpoints serving the same data as the main node (e.g., main node replicas).
+    /// If set, the miniblock fetcher fails over to these endpoints if the main node is unavailable.
+    /// Intentionally private: use getter method as it manages the missing port.
+    fallback_main_node_urls: Option<Vec<String>>,
+    /// Enables recovery of the node state from the newest snapshot on the main node instead of syncing
+    /// from genesis. Only has effect for a node with empty Postgres, or if snapshot recovery was interrupted.
+    /// Requires the snapshots object store to be configured via `SNAPSHOTS_OBJECT_STORE_*` env variables.
+    #[serde(default)]
+    pub snapshots_recovery_enabled: bool,
+    /// If set, snapshot manifests must be signed by this address for snapshot recovery to proceed.
+    /// If not set, manifests are still used to verify storage log chunks, but their signatures are not checked.
+    pub snapshots_recovery_manifest_signer: Option<Address>,
+    /// Maximum number of L1 batches that can be automatically rolled back if a reorg is detected. If a reorg
+    /// requires a deeper rollback, the node stops and requires manual intervention. If not set, rollback depth
+    /// is not limited.
+    pub reorg_max_rollback_depth: Option<u32>,
+
+    // Pruning config
+    /// Enables pruning of old miniblock-level data (events, call traces and overwritten storage logs).
+    /// Only L1 batches executed on L1 and processed by the Merkle tree are pruned.
+    #[serde(default)]
+    pub pruning_enabled: bool,
+    /// Number of the latest L1 batches for which data is retained if pruning is enabled.
+    #[serde(default = "OptionalENConfig::default_pruning_data_retention_l1_batches")]
+    pub pruning_data_retention_l1_batches: u32,
+    /// Maximum number of L1 batches pruned in a single DB transaction.
+    #[serde(default = "OptionalENConfig::default_pruning_chunk_size")]
+    pub pruning_chunk_size: u32,
+    /// Delay between pruning iterations if there's nothing to prune (in ms).
+    #[serde(default = "OptionalENConfig::default_pruning_delay_ms")]
+    pruning_delay_ms: u64,
+
+    /// Mode in which L1 batches of the chain are committed on L1. Must match the main node configuration;
+    /// used to check consistency of L1 commitments.
+    #[serde(default)]
+    pub l1_batch_commitment_mode: L1BatchCommitmentMode,
+
+    /// URL of a remote chain registry extending the registry embedded into the node. Used to validate
+    /// chain-related configuration on startup.
+    pub chain_registry_url: Option<String>,
 }
 
 impl OptionalENConfig {
@@ -206,6 +314,14 @@ impl OptionalENConfig {
         10_000
     }
 
+    const fn default_websocket_subscriptions_per_connection_limit() -> u32 {
+        1_024
+    }
+
+    const fn default_pubsub_send_timeout_ms() -> u64 {
+        1_000
+    }
+
     const fn default_req_entities_limit() -> usize {
         1_024
     }
@@ -261,6 +377,14 @@ impl OptionalENConfig {
         128
     }
 
+    const fn default_state_keeper_overlay_cache_size_mb() -> usize {
+        32
+    }
+
+    const fn default_response_cache_ttl_sec() -> u64 {
+        300
+    }
+
     const fn default_merkle_tree_multi_get_chunk_size() -> usize {
         500
     }
@@ -285,6 +409,10 @@ impl OptionalENConfig {
         500 // The default limit is chosen to be reasonably permissive.
     }
 
+    const fn default_batch_request_concurrency() -> usize {
+        1
+    }
+
     const fn default_max_response_body_size_mb() -> usize {
         10
     }
@@ -297,10 +425,40 @@ impl OptionalENConfig {
         10
     }
 
+    const fn default_pruning_data_retention_l1_batches() -> u32 {
+        10_000
+    }
+
+    const fn default_pruning_chunk_size() -> u32 {
+        10
+    }
+
+    const fn default_pruning_delay_ms() -> u64 {
+        60_000
+    }
+
+    pub fn fallback_main_node_urls(&self) -> anyhow::Result<Vec<String>> {
+        let urls = self.fallback_main_node_urls.as_deref().unwrap_or_default();
+        urls.iter()
+            .map(|url| {
+                RequiredENConfig::get_url(url)
+                    .with_context(|| format!("Could not parse fallback main node URL `{url}`"))
+            })
+            .collect()
+    }
+
     pub fn polling_interval(&self) -> Duration {
         Duration::from_millis(self.polling_interval)
     }
 
+    pub fn websocket_idle_timeout(&self) -> Option<Duration> {
+        self.websocket_idle_timeout.map(Duration::from_secs)
+    }
+
+    pub fn pubsub_send_timeout(&self) -> Duration {
+        Duration::from_millis(self.pubsub_send_timeout_ms)
+    }
+
     pub fn metadata_calculator_delay(&self) -> Duration {
         Duration::from_millis(self.metadata_calculator_delay)
     }
@@ -320,6 +478,20 @@ impl OptionalENConfig {
         self.latest_values_cache_size_mb * BYTES_IN_MEGABYTE
     }
 
+    /// Returns the size of the in-memory state keeper cache of storage values in bytes.
+    pub fn state_keeper_overlay_cache_size(&self) -> usize {
+        self.state_keeper_overlay_cache_size_mb * BYTES_IN_MEGABYTE
+    }
+
+    /// Returns the size of the response cache in bytes.
+    pub fn response_cache_size(&self) -> usize {
+        self.response_cache_size_mb * BYTES_IN_MEGABYTE
+    }
+
+    pub fn response_cache_ttl(&self) -> Duration {
+        Duration::from_secs(self.response_cache_ttl_sec)
+    }
+
     /// Returns the size of block cache for Merkle tree in bytes.
     pub fn merkle_tree_block_cache_size(&self) -> usize {
         self.merkle_tree_block_cache_size_mb * BYTES_IN_MEGABYTE
@@ -335,6 +507,15 @@ impl OptionalENConfig {
         Duration::from_secs(self.merkle_tree_stalled_writes_timeout_sec)
     }
 
+    pub fn merkle_tree_consistency_check_interval(&self) -> Option<Duration> {
+        self.merkle_tree_consistency_check_interval_sec
+            .map(Duration::from_secs)
+    }
+
+    pub fn rocksdb_backup_interval(&self) -> Option<Duration> {
+        self.rocksdb_backup_interval_sec.map(Duration::from_secs)
+    }
+
     pub fn api_namespaces(&self) -> Vec<Namespace> {
         self.api_namespaces
             .clone()
@@ -344,6 +525,14 @@ impl OptionalENConfig {
     pub fn max_response_body_size(&self) -> usize {
         self.max_response_body_size_mb * BYTES_IN_MEGABYTE
     }
+
+    pub fn slow_query_threshold(&self) -> Option<Duration> {
+        self.slow_query_threshold_ms.map(Duration::from_millis)
+    }
+
+    pub fn pruning_delay(&self) -> Duration {
+        Duration::from_millis(self.pruning_delay_ms)
+    }
 }
 
 /// This part of the external node config is required for its operation.
@@ -513,6 +702,12 @@ impl From<ExternalNodeConfig> for InternalApiConfig {
             l2_testnet_paymaster_addr: config.remote.l2_testnet_paymaster_addr,
             req_entities_limit: config.optional.req_entities_limit,
             fee_history_limit: config.optional.fee_history_limit,
+            get_logs_max_block_range: config.optional.get_logs_max_block_range,
+            get_logs_max_results: config
+                .optional
+                .get_logs_max_results
+                .unwrap_or(config.optional.req_entities_limit),
+            historical_state_depth: config.optional.historical_state_depth,
         }
     }
 }
@@ -527,6 +722,8 @@ impl From<ExternalNodeConfig> for TxSenderConfig {
                 .unwrap(),
             gas_price_scale_factor: config.optional.gas_price_scale_factor,
             max_nonce_ahead: config.optional.max_nonce_ahead,
+            // Transactions are proxied to the main node, which enforces the replacement rules.
+            tx_replacement_price_bump_percent: None,
             fair_l2_gas_price: config.remote.fair_l2_gas_price,
             vm_execution_cache_misses_limit: config.optional.vm_execution_cache_misses_limit,
             // We set these values to the maximum since we don't know the actual values
@@ -25,6 +25,10 @@ fn parsing_optional_config_from_empty_env() {
         128 * BYTES_IN_MEGABYTE
     );
     assert_eq!(config.max_response_body_size(), 10 * BYTES_IN_MEGABYTE);
+    assert!(!config.pruning_enabled);
+    assert_eq!(config.pruning_data_retention_l1_batches, 10_000);
+    assert_eq!(config.pruning_chunk_size, 10);
+    assert_eq!(config.pruning_delay(), Duration::from_secs(60));
 }
 
 #[test]
@@ -44,6 +48,10 @@ fn parsing_optional_config_from_env() {
         ("EN_MERKLE_TREE_MULTI_GET_CHUNK_SIZE", "1000"),
         ("EN_MERKLE_TREE_BLOCK_CACHE_SIZE_MB", "32"),
         ("EN_MAX_RESPONSE_BODY_SIZE_MB", "1"),
+        ("EN_PRUNING_ENABLED", "true"),
+        ("EN_PRUNING_DATA_RETENTION_L1_BATCHES", "100"),
+        ("EN_PRUNING_CHUNK_SIZE", "5"),
+        ("EN_PRUNING_DELAY_MS", "1000"),
     ];
     let env_vars = env_vars
         .into_iter()
@@ -70,4 +78,8 @@ fn parsing_optional_config_from_env() {
         32 * BYTES_IN_MEGABYTE
     );
     assert_eq!(config.max_response_body_size(), BYTES_IN_MEGABYTE);
+    assert!(config.pruning_enabled);
+    assert_eq!(config.pruning_data_retention_l1_batches, 100);
+    assert_eq!(config.pruning_chunk_size, 5);
+    assert_eq!(config.pruning_delay(), Duration::from_secs(1));
 }
@@ -6,32 +6,45 @@ use futures::{future::FusedFuture, FutureExt as _};
 use metrics::EN_METRICS;
 use prometheus_exporter::PrometheusExporterConfig;
 use tokio::{sync::watch, task, time::sleep};
-use zksync_basic_types::{Address, L2ChainId};
-use zksync_config::configs::database::MerkleTreeMode;
+use zksync_basic_types::{Address, L1BatchNumber, L2ChainId};
+use zksync_config::{configs::database::MerkleTreeMode, ObjectStoreConfig};
 use zksync_core::{
     api_server::{
         execution_sandbox::VmConcurrencyLimiter,
         healthcheck::HealthCheckHandle,
         tx_sender::{ApiContracts, TxSenderBuilder},
-        web3::{ApiBuilder, Namespace},
+        web3::{
+            backend_jsonrpsee::response_cache_middleware::ResponseCache, ApiBuilder, Namespace,
+        },
     },
     block_reverter::{BlockReverter, BlockReverterFlags, L1ExecutedBatchesRevert},
     consistency_checker::ConsistencyChecker,
+    db_pruner::{DbPruner, DbPrunerConfig},
+    factory_deps_compression::FactoryDepsCompression,
     l1_gas_price::MainNodeFeeParamsFetcher,
-    metadata_calculator::{MetadataCalculator, MetadataCalculatorConfig},
+    logs_bloom_backfill::LogsBloomBackfill,
+    metadata_calculator::{
+        MetadataCalculator, MetadataCalculatorConfig, StorageLogsRecoveryTarget,
+    },
     reorg_detector::ReorgDetector,
+    rocksdb_backup::RocksDBBackups,
     setup_sigint_handler,
+    snapshots_applier::{DiamondProxyL1Client, SnapshotsApplier},
     state_keeper::{
         seal_criteria::NoopSealer, L1BatchExecutorBuilder, MainBatchExecutorBuilder,
         MiniblockSealer, MiniblockSealerHandle, ZkSyncStateKeeper,
     },
     sync_layer::{
         batch_status_updater::BatchStatusUpdater, external_io::ExternalIO, fetcher::FetcherCursor,
-        genesis::perform_genesis_if_needed, ActionQueue, MainNodeClient, SyncState,
+        genesis::perform_genesis_if_needed, ActionQueue, FailoverMainNodeClient,
+        FetchedBlocksJournal, MainNodeClient, SyncState,
     },
+    validate_chain_config,
 };
 use zksync_dal::{healthcheck::ConnectionPoolHealthCheck, ConnectionPool};
+use zksync_env_config::{object_store::SnapshotsObjectStoreConfig, FromEnv};
 use zksync_health_check::CheckHealth;
+use zksync_object_store::{ObjectStore, ObjectStoreFactory};
 use zksync_state::PostgresStorageCaches;
 use zksync_storage::RocksDB;
 use zksync_utils::wait_for_tasks::wait_for_tasks;
@@ -56,6 +69,7 @@ async fn build_state_keeper(
     miniblock_sealer_handle: MiniblockSealerHandle,
     stop_receiver: watch::Receiver<bool>,
     chain_id: L2ChainId,
+    rocksdb_backups: Option<(Arc<dyn ObjectStore>, Duration)>,
 ) -> ZkSyncStateKeeper {
     // These config values are used on the main node, and depending on these values certain transactions can
     // be *rejected* (that is, not included into the block). However, external node only mirrors what the main
@@ -66,16 +80,25 @@ async fn build_state_keeper(
     // We only need call traces on the external node if the `debug_` namespace is enabled.
     let save_call_traces = config.optional.api_namespaces().contains(&Namespace::Debug);
 
-    let batch_executor_base: Box<dyn L1BatchExecutorBuilder> =
-        Box::new(MainBatchExecutorBuilder::new(
-            state_keeper_db_path,
-            connection_pool.clone(),
-            max_allowed_l2_tx_gas_limit,
-            save_call_traces,
-            false,
-            config.optional.enum_index_migration_chunk_size,
-            true,
-        ));
+    let mut batch_executor_base = MainBatchExecutorBuilder::new(
+        state_keeper_db_path.clone(),
+        connection_pool.clone(),
+        max_allowed_l2_tx_gas_limit,
+        save_call_traces,
+        false,
+        config.optional.enum_index_migration_chunk_size,
+        true,
+    )
+    .with_storage_overlay_cache(config.optional.state_keeper_overlay_cache_size() as u64);
+    if let Some((object_store, interval)) = rocksdb_backups {
+        let backups = RocksDBBackups::new(object_store, "state_keeper", state_keeper_db_path);
+        // The state keeper cache can always be rebuilt from Postgres, so restoration errors are not fatal.
+        if let Err(err) = backups.restore_if_needed().await {
+            tracing::warn!("Failed restoring state keeper cache from backup: {err:#}");
+        }
+        batch_executor_base = batch_executor_base.with_backups(backups, interval);
+    }
+    let batch_executor_base: Box<dyn L1BatchExecutorBuilder> = Box::new(batch_executor_base);
 
     let main_node_url = config.required.main_node_url().unwrap();
     let main_node_client = <dyn MainNodeClient>::json_rpc(&main_node_url)
@@ -106,7 +129,7 @@ async fn init_tasks(
 ) -> anyhow::Result<(
     Vec<task::JoinHandle<anyhow::Result<()>>>,
     watch::Sender<bool>,
-    HealthCheckHandle,
+    Vec<Box<dyn CheckHealth>>,
     watch::Receiver<bool>,
 )> {
     let release_manifest: serde_json::Value = serde_json::from_str(RELEASE_MANIFEST)
@@ -135,6 +158,17 @@ async fn init_tasks(
         config.optional.miniblock_seal_queue_capacity,
     );
     task_handles.push(tokio::spawn(miniblock_sealer.run()));
+    let rocksdb_backups = match config.optional.rocksdb_backup_interval() {
+        Some(interval) => {
+            let object_store_config = ObjectStoreConfig::from_env()
+                .context("object store config is required for RocksDB backups")?;
+            let object_store = ObjectStoreFactory::new(object_store_config)
+                .create_store()
+                .await;
+            Some((object_store, interval))
+        }
+        None => None,
+    };
     let pool = connection_pool.clone();
     task_handles.push(tokio::spawn(async move {
         loop {
@@ -163,11 +197,25 @@ async fn init_tasks(
         miniblock_sealer_handle,
         stop_receiver.clone(),
         config.remote.l2_chain_id,
+        rocksdb_backups.clone(),
     )
     .await;
 
     let main_node_client = <dyn MainNodeClient>::json_rpc(&main_node_url)
         .context("Failed creating JSON-RPC client for main node")?;
+    let fallback_main_node_urls = config.optional.fallback_main_node_urls()?;
+    let fetcher_client: Box<dyn MainNodeClient> = if fallback_main_node_urls.is_empty() {
+        Box::new(main_node_client)
+    } else {
+        let mut client =
+            FailoverMainNodeClient::default().with_source("main_node", Box::new(main_node_client));
+        for (i, url) in fallback_main_node_urls.iter().enumerate() {
+            let fallback_client = <dyn MainNodeClient>::json_rpc(url)
+                .with_context(|| format!("Failed creating JSON-RPC client for {url}"))?;
+            client = client.with_source(format!("fallback_{i}"), Box::new(fallback_client));
+        }
+        Box::new(client)
+    };
     let singleton_pool_builder = ConnectionPool::singleton(&config.postgres.database_url);
     let fetcher_cursor = {
         let pool = singleton_pool_builder
@@ -179,12 +227,17 @@ async fn init_tasks(
             .await
             .context("failed to load `MainNodeFetcher` cursor from Postgres")?
     };
-    let fetcher = fetcher_cursor.into_fetcher(
-        Box::new(main_node_client),
+    let mut fetcher = fetcher_cursor.into_fetcher(
+        fetcher_client,
         action_queue_sender,
         sync_state.clone(),
         stop_receiver.clone(),
     );
+    if let Some(journal_path) = &config.optional.fetched_blocks_journal_path {
+        let journal = FetchedBlocksJournal::new(journal_path)
+            .context("failed opening journal of fetched miniblocks")?;
+        fetcher = fetcher.with_journal(journal);
+    }
 
     let metadata_calculator_config = MetadataCalculatorConfig {
         db_path: config.required.merkle_tree_path.clone(),
@@ -195,9 +248,40 @@ async fn init_tasks(
         block_cache_capacity: config.optional.merkle_tree_block_cache_size(),
         memtable_capacity: config.optional.merkle_tree_memtable_capacity(),
         stalled_writes_timeout: config.optional.merkle_tree_stalled_writes_timeout(),
+        hashing_thread_count: config.optional.merkle_tree_hashing_thread_count,
+        pruning_past_versions_to_keep: config.optional.merkle_tree_pruning_past_versions_to_keep,
+        storage_logs_recovery: config
+            .optional
+            .merkle_tree_recover_from_storage_logs
+            .then(|| {
+                let l1_batch = config.optional.merkle_tree_storage_logs_recovery_l1_batch;
+                StorageLogsRecoveryTarget::new(l1_batch.map(L1BatchNumber))
+            }),
+        follower_version_lag: config.optional.merkle_tree_follower_version_lag,
+        consistency_check_interval: config.optional.merkle_tree_consistency_check_interval(),
     };
-    let metadata_calculator = MetadataCalculator::new(metadata_calculator_config, None).await;
+    let mut tree_backups = None;
+    if let Some((object_store, interval)) = rocksdb_backups {
+        let backups = RocksDBBackups::new(
+            object_store,
+            "merkle_tree",
+            &config.required.merkle_tree_path,
+        );
+        backups
+            .restore_if_needed()
+            .await
+            .context("failed restoring Merkle tree from backup")?;
+        tree_backups = Some((backups, interval));
+    }
+    let mut metadata_calculator = MetadataCalculator::new(metadata_calculator_config, None).await;
+    if let Some((backups, interval)) = tree_backups {
+        metadata_calculator = metadata_calculator.with_backups(backups, interval);
+    }
     healthchecks.push(Box::new(metadata_calculator.tree_health_check()));
+    if let Some(health_check) = metadata_calculator.consistency_health_check() {
+        healthchecks.push(Box::new(health_check));
+    }
+    let tree_reader = metadata_calculator.lazy_tree_reader();
 
     let consistency_checker = ConsistencyChecker::new(
         &config
@@ -209,7 +293,8 @@ async fn init_tasks(
             .build()
             .await
             .context("failed to build connection pool for ConsistencyChecker")?,
-    );
+    )
+    .with_commitment_mode(config.optional.l1_batch_commitment_mode);
 
     let batch_status_updater = BatchStatusUpdater::new(
         &main_node_url,
@@ -231,6 +316,39 @@ async fn init_tasks(
     let consistency_checker_handle = tokio::spawn(consistency_checker.run(stop_receiver.clone()));
 
     let updater_handle = task::spawn(batch_status_updater.run(stop_receiver.clone()));
+    if config.optional.pruning_enabled {
+        tracing::warn!(
+            "Pruning is enabled; miniblock-level data (events, call traces, overwritten storage logs) \
+             for L1 batches older than {} latest ones will be removed",
+            config.optional.pruning_data_retention_l1_batches
+        );
+        let pruner_config = DbPrunerConfig {
+            retained_l1_batches: config.optional.pruning_data_retention_l1_batches,
+            pruned_batch_chunk_size: config.optional.pruning_chunk_size,
+            next_iterations_delay: config.optional.pruning_delay(),
+        };
+        let pruner_pool = singleton_pool_builder
+            .build()
+            .await
+            .context("failed to build a connection pool for DbPruner")?;
+        let pruner = DbPruner::new(pruner_config, pruner_pool);
+        task_handles.push(tokio::spawn(pruner.run(stop_receiver.clone())));
+    }
+    let backfill_pool = singleton_pool_builder
+        .build()
+        .await
+        .context("failed to build a connection pool for LogsBloomBackfill")?;
+    let logs_bloom_backfill = LogsBloomBackfill::new(backfill_pool);
+    task_handles.push(tokio::spawn(logs_bloom_backfill.run(stop_receiver.clone())));
+    let compression_pool = singleton_pool_builder
+        .build()
+        .await
+        .context("failed to build a connection pool for FactoryDepsCompression")?;
+    let factory_deps_compression = FactoryDepsCompression::new(compression_pool);
+    task_handles.push(tokio::spawn(
+        factory_deps_compression.run(stop_receiver.clone()),
+    ));
+
     let sk_handle = task::spawn(state_keeper.run());
     let fetcher_handle = tokio::spawn(fetcher.run());
     let fee_params_fetcher_handle =
@@ -272,14 +390,33 @@ async fn init_tasks(
         (tx_sender, vm_barrier, cache_update_handle)
     };
 
+    let proxy_cache_updater_handle = tx_sender
+        .proxy_cache_updater(connection_pool.clone(), stop_receiver.clone())
+        .map(tokio::spawn);
+
+    // The response cache is shared among HTTP and WS servers.
+    let response_cache_capacity = config.optional.response_cache_size() as u64;
+    let response_cache = (response_cache_capacity > 0).then(|| {
+        ResponseCache::new(
+            response_cache_capacity,
+            config.optional.response_cache_ttl(),
+        )
+    });
+    let response_cache_handle = response_cache.clone().map(|cache| {
+        tokio::spawn(cache.run_finality_updates(connection_pool.clone(), stop_receiver.clone()))
+    });
+
     let http_server_handles =
         ApiBuilder::jsonrpsee_backend(config.clone().into(), connection_pool.clone())
             .http(config.required.http_port)
             .with_filter_limit(config.optional.filters_limit)
             .with_batch_request_size_limit(config.optional.max_batch_request_size)
+            .with_batch_request_concurrency(config.optional.batch_request_concurrency)
             .with_response_body_size_limit(config.optional.max_response_body_size())
+            .with_response_cache(response_cache.clone())
             .with_tx_sender(tx_sender.clone(), vm_barrier.clone())
             .with_sync_state(sync_state.clone())
+            .with_tree_reader(tree_reader.clone())
             .enable_api_namespaces(config.optional.api_namespaces())
             .build(stop_receiver.clone())
             .await
@@ -290,11 +427,18 @@ async fn init_tasks(
             .ws(config.required.ws_port)
             .with_filter_limit(config.optional.filters_limit)
             .with_subscriptions_limit(config.optional.subscriptions_limit)
+            .with_websocket_subscriptions_per_connection_limit(
+                config.optional.websocket_subscriptions_per_connection_limit,
+            )
+            .with_websocket_idle_timeout(config.optional.websocket_idle_timeout())
+            .with_pub_sub_send_timeout(config.optional.pubsub_send_timeout())
             .with_batch_request_size_limit(config.optional.max_batch_request_size)
             .with_response_body_size_limit(config.optional.max_response_body_size())
             .with_polling_interval(config.optional.polling_interval())
+            .with_response_cache(response_cache)
             .with_tx_sender(tx_sender, vm_barrier)
             .with_sync_state(sync_state)
+            .with_tree_reader(tree_reader)
             .enable_api_namespaces(config.optional.api_namespaces())
             .build(stop_receiver.clone())
             .await
@@ -303,18 +447,12 @@ async fn init_tasks(
     healthchecks.push(Box::new(ws_server_handles.health_check));
     healthchecks.push(Box::new(http_server_handles.health_check));
     healthchecks.push(Box::new(ConnectionPoolHealthCheck::new(connection_pool)));
-    let healthcheck_handle = HealthCheckHandle::spawn_server(
-        ([0, 0, 0, 0], config.required.healthcheck_port).into(),
-        healthchecks,
-    );
 
-    if let Some(port) = config.optional.prometheus_port {
-        let prometheus_task = PrometheusExporterConfig::pull(port).run(stop_receiver.clone());
-        task_handles.push(tokio::spawn(prometheus_task));
-    }
     task_handles.extend(http_server_handles.tasks);
     task_handles.extend(ws_server_handles.tasks);
     task_handles.extend(cache_update_handle);
+    task_handles.extend(proxy_cache_updater_handle);
+    task_handles.extend(response_cache_handle);
     task_handles.extend([
         sk_handle,
         fetcher_handle,
@@ -324,20 +462,16 @@ async fn init_tasks(
     ]);
     task_handles.push(consistency_checker_handle);
 
-    Ok((task_handles, stop_sender, healthcheck_handle, stop_receiver))
+    Ok((task_handles, stop_sender, healthchecks, stop_receiver))
 }
 
-async fn shutdown_components(
-    stop_sender: watch::Sender<bool>,
-    healthcheck_handle: HealthCheckHandle,
-) {
+async fn shutdown_components(stop_sender: watch::Sender<bool>) {
     stop_sender.send(true).ok();
     task::spawn_blocking(RocksDB::await_rocksdb_termination)
         .await
         .unwrap();
     // Sleep for some time to let components gracefully stop.
     sleep(Duration::from_secs(10)).await;
-    healthcheck_handle.stop().await;
 }
 
 #[derive(Debug, Parser)]
@@ -347,6 +481,48 @@ struct Cli {
     revert_pending_l1_batch: bool,
 }
 
+/// Checks whether the node state should be recovered from a snapshot rather than from genesis.
+async fn should_recover_from_snapshot(
+    config: &ExternalNodeConfig,
+    pool: &ConnectionPool,
+) -> anyhow::Result<bool> {
+    let mut storage = pool.access_storage().await?;
+    let recovery_status = storage
+        .snapshot_recovery_dal()
+        .get_applied_snapshot_status()
+        .await?;
+    if recovery_status.is_some() {
+        // The node was (or is being) recovered from a snapshot; recovery must be completed regardless of the config.
+        return Ok(true);
+    }
+    Ok(config.optional.snapshots_recovery_enabled
+        && storage.blocks_dal().is_genesis_needed().await?)
+}
+
+/// Removes miniblocks after the last miniblock in Postgres from the journal of fetched miniblocks (if the journal
+/// is enabled). Must be called after a rollback; otherwise, miniblocks from the reverted chain would be replayed.
+async fn truncate_fetched_blocks_journal(
+    config: &ExternalNodeConfig,
+    pool: &ConnectionPool,
+) -> anyhow::Result<()> {
+    let Some(journal_path) = &config.optional.fetched_blocks_journal_path else {
+        return Ok(());
+    };
+    let mut storage = pool.access_storage().await?;
+    let last_miniblock = storage
+        .blocks_dal()
+        .get_sealed_miniblock_number()
+        .await
+        .context("Failed getting sealed miniblock number")?;
+    drop(storage);
+
+    let journal = FetchedBlocksJournal::new(journal_path)
+        .context("failed opening journal of fetched miniblocks")?;
+    tokio::task::spawn_blocking(move || journal.truncate_after(last_miniblock))
+        .await
+        .context("panicked truncating journal of fetched miniblocks")?
+}
+
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     // Initial setup.
@@ -358,6 +534,8 @@ async fn main() -> anyhow::Result<()> {
     let sentry_url = vlog::sentry_url_from_env();
     #[allow(deprecated)] // TODO (QIT-21): Use centralized configuration approach.
     let environment = vlog::environment_from_env();
+    #[allow(deprecated)] // TODO (QIT-21): Use centralized configuration approach.
+    let opentelemetry = vlog::opentelemetry_from_env("zksync_external_node");
 
     let mut builder = vlog::ObservabilityBuilder::new().with_log_format(log_format);
     if let Some(sentry_url) = &sentry_url {
@@ -366,6 +544,9 @@ async fn main() -> anyhow::Result<()> {
             .expect("Invalid Sentry URL")
             .with_sentry_environment(environment);
     }
+    if let Some(opentelemetry) = opentelemetry.clone() {
+        builder = builder.with_opentelemetry(opentelemetry);
+    }
     let _guard = builder.build();
 
     // Report whether sentry is running after the logging subsystem was initialized.
@@ -374,6 +555,13 @@ async fn main() -> anyhow::Result<()> {
     } else {
         tracing::info!("No sentry URL was provided");
     }
+    if let Some(opentelemetry) = opentelemetry {
+        tracing::info!(
+            "OpenTelemetry traces are exported to {} with sampling ratio {}",
+            opentelemetry.endpoint,
+            opentelemetry.sampling_ratio
+        );
+    }
 
     let config = ExternalNodeConfig::collect()
         .await
@@ -383,6 +571,9 @@ async fn main() -> anyhow::Result<()> {
         .main_node_url()
         .context("Main node URL is incorrect")?;
 
+    if let Some(threshold) = config.optional.slow_query_threshold() {
+        ConnectionPool::global_config().set_slow_query_threshold(threshold)?;
+    }
     let connection_pool = ConnectionPool::builder(
         &config.postgres.database_url,
         config.postgres.max_connections,
@@ -393,8 +584,8 @@ async fn main() -> anyhow::Result<()> {
     if opt.revert_pending_l1_batch {
         tracing::info!("Rolling pending L1 batch back..");
         let reverter = BlockReverter::new(
-            config.required.state_cache_path,
-            config.required.merkle_tree_path,
+            config.required.state_cache_path.clone(),
+            config.required.merkle_tree_path.clone(),
             None,
             connection_pool.clone(),
             L1ExecutedBatchesRevert::Allowed,
@@ -415,88 +606,161 @@ async fn main() -> anyhow::Result<()> {
         reverter
             .rollback_db(sealed_l1_batch_number, BlockReverterFlags::all())
             .await;
+        truncate_fetched_blocks_journal(&config, &connection_pool).await?;
         tracing::info!(
             "Rollback successfully completed, the node has to restart to continue working"
         );
         return Ok(());
     }
 
-    let sigint_receiver = setup_sigint_handler();
+    let mut sigint_receiver = setup_sigint_handler();
     tracing::warn!("The external node is in the alpha phase, and should be used with caution.");
     tracing::info!("Started the external node");
     tracing::info!("Main node URL is: {}", main_node_url);
 
-    // Make sure that genesis is performed.
+    // The healthcheck and Prometheus servers are shared among all incarnations of the node components,
+    // so that they remain available while components are restarted after a rollback.
+    let healthcheck_handle = HealthCheckHandle::spawn_server(
+        ([0, 0, 0, 0], config.required.healthcheck_port).into(),
+        config
+            .optional
+            .admin_port
+            .map(|port| ([127, 0, 0, 1], port).into()),
+        vec![Box::new(ConnectionPoolHealthCheck::new(
+            connection_pool.clone(),
+        ))],
+    );
+    let (prometheus_stop_sender, prometheus_stop_receiver) = watch::channel(false);
+    let prometheus_handle = config.optional.prometheus_port.map(|port| {
+        tokio::spawn(PrometheusExporterConfig::pull(port).run(prometheus_stop_receiver))
+    });
+
+    // Make sure that genesis or snapshot recovery is performed.
     let main_node_client = <dyn MainNodeClient>::json_rpc(&main_node_url)
         .context("Failed creating JSON-RPC client for main node")?;
-    perform_genesis_if_needed(
-        &mut connection_pool.access_storage().await.unwrap(),
+    if should_recover_from_snapshot(&config, &connection_pool).await? {
+        let blob_store_config = SnapshotsObjectStoreConfig::from_env()
+            .context("Snapshots object store config is required for snapshot recovery")?;
+        let blob_store = ObjectStoreFactory::new(blob_store_config.0)
+            .create_store()
+            .await;
+        let l1_client = DiamondProxyL1Client::new(
+            &config.required.eth_client_url()?,
+            config.remote.diamond_proxy_addr,
+        )?;
+        let mut applier = SnapshotsApplier::new(&connection_pool, &main_node_client, &*blob_store)
+            .with_l1_client(&l1_client);
+        if let Some(signer) = config.optional.snapshots_recovery_manifest_signer {
+            applier = applier.with_manifest_signer(signer);
+        }
+        healthcheck_handle.replace_checks(vec![Box::new(applier.health_check())]);
+        applier.run().await.context("Snapshot recovery failed")?;
+    } else {
+        perform_genesis_if_needed(
+            &mut connection_pool.access_storage().await.unwrap(),
+            config.remote.l2_chain_id,
+            &main_node_client,
+        )
+        .await
+        .context("Performing genesis failed")?;
+    }
+
+    validate_chain_config(
+        &connection_pool,
         config.remote.l2_chain_id,
-        &main_node_client,
+        config.remote.l1_chain_id,
+        config.remote.diamond_proxy_addr,
+        &config.required.eth_client_url()?,
+        config.optional.chain_registry_url.as_deref(),
     )
     .await
-    .context("Performing genesis failed")?;
-
-    let (task_handles, stop_sender, health_check_handle, stop_receiver) =
-        init_tasks(config.clone(), connection_pool.clone())
-            .await
-            .context("init_tasks")?;
-
-    let reorg_detector = ReorgDetector::new(&main_node_url, connection_pool.clone(), stop_receiver);
-    let mut reorg_detector_handle = tokio::spawn(reorg_detector.run()).fuse();
-    let mut reorg_detector_result = None;
+    .context("chain config validation failed")?;
 
-    let particular_crypto_alerts = None;
-    let graceful_shutdown = None::<futures::future::Ready<()>>;
-    let tasks_allowed_to_finish = false;
+    // The node is restarted in-process after each automatic rollback caused by a reorg.
+    let mut stop_requested = false;
+    while !stop_requested {
+        let (task_handles, stop_sender, healthchecks, stop_receiver) =
+            init_tasks(config.clone(), connection_pool.clone())
+                .await
+                .context("init_tasks")?;
+        healthcheck_handle.replace_checks(healthchecks);
 
-    tokio::select! {
-        _ = wait_for_tasks(task_handles, particular_crypto_alerts, graceful_shutdown, tasks_allowed_to_finish) => {},
-        _ = sigint_receiver => {
-            tracing::info!("Stop signal received, shutting down");
-        },
-        result = &mut reorg_detector_handle => {
-            tracing::info!("Reorg detector terminated, shutting down");
-            reorg_detector_result = Some(result);
+        let mut reorg_detector =
+            ReorgDetector::new(&main_node_url, connection_pool.clone(), stop_receiver);
+        if let Some(max_depth) = config.optional.reorg_max_rollback_depth {
+            reorg_detector = reorg_detector.with_max_rollback_depth(max_depth);
         }
-    };
+        let mut reorg_detector_handle = tokio::spawn(reorg_detector.run()).fuse();
+        let mut reorg_detector_result = None;
+
+        let particular_crypto_alerts = None;
+        let graceful_shutdown = None::<futures::future::Ready<()>>;
+        let tasks_allowed_to_finish = false;
+
+        tokio::select! {
+            _ = wait_for_tasks(task_handles, particular_crypto_alerts, graceful_shutdown, tasks_allowed_to_finish) => {},
+            _ = &mut sigint_receiver => {
+                tracing::info!("Stop signal received, shutting down");
+                stop_requested = true;
+            },
+            result = &mut reorg_detector_handle => {
+                tracing::info!("Reorg detector terminated, shutting down");
+                reorg_detector_result = Some(result);
+            }
+        };
 
-    // Reaching this point means that either some actor exited unexpectedly or we received a stop signal.
-    // Broadcast the stop signal to all actors and exit.
-    shutdown_components(stop_sender, health_check_handle).await;
+        // Reaching this point means that either some actor exited unexpectedly or we received a stop signal.
+        // Broadcast the stop signal to all actors and exit.
+        shutdown_components(stop_sender).await;
 
-    if !reorg_detector_handle.is_terminated() {
-        reorg_detector_result = Some(reorg_detector_handle.await);
-    }
-    let reorg_detector_last_correct_batch = reorg_detector_result.and_then(|result| match result {
-        Ok(Ok(last_correct_batch)) => last_correct_batch,
-        Ok(Err(err)) => {
-            tracing::error!("Reorg detector failed: {err}");
-            None
-        }
-        Err(err) => {
-            tracing::error!("Reorg detector panicked: {err}");
-            None
+        if !reorg_detector_handle.is_terminated() {
+            reorg_detector_result = Some(reorg_detector_handle.await);
         }
-    });
-
-    if let Some(last_correct_batch) = reorg_detector_last_correct_batch {
+        let reorg_detector_last_correct_batch =
+            reorg_detector_result.and_then(|result| match result {
+                Ok(Ok(last_correct_batch)) => last_correct_batch,
+                Ok(Err(err)) => {
+                    tracing::error!("Reorg detector failed: {err:#}");
+                    None
+                }
+                Err(err) => {
+                    tracing::error!("Reorg detector panicked: {err}");
+                    None
+                }
+            });
+
+        let Some(last_correct_batch) = reorg_detector_last_correct_batch else {
+            break;
+        };
         tracing::info!("Performing rollback to L1 batch #{last_correct_batch}");
 
         let reverter = BlockReverter::new(
-            config.required.state_cache_path,
-            config.required.merkle_tree_path,
+            config.required.state_cache_path.clone(),
+            config.required.merkle_tree_path.clone(),
             None,
-            connection_pool,
+            connection_pool.clone(),
             L1ExecutedBatchesRevert::Allowed,
         );
         reverter
             .rollback_db(last_correct_batch, BlockReverterFlags::all())
             .await;
-        tracing::info!(
-            "Rollback successfully completed, the node has to restart to continue working"
-        );
+        truncate_fetched_blocks_journal(&config, &connection_pool).await?;
+        if stop_requested {
+            tracing::info!(
+                "Rollback successfully completed, the node has to restart to continue working"
+            );
+        } else {
+            tracing::info!("Rollback successfully completed, restarting node components");
+        }
     }
 
+    healthcheck_handle.stop().await;
+    prometheus_stop_sender.send_replace(true);
+    if let Some(prometheus_handle) = prometheus_handle {
+        prometheus_handle
+            .await
+            .context("Prometheus exporter panicked")?
+            .context("Prometheus exporter failed")?;
+    }
     Ok(())
 }
@@ -0,0 +1,409 @@
+//! [`SnapshotsCoordinator`] creating snapshots on a schedule and applying the retention policy.
+
+use std::{
+    collections::HashSet,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::Context as _;
+use tokio::sync::watch;
+use zksync_config::SnapshotsCreatorConfig;
+use zksync_object_store::{Bucket, ObjectStoreError, StoredObject};
+use zksync_types::{
+    snapshots::{
+        SnapshotFactoryDependencies, SnapshotStorageLogsChunk, SnapshotStorageLogsStorageKey,
+    },
+    L1BatchNumber,
+};
+
+use crate::{creator::SnapshotCreator, metrics::METRICS};
+
+const MINUTES_IN_DAY: u32 = 24 * 60;
+
+/// Time window within a day (in UTC) during which snapshots may be created. The window may wrap around midnight.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct TimeWindow {
+    start_minute: u32,
+    end_minute: u32,
+}
+
+impl TimeWindow {
+    fn parse_time(time: &str) -> anyhow::Result<u32> {
+        let time = time.trim();
+        let (hours, minutes) = time
+            .split_once(':')
+            .with_context(|| format!("time `{time}` is not in the `HH:MM` format"))?;
+        let hours: u32 = hours
+            .parse()
+            .with_context(|| format!("invalid hours in time `{time}`"))?;
+        let minutes: u32 = minutes
+            .parse()
+            .with_context(|| format!("invalid minutes in time `{time}`"))?;
+        anyhow::ensure!(hours < 24 && minutes < 60, "time `{time}` is out of range");
+        Ok(hours * 60 + minutes)
+    }
+
+    fn parse(window: &str) -> anyhow::Result<Self> {
+        let (start, end) = window.split_once('-').with_context(|| {
+            format!("time window `{window}` is not in the `HH:MM-HH:MM` format")
+        })?;
+        let start_minute = Self::parse_time(start)?;
+        let end_minute = Self::parse_time(end)?;
+        anyhow::ensure!(
+            start_minute != end_minute,
+            "time window `{window}` is empty"
+        );
+        Ok(Self {
+            start_minute,
+            end_minute,
+        })
+    }
+
+    fn contains(self, minute_of_day: u32) -> bool {
+        if self.start_minute < self.end_minute {
+            (self.start_minute..self.end_minute).contains(&minute_of_day)
+        } else {
+            minute_of_day >= self.start_minute || minute_of_day < self.end_minute
+        }
+    }
+}
+
+/// Schedule determining when new snapshots are created.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct SnapshotSchedule {
+    every_l1_batches: Option<u32>,
+    time_windows: Vec<TimeWindow>,
+}
+
+impl SnapshotSchedule {
+    pub fn new(config: &SnapshotsCreatorConfig) -> anyhow::Result<Self> {
+        if let Some(every_l1_batches) = config.schedule_every_l1_batches {
+            anyhow::ensure!(
+                every_l1_batches > 0,
+                "`schedule_every_l1_batches` must be positive"
+            );
+        }
+        let time_windows = config.schedule_time_windows.as_deref().unwrap_or_default();
+        let time_windows = time_windows
+            .split(',')
+            .filter(|window| !window.trim().is_empty())
+            .map(TimeWindow::parse)
+            .collect::<anyhow::Result<_>>()
+            .context("invalid `schedule_time_windows`")?;
+        Ok(Self {
+            every_l1_batches: config.schedule_every_l1_batches,
+            time_windows,
+        })
+    }
+
+    /// Checks whether a snapshot for `next_l1_batch` should be created given the latest complete snapshot.
+    fn is_due(
+        &self,
+        latest_snapshot: Option<L1BatchNumber>,
+        next_l1_batch: L1BatchNumber,
+        minute_of_day: u32,
+    ) -> bool {
+        let is_in_window = self.time_windows.is_empty()
+            || self
+                .time_windows
+                .iter()
+                .any(|window| window.contains(minute_of_day));
+        if !is_in_window {
+            return false;
+        }
+
+        let Some(latest_snapshot) = latest_snapshot else {
+            return true;
+        };
+        match self.every_l1_batches {
+            Some(every_l1_batches) => {
+                next_l1_batch.0 >= latest_snapshot.0.saturating_add(every_l1_batches)
+            }
+            None => next_l1_batch > latest_snapshot,
+        }
+    }
+}
+
+fn current_minute_of_day() -> u32 {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("current time is before Unix epoch");
+    ((timestamp.as_secs() / 60) % u64::from(MINUTES_IN_DAY)) as u32
+}
+
+/// Long-running coordinator creating snapshots according to a [`SnapshotSchedule`] and removing old snapshots
+/// according to the retention policy.
+#[derive(Debug)]
+pub(crate) struct SnapshotsCoordinator {
+    creator: SnapshotCreator,
+    config: SnapshotsCreatorConfig,
+    schedule: SnapshotSchedule,
+    min_chunk_count: u64,
+}
+
+impl SnapshotsCoordinator {
+    pub fn new(
+        creator: SnapshotCreator,
+        config: SnapshotsCreatorConfig,
+        min_chunk_count: u64,
+    ) -> anyhow::Result<Self> {
+        let schedule = SnapshotSchedule::new(&config)?;
+        Ok(Self {
+            creator,
+            config,
+            schedule,
+            min_chunk_count,
+        })
+    }
+
+    async fn should_create_snapshot(&self, minute_of_day: u32) -> anyhow::Result<bool> {
+        let mut master_conn = self
+            .creator
+            .master_pool
+            .access_storage_tagged("snapshots_creator")
+            .await?;
+        let latest_snapshot = master_conn
+            .snapshots_dal()
+            .get_newest_snapshot_metadata()
+            .await?;
+        drop(master_conn);
+
+        if let Some(snapshot) = &latest_snapshot {
+            if !snapshot.is_complete() {
+                // Pending snapshots are always resumed, regardless of the schedule.
+                return Ok(true);
+            }
+        }
+
+        let sealed_l1_batch_number = self
+            .creator
+            .replica_pool
+            .access_storage_tagged("snapshots_creator")
+            .await?
+            .blocks_dal()
+            .get_sealed_l1_batch_number()
+            .await?;
+        // Snapshots are created for the penultimate sealed L1 batch; see `SnapshotCreator`.
+        let Some(sealed_l1_batch_number) =
+            sealed_l1_batch_number.filter(|&number| number > L1BatchNumber(0))
+        else {
+            return Ok(false);
+        };
+        Ok(self.schedule.is_due(
+            latest_snapshot.map(|snapshot| snapshot.l1_batch_number),
+            sealed_l1_batch_number - 1,
+            minute_of_day,
+        ))
+    }
+
+    /// Removes complete snapshots except for the `keep_last` latest ones and the snapshots they are based on.
+    /// Returns L1 batch numbers of the removed snapshots.
+    pub(crate) async fn prune_snapshots(
+        &self,
+        keep_last: u32,
+    ) -> anyhow::Result<Vec<L1BatchNumber>> {
+        let mut master_conn = self
+            .creator
+            .master_pool
+            .access_storage_tagged("snapshots_creator")
+            .await?;
+        let complete_snapshots = master_conn
+            .snapshots_dal()
+            .get_all_complete_snapshots()
+            .await?
+            .snapshots_l1_batch_numbers;
+        if complete_snapshots.len() <= keep_last as usize {
+            return Ok(vec![]);
+        }
+
+        // Complete snapshots are ordered by descending L1 batch number. The pending snapshot (if any)
+        // must retain its base snapshots as well.
+        let mut roots: Vec<_> = complete_snapshots
+            .iter()
+            .copied()
+            .take(keep_last as usize)
+            .collect();
+        let latest_snapshot = master_conn
+            .snapshots_dal()
+            .get_newest_snapshot_metadata()
+            .await?;
+        if let Some(snapshot) = latest_snapshot.filter(|snapshot| !snapshot.is_complete()) {
+            roots.push(snapshot.l1_batch_number);
+        }
+
+        let mut kept_snapshots = HashSet::new();
+        for root in roots {
+            let mut current = Some(root);
+            while let Some(l1_batch_number) = current {
+                if !kept_snapshots.insert(l1_batch_number) {
+                    break;
+                }
+                let snapshot = master_conn
+                    .snapshots_dal()
+                    .get_snapshot_metadata(l1_batch_number)
+                    .await?
+                    .with_context(|| {
+                        format!("snapshot for L1 batch #{l1_batch_number} is missing")
+                    })?;
+                current = snapshot.base_l1_batch_number;
+            }
+        }
+
+        let mut removed_snapshots = vec![];
+        for l1_batch_number in complete_snapshots {
+            if kept_snapshots.contains(&l1_batch_number) {
+                continue;
+            }
+            let snapshot = master_conn
+                .snapshots_dal()
+                .get_snapshot_metadata(l1_batch_number)
+                .await?
+                .with_context(|| format!("snapshot for L1 batch #{l1_batch_number} is missing"))?;
+            // Remove the objects before the Postgres record, so that the removal can be retried if it fails midway.
+            let chunk_count = snapshot.storage_logs_filepaths.len() as u64;
+            for chunk_id in 0..chunk_count {
+                let key = SnapshotStorageLogsChunk::encode_key(SnapshotStorageLogsStorageKey {
+                    l1_batch_number,
+                    chunk_id,
+                });
+                self.remove_object(SnapshotStorageLogsChunk::BUCKET, &key)
+                    .await
+                    .with_context(|| format!("failed removing storage logs chunk `{key}`"))?;
+            }
+            let key = SnapshotFactoryDependencies::encode_key(l1_batch_number);
+            self.remove_object(SnapshotFactoryDependencies::BUCKET, &key)
+                .await
+                .with_context(|| format!("failed removing factory deps `{key}`"))?;
+
+            master_conn
+                .snapshots_dal()
+                .delete_snapshot(l1_batch_number)
+                .await?;
+
+            tracing::info!(
+                "Removed snapshot for L1 batch #{l1_batch_number} with {chunk_count} storage logs chunks"
+            );
+            METRICS.removed_snapshots.inc();
+            removed_snapshots.push(l1_batch_number);
+        }
+        Ok(removed_snapshots)
+    }
+
+    /// Removes an object from the store. Objects missing in the store (e.g., removed during a previous pruning attempt
+    /// that failed midway) are skipped.
+    async fn remove_object(&self, bucket: Bucket, key: &str) -> Result<(), ObjectStoreError> {
+        match self.creator.blob_store.remove_raw(bucket, key).await {
+            Ok(()) | Err(ObjectStoreError::KeyNotFound(_)) => Ok(()),
+            Err(err) => Err(err),
+        }
+    }
+
+    async fn apply_retention(&self) -> anyhow::Result<()> {
+        if let Some(keep_last) = self.config.retention_keep_last {
+            self.prune_snapshots(keep_last)
+                .await
+                .context("failed pruning old snapshots")?;
+        }
+        Ok(())
+    }
+
+    /// Creates a single snapshot (if necessary) and applies the retention policy, ignoring the schedule.
+    pub async fn run_once(self) -> anyhow::Result<()> {
+        self.creator
+            .run(self.config.clone(), self.min_chunk_count)
+            .await?;
+        self.apply_retention().await
+    }
+
+    pub async fn run(self, mut stop_receiver: watch::Receiver<bool>) -> anyhow::Result<()> {
+        let poll_interval = self.config.schedule_poll_interval();
+        tracing::info!(
+            "Starting snapshots coordinator with schedule {:?}, polling every {poll_interval:?}",
+            self.schedule
+        );
+
+        while !*stop_receiver.borrow() {
+            if self.should_create_snapshot(current_minute_of_day()).await? {
+                let run = self.creator.run(self.config.clone(), self.min_chunk_count);
+                tokio::select! {
+                    result = run => result?,
+                    // The creator is fault-tolerant, so it's safe to interrupt it.
+                    _ = stop_receiver.changed() => break,
+                }
+            }
+            self.apply_retention().await?;
+            tokio::time::timeout(poll_interval, stop_receiver.changed())
+                .await
+                .ok();
+        }
+        tracing::info!("Stop signal received, snapshots coordinator is shutting down");
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parsing_time_windows() {
+        let window = TimeWindow::parse("01:30-03:00").unwrap();
+        assert_eq!(
+            window,
+            TimeWindow {
+                start_minute: 90,
+                end_minute: 180
+            }
+        );
+        assert!(!window.contains(89));
+        assert!(window.contains(90));
+        assert!(window.contains(179));
+        assert!(!window.contains(180));
+
+        let window = TimeWindow::parse(" 23:00 - 01:00 ").unwrap();
+        assert!(window.contains(23 * 60));
+        assert!(window.contains(0));
+        assert!(window.contains(59));
+        assert!(!window.contains(60));
+        assert!(!window.contains(12 * 60));
+
+        for invalid_window in ["01:00", "01:00-01:00", "24:00-01:00", "01:60-02:00", "1-2"] {
+            TimeWindow::parse(invalid_window).unwrap_err();
+        }
+    }
+
+    #[test]
+    fn snapshot_schedule_by_l1_batches() {
+        let schedule = SnapshotSchedule {
+            every_l1_batches: Some(10),
+            time_windows: vec![],
+        };
+        assert!(schedule.is_due(None, L1BatchNumber(1), 0));
+        assert!(!schedule.is_due(Some(L1BatchNumber(5)), L1BatchNumber(5), 0));
+        assert!(!schedule.is_due(Some(L1BatchNumber(5)), L1BatchNumber(14), 0));
+        assert!(schedule.is_due(Some(L1BatchNumber(5)), L1BatchNumber(15), 0));
+
+        let schedule = SnapshotSchedule {
+            every_l1_batches: None,
+            time_windows: vec![],
+        };
+        assert!(!schedule.is_due(Some(L1BatchNumber(5)), L1BatchNumber(5), 0));
+        assert!(schedule.is_due(Some(L1BatchNumber(5)), L1BatchNumber(6), 0));
+    }
+
+    #[test]
+    fn snapshot_schedule_with_time_windows() {
+        let schedule = SnapshotSchedule {
+            every_l1_batches: Some(10),
+            time_windows: vec![
+                TimeWindow::parse("02:00-03:00").unwrap(),
+                TimeWindow::parse("14:00-15:00").unwrap(),
+            ],
+        };
+        assert!(!schedule.is_due(None, L1BatchNumber(1), 0));
+        assert!(schedule.is_due(None, L1BatchNumber(1), 2 * 60 + 30));
+        assert!(schedule.is_due(None, L1BatchNumber(1), 14 * 60));
+        assert!(!schedule.is_due(Some(L1BatchNumber(5)), L1BatchNumber(6), 14 * 60));
+        assert!(!schedule.is_due(Some(L1BatchNumber(5)), L1BatchNumber(20), 15 * 60));
+    }
+}
@@ -6,13 +6,13 @@ use anyhow::Context as _;
 use tokio::sync::Semaphore;
 use zksync_config::SnapshotsCreatorConfig;
 use zksync_dal::{ConnectionPool, StorageProcessor};
-use zksync_object_store::ObjectStore;
+use zksync_object_store::{ObjectStore, StoredObject};
 use zksync_types::{
     snapshots::{
-        SnapshotFactoryDependencies, SnapshotMetadata, SnapshotStorageLogsChunk,
+        SnapshotFactoryDependencies, SnapshotManifest, SnapshotMetadata, SnapshotStorageLogsChunk,
         SnapshotStorageLogsStorageKey,
     },
-    L1BatchNumber, MiniblockNumber,
+    L1BatchNumber, MiniblockNumber, PackedEthSignature, H256,
 };
 use zksync_utils::ceil_div;
 
@@ -27,6 +27,8 @@ use crate::{
 #[derive(Debug)]
 struct SnapshotProgress {
     l1_batch_number: L1BatchNumber,
+    /// L1 batch of the base snapshot for delta snapshots.
+    base_l1_batch_number: Option<L1BatchNumber>,
     /// `true` if the snapshot is new (i.e., its progress is not recovered from Postgres).
     is_new_snapshot: bool,
     chunk_count: u64,
@@ -34,9 +36,14 @@ struct SnapshotProgress {
 }
 
 impl SnapshotProgress {
-    fn new(l1_batch_number: L1BatchNumber, chunk_count: u64) -> Self {
+    fn new(
+        l1_batch_number: L1BatchNumber,
+        base_l1_batch_number: Option<L1BatchNumber>,
+        chunk_count: u64,
+    ) -> Self {
         Self {
             l1_batch_number,
+            base_l1_batch_number,
             is_new_snapshot: true,
             chunk_count,
             remaining_chunk_ids: (0..chunk_count).collect(),
@@ -53,6 +60,7 @@ impl SnapshotProgress {
 
         Self {
             l1_batch_number: snapshot.l1_batch_number,
+            base_l1_batch_number: snapshot.base_l1_batch_number,
             is_new_snapshot: false,
             chunk_count: snapshot.storage_logs_filepaths.len() as u64,
             remaining_chunk_ids,
@@ -60,12 +68,37 @@ impl SnapshotProgress {
     }
 }
 
+/// Limits for the storage logs chunk processing pipeline. A chunk is processed in stages: loading from Postgres,
+/// serialization and compression, and uploading to the object store. Stages for different chunks run
+/// concurrently, with each stage having its own concurrency limit.
+#[derive(Debug)]
+struct ChunkPipelineLimits {
+    /// Bounds the number of chunks held in memory.
+    in_flight: Semaphore,
+    queries: Semaphore,
+    serialization: Semaphore,
+    uploads: Semaphore,
+}
+
+impl ChunkPipelineLimits {
+    fn new(config: &SnapshotsCreatorConfig) -> Self {
+        Self {
+            in_flight: Semaphore::new(config.max_in_flight_chunks as usize),
+            queries: Semaphore::new(config.concurrent_queries_count as usize),
+            serialization: Semaphore::new(config.serialization_threads_count as usize),
+            uploads: Semaphore::new(config.concurrent_uploads_count as usize),
+        }
+    }
+}
+
 /// Creator of a single storage snapshot.
 #[derive(Debug)]
 pub(crate) struct SnapshotCreator {
     pub blob_store: Arc<dyn ObjectStore>,
     pub master_pool: ConnectionPool,
     pub replica_pool: ConnectionPool,
+    /// Private key used to sign snapshot manifests.
+    pub manifest_signing_key: Option<H256>,
     #[cfg(test)]
     pub event_listener: Box<dyn HandleEvent>,
 }
@@ -77,49 +110,108 @@ impl SnapshotCreator {
             .await
     }
 
+    /// Processes a single storage logs chunk. For delta snapshots, `delta_start` is the first miniblock
+    /// after the base snapshot.
+    #[allow(clippy::too_many_arguments)]
     async fn process_storage_logs_single_chunk(
         &self,
-        semaphore: &Semaphore,
+        limits: &ChunkPipelineLimits,
+        delta_start: Option<MiniblockNumber>,
         miniblock_number: MiniblockNumber,
         l1_batch_number: L1BatchNumber,
         chunk_id: u64,
         chunk_count: u64,
     ) -> anyhow::Result<()> {
-        let _permit = semaphore.acquire().await?;
+        let _in_flight_permit = limits.in_flight.acquire().await?;
         #[cfg(test)]
         if self.event_listener.on_chunk_started().should_exit() {
             return Ok(());
         }
+        METRICS.storage_logs_chunks_in_flight.inc_by(1);
+        let result = self
+            .process_storage_logs_chunk_stages(
+                limits,
+                delta_start,
+                miniblock_number,
+                l1_batch_number,
+                chunk_id,
+                chunk_count,
+            )
+            .await;
+        METRICS.storage_logs_chunks_in_flight.dec_by(1);
+        result
+    }
 
+    #[allow(clippy::too_many_arguments)]
+    async fn process_storage_logs_chunk_stages(
+        &self,
+        limits: &ChunkPipelineLimits,
+        delta_start: Option<MiniblockNumber>,
+        miniblock_number: MiniblockNumber,
+        l1_batch_number: L1BatchNumber,
+        chunk_id: u64,
+        chunk_count: u64,
+    ) -> anyhow::Result<()> {
         let hashed_keys_range = get_chunk_hashed_keys_range(chunk_id, chunk_count);
+        let query_permit = limits.queries.acquire().await?;
         let mut conn = self.connect_to_replica().await?;
 
         let latency =
             METRICS.storage_logs_processing_duration[&StorageChunkStage::LoadFromPostgres].start();
-        let logs = conn
-            .snapshots_creator_dal()
-            .get_storage_logs_chunk(miniblock_number, hashed_keys_range)
-            .await
-            .context("Error fetching storage logs count")?;
+        let logs = if let Some(delta_start) = delta_start {
+            conn.snapshots_creator_dal()
+                .get_storage_logs_delta_chunk(delta_start..=miniblock_number, hashed_keys_range)
+                .await
+        } else {
+            conn.snapshots_creator_dal()
+                .get_storage_logs_chunk(miniblock_number, hashed_keys_range)
+                .await
+        };
+        let logs = logs.context("Error fetching storage logs chunk")?;
         drop(conn);
+        drop(query_permit);
         let latency = latency.observe();
-        tracing::info!(
-            "Loaded chunk {chunk_id} ({} logs) from Postgres in {latency:?}",
-            logs.len()
+        let logs_count = logs.len();
+        tracing::info!("Loaded chunk {chunk_id} ({logs_count} logs) from Postgres in {latency:?}");
+
+        // Serialization and compression are CPU-bound, so they are performed on a blocking thread.
+        let serialization_permit = limits.serialization.acquire().await?;
+        let latency =
+            METRICS.storage_logs_processing_duration[&StorageChunkStage::Serialize].start();
+        let (chunk_hash, serialized_chunk) = tokio::task::spawn_blocking(move || {
+            let storage_logs_chunk = SnapshotStorageLogsChunk { storage_logs: logs };
+            let serialized_chunk = storage_logs_chunk
+                .serialize()
+                .map_err(|err| anyhow::anyhow!(err))?;
+            anyhow::Ok((storage_logs_chunk.hash(), serialized_chunk))
+        })
+        .await
+        .context("storage logs chunk serialization panicked")?
+        .context("Error serializing storage logs chunk")?;
+        drop(serialization_permit);
+        let latency = latency.observe();
+        tracing::debug!(
+            "Serialized chunk {chunk_id} ({} bytes) in {latency:?}",
+            serialized_chunk.len()
         );
 
+        let upload_permit = limits.uploads.acquire().await?;
         let latency =
             METRICS.storage_logs_processing_duration[&StorageChunkStage::SaveToGcs].start();
-        let storage_logs_chunk = SnapshotStorageLogsChunk { storage_logs: logs };
         let key = SnapshotStorageLogsStorageKey {
             l1_batch_number,
             chunk_id,
         };
-        let filename = self
-            .blob_store
-            .put(key, &storage_logs_chunk)
+        let filename = SnapshotStorageLogsChunk::encode_key(key);
+        self.blob_store
+            .put_raw(
+                SnapshotStorageLogsChunk::BUCKET,
+                &filename,
+                serialized_chunk,
+            )
             .await
             .context("Error storing storage logs chunk in blob store")?;
+        drop(upload_permit);
         let output_filepath_prefix = self
             .blob_store
             .get_storage_prefix::<SnapshotStorageLogsChunk>();
@@ -132,14 +224,20 @@ impl SnapshotCreator {
             .await?;
         master_conn
             .snapshots_dal()
-            .add_storage_logs_filepath_for_snapshot(l1_batch_number, chunk_id, &output_filepath)
+            .add_storage_logs_filepath_for_snapshot(
+                l1_batch_number,
+                chunk_id,
+                &output_filepath,
+                chunk_hash,
+            )
             .await?;
         #[cfg(test)]
         self.event_listener.on_chunk_saved();
 
         let tasks_left = METRICS.storage_logs_chunks_left_to_process.dec_by(1) - 1;
         tracing::info!(
-            "Saved chunk {chunk_id} (overall progress {}/{chunk_count}) in {latency:?} to location: {output_filepath}",
+            "Saved chunk {chunk_id} with {logs_count} logs (overall progress {}/{chunk_count}) in {latency:?} \
+             to location: {output_filepath}",
             chunk_count - tasks_left as u64
         );
         Ok(())
@@ -147,6 +245,7 @@ impl SnapshotCreator {
 
     async fn process_factory_deps(
         &self,
+        delta_start: Option<MiniblockNumber>,
         miniblock_number: MiniblockNumber,
         l1_batch_number: L1BatchNumber,
     ) -> anyhow::Result<String> {
@@ -155,10 +254,15 @@ impl SnapshotCreator {
         tracing::info!("Loading factory deps from Postgres...");
         let latency =
             METRICS.factory_deps_processing_duration[&FactoryDepsStage::LoadFromPostgres].start();
-        let factory_deps = conn
-            .snapshots_creator_dal()
-            .get_all_factory_deps(miniblock_number)
-            .await?;
+        let factory_deps = if let Some(delta_start) = delta_start {
+            conn.snapshots_creator_dal()
+                .get_factory_deps_delta(delta_start..=miniblock_number)
+                .await?
+        } else {
+            conn.snapshots_creator_dal()
+                .get_all_factory_deps(miniblock_number)
+                .await?
+        };
         drop(conn);
         let latency = latency.observe();
         tracing::info!("Loaded {} factory deps in {latency:?}", factory_deps.len());
@@ -185,11 +289,133 @@ impl SnapshotCreator {
         Ok(output_filepath)
     }
 
-    /// Returns `Ok(None)` if the created snapshot would coincide with `latest_snapshot`.
+    /// Finalizes the manifest for a complete snapshot: computes its hash and signs it if a signing key is configured.
+    /// If the manifest cannot be finalized yet (e.g., the Merkle tree has not processed the snapshot L1 batch),
+    /// finalization is postponed until the next creator run. Does nothing if the snapshot is incomplete
+    /// or its manifest is already finalized.
+    async fn finalize_manifest(&self, l1_batch_number: L1BatchNumber) -> anyhow::Result<()> {
+        let mut master_conn = self
+            .master_pool
+            .access_storage_tagged("snapshots_creator")
+            .await?;
+        let snapshot = master_conn
+            .snapshots_dal()
+            .get_snapshot_metadata(l1_batch_number)
+            .await?
+            .with_context(|| format!("snapshot for L1 batch #{l1_batch_number} is missing"))?;
+        if snapshot.manifest_hash.is_some() || !snapshot.is_complete() {
+            return Ok(());
+        }
+        let Some(storage_logs_chunk_hashes) = snapshot
+            .storage_logs_chunk_hashes
+            .iter()
+            .copied()
+            .collect::<Option<Vec<_>>>()
+        else {
+            tracing::warn!(
+                "Snapshot for L1 batch #{l1_batch_number} has chunks without hashes; it won't have a manifest"
+            );
+            return Ok(());
+        };
+
+        let base_manifest_hash = if let Some(base) = snapshot.base_l1_batch_number {
+            let base_snapshot = master_conn
+                .snapshots_dal()
+                .get_snapshot_metadata(base)
+                .await?
+                .with_context(|| format!("base snapshot for L1 batch #{base} is missing"))?;
+            let Some(hash) = base_snapshot.manifest_hash else {
+                tracing::info!(
+                    "Manifest of base snapshot for L1 batch #{base} is not finalized; postponing manifest \
+                     finalization for snapshot at L1 batch #{l1_batch_number}"
+                );
+                return Ok(());
+            };
+            Some(hash)
+        } else {
+            None
+        };
+
+        let l1_batch_root_hash = self
+            .connect_to_replica()
+            .await?
+            .blocks_dal()
+            .get_l1_batch_state_root(l1_batch_number)
+            .await?;
+        let Some(l1_batch_root_hash) = l1_batch_root_hash else {
+            tracing::info!(
+                "Root hash for L1 batch #{l1_batch_number} is not computed yet; postponing manifest finalization"
+            );
+            return Ok(());
+        };
+
+        let manifest = SnapshotManifest {
+            l1_batch_number,
+            l1_batch_root_hash,
+            base_manifest_hash,
+            storage_logs_chunk_hashes,
+            signature: None,
+        };
+        let manifest_hash = manifest.hash();
+        let signature = self
+            .manifest_signing_key
+            .map(|key| PackedEthSignature::sign_raw(&key, &manifest_hash))
+            .transpose()
+            .context("failed signing snapshot manifest")?;
+        master_conn
+            .snapshots_dal()
+            .set_snapshot_manifest(l1_batch_number, manifest_hash, signature.as_ref())
+            .await?;
+        tracing::info!(
+            "Finalized manifest for snapshot at L1 batch #{l1_batch_number} with hash {manifest_hash:?} \
+             (signed: {})",
+            signature.is_some()
+        );
+        Ok(())
+    }
+
+    /// Finalizes manifests postponed during previous runs for the latest snapshot and its base snapshots.
+    async fn finalize_postponed_manifests(&self) -> anyhow::Result<()> {
+        let mut master_conn = self
+            .master_pool
+            .access_storage_tagged("snapshots_creator")
+            .await?;
+        let mut snapshot = master_conn
+            .snapshots_dal()
+            .get_newest_snapshot_metadata()
+            .await?;
+        let mut pending_l1_batches = vec![];
+        while let Some(current) = snapshot {
+            if current.manifest_hash.is_some() {
+                break;
+            }
+            pending_l1_batches.push(current.l1_batch_number);
+            snapshot = match current.base_l1_batch_number {
+                Some(base) => {
+                    master_conn
+                        .snapshots_dal()
+                        .get_snapshot_metadata(base)
+                        .await?
+                }
+                None => None,
+            };
+        }
+        drop(master_conn);
+
+        // Base manifests must be finalized first since they are referenced by delta manifests.
+        for l1_batch_number in pending_l1_batches.into_iter().rev() {
+            self.finalize_manifest(l1_batch_number).await?;
+        }
+        Ok(())
+    }
+
+    /// Returns `Ok(None)` if the created snapshot would coincide with `latest_snapshot`. If `base_l1_batch_number`
+    /// is specified, a delta snapshot on top of the snapshot for this L1 batch is created.
     async fn initialize_snapshot_progress(
         config: &SnapshotsCreatorConfig,
         min_chunk_count: u64,
         latest_snapshot: Option<&SnapshotMetadata>,
+        base_l1_batch_number: Option<L1BatchNumber>,
         conn: &mut StorageProcessor<'_>,
     ) -> anyhow::Result<Option<SnapshotProgress>> {
         // We subtract 1 so that after restore, EN node has at least one L1 batch to fetch
@@ -210,20 +436,71 @@ impl SnapshotCreator {
             return Ok(None);
         }
 
-        let distinct_storage_logs_keys_count = conn
-            .snapshots_creator_dal()
-            .get_distinct_storage_logs_keys_count(l1_batch_number)
-            .await?;
+        let distinct_storage_logs_keys_count = if let Some(base) = base_l1_batch_number {
+            let delta_start = last_miniblock_in_batch(conn, base).await? + 1;
+            let delta_end = last_miniblock_in_batch(conn, l1_batch_number).await?;
+            conn.snapshots_creator_dal()
+                .get_changed_storage_logs_keys_count(delta_start..=delta_end)
+                .await?
+        } else {
+            conn.snapshots_creator_dal()
+                .get_distinct_storage_logs_keys_count(l1_batch_number)
+                .await?
+        };
         let chunk_size = config.storage_logs_chunk_size;
         // We force the minimum number of chunks to avoid situations where only one chunk is created in tests.
         let chunk_count =
             ceil_div(distinct_storage_logs_keys_count, chunk_size).max(min_chunk_count);
 
         tracing::info!(
-            "Selected storage logs chunking for L1 batch {l1_batch_number}: \
+            "Selected storage logs chunking for L1 batch {l1_batch_number} (base L1 batch: {base_l1_batch_number:?}): \
             {chunk_count} chunks of expected size {chunk_size}"
         );
-        Ok(Some(SnapshotProgress::new(l1_batch_number, chunk_count)))
+        Ok(Some(SnapshotProgress::new(
+            l1_batch_number,
+            base_l1_batch_number,
+            chunk_count,
+        )))
+    }
+
+    /// Selects the base snapshot for the next snapshot. Returns `None` if a full snapshot should be created.
+    async fn select_base_snapshot(
+        config: &SnapshotsCreatorConfig,
+        latest_snapshot: Option<&SnapshotMetadata>,
+        conn: &mut StorageProcessor<'_>,
+    ) -> anyhow::Result<Option<L1BatchNumber>> {
+        let Some(latest_snapshot) = latest_snapshot else {
+            return Ok(None);
+        };
+        if config.max_consecutive_delta_snapshots == 0 {
+            return Ok(None);
+        }
+
+        // Count delta snapshots between the latest snapshot and the closest full snapshot.
+        let mut delta_count = 0;
+        let mut base = latest_snapshot.base_l1_batch_number;
+        while let Some(base_l1_batch_number) = base {
+            delta_count += 1;
+            let base_snapshot = conn
+                .snapshots_dal()
+                .get_snapshot_metadata(base_l1_batch_number)
+                .await?
+                .with_context(|| {
+                    format!("base snapshot for L1 batch #{base_l1_batch_number} is missing")
+                })?;
+            base = base_snapshot.base_l1_batch_number;
+        }
+
+        if delta_count >= config.max_consecutive_delta_snapshots {
+            tracing::info!(
+                "Latest snapshot for L1 batch #{} has {delta_count} delta snapshots in its chain; \
+                 creating a full snapshot",
+                latest_snapshot.l1_batch_number
+            );
+            Ok(None)
+        } else {
+            Ok(Some(latest_snapshot.l1_batch_number))
+        }
     }
 
     /// Returns `Ok(None)` if a snapshot should not be created / resumed.
@@ -240,18 +517,21 @@ impl SnapshotCreator {
             .snapshots_dal()
             .get_newest_snapshot_metadata()
             .await?;
-        drop(master_conn);
-
         let pending_snapshot = latest_snapshot
             .as_ref()
             .filter(|snapshot| !snapshot.is_complete());
         if let Some(snapshot) = pending_snapshot {
             Ok(Some(SnapshotProgress::from_existing_snapshot(snapshot)))
         } else {
+            let base_l1_batch_number =
+                Self::select_base_snapshot(config, latest_snapshot.as_ref(), &mut master_conn)
+                    .await?;
+            drop(master_conn);
             Self::initialize_snapshot_progress(
                 config,
                 min_chunk_count,
                 latest_snapshot.as_ref(),
+                base_l1_batch_number,
                 &mut self.connect_to_replica().await?,
             )
             .await
@@ -259,12 +539,14 @@ impl SnapshotCreator {
     }
 
     pub async fn run(
-        self,
+        &self,
         config: SnapshotsCreatorConfig,
         min_chunk_count: u64,
     ) -> anyhow::Result<()> {
         let latency = METRICS.snapshot_generation_duration.start();
 
+        self.finalize_postponed_manifests().await?;
+
         let Some(progress) = self
             .load_or_initialize_snapshot_progress(&config, min_chunk_count)
             .await?
@@ -274,23 +556,37 @@ impl SnapshotCreator {
         };
 
         let mut conn = self.connect_to_replica().await?;
-        let (_, last_miniblock_number_in_batch) = conn
-            .blocks_dal()
-            .get_miniblock_range_of_l1_batch(progress.l1_batch_number)
-            .await?
-            .context("Error fetching last miniblock number")?;
+        let last_miniblock_number_in_batch =
+            last_miniblock_in_batch(&mut conn, progress.l1_batch_number).await?;
+        let delta_start = if let Some(base) = progress.base_l1_batch_number {
+            Some(last_miniblock_in_batch(&mut conn, base).await? + 1)
+        } else {
+            None
+        };
         drop(conn);
 
         METRICS.storage_logs_chunks_count.set(progress.chunk_count);
-        tracing::info!(
-            "Creating snapshot for storage logs up to miniblock {last_miniblock_number_in_batch}, \
-            L1 batch {}",
-            progress.l1_batch_number
-        );
+        if let Some(delta_start) = delta_start {
+            tracing::info!(
+                "Creating delta snapshot for storage logs in miniblocks \
+                 {delta_start}..={last_miniblock_number_in_batch}, L1 batch {}",
+                progress.l1_batch_number
+            );
+        } else {
+            tracing::info!(
+                "Creating snapshot for storage logs up to miniblock {last_miniblock_number_in_batch}, \
+                 L1 batch {}",
+                progress.l1_batch_number
+            );
+        }
 
         if progress.is_new_snapshot {
             let factory_deps_output_file = self
-                .process_factory_deps(last_miniblock_number_in_batch, progress.l1_batch_number)
+                .process_factory_deps(
+                    delta_start,
+                    last_miniblock_number_in_batch,
+                    progress.l1_batch_number,
+                )
                 .await?;
 
             let mut master_conn = self
@@ -301,6 +597,7 @@ impl SnapshotCreator {
                 .snapshots_dal()
                 .add_snapshot(
                     progress.l1_batch_number,
+                    progress.base_l1_batch_number,
                     progress.chunk_count,
                     &factory_deps_output_file,
                 )
@@ -310,10 +607,11 @@ impl SnapshotCreator {
         METRICS
             .storage_logs_chunks_left_to_process
             .set(progress.remaining_chunk_ids.len());
-        let semaphore = Semaphore::new(config.concurrent_queries_count as usize);
+        let limits = ChunkPipelineLimits::new(&config);
         let tasks = progress.remaining_chunk_ids.into_iter().map(|chunk_id| {
             self.process_storage_logs_single_chunk(
-                &semaphore,
+                &limits,
+                delta_start,
                 last_miniblock_number_in_batch,
                 progress.l1_batch_number,
                 chunk_id,
@@ -321,6 +619,7 @@ impl SnapshotCreator {
             )
         });
         futures::future::try_join_all(tasks).await?;
+        self.finalize_manifest(progress.l1_batch_number).await?;
 
         METRICS
             .snapshot_l1_batch
@@ -336,3 +635,17 @@ impl SnapshotCreator {
         Ok(())
     }
 }
+
+async fn last_miniblock_in_batch(
+    conn: &mut StorageProcessor<'_>,
+    l1_batch_number: L1BatchNumber,
+) -> anyhow::Result<MiniblockNumber> {
+    let (_, last_miniblock_number) = conn
+        .blocks_dal()
+        .get_miniblock_range_of_l1_batch(l1_batch_number)
+        .await?
+        .with_context(|| {
+            format!("Error fetching last miniblock number for L1 batch {l1_batch_number}")
+        })?;
+    Ok(last_miniblock_number)
+}
@@ -1,4 +1,7 @@
-//! Snapshot creator utility. Intended to run on a schedule, with each run creating a new snapshot.
+//! Snapshot creator utility. Can either be run on an external schedule, with each run creating a new snapshot,
+//! or as a long-running process creating snapshots according to the configured schedule (see
+//! [`SnapshotsCreatorConfig::is_scheduled()`]). In both cases, old snapshots are removed according to
+//! the retention policy if it's configured.
 //!
 //! # Assumptions
 //!
@@ -17,9 +20,10 @@ use zksync_dal::ConnectionPool;
 use zksync_env_config::{object_store::SnapshotsObjectStoreConfig, FromEnv};
 use zksync_object_store::ObjectStoreFactory;
 
-use crate::creator::SnapshotCreator;
+use crate::{coordinator::SnapshotsCoordinator, creator::SnapshotCreator};
 
 mod chunking;
+mod coordinator;
 mod creator;
 mod metrics;
 #[cfg(test)]
@@ -59,7 +63,7 @@ async fn main() -> anyhow::Result<()> {
     #[allow(deprecated)] // TODO (QIT-21): Use centralized configuration approach.
     let environment = vlog::environment_from_env();
 
-    let prometheus_exporter_task = maybe_enable_prometheus_metrics(stop_receiver).await?;
+    let prometheus_exporter_task = maybe_enable_prometheus_metrics(stop_receiver.clone()).await?;
     let mut builder = vlog::ObservabilityBuilder::new().with_log_format(log_format);
     if let Some(sentry_url) = sentry_url {
         builder = builder
@@ -94,10 +98,25 @@ async fn main() -> anyhow::Result<()> {
         blob_store,
         master_pool,
         replica_pool,
+        manifest_signing_key: creator_config.manifest_signing_key(),
         #[cfg(test)]
         event_listener: Box::new(()),
     };
-    creator.run(creator_config, MIN_CHUNK_COUNT).await?;
+    let is_scheduled = creator_config.is_scheduled();
+    let coordinator = SnapshotsCoordinator::new(creator, creator_config, MIN_CHUNK_COUNT)?;
+    if is_scheduled {
+        let coordinator_task = tokio::spawn(coordinator.run(stop_receiver));
+        tokio::select! {
+            result = coordinator_task => {
+                result.context("snapshots coordinator panicked")??;
+            }
+            _ = tokio::signal::ctrl_c() => {
+                tracing::info!("Stop signal received, shutting down");
+            }
+        }
+    } else {
+        coordinator.run_once().await?;
+    }
 
     tracing::info!("Finished running snapshot creator!");
     stop_sender.send(true).ok();
@@ -2,7 +2,9 @@
 
 use std::time::Duration;
 
-use vise::{Buckets, EncodeLabelSet, EncodeLabelValue, Family, Gauge, Histogram, Metrics, Unit};
+use vise::{
+    Buckets, Counter, EncodeLabelSet, EncodeLabelValue, Family, Gauge, Histogram, Metrics, Unit,
+};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EncodeLabelValue, EncodeLabelSet)]
 #[metrics(label = "stage", rename_all = "snake_case")]
@@ -15,6 +17,7 @@ pub(crate) enum FactoryDepsStage {
 #[metrics(label = "stage", rename_all = "snake_case")]
 pub(crate) enum StorageChunkStage {
     LoadFromPostgres,
+    Serialize,
     SaveToGcs,
 }
 
@@ -25,6 +28,8 @@ pub(crate) struct SnapshotsCreatorMetrics {
     pub storage_logs_chunks_count: Gauge<u64>,
     /// Number of chunks left to process for the snapshot being currently generated.
     pub storage_logs_chunks_left_to_process: Gauge<usize>,
+    /// Number of chunks being currently processed (i.e., loaded from Postgres, but not yet uploaded to the object store).
+    pub storage_logs_chunks_in_flight: Gauge<usize>,
     /// Total latency of snapshot generation.
     #[metrics(buckets = Buckets::LATENCIES, unit = Unit::Seconds)]
     pub snapshot_generation_duration: Histogram<Duration>,
@@ -37,6 +42,8 @@ pub(crate) struct SnapshotsCreatorMetrics {
     /// Latency of factory deps processing split by stage.
     #[metrics(buckets = Buckets::LATENCIES, unit = Unit::Seconds)]
     pub factory_deps_processing_duration: Family<FactoryDepsStage, Histogram<Duration>>,
+    /// Number of snapshots removed according to the retention policy.
+    pub removed_snapshots: Counter,
 }
 
 #[vise::register]
@@ -11,15 +11,15 @@ use std::{
 
 use rand::{thread_rng, Rng};
 use zksync_dal::StorageProcessor;
-use zksync_object_store::ObjectStore;
+use zksync_object_store::{ObjectStore, ObjectStoreError, StoredObject};
 use zksync_types::{
     block::{BlockGasCount, L1BatchHeader, MiniblockHeader},
     snapshots::{
-        SnapshotFactoryDependencies, SnapshotFactoryDependency, SnapshotStorageLog,
-        SnapshotStorageLogsChunk, SnapshotStorageLogsStorageKey,
+        SnapshotFactoryDependencies, SnapshotFactoryDependency, SnapshotManifest, SnapshotMetadata,
+        SnapshotStorageLog, SnapshotStorageLogsChunk, SnapshotStorageLogsStorageKey,
     },
-    AccountTreeId, Address, L1BatchNumber, MiniblockNumber, ProtocolVersion, StorageKey,
-    StorageLog, H256,
+    AccountTreeId, Address, L1BatchNumber, MiniblockNumber, PackedEthSignature, ProtocolVersion,
+    StorageKey, StorageLog, H256,
 };
 
 use super::*;
@@ -27,10 +27,26 @@ use super::*;
 const TEST_CONFIG: SnapshotsCreatorConfig = SnapshotsCreatorConfig {
     storage_logs_chunk_size: 1_000_000,
     concurrent_queries_count: 10,
+    max_in_flight_chunks: 10,
+    serialization_threads_count: 2,
+    concurrent_uploads_count: 5,
+    max_consecutive_delta_snapshots: 0,
+    schedule_every_l1_batches: None,
+    schedule_time_windows: None,
+    schedule_poll_interval_sec: 60,
+    retention_keep_last: None,
 };
 const SEQUENTIAL_TEST_CONFIG: SnapshotsCreatorConfig = SnapshotsCreatorConfig {
     storage_logs_chunk_size: 1_000_000,
     concurrent_queries_count: 1,
+    max_in_flight_chunks: 1,
+    serialization_threads_count: 1,
+    concurrent_uploads_count: 1,
+    max_consecutive_delta_snapshots: 0,
+    schedule_every_l1_batches: None,
+    schedule_time_windows: None,
+    schedule_poll_interval_sec: 60,
+    retention_keep_last: None,
 };
 
 #[derive(Debug)]
@@ -66,10 +82,18 @@ impl SnapshotCreator {
             blob_store,
             master_pool: pool.clone(),
             replica_pool: pool,
+            manifest_signing_key: None,
             event_listener: Box::new(()),
         }
     }
 
+    fn with_manifest_signing_key(self, key: H256) -> Self {
+        Self {
+            manifest_signing_key: Some(key),
+            ..self
+        }
+    }
+
     fn stop_after_chunk_count(self, stop_after_chunk_count: usize) -> Self {
         Self {
             event_listener: Box::new(TestEventListener::new(stop_after_chunk_count)),
@@ -131,6 +155,13 @@ struct ExpectedOutputs {
     storage_logs: HashSet<SnapshotStorageLog>,
 }
 
+impl ExpectedOutputs {
+    fn extend(&mut self, other: Self) {
+        self.deps.extend(other.deps);
+        self.storage_logs.extend(other.storage_logs);
+    }
+}
+
 async fn create_miniblock(
     conn: &mut StorageProcessor<'_>,
     miniblock_number: MiniblockNumber,
@@ -187,6 +218,54 @@ async fn create_l1_batch(
         .await;
 }
 
+/// Creates a miniblock and an L1 batch with the same number containing random storage logs and factory deps.
+/// `updated_logs` are written in the miniblock in addition to random logs; they must update existing keys.
+async fn create_block(
+    rng: &mut impl Rng,
+    conn: &mut StorageProcessor<'_>,
+    block_number: u32,
+    updated_logs: Vec<StorageLog>,
+) -> ExpectedOutputs {
+    let logs = gen_storage_logs(rng, 100);
+    let all_logs: Vec<_> = logs.iter().cloned().chain(updated_logs).collect();
+    create_miniblock(conn, MiniblockNumber(block_number), all_logs.clone()).await;
+
+    let factory_deps = gen_factory_deps(rng, 10);
+    conn.storage_dal()
+        .insert_factory_deps(MiniblockNumber(block_number), &factory_deps)
+        .await;
+
+    // Since we generate `logs` randomly, all of them are written the first time.
+    create_l1_batch(conn, L1BatchNumber(block_number), &logs).await;
+
+    let deps = factory_deps
+        .into_values()
+        .map(|bytecode| SnapshotFactoryDependency {
+            bytecode: bytecode.into(),
+        })
+        .collect();
+
+    let hashed_keys: Vec<_> = all_logs.iter().map(|log| log.key.hashed_key()).collect();
+    let expected_l1_batches_and_indices = conn
+        .storage_logs_dal()
+        .get_l1_batches_and_indices_for_initial_writes(&hashed_keys)
+        .await;
+    let storage_logs = all_logs
+        .into_iter()
+        .map(|log| {
+            let (l1_batch_number_of_initial_write, enumeration_index) =
+                expected_l1_batches_and_indices[&log.key.hashed_key()];
+            SnapshotStorageLog {
+                key: log.key,
+                value: log.value,
+                l1_batch_number_of_initial_write,
+                enumeration_index,
+            }
+        })
+        .collect();
+    ExpectedOutputs { deps, storage_logs }
+}
+
 async fn prepare_postgres(
     rng: &mut impl Rng,
     conn: &mut StorageProcessor<'_>,
@@ -198,43 +277,9 @@ async fn prepare_postgres(
 
     let mut outputs = ExpectedOutputs::default();
     for block_number in 0..block_count {
-        let logs = gen_storage_logs(rng, 100);
-        create_miniblock(conn, MiniblockNumber(block_number), logs.clone()).await;
-
-        let factory_deps = gen_factory_deps(rng, 10);
-        conn.storage_dal()
-            .insert_factory_deps(MiniblockNumber(block_number), &factory_deps)
-            .await;
-
-        // Since we generate `logs` randomly, all of them are written the first time.
-        create_l1_batch(conn, L1BatchNumber(block_number), &logs).await;
-
+        let block_outputs = create_block(rng, conn, block_number, vec![]).await;
         if block_number + 1 < block_count {
-            let factory_deps =
-                factory_deps
-                    .into_values()
-                    .map(|bytecode| SnapshotFactoryDependency {
-                        bytecode: bytecode.into(),
-                    });
-            outputs.deps.extend(factory_deps);
-
-            let hashed_keys: Vec<_> = logs.iter().map(|log| log.key.hashed_key()).collect();
-            let expected_l1_batches_and_indices = conn
-                .storage_logs_dal()
-                .get_l1_batches_and_indices_for_initial_writes(&hashed_keys)
-                .await;
-
-            let logs = logs.into_iter().map(|log| {
-                let (l1_batch_number_of_initial_write, enumeration_index) =
-                    expected_l1_batches_and_indices[&log.key.hashed_key()];
-                SnapshotStorageLog {
-                    key: log.key,
-                    value: log.value,
-                    l1_batch_number_of_initial_write,
-                    enumeration_index,
-                }
-            });
-            outputs.storage_logs.extend(logs);
+            outputs.extend(block_outputs);
         }
     }
     outputs
@@ -330,6 +375,33 @@ async fn persisting_snapshot_logs() {
     assert_storage_logs(&*object_store, snapshot_l1_batch_number, &expected_outputs).await;
 }
 
+#[tokio::test]
+async fn persisting_snapshot_logs_with_bounded_pipeline() {
+    let pool = ConnectionPool::test_pool().await;
+    let mut rng = thread_rng();
+    let object_store_factory = ObjectStoreFactory::mock();
+    let object_store = object_store_factory.create_store().await;
+    let mut conn = pool.access_storage().await.unwrap();
+    let expected_outputs = prepare_postgres(&mut rng, &mut conn, 10).await;
+
+    // More chunks can be loaded from Postgres than serialized or uploaded at the same time.
+    let config = SnapshotsCreatorConfig {
+        concurrent_queries_count: 3,
+        max_in_flight_chunks: 3,
+        serialization_threads_count: 1,
+        concurrent_uploads_count: 1,
+        ..TEST_CONFIG
+    };
+    SnapshotCreator::for_tests(object_store, pool.clone())
+        .run(config, MIN_CHUNK_COUNT)
+        .await
+        .unwrap();
+    let snapshot_l1_batch_number = L1BatchNumber(8);
+
+    let object_store = object_store_factory.create_store().await;
+    assert_storage_logs(&*object_store, snapshot_l1_batch_number, &expected_outputs).await;
+}
+
 async fn assert_storage_logs(
     object_store: &dyn ObjectStore,
     snapshot_l1_batch_number: L1BatchNumber,
@@ -457,3 +529,298 @@ async fn recovery_workflow_with_varying_chunk_size() {
     let object_store = object_store_factory.create_store().await;
     assert_storage_logs(&*object_store, snapshot_l1_batch_number, &expected_outputs).await;
 }
+
+#[tokio::test]
+async fn creating_delta_snapshots() {
+    let pool = ConnectionPool::test_pool().await;
+    let mut rng = thread_rng();
+    let object_store_factory = ObjectStoreFactory::mock();
+    let object_store = object_store_factory.create_store().await;
+    let mut conn = pool.access_storage().await.unwrap();
+    conn.protocol_versions_dal()
+        .save_protocol_version_with_tx(ProtocolVersion::default())
+        .await;
+
+    let mut block_outputs = vec![];
+    for block_number in 0..10 {
+        block_outputs.push(create_block(&mut rng, &mut conn, block_number, vec![]).await);
+    }
+    let config = SnapshotsCreatorConfig {
+        max_consecutive_delta_snapshots: 1,
+        ..TEST_CONFIG
+    };
+    SnapshotCreator::for_tests(object_store, pool.clone())
+        .run(config.clone(), MIN_CHUNK_COUNT)
+        .await
+        .unwrap();
+    let full_snapshot = conn
+        .snapshots_dal()
+        .get_snapshot_metadata(L1BatchNumber(8))
+        .await
+        .unwrap()
+        .expect("no full snapshot");
+    assert_eq!(full_snapshot.base_l1_batch_number, None);
+
+    // Update a key written in the first L1 batch.
+    let updated_log = block_outputs[0].storage_logs.iter().next().unwrap();
+    let updated_log = StorageLog::new_write_log(updated_log.key, H256::repeat_byte(0xff));
+    block_outputs.push(create_block(&mut rng, &mut conn, 10, vec![updated_log]).await);
+    block_outputs.push(create_block(&mut rng, &mut conn, 11, vec![]).await);
+
+    let object_store = object_store_factory.create_store().await;
+    SnapshotCreator::for_tests(object_store, pool.clone())
+        .run(config.clone(), MIN_CHUNK_COUNT)
+        .await
+        .unwrap();
+    let delta_snapshot = conn
+        .snapshots_dal()
+        .get_snapshot_metadata(L1BatchNumber(10))
+        .await
+        .unwrap()
+        .expect("no delta snapshot");
+    assert_eq!(delta_snapshot.base_l1_batch_number, Some(L1BatchNumber(8)));
+    assert!(delta_snapshot.is_complete());
+
+    // The delta snapshot must contain only changes in L1 batches #9 and #10.
+    let mut expected_outputs = ExpectedOutputs::default();
+    for outputs in block_outputs.drain(9..=10) {
+        expected_outputs.extend(outputs);
+    }
+    let object_store = object_store_factory.create_store().await;
+    let SnapshotFactoryDependencies { factory_deps } =
+        object_store.get(L1BatchNumber(10)).await.unwrap();
+    let actual_deps: HashSet<_> = factory_deps.into_iter().collect();
+    assert_eq!(actual_deps, expected_outputs.deps);
+    assert_storage_logs(&*object_store, L1BatchNumber(10), &expected_outputs).await;
+
+    // The delta chain is at its maximum length, so the next snapshot must be full.
+    create_block(&mut rng, &mut conn, 12, vec![]).await;
+    SnapshotCreator::for_tests(object_store, pool.clone())
+        .run(config, MIN_CHUNK_COUNT)
+        .await
+        .unwrap();
+    let next_snapshot = conn
+        .snapshots_dal()
+        .get_snapshot_metadata(L1BatchNumber(11))
+        .await
+        .unwrap()
+        .expect("no snapshot");
+    assert_eq!(next_snapshot.base_l1_batch_number, None);
+}
+
+#[tokio::test]
+async fn finalizing_snapshot_manifest() {
+    let pool = ConnectionPool::test_pool().await;
+    let mut rng = thread_rng();
+    let object_store_factory = ObjectStoreFactory::mock();
+    let object_store = object_store_factory.create_store().await;
+    let mut conn = pool.access_storage().await.unwrap();
+    prepare_postgres(&mut rng, &mut conn, 10).await;
+    let signing_key = H256::repeat_byte(1);
+
+    SnapshotCreator::for_tests(object_store, pool.clone())
+        .with_manifest_signing_key(signing_key)
+        .run(TEST_CONFIG, MIN_CHUNK_COUNT)
+        .await
+        .unwrap();
+    let snapshot_l1_batch_number = L1BatchNumber(8);
+    let snapshot_metadata = conn
+        .snapshots_dal()
+        .get_snapshot_metadata(snapshot_l1_batch_number)
+        .await
+        .unwrap()
+        .expect("No snapshot metadata");
+    // The root hash of the snapshot L1 batch is not computed, so the manifest is postponed.
+    assert_eq!(snapshot_metadata.manifest_hash, None);
+
+    let object_store = object_store_factory.create_store().await;
+    let mut chunk_hashes = vec![];
+    for chunk_id in 0..MIN_CHUNK_COUNT {
+        let key = SnapshotStorageLogsStorageKey {
+            l1_batch_number: snapshot_l1_batch_number,
+            chunk_id,
+        };
+        let chunk: SnapshotStorageLogsChunk = object_store.get(key).await.unwrap();
+        chunk_hashes.push(chunk.hash());
+    }
+    let stored_chunk_hashes: Vec<_> = snapshot_metadata
+        .storage_logs_chunk_hashes
+        .into_iter()
+        .map(Option::unwrap)
+        .collect();
+    assert_eq!(stored_chunk_hashes, chunk_hashes);
+
+    let root_hash = H256::repeat_byte(0x23);
+    conn.blocks_dal()
+        .set_l1_batch_hash(snapshot_l1_batch_number, root_hash)
+        .await
+        .unwrap();
+    SnapshotCreator::for_tests(object_store, pool.clone())
+        .with_manifest_signing_key(signing_key)
+        .run(TEST_CONFIG, MIN_CHUNK_COUNT)
+        .await
+        .unwrap();
+
+    let snapshot_metadata = conn
+        .snapshots_dal()
+        .get_snapshot_metadata(snapshot_l1_batch_number)
+        .await
+        .unwrap()
+        .expect("No snapshot metadata");
+    let manifest = SnapshotManifest {
+        l1_batch_number: snapshot_l1_batch_number,
+        l1_batch_root_hash: root_hash,
+        base_manifest_hash: None,
+        storage_logs_chunk_hashes: chunk_hashes,
+        signature: snapshot_metadata.manifest_signature,
+    };
+    assert_eq!(snapshot_metadata.manifest_hash, Some(manifest.hash()));
+    let expected_signer = PackedEthSignature::address_from_private_key(&signing_key).unwrap();
+    assert_eq!(manifest.recover_signer().unwrap(), Some(expected_signer));
+}
+
+async fn assert_snapshot_removed(object_store: &dyn ObjectStore, snapshot: &SnapshotMetadata) {
+    let l1_batch_number = snapshot.l1_batch_number;
+    let err = object_store
+        .get::<SnapshotFactoryDependencies>(l1_batch_number)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ObjectStoreError::KeyNotFound(_)), "{err}");
+    for chunk_id in 0..snapshot.storage_logs_filepaths.len() as u64 {
+        let key = SnapshotStorageLogsStorageKey {
+            l1_batch_number,
+            chunk_id,
+        };
+        let err = object_store
+            .get::<SnapshotStorageLogsChunk>(key)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ObjectStoreError::KeyNotFound(_)), "{err}");
+    }
+}
+
+#[tokio::test]
+async fn pruning_old_snapshots() {
+    let pool = ConnectionPool::test_pool().await;
+    let mut rng = thread_rng();
+    let object_store_factory = ObjectStoreFactory::mock();
+    let mut conn = pool.access_storage().await.unwrap();
+    prepare_postgres(&mut rng, &mut conn, 10).await;
+
+    for new_block_numbers in [&[10, 11][..], &[12, 13], &[]] {
+        let object_store = object_store_factory.create_store().await;
+        SnapshotCreator::for_tests(object_store, pool.clone())
+            .run(TEST_CONFIG, MIN_CHUNK_COUNT)
+            .await
+            .unwrap();
+        for &block_number in new_block_numbers {
+            create_block(&mut rng, &mut conn, block_number, vec![]).await;
+        }
+    }
+    let snapshots = conn
+        .snapshots_dal()
+        .get_all_complete_snapshots()
+        .await
+        .unwrap();
+    let expected_snapshots = [12, 10, 8].map(L1BatchNumber);
+    assert_eq!(snapshots.snapshots_l1_batch_numbers, expected_snapshots);
+    let oldest_snapshot = conn
+        .snapshots_dal()
+        .get_snapshot_metadata(L1BatchNumber(8))
+        .await
+        .unwrap()
+        .expect("no snapshot");
+
+    let object_store = object_store_factory.create_store().await;
+    // Emulate a previous pruning attempt that has failed after removing some objects.
+    let key = SnapshotStorageLogsChunk::encode_key(SnapshotStorageLogsStorageKey {
+        l1_batch_number: L1BatchNumber(8),
+        chunk_id: 0,
+    });
+    object_store
+        .remove_raw(SnapshotStorageLogsChunk::BUCKET, &key)
+        .await
+        .unwrap();
+
+    let creator = SnapshotCreator::for_tests(object_store.clone(), pool.clone());
+    let coordinator = SnapshotsCoordinator::new(creator, TEST_CONFIG, MIN_CHUNK_COUNT).unwrap();
+    let removed_snapshots = coordinator.prune_snapshots(2).await.unwrap();
+    assert_eq!(removed_snapshots, [L1BatchNumber(8)]);
+
+    let snapshots = conn
+        .snapshots_dal()
+        .get_all_complete_snapshots()
+        .await
+        .unwrap();
+    assert_eq!(
+        snapshots.snapshots_l1_batch_numbers,
+        expected_snapshots[..2]
+    );
+    assert_snapshot_removed(&*object_store, &oldest_snapshot).await;
+    object_store
+        .get::<SnapshotFactoryDependencies>(L1BatchNumber(10))
+        .await
+        .unwrap();
+
+    // Repeated pruning is a no-op.
+    let removed_snapshots = coordinator.prune_snapshots(2).await.unwrap();
+    assert!(removed_snapshots.is_empty());
+}
+
+#[tokio::test]
+async fn pruning_snapshots_retains_delta_snapshot_bases() {
+    let pool = ConnectionPool::test_pool().await;
+    let mut rng = thread_rng();
+    let object_store_factory = ObjectStoreFactory::mock();
+    let mut conn = pool.access_storage().await.unwrap();
+    prepare_postgres(&mut rng, &mut conn, 10).await;
+
+    let config = SnapshotsCreatorConfig {
+        max_consecutive_delta_snapshots: 1,
+        ..TEST_CONFIG
+    };
+    for new_block_numbers in [&[10, 11][..], &[12], &[]] {
+        let object_store = object_store_factory.create_store().await;
+        SnapshotCreator::for_tests(object_store, pool.clone())
+            .run(config.clone(), MIN_CHUNK_COUNT)
+            .await
+            .unwrap();
+        for &block_number in new_block_numbers {
+            create_block(&mut rng, &mut conn, block_number, vec![]).await;
+        }
+    }
+    // Snapshot for L1 batch #10 is a delta based on #8; snapshot for #11 is full.
+    let snapshots = conn
+        .snapshots_dal()
+        .get_all_complete_snapshots()
+        .await
+        .unwrap();
+    assert_eq!(
+        snapshots.snapshots_l1_batch_numbers,
+        [11, 10, 8].map(L1BatchNumber)
+    );
+    let delta_snapshot = conn
+        .snapshots_dal()
+        .get_snapshot_metadata(L1BatchNumber(10))
+        .await
+        .unwrap()
+        .expect("no snapshot");
+    assert_eq!(delta_snapshot.base_l1_batch_number, Some(L1BatchNumber(8)));
+
+    let object_store = object_store_factory.create_store().await;
+    let creator = SnapshotCreator::for_tests(object_store.clone(), pool.clone());
+    let coordinator = SnapshotsCoordinator::new(creator, config, MIN_CHUNK_COUNT).unwrap();
+    // The base snapshot for #10 must be retained.
+    let removed_snapshots = coordinator.prune_snapshots(2).await.unwrap();
+    assert!(removed_snapshots.is_empty());
+
+    let removed_snapshots = coordinator.prune_snapshots(1).await.unwrap();
+    assert_eq!(removed_snapshots, [10, 8].map(L1BatchNumber));
+    let snapshots = conn
+        .snapshots_dal()
+        .get_all_complete_snapshots()
+        .await
+        .unwrap();
+    assert_eq!(snapshots.snapshots_l1_batch_numbers, [L1BatchNumber(11)]);
+    assert_snapshot_removed(&*object_store, &delta_snapshot).await;
+}
@@ -4,25 +4,28 @@ use anyhow::Context as _;
 use clap::Parser;
 use zksync_config::{
     configs::{
-        api::{HealthCheckConfig, MerkleTreeApiConfig, Web3JsonRpcConfig},
+        api::{ExplorerApiConfig, HealthCheckConfig, MerkleTreeApiConfig, Web3JsonRpcConfig},
         chain::{
             CircuitBreakerConfig, MempoolConfig, NetworkConfig, OperationsManagerConfig,
             StateKeeperConfig,
         },
         fri_prover_group::FriProverGroupConfig,
         house_keeper::HouseKeeperConfig,
-        FriProofCompressorConfig, FriProverConfig, FriWitnessGeneratorConfig, PrometheusConfig,
-        ProofDataHandlerConfig, WitnessGeneratorConfig,
+        DADispatcherConfig, FriProofCompressorConfig, FriProverConfig, FriWitnessGeneratorConfig,
+        PrometheusConfig, ProofDataHandlerConfig, TokenFetcherConfig, TxEventStreamConfig,
+        WitnessGeneratorConfig,
     },
     ApiConfig, ContractsConfig, DBConfig, ETHClientConfig, ETHSenderConfig, ETHWatchConfig,
     GasAdjusterConfig, ObjectStoreConfig, PostgresConfig,
 };
 use zksync_core::{
     genesis_init, initialize_components, is_genesis_needed, setup_sigint_handler,
-    temp_config_store::TempConfigStore, Component, Components,
+    temp_config_store::TempConfigStore, validate_chain_config, Component, Components,
 };
+use zksync_dal::ConnectionPool;
 use zksync_env_config::FromEnv;
 use zksync_storage::RocksDB;
+use zksync_types::L1ChainId;
 use zksync_utils::wait_for_tasks::wait_for_tasks;
 
 #[cfg(not(target_env = "msvc"))]
@@ -72,6 +75,8 @@ async fn main() -> anyhow::Result<()> {
     let sentry_url = vlog::sentry_url_from_env();
     #[allow(deprecated)] // TODO (QIT-21): Use centralized configuration approach.
     let environment = vlog::environment_from_env();
+    #[allow(deprecated)] // TODO (QIT-21): Use centralized configuration approach.
+    let opentelemetry = vlog::opentelemetry_from_env("zksync_server");
 
     let mut builder = vlog::ObservabilityBuilder::new().with_log_format(log_format);
     if let Some(sentry_url) = &sentry_url {
@@ -80,6 +85,9 @@ async fn main() -> anyhow::Result<()> {
             .expect("Invalid Sentry URL")
             .with_sentry_environment(environment);
     }
+    if let Some(opentelemetry) = opentelemetry.clone() {
+        builder = builder.with_opentelemetry(opentelemetry);
+    }
     let _guard = builder.build();
 
     // Report whether sentry is running after the logging subsystem was initialized.
@@ -88,6 +96,13 @@ async fn main() -> anyhow::Result<()> {
     } else {
         tracing::info!("No sentry URL was provided");
     }
+    if let Some(opentelemetry) = opentelemetry {
+        tracing::info!(
+            "OpenTelemetry traces are exported to {} with sampling ratio {}",
+            opentelemetry.endpoint,
+            opentelemetry.sampling_ratio
+        );
+    }
 
     // TODO (QIT-22): Only deserialize configs on demand.
     // Right now, we are trying to deserialize all the configs that may be needed by `zksync_core`.
@@ -119,6 +134,10 @@ async fn main() -> anyhow::Result<()> {
         eth_watch_config: ETHWatchConfig::from_env().ok(),
         gas_adjuster_config: GasAdjusterConfig::from_env().ok(),
         object_store_config: ObjectStoreConfig::from_env().ok(),
+        da_dispatcher_config: DADispatcherConfig::from_env().ok(),
+        tx_event_stream_config: TxEventStreamConfig::from_env().ok(),
+        token_fetcher_config: TokenFetcherConfig::from_env().ok(),
+        explorer_api_config: ExplorerApiConfig::from_env().ok(),
     };
 
     let postgres_config = configs.postgres_config.clone().context("PostgresConfig")?;
@@ -142,6 +161,27 @@ async fn main() -> anyhow::Result<()> {
         }
     }
 
+    if let (Some(network), Some(contracts), Some(eth_client)) = (
+        &configs.network_config,
+        &configs.contracts_config,
+        &configs.eth_client_config,
+    ) {
+        let pool = ConnectionPool::singleton(postgres_config.master_url()?)
+            .build()
+            .await
+            .context("failed to build connection pool")?;
+        validate_chain_config(
+            &pool,
+            network.zksync_network_id,
+            L1ChainId(eth_client.chain_id),
+            contracts.diamond_proxy_addr,
+            &eth_client.web3_url,
+            network.chain_registry_url.as_deref(),
+        )
+        .await
+        .context("chain config validation failed")?;
+    }
+
     let components = if opt.rebuild_tree {
         vec![Component::Tree]
     } else {
@@ -0,0 +1,14 @@
+//! Basic types related to L1 batch commitments.
+
+use serde::{Deserialize, Serialize};
+
+/// Mode in which L1 batches are committed on L1, which determines whether the batch pubdata
+/// is published on L1.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum L1BatchCommitmentMode {
+    /// Pubdata is published on L1, either in the commit transaction calldata or in blobs.
+    #[default]
+    Rollup,
+    /// Pubdata is not published on L1; only its hash is committed.
+    Validium,
+}
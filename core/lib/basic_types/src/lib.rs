@@ -19,6 +19,7 @@ pub use web3::{
 #[macro_use]
 mod macros;
 pub mod basic_fri_types;
+pub mod commitment;
 pub mod network;
 
 /// Account place in the global state tree is uniquely identified by its address.
@@ -40,6 +40,10 @@ pub struct Web3JsonRpcConfig {
     pub pubsub_polling_interval: Option<u64>,
     /// Tx nonce: how far ahead from the committed nonce can it be.
     pub max_nonce_ahead: u32,
+    /// Minimum percentage by which a transaction must raise `max_fee_per_gas` and `max_priority_fee_per_gas`
+    /// to replace a pending transaction with the same sender and nonce (Ethereum clients use 10%).
+    /// If not set, pending transactions can be replaced regardless of their fees.
+    pub tx_replacement_price_bump_percent: Option<u32>,
     /// The multiplier to use when suggesting gas price. Should be higher than one,
     /// otherwise if the L1 prices soar, the suggested gas price won't be sufficient to be included in block
     pub gas_price_scale_factor: f64,
@@ -67,18 +71,60 @@ pub struct Web3JsonRpcConfig {
     /// Latest values cache size in MiBs. The default value is 128 MiB. If set to 0, the latest
     /// values cache will be disabled.
     pub latest_values_cache_size_mb: Option<usize>,
+    /// Size of the cache for immutable JSON-RPC responses (e.g., finalized blocks or transaction receipts)
+    /// shared among HTTP and WS servers, in MiBs. The cache is disabled by default (or if set to 0).
+    pub response_cache_size_mb: Option<usize>,
+    /// Time-to-live for entries in the response cache, in seconds. The default value is 300 seconds.
+    pub response_cache_ttl_sec: Option<u64>,
     /// Limit for fee history block range.
     pub fee_history_limit: Option<u64>,
+    /// Maximum block range for `eth_getLogs` and event filters. If not set, the block range is not limited.
+    pub get_logs_max_block_range: Option<u32>,
+    /// Maximum number of logs returned by a single `eth_getLogs` call. If not set, `req_entities_limit` is used.
+    pub get_logs_max_results: Option<u32>,
+    /// Number of latest miniblocks for which historical state queries (`eth_call`, `eth_getBalance`
+    /// and `eth_getStorageAt`) are served. If not set, the server runs in archival mode, i.e., serves state
+    /// for all miniblocks stored in Postgres.
+    pub historical_state_depth: Option<u32>,
     /// Maximum number of requests in a single batch JSON RPC request. Default is 500.
     pub max_batch_request_size: Option<usize>,
+    /// Maximum number of calls from a single batch request executed concurrently by the HTTP server.
+    /// If not set or set to 1, calls in a batch are executed sequentially.
+    pub batch_request_concurrency: Option<usize>,
     /// Maximum response body size in MiBs. Default is 10 MiB.
     pub max_response_body_size_mb: Option<usize>,
     /// Maximum number of requests per minute for the WebSocket server.
     /// The value is per active connection.
-    /// Note: For HTTP, rate limiting is expected to be configured on the infra level.
+    /// Note: Per-client limits for both HTTP and WebSocket servers are configured via `*_requests_per_minute_limit` options.
     pub websocket_requests_per_minute_limit: Option<NonZeroU32>,
+    /// Maximum number of active subscriptions per WebSocket connection. Default is 1024.
+    pub websocket_subscriptions_per_connection_limit: Option<u32>,
+    /// Timeout (in seconds) after which unresponsive WebSocket connections are closed. The server periodically pings
+    /// connections to detect unresponsive ones. If not set, connections are never closed because of inactivity.
+    pub websocket_idle_timeout: Option<u64>,
+    /// Timeout (in ms) for sending a notification to a subscriber. Slow subscribers that don't keep up
+    /// with notifications for this long are dropped instead of buffering notifications for them. Default is 1000 ms.
+    pub pubsub_send_timeout_ms: Option<u64>,
+    /// Maximum number of read calls (i.e., all calls except for `eth_sendRawTransaction` and `debug_*` methods)
+    /// per minute for a single client. Applies to both HTTP and WebSocket servers. If not set, read calls are not limited.
+    pub read_requests_per_minute_limit: Option<NonZeroU32>,
+    /// Maximum number of `eth_sendRawTransaction` calls per minute for a single client. If not set, transaction submission
+    /// is not limited.
+    pub send_tx_requests_per_minute_limit: Option<NonZeroU32>,
+    /// Maximum number of `debug_*` calls per minute for a single client. If not set, debug calls are not limited.
+    pub debug_requests_per_minute_limit: Option<NonZeroU32>,
+    /// Name of the HTTP header carrying the client API key. If set and present in a request, per-client rate limits
+    /// are keyed by the header value; otherwise, clients are identified by their IP address.
+    pub rate_limit_api_key_header: Option<String>,
+    /// Number of trusted reverse proxies in front of the server, each appending an entry to the `X-Forwarded-For`
+    /// header. The client IP address used to key rate limits is taken from the entry appended by the outermost
+    /// trusted proxy. If not set or 0, the header is ignored and the peer address of the connection is used.
+    pub rate_limit_trusted_proxy_count: Option<usize>,
     /// Tree API url, currently used to proxy `getProof` calls to the tree
     pub tree_api_url: Option<String>,
+    /// Whether to expose the operator-only `admin` namespace on the HTTP server. Disabled by default.
+    /// Transaction cancellation is only supported if the state keeper runs in the same process as the server.
+    pub admin_namespace_enabled: Option<bool>,
 }
 
 impl Web3JsonRpcConfig {
@@ -96,6 +142,7 @@ impl Web3JsonRpcConfig {
             subscriptions_limit: Some(10000),
             pubsub_polling_interval: Some(200),
             max_nonce_ahead: 50,
+            tx_replacement_price_bump_percent: None,
             gas_price_scale_factor: 1.2,
             request_timeout: Default::default(),
             account_pks: Default::default(),
@@ -107,11 +154,26 @@ impl Web3JsonRpcConfig {
             factory_deps_cache_size_mb: Default::default(),
             initial_writes_cache_size_mb: Default::default(),
             latest_values_cache_size_mb: Default::default(),
+            response_cache_size_mb: None,
+            response_cache_ttl_sec: None,
             fee_history_limit: Default::default(),
+            get_logs_max_block_range: None,
+            get_logs_max_results: None,
+            historical_state_depth: None,
             max_batch_request_size: Default::default(),
+            batch_request_concurrency: None,
             max_response_body_size_mb: Default::default(),
             websocket_requests_per_minute_limit: Default::default(),
+            websocket_subscriptions_per_connection_limit: None,
+            websocket_idle_timeout: None,
+            pubsub_send_timeout_ms: None,
+            read_requests_per_minute_limit: None,
+            send_tx_requests_per_minute_limit: None,
+            debug_requests_per_minute_limit: None,
+            rate_limit_api_key_header: None,
+            rate_limit_trusted_proxy_count: None,
             tree_api_url: None,
+            admin_namespace_enabled: None,
         }
     }
 
@@ -127,6 +189,11 @@ impl Web3JsonRpcConfig {
         self.req_entities_limit.unwrap_or_else(|| 2u32.pow(10)) as usize
     }
 
+    pub fn get_logs_max_results(&self) -> usize {
+        self.get_logs_max_results
+            .map_or_else(|| self.req_entities_limit(), |limit| limit as usize)
+    }
+
     pub fn filters_limit(&self) -> usize {
         self.filters_limit.unwrap_or(10000) as usize
     }
@@ -169,6 +236,15 @@ impl Web3JsonRpcConfig {
         self.latest_values_cache_size_mb.unwrap_or(128) * super::BYTES_IN_MEGABYTE
     }
 
+    /// Returns the size of the response cache in bytes.
+    pub fn response_cache_size(&self) -> usize {
+        self.response_cache_size_mb.unwrap_or(0) * super::BYTES_IN_MEGABYTE
+    }
+
+    pub fn response_cache_ttl(&self) -> Duration {
+        Duration::from_secs(self.response_cache_ttl_sec.unwrap_or(300))
+    }
+
     pub fn fee_history_limit(&self) -> u64 {
         self.fee_history_limit.unwrap_or(1024)
     }
@@ -178,6 +254,10 @@ impl Web3JsonRpcConfig {
         self.max_batch_request_size.unwrap_or(500)
     }
 
+    pub fn batch_request_concurrency(&self) -> usize {
+        self.batch_request_concurrency.unwrap_or(1)
+    }
+
     pub fn max_response_body_size(&self) -> usize {
         self.max_response_body_size_mb.unwrap_or(10) * super::BYTES_IN_MEGABYTE
     }
@@ -188,21 +268,46 @@ impl Web3JsonRpcConfig {
             .unwrap_or(NonZeroU32::new(6000).unwrap())
     }
 
+    pub fn websocket_subscriptions_per_connection_limit(&self) -> u32 {
+        self.websocket_subscriptions_per_connection_limit
+            .unwrap_or(1024)
+    }
+
+    pub fn websocket_idle_timeout(&self) -> Option<Duration> {
+        self.websocket_idle_timeout.map(Duration::from_secs)
+    }
+
+    pub fn pubsub_send_timeout(&self) -> Duration {
+        Duration::from_millis(self.pubsub_send_timeout_ms.unwrap_or(1000))
+    }
+
     pub fn tree_api_url(&self) -> Option<String> {
         self.tree_api_url.clone()
     }
+
+    pub fn admin_namespace_enabled(&self) -> bool {
+        self.admin_namespace_enabled.unwrap_or(false)
+    }
 }
 
 #[derive(Debug, Deserialize, Clone, PartialEq)]
 pub struct HealthCheckConfig {
     /// Port to which the REST server is listening.
     pub port: u16,
+    /// Port of the admin server allowing to change log directives at runtime. The server only listens
+    /// on the loopback interface. If not set, the admin server is not started.
+    pub admin_port: Option<u16>,
 }
 
 impl HealthCheckConfig {
     pub fn bind_addr(&self) -> SocketAddr {
         SocketAddr::new("0.0.0.0".parse().unwrap(), self.port)
     }
+
+    pub fn admin_bind_addr(&self) -> Option<SocketAddr> {
+        self.admin_port
+            .map(|port| SocketAddr::new("127.0.0.1".parse().unwrap(), port))
+    }
 }
 
 #[derive(Debug, Deserialize, Clone, PartialEq)]
@@ -232,3 +337,40 @@ impl MerkleTreeApiConfig {
         3_072
     }
 }
+
+/// Configuration for the etherscan-compatible explorer API.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ExplorerApiConfig {
+    /// Port to bind the explorer API server to.
+    #[serde(default = "ExplorerApiConfig::default_port")]
+    pub port: u16,
+    /// Maximum number of records that can be paginated through, i.e. the maximum value of `page * offset`
+    /// in list requests. Mirrors the result window limit of etherscan.
+    #[serde(default = "ExplorerApiConfig::default_max_result_window")]
+    pub max_result_window: usize,
+    /// Statement timeout for DB queries performed by the explorer API (in seconds).
+    #[serde(default = "ExplorerApiConfig::default_statement_timeout_sec")]
+    pub statement_timeout_sec: u64,
+}
+
+impl ExplorerApiConfig {
+    const fn default_port() -> u16 {
+        3_073
+    }
+
+    const fn default_max_result_window() -> usize {
+        10_000
+    }
+
+    const fn default_statement_timeout_sec() -> u64 {
+        10
+    }
+
+    pub fn statement_timeout(&self) -> Duration {
+        Duration::from_secs(self.statement_timeout_sec)
+    }
+
+    pub fn bind_addr(&self) -> SocketAddr {
+        SocketAddr::new("0.0.0.0".parse().unwrap(), self.port)
+    }
+}
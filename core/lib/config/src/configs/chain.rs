@@ -1,7 +1,7 @@
 use std::{str::FromStr, time::Duration};
 
 use serde::Deserialize;
-use zksync_basic_types::{network::Network, Address, L2ChainId};
+use zksync_basic_types::{commitment::L1BatchCommitmentMode, network::Network, Address, L2ChainId};
 
 #[derive(Debug, Deserialize, Clone, PartialEq)]
 pub struct NetworkConfig {
@@ -13,6 +13,10 @@ pub struct NetworkConfig {
     /// ID of current zkSync network treated as ETH network ID.
     /// Used to distinguish zkSync from other Web3-capable networks.
     pub zksync_network_id: L2ChainId,
+    /// URL of a remote chain registry (a JSON array of chain entries) extending the registry embedded
+    /// into the node. Used to validate chain-related configuration on startup.
+    #[serde(default)]
+    pub chain_registry_url: Option<String>,
 }
 
 impl NetworkConfig {
@@ -22,6 +26,7 @@ impl NetworkConfig {
             network: Network::Localhost,
             zksync_network: "localhost".into(),
             zksync_network_id: L2ChainId::default(),
+            chain_registry_url: None,
         }
     }
 }
@@ -39,6 +44,14 @@ pub struct StateKeeperConfig {
     /// sealing will block until some of the miniblocks from the queue are processed.
     /// 0 means that sealing is synchronous; this is mostly useful for performance comparison, testing etc.
     pub miniblock_seal_queue_capacity: usize,
+    /// Maximum execution gas used by transactions in a miniblock. A transaction that would exceed this limit
+    /// is moved to the next miniblock, unless it is the only transaction in the miniblock. If not specified,
+    /// miniblocks are not limited by gas.
+    pub miniblock_max_gas: Option<u64>,
+    /// Maximum pubdata (in bytes) produced by transactions in a miniblock. A transaction that would exceed this limit
+    /// is moved to the next miniblock, unless it is the only transaction in the miniblock. If not specified,
+    /// miniblocks are not limited by pubdata.
+    pub miniblock_max_pubdata_bytes: Option<usize>,
 
     /// The max number of gas to spend on an L1 tx before its batch should be sealed by the gas sealer.
     pub max_single_tx_gas: u32,
@@ -79,6 +92,59 @@ pub struct StateKeeperConfig {
 
     /// Number of keys that is processed by enum_index migration in State Keeper each L1 batch.
     pub enum_index_migration_chunk_size: Option<usize>,
+
+    /// Names of the conditional seal criteria used by the sequencer (e.g., `slots` or `gas`).
+    /// If not specified, all built-in criteria are used. Criteria enforcing bootloader, circuit and pubdata limits
+    /// (`slots`, `pub_data_size`, `circuits` and `tx_encoding_size`) are mandatory and cannot be omitted.
+    pub seal_criteria: Option<Vec<String>>,
+
+    /// Whether protective reads should be persisted asynchronously rather than during L1 batch sealing.
+    /// If set, protective reads are computed by a separate component re-executing sealed L1 batches,
+    /// and the Merkle tree waits for them before processing the batch.
+    #[serde(default)]
+    pub async_protective_reads: bool,
+
+    /// Whether the state keeper should use `COPY FROM STDIN` (via temporary staging tables) instead of
+    /// multi-row `INSERT` statements to persist storage updates, factory deps and initial writes.
+    /// Reduces DB time spent on sealing large miniblocks and L1 batches.
+    #[serde(default)]
+    pub use_copy_for_bulk_inserts: bool,
+
+    /// Whether the state keeper should wait until prerequisites of a scheduled protocol upgrade are met
+    /// (e.g., the new base system contracts are available) before switching to the new protocol version.
+    #[serde(default)]
+    pub upgrade_orchestration_enabled: bool,
+    /// Whether a new protocol version additionally requires the prover to support its verification keys.
+    /// Only used if `upgrade_orchestration_enabled` is set.
+    #[serde(default)]
+    pub upgrade_requires_prover_support: bool,
+
+    /// Version of the batch fee model. With the `V2` model, the pubdata price is independent from the L1 gas price,
+    /// and the fair L2 gas price includes the overhead for sealing L1 batches.
+    #[serde(default)]
+    pub fee_model_version: FeeModelVersion,
+    /// Part of the batch overhead covered by L2 gas (from 0 to 1). Only used by the `V2` fee model.
+    #[serde(default)]
+    pub compute_overhead_part: f64,
+    /// Part of the batch overhead covered by pubdata (from 0 to 1). Only used by the `V2` fee model.
+    #[serde(default)]
+    pub pubdata_overhead_part: f64,
+    /// Constant L1 gas overhead of an L1 batch (e.g., for its verification). Only used by the `V2` fee model.
+    #[serde(default)]
+    pub batch_overhead_l1_gas: u64,
+    /// Maximum amount of gas that can be used by an L1 batch. Only used by the `V2` fee model.
+    #[serde(default)]
+    pub max_gas_per_batch: u64,
+    /// Maximum amount of pubdata that can be used by an L1 batch. Only used by the `V2` fee model.
+    #[serde(default)]
+    pub max_pubdata_per_batch: u64,
+    /// Path to a JSON file with the fee pricing curve (L1 price caps, congestion multipliers and the minimal
+    /// L2 gas price schedule). The file is periodically reloaded, so the curve can be changed at runtime.
+    pub fee_pricing_curve_path: Option<String>,
+    /// Mode in which L1 batches are committed on L1. In the validium mode, batch pubdata is not published on L1,
+    /// and is not charged for.
+    #[serde(default)]
+    pub l1_batch_commitment_mode: L1BatchCommitmentMode,
 }
 
 impl StateKeeperConfig {
@@ -90,6 +156,8 @@ impl StateKeeperConfig {
             block_commit_deadline_ms: 2500,
             miniblock_commit_deadline_ms: 1000,
             miniblock_seal_queue_capacity: 10,
+            miniblock_max_gas: None,
+            miniblock_max_pubdata_bytes: None,
             max_single_tx_gas: 6000000,
             max_allowed_l2_tx_gas_limit: 4000000000,
             reject_tx_at_geometry_percentage: 0.95,
@@ -107,6 +175,19 @@ impl StateKeeperConfig {
             virtual_blocks_per_miniblock: 1,
             upload_witness_inputs_to_gcs: false,
             enum_index_migration_chunk_size: None,
+            seal_criteria: None,
+            async_protective_reads: false,
+            use_copy_for_bulk_inserts: false,
+            upgrade_orchestration_enabled: false,
+            upgrade_requires_prover_support: false,
+            fee_model_version: FeeModelVersion::V1,
+            compute_overhead_part: 0.0,
+            pubdata_overhead_part: 1.0,
+            batch_overhead_l1_gas: 800_000,
+            max_gas_per_batch: 200_000_000,
+            max_pubdata_per_batch: 100_000,
+            fee_pricing_curve_path: None,
+            l1_batch_commitment_mode: L1BatchCommitmentMode::Rollup,
         }
     }
 
@@ -145,6 +226,27 @@ impl CircuitBreakerConfig {
     }
 }
 
+/// Version of the batch fee model used by the main node.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+pub enum FeeModelVersion {
+    /// Pubdata price is pegged to the L1 gas price.
+    #[default]
+    V1,
+    /// Pubdata price is independent from the L1 gas price.
+    V2,
+}
+
+/// Ordering of L2 transactions from different accounts in the mempool.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MempoolOrdering {
+    /// Transactions are ordered by the time they were received.
+    #[default]
+    ReceivedAt,
+    /// Transactions with a higher effective priority fee are executed first.
+    PriorityFee,
+}
+
 #[derive(Debug, Deserialize, Clone, PartialEq)]
 pub struct MempoolConfig {
     pub sync_interval_ms: u64,
@@ -153,6 +255,10 @@ pub struct MempoolConfig {
     pub stuck_tx_timeout: u64,
     pub remove_stuck_txs: bool,
     pub delay_interval: u64,
+    /// Ordering of L2 transactions in the mempool. If not set, transactions are ordered by the time they were received.
+    pub ordering: Option<MempoolOrdering>,
+    /// Accounts which transactions are executed before transactions of all other accounts.
+    pub boosted_accounts: Option<Vec<Address>>,
 }
 
 impl MempoolConfig {
@@ -0,0 +1,42 @@
+use std::time::Duration;
+
+use serde::Deserialize;
+
+/// Configuration for the data availability dispatcher, which publishes pubdata of L1 batches
+/// to an external data availability layer.
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+pub struct DADispatcherConfig {
+    /// URL of the data availability layer API.
+    pub api_url: String,
+    /// Interval between polling the database for new L1 batches to dispatch and for inclusion proofs (in ms).
+    #[serde(default = "DADispatcherConfig::default_polling_interval_ms")]
+    pub polling_interval_ms: u64,
+    /// Maximum number of L1 batches dispatched in a single iteration.
+    #[serde(default = "DADispatcherConfig::default_max_rows_to_dispatch")]
+    pub max_rows_to_dispatch: u32,
+    /// Timeout for requests to the data availability layer API (in ms).
+    #[serde(default = "DADispatcherConfig::default_request_timeout_ms")]
+    pub request_timeout_ms: u64,
+}
+
+impl DADispatcherConfig {
+    const fn default_polling_interval_ms() -> u64 {
+        5_000
+    }
+
+    const fn default_max_rows_to_dispatch() -> u32 {
+        100
+    }
+
+    const fn default_request_timeout_ms() -> u64 {
+        30_000
+    }
+
+    pub fn polling_interval(&self) -> Duration {
+        Duration::from_millis(self.polling_interval_ms)
+    }
+
+    pub fn request_timeout(&self) -> Duration {
+        Duration::from_millis(self.request_timeout_ms)
+    }
+}
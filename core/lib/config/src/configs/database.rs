@@ -44,6 +44,34 @@ pub struct MerkleTreeConfig {
     /// Maximum number of L1 batches to be processed by the Merkle tree at a time.
     #[serde(default = "MerkleTreeConfig::default_max_l1_batches_per_iter")]
     pub max_l1_batches_per_iter: usize,
+    /// Number of threads in a dedicated thread pool used to compute node hashes. Subtrees of the Merkle tree
+    /// are hashed in parallel, so a larger pool can reduce tree update latency for large L1 batches.
+    /// If not specified, the global `rayon` thread pool will be used.
+    #[serde(default)]
+    pub hashing_thread_count: Option<usize>,
+    /// Number of past tree versions (i.e., L1 batches) to keep when pruning the Merkle tree. If not specified,
+    /// the tree is not pruned. Versions for L1 batches not yet executed on L1 are never pruned regardless
+    /// of this value, so that the tree can be reverted if necessary.
+    #[serde(default)]
+    pub pruning_past_versions_to_keep: Option<u64>,
+    /// If set, an empty Merkle tree will be recovered from the storage logs in Postgres rather than built
+    /// by processing all L1 batches starting from genesis. This can significantly speed up restoring a lost tree.
+    #[serde(default)]
+    pub recover_from_storage_logs: bool,
+    /// L1 batch to recover the Merkle tree to if `recover_from_storage_logs` is set. The L1 batch must have
+    /// metadata in Postgres. If not specified, the latest L1 batch with metadata will be used.
+    #[serde(default)]
+    pub storage_logs_recovery_l1_batch: Option<u32>,
+    /// If set, Merkle proofs are served by a read-only follower tree instead of the main tree, so that heavy
+    /// proof traffic doesn't delay computing L1 batch metadata. The follower lags behind the main tree
+    /// by the specified number of L1 batches.
+    #[serde(default)]
+    pub follower_version_lag: Option<u64>,
+    /// If set, a background task periodically verifies consistency of the latest tree version in RocksDB
+    /// and compares its root hash to the stored L1 batch metadata. The check traverses the entire tree,
+    /// so it is I/O-intensive.
+    #[serde(default)]
+    pub consistency_check_interval_sec: Option<u64>,
 }
 
 impl Default for MerkleTreeConfig {
@@ -56,6 +84,12 @@ impl Default for MerkleTreeConfig {
             memtable_capacity_mb: Self::default_memtable_capacity_mb(),
             stalled_writes_timeout_sec: Self::default_stalled_writes_timeout_sec(),
             max_l1_batches_per_iter: Self::default_max_l1_batches_per_iter(),
+            hashing_thread_count: None,
+            pruning_past_versions_to_keep: None,
+            recover_from_storage_logs: false,
+            storage_logs_recovery_l1_batch: None,
+            follower_version_lag: None,
+            consistency_check_interval_sec: None,
         }
     }
 }
@@ -99,6 +133,11 @@ impl MerkleTreeConfig {
     pub fn stalled_writes_timeout(&self) -> Duration {
         Duration::from_secs(self.stalled_writes_timeout_sec)
     }
+
+    /// Returns the interval between tree consistency checks, or `None` if the checks are disabled.
+    pub fn consistency_check_interval(&self) -> Option<Duration> {
+        self.consistency_check_interval_sec.map(Duration::from_secs)
+    }
 }
 
 /// Database configuration.
@@ -112,12 +151,76 @@ pub struct DBConfig {
     // ^ Filled in separately in `Self::from_env()`. We cannot use `serde(flatten)` because it
     // doesn't work with 'envy`.
     pub merkle_tree: MerkleTreeConfig,
+    /// Interval between backups of RocksDB instances (the Merkle tree and the state keeper cache)
+    /// to the object store. If set, the instances are also restored from the latest backup on startup
+    /// if their local data is missing or corrupted. If not set, backups are disabled.
+    #[serde(default)]
+    pub rocksdb_backup_interval_sec: Option<u64>,
+    /// Number of latest L1 batches whose touched storage keys are pre-loaded from the state keeper cache
+    /// on startup, which reduces the latency spike after a restart. If not set, the cache is not warmed up.
+    #[serde(default)]
+    pub state_keeper_cache_warm_up_l1_batches: Option<u32>,
+    /// Minimum interval between full compactions of the state keeper cache. Compaction is incremental:
+    /// a single slice of keys is compacted each time an L1 batch is started. If not set, the cache
+    /// is not compacted manually.
+    #[serde(default)]
+    pub state_keeper_cache_compaction_interval_sec: Option<u64>,
+    /// Start of the off-peak window (UTC hour, 0..=23) during which the state keeper cache is compacted.
+    /// The window is only applied if both the start and end hours are set; it may wrap around midnight.
+    #[serde(default)]
+    pub state_keeper_cache_compaction_start_hour: Option<u32>,
+    /// End (exclusive) of the off-peak window (UTC hour, 0..=23) during which the state keeper cache is compacted.
+    #[serde(default)]
+    pub state_keeper_cache_compaction_end_hour: Option<u32>,
+    /// Size of the in-memory cache of storage values shared among L1 batches executed by the state keeper, in MiBs.
+    /// The cache is kept warm across L1 batches, so that hot storage slots are not re-read from the state keeper cache.
+    /// If not set, the in-memory cache is disabled.
+    #[serde(default)]
+    pub state_keeper_overlay_cache_size_mb: Option<usize>,
 }
 
 impl DBConfig {
     fn default_state_keeper_db_path() -> String {
         "./db/state_keeper".to_owned()
     }
+
+    /// Returns the interval between RocksDB backups, or `None` if backups are disabled.
+    pub fn rocksdb_backup_interval(&self) -> Option<Duration> {
+        self.rocksdb_backup_interval_sec.map(Duration::from_secs)
+    }
+
+    /// Returns the minimum interval between state keeper cache compactions, or `None` if manual compaction
+    /// is disabled.
+    pub fn state_keeper_cache_compaction_interval(&self) -> Option<Duration> {
+        self.state_keeper_cache_compaction_interval_sec
+            .map(Duration::from_secs)
+    }
+
+    /// Returns the off-peak window for state keeper cache compaction as `(start_hour, end_hour)` in UTC.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if either of the hours is out of the `0..=23` range.
+    pub fn state_keeper_cache_compaction_window(&self) -> anyhow::Result<Option<(u32, u32)>> {
+        let (Some(start_hour), Some(end_hour)) = (
+            self.state_keeper_cache_compaction_start_hour,
+            self.state_keeper_cache_compaction_end_hour,
+        ) else {
+            return Ok(None);
+        };
+        anyhow::ensure!(
+            start_hour <= 23 && end_hour <= 23,
+            "Invalid state keeper cache compaction window {start_hour}..{end_hour}: hours must be in 0..=23"
+        );
+        Ok(Some((start_hour, end_hour)))
+    }
+
+    /// Returns the size of the in-memory state keeper cache of storage values in bytes, or `None`
+    /// if the cache is disabled.
+    pub fn state_keeper_overlay_cache_size(&self) -> Option<usize> {
+        self.state_keeper_overlay_cache_size_mb
+            .map(|size_mb| size_mb * super::BYTES_IN_MEGABYTE)
+    }
 }
 
 /// Collection of different database URLs and general PostgreSQL options.
@@ -136,6 +239,14 @@ pub struct PostgresConfig {
     /// Statement timeout in seconds for Postgres connections. Applies only to the replica
     /// connection pool used by the API servers.
     pub statement_timeout_sec: Option<u64>,
+    /// Maximum replication lag of the replica database in seconds. If set, API queries that must observe
+    /// recently submitted transactions (e.g., nonces and transaction receipts) fall back to the master database
+    /// while the replica lag exceeds this threshold; all other queries always use the replica.
+    /// If not set, API servers always read from the replica.
+    pub max_replication_lag_sec: Option<u64>,
+    /// Threshold in milliseconds for slow DB queries and connection acquisitions. Slow queries are logged
+    /// together with their arguments. If not set, the default threshold (100ms) is used.
+    pub slow_query_threshold_ms: Option<u64>,
 }
 
 impl PostgresConfig {
@@ -169,4 +280,14 @@ impl PostgresConfig {
     pub fn statement_timeout(&self) -> Option<Duration> {
         self.statement_timeout_sec.map(Duration::from_secs)
     }
+
+    /// Returns the maximum tolerated replication lag for read-after-write API server queries.
+    pub fn max_replication_lag(&self) -> Option<Duration> {
+        self.max_replication_lag_sec.map(Duration::from_secs)
+    }
+
+    /// Returns the threshold for slow DB queries.
+    pub fn slow_query_threshold(&self) -> Option<Duration> {
+        self.slow_query_threshold_ms.map(Duration::from_millis)
+    }
 }
@@ -1,7 +1,7 @@
 use std::time::Duration;
 
 use serde::Deserialize;
-use zksync_basic_types::H256;
+use zksync_basic_types::{Address, H256};
 
 /// Configuration for the Ethereum sender crate.
 #[derive(Debug, Deserialize, Clone, PartialEq)]
@@ -35,6 +35,15 @@ impl ETHSenderConfig {
                 l1_batch_min_age_before_execute_seconds: None,
                 max_acceptable_priority_fee_in_gwei: 100000000000,
                 proof_loading_mode: ProofLoadingMode::OldProofFromDb,
+                pubdata_sending_mode: PubdataSendingMode::Calldata,
+                blob_base_fee_fallback_threshold_gwei: None,
+                retired_operator_addresses: vec![],
+                max_fee_per_gas_cap_gwei: None,
+                resubmission_fee_bump_percent: None,
+                resubmission_escalation_curve: FeeEscalationCurve::Constant,
+                resubmission_escalation_rate_percent: 0,
+                commit_dry_run_enabled: false,
+                proof_verification_key_path: None,
             },
             gas_adjuster: GasAdjusterConfig {
                 default_priority_fee_per_gas: 1000000000,
@@ -45,6 +54,8 @@ impl ETHSenderConfig {
                 internal_enforced_l1_gas_price: None,
                 poll_period: 5,
                 max_l1_gas_price: None,
+                fee_oracle_urls: vec![],
+                fee_outlier_threshold_percent: None,
             },
         }
     }
@@ -63,6 +74,30 @@ pub enum ProofLoadingMode {
     FriProofFromGcs,
 }
 
+/// How pubdata for committed L1 batches is published on L1.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Default)]
+pub enum PubdataSendingMode {
+    /// Pubdata is published as a part of the commit transaction calldata.
+    #[default]
+    Calldata,
+    /// Pubdata is published in blobs of EIP-4844 commit transactions. Falls back to calldata for L1 batches
+    /// with protocol versions not supporting blobs.
+    Blobs,
+}
+
+/// Curve used to escalate the priority fee of resubmitted transactions depending on the time
+/// they have spent in the mempool.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Default)]
+pub enum FeeEscalationCurve {
+    /// The priority fee suggested by the gas adjuster is used as is.
+    #[default]
+    Constant,
+    /// The priority fee grows linearly with the number of L1 blocks spent in the mempool.
+    Linear,
+    /// The priority fee grows exponentially with the number of L1 blocks spent in the mempool.
+    Exponential,
+}
+
 #[derive(Debug, Deserialize, Clone, PartialEq)]
 pub struct SenderConfig {
     pub aggregated_proof_sizes: Vec<usize>,
@@ -96,6 +131,44 @@ pub struct SenderConfig {
 
     /// The mode in which proofs are loaded, either from DB/GCS for FRI/Old proof.
     pub proof_loading_mode: ProofLoadingMode,
+
+    /// The mode in which pubdata for committed L1 batches is published.
+    #[serde(default)]
+    pub pubdata_sending_mode: PubdataSendingMode,
+    /// If the blob base fee (in gwei) exceeds this threshold, pubdata is published in calldata even
+    /// if `pubdata_sending_mode` is `Blobs`. If not set, blobs are used regardless of the blob base fee.
+    #[serde(default)]
+    pub blob_base_fee_fallback_threshold_gwei: Option<u64>,
+    /// Addresses of operator accounts that must not be used for new L1 transactions. Retired accounts
+    /// are still used to finalize their in-flight transactions, so their private keys must remain configured
+    /// until these transactions are confirmed.
+    #[serde(default)]
+    pub retired_operator_addresses: Vec<Address>,
+
+    /// Cap on `max_fee_per_gas` (in gwei) for L1 transactions. Suggested fees are lowered to the cap; if the base fee
+    /// alone exceeds the cap, or if a stuck transaction cannot be replaced without exceeding it, the transaction
+    /// is not sent until fees go down.
+    #[serde(default)]
+    pub max_fee_per_gas_cap_gwei: Option<u64>,
+    /// Minimum increase of the priority fee (in percent) when resubmitting a stuck transaction. Defaults to 20%.
+    #[serde(default)]
+    pub resubmission_fee_bump_percent: Option<u64>,
+    /// Curve used to escalate the priority fee of resubmitted transactions.
+    #[serde(default)]
+    pub resubmission_escalation_curve: FeeEscalationCurve,
+    /// Escalation rate for the priority fee of resubmitted transactions (in percent per L1 block spent in the mempool).
+    /// Not used for the `Constant` escalation curve.
+    #[serde(default)]
+    pub resubmission_escalation_rate_percent: u64,
+    /// Whether to simulate commit transactions via `eth_call` before sending them. If the simulated call reverts,
+    /// the transaction is not saved, and the revert reason is logged. Transactions publishing pubdata in blobs
+    /// are not simulated since blobs cannot be attached to `eth_call` requests.
+    #[serde(default)]
+    pub commit_dry_run_enabled: bool,
+    /// Path to the JSON-serialized SNARK wrapper verification key. If set, real proofs are verified locally
+    /// before the prove transaction is built, so that malformed proofs don't waste L1 gas.
+    #[serde(default)]
+    pub proof_verification_key_path: Option<String>,
 }
 
 impl SenderConfig {
@@ -109,15 +182,50 @@ impl SenderConfig {
         Duration::from_secs(self.aggregate_tx_poll_period)
     }
 
+    /// Converts `self.blob_base_fee_fallback_threshold_gwei` into wei.
+    pub fn blob_base_fee_fallback_threshold(&self) -> Option<u64> {
+        self.blob_base_fee_fallback_threshold_gwei
+            .map(|threshold| threshold.saturating_mul(1_000_000_000))
+    }
+
+    /// Converts `self.max_fee_per_gas_cap_gwei` into wei.
+    pub fn max_fee_per_gas_cap(&self) -> Option<u64> {
+        self.max_fee_per_gas_cap_gwei
+            .map(|cap| cap.saturating_mul(1_000_000_000))
+    }
+
+    pub fn resubmission_fee_bump_percent(&self) -> u64 {
+        self.resubmission_fee_bump_percent.unwrap_or(20)
+    }
+
     // Don't load private key, if it's not required.
     pub fn private_key(&self) -> Option<H256> {
         std::env::var("ETH_SENDER_SENDER_OPERATOR_PRIVATE_KEY")
             .ok()
             .map(|pk| pk.parse().unwrap())
     }
+
+    /// Loads private keys for all operator accounts. The primary key returned by [`Self::private_key()`]
+    /// always goes first; it is followed by comma-separated additional keys, if any.
+    pub fn private_keys(&self) -> Vec<H256> {
+        let additional_keys = std::env::var("ETH_SENDER_SENDER_OPERATOR_ADDITIONAL_PRIVATE_KEYS")
+            .ok()
+            .into_iter()
+            .flat_map(|keys| {
+                keys.split(',')
+                    .map(str::trim)
+                    .filter(|key| !key.is_empty())
+                    .map(|key| key.parse().unwrap())
+                    .collect::<Vec<_>>()
+            });
+        self.private_key()
+            .into_iter()
+            .chain(additional_keys)
+            .collect()
+    }
 }
 
-#[derive(Debug, Deserialize, Copy, Clone, PartialEq)]
+#[derive(Debug, Deserialize, Clone, PartialEq)]
 pub struct GasAdjusterConfig {
     /// Priority Fee to be used by GasAdjuster
     pub default_priority_fee_per_gas: u64,
@@ -135,6 +243,13 @@ pub struct GasAdjusterConfig {
     pub poll_period: u64,
     /// Max number of l1 gas price that is allowed to be used in state keeper.
     pub max_l1_gas_price: Option<u64>,
+    /// URLs of external L1 fee oracles queried in addition to the L1 node.
+    #[serde(default)]
+    pub fee_oracle_urls: Vec<String>,
+    /// Fees reported by a provider that deviate from the median across providers by more than this percentage
+    /// are ignored. If not specified, a default value is used.
+    #[serde(default)]
+    pub fee_outlier_threshold_percent: Option<u64>,
 }
 
 impl GasAdjusterConfig {
@@ -146,4 +261,8 @@ impl GasAdjusterConfig {
     pub fn max_l1_gas_price(&self) -> u64 {
         self.max_l1_gas_price.unwrap_or(u64::MAX)
     }
+
+    pub fn fee_outlier_threshold_percent(&self) -> u64 {
+        self.fee_outlier_threshold_percent.unwrap_or(50)
+    }
 }
@@ -11,11 +11,33 @@ pub struct ETHWatchConfig {
     /// How often we want to poll the Ethereum node.
     /// Value in milliseconds.
     pub eth_node_poll_interval: u64,
+    /// Number of most recent L1 blocks checked for reorgs. Events from reorged blocks are re-fetched.
+    /// If not specified, a default value is used.
+    pub reorg_detection_window: Option<u64>,
+    /// Deadline (in seconds) for including a priority operation into a miniblock after it was received from L1.
+    /// If the oldest pending operation exceeds the deadline, the priority operations health check is affected.
+    /// If not specified, a default value is used.
+    pub priority_op_deadline_sec: Option<u64>,
 }
 
 impl ETHWatchConfig {
+    const DEFAULT_REORG_DETECTION_WINDOW: u64 = 1_000;
+    const DEFAULT_PRIORITY_OP_DEADLINE_SEC: u64 = 600;
+
     /// Converts `self.eth_node_poll_interval` into `Duration`.
     pub fn poll_interval(&self) -> Duration {
         Duration::from_millis(self.eth_node_poll_interval)
     }
+
+    pub fn reorg_detection_window(&self) -> u64 {
+        self.reorg_detection_window
+            .unwrap_or(Self::DEFAULT_REORG_DETECTION_WINDOW)
+    }
+
+    pub fn priority_op_deadline(&self) -> Duration {
+        Duration::from_secs(
+            self.priority_op_deadline_sec
+                .unwrap_or(Self::DEFAULT_PRIORITY_OP_DEADLINE_SEC),
+        )
+    }
 }
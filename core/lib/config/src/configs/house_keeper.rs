@@ -16,4 +16,17 @@ pub struct HouseKeeperConfig {
     pub fri_prover_stats_reporting_interval_ms: u64,
     pub fri_proof_compressor_job_retrying_interval_ms: u64,
     pub fri_proof_compressor_stats_reporting_interval_ms: u64,
+    /// Interval between runs of the `storage_logs` / `events` partitions manager. If not set, the manager is disabled.
+    #[serde(default)]
+    pub storage_partitions_managing_interval_ms: Option<u64>,
+    /// Number of miniblocks covered by a single partition created by the partitions manager.
+    #[serde(default)]
+    pub storage_partition_size_in_miniblocks: Option<u32>,
+    /// Number of latest L1 batches for which `events` partitions are retained. Older partitions are detached
+    /// by the partitions manager. If not set, partitions are never detached automatically.
+    #[serde(default)]
+    pub events_retention_l1_batches: Option<u32>,
+    /// Interval between runs of the paymaster analytics aggregator. If not set, the aggregator is disabled.
+    #[serde(default)]
+    pub paymaster_analytics_interval_ms: Option<u64>,
 }
@@ -4,6 +4,7 @@ pub use self::{
     api::ApiConfig,
     contract_verifier::ContractVerifierConfig,
     contracts::ContractsConfig,
+    da_dispatcher::DADispatcherConfig,
     database::{DBConfig, PostgresConfig},
     eth_client::ETHClientConfig,
     eth_sender::{ETHSenderConfig, GasAdjusterConfig},
@@ -16,6 +17,8 @@ pub use self::{
     object_store::ObjectStoreConfig,
     proof_data_handler::ProofDataHandlerConfig,
     snapshots_creator::SnapshotsCreatorConfig,
+    token_fetcher::TokenFetcherConfig,
+    tx_event_stream::{TxEventSinkKind, TxEventStreamConfig},
     utils::PrometheusConfig,
     witness_generator::WitnessGeneratorConfig,
 };
@@ -25,6 +28,7 @@ pub mod api;
 pub mod chain;
 pub mod contract_verifier;
 pub mod contracts;
+pub mod da_dispatcher;
 pub mod database;
 pub mod eth_client;
 pub mod eth_sender;
@@ -39,6 +43,8 @@ pub mod house_keeper;
 pub mod object_store;
 pub mod proof_data_handler;
 pub mod snapshots_creator;
+pub mod token_fetcher;
+pub mod tx_event_stream;
 pub mod utils;
 pub mod witness_generator;
 
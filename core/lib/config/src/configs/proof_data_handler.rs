@@ -14,9 +14,21 @@ pub struct ProofDataHandlerConfig {
     pub proof_generation_timeout_in_secs: u16,
     pub protocol_version_loading_mode: ProtocolVersionLoadingMode,
     pub fri_protocol_version_id: u16,
+    /// If set, L1 batches waiting to be proven for longer than this timeout are treated as having
+    /// the high priority, so that older batches are not starved by prioritized ones.
+    #[serde(default)]
+    pub job_aging_timeout_in_secs: Option<u64>,
+    /// Port of the admin server allowing to set proof generation priorities and inspect the proof generation queue.
+    /// The admin server listens on localhost only. If not set, admin endpoints are disabled.
+    #[serde(default)]
+    pub admin_http_port: Option<u16>,
 }
 impl ProofDataHandlerConfig {
     pub fn proof_generation_timeout(&self) -> Duration {
         Duration::from_secs(self.proof_generation_timeout_in_secs as u64)
     }
+
+    pub fn job_aging_timeout(&self) -> Option<Duration> {
+        self.job_aging_timeout_in_secs.map(Duration::from_secs)
+    }
 }
@@ -1,4 +1,7 @@
+use std::time::Duration;
+
 use serde::Deserialize;
+use zksync_basic_types::H256;
 
 #[derive(Debug, Clone, PartialEq, Deserialize)]
 pub struct SnapshotsCreatorConfig {
@@ -7,6 +10,91 @@ pub struct SnapshotsCreatorConfig {
 
     #[serde(default = "snapshots_creator_concurrent_queries_count")]
     pub concurrent_queries_count: u32,
+
+    /// Maximum number of storage log chunks processed at the same time, i.e. loaded from Postgres,
+    /// but not yet uploaded to the object store. Bounds the memory consumption of the creator.
+    #[serde(default = "snapshots_creator_max_in_flight_chunks")]
+    pub max_in_flight_chunks: u32,
+
+    /// Number of threads used to serialize and compress storage log chunks.
+    #[serde(default = "snapshots_creator_serialization_threads_count")]
+    pub serialization_threads_count: u32,
+
+    /// Maximum number of concurrent uploads of storage log chunks to the object store.
+    #[serde(default = "snapshots_creator_concurrent_uploads_count")]
+    pub concurrent_uploads_count: u32,
+
+    /// Maximum number of delta snapshots created on top of a full snapshot. Delta snapshots only contain
+    /// storage changes since the previous snapshot. If set to 0 (the default), only full snapshots are created.
+    #[serde(default)]
+    pub max_consecutive_delta_snapshots: u32,
+
+    /// If set, the creator runs as a long-running coordinator creating a new snapshot once the latest
+    /// snapshot L1 batch lags behind the sealed L1 batch by at least this number of batches.
+    #[serde(default)]
+    pub schedule_every_l1_batches: Option<u32>,
+
+    /// Comma-separated UTC time windows (e.g., `01:00-03:00,22:30-23:30`) during which the coordinator
+    /// is allowed to start creating snapshots. If set without `schedule_every_l1_batches`, a snapshot is created
+    /// during each window once there is a new L1 batch to snapshot.
+    #[serde(default)]
+    pub schedule_time_windows: Option<String>,
+
+    /// Interval between coordinator checks whether a new snapshot should be created, in seconds.
+    #[serde(default = "snapshots_creator_schedule_poll_interval_sec")]
+    pub schedule_poll_interval_sec: u64,
+
+    /// Number of the latest complete snapshots to keep. Older snapshots are removed together with their
+    /// object store files, unless they are the base for a kept delta snapshot. If not set, all snapshots are kept;
+    /// if set, must be positive.
+    #[serde(default)]
+    pub retention_keep_last: Option<u32>,
+}
+
+impl SnapshotsCreatorConfig {
+    /// Loads the private key used to sign snapshot manifests. If the key is not set, manifests are not signed.
+    // Like other private keys, the key is not loaded unless it's required.
+    pub fn manifest_signing_key(&self) -> Option<H256> {
+        std::env::var("SNAPSHOTS_CREATOR_MANIFEST_SIGNING_KEY")
+            .ok()
+            .map(|key| key.parse().expect("invalid snapshot manifest signing key"))
+    }
+
+    /// Returns `true` if snapshots should be created on a schedule by a long-running coordinator,
+    /// rather than once per creator invocation.
+    pub fn is_scheduled(&self) -> bool {
+        self.schedule_every_l1_batches.is_some() || self.schedule_time_windows.is_some()
+    }
+
+    pub fn schedule_poll_interval(&self) -> Duration {
+        Duration::from_secs(self.schedule_poll_interval_sec)
+    }
+
+    /// Checks that the config values are consistent. In particular, all concurrency limits must be positive;
+    /// a zero limit would make the creator wait forever.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        let concurrency_limits = [
+            ("concurrent_queries_count", self.concurrent_queries_count),
+            ("max_in_flight_chunks", self.max_in_flight_chunks),
+            (
+                "serialization_threads_count",
+                self.serialization_threads_count,
+            ),
+            ("concurrent_uploads_count", self.concurrent_uploads_count),
+        ];
+        for (name, limit) in concurrency_limits {
+            anyhow::ensure!(limit > 0, "`{name}` must be positive");
+        }
+        anyhow::ensure!(
+            self.storage_logs_chunk_size > 0,
+            "`storage_logs_chunk_size` must be positive"
+        );
+        anyhow::ensure!(
+            self.retention_keep_last != Some(0),
+            "`retention_keep_last` must be positive; leave it unset to disable snapshot pruning"
+        );
+        Ok(())
+    }
 }
 
 fn snapshots_creator_storage_logs_chunk_size_default() -> u64 {
@@ -16,3 +104,19 @@ fn snapshots_creator_storage_logs_chunk_size_default() -> u64 {
 fn snapshots_creator_concurrent_queries_count() -> u32 {
     25
 }
+
+fn snapshots_creator_max_in_flight_chunks() -> u32 {
+    50
+}
+
+fn snapshots_creator_serialization_threads_count() -> u32 {
+    4
+}
+
+fn snapshots_creator_concurrent_uploads_count() -> u32 {
+    10
+}
+
+fn snapshots_creator_schedule_poll_interval_sec() -> u64 {
+    60
+}
@@ -0,0 +1,23 @@
+use std::time::Duration;
+
+use serde::Deserialize;
+
+/// Configuration for the fetcher of token metadata and prices.
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+pub struct TokenFetcherConfig {
+    /// Interval between fetcher iterations (in ms).
+    #[serde(default = "TokenFetcherConfig::default_polling_interval_ms")]
+    pub polling_interval_ms: u64,
+    /// URL of the token price oracle API. If not set, token prices are not fetched.
+    pub price_oracle_url: Option<String>,
+}
+
+impl TokenFetcherConfig {
+    const fn default_polling_interval_ms() -> u64 {
+        60_000
+    }
+
+    pub fn polling_interval(&self) -> Duration {
+        Duration::from_millis(self.polling_interval_ms)
+    }
+}
@@ -0,0 +1,63 @@
+use std::time::Duration;
+
+use serde::Deserialize;
+
+/// Sink to which transaction lifecycle events are published.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TxEventSinkKind {
+    /// Events are printed to stdout as JSON lines.
+    #[default]
+    StdoutJson,
+    /// Events are published to a NATS JetStream subject.
+    Nats,
+    /// Events are published to a Kafka topic via the Kafka REST proxy.
+    Kafka,
+}
+
+/// Configuration for the transaction lifecycle event stream.
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+pub struct TxEventStreamConfig {
+    /// Sink to publish events to.
+    #[serde(default)]
+    pub sink: TxEventSinkKind,
+    /// URL of the NATS server. Required for the NATS sink.
+    pub nats_url: Option<String>,
+    /// Subject to which events are published in the NATS sink.
+    #[serde(default = "TxEventStreamConfig::default_nats_subject")]
+    pub nats_subject: String,
+    /// URL of the Kafka REST proxy. Required for the Kafka sink.
+    pub kafka_rest_proxy_url: Option<String>,
+    /// Topic to which events are published in the Kafka sink.
+    #[serde(default = "TxEventStreamConfig::default_kafka_topic")]
+    pub kafka_topic: String,
+    /// Interval between polling the database for new events (in ms).
+    #[serde(default = "TxEventStreamConfig::default_polling_interval_ms")]
+    pub polling_interval_ms: u64,
+    /// Maximum number of events published for a single lifecycle stage in an iteration. Events for a single
+    /// miniblock or L1 batch are never split across iterations, so the limit is exceeded if a block
+    /// contains more transactions.
+    #[serde(default = "TxEventStreamConfig::default_max_events_per_iteration")]
+    pub max_events_per_iteration: u32,
+}
+
+impl TxEventStreamConfig {
+    fn default_nats_subject() -> String {
+        "zksync.tx_events".to_owned()
+    }
+
+    fn default_kafka_topic() -> String {
+        "zksync.tx_events".to_owned()
+    }
+
+    const fn default_polling_interval_ms() -> u64 {
+        1_000
+    }
+
+    const fn default_max_events_per_iteration() -> u32 {
+        1_000
+    }
+
+    pub fn polling_interval(&self) -> Duration {
+        Duration::from_millis(self.polling_interval_ms)
+    }
+}
@@ -3,3 +3,8 @@ pub const L2_TO_L1_LOGS_TREE_ROOT_KEY: u32 = 0;
 
 /// The key of the system log with value of the state diff hash
 pub const STATE_DIFF_HASH_KEY: u32 = 2;
+
+/// Keys of the system logs with linear hashes of the EIP-4844 blobs carrying the batch pubdata.
+/// Only emitted for protocol versions supporting blobs; in earlier versions, the first of these keys
+/// is occupied by the expected system contract upgrade transaction hash.
+pub const BLOB_HASH_KEYS: [u32; 2] = [7, 8];
@@ -0,0 +1,8 @@
+CREATE TABLE IF NOT EXISTS cancelled_transactions (
+    id BIGSERIAL PRIMARY KEY,
+    tx_hash BYTEA NOT NULL,
+    initiator_address BYTEA NOT NULL,
+    nonce BIGINT NOT NULL,
+    reason TEXT,
+    created_at TIMESTAMP NOT NULL
+);
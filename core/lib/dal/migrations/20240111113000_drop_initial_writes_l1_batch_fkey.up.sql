@@ -0,0 +1,2 @@
+-- Initial writes recovered from a snapshot reference L1 batches that are not present in Postgres.
+ALTER TABLE initial_writes DROP CONSTRAINT IF EXISTS initial_writes_l1_batch_number_fkey;
@@ -0,0 +1,2 @@
+DROP INDEX IF EXISTS l1_batches_pending_protective_reads_idx;
+ALTER TABLE l1_batches DROP COLUMN IF EXISTS protective_reads_persisted;
@@ -0,0 +1,3 @@
+ALTER TABLE l1_batches ADD COLUMN IF NOT EXISTS protective_reads_persisted BOOLEAN NOT NULL DEFAULT TRUE;
+CREATE INDEX IF NOT EXISTS l1_batches_pending_protective_reads_idx
+    ON l1_batches (number) WHERE NOT protective_reads_persisted;
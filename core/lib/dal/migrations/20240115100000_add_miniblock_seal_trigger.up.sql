@@ -0,0 +1 @@
+ALTER TABLE miniblocks ADD COLUMN IF NOT EXISTS seal_trigger TEXT;
@@ -0,0 +1,77 @@
+-- Copies primary key, unique and foreign key constraints, and standalone indexes from `src` to `dst`.
+-- Constraints and indexes of `src` are renamed by appending `src_suffix` so that `dst` can reuse their names.
+CREATE OR REPLACE FUNCTION copy_constraints_and_indexes(src TEXT, dst TEXT, src_suffix TEXT)
+RETURNS VOID AS $$
+DECLARE
+    rec RECORD;
+BEGIN
+    FOR rec IN
+        SELECT conname, pg_get_constraintdef(oid) AS def
+        FROM pg_constraint
+        WHERE conrelid = src::regclass AND contype IN ('p', 'u', 'f')
+    LOOP
+        EXECUTE format('ALTER TABLE %I RENAME CONSTRAINT %I TO %I', src, rec.conname, rec.conname || src_suffix);
+        EXECUTE format('ALTER TABLE %I ADD CONSTRAINT %I %s', dst, rec.conname, rec.def);
+    END LOOP;
+
+    FOR rec IN
+        SELECT idx.relname AS name, pg_get_indexdef(i.indexrelid) AS def
+        FROM pg_index i
+        JOIN pg_class idx ON idx.oid = i.indexrelid
+        WHERE i.indrelid = src::regclass
+            AND NOT EXISTS (SELECT 1 FROM pg_constraint c WHERE c.conindid = i.indexrelid)
+    LOOP
+        EXECUTE format('ALTER INDEX %I RENAME TO %I', rec.name, rec.name || src_suffix);
+        EXECUTE regexp_replace(rec.def, ' ON (ONLY )?\S+ USING ', format(' ON %I USING ', dst));
+    END LOOP;
+END;
+$$ LANGUAGE plpgsql;
+
+-- Converts a partitioned table back to a regular one. Data in detached partitions is restored as well, provided
+-- that the partitions weren't dropped; the migration fails otherwise, so that data loss is explicit.
+CREATE OR REPLACE FUNCTION unpartition_table(tbl TEXT)
+RETURNS VOID AS $$
+DECLARE
+    partitioned TEXT := tbl || '_partitioned';
+    rec RECORD;
+BEGIN
+    FOR rec IN
+        SELECT partition_name FROM storage_partitions
+        WHERE table_name = tbl AND detached_at IS NOT NULL
+    LOOP
+        IF to_regclass(rec.partition_name) IS NULL THEN
+            RAISE EXCEPTION 'Detached partition % of table % was dropped; restore it or remove it from storage_partitions',
+                rec.partition_name, tbl;
+        END IF;
+    END LOOP;
+
+    EXECUTE format('ALTER TABLE %I RENAME TO %I', tbl, partitioned);
+    EXECUTE format(
+        'CREATE TABLE %I (LIKE %I INCLUDING DEFAULTS INCLUDING CONSTRAINTS INCLUDING STORAGE)',
+        tbl, partitioned
+    );
+    FOR rec IN
+        SELECT partition_name FROM storage_partitions
+        WHERE table_name = tbl AND detached_at IS NOT NULL
+        ORDER BY from_miniblock
+    LOOP
+        EXECUTE format('INSERT INTO %I SELECT * FROM %I', tbl, rec.partition_name);
+    END LOOP;
+    EXECUTE format('INSERT INTO %I SELECT * FROM %I', tbl, partitioned);
+    PERFORM copy_constraints_and_indexes(partitioned, tbl, '_partitioned');
+    EXECUTE format('DROP TABLE %I CASCADE', partitioned);
+    FOR rec IN
+        SELECT partition_name FROM storage_partitions
+        WHERE table_name = tbl AND detached_at IS NOT NULL
+    LOOP
+        EXECUTE format('DROP TABLE %I', rec.partition_name);
+    END LOOP;
+END;
+$$ LANGUAGE plpgsql;
+
+SELECT unpartition_table('storage_logs');
+SELECT unpartition_table('events');
+
+DROP FUNCTION unpartition_table;
+DROP FUNCTION copy_constraints_and_indexes;
+DROP TABLE IF EXISTS storage_partitions;
@@ -0,0 +1,70 @@
+-- Converts `storage_logs` and `events` into tables partitioned by miniblock number ranges. Existing data
+-- is retained in a single "legacy" partition; a default partition catches data not covered by other partitions.
+-- New partitions are created and old ones are detached at runtime; see `StoragePartitionsDal`.
+CREATE TABLE IF NOT EXISTS storage_partitions (
+    partition_name TEXT PRIMARY KEY,
+    table_name TEXT NOT NULL,
+    -- Miniblock range covered by the partition (the upper bound is exclusive).
+    from_miniblock BIGINT NOT NULL,
+    to_miniblock BIGINT NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    detached_at TIMESTAMP
+);
+CREATE INDEX IF NOT EXISTS storage_partitions_table_name_idx ON storage_partitions (table_name, from_miniblock);
+
+-- Copies primary key, unique and foreign key constraints, and standalone indexes from `src` to `dst`.
+-- Constraints and indexes of `src` are renamed by appending `src_suffix` so that `dst` can reuse their names.
+CREATE OR REPLACE FUNCTION copy_constraints_and_indexes(src TEXT, dst TEXT, src_suffix TEXT)
+RETURNS VOID AS $$
+DECLARE
+    rec RECORD;
+BEGIN
+    FOR rec IN
+        SELECT conname, pg_get_constraintdef(oid) AS def
+        FROM pg_constraint
+        WHERE conrelid = src::regclass AND contype IN ('p', 'u', 'f')
+    LOOP
+        EXECUTE format('ALTER TABLE %I RENAME CONSTRAINT %I TO %I', src, rec.conname, rec.conname || src_suffix);
+        EXECUTE format('ALTER TABLE %I ADD CONSTRAINT %I %s', dst, rec.conname, rec.def);
+    END LOOP;
+
+    FOR rec IN
+        SELECT idx.relname AS name, pg_get_indexdef(i.indexrelid) AS def
+        FROM pg_index i
+        JOIN pg_class idx ON idx.oid = i.indexrelid
+        WHERE i.indrelid = src::regclass
+            AND NOT EXISTS (SELECT 1 FROM pg_constraint c WHERE c.conindid = i.indexrelid)
+    LOOP
+        EXECUTE format('ALTER INDEX %I RENAME TO %I', rec.name, rec.name || src_suffix);
+        EXECUTE regexp_replace(rec.def, ' ON (ONLY )?\S+ USING ', format(' ON %I USING ', dst));
+    END LOOP;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE OR REPLACE FUNCTION partition_by_miniblock_number(tbl TEXT)
+RETURNS VOID AS $$
+DECLARE
+    legacy TEXT := tbl || '_p_legacy';
+    upper_bound BIGINT;
+BEGIN
+    EXECUTE format('ALTER TABLE %I RENAME TO %I', tbl, legacy);
+    EXECUTE format(
+        'CREATE TABLE %I (LIKE %I INCLUDING DEFAULTS INCLUDING CONSTRAINTS INCLUDING STORAGE) '
+        'PARTITION BY RANGE (miniblock_number)',
+        tbl, legacy
+    );
+    PERFORM copy_constraints_and_indexes(legacy, tbl, '_legacy');
+
+    EXECUTE format('SELECT COALESCE(MAX(miniblock_number) + 1, 0) FROM %I', legacy) INTO upper_bound;
+    EXECUTE format('ALTER TABLE %I ATTACH PARTITION %I FOR VALUES FROM (MINVALUE) TO (%s)', tbl, legacy, upper_bound);
+    EXECUTE format('CREATE TABLE %I PARTITION OF %I DEFAULT', tbl || '_p_default', tbl);
+    INSERT INTO storage_partitions (partition_name, table_name, from_miniblock, to_miniblock)
+    VALUES (legacy, tbl, 0, upper_bound);
+END;
+$$ LANGUAGE plpgsql;
+
+SELECT partition_by_miniblock_number('storage_logs');
+SELECT partition_by_miniblock_number('events');
+
+DROP FUNCTION partition_by_miniblock_number;
+DROP FUNCTION copy_constraints_and_indexes;
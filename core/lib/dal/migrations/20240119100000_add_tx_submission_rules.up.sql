@@ -0,0 +1,11 @@
+CREATE TABLE IF NOT EXISTS tx_submission_rules (
+    id BIGSERIAL PRIMARY KEY,
+    -- Either 'allow' or 'deny'.
+    action TEXT NOT NULL,
+    -- Either 'sender', 'contract' or 'selector'.
+    target TEXT NOT NULL,
+    value BYTEA NOT NULL,
+    comment TEXT,
+    created_at TIMESTAMP NOT NULL,
+    UNIQUE (action, target, value)
+);
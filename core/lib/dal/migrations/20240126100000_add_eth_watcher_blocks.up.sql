@@ -0,0 +1,6 @@
+CREATE TABLE IF NOT EXISTS eth_watcher_blocks (
+    number BIGINT PRIMARY KEY,
+    hash BYTEA NOT NULL,
+    created_at TIMESTAMP NOT NULL,
+    updated_at TIMESTAMP NOT NULL
+);
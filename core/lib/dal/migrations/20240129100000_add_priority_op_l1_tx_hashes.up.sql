@@ -0,0 +1,7 @@
+CREATE TABLE IF NOT EXISTS priority_op_l1_tx_hashes (
+    priority_op_id BIGINT PRIMARY KEY,
+    l1_tx_hash BYTEA NOT NULL,
+    created_at TIMESTAMP NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS priority_op_l1_tx_hashes_l1_tx_hash_idx ON priority_op_l1_tx_hashes (l1_tx_hash);
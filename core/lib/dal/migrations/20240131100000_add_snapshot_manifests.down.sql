@@ -0,0 +1,3 @@
+ALTER TABLE snapshots DROP COLUMN manifest_signature;
+ALTER TABLE snapshots DROP COLUMN manifest_hash;
+ALTER TABLE snapshots DROP COLUMN storage_logs_chunk_hashes;
@@ -0,0 +1,3 @@
+ALTER TABLE snapshots ADD COLUMN storage_logs_chunk_hashes BYTEA[] NOT NULL DEFAULT '{}';
+ALTER TABLE snapshots ADD COLUMN manifest_hash BYTEA;
+ALTER TABLE snapshots ADD COLUMN manifest_signature BYTEA;
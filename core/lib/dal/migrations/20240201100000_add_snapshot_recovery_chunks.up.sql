@@ -0,0 +1,9 @@
+CREATE TABLE IF NOT EXISTS snapshot_recovery_chunks
+(
+    chunk_id                 INT       NOT NULL PRIMARY KEY,
+    snapshot_l1_batch_number BIGINT    NOT NULL,
+    snapshot_chunk_id        INT       NOT NULL,
+    storage_logs_count       BIGINT    NOT NULL,
+
+    created_at               TIMESTAMP NOT NULL
+);
@@ -0,0 +1,2 @@
+ALTER TABLE proof_generation_details ADD COLUMN IF NOT EXISTS priority INT NOT NULL DEFAULT 0;
+ALTER TABLE proof_generation_details ADD COLUMN IF NOT EXISTS deadline TIMESTAMP;
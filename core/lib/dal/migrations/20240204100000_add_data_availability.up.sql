@@ -0,0 +1,14 @@
+CREATE TABLE IF NOT EXISTS data_availability
+(
+    l1_batch_number BIGINT PRIMARY KEY REFERENCES l1_batches (number) ON DELETE CASCADE,
+    -- Identifier of the blob with L1 batch pubdata in the data availability layer.
+    blob_id         TEXT      NOT NULL,
+    -- Proof of the blob inclusion returned by the data availability layer; NULL until the blob is included.
+    inclusion_data  BYTEA,
+    sent_at         TIMESTAMP NOT NULL,
+    created_at      TIMESTAMP NOT NULL,
+    updated_at      TIMESTAMP NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS data_availability_awaiting_inclusion_idx
+    ON data_availability (l1_batch_number) WHERE inclusion_data IS NULL;
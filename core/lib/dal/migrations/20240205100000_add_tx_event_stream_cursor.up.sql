@@ -0,0 +1,12 @@
+-- Progress of publishing transaction lifecycle events. The table contains at most one row.
+CREATE TABLE IF NOT EXISTS tx_event_stream_cursor
+(
+    id                      BOOLEAN PRIMARY KEY DEFAULT TRUE CHECK (id),
+    last_received_at        TIMESTAMP NOT NULL,
+    last_included_miniblock BIGINT    NOT NULL,
+    last_sealed_l1_batch    BIGINT    NOT NULL,
+    last_committed_l1_batch BIGINT,
+    last_proven_l1_batch    BIGINT,
+    last_executed_l1_batch  BIGINT,
+    updated_at              TIMESTAMP NOT NULL
+);
@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS l1_batch_paymaster_stats;
+DROP TABLE IF EXISTS l1_batch_paymaster_summaries;
@@ -0,0 +1,26 @@
+-- Summary of transactions sponsored by paymasters in each L1 batch. A row is inserted for every processed batch
+-- (even if it has no sponsored transactions), so the table also tracks progress of the analytics aggregator.
+CREATE TABLE IF NOT EXISTS l1_batch_paymaster_summaries
+(
+    l1_batch_number    BIGINT PRIMARY KEY,
+    sponsored_tx_count BIGINT      NOT NULL,
+    unique_users       BIGINT      NOT NULL,
+    sponsored_gas      NUMERIC(80) NOT NULL,
+    sponsored_fee      NUMERIC(80) NOT NULL,
+    created_at         TIMESTAMP   NOT NULL
+);
+
+-- Usage of individual paymasters in each L1 batch.
+CREATE TABLE IF NOT EXISTS l1_batch_paymaster_stats
+(
+    l1_batch_number BIGINT      NOT NULL,
+    paymaster       BYTEA       NOT NULL,
+    tx_count        BIGINT      NOT NULL,
+    unique_users    BIGINT      NOT NULL,
+    sponsored_gas   NUMERIC(80) NOT NULL,
+    sponsored_fee   NUMERIC(80) NOT NULL,
+    created_at      TIMESTAMP   NOT NULL,
+    PRIMARY KEY (l1_batch_number, paymaster)
+);
+CREATE INDEX IF NOT EXISTS l1_batch_paymaster_stats_paymaster_idx
+    ON l1_batch_paymaster_stats (paymaster, l1_batch_number);
@@ -0,0 +1,2 @@
+DROP INDEX IF EXISTS miniblocks_without_logs_bloom_idx;
+ALTER TABLE miniblocks DROP COLUMN IF EXISTS logs_bloom;
@@ -0,0 +1,4 @@
+-- Ethereum-style logs bloom covering emitter addresses and topics of all events in the miniblock.
+-- `NULL` for miniblocks sealed before the column was introduced; such blooms are backfilled in the background.
+ALTER TABLE miniblocks ADD COLUMN IF NOT EXISTS logs_bloom BYTEA;
+CREATE INDEX IF NOT EXISTS miniblocks_without_logs_bloom_idx ON miniblocks (number) WHERE logs_bloom IS NULL;
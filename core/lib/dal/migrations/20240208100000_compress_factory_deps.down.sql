@@ -0,0 +1,6 @@
+-- Compressed bytecodes cannot be restored in SQL, so the down migration fails if there are rows
+-- without the raw `bytecode`.
+DROP INDEX IF EXISTS factory_deps_uncompressed_idx;
+ALTER TABLE factory_deps DROP CONSTRAINT IF EXISTS factory_deps_has_bytecode;
+ALTER TABLE factory_deps ALTER COLUMN bytecode SET NOT NULL;
+ALTER TABLE factory_deps DROP COLUMN IF EXISTS bytecode_compressed;
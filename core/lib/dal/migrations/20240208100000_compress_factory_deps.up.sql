@@ -0,0 +1,7 @@
+-- zlib-compressed bytecode of the factory dependency. New rows only have this column set; rows inserted before
+-- the column was introduced keep the raw `bytecode` until they are compressed in the background.
+ALTER TABLE factory_deps ADD COLUMN IF NOT EXISTS bytecode_compressed BYTEA;
+ALTER TABLE factory_deps ALTER COLUMN bytecode DROP NOT NULL;
+ALTER TABLE factory_deps ADD CONSTRAINT factory_deps_has_bytecode
+    CHECK (bytecode IS NOT NULL OR bytecode_compressed IS NOT NULL) NOT VALID;
+CREATE INDEX IF NOT EXISTS factory_deps_uncompressed_idx ON factory_deps (bytecode_hash) WHERE bytecode IS NOT NULL;
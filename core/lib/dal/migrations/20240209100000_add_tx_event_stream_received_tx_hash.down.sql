@@ -0,0 +1 @@
+ALTER TABLE tx_event_stream_cursor DROP COLUMN IF EXISTS last_received_tx_hash;
@@ -0,0 +1,2 @@
+-- Received transactions are paginated by `(received_at, hash)`, since `received_at` is not unique.
+ALTER TABLE tx_event_stream_cursor ADD COLUMN IF NOT EXISTS last_received_tx_hash BYTEA;
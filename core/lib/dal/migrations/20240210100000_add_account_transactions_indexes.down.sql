@@ -0,0 +1,2 @@
+DROP INDEX IF EXISTS transactions_initiator_address_miniblock_idx;
+DROP INDEX IF EXISTS transactions_contract_address_miniblock_idx;
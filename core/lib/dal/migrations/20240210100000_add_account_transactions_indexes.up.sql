@@ -0,0 +1,5 @@
+-- Allow index-ordered scans of executed transactions initiated by / sent to an account (used by the explorer API).
+CREATE INDEX IF NOT EXISTS transactions_initiator_address_miniblock_idx
+    ON transactions (initiator_address, miniblock_number, index_in_block) WHERE miniblock_number IS NOT NULL;
+CREATE INDEX IF NOT EXISTS transactions_contract_address_miniblock_idx
+    ON transactions (contract_address, miniblock_number, index_in_block) WHERE miniblock_number IS NOT NULL;
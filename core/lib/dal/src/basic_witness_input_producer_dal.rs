@@ -62,7 +62,6 @@ impl BasicWitnessInputProducerDal<'_, '_> {
             BasicWitnessInputProducerJobStatus::Queued as BasicWitnessInputProducerJobStatus,
         )
         .instrument("create_basic_witness_input_producer_job")
-        .report_latency()
         .execute(self.storage.conn())
         .await?;
 
@@ -113,7 +112,6 @@ impl BasicWitnessInputProducerDal<'_, '_> {
             JOB_MAX_ATTEMPT,
         )
         .instrument("get_next_basic_witness_input_producer_job")
-        .report_latency()
         .fetch_optional(self.storage.conn())
         .await?
         .map(|job| L1BatchNumber(job.l1_batch_number as u32));
@@ -166,7 +164,6 @@ impl BasicWitnessInputProducerDal<'_, '_> {
             object_path,
         )
         .instrument("mark_job_as_successful")
-        .report_latency()
         .execute(self.storage.conn())
         .await?;
 
@@ -200,7 +197,6 @@ impl BasicWitnessInputProducerDal<'_, '_> {
             BasicWitnessInputProducerJobStatus::Successful as BasicWitnessInputProducerJobStatus,
         )
         .instrument("mark_job_as_failed")
-        .report_latency()
         .fetch_optional(self.storage.conn())
         .await?
         .map(|job| job.attempts as u32);
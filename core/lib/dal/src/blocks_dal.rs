@@ -11,14 +11,18 @@ use zksync_types::{
     aggregated_operations::AggregatedActionType,
     block::{BlockGasCount, L1BatchHeader, MiniblockHeader},
     commitment::{L1BatchMetadata, L1BatchWithMetadata},
-    Address, L1BatchNumber, LogQuery, MiniblockNumber, ProtocolVersionId, H256,
+    fee_model::BatchFeeInput,
+    Address, L1BatchNumber, LogQuery, MiniblockNumber, ProtocolVersionId, H2048, H256,
     MAX_GAS_PER_PUBDATA_BYTE, U256,
 };
 
 pub use crate::models::storage_sync::ConsensusBlockFields;
 use crate::{
     instrument::InstrumentExt,
-    models::storage_block::{StorageL1Batch, StorageL1BatchHeader, StorageMiniblockHeader},
+    models::{
+        storage_block::{StorageL1Batch, StorageL1BatchHeader, StorageMiniblockHeader},
+        storage_factory_dep::decode_bytecode,
+    },
     StorageProcessor,
 };
 
@@ -55,7 +59,6 @@ impl BlocksDal<'_, '_> {
             "#
         )
         .instrument("get_sealed_block_number")
-        .report_latency()
         .fetch_one(self.storage.conn())
         .await?;
 
@@ -72,7 +75,6 @@ impl BlocksDal<'_, '_> {
             "#
         )
         .instrument("get_sealed_miniblock_number")
-        .report_latency()
         .fetch_one(self.storage.conn())
         .await?
         .number
@@ -91,7 +93,6 @@ impl BlocksDal<'_, '_> {
             "#
         )
         .instrument("get_earliest_l1_batch_number")
-        .report_latency()
         .fetch_one(self.storage.conn())
         .await?;
 
@@ -112,7 +113,6 @@ impl BlocksDal<'_, '_> {
             "#
         )
         .instrument("get_last_block_number_with_metadata")
-        .report_latency()
         .fetch_one(self.storage.conn())
         .await?;
 
@@ -135,7 +135,6 @@ impl BlocksDal<'_, '_> {
             "#
         )
         .instrument("get_earliest_l1_batch_number_with_metadata")
-        .report_latency()
         .fetch_one(self.storage.conn())
         .await?;
 
@@ -308,7 +307,6 @@ impl BlocksDal<'_, '_> {
             number.0 as i64
         )
         .instrument("get_initial_bootloader_heap")
-        .report_latency()
         .with_arg("number", &number)
         .fetch_optional(self.storage.conn())
         .await?
@@ -337,7 +335,6 @@ impl BlocksDal<'_, '_> {
             number.0 as i64
         )
         .instrument("get_storage_refunds")
-        .report_latency()
         .with_arg("number", &number)
         .fetch_optional(self.storage.conn())
         .await?
@@ -368,7 +365,6 @@ impl BlocksDal<'_, '_> {
             number.0 as i64
         )
         .instrument("get_events_queue")
-        .report_latency()
         .with_arg("number", &number)
         .fetch_optional(self.storage.conn())
         .await?
@@ -597,6 +593,12 @@ impl BlocksDal<'_, '_> {
     ) -> anyhow::Result<()> {
         let base_fee_per_gas = BigDecimal::from_u64(miniblock_header.base_fee_per_gas)
             .context("base_fee_per_gas should fit in u64")?;
+        // The pubdata price is only recorded if it's independent from the L1 gas price, so that the fee input
+        // can be restored exactly.
+        let fair_pubdata_price = match miniblock_header.batch_fee_input {
+            BatchFeeInput::L1Pegged(_) => None,
+            BatchFeeInput::PubdataIndependent(input) => Some(input.fair_pubdata_price as i64),
+        };
         sqlx::query!(
             r#"
             INSERT INTO
@@ -614,11 +616,12 @@ impl BlocksDal<'_, '_> {
                     default_aa_code_hash,
                     protocol_version,
                     virtual_blocks,
+                    fair_pubdata_price,
                     created_at,
                     updated_at
                 )
             VALUES
-                ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, NOW(), NOW())
+                ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, NOW(), NOW())
             "#,
             miniblock_header.number.0 as i64,
             miniblock_header.timestamp as i64,
@@ -639,6 +642,7 @@ impl BlocksDal<'_, '_> {
                 .as_bytes(),
             miniblock_header.protocol_version.map(|v| v as i32),
             miniblock_header.virtual_blocks as i64,
+            fair_pubdata_price,
         )
         .execute(self.storage.conn())
         .await?;
@@ -723,6 +727,158 @@ impl BlocksDal<'_, '_> {
         Ok(())
     }
 
+    /// Records the reason the specified miniblock was sealed for (e.g., `timeout` or `gas`).
+    pub async fn set_miniblock_seal_trigger(
+        &mut self,
+        miniblock_number: MiniblockNumber,
+        trigger: &str,
+    ) -> anyhow::Result<()> {
+        let result = sqlx::query!(
+            r#"
+            UPDATE miniblocks
+            SET
+                seal_trigger = $2
+            WHERE
+                number = $1
+            "#,
+            miniblock_number.0 as i64,
+            trigger
+        )
+        .execute(self.storage.conn())
+        .await?;
+
+        anyhow::ensure!(
+            result.rows_affected() == 1,
+            "Miniblock #{miniblock_number} is not present in Postgres"
+        );
+        Ok(())
+    }
+
+    /// Returns the reason the specified miniblock was sealed for, if it was recorded.
+    pub async fn get_miniblock_seal_trigger(
+        &mut self,
+        miniblock_number: MiniblockNumber,
+    ) -> sqlx::Result<Option<String>> {
+        let row = sqlx::query!(
+            r#"
+            SELECT
+                seal_trigger
+            FROM
+                miniblocks
+            WHERE
+                number = $1
+            "#,
+            miniblock_number.0 as i64
+        )
+        .fetch_optional(self.storage.conn())
+        .await?;
+        Ok(row.and_then(|row| row.seal_trigger))
+    }
+
+    /// Sets the logs bloom for the specified miniblock.
+    pub async fn set_miniblock_logs_bloom(
+        &mut self,
+        miniblock_number: MiniblockNumber,
+        logs_bloom: &H2048,
+    ) -> anyhow::Result<()> {
+        let result = sqlx::query!(
+            r#"
+            UPDATE miniblocks
+            SET
+                logs_bloom = $2
+            WHERE
+                number = $1
+            "#,
+            miniblock_number.0 as i64,
+            logs_bloom.as_bytes()
+        )
+        .execute(self.storage.conn())
+        .await?;
+
+        anyhow::ensure!(
+            result.rows_affected() == 1,
+            "Miniblock #{miniblock_number} is not present in Postgres"
+        );
+        Ok(())
+    }
+
+    /// Sets logs blooms for multiple miniblocks at once. Miniblocks that already have a logs bloom are skipped.
+    pub async fn set_miniblock_logs_blooms(
+        &mut self,
+        logs_blooms: &[(MiniblockNumber, H2048)],
+    ) -> sqlx::Result<()> {
+        let (numbers, blooms): (Vec<_>, Vec<_>) = logs_blooms
+            .iter()
+            .map(|(number, bloom)| (i64::from(number.0), bloom.as_bytes().to_vec()))
+            .unzip();
+        sqlx::query!(
+            r#"
+            UPDATE miniblocks
+            SET
+                logs_bloom = data.logs_bloom
+            FROM
+                (
+                    SELECT
+                        UNNEST($1::BIGINT[]) AS number,
+                        UNNEST($2::BYTEA[]) AS logs_bloom
+                ) AS data
+            WHERE
+                miniblocks.number = data.number
+                AND miniblocks.logs_bloom IS NULL
+            "#,
+            &numbers,
+            &blooms
+        )
+        .instrument("set_miniblock_logs_blooms")
+        .with_arg("miniblock_count", &logs_blooms.len())
+        .execute(self.storage.conn())
+        .await?;
+        Ok(())
+    }
+
+    /// Returns the logs bloom for the specified miniblock, or `None` if the miniblock doesn't exist
+    /// or its logs bloom wasn't computed yet.
+    pub async fn get_miniblock_logs_bloom(
+        &mut self,
+        miniblock_number: MiniblockNumber,
+    ) -> sqlx::Result<Option<H2048>> {
+        let row = sqlx::query!(
+            r#"
+            SELECT
+                logs_bloom
+            FROM
+                miniblocks
+            WHERE
+                number = $1
+            "#,
+            miniblock_number.0 as i64
+        )
+        .fetch_optional(self.storage.conn())
+        .await?;
+        Ok(row
+            .and_then(|row| row.logs_bloom)
+            .map(|bloom| H2048::from_slice(&bloom)))
+    }
+
+    /// Returns the number of the latest miniblock without a logs bloom, or `None` if all miniblocks have logs blooms.
+    pub async fn get_last_miniblock_without_logs_bloom(
+        &mut self,
+    ) -> sqlx::Result<Option<MiniblockNumber>> {
+        let row = sqlx::query!(
+            r#"
+            SELECT
+                MAX(number) AS "number"
+            FROM
+                miniblocks
+            WHERE
+                logs_bloom IS NULL
+            "#
+        )
+        .fetch_one(self.storage.conn())
+        .await?;
+        Ok(row.number.map(|number| MiniblockNumber(number as u32)))
+    }
+
     pub async fn get_last_sealed_miniblock_header(
         &mut self,
     ) -> sqlx::Result<Option<MiniblockHeader>> {
@@ -741,7 +897,8 @@ impl BlocksDal<'_, '_> {
                 bootloader_code_hash,
                 default_aa_code_hash,
                 protocol_version,
-                virtual_blocks
+                virtual_blocks,
+                fair_pubdata_price
             FROM
                 miniblocks
             ORDER BY
@@ -774,7 +931,8 @@ impl BlocksDal<'_, '_> {
                 bootloader_code_hash,
                 default_aa_code_hash,
                 protocol_version,
-                virtual_blocks
+                virtual_blocks,
+                fair_pubdata_price
             FROM
                 miniblocks
             WHERE
@@ -901,7 +1059,6 @@ impl BlocksDal<'_, '_> {
         )
         .instrument("save_blocks_metadata")
         .with_arg("number", &number)
-        .report_latency()
         .execute(transaction.conn())
         .await?;
 
@@ -923,7 +1080,6 @@ impl BlocksDal<'_, '_> {
             )
             .instrument("save_batch_commitments")
             .with_arg("number", &number)
-            .report_latency()
             .execute(transaction.conn())
             .await?;
 
@@ -943,7 +1099,6 @@ impl BlocksDal<'_, '_> {
             )
             .instrument("save_batch_aux_commitment")
             .with_arg("number", &number)
-            .report_latency()
             .execute(transaction.conn())
             .await?;
         }
@@ -982,7 +1137,6 @@ impl BlocksDal<'_, '_> {
             )
             .instrument("get_matching_blocks_metadata")
             .with_arg("number", &number)
-            .report_latency()
             .fetch_one(transaction.conn())
             .await?
             .count;
@@ -1136,6 +1290,60 @@ impl BlocksDal<'_, '_> {
         Ok(row.and_then(|row| row.eth_commit_tx_id.map(|n| n as u64)))
     }
 
+    /// Returns the number of the last L1 batch for which the Ethereum commit tx is confirmed, i.e. has
+    /// `confirmed_eth_tx_history_id` set.
+    pub async fn get_last_l1_batch_with_confirmed_commit_tx(
+        &mut self,
+    ) -> sqlx::Result<Option<L1BatchNumber>> {
+        let row = sqlx::query!(
+            r#"
+            SELECT
+                l1_batches.number
+            FROM
+                l1_batches
+                JOIN eth_txs ON l1_batches.eth_commit_tx_id = eth_txs.id
+            WHERE
+                eth_txs.confirmed_eth_tx_history_id IS NOT NULL
+            ORDER BY
+                l1_batches.number DESC
+            LIMIT
+                1
+            "#
+        )
+        .instrument("get_last_l1_batch_with_confirmed_commit_tx")
+        .fetch_optional(self.storage.conn())
+        .await?;
+
+        Ok(row.map(|row| L1BatchNumber(row.number as u32)))
+    }
+
+    /// Returns the number of the last L1 batch for which the Ethereum prove tx is confirmed, i.e. has
+    /// `confirmed_eth_tx_history_id` set.
+    pub async fn get_last_l1_batch_with_confirmed_prove_tx(
+        &mut self,
+    ) -> sqlx::Result<Option<L1BatchNumber>> {
+        let row = sqlx::query!(
+            r#"
+            SELECT
+                l1_batches.number
+            FROM
+                l1_batches
+                JOIN eth_txs ON l1_batches.eth_prove_tx_id = eth_txs.id
+            WHERE
+                eth_txs.confirmed_eth_tx_history_id IS NOT NULL
+            ORDER BY
+                l1_batches.number DESC
+            LIMIT
+                1
+            "#
+        )
+        .instrument("get_last_l1_batch_with_confirmed_prove_tx")
+        .fetch_optional(self.storage.conn())
+        .await?;
+
+        Ok(row.map(|row| L1BatchNumber(row.number as u32)))
+    }
+
     /// Returns the number of the last L1 batch for which an Ethereum prove tx was sent and confirmed.
     pub async fn get_number_of_last_l1_batch_proven_on_eth(
         &mut self,
@@ -1905,11 +2113,12 @@ impl BlocksDal<'_, '_> {
         &mut self,
         l1_batch_number: L1BatchNumber,
     ) -> sqlx::Result<HashMap<H256, Vec<u8>>> {
-        Ok(sqlx::query!(
+        sqlx::query!(
             r#"
             SELECT
                 bytecode_hash,
-                bytecode
+                bytecode,
+                bytecode_compressed
             FROM
                 factory_deps
                 INNER JOIN miniblocks ON miniblocks.number = factory_deps.miniblock_number
@@ -1921,8 +2130,11 @@ impl BlocksDal<'_, '_> {
         .fetch_all(self.storage.conn())
         .await?
         .into_iter()
-        .map(|row| (H256::from_slice(&row.bytecode_hash), row.bytecode))
-        .collect())
+        .map(|row| {
+            let bytecode = decode_bytecode(row.bytecode, row.bytecode_compressed)?;
+            Ok((H256::from_slice(&row.bytecode_hash), bytecode))
+        })
+        .collect()
     }
 
     /// Deletes all L1 batches from the storage so that the specified batch number is the last one left.
@@ -1938,6 +2150,18 @@ impl BlocksDal<'_, '_> {
         last_batch_to_keep: Option<L1BatchNumber>,
     ) -> sqlx::Result<()> {
         let block_number = last_batch_to_keep.map_or(-1, |number| number.0 as i64);
+        // Initial writes are not linked to L1 batches via a foreign key (since initial writes recovered
+        // from a snapshot reference non-existing batches), so they need to be removed explicitly.
+        sqlx::query!(
+            r#"
+            DELETE FROM initial_writes
+            WHERE
+                l1_batch_number > $1
+            "#,
+            block_number
+        )
+        .execute(self.storage.conn())
+        .await?;
         sqlx::query!(
             r#"
             DELETE FROM l1_batches
@@ -2302,12 +2526,13 @@ impl BlocksDal<'_, '_> {
 mod tests {
     use zksync_contracts::BaseSystemContractsHashes;
     use zksync_types::{
+        fee_model::PubdataIndependentBatchFeeModelInput,
         l2_to_l1_log::{L2ToL1Log, UserL2ToL1Log},
         Address, ProtocolVersion, ProtocolVersionId,
     };
 
     use super::*;
-    use crate::ConnectionPool;
+    use crate::{tests::create_miniblock_header, ConnectionPool};
 
     #[tokio::test]
     async fn loading_l1_batch_header() {
@@ -2434,4 +2659,35 @@ mod tests {
             assert_eq!(gas, 3 * expected_gas);
         }
     }
+
+    #[tokio::test]
+    async fn miniblock_fee_input_roundtrip() {
+        let pool = ConnectionPool::test_pool().await;
+        let mut conn = pool.access_storage().await.unwrap();
+        conn.protocol_versions_dal()
+            .save_protocol_version_with_tx(ProtocolVersion::default())
+            .await;
+
+        let l1_pegged_header = create_miniblock_header(0);
+        let mut pubdata_independent_header = create_miniblock_header(1);
+        pubdata_independent_header.batch_fee_input =
+            BatchFeeInput::PubdataIndependent(PubdataIndependentBatchFeeModelInput {
+                l1_gas_price: 100,
+                fair_l2_gas_price: 200,
+                fair_pubdata_price: 3_000,
+            });
+        for header in [&l1_pegged_header, &pubdata_independent_header] {
+            conn.blocks_dal().insert_miniblock(header).await.unwrap();
+        }
+
+        for header in [&l1_pegged_header, &pubdata_independent_header] {
+            let loaded_header = conn
+                .blocks_dal()
+                .get_miniblock_header(header.number)
+                .await
+                .unwrap()
+                .unwrap();
+            assert_eq!(loaded_header.batch_fee_input, header.batch_fee_input);
+        }
+    }
 }
@@ -1,5 +1,10 @@
+use std::{ops, str::FromStr};
+
 use bigdecimal::BigDecimal;
-use sqlx::Row;
+use sqlx::{
+    types::chrono::{DateTime, NaiveDateTime, Utc},
+    Row,
+};
 use zksync_system_constants::EMPTY_UNCLES_HASH;
 use zksync_types::{
     api,
@@ -24,7 +29,17 @@ use crate::{
     StorageProcessor,
 };
 
-const BLOCK_GAS_LIMIT: u32 = system_params::VM_INITIAL_FRAME_ERGS;
+/// Gas limit reported for miniblocks in the Web3 API.
+pub const BLOCK_GAS_LIMIT: u32 = system_params::VM_INITIAL_FRAME_ERGS;
+
+/// Information about a transaction used to compute rewards in `eth_feeHistory`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FeeHistoryTransaction {
+    pub miniblock_number: MiniblockNumber,
+    /// Effective gas price of the transaction minus the base fee of the including miniblock.
+    pub priority_fee_per_gas: U256,
+    pub gas_used: U256,
+}
 
 #[derive(Debug)]
 pub struct BlocksWeb3Dal<'a, 'c> {
@@ -42,7 +57,6 @@ impl BlocksWeb3Dal<'_, '_> {
             "#
         )
         .instrument("get_sealed_block_number")
-        .report_latency()
         .fetch_one(self.storage.conn())
         .await?
         .number
@@ -60,7 +74,6 @@ impl BlocksWeb3Dal<'_, '_> {
             "#
         )
         .instrument("get_sealed_block_number")
-        .report_latency()
         .fetch_one(self.storage.conn())
         .await?
         .number
@@ -268,7 +281,9 @@ impl BlocksWeb3Dal<'_, '_> {
                 "SELECT number FROM miniblocks WHERE number = $1".to_owned()
             }
             api::BlockId::Number(api::BlockNumber::Earliest) => {
-                return Ok(Some(MiniblockNumber(0)));
+                // Miniblock-level data for the earliest blocks may be pruned.
+                let pruning_info = self.storage.pruning_dal().get_pruning_info().await?;
+                return Ok(Some(pruning_info.earliest_available_miniblock()));
             }
             api::BlockId::Number(block_number) => web3_block_number_to_sql(block_number),
         };
@@ -503,6 +518,48 @@ impl BlocksWeb3Dal<'_, '_> {
         Ok(result)
     }
 
+    /// Returns priority fees and gas used by transactions in the specified miniblock range, ordered by miniblock number
+    /// and then by the priority fee. Used to compute rewards in `eth_feeHistory`.
+    pub async fn get_fee_history_transactions(
+        &mut self,
+        miniblocks: ops::RangeInclusive<MiniblockNumber>,
+    ) -> sqlx::Result<Vec<FeeHistoryTransaction>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT
+                transactions.miniblock_number AS "miniblock_number!",
+                GREATEST(
+                    COALESCE(transactions.effective_gas_price, miniblocks.base_fee_per_gas) - miniblocks.base_fee_per_gas,
+                    0
+                ) AS "priority_fee_per_gas!",
+                GREATEST(transactions.gas_limit - transactions.refunded_gas, 0) AS "gas_used!"
+            FROM
+                transactions
+                INNER JOIN miniblocks ON miniblocks.number = transactions.miniblock_number
+            WHERE
+                transactions.miniblock_number BETWEEN $1 AND $2
+            ORDER BY
+                transactions.miniblock_number,
+                "priority_fee_per_gas!"
+            "#,
+            i64::from(miniblocks.start().0),
+            i64::from(miniblocks.end().0)
+        )
+        .instrument("get_fee_history_transactions")
+        .with_arg("miniblocks", &miniblocks)
+        .fetch_all(self.storage.conn())
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| FeeHistoryTransaction {
+                miniblock_number: MiniblockNumber(row.miniblock_number as u32),
+                priority_fee_per_gas: bigdecimal_to_u256(row.priority_fee_per_gas),
+                gas_used: bigdecimal_to_u256(row.gas_used),
+            })
+            .collect())
+    }
+
     pub async fn get_block_details(
         &mut self,
         block_number: MiniblockNumber,
@@ -561,7 +618,6 @@ impl BlocksWeb3Dal<'_, '_> {
             )
             .instrument("get_block_details")
             .with_arg("block_number", &block_number)
-            .report_latency()
             .fetch_optional(self.storage.conn())
             .await?;
 
@@ -616,13 +672,151 @@ impl BlocksWeb3Dal<'_, '_> {
             )
             .instrument("get_l1_batch_details")
             .with_arg("l1_batch_number", &l1_batch_number)
-            .report_latency()
             .fetch_optional(self.storage.conn())
             .await?;
 
             Ok(l1_batch_details.map(api::L1BatchDetails::from))
         }
     }
+
+    /// Returns L1 batch status updates (i.e., commit / prove / execute L1 transactions confirmed
+    /// after `from_timestamp`), ordered by the time they were recorded in the DB. The second returned value
+    /// is the cursor to be used for the next call.
+    pub async fn get_l1_batch_status_updates_after(
+        &mut self,
+        from_timestamp: NaiveDateTime,
+        limit: Option<usize>,
+    ) -> sqlx::Result<(Vec<api::L1BatchStatusUpdate>, Option<NaiveDateTime>)> {
+        let records = sqlx::query!(
+            r#"
+            SELECT
+                l1_batches.number,
+                eth_txs_history.tx_hash,
+                eth_txs_history.confirmed_at AS "confirmed_at!",
+                eth_txs_history.updated_at,
+                (l1_batches.eth_commit_tx_id = eth_txs_history.eth_tx_id) AS "is_commit!",
+                (l1_batches.eth_prove_tx_id = eth_txs_history.eth_tx_id) AS "is_prove!"
+            FROM
+                eth_txs_history
+                JOIN l1_batches ON eth_txs_history.eth_tx_id IN (
+                    l1_batches.eth_commit_tx_id,
+                    l1_batches.eth_prove_tx_id,
+                    l1_batches.eth_execute_tx_id
+                )
+            WHERE
+                eth_txs_history.confirmed_at IS NOT NULL
+                AND eth_txs_history.updated_at > $1
+            ORDER BY
+                eth_txs_history.updated_at,
+                l1_batches.number
+            LIMIT
+                $2
+            "#,
+            from_timestamp,
+            limit.map(|limit| limit as i64)
+        )
+        .instrument("get_l1_batch_status_updates_after")
+        .with_arg("from_timestamp", &from_timestamp)
+        .fetch_all(self.storage.conn())
+        .await?;
+
+        let last_timestamp = records.last().map(|record| record.updated_at);
+        let updates = records
+            .into_iter()
+            .map(|record| {
+                let stage = if record.is_commit {
+                    api::L1BatchStage::Committed
+                } else if record.is_prove {
+                    api::L1BatchStage::Proven
+                } else {
+                    api::L1BatchStage::Executed
+                };
+                api::L1BatchStatusUpdate {
+                    l1_batch_number: L1BatchNumber(record.number as u32),
+                    stage,
+                    l1_tx_hash: H256::from_str(&record.tx_hash).expect("Incorrect L1 tx hash"),
+                    happened_at: DateTime::from_naive_utc_and_offset(record.confirmed_at, Utc),
+                }
+            })
+            .collect();
+        Ok((updates, last_timestamp))
+    }
+
+    /// Returns commit / prove / execute L1 transactions created for the specified L1 batch, or `None`
+    /// if the batch doesn't exist.
+    pub async fn get_l1_batch_commitment_artifacts(
+        &mut self,
+        l1_batch_number: L1BatchNumber,
+    ) -> sqlx::Result<Option<api::L1BatchCommitmentArtifacts>> {
+        let records = sqlx::query!(
+            r#"
+            SELECT
+                l1_batches.number,
+                eth_txs.raw_tx AS "raw_tx?",
+                eth_txs.predicted_gas_cost AS "predicted_gas_cost?",
+                eth_txs.gas_used,
+                eth_txs_history.tx_hash AS "tx_hash?",
+                eth_txs_history.base_fee_per_gas AS "base_fee_per_gas?",
+                eth_txs_history.priority_fee_per_gas AS "priority_fee_per_gas?",
+                eth_txs_history.confirmed_at,
+                (l1_batches.eth_commit_tx_id = eth_txs.id) AS "is_commit?",
+                (l1_batches.eth_prove_tx_id = eth_txs.id) AS "is_prove?"
+            FROM
+                l1_batches
+                LEFT JOIN eth_txs ON eth_txs.id IN (
+                    l1_batches.eth_commit_tx_id,
+                    l1_batches.eth_prove_tx_id,
+                    l1_batches.eth_execute_tx_id
+                )
+                LEFT JOIN eth_txs_history ON eth_txs_history.id = eth_txs.confirmed_eth_tx_history_id
+            WHERE
+                l1_batches.number = $1
+            "#,
+            l1_batch_number.0 as i64
+        )
+        .instrument("get_l1_batch_commitment_artifacts")
+        .with_arg("l1_batch_number", &l1_batch_number)
+        .fetch_all(self.storage.conn())
+        .await?;
+
+        if records.is_empty() {
+            return Ok(None);
+        }
+        let mut artifacts = api::L1BatchCommitmentArtifacts {
+            number: l1_batch_number,
+            commit: None,
+            prove: None,
+            execute: None,
+        };
+        for record in records {
+            let (Some(raw_tx), Some(predicted_gas_cost)) =
+                (record.raw_tx, record.predicted_gas_cost)
+            else {
+                continue; // The batch has no L1 transactions yet
+            };
+            let tx_artifacts = api::L1BatchEthTxArtifacts {
+                calldata: raw_tx.into(),
+                predicted_gas_cost: predicted_gas_cost as u64,
+                tx_hash: record
+                    .tx_hash
+                    .map(|hash| H256::from_str(&hash).expect("Incorrect L1 tx hash")),
+                gas_used: record.gas_used.map(|gas| gas as u64),
+                base_fee_per_gas: record.base_fee_per_gas.map(|fee| fee as u64),
+                priority_fee_per_gas: record.priority_fee_per_gas.map(|fee| fee as u64),
+                confirmed_at: record
+                    .confirmed_at
+                    .map(|time| DateTime::from_naive_utc_and_offset(time, Utc)),
+            };
+            if record.is_commit == Some(true) {
+                artifacts.commit = Some(tx_artifacts);
+            } else if record.is_prove == Some(true) {
+                artifacts.prove = Some(tx_artifacts);
+            } else {
+                artifacts.execute = Some(tx_artifacts);
+            }
+        }
+        Ok(Some(artifacts))
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,297 @@
+use zksync_types::{Address, Nonce, H256};
+
+use crate::{instrument::InstrumentExt, StorageProcessor};
+
+/// Pending L2 transaction removed from the mempool by the operator.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CancelledTransaction {
+    /// Sequential ID of the cancellation.
+    pub id: i64,
+    pub tx_hash: H256,
+    pub initiator_address: Address,
+    pub nonce: Nonce,
+}
+
+#[derive(Debug)]
+pub struct CancelledTransactionsDal<'a, 'c> {
+    pub(crate) storage: &'a mut StorageProcessor<'c>,
+}
+
+/// Pending L2 transaction locked for cancellation.
+#[derive(Debug)]
+struct LockedTransaction {
+    hash: H256,
+    initiator_address: Address,
+    nonce: Nonce,
+    in_mempool: bool,
+}
+
+impl CancelledTransactionsDal<'_, '_> {
+    /// Removes a pending L2 transaction with the specified hash and records the cancellation.
+    /// Returns `None` if there is no such transaction, it is already included into a miniblock, or it is loaded
+    /// into the state keeper mempool and `remove_from_mempool` returns `false` (i.e., the transaction is already
+    /// picked up by the state keeper).
+    ///
+    /// The transaction row is locked for the duration of the cancellation, so it cannot be concurrently loaded
+    /// into the mempool; `remove_from_mempool` is only called for transactions that are already loaded.
+    pub async fn cancel_transaction_by_hash(
+        &mut self,
+        tx_hash: H256,
+        reason: Option<&str>,
+        remove_from_mempool: impl FnOnce(Address, Nonce) -> bool,
+    ) -> sqlx::Result<Option<CancelledTransaction>> {
+        let mut transaction = self.storage.start_transaction().await?;
+        let row = sqlx::query!(
+            r#"
+            SELECT
+                hash,
+                initiator_address,
+                nonce,
+                in_mempool
+            FROM
+                transactions
+            WHERE
+                hash = $1
+                AND miniblock_number IS NULL
+                AND is_priority = FALSE
+            FOR UPDATE
+            "#,
+            tx_hash.as_bytes()
+        )
+        .instrument("cancel_transaction_by_hash#lock")
+        .with_arg("tx_hash", &tx_hash)
+        .fetch_optional(transaction.conn())
+        .await?;
+
+        let Some(row) = row else {
+            return Ok(None);
+        };
+        let locked = LockedTransaction {
+            hash: H256::from_slice(&row.hash),
+            initiator_address: Address::from_slice(&row.initiator_address),
+            nonce: Nonce(row.nonce.expect("L2 transactions always have nonce") as u32),
+            in_mempool: row.in_mempool,
+        };
+        Self::cancel_locked_transaction(transaction, locked, reason, remove_from_mempool).await
+    }
+
+    /// Removes a pending L2 transaction with the specified initiator and nonce, and records the cancellation.
+    /// Has the same semantics as [`Self::cancel_transaction_by_hash()`].
+    pub async fn cancel_transaction_by_nonce(
+        &mut self,
+        initiator_address: Address,
+        nonce: Nonce,
+        reason: Option<&str>,
+        remove_from_mempool: impl FnOnce(Address, Nonce) -> bool,
+    ) -> sqlx::Result<Option<CancelledTransaction>> {
+        let mut transaction = self.storage.start_transaction().await?;
+        let row = sqlx::query!(
+            r#"
+            SELECT
+                hash,
+                in_mempool
+            FROM
+                transactions
+            WHERE
+                initiator_address = $1
+                AND nonce = $2
+                AND miniblock_number IS NULL
+                AND is_priority = FALSE
+            FOR UPDATE
+            "#,
+            initiator_address.as_bytes(),
+            i64::from(nonce.0)
+        )
+        .instrument("cancel_transaction_by_nonce#lock")
+        .with_arg("initiator_address", &initiator_address)
+        .with_arg("nonce", &nonce)
+        .fetch_optional(transaction.conn())
+        .await?;
+
+        let Some(row) = row else {
+            return Ok(None);
+        };
+        let locked = LockedTransaction {
+            hash: H256::from_slice(&row.hash),
+            initiator_address,
+            nonce,
+            in_mempool: row.in_mempool,
+        };
+        Self::cancel_locked_transaction(transaction, locked, reason, remove_from_mempool).await
+    }
+
+    async fn cancel_locked_transaction(
+        mut transaction: StorageProcessor<'_>,
+        locked: LockedTransaction,
+        reason: Option<&str>,
+        remove_from_mempool: impl FnOnce(Address, Nonce) -> bool,
+    ) -> sqlx::Result<Option<CancelledTransaction>> {
+        if locked.in_mempool && !remove_from_mempool(locked.initiator_address, locked.nonce) {
+            // The transaction is already picked up by the state keeper; the DB transaction is rolled back on drop.
+            return Ok(None);
+        }
+
+        sqlx::query!(
+            r#"
+            DELETE FROM transactions
+            WHERE
+                hash = $1
+            "#,
+            locked.hash.as_bytes()
+        )
+        .instrument("cancel_locked_transaction#delete")
+        .with_arg("tx_hash", &locked.hash)
+        .execute(transaction.conn())
+        .await?;
+
+        let id = sqlx::query_scalar!(
+            r#"
+            INSERT INTO
+                cancelled_transactions (tx_hash, initiator_address, nonce, reason, created_at)
+            VALUES
+                ($1, $2, $3, $4, NOW())
+            RETURNING
+                id
+            "#,
+            locked.hash.as_bytes(),
+            locked.initiator_address.as_bytes(),
+            i64::from(locked.nonce.0),
+            reason
+        )
+        .instrument("cancel_locked_transaction#insert")
+        .with_arg("tx_hash", &locked.hash)
+        .fetch_one(transaction.conn())
+        .await?;
+        transaction.commit().await?;
+
+        Ok(Some(CancelledTransaction {
+            id,
+            tx_hash: locked.hash,
+            initiator_address: locked.initiator_address,
+            nonce: locked.nonce,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zksync_types::{fee::TransactionExecutionMetrics, l2::L2Tx};
+
+    use super::*;
+    use crate::{tests::mock_l2_transaction, ConnectionPool};
+
+    async fn insert_pending_tx(storage: &mut StorageProcessor<'_>) -> L2Tx {
+        let tx = mock_l2_transaction();
+        storage
+            .transactions_dal()
+            .insert_transaction_l2(tx.clone(), TransactionExecutionMetrics::default())
+            .await;
+        tx
+    }
+
+    fn not_in_mempool(_: Address, _: Nonce) -> bool {
+        panic!("transaction is not loaded into mempool");
+    }
+
+    #[tokio::test]
+    async fn cancelling_pending_transactions() {
+        let pool = ConnectionPool::test_pool().await;
+        let mut storage = pool.access_storage().await.unwrap();
+        storage
+            .protocol_versions_dal()
+            .save_protocol_version_with_tx(Default::default())
+            .await;
+
+        let tx = insert_pending_tx(&mut storage).await;
+        let cancelled = storage
+            .cancelled_transactions_dal()
+            .cancel_transaction_by_hash(tx.hash(), Some("test"), not_in_mempool)
+            .await
+            .unwrap()
+            .expect("transaction was not cancelled");
+        assert_eq!(cancelled.tx_hash, tx.hash());
+        assert_eq!(cancelled.initiator_address, tx.initiator_account());
+        assert_eq!(cancelled.nonce, tx.nonce());
+        assert!(storage
+            .transactions_dal()
+            .get_tx_by_hash(tx.hash())
+            .await
+            .is_none());
+
+        // Repeated cancellation should be a no-op.
+        let cancelled_again = storage
+            .cancelled_transactions_dal()
+            .cancel_transaction_by_hash(tx.hash(), None, not_in_mempool)
+            .await
+            .unwrap();
+        assert_eq!(cancelled_again, None);
+
+        let other_tx = insert_pending_tx(&mut storage).await;
+        let other_cancelled = storage
+            .cancelled_transactions_dal()
+            .cancel_transaction_by_nonce(
+                other_tx.initiator_account(),
+                other_tx.nonce(),
+                None,
+                not_in_mempool,
+            )
+            .await
+            .unwrap()
+            .expect("transaction was not cancelled");
+        assert_eq!(other_cancelled.tx_hash, other_tx.hash());
+        assert_eq!(other_cancelled.id, cancelled.id + 1);
+    }
+
+    #[tokio::test]
+    async fn cancelling_transactions_loaded_into_mempool() {
+        let pool = ConnectionPool::test_pool().await;
+        let mut storage = pool.access_storage().await.unwrap();
+        storage
+            .protocol_versions_dal()
+            .save_protocol_version_with_tx(Default::default())
+            .await;
+
+        let tx = insert_pending_tx(&mut storage).await;
+        let (loaded_txs, _) = storage
+            .transactions_dal()
+            .sync_mempool(vec![], vec![], 0, 0, 10)
+            .await;
+        assert_eq!(loaded_txs.len(), 1);
+
+        // The transaction is already picked up by the state keeper, so it cannot be cancelled.
+        let cancelled = storage
+            .cancelled_transactions_dal()
+            .cancel_transaction_by_nonce(
+                tx.initiator_account(),
+                tx.nonce(),
+                None,
+                |account, nonce| {
+                    assert_eq!(account, tx.initiator_account());
+                    assert_eq!(nonce, tx.nonce());
+                    false
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(cancelled, None);
+        assert!(storage
+            .transactions_dal()
+            .get_tx_by_hash(tx.hash())
+            .await
+            .is_some());
+
+        // The transaction is successfully removed from the mempool.
+        let cancelled = storage
+            .cancelled_transactions_dal()
+            .cancel_transaction_by_hash(tx.hash(), None, |_, _| true)
+            .await
+            .unwrap()
+            .expect("transaction was not cancelled");
+        assert_eq!(cancelled.tx_hash, tx.hash());
+        assert!(storage
+            .transactions_dal()
+            .get_tx_by_hash(tx.hash())
+            .await
+            .is_none());
+    }
+}
@@ -1,4 +1,11 @@
-use std::{env, fmt, time::Duration};
+use std::{
+    env, fmt,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
 
 use anyhow::Context as _;
 use sqlx::{
@@ -6,9 +13,13 @@ use sqlx::{
     postgres::{PgConnectOptions, PgPool, PgPoolOptions, Postgres},
 };
 
-use crate::{metrics::CONNECTION_METRICS, StorageProcessor};
+use crate::{
+    metrics::{ReplicaRoutingTarget, CONNECTION_METRICS},
+    StorageProcessor,
+};
 
 pub mod holder;
+mod retries;
 
 /// Obtains the test database URL from the environment variable.
 fn get_test_database_url() -> anyhow::Result<String> {
@@ -18,6 +29,42 @@ fn get_test_database_url() -> anyhow::Result<String> {
     )
 }
 
+/// Global DB connection parameters applying to all [`ConnectionPool`]s in the process.
+#[derive(Debug)]
+pub struct GlobalConnectionPoolConfig {
+    slow_query_threshold_ms: AtomicU64,
+}
+
+impl GlobalConnectionPoolConfig {
+    const DEFAULT_SLOW_QUERY_THRESHOLD: Duration = Duration::from_millis(100);
+
+    const fn new() -> Self {
+        Self {
+            slow_query_threshold_ms: AtomicU64::new(
+                Self::DEFAULT_SLOW_QUERY_THRESHOLD.as_millis() as u64
+            ),
+        }
+    }
+
+    pub(crate) fn slow_query_threshold(&self) -> Duration {
+        Duration::from_millis(self.slow_query_threshold_ms.load(Ordering::Relaxed))
+    }
+
+    /// Sets the threshold for slow queries and connection acquisitions. Slow queries are logged
+    /// with their arguments and counted in metrics. The default threshold is 100ms.
+    pub fn set_slow_query_threshold(&self, threshold: Duration) -> anyhow::Result<&Self> {
+        let millis =
+            u64::try_from(threshold.as_millis()).context("slow query threshold is too large")?;
+        anyhow::ensure!(millis > 0, "slow query threshold must be positive");
+        self.slow_query_threshold_ms
+            .store(millis, Ordering::Relaxed);
+        Ok(self)
+    }
+}
+
+pub(crate) static GLOBAL_CONNECTION_POOL_CONFIG: GlobalConnectionPoolConfig =
+    GlobalConnectionPoolConfig::new();
+
 /// Builder for [`ConnectionPool`]s.
 pub struct ConnectionPoolBuilder {
     database_url: String,
@@ -70,6 +117,7 @@ impl ConnectionPoolBuilder {
         Ok(ConnectionPool {
             inner: pool,
             max_size: self.max_size,
+            master_fallback: None,
         })
     }
 }
@@ -120,10 +168,78 @@ pub(super) async fn create_test_db() -> anyhow::Result<url::Url> {
     Ok(db_url)
 }
 
+/// Master pool attached to a replica [`ConnectionPool`] together with the replica staleness guard.
+#[derive(Debug)]
+struct MasterFallback {
+    master_pool: ConnectionPool,
+    max_replication_lag: Duration,
+    /// Last observed replica freshness and the time it was checked at.
+    last_check: Mutex<Option<(Instant, bool)>>,
+}
+
+impl MasterFallback {
+    /// Minimum interval between replication lag checks.
+    const LAG_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+    /// Returns `true` if the replica is fresh enough to serve reads. The result of the check is cached
+    /// for [`Self::LAG_CHECK_INTERVAL`], so that we don't query Postgres on each connection acquisition.
+    async fn is_fresh(&self, replica_pool: &ConnectionPool) -> bool {
+        let cached = *self.last_check.lock().unwrap();
+        if let Some((checked_at, is_fresh)) = cached {
+            if checked_at.elapsed() < Self::LAG_CHECK_INTERVAL {
+                return is_fresh;
+            }
+        }
+
+        let is_fresh = match Self::replication_lag(replica_pool).await {
+            Ok(lag) => {
+                CONNECTION_METRICS.replication_lag.set(lag);
+                let is_fresh = lag <= self.max_replication_lag;
+                if !is_fresh {
+                    tracing::info!(
+                        "Replication lag {lag:?} exceeds the threshold {:?}; routing read-after-write reads to master",
+                        self.max_replication_lag
+                    );
+                }
+                is_fresh
+            }
+            Err(err) => {
+                tracing::warn!(
+                    "Failed checking replication lag, routing read-after-write reads to master: {err:#}"
+                );
+                false
+            }
+        };
+        *self.last_check.lock().unwrap() = Some((Instant::now(), is_fresh));
+        is_fresh
+    }
+
+    async fn replication_lag(replica_pool: &ConnectionPool) -> anyhow::Result<Duration> {
+        // If the replica has replayed all received WAL, it's up to date regardless of the last replay timestamp
+        // (which may be arbitrarily old if the master is idle). `pg_last_xact_replay_timestamp()` is NULL
+        // if the database is not a replica; we treat this case as no lag.
+        const LAG_QUERY: &str = "SELECT CASE \
+            WHEN pg_last_wal_receive_lsn() = pg_last_wal_replay_lsn() THEN 0 \
+            ELSE COALESCE(EXTRACT(EPOCH FROM NOW() - pg_last_xact_replay_timestamp()), 0) \
+            END::FLOAT8";
+
+        let mut conn = replica_pool
+            .acquire_connection_retried()
+            .await
+            .context("acquire_connection_retried()")?;
+        let lag_secs: f64 = sqlx::query_scalar(LAG_QUERY)
+            .fetch_one(&mut *conn)
+            .await
+            .context("failed querying replication lag")?;
+        Ok(Duration::try_from_secs_f64(lag_secs.max(0.0)).unwrap_or(Duration::MAX))
+    }
+}
+
 #[derive(Clone)]
 pub struct ConnectionPool {
     pub(crate) inner: PgPool,
     max_size: u32,
+    master_fallback: Option<Arc<MasterFallback>>,
 }
 
 impl fmt::Debug for ConnectionPool {
@@ -131,6 +247,7 @@ impl fmt::Debug for ConnectionPool {
         formatter
             .debug_struct("ConnectionPool")
             .field("max_size", &self.max_size)
+            .field("master_fallback", &self.master_fallback)
             .finish_non_exhaustive()
     }
 }
@@ -149,6 +266,11 @@ impl ConnectionPool {
             .unwrap()
     }
 
+    /// Returns the global configuration applying to all connection pools.
+    pub fn global_config() -> &'static GlobalConnectionPoolConfig {
+        &GLOBAL_CONNECTION_POOL_CONFIG
+    }
+
     /// Initializes a builder for connection pools.
     pub fn builder(database_url: &str, max_pool_size: u32) -> ConnectionPoolBuilder {
         ConnectionPoolBuilder {
@@ -164,6 +286,23 @@ impl ConnectionPool {
         Self::builder(database_url, 1)
     }
 
+    /// Attaches the master pool to this (replica) pool. Connections acquired with
+    /// [`Self::access_storage_read_after_write()`] will be routed to the master pool if the replication lag
+    /// of this pool (as reported by Postgres) exceeds `max_replication_lag`. Other methods are unaffected
+    /// and always use this pool.
+    pub fn with_master_fallback(
+        mut self,
+        master_pool: ConnectionPool,
+        max_replication_lag: Duration,
+    ) -> Self {
+        self.master_fallback = Some(Arc::new(MasterFallback {
+            master_pool,
+            max_replication_lag,
+            last_check: Mutex::new(None),
+        }));
+        self
+    }
+
     /// Returns the maximum number of connections in this pool specified during its creation.
     /// This number may be distinct from the current number of connections in the pool (including
     /// idle ones).
@@ -195,6 +334,30 @@ impl ConnectionPool {
         self.access_storage_inner(Some(requester)).await
     }
 
+    /// Acquires a connection for queries that must observe the caller's own recent writes (e.g., reading
+    /// the account nonce or a transaction receipt after submitting a transaction). If this pool has the master
+    /// pool attached (see [`Self::with_master_fallback()`]) and the replication lag exceeds the threshold,
+    /// the connection is acquired from the master pool; otherwise, this method is equivalent
+    /// to [`Self::access_storage_tagged()`].
+    pub async fn access_storage_read_after_write(
+        &self,
+        requester: &'static str,
+    ) -> anyhow::Result<StorageProcessor<'_>> {
+        let Some(fallback) = &self.master_fallback else {
+            return self.access_storage_inner(Some(requester)).await;
+        };
+        if fallback.is_fresh(self).await {
+            CONNECTION_METRICS.replica_routing[&ReplicaRoutingTarget::Replica].inc();
+            self.access_storage_inner(Some(requester)).await
+        } else {
+            CONNECTION_METRICS.replica_routing[&ReplicaRoutingTarget::Master].inc();
+            fallback
+                .master_pool
+                .access_storage_inner(Some(requester))
+                .await
+        }
+    }
+
     async fn access_storage_inner(
         &self,
         requester: Option<&'static str>,
@@ -208,6 +371,16 @@ impl ConnectionPool {
         if let Some(requester) = requester {
             CONNECTION_METRICS.acquire_tagged[&requester].observe(elapsed);
         }
+
+        if elapsed > GLOBAL_CONNECTION_POOL_CONFIG.slow_query_threshold() {
+            let requester = requester.unwrap_or("untagged");
+            tracing::info!(
+                "Acquiring DB connection for `{requester}` took {elapsed:?}; the connection pool \
+                 (max size: {}) may be saturated",
+                self.max_size
+            );
+            CONNECTION_METRICS.acquire_slow[&requester].inc();
+        }
         Ok(StorageProcessor::from_pool(conn))
     }
 
@@ -221,8 +394,9 @@ impl ConnectionPool {
                 .pool_size
                 .observe(self.inner.size() as usize);
             CONNECTION_METRICS.pool_idle.observe(self.inner.num_idle());
+            self.report_saturation();
 
-            let connection = self.inner.acquire().await;
+            let connection = self.acquire_connection().await;
             let connection_err = match connection {
                 Ok(connection) => return Ok(connection),
                 Err(err) => {
@@ -239,7 +413,7 @@ impl ConnectionPool {
         }
 
         // Attempting to get the pooled connection for the last time
-        match self.inner.acquire().await {
+        match self.acquire_connection().await {
             Ok(conn) => Ok(conn),
             Err(err) => {
                 Self::report_connection_error(&err);
@@ -248,6 +422,27 @@ impl ConnectionPool {
         }
     }
 
+    async fn acquire_connection(&self) -> Result<PoolConnection<Postgres>, sqlx::Error> {
+        /// Guard decrementing the number of waiting requesters on drop, even if the acquisition is cancelled.
+        struct WaitingGuard;
+
+        impl Drop for WaitingGuard {
+            fn drop(&mut self) {
+                CONNECTION_METRICS.pool_waiting.dec_by(1);
+            }
+        }
+
+        CONNECTION_METRICS.pool_waiting.inc_by(1);
+        let _guard = WaitingGuard;
+        self.inner.acquire().await
+    }
+
+    fn report_saturation(&self) {
+        let used_connections = self.inner.size() as usize - self.inner.num_idle();
+        let saturation = used_connections as f64 / f64::from(self.max_size.max(1));
+        CONNECTION_METRICS.pool_saturation.observe(saturation);
+    }
+
     fn report_connection_error(err: &sqlx::Error) {
         CONNECTION_METRICS.pool_acquire_error[&err.into()].inc();
     }
@@ -259,6 +454,18 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    fn configuring_slow_query_threshold() {
+        let config = GlobalConnectionPoolConfig::new();
+        assert_eq!(config.slow_query_threshold(), Duration::from_millis(100));
+        config
+            .set_slow_query_threshold(Duration::from_secs(1))
+            .unwrap();
+        assert_eq!(config.slow_query_threshold(), Duration::from_secs(1));
+        config.set_slow_query_threshold(Duration::ZERO).unwrap_err();
+        assert_eq!(config.slow_query_threshold(), Duration::from_secs(1));
+    }
+
     #[tokio::test]
     async fn setting_statement_timeout() {
         let db_url = create_test_db()
@@ -283,4 +490,50 @@ mod tests {
             sqlx::Error::Database(db_err) if db_err.message().contains("statement timeout")
         );
     }
+
+    #[tokio::test]
+    async fn read_after_write_reads_use_fresh_replica() {
+        let db_url = create_test_db()
+            .await
+            .expect("Unable to prepare test database")
+            .to_string();
+        let master = ConnectionPool::singleton(&db_url).build().await.unwrap();
+        // The test DB is not a replica, so its replication lag is reported as zero.
+        let replica = ConnectionPool::singleton(&db_url).build().await.unwrap();
+        let pool = replica.with_master_fallback(master, Duration::from_secs(1));
+
+        assert_eq!(
+            MasterFallback::replication_lag(&pool).await.unwrap(),
+            Duration::ZERO
+        );
+        let fallback = pool.master_fallback.as_ref().unwrap();
+        assert!(fallback.is_fresh(&pool).await);
+
+        // Occupy the only master connection; the read should still succeed since it's routed to the replica.
+        let _master_conn = fallback.master_pool.access_storage().await.unwrap();
+        let mut conn = pool.access_storage_read_after_write("test").await.unwrap();
+        sqlx::query("SELECT 1")
+            .map(drop)
+            .fetch_one(conn.conn())
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn read_after_write_reads_fall_back_to_master() {
+        let db_url = create_test_db()
+            .await
+            .expect("Unable to prepare test database")
+            .to_string();
+        let master = ConnectionPool::singleton(&db_url).build().await.unwrap();
+        let replica = ConnectionPool::singleton(&db_url).build().await.unwrap();
+        let pool = replica.with_master_fallback(master, Duration::from_secs(1));
+
+        let fallback = pool.master_fallback.as_ref().unwrap();
+        *fallback.last_check.lock().unwrap() = Some((Instant::now(), false));
+        // Occupy the only replica connection. Ordinary reads still use the replica, while read-after-write reads
+        // should be routed to master.
+        let _replica_conn = pool.access_storage_tagged("test").await.unwrap();
+        pool.access_storage_read_after_write("test").await.unwrap();
+    }
 }
@@ -0,0 +1,173 @@
+//! Retries for DB transactions aborted because of conflicts with concurrent transactions.
+
+use std::time::Duration;
+
+use futures::future::BoxFuture;
+
+use crate::{metrics::CONNECTION_METRICS, StorageProcessor};
+
+/// SQLSTATE codes of errors after which a transaction can be safely retried.
+/// See [Postgres docs](https://www.postgresql.org/docs/14/errcodes-appendix.html) for details.
+const RETRIABLE_ERROR_CODES: [&str; 2] = [
+    "40001", // `serialization_failure`
+    "40P01", // `deadlock_detected`
+];
+
+/// Checks whether the error (or any of its causes) is a serialization failure or deadlock reported by Postgres.
+fn is_retriable(err: &anyhow::Error) -> bool {
+    err.chain().any(|cause| {
+        let Some(sqlx::Error::Database(db_err)) = cause.downcast_ref::<sqlx::Error>() else {
+            return false;
+        };
+        db_err
+            .code()
+            .map_or(false, |code| RETRIABLE_ERROR_CODES.contains(&code.as_ref()))
+    })
+}
+
+impl StorageProcessor<'_> {
+    /// Maximum number of attempts to execute a transaction in [`Self::with_retries()`].
+    const MAX_TRANSACTION_ATTEMPTS: usize = 5;
+    /// Backoff before the first retry in [`Self::with_retries()`]. It is doubled after each subsequent failure.
+    const INITIAL_RETRY_BACKOFF: Duration = Duration::from_millis(50);
+
+    /// Executes `transaction_fn` in a DB transaction, retrying it with exponential backoff if the transaction
+    /// fails with a serialization failure or deadlock. Other errors are returned immediately. `name` is used
+    /// for logging and metrics.
+    ///
+    /// `transaction_fn` may be called multiple times, so it must not have side effects outside the DB transaction.
+    /// Retries only work if `transaction_fn` propagates DB errors; DAL methods that panic on errors
+    /// must not be used in it.
+    /// Data borrowed by the transaction should be passed via `context` rather than captured by the closure.
+    ///
+    /// If this processor is already in a transaction, `transaction_fn` is executed in a nested transaction
+    /// without retries, since the error aborts the outer transaction as well.
+    pub async fn with_retries<T, C, F>(
+        &mut self,
+        name: &'static str,
+        context: &C,
+        mut transaction_fn: F,
+    ) -> anyhow::Result<T>
+    where
+        C: ?Sized + Sync,
+        F: for<'t> FnMut(&'t mut StorageProcessor<'_>, &'t C) -> BoxFuture<'t, anyhow::Result<T>>,
+    {
+        let max_attempts = if self.in_transaction() {
+            1
+        } else {
+            Self::MAX_TRANSACTION_ATTEMPTS
+        };
+        let mut backoff = Self::INITIAL_RETRY_BACKOFF;
+        let mut attempt = 1;
+        loop {
+            let result = async {
+                let mut transaction = self.start_transaction().await?;
+                let output = transaction_fn(&mut transaction, context).await?;
+                transaction.commit().await?;
+                anyhow::Ok(output)
+            }
+            .await;
+
+            match result {
+                Err(err) if attempt < max_attempts && is_retriable(&err) => {
+                    tracing::warn!(
+                        "Transaction `{name}` failed on attempt #{attempt}, retrying in {backoff:?}: {err:#}"
+                    );
+                    CONNECTION_METRICS.transaction_retries[&name].inc();
+                    tokio::time::sleep(backoff).await;
+                    backoff *= 2;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::FutureExt as _;
+    use zksync_types::{MiniblockNumber, ProtocolVersion};
+
+    use super::*;
+    use crate::{tests::create_miniblock_header, ConnectionPool};
+
+    const RAISE_SERIALIZATION_FAILURE: &str =
+        "DO $$ BEGIN RAISE EXCEPTION USING ERRCODE = '40001'; END $$";
+
+    #[tokio::test]
+    async fn transaction_is_retried_after_serialization_failure() {
+        let pool = ConnectionPool::test_pool().await;
+        let mut conn = pool.access_storage().await.unwrap();
+        conn.protocol_versions_dal()
+            .save_protocol_version_with_tx(ProtocolVersion::default())
+            .await;
+
+        let header = create_miniblock_header(1);
+        let mut attempts = 0;
+        conn.with_retries("test", &header, |transaction, header| {
+            attempts += 1;
+            let attempt = attempts;
+            async move {
+                transaction.blocks_dal().insert_miniblock(header).await?;
+                if attempt < 3 {
+                    sqlx::query(RAISE_SERIALIZATION_FAILURE)
+                        .execute(transaction.conn())
+                        .await?;
+                }
+                anyhow::Ok(())
+            }
+            .boxed()
+        })
+        .await
+        .unwrap();
+        assert_eq!(attempts, 3);
+
+        // Changes made by failed attempts must be rolled back, so the miniblock is inserted exactly once.
+        let sealed_number = conn
+            .blocks_dal()
+            .get_sealed_miniblock_number()
+            .await
+            .unwrap();
+        assert_eq!(sealed_number, MiniblockNumber(1));
+    }
+
+    #[tokio::test]
+    async fn retries_are_limited() {
+        let pool = ConnectionPool::test_pool().await;
+        let mut conn = pool.access_storage().await.unwrap();
+
+        let mut attempts = 0;
+        let err = conn
+            .with_retries("test", &(), |transaction, _| {
+                attempts += 1;
+                async move {
+                    sqlx::query(RAISE_SERIALIZATION_FAILURE)
+                        .execute(transaction.conn())
+                        .await?;
+                    anyhow::Ok(())
+                }
+                .boxed()
+            })
+            .await
+            .unwrap_err();
+        assert!(is_retriable(&err), "{err:#}");
+        assert_eq!(attempts, StorageProcessor::MAX_TRANSACTION_ATTEMPTS);
+
+        // Non-retriable errors are returned immediately.
+        let mut attempts = 0;
+        conn.with_retries("test", &(), |transaction, _| {
+            attempts += 1;
+            async move {
+                sqlx::query("SELECT 1 / 0")
+                    .execute(transaction.conn())
+                    .await?;
+                anyhow::Ok(())
+            }
+            .boxed()
+        })
+        .await
+        .unwrap_err();
+        assert_eq!(attempts, 1);
+    }
+}
@@ -1,6 +1,17 @@
+use std::ops;
+
+use anyhow::Context as _;
 use zksync_consensus_storage::ReplicaState;
+use zksync_types::{api::en::SyncBlock, Address, MiniblockNumber, Transaction};
 
-use crate::StorageProcessor;
+use crate::{
+    instrument::InstrumentExt,
+    models::{
+        storage_sync::{ConsensusBlockFields, StorageSyncBlock},
+        storage_transaction::StorageTransaction,
+    },
+    StorageProcessor,
+};
 
 #[derive(Debug)]
 pub struct ConsensusDal<'a, 'c> {
@@ -8,6 +19,21 @@ pub struct ConsensusDal<'a, 'c> {
 }
 
 impl ConsensusDal<'_, '_> {
+    /// Version of the latest DB migration that consensus storage depends on.
+    pub const REQUIRED_MIGRATION_VERSION: i64 = 20231128123456;
+
+    /// Checks whether the DB schema includes all migrations required by consensus storage.
+    pub async fn is_schema_compatible(&mut self) -> sqlx::Result<bool> {
+        let (is_compatible,): (bool,) = sqlx::query_as(
+            "SELECT EXISTS (SELECT 1 FROM _sqlx_migrations WHERE version = $1 AND success)",
+        )
+        .bind(Self::REQUIRED_MIGRATION_VERSION)
+        .instrument("is_schema_compatible")
+        .fetch_one(self.storage.conn())
+        .await?;
+        Ok(is_compatible)
+    }
+
     pub async fn replica_state(&mut self) -> anyhow::Result<Option<ReplicaState>> {
         let Some(row) = sqlx::query!(
             r#"
@@ -47,14 +73,166 @@ impl ConsensusDal<'_, '_> {
         .await?;
         Ok(())
     }
+
+    /// Fetches consensus certificates (i.e., consensus fields) for the contiguous run of miniblocks
+    /// starting from `numbers.start`. The run ends at the first miniblock without consensus fields
+    /// or at `numbers.end` (exclusive), whichever comes first.
+    pub async fn certificates_range(
+        &mut self,
+        numbers: ops::Range<MiniblockNumber>,
+    ) -> anyhow::Result<Vec<ConsensusBlockFields>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT
+                number,
+                consensus AS "consensus!"
+            FROM
+                miniblocks
+            WHERE
+                number >= $1
+                AND number < $2
+                AND consensus IS NOT NULL
+            ORDER BY
+                number
+            "#,
+            numbers.start.0 as i64,
+            numbers.end.0 as i64
+        )
+        .instrument("certificates_range")
+        .with_arg("numbers", &numbers)
+        .fetch_all(self.storage.conn())
+        .await?;
+
+        let mut certificates = Vec::with_capacity(rows.len());
+        for (expected_number, row) in (numbers.start.0 as i64..).zip(rows) {
+            if row.number != expected_number {
+                break;
+            }
+            let certificate = zksync_protobuf::serde::deserialize(row.consensus)
+                .with_context(|| format!("consensus fields for miniblock #{expected_number}"))?;
+            certificates.push(certificate);
+        }
+        Ok(certificates)
+    }
+
+    /// Fetches miniblocks with the specified numbers together with their transactions, i.e., all data
+    /// necessary to build consensus payloads. Miniblocks are returned in the ascending order of numbers;
+    /// missing miniblocks are skipped.
+    pub async fn payloads_range(
+        &mut self,
+        numbers: ops::Range<MiniblockNumber>,
+        current_operator_address: Address,
+    ) -> anyhow::Result<Vec<SyncBlock>> {
+        let blocks = sqlx::query_as!(
+            StorageSyncBlock,
+            r#"
+            SELECT
+                miniblocks.number,
+                COALESCE(
+                    miniblocks.l1_batch_number,
+                    (
+                        SELECT
+                            (MAX(number) + 1)
+                        FROM
+                            l1_batches
+                    )
+                ) AS "l1_batch_number!",
+                (
+                    SELECT
+                        MAX(m2.number)
+                    FROM
+                        miniblocks m2
+                    WHERE
+                        miniblocks.l1_batch_number = m2.l1_batch_number
+                ) AS "last_batch_miniblock?",
+                miniblocks.timestamp,
+                miniblocks.l1_gas_price,
+                miniblocks.l2_fair_gas_price,
+                miniblocks.fair_pubdata_price,
+                miniblocks.bootloader_code_hash,
+                miniblocks.default_aa_code_hash,
+                miniblocks.virtual_blocks,
+                miniblocks.hash,
+                miniblocks.consensus,
+                miniblocks.protocol_version AS "protocol_version!",
+                l1_batches.fee_account_address AS "fee_account_address?"
+            FROM
+                miniblocks
+                LEFT JOIN l1_batches ON miniblocks.l1_batch_number = l1_batches.number
+            WHERE
+                miniblocks.number >= $1
+                AND miniblocks.number < $2
+            ORDER BY
+                miniblocks.number
+            "#,
+            numbers.start.0 as i64,
+            numbers.end.0 as i64
+        )
+        .instrument("payloads_range.blocks")
+        .with_arg("numbers", &numbers)
+        .fetch_all(self.storage.conn())
+        .await?;
+
+        let transactions = sqlx::query_as!(
+            StorageTransaction,
+            r#"
+            SELECT
+                *
+            FROM
+                transactions
+            WHERE
+                miniblock_number >= $1
+                AND miniblock_number < $2
+            ORDER BY
+                miniblock_number,
+                index_in_block
+            "#,
+            numbers.start.0 as i64,
+            numbers.end.0 as i64
+        )
+        .instrument("payloads_range.transactions")
+        .with_arg("numbers", &numbers)
+        .fetch_all(self.storage.conn())
+        .await?;
+
+        let mut transactions = transactions.into_iter().peekable();
+        let mut sync_blocks = Vec::with_capacity(blocks.len());
+        for block in blocks {
+            let mut block_transactions = vec![];
+            while let Some(tx) =
+                transactions.next_if(|tx| tx.miniblock_number == Some(block.number))
+            {
+                block_transactions.push(Transaction::from(tx));
+            }
+            let number = block.number;
+            let sync_block = block
+                .into_sync_block(current_operator_address, Some(block_transactions))
+                .with_context(|| format!("miniblock #{number}"))?;
+            sync_blocks.push(sync_block);
+        }
+        Ok(sync_blocks)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use rand::Rng as _;
+    use zksync_consensus_roles::validator;
     use zksync_consensus_storage::ReplicaState;
+    use zksync_types::{fee::TransactionExecutionMetrics, ProtocolVersion};
 
-    use crate::ConnectionPool;
+    use super::*;
+    use crate::{
+        tests::{create_miniblock_header, mock_execution_result, mock_l2_transaction},
+        ConnectionPool,
+    };
+
+    #[tokio::test]
+    async fn test_pool_schema_is_compatible() {
+        let pool = ConnectionPool::test_pool().await;
+        let mut conn = pool.access_storage().await.unwrap();
+        assert!(conn.consensus_dal().is_schema_compatible().await.unwrap());
+    }
 
     #[tokio::test]
     async fn replica_state_read_write() {
@@ -76,4 +254,87 @@ mod tests {
             );
         }
     }
+
+    #[tokio::test]
+    async fn querying_certificates_and_payloads_range() {
+        let pool = ConnectionPool::test_pool().await;
+        let mut conn = pool.access_storage().await.unwrap();
+        conn.protocol_versions_dal()
+            .save_protocol_version_with_tx(ProtocolVersion::default())
+            .await;
+
+        let rng = &mut rand::thread_rng();
+        let mut certificates = vec![];
+        let mut transactions = vec![];
+        for number in 0..5 {
+            let miniblock_number = MiniblockNumber(number);
+            let tx = mock_l2_transaction();
+            conn.transactions_dal()
+                .insert_transaction_l2(tx.clone(), TransactionExecutionMetrics::default())
+                .await;
+            conn.blocks_dal()
+                .insert_miniblock(&create_miniblock_header(number))
+                .await
+                .unwrap();
+            conn.transactions_dal()
+                .mark_txs_as_executed_in_miniblock(
+                    miniblock_number,
+                    &[mock_execution_result(tx.clone())],
+                    1.into(),
+                )
+                .await;
+            transactions.push(Transaction::from(tx));
+
+            // Miniblock #3 doesn't have consensus fields, thus breaking the contiguous run.
+            if number != 3 {
+                let block = rng.gen::<validator::FinalBlock>();
+                let certificate = ConsensusBlockFields {
+                    parent: block.header.parent,
+                    justification: block.justification,
+                };
+                conn.blocks_dal()
+                    .set_miniblock_consensus_fields(miniblock_number, &certificate)
+                    .await
+                    .unwrap();
+                certificates.push(certificate);
+            }
+        }
+
+        let range = MiniblockNumber(1)..MiniblockNumber(5);
+        let fetched_certificates = conn
+            .consensus_dal()
+            .certificates_range(range.clone())
+            .await
+            .unwrap();
+        assert_eq!(fetched_certificates, certificates[1..3]);
+        let fetched_certificates = conn
+            .consensus_dal()
+            .certificates_range(MiniblockNumber(3)..MiniblockNumber(5))
+            .await
+            .unwrap();
+        assert!(fetched_certificates.is_empty());
+
+        let operator_address = Address::repeat_byte(1);
+        let payloads = conn
+            .consensus_dal()
+            .payloads_range(range, operator_address)
+            .await
+            .unwrap();
+        assert_eq!(payloads.len(), 4);
+        for (payload, expected_tx) in payloads.iter().zip(&transactions[1..]) {
+            assert_eq!(
+                payload.transactions.as_deref(),
+                Some(std::slice::from_ref(expected_tx))
+            );
+        }
+        let numbers: Vec<_> = payloads.iter().map(|payload| payload.number.0).collect();
+        assert_eq!(numbers, [1, 2, 3, 4]);
+
+        let payloads = conn
+            .consensus_dal()
+            .payloads_range(MiniblockNumber(5)..MiniblockNumber(10), operator_address)
+            .await
+            .unwrap();
+        assert!(payloads.is_empty());
+    }
 }
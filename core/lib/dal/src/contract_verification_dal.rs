@@ -13,7 +13,13 @@ use zksync_types::{
     get_code_key, Address, CONTRACT_DEPLOYER_ADDRESS, FAILED_CONTRACT_DEPLOYMENT_BYTECODE_HASH,
 };
 
-use crate::{models::storage_verification_request::StorageVerificationRequest, StorageProcessor};
+use crate::{
+    models::{
+        storage_factory_dep::decode_bytecode,
+        storage_verification_request::StorageVerificationRequest,
+    },
+    StorageProcessor,
+};
 
 #[derive(Debug)]
 pub struct ContractVerificationDal<'a, 'c> {
@@ -288,6 +294,7 @@ impl ContractVerificationDal<'_, '_> {
             r#"
             SELECT
                 factory_deps.bytecode,
+                factory_deps.bytecode_compressed,
                 transactions.data AS "data?",
                 transactions.contract_address AS "contract_address?"
             FROM
@@ -331,7 +338,9 @@ impl ContractVerificationDal<'_, '_> {
             }
             _ => DeployContractCalldata::Ignore,
         };
-        Ok(Some((row.bytecode, calldata)))
+        let bytecode = decode_bytecode(row.bytecode, row.bytecode_compressed)
+            .context("failed decoding contract bytecode")?;
+        Ok(Some((bytecode, calldata)))
     }
 
     /// Returns true if the contract has a stored contracts_verification_info.
@@ -0,0 +1,269 @@
+use zksync_types::L1BatchNumber;
+
+use crate::{instrument::InstrumentExt, StorageProcessor};
+
+/// Blob with L1 batch pubdata dispatched to the data availability layer.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DataAvailabilityBlob {
+    pub l1_batch_number: L1BatchNumber,
+    pub blob_id: String,
+    pub inclusion_data: Option<Vec<u8>>,
+}
+
+/// L1 batch pubdata that should be dispatched to the data availability layer.
+#[derive(Debug, Clone, PartialEq)]
+pub struct L1BatchPubdata {
+    pub l1_batch_number: L1BatchNumber,
+    pub pubdata: Vec<u8>,
+}
+
+#[derive(Debug)]
+pub struct DataAvailabilityDal<'a, 'c> {
+    pub(crate) storage: &'a mut StorageProcessor<'c>,
+}
+
+impl DataAvailabilityDal<'_, '_> {
+    /// Records that pubdata for the specified L1 batch was dispatched to the data availability layer.
+    pub async fn insert_l1_batch_da(
+        &mut self,
+        l1_batch_number: L1BatchNumber,
+        blob_id: &str,
+    ) -> sqlx::Result<()> {
+        sqlx::query!(
+            r#"
+            INSERT INTO
+                data_availability (l1_batch_number, blob_id, sent_at, created_at, updated_at)
+            VALUES
+                ($1, $2, NOW(), NOW(), NOW())
+            "#,
+            i64::from(l1_batch_number.0),
+            blob_id
+        )
+        .instrument("insert_l1_batch_da")
+        .with_arg("l1_batch_number", &l1_batch_number)
+        .with_arg("blob_id", &blob_id)
+        .execute(self.storage.conn())
+        .await?;
+        Ok(())
+    }
+
+    /// Saves the inclusion proof for the blob dispatched for the specified L1 batch.
+    pub async fn save_l1_batch_inclusion_data(
+        &mut self,
+        l1_batch_number: L1BatchNumber,
+        inclusion_data: &[u8],
+    ) -> sqlx::Result<()> {
+        let result = sqlx::query!(
+            r#"
+            UPDATE data_availability
+            SET
+                inclusion_data = $1,
+                updated_at = NOW()
+            WHERE
+                l1_batch_number = $2
+                AND inclusion_data IS NULL
+            "#,
+            inclusion_data,
+            i64::from(l1_batch_number.0)
+        )
+        .instrument("save_l1_batch_inclusion_data")
+        .with_arg("l1_batch_number", &l1_batch_number)
+        .execute(self.storage.conn())
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(sqlx::Error::RowNotFound);
+        }
+        Ok(())
+    }
+
+    /// Returns the first dispatched blob for which the inclusion proof is not received yet.
+    pub async fn get_first_da_blob_awaiting_inclusion(
+        &mut self,
+    ) -> sqlx::Result<Option<DataAvailabilityBlob>> {
+        let row = sqlx::query!(
+            r#"
+            SELECT
+                l1_batch_number,
+                blob_id,
+                inclusion_data
+            FROM
+                data_availability
+            WHERE
+                inclusion_data IS NULL
+            ORDER BY
+                l1_batch_number
+            LIMIT
+                1
+            "#
+        )
+        .instrument("get_first_da_blob_awaiting_inclusion")
+        .fetch_optional(self.storage.conn())
+        .await?;
+
+        Ok(row.map(|row| DataAvailabilityBlob {
+            l1_batch_number: L1BatchNumber(row.l1_batch_number as u32),
+            blob_id: row.blob_id,
+            inclusion_data: row.inclusion_data,
+        }))
+    }
+
+    /// Returns the inclusion proof for the blob dispatched for the specified L1 batch, or `None` if the blob
+    /// was not dispatched or is not included yet.
+    pub async fn get_inclusion_data(
+        &mut self,
+        l1_batch_number: L1BatchNumber,
+    ) -> sqlx::Result<Option<Vec<u8>>> {
+        let row = sqlx::query!(
+            r#"
+            SELECT
+                inclusion_data
+            FROM
+                data_availability
+            WHERE
+                l1_batch_number = $1
+            "#,
+            i64::from(l1_batch_number.0)
+        )
+        .instrument("get_inclusion_data")
+        .with_arg("l1_batch_number", &l1_batch_number)
+        .fetch_optional(self.storage.conn())
+        .await?;
+
+        Ok(row.and_then(|row| row.inclusion_data))
+    }
+
+    /// Returns pubdata for sealed L1 batches that were not dispatched to the data availability layer yet.
+    pub async fn get_ready_for_da_dispatch_l1_batches(
+        &mut self,
+        limit: usize,
+    ) -> sqlx::Result<Vec<L1BatchPubdata>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT
+                number,
+                pubdata_input
+            FROM
+                l1_batches
+                LEFT JOIN data_availability ON data_availability.l1_batch_number = l1_batches.number
+            WHERE
+                eth_commit_tx_id IS NULL
+                AND number != 0
+                AND data_availability.blob_id IS NULL
+                AND pubdata_input IS NOT NULL
+            ORDER BY
+                number
+            LIMIT
+                $1
+            "#,
+            limit as i64
+        )
+        .instrument("get_ready_for_da_dispatch_l1_batches")
+        .with_arg("limit", &limit)
+        .fetch_all(self.storage.conn())
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| L1BatchPubdata {
+                l1_batch_number: L1BatchNumber(row.number as u32),
+                // `unwrap` is safe due to the check in the query
+                pubdata: row.pubdata_input.unwrap(),
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zksync_contracts::BaseSystemContractsHashes;
+    use zksync_types::{block::L1BatchHeader, Address, ProtocolVersion, ProtocolVersionId};
+
+    use super::*;
+    use crate::ConnectionPool;
+
+    #[tokio::test]
+    async fn dispatching_l1_batch_pubdata() {
+        let pool = ConnectionPool::test_pool().await;
+        let mut conn = pool.access_storage().await.unwrap();
+        conn.protocol_versions_dal()
+            .save_protocol_version_with_tx(ProtocolVersion::default())
+            .await;
+        for number in 0..3 {
+            let mut header = L1BatchHeader::new(
+                L1BatchNumber(number),
+                100,
+                Address::default(),
+                BaseSystemContractsHashes::default(),
+                ProtocolVersionId::default(),
+            );
+            header.pubdata_input = Some(vec![number as u8; 32]);
+            conn.blocks_dal()
+                .insert_l1_batch(&header, &[], Default::default(), &[], &[], 0)
+                .await
+                .unwrap();
+        }
+
+        let ready_batches = conn
+            .data_availability_dal()
+            .get_ready_for_da_dispatch_l1_batches(10)
+            .await
+            .unwrap();
+        let ready_numbers: Vec<_> = ready_batches
+            .iter()
+            .map(|batch| batch.l1_batch_number)
+            .collect();
+        assert_eq!(ready_numbers, [L1BatchNumber(1), L1BatchNumber(2)]);
+        assert_eq!(ready_batches[0].pubdata, [1; 32]);
+
+        conn.data_availability_dal()
+            .insert_l1_batch_da(L1BatchNumber(1), "blob-1")
+            .await
+            .unwrap();
+        let ready_batches = conn
+            .data_availability_dal()
+            .get_ready_for_da_dispatch_l1_batches(10)
+            .await
+            .unwrap();
+        assert_eq!(ready_batches.len(), 1);
+        assert_eq!(ready_batches[0].l1_batch_number, L1BatchNumber(2));
+
+        let blob = conn
+            .data_availability_dal()
+            .get_first_da_blob_awaiting_inclusion()
+            .await
+            .unwrap()
+            .expect("no blob awaiting inclusion");
+        assert_eq!(
+            blob,
+            DataAvailabilityBlob {
+                l1_batch_number: L1BatchNumber(1),
+                blob_id: "blob-1".to_owned(),
+                inclusion_data: None,
+            }
+        );
+        let inclusion_data = conn
+            .data_availability_dal()
+            .get_inclusion_data(L1BatchNumber(1))
+            .await
+            .unwrap();
+        assert_eq!(inclusion_data, None);
+
+        conn.data_availability_dal()
+            .save_l1_batch_inclusion_data(L1BatchNumber(1), b"proof")
+            .await
+            .unwrap();
+        let inclusion_data = conn
+            .data_availability_dal()
+            .get_inclusion_data(L1BatchNumber(1))
+            .await
+            .unwrap();
+        assert_eq!(inclusion_data.as_deref(), Some(&b"proof"[..]));
+        let blob = conn
+            .data_availability_dal()
+            .get_first_da_blob_awaiting_inclusion()
+            .await
+            .unwrap();
+        assert_eq!(blob, None);
+    }
+}
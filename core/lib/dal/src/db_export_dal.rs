@@ -0,0 +1,365 @@
+use std::ops;
+
+use futures::TryStreamExt as _;
+use zksync_types::{L1BatchNumber, MiniblockNumber};
+
+use crate::{instrument::InstrumentExt, StorageProcessor};
+
+/// Table included into logical DB exports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ExportedTable {
+    ProtocolVersions,
+    L1Batches,
+    Miniblocks,
+    Transactions,
+    Events,
+    L2ToL1Logs,
+    InitialWrites,
+    FactoryDeps,
+    StorageLogs,
+    Storage,
+}
+
+impl ExportedTable {
+    /// All exported tables in the order they should be imported (i.e., referenced tables go first).
+    pub const ALL: [Self; 10] = [
+        Self::ProtocolVersions,
+        Self::L1Batches,
+        Self::Miniblocks,
+        Self::Transactions,
+        Self::Events,
+        Self::L2ToL1Logs,
+        Self::InitialWrites,
+        Self::FactoryDeps,
+        Self::StorageLogs,
+        Self::Storage,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::ProtocolVersions => "protocol_versions",
+            Self::L1Batches => "l1_batches",
+            Self::Miniblocks => "miniblocks",
+            Self::Transactions => "transactions",
+            Self::Events => "events",
+            Self::L2ToL1Logs => "l2_to_l1_logs",
+            Self::InitialWrites => "initial_writes",
+            Self::FactoryDeps => "factory_deps",
+            Self::StorageLogs => "storage_logs",
+            Self::Storage => "storage",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|table| table.name() == name)
+    }
+
+    /// Columns referencing data not included into the export. These columns are exported as `NULL`s.
+    fn cleared_columns(self) -> &'static [&'static str] {
+        match self {
+            Self::ProtocolVersions => &["upgrade_tx_hash"],
+            Self::L1Batches => &["eth_commit_tx_id", "eth_prove_tx_id", "eth_execute_tx_id"],
+            _ => &[],
+        }
+    }
+
+    /// Returns the query selecting rows of the table in the specified chunk, or `None` if the chunk
+    /// contains no rows of the table.
+    fn chunk_query(self, columns: &str, chunk: &ExportChunkRange) -> Option<String> {
+        if self == Self::Storage {
+            // `storage` contains the latest values of storage slots and is not linked to miniblocks, so it's derived
+            // from `storage_logs` as of the last exported miniblock. Each slot is exported in the chunk containing
+            // its last modification, so that it's exported exactly once.
+            let (start, end) = (chunk.miniblocks.start().0, chunk.miniblocks.end().0);
+            let last_miniblock = chunk.last_exported_miniblock.0;
+            return Some(format!(
+                "SELECT DISTINCT ON (hashed_key) {columns} FROM storage_logs \
+                 WHERE miniblock_number BETWEEN {start} AND {end} AND NOT EXISTS ( \
+                     SELECT 1 FROM storage_logs AS later_logs \
+                     WHERE later_logs.hashed_key = storage_logs.hashed_key \
+                         AND later_logs.miniblock_number > {end} \
+                         AND later_logs.miniblock_number <= {last_miniblock} \
+                 ) \
+                 ORDER BY hashed_key, miniblock_number DESC, operation_number DESC"
+            ));
+        }
+
+        let filter = match self.chunk_filter(chunk) {
+            Some(filter) => filter,
+            None if chunk.is_first() => "TRUE".to_owned(),
+            None => return None,
+        };
+        Some(format!(
+            "SELECT {columns} FROM {table} WHERE {filter}",
+            table = self.name()
+        ))
+    }
+
+    /// Returns the filter selecting rows in the specified chunk, or `None` if the table is not chunked
+    /// (i.e., it is exported in full as a part of the first chunk).
+    fn chunk_filter(self, chunk: &ExportChunkRange) -> Option<String> {
+        let (column, start, end) = match self {
+            Self::ProtocolVersions | Self::Storage => return None,
+            Self::L1Batches => (
+                "number",
+                chunk.l1_batches.start().0,
+                chunk.l1_batches.end().0,
+            ),
+            Self::InitialWrites => (
+                "l1_batch_number",
+                chunk.l1_batches.start().0,
+                chunk.l1_batches.end().0,
+            ),
+            Self::Miniblocks => (
+                "number",
+                chunk.miniblocks.start().0,
+                chunk.miniblocks.end().0,
+            ),
+            Self::Transactions
+            | Self::Events
+            | Self::L2ToL1Logs
+            | Self::FactoryDeps
+            | Self::StorageLogs => (
+                "miniblock_number",
+                chunk.miniblocks.start().0,
+                chunk.miniblocks.end().0,
+            ),
+        };
+        Some(format!("{column} BETWEEN {start} AND {end}"))
+    }
+}
+
+/// Range of L1 batches and the corresponding miniblocks exported in a single chunk.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExportChunkRange {
+    pub l1_batches: ops::RangeInclusive<L1BatchNumber>,
+    pub miniblocks: ops::RangeInclusive<MiniblockNumber>,
+    /// Last miniblock included into the export (i.e., not necessarily into this chunk).
+    pub last_exported_miniblock: MiniblockNumber,
+}
+
+impl ExportChunkRange {
+    fn is_first(&self) -> bool {
+        self.l1_batches.start().0 == 0
+    }
+}
+
+/// DAL for logical (i.e., not relying on `pg_dump`) exports and imports of the core chain data:
+/// L1 batches, miniblocks (including consensus certificates), transactions, events, L2-to-L1 logs, storage logs,
+/// the latest storage state, initial writes and factory deps.
+///
+/// Rows are transferred in the Postgres text `COPY` format, so exported data can only be imported into a database
+/// with a compatible schema. Columns referencing data not included into the export (e.g., L1 transactions
+/// for L1 batches) are exported as `NULL`s.
+#[derive(Debug)]
+pub struct DbExportDal<'a, 'c> {
+    pub(crate) storage: &'a mut StorageProcessor<'c>,
+}
+
+impl DbExportDal<'_, '_> {
+    /// Makes the current transaction use a single DB snapshot for all subsequent queries, so that the exported
+    /// data is consistent even if the node is running.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the processor is not in a transaction, or if the transaction has already executed any queries.
+    pub async fn start_consistent_export(&mut self) -> sqlx::Result<()> {
+        assert!(
+            self.storage.in_transaction(),
+            "consistent export must be performed in a transaction"
+        );
+        sqlx::query("SET TRANSACTION ISOLATION LEVEL REPEATABLE READ, READ ONLY")
+            .instrument("start_consistent_export")
+            .execute(self.storage.conn())
+            .await?;
+        Ok(())
+    }
+
+    /// Returns the version of the latest applied DB migration.
+    pub async fn get_latest_migration_version(&mut self) -> sqlx::Result<Option<i64>> {
+        let row: Option<(i64,)> = sqlx::query_as(
+            "SELECT version FROM _sqlx_migrations WHERE success ORDER BY version DESC LIMIT 1",
+        )
+        .instrument("get_latest_migration_version")
+        .fetch_optional(self.storage.conn())
+        .await?;
+        Ok(row.map(|(version,)| version))
+    }
+
+    /// Returns column names of the specified table in their natural order.
+    pub async fn get_columns(&mut self, table: ExportedTable) -> sqlx::Result<Vec<String>> {
+        let rows: Vec<(String,)> = sqlx::query_as(
+            "SELECT column_name::TEXT FROM information_schema.columns \
+             WHERE table_schema = current_schema() AND table_name = $1 \
+             ORDER BY ordinal_position",
+        )
+        .bind(table.name())
+        .instrument("get_columns")
+        .with_arg("table", &table.name())
+        .fetch_all(self.storage.conn())
+        .await?;
+        Ok(rows.into_iter().map(|(name,)| name).collect())
+    }
+
+    /// Exports rows of the specified table in the provided chunk. `columns` should be obtained using
+    /// [`Self::get_columns()`]. Returns rows in the text `COPY` format.
+    pub async fn export_chunk(
+        &mut self,
+        table: ExportedTable,
+        columns: &[String],
+        chunk: &ExportChunkRange,
+    ) -> sqlx::Result<Vec<u8>> {
+        let selected_columns: Vec<_> = columns
+            .iter()
+            .map(|column| {
+                if table.cleared_columns().contains(&column.as_str()) {
+                    format!("NULL AS {column}")
+                } else {
+                    column.clone()
+                }
+            })
+            .collect();
+        let Some(query) = table.chunk_query(&selected_columns.join(", "), chunk) else {
+            return Ok(vec![]);
+        };
+        let statement = format!("COPY ({query}) TO STDOUT");
+
+        let started_at = std::time::Instant::now();
+        let mut stream = self.storage.conn().copy_out_raw(&statement).await?;
+        let mut buffer = vec![];
+        while let Some(bytes) = stream.try_next().await? {
+            buffer.extend_from_slice(&bytes);
+        }
+        tracing::debug!(
+            "Exported {} bytes from `{}` for {chunk:?} in {:?}",
+            buffer.len(),
+            table.name(),
+            started_at.elapsed()
+        );
+        Ok(buffer)
+    }
+
+    /// Imports rows previously exported with [`Self::export_chunk()`].
+    pub async fn import_chunk(
+        &mut self,
+        table: ExportedTable,
+        columns: &[String],
+        rows: &[u8],
+    ) -> sqlx::Result<()> {
+        if rows.is_empty() {
+            return Ok(());
+        }
+        let statement = format!(
+            "COPY {table} ({columns}) FROM STDIN",
+            table = table.name(),
+            columns = columns.join(", ")
+        );
+        let mut copy = self.storage.conn().copy_in_raw(&statement).await?;
+        copy.send(rows).await?;
+        copy.finish().await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zksync_types::{
+        AccountTreeId, Address, L1BatchNumber, ProtocolVersion, StorageKey, StorageLog, H256,
+    };
+
+    use super::*;
+    use crate::{tests::create_miniblock_header, ConnectionPool};
+
+    #[tokio::test]
+    async fn exporting_and_importing_chunk() {
+        let pool = ConnectionPool::test_pool().await;
+        let mut conn = pool.access_storage().await.unwrap();
+        conn.protocol_versions_dal()
+            .save_protocol_version_with_tx(ProtocolVersion::default())
+            .await;
+        conn.blocks_dal()
+            .insert_miniblock(&create_miniblock_header(0))
+            .await
+            .unwrap();
+        let key = StorageKey::new(AccountTreeId::new(Address::repeat_byte(1)), H256::zero());
+        let logs = vec![StorageLog::new_write_log(key, H256::repeat_byte(1))];
+        conn.storage_logs_dal()
+            .insert_storage_logs(MiniblockNumber(0), &[(H256::zero(), logs)])
+            .await;
+
+        let chunk = ExportChunkRange {
+            l1_batches: L1BatchNumber(0)..=L1BatchNumber(0),
+            miniblocks: MiniblockNumber(0)..=MiniblockNumber(0),
+            last_exported_miniblock: MiniblockNumber(0),
+        };
+        let mut exported = vec![];
+        let mut transaction = conn.start_transaction().await.unwrap();
+        transaction
+            .db_export_dal()
+            .start_consistent_export()
+            .await
+            .unwrap();
+        for table in ExportedTable::ALL {
+            let columns = transaction
+                .db_export_dal()
+                .get_columns(table)
+                .await
+                .unwrap();
+            assert!(!columns.is_empty(), "{table:?}");
+            let rows = transaction
+                .db_export_dal()
+                .export_chunk(table, &columns, &chunk)
+                .await
+                .unwrap();
+            exported.push((table, columns, rows));
+        }
+        drop(transaction);
+
+        for table in [ExportedTable::StorageLogs, ExportedTable::Storage] {
+            let (_, _, rows) = exported.iter().find(|(t, ..)| *t == table).unwrap();
+            assert_eq!(
+                String::from_utf8_lossy(rows).lines().count(),
+                1,
+                "{table:?}: {rows:?}"
+            );
+        }
+
+        // Clear the exported data and import it back.
+        sqlx::query("DELETE FROM storage")
+            .execute(conn.conn())
+            .await
+            .unwrap();
+        sqlx::query("DELETE FROM storage_logs")
+            .execute(conn.conn())
+            .await
+            .unwrap();
+        sqlx::query("DELETE FROM miniblocks")
+            .execute(conn.conn())
+            .await
+            .unwrap();
+        sqlx::query("DELETE FROM protocol_versions")
+            .execute(conn.conn())
+            .await
+            .unwrap();
+        for (table, columns, rows) in &exported {
+            conn.db_export_dal()
+                .import_chunk(*table, columns, rows)
+                .await
+                .unwrap();
+        }
+
+        let values = conn
+            .storage_logs_dal()
+            .get_storage_values(&[key.hashed_key()], MiniblockNumber(0))
+            .await;
+        assert_eq!(values[&key.hashed_key()], Some(H256::repeat_byte(1)));
+        let value = conn.storage_dal().get_by_key(&key).await;
+        assert_eq!(value, Some(H256::repeat_byte(1)));
+        let miniblock = conn
+            .blocks_dal()
+            .get_miniblock_header(MiniblockNumber(0))
+            .await
+            .unwrap();
+        assert!(miniblock.is_some());
+    }
+}
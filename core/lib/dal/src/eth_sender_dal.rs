@@ -7,7 +7,7 @@ use sqlx::{
 };
 use zksync_types::{
     aggregated_operations::AggregatedActionType,
-    eth_sender::{EthTx, TxHistory, TxHistoryToSend},
+    eth_sender::{EthTx, EthTxBlobSidecar, TxHistory, TxHistoryToSend},
     Address, L1BatchNumber, H256, U256,
 };
 
@@ -51,6 +51,31 @@ impl EthSenderDal<'_, '_> {
         Ok(txs.into_iter().map(|tx| tx.into()).collect())
     }
 
+    /// Returns the number of sent but not yet confirmed Ethereum transactions.
+    pub async fn get_inflight_txs_count(&mut self) -> sqlx::Result<u64> {
+        let row = sqlx::query!(
+            r#"
+            SELECT
+                COUNT(*) AS "count!"
+            FROM
+                eth_txs
+            WHERE
+                confirmed_eth_tx_history_id IS NULL
+                AND id <= (
+                    SELECT
+                        COALESCE(MAX(eth_tx_id), 0)
+                    FROM
+                        eth_txs_history
+                    WHERE
+                        sent_at_block IS NOT NULL
+                )
+            "#
+        )
+        .fetch_one(self.storage.conn())
+        .await?;
+        Ok(row.count as u64)
+    }
+
     pub async fn get_eth_l1_batches(&mut self) -> sqlx::Result<L1BatchEthSenderStats> {
         let mut stats = L1BatchEthSenderStats::default();
         for tx_type in ["execute_tx", "commit_tx", "prove_tx"] {
@@ -174,8 +199,12 @@ impl EthSenderDal<'_, '_> {
         tx_type: AggregatedActionType,
         contract_address: Address,
         predicted_gas_cost: u32,
+        blob_sidecar: Option<EthTxBlobSidecar>,
+        from_address: Address,
     ) -> sqlx::Result<EthTx> {
         let address = format!("{:#x}", contract_address);
+        let blob_sidecar = blob_sidecar
+            .map(|sidecar| bincode::serialize(&sidecar).expect("can always bincode serialize"));
         let eth_tx = sqlx::query_as!(
             StorageEthTx,
             r#"
@@ -186,11 +215,13 @@ impl EthSenderDal<'_, '_> {
                     tx_type,
                     contract_address,
                     predicted_gas_cost,
+                    blob_sidecar,
+                    from_addr,
                     created_at,
                     updated_at
                 )
             VALUES
-                ($1, $2, $3, $4, $5, NOW(), NOW())
+                ($1, $2, $3, $4, $5, $6, $7, NOW(), NOW())
             RETURNING
                 *
             "#,
@@ -198,7 +229,9 @@ impl EthSenderDal<'_, '_> {
             nonce as i64,
             tx_type.to_string(),
             address,
-            predicted_gas_cost as i64
+            predicted_gas_cost as i64,
+            blob_sidecar,
+            from_address.as_bytes()
         )
         .fetch_one(self.storage.conn())
         .await?;
@@ -210,6 +243,7 @@ impl EthSenderDal<'_, '_> {
         eth_tx_id: u32,
         base_fee_per_gas: u64,
         priority_fee_per_gas: u64,
+        blob_base_fee_per_gas: Option<u64>,
         tx_hash: H256,
         raw_signed_tx: &[u8],
     ) -> anyhow::Result<Option<u32>> {
@@ -217,6 +251,10 @@ impl EthSenderDal<'_, '_> {
             i64::try_from(priority_fee_per_gas).context("Can't convert u64 to i64")?;
         let base_fee_per_gas =
             i64::try_from(base_fee_per_gas).context("Can't convert u64 to i64")?;
+        let blob_base_fee_per_gas = blob_base_fee_per_gas
+            .map(i64::try_from)
+            .transpose()
+            .context("Can't convert u64 to i64")?;
         let tx_hash = format!("{:#x}", tx_hash);
 
         Ok(sqlx::query!(
@@ -228,11 +266,12 @@ impl EthSenderDal<'_, '_> {
                     priority_fee_per_gas,
                     tx_hash,
                     signed_raw_tx,
+                    blob_base_fee_per_gas,
                     created_at,
                     updated_at
                 )
             VALUES
-                ($1, $2, $3, $4, $5, NOW(), NOW())
+                ($1, $2, $3, $4, $5, $6, NOW(), NOW())
             ON CONFLICT (tx_hash) DO NOTHING
             RETURNING
                 id
@@ -241,7 +280,8 @@ impl EthSenderDal<'_, '_> {
             base_fee_per_gas,
             priority_fee_per_gas,
             tx_hash,
-            raw_signed_tx
+            raw_signed_tx,
+            blob_base_fee_per_gas
         )
         .fetch_optional(self.storage.conn())
         .await?
@@ -510,24 +550,60 @@ impl EthSenderDal<'_, '_> {
         Ok(history_item.map(|tx| tx.into()))
     }
 
-    pub async fn get_next_nonce(&mut self) -> sqlx::Result<Option<u64>> {
+    /// Returns the next nonce for transactions sent from the specified address. If `from_address` is `None`,
+    /// returns the next nonce for transactions without a recorded sender (i.e., ones created before
+    /// the sender address was persisted; they are always sent from the primary operator account).
+    pub async fn get_next_nonce(
+        &mut self,
+        from_address: Option<Address>,
+    ) -> sqlx::Result<Option<u64>> {
         let row = sqlx::query!(
             r#"
             SELECT
                 nonce
             FROM
                 eth_txs
+            WHERE
+                from_addr IS NOT DISTINCT FROM $1
             ORDER BY
                 id DESC
             LIMIT
                 1
-            "#
+            "#,
+            from_address.as_ref().map(Address::as_bytes)
         )
         .fetch_optional(self.storage.conn())
         .await?;
         Ok(row.map(|row| row.nonce as u64 + 1))
     }
 
+    /// Returns distinct senders of unconfirmed transactions of the specified type. `None` corresponds
+    /// to transactions without a recorded sender.
+    pub async fn get_unconfirmed_tx_senders(
+        &mut self,
+        tx_type: AggregatedActionType,
+    ) -> sqlx::Result<Vec<Option<Address>>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT DISTINCT
+                from_addr
+            FROM
+                eth_txs
+            WHERE
+                tx_type = $1
+                AND confirmed_eth_tx_history_id IS NULL
+                AND has_failed = FALSE
+            "#,
+            tx_type.to_string()
+        )
+        .fetch_all(self.storage.conn())
+        .await?;
+        Ok(rows
+            .into_iter()
+            .map(|row| row.from_addr.map(|addr| Address::from_slice(&addr)))
+            .collect())
+    }
+
     pub async fn mark_failed_transaction(&mut self, eth_tx_id: u32) -> sqlx::Result<()> {
         sqlx::query!(
             r#"
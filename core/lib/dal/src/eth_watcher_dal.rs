@@ -0,0 +1,129 @@
+use zksync_types::H256;
+
+use crate::{instrument::InstrumentExt, StorageProcessor};
+
+/// Hashes of L1 blocks processed by the Ethereum watcher. Used to detect L1 reorgs.
+#[derive(Debug)]
+pub struct EthWatcherDal<'a, 'c> {
+    pub(crate) storage: &'a mut StorageProcessor<'c>,
+}
+
+impl EthWatcherDal<'_, '_> {
+    /// Records the hash of a processed L1 block, overwriting the previously recorded hash (if any).
+    pub async fn insert_block(&mut self, number: u64, hash: H256) -> sqlx::Result<()> {
+        sqlx::query!(
+            r#"
+            INSERT INTO
+                eth_watcher_blocks (number, hash, created_at, updated_at)
+            VALUES
+                ($1, $2, NOW(), NOW())
+            ON CONFLICT (number) DO
+            UPDATE
+            SET
+                hash = excluded.hash,
+                updated_at = NOW()
+            "#,
+            number as i64,
+            hash.as_bytes()
+        )
+        .instrument("insert_eth_watcher_block")
+        .with_arg("number", &number)
+        .execute(self.storage.conn())
+        .await?;
+        Ok(())
+    }
+
+    /// Returns recorded L1 blocks starting from the most recent one.
+    pub async fn get_blocks(&mut self) -> sqlx::Result<Vec<(u64, H256)>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT
+                number,
+                hash
+            FROM
+                eth_watcher_blocks
+            ORDER BY
+                number DESC
+            "#
+        )
+        .instrument("get_eth_watcher_blocks")
+        .fetch_all(self.storage.conn())
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| (row.number as u64, H256::from_slice(&row.hash)))
+            .collect())
+    }
+
+    /// Removes blocks with numbers greater than `number`, e.g. after they were invalidated by an L1 reorg.
+    pub async fn delete_blocks_after(&mut self, number: u64) -> sqlx::Result<u64> {
+        let result = sqlx::query!(
+            r#"
+            DELETE FROM eth_watcher_blocks
+            WHERE
+                number > $1
+            "#,
+            number as i64
+        )
+        .instrument("delete_eth_watcher_blocks_after")
+        .with_arg("number", &number)
+        .execute(self.storage.conn())
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Removes blocks with numbers less than `number`. Used to limit the reorg detection window.
+    pub async fn delete_blocks_before(&mut self, number: u64) -> sqlx::Result<u64> {
+        let result = sqlx::query!(
+            r#"
+            DELETE FROM eth_watcher_blocks
+            WHERE
+                number < $1
+            "#,
+            number as i64
+        )
+        .instrument("delete_eth_watcher_blocks_before")
+        .with_arg("number", &number)
+        .execute(self.storage.conn())
+        .await?;
+        Ok(result.rows_affected())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ConnectionPool;
+
+    #[tokio::test]
+    async fn managing_eth_watcher_blocks() {
+        let pool = ConnectionPool::test_pool().await;
+        let mut storage = pool.access_storage().await.unwrap();
+        let mut dal = storage.eth_watcher_dal();
+        assert_eq!(dal.get_blocks().await.unwrap(), []);
+
+        for number in [10, 15, 20] {
+            dal.insert_block(number, H256::from_low_u64_be(number))
+                .await
+                .unwrap();
+        }
+        // Overwrite a block hash, as it happens after a reorg.
+        dal.insert_block(20, H256::repeat_byte(0xff)).await.unwrap();
+        assert_eq!(
+            dal.get_blocks().await.unwrap(),
+            [
+                (20, H256::repeat_byte(0xff)),
+                (15, H256::from_low_u64_be(15)),
+                (10, H256::from_low_u64_be(10)),
+            ]
+        );
+
+        assert_eq!(dal.delete_blocks_after(15).await.unwrap(), 1);
+        assert_eq!(dal.delete_blocks_before(15).await.unwrap(), 1);
+        assert_eq!(
+            dal.get_blocks().await.unwrap(),
+            [(15, H256::from_low_u64_be(15))]
+        );
+    }
+}
@@ -1,13 +1,16 @@
-use std::fmt;
+use std::{fmt, ops};
 
 use sqlx::types::chrono::Utc;
 use zksync_types::{
+    block::accrue_logs_bloom,
     l2_to_l1_log::{L2ToL1Log, UserL2ToL1Log},
     tx::IncludedTxLocation,
-    MiniblockNumber, VmEvent, H256,
+    MiniblockNumber, VmEvent, H2048, H256,
 };
 
-use crate::{models::storage_event::StorageL2ToL1Log, SqlxError, StorageProcessor};
+use crate::{
+    instrument::InstrumentExt, models::storage_event::StorageL2ToL1Log, SqlxError, StorageProcessor,
+};
 
 /// Wrapper around an optional event topic allowing to hex-format it for `COPY` instructions.
 #[derive(Debug)]
@@ -105,6 +108,59 @@ impl EventsDal<'_, '_> {
         .unwrap();
     }
 
+    /// Computes logs blooms for miniblocks in the specified range based on the stored events.
+    /// Miniblocks without events get an empty bloom. The returned blooms are ordered by miniblock number.
+    pub async fn compute_logs_blooms(
+        &mut self,
+        miniblock_numbers: ops::RangeInclusive<MiniblockNumber>,
+    ) -> sqlx::Result<Vec<(MiniblockNumber, H2048)>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT
+                miniblocks.number,
+                events.address AS "address?",
+                events.topic1 AS "topic1?",
+                events.topic2 AS "topic2?",
+                events.topic3 AS "topic3?",
+                events.topic4 AS "topic4?"
+            FROM
+                miniblocks
+                LEFT JOIN events ON events.miniblock_number = miniblocks.number
+            WHERE
+                miniblocks.number BETWEEN $1 AND $2
+            ORDER BY
+                miniblocks.number
+            "#,
+            miniblock_numbers.start().0 as i64,
+            miniblock_numbers.end().0 as i64
+        )
+        .instrument("compute_logs_blooms")
+        .with_arg("miniblock_numbers", &miniblock_numbers)
+        .fetch_all(self.storage.conn())
+        .await?;
+
+        let mut blooms: Vec<(MiniblockNumber, H2048)> = vec![];
+        for row in rows {
+            let number = MiniblockNumber(row.number as u32);
+            if blooms.last().map(|(last_number, _)| *last_number) != Some(number) {
+                blooms.push((number, H2048::zero()));
+            }
+            let bloom = &mut blooms.last_mut().unwrap().1;
+            let Some(address) = row.address else {
+                continue; // The miniblock has no events
+            };
+            accrue_logs_bloom(bloom, &address);
+            let topics = [row.topic1, row.topic2, row.topic3, row.topic4];
+            for topic in topics.into_iter().flatten() {
+                // Missing topics are stored as empty byte strings.
+                if !topic.is_empty() {
+                    accrue_logs_bloom(bloom, &topic);
+                }
+            }
+        }
+        Ok(blooms)
+    }
+
     /// Saves user L2-to-L1 logs from a miniblock. Logs must be ordered by transaction location
     /// and within each transaction.
     pub async fn save_user_l2_to_l1_logs(
@@ -219,7 +275,7 @@ impl EventsDal<'_, '_> {
 
 #[cfg(test)]
 mod tests {
-    use zksync_types::{Address, L1BatchNumber, ProtocolVersion};
+    use zksync_types::{block::build_logs_bloom, Address, L1BatchNumber, ProtocolVersion};
 
     use super::*;
     use crate::{tests::create_miniblock_header, ConnectionPool};
@@ -299,6 +355,67 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn computing_logs_blooms() {
+        let pool = ConnectionPool::test_pool().await;
+        let mut conn = pool.access_storage().await.unwrap();
+        conn.protocol_versions_dal()
+            .save_protocol_version_with_tx(ProtocolVersion::default())
+            .await;
+        for number in 1..=2 {
+            conn.blocks_dal()
+                .insert_miniblock(&create_miniblock_header(number))
+                .await
+                .unwrap();
+        }
+
+        let location = IncludedTxLocation {
+            tx_hash: H256([1; 32]),
+            tx_index_in_miniblock: 0,
+            tx_initiator_address: Address::default(),
+        };
+        let events = vec![create_vm_event(0, 0), create_vm_event(1, 4)];
+        conn.events_dal()
+            .save_events(MiniblockNumber(1), &[(location, events.iter().collect())])
+            .await;
+
+        let blooms = conn
+            .events_dal()
+            .compute_logs_blooms(MiniblockNumber(1)..=MiniblockNumber(3))
+            .await
+            .unwrap();
+        assert_eq!(
+            blooms,
+            [
+                (MiniblockNumber(1), build_logs_bloom(&events)),
+                (MiniblockNumber(2), H2048::zero())
+            ]
+        );
+
+        let last_without_bloom = conn
+            .blocks_dal()
+            .get_last_miniblock_without_logs_bloom()
+            .await
+            .unwrap();
+        assert_eq!(last_without_bloom, Some(MiniblockNumber(2)));
+        conn.blocks_dal()
+            .set_miniblock_logs_blooms(&blooms)
+            .await
+            .unwrap();
+        let last_without_bloom = conn
+            .blocks_dal()
+            .get_last_miniblock_without_logs_bloom()
+            .await
+            .unwrap();
+        assert_eq!(last_without_bloom, None);
+        let bloom = conn
+            .blocks_dal()
+            .get_miniblock_logs_bloom(MiniblockNumber(1))
+            .await
+            .unwrap();
+        assert_eq!(bloom, Some(blooms[0].1));
+    }
+
     fn create_l2_to_l1_log(tx_number_in_block: u16, index: u8) -> UserL2ToL1Log {
         UserL2ToL1Log(L2ToL1Log {
             shard_id: 0,
@@ -1,6 +1,7 @@
 use sqlx::Row;
 use zksync_types::{
     api::{GetLogsFilter, Log},
+    block::logs_bloom_bit_positions,
     Address, MiniblockNumber, H256,
 };
 
@@ -48,7 +49,6 @@ impl EventsWeb3Dal<'_, '_> {
             query = query.bind(offset as i32);
             let log = query
                 .instrument("get_log_block_number")
-                .report_latency()
                 .with_arg("filter", filter)
                 .with_arg("offset", &offset)
                 .fetch_optional(self.storage.conn())
@@ -59,14 +59,39 @@ impl EventsWeb3Dal<'_, '_> {
     }
 
     /// Returns logs for given filter.
-    #[allow(clippy::type_complexity)]
     pub async fn get_logs(
         &mut self,
         filter: GetLogsFilter,
         limit: usize,
+    ) -> Result<Vec<Log>, SqlxError> {
+        self.get_logs_inner(filter, None, limit).await
+    }
+
+    /// Returns logs for given filter located strictly after the specified position, which is
+    /// a miniblock number together with the log index in this miniblock. Used for paginating logs.
+    pub async fn get_logs_after(
+        &mut self,
+        filter: GetLogsFilter,
+        after: (MiniblockNumber, u32),
+        limit: usize,
+    ) -> Result<Vec<Log>, SqlxError> {
+        self.get_logs_inner(filter, Some(after), limit).await
+    }
+
+    async fn get_logs_inner(
+        &mut self,
+        filter: GetLogsFilter,
+        after: Option<(MiniblockNumber, u32)>,
+        limit: usize,
     ) -> Result<Vec<Log>, SqlxError> {
         {
-            let (where_sql, arg_index) = self.build_get_logs_where_clause(&filter);
+            let (mut where_sql, arg_index) = self.build_get_logs_where_clause(&filter);
+            if let Some((miniblock_number, log_index)) = after {
+                where_sql += &format!(
+                    " AND ((miniblock_number, event_index_in_block) > ({}, {}))",
+                    miniblock_number.0, log_index
+                );
+            }
 
             let query = format!(
                 r#"
@@ -101,8 +126,8 @@ impl EventsWeb3Dal<'_, '_> {
 
             let db_logs: Vec<StorageWeb3Log> = query
                 .instrument("get_logs")
-                .report_latency()
                 .with_arg("filter", &filter)
+                .with_arg("after", &after)
                 .with_arg("limit", &limit)
                 .fetch_all(self.storage.conn())
                 .await?;
@@ -126,10 +151,68 @@ impl EventsWeb3Dal<'_, '_> {
             where_sql += &format!(" AND (topic{} = ANY(${}))", topic_index, arg_index);
             arg_index += 1;
         }
+        if let Some(bloom_sql) = Self::build_logs_bloom_clause(filter) {
+            where_sql += &format!(
+                " AND (miniblock_number IN (SELECT number FROM miniblocks \
+                 WHERE number BETWEEN {} AND {} AND (logs_bloom IS NULL OR {})))",
+                filter.from_block.0, filter.to_block.0, bloom_sql
+            );
+        }
 
         (where_sql, arg_index)
     }
 
+    /// Builds a condition on `miniblocks.logs_bloom` allowing to skip miniblocks that definitely don't contain
+    /// matching logs. Returns `None` if the filter doesn't benefit from bloom pruning, e.g. if it covers
+    /// a small miniblock range (in which case, the `events` indices are efficient enough), or if it has
+    /// no addresses / topics or too many of them.
+    fn build_logs_bloom_clause(filter: &GetLogsFilter) -> Option<String> {
+        const MIN_BLOCK_RANGE: u32 = 1_000;
+        const MAX_FILTER_ITEMS: usize = 32;
+
+        let block_range = filter.to_block.0.saturating_sub(filter.from_block.0);
+        let item_count =
+            filter.addresses.len() + filter.topics.iter().map(|(_, t)| t.len()).sum::<usize>();
+        if block_range < MIN_BLOCK_RANGE || item_count == 0 || item_count > MAX_FILTER_ITEMS {
+            return None;
+        }
+
+        let address_items = (!filter.addresses.is_empty()).then(|| {
+            filter
+                .addresses
+                .iter()
+                .map(Address::as_bytes)
+                .collect::<Vec<_>>()
+        });
+        let topic_items = filter
+            .topics
+            .iter()
+            .filter(|(_, topics)| !topics.is_empty())
+            .map(|(_, topics)| topics.iter().map(H256::as_bytes).collect::<Vec<_>>());
+        // Items for the same position (the address or a topic with a certain index) are OR'd,
+        // conditions for different positions are AND'd.
+        let conditions: Vec<_> = address_items
+            .into_iter()
+            .chain(topic_items)
+            .map(|items| {
+                let alternatives: Vec<_> = items
+                    .into_iter()
+                    .map(|item| {
+                        let bits = logs_bloom_bit_positions(item).map(|position| {
+                            // `get_bit()` numbers bytes from the start of the array and bits within a byte
+                            // starting from the least significant one.
+                            let bit_index = (255 - position / 8) * 8 + position % 8;
+                            format!("get_bit(logs_bloom, {bit_index}) = 1")
+                        });
+                        format!("({})", bits.join(" AND "))
+                    })
+                    .collect();
+                format!("({})", alternatives.join(" OR "))
+            })
+            .collect();
+        Some(format!("({})", conditions.join(" AND ")))
+    }
+
     pub async fn get_all_logs(
         &mut self,
         from_block: MiniblockNumber,
@@ -193,10 +276,13 @@ impl EventsWeb3Dal<'_, '_> {
 
 #[cfg(test)]
 mod tests {
-    use zksync_types::{Address, H256};
+    use zksync_types::{
+        block::build_logs_bloom, tx::IncludedTxLocation, Address, L1BatchNumber, ProtocolVersion,
+        VmEvent, H256,
+    };
 
     use super::*;
-    use crate::connection::ConnectionPool;
+    use crate::{connection::ConnectionPool, tests::create_miniblock_header};
 
     #[tokio::test]
     async fn test_build_get_logs_where_clause() {
@@ -218,4 +304,164 @@ mod tests {
         assert_eq!(actual_sql, expected_sql);
         assert_eq!(actual_arg_index, expected_arg_index);
     }
+
+    #[test]
+    fn test_build_get_logs_where_clause_with_bloom() {
+        let filter = GetLogsFilter {
+            from_block: MiniblockNumber(100),
+            to_block: MiniblockNumber(5_000),
+            addresses: vec![Address::from_low_u64_be(123)],
+            topics: vec![(
+                1,
+                vec![H256::from_low_u64_be(456), H256::from_low_u64_be(789)],
+            )],
+        };
+        let bloom_sql = EventsWeb3Dal::build_logs_bloom_clause(&filter).unwrap();
+        assert_eq!(bloom_sql.matches("get_bit").count(), 9, "{bloom_sql}");
+        assert_eq!(bloom_sql.matches(" OR ").count(), 1, "{bloom_sql}");
+
+        let narrow_filter = GetLogsFilter {
+            to_block: MiniblockNumber(200),
+            ..filter.clone()
+        };
+        assert!(EventsWeb3Dal::build_logs_bloom_clause(&narrow_filter).is_none());
+        let filter_without_items = GetLogsFilter {
+            addresses: vec![],
+            topics: vec![],
+            ..filter
+        };
+        assert!(EventsWeb3Dal::build_logs_bloom_clause(&filter_without_items).is_none());
+    }
+
+    #[tokio::test]
+    async fn getting_logs_with_bloom_pruning() {
+        let connection_pool = ConnectionPool::test_pool().await;
+        let mut conn = connection_pool.access_storage().await.unwrap();
+        conn.protocol_versions_dal()
+            .save_protocol_version_with_tx(ProtocolVersion::default())
+            .await;
+
+        let first_address = Address::repeat_byte(1);
+        let second_address = Address::repeat_byte(2);
+        for (number, address) in [(1, first_address), (2, second_address), (3, first_address)] {
+            conn.blocks_dal()
+                .insert_miniblock(&create_miniblock_header(number))
+                .await
+                .unwrap();
+            let event = VmEvent {
+                location: (L1BatchNumber(1), 0),
+                address,
+                indexed_topics: vec![H256::repeat_byte(number as u8)],
+                value: vec![],
+            };
+            let location = IncludedTxLocation {
+                tx_hash: H256::repeat_byte(number as u8),
+                tx_index_in_miniblock: 0,
+                tx_initiator_address: Address::default(),
+            };
+            conn.events_dal()
+                .save_events(MiniblockNumber(number), &[(location, vec![&event])])
+                .await;
+            // Leave the bloom for the last miniblock unset to emulate a miniblock that wasn't backfilled yet.
+            if number < 3 {
+                conn.blocks_dal()
+                    .set_miniblock_logs_bloom(MiniblockNumber(number), &build_logs_bloom([&event]))
+                    .await
+                    .unwrap();
+            }
+        }
+
+        let filter = GetLogsFilter {
+            from_block: MiniblockNumber(0),
+            to_block: MiniblockNumber(10_000),
+            addresses: vec![first_address],
+            topics: vec![],
+        };
+        assert!(EventsWeb3Dal::build_logs_bloom_clause(&filter).is_some());
+        let logs = conn.events_web3_dal().get_logs(filter, 100).await.unwrap();
+        let block_numbers: Vec<_> = logs.iter().map(|log| log.block_number).collect();
+        assert_eq!(block_numbers, [Some(1.into()), Some(3.into())]);
+
+        let filter = GetLogsFilter {
+            from_block: MiniblockNumber(0),
+            to_block: MiniblockNumber(10_000),
+            addresses: vec![],
+            topics: vec![(1, vec![H256::repeat_byte(2), H256::repeat_byte(3)])],
+        };
+        let logs = conn.events_web3_dal().get_logs(filter, 100).await.unwrap();
+        let block_numbers: Vec<_> = logs.iter().map(|log| log.block_number).collect();
+        assert_eq!(block_numbers, [Some(2.into()), Some(3.into())]);
+    }
+
+    #[tokio::test]
+    async fn paginating_logs() {
+        let connection_pool = ConnectionPool::test_pool().await;
+        let mut conn = connection_pool.access_storage().await.unwrap();
+        conn.protocol_versions_dal()
+            .save_protocol_version_with_tx(ProtocolVersion::default())
+            .await;
+        let events: Vec<_> = (0..2)
+            .map(|i| VmEvent {
+                location: (L1BatchNumber(1), i),
+                address: Address::repeat_byte(1),
+                indexed_topics: vec![],
+                value: vec![i as u8],
+            })
+            .collect();
+        for number in 1..=3 {
+            conn.blocks_dal()
+                .insert_miniblock(&create_miniblock_header(number))
+                .await
+                .unwrap();
+            let location = IncludedTxLocation {
+                tx_hash: H256::repeat_byte(number as u8),
+                tx_index_in_miniblock: 0,
+                tx_initiator_address: Address::default(),
+            };
+            conn.events_dal()
+                .save_events(
+                    MiniblockNumber(number),
+                    &[(location, events.iter().collect())],
+                )
+                .await;
+        }
+
+        let filter = GetLogsFilter {
+            from_block: MiniblockNumber(1),
+            to_block: MiniblockNumber(3),
+            addresses: vec![Address::repeat_byte(1)],
+            topics: vec![],
+        };
+        let first_page = conn
+            .events_web3_dal()
+            .get_logs(filter.clone(), 3)
+            .await
+            .unwrap();
+        let positions: Vec<_> = first_page
+            .iter()
+            .map(|log| {
+                (
+                    log.block_number.unwrap().as_u32(),
+                    log.log_index.unwrap().as_u32(),
+                )
+            })
+            .collect();
+        assert_eq!(positions, [(1, 0), (1, 1), (2, 0)]);
+
+        let second_page = conn
+            .events_web3_dal()
+            .get_logs_after(filter, (MiniblockNumber(2), 0), 3)
+            .await
+            .unwrap();
+        let positions: Vec<_> = second_page
+            .iter()
+            .map(|log| {
+                (
+                    log.block_number.unwrap().as_u32(),
+                    log.log_index.unwrap().as_u32(),
+                )
+            })
+            .collect();
+        assert_eq!(positions, [(2, 1), (3, 0), (3, 1)]);
+    }
 }
@@ -264,7 +264,6 @@ impl FriProverDal<'_, '_> {
             id as i64,
         )
         .instrument("save_fri_proof")
-        .report_latency()
         .with_arg("id", &id)
         .fetch_optional(self.storage.conn())
         .await
@@ -7,14 +7,12 @@ use sqlx::{
     query::{Map, Query, QueryAs},
     FromRow, IntoArguments, Postgres,
 };
-use tokio::time::{Duration, Instant};
+use tokio::time::Instant;
 
-use crate::metrics::REQUEST_METRICS;
+use crate::{connection::GLOBAL_CONNECTION_POOL_CONFIG, metrics::REQUEST_METRICS};
 
 type ThreadSafeDebug<'a> = dyn fmt::Debug + Send + Sync + 'a;
 
-const SLOW_QUERY_TIMEOUT: Duration = Duration::from_millis(100);
-
 /// Logged arguments for an SQL query.
 #[derive(Debug, Default)]
 struct QueryArgs<'a> {
@@ -90,7 +88,6 @@ struct InstrumentedData<'a> {
     name: &'static str,
     location: &'static Location<'static>,
     args: QueryArgs<'a>,
-    report_latency: bool,
 }
 
 impl<'a> InstrumentedData<'a> {
@@ -99,7 +96,6 @@ impl<'a> InstrumentedData<'a> {
             name,
             location,
             args: QueryArgs::default(),
-            report_latency: false,
         }
     }
 
@@ -111,19 +107,19 @@ impl<'a> InstrumentedData<'a> {
             name,
             location,
             args,
-            report_latency,
         } = self;
+        let slow_query_threshold = GLOBAL_CONNECTION_POOL_CONFIG.slow_query_threshold();
         let started_at = Instant::now();
         tokio::pin!(query_future);
 
         let mut is_slow = false;
         let output =
-            tokio::time::timeout_at(started_at + SLOW_QUERY_TIMEOUT, &mut query_future).await;
+            tokio::time::timeout_at(started_at + slow_query_threshold, &mut query_future).await;
         let output = match output {
             Ok(output) => output,
             Err(_) => {
                 tracing::warn!(
-                    "Query {name}{args} called at {file}:{line} is executing for more than {SLOW_QUERY_TIMEOUT:?}",
+                    "Query {name}{args} called at {file}:{line} is executing for more than {slow_query_threshold:?}",
                     file = location.file(),
                     line = location.line()
                 );
@@ -134,9 +130,7 @@ impl<'a> InstrumentedData<'a> {
         };
 
         let elapsed = started_at.elapsed();
-        if report_latency {
-            REQUEST_METRICS.request[&name].observe(elapsed);
-        }
+        REQUEST_METRICS.request[&name].observe(elapsed);
 
         if let Err(err) = &output {
             tracing::warn!(
@@ -161,8 +155,11 @@ impl<'a> InstrumentedData<'a> {
 ///
 /// The following instrumentation logic is included:
 ///
-/// - If the query executes for too long, it is logged with a `WARN` level. The logged info includes
-///   the query name, its args provided via [Self::with_arg()`] and the caller location.
+/// - Query latency is reported using the `sql_request` histogram labeled with the query name.
+/// - If the query executes for longer than the slow query threshold (see
+///   [`GlobalConnectionPoolConfig`](crate::connection::GlobalConnectionPoolConfig)), it is logged
+///   with a `WARN` level. The logged info includes the query name, its args provided via [`Self::with_arg()`]
+///   and the caller location.
 /// - If the query returns an error, it is logged with a `WARN` level. The logged info is everything
 ///   included in the case of a slow query, plus the error info.
 /// - Slow and erroneous queries are also reported using metrics (`dal.request.slow` and `dal.request.error`,
@@ -174,12 +171,6 @@ pub(crate) struct Instrumented<'a, Q> {
 }
 
 impl<'a, Q> Instrumented<'a, Q> {
-    /// Indicates that latency should be reported for all calls.
-    pub fn report_latency(mut self) -> Self {
-        self.data.report_latency = true;
-        self
-    }
-
     /// Adds a traced query argument. The argument will be logged (using `Debug`) if the query executes too slow
     /// or finishes with an error.
     pub fn with_arg(mut self, name: &'static str, value: &'a ThreadSafeDebug) -> Self {
@@ -211,6 +202,16 @@ where
     A: 'q + IntoArguments<'q, Postgres>,
     O: Send + Unpin + for<'r> FromRow<'r, PgRow>,
 {
+    /// Fetches an optional row using this query.
+    pub async fn fetch_optional(self, conn: &mut PgConnection) -> Result<Option<O>, sqlx::Error> {
+        self.data.fetch(self.query.fetch_optional(conn)).await
+    }
+
+    /// Fetches a single row using this query.
+    pub async fn fetch_one(self, conn: &mut PgConnection) -> Result<O, sqlx::Error> {
+        self.data.fetch(self.query.fetch_one(conn)).await
+    }
+
     /// Fetches all rows using this query and collects them into a `Vec`.
     pub async fn fetch_all(self, conn: &mut PgConnection) -> Result<Vec<O>, sqlx::Error> {
         self.data.fetch(self.query.fetch_all(conn)).await
@@ -6,22 +6,26 @@ pub use sqlx::{types::BigDecimal, Error as SqlxError};
 pub use crate::connection::ConnectionPool;
 use crate::{
     accounts_dal::AccountsDal, basic_witness_input_producer_dal::BasicWitnessInputProducerDal,
-    blocks_dal::BlocksDal, blocks_web3_dal::BlocksWeb3Dal, connection::holder::ConnectionHolder,
+    blocks_dal::BlocksDal, blocks_web3_dal::BlocksWeb3Dal,
+    cancelled_transactions_dal::CancelledTransactionsDal, connection::holder::ConnectionHolder,
     consensus_dal::ConsensusDal, contract_verification_dal::ContractVerificationDal,
-    eth_sender_dal::EthSenderDal, events_dal::EventsDal, events_web3_dal::EventsWeb3Dal,
-    fri_gpu_prover_queue_dal::FriGpuProverQueueDal,
+    data_availability_dal::DataAvailabilityDal, db_export_dal::DbExportDal,
+    eth_sender_dal::EthSenderDal, eth_watcher_dal::EthWatcherDal, events_dal::EventsDal,
+    events_web3_dal::EventsWeb3Dal, fri_gpu_prover_queue_dal::FriGpuProverQueueDal,
     fri_proof_compressor_dal::FriProofCompressorDal,
     fri_protocol_versions_dal::FriProtocolVersionsDal, fri_prover_dal::FriProverDal,
     fri_scheduler_dependency_tracker_dal::FriSchedulerDependencyTrackerDal,
-    fri_witness_generator_dal::FriWitnessGeneratorDal, proof_generation_dal::ProofGenerationDal,
-    protocol_versions_dal::ProtocolVersionsDal,
-    protocol_versions_web3_dal::ProtocolVersionsWeb3Dal,
+    fri_witness_generator_dal::FriWitnessGeneratorDal,
+    paymaster_analytics_dal::PaymasterAnalyticsDal, priority_ops_dal::PriorityOpsDal,
+    proof_generation_dal::ProofGenerationDal, protocol_versions_dal::ProtocolVersionsDal,
+    protocol_versions_web3_dal::ProtocolVersionsWeb3Dal, pruning_dal::PruningDal,
     snapshot_recovery_dal::SnapshotRecoveryDal, snapshots_creator_dal::SnapshotsCreatorDal,
     snapshots_dal::SnapshotsDal, storage_dal::StorageDal, storage_logs_dal::StorageLogsDal,
-    storage_logs_dedup_dal::StorageLogsDedupDal, storage_web3_dal::StorageWeb3Dal,
-    sync_dal::SyncDal, system_dal::SystemDal, tokens_dal::TokensDal,
-    tokens_web3_dal::TokensWeb3Dal, transactions_dal::TransactionsDal,
-    transactions_web3_dal::TransactionsWeb3Dal,
+    storage_logs_dedup_dal::StorageLogsDedupDal, storage_partitions_dal::StoragePartitionsDal,
+    storage_web3_dal::StorageWeb3Dal, sync_dal::SyncDal, system_dal::SystemDal,
+    tokens_dal::TokensDal, tokens_web3_dal::TokensWeb3Dal, transactions_dal::TransactionsDal,
+    transactions_web3_dal::TransactionsWeb3Dal, tx_event_stream_dal::TxEventStreamDal,
+    tx_submission_rules_dal::TxSubmissionRulesDal,
 };
 
 #[macro_use]
@@ -30,10 +34,14 @@ pub mod accounts_dal;
 pub mod basic_witness_input_producer_dal;
 pub mod blocks_dal;
 pub mod blocks_web3_dal;
+pub mod cancelled_transactions_dal;
 pub mod connection;
 pub mod consensus_dal;
 pub mod contract_verification_dal;
+pub mod data_availability_dal;
+pub mod db_export_dal;
 pub mod eth_sender_dal;
+pub mod eth_watcher_dal;
 pub mod events_dal;
 pub mod events_web3_dal;
 pub mod fri_gpu_prover_queue_dal;
@@ -46,15 +54,19 @@ pub mod healthcheck;
 mod instrument;
 mod metrics;
 mod models;
+pub mod paymaster_analytics_dal;
+pub mod priority_ops_dal;
 pub mod proof_generation_dal;
 pub mod protocol_versions_dal;
 pub mod protocol_versions_web3_dal;
+pub mod pruning_dal;
 pub mod snapshot_recovery_dal;
 pub mod snapshots_creator_dal;
 pub mod snapshots_dal;
 pub mod storage_dal;
 pub mod storage_logs_dal;
 pub mod storage_logs_dedup_dal;
+pub mod storage_partitions_dal;
 pub mod storage_web3_dal;
 pub mod sync_dal;
 pub mod system_dal;
@@ -63,6 +75,8 @@ pub mod tokens_dal;
 pub mod tokens_web3_dal;
 pub mod transactions_dal;
 pub mod transactions_web3_dal;
+pub mod tx_event_stream_dal;
+pub mod tx_submission_rules_dal;
 
 #[cfg(test)]
 mod tests;
@@ -145,6 +159,10 @@ impl<'a> StorageProcessor<'a> {
         BlocksWeb3Dal { storage: self }
     }
 
+    pub fn cancelled_transactions_dal(&mut self) -> CancelledTransactionsDal<'_, 'a> {
+        CancelledTransactionsDal { storage: self }
+    }
+
     pub fn consensus_dal(&mut self) -> ConsensusDal<'_, 'a> {
         ConsensusDal { storage: self }
     }
@@ -153,6 +171,10 @@ impl<'a> StorageProcessor<'a> {
         EthSenderDal { storage: self }
     }
 
+    pub fn eth_watcher_dal(&mut self) -> EthWatcherDal<'_, 'a> {
+        EthWatcherDal { storage: self }
+    }
+
     pub fn events_dal(&mut self) -> EventsDal<'_, 'a> {
         EventsDal { storage: self }
     }
@@ -177,6 +199,10 @@ impl<'a> StorageProcessor<'a> {
         StorageLogsDedupDal { storage: self }
     }
 
+    pub fn storage_partitions_dal(&mut self) -> StoragePartitionsDal<'_, 'a> {
+        StoragePartitionsDal { storage: self }
+    }
+
     pub fn tokens_dal(&mut self) -> TokensDal<'_, 'a> {
         TokensDal { storage: self }
     }
@@ -189,6 +215,10 @@ impl<'a> StorageProcessor<'a> {
         ContractVerificationDal { storage: self }
     }
 
+    pub fn priority_ops_dal(&mut self) -> PriorityOpsDal<'_, 'a> {
+        PriorityOpsDal { storage: self }
+    }
+
     pub fn protocol_versions_dal(&mut self) -> ProtocolVersionsDal<'_, 'a> {
         ProtocolVersionsDal { storage: self }
     }
@@ -219,6 +249,18 @@ impl<'a> StorageProcessor<'a> {
         ProofGenerationDal { storage: self }
     }
 
+    pub fn pruning_dal(&mut self) -> PruningDal<'_, 'a> {
+        PruningDal { storage: self }
+    }
+
+    pub fn tx_submission_rules_dal(&mut self) -> TxSubmissionRulesDal<'_, 'a> {
+        TxSubmissionRulesDal { storage: self }
+    }
+
+    pub fn db_export_dal(&mut self) -> DbExportDal<'_, 'a> {
+        DbExportDal { storage: self }
+    }
+
     pub fn fri_gpu_prover_queue_dal(&mut self) -> FriGpuProverQueueDal<'_, 'a> {
         FriGpuProverQueueDal { storage: self }
     }
@@ -246,4 +288,16 @@ impl<'a> StorageProcessor<'a> {
     pub fn snapshot_recovery_dal(&mut self) -> SnapshotRecoveryDal<'_, 'a> {
         SnapshotRecoveryDal { storage: self }
     }
+
+    pub fn data_availability_dal(&mut self) -> DataAvailabilityDal<'_, 'a> {
+        DataAvailabilityDal { storage: self }
+    }
+
+    pub fn tx_event_stream_dal(&mut self) -> TxEventStreamDal<'_, 'a> {
+        TxEventStreamDal { storage: self }
+    }
+
+    pub fn paymaster_analytics_dal(&mut self) -> PaymasterAnalyticsDal<'_, 'a> {
+        PaymasterAnalyticsDal { storage: self }
+    }
 }
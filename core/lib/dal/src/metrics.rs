@@ -3,7 +3,7 @@
 use std::{thread, time::Duration};
 
 use vise::{
-    Buckets, Counter, EncodeLabelSet, EncodeLabelValue, Family, Histogram, LabeledFamily,
+    Buckets, Counter, EncodeLabelSet, EncodeLabelValue, Family, Gauge, Histogram, LabeledFamily,
     LatencyObserver, Metrics,
 };
 
@@ -11,7 +11,7 @@ use vise::{
 #[derive(Debug, Metrics)]
 #[metrics(prefix = "sql")]
 pub(crate) struct RequestMetrics {
-    /// Latency of a DB request.
+    /// Latency of a DB request, labeled with the DAL method name.
     #[metrics(buckets = Buckets::LATENCIES, labels = ["method"])]
     pub request: LabeledFamily<&'static str, Histogram<Duration>>,
     /// Counter of slow DB requests.
@@ -26,7 +26,7 @@ pub(crate) struct RequestMetrics {
 pub(crate) static REQUEST_METRICS: vise::Global<RequestMetrics> = vise::Global::new();
 
 /// Reporter of latency for DAL methods consisting of multiple DB queries. If there's a single query,
-/// use `.instrument().report_latency()` on it instead.
+/// use `.instrument()` on it instead.
 ///
 /// Should be created at the start of the relevant method and dropped when the latency needs to be reported.
 #[derive(Debug)]
@@ -58,6 +58,14 @@ pub(crate) enum ConnectionErrorKind {
     Other,
 }
 
+/// Target of a read routed via a replica-aware connection pool.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EncodeLabelValue, EncodeLabelSet)]
+#[metrics(label = "target", rename_all = "snake_case")]
+pub(crate) enum ReplicaRoutingTarget {
+    Replica,
+    Master,
+}
+
 impl From<&sqlx::Error> for ConnectionErrorKind {
     fn from(err: &sqlx::Error) -> Self {
         match err {
@@ -70,6 +78,7 @@ impl From<&sqlx::Error> for ConnectionErrorKind {
 }
 
 const POOL_SIZE_BUCKETS: Buckets = Buckets::linear(0.0..=100.0, 10.0);
+const POOL_SATURATION_BUCKETS: Buckets = Buckets::linear(0.0..=1.0, 0.1);
 
 /// Connection-related metrics.
 #[derive(Debug, Metrics)]
@@ -87,8 +96,23 @@ pub(crate) struct ConnectionMetrics {
     /// Current number of idle connections in the DB pool.
     #[metrics(buckets = POOL_SIZE_BUCKETS)]
     pub pool_idle: Histogram<usize>,
+    /// Ratio of connections in use to the maximum pool size observed when acquiring a connection.
+    #[metrics(buckets = POOL_SATURATION_BUCKETS)]
+    pub pool_saturation: Histogram<f64>,
+    /// Current number of requesters waiting to acquire a DB connection.
+    pub pool_waiting: Gauge<u64>,
+    /// Number of DB connection acquisitions that took longer than the slow query threshold.
+    #[metrics(labels = ["requester"])]
+    pub acquire_slow: LabeledFamily<&'static str, Counter>,
     /// Number of errors occurred when acquiring a DB connection.
     pub pool_acquire_error: Family<ConnectionErrorKind, Counter>,
+    /// Number of replica reads routed to the replica or falling back to master.
+    pub replica_routing: Family<ReplicaRoutingTarget, Counter>,
+    /// Last observed replication lag of the read replica.
+    pub replication_lag: Gauge<Duration>,
+    /// Number of DB transaction retries after serialization failures or deadlocks.
+    #[metrics(labels = ["name"])]
+    pub transaction_retries: LabeledFamily<&'static str, Counter>,
 }
 
 #[vise::register]
@@ -2,6 +2,7 @@ mod proto;
 pub mod storage_block;
 pub mod storage_eth_tx;
 pub mod storage_event;
+pub(crate) mod storage_factory_dep;
 pub mod storage_fee_monitor;
 pub mod storage_log;
 pub mod storage_protocol_version;
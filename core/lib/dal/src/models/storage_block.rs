@@ -12,6 +12,7 @@ use zksync_types::{
     api,
     block::{L1BatchHeader, MiniblockHeader},
     commitment::{L1BatchMetaParameters, L1BatchMetadata},
+    fee_model::{BatchFeeInput, PubdataIndependentBatchFeeModelInput},
     l2_to_l1_log::{L2ToL1Log, SystemL2ToL1Log, UserL2ToL1Log},
     Address, L1BatchNumber, MiniblockNumber, H2048, H256,
 };
@@ -518,10 +519,23 @@ pub struct StorageMiniblockHeader {
     // `min(virtual_blocks`, `miniblock_number - virtual_block_number`), i.e. making sure that virtual blocks
     // never go beyond the miniblock they are based on.
     pub virtual_blocks: i64,
+    /// Fair pubdata price; only set for miniblocks using the pubdata-independent fee input.
+    pub fair_pubdata_price: Option<i64>,
 }
 
 impl From<StorageMiniblockHeader> for MiniblockHeader {
     fn from(row: StorageMiniblockHeader) -> Self {
+        let batch_fee_input = match row.fair_pubdata_price {
+            Some(fair_pubdata_price) => {
+                BatchFeeInput::PubdataIndependent(PubdataIndependentBatchFeeModelInput {
+                    l1_gas_price: row.l1_gas_price as u64,
+                    fair_l2_gas_price: row.l2_fair_gas_price as u64,
+                    fair_pubdata_price: fair_pubdata_price as u64,
+                })
+            }
+            None => BatchFeeInput::l1_pegged(row.l1_gas_price as u64, row.l2_fair_gas_price as u64),
+        };
+
         MiniblockHeader {
             number: MiniblockNumber(row.number as u32),
             timestamp: row.timestamp as u64,
@@ -529,11 +543,7 @@ impl From<StorageMiniblockHeader> for MiniblockHeader {
             l1_tx_count: row.l1_tx_count as u16,
             l2_tx_count: row.l2_tx_count as u16,
             base_fee_per_gas: row.base_fee_per_gas.to_u64().unwrap(),
-            // For now, only L1 pegged fee model is supported.
-            batch_fee_input: zksync_types::fee_model::BatchFeeInput::l1_pegged(
-                row.l1_gas_price as u64,
-                row.l2_fair_gas_price as u64,
-            ),
+            batch_fee_input,
             base_system_contracts_hashes: convert_base_system_contracts_hashes(
                 row.bootloader_code_hash,
                 row.default_aa_code_hash,
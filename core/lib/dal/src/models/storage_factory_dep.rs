@@ -0,0 +1,57 @@
+//! Compression of factory dependency bytecodes stored in the `factory_deps` table.
+
+use std::io::{Read, Write};
+
+use flate2::{read::ZlibDecoder, write::ZlibEncoder, Compression};
+
+/// Compresses a bytecode to be stored in the `factory_deps.bytecode_compressed` column.
+pub(crate) fn compress_bytecode(bytecode: &[u8]) -> Vec<u8> {
+    let mut encoder = ZlibEncoder::new(Vec::with_capacity(bytecode.len() / 2), Compression::best());
+    encoder
+        .write_all(bytecode)
+        .expect("failed compressing bytecode into an in-memory buffer");
+    encoder
+        .finish()
+        .expect("failed compressing bytecode into an in-memory buffer")
+}
+
+/// Restores a bytecode from the `bytecode` and `bytecode_compressed` columns of the `factory_deps` table.
+/// Rows inserted before bytecode compression was introduced have the raw `bytecode` until they are processed
+/// by the compression backfill; all other rows only have `bytecode_compressed`.
+pub(crate) fn decode_bytecode(
+    bytecode: Option<Vec<u8>>,
+    bytecode_compressed: Option<Vec<u8>>,
+) -> sqlx::Result<Vec<u8>> {
+    if let Some(bytecode) = bytecode {
+        return Ok(bytecode);
+    }
+    let compressed = bytecode_compressed.ok_or_else(|| {
+        sqlx::Error::Decode("factory dependency has neither raw nor compressed bytecode".into())
+    })?;
+    let mut bytecode = Vec::with_capacity(compressed.len() * 2);
+    ZlibDecoder::new(compressed.as_slice())
+        .read_to_end(&mut bytecode)
+        .map_err(|err| sqlx::Error::Decode(err.into()))?;
+    Ok(bytecode)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bytecode_compression_roundtrip() {
+        let bytecode: Vec<u8> = (0..1_024_u32).flat_map(|i| (i % 7).to_be_bytes()).collect();
+        let compressed = compress_bytecode(&bytecode);
+        assert!(compressed.len() < bytecode.len());
+
+        let decoded = decode_bytecode(None, Some(compressed.clone())).unwrap();
+        assert_eq!(decoded, bytecode);
+        // Raw bytecode takes precedence.
+        let decoded = decode_bytecode(Some(vec![1, 2, 3]), Some(compressed)).unwrap();
+        assert_eq!(decoded, [1, 2, 3]);
+
+        decode_bytecode(None, None).unwrap_err();
+        decode_bytecode(None, Some(vec![0xff; 16])).unwrap_err();
+    }
+}
@@ -14,6 +14,8 @@ pub(crate) struct StorageSyncBlock {
     pub l1_gas_price: i64,
     // L2 gas price assumed in the corresponding batch
     pub l2_fair_gas_price: i64,
+    // Pubdata price assumed in the corresponding batch; `None` for L1-pegged fee input
+    pub fair_pubdata_price: Option<i64>,
     pub bootloader_code_hash: Option<Vec<u8>>,
     pub default_aa_code_hash: Option<Vec<u8>>,
     pub fee_account_address: Option<Vec<u8>>, // May be None if the block is not yet sealed
@@ -52,6 +54,10 @@ impl StorageSyncBlock {
                 .l2_fair_gas_price
                 .try_into()
                 .context("l2_fair_gas_price")?,
+            fair_pubdata_price: self
+                .fair_pubdata_price
+                .map(|price| price.try_into().context("fair_pubdata_price"))
+                .transpose()?,
             // TODO (SMA-1635): Make these fields non optional in database
             base_system_contracts_hashes: BaseSystemContractsHashes {
                 bootloader: parse_h256(
@@ -0,0 +1,380 @@
+use std::ops::RangeInclusive;
+
+use zksync_types::{
+    api::{L1BatchPaymasterStats, PaymasterBatchUsage, PaymasterUsage},
+    Address, L1BatchNumber,
+};
+use zksync_utils::bigdecimal_to_u256;
+
+use crate::{instrument::InstrumentExt, StorageProcessor};
+
+#[derive(Debug)]
+pub struct PaymasterAnalyticsDal<'a, 'c> {
+    pub(crate) storage: &'a mut StorageProcessor<'c>,
+}
+
+impl PaymasterAnalyticsDal<'_, '_> {
+    /// Returns the number of the last L1 batch for which paymaster usage was aggregated.
+    pub async fn get_last_aggregated_l1_batch(&mut self) -> sqlx::Result<Option<L1BatchNumber>> {
+        let row = sqlx::query!(
+            r#"
+            SELECT
+                MAX(l1_batch_number) AS "number"
+            FROM
+                l1_batch_paymaster_summaries
+            "#
+        )
+        .instrument("get_last_aggregated_l1_batch")
+        .fetch_one(self.storage.conn())
+        .await?;
+        Ok(row.number.map(|number| L1BatchNumber(number as u32)))
+    }
+
+    /// Aggregates usage of paymasters in the specified L1 batch based on the batch transactions. The batch must be
+    /// sealed. The method is idempotent; repeated calls for the same batch are no-ops.
+    pub async fn aggregate_l1_batch(&mut self, l1_batch_number: L1BatchNumber) -> sqlx::Result<()> {
+        // Transactions not using a paymaster have the zero paymaster address.
+        let no_paymaster = Address::zero();
+        let mut transaction = self.storage.start_transaction().await?;
+        sqlx::query!(
+            r#"
+            INSERT INTO
+                l1_batch_paymaster_stats (
+                    l1_batch_number,
+                    paymaster,
+                    tx_count,
+                    unique_users,
+                    sponsored_gas,
+                    sponsored_fee,
+                    created_at
+                )
+            SELECT
+                $1,
+                paymaster,
+                COUNT(*),
+                COUNT(DISTINCT initiator_address),
+                SUM(COALESCE(gas_limit, 0) - refunded_gas),
+                SUM((COALESCE(gas_limit, 0) - refunded_gas) * COALESCE(effective_gas_price, 0)),
+                NOW()
+            FROM
+                transactions
+            WHERE
+                l1_batch_number = $1
+                AND paymaster != $2
+            GROUP BY
+                paymaster
+            ON CONFLICT (l1_batch_number, paymaster) DO NOTHING
+            "#,
+            i64::from(l1_batch_number.0),
+            no_paymaster.as_bytes()
+        )
+        .instrument("aggregate_l1_batch#paymaster_stats")
+        .with_arg("l1_batch_number", &l1_batch_number)
+        .execute(transaction.conn())
+        .await?;
+
+        sqlx::query!(
+            r#"
+            INSERT INTO
+                l1_batch_paymaster_summaries (
+                    l1_batch_number,
+                    sponsored_tx_count,
+                    unique_users,
+                    sponsored_gas,
+                    sponsored_fee,
+                    created_at
+                )
+            SELECT
+                $1,
+                COUNT(*),
+                COUNT(DISTINCT initiator_address),
+                COALESCE(SUM(COALESCE(gas_limit, 0) - refunded_gas), 0),
+                COALESCE(
+                    SUM((COALESCE(gas_limit, 0) - refunded_gas) * COALESCE(effective_gas_price, 0)),
+                    0
+                ),
+                NOW()
+            FROM
+                transactions
+            WHERE
+                l1_batch_number = $1
+                AND paymaster != $2
+            ON CONFLICT (l1_batch_number) DO NOTHING
+            "#,
+            i64::from(l1_batch_number.0),
+            no_paymaster.as_bytes()
+        )
+        .instrument("aggregate_l1_batch#summary")
+        .with_arg("l1_batch_number", &l1_batch_number)
+        .execute(transaction.conn())
+        .await?;
+
+        transaction.commit().await
+    }
+
+    /// Removes aggregated paymaster usage for L1 batches after `last_l1_batch_to_keep`. Used when L1 batches
+    /// are reverted, so that the batches re-sealed in their place are aggregated anew.
+    pub async fn delete_paymaster_analytics(
+        &mut self,
+        last_l1_batch_to_keep: L1BatchNumber,
+    ) -> sqlx::Result<()> {
+        let mut transaction = self.storage.start_transaction().await?;
+        sqlx::query!(
+            r#"
+            DELETE FROM l1_batch_paymaster_stats
+            WHERE
+                l1_batch_number > $1
+            "#,
+            i64::from(last_l1_batch_to_keep.0)
+        )
+        .instrument("delete_paymaster_analytics#stats")
+        .with_arg("last_l1_batch_to_keep", &last_l1_batch_to_keep)
+        .execute(transaction.conn())
+        .await?;
+
+        sqlx::query!(
+            r#"
+            DELETE FROM l1_batch_paymaster_summaries
+            WHERE
+                l1_batch_number > $1
+            "#,
+            i64::from(last_l1_batch_to_keep.0)
+        )
+        .instrument("delete_paymaster_analytics#summaries")
+        .with_arg("last_l1_batch_to_keep", &last_l1_batch_to_keep)
+        .execute(transaction.conn())
+        .await?;
+
+        transaction.commit().await
+    }
+
+    /// Returns aggregated paymaster usage for the specified L1 batch, or `None` if the batch is not aggregated yet.
+    pub async fn get_l1_batch_paymaster_stats(
+        &mut self,
+        l1_batch_number: L1BatchNumber,
+    ) -> sqlx::Result<Option<L1BatchPaymasterStats>> {
+        let Some(summary) = sqlx::query!(
+            r#"
+            SELECT
+                sponsored_tx_count,
+                unique_users,
+                sponsored_gas,
+                sponsored_fee
+            FROM
+                l1_batch_paymaster_summaries
+            WHERE
+                l1_batch_number = $1
+            "#,
+            i64::from(l1_batch_number.0)
+        )
+        .instrument("get_l1_batch_paymaster_stats#summary")
+        .with_arg("l1_batch_number", &l1_batch_number)
+        .fetch_optional(self.storage.conn())
+        .await?
+        else {
+            return Ok(None);
+        };
+
+        let rows = sqlx::query!(
+            r#"
+            SELECT
+                paymaster,
+                tx_count,
+                unique_users,
+                sponsored_gas,
+                sponsored_fee
+            FROM
+                l1_batch_paymaster_stats
+            WHERE
+                l1_batch_number = $1
+            ORDER BY
+                tx_count DESC,
+                paymaster
+            "#,
+            i64::from(l1_batch_number.0)
+        )
+        .instrument("get_l1_batch_paymaster_stats#paymasters")
+        .with_arg("l1_batch_number", &l1_batch_number)
+        .fetch_all(self.storage.conn())
+        .await?;
+
+        let paymasters = rows
+            .into_iter()
+            .map(|row| PaymasterBatchUsage {
+                paymaster: Address::from_slice(&row.paymaster),
+                tx_count: row.tx_count as u64,
+                unique_users: row.unique_users as u64,
+                sponsored_gas: bigdecimal_to_u256(row.sponsored_gas),
+                sponsored_fee: bigdecimal_to_u256(row.sponsored_fee),
+            })
+            .collect();
+        Ok(Some(L1BatchPaymasterStats {
+            l1_batch_number,
+            sponsored_tx_count: summary.sponsored_tx_count as u64,
+            unique_users: summary.unique_users as u64,
+            sponsored_gas: bigdecimal_to_u256(summary.sponsored_gas),
+            sponsored_fee: bigdecimal_to_u256(summary.sponsored_fee),
+            paymasters,
+        }))
+    }
+
+    /// Returns paymasters with the most sponsored gas in the specified range of L1 batches.
+    pub async fn get_top_paymasters(
+        &mut self,
+        l1_batch_numbers: RangeInclusive<L1BatchNumber>,
+        limit: usize,
+    ) -> sqlx::Result<Vec<PaymasterUsage>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT
+                paymaster,
+                SUM(tx_count) AS "tx_count!",
+                SUM(sponsored_gas) AS "sponsored_gas!",
+                SUM(sponsored_fee) AS "sponsored_fee!"
+            FROM
+                l1_batch_paymaster_stats
+            WHERE
+                l1_batch_number BETWEEN $1 AND $2
+            GROUP BY
+                paymaster
+            ORDER BY
+                SUM(sponsored_gas) DESC,
+                paymaster
+            LIMIT
+                $3
+            "#,
+            i64::from(l1_batch_numbers.start().0),
+            i64::from(l1_batch_numbers.end().0),
+            limit as i64
+        )
+        .instrument("get_top_paymasters")
+        .with_arg("l1_batch_numbers", &l1_batch_numbers)
+        .with_arg("limit", &limit)
+        .fetch_all(self.storage.conn())
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| PaymasterUsage {
+                paymaster: Address::from_slice(&row.paymaster),
+                tx_count: bigdecimal_to_u256(row.tx_count).as_u64(),
+                sponsored_gas: bigdecimal_to_u256(row.sponsored_gas),
+                sponsored_fee: bigdecimal_to_u256(row.sponsored_fee),
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zksync_contracts::BaseSystemContractsHashes;
+    use zksync_types::{
+        block::L1BatchHeader, fee::TransactionExecutionMetrics, ProtocolVersion, ProtocolVersionId,
+        U256,
+    };
+
+    use super::*;
+    use crate::{
+        tests::{mock_execution_result, mock_l2_transaction},
+        ConnectionPool,
+    };
+
+    #[tokio::test]
+    async fn aggregating_paymaster_usage() {
+        let pool = ConnectionPool::test_pool().await;
+        let mut conn = pool.access_storage().await.unwrap();
+        conn.protocol_versions_dal()
+            .save_protocol_version_with_tx(ProtocolVersion::default())
+            .await;
+        let header = L1BatchHeader::new(
+            L1BatchNumber(1),
+            100,
+            Address::default(),
+            BaseSystemContractsHashes::default(),
+            ProtocolVersionId::default(),
+        );
+        conn.blocks_dal()
+            .insert_l1_batch(&header, &[], Default::default(), &[], &[], 0)
+            .await
+            .unwrap();
+
+        let paymaster = Address::repeat_byte(0x11);
+        let mut tx = mock_l2_transaction();
+        tx.common_data.paymaster_params.paymaster = paymaster;
+        let gas_limit = tx.common_data.fee.gas_limit;
+        conn.transactions_dal()
+            .insert_transaction_l2(tx.clone(), TransactionExecutionMetrics::default())
+            .await;
+        conn.transactions_dal()
+            .mark_txs_as_executed_in_l1_batch(L1BatchNumber(1), &[mock_execution_result(tx)])
+            .await;
+
+        assert_eq!(
+            conn.paymaster_analytics_dal()
+                .get_last_aggregated_l1_batch()
+                .await
+                .unwrap(),
+            None
+        );
+        for _ in 0..2 {
+            // Check that aggregation is idempotent.
+            conn.paymaster_analytics_dal()
+                .aggregate_l1_batch(L1BatchNumber(1))
+                .await
+                .unwrap();
+        }
+        assert_eq!(
+            conn.paymaster_analytics_dal()
+                .get_last_aggregated_l1_batch()
+                .await
+                .unwrap(),
+            Some(L1BatchNumber(1))
+        );
+
+        let stats = conn
+            .paymaster_analytics_dal()
+            .get_l1_batch_paymaster_stats(L1BatchNumber(1))
+            .await
+            .unwrap()
+            .expect("no stats for L1 batch");
+        assert_eq!(stats.sponsored_tx_count, 1);
+        assert_eq!(stats.unique_users, 1);
+        assert_eq!(stats.sponsored_gas, gas_limit);
+        assert_eq!(stats.paymasters.len(), 1);
+        assert_eq!(stats.paymasters[0].paymaster, paymaster);
+        assert_eq!(stats.paymasters[0].tx_count, 1);
+
+        let top_paymasters = conn
+            .paymaster_analytics_dal()
+            .get_top_paymasters(L1BatchNumber(0)..=L1BatchNumber(1), 10)
+            .await
+            .unwrap();
+        assert_eq!(
+            top_paymasters,
+            [PaymasterUsage {
+                paymaster,
+                tx_count: 1,
+                sponsored_gas: gas_limit,
+                sponsored_fee: U256::zero(),
+            }]
+        );
+
+        conn.paymaster_analytics_dal()
+            .delete_paymaster_analytics(L1BatchNumber(0))
+            .await
+            .unwrap();
+        assert_eq!(
+            conn.paymaster_analytics_dal()
+                .get_last_aggregated_l1_batch()
+                .await
+                .unwrap(),
+            None
+        );
+        let top_paymasters = conn
+            .paymaster_analytics_dal()
+            .get_top_paymasters(L1BatchNumber(0)..=L1BatchNumber(1), 10)
+            .await
+            .unwrap();
+        assert!(top_paymasters.is_empty());
+    }
+}
@@ -0,0 +1,291 @@
+use std::ops::RangeInclusive;
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+use zksync_types::{
+    api::{PriorityOpStage, PriorityOpStatus},
+    L1BatchNumber, MiniblockNumber, PriorityOpId, H256,
+};
+
+use crate::{instrument::InstrumentExt, StorageProcessor};
+
+/// Priority operation received from L1, but not yet included into a miniblock.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PendingPriorityOp {
+    pub serial_id: PriorityOpId,
+    /// Hash of the L1 transaction that has emitted the operation. May be missing for operations
+    /// received before L1 transaction hashes started being recorded.
+    pub l1_tx_hash: Option<H256>,
+    pub received_at: DateTime<Utc>,
+}
+
+/// Priority operation included into a miniblock.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IncludedPriorityOp {
+    pub serial_id: PriorityOpId,
+    pub miniblock_number: MiniblockNumber,
+    pub received_at: DateTime<Utc>,
+    pub included_at: DateTime<Utc>,
+}
+
+fn timestamp_to_datetime(timestamp: i64) -> DateTime<Utc> {
+    let naive = NaiveDateTime::from_timestamp_opt(timestamp, 0).expect("invalid timestamp");
+    DateTime::from_naive_utc_and_offset(naive, Utc)
+}
+
+/// Tracks L1 -> L2 priority operations from their emission on L1 to inclusion on L2.
+#[derive(Debug)]
+pub struct PriorityOpsDal<'a, 'c> {
+    pub(crate) storage: &'a mut StorageProcessor<'c>,
+}
+
+impl PriorityOpsDal<'_, '_> {
+    /// Returns statuses of all priority operations emitted by the specified L1 transaction, ordered by serial ID.
+    pub async fn get_priority_op_statuses(
+        &mut self,
+        l1_tx_hash: H256,
+    ) -> sqlx::Result<Vec<PriorityOpStatus>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT
+                transactions.priority_op_id AS "priority_op_id!",
+                transactions.hash,
+                transactions.l1_block_number,
+                transactions.received_at,
+                transactions.miniblock_number,
+                transactions.l1_batch_number,
+                miniblocks.timestamp AS "miniblock_timestamp?"
+            FROM
+                priority_op_l1_tx_hashes
+                INNER JOIN transactions ON transactions.priority_op_id = priority_op_l1_tx_hashes.priority_op_id
+                LEFT JOIN miniblocks ON miniblocks.number = transactions.miniblock_number
+            WHERE
+                priority_op_l1_tx_hashes.l1_tx_hash = $1
+            ORDER BY
+                transactions.priority_op_id
+            "#,
+            l1_tx_hash.as_bytes()
+        )
+        .instrument("get_priority_op_statuses")
+        .with_arg("l1_tx_hash", &l1_tx_hash)
+        .fetch_all(self.storage.conn())
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| {
+                let stage = match (row.miniblock_number, row.l1_batch_number) {
+                    (_, Some(_)) => PriorityOpStage::Sealed,
+                    (Some(_), None) => PriorityOpStage::Included,
+                    (None, None) => PriorityOpStage::Pending,
+                };
+                PriorityOpStatus {
+                    serial_id: row.priority_op_id as u64,
+                    l1_tx_hash,
+                    l2_tx_hash: H256::from_slice(&row.hash),
+                    l1_block_number: row.l1_block_number.map(|number| number as u64),
+                    stage,
+                    received_at: DateTime::from_naive_utc_and_offset(row.received_at, Utc),
+                    miniblock_number: row
+                        .miniblock_number
+                        .map(|number| MiniblockNumber(number as u32)),
+                    included_at: row.miniblock_timestamp.map(timestamp_to_datetime),
+                    l1_batch_number: row
+                        .l1_batch_number
+                        .map(|number| L1BatchNumber(number as u32)),
+                }
+            })
+            .collect())
+    }
+
+    /// Returns the pending priority operation with the least serial ID. Since priority operations are processed
+    /// in order, this is the operation waiting for inclusion for the longest time.
+    pub async fn get_first_pending_priority_op(
+        &mut self,
+    ) -> sqlx::Result<Option<PendingPriorityOp>> {
+        let row = sqlx::query!(
+            r#"
+            SELECT
+                transactions.priority_op_id AS "priority_op_id!",
+                transactions.received_at,
+                priority_op_l1_tx_hashes.l1_tx_hash AS "l1_tx_hash?"
+            FROM
+                transactions
+                LEFT JOIN priority_op_l1_tx_hashes ON priority_op_l1_tx_hashes.priority_op_id = transactions.priority_op_id
+            WHERE
+                transactions.priority_op_id > COALESCE(
+                    (
+                        SELECT
+                            MAX(priority_op_id)
+                        FROM
+                            transactions
+                        WHERE
+                            priority_op_id IS NOT NULL
+                            AND miniblock_number IS NOT NULL
+                    ),
+                    -1
+                )
+            ORDER BY
+                transactions.priority_op_id
+            LIMIT
+                1
+            "#
+        )
+        .instrument("get_first_pending_priority_op")
+        .fetch_optional(self.storage.conn())
+        .await?;
+
+        Ok(row.map(|row| PendingPriorityOp {
+            serial_id: PriorityOpId(row.priority_op_id as u64),
+            l1_tx_hash: row.l1_tx_hash.as_deref().map(H256::from_slice),
+            received_at: DateTime::from_naive_utc_and_offset(row.received_at, Utc),
+        }))
+    }
+
+    /// Returns the number of priority operations not yet included into a miniblock.
+    pub async fn get_pending_priority_ops_count(&mut self) -> sqlx::Result<usize> {
+        let count = sqlx::query!(
+            r#"
+            SELECT
+                COUNT(*) AS "count!"
+            FROM
+                transactions
+            WHERE
+                priority_op_id > COALESCE(
+                    (
+                        SELECT
+                            MAX(priority_op_id)
+                        FROM
+                            transactions
+                        WHERE
+                            priority_op_id IS NOT NULL
+                            AND miniblock_number IS NOT NULL
+                    ),
+                    -1
+                )
+            "#
+        )
+        .instrument("get_pending_priority_ops_count")
+        .fetch_one(self.storage.conn())
+        .await?
+        .count;
+        Ok(count as usize)
+    }
+
+    /// Returns priority operations included into miniblocks in the specified range.
+    pub async fn get_included_priority_ops(
+        &mut self,
+        miniblocks: RangeInclusive<MiniblockNumber>,
+    ) -> sqlx::Result<Vec<IncludedPriorityOp>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT
+                transactions.priority_op_id AS "priority_op_id!",
+                transactions.miniblock_number AS "miniblock_number!",
+                transactions.received_at,
+                miniblocks.timestamp
+            FROM
+                transactions
+                INNER JOIN miniblocks ON miniblocks.number = transactions.miniblock_number
+            WHERE
+                transactions.miniblock_number BETWEEN $1 AND $2
+                AND transactions.priority_op_id IS NOT NULL
+            ORDER BY
+                transactions.priority_op_id
+            "#,
+            miniblocks.start().0 as i64,
+            miniblocks.end().0 as i64
+        )
+        .instrument("get_included_priority_ops")
+        .with_arg("miniblocks", &miniblocks)
+        .fetch_all(self.storage.conn())
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| IncludedPriorityOp {
+                serial_id: PriorityOpId(row.priority_op_id as u64),
+                miniblock_number: MiniblockNumber(row.miniblock_number as u32),
+                received_at: DateTime::from_naive_utc_and_offset(row.received_at, Utc),
+                included_at: timestamp_to_datetime(row.timestamp),
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zksync_types::{l1::L1Tx, L1BlockNumber};
+
+    use super::*;
+    use crate::{
+        tests::{create_miniblock_header, mock_execution_result, mock_l1_execute},
+        ConnectionPool,
+    };
+
+    fn l1_tx(serial_id: u64, l1_tx_hash: H256) -> L1Tx {
+        let mut tx = mock_l1_execute();
+        tx.common_data.serial_id = PriorityOpId(serial_id);
+        tx.common_data.canonical_tx_hash = H256::from_low_u64_be(serial_id + 1);
+        tx.common_data.eth_hash = l1_tx_hash;
+        tx
+    }
+
+    #[tokio::test]
+    async fn tracking_priority_ops() {
+        let pool = ConnectionPool::test_pool().await;
+        let mut storage = pool.access_storage().await.unwrap();
+        let l1_tx_hash = H256::repeat_byte(1);
+        let txs = [l1_tx(0, l1_tx_hash), l1_tx(1, l1_tx_hash)];
+        for tx in &txs {
+            storage
+                .transactions_dal()
+                .insert_transaction_l1(tx.clone(), L1BlockNumber(1))
+                .await;
+        }
+
+        let mut dal = storage.priority_ops_dal();
+        assert_eq!(dal.get_pending_priority_ops_count().await.unwrap(), 2);
+        let first_pending = dal.get_first_pending_priority_op().await.unwrap().unwrap();
+        assert_eq!(first_pending.serial_id, PriorityOpId(0));
+        assert_eq!(first_pending.l1_tx_hash, Some(l1_tx_hash));
+        let statuses = dal.get_priority_op_statuses(l1_tx_hash).await.unwrap();
+        assert_eq!(statuses.len(), 2);
+        assert!(statuses
+            .iter()
+            .all(|status| status.stage == PriorityOpStage::Pending));
+        assert_eq!(statuses[1].l2_tx_hash, txs[1].hash());
+        assert!(dal
+            .get_priority_op_statuses(H256::zero())
+            .await
+            .unwrap()
+            .is_empty());
+
+        storage
+            .blocks_dal()
+            .insert_miniblock(&create_miniblock_header(1))
+            .await
+            .unwrap();
+        let executed_tx = mock_execution_result(txs[0].clone());
+        storage
+            .transactions_dal()
+            .mark_txs_as_executed_in_miniblock(MiniblockNumber(1), &[executed_tx], 1.into())
+            .await;
+
+        let mut dal = storage.priority_ops_dal();
+        assert_eq!(dal.get_pending_priority_ops_count().await.unwrap(), 1);
+        let first_pending = dal.get_first_pending_priority_op().await.unwrap().unwrap();
+        assert_eq!(first_pending.serial_id, PriorityOpId(1));
+        let statuses = dal.get_priority_op_statuses(l1_tx_hash).await.unwrap();
+        assert_eq!(statuses[0].stage, PriorityOpStage::Included);
+        assert_eq!(statuses[0].miniblock_number, Some(MiniblockNumber(1)));
+        assert!(statuses[0].included_at.is_some());
+        assert_eq!(statuses[1].stage, PriorityOpStage::Pending);
+
+        let included_ops = dal
+            .get_included_priority_ops(MiniblockNumber(1)..=MiniblockNumber(1))
+            .await
+            .unwrap();
+        assert_eq!(included_ops.len(), 1);
+        assert_eq!(included_ops[0].serial_id, PriorityOpId(0));
+    }
+}
@@ -1,7 +1,10 @@
 use std::time::Duration;
 
 use strum::{Display, EnumString};
-use zksync_types::L1BatchNumber;
+use zksync_types::{
+    prover_server_api::{ProofGenerationPriority, ProofGenerationQueueStats},
+    L1BatchNumber,
+};
 
 use crate::{time_utils::pg_interval_from_duration, SqlxError, StorageProcessor};
 
@@ -23,11 +26,16 @@ enum ProofGenerationJobStatus {
 }
 
 impl ProofGenerationDal<'_, '_> {
+    /// Picks the next L1 batch to be proven. Batches are ordered by their priority, then by deadline,
+    /// and then by number. Batches with an expired deadline, and batches waiting for longer than `aging_timeout`
+    /// (if specified) are treated as having the high priority.
     pub async fn get_next_block_to_be_proven(
         &mut self,
         processing_timeout: Duration,
+        aging_timeout: Option<Duration>,
     ) -> Option<L1BatchNumber> {
         let processing_timeout = pg_interval_from_duration(processing_timeout);
+        let aging_timeout = aging_timeout.map(pg_interval_from_duration);
         let result: Option<L1BatchNumber> = sqlx::query!(
             r#"
             UPDATE proof_generation_details
@@ -48,6 +56,12 @@ impl ProofGenerationDal<'_, '_> {
                             AND prover_taken_at < NOW() - $1::INTERVAL
                         )
                     ORDER BY
+                        CASE
+                            WHEN deadline <= NOW() THEN GREATEST(priority, $3)
+                            WHEN created_at < NOW() - $2::INTERVAL THEN GREATEST(priority, $3)
+                            ELSE priority
+                        END DESC,
+                        deadline ASC NULLS LAST,
                         l1_batch_number ASC
                     LIMIT
                         1
@@ -58,6 +72,8 @@ impl ProofGenerationDal<'_, '_> {
                 proof_generation_details.l1_batch_number
             "#,
             &processing_timeout,
+            aging_timeout.as_ref(),
+            ProofGenerationPriority::High.to_db_value()
         )
         .fetch_optional(self.storage.conn())
         .await
@@ -67,6 +83,70 @@ impl ProofGenerationDal<'_, '_> {
         result
     }
 
+    /// Sets the priority and deadline for proving the specified L1 batch. If `deadline` is `None`,
+    /// the previously set deadline is cleared.
+    pub async fn set_proof_generation_priority(
+        &mut self,
+        block_number: L1BatchNumber,
+        priority: ProofGenerationPriority,
+        deadline: Option<Duration>,
+    ) -> Result<(), SqlxError> {
+        let deadline = deadline.map(pg_interval_from_duration);
+        sqlx::query!(
+            r#"
+            UPDATE proof_generation_details
+            SET
+                priority = $1,
+                deadline = NOW() + $2::INTERVAL,
+                updated_at = NOW()
+            WHERE
+                l1_batch_number = $3
+            "#,
+            priority.to_db_value(),
+            deadline.as_ref(),
+            block_number.0 as i64,
+        )
+        .execute(self.storage.conn())
+        .await?
+        .rows_affected()
+        .eq(&1)
+        .then_some(())
+        .ok_or(sqlx::Error::RowNotFound)
+    }
+
+    /// Returns statistics for L1 batches waiting to be picked by a prover, grouped by priority.
+    pub async fn get_queue_stats(&mut self) -> Result<ProofGenerationQueueStats, SqlxError> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT
+                priority,
+                COUNT(*) AS "count!",
+                MIN(l1_batch_number) AS "oldest_l1_batch_number!"
+            FROM
+                proof_generation_details
+            WHERE
+                status = 'ready_to_be_proven'
+            GROUP BY
+                priority
+            "#
+        )
+        .fetch_all(self.storage.conn())
+        .await?;
+
+        let mut stats = ProofGenerationQueueStats::default();
+        for row in rows {
+            let priority = ProofGenerationPriority::from_db_value(row.priority);
+            *stats.queued_jobs.entry(priority).or_default() += row.count as usize;
+            let oldest_l1_batch = L1BatchNumber(row.oldest_l1_batch_number as u32);
+            stats.oldest_queued_l1_batch = Some(
+                stats
+                    .oldest_queued_l1_batch
+                    .map_or(oldest_l1_batch, |batch| batch.min(oldest_l1_batch)),
+            );
+        }
+        Ok(stats)
+    }
+
     pub async fn save_proof_artifacts_metadata(
         &mut self,
         block_number: L1BatchNumber,
@@ -184,3 +264,85 @@ impl ProofGenerationDal<'_, '_> {
         result
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::ConnectionPool;
+
+    const PROCESSING_TIMEOUT: Duration = Duration::from_secs(3_600);
+
+    #[tokio::test]
+    async fn picking_jobs_by_priority() {
+        let pool = ConnectionPool::test_pool().await;
+        let mut conn = pool.access_storage().await.unwrap();
+        let mut dal = conn.proof_generation_dal();
+        for number in 1..=4 {
+            dal.insert_proof_generation_details(L1BatchNumber(number), "data")
+                .await;
+        }
+        dal.set_proof_generation_priority(L1BatchNumber(1), ProofGenerationPriority::Low, None)
+            .await
+            .unwrap();
+        dal.set_proof_generation_priority(L1BatchNumber(3), ProofGenerationPriority::High, None)
+            .await
+            .unwrap();
+        // The deadline is already expired, so the batch should be treated as having the high priority.
+        dal.set_proof_generation_priority(
+            L1BatchNumber(4),
+            ProofGenerationPriority::Normal,
+            Some(Duration::ZERO),
+        )
+        .await
+        .unwrap();
+        dal.set_proof_generation_priority(L1BatchNumber(5), ProofGenerationPriority::High, None)
+            .await
+            .unwrap_err();
+
+        let stats = dal.get_queue_stats().await.unwrap();
+        assert_eq!(
+            stats.queued_jobs,
+            HashMap::from([
+                (ProofGenerationPriority::Low, 1),
+                (ProofGenerationPriority::Normal, 2),
+                (ProofGenerationPriority::High, 1),
+            ])
+        );
+        assert_eq!(stats.oldest_queued_l1_batch, Some(L1BatchNumber(1)));
+
+        let mut picked_batches = vec![];
+        while let Some(number) = dal
+            .get_next_block_to_be_proven(PROCESSING_TIMEOUT, None)
+            .await
+        {
+            picked_batches.push(number.0);
+        }
+        assert_eq!(picked_batches, [4, 3, 2, 1]);
+        assert_eq!(
+            dal.get_queue_stats().await.unwrap(),
+            ProofGenerationQueueStats::default()
+        );
+    }
+
+    #[tokio::test]
+    async fn old_jobs_are_prioritized() {
+        let pool = ConnectionPool::test_pool().await;
+        let mut conn = pool.access_storage().await.unwrap();
+        let mut dal = conn.proof_generation_dal();
+        dal.insert_proof_generation_details(L1BatchNumber(1), "data")
+            .await;
+        dal.insert_proof_generation_details(L1BatchNumber(2), "data")
+            .await;
+        dal.set_proof_generation_priority(L1BatchNumber(2), ProofGenerationPriority::High, None)
+            .await
+            .unwrap();
+
+        // With zero aging timeout, all batches are treated as having the high priority.
+        let number = dal
+            .get_next_block_to_be_proven(PROCESSING_TIMEOUT, Some(Duration::ZERO))
+            .await;
+        assert_eq!(number, Some(L1BatchNumber(1)));
+    }
+}
@@ -1,10 +1,11 @@
-use std::convert::TryInto;
+use std::{collections::HashMap, convert::TryInto};
 
 use zksync_contracts::{BaseSystemContracts, BaseSystemContractsHashes};
 use zksync_types::{
     protocol_version::{L1VerifierConfig, ProtocolUpgradeTx, ProtocolVersion, VerifierParams},
     Address, ProtocolVersionId, H256,
 };
+use zksync_utils::bytecode::hash_bytecode;
 
 use crate::{
     models::storage_protocol_version::{protocol_version_from_storage, StorageProtocolVersion},
@@ -133,6 +134,33 @@ impl ProtocolVersionsDal<'_, '_> {
         (contracts, (row.id as u16).try_into().unwrap())
     }
 
+    /// Returns the ID of the protocol version that should be used for an L1 batch with the specified timestamp,
+    /// i.e., the latest version with the upgrade timestamp not exceeding `current_timestamp`.
+    pub async fn protocol_version_id_by_timestamp(
+        &mut self,
+        current_timestamp: u64,
+    ) -> ProtocolVersionId {
+        let row = sqlx::query!(
+            r#"
+            SELECT
+                id
+            FROM
+                protocol_versions
+            WHERE
+                timestamp <= $1
+            ORDER BY
+                id DESC
+            LIMIT
+                1
+            "#,
+            current_timestamp as i64
+        )
+        .fetch_one(self.storage.conn())
+        .await
+        .unwrap();
+        (row.id as u16).try_into().unwrap()
+    }
+
     pub async fn load_base_system_contracts_by_version_id(
         &mut self,
         version_id: u16,
@@ -152,19 +180,31 @@ impl ProtocolVersionsDal<'_, '_> {
         .fetch_optional(self.storage.conn())
         .await
         .unwrap();
-        if let Some(row) = row {
-            Some(
-                self.storage
-                    .storage_dal()
-                    .get_base_system_contracts(
-                        H256::from_slice(&row.bootloader_code_hash),
-                        H256::from_slice(&row.default_account_code_hash),
-                    )
-                    .await,
-            )
-        } else {
-            None
-        }
+        let row = row?;
+
+        // Base system contracts introduced by a protocol upgrade are only persisted as factory deps
+        // once the miniblock with the upgrade transaction is sealed; until then, they are taken
+        // from the upgrade transaction itself.
+        let upgrade_tx = match ProtocolVersionId::try_from(version_id) {
+            Ok(version_id) => self.get_protocol_upgrade_tx(version_id).await,
+            Err(_) => None,
+        };
+        let upgrade_factory_deps: HashMap<_, _> = upgrade_tx
+            .and_then(|tx| tx.execute.factory_deps)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|bytecode| (hash_bytecode(&bytecode), bytecode))
+            .collect();
+        Some(
+            self.storage
+                .storage_dal()
+                .get_base_system_contracts_with_fallback(
+                    H256::from_slice(&row.bootloader_code_hash),
+                    H256::from_slice(&row.default_account_code_hash),
+                    &upgrade_factory_deps,
+                )
+                .await,
+        )
     }
 
     pub async fn load_previous_version(
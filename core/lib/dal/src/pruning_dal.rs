@@ -0,0 +1,305 @@
+use zksync_types::{L1BatchNumber, MiniblockNumber};
+
+use crate::{instrument::InstrumentExt, StorageProcessor};
+
+/// Information about pruned data in the storage.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PruningInfo {
+    pub last_pruned_l1_batch: Option<L1BatchNumber>,
+    pub last_pruned_miniblock: Option<MiniblockNumber>,
+}
+
+impl PruningInfo {
+    /// Returns the earliest miniblock for which miniblock-level data (events, call traces and historical state)
+    /// is available.
+    pub fn earliest_available_miniblock(&self) -> MiniblockNumber {
+        self.last_pruned_miniblock
+            .map_or(MiniblockNumber(0), |number| number + 1)
+    }
+}
+
+/// Statistics of a single [`PruningDal::prune_batches_range()`] call.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PruningStats {
+    pub deleted_events: u64,
+    pub deleted_call_traces: u64,
+    /// Number of storage logs in the pruned miniblocks overwritten by later logs in the same miniblocks.
+    pub deleted_storage_logs_from_pruned_batches: u64,
+    /// Number of storage logs from previously pruned miniblocks overwritten by logs in the pruned miniblocks.
+    pub deleted_storage_logs_from_past_batches: u64,
+}
+
+/// DAL removing miniblock-level data for old L1 batches.
+///
+/// Pruning retains the latest value of each storage slot as of the last pruned miniblock, so the current state
+/// (and the state for each non-pruned miniblock) can still be reconstructed from `storage_logs`. Block headers,
+/// transactions and L2-to-L1 logs are retained as well.
+#[derive(Debug)]
+pub struct PruningDal<'a, 'c> {
+    pub(crate) storage: &'a mut StorageProcessor<'c>,
+}
+
+impl PruningDal<'_, '_> {
+    pub async fn get_pruning_info(&mut self) -> sqlx::Result<PruningInfo> {
+        let row = sqlx::query!(
+            r#"
+            SELECT
+                pruned_l1_batch,
+                pruned_miniblock
+            FROM
+                pruning_log
+            ORDER BY
+                pruned_l1_batch DESC
+            LIMIT
+                1
+            "#
+        )
+        .instrument("get_pruning_info")
+        .fetch_optional(self.storage.conn())
+        .await?;
+
+        Ok(row.map_or_else(PruningInfo::default, |row| PruningInfo {
+            last_pruned_l1_batch: Some(L1BatchNumber(row.pruned_l1_batch as u32)),
+            last_pruned_miniblock: Some(MiniblockNumber(row.pruned_miniblock as u32)),
+        }))
+    }
+
+    /// Prunes data for all miniblocks after the previously pruned miniblock up to and including
+    /// `last_miniblock_to_prune`, which must be the last miniblock in `last_l1_batch_to_prune`.
+    ///
+    /// The caller is responsible for checking that the pruned data is not needed by other components
+    /// (e.g., that the pruned L1 batches are executed on L1 and processed by the Merkle tree).
+    pub async fn prune_batches_range(
+        &mut self,
+        last_l1_batch_to_prune: L1BatchNumber,
+        last_miniblock_to_prune: MiniblockNumber,
+    ) -> sqlx::Result<PruningStats> {
+        let mut transaction = self.storage.start_transaction().await?;
+        let first_miniblock_to_prune = transaction
+            .pruning_dal()
+            .get_pruning_info()
+            .await?
+            .earliest_available_miniblock();
+        let miniblocks = (
+            i64::from(first_miniblock_to_prune.0),
+            i64::from(last_miniblock_to_prune.0),
+        );
+
+        let deleted_events = sqlx::query!(
+            r#"
+            DELETE FROM events
+            WHERE
+                miniblock_number BETWEEN $1 AND $2
+            "#,
+            miniblocks.0,
+            miniblocks.1
+        )
+        .instrument("prune_batches_range#events")
+        .with_arg("miniblocks", &miniblocks)
+        .execute(transaction.conn())
+        .await?
+        .rows_affected();
+
+        let deleted_call_traces = sqlx::query!(
+            r#"
+            DELETE FROM call_traces USING transactions
+            WHERE
+                transactions.miniblock_number BETWEEN $1 AND $2
+                AND call_traces.tx_hash = transactions.hash
+            "#,
+            miniblocks.0,
+            miniblocks.1
+        )
+        .instrument("prune_batches_range#call_traces")
+        .with_arg("miniblocks", &miniblocks)
+        .execute(transaction.conn())
+        .await?
+        .rows_affected();
+
+        let deleted_storage_logs_from_past_batches = sqlx::query!(
+            r#"
+            DELETE FROM storage_logs
+            WHERE
+                miniblock_number < $1
+                AND hashed_key IN (
+                    SELECT
+                        hashed_key
+                    FROM
+                        storage_logs
+                    WHERE
+                        miniblock_number BETWEEN $1 AND $2
+                )
+            "#,
+            miniblocks.0,
+            miniblocks.1
+        )
+        .instrument("prune_batches_range#storage_logs_from_past_batches")
+        .with_arg("miniblocks", &miniblocks)
+        .execute(transaction.conn())
+        .await?
+        .rows_affected();
+
+        let deleted_storage_logs_from_pruned_batches = sqlx::query!(
+            r#"
+            DELETE FROM storage_logs USING (
+                SELECT
+                    hashed_key,
+                    MAX(ARRAY[miniblock_number, operation_number]::INT[]) AS op
+                FROM
+                    storage_logs
+                WHERE
+                    miniblock_number BETWEEN $1 AND $2
+                GROUP BY
+                    hashed_key
+            ) AS last_storage_logs
+            WHERE
+                storage_logs.miniblock_number BETWEEN $1 AND $2
+                AND last_storage_logs.hashed_key = storage_logs.hashed_key
+                AND (
+                    storage_logs.miniblock_number != last_storage_logs.op[1]
+                    OR storage_logs.operation_number != last_storage_logs.op[2]
+                )
+            "#,
+            miniblocks.0,
+            miniblocks.1
+        )
+        .instrument("prune_batches_range#storage_logs_from_pruned_batches")
+        .with_arg("miniblocks", &miniblocks)
+        .execute(transaction.conn())
+        .await?
+        .rows_affected();
+
+        sqlx::query!(
+            r#"
+            INSERT INTO
+                pruning_log (pruned_l1_batch, pruned_miniblock, created_at, updated_at)
+            VALUES
+                ($1, $2, NOW(), NOW())
+            "#,
+            i64::from(last_l1_batch_to_prune.0),
+            miniblocks.1
+        )
+        .instrument("prune_batches_range#insert_pruning_log")
+        .with_arg("last_l1_batch_to_prune", &last_l1_batch_to_prune)
+        .execute(transaction.conn())
+        .await?;
+        transaction.commit().await?;
+
+        Ok(PruningStats {
+            deleted_events,
+            deleted_call_traces,
+            deleted_storage_logs_from_pruned_batches,
+            deleted_storage_logs_from_past_batches,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zksync_types::{
+        api, tx::IncludedTxLocation, AccountTreeId, Address, ProtocolVersion, StorageKey,
+        StorageLog, VmEvent, H256,
+    };
+
+    use super::*;
+    use crate::{tests::create_miniblock_header, ConnectionPool};
+
+    fn storage_key(index: u64) -> StorageKey {
+        StorageKey::new(
+            AccountTreeId::new(Address::repeat_byte(1)),
+            H256::from_low_u64_be(index),
+        )
+    }
+
+    async fn insert_miniblock(conn: &mut StorageProcessor<'_>, number: u32) {
+        conn.blocks_dal()
+            .insert_miniblock(&create_miniblock_header(number))
+            .await
+            .unwrap();
+        // Each miniblock overwrites slot #0 and writes a unique slot.
+        let logs = vec![
+            StorageLog::new_write_log(storage_key(0), H256::repeat_byte(number as u8)),
+            StorageLog::new_write_log(storage_key(u64::from(number) + 1), H256::repeat_byte(0xff)),
+        ];
+        conn.storage_logs_dal()
+            .insert_storage_logs(MiniblockNumber(number), &[(H256::zero(), logs)])
+            .await;
+
+        let location = IncludedTxLocation {
+            tx_hash: H256::from_low_u64_be(number.into()),
+            tx_index_in_miniblock: 0,
+            tx_initiator_address: Address::default(),
+        };
+        let event = VmEvent {
+            location: (L1BatchNumber(number), 0),
+            address: Address::repeat_byte(1),
+            indexed_topics: vec![],
+            value: vec![],
+        };
+        conn.events_dal()
+            .save_events(MiniblockNumber(number), &[(location, vec![&event])])
+            .await;
+    }
+
+    async fn count_rows(conn: &mut StorageProcessor<'_>, table: &str) -> i64 {
+        sqlx::query_scalar(&format!("SELECT COUNT(*) FROM {table}"))
+            .fetch_one(conn.conn())
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn pruning_miniblocks() {
+        let pool = ConnectionPool::test_pool().await;
+        let mut conn = pool.access_storage().await.unwrap();
+        conn.protocol_versions_dal()
+            .save_protocol_version_with_tx(ProtocolVersion::default())
+            .await;
+        for number in 0..5 {
+            insert_miniblock(&mut conn, number).await;
+        }
+
+        let info = conn.pruning_dal().get_pruning_info().await.unwrap();
+        assert_eq!(info, PruningInfo::default());
+
+        let stats = conn
+            .pruning_dal()
+            .prune_batches_range(L1BatchNumber(1), MiniblockNumber(2))
+            .await
+            .unwrap();
+        assert_eq!(stats.deleted_events, 3);
+        assert_eq!(stats.deleted_storage_logs_from_pruned_batches, 2);
+        assert_eq!(stats.deleted_storage_logs_from_past_batches, 0);
+        let info = conn.pruning_dal().get_pruning_info().await.unwrap();
+        assert_eq!(info.last_pruned_l1_batch, Some(L1BatchNumber(1)));
+        assert_eq!(info.last_pruned_miniblock, Some(MiniblockNumber(2)));
+        assert_eq!(info.earliest_available_miniblock(), MiniblockNumber(3));
+        let earliest_block = conn
+            .blocks_web3_dal()
+            .resolve_block_id(api::BlockId::Number(api::BlockNumber::Earliest))
+            .await
+            .unwrap();
+        assert_eq!(earliest_block, Some(MiniblockNumber(3)));
+
+        let stats = conn
+            .pruning_dal()
+            .prune_batches_range(L1BatchNumber(2), MiniblockNumber(3))
+            .await
+            .unwrap();
+        assert_eq!(stats.deleted_events, 1);
+        assert_eq!(stats.deleted_storage_logs_from_pruned_batches, 0);
+        // The value of slot #0 written in miniblock #2 is overwritten in miniblock #3.
+        assert_eq!(stats.deleted_storage_logs_from_past_batches, 1);
+
+        assert_eq!(count_rows(&mut conn, "events").await, 1);
+        // Unique slots for miniblocks #0..=4, and slot #0 written in miniblocks #3 and #4.
+        assert_eq!(count_rows(&mut conn, "storage_logs").await, 7);
+        // Current state must be retained.
+        let hashed_key = storage_key(0).hashed_key();
+        let values = conn
+            .storage_logs_dal()
+            .get_storage_values(&[hashed_key], MiniblockNumber(4))
+            .await;
+        assert_eq!(values[&hashed_key], Some(H256::repeat_byte(4)));
+    }
+}
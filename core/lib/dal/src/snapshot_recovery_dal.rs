@@ -2,6 +2,18 @@ use zksync_types::{snapshots::SnapshotRecoveryStatus, L1BatchNumber, MiniblockNu
 
 use crate::StorageProcessor;
 
+/// Storage logs chunk applied during snapshot recovery.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AppliedSnapshotChunk {
+    /// Sequential chunk ID across all snapshots being recovered.
+    pub chunk_id: u64,
+    /// L1 batch of the snapshot containing the chunk.
+    pub snapshot_l1_batch_number: L1BatchNumber,
+    /// Chunk ID within the snapshot.
+    pub snapshot_chunk_id: u64,
+    pub storage_logs_count: u64,
+}
+
 #[derive(Debug)]
 pub struct SnapshotRecoveryDal<'a, 'c> {
     pub(crate) storage: &'a mut StorageProcessor<'c>,
@@ -78,12 +90,72 @@ impl SnapshotRecoveryDal<'_, '_> {
             total_chunk_count: r.total_chunk_count as u64,
         }))
     }
+
+    /// Records that a storage logs chunk was applied. This should be called in the same transaction
+    /// as applying the chunk.
+    pub async fn mark_chunk_as_applied(
+        &mut self,
+        chunk: &AppliedSnapshotChunk,
+    ) -> sqlx::Result<()> {
+        sqlx::query!(
+            r#"
+            INSERT INTO
+                snapshot_recovery_chunks (
+                    chunk_id,
+                    snapshot_l1_batch_number,
+                    snapshot_chunk_id,
+                    storage_logs_count,
+                    created_at
+                )
+            VALUES
+                ($1, $2, $3, $4, NOW())
+            ON CONFLICT (chunk_id) DO NOTHING
+            "#,
+            chunk.chunk_id as i32,
+            chunk.snapshot_l1_batch_number.0 as i64,
+            chunk.snapshot_chunk_id as i32,
+            chunk.storage_logs_count as i64
+        )
+        .execute(self.storage.conn())
+        .await?;
+        Ok(())
+    }
+
+    /// Returns all applied storage logs chunks ordered by chunk ID.
+    pub async fn get_applied_chunks(&mut self) -> sqlx::Result<Vec<AppliedSnapshotChunk>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT
+                chunk_id,
+                snapshot_l1_batch_number,
+                snapshot_chunk_id,
+                storage_logs_count
+            FROM
+                snapshot_recovery_chunks
+            ORDER BY
+                chunk_id
+            "#
+        )
+        .fetch_all(self.storage.conn())
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| AppliedSnapshotChunk {
+                chunk_id: row.chunk_id as u64,
+                snapshot_l1_batch_number: L1BatchNumber(row.snapshot_l1_batch_number as u32),
+                snapshot_chunk_id: row.snapshot_chunk_id as u64,
+                storage_logs_count: row.storage_logs_count as u64,
+            })
+            .collect())
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use zksync_types::{snapshots::SnapshotRecoveryStatus, L1BatchNumber, MiniblockNumber, H256};
 
+    use super::AppliedSnapshotChunk;
     use crate::ConnectionPool;
 
     #[tokio::test]
@@ -132,4 +204,28 @@ mod tests {
             .unwrap();
         assert_eq!(Some(updated_status), updated_status_from_db);
     }
+
+    #[tokio::test]
+    async fn tracking_applied_chunks() {
+        let connection_pool = ConnectionPool::test_pool().await;
+        let mut conn = connection_pool.access_storage().await.unwrap();
+        let mut dal = conn.snapshot_recovery_dal();
+        assert_eq!(dal.get_applied_chunks().await.unwrap(), []);
+
+        let chunks = [1, 0].map(|chunk_id| AppliedSnapshotChunk {
+            chunk_id,
+            snapshot_l1_batch_number: L1BatchNumber(123),
+            snapshot_chunk_id: chunk_id,
+            storage_logs_count: 100,
+        });
+        for chunk in &chunks {
+            dal.mark_chunk_as_applied(chunk).await.unwrap();
+        }
+        // Repeated marking should be a no-op.
+        dal.mark_chunk_as_applied(&chunks[0]).await.unwrap();
+        assert_eq!(
+            dal.get_applied_chunks().await.unwrap(),
+            [chunks[1], chunks[0]]
+        );
+    }
 }
@@ -3,7 +3,9 @@ use zksync_types::{
     AccountTreeId, Address, L1BatchNumber, MiniblockNumber, StorageKey, H256,
 };
 
-use crate::{instrument::InstrumentExt, StorageProcessor};
+use crate::{
+    instrument::InstrumentExt, models::storage_factory_dep::decode_bytecode, StorageProcessor,
+};
 
 #[derive(Debug)]
 pub struct SnapshotsCreatorDal<'a, 'c> {
@@ -32,7 +34,6 @@ impl SnapshotsCreatorDal<'_, '_> {
             l1_batch_number.0 as i32
         )
         .instrument("get_storage_logs_count")
-        .report_latency()
         .fetch_one(self.storage.conn())
         .await?
         .index;
@@ -99,6 +100,92 @@ impl SnapshotsCreatorDal<'_, '_> {
         Ok(storage_logs)
     }
 
+    /// Returns the number of distinct storage keys changed in the specified miniblock range.
+    /// Used to choose chunking for delta snapshots.
+    pub async fn get_changed_storage_logs_keys_count(
+        &mut self,
+        miniblocks: std::ops::RangeInclusive<MiniblockNumber>,
+    ) -> sqlx::Result<u64> {
+        let count = sqlx::query!(
+            r#"
+            SELECT
+                COUNT(DISTINCT hashed_key) AS "count!"
+            FROM
+                storage_logs
+            WHERE
+                miniblock_number BETWEEN $1 AND $2
+            "#,
+            miniblocks.start().0 as i64,
+            miniblocks.end().0 as i64
+        )
+        .instrument("get_changed_storage_logs_keys_count")
+        .with_arg("miniblocks", &miniblocks)
+        .fetch_one(self.storage.conn())
+        .await?
+        .count;
+        Ok(count as u64)
+    }
+
+    /// Returns the latest values of storage keys in the specified range of hashed keys that were changed
+    /// in the specified miniblock range. Used to produce delta snapshots.
+    pub async fn get_storage_logs_delta_chunk(
+        &mut self,
+        miniblocks: std::ops::RangeInclusive<MiniblockNumber>,
+        hashed_keys_range: std::ops::RangeInclusive<H256>,
+    ) -> sqlx::Result<Vec<SnapshotStorageLog>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT
+                storage_logs.key AS "key!",
+                storage_logs.value AS "value!",
+                storage_logs.address AS "address!",
+                initial_writes.l1_batch_number AS "l1_batch_number!",
+                initial_writes.index
+            FROM
+                (
+                    SELECT
+                        hashed_key,
+                        MAX(ARRAY[miniblock_number, operation_number]::INT[]) AS op
+                    FROM
+                        storage_logs
+                    WHERE
+                        miniblock_number BETWEEN $1 AND $2
+                        AND hashed_key >= $3
+                        AND hashed_key < $4
+                    GROUP BY
+                        hashed_key
+                    ORDER BY
+                        hashed_key
+                ) AS keys
+                INNER JOIN storage_logs ON keys.hashed_key = storage_logs.hashed_key
+                AND storage_logs.miniblock_number = keys.op[1]
+                AND storage_logs.operation_number = keys.op[2]
+                INNER JOIN initial_writes ON keys.hashed_key = initial_writes.hashed_key;
+            "#,
+            miniblocks.start().0 as i64,
+            miniblocks.end().0 as i64,
+            hashed_keys_range.start().0.as_slice(),
+            hashed_keys_range.end().0.as_slice(),
+        )
+        .instrument("get_storage_logs_delta_chunk")
+        .with_arg("miniblocks", &miniblocks)
+        .fetch_all(self.storage.conn())
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| SnapshotStorageLog {
+                key: StorageKey::new(
+                    AccountTreeId::new(Address::from_slice(&row.address)),
+                    H256::from_slice(&row.key),
+                ),
+                value: H256::from_slice(&row.value),
+                l1_batch_number_of_initial_write: L1BatchNumber(row.l1_batch_number as u32),
+                enumeration_index: row.index as u64,
+            })
+            .collect())
+    }
+
     pub async fn get_all_factory_deps(
         &mut self,
         miniblock_number: MiniblockNumber,
@@ -106,7 +193,8 @@ impl SnapshotsCreatorDal<'_, '_> {
         let rows = sqlx::query!(
             r#"
             SELECT
-                bytecode
+                bytecode,
+                bytecode_compressed
             FROM
                 factory_deps
             WHERE
@@ -115,15 +203,49 @@ impl SnapshotsCreatorDal<'_, '_> {
             miniblock_number.0 as i64,
         )
         .instrument("get_all_factory_deps")
-        .report_latency()
         .fetch_all(self.storage.conn())
         .await?;
 
-        Ok(rows
-            .into_iter()
-            .map(|row| SnapshotFactoryDependency {
-                bytecode: row.bytecode.into(),
+        rows.into_iter()
+            .map(|row| {
+                let bytecode = decode_bytecode(row.bytecode, row.bytecode_compressed)?;
+                Ok(SnapshotFactoryDependency {
+                    bytecode: bytecode.into(),
+                })
             })
-            .collect())
+            .collect()
+    }
+
+    /// Returns factory deps added in the specified miniblock range. Used to produce delta snapshots.
+    pub async fn get_factory_deps_delta(
+        &mut self,
+        miniblocks: std::ops::RangeInclusive<MiniblockNumber>,
+    ) -> sqlx::Result<Vec<SnapshotFactoryDependency>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT
+                bytecode,
+                bytecode_compressed
+            FROM
+                factory_deps
+            WHERE
+                miniblock_number BETWEEN $1 AND $2
+            "#,
+            miniblocks.start().0 as i64,
+            miniblocks.end().0 as i64
+        )
+        .instrument("get_factory_deps_delta")
+        .with_arg("miniblocks", &miniblocks)
+        .fetch_all(self.storage.conn())
+        .await?;
+
+        rows.into_iter()
+            .map(|row| {
+                let bytecode = decode_bytecode(row.bytecode, row.bytecode_compressed)?;
+                Ok(SnapshotFactoryDependency {
+                    bytecode: bytecode.into(),
+                })
+            })
+            .collect()
     }
 }
@@ -1,6 +1,6 @@
 use zksync_types::{
     snapshots::{AllSnapshots, SnapshotMetadata},
-    L1BatchNumber,
+    L1BatchNumber, PackedEthSignature, H256,
 };
 
 use crate::{instrument::InstrumentExt, StorageProcessor};
@@ -8,20 +8,40 @@ use crate::{instrument::InstrumentExt, StorageProcessor};
 #[derive(Debug, sqlx::FromRow)]
 struct StorageSnapshotMetadata {
     l1_batch_number: i64,
+    base_l1_batch_number: Option<i64>,
     storage_logs_filepaths: Vec<String>,
+    storage_logs_chunk_hashes: Vec<Vec<u8>>,
     factory_deps_filepath: String,
+    manifest_hash: Option<Vec<u8>>,
+    manifest_signature: Option<Vec<u8>>,
 }
 
 impl From<StorageSnapshotMetadata> for SnapshotMetadata {
     fn from(row: StorageSnapshotMetadata) -> Self {
+        // Chunk hashes are empty for snapshots created before hashes were introduced.
+        let storage_logs_chunk_hashes = (0..row.storage_logs_filepaths.len())
+            .map(|chunk_id| {
+                let hash = row.storage_logs_chunk_hashes.get(chunk_id)?;
+                (!hash.is_empty()).then(|| H256::from_slice(hash))
+            })
+            .collect();
         Self {
             l1_batch_number: L1BatchNumber(row.l1_batch_number as u32),
+            base_l1_batch_number: row
+                .base_l1_batch_number
+                .map(|number| L1BatchNumber(number as u32)),
             storage_logs_filepaths: row
                 .storage_logs_filepaths
                 .into_iter()
                 .map(|path| (!path.is_empty()).then_some(path))
                 .collect(),
+            storage_logs_chunk_hashes,
             factory_deps_filepath: row.factory_deps_filepath,
+            manifest_hash: row.manifest_hash.as_deref().map(H256::from_slice),
+            manifest_signature: row.manifest_signature.map(|signature| {
+                PackedEthSignature::deserialize_packed(&signature)
+                    .expect("invalid snapshot manifest signature in Postgres")
+            }),
         }
     }
 }
@@ -32,9 +52,12 @@ pub struct SnapshotsDal<'a, 'c> {
 }
 
 impl SnapshotsDal<'_, '_> {
+    /// Adds a snapshot with no storage log chunks produced yet. `base_l1_batch_number` must be set
+    /// for delta snapshots.
     pub async fn add_snapshot(
         &mut self,
         l1_batch_number: L1BatchNumber,
+        base_l1_batch_number: Option<L1BatchNumber>,
         storage_logs_chunk_count: u64,
         factory_deps_filepaths: &str,
     ) -> sqlx::Result<()> {
@@ -43,36 +66,49 @@ impl SnapshotsDal<'_, '_> {
             INSERT INTO
                 snapshots (
                     l1_batch_number,
+                    base_l1_batch_number,
                     storage_logs_filepaths,
+                    storage_logs_chunk_hashes,
                     factory_deps_filepath,
                     created_at,
                     updated_at
                 )
             VALUES
-                ($1, ARRAY_FILL(''::TEXT, ARRAY[$2::INTEGER]), $3, NOW(), NOW())
+                (
+                    $1,
+                    $2,
+                    ARRAY_FILL(''::TEXT, ARRAY[$3::INTEGER]),
+                    ARRAY_FILL(''::BYTEA, ARRAY[$3::INTEGER]),
+                    $4,
+                    NOW(),
+                    NOW()
+                )
             "#,
             l1_batch_number.0 as i32,
+            base_l1_batch_number.map(|number| i64::from(number.0)),
             storage_logs_chunk_count as i32,
             factory_deps_filepaths,
         )
         .instrument("add_snapshot")
-        .report_latency()
         .execute(self.storage.conn())
         .await?;
         Ok(())
     }
 
+    /// Records a produced storage logs chunk together with its hash.
     pub async fn add_storage_logs_filepath_for_snapshot(
         &mut self,
         l1_batch_number: L1BatchNumber,
         chunk_id: u64,
         storage_logs_filepath: &str,
+        chunk_hash: H256,
     ) -> sqlx::Result<()> {
         sqlx::query!(
             r#"
             UPDATE snapshots
             SET
                 storage_logs_filepaths[$2] = $3,
+                storage_logs_chunk_hashes[$2] = $4,
                 updated_at = NOW()
             WHERE
                 l1_batch_number = $1
@@ -80,6 +116,7 @@ impl SnapshotsDal<'_, '_> {
             l1_batch_number.0 as i32,
             chunk_id as i32 + 1,
             storage_logs_filepath,
+            chunk_hash.as_bytes()
         )
         .execute(self.storage.conn())
         .await?;
@@ -87,6 +124,35 @@ impl SnapshotsDal<'_, '_> {
         Ok(())
     }
 
+    /// Sets the hash and (optionally) the signature of the snapshot manifest.
+    pub async fn set_snapshot_manifest(
+        &mut self,
+        l1_batch_number: L1BatchNumber,
+        manifest_hash: H256,
+        signature: Option<&PackedEthSignature>,
+    ) -> sqlx::Result<()> {
+        let signature = signature.map(PackedEthSignature::serialize_packed);
+        sqlx::query!(
+            r#"
+            UPDATE snapshots
+            SET
+                manifest_hash = $2,
+                manifest_signature = $3,
+                updated_at = NOW()
+            WHERE
+                l1_batch_number = $1
+            "#,
+            i64::from(l1_batch_number.0),
+            manifest_hash.as_bytes(),
+            signature.as_ref().map(<[u8; 65]>::as_slice)
+        )
+        .instrument("set_snapshot_manifest")
+        .with_arg("l1_batch_number", &l1_batch_number)
+        .execute(self.storage.conn())
+        .await?;
+        Ok(())
+    }
+
     pub async fn get_all_complete_snapshots(&mut self) -> sqlx::Result<AllSnapshots> {
         let rows = sqlx::query!(
             r#"
@@ -101,7 +167,6 @@ impl SnapshotsDal<'_, '_> {
             "#
         )
         .instrument("get_all_complete_snapshots")
-        .report_latency()
         .fetch_all(self.storage.conn())
         .await?;
 
@@ -121,8 +186,12 @@ impl SnapshotsDal<'_, '_> {
             r#"
             SELECT
                 l1_batch_number,
+                base_l1_batch_number,
                 factory_deps_filepath,
-                storage_logs_filepaths
+                storage_logs_filepaths,
+                storage_logs_chunk_hashes,
+                manifest_hash,
+                manifest_signature
             FROM
                 snapshots
             ORDER BY
@@ -132,7 +201,6 @@ impl SnapshotsDal<'_, '_> {
             "#
         )
         .instrument("get_newest_snapshot_metadata")
-        .report_latency()
         .fetch_optional(self.storage.conn())
         .await?;
 
@@ -148,8 +216,12 @@ impl SnapshotsDal<'_, '_> {
             r#"
             SELECT
                 l1_batch_number,
+                base_l1_batch_number,
                 factory_deps_filepath,
-                storage_logs_filepaths
+                storage_logs_filepaths,
+                storage_logs_chunk_hashes,
+                manifest_hash,
+                manifest_signature
             FROM
                 snapshots
             WHERE
@@ -158,17 +230,34 @@ impl SnapshotsDal<'_, '_> {
             l1_batch_number.0 as i32
         )
         .instrument("get_snapshot_metadata")
-        .report_latency()
         .fetch_optional(self.storage.conn())
         .await?;
 
         Ok(row.map(Into::into))
     }
+
+    /// Removes the snapshot for the specified L1 batch. Returns `false` if the snapshot does not exist.
+    /// Files referenced by the snapshot should be removed from the object store separately.
+    pub async fn delete_snapshot(&mut self, l1_batch_number: L1BatchNumber) -> sqlx::Result<bool> {
+        let result = sqlx::query!(
+            r#"
+            DELETE FROM snapshots
+            WHERE
+                l1_batch_number = $1
+            "#,
+            i64::from(l1_batch_number.0)
+        )
+        .instrument("delete_snapshot")
+        .with_arg("l1_batch_number", &l1_batch_number)
+        .execute(self.storage.conn())
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use zksync_types::L1BatchNumber;
+    use zksync_types::{L1BatchNumber, PackedEthSignature, H256};
 
     use crate::ConnectionPool;
 
@@ -178,7 +267,7 @@ mod tests {
         let mut conn = pool.access_storage().await.unwrap();
         let mut dal = conn.snapshots_dal();
         let l1_batch_number = L1BatchNumber(100);
-        dal.add_snapshot(l1_batch_number, 2, "gs:///bucket/factory_deps.bin")
+        dal.add_snapshot(l1_batch_number, None, 2, "gs:///bucket/factory_deps.bin")
             .await
             .expect("Failed to add snapshot");
 
@@ -193,6 +282,7 @@ mod tests {
                 l1_batch_number,
                 i,
                 "gs:///bucket/chunk.bin",
+                H256::from_low_u64_be(i),
             )
             .await
             .unwrap();
@@ -210,6 +300,35 @@ mod tests {
             .expect("Failed to retrieve snapshot")
             .unwrap();
         assert_eq!(snapshot_metadata.l1_batch_number, l1_batch_number);
+        assert_eq!(
+            snapshot_metadata.storage_logs_chunk_hashes,
+            [
+                Some(H256::from_low_u64_be(0)),
+                Some(H256::from_low_u64_be(1))
+            ]
+        );
+        assert_eq!(snapshot_metadata.manifest_hash, None);
+
+        let manifest_hash = H256::repeat_byte(0x11);
+        let signature =
+            PackedEthSignature::sign_raw(&H256::repeat_byte(1), &manifest_hash).unwrap();
+        dal.set_snapshot_manifest(l1_batch_number, manifest_hash, Some(&signature))
+            .await
+            .unwrap();
+        let snapshot_metadata = dal
+            .get_newest_snapshot_metadata()
+            .await
+            .unwrap()
+            .expect("no snapshot");
+        assert_eq!(snapshot_metadata.manifest_hash, Some(manifest_hash));
+        assert_eq!(snapshot_metadata.manifest_signature, Some(signature));
+
+        assert!(dal.delete_snapshot(l1_batch_number).await.unwrap());
+        assert!(!dal.delete_snapshot(l1_batch_number).await.unwrap());
+        let snapshots = dal.get_all_complete_snapshots().await.unwrap();
+        assert_eq!(snapshots.snapshots_l1_batch_numbers, []);
+        let snapshot_metadata = dal.get_snapshot_metadata(l1_batch_number).await.unwrap();
+        assert!(snapshot_metadata.is_none());
     }
 
     #[tokio::test]
@@ -218,14 +337,19 @@ mod tests {
         let mut conn = pool.access_storage().await.unwrap();
         let mut dal = conn.snapshots_dal();
         let l1_batch_number = L1BatchNumber(100);
-        dal.add_snapshot(l1_batch_number, 2, "gs:///bucket/factory_deps.bin")
+        dal.add_snapshot(l1_batch_number, None, 2, "gs:///bucket/factory_deps.bin")
             .await
             .expect("Failed to add snapshot");
 
         let storage_log_filepaths = ["gs:///bucket/test_file1.bin", "gs:///bucket/test_file2.bin"];
-        dal.add_storage_logs_filepath_for_snapshot(l1_batch_number, 1, storage_log_filepaths[1])
-            .await
-            .unwrap();
+        dal.add_storage_logs_filepath_for_snapshot(
+            l1_batch_number,
+            1,
+            storage_log_filepaths[1],
+            H256::repeat_byte(2),
+        )
+        .await
+        .unwrap();
 
         let files = dal
             .get_snapshot_metadata(l1_batch_number)
@@ -238,9 +362,14 @@ mod tests {
             [None, Some("gs:///bucket/test_file2.bin".to_string())]
         );
 
-        dal.add_storage_logs_filepath_for_snapshot(l1_batch_number, 0, storage_log_filepaths[0])
-            .await
-            .unwrap();
+        dal.add_storage_logs_filepath_for_snapshot(
+            l1_batch_number,
+            0,
+            storage_log_filepaths[0],
+            H256::repeat_byte(1),
+        )
+        .await
+        .unwrap();
 
         let files = dal
             .get_snapshot_metadata(l1_batch_number)
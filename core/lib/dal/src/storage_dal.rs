@@ -5,7 +5,11 @@ use zksync_contracts::{BaseSystemContracts, SystemContractCode};
 use zksync_types::{MiniblockNumber, StorageKey, StorageLog, StorageValue, H256, U256};
 use zksync_utils::{bytes_to_be_words, bytes_to_chunks};
 
-use crate::{instrument::InstrumentExt, StorageProcessor};
+use crate::{
+    instrument::InstrumentExt,
+    models::storage_factory_dep::{compress_bytecode, decode_bytecode},
+    StorageProcessor,
+};
 
 #[derive(Debug)]
 pub struct StorageDal<'a, 'c> {
@@ -14,7 +18,8 @@ pub struct StorageDal<'a, 'c> {
 
 impl StorageDal<'_, '_> {
     /// Inserts factory dependencies for a miniblock. Factory deps are specified as a map of
-    /// `(bytecode_hash, bytecode)` entries.
+    /// `(bytecode_hash, bytecode)` entries. Bytecodes are stored compressed; since factory deps are keyed
+    /// by the bytecode hash, bytecodes deployed multiple times are only stored once.
     pub async fn insert_factory_deps(
         &mut self,
         block_number: MiniblockNumber,
@@ -22,26 +27,32 @@ impl StorageDal<'_, '_> {
     ) {
         let (bytecode_hashes, bytecodes): (Vec<_>, Vec<_>) = factory_deps
             .iter()
-            .map(|dep| (dep.0.as_bytes(), dep.1.as_slice()))
+            .map(|dep| (dep.0.as_bytes(), compress_bytecode(dep.1)))
             .unzip();
 
         // Copy from stdin can't be used here because of `ON CONFLICT`.
         sqlx::query!(
             r#"
             INSERT INTO
-                factory_deps (bytecode_hash, bytecode, miniblock_number, created_at, updated_at)
+                factory_deps (
+                    bytecode_hash,
+                    bytecode_compressed,
+                    miniblock_number,
+                    created_at,
+                    updated_at
+                )
             SELECT
                 u.bytecode_hash,
-                u.bytecode,
+                u.bytecode_compressed,
                 $3,
                 NOW(),
                 NOW()
             FROM
-                UNNEST($1::bytea[], $2::bytea[]) AS u (bytecode_hash, bytecode)
+                UNNEST($1::bytea[], $2::bytea[]) AS u (bytecode_hash, bytecode_compressed)
             ON CONFLICT (bytecode_hash) DO NOTHING
             "#,
             &bytecode_hashes as &[&[u8]],
-            &bytecodes as &[&[u8]],
+            &bytecodes,
             block_number.0 as i64,
         )
         .execute(self.storage.conn())
@@ -49,12 +60,78 @@ impl StorageDal<'_, '_> {
         .unwrap();
     }
 
+    /// Same as [`Self::insert_factory_deps()`], but uploads factory deps to a temporary staging table
+    /// using `COPY FROM STDIN` instead of passing them as query params.
+    pub async fn insert_factory_deps_via_copy(
+        &mut self,
+        block_number: MiniblockNumber,
+        factory_deps: &HashMap<H256, Vec<u8>>,
+    ) -> sqlx::Result<()> {
+        let mut transaction = self.storage.start_transaction().await?;
+        sqlx::query(
+            "CREATE TEMPORARY TABLE factory_deps_staging (bytecode_hash BYTEA NOT NULL, bytecode_compressed BYTEA NOT NULL) \
+            ON COMMIT DROP",
+        )
+        .execute(transaction.conn())
+        .await?;
+
+        let mut copy = transaction
+            .conn()
+            .copy_in_raw(
+                "COPY factory_deps_staging (bytecode_hash, bytecode_compressed) FROM STDIN WITH (DELIMITER '|')",
+            )
+            .await?;
+        let mut buffer = String::new();
+        for (bytecode_hash, bytecode) in factory_deps {
+            writeln_str!(
+                &mut buffer,
+                r"\\x{bytecode_hash:x}|\\x{bytecode}",
+                bytecode = hex::encode(compress_bytecode(bytecode))
+            );
+        }
+        copy.send(buffer.as_bytes()).await?;
+        copy.finish().await?;
+
+        sqlx::query!(
+            r#"
+            INSERT INTO
+                factory_deps (
+                    bytecode_hash,
+                    bytecode_compressed,
+                    miniblock_number,
+                    created_at,
+                    updated_at
+                )
+            SELECT
+                bytecode_hash,
+                bytecode_compressed,
+                $1,
+                NOW(),
+                NOW()
+            FROM
+                factory_deps_staging
+            ON CONFLICT (bytecode_hash) DO NOTHING
+            "#,
+            block_number.0 as i64
+        )
+        .instrument("insert_factory_deps_via_copy")
+        .with_arg("block_number", &block_number)
+        .execute(transaction.conn())
+        .await?;
+        // Drop the staging table explicitly, so that the method can be called multiple times in a transaction.
+        sqlx::query("DROP TABLE factory_deps_staging")
+            .execute(transaction.conn())
+            .await?;
+        transaction.commit().await
+    }
+
     /// Returns bytecode for a factory dependency with the specified bytecode `hash`.
     pub async fn get_factory_dep(&mut self, hash: H256) -> Option<Vec<u8>> {
         sqlx::query!(
             r#"
             SELECT
-                bytecode
+                bytecode,
+                bytecode_compressed
             FROM
                 factory_deps
             WHERE
@@ -65,7 +142,7 @@ impl StorageDal<'_, '_> {
         .fetch_optional(self.storage.conn())
         .await
         .unwrap()
-        .map(|row| row.bytecode)
+        .map(|row| decode_bytecode(row.bytecode, row.bytecode_compressed).unwrap())
     }
 
     pub async fn get_base_system_contracts(
@@ -73,19 +150,41 @@ impl StorageDal<'_, '_> {
         bootloader_hash: H256,
         default_aa_hash: H256,
     ) -> BaseSystemContracts {
-        let bootloader_bytecode = self
-            .get_factory_dep(bootloader_hash)
-            .await
-            .expect("Bootloader code should be present in the database");
+        self.get_base_system_contracts_with_fallback(
+            bootloader_hash,
+            default_aa_hash,
+            &HashMap::new(),
+        )
+        .await
+    }
+
+    /// Same as [`Self::get_base_system_contracts()`], but takes bytecodes missing from factory deps
+    /// from `fallback_bytecodes`.
+    pub async fn get_base_system_contracts_with_fallback(
+        &mut self,
+        bootloader_hash: H256,
+        default_aa_hash: H256,
+        fallback_bytecodes: &HashMap<H256, Vec<u8>>,
+    ) -> BaseSystemContracts {
+        let bootloader_bytecode = match self.get_factory_dep(bootloader_hash).await {
+            Some(bytecode) => bytecode,
+            None => fallback_bytecodes
+                .get(&bootloader_hash)
+                .cloned()
+                .expect("Bootloader code should be present in the database"),
+        };
         let bootloader_code = SystemContractCode {
             code: bytes_to_be_words(bootloader_bytecode),
             hash: bootloader_hash,
         };
 
-        let default_aa_bytecode = self
-            .get_factory_dep(default_aa_hash)
-            .await
-            .expect("Default account code should be present in the database");
+        let default_aa_bytecode = match self.get_factory_dep(default_aa_hash).await {
+            Some(bytecode) => bytecode,
+            None => fallback_bytecodes
+                .get(&default_aa_hash)
+                .cloned()
+                .expect("Default account code should be present in the database"),
+        };
 
         let default_aa_code = SystemContractCode {
             code: bytes_to_be_words(default_aa_bytecode),
@@ -108,6 +207,7 @@ impl StorageDal<'_, '_> {
             r#"
             SELECT
                 bytecode,
+                bytecode_compressed,
                 bytecode_hash
             FROM
                 factory_deps
@@ -121,9 +221,10 @@ impl StorageDal<'_, '_> {
         .unwrap()
         .into_iter()
         .map(|row| {
+            let bytecode = decode_bytecode(row.bytecode, row.bytecode_compressed).unwrap();
             (
                 U256::from_big_endian(&row.bytecode_hash),
-                bytes_to_chunks(&row.bytecode),
+                bytes_to_chunks(&bytecode),
             )
         })
         .collect()
@@ -154,20 +255,72 @@ impl StorageDal<'_, '_> {
         .collect()
     }
 
+    /// Compresses up to `limit` factory deps stored with raw bytecodes (i.e., inserted before bytecode compression
+    /// was introduced). Returns the number of compressed factory deps; 0 means that all factory deps are compressed.
+    pub async fn compress_factory_deps(&mut self, limit: usize) -> sqlx::Result<usize> {
+        let mut transaction = self.storage.start_transaction().await?;
+        let rows = sqlx::query!(
+            r#"
+            SELECT
+                bytecode_hash,
+                bytecode AS "bytecode!"
+            FROM
+                factory_deps
+            WHERE
+                bytecode IS NOT NULL
+            LIMIT
+                $1
+            FOR UPDATE
+            "#,
+            limit as i64
+        )
+        .instrument("compress_factory_deps#select")
+        .with_arg("limit", &limit)
+        .fetch_all(transaction.conn())
+        .await?;
+        if rows.is_empty() {
+            return Ok(0);
+        }
+
+        let (bytecode_hashes, compressed_bytecodes): (Vec<_>, Vec<_>) = rows
+            .iter()
+            .map(|row| {
+                (
+                    row.bytecode_hash.as_slice(),
+                    compress_bytecode(&row.bytecode),
+                )
+            })
+            .unzip();
+        sqlx::query!(
+            r#"
+            UPDATE factory_deps
+            SET
+                bytecode_compressed = u.bytecode_compressed,
+                bytecode = NULL,
+                updated_at = NOW()
+            FROM
+                UNNEST($1::bytea[], $2::bytea[]) AS u (bytecode_hash, bytecode_compressed)
+            WHERE
+                factory_deps.bytecode_hash = u.bytecode_hash
+            "#,
+            &bytecode_hashes as &[&[u8]],
+            &compressed_bytecodes
+        )
+        .instrument("compress_factory_deps#update")
+        .with_arg("count", &rows.len())
+        .execute(transaction.conn())
+        .await?;
+        transaction.commit().await?;
+        Ok(rows.len())
+    }
+
     /// Applies the specified storage logs for a miniblock. Returns the map of unique storage updates.
     // We likely don't need `storage` table at all, as we have `storage_logs` table
     pub async fn apply_storage_logs(
         &mut self,
         updates: &[(H256, Vec<StorageLog>)],
     ) -> HashMap<StorageKey, (H256, StorageValue)> {
-        let unique_updates: HashMap<_, _> = updates
-            .iter()
-            .flat_map(|(tx_hash, storage_logs)| {
-                storage_logs
-                    .iter()
-                    .map(move |log| (log.key, (*tx_hash, log.value)))
-            })
-            .collect();
+        let unique_updates = Self::unique_updates(updates);
 
         let query_parts = unique_updates.iter().map(|(key, (tx_hash, value))| {
             (
@@ -221,6 +374,90 @@ impl StorageDal<'_, '_> {
         unique_updates
     }
 
+    fn unique_updates(
+        updates: &[(H256, Vec<StorageLog>)],
+    ) -> HashMap<StorageKey, (H256, StorageValue)> {
+        updates
+            .iter()
+            .flat_map(|(tx_hash, storage_logs)| {
+                storage_logs
+                    .iter()
+                    .map(move |log| (log.key, (*tx_hash, log.value)))
+            })
+            .collect()
+    }
+
+    /// Same as [`Self::apply_storage_logs()`], but uploads updates to a temporary staging table
+    /// using `COPY FROM STDIN` instead of passing them as query params.
+    pub async fn apply_storage_logs_via_copy(
+        &mut self,
+        updates: &[(H256, Vec<StorageLog>)],
+    ) -> sqlx::Result<HashMap<StorageKey, (H256, StorageValue)>> {
+        let unique_updates = Self::unique_updates(updates);
+        let mut transaction = self.storage.start_transaction().await?;
+        sqlx::query(
+            "CREATE TEMPORARY TABLE storage_staging (\
+                hashed_key BYTEA NOT NULL, address BYTEA NOT NULL, key BYTEA NOT NULL, \
+                value BYTEA NOT NULL, tx_hash BYTEA NOT NULL\
+            ) ON COMMIT DROP",
+        )
+        .execute(transaction.conn())
+        .await?;
+
+        let mut copy = transaction
+            .conn()
+            .copy_in_raw(
+                "COPY storage_staging (hashed_key, address, key, value, tx_hash) \
+                FROM STDIN WITH (DELIMITER '|')",
+            )
+            .await?;
+        let mut buffer = String::new();
+        for (key, (tx_hash, value)) in &unique_updates {
+            writeln_str!(
+                &mut buffer,
+                r"\\x{hashed_key:x}|\\x{address:x}|\\x{key:x}|\\x{value:x}|\\x{tx_hash:x}",
+                hashed_key = key.hashed_key(),
+                address = key.address(),
+                key = key.key()
+            );
+        }
+        copy.send(buffer.as_bytes()).await?;
+        copy.finish().await?;
+
+        sqlx::query!(
+            r#"
+            INSERT INTO
+                storage (hashed_key, address, key, value, tx_hash, created_at, updated_at)
+            SELECT
+                hashed_key,
+                address,
+                key,
+                value,
+                tx_hash,
+                NOW(),
+                NOW()
+            FROM
+                storage_staging
+            ON CONFLICT (hashed_key) DO
+            UPDATE
+            SET
+                tx_hash = excluded.tx_hash,
+                value = excluded.value,
+                updated_at = NOW()
+            "#
+        )
+        .instrument("apply_storage_logs_via_copy")
+        .with_arg("updates.len", &unique_updates.len())
+        .execute(transaction.conn())
+        .await?;
+        // Drop the staging table explicitly, so that the method can be called multiple times in a transaction.
+        sqlx::query("DROP TABLE storage_staging")
+            .execute(transaction.conn())
+            .await?;
+        transaction.commit().await?;
+        Ok(unique_updates)
+    }
+
     /// Gets the current storage value at the specified `key`.
     pub async fn get_by_key(&mut self, key: &StorageKey) -> Option<H256> {
         let hashed_key = key.hashed_key();
@@ -237,7 +474,6 @@ impl StorageDal<'_, '_> {
             hashed_key.as_bytes()
         )
         .instrument("get_by_key")
-        .report_latency()
         .with_arg("key", &hashed_key)
         .fetch_optional(self.storage.conn())
         .await
@@ -266,7 +502,7 @@ mod tests {
     use zksync_types::{AccountTreeId, Address};
 
     use super::*;
-    use crate::ConnectionPool;
+    use crate::{tests::create_miniblock_header, ConnectionPool};
 
     #[tokio::test]
     async fn applying_storage_logs() {
@@ -288,4 +524,106 @@ mod tests {
         let second_value = conn.storage_dal().get_by_key(&second_key).await.unwrap();
         assert_eq!(second_value, H256::repeat_byte(2));
     }
+
+    #[tokio::test]
+    async fn bulk_inserts_via_copy() {
+        let pool = ConnectionPool::test_pool().await;
+        let mut conn = pool.access_storage().await.unwrap();
+        conn.protocol_versions_dal()
+            .save_protocol_version_with_tx(Default::default())
+            .await;
+        conn.blocks_dal()
+            .insert_miniblock(&create_miniblock_header(0))
+            .await
+            .unwrap();
+
+        let account = AccountTreeId::new(Address::repeat_byte(1));
+        let key = StorageKey::new(account, H256::zero());
+        for value in [H256::repeat_byte(1), H256::repeat_byte(2)] {
+            let updates = [(
+                H256::repeat_byte(1),
+                vec![StorageLog::new_write_log(key, value)],
+            )];
+            let unique_updates = conn
+                .storage_dal()
+                .apply_storage_logs_via_copy(&updates)
+                .await
+                .unwrap();
+            assert_eq!(unique_updates.len(), 1);
+            assert_eq!(conn.storage_dal().get_by_key(&key).await, Some(value));
+        }
+
+        let factory_deps = HashMap::from([(H256::repeat_byte(1), vec![0xfe; 64])]);
+        // Inserting the same factory deps twice must not fail.
+        for _ in 0..2 {
+            conn.storage_dal()
+                .insert_factory_deps_via_copy(MiniblockNumber(0), &factory_deps)
+                .await
+                .unwrap();
+        }
+        let bytecode = conn
+            .storage_dal()
+            .get_factory_dep(H256::repeat_byte(1))
+            .await;
+        assert_eq!(bytecode, Some(vec![0xfe; 64]));
+    }
+
+    #[tokio::test]
+    async fn compressing_legacy_factory_deps() {
+        let pool = ConnectionPool::test_pool().await;
+        let mut conn = pool.access_storage().await.unwrap();
+        conn.protocol_versions_dal()
+            .save_protocol_version_with_tx(Default::default())
+            .await;
+        conn.blocks_dal()
+            .insert_miniblock(&create_miniblock_header(0))
+            .await
+            .unwrap();
+
+        let new_deps = HashMap::from([(H256::repeat_byte(1), vec![0xfe; 64])]);
+        conn.storage_dal()
+            .insert_factory_deps(MiniblockNumber(0), &new_deps)
+            .await;
+        // Emulate factory deps inserted before bytecode compression was introduced.
+        let legacy_deps: HashMap<_, _> = (2_u8..5)
+            .map(|byte| (H256::repeat_byte(byte), vec![byte; 128]))
+            .collect();
+        for (hash, bytecode) in &legacy_deps {
+            sqlx::query(
+                "INSERT INTO factory_deps (bytecode_hash, bytecode, miniblock_number, created_at, updated_at) \
+                 VALUES ($1, $2, 0, NOW(), NOW())",
+            )
+            .bind(hash.as_bytes())
+            .bind(bytecode)
+            .execute(conn.conn())
+            .await
+            .unwrap();
+        }
+
+        let all_hashes: HashSet<_> = legacy_deps.keys().chain(new_deps.keys()).copied().collect();
+        let expected_deps: HashMap<_, _> = legacy_deps
+            .iter()
+            .chain(&new_deps)
+            .map(|(hash, bytecode)| {
+                let hash = U256::from_big_endian(hash.as_bytes());
+                (hash, bytes_to_chunks(bytecode))
+            })
+            .collect();
+        let deps = conn.storage_dal().get_factory_deps(&all_hashes).await;
+        assert_eq!(deps, expected_deps);
+
+        let compressed_count = conn.storage_dal().compress_factory_deps(2).await.unwrap();
+        assert_eq!(compressed_count, 2);
+        let compressed_count = conn.storage_dal().compress_factory_deps(2).await.unwrap();
+        assert_eq!(compressed_count, 1);
+        let compressed_count = conn.storage_dal().compress_factory_deps(2).await.unwrap();
+        assert_eq!(compressed_count, 0);
+
+        let deps = conn.storage_dal().get_factory_deps(&all_hashes).await;
+        assert_eq!(deps, expected_deps);
+        for (hash, bytecode) in &legacy_deps {
+            let dep = conn.storage_dal().get_factory_dep(*hash).await;
+            assert_eq!(dep.as_ref(), Some(bytecode));
+        }
+    }
 }
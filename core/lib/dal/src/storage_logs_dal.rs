@@ -2,8 +2,8 @@ use std::{collections::HashMap, ops, time::Instant};
 
 use sqlx::{types::chrono::Utc, Row};
 use zksync_types::{
-    get_code_key, AccountTreeId, Address, L1BatchNumber, MiniblockNumber, StorageKey, StorageLog,
-    FAILED_CONTRACT_DEPLOYMENT_BYTECODE_HASH, H256, U256,
+    get_code_key, snapshots::SnapshotStorageLog, AccountTreeId, Address, L1BatchNumber,
+    MiniblockNumber, StorageKey, StorageLog, FAILED_CONTRACT_DEPLOYMENT_BYTECODE_HASH, H256, U256,
 };
 
 use crate::{instrument::InstrumentExt, models::storage_log::StorageTreeEntry, StorageProcessor};
@@ -67,6 +67,72 @@ impl StorageLogsDal<'_, '_> {
         copy.finish().await.unwrap();
     }
 
+    /// Inserts storage logs recovered from a snapshot. All logs are attributed to the snapshot miniblock.
+    pub async fn insert_storage_logs_from_snapshot(
+        &mut self,
+        miniblock_number: MiniblockNumber,
+        snapshot_storage_logs: &[SnapshotStorageLog],
+    ) -> sqlx::Result<()> {
+        let mut copy = self
+            .storage
+            .conn()
+            .copy_in_raw(
+                "COPY storage_logs(
+                    hashed_key, address, key, value, operation_number, tx_hash, miniblock_number,
+                    created_at, updated_at
+                )
+                FROM STDIN WITH (DELIMITER '|')",
+            )
+            .await?;
+
+        let mut buffer = String::new();
+        let now = Utc::now().naive_utc().to_string();
+        for (operation_number, log) in snapshot_storage_logs.iter().enumerate() {
+            write_str!(
+                &mut buffer,
+                r"\\x{hashed_key:x}|\\x{address:x}|\\x{key:x}|\\x{value:x}|",
+                hashed_key = log.key.hashed_key(),
+                address = log.key.address(),
+                key = log.key.key(),
+                value = log.value
+            );
+            writeln_str!(
+                &mut buffer,
+                r"{operation_number}|\\x{tx_hash:x}|{miniblock_number}|{now}|{now}",
+                tx_hash = H256::zero()
+            );
+        }
+        copy.send(buffer.as_bytes()).await?;
+        copy.finish().await?;
+        Ok(())
+    }
+
+    /// Removes storage logs for the specified keys in the snapshot miniblock. Used when applying delta snapshots
+    /// to overwrite values recovered from the base snapshot.
+    pub async fn delete_storage_logs_from_snapshot(
+        &mut self,
+        miniblock_number: MiniblockNumber,
+        hashed_keys: &[H256],
+    ) -> sqlx::Result<u64> {
+        let hashed_keys: Vec<_> = hashed_keys.iter().map(H256::as_bytes).collect();
+        let result = sqlx::query!(
+            r#"
+            DELETE FROM storage_logs
+            WHERE
+                miniblock_number = $1
+                AND hashed_key = ANY ($2)
+            "#,
+            miniblock_number.0 as i64,
+            &hashed_keys as &[&[u8]]
+        )
+        .instrument("delete_storage_logs_from_snapshot")
+        .with_arg("miniblock_number", &miniblock_number)
+        .with_arg("hashed_keys.len", &hashed_keys.len())
+        .execute(self.storage.conn())
+        .await?;
+        Ok(result.rows_affected())
+    }
+
     pub async fn append_storage_logs(
         &mut self,
         block_number: MiniblockNumber,
@@ -411,7 +477,6 @@ impl StorageLogsDal<'_, '_> {
             &hashed_keys as &[&[u8]],
         )
         .instrument("get_l1_batches_and_indices_for_initial_writes")
-        .report_latency()
         .fetch_all(self.storage.conn())
         .await
         .unwrap();
@@ -621,6 +686,94 @@ impl StorageLogsDal<'_, '_> {
         Ok(rows.collect())
     }
 
+    /// Gets a starting tree entry for each of the supplied `key_ranges` in the storage state
+    /// as of the end of the specified `miniblock_number`. Unlike [`Self::get_chunk_starts_for_miniblock()`],
+    /// this method considers all storage logs up to and including the miniblock, so it can be used
+    /// to recover the Merkle tree from the full storage history rather than from a snapshot.
+    pub async fn get_chunk_starts_up_to_miniblock(
+        &mut self,
+        miniblock_number: MiniblockNumber,
+        key_ranges: &[ops::RangeInclusive<H256>],
+    ) -> sqlx::Result<Vec<Option<StorageTreeEntry>>> {
+        let mut output = Vec::with_capacity(key_ranges.len());
+        for key_range in key_ranges {
+            let row = sqlx::query!(
+                r#"
+                SELECT
+                    storage_logs.hashed_key,
+                    storage_logs.value,
+                    initial_writes.index
+                FROM
+                    storage_logs
+                    INNER JOIN initial_writes ON storage_logs.hashed_key = initial_writes.hashed_key
+                WHERE
+                    storage_logs.miniblock_number <= $1
+                    AND storage_logs.hashed_key >= $2::bytea
+                    AND storage_logs.hashed_key <= $3::bytea
+                ORDER BY
+                    storage_logs.hashed_key,
+                    storage_logs.miniblock_number DESC,
+                    storage_logs.operation_number DESC
+                LIMIT
+                    1
+                "#,
+                miniblock_number.0 as i64,
+                key_range.start().as_bytes(),
+                key_range.end().as_bytes()
+            )
+            .fetch_optional(self.storage.conn())
+            .await?;
+
+            output.push(row.map(|row| StorageTreeEntry {
+                key: U256::from_little_endian(&row.hashed_key),
+                value: H256::from_slice(&row.value),
+                leaf_index: row.index as u64,
+            }));
+        }
+        Ok(output)
+    }
+
+    /// Fetches tree entries for the storage state as of the end of the specified `miniblock_number`
+    /// for the given `key_range`. Entries are sorted by the hashed key. This is used during
+    /// Merkle tree recovery from the full storage history.
+    pub async fn get_tree_entries_up_to_miniblock(
+        &mut self,
+        miniblock_number: MiniblockNumber,
+        key_range: ops::RangeInclusive<H256>,
+    ) -> sqlx::Result<Vec<StorageTreeEntry>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT DISTINCT
+                ON (storage_logs.hashed_key) storage_logs.hashed_key,
+                storage_logs.value,
+                initial_writes.index
+            FROM
+                storage_logs
+                INNER JOIN initial_writes ON storage_logs.hashed_key = initial_writes.hashed_key
+            WHERE
+                storage_logs.miniblock_number <= $1
+                AND storage_logs.hashed_key >= $2::bytea
+                AND storage_logs.hashed_key <= $3::bytea
+            ORDER BY
+                storage_logs.hashed_key,
+                storage_logs.miniblock_number DESC,
+                storage_logs.operation_number DESC
+            "#,
+            miniblock_number.0 as i64,
+            key_range.start().as_bytes(),
+            key_range.end().as_bytes()
+        )
+        .fetch_all(self.storage.conn())
+        .await?;
+
+        let entries = rows.into_iter().map(|row| StorageTreeEntry {
+            key: U256::from_little_endian(&row.hashed_key),
+            value: H256::from_slice(&row.value),
+            leaf_index: row.index as u64,
+        });
+        Ok(entries.collect())
+    }
+
     pub async fn retain_storage_logs(
         &mut self,
         miniblock_number: MiniblockNumber,
@@ -1,10 +1,13 @@
 use std::collections::HashSet;
 
 use sqlx::types::chrono::Utc;
-use zksync_types::{AccountTreeId, Address, L1BatchNumber, LogQuery, StorageKey, H256};
+use zksync_types::{
+    snapshots::SnapshotStorageLog, AccountTreeId, Address, L1BatchNumber, LogQuery, StorageKey,
+    H256,
+};
 use zksync_utils::u256_to_h256;
 
-use crate::StorageProcessor;
+use crate::{instrument::InstrumentExt, StorageProcessor};
 
 #[derive(Debug)]
 pub struct StorageLogsDedupDal<'a, 'c> {
@@ -42,6 +45,39 @@ impl StorageLogsDedupDal<'_, '_> {
         copy.finish().await.unwrap();
     }
 
+    /// Inserts initial writes recovered from a snapshot. Unlike [`Self::insert_initial_writes()`],
+    /// enumeration indices are taken from the snapshot rather than assigned sequentially.
+    pub async fn insert_initial_writes_from_snapshot(
+        &mut self,
+        snapshot_storage_logs: &[SnapshotStorageLog],
+    ) -> sqlx::Result<()> {
+        let mut copy = self
+            .storage
+            .conn()
+            .copy_in_raw(
+                "COPY initial_writes (hashed_key, index, l1_batch_number, created_at, updated_at) \
+                FROM STDIN WITH (DELIMITER '|')",
+            )
+            .await?;
+
+        let mut bytes: Vec<u8> = Vec::new();
+        let now = Utc::now().naive_utc().to_string();
+        for log in snapshot_storage_logs {
+            let row = format!(
+                "\\\\x{:x}|{}|{}|{}|{}\n",
+                log.key.hashed_key(),
+                log.enumeration_index,
+                log.l1_batch_number_of_initial_write,
+                now,
+                now
+            );
+            bytes.extend_from_slice(row.as_bytes());
+        }
+        copy.send(bytes).await?;
+        copy.finish().await?;
+        Ok(())
+    }
+
     /// Insert initial writes and assigns indices to them.
     /// Assumes indices are already assigned for all saved initial_writes, so must be called only after the migration.
     pub async fn insert_initial_writes(
@@ -81,6 +117,37 @@ impl StorageLogsDedupDal<'_, '_> {
         .unwrap();
     }
 
+    /// Same as [`Self::insert_initial_writes()`], but uses `COPY FROM STDIN` instead of a multi-row `INSERT`.
+    /// This is faster for L1 batches with many initial writes.
+    pub async fn insert_initial_writes_via_copy(
+        &mut self,
+        l1_batch_number: L1BatchNumber,
+        written_storage_keys: &[StorageKey],
+    ) -> sqlx::Result<()> {
+        let last_index = self.max_enumeration_index().await.unwrap_or(0);
+        let mut copy = self
+            .storage
+            .conn()
+            .copy_in_raw(
+                "COPY initial_writes (hashed_key, index, l1_batch_number, created_at, updated_at) \
+                FROM STDIN WITH (DELIMITER '|')",
+            )
+            .await?;
+
+        let mut buffer = String::new();
+        let now = Utc::now().naive_utc().to_string();
+        for (index, key) in (last_index + 1..).zip(written_storage_keys) {
+            writeln_str!(
+                &mut buffer,
+                r"\\x{hashed_key:x}|{index}|{l1_batch_number}|{now}|{now}",
+                hashed_key = key.hashed_key()
+            );
+        }
+        copy.send(buffer.as_bytes()).await?;
+        copy.finish().await?;
+        Ok(())
+    }
+
     pub async fn get_protective_reads_for_l1_batch(
         &mut self,
         l1_batch_number: L1BatchNumber,
@@ -110,6 +177,95 @@ impl StorageLogsDedupDal<'_, '_> {
         .collect()
     }
 
+    /// Marks protective reads for the specified L1 batch as not persisted yet. This is used if protective reads
+    /// are persisted asynchronously after the batch is sealed.
+    pub async fn mark_protective_reads_as_pending(
+        &mut self,
+        l1_batch_number: L1BatchNumber,
+    ) -> sqlx::Result<()> {
+        sqlx::query!(
+            r#"
+            UPDATE l1_batches
+            SET
+                protective_reads_persisted = FALSE
+            WHERE
+                number = $1
+            "#,
+            l1_batch_number.0 as i64
+        )
+        .instrument("mark_protective_reads_as_pending")
+        .with_arg("l1_batch_number", &l1_batch_number)
+        .execute(self.storage.conn())
+        .await?;
+        Ok(())
+    }
+
+    /// Marks protective reads for the specified L1 batch as persisted.
+    pub async fn mark_protective_reads_as_persisted(
+        &mut self,
+        l1_batch_number: L1BatchNumber,
+    ) -> sqlx::Result<()> {
+        sqlx::query!(
+            r#"
+            UPDATE l1_batches
+            SET
+                protective_reads_persisted = TRUE
+            WHERE
+                number = $1
+            "#,
+            l1_batch_number.0 as i64
+        )
+        .instrument("mark_protective_reads_as_persisted")
+        .with_arg("l1_batch_number", &l1_batch_number)
+        .execute(self.storage.conn())
+        .await?;
+        Ok(())
+    }
+
+    /// Returns the earliest L1 batch for which protective reads are not persisted yet.
+    pub async fn get_next_l1_batch_with_pending_protective_reads(
+        &mut self,
+    ) -> sqlx::Result<Option<L1BatchNumber>> {
+        let row = sqlx::query!(
+            r#"
+            SELECT
+                MIN(number) AS "number?"
+            FROM
+                l1_batches
+            WHERE
+                NOT protective_reads_persisted
+            "#
+        )
+        .instrument("get_next_l1_batch_with_pending_protective_reads")
+        .fetch_one(self.storage.conn())
+        .await?;
+        Ok(row.number.map(|number| L1BatchNumber(number as u32)))
+    }
+
+    /// Checks whether protective reads are persisted for the specified L1 batch. Returns `None` if the batch
+    /// is not present in the storage.
+    pub async fn are_protective_reads_persisted(
+        &mut self,
+        l1_batch_number: L1BatchNumber,
+    ) -> sqlx::Result<Option<bool>> {
+        let row = sqlx::query!(
+            r#"
+            SELECT
+                protective_reads_persisted
+            FROM
+                l1_batches
+            WHERE
+                number = $1
+            "#,
+            l1_batch_number.0 as i64
+        )
+        .instrument("are_protective_reads_persisted")
+        .with_arg("l1_batch_number", &l1_batch_number)
+        .fetch_optional(self.storage.conn())
+        .await?;
+        Ok(row.map(|row| row.protective_reads_persisted))
+    }
+
     pub async fn max_enumeration_index(&mut self) -> Option<u64> {
         sqlx::query!(
             r#"
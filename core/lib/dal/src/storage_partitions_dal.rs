@@ -0,0 +1,530 @@
+use std::ops;
+
+use anyhow::Context as _;
+use zksync_types::{L1BatchNumber, MiniblockNumber};
+
+use crate::{instrument::InstrumentExt, StorageProcessor};
+
+/// Table partitioned by miniblock number ranges.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PartitionedTable {
+    StorageLogs,
+    Events,
+}
+
+impl PartitionedTable {
+    /// All partitioned tables.
+    pub const ALL: [Self; 2] = [Self::StorageLogs, Self::Events];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::StorageLogs => "storage_logs",
+            Self::Events => "events",
+        }
+    }
+
+    fn default_partition_name(self) -> String {
+        format!("{}_p_default", self.name())
+    }
+
+    /// Name of the temporary check constraint on the default partition excluding rows of a partition being created.
+    fn default_partition_check_name(self) -> String {
+        format!("{}_p_default_new_partition_check", self.name())
+    }
+
+    fn partition_name(self, miniblocks: &ops::Range<MiniblockNumber>) -> String {
+        format!(
+            "{}_p{}_{}",
+            self.name(),
+            miniblocks.start.0,
+            miniblocks.end.0
+        )
+    }
+}
+
+/// Information about a partition of a [`PartitionedTable`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StoragePartition {
+    pub name: String,
+    /// Range of miniblocks covered by the partition.
+    pub miniblocks: ops::Range<MiniblockNumber>,
+    pub is_detached: bool,
+}
+
+/// DAL managing partitions of the `storage_logs` and `events` tables.
+///
+/// Both tables are partitioned by miniblock number ranges; partition boundaries are chosen by the caller.
+/// Besides partitions tracked by this DAL, each table has a default partition, which receives rows
+/// not covered by other partitions. New partitions should be created ahead of time, so that the default partition
+/// stays small. Old partitions can be detached, e.g., after the data in them is pruned.
+#[derive(Debug)]
+pub struct StoragePartitionsDal<'a, 'c> {
+    pub(crate) storage: &'a mut StorageProcessor<'c>,
+}
+
+impl StoragePartitionsDal<'_, '_> {
+    /// Returns partitions of the specified table ordered by the starting miniblock.
+    /// The default partition is not included.
+    pub async fn get_partitions(
+        &mut self,
+        table: PartitionedTable,
+    ) -> sqlx::Result<Vec<StoragePartition>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT
+                partition_name,
+                from_miniblock,
+                to_miniblock,
+                detached_at IS NOT NULL AS "is_detached!"
+            FROM
+                storage_partitions
+            WHERE
+                table_name = $1
+            ORDER BY
+                from_miniblock
+            "#,
+            table.name()
+        )
+        .instrument("get_partitions")
+        .with_arg("table", &table.name())
+        .fetch_all(self.storage.conn())
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| StoragePartition {
+                name: row.partition_name,
+                miniblocks: MiniblockNumber(row.from_miniblock as u32)
+                    ..MiniblockNumber(row.to_miniblock as u32),
+                is_detached: row.is_detached,
+            })
+            .collect())
+    }
+
+    /// Returns the first miniblock that can be covered by a new partition of the specified table, i.e.,
+    /// the first miniblock after all existing partitions and rows in the default partition.
+    pub async fn next_partition_start(
+        &mut self,
+        table: PartitionedTable,
+    ) -> anyhow::Result<MiniblockNumber> {
+        let partitions_end = sqlx::query!(
+            r#"
+            SELECT
+                MAX(to_miniblock) AS "max?"
+            FROM
+                storage_partitions
+            WHERE
+                table_name = $1
+            "#,
+            table.name()
+        )
+        .instrument("next_partition_start")
+        .with_arg("table", &table.name())
+        .fetch_one(self.storage.conn())
+        .await?
+        .max
+        .unwrap_or(0);
+
+        // The default partition may contain rows if partitions weren't created in time. New partitions
+        // must not overlap with these rows.
+        let query = format!(
+            "SELECT MAX(miniblock_number) FROM {}",
+            table.default_partition_name()
+        );
+        let default_partition_max: Option<i64> = sqlx::query_scalar(&query)
+            .fetch_one(self.storage.conn())
+            .await
+            .context("failed getting max miniblock in default partition")?;
+        let start = partitions_end.max(default_partition_max.map_or(0, |max| max + 1));
+        Ok(MiniblockNumber(
+            start.try_into().context("partition start")?,
+        ))
+    }
+
+    /// Creates a new partition of the specified table covering the specified miniblocks. The range must not overlap
+    /// with existing partitions or rows in the default partition; use [`Self::next_partition_start()`]
+    /// to choose the range start. If the partition already exists (e.g., it was created concurrently), this is a no-op.
+    ///
+    /// The partition is created in several transactions, so this method must not be called within a transaction.
+    pub async fn create_partition(
+        &mut self,
+        table: PartitionedTable,
+        miniblocks: ops::Range<MiniblockNumber>,
+    ) -> anyhow::Result<StoragePartition> {
+        anyhow::ensure!(
+            miniblocks.start < miniblocks.end,
+            "cannot create partition for empty miniblock range {miniblocks:?}"
+        );
+        let name = table.partition_name(&miniblocks);
+        let (start, end) = (miniblocks.start.0, miniblocks.end.0);
+        anyhow::ensure!(
+            !self.storage.in_transaction(),
+            "partition `{name}` must be created outside of a transaction"
+        );
+        let default_partition = table.default_partition_name();
+        let check_name = table.default_partition_check_name();
+
+        // Creating a partition requires scanning the default partition for rows belonging to the new partition
+        // while holding an `ACCESS EXCLUSIVE` lock on it, which blocks writes to the partitioned table. Postgres skips
+        // the scan if the default partition has a validated check constraint excluding these rows. Hence, we add
+        // such a constraint as `NOT VALID` (which takes an `ACCESS EXCLUSIVE` lock, but doesn't scan the partition)
+        // and commit immediately. The constraint is then validated in a separate transaction, which scans
+        // the partition holding only a `SHARE UPDATE EXCLUSIVE` lock that doesn't block writes.
+        let drop_check_statement =
+            format!("ALTER TABLE {default_partition} DROP CONSTRAINT IF EXISTS {check_name}");
+        let add_check_statement = format!(
+            "ALTER TABLE {default_partition} ADD CONSTRAINT {check_name} \
+             CHECK (miniblock_number < {start} OR miniblock_number >= {end}) NOT VALID"
+        );
+        let mut transaction = self.storage.start_transaction().await?;
+        Self::execute_statements(
+            &mut transaction,
+            &name,
+            &[drop_check_statement.clone(), add_check_statement],
+        )
+        .await?;
+        transaction.commit().await?;
+
+        let result = self
+            .validate_check_and_attach_partition(table, &name, &miniblocks)
+            .await;
+        if result.is_err() {
+            // The constraint would otherwise reject writes to the default partition for the partition range.
+            if let Err(err) = sqlx::query(&drop_check_statement)
+                .execute(self.storage.conn())
+                .await
+            {
+                tracing::warn!(
+                    "Failed removing check constraint `{check_name}` from `{default_partition}`: {err}"
+                );
+            }
+        }
+        let inserted_rows = result?;
+
+        if inserted_rows > 0 {
+            tracing::info!("Created partition `{name}` for miniblocks {miniblocks:?}");
+        } else {
+            tracing::info!("Partition `{name}` for miniblocks {miniblocks:?} already exists");
+        }
+        Ok(StoragePartition {
+            name,
+            miniblocks,
+            is_detached: false,
+        })
+    }
+
+    async fn execute_statements(
+        storage: &mut StorageProcessor<'_>,
+        partition_name: &str,
+        statements: &[String],
+    ) -> anyhow::Result<()> {
+        for statement in statements {
+            sqlx::query(statement)
+                .execute(storage.conn())
+                .await
+                .with_context(|| {
+                    format!("failed creating partition `{partition_name}`: `{statement}`")
+                })?;
+        }
+        Ok(())
+    }
+
+    /// Validates the check constraint on the default partition added by [`Self::create_partition()`], and then creates
+    /// the partition and removes the constraint. Returns the number of rows inserted into `storage_partitions`.
+    async fn validate_check_and_attach_partition(
+        &mut self,
+        table: PartitionedTable,
+        name: &str,
+        miniblocks: &ops::Range<MiniblockNumber>,
+    ) -> anyhow::Result<u64> {
+        let (start, end) = (miniblocks.start.0, miniblocks.end.0);
+        let default_partition = table.default_partition_name();
+        let check_name = table.default_partition_check_name();
+
+        let validate_statement =
+            format!("ALTER TABLE {default_partition} VALIDATE CONSTRAINT {check_name}");
+        Self::execute_statements(self.storage, name, &[validate_statement]).await?;
+
+        // Creating the partition and removing the constraint take an `ACCESS EXCLUSIVE` lock on the default partition,
+        // but are fast since the default partition is not scanned.
+        let mut transaction = self.storage.start_transaction().await?;
+        let statements = [
+            format!(
+                "CREATE TABLE IF NOT EXISTS {name} PARTITION OF {table} FOR VALUES FROM ({start}) TO ({end})",
+                table = table.name()
+            ),
+            format!("ALTER TABLE {default_partition} DROP CONSTRAINT {check_name}"),
+        ];
+        Self::execute_statements(&mut transaction, name, &statements).await?;
+        let inserted_rows = sqlx::query!(
+            r#"
+            INSERT INTO
+                storage_partitions (partition_name, table_name, from_miniblock, to_miniblock)
+            VALUES
+                ($1, $2, $3, $4)
+            ON CONFLICT (partition_name) DO NOTHING
+            "#,
+            name,
+            table.name(),
+            miniblocks.start.0 as i64,
+            miniblocks.end.0 as i64
+        )
+        .instrument("create_partition")
+        .with_arg("name", &name)
+        .execute(transaction.conn())
+        .await?
+        .rows_affected();
+        transaction.commit().await?;
+        Ok(inserted_rows)
+    }
+
+    /// Detaches all partitions of the specified table that only contain data for L1 batches up to and including
+    /// `last_l1_batch`. Returns the names of detached partitions. Detached partitions are retained as separate tables
+    /// and can be archived and/or dropped.
+    ///
+    /// The caller is responsible for ensuring that data in detached partitions is not needed anymore. In particular,
+    /// `storage_logs` partitions may contain the latest values of storage slots, which are necessary to access
+    /// the current state.
+    pub async fn detach_partitions_before(
+        &mut self,
+        table: PartitionedTable,
+        last_l1_batch: L1BatchNumber,
+    ) -> anyhow::Result<Vec<String>> {
+        let Some((_, last_miniblock)) = self
+            .storage
+            .blocks_dal()
+            .get_miniblock_range_of_l1_batch(last_l1_batch)
+            .await?
+        else {
+            return Ok(vec![]);
+        };
+
+        let mut transaction = self.storage.start_transaction().await?;
+        let partition_names: Vec<_> = sqlx::query!(
+            r#"
+            SELECT
+                partition_name
+            FROM
+                storage_partitions
+            WHERE
+                table_name = $1
+                AND detached_at IS NULL
+                AND to_miniblock <= $2
+            ORDER BY
+                from_miniblock
+            "#,
+            table.name(),
+            i64::from(last_miniblock.0) + 1
+        )
+        .instrument("detach_partitions_before")
+        .with_arg("table", &table.name())
+        .with_arg("last_miniblock", &last_miniblock)
+        .fetch_all(transaction.conn())
+        .await?
+        .into_iter()
+        .map(|row| row.partition_name)
+        .collect();
+
+        for name in &partition_names {
+            let query = format!("ALTER TABLE {} DETACH PARTITION {name}", table.name());
+            sqlx::query(&query)
+                .execute(transaction.conn())
+                .await
+                .with_context(|| format!("failed detaching partition `{name}`"))?;
+            sqlx::query!(
+                r#"
+                UPDATE storage_partitions
+                SET
+                    detached_at = NOW()
+                WHERE
+                    partition_name = $1
+                "#,
+                name
+            )
+            .instrument("detach_partitions_before#mark_detached")
+            .with_arg("name", name)
+            .execute(transaction.conn())
+            .await?;
+        }
+        transaction.commit().await?;
+
+        if !partition_names.is_empty() {
+            tracing::info!(
+                "Detached partitions {partition_names:?} of table `{}`",
+                table.name()
+            );
+        }
+        Ok(partition_names)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zksync_types::{
+        block::{BlockGasCount, L1BatchHeader},
+        AccountTreeId, Address, ProtocolVersion, ProtocolVersionId, StorageKey, StorageLog, H256,
+    };
+
+    use super::*;
+    use crate::{tests::create_miniblock_header, ConnectionPool};
+
+    async fn insert_miniblock_with_logs(conn: &mut StorageProcessor<'_>, number: u32) {
+        conn.blocks_dal()
+            .insert_miniblock(&create_miniblock_header(number))
+            .await
+            .unwrap();
+        let key = StorageKey::new(
+            AccountTreeId::new(Address::repeat_byte(1)),
+            H256::from_low_u64_be(number.into()),
+        );
+        let log = StorageLog::new_write_log(key, H256::repeat_byte(0xff));
+        conn.storage_logs_dal()
+            .insert_storage_logs(MiniblockNumber(number), &[(H256::zero(), vec![log])])
+            .await;
+    }
+
+    #[tokio::test]
+    async fn creating_partition_overlapping_default_partition_rows() {
+        let pool = ConnectionPool::test_pool().await;
+        let mut conn = pool.access_storage().await.unwrap();
+        conn.protocol_versions_dal()
+            .save_protocol_version_with_tx(ProtocolVersion::default())
+            .await;
+        insert_miniblock_with_logs(&mut conn, 0).await;
+
+        let table = PartitionedTable::StorageLogs;
+        conn.storage_partitions_dal()
+            .create_partition(table, MiniblockNumber(0)..MiniblockNumber(2))
+            .await
+            .unwrap_err();
+        // The check constraint must be removed, so that it doesn't reject writes to the default partition.
+        let constraint_count: i64 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM pg_constraint WHERE conname = $1")
+                .bind(table.default_partition_check_name())
+                .fetch_one(conn.conn())
+                .await
+                .unwrap();
+        assert_eq!(constraint_count, 0);
+        insert_miniblock_with_logs(&mut conn, 1).await;
+
+        let mut transaction = conn.start_transaction().await.unwrap();
+        transaction
+            .storage_partitions_dal()
+            .create_partition(table, MiniblockNumber(2)..MiniblockNumber(4))
+            .await
+            .unwrap_err();
+        drop(transaction);
+
+        conn.storage_partitions_dal()
+            .create_partition(table, MiniblockNumber(2)..MiniblockNumber(4))
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn managing_partitions() {
+        let pool = ConnectionPool::test_pool().await;
+        let mut conn = pool.access_storage().await.unwrap();
+        conn.protocol_versions_dal()
+            .save_protocol_version_with_tx(ProtocolVersion::default())
+            .await;
+
+        // The default partition may contain rows.
+        insert_miniblock_with_logs(&mut conn, 0).await;
+        let mut partitions_dal = conn.storage_partitions_dal();
+        let start = partitions_dal
+            .next_partition_start(PartitionedTable::StorageLogs)
+            .await
+            .unwrap();
+        assert_eq!(start, MiniblockNumber(1));
+        let partition = partitions_dal
+            .create_partition(PartitionedTable::StorageLogs, start..MiniblockNumber(3))
+            .await
+            .unwrap();
+        assert_eq!(partition.name, "storage_logs_p1_3");
+        partitions_dal
+            .create_partition(
+                PartitionedTable::StorageLogs,
+                MiniblockNumber(3)..MiniblockNumber(5),
+            )
+            .await
+            .unwrap();
+        let next_start = partitions_dal
+            .next_partition_start(PartitionedTable::StorageLogs)
+            .await
+            .unwrap();
+        assert_eq!(next_start, MiniblockNumber(5));
+        // Creating an existing partition should be a no-op.
+        partitions_dal
+            .create_partition(
+                PartitionedTable::StorageLogs,
+                MiniblockNumber(3)..MiniblockNumber(5),
+            )
+            .await
+            .unwrap();
+        let partitions = partitions_dal
+            .get_partitions(PartitionedTable::StorageLogs)
+            .await
+            .unwrap();
+        assert_eq!(partitions.len(), 3); // the legacy partition + 2 created ones
+
+        for number in 1..4 {
+            insert_miniblock_with_logs(&mut conn, number).await;
+        }
+        let header = L1BatchHeader::new(
+            L1BatchNumber(1),
+            1,
+            Address::repeat_byte(0x42),
+            Default::default(),
+            ProtocolVersionId::latest(),
+        );
+        conn.blocks_dal()
+            .insert_l1_batch(&header, &[], BlockGasCount::default(), &[], &[], 0)
+            .await
+            .unwrap();
+        conn.blocks_dal()
+            .mark_miniblocks_as_executed_in_l1_batch(L1BatchNumber(1))
+            .await
+            .unwrap();
+
+        // Only the first partition is fully covered by L1 batch #1.
+        let detached = conn
+            .storage_partitions_dal()
+            .detach_partitions_before(PartitionedTable::StorageLogs, L1BatchNumber(1))
+            .await
+            .unwrap();
+        assert_eq!(detached, ["storage_logs_p_legacy", "storage_logs_p1_3"]);
+        let partitions = conn
+            .storage_partitions_dal()
+            .get_partitions(PartitionedTable::StorageLogs)
+            .await
+            .unwrap();
+        let detached_flags: Vec<_> = partitions.iter().map(|p| p.is_detached).collect();
+        assert_eq!(detached_flags, [true, true, false]);
+
+        // Logs in the remaining partitions should be accessible.
+        let log_count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM storage_logs")
+            .fetch_one(conn.conn())
+            .await
+            .unwrap();
+        assert_eq!(log_count, 2); // logs for miniblocks #0 (default partition) and #3
+
+        // Partitions cannot cover rows in the default partition.
+        insert_miniblock_with_logs(&mut conn, 10).await;
+        let err = conn
+            .storage_partitions_dal()
+            .create_partition(
+                PartitionedTable::StorageLogs,
+                MiniblockNumber(5)..MiniblockNumber(15),
+            )
+            .await
+            .unwrap_err();
+        assert!(format!("{err:#}").contains("storage_logs_p5_15"), "{err:#}");
+        let next_start = conn
+            .storage_partitions_dal()
+            .next_partition_start(PartitionedTable::StorageLogs)
+            .await
+            .unwrap();
+        assert_eq!(next_start, MiniblockNumber(11));
+    }
+}
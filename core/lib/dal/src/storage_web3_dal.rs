@@ -9,8 +9,9 @@ use zksync_types::{
 use zksync_utils::h256_to_u256;
 
 use crate::{
-    instrument::InstrumentExt, models::storage_block::ResolvedL1BatchForMiniblock, SqlxError,
-    StorageProcessor,
+    instrument::InstrumentExt,
+    models::{storage_block::ResolvedL1BatchForMiniblock, storage_factory_dep::decode_bytecode},
+    SqlxError, StorageProcessor,
 };
 
 #[derive(Debug)]
@@ -77,7 +78,6 @@ impl StorageWeb3Dal<'_, '_> {
                 block_number.0 as i64
             )
             .instrument("get_historical_value_unchecked")
-            .report_latency()
             .with_arg("key", &hashed_key)
             .fetch_optional(self.storage.conn())
             .await
@@ -142,7 +142,6 @@ impl StorageWeb3Dal<'_, '_> {
             hashed_key.as_bytes(),
         )
         .instrument("get_l1_batch_number_for_initial_write")
-        .report_latency()
         .with_arg("key", &hashed_key)
         .fetch_optional(self.storage.conn())
         .await?;
@@ -185,10 +184,11 @@ impl StorageWeb3Dal<'_, '_> {
     ) -> Result<Option<Vec<u8>>, SqlxError> {
         let hashed_key = get_code_key(&address).hashed_key();
         {
-            sqlx::query!(
+            let row = sqlx::query!(
                 r#"
                 SELECT
-                    bytecode
+                    bytecode,
+                    bytecode_compressed
                 FROM
                     (
                         SELECT
@@ -213,8 +213,9 @@ impl StorageWeb3Dal<'_, '_> {
                 FAILED_CONTRACT_DEPLOYMENT_BYTECODE_HASH.as_bytes(),
             )
             .fetch_optional(self.storage.conn())
-            .await
-            .map(|option_row| option_row.map(|row| row.bytecode))
+            .await?;
+            row.map(|row| decode_bytecode(row.bytecode, row.bytecode_compressed))
+                .transpose()
         }
     }
 
@@ -226,10 +227,11 @@ impl StorageWeb3Dal<'_, '_> {
         block_number: MiniblockNumber,
     ) -> Result<Option<Vec<u8>>, SqlxError> {
         {
-            sqlx::query!(
+            let row = sqlx::query!(
                 r#"
                 SELECT
-                    bytecode
+                    bytecode,
+                    bytecode_compressed
                 FROM
                     factory_deps
                 WHERE
@@ -240,8 +242,9 @@ impl StorageWeb3Dal<'_, '_> {
                 block_number.0 as i64
             )
             .fetch_optional(self.storage.conn())
-            .await
-            .map(|option_row| option_row.map(|row| row.bytecode))
+            .await?;
+            row.map(|row| decode_bytecode(row.bytecode, row.bytecode_compressed))
+                .transpose()
         }
     }
 }
@@ -46,6 +46,7 @@ impl SyncDal<'_, '_> {
                 miniblocks.timestamp,
                 miniblocks.l1_gas_price,
                 miniblocks.l2_fair_gas_price,
+                miniblocks.fair_pubdata_price,
                 miniblocks.bootloader_code_hash,
                 miniblocks.default_aa_code_hash,
                 miniblocks.virtual_blocks,
@@ -104,8 +105,9 @@ impl SyncDal<'_, '_> {
 #[cfg(test)]
 mod tests {
     use zksync_types::{
-        block::{BlockGasCount, L1BatchHeader},
+        block::{BlockGasCount, L1BatchHeader, MiniblockHeader},
         fee::TransactionExecutionMetrics,
+        fee_model::BatchFeeInput,
         L1BatchNumber, ProtocolVersion, ProtocolVersionId,
     };
 
@@ -153,7 +155,10 @@ mod tests {
             .is_none());
 
         // Insert another block in the store.
-        let miniblock_header = create_miniblock_header(1);
+        let miniblock_header = MiniblockHeader {
+            batch_fee_input: BatchFeeInput::pubdata_independent(100, 100, 3_000),
+            ..create_miniblock_header(1)
+        };
         let tx = mock_l2_transaction();
         conn.transactions_dal()
             .insert_transaction_l2(tx.clone(), TransactionExecutionMetrics::default())
@@ -196,6 +201,7 @@ mod tests {
             block.l2_fair_gas_price,
             miniblock_header.batch_fee_input.fair_l2_gas_price()
         );
+        assert_eq!(block.fair_pubdata_price, Some(3_000));
         assert_eq!(block.operator_address, operator_address);
         assert!(block.transactions.is_none());
 
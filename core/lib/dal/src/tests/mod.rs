@@ -10,7 +10,7 @@ use zksync_types::{
     l2::L2Tx,
     tx::{tx_execution_info::TxExecutionStatus, ExecutionMetrics, TransactionExecutionResult},
     Address, Execute, L1BlockNumber, L1TxCommonData, L2ChainId, MiniblockNumber, PriorityOpId,
-    ProtocolVersionId, H160, H256, MAX_GAS_PER_PUBDATA_BYTE, U256,
+    ProtocolVersionId, Transaction, H160, H256, MAX_GAS_PER_PUBDATA_BYTE, U256,
 };
 
 use crate::{
@@ -68,7 +68,7 @@ pub(crate) fn mock_l2_transaction() -> L2Tx {
     l2_tx
 }
 
-fn mock_l1_execute() -> L1Tx {
+pub(crate) fn mock_l1_execute() -> L1Tx {
     let serial_id = 1;
     let priority_op_data = L1TxCommonData {
         sender: H160::random(),
@@ -102,10 +102,13 @@ fn mock_l1_execute() -> L1Tx {
     }
 }
 
-pub(crate) fn mock_execution_result(transaction: L2Tx) -> TransactionExecutionResult {
+pub(crate) fn mock_execution_result(
+    transaction: impl Into<Transaction>,
+) -> TransactionExecutionResult {
+    let transaction: Transaction = transaction.into();
     TransactionExecutionResult {
         hash: transaction.hash(),
-        transaction: transaction.into(),
+        transaction,
         execution_info: ExecutionMetrics::default(),
         execution_status: TxExecutionStatus::Success,
         refunded_gas: 0,
@@ -163,6 +166,64 @@ async fn workflow_with_submit_tx_diff_hashes() {
     assert_eq!(result, L2TxSubmissionResult::Replaced);
 }
 
+#[tokio::test]
+async fn workflow_with_submit_tx_and_price_bump() {
+    let connection_pool = ConnectionPool::test_pool().await;
+    let storage = &mut connection_pool.access_storage().await.unwrap();
+    let mut transactions_dal = TransactionsDal { storage };
+
+    let tx = mock_l2_transaction();
+    let result = transactions_dal
+        .insert_transaction_l2_with_price_bump(tx.clone(), mock_tx_execution_metrics(), Some(10))
+        .await;
+    assert_eq!(result, L2TxSubmissionResult::Added);
+
+    let result = transactions_dal
+        .insert_transaction_l2_with_price_bump(tx.clone(), mock_tx_execution_metrics(), Some(10))
+        .await;
+    assert_eq!(result, L2TxSubmissionResult::Duplicate);
+
+    let mut underpriced_tx = tx.clone();
+    underpriced_tx.common_data.fee.max_fee_per_gas = tx.common_data.fee.max_fee_per_gas * 109 / 100;
+    underpriced_tx.set_input(H256::random().0.to_vec(), H256::random());
+    let result = transactions_dal
+        .insert_transaction_l2_with_price_bump(
+            underpriced_tx,
+            mock_tx_execution_metrics(),
+            Some(10),
+        )
+        .await;
+    assert_eq!(result, L2TxSubmissionResult::ReplacementUnderpriced);
+
+    let mut replacement_tx = tx.clone();
+    replacement_tx.common_data.fee.max_fee_per_gas = tx.common_data.fee.max_fee_per_gas * 110 / 100;
+    replacement_tx.set_input(H256::random().0.to_vec(), H256::random());
+    let result = transactions_dal
+        .insert_transaction_l2_with_price_bump(
+            replacement_tx.clone(),
+            mock_tx_execution_metrics(),
+            Some(10),
+        )
+        .await;
+    assert_eq!(result, L2TxSubmissionResult::Replaced);
+
+    let pending_tx = transactions_dal
+        .get_replaceable_l2_tx_fees(tx.initiator_account(), tx.common_data.nonce)
+        .await
+        .unwrap()
+        .expect("no pending tx");
+    assert_eq!(pending_tx.hash, replacement_tx.hash());
+    assert_eq!(
+        pending_tx.max_fee_per_gas,
+        replacement_tx.common_data.fee.max_fee_per_gas
+    );
+    assert!(!pending_tx.can_be_replaced_by(
+        replacement_tx.common_data.fee.max_fee_per_gas,
+        U256::zero(),
+        10
+    ));
+}
+
 #[tokio::test]
 async fn remove_stuck_txs() {
     let connection_pool = ConnectionPool::test_pool().await;
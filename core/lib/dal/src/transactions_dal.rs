@@ -16,7 +16,7 @@ use zksync_types::{
     Address, ExecuteTransactionCommon, L1BatchNumber, L1BlockNumber, MiniblockNumber, Nonce,
     PriorityOpId, Transaction, H256, PROTOCOL_UPGRADE_TX_TYPE, U256,
 };
-use zksync_utils::{h256_to_u32, u256_to_big_decimal};
+use zksync_utils::{bigdecimal_to_u256, h256_to_u32, u256_to_big_decimal};
 
 use crate::{
     instrument::InstrumentExt,
@@ -32,6 +32,9 @@ pub enum L2TxSubmissionResult {
     AlreadyExecuted,
     Duplicate,
     Proxied,
+    /// A pending transaction with the same initiator and nonce exists, and the new transaction
+    /// doesn't raise its fees enough to replace it.
+    ReplacementUnderpriced,
 }
 
 impl fmt::Display for L2TxSubmissionResult {
@@ -42,10 +45,37 @@ impl fmt::Display for L2TxSubmissionResult {
             Self::AlreadyExecuted => "already_executed",
             Self::Duplicate => "duplicate",
             Self::Proxied => "proxied",
+            Self::ReplacementUnderpriced => "replacement_underpriced",
         })
     }
 }
 
+/// Fees of a pending L2 transaction that can be replaced by another transaction with the same initiator and nonce.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReplaceableL2TxFees {
+    pub hash: H256,
+    pub max_fee_per_gas: U256,
+    pub max_priority_fee_per_gas: U256,
+}
+
+impl ReplaceableL2TxFees {
+    /// Checks whether a transaction with the specified fees can replace this transaction. Both `max_fee_per_gas`
+    /// and `max_priority_fee_per_gas` must be raised by at least `min_price_bump_percent` percent
+    /// (same as in Ethereum clients).
+    pub fn can_be_replaced_by(
+        &self,
+        max_fee_per_gas: U256,
+        max_priority_fee_per_gas: U256,
+        min_price_bump_percent: u32,
+    ) -> bool {
+        let bump_multiplier = U256::from(100 + min_price_bump_percent);
+        let hundred = U256::from(100);
+        self.max_fee_per_gas.full_mul(bump_multiplier) <= max_fee_per_gas.full_mul(hundred)
+            && self.max_priority_fee_per_gas.full_mul(bump_multiplier)
+                <= max_priority_fee_per_gas.full_mul(hundred)
+    }
+}
+
 #[derive(Debug)]
 pub struct TransactionsDal<'c, 'a> {
     pub(crate) storage: &'c mut StorageProcessor<'a>,
@@ -153,6 +183,24 @@ impl TransactionsDal<'_, '_> {
             .fetch_optional(self.storage.conn())
             .await
             .unwrap();
+
+            sqlx::query!(
+                r#"
+                INSERT INTO
+                    priority_op_l1_tx_hashes (priority_op_id, l1_tx_hash, created_at)
+                VALUES
+                    ($1, $2, NOW())
+                ON CONFLICT (priority_op_id) DO
+                UPDATE
+                SET
+                    l1_tx_hash = excluded.l1_tx_hash
+                "#,
+                serial_id,
+                tx.common_data.eth_hash.as_bytes()
+            )
+            .execute(self.storage.conn())
+            .await
+            .unwrap();
         }
     }
 
@@ -250,10 +298,25 @@ impl TransactionsDal<'_, '_> {
         }
     }
 
+    /// Inserts an L2 transaction, replacing a pending transaction with the same initiator and nonce regardless
+    /// of its fees.
     pub async fn insert_transaction_l2(
         &mut self,
         tx: L2Tx,
         exec_info: TransactionExecutionMetrics,
+    ) -> L2TxSubmissionResult {
+        self.insert_transaction_l2_with_price_bump(tx, exec_info, None)
+            .await
+    }
+
+    /// Inserts an L2 transaction. If `min_price_bump_percent` is set, a pending transaction with the same initiator
+    /// and nonce is only replaced if the new transaction raises both its fees by at least the specified percentage;
+    /// otherwise, [`L2TxSubmissionResult::ReplacementUnderpriced`] is returned.
+    pub async fn insert_transaction_l2_with_price_bump(
+        &mut self,
+        tx: L2Tx,
+        exec_info: TransactionExecutionMetrics,
+        min_price_bump_percent: Option<u32>,
     ) -> L2TxSubmissionResult {
         {
             let tx_hash = tx.hash();
@@ -287,6 +350,8 @@ impl TransactionsDal<'_, '_> {
             // Otherwise, if the subquery won't return NULL it means that there is already tx with such nonce and `initiator_address` in DB
             // and we can replace it WHERE clause conditions are met.
             // It is worth mentioning that if WHERE clause conditions are not met, None will be returned.
+            // This also happens if the new transaction doesn't bump fees of the replaced transaction enough.
+            let min_price_bump_percent = min_price_bump_percent.map(|percent| percent as i32);
             let query_result = sqlx::query!(
                 r#"
                 INSERT INTO
@@ -360,6 +425,13 @@ impl TransactionsDal<'_, '_> {
                 WHERE
                     transactions.is_priority = FALSE
                     AND transactions.miniblock_number IS NULL
+                    AND (
+                        $20::INT IS NULL
+                        OR (
+                            transactions.max_fee_per_gas * (100 + $20) <= $6 * 100
+                            AND transactions.max_priority_fee_per_gas * (100 + $20) <= $7 * 100
+                        )
+                    )
                 RETURNING
                     (
                         SELECT
@@ -389,7 +461,8 @@ impl TransactionsDal<'_, '_> {
                 exec_info.gas_used as i64,
                 (exec_info.initial_storage_writes + exec_info.repeated_storage_writes) as i32,
                 exec_info.contracts_used as i32,
-                received_at
+                received_at,
+                min_price_bump_percent
             )
                 .fetch_optional(self.storage.conn())
                 .await
@@ -399,7 +472,23 @@ impl TransactionsDal<'_, '_> {
                 Ok(option_query_result) => match option_query_result {
                     Some(true) => L2TxSubmissionResult::Replaced,
                     Some(false) => L2TxSubmissionResult::Added,
-                    None => L2TxSubmissionResult::AlreadyExecuted,
+                    None if min_price_bump_percent.is_none() => {
+                        L2TxSubmissionResult::AlreadyExecuted
+                    }
+                    None => {
+                        // Distinguish between an executed transaction and an underpriced replacement.
+                        let pending_tx = self
+                            .get_replaceable_l2_tx_fees(initiator_address, Nonce(nonce as u32))
+                            .await
+                            .unwrap();
+                        match pending_tx {
+                            Some(pending_tx) if pending_tx.hash == tx_hash => {
+                                L2TxSubmissionResult::Duplicate
+                            }
+                            Some(_) => L2TxSubmissionResult::ReplacementUnderpriced,
+                            None => L2TxSubmissionResult::AlreadyExecuted,
+                        }
+                    }
                 },
                 Err(err) => {
                     // So, we consider a tx hash to be a primary key of the transaction
@@ -432,6 +521,45 @@ impl TransactionsDal<'_, '_> {
         }
     }
 
+    /// Returns fees of a pending (i.e., not included into a miniblock) L2 transaction with the specified initiator
+    /// and nonce, if any.
+    pub async fn get_replaceable_l2_tx_fees(
+        &mut self,
+        initiator_address: Address,
+        nonce: Nonce,
+    ) -> sqlx::Result<Option<ReplaceableL2TxFees>> {
+        let row = sqlx::query!(
+            r#"
+            SELECT
+                hash,
+                max_fee_per_gas,
+                max_priority_fee_per_gas
+            FROM
+                transactions
+            WHERE
+                initiator_address = $1
+                AND nonce = $2
+                AND is_priority = FALSE
+                AND miniblock_number IS NULL
+            "#,
+            initiator_address.as_bytes(),
+            i64::from(nonce.0)
+        )
+        .instrument("get_replaceable_l2_tx_fees")
+        .with_arg("initiator_address", &initiator_address)
+        .with_arg("nonce", &nonce)
+        .fetch_optional(self.storage.conn())
+        .await?;
+
+        Ok(row.map(|row| ReplaceableL2TxFees {
+            hash: H256::from_slice(&row.hash),
+            max_fee_per_gas: bigdecimal_to_u256(row.max_fee_per_gas.unwrap_or_default()),
+            max_priority_fee_per_gas: bigdecimal_to_u256(
+                row.max_priority_fee_per_gas.unwrap_or_default(),
+            ),
+        }))
+    }
+
     pub async fn mark_txs_as_executed_in_l1_batch(
         &mut self,
         block_number: L1BatchNumber,
@@ -786,7 +914,6 @@ impl TransactionsDal<'_, '_> {
                     &bytea_call_traces
                 )
                 .instrument("insert_call_tracer")
-                .report_latency()
                 .execute(transaction.conn())
                 .await
                 .unwrap();
@@ -1036,6 +1163,25 @@ impl TransactionsDal<'_, '_> {
         }
     }
 
+    /// Returns the number of transactions that are not included into a miniblock and are not rejected.
+    pub async fn get_mempool_size(&mut self) -> sqlx::Result<u64> {
+        let row = sqlx::query!(
+            r#"
+            SELECT
+                COUNT(*) AS "count!"
+            FROM
+                transactions
+            WHERE
+                miniblock_number IS NULL
+                AND error IS NULL
+            "#
+        )
+        .instrument("get_mempool_size")
+        .fetch_one(self.storage.conn())
+        .await?;
+        Ok(row.count as u64)
+    }
+
     pub async fn get_last_processed_l1_block(&mut self) -> Option<L1BlockNumber> {
         {
             sqlx::query!(
@@ -1059,6 +1205,25 @@ impl TransactionsDal<'_, '_> {
         }
     }
 
+    /// Returns the hash of the priority operation with the specified serial ID, or `None` if it's not stored.
+    pub async fn get_priority_op_hash(&mut self, id: PriorityOpId) -> Option<H256> {
+        sqlx::query!(
+            r#"
+            SELECT
+                hash
+            FROM
+                transactions
+            WHERE
+                priority_op_id = $1
+            "#,
+            id.0 as i64
+        )
+        .fetch_optional(self.storage.conn())
+        .await
+        .unwrap()
+        .map(|row| H256::from_slice(&row.hash))
+    }
+
     pub async fn last_priority_id(&mut self) -> Option<PriorityOpId> {
         {
             let op_id = sqlx::query!(
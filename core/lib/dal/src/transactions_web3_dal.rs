@@ -1,4 +1,9 @@
-use sqlx::types::chrono::NaiveDateTime;
+use std::ops;
+
+use sqlx::{
+    types::{chrono::NaiveDateTime, BigDecimal},
+    Row,
+};
 use zksync_types::{
     api, Address, L2ChainId, MiniblockNumber, Transaction, ACCOUNT_CODE_STORAGE_ADDRESS,
     FAILED_CONTRACT_DEPLOYMENT_BYTECODE_HASH, H160, H256, U256, U64,
@@ -18,6 +23,16 @@ use crate::{
     SqlxError, StorageProcessor,
 };
 
+/// Executed transaction sent from or to a certain account.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AccountTransaction {
+    pub transaction: api::Transaction,
+    /// Timestamp of the miniblock the transaction was included in.
+    pub timestamp: u64,
+    pub is_failed: bool,
+    pub gas_used: U256,
+}
+
 #[derive(Debug)]
 pub struct TransactionsWeb3Dal<'a, 'c> {
     pub(crate) storage: &'a mut StorageProcessor<'c>,
@@ -286,6 +301,83 @@ impl TransactionsWeb3Dal<'_, '_> {
         }
     }
 
+    /// Returns executed transactions initiated by or sent to the specified `address` in the specified
+    /// miniblock range. Transactions are ordered by their position in the chain; `offset` and `limit`
+    /// are applied after ordering.
+    ///
+    /// Transactions are looked up separately by initiator and by recipient, ordering by the
+    /// `(miniblock_number, index_in_block)` key, so that each lookup is an index-ordered scan reading
+    /// at most `offset + limit` rows regardless of the total number of the account transactions.
+    pub async fn get_account_transactions(
+        &mut self,
+        address: Address,
+        miniblocks: ops::RangeInclusive<MiniblockNumber>,
+        offset: usize,
+        limit: usize,
+        ascending: bool,
+        chain_id: L2ChainId,
+    ) -> Result<Vec<AccountTransaction>, SqlxError> {
+        // The ordering must be static for Postgres to use indexes on `(address, miniblock_number, index_in_block)`.
+        let order = if ascending { "ASC" } else { "DESC" };
+        let query = format!(
+            "WITH matching_txs AS (
+                (
+                    SELECT hash, miniblock_number, index_in_block
+                    FROM transactions
+                    WHERE initiator_address = $1 AND miniblock_number BETWEEN $2 AND $3
+                    ORDER BY miniblock_number {order}, index_in_block {order}
+                    LIMIT $4
+                )
+                UNION
+                (
+                    SELECT hash, miniblock_number, index_in_block
+                    FROM transactions
+                    WHERE contract_address = $1 AND miniblock_number BETWEEN $2 AND $3
+                    ORDER BY miniblock_number {order}, index_in_block {order}
+                    LIMIT $4
+                )
+                ORDER BY miniblock_number {order}, index_in_block {order}
+                OFFSET $5
+                LIMIT $6
+            )
+            SELECT {select},
+                miniblocks.timestamp AS block_timestamp,
+                transactions.error AS tx_error,
+                transactions.refunded_gas AS refunded_gas
+            FROM matching_txs
+            INNER JOIN transactions ON transactions.hash = matching_txs.hash
+            INNER JOIN miniblocks ON miniblocks.number = transactions.miniblock_number
+            ORDER BY transactions.miniblock_number {order}, transactions.index_in_block {order}",
+            select = web3_transaction_select_sql()
+        );
+        let rows = sqlx::query(&query)
+            .bind(address.as_bytes())
+            .bind(i64::from(miniblocks.start().0))
+            .bind(i64::from(miniblocks.end().0))
+            .bind(offset.saturating_add(limit) as i64)
+            .bind(offset as i64)
+            .bind(limit as i64)
+            .instrument("get_account_transactions")
+            .with_arg("address", &address)
+            .with_arg("miniblocks", &miniblocks)
+            .fetch_all(self.storage.conn())
+            .await?;
+
+        let transactions = rows.into_iter().map(|db_row| {
+            let timestamp = db_row.get::<i64, &str>("block_timestamp") as u64;
+            let is_failed = db_row.get::<Option<String>, &str>("tx_error").is_some();
+            let gas_limit = bigdecimal_to_u256(db_row.get::<BigDecimal, &str>("gas_limit"));
+            let refunded_gas = U256::from(db_row.get::<i64, &str>("refunded_gas") as u64);
+            AccountTransaction {
+                transaction: extract_web3_transaction(db_row, chain_id),
+                timestamp,
+                is_failed,
+                gas_used: gas_limit.saturating_sub(refunded_gas),
+            }
+        });
+        Ok(transactions.collect())
+    }
+
     /// Returns hashes of txs which were received after `from_timestamp` and the time of receiving the last tx.
     pub async fn get_pending_txs_hashes_after(
         &mut self,
@@ -510,6 +602,82 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn getting_account_transactions() {
+        let connection_pool = ConnectionPool::test_pool().await;
+        let mut conn = connection_pool.access_storage().await.unwrap();
+        conn.protocol_versions_dal()
+            .save_protocol_version_with_tx(ProtocolVersion::default())
+            .await;
+        let tx = mock_l2_transaction();
+        let tx_hash = tx.hash();
+        let initiator = tx.initiator_account();
+        let recipient = tx.execute.contract_address;
+        prepare_transaction(&mut conn, tx).await;
+
+        let all_miniblocks = MiniblockNumber(0)..=MiniblockNumber(10);
+        for address in [initiator, recipient] {
+            let txs = conn
+                .transactions_web3_dal()
+                .get_account_transactions(
+                    address,
+                    all_miniblocks.clone(),
+                    0,
+                    10,
+                    true,
+                    L2ChainId::from(270),
+                )
+                .await
+                .unwrap();
+            assert_eq!(txs.len(), 1);
+            assert_eq!(txs[0].transaction.hash, tx_hash);
+            assert_eq!(txs[0].transaction.block_number, Some(1.into()));
+            assert!(!txs[0].is_failed);
+
+            let txs = conn
+                .transactions_web3_dal()
+                .get_account_transactions(
+                    address,
+                    all_miniblocks.clone(),
+                    0,
+                    10,
+                    false,
+                    L2ChainId::from(270),
+                )
+                .await
+                .unwrap();
+            assert_eq!(txs.len(), 1);
+            assert_eq!(txs[0].transaction.hash, tx_hash);
+        }
+
+        let txs = conn
+            .transactions_web3_dal()
+            .get_account_transactions(
+                initiator,
+                MiniblockNumber(2)..=MiniblockNumber(10),
+                0,
+                10,
+                true,
+                L2ChainId::from(270),
+            )
+            .await
+            .unwrap();
+        assert!(txs.is_empty());
+        let txs = conn
+            .transactions_web3_dal()
+            .get_account_transactions(
+                initiator,
+                all_miniblocks,
+                1,
+                10,
+                false,
+                L2ChainId::from(270),
+            )
+            .await
+            .unwrap();
+        assert!(txs.is_empty());
+    }
+
     #[tokio::test]
     async fn getting_miniblock_transactions() {
         let connection_pool = ConnectionPool::test_pool().await;
@@ -0,0 +1,420 @@
+use std::ops;
+
+use sqlx::types::chrono::NaiveDateTime;
+use zksync_types::{Address, L1BatchNumber, MiniblockNumber, H256};
+
+use crate::{instrument::InstrumentExt, StorageProcessor};
+
+/// Progress of publishing transaction lifecycle events. Each field is the last value for which
+/// events were published.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TxEventStreamCursor {
+    pub last_received_at: NaiveDateTime,
+    /// Hash of the last received transaction. Together with `last_received_at`, forms a keyset cursor
+    /// for received transactions since multiple transactions can have the same timestamp.
+    /// `None` means that all transactions received at `last_received_at` must be published.
+    pub last_received_tx_hash: Option<H256>,
+    pub last_included_miniblock: MiniblockNumber,
+    pub last_sealed_l1_batch: L1BatchNumber,
+    pub last_committed_l1_batch: Option<L1BatchNumber>,
+    pub last_proven_l1_batch: Option<L1BatchNumber>,
+    pub last_executed_l1_batch: Option<L1BatchNumber>,
+}
+
+/// Transaction received by the node.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReceivedTransaction {
+    pub tx_hash: H256,
+    pub initiator_address: Address,
+    pub received_at: NaiveDateTime,
+}
+
+#[derive(Debug)]
+pub struct TxEventStreamDal<'a, 'c> {
+    pub(crate) storage: &'a mut StorageProcessor<'c>,
+}
+
+impl TxEventStreamDal<'_, '_> {
+    /// Returns the persisted cursor, or `None` if events were never published.
+    pub async fn get_cursor(&mut self) -> sqlx::Result<Option<TxEventStreamCursor>> {
+        let row = sqlx::query!(
+            r#"
+            SELECT
+                last_received_at,
+                last_received_tx_hash,
+                last_included_miniblock,
+                last_sealed_l1_batch,
+                last_committed_l1_batch,
+                last_proven_l1_batch,
+                last_executed_l1_batch
+            FROM
+                tx_event_stream_cursor
+            "#
+        )
+        .instrument("get_tx_event_stream_cursor")
+        .fetch_optional(self.storage.conn())
+        .await?;
+
+        Ok(row.map(|row| TxEventStreamCursor {
+            last_received_at: row.last_received_at,
+            last_received_tx_hash: row
+                .last_received_tx_hash
+                .map(|hash| H256::from_slice(&hash)),
+            last_included_miniblock: MiniblockNumber(row.last_included_miniblock as u32),
+            last_sealed_l1_batch: L1BatchNumber(row.last_sealed_l1_batch as u32),
+            last_committed_l1_batch: row
+                .last_committed_l1_batch
+                .map(|number| L1BatchNumber(number as u32)),
+            last_proven_l1_batch: row
+                .last_proven_l1_batch
+                .map(|number| L1BatchNumber(number as u32)),
+            last_executed_l1_batch: row
+                .last_executed_l1_batch
+                .map(|number| L1BatchNumber(number as u32)),
+        }))
+    }
+
+    /// Inserts or overwrites the cursor.
+    pub async fn set_cursor(&mut self, cursor: &TxEventStreamCursor) -> sqlx::Result<()> {
+        sqlx::query!(
+            r#"
+            INSERT INTO
+                tx_event_stream_cursor (
+                    id,
+                    last_received_at,
+                    last_received_tx_hash,
+                    last_included_miniblock,
+                    last_sealed_l1_batch,
+                    last_committed_l1_batch,
+                    last_proven_l1_batch,
+                    last_executed_l1_batch,
+                    updated_at
+                )
+            VALUES
+                (TRUE, $1, $2, $3, $4, $5, $6, $7, NOW())
+            ON CONFLICT (id) DO
+            UPDATE
+            SET
+                last_received_at = excluded.last_received_at,
+                last_received_tx_hash = excluded.last_received_tx_hash,
+                last_included_miniblock = excluded.last_included_miniblock,
+                last_sealed_l1_batch = excluded.last_sealed_l1_batch,
+                last_committed_l1_batch = excluded.last_committed_l1_batch,
+                last_proven_l1_batch = excluded.last_proven_l1_batch,
+                last_executed_l1_batch = excluded.last_executed_l1_batch,
+                updated_at = NOW()
+            "#,
+            cursor.last_received_at,
+            cursor.last_received_tx_hash.as_ref().map(H256::as_bytes),
+            i64::from(cursor.last_included_miniblock.0),
+            i64::from(cursor.last_sealed_l1_batch.0),
+            cursor
+                .last_committed_l1_batch
+                .map(|number| i64::from(number.0)),
+            cursor
+                .last_proven_l1_batch
+                .map(|number| i64::from(number.0)),
+            cursor
+                .last_executed_l1_batch
+                .map(|number| i64::from(number.0))
+        )
+        .instrument("set_tx_event_stream_cursor")
+        .with_arg("cursor", cursor)
+        .execute(self.storage.conn())
+        .await?;
+        Ok(())
+    }
+
+    /// Moves the cursor back after the block reverter removes L1 batches and miniblocks, so that events
+    /// are republished for the blocks sealed in their place.
+    pub async fn reset_cursor_after_revert(
+        &mut self,
+        last_l1_batch_to_keep: L1BatchNumber,
+        last_miniblock_to_keep: MiniblockNumber,
+    ) -> sqlx::Result<()> {
+        sqlx::query!(
+            r#"
+            UPDATE tx_event_stream_cursor
+            SET
+                last_included_miniblock = LEAST(last_included_miniblock, $2),
+                last_sealed_l1_batch = LEAST(last_sealed_l1_batch, $1),
+                last_committed_l1_batch = CASE
+                    WHEN last_committed_l1_batch > $1 THEN $1
+                    ELSE last_committed_l1_batch
+                END,
+                last_proven_l1_batch = CASE
+                    WHEN last_proven_l1_batch > $1 THEN $1
+                    ELSE last_proven_l1_batch
+                END,
+                last_executed_l1_batch = CASE
+                    WHEN last_executed_l1_batch > $1 THEN $1
+                    ELSE last_executed_l1_batch
+                END,
+                updated_at = NOW()
+            "#,
+            i64::from(last_l1_batch_to_keep.0),
+            i64::from(last_miniblock_to_keep.0)
+        )
+        .instrument("reset_tx_event_stream_cursor_after_revert")
+        .with_arg("last_l1_batch_to_keep", &last_l1_batch_to_keep)
+        .with_arg("last_miniblock_to_keep", &last_miniblock_to_keep)
+        .execute(self.storage.conn())
+        .await?;
+        Ok(())
+    }
+
+    /// Returns the `received_at` timestamp and hash of the last received transaction.
+    pub async fn get_last_received_transaction(
+        &mut self,
+    ) -> sqlx::Result<Option<(NaiveDateTime, H256)>> {
+        let row = sqlx::query!(
+            r#"
+            SELECT
+                received_at,
+                hash
+            FROM
+                transactions
+            ORDER BY
+                received_at DESC,
+                hash DESC
+            LIMIT
+                1
+            "#
+        )
+        .instrument("get_last_received_transaction")
+        .fetch_optional(self.storage.conn())
+        .await?;
+        Ok(row.map(|row| (row.received_at, H256::from_slice(&row.hash))))
+    }
+
+    /// Returns transactions following the specified `(received_at, hash)` pair, ordered by the receiving time
+    /// and then by hash. If `last_tx_hash` is `None`, all transactions received at `received_after` are returned.
+    pub async fn get_received_transactions(
+        &mut self,
+        received_after: NaiveDateTime,
+        last_tx_hash: Option<H256>,
+        limit: usize,
+    ) -> sqlx::Result<Vec<ReceivedTransaction>> {
+        // An empty byte string precedes any hash in the `bytea` ordering.
+        let last_tx_hash_bytes = last_tx_hash.as_ref().map_or(&[][..], H256::as_bytes);
+        let rows = sqlx::query!(
+            r#"
+            SELECT
+                hash,
+                initiator_address,
+                received_at
+            FROM
+                transactions
+            WHERE
+                (received_at, hash) > ($1, $2)
+            ORDER BY
+                received_at,
+                hash
+            LIMIT
+                $3
+            "#,
+            received_after,
+            last_tx_hash_bytes,
+            limit as i64
+        )
+        .instrument("get_received_transactions")
+        .with_arg("received_after", &received_after)
+        .with_arg("last_tx_hash", &last_tx_hash)
+        .with_arg("limit", &limit)
+        .fetch_all(self.storage.conn())
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| ReceivedTransaction {
+                tx_hash: H256::from_slice(&row.hash),
+                initiator_address: Address::from_slice(&row.initiator_address),
+                received_at: row.received_at,
+            })
+            .collect())
+    }
+
+    /// Returns hashes of transactions included into miniblocks in the specified range, ordered by inclusion.
+    /// If `limit` is specified, at most this number of transactions is returned.
+    pub async fn get_miniblock_transactions(
+        &mut self,
+        numbers: ops::RangeInclusive<MiniblockNumber>,
+        limit: Option<usize>,
+    ) -> sqlx::Result<Vec<(H256, MiniblockNumber)>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT
+                hash,
+                miniblock_number AS "miniblock_number!"
+            FROM
+                transactions
+            WHERE
+                miniblock_number BETWEEN $1 AND $2
+            ORDER BY
+                miniblock_number,
+                index_in_block
+            LIMIT
+                $3
+            "#,
+            i64::from(numbers.start().0),
+            i64::from(numbers.end().0),
+            limit.map(|limit| limit as i64)
+        )
+        .instrument("get_miniblock_transactions")
+        .with_arg("numbers", &numbers)
+        .with_arg("limit", &limit)
+        .fetch_all(self.storage.conn())
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| {
+                let number = MiniblockNumber(row.miniblock_number as u32);
+                (H256::from_slice(&row.hash), number)
+            })
+            .collect())
+    }
+
+    /// Returns hashes of transactions included into L1 batches in the specified range, ordered by inclusion.
+    /// If `limit` is specified, at most this number of transactions is returned.
+    pub async fn get_l1_batch_transactions(
+        &mut self,
+        numbers: ops::RangeInclusive<L1BatchNumber>,
+        limit: Option<usize>,
+    ) -> sqlx::Result<Vec<(H256, L1BatchNumber)>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT
+                hash,
+                l1_batch_number AS "l1_batch_number!"
+            FROM
+                transactions
+            WHERE
+                l1_batch_number BETWEEN $1 AND $2
+            ORDER BY
+                l1_batch_number,
+                l1_batch_tx_index
+            LIMIT
+                $3
+            "#,
+            i64::from(numbers.start().0),
+            i64::from(numbers.end().0),
+            limit.map(|limit| limit as i64)
+        )
+        .instrument("get_l1_batch_transactions")
+        .with_arg("numbers", &numbers)
+        .with_arg("limit", &limit)
+        .fetch_all(self.storage.conn())
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| {
+                let number = L1BatchNumber(row.l1_batch_number as u32);
+                (H256::from_slice(&row.hash), number)
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zksync_types::{fee::TransactionExecutionMetrics, ProtocolVersion};
+
+    use super::*;
+    use crate::{tests::mock_l2_transaction, ConnectionPool};
+
+    #[tokio::test]
+    async fn tx_event_stream_cursor_roundtrip() {
+        let pool = ConnectionPool::test_pool().await;
+        let mut conn = pool.access_storage().await.unwrap();
+        assert_eq!(conn.tx_event_stream_dal().get_cursor().await.unwrap(), None);
+
+        let mut cursor = TxEventStreamCursor {
+            last_received_at: NaiveDateTime::from_timestamp_opt(1_000, 0).unwrap(),
+            last_received_tx_hash: None,
+            last_included_miniblock: MiniblockNumber(3),
+            last_sealed_l1_batch: L1BatchNumber(1),
+            last_committed_l1_batch: None,
+            last_proven_l1_batch: None,
+            last_executed_l1_batch: None,
+        };
+        conn.tx_event_stream_dal()
+            .set_cursor(&cursor)
+            .await
+            .unwrap();
+        let loaded = conn.tx_event_stream_dal().get_cursor().await.unwrap();
+        assert_eq!(loaded.as_ref(), Some(&cursor));
+
+        cursor.last_received_tx_hash = Some(H256::repeat_byte(1));
+        cursor.last_included_miniblock = MiniblockNumber(5);
+        cursor.last_committed_l1_batch = Some(L1BatchNumber(1));
+        conn.tx_event_stream_dal()
+            .set_cursor(&cursor)
+            .await
+            .unwrap();
+        let loaded = conn.tx_event_stream_dal().get_cursor().await.unwrap();
+        assert_eq!(loaded.as_ref(), Some(&cursor));
+
+        conn.tx_event_stream_dal()
+            .reset_cursor_after_revert(L1BatchNumber(0), MiniblockNumber(2))
+            .await
+            .unwrap();
+        let loaded = conn.tx_event_stream_dal().get_cursor().await.unwrap();
+        cursor.last_included_miniblock = MiniblockNumber(2);
+        cursor.last_sealed_l1_batch = L1BatchNumber(0);
+        cursor.last_committed_l1_batch = Some(L1BatchNumber(0));
+        assert_eq!(loaded, Some(cursor));
+    }
+
+    #[tokio::test]
+    async fn getting_received_transactions() {
+        let pool = ConnectionPool::test_pool().await;
+        let mut conn = pool.access_storage().await.unwrap();
+        conn.protocol_versions_dal()
+            .save_protocol_version_with_tx(ProtocolVersion::default())
+            .await;
+        let tx = mock_l2_transaction();
+        let tx_hash = tx.hash();
+        let initiator_address = tx.initiator_account();
+        conn.transactions_dal()
+            .insert_transaction_l2(tx, TransactionExecutionMetrics::default())
+            .await;
+
+        let (received_at, last_tx_hash) = conn
+            .tx_event_stream_dal()
+            .get_last_received_transaction()
+            .await
+            .unwrap()
+            .expect("no transactions");
+        assert_eq!(last_tx_hash, tx_hash);
+        let epoch = NaiveDateTime::from_timestamp_opt(0, 0).unwrap();
+        let received = conn
+            .tx_event_stream_dal()
+            .get_received_transactions(epoch, None, 10)
+            .await
+            .unwrap();
+        assert_eq!(
+            received,
+            [ReceivedTransaction {
+                tx_hash,
+                initiator_address,
+                received_at,
+            }]
+        );
+
+        // Transactions with the same timestamp are not skipped if the hash is not specified.
+        let received = conn
+            .tx_event_stream_dal()
+            .get_received_transactions(received_at, None, 10)
+            .await
+            .unwrap();
+        assert_eq!(received.len(), 1);
+        let received = conn
+            .tx_event_stream_dal()
+            .get_received_transactions(received_at, Some(tx_hash), 10)
+            .await
+            .unwrap();
+        assert!(received.is_empty());
+    }
+}
@@ -0,0 +1,252 @@
+use std::{fmt, str::FromStr};
+
+use crate::{instrument::InstrumentExt, StorageProcessor};
+
+/// Action taken for transactions matching a [`TxSubmissionRule`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TxSubmissionRuleAction {
+    /// Only matching transactions are accepted (unless they match a `Deny` rule).
+    Allow,
+    /// Matching transactions are rejected.
+    Deny,
+}
+
+impl TxSubmissionRuleAction {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Allow => "allow",
+            Self::Deny => "deny",
+        }
+    }
+}
+
+impl FromStr for TxSubmissionRuleAction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "allow" => Ok(Self::Allow),
+            "deny" => Ok(Self::Deny),
+            _ => Err(format!("unknown tx submission rule action: {s}")),
+        }
+    }
+}
+
+/// Part of a transaction checked by a [`TxSubmissionRule`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TxSubmissionRuleTarget {
+    /// Transaction initiator address.
+    Sender,
+    /// Contract called by the transaction.
+    Contract,
+    /// 4-byte selector of the transaction calldata.
+    Selector,
+}
+
+impl TxSubmissionRuleTarget {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Sender => "sender",
+            Self::Contract => "contract",
+            Self::Selector => "selector",
+        }
+    }
+}
+
+impl FromStr for TxSubmissionRuleTarget {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "sender" => Ok(Self::Sender),
+            "contract" => Ok(Self::Contract),
+            "selector" => Ok(Self::Selector),
+            _ => Err(format!("unknown tx submission rule target: {s}")),
+        }
+    }
+}
+
+impl fmt::Display for TxSubmissionRuleTarget {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str(self.as_str())
+    }
+}
+
+/// Operator-defined rule restricting transactions accepted by the API server.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TxSubmissionRule {
+    pub id: i64,
+    pub action: TxSubmissionRuleAction,
+    pub target: TxSubmissionRuleTarget,
+    /// Address (for sender and contract rules) or 4-byte selector.
+    pub value: Vec<u8>,
+    pub comment: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct TxSubmissionRulesDal<'a, 'c> {
+    pub(crate) storage: &'a mut StorageProcessor<'c>,
+}
+
+impl TxSubmissionRulesDal<'_, '_> {
+    /// Returns all rules ordered by ID.
+    pub async fn get_rules(&mut self) -> sqlx::Result<Vec<TxSubmissionRule>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT
+                id,
+                action,
+                target,
+                value,
+                comment
+            FROM
+                tx_submission_rules
+            ORDER BY
+                id
+            "#
+        )
+        .instrument("get_tx_submission_rules")
+        .fetch_all(self.storage.conn())
+        .await?;
+
+        rows.into_iter()
+            .map(|row| {
+                Ok(TxSubmissionRule {
+                    id: row.id,
+                    action: row.action.parse().map_err(decode_error)?,
+                    target: row.target.parse().map_err(decode_error)?,
+                    value: row.value,
+                    comment: row.comment,
+                })
+            })
+            .collect()
+    }
+
+    /// Inserts a new rule or updates the comment of an existing one. Returns the rule ID.
+    pub async fn insert_rule(
+        &mut self,
+        action: TxSubmissionRuleAction,
+        target: TxSubmissionRuleTarget,
+        value: &[u8],
+        comment: Option<&str>,
+    ) -> sqlx::Result<i64> {
+        let row = sqlx::query!(
+            r#"
+            INSERT INTO
+                tx_submission_rules (action, target, value, comment, created_at)
+            VALUES
+                ($1, $2, $3, $4, NOW())
+            ON CONFLICT (action, target, value) DO
+            UPDATE
+            SET
+                comment = excluded.comment
+            RETURNING
+                id
+            "#,
+            action.as_str(),
+            target.as_str(),
+            value,
+            comment
+        )
+        .instrument("insert_tx_submission_rule")
+        .with_arg("action", &action)
+        .with_arg("target", &target)
+        .fetch_one(self.storage.conn())
+        .await?;
+        Ok(row.id)
+    }
+
+    /// Removes the rule with the specified ID. Returns `false` if there is no such rule.
+    pub async fn remove_rule(&mut self, id: i64) -> sqlx::Result<bool> {
+        let result = sqlx::query!(
+            r#"
+            DELETE FROM tx_submission_rules
+            WHERE
+                id = $1
+            "#,
+            id
+        )
+        .instrument("remove_tx_submission_rule")
+        .with_arg("id", &id)
+        .execute(self.storage.conn())
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+fn decode_error(message: String) -> sqlx::Error {
+    sqlx::Error::Decode(message.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use zksync_types::Address;
+
+    use super::*;
+    use crate::ConnectionPool;
+
+    #[tokio::test]
+    async fn managing_tx_submission_rules() {
+        let pool = ConnectionPool::test_pool().await;
+        let mut storage = pool.access_storage().await.unwrap();
+        let mut dal = storage.tx_submission_rules_dal();
+        assert_eq!(dal.get_rules().await.unwrap(), []);
+
+        let sender = Address::repeat_byte(1);
+        let sender_rule_id = dal
+            .insert_rule(
+                TxSubmissionRuleAction::Deny,
+                TxSubmissionRuleTarget::Sender,
+                sender.as_bytes(),
+                None,
+            )
+            .await
+            .unwrap();
+        let selector_rule_id = dal
+            .insert_rule(
+                TxSubmissionRuleAction::Allow,
+                TxSubmissionRuleTarget::Selector,
+                &[1, 2, 3, 4],
+                Some("transfer"),
+            )
+            .await
+            .unwrap();
+        let updated_rule_id = dal
+            .insert_rule(
+                TxSubmissionRuleAction::Deny,
+                TxSubmissionRuleTarget::Sender,
+                sender.as_bytes(),
+                Some("sanctioned"),
+            )
+            .await
+            .unwrap();
+        assert_eq!(updated_rule_id, sender_rule_id);
+
+        let rules = dal.get_rules().await.unwrap();
+        assert_eq!(
+            rules,
+            [
+                TxSubmissionRule {
+                    id: sender_rule_id,
+                    action: TxSubmissionRuleAction::Deny,
+                    target: TxSubmissionRuleTarget::Sender,
+                    value: sender.as_bytes().to_vec(),
+                    comment: Some("sanctioned".to_owned()),
+                },
+                TxSubmissionRule {
+                    id: selector_rule_id,
+                    action: TxSubmissionRuleAction::Allow,
+                    target: TxSubmissionRuleTarget::Selector,
+                    value: vec![1, 2, 3, 4],
+                    comment: Some("transfer".to_owned()),
+                },
+            ]
+        );
+
+        assert!(dal.remove_rule(sender_rule_id).await.unwrap());
+        assert!(!dal.remove_rule(sender_rule_id).await.unwrap());
+        let rules = dal.get_rules().await.unwrap();
+        assert_eq!(rules.len(), 1);
+        assert_eq!(rules[0].id, selector_rule_id);
+    }
+}
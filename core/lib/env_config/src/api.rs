@@ -1,7 +1,8 @@
 use anyhow::Context as _;
 use zksync_config::configs::{
     api::{
-        ContractVerificationApiConfig, HealthCheckConfig, MerkleTreeApiConfig, Web3JsonRpcConfig,
+        ContractVerificationApiConfig, ExplorerApiConfig, HealthCheckConfig, MerkleTreeApiConfig,
+        Web3JsonRpcConfig,
     },
     ApiConfig, PrometheusConfig,
 };
@@ -46,6 +47,12 @@ impl FromEnv for MerkleTreeApiConfig {
     }
 }
 
+impl FromEnv for ExplorerApiConfig {
+    fn from_env() -> anyhow::Result<Self> {
+        envy_load("explorer_api", "API_EXPLORER_")
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::num::NonZeroU32;
@@ -67,6 +74,7 @@ mod tests {
                 subscriptions_limit: Some(10000),
                 pubsub_polling_interval: Some(200),
                 max_nonce_ahead: 5,
+                tx_replacement_price_bump_percent: Some(10),
                 request_timeout: Some(10),
                 account_pks: Some(vec![
                     hash("0x0000000000000000000000000000000000000000000000000000000000000001"),
@@ -81,11 +89,26 @@ mod tests {
                 factory_deps_cache_size_mb: Some(128),
                 initial_writes_cache_size_mb: Some(32),
                 latest_values_cache_size_mb: Some(256),
+                response_cache_size_mb: Some(16),
+                response_cache_ttl_sec: Some(60),
                 fee_history_limit: Some(100),
+                get_logs_max_block_range: Some(50000),
+                get_logs_max_results: Some(5000),
+                historical_state_depth: Some(100000),
                 max_batch_request_size: Some(200),
+                batch_request_concurrency: Some(8),
                 max_response_body_size_mb: Some(10),
                 websocket_requests_per_minute_limit: Some(NonZeroU32::new(10).unwrap()),
+                websocket_subscriptions_per_connection_limit: Some(100),
+                websocket_idle_timeout: Some(60),
+                pubsub_send_timeout_ms: Some(500),
+                read_requests_per_minute_limit: Some(NonZeroU32::new(6000).unwrap()),
+                send_tx_requests_per_minute_limit: Some(NonZeroU32::new(60).unwrap()),
+                debug_requests_per_minute_limit: None,
+                rate_limit_api_key_header: Some("X-Api-Key".to_owned()),
+                rate_limit_trusted_proxy_count: Some(1),
                 tree_api_url: None,
+                admin_namespace_enabled: Some(true),
             },
             contract_verification: ContractVerificationApiConfig {
                 port: 3070,
@@ -96,7 +119,10 @@ mod tests {
                 pushgateway_url: "http://127.0.0.1:9091".into(),
                 push_interval_ms: Some(100),
             },
-            healthcheck: HealthCheckConfig { port: 8081 },
+            healthcheck: HealthCheckConfig {
+                port: 8081,
+                admin_port: Some(8083),
+            },
             merkle_tree: MerkleTreeApiConfig { port: 8082 },
         }
     }
@@ -114,6 +140,7 @@ mod tests {
             API_WEB3_JSON_RPC_SUBSCRIPTIONS_LIMIT=10000
             API_WEB3_JSON_RPC_PUBSUB_POLLING_INTERVAL=200
             API_WEB3_JSON_RPC_MAX_NONCE_AHEAD=5
+            API_WEB3_JSON_RPC_TX_REPLACEMENT_PRICE_BUMP_PERCENT=10
             API_WEB3_JSON_RPC_GAS_PRICE_SCALE_FACTOR=1.2
             API_WEB3_JSON_RPC_REQUEST_TIMEOUT=10
             API_WEB3_JSON_RPC_ACCOUNT_PKS="0x0000000000000000000000000000000000000000000000000000000000000001,0x0000000000000000000000000000000000000000000000000000000000000002"
@@ -125,15 +152,30 @@ mod tests {
             API_WEB3_JSON_RPC_INITIAL_WRITES_CACHE_SIZE_MB=32
             API_WEB3_JSON_RPC_LATEST_VALUES_CACHE_SIZE_MB=256
             API_WEB3_JSON_RPC_FEE_HISTORY_LIMIT=100
+            API_WEB3_JSON_RPC_RESPONSE_CACHE_SIZE_MB=16
+            API_WEB3_JSON_RPC_RESPONSE_CACHE_TTL_SEC=60
+            API_WEB3_JSON_RPC_GET_LOGS_MAX_BLOCK_RANGE=50000
+            API_WEB3_JSON_RPC_GET_LOGS_MAX_RESULTS=5000
+            API_WEB3_JSON_RPC_HISTORICAL_STATE_DEPTH=100000
             API_WEB3_JSON_RPC_MAX_BATCH_REQUEST_SIZE=200
+            API_WEB3_JSON_RPC_BATCH_REQUEST_CONCURRENCY=8
             API_WEB3_JSON_RPC_WEBSOCKET_REQUESTS_PER_MINUTE_LIMIT=10
+            API_WEB3_JSON_RPC_WEBSOCKET_SUBSCRIPTIONS_PER_CONNECTION_LIMIT=100
+            API_WEB3_JSON_RPC_WEBSOCKET_IDLE_TIMEOUT=60
+            API_WEB3_JSON_RPC_PUBSUB_SEND_TIMEOUT_MS=500
+            API_WEB3_JSON_RPC_READ_REQUESTS_PER_MINUTE_LIMIT=6000
+            API_WEB3_JSON_RPC_SEND_TX_REQUESTS_PER_MINUTE_LIMIT=60
+            API_WEB3_JSON_RPC_RATE_LIMIT_API_KEY_HEADER="X-Api-Key"
+            API_WEB3_JSON_RPC_RATE_LIMIT_TRUSTED_PROXY_COUNT=1
             API_CONTRACT_VERIFICATION_PORT="3070"
             API_CONTRACT_VERIFICATION_URL="http://127.0.0.1:3070"
             API_WEB3_JSON_RPC_MAX_RESPONSE_BODY_SIZE_MB=10
+            API_WEB3_JSON_RPC_ADMIN_NAMESPACE_ENABLED=true
             API_PROMETHEUS_LISTENER_PORT="3312"
             API_PROMETHEUS_PUSHGATEWAY_URL="http://127.0.0.1:9091"
             API_PROMETHEUS_PUSH_INTERVAL_MS=100
             API_HEALTHCHECK_PORT=8081
+            API_HEALTHCHECK_ADMIN_PORT=8083
             API_MERKLE_TREE_PORT=8082
         "#;
         lock.set_env(config);
@@ -141,4 +183,25 @@ mod tests {
         let actual = ApiConfig::from_env().unwrap();
         assert_eq!(actual, expected_config());
     }
+
+    #[test]
+    fn explorer_api_from_env() {
+        let mut lock = MUTEX.lock();
+        let config = r#"
+            API_EXPLORER_PORT=3073
+            API_EXPLORER_MAX_RESULT_WINDOW=5000
+            API_EXPLORER_STATEMENT_TIMEOUT_SEC=5
+        "#;
+        lock.set_env(config);
+
+        let actual = ExplorerApiConfig::from_env().unwrap();
+        assert_eq!(
+            actual,
+            ExplorerApiConfig {
+                port: 3073,
+                max_result_window: 5000,
+                statement_timeout_sec: 5,
+            }
+        );
+    }
 }
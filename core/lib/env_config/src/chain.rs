@@ -36,7 +36,8 @@ impl FromEnv for MempoolConfig {
 
 #[cfg(test)]
 mod tests {
-    use zksync_basic_types::L2ChainId;
+    use zksync_basic_types::{commitment::L1BatchCommitmentMode, L2ChainId};
+    use zksync_config::configs::chain::{FeeModelVersion, MempoolOrdering};
 
     use super::*;
     use crate::test_utils::{addr, EnvMutex};
@@ -48,6 +49,7 @@ mod tests {
             network: "localhost".parse().unwrap(),
             zksync_network: "localhost".to_string(),
             zksync_network_id: L2ChainId::from(270),
+            chain_registry_url: None,
         }
     }
 
@@ -71,6 +73,8 @@ mod tests {
             block_commit_deadline_ms: 2500,
             miniblock_commit_deadline_ms: 1000,
             miniblock_seal_queue_capacity: 10,
+            miniblock_max_gas: Some(50_000_000),
+            miniblock_max_pubdata_bytes: None,
             max_single_tx_gas: 1_000_000,
             max_allowed_l2_tx_gas_limit: 2_000_000_000,
             close_block_at_eth_params_percentage: 0.2,
@@ -87,6 +91,24 @@ mod tests {
             virtual_blocks_per_miniblock: 1,
             upload_witness_inputs_to_gcs: false,
             enum_index_migration_chunk_size: Some(2_000),
+            seal_criteria: Some(vec![
+                "slots".to_owned(),
+                "pub_data_size".to_owned(),
+                "circuits".to_owned(),
+                "tx_encoding_size".to_owned(),
+            ]),
+            async_protective_reads: true,
+            use_copy_for_bulk_inserts: true,
+            upgrade_orchestration_enabled: true,
+            upgrade_requires_prover_support: true,
+            fee_model_version: FeeModelVersion::V2,
+            compute_overhead_part: 0.0,
+            pubdata_overhead_part: 1.0,
+            batch_overhead_l1_gas: 800000,
+            max_gas_per_batch: 200000000,
+            max_pubdata_per_batch: 100000,
+            fee_pricing_curve_path: Some("/etc/zksync/fee_pricing_curve.json".to_owned()),
+            l1_batch_commitment_mode: L1BatchCommitmentMode::Validium,
         }
     }
 
@@ -107,11 +129,25 @@ mod tests {
             CHAIN_STATE_KEEPER_BLOCK_COMMIT_DEADLINE_MS="2500"
             CHAIN_STATE_KEEPER_MINIBLOCK_COMMIT_DEADLINE_MS="1000"
             CHAIN_STATE_KEEPER_MINIBLOCK_SEAL_QUEUE_CAPACITY="10"
+            CHAIN_STATE_KEEPER_MINIBLOCK_MAX_GAS="50000000"
             CHAIN_STATE_KEEPER_FAIR_L2_GAS_PRICE="250000000"
             CHAIN_STATE_KEEPER_VALIDATION_COMPUTATIONAL_GAS_LIMIT="10000000"
             CHAIN_STATE_KEEPER_SAVE_CALL_TRACES="false"
             CHAIN_STATE_KEEPER_UPLOAD_WITNESS_INPUTS_TO_GCS="false"
             CHAIN_STATE_KEEPER_ENUM_INDEX_MIGRATION_CHUNK_SIZE="2000"
+            CHAIN_STATE_KEEPER_SEAL_CRITERIA="slots,pub_data_size,circuits,tx_encoding_size"
+            CHAIN_STATE_KEEPER_ASYNC_PROTECTIVE_READS="true"
+            CHAIN_STATE_KEEPER_USE_COPY_FOR_BULK_INSERTS="true"
+            CHAIN_STATE_KEEPER_UPGRADE_ORCHESTRATION_ENABLED="true"
+            CHAIN_STATE_KEEPER_UPGRADE_REQUIRES_PROVER_SUPPORT="true"
+            CHAIN_STATE_KEEPER_FEE_MODEL_VERSION="V2"
+            CHAIN_STATE_KEEPER_COMPUTE_OVERHEAD_PART="0.0"
+            CHAIN_STATE_KEEPER_PUBDATA_OVERHEAD_PART="1.0"
+            CHAIN_STATE_KEEPER_BATCH_OVERHEAD_L1_GAS="800000"
+            CHAIN_STATE_KEEPER_MAX_GAS_PER_BATCH="200000000"
+            CHAIN_STATE_KEEPER_MAX_PUBDATA_PER_BATCH="100000"
+            CHAIN_STATE_KEEPER_FEE_PRICING_CURVE_PATH="/etc/zksync/fee_pricing_curve.json"
+            CHAIN_STATE_KEEPER_L1_BATCH_COMMITMENT_MODE="Validium"
         "#;
         lock.set_env(config);
 
@@ -145,6 +181,11 @@ mod tests {
             stuck_tx_timeout: 10,
             remove_stuck_txs: true,
             delay_interval: 100,
+            ordering: Some(MempoolOrdering::PriorityFee),
+            boosted_accounts: Some(vec![
+                addr("0x0000000000000000000000000000000000000001"),
+                addr("0x0000000000000000000000000000000000000002"),
+            ]),
         }
     }
 
@@ -158,6 +199,8 @@ mod tests {
             CHAIN_MEMPOOL_REMOVE_STUCK_TXS="true"
             CHAIN_MEMPOOL_DELAY_INTERVAL="100"
             CHAIN_MEMPOOL_CAPACITY="1000000"
+            CHAIN_MEMPOOL_ORDERING="priority_fee"
+            CHAIN_MEMPOOL_BOOSTED_ACCOUNTS="0x0000000000000000000000000000000000000001,0x0000000000000000000000000000000000000002"
         "#;
         lock.set_env(config);
 
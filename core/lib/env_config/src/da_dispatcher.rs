@@ -0,0 +1,39 @@
+use zksync_config::configs::DADispatcherConfig;
+
+use crate::{envy_load, FromEnv};
+
+impl FromEnv for DADispatcherConfig {
+    fn from_env() -> anyhow::Result<Self> {
+        envy_load("da_dispatcher", "DA_DISPATCHER_")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::EnvMutex;
+
+    static MUTEX: EnvMutex = EnvMutex::new();
+
+    #[test]
+    fn from_env() {
+        let config = r#"
+            DA_DISPATCHER_API_URL="http://127.0.0.1:3100/"
+            DA_DISPATCHER_POLLING_INTERVAL_MS="1000"
+            DA_DISPATCHER_MAX_ROWS_TO_DISPATCH="10"
+            DA_DISPATCHER_REQUEST_TIMEOUT_MS="5000"
+        "#;
+        let mut lock = MUTEX.lock();
+        lock.set_env(config);
+        let actual = DADispatcherConfig::from_env().unwrap();
+        assert_eq!(
+            actual,
+            DADispatcherConfig {
+                api_url: "http://127.0.0.1:3100/".to_owned(),
+                polling_interval_ms: 1_000,
+                max_rows_to_dispatch: 10,
+                request_timeout_ms: 5_000,
+            }
+        );
+    }
+}
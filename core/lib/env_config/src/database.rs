@@ -34,6 +34,20 @@ impl FromEnv for PostgresConfig {
                     .context("failed to parse DATABASE_STATEMENT_TIMEOUT_SEC")
             })
             .transpose()?;
+        let max_replication_lag_sec = env::var("DATABASE_MAX_REPLICATION_LAG_SEC")
+            .ok()
+            .map(|val| {
+                val.parse()
+                    .context("failed to parse DATABASE_MAX_REPLICATION_LAG_SEC")
+            })
+            .transpose()?;
+        let slow_query_threshold_ms = env::var("DATABASE_SLOW_QUERY_THRESHOLD_MS")
+            .ok()
+            .map(|val| {
+                val.parse()
+                    .context("failed to parse DATABASE_SLOW_QUERY_THRESHOLD_MS")
+            })
+            .transpose()?;
 
         Ok(Self {
             master_url,
@@ -41,6 +55,8 @@ impl FromEnv for PostgresConfig {
             prover_url,
             max_connections,
             statement_timeout_sec,
+            max_replication_lag_sec,
+            slow_query_threshold_ms,
         })
     }
 }
@@ -61,23 +77,74 @@ mod tests {
         let mut lock = MUTEX.lock();
         let config = r#"
             DATABASE_STATE_KEEPER_DB_PATH="/db/state_keeper"
+            DATABASE_ROCKSDB_BACKUP_INTERVAL_SEC=3600
+            DATABASE_STATE_KEEPER_CACHE_WARM_UP_L1_BATCHES=10
+            DATABASE_STATE_KEEPER_CACHE_COMPACTION_INTERVAL_SEC=86400
+            DATABASE_STATE_KEEPER_CACHE_COMPACTION_START_HOUR=22
+            DATABASE_STATE_KEEPER_CACHE_COMPACTION_END_HOUR=4
+            DATABASE_STATE_KEEPER_OVERLAY_CACHE_SIZE_MB=64
             DATABASE_MERKLE_TREE_PATH="/db/tree"
             DATABASE_MERKLE_TREE_MODE=lightweight
             DATABASE_MERKLE_TREE_MULTI_GET_CHUNK_SIZE=250
             DATABASE_MERKLE_TREE_MEMTABLE_CAPACITY_MB=512
             DATABASE_MERKLE_TREE_STALLED_WRITES_TIMEOUT_SEC=60
             DATABASE_MERKLE_TREE_MAX_L1_BATCHES_PER_ITER=50
+            DATABASE_MERKLE_TREE_HASHING_THREAD_COUNT=8
+            DATABASE_MERKLE_TREE_PRUNING_PAST_VERSIONS_TO_KEEP=100
+            DATABASE_MERKLE_TREE_RECOVER_FROM_STORAGE_LOGS=true
+            DATABASE_MERKLE_TREE_STORAGE_LOGS_RECOVERY_L1_BATCH=123
+            DATABASE_MERKLE_TREE_FOLLOWER_VERSION_LAG=5
+            DATABASE_MERKLE_TREE_CONSISTENCY_CHECK_INTERVAL_SEC=3600
         "#;
         lock.set_env(config);
 
         let db_config = DBConfig::from_env().unwrap();
         assert_eq!(db_config.state_keeper_db_path, "/db/state_keeper");
+        assert_eq!(
+            db_config.rocksdb_backup_interval(),
+            Some(Duration::from_secs(3600))
+        );
+        assert_eq!(db_config.state_keeper_cache_warm_up_l1_batches, Some(10));
+        assert_eq!(
+            db_config.state_keeper_cache_compaction_interval(),
+            Some(Duration::from_secs(86400))
+        );
+        assert_eq!(
+            db_config.state_keeper_cache_compaction_window().unwrap(),
+            Some((22, 4))
+        );
+        let invalid_config = DBConfig {
+            state_keeper_cache_compaction_end_hour: Some(24),
+            ..db_config.clone()
+        };
+        invalid_config
+            .state_keeper_cache_compaction_window()
+            .unwrap_err();
+        assert_eq!(
+            db_config.state_keeper_overlay_cache_size(),
+            Some(64 * 1_024 * 1_024)
+        );
         assert_eq!(db_config.merkle_tree.path, "/db/tree");
         assert_eq!(db_config.merkle_tree.mode, MerkleTreeMode::Lightweight);
         assert_eq!(db_config.merkle_tree.multi_get_chunk_size, 250);
         assert_eq!(db_config.merkle_tree.max_l1_batches_per_iter, 50);
         assert_eq!(db_config.merkle_tree.memtable_capacity_mb, 512);
         assert_eq!(db_config.merkle_tree.stalled_writes_timeout_sec, 60);
+        assert_eq!(db_config.merkle_tree.hashing_thread_count, Some(8));
+        assert_eq!(
+            db_config.merkle_tree.pruning_past_versions_to_keep,
+            Some(100)
+        );
+        assert!(db_config.merkle_tree.recover_from_storage_logs);
+        assert_eq!(
+            db_config.merkle_tree.storage_logs_recovery_l1_batch,
+            Some(123)
+        );
+        assert_eq!(db_config.merkle_tree.follower_version_lag, Some(5));
+        assert_eq!(
+            db_config.merkle_tree.consistency_check_interval(),
+            Some(Duration::from_secs(3600))
+        );
     }
 
     #[test]
@@ -85,6 +152,12 @@ mod tests {
         let mut lock = MUTEX.lock();
         lock.remove_env(&[
             "DATABASE_STATE_KEEPER_DB_PATH",
+            "DATABASE_ROCKSDB_BACKUP_INTERVAL_SEC",
+            "DATABASE_STATE_KEEPER_CACHE_WARM_UP_L1_BATCHES",
+            "DATABASE_STATE_KEEPER_CACHE_COMPACTION_INTERVAL_SEC",
+            "DATABASE_STATE_KEEPER_CACHE_COMPACTION_START_HOUR",
+            "DATABASE_STATE_KEEPER_CACHE_COMPACTION_END_HOUR",
+            "DATABASE_STATE_KEEPER_OVERLAY_CACHE_SIZE_MB",
             "DATABASE_MERKLE_TREE_BACKUP_PATH",
             "DATABASE_MERKLE_TREE_PATH",
             "DATABASE_MERKLE_TREE_MODE",
@@ -93,10 +166,24 @@ mod tests {
             "DATABASE_MERKLE_TREE_MEMTABLE_CAPACITY_MB",
             "DATABASE_MERKLE_TREE_STALLED_WRITES_TIMEOUT_SEC",
             "DATABASE_MERKLE_TREE_MAX_L1_BATCHES_PER_ITER",
+            "DATABASE_MERKLE_TREE_HASHING_THREAD_COUNT",
+            "DATABASE_MERKLE_TREE_PRUNING_PAST_VERSIONS_TO_KEEP",
+            "DATABASE_MERKLE_TREE_RECOVER_FROM_STORAGE_LOGS",
+            "DATABASE_MERKLE_TREE_STORAGE_LOGS_RECOVERY_L1_BATCH",
+            "DATABASE_MERKLE_TREE_FOLLOWER_VERSION_LAG",
+            "DATABASE_MERKLE_TREE_CONSISTENCY_CHECK_INTERVAL_SEC",
         ]);
 
         let db_config = DBConfig::from_env().unwrap();
         assert_eq!(db_config.state_keeper_db_path, "./db/state_keeper");
+        assert_eq!(db_config.rocksdb_backup_interval(), None);
+        assert_eq!(db_config.state_keeper_cache_warm_up_l1_batches, None);
+        assert_eq!(db_config.state_keeper_cache_compaction_interval(), None);
+        assert_eq!(
+            db_config.state_keeper_cache_compaction_window().unwrap(),
+            None
+        );
+        assert_eq!(db_config.state_keeper_overlay_cache_size(), None);
         assert_eq!(db_config.merkle_tree.path, "./db/lightweight-new");
         assert_eq!(db_config.merkle_tree.mode, MerkleTreeMode::Full);
         assert_eq!(db_config.merkle_tree.multi_get_chunk_size, 500);
@@ -104,6 +191,12 @@ mod tests {
         assert_eq!(db_config.merkle_tree.block_cache_size_mb, 128);
         assert_eq!(db_config.merkle_tree.memtable_capacity_mb, 256);
         assert_eq!(db_config.merkle_tree.stalled_writes_timeout_sec, 30);
+        assert_eq!(db_config.merkle_tree.hashing_thread_count, None);
+        assert_eq!(db_config.merkle_tree.pruning_past_versions_to_keep, None);
+        assert!(!db_config.merkle_tree.recover_from_storage_logs);
+        assert_eq!(db_config.merkle_tree.storage_logs_recovery_l1_batch, None);
+        assert_eq!(db_config.merkle_tree.follower_version_lag, None);
+        assert_eq!(db_config.merkle_tree.consistency_check_interval(), None);
 
         // Check that new env variable for Merkle tree path is supported
         lock.set_env("DATABASE_MERKLE_TREE_PATH=/db/tree/main");
@@ -130,6 +223,8 @@ mod tests {
             DATABASE_URL=postgres://postgres@localhost/zksync_local
             DATABASE_POOL_SIZE=50
             DATABASE_STATEMENT_TIMEOUT_SEC=300
+            DATABASE_MAX_REPLICATION_LAG_SEC=5
+            DATABASE_SLOW_QUERY_THRESHOLD_MS=250
         "#;
         lock.set_env(config);
 
@@ -143,5 +238,13 @@ mod tests {
             postgres_config.statement_timeout(),
             Some(Duration::from_secs(300))
         );
+        assert_eq!(
+            postgres_config.max_replication_lag(),
+            Some(Duration::from_secs(5))
+        );
+        assert_eq!(
+            postgres_config.slow_query_threshold(),
+            Some(Duration::from_millis(250))
+        );
     }
 }
@@ -26,10 +26,12 @@ impl FromEnv for GasAdjusterConfig {
 
 #[cfg(test)]
 mod tests {
-    use zksync_config::configs::eth_sender::{ProofLoadingMode, ProofSendingMode};
+    use zksync_config::configs::eth_sender::{
+        FeeEscalationCurve, ProofLoadingMode, ProofSendingMode, PubdataSendingMode,
+    };
 
     use super::*;
-    use crate::test_utils::{hash, EnvMutex};
+    use crate::test_utils::{addr, hash, EnvMutex};
 
     static MUTEX: EnvMutex = EnvMutex::new();
 
@@ -54,6 +56,18 @@ mod tests {
                 l1_batch_min_age_before_execute_seconds: Some(1000),
                 max_acceptable_priority_fee_in_gwei: 100_000_000_000,
                 proof_loading_mode: ProofLoadingMode::OldProofFromDb,
+                pubdata_sending_mode: PubdataSendingMode::Blobs,
+                blob_base_fee_fallback_threshold_gwei: Some(100),
+                retired_operator_addresses: vec![
+                    addr("0x7b3fa8b6c9bb9a3b9bd0ef3fd4e6cc1edca8a5f7"),
+                    addr("0x0d8ef4a1a2e5b1b7c0bbbe8e1a68a6a6e9b1f4a3"),
+                ],
+                max_fee_per_gas_cap_gwei: Some(500),
+                resubmission_fee_bump_percent: Some(25),
+                resubmission_escalation_curve: FeeEscalationCurve::Linear,
+                resubmission_escalation_rate_percent: 5,
+                commit_dry_run_enabled: true,
+                proof_verification_key_path: Some("/etc/snark_verification_key.json".to_owned()),
             },
             gas_adjuster: GasAdjusterConfig {
                 default_priority_fee_per_gas: 20000000000,
@@ -64,6 +78,11 @@ mod tests {
                 internal_enforced_l1_gas_price: None,
                 poll_period: 15,
                 max_l1_gas_price: Some(100000000),
+                fee_oracle_urls: vec![
+                    "https://oracle-a.example.com/fees".to_owned(),
+                    "https://oracle-b.example.com/fees".to_owned(),
+                ],
+                fee_outlier_threshold_percent: Some(30),
             },
         }
     }
@@ -85,6 +104,8 @@ mod tests {
             ETH_SENDER_GAS_ADJUSTER_INTERNAL_L1_PRICING_MULTIPLIER="0.8"
             ETH_SENDER_GAS_ADJUSTER_POLL_PERIOD="15"
             ETH_SENDER_GAS_ADJUSTER_MAX_L1_GAS_PRICE="100000000"
+            ETH_SENDER_GAS_ADJUSTER_FEE_ORACLE_URLS="https://oracle-a.example.com/fees,https://oracle-b.example.com/fees"
+            ETH_SENDER_GAS_ADJUSTER_FEE_OUTLIER_THRESHOLD_PERCENT="30"
             ETH_SENDER_WAIT_FOR_PROOFS="false"
             ETH_SENDER_SENDER_AGGREGATED_PROOF_SIZES="1,5"
             ETH_SENDER_SENDER_MAX_AGGREGATED_BLOCKS_TO_COMMIT="3"
@@ -98,6 +119,16 @@ mod tests {
             ETH_SENDER_SENDER_L1_BATCH_MIN_AGE_BEFORE_EXECUTE_SECONDS="1000"
             ETH_SENDER_SENDER_MAX_ACCEPTABLE_PRIORITY_FEE_IN_GWEI="100000000000"
             ETH_SENDER_SENDER_PROOF_LOADING_MODE="OldProofFromDb"
+            ETH_SENDER_SENDER_PUBDATA_SENDING_MODE="Blobs"
+            ETH_SENDER_SENDER_BLOB_BASE_FEE_FALLBACK_THRESHOLD_GWEI="100"
+            ETH_SENDER_SENDER_RETIRED_OPERATOR_ADDRESSES="0x7b3fa8b6c9bb9a3b9bd0ef3fd4e6cc1edca8a5f7,0x0d8ef4a1a2e5b1b7c0bbbe8e1a68a6a6e9b1f4a3"
+            ETH_SENDER_SENDER_MAX_FEE_PER_GAS_CAP_GWEI="500"
+            ETH_SENDER_SENDER_RESUBMISSION_FEE_BUMP_PERCENT="25"
+            ETH_SENDER_SENDER_RESUBMISSION_ESCALATION_CURVE="Linear"
+            ETH_SENDER_SENDER_RESUBMISSION_ESCALATION_RATE_PERCENT="5"
+            ETH_SENDER_SENDER_COMMIT_DRY_RUN_ENABLED="true"
+            ETH_SENDER_SENDER_PROOF_VERIFICATION_KEY_PATH="/etc/snark_verification_key.json"
+            ETH_SENDER_SENDER_OPERATOR_ADDITIONAL_PRIVATE_KEYS="0xa5b7c3f4e3b91bfc2fdd3f4f7e6d8b7b8fa0e6d3cb4b4e1fd9c9a2c3d0e3a4b5"
         "#;
         lock.set_env(config);
 
@@ -107,5 +138,12 @@ mod tests {
             actual.sender.private_key().unwrap(),
             hash("27593fea79697e947890ecbecce7901b0008345e5d7259710d0dd5e500d040be")
         );
+        assert_eq!(
+            actual.sender.private_keys(),
+            [
+                hash("27593fea79697e947890ecbecce7901b0008345e5d7259710d0dd5e500d040be"),
+                hash("a5b7c3f4e3b91bfc2fdd3f4f7e6d8b7b8fa0e6d3cb4b4e1fd9c9a2c3d0e3a4b5"),
+            ]
+        );
     }
 }
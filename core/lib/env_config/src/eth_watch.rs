@@ -19,6 +19,8 @@ mod tests {
         ETHWatchConfig {
             confirmations_for_eth_event: Some(0),
             eth_node_poll_interval: 300,
+            reorg_detection_window: Some(500),
+            priority_op_deadline_sec: Some(120),
         }
     }
 
@@ -28,6 +30,8 @@ mod tests {
         let config = r#"
             ETH_WATCH_CONFIRMATIONS_FOR_ETH_EVENT="0"
             ETH_WATCH_ETH_NODE_POLL_INTERVAL="300"
+            ETH_WATCH_REORG_DETECTION_WINDOW="500"
+            ETH_WATCH_PRIORITY_OP_DEADLINE_SEC="120"
         "#;
         lock.set_env(config);
 
@@ -30,6 +30,10 @@ mod tests {
             fri_prover_stats_reporting_interval_ms: 30_000,
             fri_proof_compressor_job_retrying_interval_ms: 30_000,
             fri_proof_compressor_stats_reporting_interval_ms: 30_000,
+            storage_partitions_managing_interval_ms: Some(60_000),
+            storage_partition_size_in_miniblocks: Some(1_000_000),
+            events_retention_l1_batches: None,
+            paymaster_analytics_interval_ms: Some(10_000),
         }
     }
 
@@ -50,6 +54,9 @@ mod tests {
             HOUSE_KEEPER_FRI_PROVER_STATS_REPORTING_INTERVAL_MS="30000"
             HOUSE_KEEPER_FRI_PROOF_COMPRESSOR_STATS_REPORTING_INTERVAL_MS="30000"
             HOUSE_KEEPER_FRI_PROOF_COMPRESSOR_JOB_RETRYING_INTERVAL_MS="30000"
+            HOUSE_KEEPER_STORAGE_PARTITIONS_MANAGING_INTERVAL_MS="60000"
+            HOUSE_KEEPER_STORAGE_PARTITION_SIZE_IN_MINIBLOCKS="1000000"
+            HOUSE_KEEPER_PAYMASTER_ANALYTICS_INTERVAL_MS="10000"
         "#;
         lock.set_env(config);
 
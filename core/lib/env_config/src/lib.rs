@@ -6,6 +6,7 @@ mod api;
 mod chain;
 mod contract_verifier;
 mod contracts;
+mod da_dispatcher;
 mod database;
 mod eth_client;
 mod eth_sender;
@@ -20,6 +21,8 @@ mod house_keeper;
 pub mod object_store;
 mod proof_data_handler;
 mod snapshots_creator;
+mod token_fetcher;
+mod tx_event_stream;
 mod utils;
 mod witness_generator;
 
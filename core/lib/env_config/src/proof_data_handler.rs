@@ -23,6 +23,8 @@ mod tests {
             proof_generation_timeout_in_secs: 18000,
             protocol_version_loading_mode: ProtocolVersionLoadingMode::FromEnvVar,
             fri_protocol_version_id: 2,
+            job_aging_timeout_in_secs: Some(7200),
+            admin_http_port: Some(3321),
         }
     }
 
@@ -33,6 +35,8 @@ mod tests {
             PROOF_DATA_HANDLER_HTTP_PORT="3320"
             PROOF_DATA_HANDLER_PROTOCOL_VERSION_LOADING_MODE="FromEnvVar"
             PROOF_DATA_HANDLER_FRI_PROTOCOL_VERSION_ID="2"
+            PROOF_DATA_HANDLER_JOB_AGING_TIMEOUT_IN_SECS="7200"
+            PROOF_DATA_HANDLER_ADMIN_HTTP_PORT="3321"
         "#;
         let mut lock = MUTEX.lock();
         lock.set_env(config);
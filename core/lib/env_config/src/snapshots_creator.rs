@@ -4,6 +4,48 @@ use crate::{envy_load, FromEnv};
 
 impl FromEnv for SnapshotsCreatorConfig {
     fn from_env() -> anyhow::Result<Self> {
-        envy_load("snapshots_creator", "SNAPSHOTS_CREATOR_")
+        let config: Self = envy_load("snapshots_creator", "SNAPSHOTS_CREATOR_")?;
+        config.validate()?;
+        Ok(config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::EnvMutex;
+
+    static MUTEX: EnvMutex = EnvMutex::new();
+
+    #[test]
+    fn from_env() {
+        let config = r#"
+            SNAPSHOTS_CREATOR_STORAGE_LOGS_CHUNK_SIZE="1000"
+            SNAPSHOTS_CREATOR_CONCURRENT_QUERIES_COUNT="5"
+            SNAPSHOTS_CREATOR_CONCURRENT_UPLOADS_COUNT="2"
+        "#;
+        let mut lock = MUTEX.lock();
+        lock.set_env(config);
+        let actual = SnapshotsCreatorConfig::from_env().unwrap();
+        assert_eq!(actual.storage_logs_chunk_size, 1_000);
+        assert_eq!(actual.concurrent_queries_count, 5);
+        assert_eq!(actual.concurrent_uploads_count, 2);
+        assert_eq!(actual.max_in_flight_chunks, 50);
+
+        lock.set_env(r#"SNAPSHOTS_CREATOR_CONCURRENT_UPLOADS_COUNT="0""#);
+        let err = SnapshotsCreatorConfig::from_env().unwrap_err();
+        assert!(
+            err.to_string().contains("concurrent_uploads_count"),
+            "{err}"
+        );
+
+        lock.set_env(
+            r#"
+            SNAPSHOTS_CREATOR_CONCURRENT_UPLOADS_COUNT="2"
+            SNAPSHOTS_CREATOR_RETENTION_KEEP_LAST="0"
+        "#,
+        );
+        let err = SnapshotsCreatorConfig::from_env().unwrap_err();
+        assert!(err.to_string().contains("retention_keep_last"), "{err}");
     }
 }
@@ -0,0 +1,35 @@
+use zksync_config::configs::TokenFetcherConfig;
+
+use crate::{envy_load, FromEnv};
+
+impl FromEnv for TokenFetcherConfig {
+    fn from_env() -> anyhow::Result<Self> {
+        envy_load("token_fetcher", "TOKEN_FETCHER_")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::EnvMutex;
+
+    static MUTEX: EnvMutex = EnvMutex::new();
+
+    #[test]
+    fn from_env() {
+        let config = r#"
+            TOKEN_FETCHER_POLLING_INTERVAL_MS="30000"
+            TOKEN_FETCHER_PRICE_ORACLE_URL="http://127.0.0.1:8080/"
+        "#;
+        let mut lock = MUTEX.lock();
+        lock.set_env(config);
+        let actual = TokenFetcherConfig::from_env().unwrap();
+        assert_eq!(
+            actual,
+            TokenFetcherConfig {
+                polling_interval_ms: 30_000,
+                price_oracle_url: Some("http://127.0.0.1:8080/".to_owned()),
+            }
+        );
+    }
+}
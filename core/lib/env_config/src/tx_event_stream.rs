@@ -0,0 +1,47 @@
+use zksync_config::configs::TxEventStreamConfig;
+
+use crate::{envy_load, FromEnv};
+
+impl FromEnv for TxEventStreamConfig {
+    fn from_env() -> anyhow::Result<Self> {
+        envy_load("tx_event_stream", "TX_EVENT_STREAM_")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zksync_config::configs::tx_event_stream::TxEventSinkKind;
+
+    use super::*;
+    use crate::test_utils::EnvMutex;
+
+    static MUTEX: EnvMutex = EnvMutex::new();
+
+    #[test]
+    fn from_env() {
+        let config = r#"
+            TX_EVENT_STREAM_SINK="Kafka"
+            TX_EVENT_STREAM_NATS_URL="nats://127.0.0.1:4222"
+            TX_EVENT_STREAM_NATS_SUBJECT="era.tx_events"
+            TX_EVENT_STREAM_KAFKA_REST_PROXY_URL="http://127.0.0.1:8082"
+            TX_EVENT_STREAM_KAFKA_TOPIC="era.tx_events"
+            TX_EVENT_STREAM_POLLING_INTERVAL_MS="500"
+            TX_EVENT_STREAM_MAX_EVENTS_PER_ITERATION="100"
+        "#;
+        let mut lock = MUTEX.lock();
+        lock.set_env(config);
+        let actual = TxEventStreamConfig::from_env().unwrap();
+        assert_eq!(
+            actual,
+            TxEventStreamConfig {
+                sink: TxEventSinkKind::Kafka,
+                nats_url: Some("nats://127.0.0.1:4222".to_owned()),
+                nats_subject: "era.tx_events".to_owned(),
+                kafka_rest_proxy_url: Some("http://127.0.0.1:8082".to_owned()),
+                kafka_topic: "era.tx_events".to_owned(),
+                polling_interval_ms: 500,
+                max_events_per_iteration: 100,
+            }
+        );
+    }
+}
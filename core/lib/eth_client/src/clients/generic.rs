@@ -6,16 +6,16 @@ use zksync_types::{
         contract::Options,
         ethabi,
         types::{
-            Address, Block, BlockId, BlockNumber, Filter, Log, Transaction, TransactionReceipt,
-            H160, H256, U256, U64,
+            Address, Block, BlockId, BlockNumber, Bytes, CallRequest, Filter, Log, Transaction,
+            TransactionReceipt, H160, H256, U256, U64,
         },
     },
     L1ChainId,
 };
 
 use crate::{
-    BoundEthInterface, ContractCall, Error, EthInterface, ExecutedTxStatus, FailureInfo,
-    RawTransactionBytes, SignedCallResult,
+    BlobTxParams, BoundEthInterface, ContractCall, Error, EthInterface, ExecutedTxStatus,
+    FailureInfo, RawTransactionBytes, SignedCallResult,
 };
 
 #[async_trait]
@@ -51,6 +51,10 @@ impl<C: EthInterface + ?Sized> EthInterface for Arc<C> {
             .await
     }
 
+    async fn get_blob_base_fee(&self, component: &'static str) -> Result<U256, Error> {
+        self.as_ref().get_blob_base_fee(component).await
+    }
+
     async fn get_gas_price(&self, component: &'static str) -> Result<U256, Error> {
         self.as_ref().get_gas_price(component).await
     }
@@ -102,6 +106,15 @@ impl<C: EthInterface + ?Sized> EthInterface for Arc<C> {
         self.as_ref().call_contract_function(call).await
     }
 
+    async fn call(
+        &self,
+        request: CallRequest,
+        block: Option<BlockId>,
+        component: &'static str,
+    ) -> Result<Bytes, Error> {
+        self.as_ref().call(request, block, component).await
+    }
+
     async fn logs(&self, filter: Filter, component: &'static str) -> Result<Vec<Log>, Error> {
         self.as_ref().logs(filter, component).await
     }
@@ -156,6 +169,19 @@ impl<C: BoundEthInterface + ?Sized> BoundEthInterface for Arc<C> {
             .await
     }
 
+    async fn sign_prepared_blob_tx_for_addr(
+        &self,
+        data: Vec<u8>,
+        contract_addr: H160,
+        options: Options,
+        blob_params: BlobTxParams,
+        component: &'static str,
+    ) -> Result<SignedCallResult, Error> {
+        self.as_ref()
+            .sign_prepared_blob_tx_for_addr(data, contract_addr, options, blob_params, component)
+            .await
+    }
+
     async fn nonce_at(&self, block: BlockNumber, component: &'static str) -> Result<U256, Error> {
         self.as_ref().nonce_at(block, component).await
     }
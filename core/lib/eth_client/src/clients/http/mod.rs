@@ -22,10 +22,13 @@ enum Method {
     BaseFeeHistory,
     #[metrics(name = "get_pending_block_base_fee_per_gas")]
     PendingBlockBaseFee,
+    #[metrics(name = "get_blob_base_fee")]
+    BlobBaseFee,
     GetTxStatus,
     FailureReason,
     GetTx,
     CallContractFunction,
+    Call,
     TxReceipt,
     EthBalance,
     Logs,
@@ -7,10 +7,10 @@ use zksync_types::web3::{
     ethabi,
     transports::Http,
     types::{
-        Address, Block, BlockId, BlockNumber, Bytes, Filter, Log, Transaction, TransactionId,
-        TransactionReceipt, H256, U256, U64,
+        Address, Block, BlockId, BlockNumber, Bytes, CallRequest, Filter, Log, Transaction,
+        TransactionId, TransactionReceipt, H256, U256, U64,
     },
-    Web3,
+    Transport, Web3,
 };
 
 use crate::{
@@ -77,6 +77,19 @@ impl EthInterface for QueryClient {
         Ok(network_gas_price)
     }
 
+    async fn get_blob_base_fee(&self, component: &'static str) -> Result<U256, Error> {
+        COUNTERS.call[&(Method::BlobBaseFee, component)].inc();
+        let latency = LATENCIES.direct[&Method::BlobBaseFee].start();
+        let response = self
+            .web3
+            .transport()
+            .execute("eth_blobBaseFee", vec![])
+            .await?;
+        let blob_base_fee = web3::helpers::decode(response)?;
+        latency.observe();
+        Ok(blob_base_fee)
+    }
+
     async fn send_raw_tx(&self, tx: RawTransactionBytes) -> Result<H256, Error> {
         let latency = LATENCIES.direct[&Method::SendRawTx].start();
         let tx = self.web3.eth().send_raw_transaction(Bytes(tx.0)).await?;
@@ -269,6 +282,19 @@ impl EthInterface for QueryClient {
         Ok(balance)
     }
 
+    async fn call(
+        &self,
+        request: CallRequest,
+        block: Option<BlockId>,
+        component: &'static str,
+    ) -> Result<Bytes, Error> {
+        COUNTERS.call[&(Method::Call, component)].inc();
+        let latency = LATENCIES.direct[&Method::Call].start();
+        let output = self.web3.eth().call(request, block).await?;
+        latency.observe();
+        Ok(output)
+    }
+
     async fn logs(&self, filter: Filter, component: &'static str) -> Result<Vec<Log>, Error> {
         COUNTERS.call[&(Method::Logs, component)].inc();
         let latency = LATENCIES.direct[&Method::Logs].start();
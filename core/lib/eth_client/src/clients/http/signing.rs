@@ -3,24 +3,26 @@ use std::{fmt, sync::Arc};
 use async_trait::async_trait;
 use zksync_config::{ContractsConfig, ETHClientConfig, ETHSenderConfig};
 use zksync_contracts::zksync_contract;
-use zksync_eth_signer::{raw_ethereum_tx::TransactionParameters, EthereumSigner, PrivateKeySigner};
+use zksync_eth_signer::{
+    raw_ethereum_tx::{signed_tx_hash, TransactionParameters},
+    EthereumSigner, PrivateKeySigner,
+};
 use zksync_types::{
     web3::{
-        self,
         contract::{tokens::Detokenize, Options},
         ethabi,
         transports::Http,
         types::{
-            Address, Block, BlockId, BlockNumber, Filter, Log, Transaction, TransactionReceipt,
-            H160, H256, U256, U64,
+            Address, Block, BlockId, BlockNumber, Bytes, CallRequest, Filter, Log, Transaction,
+            TransactionReceipt, H160, H256, U256, U64,
         },
     },
-    L1ChainId, PackedEthSignature, EIP_1559_TX_TYPE,
+    L1ChainId, PackedEthSignature, EIP_1559_TX_TYPE, EIP_4844_TX_TYPE,
 };
 
 use super::{query::QueryClient, Method, LATENCIES};
 use crate::{
-    types::{Error, ExecutedTxStatus, FailureInfo, SignedCallResult},
+    types::{BlobTxParams, Error, ExecutedTxStatus, FailureInfo, SignedCallResult},
     BoundEthInterface, CallFunctionArgs, ContractCall, EthInterface, RawTransactionBytes,
 };
 
@@ -33,13 +35,28 @@ impl PKSigningClient {
         contracts_config: &ContractsConfig,
         eth_client: &ETHClientConfig,
     ) -> Self {
-        // Gather required data from the config.
-        // It's done explicitly to simplify getting rid of this function later.
-        let main_node_url = &eth_client.web3_url;
         let operator_private_key = eth_sender
             .sender
             .private_key()
             .expect("Operator private key is required for signing client");
+        Self::from_config_with_private_key(
+            eth_sender,
+            contracts_config,
+            eth_client,
+            operator_private_key,
+        )
+    }
+
+    /// Same as [`Self::from_config()`], but signs transactions with the provided operator key.
+    pub fn from_config_with_private_key(
+        eth_sender: &ETHSenderConfig,
+        contracts_config: &ContractsConfig,
+        eth_client: &ETHClientConfig,
+        operator_private_key: H256,
+    ) -> Self {
+        // Gather required data from the config.
+        // It's done explicitly to simplify getting rid of this function later.
+        let main_node_url = &eth_client.web3_url;
         let diamond_proxy_addr = contracts_config.diamond_proxy_addr;
         let default_priority_fee_per_gas = eth_sender.gas_adjuster.default_priority_fee_per_gas;
         let l1_chain_id = eth_client.chain_id;
@@ -114,6 +131,10 @@ impl<S: EthereumSigner> EthInterface for SigningClient<S> {
         self.query_client.block_number(component).await
     }
 
+    async fn get_blob_base_fee(&self, component: &'static str) -> Result<U256, Error> {
+        self.query_client.get_blob_base_fee(component).await
+    }
+
     async fn get_gas_price(&self, component: &'static str) -> Result<U256, Error> {
         self.query_client.get_gas_price(component).await
     }
@@ -181,6 +202,15 @@ impl<S: EthereumSigner> EthInterface for SigningClient<S> {
         self.query_client.eth_balance(address, component).await
     }
 
+    async fn call(
+        &self,
+        request: CallRequest,
+        block: Option<BlockId>,
+        component: &'static str,
+    ) -> Result<Bytes, Error> {
+        self.query_client.call(request, block, component).await
+    }
+
     async fn logs(&self, filter: Filter, component: &'static str) -> Result<Vec<Log>, Error> {
         self.query_client.logs(filter, component).await
     }
@@ -218,6 +248,46 @@ impl<S: EthereumSigner> BoundEthInterface for SigningClient<S> {
         contract_addr: H160,
         options: Options,
         component: &'static str,
+    ) -> Result<SignedCallResult, Error> {
+        self.sign_tx(data, contract_addr, options, None, component)
+            .await
+    }
+
+    async fn sign_prepared_blob_tx_for_addr(
+        &self,
+        data: Vec<u8>,
+        contract_addr: H160,
+        options: Options,
+        blob_params: BlobTxParams,
+        component: &'static str,
+    ) -> Result<SignedCallResult, Error> {
+        self.sign_tx(data, contract_addr, options, Some(blob_params), component)
+            .await
+    }
+
+    async fn allowance_on_account(
+        &self,
+        token_address: Address,
+        address: Address,
+        erc20_abi: ethabi::Contract,
+    ) -> Result<U256, Error> {
+        let latency = LATENCIES.direct[&Method::Allowance].start();
+        let args = CallFunctionArgs::new("allowance", (self.inner.sender_account, address))
+            .for_contract(token_address, erc20_abi);
+        let res = self.call_contract_function(args).await?;
+        latency.observe();
+        Ok(U256::from_tokens(res)?)
+    }
+}
+
+impl<S: EthereumSigner> SigningClient<S> {
+    async fn sign_tx(
+        &self,
+        data: Vec<u8>,
+        contract_addr: H160,
+        options: Options,
+        blob_params: Option<BlobTxParams>,
+        component: &'static str,
     ) -> Result<SignedCallResult, Error> {
         let latency = LATENCIES.direct[&Method::SignPreparedTx].start();
         // Fetch current max priority fee per gas
@@ -258,6 +328,20 @@ impl<S: EthereumSigner> BoundEthInterface for SigningClient<S> {
             U256::from(FALLBACK_GAS_LIMIT)
         });
 
+        let (transaction_type, max_fee_per_blob_gas, blob_versioned_hashes, blob_tx_sidecar) =
+            match blob_params {
+                Some(BlobTxParams {
+                    max_fee_per_blob_gas,
+                    sidecar,
+                }) => (
+                    EIP_4844_TX_TYPE,
+                    Some(max_fee_per_blob_gas),
+                    Some(sidecar.versioned_hashes()),
+                    Some(sidecar),
+                ),
+                None => (EIP_1559_TX_TYPE, None, None, None),
+            };
+
         let tx = TransactionParameters {
             nonce,
             to: Some(contract_addr),
@@ -267,13 +351,16 @@ impl<S: EthereumSigner> BoundEthInterface for SigningClient<S> {
             chain_id: self.inner.chain_id.0,
             max_priority_fee_per_gas,
             gas_price: None,
-            transaction_type: Some(EIP_1559_TX_TYPE.into()),
+            transaction_type: Some(transaction_type.into()),
             access_list: None,
             max_fee_per_gas,
+            max_fee_per_blob_gas,
+            blob_versioned_hashes,
+            blob_tx_sidecar,
         };
 
         let signed_tx = self.inner.eth_signer.sign_transaction(tx).await?;
-        let hash = web3::signing::keccak256(&signed_tx).into();
+        let hash = signed_tx_hash(&signed_tx);
         latency.observe();
         Ok(SignedCallResult {
             raw_tx: RawTransactionBytes(signed_tx),
@@ -284,22 +371,6 @@ impl<S: EthereumSigner> BoundEthInterface for SigningClient<S> {
         })
     }
 
-    async fn allowance_on_account(
-        &self,
-        token_address: Address,
-        address: Address,
-        erc20_abi: ethabi::Contract,
-    ) -> Result<U256, Error> {
-        let latency = LATENCIES.direct[&Method::Allowance].start();
-        let args = CallFunctionArgs::new("allowance", (self.inner.sender_account, address))
-            .for_contract(token_address, erc20_abi);
-        let res = self.call_contract_function(args).await?;
-        latency.observe();
-        Ok(U256::from_tokens(res)?)
-    }
-}
-
-impl<S: EthereumSigner> SigningClient<S> {
     pub fn new(
         transport: Http,
         contract: ethabi::Contract,
@@ -9,14 +9,17 @@ use zksync_types::{
     web3::{
         contract::{tokens::Tokenize, Options},
         ethabi,
-        types::{Block, BlockId, BlockNumber, Filter, Log, Transaction, TransactionReceipt, U64},
+        types::{
+            Block, BlockId, BlockNumber, Bytes, CallRequest, Filter, Log, Transaction,
+            TransactionReceipt, U64,
+        },
         Error as Web3Error,
     },
     Address, L1ChainId, ProtocolVersionId, H160, H256, U256,
 };
 
 use crate::{
-    types::{Error, ExecutedTxStatus, FailureInfo, SignedCallResult},
+    types::{BlobTxParams, Error, ExecutedTxStatus, FailureInfo, SignedCallResult},
     BoundEthInterface, ContractCall, EthInterface, RawTransactionBytes,
 };
 
@@ -73,6 +76,9 @@ struct MockEthereumInner {
     current_nonce: u64,
     pending_nonce: u64,
     nonces: BTreeMap<u64, u64>,
+    /// Revert data returned by `eth_call` requests; if `None`, calls succeed with empty output.
+    call_revert_data: Option<Vec<u8>>,
+    call_requests: Vec<CallRequest>,
 }
 
 impl MockEthereumInner {
@@ -112,16 +118,23 @@ impl MockEthereumInner {
     }
 }
 
+fn revert_data_to_hex(data: &[u8]) -> String {
+    let hex: String = data.iter().map(|byte| format!("{byte:02x}")).collect();
+    format!("0x{hex}")
+}
+
 /// Mock Ethereum client is capable of recording all the incoming requests for the further analysis.
 #[derive(Debug)]
 pub struct MockEthereum {
     max_fee_per_gas: U256,
     max_priority_fee_per_gas: U256,
     base_fee_history: Vec<u64>,
+    blob_base_fee: U256,
     /// If true, the mock will not check the ordering nonces of the transactions.
     /// This is useful for testing the cases when the transactions are executed out of order.
     non_ordering_confirmations: bool,
     multicall_address: Address,
+    sender_account: Address,
     inner: RwLock<MockEthereumInner>,
 }
 
@@ -131,8 +144,10 @@ impl Default for MockEthereum {
             max_fee_per_gas: 100.into(),
             max_priority_fee_per_gas: 10.into(),
             base_fee_history: vec![],
+            blob_base_fee: 1.into(),
             non_ordering_confirmations: false,
             multicall_address: Address::default(),
+            sender_account: Address::repeat_byte(0x11),
             inner: RwLock::default(),
         }
     }
@@ -196,6 +211,16 @@ impl MockEthereum {
         })
     }
 
+    /// Makes all subsequent `eth_call` requests revert with the specified data, or succeed if `None` is provided.
+    pub fn set_call_revert_data(&self, revert_data: Option<Vec<u8>>) {
+        self.inner.write().unwrap().call_revert_data = revert_data;
+    }
+
+    /// Returns all `eth_call` requests made via this client.
+    pub fn call_requests(&self) -> Vec<CallRequest> {
+        self.inner.read().unwrap().call_requests.clone()
+    }
+
     pub fn advance_block_number(&self, val: u64) -> u64 {
         let mut inner = self.inner.write().unwrap();
         inner.block_number += val;
@@ -209,6 +234,20 @@ impl MockEthereum {
         }
     }
 
+    pub fn with_blob_base_fee(self, blob_base_fee: U256) -> Self {
+        Self {
+            blob_base_fee,
+            ..self
+        }
+    }
+
+    pub fn with_sender_account(self, sender_account: Address) -> Self {
+        Self {
+            sender_account,
+            ..self
+        }
+    }
+
     pub fn with_non_ordering_confirmation(self, non_ordering_confirmations: bool) -> Self {
         Self {
             non_ordering_confirmations,
@@ -271,6 +310,10 @@ impl EthInterface for MockEthereum {
         Ok(self.max_fee_per_gas)
     }
 
+    async fn get_blob_base_fee(&self, _: &'static str) -> Result<U256, Error> {
+        Ok(self.blob_base_fee)
+    }
+
     async fn base_fee_history(
         &self,
         from_block: usize,
@@ -322,6 +365,9 @@ impl EthInterface for MockEthereum {
             ]);
             return Ok(vec![token]);
         }
+        if call.inner.name == "verificationKeyHash" {
+            return Ok(vec![Token::FixedBytes(H256::zero().0.to_vec())]);
+        }
         Ok(vec![])
     }
 
@@ -353,6 +399,24 @@ impl EthInterface for MockEthereum {
         unimplemented!("Not needed right now")
     }
 
+    async fn call(
+        &self,
+        request: CallRequest,
+        _block: Option<BlockId>,
+        _component: &'static str,
+    ) -> Result<Bytes, Error> {
+        let mut inner = self.inner.write().unwrap();
+        inner.call_requests.push(request);
+        match &inner.call_revert_data {
+            None => Ok(Bytes::default()),
+            Some(revert_data) => Err(Error::EthereumGateway(Web3Error::Rpc(RpcError {
+                message: "execution reverted".to_string(),
+                code: 3.into(),
+                data: Some(revert_data_to_hex(revert_data).into()),
+            }))),
+        }
+    }
+
     async fn logs(&self, _filter: Filter, _component: &'static str) -> Result<Vec<Log>, Error> {
         unimplemented!("Not needed right now")
     }
@@ -381,7 +445,7 @@ impl BoundEthInterface for MockEthereum {
     }
 
     fn sender_account(&self) -> Address {
-        Address::repeat_byte(0x11)
+        self.sender_account
     }
 
     async fn sign_prepared_tx_for_addr(
@@ -394,6 +458,17 @@ impl BoundEthInterface for MockEthereum {
         self.sign_prepared_tx(data, options)
     }
 
+    async fn sign_prepared_blob_tx_for_addr(
+        &self,
+        data: Vec<u8>,
+        _contract_addr: H160,
+        options: Options,
+        _blob_params: BlobTxParams,
+        _component: &'static str,
+    ) -> Result<SignedCallResult, Error> {
+        self.sign_prepared_tx(data, options)
+    }
+
     async fn allowance_on_account(
         &self,
         _token_address: Address,
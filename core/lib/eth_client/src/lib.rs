@@ -6,16 +6,16 @@ use zksync_types::{
         contract::Options,
         ethabi,
         types::{
-            Address, Block, BlockId, BlockNumber, Filter, Log, Transaction, TransactionReceipt,
-            H160, H256, U256, U64,
+            Address, Block, BlockId, BlockNumber, Bytes, CallRequest, Filter, Log, Transaction,
+            TransactionReceipt, H160, H256, U256, U64,
         },
     },
     L1ChainId,
 };
 
 pub use crate::types::{
-    CallFunctionArgs, ContractCall, Error, ExecutedTxStatus, FailureInfo, RawTransactionBytes,
-    SignedCallResult,
+    BlobTxParams, CallFunctionArgs, ContractCall, Error, ExecutedTxStatus, FailureInfo,
+    RawTransactionBytes, SignedCallResult,
 };
 
 pub mod clients;
@@ -64,6 +64,9 @@ pub trait EthInterface: 'static + Sync + Send + fmt::Debug {
         component: &'static str,
     ) -> Result<U256, Error>;
 
+    /// Returns the blob base fee (EIP-4844) for the next L1 block.
+    async fn get_blob_base_fee(&self, component: &'static str) -> Result<U256, Error>;
+
     /// Returns the current gas price.
     async fn get_gas_price(&self, component: &'static str) -> Result<U256, Error>;
 
@@ -112,6 +115,15 @@ pub trait EthInterface: 'static + Sync + Send + fmt::Debug {
     async fn call_contract_function(&self, call: ContractCall)
         -> Result<Vec<ethabi::Token>, Error>;
 
+    /// Performs a raw `eth_call` with the specified request and returns the output data.
+    /// If the call reverts, the revert data (if any) is contained in the returned RPC error.
+    async fn call(
+        &self,
+        request: CallRequest,
+        block: Option<BlockId>,
+        component: &'static str,
+    ) -> Result<Bytes, Error>;
+
     /// Returns the logs for the specified filter.
     async fn logs(&self, filter: Filter, component: &'static str) -> Result<Vec<Log>, Error>;
 
@@ -172,6 +184,17 @@ pub trait BoundEthInterface: EthInterface {
         component: &'static str,
     ) -> Result<SignedCallResult, Error>;
 
+    /// Same as [`Self::sign_prepared_tx_for_addr()`], but signs an EIP-4844 transaction carrying
+    /// the specified blobs.
+    async fn sign_prepared_blob_tx_for_addr(
+        &self,
+        data: Vec<u8>,
+        contract_addr: H160,
+        options: Options,
+        blob_params: BlobTxParams,
+        component: &'static str,
+    ) -> Result<SignedCallResult, Error>;
+
     /// Returns the nonce of the `Self::sender_account()` at the specified block.
     async fn nonce_at(&self, block: BlockNumber, component: &'static str) -> Result<U256, Error> {
         self.nonce_at_for_account(self.sender_account(), block, component)
@@ -122,6 +122,11 @@ impl EthereumSigner for JsonRpcSigner {
         &self,
         raw_tx: TransactionParameters,
     ) -> Result<Vec<u8>, SignerError> {
+        if raw_tx.blob_tx_sidecar.is_some() {
+            return Err(SignerError::SigningFailed(
+                "blob transactions are not supported by JSON-RPC signer".to_owned(),
+            ));
+        }
         let msg = JsonRpcRequest::sign_transaction(self.address()?, raw_tx);
 
         let ret = self
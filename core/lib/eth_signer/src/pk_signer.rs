@@ -77,6 +77,9 @@ impl EthereumSigner for PrivateKeySigner {
             transaction_type: raw_tx.transaction_type,
             access_list: raw_tx.access_list.unwrap_or_default(),
             max_priority_fee_per_gas,
+            max_fee_per_blob_gas: raw_tx.max_fee_per_blob_gas.unwrap_or_default(),
+            blob_versioned_hashes: raw_tx.blob_versioned_hashes.unwrap_or_default(),
+            blob_tx_sidecar: raw_tx.blob_tx_sidecar,
         };
 
         let signed = tx.sign(&key, raw_tx.chain_id);
@@ -86,10 +89,18 @@ impl EthereumSigner for PrivateKeySigner {
 
 #[cfg(test)]
 mod test {
-    use zksync_types::{H160, H256, U256, U64};
+    use rlp::Rlp;
+    use zksync_types::{
+        eth_sender::{EthTxBlob, EthTxBlobSidecar},
+        web3::signing::keccak256,
+        H160, H256, U256, U64,
+    };
 
     use super::PrivateKeySigner;
-    use crate::{raw_ethereum_tx::TransactionParameters, EthereumSigner};
+    use crate::{
+        raw_ethereum_tx::{signed_tx_hash, TransactionParameters},
+        EthereumSigner,
+    };
 
     #[tokio::test]
     async fn test_generating_signed_raw_transaction() {
@@ -107,6 +118,9 @@ mod test {
             chain_id: 270,
             transaction_type: Some(U64::from(1u32)),
             access_list: None,
+            max_fee_per_blob_gas: None,
+            blob_versioned_hashes: None,
+            blob_tx_sidecar: None,
         };
         let raw_tx = signer
             .sign_transaction(raw_transaction.clone())
@@ -124,4 +138,64 @@ mod test {
         ];
         assert_eq!(raw_tx, precalculated_raw_tx);
     }
+
+    #[tokio::test]
+    async fn signing_blob_transaction() {
+        let signer = PrivateKeySigner::new(H256::from([5; 32]));
+        let sidecar = EthTxBlobSidecar {
+            blobs: vec![EthTxBlob {
+                blob: vec![1; 64],
+                commitment: vec![2; 48],
+                proof: vec![3; 48],
+                versioned_hash: H256::repeat_byte(1),
+            }],
+        };
+        let raw_transaction = TransactionParameters {
+            nonce: U256::from(1u32),
+            to: Some(H160::default()),
+            gas: U256::from(100_000u32),
+            gas_price: None,
+            max_fee_per_gas: U256::from(2u32),
+            max_priority_fee_per_gas: U256::from(1u32),
+            value: Default::default(),
+            data: vec![1, 2, 3],
+            chain_id: 270,
+            transaction_type: Some(U64::from(3u32)),
+            access_list: None,
+            max_fee_per_blob_gas: Some(U256::from(3u32)),
+            blob_versioned_hashes: Some(sidecar.versioned_hashes()),
+            blob_tx_sidecar: Some(sidecar.clone()),
+        };
+        let raw_tx = signer
+            .sign_transaction(raw_transaction.clone())
+            .await
+            .unwrap();
+        assert_eq!(raw_tx[0], 3);
+
+        let rlp = Rlp::new(&raw_tx[1..]);
+        assert_eq!(rlp.item_count().unwrap(), 4);
+        let payload = rlp.at(0).unwrap();
+        assert_eq!(payload.item_count().unwrap(), 14);
+        let blobs: Vec<Vec<u8>> = rlp.list_at(1).unwrap();
+        assert_eq!(blobs, [sidecar.blobs[0].blob.clone()]);
+        let commitments: Vec<Vec<u8>> = rlp.list_at(2).unwrap();
+        assert_eq!(commitments, [sidecar.blobs[0].commitment.clone()]);
+
+        let canonical_tx = [&[3], payload.as_raw()].concat();
+        assert_eq!(signed_tx_hash(&raw_tx), H256(keccak256(&canonical_tx)));
+
+        // The transaction without the sidecar should be identical to the canonical form.
+        let raw_tx_without_sidecar = signer
+            .sign_transaction(TransactionParameters {
+                blob_tx_sidecar: None,
+                ..raw_transaction
+            })
+            .await
+            .unwrap();
+        assert_eq!(raw_tx_without_sidecar, canonical_tx);
+        assert_eq!(
+            signed_tx_hash(&raw_tx_without_sidecar),
+            signed_tx_hash(&raw_tx)
+        );
+    }
 }
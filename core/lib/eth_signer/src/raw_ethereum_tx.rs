@@ -9,19 +9,21 @@
 //! In the case where it will be possible to use only the web3 library without copy-paste, the changes will be small and simple
 //! Link to @Deniallugo's PR to web3: https://github.com/tomusdrw/rust-web3/pull/630
 
-use rlp::RlpStream;
+use rlp::{Rlp, RlpStream};
 use zksync_types::{
+    eth_sender::EthTxBlobSidecar,
     ethabi::Address,
     web3::{
         signing::{self, Signature},
         types::{AccessList, SignedTransaction},
     },
-    U256, U64,
+    H256, U256, U64,
 };
 
 const LEGACY_TX_ID: u64 = 0;
 const ACCESSLISTS_TX_ID: u64 = 1;
 const EIP1559_TX_ID: u64 = 2;
+const EIP4844_TX_ID: u64 = 3;
 
 #[derive(Clone, Debug, PartialEq, Default)]
 pub struct TransactionParameters {
@@ -47,6 +49,12 @@ pub struct TransactionParameters {
     pub max_fee_per_gas: U256,
     /// miner bribe
     pub max_priority_fee_per_gas: U256,
+    /// Max fee per blob gas (EIP-4844 transactions only)
+    pub max_fee_per_blob_gas: Option<U256>,
+    /// Versioned hashes of the blobs carried by the transaction (EIP-4844 transactions only)
+    pub blob_versioned_hashes: Option<Vec<H256>>,
+    /// Blobs carried by the transaction (EIP-4844 transactions only)
+    pub blob_tx_sidecar: Option<EthTxBlobSidecar>,
 }
 
 /// A transaction used for RLP encoding, hashing and signing.
@@ -61,6 +69,9 @@ pub struct Transaction {
     pub transaction_type: Option<U64>,
     pub access_list: AccessList,
     pub max_priority_fee_per_gas: U256,
+    pub max_fee_per_blob_gas: U256,
+    pub blob_versioned_hashes: Vec<H256>,
+    pub blob_tx_sidecar: Option<EthTxBlobSidecar>,
 }
 
 impl Transaction {
@@ -147,6 +158,61 @@ impl Transaction {
         stream
     }
 
+    fn encode_eip4844_payload(&self, chain_id: u64, signature: Option<&Signature>) -> RlpStream {
+        let mut stream = RlpStream::new();
+
+        let list_size = if signature.is_some() { 14 } else { 11 };
+        stream.begin_list(list_size);
+
+        // append `chain_id`. from EIP-2930: `chainId` is defined to be an integer of arbitrary size.
+        stream.append(&chain_id);
+
+        stream.append(&self.nonce);
+        stream.append(&self.max_priority_fee_per_gas);
+        stream.append(&self.gas_price);
+        stream.append(&self.gas);
+        // EIP-4844 transactions cannot create contracts, so `to` is mandatory.
+        stream.append(
+            &self
+                .to
+                .expect("EIP-4844 transactions must have a recipient"),
+        );
+        stream.append(&self.value);
+        stream.append(&self.data);
+
+        self.rlp_append_access_list(&mut stream);
+
+        stream.append(&self.max_fee_per_blob_gas);
+        stream.append_list::<H256, _>(&self.blob_versioned_hashes);
+
+        if let Some(signature) = signature {
+            self.rlp_append_signature(&mut stream, signature);
+        }
+
+        stream
+    }
+
+    /// Wraps a signed EIP-4844 transaction payload into the network form also containing the blobs:
+    /// `rlp([tx_payload_body, blobs, commitments, proofs])`.
+    fn rlp_wrap_with_sidecar(payload: &RlpStream, sidecar: &EthTxBlobSidecar) -> RlpStream {
+        let mut stream = RlpStream::new();
+        stream.begin_list(4);
+        stream.append_raw(payload.as_raw(), 1);
+        stream.begin_list(sidecar.blobs.len());
+        for blob in &sidecar.blobs {
+            stream.append(&blob.blob);
+        }
+        stream.begin_list(sidecar.blobs.len());
+        for blob in &sidecar.blobs {
+            stream.append(&blob.commitment);
+        }
+        stream.begin_list(sidecar.blobs.len());
+        for blob in &sidecar.blobs {
+            stream.append(&blob.proof);
+        }
+        stream
+    }
+
     fn rlp_append_signature(&self, stream: &mut RlpStream, signature: &Signature) {
         stream.append(&signature.v);
         stream.append(&U256::from_big_endian(signature.r.as_bytes()));
@@ -184,6 +250,12 @@ impl Transaction {
                 [&[tx_id], stream.as_raw()].concat()
             }
 
+            Some(EIP4844_TX_ID) => {
+                let tx_id: u8 = EIP4844_TX_ID as u8;
+                let stream = self.encode_eip4844_payload(chain_id, signature);
+                [&[tx_id], stream.as_raw()].concat()
+            }
+
             _ => {
                 panic!("Unsupported transaction type");
             }
@@ -209,8 +281,14 @@ impl Transaction {
                 .expect("hash is non-zero 32-bytes; qed")
         };
 
-        let signed = self.encode(chain_id, Some(&signature));
+        let mut signed = self.encode(chain_id, Some(&signature));
+        // The transaction hash doesn't cover the blob sidecar, so it must be computed before wrapping.
         let transaction_hash = signing::keccak256(signed.as_ref()).into();
+        if let Some(sidecar) = &self.blob_tx_sidecar {
+            let payload = self.encode_eip4844_payload(chain_id, Some(&signature));
+            let stream = Self::rlp_wrap_with_sidecar(&payload, sidecar);
+            signed = [&[EIP4844_TX_ID as u8], stream.as_raw()].concat();
+        }
 
         SignedTransaction {
             message_hash: hash.into(),
@@ -222,3 +300,19 @@ impl Transaction {
         }
     }
 }
+
+/// Computes the hash of a signed raw transaction. Unlike for other transaction types, the hash of
+/// an EIP-4844 transaction in the network form doesn't cover the entire raw transaction; the blob sidecar is excluded.
+pub fn signed_tx_hash(raw_tx: &[u8]) -> H256 {
+    if raw_tx.first() == Some(&(EIP4844_TX_ID as u8)) {
+        let rlp = Rlp::new(&raw_tx[1..]);
+        // In the network form, the first item is the signed transaction payload (a list); otherwise, it's `chain_id`.
+        if let Ok(payload) = rlp.at(0) {
+            if payload.is_list() {
+                let payload = [&[EIP4844_TX_ID as u8], payload.as_raw()].concat();
+                return signing::keccak256(&payload).into();
+            }
+        }
+    }
+    signing::keccak256(raw_tx).into()
+}
@@ -15,6 +15,8 @@ pub enum HealthStatus {
     NotReady,
     /// Component is ready for operations.
     Ready,
+    /// Component is operational, but has detected an issue that requires attention.
+    Affected,
     /// Component is shut down.
     ShutDown,
     /// Component has been abnormally interrupted by a panic.
@@ -24,15 +26,16 @@ pub enum HealthStatus {
 impl HealthStatus {
     /// Checks whether a component is ready according to this status.
     pub fn is_ready(self) -> bool {
-        matches!(self, Self::Ready)
+        matches!(self, Self::Ready | Self::Affected)
     }
 
     fn priority_for_aggregation(self) -> usize {
         match self {
             Self::Ready => 0,
-            Self::ShutDown => 1,
-            Self::NotReady => 2,
-            Self::Panicked => 3,
+            Self::Affected => 1,
+            Self::ShutDown => 2,
+            Self::NotReady => 3,
+            Self::Panicked => 4,
         }
     }
 }
@@ -256,4 +259,22 @@ mod tests {
         let updated = health_updater.update(health);
         assert!(updated);
     }
+    #[tokio::test]
+    async fn aggregating_affected_health_status() {
+        let (ready_check, ready_updater) = ReactiveHealthCheck::new("ready");
+        let (affected_check, affected_updater) = ReactiveHealthCheck::new("affected");
+        ready_updater.update(HealthStatus::Ready.into());
+        affected_updater.update(HealthStatus::Affected.into());
+
+        let checks: Vec<Box<dyn CheckHealth>> =
+            vec![Box::new(ready_check), Box::new(affected_check)];
+        let app_health = AppHealth::new(&checks).await;
+        assert_matches!(app_health.inner.status(), HealthStatus::Affected);
+        assert!(app_health.is_ready());
+
+        ready_updater.update(HealthStatus::NotReady.into());
+        let app_health = AppHealth::new(&checks).await;
+        assert_matches!(app_health.inner.status(), HealthStatus::NotReady);
+        assert!(!app_health.is_ready());
+    }
 }
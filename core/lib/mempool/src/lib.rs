@@ -5,5 +5,5 @@ mod types;
 
 pub use crate::{
     mempool_store::{MempoolInfo, MempoolStore},
-    types::L2TxFilter,
+    types::{FeeOrdering, L2TxFilter, MempoolOrderingPolicy},
 };
@@ -1,10 +1,12 @@
 use std::collections::{hash_map, BTreeSet, HashMap, HashSet};
 
 use zksync_types::{
-    l1::L1Tx, l2::L2Tx, Address, ExecuteTransactionCommon, Nonce, PriorityOpId, Transaction,
+    l1::L1Tx, l2::L2Tx, Address, ExecuteTransactionCommon, Nonce, PriorityOpId, Transaction, U256,
 };
 
-use crate::types::{AccountTransactions, L2TxFilter, MempoolScore};
+use crate::types::{
+    AccountTransactions, L2TxFilter, MempoolOrderingPolicy, MempoolScore, TxScorer,
+};
 
 #[derive(Debug)]
 pub struct MempoolInfo {
@@ -29,6 +31,8 @@ pub struct MempoolStore {
     l2_priority_queue: BTreeSet<MempoolScore>,
     /// Next priority operation
     next_priority_id: PriorityOpId,
+    /// Scorer for L2 transactions in the priority queue
+    scorer: TxScorer,
     stashed_accounts: Vec<Address>,
     /// Number of L2 transactions in the mempool.
     size: u64,
@@ -42,12 +46,40 @@ impl MempoolStore {
             l2_transactions_per_account: HashMap::new(),
             l2_priority_queue: BTreeSet::new(),
             next_priority_id,
+            scorer: TxScorer::default(),
             stashed_accounts: vec![],
             size: 0,
             capacity,
         }
     }
 
+    /// Changes the policy used to order L2 transactions. Transactions already present
+    /// in the mempool are rescored according to the new policy.
+    pub fn set_ordering_policy(&mut self, policy: MempoolOrderingPolicy) {
+        self.scorer.policy = policy;
+        self.rescore();
+    }
+
+    /// Updates the base fee used to compute effective priority fees of L2 transactions. If scores depend
+    /// on the base fee, transactions already present in the mempool are rescored.
+    fn set_base_fee(&mut self, base_fee_per_gas: U256) {
+        if self.scorer.base_fee_per_gas == base_fee_per_gas {
+            return;
+        }
+        self.scorer.base_fee_per_gas = base_fee_per_gas;
+        if self.scorer.depends_on_base_fee() {
+            self.rescore();
+        }
+    }
+
+    fn rescore(&mut self) {
+        self.l2_priority_queue = self
+            .l2_transactions_per_account
+            .values()
+            .filter_map(|txs| txs.head_score(&self.scorer))
+            .collect();
+    }
+
     /// Inserts batch of new transactions to mempool
     /// `initial_nonces` provides current committed nonce information to mempool
     /// variable is used only if account is not present in mempool yet and we have to bootstrap it
@@ -103,12 +135,12 @@ impl MempoolStore {
         let account = transaction.initiator_account();
 
         let metadata = match self.l2_transactions_per_account.entry(account) {
-            hash_map::Entry::Occupied(mut txs) => txs.get_mut().insert(transaction),
+            hash_map::Entry::Occupied(mut txs) => txs.get_mut().insert(transaction, &self.scorer),
             hash_map::Entry::Vacant(entry) => {
                 let account_nonce = initial_nonces.get(&account).cloned().unwrap_or(Nonce(0));
                 entry
                     .insert(AccountTransactions::new(account_nonce))
-                    .insert(transaction)
+                    .insert(transaction, &self.scorer)
             }
         };
         if let Some(score) = metadata.previous_score {
@@ -139,6 +171,7 @@ impl MempoolStore {
             return Some(transaction.into());
         }
 
+        self.set_base_fee(filter.fee_per_gas.into());
         let mut removed = 0;
         // We want to fetch the next transaction that would match the fee requirements.
         let tx_pointer = self
@@ -167,7 +200,7 @@ impl MempoolStore {
             .l2_transactions_per_account
             .get_mut(&tx_pointer.account)
             .expect("mempool: dangling pointer in priority queue")
-            .next();
+            .next(&self.scorer);
 
         if let Some(score) = score {
             self.l2_priority_queue.insert(score);
@@ -194,7 +227,7 @@ impl MempoolStore {
                     .l2_transactions_per_account
                     .get_mut(&tx.initiator_account())
                     .expect("account is not available in mempool")
-                    .reset(tx)
+                    .reset(tx, &self.scorer)
                 {
                     self.l2_priority_queue.remove(&score);
                 }
@@ -205,6 +238,25 @@ impl MempoolStore {
         }
     }
 
+    /// Removes a pending L2 transaction with the specified initiator and nonce, e.g. if it was cancelled
+    /// by the operator. Returns `true` if the transaction was present in the mempool.
+    pub fn remove_l2_transaction(&mut self, account: Address, nonce: Nonce) -> bool {
+        let Some(account_transactions) = self.l2_transactions_per_account.get_mut(&account) else {
+            return false;
+        };
+        let Some((_, score)) = account_transactions.remove(nonce, &self.scorer) else {
+            return false;
+        };
+        if let Some(score) = score {
+            self.l2_priority_queue.remove(&score);
+        }
+        self.size = self
+            .size
+            .checked_sub(1)
+            .expect("mempool size can't be negative");
+        true
+    }
+
     pub fn get_mempool_info(&mut self) -> MempoolInfo {
         MempoolInfo {
             stashed_accounts: std::mem::take(&mut self.stashed_accounts),
@@ -12,7 +12,10 @@ use zksync_types::{
     H256, U256,
 };
 
-use crate::{mempool_store::MempoolStore, types::L2TxFilter};
+use crate::{
+    mempool_store::MempoolStore,
+    types::{FeeOrdering, L2TxFilter, MempoolOrderingPolicy},
+};
 
 #[test]
 fn basic_flow() {
@@ -365,6 +368,136 @@ fn mempool_capacity() {
     );
 }
 
+#[test]
+fn priority_fee_ordering() {
+    let mut mempool = MempoolStore::new(PriorityOpId(0), 100);
+    mempool.set_ordering_policy(MempoolOrderingPolicy {
+        fee_ordering: FeeOrdering::PriorityFee,
+        ..MempoolOrderingPolicy::default()
+    });
+    let account0 = Address::random();
+    let account1 = Address::random();
+    let account2 = Address::random();
+    let transactions = vec![
+        gen_l2_tx_with_priority_fee(account0, Nonce(0), 0, 1),
+        gen_l2_tx_with_priority_fee(account0, Nonce(1), 1, 100),
+        gen_l2_tx_with_priority_fee(account1, Nonce(0), 2, 10),
+        gen_l2_tx_with_priority_fee(account2, Nonce(0), 3, 5),
+    ];
+    mempool.insert(transactions, HashMap::new());
+
+    let filter = L2TxFilter::default();
+    assert_eq!(view(mempool.next_transaction(&filter)), (account1, 0));
+    assert_eq!(view(mempool.next_transaction(&filter)), (account2, 0));
+    // The second transaction of `account0` has the highest fee, but it must not overtake the first one.
+    assert_eq!(view(mempool.next_transaction(&filter)), (account0, 0));
+    assert_eq!(view(mempool.next_transaction(&filter)), (account0, 1));
+}
+
+#[test]
+fn priority_fee_ordering_accounts_for_base_fee() {
+    let mut mempool = MempoolStore::new(PriorityOpId(0), 100);
+    mempool.set_ordering_policy(MempoolOrderingPolicy {
+        fee_ordering: FeeOrdering::PriorityFee,
+        ..MempoolOrderingPolicy::default()
+    });
+    let account0 = Address::random();
+    let account1 = Address::random();
+    let transactions = vec![
+        gen_l2_tx_with_fees(account0, Nonce(0), 0, 40, 30),
+        gen_l2_tx_with_fees(account0, Nonce(1), 1, 40, 30),
+        gen_l2_tx_with_fees(account1, Nonce(0), 2, 100, 25),
+        gen_l2_tx_with_fees(account1, Nonce(1), 3, 100, 25),
+    ];
+    mempool.insert(transactions, HashMap::new());
+
+    // With zero base fee, effective priority fees are 30 and 25 respectively.
+    let filter = L2TxFilter::default();
+    assert_eq!(view(mempool.next_transaction(&filter)), (account0, 0));
+    // With base fee 20, effective priority fees are `min(30, 40 - 20) = 20` and `min(25, 100 - 20) = 25`.
+    let filter = L2TxFilter {
+        fee_per_gas: 20,
+        ..L2TxFilter::default()
+    };
+    assert_eq!(view(mempool.next_transaction(&filter)), (account1, 0));
+    assert_eq!(view(mempool.next_transaction(&filter)), (account1, 1));
+    assert_eq!(view(mempool.next_transaction(&filter)), (account0, 1));
+}
+
+#[test]
+fn boosted_accounts_ordering() {
+    let mut mempool = MempoolStore::new(PriorityOpId(0), 100);
+    let account0 = Address::random();
+    let account1 = Address::random();
+    mempool.set_ordering_policy(MempoolOrderingPolicy {
+        fee_ordering: FeeOrdering::PriorityFee,
+        boosted_accounts: HashSet::from([account1]),
+    });
+    let transactions = vec![
+        gen_l2_tx_with_priority_fee(account0, Nonce(0), 0, 100),
+        gen_l2_tx_with_priority_fee(account1, Nonce(0), 1, 1),
+    ];
+    mempool.insert(transactions, HashMap::new());
+
+    let filter = L2TxFilter::default();
+    assert_eq!(view(mempool.next_transaction(&filter)), (account1, 0));
+    assert_eq!(view(mempool.next_transaction(&filter)), (account0, 0));
+}
+
+#[test]
+fn changing_ordering_policy() {
+    let mut mempool = MempoolStore::new(PriorityOpId(0), 100);
+    let account0 = Address::random();
+    let account1 = Address::random();
+    let transactions = vec![
+        gen_l2_tx_with_priority_fee(account0, Nonce(0), 0, 1),
+        gen_l2_tx_with_priority_fee(account0, Nonce(1), 1, 1),
+        gen_l2_tx_with_priority_fee(account1, Nonce(0), 2, 10),
+    ];
+    mempool.insert(transactions, HashMap::new());
+
+    let filter = L2TxFilter::default();
+    assert_eq!(view(mempool.next_transaction(&filter)), (account0, 0));
+    mempool.set_ordering_policy(MempoolOrderingPolicy {
+        fee_ordering: FeeOrdering::PriorityFee,
+        ..MempoolOrderingPolicy::default()
+    });
+    assert_eq!(view(mempool.next_transaction(&filter)), (account1, 0));
+    assert_eq!(view(mempool.next_transaction(&filter)), (account0, 1));
+    assert_eq!(mempool.next_transaction(&filter), None);
+}
+
+#[test]
+fn removing_l2_transaction() {
+    let mut mempool = MempoolStore::new(PriorityOpId(0), 100);
+    let account0 = Address::random();
+    let account1 = Address::random();
+    let transactions = vec![
+        gen_l2_tx_with_timestamp(account0, Nonce(0), 0),
+        gen_l2_tx_with_timestamp(account0, Nonce(1), 1),
+        gen_l2_tx_with_timestamp(account1, Nonce(0), 2),
+    ];
+    mempool.insert(transactions, HashMap::new());
+    assert_eq!(mempool.stats().l2_transaction_count, 3);
+
+    assert!(!mempool.remove_l2_transaction(account0, Nonce(2)));
+    assert!(!mempool.remove_l2_transaction(Address::random(), Nonce(0)));
+    assert!(mempool.remove_l2_transaction(account0, Nonce(0)));
+    assert_eq!(mempool.stats().l2_transaction_count, 2);
+    assert_eq!(mempool.stats().l2_priority_queue_size, 1);
+
+    // The remaining transaction of `account0` cannot be executed until the nonce gap is filled.
+    let filter = L2TxFilter::default();
+    assert_eq!(view(mempool.next_transaction(&filter)), (account1, 0));
+    assert_eq!(mempool.next_transaction(&filter), None);
+    mempool.insert(
+        vec![gen_l2_tx_with_timestamp(account0, Nonce(0), 3)],
+        HashMap::new(),
+    );
+    assert_eq!(view(mempool.next_transaction(&filter)), (account0, 0));
+    assert_eq!(view(mempool.next_transaction(&filter)), (account0, 1));
+}
+
 fn gen_l2_tx(address: Address, nonce: Nonce) -> Transaction {
     gen_l2_tx_with_timestamp(address, nonce, unix_timestamp_ms())
 }
@@ -384,6 +517,39 @@ fn gen_l2_tx_with_timestamp(address: Address, nonce: Nonce, received_at_ms: u64)
     txn.into()
 }
 
+fn gen_l2_tx_with_priority_fee(
+    address: Address,
+    nonce: Nonce,
+    received_at_ms: u64,
+    max_priority_fee_per_gas: u64,
+) -> Transaction {
+    gen_l2_tx_with_fees(
+        address,
+        nonce,
+        received_at_ms,
+        max_priority_fee_per_gas,
+        max_priority_fee_per_gas,
+    )
+}
+
+fn gen_l2_tx_with_fees(
+    address: Address,
+    nonce: Nonce,
+    received_at_ms: u64,
+    max_fee_per_gas: u64,
+    max_priority_fee_per_gas: u64,
+) -> Transaction {
+    let mut tx = gen_l2_tx_with_timestamp(address, nonce, received_at_ms);
+    match &mut tx.common_data {
+        ExecuteTransactionCommon::L2(data) => {
+            data.fee.max_fee_per_gas = U256::from(max_fee_per_gas);
+            data.fee.max_priority_fee_per_gas = U256::from(max_priority_fee_per_gas);
+        }
+        _ => unreachable!(),
+    }
+    tx
+}
+
 fn gen_l1_tx(priority_id: PriorityOpId) -> Transaction {
     let execute = Execute {
         contract_address: Address::repeat_byte(0x11),
@@ -1,4 +1,7 @@
-use std::{cmp::Ordering, collections::HashMap};
+use std::{
+    cmp::Ordering,
+    collections::{HashMap, HashSet},
+};
 
 use zksync_types::{
     fee::Fee, fee_model::BatchFeeInput, l2::L2Tx, Address, Nonce, Transaction, U256,
@@ -23,18 +26,18 @@ impl AccountTransactions {
     }
 
     /// Inserts new transaction for given account. Returns insertion metadata
-    pub fn insert(&mut self, transaction: L2Tx) -> InsertionMetadata {
+    pub fn insert(&mut self, transaction: L2Tx, scorer: &TxScorer) -> InsertionMetadata {
         let mut metadata = InsertionMetadata::default();
         let nonce = transaction.common_data.nonce;
         // skip insertion if transaction is old
         if nonce < self.nonce {
             return metadata;
         }
-        let new_score = Self::score_for_transaction(&transaction);
+        let new_score = Self::score_for_transaction(&transaction, scorer);
         let previous_score = self
             .transactions
             .insert(nonce, transaction)
-            .map(|tx| Self::score_for_transaction(&tx));
+            .map(|tx| Self::score_for_transaction(&tx, scorer));
         metadata.is_new = previous_score.is_none();
         if nonce == self.nonce {
             metadata.new_score = Some(new_score);
@@ -45,21 +48,17 @@ impl AccountTransactions {
 
     /// Returns next transaction to be included in block and optional score of its successor
     /// Panics if no such transaction exists
-    pub fn next(&mut self) -> (L2Tx, Option<MempoolScore>) {
+    pub fn next(&mut self, scorer: &TxScorer) -> (L2Tx, Option<MempoolScore>) {
         let transaction = self
             .transactions
             .remove(&self.nonce)
             .expect("missing transaction in mempool");
         self.nonce += 1;
-        let score = self
-            .transactions
-            .get(&self.nonce)
-            .map(Self::score_for_transaction);
-        (transaction, score)
+        (transaction, self.head_score(scorer))
     }
 
     /// Handles transaction rejection. Returns optional score of its successor
-    pub fn reset(&mut self, transaction: &Transaction) -> Option<MempoolScore> {
+    pub fn reset(&mut self, transaction: &Transaction, scorer: &TxScorer) -> Option<MempoolScore> {
         // current nonce for the group needs to be reset
         let tx_nonce = transaction
             .nonce()
@@ -67,27 +66,110 @@ impl AccountTransactions {
         self.nonce = self.nonce.min(tx_nonce);
         self.transactions
             .get(&(tx_nonce + 1))
-            .map(Self::score_for_transaction)
+            .map(|tx| Self::score_for_transaction(tx, scorer))
+    }
+
+    /// Returns score of the transaction that can be executed next for this account, if any.
+    pub fn head_score(&self, scorer: &TxScorer) -> Option<MempoolScore> {
+        self.transactions
+            .get(&self.nonce)
+            .map(|tx| Self::score_for_transaction(tx, scorer))
+    }
+
+    /// Removes a transaction with the specified nonce. Returns the removed transaction and its score
+    /// if it was the next transaction to be executed for the account.
+    pub fn remove(
+        &mut self,
+        nonce: Nonce,
+        scorer: &TxScorer,
+    ) -> Option<(L2Tx, Option<MempoolScore>)> {
+        let transaction = self.transactions.remove(&nonce)?;
+        let score =
+            (nonce == self.nonce).then(|| Self::score_for_transaction(&transaction, scorer));
+        Some((transaction, score))
     }
 
     pub fn len(&self) -> usize {
         self.transactions.len()
     }
 
-    fn score_for_transaction(transaction: &L2Tx) -> MempoolScore {
+    fn score_for_transaction(transaction: &L2Tx, scorer: &TxScorer) -> MempoolScore {
         MempoolScore {
             account: transaction.initiator_account(),
+            priority: scorer.priority(transaction),
             received_at_ms: transaction.received_timestamp_ms,
             fee_data: transaction.common_data.fee.clone(),
         }
     }
 }
 
-/// Mempool score of transaction. Used to prioritize L2 transactions in mempool
-/// Currently trivial ordering is used based on received at timestamp
+/// Ordering of L2 transactions by fees.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FeeOrdering {
+    /// Fees are ignored; transactions are ordered by the time they were received.
+    #[default]
+    ReceivedAt,
+    /// Transactions with a higher effective priority fee, i.e. `min(max_priority_fee_per_gas, max_fee_per_gas - base_fee)`,
+    /// are executed first.
+    PriorityFee,
+}
+
+/// Policy used to order L2 transactions of different accounts in the mempool. Transactions
+/// of a single account are always executed in the nonce order, regardless of the policy.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MempoolOrderingPolicy {
+    pub fee_ordering: FeeOrdering,
+    /// Accounts which transactions are executed before transactions of all other accounts.
+    pub boosted_accounts: HashSet<Address>,
+}
+
+/// Assigns priorities to L2 transactions according to [`MempoolOrderingPolicy`] and the current base fee.
+#[derive(Debug, Default)]
+pub(crate) struct TxScorer {
+    pub policy: MempoolOrderingPolicy,
+    pub base_fee_per_gas: U256,
+}
+
+impl TxScorer {
+    /// Checks whether priorities depend on the base fee, i.e., whether transactions need to be rescored
+    /// if the base fee changes.
+    pub fn depends_on_base_fee(&self) -> bool {
+        self.policy.fee_ordering == FeeOrdering::PriorityFee
+    }
+
+    fn priority(&self, transaction: &L2Tx) -> TxPriority {
+        let fee = &transaction.common_data.fee;
+        let priority_fee = match self.policy.fee_ordering {
+            FeeOrdering::ReceivedAt => U256::zero(),
+            // The effective priority fee is capped by the part of the max fee left after paying the base fee.
+            FeeOrdering::PriorityFee => fee
+                .max_priority_fee_per_gas
+                .min(fee.max_fee_per_gas.saturating_sub(self.base_fee_per_gas)),
+        };
+        TxPriority {
+            is_boosted: self
+                .policy
+                .boosted_accounts
+                .contains(&transaction.initiator_account()),
+            priority_fee,
+        }
+    }
+}
+
+/// Priority of a transaction assigned by [`MempoolOrderingPolicy`]. Transactions with
+/// a greater priority are executed first.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TxPriority {
+    is_boosted: bool,
+    priority_fee: U256,
+}
+
+/// Mempool score of transaction. Used to prioritize L2 transactions in mempool.
+/// Transactions are ordered by priority and then by received at timestamp.
 #[derive(Eq, PartialEq, Clone, Debug, Hash)]
 pub struct MempoolScore {
     pub account: Address,
+    pub priority: TxPriority,
     pub received_at_ms: u64,
     // Not used for actual scoring, but state keeper would request
     // transactions that have acceptable fee values (so transactions
@@ -105,6 +187,10 @@ impl MempoolScore {
 
 impl Ord for MempoolScore {
     fn cmp(&self, other: &MempoolScore) -> Ordering {
+        match self.priority.cmp(&other.priority) {
+            Ordering::Equal => {}
+            ordering => return ordering,
+        }
         match self.received_at_ms.cmp(&other.received_at_ms).reverse() {
             Ordering::Equal => {}
             ordering => return ordering,
@@ -159,6 +245,7 @@ mod tests {
 
         let score = MempoolScore {
             account: Address::random(),
+            priority: TxPriority::default(),    // Not important
             received_at_ms: Default::default(), // Not important
             fee_data: Fee {
                 gas_limit: Default::default(), // Not important
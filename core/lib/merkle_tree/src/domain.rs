@@ -15,7 +15,8 @@ use crate::{
         Key, Root, TreeEntry, TreeEntryWithProof, TreeInstruction, TreeLogEntry, ValueHash,
         TREE_DEPTH,
     },
-    BlockOutput, HashTree, MerkleTree, NoVersionError,
+    BlockOutput, ConsistencyError, HashTree, MerkleTree, MerkleTreePruner, MerkleTreePrunerHandle,
+    NoVersionError,
 };
 
 /// Metadata for the current tree state.
@@ -115,6 +116,16 @@ impl ZkSyncTree {
         ZkSyncTreeReader(MerkleTree::new(db))
     }
 
+    /// Creates a pruner for this tree keeping the specified number of past tree versions (i.e., L1 batches).
+    /// The pruner should be run on a separate thread; see [`MerkleTreePruner`] docs for details.
+    pub fn pruner(
+        &self,
+        past_versions_to_keep: u64,
+    ) -> (MerkleTreePruner<RocksDBWrapper>, MerkleTreePrunerHandle) {
+        let db = self.tree.db.inner().clone();
+        MerkleTreePruner::new(db, past_versions_to_keep)
+    }
+
     /// Sets the chunk size for multi-get operations. The requested keys will be split
     /// into chunks of this size and requested in parallel using `rayon`. Setting chunk size
     /// to a large value (e.g., `usize::MAX`) will effectively disable parallelism.
@@ -439,6 +450,11 @@ impl Clone for ZkSyncTreeReader {
 }
 
 impl ZkSyncTreeReader {
+    /// Creates a reader based on the specified database. The database may be a secondary RocksDB instance.
+    pub fn new(db: RocksDBWrapper) -> Self {
+        Self(MerkleTree::new(db))
+    }
+
     /// Returns the current root hash of this tree.
     pub fn root_hash(&self) -> ValueHash {
         self.0.latest_root_hash()
@@ -458,6 +474,26 @@ impl ZkSyncTreeReader {
         self.0.latest_root().leaf_count()
     }
 
+    /// Returns the root hash of the tree after applying the specified L1 batch, or `None`
+    /// if the corresponding tree version is missing.
+    pub fn l1_batch_root_hash(&self, l1_batch_number: L1BatchNumber) -> Option<ValueHash> {
+        self.0.root_hash(u64::from(l1_batch_number.0))
+    }
+
+    /// Verifies tree consistency as of the specified L1 batch. Unlike [`ZkSyncTree::verify_consistency()`],
+    /// this method does not panic if an inconsistency is detected.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the tree version is missing or is inconsistent.
+    pub fn verify_consistency(
+        &self,
+        l1_batch_number: L1BatchNumber,
+    ) -> Result<(), ConsistencyError> {
+        let version = u64::from(l1_batch_number.0);
+        self.0.verify_consistency(version, true)
+    }
+
     /// Reads entries together with Merkle proofs with the specified keys from the tree. The entries are returned
     /// in the same order as requested.
     ///
@@ -472,4 +508,30 @@ impl ZkSyncTreeReader {
         let version = u64::from(l1_batch_number.0);
         self.0.entries_with_proofs(version, keys)
     }
+
+    /// Same as [`Self::entries_with_proofs()`], but treats `version_lag` latest tree versions as missing.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the tree `version` is missing or is among `version_lag` latest versions.
+    pub fn lagging_entries_with_proofs(
+        &self,
+        l1_batch_number: L1BatchNumber,
+        keys: &[Key],
+        version_lag: u64,
+    ) -> Result<Vec<TreeEntryWithProof>, NoVersionError> {
+        let version = u64::from(l1_batch_number.0);
+        let version_count = self
+            .0
+            .latest_version()
+            .map_or(0, |version| version + 1)
+            .saturating_sub(version_lag);
+        if version >= version_count {
+            return Err(NoVersionError {
+                missing_version: version,
+                version_count,
+            });
+        }
+        self.0.entries_with_proofs(version, keys)
+    }
 }
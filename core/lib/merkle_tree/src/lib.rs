@@ -49,6 +49,7 @@
 use zksync_crypto::hasher::blake2::Blake2Hasher;
 
 pub use crate::{
+    consistency::ConsistencyError,
     errors::NoVersionError,
     hasher::{HashTree, TreeRangeDigest},
     pruning::{MerkleTreePruner, MerkleTreePrunerHandle},
@@ -1,6 +1,13 @@
 //! Tree pruning logic.
 
-use std::{fmt, sync::mpsc, time::Duration};
+use std::{
+    fmt,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        mpsc, Arc,
+    },
+    time::Duration,
+};
 
 use crate::{
     metrics::{PruningStats, PRUNING_TIMINGS},
@@ -14,9 +21,19 @@ use crate::{
 #[derive(Debug)]
 pub struct MerkleTreePrunerHandle {
     aborted_sender: mpsc::Sender<()>,
+    retention_limit: Arc<AtomicU64>,
 }
 
 impl MerkleTreePrunerHandle {
+    /// Limits pruning so that all tree versions starting from `version` are retained regardless
+    /// of the number of past versions to keep configured for the pruner. This can be used to protect
+    /// versions that may still be required by other components (e.g., to revert the tree or to serve proofs).
+    ///
+    /// By default, there is no limit.
+    pub fn set_retention_limit(&self, version: u64) {
+        self.retention_limit.store(version, Ordering::Relaxed);
+    }
+
     /// Aborts the pruner that this handle is attached to. If the pruner has already terminated
     /// (e.g., due to a panic), this is a no-op.
     pub fn abort(self) {
@@ -36,10 +53,12 @@ impl MerkleTreePrunerHandle {
 /// by a certain range of tree versions, and removes the corresponding nodes from the tree
 /// (in RocksDB, this uses simple pointwise `delete_cf()` operations). The range of versions
 /// depends on pruning policies; for now, it's "remove versions older than `latest_version - N`",
-/// where `N` is a configurable number set when the pruner [is created](Self::new()).
+/// where `N` is a configurable number set when the pruner [is created](Self::new()). Additionally,
+/// pruning can be limited dynamically via [`MerkleTreePrunerHandle::set_retention_limit()`].
 pub struct MerkleTreePruner<DB> {
     db: DB,
     past_versions_to_keep: u64,
+    retention_limit: Arc<AtomicU64>,
     target_pruned_key_count: usize,
     poll_interval: Duration,
     aborted_receiver: mpsc::Receiver<()>,
@@ -50,6 +69,10 @@ impl<DB> fmt::Debug for MerkleTreePruner<DB> {
         formatter
             .debug_struct("MerkleTreePruner")
             .field("past_versions_to_keep", &self.past_versions_to_keep)
+            .field(
+                "retention_limit",
+                &self.retention_limit.load(Ordering::Relaxed),
+            )
             .field("target_pruned_key_count", &self.target_pruned_key_count)
             .field("poll_interval", &self.poll_interval)
             .finish_non_exhaustive()
@@ -66,10 +89,15 @@ impl<DB: PruneDatabase> MerkleTreePruner<DB> {
     /// is dropped.*
     pub fn new(db: DB, past_versions_to_keep: u64) -> (Self, MerkleTreePrunerHandle) {
         let (aborted_sender, aborted_receiver) = mpsc::channel();
-        let handle = MerkleTreePrunerHandle { aborted_sender };
+        let retention_limit = Arc::new(AtomicU64::new(u64::MAX));
+        let handle = MerkleTreePrunerHandle {
+            aborted_sender,
+            retention_limit: retention_limit.clone(),
+        };
         let this = Self {
             db,
             past_versions_to_keep,
+            retention_limit,
             target_pruned_key_count: 500_000,
             poll_interval: Duration::from_secs(60),
             aborted_receiver,
@@ -97,7 +125,8 @@ impl<DB: PruneDatabase> MerkleTreePruner<DB> {
     fn target_retained_version(&self) -> Option<u64> {
         let manifest = self.db.manifest()?;
         let latest_version = manifest.version_count.checked_sub(1)?;
-        latest_version.checked_sub(self.past_versions_to_keep)
+        let target_version = latest_version.checked_sub(self.past_versions_to_keep)?;
+        Some(target_version.min(self.retention_limit.load(Ordering::Relaxed)))
     }
 
     #[doc(hidden)] // Used in integration tests; logically private
@@ -233,6 +262,29 @@ mod tests {
         }
     }
 
+    #[test]
+    fn pruner_with_retention_limit() {
+        let mut db = create_db();
+        let (mut pruner, handle) = MerkleTreePruner::new(&mut db, 0);
+        handle.set_retention_limit(0);
+        assert!(pruner.run_once().is_none());
+
+        handle.set_retention_limit(2);
+        let stats = pruner.run_once().unwrap();
+        assert!(stats.pruned_key_count > 0);
+        assert_eq!(stats.deleted_stale_key_versions, 1..3);
+        assert_eq!(stats.target_retained_version, 2);
+        assert!(!stats.has_more_work());
+
+        for version in 0..2 {
+            assert!(db.root_mut(version).is_none());
+        }
+        let tree = MerkleTree::new(&mut db);
+        for version in 2..5 {
+            tree.verify_consistency(version, true).unwrap();
+        }
+    }
+
     #[test]
     fn pruner_is_aborted_immediately_when_requested() {
         let (mut pruner, pruner_handle) = MerkleTreePruner::new(PatchSet::default(), 0);
@@ -353,32 +353,114 @@ impl WorkingPatchSet {
         hasher: &dyn HashTree,
     ) -> (ValueHash, PatchSet, HashingStats) {
         let mut stats = HashingStats::default();
-        let (root_hash, patch) = self.finalize_inner(
+        let started_at = Instant::now();
+        let this = self.hash_subtrees(hasher, &stats);
+        let (root_hash, patch) = this.finalize_inner(
             manifest,
             leaf_count,
             operation,
             |nibble_count, level_changes| {
-                let started_at = Instant::now();
-                let tree_level = nibble_count * 4;
-                // `into_par_iter()` below uses `rayon` to parallelize hash computations.
-                let output = level_changes
-                    .into_par_iter()
-                    .map_init(
-                        || hasher.with_stats(&stats),
-                        |hasher, (nibbles, node)| {
-                            let nibbles = Nibbles::from_parts(nibbles, nibble_count);
-                            (nibbles, Some(node.inner.hash(hasher, tree_level)), node)
-                        },
-                    )
-                    .collect::<Vec<_>>();
-                stats.hashing_duration += started_at.elapsed();
-                output
+                let mut hasher = hasher.with_stats(&stats);
+                // Hashes of non-root nodes are already propagated to their parents
+                // by `hash_subtrees()`, so we only need to hash the root node.
+                level_changes
+                    .into_iter()
+                    .map(|(nibbles, node)| {
+                        let nibbles = Nibbles::from_parts(nibbles, nibble_count);
+                        let hash = (nibble_count == 0).then(|| node.inner.hash(&mut hasher, 0));
+                        (nibbles, hash, node)
+                    })
+                    .collect::<Vec<_>>()
             },
         );
+        stats.hashing_duration += started_at.elapsed();
         let root_hash = root_hash.unwrap_or_else(|| hasher.empty_tree_hash());
         (root_hash, patch, stats)
     }
 
+    /// Computes hashes for all changed non-root nodes and writes them to the child refs
+    /// of the parent nodes. The patch set is split into subtrees by the first key nibble,
+    /// and the subtrees are hashed in parallel with the help of `rayon` (i.e., with work stealing),
+    /// so that hashing doesn't need to synchronize on each tree level.
+    fn hash_subtrees(self, hasher: &dyn HashTree, stats: &HashingStats) -> Self {
+        let has_internal_root = matches!(self.get(&Nibbles::EMPTY), Some(Node::Internal(_)));
+        if !has_internal_root || self.changes_by_nibble_count.len() < 2 {
+            return self;
+        }
+
+        // `into_par_iter()` below uses `rayon` to parallelize hash computations.
+        let (parts, subtree_hashes): (Vec<_>, Vec<_>) = self
+            .split()
+            .into_par_iter()
+            .map(|mut part| {
+                let subtree_hashes = part.hash_subtree(hasher, stats);
+                (part, subtree_hashes)
+            })
+            .unzip();
+
+        let mut this = parts
+            .into_iter()
+            .reduce(|mut this, other| {
+                this.merge(other);
+                this
+            })
+            .unwrap();
+        // ^ `unwrap()` is safe: there are always `SUBTREE_COUNT` parts
+        for (nibble, hash) in subtree_hashes.into_iter().flatten() {
+            let child_ref = this.child_ref_mut(&Nibbles::EMPTY, nibble).unwrap();
+            // ^ `unwrap()` is safe by construction: the root node must reference all changed subtrees
+            child_ref.hash = hash;
+        }
+        this
+    }
+
+    /// Hashes changed nodes in a single subtree (i.e., a part produced by [`Self::split()`]),
+    /// propagating hashes to the parents. Returns hashes of the changed root children
+    /// keyed by their nibble. The root node is not modified.
+    fn hash_subtree(
+        &mut self,
+        hasher: &dyn HashTree,
+        stats: &HashingStats,
+    ) -> Vec<(u8, ValueHash)> {
+        let mut subtree_hashes = vec![];
+        for nibble_count in (1..self.changes_by_nibble_count.len()).rev() {
+            let (upper_levels, lower_levels) =
+                self.changes_by_nibble_count.split_at_mut(nibble_count);
+            let level_changes = &lower_levels[0];
+            let tree_level = nibble_count * 4;
+            // Large levels are hashed in parallel as well; `rayon` distributes the work
+            // among the threads not busy with other subtrees.
+            let hashes: Vec<_> = level_changes
+                .par_iter()
+                .map_init(
+                    || hasher.with_stats(stats),
+                    |hasher, (nibbles, node)| {
+                        let nibbles = Nibbles::from_parts(*nibbles, nibble_count);
+                        (nibbles, node.inner.hash(hasher, tree_level))
+                    },
+                )
+                .collect();
+
+            for (nibbles, hash) in hashes {
+                let (parent_nibbles, last_nibble) = nibbles.split_last().unwrap();
+                if nibble_count == 1 {
+                    subtree_hashes.push((last_nibble, hash));
+                    continue;
+                }
+                let parent = upper_levels[nibble_count - 1]
+                    .get_mut(parent_nibbles.bytes())
+                    .unwrap();
+                let Node::Internal(parent) = &mut parent.inner else {
+                    unreachable!("Node parent must be an internal node");
+                };
+                // ^ `unwrap()`s are safe by construction: the parent of any changed node
+                // is an `InternalNode` that must be in the change set as well.
+                parent.child_ref_mut(last_nibble).unwrap().hash = hash;
+            }
+        }
+        subtree_hashes
+    }
+
     fn finalize_inner<I>(
         self,
         manifest: Manifest,
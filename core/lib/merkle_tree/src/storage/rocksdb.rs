@@ -268,6 +268,9 @@ impl PruneDatabase for RocksDBWrapper {
         let mut write_batch = self.db.new_write_batch();
 
         let tree_cf = MerkleTreeColumnFamily::Tree;
+        let pruned_versions = patch.pruned_node_keys.iter().map(|key| key.version);
+        let min_pruned_version = pruned_versions.clone().min();
+        let max_pruned_version = pruned_versions.max();
         for pruned_key in patch.pruned_node_keys {
             write_batch.delete_cf(tree_cf, &pruned_key.to_db_key());
         }
@@ -280,6 +283,14 @@ impl PruneDatabase for RocksDBWrapper {
         self.db
             .write(write_batch)
             .expect("Failed writing a batch to RocksDB");
+
+        // Node keys start with a big-endian version, so pruned nodes occupy a contiguous key range.
+        // Compacting this range reclaims disk space occupied by the pruned nodes.
+        if let (Some(min_version), Some(max_version)) = (min_pruned_version, max_pruned_version) {
+            let start = min_version.to_be_bytes();
+            let end = (max_version + 1).to_be_bytes();
+            self.db.compact_range_cf(tree_cf, &start..&end);
+        }
     }
 }
 
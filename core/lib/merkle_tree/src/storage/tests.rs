@@ -14,6 +14,7 @@ use super::*;
 use crate::{
     hasher::{HasherWithStats, MerklePath},
     types::{NodeKey, TreeInstruction, KEY_SIZE},
+    MerkleTree,
 };
 
 pub(super) const FIRST_KEY: Key = U256([0, 0, 0, 0x_dead_beef_0000_0000]);
@@ -808,3 +809,45 @@ fn recovery_pruning_equivalence(
         test_recovery_pruning_equivalence(kind, chunk_size, recovery_chunk_size, hasher);
     }
 }
+
+#[test_casing(2, [false, true])]
+#[test]
+fn subtree_hashing_is_consistent(with_updates: bool) {
+    const RNG_SEED: u64 = 321;
+
+    let mut rng = StdRng::seed_from_u64(RNG_SEED);
+    let mut database = PatchSet::default();
+    let mut database_with_proofs = PatchSet::default();
+    if with_updates {
+        let kvs: Vec<_> = (0..500)
+            .map(|i| TreeEntry::new(big_endian_key(i), i + 1, H256::repeat_byte(1)))
+            .collect();
+        let storage = Storage::new(&database, &Blake2Hasher, 0, true);
+        let (_, patch) = storage.extend(kvs.clone());
+        database.apply_patch(patch);
+        let storage = Storage::new(&database_with_proofs, &Blake2Hasher, 0, true);
+        let (_, patch) =
+            storage.extend_with_proofs(kvs.into_iter().map(TreeInstruction::Write).collect());
+        database_with_proofs.apply_patch(patch);
+    }
+
+    let version = u64::from(with_updates);
+    let leaf_count = if with_updates { 500 } else { 0 };
+    // Keys are random, so all subtrees should be affected.
+    let kvs: Vec<_> = (0..1_000)
+        .map(|i| {
+            let key = Key::from_big_endian(&rng.gen::<[u8; 32]>());
+            TreeEntry::new(key, leaf_count + i + 1, H256(rng.gen()))
+        })
+        .collect();
+    let storage = Storage::new(&database, &Blake2Hasher, version, true);
+    let (output, patch) = storage.extend(kvs.clone());
+    database.apply_patch(patch);
+    let storage = Storage::new(&database_with_proofs, &Blake2Hasher, version, true);
+    let (output_with_proofs, _) =
+        storage.extend_with_proofs(kvs.into_iter().map(TreeInstruction::Write).collect());
+    assert_eq!(output.root_hash, output_with_proofs.root_hash().unwrap());
+
+    let tree = MerkleTree::new(database);
+    tree.verify_consistency(version, true).unwrap();
+}
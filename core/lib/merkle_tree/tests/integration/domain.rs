@@ -6,7 +6,10 @@ use serde::{Deserialize, Serialize};
 use serde_with::{hex::Hex, serde_as};
 use tempfile::TempDir;
 use zksync_crypto::hasher::blake2::Blake2Hasher;
-use zksync_merkle_tree::{domain::ZkSyncTree, HashTree, TreeEntry, TreeInstruction};
+use zksync_merkle_tree::{
+    domain::{ZkSyncTree, ZkSyncTreeReader},
+    HashTree, MerkleTreeColumnFamily, TreeEntry, TreeInstruction,
+};
 use zksync_storage::RocksDB;
 use zksync_system_constants::ACCOUNT_CODE_STORAGE_ADDRESS;
 use zksync_types::{
@@ -80,6 +83,41 @@ fn basic_workflow() {
     assert_eq!(tree.next_l1_batch_number(), L1BatchNumber(1));
 }
 
+#[test]
+fn reading_from_secondary_instance() {
+    let temp_dir = TempDir::new().expect("failed get temporary directory for RocksDB");
+    let secondary_dir = TempDir::new().expect("failed get temporary directory for RocksDB");
+    let logs = gen_storage_logs();
+
+    let db = RocksDB::new(temp_dir.as_ref()).with_sync_writes();
+    let mut tree = ZkSyncTree::new_lightweight(db.into());
+    tree.process_l1_batch(&logs[..50]);
+    tree.save();
+
+    let secondary_db =
+        RocksDB::<MerkleTreeColumnFamily>::secondary(temp_dir.as_ref(), secondary_dir.as_ref());
+    let reader = ZkSyncTreeReader::new(secondary_db.clone().into());
+    assert_eq!(reader.next_l1_batch_number(), L1BatchNumber(1));
+    assert_eq!(reader.root_hash(), tree.root_hash());
+
+    tree.process_l1_batch(&logs[50..]);
+    tree.save();
+    secondary_db.try_catch_up_with_primary().unwrap();
+    assert_eq!(reader.next_l1_batch_number(), L1BatchNumber(2));
+    assert_eq!(reader.root_hash(), tree.root_hash());
+
+    reader
+        .lagging_entries_with_proofs(L1BatchNumber(0), &[], 1)
+        .unwrap();
+    let err = reader
+        .lagging_entries_with_proofs(L1BatchNumber(1), &[], 1)
+        .unwrap_err();
+    assert!(err.to_string().contains("does not exist"), "{err}");
+    reader
+        .lagging_entries_with_proofs(L1BatchNumber(1), &[], 0)
+        .unwrap();
+}
+
 #[test]
 fn basic_workflow_multiblock() {
     let temp_dir = TempDir::new().expect("failed get temporary directory for RocksDB");
@@ -39,6 +39,9 @@ pub mod _reexports {
 }
 
 pub use self::{
-    objects::{AggregationsKey, CircuitKey, ClosedFormInputKey, FriCircuitKey, StoredObject},
+    objects::{
+        AggregationsKey, CircuitKey, ClosedFormInputKey, FriCircuitKey,
+        PrepareBasicCircuitsJobChunkKey, StoredObject,
+    },
     raw::{Bucket, ObjectStore, ObjectStoreError, ObjectStoreFactory},
 };
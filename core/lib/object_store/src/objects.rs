@@ -8,7 +8,10 @@ use prost::Message;
 use zksync_protobuf::{decode, ProtoFmt};
 use zksync_types::{
     aggregated_operations::L1BatchProofForL1,
-    proofs::{AggregationRound, PrepareBasicCircuitsJob},
+    proofs::{
+        AggregationRound, L1BatchWitnessBundle, PrepareBasicCircuitsJob,
+        PrepareBasicCircuitsJobChunk, PrepareBasicCircuitsJobHeader,
+    },
     snapshots::{
         SnapshotFactoryDependencies, SnapshotStorageLogsChunk, SnapshotStorageLogsStorageKey,
     },
@@ -153,6 +156,49 @@ impl StoredObject for PrepareBasicCircuitsJob {
     serialize_using_bincode!();
 }
 
+impl StoredObject for PrepareBasicCircuitsJobHeader {
+    const BUCKET: Bucket = Bucket::WitnessInput;
+    type Key<'a> = L1BatchNumber;
+
+    fn encode_key(key: Self::Key<'_>) -> String {
+        format!("merkel_tree_paths_{key}_header.bin")
+    }
+
+    serialize_using_bincode!();
+}
+
+/// Storage key for a [`PrepareBasicCircuitsJobChunk`].
+#[derive(Debug, Clone, Copy)]
+pub struct PrepareBasicCircuitsJobChunkKey {
+    pub l1_batch_number: L1BatchNumber,
+    pub chunk_idx: usize,
+}
+
+impl StoredObject for PrepareBasicCircuitsJobChunk {
+    const BUCKET: Bucket = Bucket::WitnessInput;
+    type Key<'a> = PrepareBasicCircuitsJobChunkKey;
+
+    fn encode_key(key: Self::Key<'_>) -> String {
+        format!(
+            "merkel_tree_paths_{}_chunk_{:0>4}.bin",
+            key.l1_batch_number, key.chunk_idx
+        )
+    }
+
+    serialize_using_bincode!();
+}
+
+impl StoredObject for L1BatchWitnessBundle {
+    const BUCKET: Bucket = Bucket::WitnessBundles;
+    type Key<'a> = L1BatchNumber;
+
+    fn encode_key(key: Self::Key<'_>) -> String {
+        format!("witness_bundle_{key}.bin")
+    }
+
+    serialize_using_bincode!();
+}
+
 impl StoredObject for BlockBasicCircuits<Bn256> {
     const BUCKET: Bucket = Bucket::LeafAggregationWitnessJobs;
     type Key<'a> = L1BatchNumber;
@@ -314,13 +360,64 @@ impl dyn ObjectStore + '_ {
     pub fn get_storage_prefix<V: StoredObject>(&self) -> String {
         self.storage_prefix_raw(V::BUCKET)
     }
+
+    /// Stores a [`PrepareBasicCircuitsJob`] in chunks with at most `paths_per_chunk` Merkle paths each,
+    /// so that the job is never serialized as a whole. Chunks are uploaded sequentially; the header is uploaded last,
+    /// so its presence signals that all chunks are available. Returns the key of the header.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if serialization or an upload fails.
+    pub async fn put_chunked_witness_input(
+        &self,
+        l1_batch_number: L1BatchNumber,
+        job: PrepareBasicCircuitsJob,
+        paths_per_chunk: usize,
+    ) -> Result<String, ObjectStoreError> {
+        let (header, chunks) = job.into_chunks(paths_per_chunk);
+        for (chunk_idx, chunk) in chunks.enumerate() {
+            let key = PrepareBasicCircuitsJobChunkKey {
+                l1_batch_number,
+                chunk_idx,
+            };
+            self.put(key, &chunk).await?;
+        }
+        self.put(l1_batch_number, &header).await
+    }
+
+    /// Fetches a [`PrepareBasicCircuitsJob`] stored either in chunks (see [`Self::put_chunked_witness_input()`])
+    /// or as a single object.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the job or any of its chunks doesn't exist, cannot be accessed, or cannot be deserialized.
+    pub async fn get_witness_input(
+        &self,
+        l1_batch_number: L1BatchNumber,
+    ) -> Result<PrepareBasicCircuitsJob, ObjectStoreError> {
+        let header: PrepareBasicCircuitsJobHeader = match self.get(l1_batch_number).await {
+            Ok(header) => header,
+            Err(ObjectStoreError::KeyNotFound(_)) => return self.get(l1_batch_number).await,
+            Err(err) => return Err(err),
+        };
+        let mut chunks = Vec::with_capacity(header.chunk_count);
+        for chunk_idx in 0..header.chunk_count {
+            let key = PrepareBasicCircuitsJobChunkKey {
+                l1_batch_number,
+                chunk_idx,
+            };
+            chunks.push(self.get(key).await?);
+        }
+        Ok(PrepareBasicCircuitsJob::from_chunks(header, chunks))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use zksync_types::{
+        proofs::StorageLogMetadata,
         snapshots::{SnapshotFactoryDependency, SnapshotStorageLog},
-        AccountTreeId, Bytes, StorageKey, H160, H256,
+        AccountTreeId, Bytes, StorageKey, H160, H256, U256,
     };
 
     use super::*;
@@ -400,4 +497,60 @@ mod tests {
         let reconstructed_factory_deps = store.get(key).await.unwrap();
         assert_eq!(factory_deps, reconstructed_factory_deps);
     }
+
+    fn create_witness_input(path_count: u64) -> PrepareBasicCircuitsJob {
+        let mut job = PrepareBasicCircuitsJob::new(10);
+        for i in 0..path_count {
+            job.push_merkle_path(StorageLogMetadata {
+                root_hash: [1; 32],
+                is_write: i % 2 == 0,
+                first_write: false,
+                merkle_paths: vec![[u8::try_from(i).unwrap(); 32]; 256],
+                leaf_hashed_key: U256::from(i),
+                leaf_enumeration_index: i + 1,
+                value_written: [2; 32],
+                value_read: [3; 32],
+            });
+        }
+        job
+    }
+
+    #[tokio::test]
+    async fn witness_input_can_be_stored_in_chunks() {
+        let store = ObjectStoreFactory::mock().create_store().await;
+        let job = create_witness_input(10);
+        let key = store
+            .put_chunked_witness_input(L1BatchNumber(1), job.clone(), 3)
+            .await
+            .unwrap();
+        assert_eq!(key, "merkel_tree_paths_1_header.bin");
+        let header: PrepareBasicCircuitsJobHeader = store.get(L1BatchNumber(1)).await.unwrap();
+        assert_eq!(header.chunk_count, 4);
+        let last_chunk_key = PrepareBasicCircuitsJobChunkKey {
+            l1_batch_number: L1BatchNumber(1),
+            chunk_idx: 3,
+        };
+        let last_chunk: PrepareBasicCircuitsJobChunk = store.get(last_chunk_key).await.unwrap();
+        assert_eq!(last_chunk.merkle_paths.len(), 1);
+
+        let restored_job = store.get_witness_input(L1BatchNumber(1)).await.unwrap();
+        let restored_paths: Vec<_> = restored_job.into_merkle_paths().collect();
+        let expected_paths: Vec<_> = job.into_merkle_paths().collect();
+        assert_eq!(restored_paths, expected_paths);
+    }
+
+    #[tokio::test]
+    async fn witness_input_stored_as_single_object_can_be_read() {
+        let store = ObjectStoreFactory::mock().create_store().await;
+        let job = create_witness_input(5);
+        store.put(L1BatchNumber(2), &job).await.unwrap();
+
+        let restored_job = store.get_witness_input(L1BatchNumber(2)).await.unwrap();
+        assert_eq!(
+            restored_job.into_merkle_paths().collect::<Vec<_>>(),
+            job.into_merkle_paths().collect::<Vec<_>>()
+        );
+        let err = store.get_witness_input(L1BatchNumber(3)).await.unwrap_err();
+        assert!(matches!(err, ObjectStoreError::KeyNotFound(_)), "{err}");
+    }
 }
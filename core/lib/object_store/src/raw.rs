@@ -20,6 +20,9 @@ pub enum Bucket {
     SchedulerWitnessJobsFri,
     ProofsFri,
     StorageSnapshot,
+    DbExports,
+    RocksDBBackups,
+    WitnessBundles,
 }
 
 impl Bucket {
@@ -36,6 +39,9 @@ impl Bucket {
             Self::SchedulerWitnessJobsFri => "scheduler_witness_jobs_fri",
             Self::ProofsFri => "proofs_fri",
             Self::StorageSnapshot => "storage_logs_snapshots",
+            Self::DbExports => "db_exports",
+            Self::RocksDBBackups => "rocksdb_backups",
+            Self::WitnessBundles => "witness_bundles",
         }
     }
 }
@@ -22,6 +22,8 @@ mod in_memory;
 mod postgres;
 mod rocksdb;
 mod shadow_storage;
+mod storage_overlay;
+mod storage_overrides;
 mod storage_view;
 #[cfg(test)]
 mod test_utils;
@@ -30,8 +32,10 @@ mod witness;
 pub use self::{
     in_memory::{InMemoryStorage, IN_MEMORY_STORAGE_DEFAULT_NETWORK_ID},
     postgres::{PostgresStorage, PostgresStorageCaches},
-    rocksdb::RocksdbStorage,
+    rocksdb::{RocksdbMaintenanceHandle, RocksdbStorage},
     shadow_storage::ShadowStorage,
+    storage_overlay::{StorageOverlay, StorageOverlayCache},
+    storage_overrides::StorageWithOverrides,
     storage_view::{StorageView, StorageViewMetrics},
     witness::WitnessStorage,
 };
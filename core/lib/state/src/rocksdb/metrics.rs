@@ -14,6 +14,12 @@ pub(super) struct RocksdbStorageMetrics {
     pub lag: Gauge<u64>,
     /// Estimated number of entries in the secondary storage.
     pub size: Gauge<u64>,
+    /// Latency of warming up the storage after initialization.
+    #[metrics(buckets = Buckets::LATENCIES)]
+    pub warm_up: Histogram<Duration>,
+    /// Latency of compacting a single slice of the storage.
+    #[metrics(buckets = Buckets::LATENCIES)]
+    pub compaction: Histogram<Duration>,
 }
 
 #[vise::register]
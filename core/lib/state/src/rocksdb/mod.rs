@@ -107,6 +107,8 @@ pub struct RocksdbStorage {
 impl RocksdbStorage {
     const BLOCK_NUMBER_KEY: &'static [u8] = b"block_number";
     const ENUM_INDEX_MIGRATION_CURSOR: &'static [u8] = b"enum_index_migration_cursor";
+    /// Number of key slices used in [`RocksdbMaintenanceHandle::compact_slice()`].
+    pub const COMPACTION_SLICE_COUNT: u8 = 16;
 
     fn is_special_key(key: &[u8]) -> bool {
         key == Self::BLOCK_NUMBER_KEY || key == Self::ENUM_INDEX_MIGRATION_CURSOR
@@ -441,13 +443,23 @@ impl RocksdbStorage {
         save_task.await.unwrap();
     }
 
+    /// Returns a handle allowing to warm up or compact this storage on a background thread.
+    pub fn maintenance_handle(&self) -> RocksdbMaintenanceHandle {
+        RocksdbMaintenanceHandle {
+            db: self.db.clone(),
+        }
+    }
+
     /// Returns the last processed l1 batch number + 1
     /// # Panics
     /// Panics on RocksDB errors.
     pub fn l1_batch_number(&self) -> L1BatchNumber {
+        Self::read_l1_batch_number(&self.db)
+    }
+
+    fn read_l1_batch_number(db: &RocksDB<StateKeeperColumnFamily>) -> L1BatchNumber {
         let cf = StateKeeperColumnFamily::State;
-        let block_number = self
-            .db
+        let block_number = db
             .get_cf(cf, Self::BLOCK_NUMBER_KEY)
             .expect("failed to fetch block number");
         let block_number = block_number.map_or(0, |bytes| deserialize_block_number(&bytes));
@@ -480,6 +492,108 @@ impl RocksdbStorage {
     }
 }
 
+/// Handle to the RocksDB instance backing a [`RocksdbStorage`] allowing to warm up, compact or checkpoint the instance
+/// concurrently with using the storage. The instance is closed only after the storage and all its handles
+/// are dropped.
+#[derive(Debug, Clone)]
+pub struct RocksdbMaintenanceHandle {
+    db: RocksDB<StateKeeperColumnFamily>,
+}
+
+impl RocksdbMaintenanceHandle {
+    /// Warms up the storage by reading state values for keys touched (written to or protectively read)
+    /// in up to `l1_batch_count` latest L1 batches processed by the storage. This loads hot keys into RocksDB
+    /// and OS caches, which reduces latency of transaction execution after a restart. Values are read
+    /// on a blocking thread.
+    ///
+    /// Returns the number of keys read.
+    pub async fn warm_up_from_postgres(
+        &self,
+        conn: &mut StorageProcessor<'_>,
+        l1_batch_count: u32,
+    ) -> usize {
+        let latency = METRICS.warm_up.start();
+        let next_l1_batch_number = RocksdbStorage::read_l1_batch_number(&self.db).0;
+        let first_l1_batch_number = next_l1_batch_number.saturating_sub(l1_batch_count);
+
+        let mut key_count = 0;
+        for l1_batch_number in first_l1_batch_number..next_l1_batch_number {
+            let l1_batch_number = L1BatchNumber(l1_batch_number);
+            let touched_slots = conn
+                .storage_logs_dal()
+                .get_touched_slots_for_l1_batch(l1_batch_number)
+                .await;
+            let protective_reads = conn
+                .storage_logs_dedup_dal()
+                .get_protective_reads_for_l1_batch(l1_batch_number)
+                .await;
+            let keys = touched_slots.keys().chain(&protective_reads);
+            let serialized_keys: Vec<_> = keys
+                .map(|key| RocksdbStorage::serialize_state_key(key).to_vec())
+                .collect();
+
+            let db = self.db.clone();
+            key_count += tokio::task::spawn_blocking(move || {
+                db.multi_get_cf(StateKeeperColumnFamily::State, serialized_keys.into_iter())
+                    .len()
+            })
+            .await
+            .unwrap();
+        }
+
+        let latency = latency.observe();
+        tracing::info!(
+            "Warmed up secondary storage with {key_count} keys touched in L1 batches \
+             #{first_l1_batch_number}..#{next_l1_batch_number} in {latency:?}"
+        );
+        key_count
+    }
+
+    /// Compacts the specified slice of keys in all column families. The key space is split into
+    /// [`RocksdbStorage::COMPACTION_SLICE_COUNT`] slices by the first key byte; since most keys are hashes,
+    /// slices have roughly equal sizes. Compacting the storage slice by slice allows to bound the time spent
+    /// on a single compaction step.
+    ///
+    /// This method is blocking.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `slice >= RocksdbStorage::COMPACTION_SLICE_COUNT`.
+    pub fn compact_slice(&self, slice: u8) {
+        const SLICE_COUNT: u8 = RocksdbStorage::COMPACTION_SLICE_COUNT;
+
+        assert!(slice < SLICE_COUNT);
+        let slice_size = (256 / u16::from(SLICE_COUNT)) as u8;
+        let start = vec![slice * slice_size];
+        let end = if slice == SLICE_COUNT - 1 {
+            // The upper bound for the last slice must be greater than any key.
+            vec![0xff; 33]
+        } else {
+            vec![(slice + 1) * slice_size]
+        };
+
+        let latency = METRICS.compaction.start();
+        for &cf in StateKeeperColumnFamily::ALL {
+            self.db
+                .compact_range_cf(cf, start.as_slice()..end.as_slice());
+        }
+        let latency = latency.observe();
+        tracing::debug!("Compacted slice #{slice} of secondary storage in {latency:?}");
+    }
+
+    /// Creates a checkpoint of the RocksDB instance in the specified directory, e.g. to back it up.
+    /// Changes not yet saved to RocksDB are not included into the checkpoint.
+    ///
+    /// This method is blocking.
+    ///
+    /// # Errors
+    ///
+    /// Propagates RocksDB errors.
+    pub fn create_checkpoint(&self, path: &Path) -> Result<(), zksync_storage::rocksdb::Error> {
+        self.db.create_checkpoint(path)
+    }
+}
+
 impl ReadStorage for RocksdbStorage {
     fn read_value(&mut self, key: &StorageKey) -> StorageValue {
         self.read_value_inner(key).unwrap_or_else(H256::zero)
@@ -570,6 +684,35 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn warming_up_and_compacting_rocksdb_storage() {
+        let pool = ConnectionPool::test_pool().await;
+        let mut conn = pool.access_storage().await.unwrap();
+        prepare_postgres(&mut conn).await;
+        let storage_logs = gen_storage_logs(20..40);
+        create_miniblock(&mut conn, MiniblockNumber(1), storage_logs.clone()).await;
+        create_l1_batch(&mut conn, L1BatchNumber(1), &storage_logs).await;
+
+        let dir = TempDir::new().expect("cannot create temporary dir for state keeper");
+        let mut storage = RocksdbStorage::new(dir.path());
+        storage.update_from_postgres(&mut conn).await;
+
+        let handle = storage.maintenance_handle();
+        let key_count = handle.warm_up_from_postgres(&mut conn, 1).await;
+        assert_eq!(key_count, storage_logs.len());
+        // Requesting more L1 batches than there are should be fine. The genesis batch touches the same number of keys.
+        let key_count = handle.warm_up_from_postgres(&mut conn, 10).await;
+        assert_eq!(key_count, 2 * storage_logs.len());
+
+        for slice in 0..RocksdbStorage::COMPACTION_SLICE_COUNT {
+            handle.compact_slice(slice);
+        }
+        assert_eq!(storage.l1_batch_number(), L1BatchNumber(2));
+        for log in &storage_logs {
+            assert_eq!(storage.read_value(&log.key), log.value);
+        }
+    }
+
     async fn insert_factory_deps(
         conn: &mut StorageProcessor<'_>,
         miniblock_number: MiniblockNumber,
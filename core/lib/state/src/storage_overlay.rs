@@ -0,0 +1,389 @@
+//! Copy-on-write storage overlay with an optional cache shared among consecutive executions.
+
+use std::{
+    collections::HashMap,
+    mem,
+    sync::{Arc, RwLock},
+};
+
+use vise::{Counter, EncodeLabelSet, EncodeLabelValue, Family, Metrics};
+use zksync_types::{MiniblockNumber, StorageKey, StorageValue, H256};
+
+use crate::{
+    cache::{Cache, CacheValue},
+    ReadStorage,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EncodeLabelValue, EncodeLabelSet)]
+#[metrics(label = "source", rename_all = "snake_case")]
+enum ReadSource {
+    /// Value was written to the overlay itself.
+    Local,
+    /// Value was taken from the shared cache.
+    Cache,
+    /// Value was loaded from the underlying storage.
+    Storage,
+}
+
+#[derive(Debug, Metrics)]
+#[metrics(prefix = "server_state_storage_overlay")]
+struct StorageOverlayMetrics {
+    /// Number of storage reads served by the overlay, grouped by the source of the value.
+    reads: Family<ReadSource, Counter>,
+    /// Number of times the shared cache was reset because an overlay was created for a state snapshot
+    /// the cache is not valid for (e.g., after a reorg or revert).
+    snapshot_resets: Counter,
+}
+
+#[vise::register]
+static METRICS: vise::Global<StorageOverlayMetrics> = vise::Global::new();
+
+impl CacheValue<StorageKey> for H256 {
+    #[allow(clippy::cast_possible_truncation)] // doesn't happen in practice
+    fn cache_weight(&self) -> u32 {
+        const WEIGHT: usize = mem::size_of::<H256>() + mem::size_of::<StorageKey>();
+        // ^ Since values are small in size, we want to account for key sizes as well
+
+        WEIGHT as u32
+    }
+}
+
+/// State snapshot a [`StorageOverlayCache`] is valid for, i.e., the state after a certain miniblock.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct StateSnapshot {
+    miniblock_number: MiniblockNumber,
+    /// Hash of the miniblock. May be unknown if the cache was advanced by the executor itself
+    /// (the hash is only computed when the miniblock is sealed); in this case, the hash is adopted
+    /// from the next overlay created for the same miniblock.
+    miniblock_hash: Option<H256>,
+}
+
+#[derive(Debug)]
+struct StorageOverlayCacheInner {
+    snapshot: RwLock<Option<StateSnapshot>>,
+    values: Cache<StorageKey, StorageValue>,
+}
+
+/// Cache of storage values shared among [`StorageOverlay`]s created on top of the same state snapshot.
+///
+/// The snapshot is identified by both the miniblock number and hash, so that the cache cannot serve values
+/// from a reverted chain after a reorg. Creating an overlay for a different snapshot resets the cache.
+/// A single executor processing L1 batches one after another can keep the cache warm across batches
+/// via [`Self::advance()`], which evicts values modified by the executed batch.
+///
+/// The cache is intended for executors that do not have a values cache of their own (e.g., the state keeper
+/// reading from RocksDB); [`PostgresStorage`](crate::PostgresStorage) should use the latest values cache instead.
+#[derive(Debug, Clone)]
+pub struct StorageOverlayCache {
+    inner: Arc<StorageOverlayCacheInner>,
+}
+
+impl StorageOverlayCache {
+    /// Creates a cache with the specified capacity measured in bytes. If the capacity is zero,
+    /// the cache is disabled.
+    pub fn new(capacity: u64) -> Self {
+        Self {
+            inner: Arc::new(StorageOverlayCacheInner {
+                snapshot: RwLock::new(None),
+                values: Cache::new("storage_overlay_cache", capacity),
+            }),
+        }
+    }
+
+    /// Makes the cache valid for the specified snapshot, resetting it if necessary.
+    fn use_for_snapshot(&self, snapshot: StateSnapshot) {
+        let mut current_snapshot = self
+            .inner
+            .snapshot
+            .write()
+            .expect("snapshot lock is poisoned");
+        match *current_snapshot {
+            Some(current) if current == snapshot => { /* The cache is valid */ }
+            Some(StateSnapshot {
+                miniblock_number,
+                miniblock_hash: None,
+            }) if miniblock_number == snapshot.miniblock_number => {
+                *current_snapshot = Some(snapshot);
+            }
+            _ => {
+                self.inner.values.clear();
+                *current_snapshot = Some(snapshot);
+                METRICS.snapshot_resets.inc();
+            }
+        }
+    }
+
+    /// Advances the cache to the state after `miniblock_number`, which is obtained by applying `modified_keys`
+    /// to the snapshot the cache is currently valid for. The hash of the new snapshot is adopted
+    /// from the next overlay created for `miniblock_number`.
+    pub fn advance<'a>(
+        &self,
+        miniblock_number: MiniblockNumber,
+        modified_keys: impl IntoIterator<Item = &'a StorageKey>,
+    ) {
+        let mut current_snapshot = self
+            .inner
+            .snapshot
+            .write()
+            .expect("snapshot lock is poisoned");
+        if current_snapshot.is_none() {
+            return;
+        }
+        for key in modified_keys {
+            self.inner.values.remove(key);
+        }
+        *current_snapshot = Some(StateSnapshot {
+            miniblock_number,
+            miniblock_hash: None,
+        });
+    }
+
+    fn get(&self, snapshot: StateSnapshot, key: &StorageKey) -> Option<StorageValue> {
+        let current_snapshot = self
+            .inner
+            .snapshot
+            .read()
+            .expect("snapshot lock is poisoned");
+        if *current_snapshot != Some(snapshot) {
+            return None;
+        }
+        self.inner.values.get(key)
+    }
+
+    fn insert(&self, snapshot: StateSnapshot, key: StorageKey, value: StorageValue) {
+        // Holding the read lock ensures that the cache cannot be reset or advanced concurrently.
+        let current_snapshot = self
+            .inner
+            .snapshot
+            .read()
+            .expect("snapshot lock is poisoned");
+        if *current_snapshot == Some(snapshot) {
+            self.inner.values.insert(key, value);
+        }
+    }
+}
+
+/// Copy-on-write [`ReadStorage`] overlay.
+///
+/// Writes performed via [`Self::set_value()`] are kept in the overlay and are never propagated
+/// to the underlying storage or the shared cache. Reads are served from the local writes, then from
+/// the shared [`StorageOverlayCache`] (if any), and only then from the underlying storage.
+#[derive(Debug)]
+pub struct StorageOverlay<S> {
+    storage_handle: S,
+    cache: Option<(StorageOverlayCache, StateSnapshot)>,
+    local_writes: HashMap<StorageKey, StorageValue>,
+}
+
+impl<S: ReadStorage> StorageOverlay<S> {
+    /// Creates an overlay on top of `storage_handle` without a shared cache.
+    pub fn new(storage_handle: S) -> Self {
+        Self {
+            storage_handle,
+            cache: None,
+            local_writes: HashMap::new(),
+        }
+    }
+
+    /// Enables the shared `cache`. The underlying storage must represent the state after the miniblock
+    /// with the specified number and hash.
+    #[must_use]
+    pub fn with_cache(
+        mut self,
+        cache: StorageOverlayCache,
+        miniblock_number: MiniblockNumber,
+        miniblock_hash: H256,
+    ) -> Self {
+        let snapshot = StateSnapshot {
+            miniblock_number,
+            miniblock_hash: Some(miniblock_hash),
+        };
+        cache.use_for_snapshot(snapshot);
+        self.cache = Some((cache, snapshot));
+        self
+    }
+
+    /// Writes a value into the overlay. The write is visible only via this overlay.
+    pub fn set_value(&mut self, key: StorageKey, value: StorageValue) {
+        self.local_writes.insert(key, value);
+    }
+}
+
+impl<S: ReadStorage> ReadStorage for StorageOverlay<S> {
+    fn read_value(&mut self, key: &StorageKey) -> StorageValue {
+        if let Some(value) = self.local_writes.get(key) {
+            METRICS.reads[&ReadSource::Local].inc();
+            return *value;
+        }
+        if let Some(value) = self
+            .cache
+            .as_ref()
+            .and_then(|(cache, snapshot)| cache.get(*snapshot, key))
+        {
+            METRICS.reads[&ReadSource::Cache].inc();
+            return value;
+        }
+
+        METRICS.reads[&ReadSource::Storage].inc();
+        let value = self.storage_handle.read_value(key);
+        if let Some((cache, snapshot)) = &self.cache {
+            cache.insert(*snapshot, *key, value);
+        }
+        value
+    }
+
+    fn is_write_initial(&mut self, key: &StorageKey) -> bool {
+        self.storage_handle.is_write_initial(key)
+    }
+
+    fn load_factory_dep(&mut self, hash: H256) -> Option<Vec<u8>> {
+        self.storage_handle.load_factory_dep(hash)
+    }
+
+    fn get_enumeration_index(&mut self, key: &StorageKey) -> Option<u64> {
+        self.storage_handle.get_enumeration_index(key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zksync_types::{AccountTreeId, Address};
+
+    use super::*;
+    use crate::InMemoryStorage;
+
+    /// Wrapper counting reads from the underlying storage.
+    #[derive(Debug)]
+    struct CountingStorage {
+        inner: InMemoryStorage,
+        reads: usize,
+    }
+
+    impl ReadStorage for CountingStorage {
+        fn read_value(&mut self, key: &StorageKey) -> StorageValue {
+            self.reads += 1;
+            self.inner.read_value(key)
+        }
+
+        fn is_write_initial(&mut self, key: &StorageKey) -> bool {
+            self.inner.is_write_initial(key)
+        }
+
+        fn load_factory_dep(&mut self, hash: H256) -> Option<Vec<u8>> {
+            self.inner.load_factory_dep(hash)
+        }
+
+        fn get_enumeration_index(&mut self, key: &StorageKey) -> Option<u64> {
+            self.inner.get_enumeration_index(key)
+        }
+    }
+
+    fn counting_storage(key: StorageKey, value: StorageValue) -> CountingStorage {
+        let mut inner = InMemoryStorage::default();
+        inner.set_value(key, value);
+        CountingStorage { inner, reads: 0 }
+    }
+
+    fn test_key(byte: u8) -> StorageKey {
+        StorageKey::new(AccountTreeId::new(Address::repeat_byte(byte)), H256::zero())
+    }
+
+    #[test]
+    fn overlay_writes_are_not_shared() {
+        let key = test_key(1);
+        let cache = StorageOverlayCache::new(1_024);
+
+        let mut overlay = StorageOverlay::new(counting_storage(key, H256::repeat_byte(1)))
+            .with_cache(cache.clone(), MiniblockNumber(1), H256::repeat_byte(0xff));
+        overlay.set_value(key, H256::repeat_byte(2));
+        assert_eq!(overlay.read_value(&key), H256::repeat_byte(2));
+        assert_eq!(overlay.storage_handle.reads, 0);
+
+        let mut other_overlay = StorageOverlay::new(counting_storage(key, H256::repeat_byte(1)))
+            .with_cache(cache, MiniblockNumber(1), H256::repeat_byte(0xff));
+        assert_eq!(other_overlay.read_value(&key), H256::repeat_byte(1));
+        assert_eq!(other_overlay.storage_handle.reads, 1);
+    }
+
+    #[test]
+    fn overlays_share_cache_within_snapshot() {
+        let key = test_key(1);
+        let hash = H256::repeat_byte(0xff);
+        let cache = StorageOverlayCache::new(1_024);
+
+        let mut overlay = StorageOverlay::new(counting_storage(key, H256::repeat_byte(1)))
+            .with_cache(cache.clone(), MiniblockNumber(1), hash);
+        assert_eq!(overlay.read_value(&key), H256::repeat_byte(1));
+        assert_eq!(overlay.storage_handle.reads, 1);
+
+        let mut overlay = StorageOverlay::new(counting_storage(key, H256::repeat_byte(1)))
+            .with_cache(cache.clone(), MiniblockNumber(1), hash);
+        assert_eq!(overlay.read_value(&key), H256::repeat_byte(1));
+        assert_eq!(overlay.storage_handle.reads, 0);
+
+        // Another snapshot resets the cache.
+        let mut overlay = StorageOverlay::new(counting_storage(key, H256::repeat_byte(3)))
+            .with_cache(cache.clone(), MiniblockNumber(2), hash);
+        assert_eq!(overlay.read_value(&key), H256::repeat_byte(3));
+        assert_eq!(overlay.storage_handle.reads, 1);
+    }
+
+    #[test]
+    fn cache_is_reset_after_reorg() {
+        let key = test_key(1);
+        let cache = StorageOverlayCache::new(1_024);
+
+        let mut overlay = StorageOverlay::new(counting_storage(key, H256::repeat_byte(1)))
+            .with_cache(cache.clone(), MiniblockNumber(1), H256::repeat_byte(0xff));
+        assert_eq!(overlay.read_value(&key), H256::repeat_byte(1));
+
+        // Same miniblock number, but a different hash (e.g., the miniblock was reverted and re-sealed).
+        let mut overlay = StorageOverlay::new(counting_storage(key, H256::repeat_byte(2)))
+            .with_cache(cache.clone(), MiniblockNumber(1), H256::repeat_byte(0xfe));
+        assert_eq!(overlay.read_value(&key), H256::repeat_byte(2));
+        assert_eq!(overlay.storage_handle.reads, 1);
+
+        // The outdated overlay must not use the cache anymore.
+        assert_eq!(
+            cache.get(
+                StateSnapshot {
+                    miniblock_number: MiniblockNumber(1),
+                    miniblock_hash: Some(H256::repeat_byte(0xff)),
+                },
+                &key
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn advancing_cache() {
+        let (key, other_key) = (test_key(1), test_key(2));
+        let cache = StorageOverlayCache::new(1_024);
+
+        let mut storage = counting_storage(key, H256::repeat_byte(1));
+        storage.inner.set_value(other_key, H256::repeat_byte(2));
+        let mut overlay = StorageOverlay::new(storage).with_cache(
+            cache.clone(),
+            MiniblockNumber(1),
+            H256::zero(),
+        );
+        overlay.read_value(&key);
+        overlay.read_value(&other_key);
+        assert_eq!(overlay.storage_handle.reads, 2);
+
+        cache.advance(MiniblockNumber(3), [&key]);
+
+        let mut storage = counting_storage(key, H256::repeat_byte(3));
+        storage.inner.set_value(other_key, H256::repeat_byte(2));
+        let mut overlay = StorageOverlay::new(storage).with_cache(
+            cache.clone(),
+            MiniblockNumber(3),
+            H256::repeat_byte(3),
+        );
+        // The modified key must be loaded from the storage, and the other key must be cached.
+        assert_eq!(overlay.read_value(&key), H256::repeat_byte(3));
+        assert_eq!(overlay.read_value(&other_key), H256::repeat_byte(2));
+        assert_eq!(overlay.storage_handle.reads, 1);
+    }
+}
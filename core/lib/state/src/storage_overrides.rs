@@ -0,0 +1,120 @@
+use std::collections::{HashMap, HashSet};
+
+use zksync_types::{AccountTreeId, StorageKey, StorageValue, H256};
+
+use crate::ReadStorage;
+
+/// [`ReadStorage`] implementation allowing to override values of storage slots and factory dependencies
+/// on top of the underlying storage. Used to execute calls against modified state (e.g., `eth_call`
+/// with a state override set).
+#[derive(Debug)]
+pub struct StorageWithOverrides<S> {
+    storage_handle: S,
+    overridden_slots: HashMap<StorageKey, StorageValue>,
+    overridden_factory_deps: HashMap<H256, Vec<u8>>,
+    /// Accounts with fully replaced storage; non-overridden slots of these accounts are read as zero.
+    empty_accounts: HashSet<AccountTreeId>,
+}
+
+impl<S: ReadStorage> StorageWithOverrides<S> {
+    /// Creates a new storage without any overrides.
+    pub fn new(storage_handle: S) -> Self {
+        Self {
+            storage_handle,
+            overridden_slots: HashMap::new(),
+            overridden_factory_deps: HashMap::new(),
+            empty_accounts: HashSet::new(),
+        }
+    }
+
+    /// Overrides the value of the specified storage slot.
+    pub fn set_value(&mut self, key: StorageKey, value: StorageValue) {
+        self.overridden_slots.insert(key, value);
+    }
+
+    /// Overrides a factory dependency with the specified hash.
+    pub fn store_factory_dep(&mut self, hash: H256, bytecode: Vec<u8>) {
+        self.overridden_factory_deps.insert(hash, bytecode);
+    }
+
+    /// Clears storage of the specified account, so that all its slots that are not overridden
+    /// via [`Self::set_value()`] are read as zero.
+    pub fn clear_account_storage(&mut self, account: AccountTreeId) {
+        self.overridden_slots
+            .retain(|key, _| *key.account() != account);
+        self.empty_accounts.insert(account);
+    }
+}
+
+impl<S: ReadStorage> ReadStorage for StorageWithOverrides<S> {
+    fn read_value(&mut self, key: &StorageKey) -> StorageValue {
+        if let Some(value) = self.overridden_slots.get(key) {
+            return *value;
+        }
+        if self.empty_accounts.contains(key.account()) {
+            return StorageValue::zero();
+        }
+        self.storage_handle.read_value(key)
+    }
+
+    fn is_write_initial(&mut self, key: &StorageKey) -> bool {
+        self.storage_handle.is_write_initial(key)
+    }
+
+    fn load_factory_dep(&mut self, hash: H256) -> Option<Vec<u8>> {
+        if let Some(bytecode) = self.overridden_factory_deps.get(&hash) {
+            return Some(bytecode.clone());
+        }
+        self.storage_handle.load_factory_dep(hash)
+    }
+
+    fn get_enumeration_index(&mut self, key: &StorageKey) -> Option<u64> {
+        self.storage_handle.get_enumeration_index(key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zksync_types::Address;
+
+    use super::*;
+    use crate::InMemoryStorage;
+
+    #[test]
+    fn overriding_storage_values() {
+        let account = AccountTreeId::new(Address::repeat_byte(1));
+        let other_account = AccountTreeId::new(Address::repeat_byte(2));
+        let key = StorageKey::new(account, H256::zero());
+        let other_key = StorageKey::new(account, H256::repeat_byte(1));
+        let other_account_key = StorageKey::new(other_account, H256::zero());
+
+        let mut in_memory_storage = InMemoryStorage::default();
+        for storage_key in [key, other_key, other_account_key] {
+            in_memory_storage.set_value(storage_key, H256::repeat_byte(0xff));
+        }
+        let mut storage = StorageWithOverrides::new(in_memory_storage);
+        storage.set_value(key, H256::repeat_byte(3));
+        assert_eq!(storage.read_value(&key), H256::repeat_byte(3));
+        assert_eq!(storage.read_value(&other_key), H256::repeat_byte(0xff));
+
+        storage.clear_account_storage(account);
+        assert_eq!(storage.read_value(&key), H256::zero());
+        assert_eq!(storage.read_value(&other_key), H256::zero());
+        assert_eq!(
+            storage.read_value(&other_account_key),
+            H256::repeat_byte(0xff)
+        );
+
+        storage.set_value(other_key, H256::repeat_byte(4));
+        assert_eq!(storage.read_value(&other_key), H256::repeat_byte(4));
+    }
+
+    #[test]
+    fn overriding_factory_deps() {
+        let mut storage = StorageWithOverrides::new(InMemoryStorage::default());
+        let hash = H256::repeat_byte(1);
+        assert_eq!(storage.load_factory_dep(hash), None);
+        storage.store_factory_dep(hash, vec![1; 32]);
+        assert_eq!(storage.load_factory_dep(hash), Some(vec![1; 32]));
+    }
+}
@@ -379,6 +379,51 @@ impl<CF: NamedColumnFamily> RocksDB<CF> {
         }
     }
 
+    /// Opens a read-only secondary instance of the database located at `primary_path`. `secondary_path`
+    /// is used by RocksDB to store the secondary instance info logs.
+    ///
+    /// The secondary instance does not automatically observe changes made by the primary instance;
+    /// it needs to be synchronized using [`Self::try_catch_up_with_primary()`].
+    pub fn secondary(primary_path: &Path, secondary_path: &Path) -> Self {
+        let mut db_options = Self::rocksdb_options(None, None);
+        // Required for secondary instances; see RocksDB docs.
+        db_options.set_max_open_files(-1);
+        let cf_names: HashSet<_> = CF::ALL.iter().map(|cf| cf.name()).collect();
+        let db = DB::open_cf_as_secondary(&db_options, primary_path, secondary_path, &cf_names)
+            .expect("failed to init secondary rocksdb");
+        let inner = Arc::new(RocksDBInner {
+            db,
+            db_name: CF::DB_NAME,
+            cf_names,
+            _registry_entry: RegistryEntry::new(),
+            _caches: RocksDBCaches::new(None),
+        });
+
+        tracing::info!(
+            "Initialized secondary RocksDB `{}` for primary at `{}`",
+            CF::DB_NAME,
+            primary_path.display()
+        );
+        Self {
+            inner,
+            sync_writes: false,
+            stalled_writes_retries: StalledWritesRetries::new(Duration::ZERO),
+            _cf: PhantomData,
+        }
+    }
+
+    /// Creates a consistent checkpoint of the database in the specified directory. The directory must not exist;
+    /// if it's on the same filesystem as the database, SST files are hard-linked rather than copied.
+    pub fn create_checkpoint(&self, path: &Path) -> Result<(), rocksdb::Error> {
+        let checkpoint = rocksdb::checkpoint::Checkpoint::new(&self.inner.db)?;
+        checkpoint.create_checkpoint(path)
+    }
+
+    /// Synchronizes a secondary instance (see [`Self::secondary()`]) with the primary one.
+    pub fn try_catch_up_with_primary(&self) -> Result<(), rocksdb::Error> {
+        self.inner.db.try_catch_up_with_primary()
+    }
+
     /// Switches on sync writes in [`Self::write()`] and [`Self::put()`]. This has a performance
     /// penalty and is mostly useful for tests.
     #[must_use]
@@ -509,6 +554,16 @@ impl<CF: NamedColumnFamily> RocksDB<CF> {
             .unwrap_or_else(|| panic!("Column family `{}` doesn't exist", cf.name()))
     }
 
+    /// Compacts the specified key range in the column family `cf`. This can be used to reclaim disk space
+    /// after removing many keys. This method is blocking and should be wrapped in `spawn_blocking(_)`
+    /// if run in the async context.
+    pub fn compact_range_cf(&self, cf: CF, keys: ops::Range<&[u8]>) {
+        let cf = self.column_family(cf);
+        self.inner
+            .db
+            .compact_range_cf(cf, Some(keys.start), Some(keys.end));
+    }
+
     pub fn get_cf(&self, cf: CF, key: &[u8]) -> Result<Option<Vec<u8>>, rocksdb::Error> {
         let cf = self.column_family(cf);
         self.inner.db.get_cf(cf, key)
@@ -593,6 +648,14 @@ impl Drop for RegistryEntry {
     }
 }
 
+/// Checks that a RocksDB instance at the specified path exists and can be opened, i.e., that its metadata
+/// is not corrupted. Does not verify checksums of all data in the database.
+pub fn check_integrity(path: &Path) -> Result<(), rocksdb::Error> {
+    let options = Options::default();
+    let cf_names = DB::list_cf(&options, path)?;
+    DB::open_cf_for_read_only(&options, path, cf_names, false).map(drop)
+}
+
 #[cfg(test)]
 mod tests {
     use tempfile::TempDir;
@@ -726,4 +789,25 @@ mod tests {
             .unwrap();
         assert_eq!(value, b"value2");
     }
+
+    #[test]
+    fn creating_checkpoint() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("db");
+        let checkpoint_path = temp_dir.path().join("checkpoint");
+        assert!(check_integrity(&db_path).is_err());
+
+        let db = RocksDB::<NewColumnFamilies>::new(&db_path).with_sync_writes();
+        let mut batch = db.new_write_batch();
+        batch.put_cf(NewColumnFamilies::Other, b"test", b"value");
+        db.write(batch).unwrap();
+        db.create_checkpoint(&checkpoint_path).unwrap();
+        drop(db);
+
+        check_integrity(&db_path).unwrap();
+        check_integrity(&checkpoint_path).unwrap();
+        let db = RocksDB::<NewColumnFamilies>::new(&checkpoint_path);
+        let value = db.get_cf(NewColumnFamilies::Other, b"test").unwrap();
+        assert_eq!(value.unwrap(), b"value");
+    }
 }
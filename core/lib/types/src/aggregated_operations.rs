@@ -9,7 +9,10 @@ use zkevm_test_harness::{
 };
 use zksync_basic_types::{ethabi::Token, L1BatchNumber};
 
-use crate::{commitment::L1BatchWithMetadata, ProtocolVersionId, U256};
+use crate::{
+    commitment::{L1BatchCommitmentMode, L1BatchWithMetadata},
+    ProtocolVersionId, U256,
+};
 
 fn l1_batch_range_from_batches(
     batches: &[L1BatchWithMetadata],
@@ -32,12 +35,37 @@ pub struct L1BatchCommitOperation {
 }
 
 impl L1BatchCommitOperation {
-    pub fn get_eth_tx_args(&self) -> Vec<Token> {
+    /// Returns arguments for the `commitBatches` call with pubdata encoded according to the specified commitment mode.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any of the committed L1 batches doesn't support the commitment mode.
+    pub fn get_eth_tx_args(&self, mode: L1BatchCommitmentMode) -> anyhow::Result<Vec<Token>> {
+        let stored_batch_info = self.last_committed_l1_batch.l1_header_data();
+        let l1_batches_to_commit = self
+            .l1_batches
+            .iter()
+            .map(|l1_batch| l1_batch.l1_commit_data_for_mode(mode))
+            .collect::<anyhow::Result<_>>()?;
+
+        Ok(vec![stored_batch_info, Token::Array(l1_batches_to_commit)])
+    }
+
+    /// Same as [`Self::get_eth_tx_args()`] in the rollup mode, but with pubdata for each L1 batch replaced
+    /// with commitments to the blobs carrying it.
+    pub fn get_eth_tx_args_with_pubdata_commitments(
+        &self,
+        pubdata_commitments: Vec<Vec<u8>>,
+    ) -> Vec<Token> {
+        assert_eq!(pubdata_commitments.len(), self.l1_batches.len());
         let stored_batch_info = self.last_committed_l1_batch.l1_header_data();
         let l1_batches_to_commit = self
             .l1_batches
             .iter()
-            .map(L1BatchWithMetadata::l1_commit_data)
+            .zip(pubdata_commitments)
+            .map(|(l1_batch, commitments)| {
+                l1_batch.l1_commit_data_with_pubdata_commitments(commitments)
+            })
             .collect();
 
         vec![stored_batch_info, Token::Array(l1_batches_to_commit)]
@@ -1,3 +1,5 @@
+use std::collections::{BTreeMap, HashMap};
+
 use chrono::{DateTime, Utc};
 use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 use strum::Display;
@@ -546,6 +548,75 @@ pub struct TransactionDetails {
     pub eth_execute_tx_hash: Option<H256>,
 }
 
+/// Processing stage of an L1 -> L2 priority operation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PriorityOpStage {
+    /// Operation is received from L1, but isn't included into a miniblock yet.
+    Pending,
+    /// Operation is included into a miniblock.
+    Included,
+    /// Operation is included into a sealed L1 batch.
+    Sealed,
+}
+
+/// Status of an L1 -> L2 priority operation.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PriorityOpStatus {
+    pub serial_id: u64,
+    pub l1_tx_hash: H256,
+    pub l2_tx_hash: H256,
+    pub l1_block_number: Option<u64>,
+    pub stage: PriorityOpStage,
+    /// Time when the operation was received from L1.
+    pub received_at: DateTime<Utc>,
+    pub miniblock_number: Option<MiniblockNumber>,
+    /// Timestamp of the miniblock the operation is included into.
+    pub included_at: Option<DateTime<Utc>>,
+    pub l1_batch_number: Option<L1BatchNumber>,
+}
+
+/// Usage of a single paymaster in an L1 batch.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PaymasterBatchUsage {
+    pub paymaster: Address,
+    /// Number of transactions sponsored by the paymaster.
+    pub tx_count: u64,
+    /// Number of distinct accounts that initiated sponsored transactions.
+    pub unique_users: u64,
+    /// Gas used by sponsored transactions (i.e., gas limit minus refunded gas).
+    pub sponsored_gas: U256,
+    /// Fee paid for sponsored transactions, in wei.
+    pub sponsored_fee: U256,
+}
+
+/// Paymaster usage aggregated for an L1 batch.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct L1BatchPaymasterStats {
+    pub l1_batch_number: L1BatchNumber,
+    /// Total number of transactions sponsored by paymasters in the batch.
+    pub sponsored_tx_count: u64,
+    /// Number of distinct accounts that initiated sponsored transactions in the batch.
+    pub unique_users: u64,
+    pub sponsored_gas: U256,
+    pub sponsored_fee: U256,
+    /// Per-paymaster usage, ordered by the number of sponsored transactions (descending).
+    pub paymasters: Vec<PaymasterBatchUsage>,
+}
+
+/// Usage of a paymaster aggregated over a range of L1 batches.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PaymasterUsage {
+    pub paymaster: Address,
+    pub tx_count: u64,
+    pub sponsored_gas: U256,
+    pub sponsored_fee: U256,
+}
+
 #[derive(Debug, Clone)]
 pub struct GetLogsFilter {
     pub from_block: MiniblockNumber,
@@ -554,6 +625,40 @@ pub struct GetLogsFilter {
     pub topics: Vec<(u32, Vec<H256>)>,
 }
 
+/// Page of logs returned by `zks_getLogsPaginated`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogsPage {
+    pub logs: Vec<Log>,
+    /// Opaque cursor to pass to the next call to get the following page; `null` if there are no more logs
+    /// in the requested block range.
+    pub next_cursor: Option<String>,
+}
+
+/// Aggregated node-level stats returned by `zks_nodeStats`. Stats are collected periodically by the node,
+/// so they may lag behind the actual node state by several seconds.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NodeStats {
+    /// UNIX timestamp (in seconds) at which the stats were collected.
+    pub collected_at: u64,
+    pub sealed_miniblock: MiniblockNumber,
+    pub sealed_l1_batch: Option<L1BatchNumber>,
+    /// Last L1 batch processed by the Merkle tree.
+    pub last_l1_batch_with_metadata: Option<L1BatchNumber>,
+    /// Number of sealed L1 batches not yet processed by the Merkle tree.
+    pub tree_lag: u32,
+    pub last_committed_l1_batch: Option<L1BatchNumber>,
+    pub last_proven_l1_batch: Option<L1BatchNumber>,
+    pub last_executed_l1_batch: Option<L1BatchNumber>,
+    /// Number of transactions not included into miniblocks yet.
+    pub mempool_size: u64,
+    /// Number of sent, but not yet confirmed Ethereum transactions. Always 0 for external nodes.
+    pub eth_sender_inflight_txs: u64,
+    /// Lag of the node behind the main node in miniblocks. `null` for the main node or if the lag is unknown.
+    pub sync_lag: Option<u32>,
+}
+
 /// Result of debugging block
 /// For some reasons geth returns result as {result: DebugCall}
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -622,10 +727,11 @@ pub struct ProtocolVersion {
     pub l2_system_upgrade_tx_hash: Option<H256>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
 #[serde(rename_all = "camelCase")]
 pub enum SupportedTracers {
     CallTracer,
+    PrestateTracer,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
@@ -634,12 +740,118 @@ pub struct CallTracerConfig {
     pub only_top_call: bool,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
 #[serde(rename_all = "camelCase")]
-pub struct TracerConfig {
-    pub tracer: SupportedTracers,
+pub struct PrestateTracerConfig {
+    /// If set, the tracer returns both the state before and after the transaction
+    /// restricted to the modified accounts and storage slots.
     #[serde(default)]
-    pub tracer_config: CallTracerConfig,
+    pub diff_mode: bool,
+}
+
+/// Tracer options accepted by `debug_trace*` methods. Mirrors the Geth format, i.e.
+/// `{ "tracer": "callTracer", "tracerConfig": { "onlyTopCall": true } }`; `tracerConfig`
+/// is interpreted based on the `tracer` value.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(try_from = "RawTracerConfig", into = "RawTracerConfig")]
+pub enum TracerConfig {
+    CallTracer(CallTracerConfig),
+    PrestateTracer(PrestateTracerConfig),
+}
+
+impl Default for TracerConfig {
+    fn default() -> Self {
+        Self::CallTracer(CallTracerConfig::default())
+    }
+}
+
+impl TracerConfig {
+    pub fn tracer(&self) -> SupportedTracers {
+        match self {
+            Self::CallTracer(_) => SupportedTracers::CallTracer,
+            Self::PrestateTracer(_) => SupportedTracers::PrestateTracer,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawTracerConfig {
+    tracer: SupportedTracers,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    tracer_config: Option<serde_json::Value>,
+}
+
+impl TryFrom<RawTracerConfig> for TracerConfig {
+    type Error = serde_json::Error;
+
+    fn try_from(raw: RawTracerConfig) -> Result<Self, Self::Error> {
+        let tracer_config = raw.tracer_config.unwrap_or_else(|| serde_json::json!({}));
+        Ok(match raw.tracer {
+            SupportedTracers::CallTracer => {
+                Self::CallTracer(serde_json::from_value(tracer_config)?)
+            }
+            SupportedTracers::PrestateTracer => {
+                Self::PrestateTracer(serde_json::from_value(tracer_config)?)
+            }
+        })
+    }
+}
+
+impl From<TracerConfig> for RawTracerConfig {
+    fn from(config: TracerConfig) -> Self {
+        let tracer = config.tracer();
+        let tracer_config = match config {
+            TracerConfig::CallTracer(config) => serde_json::to_value(config),
+            TracerConfig::PrestateTracer(config) => serde_json::to_value(config),
+        };
+        Self {
+            tracer,
+            tracer_config: Some(tracer_config.expect("tracer config is always serializable")),
+        }
+    }
+}
+
+/// Account state returned by the prestate tracer.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PrestateAccount {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub balance: Option<U256>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nonce: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<Bytes>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub storage: BTreeMap<H256, H256>,
+}
+
+/// Output of the prestate tracer.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum PrestateTrace {
+    /// Output in the diff mode.
+    Diff {
+        pre: BTreeMap<Address, PrestateAccount>,
+        post: BTreeMap<Address, PrestateAccount>,
+    },
+    /// State of all accounts touched by a transaction before its execution.
+    State(BTreeMap<Address, PrestateAccount>),
+}
+
+/// Output of a `debug_trace*` method; depends on the requested tracer.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(untagged)]
+pub enum DebugTrace {
+    Call(DebugCall),
+    Prestate(PrestateTrace),
+}
+
+/// For some reasons geth returns result as {result: DebugTrace}
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ResultDebugTrace {
+    pub result: DebugTrace,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -687,6 +899,97 @@ pub struct L1BatchDetails {
     pub base: BlockDetailsBase,
 }
 
+/// Commitment to an EIP-4844 blob carrying L1 batch pubdata.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PubdataBlobCommitment {
+    /// Versioned hash of the blob, as referenced by the commit transaction.
+    pub versioned_hash: H256,
+    /// KZG commitment to the blob.
+    pub commitment: Bytes,
+    /// KZG proof for the blob.
+    pub proof: Bytes,
+}
+
+/// Pubdata of an L1 batch, as returned by `zks_getL1BatchPubdata`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct L1BatchPubdata {
+    pub number: L1BatchNumber,
+    /// Pubdata of the batch: L2-to-L1 logs and messages, published bytecodes and compressed state diffs.
+    /// In the validium mode, this data is not published on L1; only its hash is.
+    pub pubdata: Bytes,
+    /// Keccak-256 hash of the pubdata.
+    pub pubdata_hash: H256,
+    /// Hash of the L1 transaction that has committed the batch, if any.
+    pub commit_tx_hash: Option<H256>,
+    /// Commitments to the blobs carrying the pubdata. `None` if the batch is not committed, or if its pubdata
+    /// was not published in blobs (or this is unknown to the node, e.g. on external nodes).
+    pub blobs: Option<Vec<PubdataBlobCommitment>>,
+}
+
+/// L1 transaction moving an L1 batch to a certain stage.
+///
+/// Note that a single L1 transaction may process several L1 batches, so the gas usage is reported
+/// for the entire transaction.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct L1BatchEthTxArtifacts {
+    /// Calldata of the transaction. Not meaningful for transactions not sent by the node itself
+    /// (e.g., on external nodes).
+    pub calldata: Bytes,
+    /// Gas cost of the transaction predicted by the node when creating the transaction.
+    pub predicted_gas_cost: u64,
+    /// Hash of the confirmed transaction, or `None` if the transaction is not confirmed yet.
+    pub tx_hash: Option<H256>,
+    /// Gas used by the confirmed transaction.
+    pub gas_used: Option<u64>,
+    pub base_fee_per_gas: Option<u64>,
+    pub priority_fee_per_gas: Option<u64>,
+    pub confirmed_at: Option<DateTime<Utc>>,
+}
+
+/// L1 transactions for an L1 batch, as returned by `zks_getL1BatchCommitmentArtifacts`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct L1BatchCommitmentArtifacts {
+    pub number: L1BatchNumber,
+    pub commit: Option<L1BatchEthTxArtifacts>,
+    pub prove: Option<L1BatchEthTxArtifacts>,
+    pub execute: Option<L1BatchEthTxArtifacts>,
+}
+
+/// Stage of L1 batch processing on L1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum L1BatchStage {
+    Committed,
+    Proven,
+    Executed,
+}
+
+/// Notification about an L1 batch reaching a certain [stage](L1BatchStage) on L1.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct L1BatchStatusUpdate {
+    pub l1_batch_number: L1BatchNumber,
+    pub stage: L1BatchStage,
+    /// Hash of the L1 transaction that has moved the batch to the stage.
+    pub l1_tx_hash: H256,
+    /// Time when the L1 transaction was confirmed.
+    pub happened_at: DateTime<Utc>,
+}
+
+/// Consensus certificate for a miniblock, i.e., a proof that the miniblock was finalized by the validator set.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConsensusBlockCertificate {
+    pub number: MiniblockNumber,
+    /// Protobuf-encoded commit quorum certificate (`CommitQC` from `zksync_consensus_roles::validator`).
+    /// Contains the miniblock header and the aggregate signature of the validators that have committed to it.
+    pub justification: Bytes,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct StorageProof {
@@ -702,3 +1005,67 @@ pub struct Proof {
     pub address: Address,
     pub storage_proof: Vec<StorageProof>,
 }
+
+/// Override of an account state applied before executing a call, similar to the state override set
+/// supported by `eth_call` in geth.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OverrideAccount {
+    /// Overridden base token balance of the account.
+    pub balance: Option<U256>,
+    /// Overridden transaction nonce of the account.
+    pub nonce: Option<U256>,
+    /// Overridden bytecode of the account. Must be a valid zkEVM bytecode.
+    pub code: Option<Bytes>,
+    /// Storage slots replacing the entire account storage. Mutually exclusive with `state_diff`.
+    pub state: Option<HashMap<H256, H256>>,
+    /// Storage slots patched on top of the existing account storage.
+    pub state_diff: Option<HashMap<H256, H256>>,
+}
+
+/// State overrides for a call, keyed by the account address.
+pub type StateOverride = HashMap<Address, OverrideAccount>;
+
+/// Curve used to escalate the priority fee of resubmitted L1 transactions depending on the time
+/// they have spent in the mempool.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum FeeEscalationCurve {
+    Constant,
+    Linear,
+    Exponential,
+}
+
+/// Current state of the resubmission policy used to send L1 transactions.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResubmissionPolicyDetails {
+    /// Cap on `maxFeePerGas` of L1 transactions in wei, if any.
+    pub max_fee_per_gas_cap: Option<u64>,
+    /// Minimum increase of the priority fee (in percent) when resubmitting a transaction.
+    pub fee_bump_percent: u64,
+    pub escalation_curve: FeeEscalationCurve,
+    /// Escalation rate of the priority fee (in percent per L1 block spent in the mempool).
+    pub escalation_rate_percent: u64,
+    pub max_txs_in_flight: u64,
+    /// L1 transactions that were sent, but are not confirmed yet.
+    pub inflight_txs: Vec<InflightEthTxDetails>,
+}
+
+/// Information about an in-flight L1 transaction.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InflightEthTxDetails {
+    pub id: u32,
+    pub tx_type: String,
+    pub nonce: u32,
+    pub from_addr: Option<Address>,
+    /// Number of sending attempts made so far.
+    pub attempts: usize,
+    /// L1 block at which the transaction was first sent.
+    pub first_sent_at_block: Option<u32>,
+    /// Fees used in the latest sending attempt.
+    pub base_fee_per_gas: Option<u64>,
+    pub priority_fee_per_gas: Option<u64>,
+    pub blob_base_fee_per_gas: Option<u64>,
+}
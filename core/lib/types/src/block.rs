@@ -12,6 +12,7 @@ use crate::{
     priority_op_onchain_data::PriorityOpOnchainData,
     web3::signing::keccak256,
     AccountTreeId, Address, L1BatchNumber, MiniblockNumber, ProtocolVersionId, Transaction,
+    VmEvent,
 };
 
 /// Represents a successfully deployed smart contract.
@@ -264,6 +265,41 @@ pub fn pack_block_info(block_number: u64, block_timestamp: u64) -> U256 {
         + U256::from(block_timestamp)
 }
 
+/// Returns the positions of bits set in an Ethereum-style logs bloom for the specified `input`
+/// (an event emitter address or an event topic). Positions are counted from the least significant bit
+/// of the bloom interpreted as a big-endian 2048-bit integer.
+pub fn logs_bloom_bit_positions(input: &[u8]) -> [usize; 3] {
+    let hash = keccak256(input);
+    [0, 2, 4].map(|i| (usize::from(hash[i]) << 8 | usize::from(hash[i + 1])) & 2047)
+}
+
+/// Adds `input` (an event emitter address or an event topic) to the logs bloom.
+pub fn accrue_logs_bloom(bloom: &mut H2048, input: &[u8]) {
+    for position in logs_bloom_bit_positions(input) {
+        bloom.0[255 - position / 8] |= 1 << (position % 8);
+    }
+}
+
+/// Checks whether the logs bloom may contain `input`. As with all Bloom filters, false positives are possible,
+/// but false negatives are not.
+pub fn logs_bloom_contains(bloom: &H2048, input: &[u8]) -> bool {
+    logs_bloom_bit_positions(input)
+        .into_iter()
+        .all(|position| bloom.0[255 - position / 8] & (1 << (position % 8)) != 0)
+}
+
+/// Builds a logs bloom covering emitter addresses and topics of the specified events.
+pub fn build_logs_bloom<'a>(events: impl IntoIterator<Item = &'a VmEvent>) -> H2048 {
+    let mut bloom = H2048::zero();
+    for event in events {
+        accrue_logs_bloom(&mut bloom, event.address.as_bytes());
+        for topic in &event.indexed_topics {
+            accrue_logs_bloom(&mut bloom, topic.as_bytes());
+        }
+    }
+    bloom
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -316,4 +352,30 @@ mod tests {
         assert_eq!(block_number, unpacked_block_number);
         assert_eq!(block_timestamp, unpacked_block_timestamp);
     }
+
+    #[test]
+    fn building_logs_bloom() {
+        let event = VmEvent {
+            location: (L1BatchNumber(1), 0),
+            address: Address::repeat_byte(1),
+            indexed_topics: vec![H256::repeat_byte(2), H256::repeat_byte(3)],
+            value: vec![],
+        };
+        let bloom = build_logs_bloom([&event]);
+        let set_bits: u32 = bloom.0.iter().map(|byte| byte.count_ones()).sum();
+        assert!(set_bits > 0 && set_bits <= 9, "{set_bits}");
+
+        assert!(logs_bloom_contains(&bloom, event.address.as_bytes()));
+        for topic in &event.indexed_topics {
+            assert!(logs_bloom_contains(&bloom, topic.as_bytes()));
+        }
+        assert!(!logs_bloom_contains(
+            &bloom,
+            Address::repeat_byte(4).as_bytes()
+        ));
+        assert!(!logs_bloom_contains(
+            &H2048::zero(),
+            event.address.as_bytes()
+        ));
+    }
 }
@@ -8,6 +8,7 @@
 
 use std::{collections::HashMap, convert::TryFrom};
 
+use anyhow::Context as _;
 use serde::{Deserialize, Serialize};
 use zksync_mini_merkle_tree::MiniMerkleTree;
 use zksync_system_constants::{
@@ -26,6 +27,7 @@ use crate::{
     },
     H256, KNOWN_CODES_STORAGE_ADDRESS, U256,
 };
+pub use zksync_basic_types::commitment::L1BatchCommitmentMode;
 
 /// Type that can be serialized for commitment.
 pub trait SerializeCommitment {
@@ -92,6 +94,10 @@ pub struct L1BatchWithMetadata {
     pub header: L1BatchHeader,
     pub metadata: L1BatchMetadata,
     pub factory_deps: Vec<Vec<u8>>,
+    /// Proof of inclusion of the batch pubdata into an external data availability layer. Only used
+    /// in the validium mode.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub da_inclusion_data: Option<Vec<u8>>,
 }
 
 impl L1BatchWithMetadata {
@@ -106,6 +112,7 @@ impl L1BatchWithMetadata {
                 .collect(),
             header,
             metadata,
+            da_inclusion_data: None,
         }
     }
 
@@ -160,6 +167,68 @@ impl L1BatchWithMetadata {
 
     /// Encodes the L1Batch into CommitBatchInfo (see IExecutor.sol).
     pub fn l1_commit_data(&self) -> Token {
+        self.l1_commit_data_with_pubdata(self.pubdata())
+    }
+
+    /// Encodes the L1 batch into `CommitBatchInfo` according to the specified commitment mode. In the validium mode,
+    /// pubdata is not published; `totalL2ToL1Pubdata` only contains [its hash](Self::pubdata_hash()), followed
+    /// by the [inclusion proof](Self::da_inclusion_data) from the data availability layer, if any.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the batch cannot be committed in the specified mode, e.g. if the validium mode
+    /// is not supported by the batch protocol version.
+    pub fn l1_commit_data_for_mode(&self, mode: L1BatchCommitmentMode) -> anyhow::Result<Token> {
+        match mode {
+            L1BatchCommitmentMode::Rollup => Ok(self.l1_commit_data()),
+            L1BatchCommitmentMode::Validium => {
+                let number = self.header.number;
+                let protocol_version = self
+                    .header
+                    .protocol_version
+                    .with_context(|| format!("L1 batch #{number} has unknown protocol version"))?;
+                anyhow::ensure!(
+                    protocol_version.supports_validium(),
+                    "L1 batch #{number} has protocol version {protocol_version:?}, which doesn't support \
+                     the validium commitment mode"
+                );
+                Ok(self.l1_commit_data_with_pubdata(self.validium_pubdata_commitment()))
+            }
+        }
+    }
+
+    /// Returns `totalL2ToL1Pubdata` for the validium mode: the pubdata hash followed by the inclusion data.
+    /// Only the pubdata hash is checked against the batch system logs on L1; the inclusion data is not hashed
+    /// and is only passed to the data availability verifier.
+    fn validium_pubdata_commitment(&self) -> Vec<u8> {
+        let mut pubdata_commitment = self.pubdata_hash().as_bytes().to_vec();
+        if let Some(inclusion_data) = &self.da_inclusion_data {
+            pubdata_commitment.extend_from_slice(inclusion_data);
+        }
+        pubdata_commitment
+    }
+
+    /// Splits `totalL2ToL1Pubdata` committed in the validium mode into the pubdata hash and the inclusion data
+    /// following it. Returns `None` if the data is too short to contain the hash.
+    pub fn split_validium_pubdata_commitment(
+        total_l2_to_l1_pubdata: &[u8],
+    ) -> Option<(&[u8], &[u8])> {
+        (total_l2_to_l1_pubdata.len() >= H256::len_bytes())
+            .then(|| total_l2_to_l1_pubdata.split_at(H256::len_bytes()))
+    }
+
+    /// Same as [`Self::l1_commit_data()`], but with `totalL2ToL1Pubdata` set to the commitments to the blobs
+    /// carrying the batch pubdata. Must only be used for batches with protocol versions supporting blobs.
+    pub fn l1_commit_data_with_pubdata_commitments(&self, pubdata_commitments: Vec<u8>) -> Token {
+        let protocol_version = self.header.protocol_version.unwrap();
+        assert!(
+            protocol_version.supports_blobs(),
+            "Pubdata cannot be published in blobs for L1 batches with protocol version {protocol_version:?}"
+        );
+        self.l1_commit_data_with_pubdata(pubdata_commitments)
+    }
+
+    fn l1_commit_data_with_pubdata(&self, total_l2_to_l1_pubdata: Vec<u8>) -> Token {
         if self.header.protocol_version.unwrap().is_pre_boojum() {
             Token::Tuple(vec![
                 Token::Uint(U256::from(self.header.number.0)),
@@ -229,18 +298,33 @@ impl L1BatchWithMetadata {
                 // `systemLogs`
                 Token::Bytes(self.metadata.l2_l1_messages_compressed.clone()),
                 // `totalL2ToL1Pubdata`
-                Token::Bytes(
-                    self.header
-                        .pubdata_input
-                        .clone()
-                        .unwrap_or(self.construct_pubdata()),
-                ),
+                Token::Bytes(total_l2_to_l1_pubdata),
             ])
         }
     }
 
-    pub fn l1_commit_data_size(&self) -> usize {
-        crate::ethabi::encode(&[Token::Array(vec![self.l1_commit_data()])]).len()
+    /// Returns the size of the encoded `CommitBatchInfo` for this batch in the specified commitment mode.
+    /// Unlike [`Self::l1_commit_data_for_mode()`], doesn't check whether the mode is supported.
+    pub fn l1_commit_data_size(&self, mode: L1BatchCommitmentMode) -> usize {
+        let total_l2_to_l1_pubdata = match mode {
+            L1BatchCommitmentMode::Rollup => self.pubdata(),
+            L1BatchCommitmentMode::Validium => self.validium_pubdata_commitment(),
+        };
+        let commit_data = self.l1_commit_data_with_pubdata(total_l2_to_l1_pubdata);
+        crate::ethabi::encode(&[Token::Array(vec![commit_data])]).len()
+    }
+
+    /// Returns pubdata for the batch, either the one provided by the VM or the one reconstructed from the batch data.
+    pub fn pubdata(&self) -> Vec<u8> {
+        self.header
+            .pubdata_input
+            .clone()
+            .unwrap_or_else(|| self.construct_pubdata())
+    }
+
+    /// Returns the hash of the batch pubdata, which is committed on L1 instead of the pubdata in the validium mode.
+    pub fn pubdata_hash(&self) -> H256 {
+        H256(keccak256(&self.pubdata()))
     }
 
     /// Packs all pubdata needed for batch commitment in boojum into one bytes array. The packing contains the
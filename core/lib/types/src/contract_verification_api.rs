@@ -25,6 +25,12 @@ pub enum SourceCodeData {
 impl SourceCodeData {
     pub fn compiler_type(&self) -> CompilerType {
         match self {
+            // Standard JSON input is used both by Solidity and Vyper compilers.
+            SourceCodeData::StandardJsonInput(input)
+                if input.get("language").and_then(serde_json::Value::as_str) == Some("Vyper") =>
+            {
+                CompilerType::Vyper
+            }
             SourceCodeData::SolSingleFile(_)
             | SourceCodeData::StandardJsonInput(_)
             | SourceCodeData::YulSingleFile(_) => CompilerType::Solc,
@@ -241,7 +247,7 @@ pub enum DeployContractCalldata {
 
 #[cfg(test)]
 mod tests {
-    use super::SourceCodeData;
+    use super::{CompilerType, SourceCodeData};
 
     #[test]
     fn source_code_deserialization() {
@@ -260,6 +266,14 @@ mod tests {
             Ok(SourceCodeData::StandardJsonInput(_))
         ));
 
+        let vyper_multi_file_str =
+            r#"{"codeFormat": "vyper-multi-file", "sourceCode": {"contracts/Token.vy": "text"}}"#;
+        let vyper_multi_file_result = serde_json::from_str::<SourceCodeData>(vyper_multi_file_str);
+        assert!(matches!(
+            vyper_multi_file_result,
+            Ok(SourceCodeData::VyperMultiFile(sources)) if sources.contains_key("contracts/Token.vy")
+        ));
+
         let type_not_specified_str = r#"{"sourceCode": "text"}"#;
         let type_not_specified_result =
             serde_json::from_str::<SourceCodeData>(type_not_specified_str);
@@ -273,4 +287,15 @@ mod tests {
             serde_json::from_str::<SourceCodeData>(type_not_specified_object_str);
         assert!(type_not_specified_object_result.is_err());
     }
+
+    #[test]
+    fn compiler_type_for_standard_json_input() {
+        let solidity_input_str = r#"{"codeFormat": "solidity-standard-json-input", "sourceCode": {"language": "Solidity"}}"#;
+        let solidity_input = serde_json::from_str::<SourceCodeData>(solidity_input_str).unwrap();
+        assert_eq!(solidity_input.compiler_type(), CompilerType::Solc);
+
+        let vyper_input_str = r#"{"codeFormat": "solidity-standard-json-input", "sourceCode": {"language": "Vyper"}}"#;
+        let vyper_input = serde_json::from_str::<SourceCodeData>(vyper_input_str).unwrap();
+        assert_eq!(vyper_input.compiler_type(), CompilerType::Vyper);
+    }
 }
@@ -53,6 +53,27 @@ impl Fee {
     }
 }
 
+/// Breakdown of the gas limit returned by fee estimation. Components sum up to the estimated gas limit.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct FeeBreakdown {
+    /// Gas spent on the transaction execution, excluding gas for published data.
+    pub compute_gas: U256,
+    /// Gas spent on publishing data (storage writes, bytecodes, L2-to-L1 messages) on L1.
+    pub pubdata_gas: U256,
+    /// Batch overhead charged from the transaction.
+    pub overhead_gas: U256,
+}
+
+/// Fee estimate returned by `zks_estimateFee`. Serialized as [`Fee`] with an additional `breakdown` field,
+/// so that it's backward-compatible with clients expecting [`Fee`].
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct FeeEstimate {
+    #[serde(flatten)]
+    pub fee: Fee,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub breakdown: Option<FeeBreakdown>,
+}
+
 /// Returns how many slots would ABI-encoding of the transaction with such parameters take
 pub fn encoding_len(
     data_len: u64,
@@ -27,6 +27,18 @@ impl BatchFeeInput {
             fair_l2_gas_price,
         })
     }
+
+    pub fn pubdata_independent(
+        l1_gas_price: u64,
+        fair_l2_gas_price: u64,
+        fair_pubdata_price: u64,
+    ) -> Self {
+        Self::PubdataIndependent(PubdataIndependentBatchFeeModelInput {
+            l1_gas_price,
+            fair_l2_gas_price,
+            fair_pubdata_price,
+        })
+    }
 }
 
 impl Default for BatchFeeInput {
@@ -1,6 +1,7 @@
 use std::{
     convert::{TryFrom, TryInto},
     fmt::Debug,
+    iter,
     net::IpAddr,
     ops::Add,
     str::FromStr,
@@ -22,6 +23,14 @@ use zkevm_test_harness::{
 };
 use zksync_basic_types::{L1BatchNumber, H256, U256};
 
+use crate::{
+    storage::{
+        witness_block_state::WitnessBlockState,
+        writes::{InitialStorageWrite, RepeatedStorageWrite},
+    },
+    StorageKey, StorageValue,
+};
+
 const HASH_LEN: usize = H256::len_bytes();
 
 /// Metadata emitted by a Merkle tree after processing single storage log.
@@ -202,6 +211,44 @@ impl PrepareBasicCircuitsJob {
         self.merkle_paths.push(path);
     }
 
+    /// Splits this job into a header and chunks, each containing at most `paths_per_chunk` Merkle paths.
+    /// Allows to store and stream large jobs chunk by chunk.
+    pub fn into_chunks(
+        self,
+        paths_per_chunk: usize,
+    ) -> (
+        PrepareBasicCircuitsJobHeader,
+        impl Iterator<Item = PrepareBasicCircuitsJobChunk>,
+    ) {
+        let paths_per_chunk = paths_per_chunk.max(1);
+        let header = PrepareBasicCircuitsJobHeader {
+            next_enumeration_index: self.next_enumeration_index,
+            chunk_count: (self.merkle_paths.len() + paths_per_chunk - 1) / paths_per_chunk,
+        };
+        let mut merkle_paths = self.merkle_paths.into_iter();
+        let chunks = iter::from_fn(move || {
+            let chunk: Vec<_> = merkle_paths.by_ref().take(paths_per_chunk).collect();
+            (!chunk.is_empty()).then_some(PrepareBasicCircuitsJobChunk {
+                merkle_paths: chunk,
+            })
+        });
+        (header, chunks)
+    }
+
+    /// Restores a job from the parts produced by [`Self::into_chunks()`].
+    pub fn from_chunks(
+        header: PrepareBasicCircuitsJobHeader,
+        chunks: impl IntoIterator<Item = PrepareBasicCircuitsJobChunk>,
+    ) -> Self {
+        Self {
+            merkle_paths: chunks
+                .into_iter()
+                .flat_map(|chunk| chunk.merkle_paths)
+                .collect(),
+            next_enumeration_index: header.next_enumeration_index,
+        }
+    }
+
     /// Converts this job into an iterator over the contained Merkle paths.
     pub fn into_merkle_paths(self) -> impl ExactSizeIterator<Item = StorageLogMetadata> {
         let mut merkle_paths = self.merkle_paths;
@@ -223,6 +270,77 @@ impl PrepareBasicCircuitsJob {
     }
 }
 
+/// Header of a [`PrepareBasicCircuitsJob`] stored in chunks (see [`PrepareBasicCircuitsJob::into_chunks()`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PrepareBasicCircuitsJobHeader {
+    pub next_enumeration_index: u64,
+    pub chunk_count: usize,
+}
+
+/// Chunk of Merkle paths of a [`PrepareBasicCircuitsJob`]. Paths are in the compact form, i.e., they may need
+/// hashes from the first path of the job (contained in the first chunk) to be restored.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PrepareBasicCircuitsJobChunk {
+    pub merkle_paths: Vec<StorageLogMetadata>,
+}
+
+/// Self-contained witness of storage accesses performed in an L1 batch. Allows re-proving or auditing the batch
+/// without access to the node database.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct L1BatchWitnessBundle {
+    pub l1_batch_number: L1BatchNumber,
+    /// Values of storage slots read during batch execution, as they were at the start of the batch.
+    /// Sorted by the storage key.
+    pub initial_reads: Vec<(StorageKey, StorageValue)>,
+    /// Writes to storage slots that were never written to before the batch, in the order of application.
+    pub initial_writes: Vec<InitialStorageWrite>,
+    /// Writes to storage slots that were written to before the batch, in the order of application.
+    pub repeated_writes: Vec<RepeatedStorageWrite>,
+    /// Merkle paths for all reads and writes in the batch, as produced by the Merkle tree.
+    pub merkle_paths: PrepareBasicCircuitsJob,
+    /// Initial content of the bootloader memory.
+    pub initial_bootloader_memory: Vec<(usize, U256)>,
+}
+
+impl L1BatchWitnessBundle {
+    pub fn new(
+        l1_batch_number: L1BatchNumber,
+        storage_state: WitnessBlockState,
+        merkle_paths: PrepareBasicCircuitsJob,
+        initial_bootloader_memory: Vec<(usize, U256)>,
+    ) -> Self {
+        let mut initial_reads: Vec<_> = storage_state.read_storage_key.into_iter().collect();
+        initial_reads.sort_unstable_by_key(|(key, _)| *key);
+
+        let mut initial_writes = vec![];
+        let mut repeated_writes = vec![];
+        // Compaction of Merkle paths doesn't influence the fields used here.
+        for log in merkle_paths.merkle_paths.iter().filter(|log| log.is_write) {
+            if log.first_write {
+                initial_writes.push(InitialStorageWrite {
+                    index: log.leaf_enumeration_index,
+                    key: log.leaf_hashed_key,
+                    value: H256(log.value_written),
+                });
+            } else {
+                repeated_writes.push(RepeatedStorageWrite {
+                    index: log.leaf_enumeration_index,
+                    value: H256(log.value_written),
+                });
+            }
+        }
+
+        Self {
+            l1_batch_number,
+            initial_reads,
+            initial_writes,
+            repeated_writes,
+            merkle_paths,
+            initial_bootloader_memory,
+        }
+    }
+}
+
 /// Enriched `PrepareBasicCircuitsJob`. All the other fields are taken from the `l1_batches` table.
 #[derive(Debug, Clone)]
 pub struct BasicCircuitWitnessGeneratorInput {
@@ -493,7 +611,75 @@ mod tests {
             assert_eq!(log.merkle_paths.len(), expected_merkle_path_len);
         }
 
+        for paths_per_chunk in [1, 3, 10, 100] {
+            let (header, chunks) = job.clone().into_chunks(paths_per_chunk);
+            let chunks: Vec<_> = chunks.collect();
+            let expected_chunk_count = (logs.len() + paths_per_chunk - 1) / paths_per_chunk;
+            assert_eq!(header.chunk_count, expected_chunk_count);
+            assert_eq!(chunks.len(), expected_chunk_count);
+            let restored_job = PrepareBasicCircuitsJob::from_chunks(header, chunks);
+            assert_eq!(
+                serde_json::to_value(&restored_job).unwrap(),
+                serde_json::to_value(&job).unwrap()
+            );
+        }
+        let (header, chunks) = PrepareBasicCircuitsJob::new(1).into_chunks(10);
+        assert_eq!(header.chunk_count, 0);
+        assert_eq!(chunks.count(), 0);
+
         let logs_from_job: Vec<_> = job.into_merkle_paths().collect();
         assert_eq!(logs_from_job, logs);
     }
+
+    #[test]
+    fn witness_bundle_splits_writes() {
+        let mut job = PrepareBasicCircuitsJob::new(4);
+        for i in 0..6_u64 {
+            job.push_merkle_path(StorageLogMetadata {
+                root_hash: [0; 32],
+                is_write: i % 3 != 0,
+                first_write: i % 2 == 0,
+                merkle_paths: vec![[0; 32]; 256],
+                leaf_hashed_key: U256::from(i),
+                leaf_enumeration_index: i + 1,
+                value_written: [i as u8; 32],
+                value_read: [0; 32],
+            });
+        }
+
+        let bundle = L1BatchWitnessBundle::new(
+            L1BatchNumber(1),
+            WitnessBlockState::default(),
+            job,
+            vec![(0, U256::one())],
+        );
+        assert_eq!(
+            bundle.initial_writes,
+            [
+                InitialStorageWrite {
+                    index: 3,
+                    key: U256::from(2),
+                    value: H256::repeat_byte(2),
+                },
+                InitialStorageWrite {
+                    index: 5,
+                    key: U256::from(4),
+                    value: H256::repeat_byte(4),
+                }
+            ]
+        );
+        assert_eq!(
+            bundle.repeated_writes,
+            [
+                RepeatedStorageWrite {
+                    index: 2,
+                    value: H256::repeat_byte(1),
+                },
+                RepeatedStorageWrite {
+                    index: 6,
+                    value: H256::repeat_byte(5),
+                }
+            ]
+        );
+    }
 }
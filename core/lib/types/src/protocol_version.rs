@@ -88,6 +88,18 @@ impl ProtocolVersionId {
     pub fn last_pre_boojum() -> Self {
         ProtocolVersionId::Version17
     }
+
+    /// Returns `true` if L1 contracts of this version accept commitments for L1 batches in the validium mode
+    /// (i.e., `totalL2ToL1Pubdata` containing the pubdata hash instead of the pubdata itself).
+    pub fn supports_validium(&self) -> bool {
+        self >= &ProtocolVersionId::Version20
+    }
+
+    /// Returns `true` if the VM of this version emits blob hashes in system logs and L1 contracts of this version
+    /// accept pubdata published in EIP-4844 blobs. Versions up to and including `Version20` don't.
+    pub fn supports_blobs(&self) -> bool {
+        self > &ProtocolVersionId::Version20
+    }
 }
 
 impl Default for ProtocolVersionId {
@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use serde::{Deserialize, Serialize};
 use zksync_basic_types::L1BatchNumber;
 
@@ -7,10 +9,12 @@ use crate::{
     protocol_version::{FriProtocolVersionId, L1VerifierConfig},
 };
 
+/// Data required to generate a proof for an L1 batch. The type of the witness input is generic, so that it can be
+/// streamed by the server instead of being loaded in memory in its entirety.
 #[derive(Debug, Serialize, Deserialize)]
-pub struct ProofGenerationData {
+pub struct ProofGenerationData<D = PrepareBasicCircuitsJob> {
     pub l1_batch_number: L1BatchNumber,
-    pub data: PrepareBasicCircuitsJob,
+    pub data: D,
     pub fri_protocol_version_id: FriProtocolVersionId,
     pub l1_verifier_config: L1VerifierConfig,
 }
@@ -19,8 +23,8 @@ pub struct ProofGenerationData {
 pub struct ProofGenerationDataRequest {}
 
 #[derive(Debug, Serialize, Deserialize)]
-pub enum ProofGenerationDataResponse {
-    Success(Option<ProofGenerationData>),
+pub enum ProofGenerationDataResponse<D = PrepareBasicCircuitsJob> {
+    Success(Option<ProofGenerationData<D>>),
     Error(String),
 }
 
@@ -36,3 +40,54 @@ pub enum SubmitProofResponse {
     Success,
     Error(String),
 }
+
+/// Priority class of a proof generation job. Jobs with higher priority are handed out to provers first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProofGenerationPriority {
+    Low,
+    Normal,
+    /// Used for jobs flagged by the operator, jobs with an expired deadline, and jobs waiting for too long.
+    High,
+}
+
+impl ProofGenerationPriority {
+    pub const ALL: [Self; 3] = [Self::Low, Self::Normal, Self::High];
+
+    /// Returns the integer value of this priority stored in Postgres.
+    pub fn to_db_value(self) -> i32 {
+        match self {
+            Self::Low => -1,
+            Self::Normal => 0,
+            Self::High => 1,
+        }
+    }
+
+    /// Converts a value stored in Postgres; out-of-range values are clamped.
+    pub fn from_db_value(value: i32) -> Self {
+        match value {
+            i32::MIN..=-1 => Self::Low,
+            0 => Self::Normal,
+            _ => Self::High,
+        }
+    }
+}
+
+/// Request of the admin API setting the priority and / or deadline of a proof generation job.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SetProofGenerationPriorityRequest {
+    pub priority: ProofGenerationPriority,
+    /// Deadline for the job relative to the request time. After the deadline, the job is treated
+    /// as having the high priority.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deadline_in_secs: Option<u64>,
+}
+
+/// Statistics for proof generation jobs waiting to be picked by a prover, grouped by priority.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ProofGenerationQueueStats {
+    pub queued_jobs: HashMap<ProofGenerationPriority, usize>,
+    /// Oldest L1 batch waiting to be picked.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub oldest_queued_l1_batch: Option<L1BatchNumber>,
+}
@@ -2,10 +2,13 @@ use std::convert::TryFrom;
 
 use anyhow::Context;
 use serde::{Deserialize, Serialize};
-use zksync_basic_types::{AccountTreeId, L1BatchNumber, MiniblockNumber, H256};
+use zksync_basic_types::{AccountTreeId, Address, L1BatchNumber, MiniblockNumber, H256};
 use zksync_protobuf::{required, ProtoFmt};
 
-use crate::{commitment::L1BatchWithMetadata, Bytes, StorageKey, StorageValue};
+use crate::{
+    commitment::L1BatchWithMetadata, web3::signing::keccak256, Bytes, PackedEthSignature,
+    StorageKey, StorageValue,
+};
 
 /// Information about all snapshots persisted by the node.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -21,11 +24,22 @@ pub struct AllSnapshots {
 pub struct SnapshotMetadata {
     /// L1 batch for the snapshot. The data in the snapshot captures node storage at the end of this batch.
     pub l1_batch_number: L1BatchNumber,
+    /// L1 batch of the snapshot this snapshot is a delta to, or `None` for full snapshots. A delta snapshot
+    /// only contains storage logs and factory deps changed after the base snapshot.
+    pub base_l1_batch_number: Option<L1BatchNumber>,
     /// Path to the factory dependencies blob.
     pub factory_deps_filepath: String,
     /// Paths to the storage log blobs. Ordered by the chunk ID. If a certain chunk is not produced yet,
     /// the corresponding path is `None`.
     pub storage_logs_filepaths: Vec<Option<String>>,
+    /// Hashes of storage log chunks (see [`SnapshotStorageLogsChunk::hash()`]). Ordered by the chunk ID.
+    /// If a certain chunk is not produced yet, or the snapshot was created before chunks were hashed,
+    /// the corresponding hash is `None`.
+    pub storage_logs_chunk_hashes: Vec<Option<H256>>,
+    /// Hash of the snapshot manifest. Set once all chunks are produced and the manifest is finalized.
+    pub manifest_hash: Option<H256>,
+    /// Signature of the manifest hash by the snapshot creator, if manifest signing is enabled.
+    pub manifest_signature: Option<PackedEthSignature>,
 }
 
 impl SnapshotMetadata {
@@ -42,10 +56,61 @@ impl SnapshotMetadata {
 pub struct SnapshotHeader {
     pub l1_batch_number: L1BatchNumber,
     pub miniblock_number: MiniblockNumber,
+    /// L1 batch of the base snapshot if this is a delta snapshot. To recover from a delta snapshot,
+    /// the base snapshot (which may be a delta itself) must be applied first.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_l1_batch_number: Option<L1BatchNumber>,
     /// Ordered by chunk IDs.
     pub storage_logs_chunks: Vec<SnapshotStorageLogsChunkMetadata>,
     pub factory_deps_filepath: String,
     pub last_l1_batch_with_metadata: L1BatchWithMetadata,
+    /// Integrity manifest of the snapshot. May be missing for snapshots created before manifests were introduced.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub manifest: Option<SnapshotManifest>,
+}
+
+/// Integrity manifest of a snapshot allowing to verify storage log chunks loaded from the object store.
+/// Manifests of delta snapshots are chained with the manifests of their base snapshots.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotManifest {
+    pub l1_batch_number: L1BatchNumber,
+    /// Merkle tree root hash after the snapshot L1 batch.
+    pub l1_batch_root_hash: H256,
+    /// Manifest hash of the base snapshot for delta snapshots.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_manifest_hash: Option<H256>,
+    /// Hashes of storage log chunks ordered by chunk ID (see [`SnapshotStorageLogsChunk::hash()`]).
+    pub storage_logs_chunk_hashes: Vec<H256>,
+    /// Signature of the [manifest hash](Self::hash()) by the snapshot creator.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<PackedEthSignature>,
+}
+
+impl SnapshotManifest {
+    /// Computes the manifest hash. The hash covers all manifest fields except for the signature.
+    pub fn hash(&self) -> H256 {
+        let mut preimage = Vec::with_capacity(68 + 32 * self.storage_logs_chunk_hashes.len());
+        preimage.extend_from_slice(&self.l1_batch_number.0.to_be_bytes());
+        preimage.extend_from_slice(self.l1_batch_root_hash.as_bytes());
+        let base_manifest_hash = self.base_manifest_hash.unwrap_or_default();
+        preimage.extend_from_slice(base_manifest_hash.as_bytes());
+        for chunk_hash in &self.storage_logs_chunk_hashes {
+            preimage.extend_from_slice(chunk_hash.as_bytes());
+        }
+        H256(keccak256(&preimage))
+    }
+
+    /// Recovers the address that has signed the manifest. Returns `Ok(None)` if the manifest is not signed.
+    pub fn recover_signer(&self) -> anyhow::Result<Option<Address>> {
+        let Some(signature) = &self.signature else {
+            return Ok(None);
+        };
+        let signer = signature
+            .signature_recover_signer(&self.hash())
+            .context("invalid manifest signature")?;
+        Ok(Some(signer))
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -68,6 +133,25 @@ pub struct SnapshotStorageLogsChunk {
     pub storage_logs: Vec<SnapshotStorageLog>,
 }
 
+impl SnapshotStorageLogsChunk {
+    /// Computes the hash of this chunk. Unlike the object store representation, the hash doesn't depend
+    /// on the serialization format or compression.
+    pub fn hash(&self) -> H256 {
+        // address (20 bytes) + key (32) + value (32) + L1 batch of initial write (4) + enumeration index (8)
+        const LOG_SIZE: usize = 96;
+
+        let mut preimage = Vec::with_capacity(LOG_SIZE * self.storage_logs.len());
+        for log in &self.storage_logs {
+            preimage.extend_from_slice(log.key.address().as_bytes());
+            preimage.extend_from_slice(log.key.key().as_bytes());
+            preimage.extend_from_slice(log.value.as_bytes());
+            preimage.extend_from_slice(&log.l1_batch_number_of_initial_write.0.to_be_bytes());
+            preimage.extend_from_slice(&log.enumeration_index.to_be_bytes());
+        }
+        H256(keccak256(&preimage))
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct SnapshotStorageLog {
     pub key: StorageKey,
@@ -196,3 +280,34 @@ pub struct SnapshotRecoveryStatus {
     pub last_finished_chunk_id: Option<u64>,
     pub total_chunk_count: u64,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signing_snapshot_manifest() {
+        let signing_key = H256::repeat_byte(1);
+        let mut manifest = SnapshotManifest {
+            l1_batch_number: L1BatchNumber(10),
+            l1_batch_root_hash: H256::repeat_byte(2),
+            base_manifest_hash: None,
+            storage_logs_chunk_hashes: vec![H256::repeat_byte(3), H256::repeat_byte(4)],
+            signature: None,
+        };
+        assert_eq!(manifest.recover_signer().unwrap(), None);
+
+        let signature = PackedEthSignature::sign_raw(&signing_key, &manifest.hash()).unwrap();
+        manifest.signature = Some(signature);
+        let expected_signer = PackedEthSignature::address_from_private_key(&signing_key).unwrap();
+        assert_eq!(manifest.recover_signer().unwrap(), Some(expected_signer));
+
+        let serialized = serde_json::to_value(&manifest).unwrap();
+        let deserialized: SnapshotManifest = serde_json::from_value(serialized).unwrap();
+        assert_eq!(deserialized, manifest);
+
+        // Any change to the manifest should invalidate the signature.
+        manifest.base_manifest_hash = Some(H256::repeat_byte(5));
+        assert_ne!(manifest.recover_signer().unwrap(), Some(expected_signer));
+    }
+}
@@ -1,13 +1,33 @@
 //! This module contains the observability subsystem.
 //! It is responsible for providing a centralized interface for consistent observability configuration.
 
-use std::{backtrace::Backtrace, borrow::Cow, panic::PanicInfo};
+use std::{
+    backtrace::Backtrace, borrow::Cow, error::Error as StdError, panic::PanicInfo, sync::OnceLock,
+};
+
+use opentelemetry::{
+    sdk::{
+        propagation::TraceContextPropagator,
+        trace::{self, Sampler, Tracer},
+        Resource,
+    },
+    KeyValue,
+};
+use opentelemetry_otlp::WithExportConfig;
 
 // Temporary re-export of `sentry::capture_message` aiming to simplify the transition from `vlog` to using
 // crates directly.
 pub use sentry::{capture_message, Level as AlertLevel};
 use sentry::{types::Dsn, ClientInitGuard};
-use tracing_subscriber::{fmt, layer::SubscriberExt, util::SubscriberInitExt};
+use tracing_subscriber::{
+    fmt, layer::SubscriberExt, reload, util::SubscriberInitExt, EnvFilter, Registry,
+};
+
+pub use crate::trace_links::{link_current_span, record_current_span, TraceKey};
+
+mod trace_links;
+
+static LOG_DIRECTIVES_HANDLE: OnceLock<LogDirectivesHandle> = OnceLock::new();
 
 /// Specifies the format of the logs in stdout.
 #[derive(Debug, Clone, Copy, Default)]
@@ -17,19 +37,94 @@ pub enum LogFormat {
     Json,
 }
 
+/// Handle allowing to change log directives (in the `RUST_LOG` format) at runtime, e.g. to temporarily
+/// increase verbosity of a single component without restarting the node.
+#[derive(Debug, Clone)]
+pub struct LogDirectivesHandle(reload::Handle<EnvFilter, Registry>);
+
+impl LogDirectivesHandle {
+    /// Returns currently active log directives.
+    pub fn current(&self) -> String {
+        self.0.with_current(ToString::to_string).unwrap_or_default()
+    }
+
+    /// Replaces log directives, e.g. with `zksync_core=info,zksync_core::consensus=trace`.
+    /// Returns an error if the directives cannot be parsed; in this case, the current directives are retained.
+    pub fn set(&self, directives: &str) -> Result<(), Box<dyn StdError + Send + Sync>> {
+        let filter = EnvFilter::try_new(directives)?;
+        self.0.reload(filter)?;
+        Ok(())
+    }
+}
+
+/// Returns the handle to change log directives at runtime, or `None` if the observability subsystem
+/// was not initialized with [`ObservabilityBuilder::build()`].
+pub fn log_directives_handle() -> Option<LogDirectivesHandle> {
+    LOG_DIRECTIVES_HANDLE.get().cloned()
+}
+
+/// Configuration of the OpenTelemetry (OTLP) trace export.
+#[derive(Debug, Clone)]
+pub struct OpenTelemetryOptions {
+    /// URL of the OTLP collector accepting traces over HTTP, e.g. `http://localhost:4318/v1/traces`.
+    pub endpoint: String,
+    /// Share of traces to be sampled, from 0.0 (no traces) to 1.0 (all traces).
+    pub sampling_ratio: f64,
+    /// Name of the service reported with the exported spans.
+    pub service_name: String,
+}
+
+impl OpenTelemetryOptions {
+    fn install_tracer(self) -> Tracer {
+        // Propagate trace context in the W3C format, so that traces can span several services.
+        opentelemetry::global::set_text_map_propagator(TraceContextPropagator::new());
+
+        let resource = Resource::new([KeyValue::new(
+            opentelemetry_semantic_conventions::resource::SERVICE_NAME,
+            self.service_name,
+        )]);
+        let exporter = opentelemetry_otlp::new_exporter()
+            .http()
+            .with_endpoint(self.endpoint);
+        let trace_config =
+            trace::config()
+                .with_resource(resource)
+                .with_sampler(Sampler::ParentBased(Box::new(Sampler::TraceIdRatioBased(
+                    self.sampling_ratio,
+                ))));
+        opentelemetry_otlp::new_pipeline()
+            .tracing()
+            .with_exporter(exporter)
+            .with_trace_config(trace_config)
+            .install_batch(opentelemetry::runtime::Tokio)
+            .expect("Failed installing OpenTelemetry tracer")
+    }
+}
+
 /// Builder for the observability subsystem.
-/// Currently capable of configuring logging output and sentry integration.
+/// Currently capable of configuring logging output, sentry integration and OpenTelemetry trace export.
 #[derive(Debug, Default)]
 pub struct ObservabilityBuilder {
     log_format: LogFormat,
     sentry_url: Option<Dsn>,
     sentry_environment: Option<String>,
+    opentelemetry: Option<OpenTelemetryOptions>,
 }
 
 /// Guard for the observability subsystem.
 /// Releases configured integrations upon being dropped.
 pub struct ObservabilityGuard {
     _sentry_guard: Option<ClientInitGuard>,
+    has_opentelemetry: bool,
+}
+
+impl Drop for ObservabilityGuard {
+    fn drop(&mut self) {
+        if self.has_opentelemetry {
+            // Flushes spans that are not exported yet.
+            opentelemetry::global::shutdown_tracer_provider();
+        }
+    }
 }
 
 impl std::fmt::Debug for ObservabilityGuard {
@@ -69,20 +164,37 @@ impl ObservabilityBuilder {
         self
     }
 
+    /// Enables export of traces to an OpenTelemetry collector.
+    /// Must be called from within a Tokio runtime, since spans are exported in the background.
+    pub fn with_opentelemetry(mut self, options: OpenTelemetryOptions) -> Self {
+        self.opentelemetry = Some(options);
+        self
+    }
+
     /// Initializes the observability subsystem.
     pub fn build(self) -> ObservabilityGuard {
+        let has_opentelemetry = self.opentelemetry.is_some();
+        let tracer = self.opentelemetry.map(OpenTelemetryOptions::install_tracer);
+        let opentelemetry_layer =
+            tracer.map(|tracer| tracing_opentelemetry::layer().with_tracer(tracer));
+        let (filter_layer, filter_handle) = reload::Layer::new(EnvFilter::from_default_env());
+        LOG_DIRECTIVES_HANDLE
+            .set(LogDirectivesHandle(filter_handle))
+            .expect("Observability subsystem is initialized multiple times");
+
         // Initialize logs.
         match self.log_format {
             LogFormat::Plain => {
                 tracing_subscriber::registry()
-                    .with(tracing_subscriber::EnvFilter::from_default_env())
+                    .with(filter_layer)
                     .with(fmt::Layer::default())
+                    .with(opentelemetry_layer)
                     .init();
             }
             LogFormat::Json => {
                 let timer = tracing_subscriber::fmt::time::UtcTime::rfc_3339();
                 tracing_subscriber::registry()
-                    .with(tracing_subscriber::EnvFilter::from_default_env())
+                    .with(filter_layer)
                     .with(
                         fmt::Layer::default()
                             .with_file(true)
@@ -90,6 +202,7 @@ impl ObservabilityBuilder {
                             .with_timer(timer)
                             .json(),
                     )
+                    .with(opentelemetry_layer)
                     .init();
             }
         };
@@ -121,6 +234,7 @@ impl ObservabilityBuilder {
 
         ObservabilityGuard {
             _sentry_guard: sentry_guard,
+            has_opentelemetry,
         }
     }
 }
@@ -168,6 +282,44 @@ pub fn sentry_url_from_env() -> Option<String> {
     }
 }
 
+/// Loads the OpenTelemetry trace export options from the environment variables according to the existing zkSync
+/// configuration scheme. Export is disabled if the collector URL is not set or is `unset`.
+///
+/// This is a deprecated function existing for compatibility with the old configuration scheme.
+/// Not recommended for use in new applications.
+///
+/// # Panics
+///
+/// Panics if the sampling ratio is set, but is not a number in the `[0, 1]` range.
+#[deprecated(
+    note = "This function will be removed in the future. Applications are expected to handle their configuration themselves."
+)]
+pub fn opentelemetry_from_env(service_name: &str) -> Option<OpenTelemetryOptions> {
+    let endpoint = match std::env::var("MISC_OTLP_URL") {
+        Ok(str) if str == "unset" => return None,
+        Ok(str) => str,
+        Err(_) => return None,
+    };
+    let sampling_ratio = match std::env::var("MISC_OTLP_SAMPLING_RATIO") {
+        Ok(ratio) => {
+            let ratio: f64 = ratio.parse().unwrap_or_else(|_| {
+                panic!("MISC_OTLP_SAMPLING_RATIO has an unexpected value {ratio}")
+            });
+            assert!(
+                (0.0..=1.0).contains(&ratio),
+                "MISC_OTLP_SAMPLING_RATIO must be in the [0, 1] range, got {ratio}"
+            );
+            ratio
+        }
+        Err(_) => 1.0,
+    };
+    Some(OpenTelemetryOptions {
+        endpoint,
+        sampling_ratio,
+        service_name: service_name.to_owned(),
+    })
+}
+
 /// Prepared the Sentry environment ID from the environment variable according to the existing zkSync configuration
 /// scheme.
 /// This function mimics like `vlog` configuration worked historically, e.g. it would also try to load environment
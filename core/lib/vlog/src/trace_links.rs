@@ -0,0 +1,90 @@
+//! Linking of spans produced by different components for the same entity (e.g., an L2 transaction
+//! or an L1 batch).
+//!
+//! Components like the API server, state keeper and Ethereum sender don't call each other directly;
+//! they communicate via Postgres. Hence, their spans cannot be nested into a single trace. Instead,
+//! a component processing an entity records the context of its span, and subsequent components
+//! add a [link](https://opentelemetry.io/docs/concepts/signals/traces/#span-links) to this context
+//! to their spans. Contexts are kept in memory, so spans are only linked within a single process.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{Mutex, OnceLock},
+};
+
+use opentelemetry::trace::{SpanContext, TraceContextExt};
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+/// Maximum number of recorded span contexts. The oldest contexts are evicted once this number is exceeded.
+const MAX_RECORDED_CONTEXTS: usize = 100_000;
+
+/// Entity processed by several components, spans for which should be linked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TraceKey {
+    /// L2 transaction with the specified hash.
+    Transaction([u8; 32]),
+    /// L1 batch with the specified number.
+    L1Batch(u32),
+    /// L1 transaction with the specified ID.
+    EthTx(u32),
+}
+
+#[derive(Debug, Default)]
+struct SpanContexts {
+    contexts: HashMap<TraceKey, SpanContext>,
+    insertion_order: VecDeque<TraceKey>,
+}
+
+impl SpanContexts {
+    fn get() -> &'static Mutex<Self> {
+        static CONTEXTS: OnceLock<Mutex<SpanContexts>> = OnceLock::new();
+        CONTEXTS.get_or_init(Mutex::default)
+    }
+
+    fn insert(&mut self, key: TraceKey, context: SpanContext) {
+        if self.contexts.insert(key, context).is_none() {
+            self.insertion_order.push_back(key);
+        }
+        while self.insertion_order.len() > MAX_RECORDED_CONTEXTS {
+            if let Some(evicted_key) = self.insertion_order.pop_front() {
+                self.contexts.remove(&evicted_key);
+            }
+        }
+    }
+}
+
+fn current_span_context() -> Option<SpanContext> {
+    let context = tracing::Span::current().context();
+    let span_context = context.span().span_context().clone();
+    // The context is invalid if OpenTelemetry export is disabled or there is no current span.
+    span_context.is_valid().then_some(span_context)
+}
+
+/// Records the context of the current span for the specified entity, so that spans in other components
+/// can link to it using [`link_current_span()`]. If a context is already recorded for the entity, it is replaced.
+/// No-op if OpenTelemetry export is disabled.
+pub fn record_current_span(key: TraceKey) {
+    if let Some(span_context) = current_span_context() {
+        SpanContexts::get()
+            .lock()
+            .unwrap()
+            .insert(key, span_context);
+    }
+}
+
+/// Links the current span to the span recorded for the specified entity using [`record_current_span()`].
+/// No-op if OpenTelemetry export is disabled, or if no span was recorded for the entity in this process.
+pub fn link_current_span(key: TraceKey) {
+    if current_span_context().is_none() {
+        return;
+    }
+    let linked_context = SpanContexts::get()
+        .lock()
+        .unwrap()
+        .contexts
+        .get(&key)
+        .cloned();
+    if let Some(linked_context) = linked_context {
+        tracing::Span::current().add_link(linked_context);
+    }
+}
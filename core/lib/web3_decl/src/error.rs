@@ -1,8 +1,102 @@
 //! Definition of errors that can occur in the zkSync Web3 API.
 
+use jsonrpsee::types::ErrorObjectOwned;
+use serde_json::Value;
 use thiserror::Error;
 use zksync_types::api::SerializationTransactionError;
 
+/// Catalogue of numeric codes returned in the `code` field of JSON-RPC errors.
+///
+/// Codes are a part of the public API: SDKs can rely on them instead of parsing error messages. Hence, codes
+/// must never be changed or reused for different errors; new errors should get new codes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(i32)]
+pub enum ApiErrorCode {
+    /// Invalid method params (e.g., a non-existing block).
+    InvalidParams = -32602,
+    /// Internal server error.
+    Internal = -32603,
+    /// Transaction or call execution reverted. `data` contains the hex-encoded revert data.
+    ExecutionReverted = 3,
+    /// Pubsub connection timed out.
+    PubSubTimeout = 4,
+    /// Request processing timed out.
+    RequestTimeout = 5,
+    /// Merkle tree API is not available on the node.
+    TreeApiUnavailable = 6,
+    /// Transaction nonce is too far ahead of the account nonce. `data` contains the allowed nonce range.
+    NonceTooHigh = 100,
+    /// Transaction nonce is already used. `data` contains the allowed nonce range.
+    NonceTooLow = 101,
+    /// Transaction doesn't raise fees enough to replace a pending transaction. `data` contains the required bump.
+    ReplacementUnderpriced = 102,
+    /// Transaction is malformed (e.g., has an invalid signature or cannot be deserialized).
+    InvalidTransaction = 103,
+    /// Sender balance doesn't cover the transaction fee and value. `data` contains the balance and required amounts.
+    InsufficientFunds = 104,
+    /// Transaction gas limit exceeds the maximum allowed value.
+    GasLimitTooHigh = 105,
+    /// Max fee per gas is lower than the current base fee. `data` contains the required max fee per gas.
+    MaxFeePerGasTooLow = 106,
+    /// Transaction fee parameters are inconsistent or out of range.
+    InvalidFeeParams = 107,
+    /// Transaction gas limit doesn't cover intrinsic costs.
+    IntrinsicGasTooLow = 108,
+    /// Transaction has too many factory dependencies. `data` contains the actual and maximum number of dependencies.
+    TooManyFactoryDeps = 109,
+    /// Transaction submission is rate-limited.
+    RateLimited = 110,
+    /// Server is shutting down and doesn't accept transactions.
+    ServerShuttingDown = 111,
+    /// Transaction is rejected by the operator-defined submission policy.
+    RejectedByPolicy = 112,
+    /// Transaction cannot be executed by the VM (e.g., because of a bootloader failure).
+    Unexecutable = 120,
+    /// Account validation of the transaction failed.
+    ValidationFailed = 121,
+    /// Paymaster validation or preparation failed.
+    PaymasterValidationFailed = 122,
+    /// Transaction fee cannot be charged from the account.
+    FailedToChargeFee = 123,
+    /// Main node could not be reached when proxying a transaction. Errors returned by the main node itself
+    /// are passed through with their original code and `data`.
+    MainNodeError = 130,
+}
+
+impl ApiErrorCode {
+    /// All codes in the catalogue.
+    pub const ALL: &'static [Self] = &[
+        Self::InvalidParams,
+        Self::Internal,
+        Self::ExecutionReverted,
+        Self::PubSubTimeout,
+        Self::RequestTimeout,
+        Self::TreeApiUnavailable,
+        Self::NonceTooHigh,
+        Self::NonceTooLow,
+        Self::ReplacementUnderpriced,
+        Self::InvalidTransaction,
+        Self::InsufficientFunds,
+        Self::GasLimitTooHigh,
+        Self::MaxFeePerGasTooLow,
+        Self::InvalidFeeParams,
+        Self::IntrinsicGasTooLow,
+        Self::TooManyFactoryDeps,
+        Self::RateLimited,
+        Self::ServerShuttingDown,
+        Self::RejectedByPolicy,
+        Self::Unexecutable,
+        Self::ValidationFailed,
+        Self::PaymasterValidationFailed,
+        Self::FailedToChargeFee,
+        Self::MainNodeError,
+    ];
+
+    pub fn code(self) -> i32 {
+        self as i32
+    }
+}
+
 #[derive(Debug, Error)]
 pub enum Web3Error {
     #[error("Block with such an ID doesn't exist yet")]
@@ -17,8 +111,20 @@ pub enum Web3Error {
     NoSuchFunction,
     #[error("Invalid transaction data: {0}")]
     InvalidTransactionData(#[from] zksync_types::ethabi::Error),
+    /// Transaction or call execution reverted; contains the revert reason and revert data.
     #[error("{0}")]
     SubmitTransactionError(String, Vec<u8>),
+    /// Transaction was rejected for a reason other than execution revert.
+    #[error("{message}")]
+    TransactionRejected {
+        code: ApiErrorCode,
+        message: String,
+        data: Option<Value>,
+    },
+    /// Error returned by the main node when proxying a request. It is passed to the client as is,
+    /// including the original code and data.
+    #[error("{}", .0.message())]
+    ProxyError(ErrorObjectOwned),
     #[error("Failed to serialize transaction: {0}")]
     SerializationError(#[from] SerializationTransactionError),
     #[error("Invalid fee parameters: {0}")]
@@ -33,8 +139,203 @@ pub enum Web3Error {
     NotImplemented,
     #[error("Query returned more than {0} results. Try with this block range [{1:#x}, {2:#x}].")]
     LogsLimitExceeded(usize, u32, u32),
+    /// Requested logs block range exceeds the limit; contains the limit and the requested range.
+    #[error("Query block range [{1:#x}, {2:#x}] exceeds the limit of {0} blocks. Try with narrower block ranges.")]
+    LogsBlockRangeExceeded(u32, u32, u32),
+    #[error("Invalid logs pagination cursor")]
+    InvalidLogsCursor,
     #[error("invalid filter: if blockHash is supplied fromBlock and toBlock must not be")]
     InvalidFilterBlockHash,
     #[error("Tree API is not available")]
     TreeApiUnavailable,
+    #[error("Merkle proofs for L1 batch #{0} are not available on this node")]
+    ProofUnavailable(u32),
+    #[error("State for block #{0} is not available on this node; the oldest block with available state is #{1}")]
+    HistoricalStateUnavailable(u32, u32),
+    #[error("Block #{0} has been pruned on this node; the earliest available block is #{1}")]
+    PrunedBlock(u32, u32),
+    #[error("Invalid state override for account {0:?}: {1}")]
+    InvalidStateOverride(zksync_types::Address, String),
+    /// Requested L1 batch range exceeds the limit; contains the limit and the requested range.
+    #[error("L1 batch range [{1}, {2}] exceeds the limit of {0} batches")]
+    L1BatchRangeExceeded(u32, u32, u32),
+}
+
+impl Web3Error {
+    /// Returns the stable code of this error.
+    pub fn code(&self) -> ApiErrorCode {
+        match self {
+            Self::InternalError | Self::NotImplemented => ApiErrorCode::Internal,
+            Self::NoBlock
+            | Self::NoSuchFunction
+            | Self::RLPError(_)
+            | Self::InvalidTransactionData(_)
+            | Self::TooManyTopics
+            | Self::FilterNotFound
+            | Self::InvalidFeeParams(_)
+            | Self::InvalidFilterBlockHash
+            | Self::LogsLimitExceeded(..)
+            | Self::LogsBlockRangeExceeded(..)
+            | Self::InvalidLogsCursor
+            | Self::HistoricalStateUnavailable(..)
+            | Self::PrunedBlock(..)
+            | Self::InvalidStateOverride(..)
+            | Self::L1BatchRangeExceeded(..)
+            | Self::ProofUnavailable(_) => ApiErrorCode::InvalidParams,
+            Self::SubmitTransactionError(..) => ApiErrorCode::ExecutionReverted,
+            Self::SerializationError(_) => ApiErrorCode::InvalidTransaction,
+            Self::TransactionRejected { code, .. } => *code,
+            // Not used for the returned error object; see `ProxyError` docs.
+            Self::ProxyError(_) => ApiErrorCode::MainNodeError,
+            Self::PubSubTimeout => ApiErrorCode::PubSubTimeout,
+            Self::RequestTimeout => ApiErrorCode::RequestTimeout,
+            Self::TreeApiUnavailable => ApiErrorCode::TreeApiUnavailable,
+        }
+    }
+
+    /// Returns machine-readable data for this error, if any.
+    pub fn data(&self) -> Option<Value> {
+        match self {
+            Self::SubmitTransactionError(_, data) => {
+                Some(Value::String(format!("0x{}", hex::encode(data))))
+            }
+            Self::TransactionRejected { data, .. } => data.clone(),
+            Self::ProxyError(err) => err
+                .data()
+                .and_then(|data| serde_json::from_str(data.get()).ok()),
+            Self::LogsLimitExceeded(limit, from_block, to_block) => Some(serde_json::json!({
+                "limit": limit,
+                "fromBlock": from_block,
+                "toBlock": to_block,
+            })),
+            Self::LogsBlockRangeExceeded(max_range, from_block, to_block) => {
+                Some(serde_json::json!({
+                    "maxBlockRange": max_range,
+                    "fromBlock": from_block,
+                    "toBlock": to_block,
+                    "suggestedRanges": suggest_block_ranges(*max_range, *from_block, *to_block),
+                }))
+            }
+            Self::HistoricalStateUnavailable(_, oldest_available_block) => {
+                Some(serde_json::json!({ "oldestAvailableBlock": oldest_available_block }))
+            }
+            Self::PrunedBlock(_, earliest_available_block) => {
+                Some(serde_json::json!({ "earliestAvailableBlock": earliest_available_block }))
+            }
+            Self::L1BatchRangeExceeded(max_range, from_batch, to_batch) => {
+                Some(serde_json::json!({
+                    "maxL1BatchRange": max_range,
+                    "fromBatch": from_batch,
+                    "toBatch": to_batch,
+                }))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Splits the `[from_block, to_block]` range into consecutive ranges of at most `max_range` blocks.
+/// Only the first few ranges are returned to keep error data small.
+fn suggest_block_ranges(max_range: u32, from_block: u32, to_block: u32) -> Vec<Value> {
+    const MAX_SUGGESTED_RANGES: usize = 10;
+
+    let max_range = max_range.max(1);
+    let mut ranges = vec![];
+    let mut start = from_block;
+    while start <= to_block && ranges.len() < MAX_SUGGESTED_RANGES {
+        let end = start.saturating_add(max_range - 1).min(to_block);
+        ranges.push(serde_json::json!({ "fromBlock": start, "toBlock": end }));
+        match end.checked_add(1) {
+            Some(next_start) => start = next_start,
+            None => break,
+        }
+    }
+    ranges
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    /// Codes are a part of the public API; this test must only be updated when adding new codes.
+    #[test]
+    fn api_error_codes_are_stable() {
+        let codes: Vec<_> = ApiErrorCode::ALL
+            .iter()
+            .map(|&code| (format!("{code:?}"), code.code()))
+            .collect();
+        let expected_codes = [
+            ("InvalidParams", -32602),
+            ("Internal", -32603),
+            ("ExecutionReverted", 3),
+            ("PubSubTimeout", 4),
+            ("RequestTimeout", 5),
+            ("TreeApiUnavailable", 6),
+            ("NonceTooHigh", 100),
+            ("NonceTooLow", 101),
+            ("ReplacementUnderpriced", 102),
+            ("InvalidTransaction", 103),
+            ("InsufficientFunds", 104),
+            ("GasLimitTooHigh", 105),
+            ("MaxFeePerGasTooLow", 106),
+            ("InvalidFeeParams", 107),
+            ("IntrinsicGasTooLow", 108),
+            ("TooManyFactoryDeps", 109),
+            ("RateLimited", 110),
+            ("ServerShuttingDown", 111),
+            ("RejectedByPolicy", 112),
+            ("Unexecutable", 120),
+            ("ValidationFailed", 121),
+            ("PaymasterValidationFailed", 122),
+            ("FailedToChargeFee", 123),
+            ("MainNodeError", 130),
+        ];
+        let expected_codes: Vec<_> = expected_codes
+            .iter()
+            .map(|&(name, code)| (name.to_owned(), code))
+            .collect();
+        assert_eq!(codes, expected_codes);
+
+        let unique_codes: HashSet<_> = codes.iter().map(|(_, code)| code).collect();
+        assert_eq!(unique_codes.len(), codes.len());
+    }
+
+    #[test]
+    fn error_data() {
+        let err = Web3Error::SubmitTransactionError("reverted".to_owned(), vec![0xde, 0xad]);
+        assert_eq!(err.code(), ApiErrorCode::ExecutionReverted);
+        assert_eq!(err.data(), Some(Value::String("0xdead".to_owned())));
+
+        let err = Web3Error::LogsLimitExceeded(10_000, 1, 2);
+        assert_eq!(err.code(), ApiErrorCode::InvalidParams);
+        assert_eq!(
+            err.data(),
+            Some(serde_json::json!({ "limit": 10_000, "fromBlock": 1, "toBlock": 2 }))
+        );
+    }
+
+    #[test]
+    fn logs_block_range_error_data() {
+        let err = Web3Error::LogsBlockRangeExceeded(100, 1_000, 1_250);
+        assert_eq!(err.code(), ApiErrorCode::InvalidParams);
+        assert_eq!(
+            err.data(),
+            Some(serde_json::json!({
+                "maxBlockRange": 100,
+                "fromBlock": 1_000,
+                "toBlock": 1_250,
+                "suggestedRanges": [
+                    { "fromBlock": 1_000, "toBlock": 1_099 },
+                    { "fromBlock": 1_100, "toBlock": 1_199 },
+                    { "fromBlock": 1_200, "toBlock": 1_250 },
+                ],
+            }))
+        );
+
+        let err = Web3Error::LogsBlockRangeExceeded(1, 0, u32::MAX);
+        let data = err.data().unwrap();
+        assert_eq!(data["suggestedRanges"].as_array().unwrap().len(), 10);
+    }
 }
@@ -0,0 +1,43 @@
+use jsonrpsee::{core::RpcResult, proc_macros::rpc};
+use zksync_types::{api, Address, Nonce, H256};
+
+/// Operator-only methods. This namespace must not be exposed publicly.
+#[cfg_attr(
+    all(feature = "client", feature = "server"),
+    rpc(server, client, namespace = "admin")
+)]
+#[cfg_attr(
+    all(feature = "client", not(feature = "server")),
+    rpc(client, namespace = "admin")
+)]
+#[cfg_attr(
+    all(not(feature = "client"), feature = "server"),
+    rpc(server, namespace = "admin")
+)]
+pub trait AdminNamespace {
+    /// Removes a pending L2 transaction with the specified hash from the mempool.
+    /// Returns the hash of the cancelled transaction, or `null` if there is no such pending transaction
+    /// or it is already picked up by the state keeper.
+    #[method(name = "cancelTransaction")]
+    async fn cancel_transaction(
+        &self,
+        hash: H256,
+        reason: Option<String>,
+    ) -> RpcResult<Option<H256>>;
+
+    /// Removes a pending L2 transaction with the specified initiator and nonce from the mempool.
+    /// Returns the hash of the cancelled transaction, or `null` if there is no such pending transaction
+    /// or it is already picked up by the state keeper.
+    #[method(name = "cancelTransactionByNonce")]
+    async fn cancel_transaction_by_nonce(
+        &self,
+        initiator: Address,
+        nonce: Nonce,
+        reason: Option<String>,
+    ) -> RpcResult<Option<H256>>;
+
+    /// Returns the current resubmission policy of the Ethereum sender together with fees
+    /// of in-flight L1 transactions.
+    #[method(name = "getResubmissionPolicy")]
+    async fn get_resubmission_policy(&self) -> RpcResult<api::ResubmissionPolicyDetails>;
+}
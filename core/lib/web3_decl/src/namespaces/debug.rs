@@ -1,6 +1,6 @@
 use jsonrpsee::{core::RpcResult, proc_macros::rpc};
 use zksync_types::{
-    api::{BlockId, BlockNumber, DebugCall, ResultDebugCall, TracerConfig},
+    api::{BlockId, BlockNumber, DebugCall, DebugTrace, ResultDebugTrace, TracerConfig},
     transaction_request::CallRequest,
 };
 
@@ -24,13 +24,13 @@ pub trait DebugNamespace {
         &self,
         block: BlockNumber,
         options: Option<TracerConfig>,
-    ) -> RpcResult<Vec<ResultDebugCall>>;
+    ) -> RpcResult<Vec<ResultDebugTrace>>;
     #[method(name = "traceBlockByHash")]
     async fn trace_block_by_hash(
         &self,
         hash: H256,
         options: Option<TracerConfig>,
-    ) -> RpcResult<Vec<ResultDebugCall>>;
+    ) -> RpcResult<Vec<ResultDebugTrace>>;
     #[method(name = "traceCall")]
     async fn trace_call(
         &self,
@@ -43,5 +43,5 @@ pub trait DebugNamespace {
         &self,
         tx_hash: H256,
         options: Option<TracerConfig>,
-    ) -> RpcResult<Option<DebugCall>>;
+    ) -> RpcResult<Option<DebugTrace>>;
 }
@@ -4,16 +4,17 @@ use bigdecimal::BigDecimal;
 use jsonrpsee::{core::RpcResult, proc_macros::rpc};
 use zksync_types::{
     api::{
-        BlockDetails, BridgeAddresses, L1BatchDetails, L2ToL1LogProof, Proof, ProtocolVersion,
-        TransactionDetails,
+        BlockDetails, BridgeAddresses, ConsensusBlockCertificate, L1BatchCommitmentArtifacts,
+        L1BatchDetails, L1BatchPaymasterStats, L1BatchPubdata, L2ToL1LogProof, LogsPage, NodeStats,
+        PaymasterUsage, PriorityOpStatus, Proof, ProtocolVersion, TransactionDetails,
     },
-    fee::Fee,
+    fee::FeeEstimate,
     fee_model::FeeParams,
     transaction_request::CallRequest,
     Address, L1BatchNumber, MiniblockNumber, H256, U256, U64,
 };
 
-use crate::types::Token;
+use crate::types::{Filter, Token};
 
 #[cfg_attr(
     all(feature = "client", feature = "server"),
@@ -29,7 +30,7 @@ use crate::types::Token;
 )]
 pub trait ZksNamespace {
     #[method(name = "estimateFee")]
-    async fn estimate_fee(&self, req: CallRequest) -> RpcResult<Fee>;
+    async fn estimate_fee(&self, req: CallRequest) -> RpcResult<FeeEstimate>;
 
     #[method(name = "estimateGasL1ToL2")]
     async fn estimate_gas_l1_to_l2(&self, req: CallRequest) -> RpcResult<U256>;
@@ -118,4 +119,60 @@ pub trait ZksNamespace {
         keys: Vec<H256>,
         l1_batch_number: L1BatchNumber,
     ) -> RpcResult<Proof>;
+
+    #[method(name = "getConsensusBlockCertificate")]
+    async fn get_consensus_block_certificate(
+        &self,
+        block_number: MiniblockNumber,
+    ) -> RpcResult<Option<ConsensusBlockCertificate>>;
+
+    /// Returns statuses of priority operations emitted by the specified L1 transaction.
+    #[method(name = "getPriorityOpStatus")]
+    async fn get_priority_op_status(&self, l1_tx_hash: H256) -> RpcResult<Vec<PriorityOpStatus>>;
+
+    /// Returns commit / prove / execute L1 transactions (calldata, hashes and gas usage) for the specified L1 batch.
+    /// Returns `null` if the batch doesn't exist.
+    #[method(name = "getL1BatchCommitmentArtifacts")]
+    async fn get_l1_batch_commitment_artifacts(
+        &self,
+        batch: L1BatchNumber,
+    ) -> RpcResult<Option<L1BatchCommitmentArtifacts>>;
+
+    /// Returns pubdata of the specified L1 batch together with commitments to the blobs it was published in, if any.
+    /// Returns `null` if the batch doesn't exist, has no metadata yet, or predates boojum.
+    #[method(name = "getL1BatchPubdata")]
+    async fn get_l1_batch_pubdata(&self, batch: L1BatchNumber)
+        -> RpcResult<Option<L1BatchPubdata>>;
+
+    /// Returns paymaster usage aggregated for the specified L1 batch, or `null` if the batch is not aggregated yet.
+    #[method(name = "getL1BatchPaymasterStats")]
+    async fn get_l1_batch_paymaster_stats(
+        &self,
+        batch: L1BatchNumber,
+    ) -> RpcResult<Option<L1BatchPaymasterStats>>;
+
+    /// Returns paymasters with the most sponsored gas in the specified inclusive range of L1 batches.
+    #[method(name = "getTopPaymasters")]
+    async fn get_top_paymasters(
+        &self,
+        from_batch: L1BatchNumber,
+        to_batch: L1BatchNumber,
+        limit: Option<u32>,
+    ) -> RpcResult<Vec<PaymasterUsage>>;
+
+    /// Returns a page of logs matching the filter. Unlike `eth_getLogs`, the total number of matching logs
+    /// is not limited; to get the next page, pass `nextCursor` from the previous response as `cursor`.
+    #[method(name = "getLogsPaginated")]
+    async fn get_logs_paginated(
+        &self,
+        filter: Filter,
+        cursor: Option<String>,
+        limit: Option<u32>,
+    ) -> RpcResult<LogsPage>;
+
+    /// Returns aggregated node stats (sealed, committed, proven and executed L1 batches, Merkle tree lag,
+    /// mempool size etc.). Stats are collected in the background, so they may be slightly outdated;
+    /// returns `null` if stats were not collected yet.
+    #[method(name = "nodeStats")]
+    async fn get_node_stats(&self) -> RpcResult<Option<NodeStats>>;
 }
@@ -15,7 +15,10 @@ use itertools::unfold;
 use rlp::Rlp;
 use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 pub use zksync_types::{
-    api::{Block, BlockNumber, Log, TransactionReceipt, TransactionRequest},
+    api::{
+        Block, BlockNumber, L1BatchStage, L1BatchStatusUpdate, Log, TransactionReceipt,
+        TransactionRequest,
+    },
     vm_trace::{ContractSourceDebugInfo, VmDebugTrace, VmExecutionStep},
     web3::{
         ethabi,
@@ -343,6 +346,7 @@ fn topic_to_option<T>(topic: ethabi::Topic<T>) -> Option<Vec<T>> {
 pub enum PubSubResult {
     Header(BlockHeader),
     Log(Log),
+    L1BatchStatus(L1BatchStatusUpdate),
     TxHash(H256),
     Syncing(bool),
 }
@@ -3,7 +3,10 @@ use actix_web::{
     HttpResponse, Result as ActixResult,
 };
 use serde::Serialize;
-use zksync_types::{contract_verification_api::VerificationIncomingRequest, Address};
+use zksync_types::{
+    contract_verification_api::{SourceCodeData, VerificationIncomingRequest},
+    Address,
+};
 
 use super::{api_decl::RestApi, metrics::METRICS};
 
@@ -19,6 +22,20 @@ impl RestApi {
         if query.source_code_data.compiler_type() != query.compiler_versions.compiler_type() {
             return Err(HttpResponse::BadRequest().body("incorrect compiler versions"));
         }
+        match &query.source_code_data {
+            SourceCodeData::StandardJsonInput(input)
+                if !input
+                    .get("sources")
+                    .map_or(false, |sources| sources.is_object()) =>
+            {
+                return Err(HttpResponse::BadRequest()
+                    .body("standard JSON input must contain `sources` object"));
+            }
+            SourceCodeData::VyperMultiFile(sources) if sources.is_empty() => {
+                return Err(HttpResponse::BadRequest().body("no Vyper source files provided"));
+            }
+            _ => {}
+        }
 
         Ok(())
     }
@@ -15,20 +15,22 @@ use multivm::{
     VmInstance,
 };
 use zksync_dal::{ConnectionPool, SqlxError, StorageProcessor};
-use zksync_state::{PostgresStorage, ReadStorage, StorageView, WriteStorage};
+use zksync_state::{PostgresStorage, ReadStorage, StorageView, StorageWithOverrides, WriteStorage};
 use zksync_system_constants::{
     SYSTEM_CONTEXT_ADDRESS, SYSTEM_CONTEXT_CURRENT_L2_BLOCK_INFO_POSITION,
     SYSTEM_CONTEXT_CURRENT_TX_ROLLING_HASH_POSITION, ZKPORTER_IS_AVAILABLE,
 };
 use zksync_types::{
-    api,
+    api::{self, StateOverride},
     block::{pack_block_info, unpack_block_info, MiniblockHasher},
-    get_nonce_key,
+    get_code_key, get_known_code_key, get_nonce_key,
     utils::{decompose_full_nonce, nonces_to_full_nonce, storage_key_for_eth_balance},
     AccountTreeId, L1BatchNumber, MiniblockNumber, Nonce, ProtocolVersionId, StorageKey,
     Transaction, H256, U256,
 };
-use zksync_utils::{h256_to_u256, time::seconds_since_epoch, u256_to_h256};
+use zksync_utils::{
+    bytecode::hash_bytecode, h256_to_u256, time::seconds_since_epoch, u256_to_h256,
+};
 
 use super::{
     vm_metrics::{self, SandboxStage, SANDBOX_METRICS},
@@ -48,7 +50,7 @@ pub(super) fn apply_vm_in_sandbox<T>(
     tx: Transaction,
     block_args: BlockArgs,
     apply: impl FnOnce(
-        &mut VmInstance<StorageView<PostgresStorage<'_>>, HistoryDisabled>,
+        &mut VmInstance<StorageView<StorageWithOverrides<PostgresStorage<'_>>>, HistoryDisabled>,
         Transaction,
     ) -> T,
 ) -> T {
@@ -131,8 +133,23 @@ pub(super) fn apply_vm_in_sandbox<T>(
         }
     };
 
-    let storage = PostgresStorage::new(rt_handle.clone(), connection, state_l2_block_number, false)
-        .with_caches(shared_args.caches);
+    // The L2 block environment is the same for re-executed miniblocks; only the state differs.
+    let storage_l2_block_number = if block_args.state_before_block {
+        state_l2_block_number - 1
+    } else {
+        state_l2_block_number
+    };
+    let storage = PostgresStorage::new(
+        rt_handle.clone(),
+        connection,
+        storage_l2_block_number,
+        false,
+    )
+    .with_caches(shared_args.caches);
+    let mut storage = StorageWithOverrides::new(storage);
+    if let Some(state_override) = &execution_args.state_override {
+        apply_state_override(&mut storage, state_override);
+    }
     let mut storage_view = StorageView::new(storage);
 
     let storage_view_setup_started_at = Instant::now();
@@ -250,6 +267,45 @@ pub(super) fn apply_vm_in_sandbox<T>(
     result
 }
 
+/// Applies overrides of account balances, nonces, code and storage to the VM storage.
+fn apply_state_override<S: ReadStorage>(
+    storage: &mut StorageWithOverrides<S>,
+    state_override: &StateOverride,
+) {
+    for (address, account) in state_override {
+        if let Some(balance) = account.balance {
+            storage.set_value(storage_key_for_eth_balance(address), u256_to_h256(balance));
+        }
+        if let Some(nonce) = account.nonce {
+            let nonce_key = get_nonce_key(address);
+            let full_nonce = storage.read_value(&nonce_key);
+            let (_, deployment_nonce) = decompose_full_nonce(h256_to_u256(full_nonce));
+            let new_full_nonce = nonces_to_full_nonce(nonce, deployment_nonce);
+            storage.set_value(nonce_key, u256_to_h256(new_full_nonce));
+        }
+        if let Some(code) = &account.code {
+            // The bytecode must be marked as known, so that the VM can decommit it.
+            let code_hash = hash_bytecode(&code.0);
+            storage.set_value(get_code_key(address), code_hash);
+            storage.set_value(get_known_code_key(&code_hash), H256::from_low_u64_be(1));
+            storage.store_factory_dep(code_hash, code.0.clone());
+        }
+
+        let account_id = AccountTreeId::new(*address);
+        let (slots, replace_storage) = match (&account.state, &account.state_diff) {
+            (Some(state), _) => (state, true),
+            (None, Some(state_diff)) => (state_diff, false),
+            (None, None) => continue,
+        };
+        if replace_storage {
+            storage.clear_account_storage(account_id);
+        }
+        for (&slot, &value) in slots {
+            storage.set_value(StorageKey::new(account_id, slot), value);
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 struct StoredL2BlockInfo {
     pub l2_block_number: u32,
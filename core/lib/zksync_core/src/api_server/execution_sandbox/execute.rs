@@ -9,8 +9,8 @@ use multivm::{
 use tracing::{span, Level};
 use zksync_dal::ConnectionPool;
 use zksync_types::{
-    fee::TransactionExecutionMetrics, l2::L2Tx, ExecuteTransactionCommon, Nonce,
-    PackedEthSignature, Transaction, U256,
+    api::StateOverride, fee::TransactionExecutionMetrics, l2::L2Tx, ExecuteTransactionCommon,
+    Nonce, PackedEthSignature, Transaction, U256,
 };
 
 use super::{apply, vm_metrics, ApiTracer, BlockArgs, TxSharedArgs, VmPermit};
@@ -22,6 +22,8 @@ pub(crate) struct TxExecutionArgs {
     pub added_balance: U256,
     pub enforced_base_fee: Option<u64>,
     pub missed_storage_invocation_limit: usize,
+    /// State overrides applied to the VM storage before execution.
+    pub state_override: Option<StateOverride>,
 }
 
 impl TxExecutionArgs {
@@ -32,12 +34,14 @@ impl TxExecutionArgs {
             added_balance: U256::zero(),
             enforced_base_fee: Some(tx.common_data.fee.max_fee_per_gas.as_u64()),
             missed_storage_invocation_limit: usize::MAX,
+            state_override: None,
         }
     }
 
     fn for_eth_call(
         enforced_base_fee: u64,
         vm_execution_cache_misses_limit: Option<usize>,
+        state_override: Option<StateOverride>,
     ) -> Self {
         let missed_storage_invocation_limit = vm_execution_cache_misses_limit.unwrap_or(usize::MAX);
         Self {
@@ -46,6 +50,20 @@ impl TxExecutionArgs {
             added_balance: U256::zero(),
             enforced_base_fee: Some(enforced_base_fee),
             missed_storage_invocation_limit,
+            state_override,
+        }
+    }
+
+    /// Arguments for re-executing a transaction that was already included into a miniblock.
+    pub fn for_reexecution(base_fee: u64, vm_execution_cache_misses_limit: Option<usize>) -> Self {
+        let missed_storage_invocation_limit = vm_execution_cache_misses_limit.unwrap_or(usize::MAX);
+        Self {
+            execution_mode: TxExecutionMode::VerifyExecute,
+            enforced_nonce: None,
+            added_balance: U256::zero(),
+            enforced_base_fee: Some(base_fee),
+            missed_storage_invocation_limit,
+            state_override: None,
         }
     }
 
@@ -69,6 +87,7 @@ impl TxExecutionArgs {
             enforced_nonce: tx.nonce(),
             added_balance,
             enforced_base_fee: Some(base_fee),
+            state_override: None,
         }
     }
 }
@@ -80,11 +99,15 @@ pub(crate) async fn execute_tx_eth_call(
     mut tx: L2Tx,
     block_args: BlockArgs,
     vm_execution_cache_misses_limit: Option<usize>,
+    state_override: Option<StateOverride>,
     custom_tracers: Vec<ApiTracer>,
 ) -> VmExecutionResultAndLogs {
     let enforced_base_fee = tx.common_data.fee.max_fee_per_gas.as_u64();
-    let execution_args =
-        TxExecutionArgs::for_eth_call(enforced_base_fee, vm_execution_cache_misses_limit);
+    let execution_args = TxExecutionArgs::for_eth_call(
+        enforced_base_fee,
+        vm_execution_cache_misses_limit,
+        state_override,
+    );
 
     if tx.common_data.signature.is_empty() {
         tx.common_data.signature = PackedEthSignature::default().serialize_packed().into();
@@ -167,3 +190,60 @@ pub(crate) async fn execute_tx_in_sandbox(
         published_bytecodes.is_ok(),
     )
 }
+
+/// Re-executes transactions one after another in a single VM instance, so that each transaction
+/// observes the effects of the previous ones. Each transaction is executed with its own set of tracers.
+/// Returns execution results in the order of the supplied transactions.
+#[tracing::instrument(skip_all)]
+pub(crate) async fn reexecute_txs_in_sandbox(
+    vm_permit: VmPermit,
+    shared_args: TxSharedArgs,
+    execution_args: TxExecutionArgs,
+    connection_pool: ConnectionPool,
+    txs: Vec<(Transaction, Vec<ApiTracer>)>,
+    block_args: BlockArgs,
+) -> Vec<VmExecutionResultAndLogs> {
+    let Some((first_tx, _)) = txs.first() else {
+        return vec![];
+    };
+    // The first transaction is only used to set up the storage view; since nothing is enforced
+    // by `execution_args`, this doesn't influence execution.
+    let first_tx = first_tx.clone();
+    let missed_storage_invocation_limit = execution_args.missed_storage_invocation_limit;
+
+    tokio::task::spawn_blocking(move || {
+        let span = span!(Level::DEBUG, "reexecute_in_sandbox").entered();
+        let results = apply::apply_vm_in_sandbox(
+            vm_permit,
+            shared_args,
+            false,
+            &execution_args,
+            &connection_pool,
+            first_tx,
+            block_args,
+            |vm, _| {
+                txs.into_iter()
+                    .map(|(tx, custom_tracers)| {
+                        let storage_invocation_tracer =
+                            StorageInvocations::new(missed_storage_invocation_limit);
+                        let custom_tracers: Vec<_> = custom_tracers
+                            .into_iter()
+                            .map(|tracer| tracer.into_boxed())
+                            .chain(vec![storage_invocation_tracer.into_tracer_pointer()])
+                            .collect();
+                        let (_, result) = vm.inspect_transaction_with_bytecode_compression(
+                            custom_tracers.into(),
+                            tx,
+                            true,
+                        );
+                        result
+                    })
+                    .collect()
+            },
+        );
+        span.exit();
+        results
+    })
+    .await
+    .unwrap()
+}
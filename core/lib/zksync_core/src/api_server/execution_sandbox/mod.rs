@@ -10,7 +10,9 @@ use zksync_utils::bytecode::{compress_bytecode, hash_bytecode};
 use self::vm_metrics::SandboxStage;
 pub(super) use self::{
     error::SandboxExecutionError,
-    execute::{execute_tx_eth_call, execute_tx_in_sandbox, TxExecutionArgs},
+    execute::{
+        execute_tx_eth_call, execute_tx_in_sandbox, reexecute_txs_in_sandbox, TxExecutionArgs,
+    },
     tracers::ApiTracer,
     vm_metrics::{SubmitTxStage, SANDBOX_METRICS},
 };
@@ -214,6 +216,8 @@ pub(crate) struct BlockArgs {
     block_id: api::BlockId,
     resolved_block_number: MiniblockNumber,
     l1_batch_timestamp_s: Option<u64>,
+    /// If set, transactions are executed on top of the state *before* the resolved miniblock rather than after it.
+    state_before_block: bool,
 }
 
 impl BlockArgs {
@@ -223,6 +227,7 @@ impl BlockArgs {
             block_id,
             resolved_block_number,
             l1_batch_timestamp_s: None,
+            state_before_block: false,
         }
     }
 
@@ -260,6 +265,22 @@ impl BlockArgs {
             block_id,
             resolved_block_number,
             l1_batch_timestamp_s,
+            state_before_block: false,
+        }))
+    }
+
+    /// Loads block information to re-execute transactions of the specified sealed miniblock in its original
+    /// context, i.e., on top of the state after the previous miniblock and with the number, timestamp
+    /// and L1 batch of the re-executed miniblock.
+    pub async fn for_reexecution(
+        connection: &mut StorageProcessor<'_>,
+        miniblock_number: MiniblockNumber,
+    ) -> Result<Option<Self>, SqlxError> {
+        let block_id = api::BlockId::Number(api::BlockNumber::Number(miniblock_number.0.into()));
+        let block_args = Self::new(connection, block_id).await?;
+        Ok(block_args.map(|block_args| Self {
+            state_before_block: true,
+            ..block_args
         }))
     }
 
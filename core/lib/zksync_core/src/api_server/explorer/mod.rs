@@ -0,0 +1,375 @@
+//! Etherscan-compatible REST API allowing block explorers and tooling that expect etherscan semantics
+//! to query the node directly.
+//!
+//! Only a subset of etherscan endpoints is supported:
+//!
+//! - `module=account&action=txlist`: lists executed transactions sent from or to an account
+//! - `module=contract&action=getabi`: returns ABI of a verified contract
+//! - `module=contract&action=getsourcecode`: returns source code and compilation settings of a verified contract
+//!
+//! As with etherscan, all endpoints are served at `GET /api`, and errors are returned as successful HTTP responses
+//! with `status` set to `"0"`.
+
+use std::{net::SocketAddr, ops};
+
+use anyhow::Context as _;
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing, Json, Router,
+};
+use serde::{Deserialize, Serialize};
+use tokio::sync::watch;
+use zksync_config::configs::api::ExplorerApiConfig;
+use zksync_dal::{transactions_web3_dal::AccountTransaction, ConnectionPool};
+use zksync_types::{
+    contract_verification_api::{SourceCodeData, VerificationInfo},
+    Address, L2ChainId, MiniblockNumber,
+};
+
+#[cfg(test)]
+mod tests;
+
+/// Query parameters of an etherscan API request. Parameter names follow etherscan.
+#[derive(Debug, Default, Deserialize)]
+struct ApiRequest {
+    module: String,
+    action: String,
+    address: Option<String>,
+    startblock: Option<u32>,
+    endblock: Option<u32>,
+    page: Option<usize>,
+    offset: Option<usize>,
+    sort: Option<String>,
+}
+
+impl ApiRequest {
+    fn address(&self) -> Result<Address, ApiError> {
+        let address = self.address.as_deref().ok_or(ApiError::InvalidAddress)?;
+        address.parse().map_err(|_| ApiError::InvalidAddress)
+    }
+
+    fn is_ascending(&self) -> Result<bool, ApiError> {
+        match self.sort.as_deref() {
+            None | Some("asc") => Ok(true),
+            Some("desc") => Ok(false),
+            Some(_) => Err(ApiError::InvalidSort),
+        }
+    }
+}
+
+/// Etherscan API response envelope.
+#[derive(Debug, Serialize, Deserialize)]
+struct ApiResponse {
+    status: String,
+    message: String,
+    result: serde_json::Value,
+}
+
+impl ApiResponse {
+    fn ok(result: impl Serialize) -> Self {
+        Self::new(true, "OK", result)
+    }
+
+    fn new(is_success: bool, message: &str, result: impl Serialize) -> Self {
+        Self {
+            status: if is_success { "1" } else { "0" }.to_owned(),
+            message: message.to_owned(),
+            result: serde_json::to_value(result).expect("failed serializing API result"),
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+enum ApiError {
+    #[error("Error! Missing or invalid module / action name")]
+    UnknownAction,
+    #[error("Error! Invalid address format")]
+    InvalidAddress,
+    #[error("Error! Invalid sort order, must be `asc` or `desc`")]
+    InvalidSort,
+    #[error("Result window is too large, PageNo x Offset size must be less than or equal to {0}")]
+    ResultWindowTooLarge(usize),
+    #[error("Contract source code not verified")]
+    NotVerified,
+    #[error("Internal error")]
+    Internal(#[from] anyhow::Error),
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        if let Self::Internal(err) = &self {
+            tracing::error!("Internal error processing explorer API request: {err:#}");
+            let response = ApiResponse::new(false, "NOTOK", self.to_string());
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(response)).into_response();
+        }
+        Json(ApiResponse::new(false, "NOTOK", self.to_string())).into_response()
+    }
+}
+
+/// Transaction in the `txlist` response. All values are formatted as strings, as in etherscan.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ExplorerTransaction {
+    block_number: String,
+    time_stamp: String,
+    hash: String,
+    nonce: String,
+    block_hash: String,
+    transaction_index: String,
+    from: String,
+    to: String,
+    value: String,
+    gas: String,
+    gas_price: String,
+    is_error: String,
+    #[serde(rename = "txreceipt_status")]
+    tx_receipt_status: String,
+    input: String,
+    contract_address: String,
+    gas_used: String,
+    confirmations: String,
+    method_id: String,
+}
+
+impl ExplorerTransaction {
+    fn new(account_tx: AccountTransaction, sealed_miniblock: MiniblockNumber) -> Self {
+        let tx = account_tx.transaction;
+        let block_number = tx.block_number.unwrap_or_default().as_u64();
+        let confirmations = u64::from(sealed_miniblock.0).saturating_sub(block_number) + 1;
+        let method_id = tx.input.0.get(..4).unwrap_or_default();
+        Self {
+            block_number: block_number.to_string(),
+            time_stamp: account_tx.timestamp.to_string(),
+            hash: format!("{:?}", tx.hash),
+            nonce: tx.nonce.to_string(),
+            block_hash: tx
+                .block_hash
+                .map_or_else(String::new, |hash| format!("{hash:?}")),
+            transaction_index: tx.transaction_index.unwrap_or_default().to_string(),
+            from: tx
+                .from
+                .map_or_else(String::new, |address| format!("{address:?}")),
+            to: tx
+                .to
+                .map_or_else(String::new, |address| format!("{address:?}")),
+            value: tx.value.to_string(),
+            gas: tx.gas.to_string(),
+            gas_price: tx.gas_price.unwrap_or_default().to_string(),
+            is_error: if account_tx.is_failed { "1" } else { "0" }.to_owned(),
+            tx_receipt_status: if account_tx.is_failed { "0" } else { "1" }.to_owned(),
+            input: format!("0x{}", hex::encode(&tx.input.0)),
+            // Deployed contract addresses are not tracked per transaction.
+            contract_address: String::new(),
+            gas_used: account_tx.gas_used.to_string(),
+            confirmations: confirmations.to_string(),
+            method_id: format!("0x{}", hex::encode(method_id)),
+        }
+    }
+}
+
+/// Contract information in the `getsourcecode` response.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ContractSourceCode {
+    source_code: String,
+    #[serde(rename = "ABI")]
+    abi: String,
+    contract_name: String,
+    compiler_version: String,
+    zk_compiler_version: String,
+    optimization_used: String,
+    optimizer_mode: String,
+    constructor_arguments: String,
+}
+
+impl ContractSourceCode {
+    fn not_verified() -> Self {
+        Self {
+            source_code: String::new(),
+            abi: ApiError::NotVerified.to_string(),
+            contract_name: String::new(),
+            compiler_version: String::new(),
+            zk_compiler_version: String::new(),
+            optimization_used: String::new(),
+            optimizer_mode: String::new(),
+            constructor_arguments: String::new(),
+        }
+    }
+
+    fn new(info: VerificationInfo) -> Self {
+        let request = info.request.req;
+        let source_code = match request.source_code_data {
+            SourceCodeData::SolSingleFile(code) | SourceCodeData::YulSingleFile(code) => code,
+            // Etherscan wraps standard JSON input in double braces.
+            SourceCodeData::StandardJsonInput(input) => {
+                format!("{{{}}}", serde_json::Value::Object(input))
+            }
+            SourceCodeData::VyperMultiFile(sources) => {
+                serde_json::to_string(&sources).expect("failed serializing Vyper sources")
+            }
+        };
+        Self {
+            source_code,
+            abi: info.artifacts.abi.to_string(),
+            contract_name: request.contract_name,
+            compiler_version: request.compiler_versions.compiler_version(),
+            zk_compiler_version: request.compiler_versions.zk_compiler_version(),
+            optimization_used: if request.optimization_used { "1" } else { "0" }.to_owned(),
+            optimizer_mode: request.optimizer_mode.unwrap_or_default(),
+            constructor_arguments: hex::encode(&request.constructor_arguments.0),
+        }
+    }
+}
+
+/// Etherscan-compatible explorer API server.
+#[derive(Debug, Clone)]
+pub struct ExplorerApi {
+    pool: ConnectionPool,
+    chain_id: L2ChainId,
+    max_result_window: usize,
+}
+
+impl ExplorerApi {
+    pub fn new(pool: ConnectionPool, chain_id: L2ChainId, config: &ExplorerApiConfig) -> Self {
+        Self {
+            pool,
+            chain_id,
+            max_result_window: config.max_result_window,
+        }
+    }
+
+    async fn handle_request(
+        State(this): State<Self>,
+        Query(request): Query<ApiRequest>,
+    ) -> Result<Json<ApiResponse>, ApiError> {
+        this.process_request(&request).await.map(Json)
+    }
+
+    async fn process_request(&self, request: &ApiRequest) -> Result<ApiResponse, ApiError> {
+        match (request.module.as_str(), request.action.as_str()) {
+            ("account", "txlist") => self.account_transactions(request).await,
+            ("contract", "getabi") => self.contract_abi(request).await,
+            ("contract", "getsourcecode") => self.contract_source_code(request).await,
+            _ => Err(ApiError::UnknownAction),
+        }
+    }
+
+    async fn account_transactions(&self, request: &ApiRequest) -> Result<ApiResponse, ApiError> {
+        let address = request.address()?;
+        let ascending = request.is_ascending()?;
+        let page = request.page.unwrap_or(1).max(1);
+        let limit = request
+            .offset
+            .filter(|&offset| offset > 0)
+            .unwrap_or(self.max_result_window);
+        let window_end = page
+            .checked_mul(limit)
+            .filter(|&end| end <= self.max_result_window)
+            .ok_or(ApiError::ResultWindowTooLarge(self.max_result_window))?;
+        let miniblocks: ops::RangeInclusive<MiniblockNumber> =
+            MiniblockNumber(request.startblock.unwrap_or(0))
+                ..=MiniblockNumber(request.endblock.unwrap_or(u32::MAX));
+
+        let mut storage = self
+            .pool
+            .access_storage_tagged("api")
+            .await
+            .context("access_storage_tagged()")?;
+        let transactions = storage
+            .transactions_web3_dal()
+            .get_account_transactions(
+                address,
+                miniblocks,
+                window_end - limit,
+                limit,
+                ascending,
+                self.chain_id,
+            )
+            .await
+            .context("get_account_transactions()")?;
+        if transactions.is_empty() {
+            return Ok(ApiResponse::new(
+                false,
+                "No transactions found",
+                Vec::<()>::new(),
+            ));
+        }
+        let sealed_miniblock = storage
+            .blocks_web3_dal()
+            .get_sealed_miniblock_number()
+            .await
+            .context("get_sealed_miniblock_number()")?;
+        drop(storage);
+
+        let transactions: Vec<_> = transactions
+            .into_iter()
+            .map(|tx| ExplorerTransaction::new(tx, sealed_miniblock))
+            .collect();
+        Ok(ApiResponse::ok(transactions))
+    }
+
+    async fn verification_info(
+        &self,
+        request: &ApiRequest,
+    ) -> Result<Option<VerificationInfo>, ApiError> {
+        let address = request.address()?;
+        let mut storage = self
+            .pool
+            .access_storage_tagged("api")
+            .await
+            .context("access_storage_tagged()")?;
+        let info = storage
+            .contract_verification_dal()
+            .get_contract_verification_info(address)
+            .await
+            .context("get_contract_verification_info()")?;
+        Ok(info)
+    }
+
+    async fn contract_abi(&self, request: &ApiRequest) -> Result<ApiResponse, ApiError> {
+        let info = self
+            .verification_info(request)
+            .await?
+            .ok_or(ApiError::NotVerified)?;
+        // Etherscan returns ABI as a serialized JSON string.
+        Ok(ApiResponse::ok(info.artifacts.abi.to_string()))
+    }
+
+    async fn contract_source_code(&self, request: &ApiRequest) -> Result<ApiResponse, ApiError> {
+        let source_code = match self.verification_info(request).await? {
+            Some(info) => ContractSourceCode::new(info),
+            None => ContractSourceCode::not_verified(),
+        };
+        Ok(ApiResponse::ok([source_code]))
+    }
+
+    /// Runs the explorer API server until a stop signal is received.
+    pub async fn run_server(
+        self,
+        bind_address: SocketAddr,
+        mut stop_receiver: watch::Receiver<bool>,
+    ) -> anyhow::Result<()> {
+        tracing::debug!("Starting explorer API server on {bind_address}");
+        let app = Router::new()
+            .route("/api", routing::get(Self::handle_request))
+            .with_state(self);
+
+        axum::Server::try_bind(&bind_address)
+            .with_context(|| format!("Failed binding explorer API server to {bind_address}"))?
+            .serve(app.into_make_service())
+            .with_graceful_shutdown(async move {
+                if stop_receiver.changed().await.is_err() {
+                    tracing::warn!(
+                        "Stop signal sender for explorer API server was dropped without sending a signal"
+                    );
+                }
+                tracing::info!("Stop signal received, explorer API server is shutting down");
+            })
+            .await
+            .context("Explorer API server failed")?;
+        tracing::info!("Explorer API server shut down");
+        Ok(())
+    }
+}
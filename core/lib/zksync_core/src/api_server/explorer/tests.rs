@@ -0,0 +1,284 @@
+//! Tests for the explorer API.
+
+use assert_matches::assert_matches;
+use chrono::Utc;
+use zksync_types::{
+    contract_verification_api::{
+        CompilationArtifacts, CompilerVersions, VerificationIncomingRequest, VerificationRequest,
+    },
+    fee::TransactionExecutionMetrics,
+    tx::{
+        tx_execution_info::{ExecutionMetrics, TxExecutionStatus},
+        TransactionExecutionResult,
+    },
+    Bytes,
+};
+
+use super::*;
+use crate::{
+    genesis::{ensure_genesis_state, GenesisParams},
+    utils::testonly::{create_l2_transaction, create_miniblock},
+};
+
+fn test_api(pool: ConnectionPool) -> ExplorerApi {
+    let config = ExplorerApiConfig {
+        port: 0,
+        max_result_window: 100,
+        statement_timeout_sec: 10,
+    };
+    ExplorerApi::new(pool, L2ChainId::default(), &config)
+}
+
+fn request(module: &str, action: &str, address: Address) -> ApiRequest {
+    ApiRequest {
+        module: module.to_owned(),
+        action: action.to_owned(),
+        address: Some(format!("{address:?}")),
+        ..ApiRequest::default()
+    }
+}
+
+async fn prepare_storage(pool: &ConnectionPool) {
+    let mut storage = pool.access_storage().await.unwrap();
+    ensure_genesis_state(&mut storage, L2ChainId::default(), &GenesisParams::mock())
+        .await
+        .unwrap();
+}
+
+const RECIPIENT: Address = Address::repeat_byte(0x11);
+
+/// Stores miniblocks #1..=`count`, each with a single L2 transaction sent to [`RECIPIENT`].
+/// Returns hashes of the stored transactions.
+async fn store_transactions(pool: &ConnectionPool, count: u32) -> Vec<String> {
+    let mut storage = pool.access_storage().await.unwrap();
+    let mut hashes = vec![];
+    for number in 1..=count {
+        let mut tx = create_l2_transaction(1, 2);
+        tx.execute.contract_address = RECIPIENT;
+        hashes.push(format!("{:?}", tx.hash()));
+        storage
+            .transactions_dal()
+            .insert_transaction_l2(tx.clone(), TransactionExecutionMetrics::default())
+            .await;
+        storage
+            .blocks_dal()
+            .insert_miniblock(&create_miniblock(number))
+            .await
+            .unwrap();
+        let execution_result = TransactionExecutionResult {
+            hash: tx.hash(),
+            transaction: tx.into(),
+            execution_info: ExecutionMetrics::default(),
+            execution_status: TxExecutionStatus::Success,
+            refunded_gas: 0,
+            operator_suggested_refund: 0,
+            compressed_bytecodes: vec![],
+            call_traces: vec![],
+            revert_reason: None,
+        };
+        storage
+            .transactions_dal()
+            .mark_txs_as_executed_in_miniblock(
+                MiniblockNumber(number),
+                &[execution_result],
+                1.into(),
+            )
+            .await;
+    }
+    hashes
+}
+
+fn parse_transactions(response: ApiResponse) -> Vec<ExplorerTransaction> {
+    assert_eq!(response.status, "1", "{response:?}");
+    serde_json::from_value(response.result).unwrap()
+}
+
+#[tokio::test]
+async fn listing_account_transactions() {
+    let pool = ConnectionPool::test_pool().await;
+    prepare_storage(&pool).await;
+    let hashes = store_transactions(&pool, 3).await;
+    let api = test_api(pool);
+
+    let response = api
+        .process_request(&request("account", "txlist", RECIPIENT))
+        .await
+        .unwrap();
+    let transactions = parse_transactions(response);
+    let tx_hashes: Vec<_> = transactions.iter().map(|tx| tx.hash.clone()).collect();
+    assert_eq!(tx_hashes, hashes);
+    let tx = &transactions[0];
+    assert_eq!(tx.block_number, "1");
+    assert_eq!(tx.to, format!("{RECIPIENT:?}"));
+    assert_eq!(tx.is_error, "0");
+    assert_eq!(tx.tx_receipt_status, "1");
+    assert_eq!(tx.confirmations, "3");
+    assert_eq!(tx.method_id, "0x"); // Transactions have empty calldata
+
+    let response = api
+        .process_request(&ApiRequest {
+            endblock: Some(2),
+            sort: Some("desc".to_owned()),
+            ..request("account", "txlist", RECIPIENT)
+        })
+        .await
+        .unwrap();
+    let tx_hashes: Vec<_> = parse_transactions(response)
+        .into_iter()
+        .map(|tx| tx.hash)
+        .collect();
+    assert_eq!(tx_hashes, [hashes[1].clone(), hashes[0].clone()]);
+
+    let response = api
+        .process_request(&ApiRequest {
+            startblock: Some(4),
+            ..request("account", "txlist", RECIPIENT)
+        })
+        .await
+        .unwrap();
+    assert_eq!(response.status, "0");
+    assert_eq!(response.message, "No transactions found");
+    assert_eq!(response.result, serde_json::json!([]));
+}
+
+#[tokio::test]
+async fn paginating_account_transactions() {
+    let pool = ConnectionPool::test_pool().await;
+    prepare_storage(&pool).await;
+    let hashes = store_transactions(&pool, 3).await;
+    let api = test_api(pool);
+
+    let response = api
+        .process_request(&ApiRequest {
+            page: Some(2),
+            offset: Some(2),
+            ..request("account", "txlist", RECIPIENT)
+        })
+        .await
+        .unwrap();
+    let transactions = parse_transactions(response);
+    assert_eq!(transactions.len(), 1);
+    assert_eq!(transactions[0].hash, hashes[2]);
+
+    let response = api
+        .process_request(&ApiRequest {
+            page: Some(2),
+            offset: Some(1),
+            sort: Some("desc".to_owned()),
+            ..request("account", "txlist", RECIPIENT)
+        })
+        .await
+        .unwrap();
+    let transactions = parse_transactions(response);
+    assert_eq!(transactions.len(), 1);
+    assert_eq!(transactions[0].hash, hashes[1]);
+
+    let err = api
+        .process_request(&ApiRequest {
+            page: Some(11),
+            offset: Some(10),
+            ..request("account", "txlist", RECIPIENT)
+        })
+        .await
+        .unwrap_err();
+    assert_matches!(err, ApiError::ResultWindowTooLarge(100));
+
+    let err = api
+        .process_request(&ApiRequest {
+            sort: Some("random".to_owned()),
+            ..request("account", "txlist", RECIPIENT)
+        })
+        .await
+        .unwrap_err();
+    assert_matches!(err, ApiError::InvalidSort);
+}
+
+#[tokio::test]
+async fn invalid_requests() {
+    let pool = ConnectionPool::test_pool().await;
+    let api = test_api(pool);
+
+    let err = api
+        .process_request(&request("account", "balance", Address::zero()))
+        .await
+        .unwrap_err();
+    assert_matches!(err, ApiError::UnknownAction);
+
+    let err = api
+        .process_request(&ApiRequest {
+            address: Some("0x123".to_owned()),
+            ..request("contract", "getabi", Address::zero())
+        })
+        .await
+        .unwrap_err();
+    assert_matches!(err, ApiError::InvalidAddress);
+}
+
+#[tokio::test]
+async fn getting_contract_abi_and_source_code() {
+    let pool = ConnectionPool::test_pool().await;
+    let contract_address = Address::repeat_byte(0x23);
+    let abi = serde_json::json!([{ "type": "function", "name": "test" }]);
+    let verification_info = VerificationInfo {
+        request: VerificationRequest {
+            id: 1,
+            req: VerificationIncomingRequest {
+                contract_address,
+                source_code_data: SourceCodeData::SolSingleFile("contract Test {}".to_owned()),
+                contract_name: "Test".to_owned(),
+                compiler_versions: CompilerVersions::Solc {
+                    compiler_zksolc_version: "v1.3.18".to_owned(),
+                    compiler_solc_version: "0.8.20".to_owned(),
+                },
+                optimization_used: true,
+                optimizer_mode: None,
+                constructor_arguments: Bytes(vec![1, 2]),
+                is_system: false,
+            },
+        },
+        artifacts: CompilationArtifacts {
+            bytecode: vec![0; 32],
+            abi: abi.clone(),
+        },
+        verified_at: Utc::now(),
+    };
+    pool.access_storage()
+        .await
+        .unwrap()
+        .contract_verification_dal()
+        .save_verification_info(verification_info)
+        .await
+        .unwrap();
+    let api = test_api(pool);
+
+    let response = api
+        .process_request(&request("contract", "getabi", contract_address))
+        .await
+        .unwrap();
+    assert_eq!(response.status, "1");
+    assert_eq!(response.result, serde_json::json!(abi.to_string()));
+
+    let response = api
+        .process_request(&request("contract", "getsourcecode", contract_address))
+        .await
+        .unwrap();
+    let [source_code]: [ContractSourceCode; 1] = serde_json::from_value(response.result).unwrap();
+    assert_eq!(source_code.source_code, "contract Test {}");
+    assert_eq!(source_code.contract_name, "Test");
+    assert_eq!(source_code.compiler_version, "0.8.20");
+    assert_eq!(source_code.optimization_used, "1");
+    assert_eq!(source_code.constructor_arguments, "0102");
+
+    let unverified_address = Address::repeat_byte(0x42);
+    let err = api
+        .process_request(&request("contract", "getabi", unverified_address))
+        .await
+        .unwrap_err();
+    assert_matches!(err, ApiError::NotVerified);
+    let response = api
+        .process_request(&request("contract", "getsourcecode", unverified_address))
+        .await
+        .unwrap();
+    let [source_code]: [ContractSourceCode; 1] = serde_json::from_value(response.result).unwrap();
+    assert_eq!(source_code.abi, "Contract source code not verified");
+}
@@ -1,12 +1,20 @@
-use std::{collections::HashSet, net::SocketAddr, sync::Arc, time::Duration};
+use std::{
+    collections::HashSet,
+    fmt,
+    net::SocketAddr,
+    sync::{Arc, RwLock},
+    time::Duration,
+};
 
 use axum::{extract::State, http::StatusCode, routing::get, Json, Router};
 use tokio::sync::watch;
 use zksync_health_check::{AppHealth, CheckHealth};
 
-type SharedHealthchecks = Arc<[Box<dyn CheckHealth>]>;
+/// Health checks shared with the server. Checks can be replaced while the server is running.
+type SharedHealthchecks = Arc<RwLock<Arc<[Box<dyn CheckHealth>]>>>;
 
 async fn check_health(health_checks: State<SharedHealthchecks>) -> (StatusCode, Json<AppHealth>) {
+    let health_checks = health_checks.read().unwrap().clone();
     let response = AppHealth::new(&health_checks).await;
     let response_code = if response.is_ready() {
         StatusCode::OK
@@ -16,13 +24,40 @@ async fn check_health(health_checks: State<SharedHealthchecks>) -> (StatusCode,
     (response_code, Json(response))
 }
 
-async fn run_server(
-    bind_address: &SocketAddr,
-    health_checks: Vec<Box<dyn CheckHealth>>,
-    mut stop_receiver: watch::Receiver<bool>,
-) {
+async fn get_log_directives() -> (StatusCode, String) {
+    match vlog::log_directives_handle() {
+        Some(handle) => (StatusCode::OK, handle.current()),
+        None => (
+            StatusCode::NOT_FOUND,
+            "Log directives cannot be changed at runtime".to_owned(),
+        ),
+    }
+}
+
+/// Replaces log directives (in the `RUST_LOG` format) with the ones provided in the request body.
+async fn set_log_directives(directives: String) -> (StatusCode, String) {
+    let Some(handle) = vlog::log_directives_handle() else {
+        return (
+            StatusCode::NOT_FOUND,
+            "Log directives cannot be changed at runtime".to_owned(),
+        );
+    };
+    let directives = directives.trim();
+    match handle.set(directives) {
+        Ok(()) => {
+            tracing::info!("Log directives changed to `{directives}`");
+            (StatusCode::OK, handle.current())
+        }
+        Err(err) => (
+            StatusCode::BAD_REQUEST,
+            format!("Invalid log directives: {err}"),
+        ),
+    }
+}
+
+fn health_check_names(health_checks: &[Box<dyn CheckHealth>]) -> HashSet<&'static str> {
     let mut health_check_names = HashSet::with_capacity(health_checks.len());
-    for check in &health_checks {
+    for check in health_checks {
         let health_check_name = check.name();
         if !health_check_names.insert(health_check_name) {
             tracing::warn!(
@@ -31,59 +66,169 @@ async fn run_server(
             );
         }
     }
-    tracing::debug!(
-        "Starting healthcheck server with checks {health_check_names:?} on {bind_address}"
-    );
+    health_check_names
+}
 
-    let health_checks = SharedHealthchecks::from(health_checks);
-    let app = Router::new()
+fn healthcheck_router(health_checks: SharedHealthchecks) -> Router {
+    Router::new()
         .route("/health", get(check_health))
-        .with_state(health_checks);
+        .with_state(health_checks)
+}
+
+/// Router for the admin server. Unlike `/health`, these endpoints change node behavior, so they must not
+/// be exposed on the healthcheck port, which is commonly reachable by load balancers etc.
+fn admin_router() -> Router {
+    Router::new().route(
+        "/log_directives",
+        get(get_log_directives).put(set_log_directives),
+    )
+}
 
+async fn run_server(
+    server_name: &str,
+    bind_address: &SocketAddr,
+    app: Router,
+    mut stop_receiver: watch::Receiver<bool>,
+) {
     axum::Server::bind(bind_address)
         .serve(app.into_make_service())
         .with_graceful_shutdown(async move {
             if stop_receiver.changed().await.is_err() {
-                tracing::warn!("Stop signal sender for healthcheck server was dropped without sending a signal");
+                tracing::warn!("Stop signal sender for {server_name} server was dropped without sending a signal");
             }
-            tracing::info!("Stop signal received, healthcheck server is shutting down");
+            tracing::info!("Stop signal received, {server_name} server is shutting down");
         })
         .await
-        .expect("Healthcheck server failed");
-    tracing::info!("Healthcheck server shut down");
+        .unwrap_or_else(|err| panic!("{server_name} server failed: {err}"));
+    tracing::info!("{server_name} server shut down");
 }
 
-#[derive(Debug)]
 pub struct HealthCheckHandle {
     server: tokio::task::JoinHandle<()>,
+    admin_server: Option<tokio::task::JoinHandle<()>>,
+    health_checks: SharedHealthchecks,
     stop_sender: watch::Sender<bool>,
 }
 
+impl fmt::Debug for HealthCheckHandle {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter
+            .debug_struct("HealthCheckHandle")
+            .field("server", &self.server)
+            .field("admin_server", &self.admin_server)
+            .finish_non_exhaustive()
+    }
+}
+
 impl HealthCheckHandle {
-    pub fn spawn_server(addr: SocketAddr, healthchecks: Vec<Box<dyn CheckHealth>>) -> Self {
+    /// Spawns the healthcheck server on `addr`. If `admin_addr` is specified, also spawns the admin server
+    /// (which allows e.g. changing log directives at runtime) on it; this address should not be publicly reachable.
+    pub fn spawn_server(
+        addr: SocketAddr,
+        admin_addr: Option<SocketAddr>,
+        healthchecks: Vec<Box<dyn CheckHealth>>,
+    ) -> Self {
         let (stop_sender, stop_receiver) = watch::channel(false);
-        let server = tokio::spawn(async move {
-            run_server(&addr, healthchecks, stop_receiver).await;
+        let health_checks: SharedHealthchecks = Arc::new(RwLock::new(healthchecks.into()));
+        let health_check_names = health_check_names(&health_checks.read().unwrap());
+        tracing::debug!("Starting healthcheck server with checks {health_check_names:?} on {addr}");
+        let server = tokio::spawn({
+            let app = healthcheck_router(Arc::clone(&health_checks));
+            let stop_receiver = stop_receiver.clone();
+            async move {
+                run_server("healthcheck", &addr, app, stop_receiver).await;
+            }
+        });
+        let admin_server = admin_addr.map(|admin_addr| {
+            tracing::debug!("Starting admin server on {admin_addr}");
+            tokio::spawn(async move {
+                run_server("admin", &admin_addr, admin_router(), stop_receiver).await;
+            })
         });
 
         Self {
             server,
+            admin_server,
+            health_checks,
             stop_sender,
         }
     }
 
+    /// Replaces health checks served by the server. This allows to keep the server running
+    /// when node components are restarted.
+    pub fn replace_checks(&self, healthchecks: Vec<Box<dyn CheckHealth>>) {
+        let health_check_names = health_check_names(&healthchecks);
+        tracing::debug!("Replacing healthcheck server checks with {health_check_names:?}");
+        *self.health_checks.write().unwrap() = healthchecks.into();
+    }
+
     pub async fn stop(self) {
         // Paradoxically, `hyper` server is quite slow to shut down if it isn't queried during shutdown:
         // <https://github.com/hyperium/hyper/issues/3188>. It is thus recommended to set a timeout for shutdown.
         const GRACEFUL_SHUTDOWN_WAIT: Duration = Duration::from_secs(10);
 
         self.stop_sender.send(true).ok();
-        let server_result = tokio::time::timeout(GRACEFUL_SHUTDOWN_WAIT, self.server).await;
-        if let Ok(server_result) = server_result {
-            // Propagate potential panics from the server task.
-            server_result.unwrap();
-        } else {
-            tracing::debug!("Timed out {GRACEFUL_SHUTDOWN_WAIT:?} waiting for healthcheck server to gracefully shut down");
+        let servers = [Some(self.server), self.admin_server];
+        for server in servers.into_iter().flatten() {
+            let server_result = tokio::time::timeout(GRACEFUL_SHUTDOWN_WAIT, server).await;
+            if let Ok(server_result) = server_result {
+                // Propagate potential panics from the server task.
+                server_result.unwrap();
+            } else {
+                tracing::debug!("Timed out {GRACEFUL_SHUTDOWN_WAIT:?} waiting for healthcheck server to gracefully shut down");
+            }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use axum::{body::Body, http::Request};
+    use tower::ServiceExt;
+
+    use super::*;
+
+    async fn send_request(
+        app: Router,
+        method: &str,
+        uri: &str,
+        body: &str,
+    ) -> (StatusCode, String) {
+        let request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .body(Body::from(body.to_owned()))
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        let status = response.status();
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        (status, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    #[tokio::test]
+    async fn log_directives_are_not_exposed_on_healthcheck_port() {
+        let health_checks: SharedHealthchecks = Arc::new(RwLock::new(Arc::from([])));
+        let app = healthcheck_router(health_checks);
+
+        let (status, _) = send_request(app.clone(), "GET", "/health", "").await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, _) = send_request(app.clone(), "GET", "/log_directives", "").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        let (status, _) = send_request(app, "PUT", "/log_directives", "trace").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn log_directives_on_admin_server() {
+        let app = admin_router();
+        let (status, body) = send_request(app.clone(), "GET", "/log_directives", "").await;
+        // The observability subsystem is not initialized in tests.
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert!(body.contains("cannot be changed at runtime"), "{body}");
+
+        let (status, _) = send_request(app.clone(), "PUT", "/log_directives", "trace").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        let (status, _) = send_request(app, "GET", "/health", "").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+}
@@ -16,7 +16,7 @@ use zksync_merkle_tree::NoVersionError;
 use zksync_types::{L1BatchNumber, H256, U256};
 
 use self::metrics::{MerkleTreeApiMethod, API_METRICS};
-use crate::metadata_calculator::{AsyncTreeReader, MerkleTreeInfo};
+use crate::metadata_calculator::{AsyncTreeReader, LazyAsyncTreeReader, MerkleTreeInfo};
 
 mod metrics;
 #[cfg(test)]
@@ -61,11 +61,11 @@ impl TreeEntryWithProof {
 }
 
 #[derive(Debug)]
-enum TreeApiError {
+enum TreeApiServerError {
     NoTreeVersion(NoVersionError),
 }
 
-impl IntoResponse for TreeApiError {
+impl IntoResponse for TreeApiServerError {
     fn into_response(self) -> Response {
         let (status, title, detail) = match self {
             Self::NoTreeVersion(err) => {
@@ -84,18 +84,33 @@ impl IntoResponse for TreeApiError {
     }
 }
 
+/// Errors returned by [`TreeApiClient`].
+#[derive(Debug, thiserror::Error)]
+pub(crate) enum TreeApiError {
+    /// The requested L1 batch is not present in the tree, either because it isn't processed yet,
+    /// or because it was pruned.
+    #[error("L1 batch #{0} is not available in the Merkle tree")]
+    NoVersion(L1BatchNumber),
+    /// The tree is not initialized yet (e.g., it is being recovered from a snapshot).
+    #[error("Merkle tree is not ready yet")]
+    NotReady,
+    #[error(transparent)]
+    Internal(#[from] anyhow::Error),
+}
+
 /// Client accessing Merkle tree API.
 #[async_trait]
-pub(crate) trait TreeApiClient {
+pub(crate) trait TreeApiClient: 'static + Send + Sync + fmt::Debug {
     /// Obtains general information about the tree.
     async fn get_info(&self) -> anyhow::Result<MerkleTreeInfo>;
 
     /// Obtains proofs for the specified `hashed_keys` at the specified tree version (= L1 batch number).
+    /// Proofs for missing keys are exclusion proofs, i.e., have zero `value` and `index`.
     async fn get_proofs(
         &self,
         l1_batch_number: L1BatchNumber,
         hashed_keys: Vec<U256>,
-    ) -> anyhow::Result<Vec<TreeEntryWithProof>>;
+    ) -> Result<Vec<TreeEntryWithProof>, TreeApiError>;
 }
 
 /// In-memory client implementation.
@@ -109,10 +124,28 @@ impl TreeApiClient for AsyncTreeReader {
         &self,
         l1_batch_number: L1BatchNumber,
         hashed_keys: Vec<U256>,
-    ) -> anyhow::Result<Vec<TreeEntryWithProof>> {
+    ) -> Result<Vec<TreeEntryWithProof>, TreeApiError> {
         self.get_proofs_inner(l1_batch_number, hashed_keys)
             .await
-            .map_err(Into::into)
+            .map_err(|_| TreeApiError::NoVersion(l1_batch_number))
+    }
+}
+
+/// In-memory client implementation for a tree that may not be initialized yet.
+#[async_trait]
+impl TreeApiClient for LazyAsyncTreeReader {
+    async fn get_info(&self) -> anyhow::Result<MerkleTreeInfo> {
+        let reader = self.read().context("Merkle tree is not ready yet")?;
+        reader.get_info().await
+    }
+
+    async fn get_proofs(
+        &self,
+        l1_batch_number: L1BatchNumber,
+        hashed_keys: Vec<U256>,
+    ) -> Result<Vec<TreeEntryWithProof>, TreeApiError> {
+        let reader = self.read().ok_or(TreeApiError::NotReady)?;
+        reader.get_proofs(l1_batch_number, hashed_keys).await
     }
 }
 
@@ -156,7 +189,7 @@ impl TreeApiClient for TreeApiHttpClient {
         &self,
         l1_batch_number: L1BatchNumber,
         hashed_keys: Vec<U256>,
-    ) -> anyhow::Result<Vec<TreeEntryWithProof>> {
+    ) -> Result<Vec<TreeEntryWithProof>, TreeApiError> {
         let response = self
             .inner
             .post(&self.proofs_url)
@@ -167,6 +200,9 @@ impl TreeApiClient for TreeApiHttpClient {
             .send()
             .await
             .with_context(|| format!("Failed requesting proofs for L1 batch #{l1_batch_number}"))?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Err(TreeApiError::NoVersion(l1_batch_number));
+        }
         let response = response.error_for_status().with_context(|| {
             format!("Requesting proofs for L1 batch #{l1_batch_number} returned non-OK response")
         })?;
@@ -200,12 +236,12 @@ impl AsyncTreeReader {
     async fn get_proofs_handler(
         State(this): State<Self>,
         Json(request): Json<TreeProofsRequest>,
-    ) -> Result<Json<TreeProofsResponse>, TreeApiError> {
+    ) -> Result<Json<TreeProofsResponse>, TreeApiServerError> {
         let latency = API_METRICS.latency[&MerkleTreeApiMethod::GetProofs].start();
         let entries = this
             .get_proofs_inner(request.l1_batch_number, request.hashed_keys)
             .await
-            .map_err(TreeApiError::NoTreeVersion)?;
+            .map_err(TreeApiServerError::NoTreeVersion)?;
         let response = TreeProofsResponse { entries };
         latency.observe();
         Ok(Json(response))
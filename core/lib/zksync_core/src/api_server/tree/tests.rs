@@ -2,6 +2,7 @@
 
 use std::net::Ipv4Addr;
 
+use assert_matches::assert_matches;
 use tempfile::TempDir;
 use zksync_dal::ConnectionPool;
 
@@ -60,21 +61,47 @@ async fn merkle_tree_api() {
         .get_proofs(L1BatchNumber(10), vec![])
         .await
         .unwrap_err();
-    let err = format!("{err:?}");
-    // Check that the error message contains all necessary info to troubleshoot it.
-    assert!(
-        err.contains("Requesting proofs for L1 batch #10 returned non-OK response"),
-        "{}",
-        err
-    );
-    assert!(err.contains("404 Not Found"), "{}", err);
-    assert!(
-        err.contains(&format!("http://{local_addr}/proofs")),
-        "{}",
-        err
-    );
+    assert_matches!(err, TreeApiError::NoVersion(L1BatchNumber(10)));
 
     // Stop the calculator and the tree API server.
     stop_sender.send_replace(true);
     api_server_task.await.unwrap().unwrap();
 }
+
+#[tokio::test]
+async fn lazy_tree_reader_api() {
+    let pool = ConnectionPool::test_pool().await;
+    let temp_dir = TempDir::new().expect("failed get temporary directory for RocksDB");
+    let (calculator, _) = setup_calculator(temp_dir.path(), &pool).await;
+    reset_db_state(&pool, 5).await;
+
+    let tree_reader = calculator.lazy_tree_reader();
+    let err = tree_reader
+        .get_proofs(L1BatchNumber(5), vec![])
+        .await
+        .unwrap_err();
+    assert_matches!(err, TreeApiError::NotReady);
+
+    run_calculator(calculator, pool).await;
+
+    let hashed_keys: Vec<_> = gen_storage_logs(20..30, 1)[0]
+        .iter()
+        .map(|log| log.key.hashed_key_u256())
+        .chain([U256::zero()])
+        .collect();
+    let proofs = tree_reader
+        .get_proofs(L1BatchNumber(5), hashed_keys)
+        .await
+        .unwrap();
+    assert_eq!(proofs.len(), 11);
+    assert!(proofs[..10].iter().all(|proof| proof.index != 0));
+    // The last key is missing from the tree, so the proof should be an exclusion proof.
+    assert_eq!(proofs[10].index, 0);
+    assert!(proofs[10].value.is_zero());
+
+    let err = tree_reader
+        .get_proofs(L1BatchNumber(10), vec![])
+        .await
+        .unwrap_err();
+    assert_matches!(err, TreeApiError::NoVersion(L1BatchNumber(10)));
+}
@@ -1,18 +1,20 @@
 //! Helper module to submit transactions into the zkSync Network.
 
-use std::{cmp, sync::Arc, time::Instant};
+use std::{cmp, future::Future, sync::Arc, time::Instant};
 
 use multivm::{
     interface::VmExecutionResultAndLogs,
     utils::{adjust_pubdata_price_for_tx, derive_base_fee_and_gas_per_pubdata, derive_overhead},
     vm_latest::constants::{BLOCK_GAS_LIMIT, MAX_PUBDATA_PER_BLOCK},
 };
+use tokio::sync::watch;
 use zksync_config::configs::{api::Web3JsonRpcConfig, chain::StateKeeperConfig};
 use zksync_contracts::BaseSystemContracts;
 use zksync_dal::{transactions_dal::L2TxSubmissionResult, ConnectionPool};
 use zksync_state::PostgresStorageCaches;
 use zksync_types::{
-    fee::{Fee, TransactionExecutionMetrics},
+    api::StateOverride,
+    fee::{Fee, FeeBreakdown, FeeEstimate, TransactionExecutionMetrics},
     fee_model::BatchFeeInput,
     get_code_key, get_intrinsic_constants,
     l2::{error::TxCheckError::TxDuplication, L2Tx},
@@ -23,6 +25,7 @@ use zksync_types::{
 };
 use zksync_utils::h256_to_u256;
 
+pub use self::policy::{DbTxSubmissionPolicy, TxSubmissionPolicy, TxSubmissionRules};
 pub(super) use self::{proxy::TxProxy, result::SubmitTxError};
 use super::execution_sandbox::execute_tx_in_sandbox;
 use crate::{
@@ -38,6 +41,7 @@ use crate::{
     state_keeper::seal_criteria::{ConditionalSealer, NoopSealer, SealData},
 };
 
+mod policy;
 mod proxy;
 mod result;
 
@@ -137,6 +141,8 @@ pub struct TxSenderBuilder {
     proxy: Option<TxProxy>,
     /// Batch sealer used to check whether transaction can be executed by the sequencer.
     sealer: Option<Arc<dyn ConditionalSealer>>,
+    /// Policy restricting submitted transactions.
+    submission_policy: Option<Arc<dyn TxSubmissionPolicy>>,
 }
 
 impl TxSenderBuilder {
@@ -147,6 +153,7 @@ impl TxSenderBuilder {
             master_connection_pool: None,
             proxy: None,
             sealer: None,
+            submission_policy: None,
         }
     }
 
@@ -155,6 +162,11 @@ impl TxSenderBuilder {
         self
     }
 
+    pub fn with_submission_policy(mut self, policy: Arc<dyn TxSubmissionPolicy>) -> Self {
+        self.submission_policy = Some(policy);
+        self
+    }
+
     pub fn with_tx_proxy(mut self, main_node_url: &str) -> Self {
         self.proxy = Some(TxProxy::new(main_node_url));
         self
@@ -190,6 +202,7 @@ impl TxSenderBuilder {
             vm_concurrency_limiter,
             storage_caches,
             sealer,
+            submission_policy: self.submission_policy,
         }))
     }
 }
@@ -203,6 +216,7 @@ pub struct TxSenderConfig {
     pub fee_account_addr: Address,
     pub gas_price_scale_factor: f64,
     pub max_nonce_ahead: u32,
+    pub tx_replacement_price_bump_percent: Option<u32>,
     pub max_allowed_l2_tx_gas_limit: u32,
     pub fair_l2_gas_price: u64,
     pub vm_execution_cache_misses_limit: Option<usize>,
@@ -220,6 +234,7 @@ impl TxSenderConfig {
             fee_account_addr: state_keeper_config.fee_account_addr,
             gas_price_scale_factor: web3_json_config.gas_price_scale_factor,
             max_nonce_ahead: web3_json_config.max_nonce_ahead,
+            tx_replacement_price_bump_percent: web3_json_config.tx_replacement_price_bump_percent,
             max_allowed_l2_tx_gas_limit: state_keeper_config.max_allowed_l2_tx_gas_limit,
             fair_l2_gas_price: state_keeper_config.fair_l2_gas_price,
             vm_execution_cache_misses_limit: web3_json_config.vm_execution_cache_misses_limit,
@@ -245,6 +260,8 @@ pub struct TxSenderInner {
     storage_caches: PostgresStorageCaches,
     /// Batch sealer used to check whether transaction can be executed by the sequencer.
     sealer: Arc<dyn ConditionalSealer>,
+    /// Policy restricting submitted transactions.
+    submission_policy: Option<Arc<dyn TxSubmissionPolicy>>,
 }
 
 #[derive(Clone)]
@@ -265,10 +282,31 @@ impl TxSender {
         self.0.storage_caches.clone()
     }
 
-    #[tracing::instrument(skip(self, tx))]
+    /// Returns a task removing transactions proxied to the main node from the local cache once they are synced back.
+    /// Returns `None` if this sender doesn't proxy transactions (i.e., it's not running on an external node).
+    pub fn proxy_cache_updater(
+        &self,
+        pool: ConnectionPool,
+        stop_receiver: watch::Receiver<bool>,
+    ) -> Option<impl Future<Output = anyhow::Result<()>>> {
+        let proxy = self.0.proxy.as_ref()?;
+        Some(proxy.run_account_nonce_sweeper(pool, stop_receiver))
+    }
+
+    #[tracing::instrument(skip(self, tx), fields(tx.hash = ?tx.hash()))]
     pub async fn submit_tx(&self, tx: L2Tx) -> Result<L2TxSubmissionResult, SubmitTxError> {
+        vlog::record_current_span(vlog::TraceKey::Transaction(tx.hash().0));
         let stage_latency = SANDBOX_METRICS.submit_tx[&SubmitTxStage::Validate].start();
         self.validate_tx(&tx).await?;
+        if let Some(policy) = &self.0.submission_policy {
+            if let Err(reason) = policy.check_tx(&tx).await {
+                tracing::info!(
+                    "Submitted tx {:?} is rejected by policy: {reason}",
+                    tx.hash()
+                );
+                return Err(SubmitTxError::RejectedByPolicy(reason));
+            }
+        }
         stage_latency.observe();
 
         let stage_latency = SANDBOX_METRICS.submit_tx[&SubmitTxStage::DryRun].start();
@@ -330,13 +368,14 @@ impl TxSender {
         if let Some(proxy) = &self.0.proxy {
             // We're running an external node: we have to proxy the transaction to the main node.
             // But before we do that, save the tx to cache in case someone will request it
-            // Before it reaches the main node.
-            proxy.save_tx(tx.hash(), tx.clone()).await;
-            proxy.submit_tx(&tx).await?;
-            // Now, after we are sure that the tx is on the main node, remove it from cache
-            // since we don't want to store txs that might have been replaced or otherwise removed
-            // from the mempool.
-            proxy.forget_tx(tx.hash()).await;
+            // before it reaches the main node. The tx is kept in the cache until it's synced back,
+            // so that pending nonces and `eth_getTransactionByHash` are consistent with the main node.
+            proxy.save_tx(tx.clone()).await;
+            if let Err(err) = proxy.submit_tx(&tx).await {
+                // The tx was rejected by the main node, so it will never be synced back.
+                proxy.forget_tx(tx.hash()).await;
+                return Err(err.into());
+            }
             SANDBOX_METRICS.submit_tx[&SubmitTxStage::TxProxy].observe(stage_started_at.elapsed());
             APP_METRICS.processed_txs[&TxStage::Proxied].inc();
             return Ok(L2TxSubmissionResult::Proxied);
@@ -359,7 +398,11 @@ impl TxSender {
             .await
             .unwrap()
             .transactions_dal()
-            .insert_transaction_l2(tx, tx_metrics)
+            .insert_transaction_l2_with_price_bump(
+                tx,
+                tx_metrics,
+                self.0.sender_config.tx_replacement_price_bump_percent,
+            )
             .await;
 
         APP_METRICS.processed_txs[&TxStage::Mempool(submission_res_handle)].inc();
@@ -371,6 +414,14 @@ impl TxSender {
                 nonce,
             )),
             L2TxSubmissionResult::Duplicate => Err(SubmitTxError::IncorrectTx(TxDuplication(hash))),
+            L2TxSubmissionResult::ReplacementUnderpriced => {
+                Err(SubmitTxError::ReplacementUnderpriced(
+                    self.0
+                        .sender_config
+                        .tx_replacement_price_bump_percent
+                        .unwrap_or_default(),
+                ))
+            }
             _ => {
                 SANDBOX_METRICS.submit_tx[&SubmitTxStage::DbInsert]
                     .observe(stage_started_at.elapsed());
@@ -417,7 +468,9 @@ impl TxSender {
                 tx.hash(),
                 tx.common_data.fee.max_fee_per_gas
             );
-            return Err(SubmitTxError::MaxFeePerGasTooLow);
+            return Err(SubmitTxError::MaxFeePerGasTooLow(
+                self.0.sender_config.fair_l2_gas_price.into(),
+            ));
         }
         if tx.common_data.fee.max_fee_per_gas < tx.common_data.fee.max_priority_fee_per_gas {
             tracing::info!(
@@ -447,6 +500,9 @@ impl TxSender {
         // We still double-check the nonce manually
         // to make sure that only the correct nonce is submitted and the transaction's hashes never repeat
         self.validate_account_nonce(tx).await?;
+        // The replacement check is repeated atomically on insertion; checking it here allows to reject
+        // underpriced replacements without executing them in the sandbox.
+        self.validate_replacement_fees(tx).await?;
         // Even though without enough balance the tx will not pass anyway
         // we check the user for enough balance explicitly here for better DevEx.
         self.validate_enough_balance(tx).await?;
@@ -476,11 +532,46 @@ impl TxSender {
         }
     }
 
+    async fn validate_replacement_fees(&self, tx: &L2Tx) -> Result<(), SubmitTxError> {
+        let Some(price_bump_percent) = self.0.sender_config.tx_replacement_price_bump_percent
+        else {
+            return Ok(());
+        };
+        let mut connection = self
+            .0
+            .replica_connection_pool
+            .access_storage_read_after_write("api")
+            .await
+            .unwrap();
+        let pending_tx = connection
+            .transactions_dal()
+            .get_replaceable_l2_tx_fees(tx.initiator_account(), tx.common_data.nonce)
+            .await
+            .unwrap();
+        let Some(pending_tx) = pending_tx else {
+            return Ok(());
+        };
+
+        // Resubmitted transactions are reported as duplicates on insertion.
+        let fee = &tx.common_data.fee;
+        if pending_tx.hash == tx.hash()
+            || pending_tx.can_be_replaced_by(
+                fee.max_fee_per_gas,
+                fee.max_priority_fee_per_gas,
+                price_bump_percent,
+            )
+        {
+            Ok(())
+        } else {
+            Err(SubmitTxError::ReplacementUnderpriced(price_bump_percent))
+        }
+    }
+
     async fn get_expected_nonce(&self, tx: &L2Tx) -> Nonce {
         let mut connection = self
             .0
             .replica_connection_pool
-            .access_storage_tagged("api")
+            .access_storage_read_after_write("api")
             .await
             .unwrap();
 
@@ -625,7 +716,7 @@ impl TxSender {
         mut tx: Transaction,
         estimated_fee_scale_factor: f64,
         acceptable_overestimation: u32,
-    ) -> Result<Fee, SubmitTxError> {
+    ) -> Result<FeeEstimate, SubmitTxError> {
         let estimation_started_at = Instant::now();
 
         let mut connection = self
@@ -832,11 +923,26 @@ impl TxSender {
                 }
             };
 
-        Ok(Fee {
+        // Pubdata published during execution is paid from the transaction body gas; we attribute it to pubdata
+        // in the breakdown. This is an approximation, since the published pubdata may be affected by the gas limit.
+        let execution_pubdata_gas = u64::from(tx_metrics.pubdata_published)
+            .saturating_mul(gas_per_pubdata_byte)
+            .min(u64::from(tx_body_gas_limit)) as u32;
+        let breakdown = FeeBreakdown {
+            compute_gas: (tx_body_gas_limit - execution_pubdata_gas).into(),
+            pubdata_gas: (gas_for_bytecodes_pubdata + execution_pubdata_gas).into(),
+            overhead_gas: overhead.into(),
+        };
+
+        let fee = Fee {
             max_fee_per_gas: base_fee.into(),
             max_priority_fee_per_gas: 0u32.into(),
             gas_limit: full_gas_limit.into(),
             gas_per_pubdata_limit: gas_per_pubdata_byte.into(),
+        };
+        Ok(FeeEstimate {
+            fee,
+            breakdown: Some(breakdown),
         })
     }
 
@@ -844,6 +950,7 @@ impl TxSender {
         &self,
         block_args: BlockArgs,
         tx: L2Tx,
+        state_override: Option<StateOverride>,
     ) -> Result<Vec<u8>, SubmitTxError> {
         let vm_permit = self.0.vm_concurrency_limiter.acquire().await;
         let vm_permit = vm_permit.ok_or(SubmitTxError::ServerShuttingDown)?;
@@ -856,6 +963,7 @@ impl TxSender {
             tx,
             block_args,
             vm_execution_cache_misses_limit,
+            state_override,
             vec![],
         )
         .await
@@ -0,0 +1,242 @@
+//! Policies restricting transactions accepted by `TxSender` (e.g., for compliance purposes).
+
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+
+use anyhow::Context as _;
+use async_trait::async_trait;
+use tokio::sync::watch;
+use zksync_dal::{
+    tx_submission_rules_dal::{TxSubmissionRule, TxSubmissionRuleAction, TxSubmissionRuleTarget},
+    ConnectionPool,
+};
+use zksync_types::l2::L2Tx;
+
+/// Policy checked by `TxSender` for each submitted transaction before executing it in the sandbox.
+#[async_trait]
+pub trait TxSubmissionPolicy: fmt::Debug + Send + Sync {
+    /// Checks whether the transaction may be submitted. Returns a human-readable reason if it may not;
+    /// the reason is returned to the submitter.
+    async fn check_tx(&self, tx: &L2Tx) -> Result<(), String>;
+}
+
+/// Allow and deny lists for a single [`TxSubmissionRuleTarget`].
+#[derive(Debug, Default)]
+struct RuleLists {
+    allowed: HashSet<Vec<u8>>,
+    denied: HashSet<Vec<u8>>,
+}
+
+impl RuleLists {
+    fn is_allowed(&self, value: &[u8]) -> bool {
+        !self.denied.contains(value) && (self.allowed.is_empty() || self.allowed.contains(value))
+    }
+}
+
+/// Snapshot of transaction submission rules.
+///
+/// For each target (sender, called contract, calldata selector), a transaction is rejected if the target value
+/// is denied, or if there are allow rules for the target and the value is not allowed by any of them.
+/// Transactions without a calldata selector (e.g., plain transfers) are not checked against selector rules.
+#[derive(Debug, Default)]
+pub struct TxSubmissionRules {
+    lists: HashMap<TxSubmissionRuleTarget, RuleLists>,
+}
+
+impl TxSubmissionRules {
+    pub fn new(rules: impl IntoIterator<Item = TxSubmissionRule>) -> Self {
+        let mut lists = HashMap::<_, RuleLists>::new();
+        for rule in rules {
+            let target_lists = lists.entry(rule.target).or_default();
+            match rule.action {
+                TxSubmissionRuleAction::Allow => target_lists.allowed.insert(rule.value),
+                TxSubmissionRuleAction::Deny => target_lists.denied.insert(rule.value),
+            };
+        }
+        Self { lists }
+    }
+
+    fn check_value(&self, target: TxSubmissionRuleTarget, value: &[u8]) -> Result<(), String> {
+        let Some(lists) = self.lists.get(&target) else {
+            return Ok(());
+        };
+        if lists.is_allowed(value) {
+            Ok(())
+        } else {
+            Err(format!("{target} 0x{} is not allowed", hex::encode(value)))
+        }
+    }
+
+    pub fn check_tx(&self, tx: &L2Tx) -> Result<(), String> {
+        self.check_value(
+            TxSubmissionRuleTarget::Sender,
+            tx.initiator_account().as_bytes(),
+        )?;
+        self.check_value(
+            TxSubmissionRuleTarget::Contract,
+            tx.recipient_account().as_bytes(),
+        )?;
+        if let Some(selector) = tx.execute.calldata.get(..4) {
+            self.check_value(TxSubmissionRuleTarget::Selector, selector)?;
+        }
+        Ok(())
+    }
+}
+
+/// [`TxSubmissionPolicy`] based on the rules stored in Postgres. Rules are periodically reloaded,
+/// so that they can be changed without restarting the server.
+#[derive(Debug, Default)]
+pub struct DbTxSubmissionPolicy {
+    rules: RwLock<Arc<TxSubmissionRules>>,
+}
+
+impl DbTxSubmissionPolicy {
+    const RELOAD_INTERVAL: Duration = Duration::from_secs(10);
+
+    /// Reloads rules from the database.
+    pub async fn reload(&self, pool: &ConnectionPool) -> anyhow::Result<()> {
+        let mut storage = pool.access_storage_tagged("api").await?;
+        let rules = storage
+            .tx_submission_rules_dal()
+            .get_rules()
+            .await
+            .context("failed loading tx submission rules")?;
+        drop(storage);
+
+        let rules = Arc::new(TxSubmissionRules::new(rules));
+        *self
+            .rules
+            .write()
+            .expect("tx submission rules are poisoned") = rules;
+        Ok(())
+    }
+
+    /// Periodically reloads rules until a stop signal is received.
+    pub async fn run(
+        self: Arc<Self>,
+        pool: ConnectionPool,
+        mut stop_receiver: watch::Receiver<bool>,
+    ) -> anyhow::Result<()> {
+        loop {
+            if *stop_receiver.borrow() {
+                tracing::info!(
+                    "Stop signal received, tx submission rules updater is shutting down"
+                );
+                return Ok(());
+            }
+            if let Err(err) = self.reload(&pool).await {
+                // Keep using the previously loaded rules.
+                tracing::warn!("Failed reloading tx submission rules: {err:#}");
+            }
+            tokio::time::timeout(Self::RELOAD_INTERVAL, stop_receiver.changed())
+                .await
+                .ok();
+        }
+    }
+}
+
+#[async_trait]
+impl TxSubmissionPolicy for DbTxSubmissionPolicy {
+    async fn check_tx(&self, tx: &L2Tx) -> Result<(), String> {
+        let rules = self
+            .rules
+            .read()
+            .expect("tx submission rules are poisoned")
+            .clone();
+        rules.check_tx(tx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zksync_types::{Address, Execute};
+
+    use super::*;
+    use crate::utils::testonly::create_l2_transaction;
+
+    fn rule(
+        action: TxSubmissionRuleAction,
+        target: TxSubmissionRuleTarget,
+        value: &[u8],
+    ) -> TxSubmissionRule {
+        TxSubmissionRule {
+            id: 0,
+            action,
+            target,
+            value: value.to_vec(),
+            comment: None,
+        }
+    }
+
+    fn transaction(sender: Address, contract: Address, calldata: Vec<u8>) -> L2Tx {
+        let mut tx = create_l2_transaction(10, 100);
+        tx.common_data.initiator_address = sender;
+        tx.execute = Execute {
+            contract_address: contract,
+            calldata,
+            value: 0.into(),
+            factory_deps: None,
+        };
+        tx
+    }
+
+    #[test]
+    fn checking_transactions_against_rules() {
+        let sanctioned = Address::repeat_byte(1);
+        let contract = Address::repeat_byte(2);
+        let other_contract = Address::repeat_byte(3);
+        let sender = Address::repeat_byte(4);
+        let rules = TxSubmissionRules::new([
+            rule(
+                TxSubmissionRuleAction::Deny,
+                TxSubmissionRuleTarget::Sender,
+                sanctioned.as_bytes(),
+            ),
+            rule(
+                TxSubmissionRuleAction::Allow,
+                TxSubmissionRuleTarget::Contract,
+                contract.as_bytes(),
+            ),
+            rule(
+                TxSubmissionRuleAction::Deny,
+                TxSubmissionRuleTarget::Selector,
+                &[0xde, 0xad, 0xbe, 0xef],
+            ),
+        ]);
+
+        rules
+            .check_tx(&transaction(sender, contract, vec![1, 2, 3, 4, 5]))
+            .unwrap();
+        rules
+            .check_tx(&transaction(sender, contract, vec![]))
+            .unwrap();
+
+        let err = rules
+            .check_tx(&transaction(sanctioned, contract, vec![]))
+            .unwrap_err();
+        assert!(err.starts_with("sender 0x0101"), "{err}");
+        let err = rules
+            .check_tx(&transaction(sender, other_contract, vec![]))
+            .unwrap_err();
+        assert!(err.starts_with("contract 0x0303"), "{err}");
+        let err = rules
+            .check_tx(&transaction(
+                sender,
+                contract,
+                vec![0xde, 0xad, 0xbe, 0xef, 0],
+            ))
+            .unwrap_err();
+        assert_eq!(err, "selector 0xdeadbeef is not allowed");
+    }
+
+    #[test]
+    fn empty_rules_allow_everything() {
+        let rules = TxSubmissionRules::default();
+        let tx = transaction(Address::repeat_byte(1), Address::repeat_byte(2), vec![0; 4]);
+        rules.check_tx(&tx).unwrap();
+    }
+}
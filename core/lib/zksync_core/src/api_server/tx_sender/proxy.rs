@@ -1,10 +1,17 @@
-use std::collections::HashMap;
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
-use tokio::sync::RwLock;
+use anyhow::Context as _;
+use tokio::sync::{watch, RwLock};
+use zksync_dal::ConnectionPool;
 use zksync_types::{
-    api::{BlockId, Transaction, TransactionDetails, TransactionId},
+    api::{BlockId, BlockNumber, Transaction, TransactionDetails, TransactionId},
     l2::L2Tx,
-    H256,
+    utils::decompose_full_nonce,
+    Address, Nonce, H256,
 };
 use zksync_web3_decl::{
     jsonrpsee::http_client::{HttpClient, HttpClientBuilder},
@@ -12,11 +19,231 @@ use zksync_web3_decl::{
     RpcResult,
 };
 
+#[derive(Debug)]
+struct CachedTx {
+    tx: L2Tx,
+    inserted_at: Instant,
+}
+
+#[derive(Debug, Default)]
+struct TxCacheInner {
+    tx_cache: HashMap<H256, CachedTx>,
+    /// Hashes of cached transactions indexed by initiator account and nonce. There may be several transactions
+    /// with the same nonce (e.g., replacement transactions).
+    tx_hashes_by_account: HashMap<Address, BTreeMap<Nonce, HashSet<H256>>>,
+    /// Hashes of cached transactions ordered by insertion time.
+    tx_hashes_by_insertion: BTreeSet<(Instant, H256)>,
+}
+
+impl TxCacheInner {
+    fn push(&mut self, tx: L2Tx, now: Instant, capacity: usize) {
+        let tx_hash = tx.hash();
+        self.remove_tx(&tx_hash);
+        while self.tx_cache.len() >= capacity {
+            let Some(&(_, oldest_hash)) = self.tx_hashes_by_insertion.first() else {
+                break;
+            };
+            tracing::debug!("Evicting proxied transaction {oldest_hash:?} from full cache");
+            self.remove_tx(&oldest_hash);
+        }
+
+        self.tx_hashes_by_account
+            .entry(tx.initiator_account())
+            .or_default()
+            .entry(tx.common_data.nonce)
+            .or_default()
+            .insert(tx_hash);
+        self.tx_hashes_by_insertion.insert((now, tx_hash));
+        self.tx_cache.insert(
+            tx_hash,
+            CachedTx {
+                tx,
+                inserted_at: now,
+            },
+        );
+    }
+
+    fn remove_tx(&mut self, tx_hash: &H256) {
+        let Some(CachedTx { tx, inserted_at }) = self.tx_cache.remove(tx_hash) else {
+            return;
+        };
+        self.tx_hashes_by_insertion.remove(&(inserted_at, *tx_hash));
+        let account = tx.initiator_account();
+        let Some(nonces) = self.tx_hashes_by_account.get_mut(&account) else {
+            return;
+        };
+        let nonce = tx.common_data.nonce;
+        if let Some(hashes) = nonces.get_mut(&nonce) {
+            hashes.remove(tx_hash);
+            if hashes.is_empty() {
+                nonces.remove(&nonce);
+            }
+        }
+        if nonces.is_empty() {
+            self.tx_hashes_by_account.remove(&account);
+        }
+    }
+
+    /// Removes transactions inserted before `threshold`. Returns the number of removed transactions.
+    fn remove_inserted_before(&mut self, threshold: Instant) -> usize {
+        let mut removed_count = 0;
+        while let Some(&(inserted_at, tx_hash)) = self.tx_hashes_by_insertion.first() {
+            if inserted_at >= threshold {
+                break;
+            }
+            self.remove_tx(&tx_hash);
+            removed_count += 1;
+        }
+        removed_count
+    }
+
+    /// Removes transactions for `account` with nonces below `synced_nonce`. Returns the number of removed transactions.
+    fn remove_synced(&mut self, account: Address, synced_nonce: Nonce) -> usize {
+        let Some(nonces) = self.tx_hashes_by_account.get(&account) else {
+            return 0;
+        };
+        let stale_hashes: Vec<_> = nonces
+            .range(..synced_nonce)
+            .flat_map(|(_, hashes)| hashes.iter().copied())
+            .collect();
+        for tx_hash in &stale_hashes {
+            self.remove_tx(tx_hash);
+        }
+        stale_hashes.len()
+    }
+}
+
+/// Cache of transactions proxied to the main node that are not synced back to the external node yet.
+///
+/// The cache is bounded both in size and in time: if the cache is full, the oldest transactions are evicted,
+/// and transactions not synced back within [`Self::TTL`] (e.g., ones dropped from the main node mempool)
+/// are removed by the updater task.
+#[derive(Debug, Clone)]
+struct TxCache {
+    inner: Arc<RwLock<TxCacheInner>>,
+    capacity: usize,
+    ttl: Duration,
+}
+
+impl Default for TxCache {
+    fn default() -> Self {
+        Self {
+            inner: Arc::default(),
+            capacity: Self::CAPACITY,
+            ttl: Self::TTL,
+        }
+    }
+}
+
+impl TxCache {
+    /// Maximum number of cached transactions.
+    const CAPACITY: usize = 10_000;
+    /// Maximum time a transaction is retained in the cache.
+    const TTL: Duration = Duration::from_secs(600);
+
+    async fn push(&self, tx: L2Tx) {
+        let mut inner = self.inner.write().await;
+        inner.push(tx, Instant::now(), self.capacity);
+    }
+
+    async fn get_tx(&self, tx_hash: H256) -> Option<L2Tx> {
+        let inner = self.inner.read().await;
+        inner.tx_cache.get(&tx_hash).map(|cached| cached.tx.clone())
+    }
+
+    async fn get_nonces_for_account(&self, account_address: Address) -> BTreeSet<Nonce> {
+        let inner = self.inner.read().await;
+        inner
+            .tx_hashes_by_account
+            .get(&account_address)
+            .map(|nonces| nonces.keys().copied().collect())
+            .unwrap_or_default()
+    }
+
+    async fn remove_tx(&self, tx_hash: H256) {
+        self.inner.write().await.remove_tx(&tx_hash);
+    }
+
+    /// Removes expired transactions and transactions with nonces below the account nonces in the synced state.
+    async fn reconcile(&self, pool: &ConnectionPool) -> anyhow::Result<()> {
+        let accounts: Vec<_> = {
+            let mut inner = self.inner.write().await;
+            if let Some(threshold) = Instant::now().checked_sub(self.ttl) {
+                let expired_count = inner.remove_inserted_before(threshold);
+                if expired_count > 0 {
+                    tracing::info!(
+                        "Removed {expired_count} proxied transactions not synced from the main node within {:?}",
+                        self.ttl
+                    );
+                }
+            }
+            inner.tx_hashes_by_account.keys().copied().collect()
+        };
+        if accounts.is_empty() {
+            return Ok(());
+        }
+
+        let mut storage = pool.access_storage_tagged("api").await?;
+        let latest_block = storage
+            .blocks_web3_dal()
+            .resolve_block_id(BlockId::Number(BlockNumber::Latest))
+            .await
+            .context("failed resolving latest miniblock")?
+            .context("no miniblocks in storage")?;
+        let mut synced_nonces = HashMap::with_capacity(accounts.len());
+        for account in accounts {
+            let full_nonce = storage
+                .storage_web3_dal()
+                .get_address_historical_nonce(account, latest_block)
+                .await
+                .with_context(|| format!("failed getting nonce for {account:?}"))?;
+            let (account_nonce, _) = decompose_full_nonce(full_nonce);
+            synced_nonces.insert(account, Nonce(account_nonce.as_u32()));
+        }
+        drop(storage);
+
+        let mut inner = self.inner.write().await;
+        let removed_count: usize = synced_nonces
+            .into_iter()
+            .map(|(account, synced_nonce)| inner.remove_synced(account, synced_nonce))
+            .sum();
+        if removed_count > 0 {
+            tracing::debug!(
+                "Removed {removed_count} proxied transactions synced from the main node"
+            );
+        }
+        Ok(())
+    }
+
+    async fn run_updates(
+        self,
+        pool: ConnectionPool,
+        mut stop_receiver: watch::Receiver<bool>,
+    ) -> anyhow::Result<()> {
+        const UPDATE_INTERVAL: Duration = Duration::from_secs(1);
+
+        loop {
+            if *stop_receiver.borrow() {
+                tracing::info!(
+                    "Stop signal received, proxied transactions cache updater is shutting down"
+                );
+                return Ok(());
+            }
+            if let Err(err) = self.reconcile(&pool).await {
+                tracing::warn!("Failed updating proxied transactions cache: {err:#}");
+            }
+            tokio::time::timeout(UPDATE_INTERVAL, stop_receiver.changed())
+                .await
+                .ok();
+        }
+    }
+}
+
 /// Used by external node to proxy transaction to the main node
 /// and store them while they're not synced back yet
 #[derive(Debug)]
 pub struct TxProxy {
-    tx_cache: RwLock<HashMap<H256, L2Tx>>,
+    tx_cache: TxCache,
     client: HttpClient,
 }
 
@@ -25,20 +252,47 @@ impl TxProxy {
         let client = HttpClientBuilder::default().build(main_node_url).unwrap();
         Self {
             client,
-            tx_cache: RwLock::new(HashMap::new()),
+            tx_cache: TxCache::default(),
         }
     }
 
     pub async fn find_tx(&self, tx_hash: H256) -> Option<L2Tx> {
-        self.tx_cache.read().await.get(&tx_hash).cloned()
+        self.tx_cache.get_tx(tx_hash).await
     }
 
     pub async fn forget_tx(&self, tx_hash: H256) {
-        self.tx_cache.write().await.remove(&tx_hash);
+        self.tx_cache.remove_tx(tx_hash).await;
+    }
+
+    pub async fn save_tx(&self, tx: L2Tx) {
+        self.tx_cache.push(tx).await;
+    }
+
+    /// Returns the pending nonce for the account taking into account proxied transactions not synced yet.
+    /// `current_nonce` is the pending nonce based on the synced state.
+    pub async fn next_nonce_by_initiator_account(
+        &self,
+        account_address: Address,
+        current_nonce: u32,
+    ) -> Nonce {
+        let mut pending_nonce = Nonce(current_nonce);
+        let nonces = self.tx_cache.get_nonces_for_account(account_address).await;
+        for nonce in nonces.range(pending_nonce..) {
+            if *nonce != pending_nonce {
+                break;
+            }
+            pending_nonce += 1;
+        }
+        pending_nonce
     }
 
-    pub async fn save_tx(&self, tx_hash: H256, tx: L2Tx) {
-        self.tx_cache.write().await.insert(tx_hash, tx);
+    /// Returns a task removing proxied transactions from the cache once they are synced from the main node.
+    pub fn run_account_nonce_sweeper(
+        &self,
+        pool: ConnectionPool,
+        stop_receiver: watch::Receiver<bool>,
+    ) -> impl std::future::Future<Output = anyhow::Result<()>> {
+        self.tx_cache.clone().run_updates(pool, stop_receiver)
     }
 
     pub async fn submit_tx(&self, tx: &L2Tx) -> RpcResult<H256> {
@@ -68,3 +322,120 @@ impl TxProxy {
         self.client.get_transaction_details(hash).await
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::testonly::create_l2_transaction;
+
+    fn create_tx(initiator: Address, nonce: u32) -> L2Tx {
+        let mut tx = create_l2_transaction(10, 100);
+        tx.common_data.initiator_address = initiator;
+        tx.common_data.nonce = Nonce(nonce);
+        tx
+    }
+
+    #[tokio::test]
+    async fn pending_nonce_takes_proxied_transactions_into_account() {
+        let proxy = TxProxy::new("http://localhost:3050");
+        let account = Address::repeat_byte(1);
+        assert_eq!(
+            proxy.next_nonce_by_initiator_account(account, 2).await,
+            Nonce(2)
+        );
+
+        let txs: Vec<_> = [2, 3, 5].map(|nonce| create_tx(account, nonce)).into();
+        for tx in &txs {
+            proxy.save_tx(tx.clone()).await;
+        }
+        // Nonce 4 is missing, so the nonce sequence is interrupted.
+        assert_eq!(
+            proxy.next_nonce_by_initiator_account(account, 2).await,
+            Nonce(4)
+        );
+        assert_eq!(
+            proxy.next_nonce_by_initiator_account(account, 5).await,
+            Nonce(6)
+        );
+        let other_account = Address::repeat_byte(2);
+        assert_eq!(
+            proxy
+                .next_nonce_by_initiator_account(other_account, 2)
+                .await,
+            Nonce(2)
+        );
+
+        assert_eq!(proxy.find_tx(txs[1].hash()).await, Some(txs[1].clone()));
+        proxy.forget_tx(txs[1].hash()).await;
+        assert_eq!(proxy.find_tx(txs[1].hash()).await, None);
+        assert_eq!(
+            proxy.next_nonce_by_initiator_account(account, 2).await,
+            Nonce(3)
+        );
+    }
+
+    #[tokio::test]
+    async fn replacement_transaction_retains_nonce() {
+        let proxy = TxProxy::new("http://localhost:3050");
+        let account = Address::repeat_byte(1);
+        let tx = create_tx(account, 0);
+        let replacement_tx = create_tx(account, 0);
+        proxy.save_tx(tx.clone()).await;
+        proxy.save_tx(replacement_tx).await;
+
+        proxy.forget_tx(tx.hash()).await;
+        assert_eq!(
+            proxy.next_nonce_by_initiator_account(account, 0).await,
+            Nonce(1)
+        );
+    }
+
+    #[test]
+    fn cache_evicts_oldest_transactions_when_full() {
+        let mut cache = TxCacheInner::default();
+        let account = Address::repeat_byte(1);
+        let start = Instant::now();
+        let txs: Vec<_> = (0..3).map(|nonce| create_tx(account, nonce)).collect();
+        for (i, tx) in txs.iter().enumerate() {
+            cache.push(tx.clone(), start + Duration::from_secs(i as u64), 2);
+        }
+
+        assert_eq!(cache.tx_cache.len(), 2);
+        assert!(!cache.tx_cache.contains_key(&txs[0].hash()));
+        assert_eq!(cache.tx_hashes_by_insertion.len(), 2);
+        let nonces: Vec<_> = cache.tx_hashes_by_account[&account]
+            .keys()
+            .copied()
+            .collect();
+        assert_eq!(nonces, [Nonce(1), Nonce(2)]);
+    }
+
+    #[test]
+    fn cache_removes_expired_and_synced_transactions() {
+        let mut cache = TxCacheInner::default();
+        let account = Address::repeat_byte(1);
+        let other_account = Address::repeat_byte(2);
+        let start = Instant::now();
+        cache.push(create_tx(account, 0), start, 10);
+        cache.push(create_tx(account, 0), start, 10);
+        cache.push(
+            create_tx(other_account, 0),
+            start + Duration::from_secs(1),
+            10,
+        );
+        cache.push(create_tx(account, 1), start + Duration::from_secs(2), 10);
+
+        assert_eq!(
+            cache.remove_inserted_before(start + Duration::from_secs(1)),
+            2
+        );
+        assert_eq!(cache.tx_cache.len(), 2);
+        assert_eq!(cache.tx_hashes_by_account[&account].len(), 1);
+
+        assert_eq!(cache.remove_synced(account, Nonce(2)), 1);
+        assert_eq!(cache.remove_synced(other_account, Nonce(0)), 0);
+        assert_eq!(cache.tx_cache.len(), 1);
+        assert!(!cache.tx_hashes_by_account.contains_key(&account));
+        assert_eq!(cache.tx_hashes_by_insertion.len(), 1);
+    }
+}
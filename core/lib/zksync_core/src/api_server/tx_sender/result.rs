@@ -2,8 +2,13 @@ use multivm::{
     interface::{ExecutionResult, VmExecutionResultAndLogs},
     tracers::validator::ValidationError,
 };
+use serde_json::{json, Value};
 use thiserror::Error;
 use zksync_types::{l2::error::TxCheckError, U256};
+use zksync_web3_decl::{
+    error::{ApiErrorCode, Web3Error},
+    jsonrpsee::core::ClientError,
+};
 
 use crate::api_server::execution_sandbox::SandboxExecutionError;
 
@@ -13,6 +18,10 @@ pub enum SubmitTxError {
     NonceIsTooHigh(u32, u32, u32),
     #[error("nonce too low. allowed nonce range: {0} - {1}, actual: {2}")]
     NonceIsTooLow(u32, u32, u32),
+    /// A pending transaction with the same sender and nonce exists, and the submitted transaction
+    /// doesn't raise its fees enough to replace it.
+    #[error("replacement transaction underpriced. fees must be raised by at least {0}%")]
+    ReplacementUnderpriced(u32),
     #[error("{0}")]
     IncorrectTx(#[from] TxCheckError),
     #[error("insufficient funds for gas + value. balance: {0}, fee: {1}, value: {2}")]
@@ -39,8 +48,9 @@ pub enum SubmitTxError {
     PrePaymasterPreparationFailed(String),
     #[error("invalid sender. can't start a transaction from a non-account")]
     FromIsNotAnAccount,
+    /// Contains the minimum acceptable max fee per gas.
     #[error("max fee per gas less than block base fee")]
-    MaxFeePerGasTooLow,
+    MaxFeePerGasTooLow(U256),
     #[error("max priority fee per gas higher than max fee per gas")]
     MaxPriorityFeeGreaterThanMaxFee,
     #[error(
@@ -71,6 +81,9 @@ pub enum SubmitTxError {
     ProxyError(#[from] zksync_web3_decl::jsonrpsee::core::ClientError),
     #[error("not enough gas to publish compressed bytecodes")]
     FailedToPublishCompressedBytecodes,
+    /// Transaction was rejected by the operator-defined submission policy.
+    #[error("transaction rejected by submission policy: {0}")]
+    RejectedByPolicy(String),
 }
 
 impl SubmitTxError {
@@ -78,6 +91,7 @@ impl SubmitTxError {
         match self {
             Self::NonceIsTooHigh(_, _, _) => "nonce-is-too-high",
             Self::NonceIsTooLow(_, _, _) => "nonce-is-too-low",
+            Self::ReplacementUnderpriced(_) => "replacement-underpriced",
             Self::IncorrectTx(_) => "incorrect-tx",
             Self::NotEnoughBalanceForFeeValue(_, _, _) => "not-enough-balance-for-fee",
             Self::ExecutionReverted(_, _) => "execution-reverted",
@@ -91,7 +105,7 @@ impl SubmitTxError {
             Self::PaymasterValidationFailed(_) => "failed-paymaster-validation",
             Self::PrePaymasterPreparationFailed(_) => "failed-prepaymaster-preparation",
             Self::FromIsNotAnAccount => "from-is-not-an-account",
-            Self::MaxFeePerGasTooLow => "max-fee-per-gas-too-low",
+            Self::MaxFeePerGasTooLow(_) => "max-fee-per-gas-too-low",
             Self::MaxPriorityFeeGreaterThanMaxFee => "max-priority-fee-greater-than-max-fee",
             Self::UnexpectedVMBehavior(_) => "unexpected-vm-behavior",
             Self::UnrealisticPubdataPriceLimit => "unrealistic-pubdata-price-limit",
@@ -102,6 +116,7 @@ impl SubmitTxError {
             Self::IntrinsicGas => "intrinsic-gas",
             Self::ProxyError(_) => "proxy-error",
             Self::FailedToPublishCompressedBytecodes => "failed-to-publish-compressed-bytecodes",
+            Self::RejectedByPolicy(_) => "rejected-by-policy",
         }
     }
 
@@ -112,6 +127,86 @@ impl SubmitTxError {
             Vec::new()
         }
     }
+
+    /// Returns the stable API code for this error.
+    pub fn api_error_code(&self) -> ApiErrorCode {
+        match self {
+            Self::NonceIsTooHigh(..) => ApiErrorCode::NonceTooHigh,
+            Self::NonceIsTooLow(..) => ApiErrorCode::NonceTooLow,
+            Self::ReplacementUnderpriced(_) => ApiErrorCode::ReplacementUnderpriced,
+            Self::IncorrectTx(_) => ApiErrorCode::InvalidTransaction,
+            Self::NotEnoughBalanceForFeeValue(..) | Self::InsufficientFundsForTransfer => {
+                ApiErrorCode::InsufficientFunds
+            }
+            Self::ExecutionReverted(..) => ApiErrorCode::ExecutionReverted,
+            Self::GasLimitIsTooBig => ApiErrorCode::GasLimitTooHigh,
+            Self::MaxFeePerGasTooLow(_) => ApiErrorCode::MaxFeePerGasTooLow,
+            Self::MaxPriorityFeeGreaterThanMaxFee
+            | Self::UnrealisticPubdataPriceLimit
+            | Self::FeePerGasTooHigh
+            | Self::FeePerPubdataByteTooHigh => ApiErrorCode::InvalidFeeParams,
+            Self::IntrinsicGas => ApiErrorCode::IntrinsicGasTooLow,
+            Self::TooManyFactoryDependencies(..) => ApiErrorCode::TooManyFactoryDeps,
+            Self::RateLimitExceeded => ApiErrorCode::RateLimited,
+            Self::ServerShuttingDown => ApiErrorCode::ServerShuttingDown,
+            Self::RejectedByPolicy(_) => ApiErrorCode::RejectedByPolicy,
+            Self::Unexecutable(_)
+            | Self::BootloaderFailure(_)
+            | Self::UnexpectedVMBehavior(_)
+            | Self::FailedToPublishCompressedBytecodes => ApiErrorCode::Unexecutable,
+            Self::ValidationFailed(_) | Self::FromIsNotAnAccount => ApiErrorCode::ValidationFailed,
+            Self::PaymasterValidationFailed(_) | Self::PrePaymasterPreparationFailed(_) => {
+                ApiErrorCode::PaymasterValidationFailed
+            }
+            Self::FailedToChargeFee(_) => ApiErrorCode::FailedToChargeFee,
+            Self::ProxyError(_) => ApiErrorCode::MainNodeError,
+        }
+    }
+
+    /// Returns machine-readable details of this error, if any.
+    fn api_error_data(&self) -> Option<Value> {
+        Some(match self {
+            Self::NonceIsTooHigh(min_nonce, max_nonce, actual_nonce)
+            | Self::NonceIsTooLow(min_nonce, max_nonce, actual_nonce) => json!({
+                "minNonce": min_nonce,
+                "maxNonce": max_nonce,
+                "actualNonce": actual_nonce,
+            }),
+            Self::ReplacementUnderpriced(price_bump_percent) => {
+                json!({ "minPriceBumpPercent": price_bump_percent })
+            }
+            Self::NotEnoughBalanceForFeeValue(balance, fee, value) => json!({
+                "balance": balance,
+                "fee": fee,
+                "value": value,
+            }),
+            Self::MaxFeePerGasTooLow(required_max_fee_per_gas) => {
+                json!({ "requiredMaxFeePerGas": required_max_fee_per_gas })
+            }
+            Self::TooManyFactoryDependencies(actual, max) => json!({
+                "factoryDeps": actual,
+                "maxFactoryDeps": max,
+            }),
+            _ => return None,
+        })
+    }
+}
+
+impl From<SubmitTxError> for Web3Error {
+    fn from(err: SubmitTxError) -> Self {
+        match err {
+            SubmitTxError::ExecutionReverted(..) => {
+                return Self::SubmitTransactionError(err.to_string(), err.data());
+            }
+            SubmitTxError::ProxyError(ClientError::Call(err)) => return Self::ProxyError(err),
+            _ => {}
+        }
+        Self::TransactionRejected {
+            code: err.api_error_code(),
+            data: err.api_error_data(),
+            message: err.to_string(),
+        }
+    }
 }
 
 impl From<SandboxExecutionError> for SubmitTxError {
@@ -168,3 +263,49 @@ impl ApiCallResult for VmExecutionResultAndLogs {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use assert_matches::assert_matches;
+    use zksync_web3_decl::jsonrpsee::types::ErrorObjectOwned;
+
+    use super::*;
+
+    #[test]
+    fn converting_submit_errors_to_api_errors() {
+        let err = Web3Error::from(SubmitTxError::ExecutionReverted(
+            "oops".to_owned(),
+            vec![1, 2],
+        ));
+        assert_eq!(err.code(), ApiErrorCode::ExecutionReverted);
+        assert_eq!(err.to_string(), "execution reverted: oops");
+        assert_eq!(err.data(), Some(Value::String("0x0102".to_owned())));
+
+        let err = Web3Error::from(SubmitTxError::MaxFeePerGasTooLow(100_000_000.into()));
+        assert_eq!(err.code(), ApiErrorCode::MaxFeePerGasTooLow);
+        assert_eq!(err.to_string(), "max fee per gas less than block base fee");
+        assert_eq!(
+            err.data(),
+            Some(json!({ "requiredMaxFeePerGas": "0x5f5e100" }))
+        );
+
+        let err = Web3Error::from(SubmitTxError::NonceIsTooLow(5, 55, 3));
+        assert_eq!(err.code(), ApiErrorCode::NonceTooLow);
+        assert_eq!(
+            err.data(),
+            Some(json!({ "minNonce": 5, "maxNonce": 55, "actualNonce": 3 }))
+        );
+
+        let upstream_err = ErrorObjectOwned::owned(3, "execution reverted", Some("0x0102"));
+        let err = Web3Error::from(SubmitTxError::ProxyError(ClientError::Call(
+            upstream_err.clone(),
+        )));
+        assert_matches!(&err, Web3Error::ProxyError(err) if *err == upstream_err);
+        assert_eq!(err.to_string(), "execution reverted");
+        assert_eq!(err.data(), Some(Value::String("0x0102".to_owned())));
+
+        let err = Web3Error::from(SubmitTxError::ServerShuttingDown);
+        assert_eq!(err.code(), ApiErrorCode::ServerShuttingDown);
+        assert_eq!(err.data(), None);
+    }
+}
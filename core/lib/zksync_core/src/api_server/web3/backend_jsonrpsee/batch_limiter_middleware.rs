@@ -1,4 +1,4 @@
-use std::num::NonZeroU32;
+use std::{num::NonZeroU32, time::Instant};
 
 use governor::{
     clock::DefaultClock,
@@ -20,6 +20,7 @@ use crate::api_server::web3::metrics::API_METRICS;
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EncodeLabelValue, EncodeLabelSet)]
 #[metrics(label = "transport", rename_all = "snake_case")]
 pub(crate) enum Transport {
+    Http,
     Ws,
 }
 
@@ -45,6 +46,7 @@ pub(crate) struct LimitMiddleware<S> {
     inner: S,
     rate_limiter: Option<RateLimiter<NotKeyed, InMemoryState, DefaultClock, NoOpMiddleware>>,
     transport: Transport,
+    started_at: Instant,
     _guard: GaugeGuard,
 }
 
@@ -55,11 +57,20 @@ impl<S> LimitMiddleware<S> {
             rate_limiter: requests_per_minute_limit
                 .map(|limit| RateLimiter::direct(Quota::per_minute(limit))),
             transport: Transport::Ws,
+            started_at: Instant::now(),
             _guard: API_METRICS.ws_open_sessions.inc_guard(1),
         }
     }
 }
 
+impl<S> Drop for LimitMiddleware<S> {
+    fn drop(&mut self) {
+        API_METRICS
+            .ws_session_lifetime
+            .observe(self.started_at.elapsed());
+    }
+}
+
 impl<'a, S> RpcServiceT<'a> for LimitMiddleware<S>
 where
     S: Send + Clone + Sync + RpcServiceT<'a>,
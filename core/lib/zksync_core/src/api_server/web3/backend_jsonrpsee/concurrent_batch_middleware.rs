@@ -0,0 +1,331 @@
+//! HTTP middleware executing calls from JSON-RPC batch requests concurrently.
+//!
+//! `jsonrpsee` processes calls in a batch sequentially, so a single slow call (e.g., `eth_call` or `debug_traceCall`)
+//! delays the entire batch. This middleware splits a batch into separate requests, dispatches them to the wrapped
+//! service with bounded concurrency and assembles the responses in the original order. Errors are reported
+//! per call; a failure of one call does not affect other calls in the batch. Like in `jsonrpsee`, the response
+//! size limit applies to the entire batch response rather than to individual calls.
+
+use std::{
+    future::Future,
+    num::NonZeroUsize,
+    pin::{pin, Pin},
+    task::{Context, Poll},
+};
+
+use futures::{StreamExt, TryStreamExt};
+use http::{
+    header::{CONTENT_LENGTH, CONTENT_TYPE},
+    HeaderValue, Request, Response, StatusCode,
+};
+use hyper::Body;
+use serde_json::Value;
+use tower::ServiceExt;
+use vise::{Buckets, Counter, Histogram, Metrics};
+use zksync_web3_decl::jsonrpsee::types::error::{
+    ErrorCode, OVERSIZED_RESPONSE_CODE, OVERSIZED_RESPONSE_MSG,
+};
+
+/// Maximum size of request bodies buffered by the middleware. Larger requests (as well as requests without
+/// the `Content-Length` header) are passed to `jsonrpsee` as is. Corresponds to the default request size limit
+/// in `jsonrpsee`.
+const MAX_BUFFERED_BODY_SIZE: usize = 10 << 20;
+
+#[derive(Debug, Metrics)]
+#[metrics(prefix = "api_jsonrpc_backend_concurrent_batch")]
+struct ConcurrentBatchMetrics {
+    /// Size of batch requests split by the middleware.
+    #[metrics(buckets = Buckets::exponential(1.0..=512.0, 2.0))]
+    size: Histogram<usize>,
+    /// Number of batch requests rejected because of the response size limit.
+    oversized_responses: Counter,
+}
+
+#[vise::register]
+static METRICS: vise::Global<ConcurrentBatchMetrics> = vise::Global::new();
+
+/// Layer producing [`ConcurrentBatchService`]s.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct ConcurrentBatchLayer {
+    concurrency: NonZeroUsize,
+    max_batch_size: Option<usize>,
+    max_response_body_size: usize,
+}
+
+impl ConcurrentBatchLayer {
+    /// Creates a layer executing up to `concurrency` calls from a batch at the same time. Batches larger than
+    /// `max_batch_size` are not split, so that they are rejected by `jsonrpsee` as usual. Batches with the total
+    /// response size exceeding `max_response_body_size` are rejected.
+    pub(crate) fn new(
+        concurrency: NonZeroUsize,
+        max_batch_size: Option<usize>,
+        max_response_body_size: usize,
+    ) -> Self {
+        Self {
+            concurrency,
+            max_batch_size,
+            max_response_body_size,
+        }
+    }
+}
+
+impl<S> tower::Layer<S> for ConcurrentBatchLayer {
+    type Service = ConcurrentBatchService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        ConcurrentBatchService {
+            inner,
+            concurrency: self.concurrency,
+            max_batch_size: self.max_batch_size,
+            max_response_body_size: self.max_response_body_size,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct ConcurrentBatchService<S> {
+    inner: S,
+    concurrency: NonZeroUsize,
+    max_batch_size: Option<usize>,
+    max_response_body_size: usize,
+}
+
+/// Splits a batch request into separate calls. Returns `None` if the request should be passed to `jsonrpsee` as is.
+fn split_batch(body: &[u8], max_batch_size: Option<usize>) -> Option<Vec<Value>> {
+    let first_char = body.iter().find(|byte| !byte.is_ascii_whitespace())?;
+    if *first_char != b'[' {
+        return None;
+    }
+    let Ok(Value::Array(calls)) = serde_json::from_slice(body) else {
+        return None; // Let `jsonrpsee` report the parsing error
+    };
+    let is_splittable =
+        calls.len() > 1 && max_batch_size.map_or(true, |limit| calls.len() <= limit);
+    is_splittable.then_some(calls)
+}
+
+fn content_length(request: &Request<Body>) -> Option<usize> {
+    request
+        .headers()
+        .get(CONTENT_LENGTH)?
+        .to_str()
+        .ok()?
+        .parse()
+        .ok()
+}
+
+fn call_request(parts: &http::request::Parts, call: &Value) -> Request<Body> {
+    let body = serde_json::to_vec(call).expect("failed serializing JSON value");
+    let mut request = Request::new(Body::empty());
+    *request.method_mut() = parts.method.clone();
+    *request.uri_mut() = parts.uri.clone();
+    *request.version_mut() = parts.version;
+    *request.headers_mut() = parts.headers.clone();
+    request
+        .headers_mut()
+        .insert(CONTENT_LENGTH, HeaderValue::from(body.len()));
+    *request.body_mut() = Body::from(body);
+    request
+}
+
+/// Converts a response for a single call into a JSON value. Returns `None` for notifications.
+fn call_response(call: &Value, body: &[u8]) -> Option<Value> {
+    if body.is_empty() {
+        return None;
+    }
+    Some(serde_json::from_slice(body).unwrap_or_else(|_| {
+        // The response is not JSON, e.g., if it was produced by one of HTTP middleware layers.
+        serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": call.get("id").cloned().unwrap_or(Value::Null),
+            "error": {
+                "code": ErrorCode::InternalError.code(),
+                "message": String::from_utf8_lossy(body),
+            },
+        })
+    }))
+}
+
+/// Creates a response for a batch with an oversized response, which is the same as returned by `jsonrpsee`.
+fn oversized_batch_response(max_response_body_size: usize) -> Response<Body> {
+    let body = serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": Value::Null,
+        "error": {
+            "code": OVERSIZED_RESPONSE_CODE,
+            "message": OVERSIZED_RESPONSE_MSG,
+            "data": format!("Exceeded max limit of {max_response_body_size}"),
+        },
+    });
+    let mut response = Response::new(Body::from(body.to_string()));
+    response.headers_mut().insert(
+        CONTENT_TYPE,
+        HeaderValue::from_static("application/json; charset=utf-8"),
+    );
+    response
+}
+
+impl<S> tower::Service<Request<Body>> for ConcurrentBatchService<S>
+where
+    S: tower::Service<Request<Body>, Response = Response<Body>> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    S::Error: From<hyper::Error> + Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request<Body>) -> Self::Future {
+        // Use the service that was polled for readiness; leave a fresh clone in its place.
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        if !matches!(content_length(&request), Some(len) if len <= MAX_BUFFERED_BODY_SIZE) {
+            return Box::pin(inner.call(request));
+        }
+
+        let concurrency = self.concurrency;
+        let max_batch_size = self.max_batch_size;
+        let max_response_body_size = self.max_response_body_size;
+        Box::pin(async move {
+            let (parts, body) = request.into_parts();
+            let body = hyper::body::to_bytes(body).await?;
+            let Some(calls) = split_batch(&body, max_batch_size) else {
+                return inner.call(Request::from_parts(parts, body.into())).await;
+            };
+            METRICS.size.observe(calls.len());
+
+            // Futures are collected eagerly so that they don't borrow `inner` across `await` points.
+            let call_futures: Vec<_> = calls
+                .iter()
+                .map(|call| {
+                    let request = call_request(&parts, call);
+                    let inner = inner.clone();
+                    async move {
+                        let response = inner.oneshot(request).await?;
+                        let status = response.status();
+                        let body = hyper::body::to_bytes(response.into_body()).await?;
+                        Ok::<_, S::Error>((status, body))
+                    }
+                })
+                .collect();
+            let mut call_responses_stream =
+                pin!(futures::stream::iter(call_futures).buffered(concurrency.get()));
+            let mut call_responses = Vec::with_capacity(calls.len());
+            let mut response_size = 2; // opening and closing brackets
+            while let Some((status, body)) = call_responses_stream.try_next().await? {
+                if !body.is_empty() {
+                    response_size += body.len() + 1; // + separating comma
+                }
+                if response_size > max_response_body_size {
+                    // Remaining calls are cancelled by dropping the stream.
+                    METRICS.oversized_responses.inc();
+                    return Ok(oversized_batch_response(max_response_body_size));
+                }
+                call_responses.push((status, body));
+            }
+
+            let all_rate_limited = call_responses
+                .iter()
+                .all(|(status, _)| *status == StatusCode::TOO_MANY_REQUESTS);
+            let responses: Vec<_> = calls
+                .iter()
+                .zip(&call_responses)
+                .filter_map(|(call, (_, body))| call_response(call, body))
+                .collect();
+            if responses.is_empty() {
+                // All calls are notifications.
+                return Ok(Response::new(Body::empty()));
+            }
+
+            let body = serde_json::to_vec(&responses).expect("failed serializing JSON values");
+            let mut response = Response::new(Body::from(body));
+            if all_rate_limited {
+                *response.status_mut() = StatusCode::TOO_MANY_REQUESTS;
+            }
+            response.headers_mut().insert(
+                CONTENT_TYPE,
+                HeaderValue::from_static("application/json; charset=utf-8"),
+            );
+            Ok(response)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tower::Layer;
+
+    use super::*;
+
+    #[test]
+    fn splitting_batches() {
+        assert_eq!(split_batch(br#"{"id":1}"#, Some(3)), None);
+        assert_eq!(split_batch(br#"[{"id":1}"#, Some(3)), None);
+        assert_eq!(split_batch(br#"[{"id":1}]"#, Some(3)), None);
+        let big_batch = br#"[{"id":1},{"id":2},{"id":3},{"id":4}]"#;
+        assert_eq!(split_batch(big_batch, Some(3)), None);
+        assert_eq!(split_batch(big_batch, None).unwrap().len(), 4);
+
+        let calls = split_batch(br#"  [{"id":1}, {"id":2}]"#, Some(3)).unwrap();
+        assert_eq!(
+            calls,
+            [
+                serde_json::json!({ "id": 1 }),
+                serde_json::json!({ "id": 2 })
+            ]
+        );
+    }
+
+    #[test]
+    fn converting_call_responses() {
+        let call = serde_json::json!({ "jsonrpc": "2.0", "id": 5, "method": "eth_chainId" });
+        assert_eq!(call_response(&call, b""), None);
+
+        let response = call_response(&call, br#"{"jsonrpc":"2.0","id":5,"result":"0x1"}"#).unwrap();
+        assert_eq!(response["result"], "0x1");
+
+        let response = call_response(&call, b"Too many requests").unwrap();
+        assert_eq!(response["id"], 5);
+        assert_eq!(response["error"]["message"], "Too many requests");
+    }
+
+    #[tokio::test]
+    async fn oversized_batch_responses_are_rejected() {
+        let inner = tower::service_fn(|request: Request<Body>| async move {
+            let body = hyper::body::to_bytes(request.into_body()).await?;
+            let call: Value = serde_json::from_slice(&body).unwrap();
+            let response =
+                serde_json::json!({ "jsonrpc": "2.0", "id": call["id"], "result": "0x1" });
+            Ok::<_, hyper::Error>(Response::new(Body::from(response.to_string())))
+        });
+        let batch: &'static [u8] = br#"[
+            {"jsonrpc":"2.0","id":1,"method":"eth_chainId"},
+            {"jsonrpc":"2.0","id":2,"method":"eth_chainId"}
+        ]"#;
+        let request = || {
+            Request::builder()
+                .header(CONTENT_LENGTH, batch.len())
+                .body(Body::from(batch))
+                .unwrap()
+        };
+        let concurrency = NonZeroUsize::new(2).unwrap();
+
+        let service = ConcurrentBatchLayer::new(concurrency, None, 1_000).layer(inner);
+        let response = service.oneshot(request()).await.unwrap();
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let responses: Vec<Value> = serde_json::from_slice(&body).unwrap();
+        assert_eq!(responses.len(), 2);
+        assert_eq!(responses[1]["id"], 2);
+
+        // Each call response fits into the limit, but the batch response doesn't.
+        let service = ConcurrentBatchLayer::new(concurrency, None, 50).layer(inner);
+        let response = service.oneshot(request()).await.unwrap();
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let response: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(response["error"]["code"], OVERSIZED_RESPONSE_CODE);
+    }
+}
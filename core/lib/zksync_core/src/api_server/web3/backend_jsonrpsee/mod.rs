@@ -12,39 +12,20 @@ use zksync_web3_decl::{
 use crate::api_server::web3::metrics::API_METRICS;
 
 pub mod batch_limiter_middleware;
+pub mod concurrent_batch_middleware;
 pub mod namespaces;
+pub mod rate_limit_middleware;
+pub mod response_cache_middleware;
 
 pub fn from_std_error(e: impl Error) -> ErrorObjectOwned {
     ErrorObjectOwned::owned(ErrorCode::InternalError.code(), e.to_string(), Some(()))
 }
 
 pub fn into_jsrpc_error(err: Web3Error) -> ErrorObjectOwned {
-    ErrorObjectOwned::owned(
-        match err {
-            Web3Error::InternalError | Web3Error::NotImplemented => ErrorCode::InternalError.code(),
-            Web3Error::NoBlock
-            | Web3Error::NoSuchFunction
-            | Web3Error::RLPError(_)
-            | Web3Error::InvalidTransactionData(_)
-            | Web3Error::TooManyTopics
-            | Web3Error::FilterNotFound
-            | Web3Error::InvalidFeeParams(_)
-            | Web3Error::InvalidFilterBlockHash
-            | Web3Error::LogsLimitExceeded(_, _, _) => ErrorCode::InvalidParams.code(),
-            Web3Error::SubmitTransactionError(_, _) | Web3Error::SerializationError(_) => 3,
-            Web3Error::PubSubTimeout => 4,
-            Web3Error::RequestTimeout => 5,
-            Web3Error::TreeApiUnavailable => 6,
-        },
-        match err {
-            Web3Error::SubmitTransactionError(ref message, _) => message.clone(),
-            _ => err.to_string(),
-        },
-        match err {
-            Web3Error::SubmitTransactionError(_, data) => Some(format!("0x{}", hex::encode(data))),
-            _ => None,
-        },
-    )
+    if let Web3Error::ProxyError(err) = err {
+        return err;
+    }
+    ErrorObjectOwned::owned(err.code().code(), err.to_string(), err.data())
 }
 
 pub fn internal_error(method_name: &'static str, error: impl fmt::Display) -> Web3Error {
@@ -0,0 +1,35 @@
+use async_trait::async_trait;
+use zksync_types::{api, Address, Nonce, H256};
+use zksync_web3_decl::{jsonrpsee::core::RpcResult, namespaces::AdminNamespaceServer};
+
+use crate::api_server::web3::{backend_jsonrpsee::into_jsrpc_error, namespaces::AdminNamespace};
+
+#[async_trait]
+impl AdminNamespaceServer for AdminNamespace {
+    async fn cancel_transaction(
+        &self,
+        hash: H256,
+        reason: Option<String>,
+    ) -> RpcResult<Option<H256>> {
+        self.cancel_transaction_impl(hash, reason)
+            .await
+            .map_err(into_jsrpc_error)
+    }
+
+    async fn cancel_transaction_by_nonce(
+        &self,
+        initiator: Address,
+        nonce: Nonce,
+        reason: Option<String>,
+    ) -> RpcResult<Option<H256>> {
+        self.cancel_transaction_by_nonce_impl(initiator, nonce, reason)
+            .await
+            .map_err(into_jsrpc_error)
+    }
+
+    async fn get_resubmission_policy(&self) -> RpcResult<api::ResubmissionPolicyDetails> {
+        self.get_resubmission_policy_impl()
+            .await
+            .map_err(into_jsrpc_error)
+    }
+}
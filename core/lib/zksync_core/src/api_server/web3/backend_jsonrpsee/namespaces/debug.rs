@@ -1,5 +1,5 @@
 use zksync_types::{
-    api::{BlockId, BlockNumber, DebugCall, ResultDebugCall, TracerConfig},
+    api::{BlockId, BlockNumber, DebugCall, DebugTrace, ResultDebugTrace, TracerConfig},
     transaction_request::CallRequest,
     H256,
 };
@@ -16,7 +16,7 @@ impl DebugNamespaceServer for DebugNamespace {
         &self,
         block: BlockNumber,
         options: Option<TracerConfig>,
-    ) -> RpcResult<Vec<ResultDebugCall>> {
+    ) -> RpcResult<Vec<ResultDebugTrace>> {
         self.debug_trace_block_impl(BlockId::Number(block), options)
             .await
             .map_err(into_jsrpc_error)
@@ -25,7 +25,7 @@ impl DebugNamespaceServer for DebugNamespace {
         &self,
         hash: H256,
         options: Option<TracerConfig>,
-    ) -> RpcResult<Vec<ResultDebugCall>> {
+    ) -> RpcResult<Vec<ResultDebugTrace>> {
         self.debug_trace_block_impl(BlockId::Hash(hash), options)
             .await
             .map_err(into_jsrpc_error)
@@ -44,7 +44,9 @@ impl DebugNamespaceServer for DebugNamespace {
         &self,
         tx_hash: H256,
         options: Option<TracerConfig>,
-    ) -> RpcResult<Option<DebugCall>> {
-        Ok(self.debug_trace_transaction_impl(tx_hash, options).await)
+    ) -> RpcResult<Option<DebugTrace>> {
+        self.debug_trace_transaction_impl(tx_hash, options)
+            .await
+            .map_err(into_jsrpc_error)
     }
 }
@@ -3,10 +3,11 @@ use std::collections::HashMap;
 use bigdecimal::BigDecimal;
 use zksync_types::{
     api::{
-        BlockDetails, BridgeAddresses, L1BatchDetails, L2ToL1LogProof, Proof, ProtocolVersion,
-        TransactionDetails,
+        BlockDetails, BridgeAddresses, ConsensusBlockCertificate, L1BatchCommitmentArtifacts,
+        L1BatchDetails, L1BatchPaymasterStats, L1BatchPubdata, L2ToL1LogProof, LogsPage, NodeStats,
+        PaymasterUsage, PriorityOpStatus, Proof, ProtocolVersion, TransactionDetails,
     },
-    fee::Fee,
+    fee::FeeEstimate,
     fee_model::FeeParams,
     transaction_request::CallRequest,
     Address, L1BatchNumber, MiniblockNumber, H256, U256, U64,
@@ -14,14 +15,14 @@ use zksync_types::{
 use zksync_web3_decl::{
     jsonrpsee::core::{async_trait, RpcResult},
     namespaces::zks::ZksNamespaceServer,
-    types::Token,
+    types::{Filter, Token},
 };
 
 use crate::api_server::web3::{backend_jsonrpsee::into_jsrpc_error, ZksNamespace};
 
 #[async_trait]
 impl ZksNamespaceServer for ZksNamespace {
-    async fn estimate_fee(&self, req: CallRequest) -> RpcResult<Fee> {
+    async fn estimate_fee(&self, req: CallRequest) -> RpcResult<FeeEstimate> {
         self.estimate_fee_impl(req).await.map_err(into_jsrpc_error)
     }
 
@@ -164,4 +165,72 @@ impl ZksNamespaceServer for ZksNamespace {
             .await
             .map_err(into_jsrpc_error)
     }
+
+    async fn get_consensus_block_certificate(
+        &self,
+        block_number: MiniblockNumber,
+    ) -> RpcResult<Option<ConsensusBlockCertificate>> {
+        self.get_consensus_block_certificate_impl(block_number)
+            .await
+            .map_err(into_jsrpc_error)
+    }
+
+    async fn get_priority_op_status(&self, l1_tx_hash: H256) -> RpcResult<Vec<PriorityOpStatus>> {
+        self.get_priority_op_status_impl(l1_tx_hash)
+            .await
+            .map_err(into_jsrpc_error)
+    }
+
+    async fn get_l1_batch_commitment_artifacts(
+        &self,
+        batch: L1BatchNumber,
+    ) -> RpcResult<Option<L1BatchCommitmentArtifacts>> {
+        self.get_l1_batch_commitment_artifacts_impl(batch)
+            .await
+            .map_err(into_jsrpc_error)
+    }
+
+    async fn get_l1_batch_pubdata(
+        &self,
+        batch: L1BatchNumber,
+    ) -> RpcResult<Option<L1BatchPubdata>> {
+        self.get_l1_batch_pubdata_impl(batch)
+            .await
+            .map_err(into_jsrpc_error)
+    }
+
+    async fn get_l1_batch_paymaster_stats(
+        &self,
+        batch: L1BatchNumber,
+    ) -> RpcResult<Option<L1BatchPaymasterStats>> {
+        self.get_l1_batch_paymaster_stats_impl(batch)
+            .await
+            .map_err(into_jsrpc_error)
+    }
+
+    async fn get_top_paymasters(
+        &self,
+        from_batch: L1BatchNumber,
+        to_batch: L1BatchNumber,
+        limit: Option<u32>,
+    ) -> RpcResult<Vec<PaymasterUsage>> {
+        self.get_top_paymasters_impl(from_batch, to_batch, limit)
+            .await
+            .map_err(into_jsrpc_error)
+    }
+
+    async fn get_logs_paginated(
+        &self,
+        filter: Filter,
+        cursor: Option<String>,
+        limit: Option<u32>,
+    ) -> RpcResult<LogsPage> {
+        self.get_logs_paginated_impl(filter, cursor, limit)
+            .await
+            .map_err(into_jsrpc_error)
+    }
+
+    async fn get_node_stats(&self) -> RpcResult<Option<NodeStats>> {
+        Ok(self.get_node_stats_impl())
+    }
 }
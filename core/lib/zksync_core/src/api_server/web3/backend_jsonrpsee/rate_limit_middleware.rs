@@ -0,0 +1,462 @@
+//! Per-client rate limiting for JSON-RPC calls.
+//!
+//! Limits are configured separately for several method groups (see [`MethodGroup`]) and are keyed
+//! by the client identity: the API key (if the corresponding header is configured and present in the request)
+//! or the client IP address. The IP address is taken from the `X-Forwarded-For` entry appended by the outermost
+//! trusted reverse proxy, or from the peer socket address if no proxies are trusted. Identity is extracted
+//! by [`ClientKeyLayer`] on the HTTP level and is passed to [`MethodQuotaMiddleware`] via a task-local variable.
+
+use std::{
+    future::Future,
+    net::{IpAddr, SocketAddr},
+    num::NonZeroU32,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+        Arc,
+    },
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use governor::{
+    clock::DefaultClock, middleware::NoOpMiddleware, state::keyed::DefaultKeyedStateStore, Quota,
+    RateLimiter,
+};
+use reqwest::{
+    header::{HeaderMap, HeaderName},
+    StatusCode,
+};
+use vise::{Counter, EncodeLabelSet, EncodeLabelValue, Family, Metrics};
+use zksync_web3_decl::jsonrpsee::{
+    server::middleware::rpc::{layer::ResponseFuture, RpcServiceT},
+    types::{error::ErrorCode, ErrorObject, Request},
+    MethodResponse,
+};
+
+use super::batch_limiter_middleware::Transport;
+
+/// Group of JSON-RPC methods sharing a rate limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EncodeLabelValue)]
+#[metrics(rename_all = "snake_case")]
+pub(crate) enum MethodGroup {
+    /// All methods not covered by other groups.
+    Read,
+    /// `eth_sendRawTransaction`.
+    SendTx,
+    /// Methods in the `debug` namespace.
+    Debug,
+}
+
+impl MethodGroup {
+    fn new(method_name: &str) -> Self {
+        if method_name == "eth_sendRawTransaction" {
+            Self::SendTx
+        } else if method_name.starts_with("debug_") {
+            Self::Debug
+        } else {
+            Self::Read
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EncodeLabelSet)]
+struct RateLimitLabels {
+    transport: Transport,
+    group: MethodGroup,
+}
+
+#[derive(Debug, Metrics)]
+#[metrics(prefix = "api_jsonrpc_backend_rate_limit")]
+struct RateLimitMetrics {
+    /// Number of calls rejected because of per-client method quotas.
+    rejected_calls: Family<RateLimitLabels, Counter>,
+    /// Number of HTTP requests responded with the 429 status code.
+    rejected_http_requests: Counter,
+}
+
+#[vise::register]
+static METRICS: vise::Global<RateLimitMetrics> = vise::Global::new();
+
+/// Per-minute call quotas for a single client.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MethodQuotas {
+    pub read: Option<NonZeroU32>,
+    pub send_tx: Option<NonZeroU32>,
+    pub debug: Option<NonZeroU32>,
+}
+
+impl MethodQuotas {
+    pub(crate) fn is_empty(&self) -> bool {
+        self.read.is_none() && self.send_tx.is_none() && self.debug.is_none()
+    }
+}
+
+/// Client identity used as a rate limiting key.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) enum ClientKey {
+    ApiKey(String),
+    Ip(IpAddr),
+    /// Session that could not be associated with an API key or an IP address.
+    Session(u64),
+}
+
+impl ClientKey {
+    /// Identifies the client. `trusted_proxy_count` is the number of reverse proxies in front of the server
+    /// that append to the `X-Forwarded-For` header; entries to the left of the one appended by the outermost
+    /// trusted proxy are client-controlled and are thus ignored.
+    fn new(
+        headers: &HeaderMap,
+        peer_addr: Option<SocketAddr>,
+        api_key_header: Option<&HeaderName>,
+        trusted_proxy_count: usize,
+    ) -> Option<Self> {
+        let api_key = api_key_header
+            .and_then(|name| headers.get(name))
+            .and_then(|value| value.to_str().ok())
+            .filter(|value| !value.is_empty());
+        if let Some(api_key) = api_key {
+            return Some(Self::ApiKey(api_key.to_owned()));
+        }
+
+        if trusted_proxy_count > 0 {
+            let forwarded_ip = headers
+                .get_all("x-forwarded-for")
+                .iter()
+                .filter_map(|value| value.to_str().ok())
+                .flat_map(|value| value.split(','))
+                .collect::<Vec<_>>();
+            // The header may be missing if the request has bypassed the proxies, in which case we fall back
+            // to the peer address.
+            let ip = forwarded_ip
+                .iter()
+                .rev()
+                .nth(trusted_proxy_count - 1)
+                .and_then(|ip| ip.trim().parse().ok());
+            if let Some(ip) = ip {
+                return Some(Self::Ip(ip));
+            }
+        }
+        peer_addr.map(|addr| Self::Ip(addr.ip()))
+    }
+}
+
+/// Client information shared between [`ClientKeyLayer`] and [`MethodQuotaMiddleware`].
+#[derive(Debug)]
+struct ClientContext {
+    key: ClientKey,
+    calls: AtomicUsize,
+    rate_limited_calls: AtomicUsize,
+}
+
+tokio::task_local! {
+    static CLIENT_CONTEXT: Arc<ClientContext>;
+}
+
+type KeyedRateLimiter =
+    RateLimiter<ClientKey, DefaultKeyedStateStore<ClientKey>, DefaultClock, NoOpMiddleware>;
+
+/// Rate limiters for all method groups shared among all server sessions.
+pub(crate) struct MethodRateLimiters {
+    read: Option<KeyedRateLimiter>,
+    send_tx: Option<KeyedRateLimiter>,
+    debug: Option<KeyedRateLimiter>,
+}
+
+impl MethodRateLimiters {
+    /// Interval between pruning inactive client keys from the rate limiter state.
+    const PRUNE_INTERVAL: Duration = Duration::from_secs(60);
+
+    pub(crate) fn new(quotas: MethodQuotas) -> Self {
+        let limiter = |limit: Option<NonZeroU32>| {
+            limit.map(|limit| RateLimiter::keyed(Quota::per_minute(limit)))
+        };
+        Self {
+            read: limiter(quotas.read),
+            send_tx: limiter(quotas.send_tx),
+            debug: limiter(quotas.debug),
+        }
+    }
+
+    fn limiter(&self, group: MethodGroup) -> Option<&KeyedRateLimiter> {
+        match group {
+            MethodGroup::Read => self.read.as_ref(),
+            MethodGroup::SendTx => self.send_tx.as_ref(),
+            MethodGroup::Debug => self.debug.as_ref(),
+        }
+    }
+
+    fn check(&self, group: MethodGroup, key: &ClientKey) -> bool {
+        self.limiter(group)
+            .map_or(true, |limiter| limiter.check_key(key).is_ok())
+    }
+
+    /// Periodically removes state for clients that have fully replenished their quotas.
+    /// Should be spawned as a background task; terminates once the limiters are dropped elsewhere.
+    pub(crate) async fn run_pruning(self: Arc<Self>) {
+        let mut interval = tokio::time::interval(Self::PRUNE_INTERVAL);
+        loop {
+            interval.tick().await;
+            if Arc::strong_count(&self) == 1 {
+                return;
+            }
+            for limiter in [&self.read, &self.send_tx, &self.debug]
+                .into_iter()
+                .flatten()
+            {
+                limiter.retain_recent();
+                limiter.shrink_to_fit();
+            }
+        }
+    }
+}
+
+/// HTTP-level layer extracting client identity from request headers and the peer address
+/// (passed as a [`SocketAddr`] request extension). Responds with the 429 status code
+/// if all calls in the request were rate-limited.
+#[derive(Debug, Clone)]
+pub(crate) struct ClientKeyLayer {
+    api_key_header: Option<HeaderName>,
+    trusted_proxy_count: usize,
+}
+
+impl ClientKeyLayer {
+    pub(crate) fn new(api_key_header: Option<HeaderName>, trusted_proxy_count: usize) -> Self {
+        Self {
+            api_key_header,
+            trusted_proxy_count,
+        }
+    }
+}
+
+impl<S> tower::Layer<S> for ClientKeyLayer {
+    type Service = ClientKeyService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        ClientKeyService {
+            inner,
+            api_key_header: self.api_key_header.clone(),
+            trusted_proxy_count: self.trusted_proxy_count,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct ClientKeyService<S> {
+    inner: S,
+    api_key_header: Option<HeaderName>,
+    trusted_proxy_count: usize,
+}
+
+impl<S, B, RB> tower::Service<http::Request<B>> for ClientKeyService<S>
+where
+    S: tower::Service<http::Request<B>, Response = http::Response<RB>>,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: http::Request<B>) -> Self::Future {
+        let peer_addr = request.extensions().get::<SocketAddr>().copied();
+        let key = ClientKey::new(
+            request.headers(),
+            peer_addr,
+            self.api_key_header.as_ref(),
+            self.trusted_proxy_count,
+        );
+        let Some(key) = key else {
+            // Shouldn't happen since the server always provides the peer address; the RPC middleware
+            // will use a per-session key in this case.
+            return Box::pin(self.inner.call(request));
+        };
+        let context = Arc::new(ClientContext {
+            key,
+            calls: AtomicUsize::new(0),
+            rate_limited_calls: AtomicUsize::new(0),
+        });
+        // The inner service may create RPC middleware synchronously (e.g., for WebSocket connections),
+        // so we set the context both for `call()` and for the returned future.
+        let inner_future = CLIENT_CONTEXT.sync_scope(context.clone(), || self.inner.call(request));
+        let future = CLIENT_CONTEXT.scope(context.clone(), inner_future);
+
+        Box::pin(async move {
+            let mut response = future.await?;
+            let calls = context.calls.load(Ordering::Relaxed);
+            let rate_limited_calls = context.rate_limited_calls.load(Ordering::Relaxed);
+            if calls > 0 && rate_limited_calls == calls {
+                METRICS.rejected_http_requests.inc();
+                *response.status_mut() = StatusCode::TOO_MANY_REQUESTS;
+            }
+            Ok(response)
+        })
+    }
+}
+
+/// RPC-level middleware enforcing [`MethodQuotas`].
+///
+/// `jsonrpsee` will allocate the instance of this struct once per session (i.e., per HTTP request or per
+/// WebSocket connection).
+pub(crate) struct MethodQuotaMiddleware<S> {
+    inner: S,
+    limiters: Arc<MethodRateLimiters>,
+    /// Key used if the client context is not available for a call.
+    fallback_key: ClientKey,
+    transport: Transport,
+}
+
+impl<S> MethodQuotaMiddleware<S> {
+    pub(crate) fn new(inner: S, limiters: Arc<MethodRateLimiters>, transport: Transport) -> Self {
+        static SESSION_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+        let fallback_key = CLIENT_CONTEXT
+            .try_with(|context| context.key.clone())
+            .unwrap_or_else(|_| {
+                ClientKey::Session(SESSION_COUNTER.fetch_add(1, Ordering::Relaxed))
+            });
+        Self {
+            inner,
+            limiters,
+            fallback_key,
+            transport,
+        }
+    }
+}
+
+impl<'a, S> RpcServiceT<'a> for MethodQuotaMiddleware<S>
+where
+    S: Send + Clone + Sync + RpcServiceT<'a>,
+{
+    type Future = ResponseFuture<S::Future>;
+
+    fn call(&self, request: Request<'a>) -> Self::Future {
+        let group = MethodGroup::new(request.method_name());
+        let context = CLIENT_CONTEXT.try_with(Arc::clone).ok();
+        let key = context
+            .as_ref()
+            .map_or(&self.fallback_key, |context| &context.key);
+        if let Some(context) = &context {
+            context.calls.fetch_add(1, Ordering::Relaxed);
+        }
+
+        if !self.limiters.check(group, key) {
+            METRICS.rejected_calls[&RateLimitLabels {
+                transport: self.transport,
+                group,
+            }]
+                .inc();
+            if let Some(context) = &context {
+                context.rate_limited_calls.fetch_add(1, Ordering::Relaxed);
+            }
+
+            let rp = MethodResponse::error(
+                request.id,
+                ErrorObject::borrowed(
+                    ErrorCode::ServerError(StatusCode::TOO_MANY_REQUESTS.as_u16().into()).code(),
+                    "Too many requests",
+                    None,
+                ),
+            );
+            return ResponseFuture::ready(rp);
+        }
+        ResponseFuture::future(self.inner.call(request))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn method_groups() {
+        assert_eq!(MethodGroup::new("eth_call"), MethodGroup::Read);
+        assert_eq!(MethodGroup::new("zks_getL1BatchDetails"), MethodGroup::Read);
+        assert_eq!(
+            MethodGroup::new("eth_sendRawTransaction"),
+            MethodGroup::SendTx
+        );
+        assert_eq!(MethodGroup::new("debug_traceCall"), MethodGroup::Debug);
+    }
+
+    #[test]
+    fn extracting_client_key() {
+        let api_key_header = HeaderName::from_static("x-api-key");
+        let peer_addr = SocketAddr::from(([10, 0, 0, 100], 12345));
+        let mut headers = HeaderMap::new();
+        assert_eq!(
+            ClientKey::new(&headers, None, Some(&api_key_header), 1),
+            None
+        );
+        assert_eq!(
+            ClientKey::new(&headers, Some(peer_addr), Some(&api_key_header), 1),
+            Some(ClientKey::Ip(peer_addr.ip()))
+        );
+
+        // The first entry is spoofed by the client; the second one is appended by the trusted proxy,
+        // and the last one by an untrusted hop.
+        headers.insert(
+            "x-forwarded-for",
+            "1.2.3.4, 10.0.0.1, 10.0.0.3".parse().unwrap(),
+        );
+        assert_eq!(
+            ClientKey::new(&headers, Some(peer_addr), Some(&api_key_header), 1),
+            Some(ClientKey::Ip([10, 0, 0, 3].into()))
+        );
+        assert_eq!(
+            ClientKey::new(&headers, Some(peer_addr), Some(&api_key_header), 2),
+            Some(ClientKey::Ip([10, 0, 0, 1].into()))
+        );
+        // If no proxies are trusted, the header is ignored.
+        assert_eq!(
+            ClientKey::new(&headers, Some(peer_addr), Some(&api_key_header), 0),
+            Some(ClientKey::Ip(peer_addr.ip()))
+        );
+        // Not enough hops (e.g., the request bypassed one of the proxies).
+        assert_eq!(
+            ClientKey::new(&headers, Some(peer_addr), Some(&api_key_header), 5),
+            Some(ClientKey::Ip(peer_addr.ip()))
+        );
+
+        headers.insert(api_key_header.clone(), "secret".parse().unwrap());
+        assert_eq!(
+            ClientKey::new(&headers, Some(peer_addr), Some(&api_key_header), 1),
+            Some(ClientKey::ApiKey("secret".to_owned()))
+        );
+        assert_eq!(
+            ClientKey::new(&headers, Some(peer_addr), None, 1),
+            Some(ClientKey::Ip([10, 0, 0, 3].into()))
+        );
+    }
+
+    #[test]
+    fn extracting_client_key_from_multiple_forwarded_headers() {
+        let mut headers = HeaderMap::new();
+        headers.append("x-forwarded-for", "1.2.3.4".parse().unwrap());
+        headers.append("x-forwarded-for", "10.0.0.1, 10.0.0.2".parse().unwrap());
+        assert_eq!(
+            ClientKey::new(&headers, None, None, 3),
+            Some(ClientKey::Ip([1, 2, 3, 4].into()))
+        );
+    }
+
+    #[test]
+    fn keyed_limits() {
+        let limiters = MethodRateLimiters::new(MethodQuotas {
+            send_tx: NonZeroU32::new(1),
+            ..MethodQuotas::default()
+        });
+        let alice = ClientKey::ApiKey("alice".to_owned());
+        let bob = ClientKey::ApiKey("bob".to_owned());
+
+        assert!(limiters.check(MethodGroup::SendTx, &alice));
+        assert!(!limiters.check(MethodGroup::SendTx, &alice));
+        assert!(limiters.check(MethodGroup::SendTx, &bob));
+        for _ in 0..10 {
+            assert!(limiters.check(MethodGroup::Read, &alice));
+        }
+    }
+}
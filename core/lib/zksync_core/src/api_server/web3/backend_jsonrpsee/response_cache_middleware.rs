@@ -0,0 +1,355 @@
+//! Caching of immutable JSON-RPC responses shared between HTTP and WS servers.
+
+use std::{
+    sync::{
+        atomic::{AtomicI64, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use anyhow::Context as _;
+use futures::{future::BoxFuture, FutureExt};
+use mini_moka::sync::Cache;
+use serde::Deserialize;
+use tokio::sync::watch;
+use vise::{Counter, EncodeLabelSet, EncodeLabelValue, Family, Gauge, Metrics};
+use zksync_dal::ConnectionPool;
+use zksync_types::{api, MiniblockNumber};
+use zksync_web3_decl::jsonrpsee::{
+    server::middleware::rpc::RpcServiceT,
+    types::{Request, ResponsePayload},
+    MethodResponse,
+};
+
+use super::batch_limiter_middleware::Transport;
+
+/// Interval between updates of the finalized miniblock number used by the cache.
+const FINALITY_UPDATE_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Block tags in call params. Responses for calls referencing a tag are never cached since they can change
+/// with each new block.
+const BLOCK_TAGS: [&str; 6] = [
+    "\"latest\"",
+    "\"pending\"",
+    "\"committed\"",
+    "\"finalized\"",
+    "\"safe\"",
+    "\"earliest\"",
+];
+
+/// Conditions under which the response of a certain method can be cached.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CachePolicy {
+    /// Response depends only on the node configuration.
+    Static,
+    /// Response is immutable once it's non-null (e.g., it's content-addressed).
+    NonNull,
+    /// Response is immutable once it's non-null and the miniblock in the specified response field is finalized.
+    Finalized(&'static str),
+}
+
+impl CachePolicy {
+    const METHODS: &'static [(&'static str, Self)] = &[
+        ("eth_chainId", Self::Static),
+        ("net_version", Self::Static),
+        ("zks_L1ChainId", Self::Static),
+        ("zks_getMainContract", Self::Static),
+        ("zks_getBridgeContracts", Self::Static),
+        ("zks_getBytecodeByHash", Self::NonNull),
+        ("eth_getBlockByNumber", Self::Finalized("number")),
+        ("eth_getBlockByHash", Self::Finalized("number")),
+        ("zks_getBlockDetails", Self::Finalized("number")),
+        ("eth_getTransactionByHash", Self::Finalized("blockNumber")),
+        ("eth_getTransactionReceipt", Self::Finalized("blockNumber")),
+    ];
+
+    fn for_method(method: &str) -> Option<(&'static str, Self)> {
+        Self::METHODS
+            .iter()
+            .find(|(name, _)| *name == method)
+            .copied()
+    }
+
+    fn allows(self, result: &serde_json::Value, finalized_miniblock: Option<u64>) -> bool {
+        match self {
+            Self::Static => true,
+            Self::NonNull => !result.is_null(),
+            Self::Finalized(field) => {
+                let Some(finalized_miniblock) = finalized_miniblock else {
+                    return false;
+                };
+                let number = result.get(field).and_then(|number| {
+                    // Web3 types encode block numbers as hex strings, while zkSync-specific types use plain integers.
+                    number.as_u64().or_else(|| {
+                        let hex = number.as_str()?.strip_prefix("0x")?;
+                        u64::from_str_radix(hex, 16).ok()
+                    })
+                });
+                number.map_or(false, |number| number <= finalized_miniblock)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EncodeLabelValue)]
+#[metrics(rename_all = "snake_case")]
+enum CacheOutcome {
+    Hit,
+    Miss,
+    Uncacheable,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, EncodeLabelSet)]
+struct CacheLabels {
+    transport: Transport,
+    method: &'static str,
+    outcome: CacheOutcome,
+}
+
+#[derive(Debug, Metrics)]
+#[metrics(prefix = "api_jsonrpc_backend_response_cache")]
+struct ResponseCacheMetrics {
+    /// Number of calls to cacheable methods split by the cache outcome.
+    calls: Family<CacheLabels, Counter>,
+    /// Approximate size of cached responses in bytes.
+    used_bytes: Gauge<u64>,
+    /// Finalized miniblock used to decide whether block-related responses can be cached.
+    finalized_miniblock: Gauge<u64>,
+}
+
+#[vise::register]
+static METRICS: vise::Global<ResponseCacheMetrics> = vise::Global::new();
+
+#[derive(Debug, Clone)]
+struct CachedResponse {
+    result: Arc<serde_json::Value>,
+    size: usize,
+}
+
+/// Successful JSON-RPC response with the result deserialized into a generic JSON value.
+#[derive(Debug, Deserialize)]
+struct SerializedResponse {
+    result: serde_json::Value,
+}
+
+/// Cache of immutable JSON-RPC responses (e.g., the chain ID, finalized blocks or bytecodes by hash),
+/// bounded by memory usage and entry TTL. The cache is cheap to clone; a single instance should be shared
+/// among HTTP and WS servers.
+#[derive(Debug, Clone)]
+pub struct ResponseCache {
+    cache: Cache<(&'static str, String), CachedResponse>,
+    /// Last finalized miniblock number, or -1 if it's not known yet.
+    finalized_miniblock: Arc<AtomicI64>,
+}
+
+impl ResponseCache {
+    /// Creates a cache with the specified capacity in bytes and TTL for entries.
+    pub fn new(capacity: u64, time_to_live: Duration) -> Self {
+        let cache = Cache::builder()
+            .weigher(
+                |(_, params): &(&'static str, String), value: &CachedResponse| {
+                    (params.len() + value.size).try_into().unwrap_or(u32::MAX)
+                },
+            )
+            .max_capacity(capacity)
+            .time_to_live(time_to_live)
+            .build();
+        Self {
+            cache,
+            finalized_miniblock: Arc::new(AtomicI64::new(-1)),
+        }
+    }
+
+    fn finalized_miniblock(&self) -> Option<u64> {
+        let number = self.finalized_miniblock.load(Ordering::Relaxed);
+        u64::try_from(number).ok()
+    }
+
+    pub(crate) fn set_finalized_miniblock(&self, number: MiniblockNumber) {
+        self.finalized_miniblock
+            .store(number.0.into(), Ordering::Relaxed);
+        METRICS.finalized_miniblock.set(number.0.into());
+    }
+
+    fn get(&self, key: &(&'static str, String)) -> Option<CachedResponse> {
+        self.cache.get(key)
+    }
+
+    #[cfg(test)]
+    pub(crate) fn cached_methods(&self) -> std::collections::HashSet<&'static str> {
+        self.cache.iter().map(|entry| entry.key().0).collect()
+    }
+
+    fn insert_if_cacheable(
+        &self,
+        key: (&'static str, String),
+        policy: CachePolicy,
+        response: &MethodResponse,
+    ) {
+        if !response.is_success() {
+            return;
+        }
+        let response_size = response.result.len();
+        let result = match serde_json::from_str::<SerializedResponse>(&response.result) {
+            Ok(response) => response.result,
+            Err(err) => {
+                tracing::warn!("Failed deserializing response for `{}`: {err}", key.0);
+                return;
+            }
+        };
+        if policy.allows(&result, self.finalized_miniblock()) {
+            let value = CachedResponse {
+                result: Arc::new(result),
+                size: response_size,
+            };
+            self.cache.insert(key, value);
+            METRICS.used_bytes.set(self.cache.weighted_size());
+        }
+    }
+
+    async fn update_finalized_miniblock(&self, pool: &ConnectionPool) -> anyhow::Result<()> {
+        let mut storage = pool.access_storage_tagged("api").await?;
+        let finalized_miniblock = storage
+            .blocks_web3_dal()
+            .resolve_block_id(api::BlockId::Number(api::BlockNumber::Finalized))
+            .await
+            .context("failed resolving finalized miniblock")?;
+        drop(storage);
+
+        if let Some(number) = finalized_miniblock {
+            self.set_finalized_miniblock(number);
+        }
+        Ok(())
+    }
+
+    /// Periodically updates the finalized miniblock number, which determines whether block-related
+    /// responses are cacheable. Errors are logged and the update is retried on the next tick;
+    /// in the meantime, the cache keeps using the last known finalized miniblock.
+    pub async fn run_finality_updates(
+        self,
+        pool: ConnectionPool,
+        mut stop_receiver: watch::Receiver<bool>,
+    ) -> anyhow::Result<()> {
+        while !*stop_receiver.borrow() {
+            if let Err(err) = self.update_finalized_miniblock(&pool).await {
+                tracing::warn!("Failed updating finalized miniblock for response cache: {err:#}");
+            }
+            tokio::time::timeout(FINALITY_UPDATE_INTERVAL, stop_receiver.changed())
+                .await
+                .ok();
+        }
+        tracing::info!("Stop signal received, response cache finality updates are shutting down");
+        Ok(())
+    }
+}
+
+/// Middleware serving responses for immutable data from a [`ResponseCache`].
+///
+/// `jsonrpsee` will allocate the instance of this struct once per session.
+pub(crate) struct ResponseCacheMiddleware<S> {
+    inner: S,
+    cache: Option<ResponseCache>,
+    transport: Transport,
+}
+
+impl<S> ResponseCacheMiddleware<S> {
+    pub(crate) fn new(inner: S, cache: Option<ResponseCache>, transport: Transport) -> Self {
+        Self {
+            inner,
+            cache,
+            transport,
+        }
+    }
+}
+
+impl<'a, S> RpcServiceT<'a> for ResponseCacheMiddleware<S>
+where
+    S: Send + Clone + Sync + RpcServiceT<'a>,
+    S::Future: 'a,
+{
+    type Future = BoxFuture<'a, MethodResponse>;
+
+    fn call(&self, request: Request<'a>) -> Self::Future {
+        let Some(cache) = &self.cache else {
+            return self.inner.call(request).boxed();
+        };
+        let Some((method, policy)) = CachePolicy::for_method(request.method_name()) else {
+            return self.inner.call(request).boxed();
+        };
+
+        let params = request.params();
+        let params = params.as_str().unwrap_or_default();
+        let mut labels = CacheLabels {
+            transport: self.transport,
+            method,
+            outcome: CacheOutcome::Uncacheable,
+        };
+        if BLOCK_TAGS.iter().any(|tag| params.contains(tag)) {
+            METRICS.calls[&labels].inc();
+            return self.inner.call(request).boxed();
+        }
+
+        let key = (method, params.to_owned());
+        if let Some(cached) = cache.get(&key) {
+            labels.outcome = CacheOutcome::Hit;
+            METRICS.calls[&labels].inc();
+            let payload = ResponsePayload::result(&*cached.result);
+            let response = MethodResponse::response(request.id, payload, usize::MAX);
+            return futures::future::ready(response).boxed();
+        }
+
+        labels.outcome = CacheOutcome::Miss;
+        METRICS.calls[&labels].inc();
+        let cache = cache.clone();
+        let response = self.inner.call(request);
+        async move {
+            let response = response.await;
+            cache.insert_if_cacheable(key, policy, &response);
+            response
+        }
+        .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn determining_cache_policy() {
+        assert_eq!(
+            CachePolicy::for_method("eth_chainId"),
+            Some(("eth_chainId", CachePolicy::Static))
+        );
+        assert_eq!(
+            CachePolicy::for_method("eth_getTransactionReceipt"),
+            Some((
+                "eth_getTransactionReceipt",
+                CachePolicy::Finalized("blockNumber")
+            ))
+        );
+        assert_eq!(CachePolicy::for_method("eth_blockNumber"), None);
+        assert_eq!(CachePolicy::for_method("eth_getLogs"), None);
+    }
+
+    #[test]
+    fn checking_cacheability() {
+        assert!(CachePolicy::Static.allows(&json!("0x10e"), None));
+        assert!(!CachePolicy::NonNull.allows(&json!(null), None));
+        assert!(CachePolicy::NonNull.allows(&json!("0x0123"), None));
+
+        let policy = CachePolicy::Finalized("number");
+        let block = json!({ "number": "0x5", "hash": "0x00" });
+        assert!(!policy.allows(&block, None));
+        assert!(!policy.allows(&block, Some(4)));
+        assert!(policy.allows(&block, Some(5)));
+        assert!(!policy.allows(&json!(null), Some(5)));
+
+        let block_details = json!({ "number": 5 });
+        assert!(policy.allows(&block_details, Some(10)));
+        assert!(!policy.allows(&block_details, Some(3)));
+    }
+}
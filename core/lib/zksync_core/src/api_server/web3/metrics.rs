@@ -147,6 +147,9 @@ pub(super) struct ApiMetrics {
     pub web3_in_flight_requests: Family<ApiTransportLabel, Histogram<usize>>,
     /// Number of currently open WebSocket sessions.
     pub ws_open_sessions: Gauge,
+    /// Lifetime of WebSocket sessions.
+    #[metrics(buckets = Buckets::exponential(1.0..=86_400.0, 4.0), unit = Unit::Seconds)]
+    pub ws_session_lifetime: Histogram<Duration>,
 }
 
 impl ApiMetrics {
@@ -179,6 +182,7 @@ pub(super) enum SubscriptionType {
     Blocks,
     Txs,
     Logs,
+    L1BatchStatuses,
 }
 
 #[derive(Debug, Metrics)]
@@ -205,6 +209,13 @@ pub(super) struct PubSubMetrics {
     pub skipped_broadcast_messages: Family<SubscriptionType, Histogram<u64>>,
     /// Number of subscribers dropped because of a send timeout.
     pub subscriber_send_timeouts: Family<SubscriptionType, Counter>,
+    /// Number of notifications not delivered to subscribers dropped because of a send timeout.
+    pub dropped_notifications: Family<SubscriptionType, Counter>,
+    /// Number of currently registered log subscriptions in the log subscriptions index.
+    pub log_subscriptions: Gauge<usize>,
+    /// Latency to match new logs against the log subscriptions index and send them to matching subscribers.
+    #[metrics(buckets = Buckets::LATENCIES)]
+    pub logs_dispatch_latency: Histogram<Duration>,
 }
 
 #[vise::register]
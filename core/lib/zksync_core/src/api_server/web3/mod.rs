@@ -1,11 +1,20 @@
-use std::{net::SocketAddr, num::NonZeroU32, sync::Arc, time::Duration};
+use std::{
+    net::SocketAddr,
+    num::{NonZeroU32, NonZeroUsize},
+    sync::Arc,
+    time::Duration,
+};
 
 use anyhow::Context as _;
 use chrono::NaiveDateTime;
 use futures::future;
+use hyper::{
+    server::conn::{AddrIncoming, AddrStream},
+    service::make_service_fn,
+};
 use serde::Deserialize;
 use tokio::{
-    sync::{mpsc, oneshot, watch, Mutex},
+    sync::{mpsc, oneshot, watch, Mutex, Semaphore},
     task::JoinHandle,
 };
 use tower_http::{cors::CorsLayer, metrics::InFlightRequestsLayer};
@@ -15,12 +24,16 @@ use zksync_types::{api, MiniblockNumber};
 use zksync_web3_decl::{
     error::Web3Error,
     jsonrpsee::{
-        server::{BatchRequestConfig, RpcServiceBuilder, ServerBuilder},
-        RpcModule,
+        server::{
+            stop_channel, BatchRequestConfig, PingConfig, RpcServiceBuilder, ServerBuilder,
+            ServerHandle, StopHandle,
+        },
+        Methods, RpcModule,
     },
     namespaces::{
-        DebugNamespaceServer, EnNamespaceServer, EthNamespaceServer, EthPubSubServer,
-        NetNamespaceServer, SnapshotsNamespaceServer, Web3NamespaceServer, ZksNamespaceServer,
+        AdminNamespaceServer, DebugNamespaceServer, EnNamespaceServer, EthNamespaceServer,
+        EthPubSubServer, NetNamespaceServer, SnapshotsNamespaceServer, Web3NamespaceServer,
+        ZksNamespaceServer,
     },
     types::Filter,
 };
@@ -29,17 +42,29 @@ use self::{
     backend_jsonrpsee::internal_error,
     metrics::API_METRICS,
     namespaces::{
-        DebugNamespace, EnNamespace, EthNamespace, NetNamespace, SnapshotsNamespace, Web3Namespace,
-        ZksNamespace,
+        AdminNamespace, DebugNamespace, EnNamespace, EthNamespace, NetNamespace,
+        SnapshotsNamespace, Web3Namespace, ZksNamespace,
     },
     pubsub::{EthSubscribe, EthSubscriptionIdProvider, PubSubEvent},
-    state::{Filters, InternalApiConfig, RpcState, SealedMiniblockNumber},
+    state::{Filters, InternalApiConfig, NodeStatsCollector, RpcState, SealedMiniblockNumber},
 };
 use crate::{
     api_server::{
-        execution_sandbox::VmConcurrencyBarrier, tree::TreeApiHttpClient, tx_sender::TxSender,
-        web3::backend_jsonrpsee::batch_limiter_middleware::LimitMiddleware,
+        execution_sandbox::VmConcurrencyBarrier,
+        tree::{TreeApiClient, TreeApiHttpClient},
+        tx_sender::TxSender,
+        web3::backend_jsonrpsee::{
+            batch_limiter_middleware::{LimitMiddleware, Transport},
+            concurrent_batch_middleware::ConcurrentBatchLayer,
+            rate_limit_middleware::{
+                ClientKeyLayer, MethodQuotaMiddleware, MethodQuotas, MethodRateLimiters,
+            },
+            response_cache_middleware::{ResponseCache, ResponseCacheMiddleware},
+        },
     },
+    eth_sender::ResubmissionPolicy,
+    metadata_calculator::LazyAsyncTreeReader,
+    state_keeper::MempoolGuard,
     sync_layer::SyncState,
 };
 
@@ -82,6 +107,8 @@ pub enum Namespace {
     En,
     Pubsub,
     Snapshots,
+    /// Operator-only methods; must not be exposed publicly.
+    Admin,
 }
 
 impl Namespace {
@@ -110,10 +137,28 @@ struct OptionalApiParams {
     filters_limit: Option<usize>,
     subscriptions_limit: Option<usize>,
     batch_request_size_limit: Option<usize>,
+    batch_request_concurrency: Option<NonZeroUsize>,
     response_body_size_limit: Option<usize>,
     websocket_requests_per_minute_limit: Option<NonZeroU32>,
-    tree_api_url: Option<String>,
+    websocket_subscriptions_per_connection_limit: Option<u32>,
+    websocket_idle_timeout: Option<Duration>,
+    pub_sub_send_timeout: Option<Duration>,
+    method_quotas: MethodQuotas,
+    rate_limit_api_key_header: Option<String>,
+    rate_limit_trusted_proxy_count: usize,
+    tree_api: Option<Arc<dyn TreeApiClient>>,
     pub_sub_events_sender: Option<mpsc::UnboundedSender<PubSubEvent>>,
+    resubmission_policy: Option<ResubmissionPolicy>,
+    mempool: Option<MempoolGuard>,
+    response_cache: Option<ResponseCache>,
+}
+
+/// Limits applied to WebSocket connections.
+#[derive(Debug, Clone, Copy)]
+struct WebSocketLimits {
+    requests_per_minute: Option<NonZeroU32>,
+    subscriptions_per_connection: Option<u32>,
+    idle_timeout: Option<Duration>,
 }
 
 /// Full API server parameters.
@@ -121,6 +166,7 @@ struct OptionalApiParams {
 struct FullApiParams {
     pool: ConnectionPool,
     last_miniblock_pool: ConnectionPool,
+    node_stats_pool: ConnectionPool,
     config: InternalApiConfig,
     transport: ApiTransport,
     tx_sender: TxSender,
@@ -134,6 +180,7 @@ struct FullApiParams {
 pub struct ApiBuilder {
     pool: ConnectionPool,
     last_miniblock_pool: ConnectionPool,
+    node_stats_pool: ConnectionPool,
     config: InternalApiConfig,
     polling_interval: Duration,
     // Mandatory params that must be set using builder methods.
@@ -152,6 +199,7 @@ impl ApiBuilder {
     pub fn jsonrpsee_backend(config: InternalApiConfig, pool: ConnectionPool) -> Self {
         Self {
             last_miniblock_pool: pool.clone(),
+            node_stats_pool: pool.clone(),
             pool,
             config,
             polling_interval: Self::DEFAULT_POLLING_INTERVAL,
@@ -181,6 +229,14 @@ impl ApiBuilder {
         self
     }
 
+    /// Configures a dedicated DB pool to be used for collecting node stats in a background task. If not called,
+    /// the main pool will be used. Stats collection runs heavy queries, so it must not share a single-connection pool
+    /// with latest miniblock updates.
+    pub fn with_node_stats_pool(mut self, pool: ConnectionPool) -> Self {
+        self.node_stats_pool = pool;
+        self
+    }
+
     pub fn with_tx_sender(mut self, tx_sender: TxSender, vm_barrier: VmConcurrencyBarrier) -> Self {
         self.tx_sender = Some(tx_sender);
         self.vm_barrier = Some(vm_barrier);
@@ -202,6 +258,14 @@ impl ApiBuilder {
         self
     }
 
+    /// Sets the maximum number of calls from a single batch request executed concurrently. Only applies
+    /// to the HTTP server; values less than 2 mean that batch calls are executed sequentially.
+    pub fn with_batch_request_concurrency(mut self, batch_request_concurrency: usize) -> Self {
+        self.optional.batch_request_concurrency =
+            NonZeroUsize::new(batch_request_concurrency).filter(|value| value.get() > 1);
+        self
+    }
+
     pub fn with_response_body_size_limit(mut self, response_body_size_limit: usize) -> Self {
         self.optional.response_body_size_limit = Some(response_body_size_limit);
         self
@@ -216,6 +280,51 @@ impl ApiBuilder {
         self
     }
 
+    /// Sets the maximum number of active subscriptions per WebSocket connection.
+    pub fn with_websocket_subscriptions_per_connection_limit(mut self, limit: u32) -> Self {
+        self.optional.websocket_subscriptions_per_connection_limit = Some(limit);
+        self
+    }
+
+    /// Sets the timeout after which unresponsive WebSocket connections are closed. Connections are pinged
+    /// to detect whether they are responsive.
+    pub fn with_websocket_idle_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.optional.websocket_idle_timeout = timeout;
+        self
+    }
+
+    /// Sets the timeout for sending a notification to a subscriber. Subscribers that don't accept notifications
+    /// within this timeout are dropped.
+    pub fn with_pub_sub_send_timeout(mut self, timeout: Duration) -> Self {
+        self.optional.pub_sub_send_timeout = Some(timeout);
+        self
+    }
+
+    /// Sets per-client quotas for different method groups. Applies to both HTTP and WS servers.
+    pub fn with_method_quotas(mut self, method_quotas: MethodQuotas) -> Self {
+        self.optional.method_quotas = method_quotas;
+        self
+    }
+
+    /// Sets the name of the HTTP header carrying the client API key, which is used to key method quotas.
+    pub fn with_rate_limit_api_key_header(mut self, header: Option<String>) -> Self {
+        self.optional.rate_limit_api_key_header = header;
+        self
+    }
+
+    /// Sets the number of trusted reverse proxies appending to the `X-Forwarded-For` header. If 0 (the default),
+    /// clients without an API key are identified by the peer address of the connection.
+    pub fn with_rate_limit_trusted_proxy_count(mut self, count: usize) -> Self {
+        self.optional.rate_limit_trusted_proxy_count = count;
+        self
+    }
+
+    /// Sets the cache for immutable responses. The same cache should be shared among HTTP and WS servers.
+    pub fn with_response_cache(mut self, cache: Option<ResponseCache>) -> Self {
+        self.optional.response_cache = cache;
+        self
+    }
+
     pub fn with_sync_state(mut self, sync_state: SyncState) -> Self {
         self.optional.sync_state = Some(sync_state);
         self
@@ -232,7 +341,28 @@ impl ApiBuilder {
     }
 
     pub fn with_tree_api(mut self, tree_api_url: Option<String>) -> Self {
-        self.optional.tree_api_url = tree_api_url;
+        if let Some(url) = tree_api_url {
+            self.optional.tree_api = Some(Arc::new(TreeApiHttpClient::new(&url)));
+        }
+        self
+    }
+
+    /// Serves Merkle tree proofs using an in-process tree, e.g. the one maintained by the external node.
+    pub fn with_tree_reader(mut self, tree_reader: LazyAsyncTreeReader) -> Self {
+        self.optional.tree_api = Some(Arc::new(tree_reader));
+        self
+    }
+
+    /// Exposes the Ethereum sender resubmission policy in the `admin` namespace.
+    pub fn with_resubmission_policy(mut self, policy: Option<ResubmissionPolicy>) -> Self {
+        self.optional.resubmission_policy = policy;
+        self
+    }
+
+    /// Allows the `admin` namespace to cancel transactions already loaded into the state keeper mempool.
+    /// Should only be set if the state keeper runs in the same process.
+    pub(crate) fn with_mempool(mut self, mempool: Option<MempoolGuard>) -> Self {
+        self.optional.mempool = mempool;
         self
     }
 
@@ -246,6 +376,7 @@ impl ApiBuilder {
         Ok(FullApiParams {
             pool: self.pool,
             last_miniblock_pool: self.last_miniblock_pool,
+            node_stats_pool: self.node_stats_pool,
             config: self.config,
             transport: self.transport.context("API transport not set")?,
             tx_sender: self.tx_sender.context("Transaction sender not set")?,
@@ -278,11 +409,19 @@ impl FullApiParams {
         // processes enough requests, information about the latest sealed miniblock will be updated
         // by reporting block difference metrics, so the actual update lag would be much smaller than this value.
         const SEALED_MINIBLOCK_UPDATE_INTERVAL: Duration = Duration::from_millis(25);
+        // Node stats are intended for monitoring, so they don't need to be updated often.
+        const NODE_STATS_UPDATE_INTERVAL: Duration = Duration::from_secs(5);
 
+        let (node_stats, node_stats_task) = NodeStatsCollector::new(
+            self.node_stats_pool,
+            self.optional.sync_state.clone(),
+            NODE_STATS_UPDATE_INTERVAL,
+        );
         let (last_sealed_miniblock, update_task) =
             SealedMiniblockNumber::new(self.last_miniblock_pool, SEALED_MINIBLOCK_UPDATE_INTERVAL);
-        // The update tasks takes care of its termination, so we don't need to retain its handle.
+        // The update tasks take care of their termination, so we don't need to retain their handles.
         tokio::spawn(update_task);
+        tokio::spawn(node_stats_task);
 
         RpcState {
             installed_filters: Arc::new(Mutex::new(Filters::new(self.optional.filters_limit))),
@@ -291,10 +430,10 @@ impl FullApiParams {
             sync_state: self.optional.sync_state,
             api_config: self.config,
             last_sealed_miniblock,
-            tree_api: self
-                .optional
-                .tree_api_url
-                .map(|url| TreeApiHttpClient::new(url.as_str())),
+            node_stats,
+            tree_api: self.optional.tree_api,
+            resubmission_policy: self.optional.resubmission_policy,
+            mempool: self.optional.mempool,
         }
     }
 
@@ -335,9 +474,13 @@ impl FullApiParams {
                 .expect("Can't merge debug namespace");
         }
         if namespaces.contains(&Namespace::Snapshots) {
-            rpc.merge(SnapshotsNamespace::new(rpc_state).into_rpc())
+            rpc.merge(SnapshotsNamespace::new(rpc_state.clone()).into_rpc())
                 .expect("Can't merge snapshots namespace");
         }
+        if namespaces.contains(&Namespace::Admin) {
+            rpc.merge(AdminNamespace::new(rpc_state).into_rpc())
+                .expect("Can't merge admin namespace");
+        }
         rpc
     }
 
@@ -406,9 +549,32 @@ impl FullApiParams {
             .optional
             .response_body_size_limit
             .map_or(u32::MAX, |limit| limit as u32);
+        let batch_concurrency = self.optional.batch_request_concurrency.map(|concurrency| {
+            ConcurrentBatchLayer::new(
+                concurrency,
+                self.optional.batch_request_size_limit,
+                response_body_size_limit as usize,
+            )
+        });
 
-        let websocket_requests_per_minute_limit = self.optional.websocket_requests_per_minute_limit;
+        let websocket_limits = WebSocketLimits {
+            requests_per_minute: self.optional.websocket_requests_per_minute_limit,
+            subscriptions_per_connection: self
+                .optional
+                .websocket_subscriptions_per_connection_limit,
+            idle_timeout: self.optional.websocket_idle_timeout,
+        };
         let subscriptions_limit = self.optional.subscriptions_limit;
+        let method_quotas = self.optional.method_quotas;
+        let rate_limit_api_key_header = self
+            .optional
+            .rate_limit_api_key_header
+            .as_deref()
+            .map(reqwest::header::HeaderName::try_from)
+            .transpose()
+            .context("invalid rate limit API key header")?;
+        let rate_limit_trusted_proxy_count = self.optional.rate_limit_trusted_proxy_count;
+        let response_cache = self.optional.response_cache;
 
         let mut tasks = vec![];
         let mut pubsub = None;
@@ -419,6 +585,9 @@ impl FullApiParams {
             if let Some(sender) = &self.optional.pub_sub_events_sender {
                 pub_sub.set_events_sender(sender.clone());
             }
+            if let Some(timeout) = self.optional.pub_sub_send_timeout {
+                pub_sub.set_send_timeout(timeout);
+            }
 
             tasks.extend(pub_sub.spawn_notifiers(
                 self.pool.clone(),
@@ -439,9 +608,14 @@ impl FullApiParams {
             health_updater,
             vm_barrier,
             batch_request_config,
+            batch_concurrency,
             response_body_size_limit,
             subscriptions_limit,
-            websocket_requests_per_minute_limit,
+            websocket_limits,
+            method_quotas,
+            rate_limit_api_key_header,
+            rate_limit_trusted_proxy_count,
+            response_cache,
         ));
 
         let local_addr = match local_addr.await {
@@ -472,9 +646,14 @@ impl FullApiParams {
         health_updater: HealthUpdater,
         vm_barrier: VmConcurrencyBarrier,
         batch_request_config: BatchRequestConfig,
+        batch_concurrency: Option<ConcurrentBatchLayer>,
         response_body_size_limit: u32,
         subscriptions_limit: Option<usize>,
-        websocket_requests_per_minute_limit: Option<NonZeroU32>,
+        websocket_limits: WebSocketLimits,
+        method_quotas: MethodQuotas,
+        rate_limit_api_key_header: Option<reqwest::header::HeaderName>,
+        rate_limit_trusted_proxy_count: usize,
+        response_cache: Option<ResponseCache>,
     ) -> anyhow::Result<()> {
         let (transport_str, is_http, addr) = match transport {
             ApiTransport::Http(addr) => ("HTTP", true, addr),
@@ -499,10 +678,20 @@ impl FullApiParams {
                 future::ready(())
             }),
         );
+        // Setup per-client method quotas.
+        let method_limiters = Arc::new(MethodRateLimiters::new(method_quotas));
+        if !method_quotas.is_empty() {
+            tokio::spawn(method_limiters.clone().run_pruning());
+        }
         // Assemble server middleware.
         let middleware = tower::ServiceBuilder::new()
             .layer(in_flight_requests)
-            .option_layer(cors);
+            .option_layer(cors)
+            .option_layer(batch_concurrency.filter(|_| is_http))
+            .layer(ClientKeyLayer::new(
+                rate_limit_api_key_header,
+                rate_limit_trusted_proxy_count,
+            ));
 
         // Settings shared by HTTP and WS servers.
         let max_connections = !is_http
@@ -515,29 +704,58 @@ impl FullApiParams {
             .max_response_body_size(response_body_size_limit)
             .set_batch_request_config(batch_request_config);
 
+        let methods = Methods::from(rpc);
         let (local_addr, server_handle) = if is_http {
             // HTTP-specific settings
-            let server = server_builder
+            let service_builder = server_builder
+                .set_rpc_middleware(
+                    RpcServiceBuilder::new()
+                        .layer_fn(move |a| {
+                            MethodQuotaMiddleware::new(a, method_limiters.clone(), Transport::Http)
+                        })
+                        .layer_fn(move |a| {
+                            ResponseCacheMiddleware::new(a, response_cache.clone(), Transport::Http)
+                        }),
+                )
                 .http_only()
-                .build(addr)
-                .await
-                .context("Failed building HTTP JSON-RPC server")?;
-            (server.local_addr(), server.start(rpc))
+                .to_service_builder();
+            Self::serve_with_peer_addrs(addr, max_connections, move |stop_handle| {
+                service_builder.clone().build(methods.clone(), stop_handle)
+            })
+            .context("Failed building HTTP JSON-RPC server")?
         } else {
             // WS specific settings
-            let server = server_builder
-                .set_rpc_middleware(RpcServiceBuilder::new().layer_fn(move |a| {
-                    LimitMiddleware::new(a, websocket_requests_per_minute_limit)
-                }))
+            let mut server_builder = server_builder;
+            if let Some(limit) = websocket_limits.subscriptions_per_connection {
+                server_builder = server_builder.max_subscriptions_per_connection(limit);
+            }
+            if let Some(idle_timeout) = websocket_limits.idle_timeout {
+                let ping_config = PingConfig::new()
+                    .ping_interval(idle_timeout / 2)
+                    .inactive_limit(idle_timeout);
+                server_builder = server_builder.enable_ws_ping(ping_config);
+            }
+            let websocket_requests_per_minute_limit = websocket_limits.requests_per_minute;
+            let service_builder = server_builder
+                .set_rpc_middleware(
+                    RpcServiceBuilder::new()
+                        .layer_fn(move |a| {
+                            LimitMiddleware::new(a, websocket_requests_per_minute_limit)
+                        })
+                        .layer_fn(move |a| {
+                            MethodQuotaMiddleware::new(a, method_limiters.clone(), Transport::Ws)
+                        })
+                        .layer_fn(move |a| {
+                            ResponseCacheMiddleware::new(a, response_cache.clone(), Transport::Ws)
+                        }),
+                )
                 .set_id_provider(EthSubscriptionIdProvider)
-                .build(addr)
-                .await
-                .context("Failed building WS JSON-RPC server")?;
-            (server.local_addr(), server.start(rpc))
+                .to_service_builder();
+            Self::serve_with_peer_addrs(addr, max_connections, move |stop_handle| {
+                service_builder.clone().build(methods.clone(), stop_handle)
+            })
+            .context("Failed building WS JSON-RPC server")?
         };
-        let local_addr = local_addr.with_context(|| {
-            format!("Failed getting local address for {transport_str} JSON-RPC server")
-        })?;
         local_addr_sender.send(local_addr).ok();
 
         let close_handle = server_handle.clone();
@@ -563,6 +781,61 @@ impl FullApiParams {
         Self::wait_for_vm(vm_barrier, transport_str).await;
         Ok(())
     }
+
+    /// Serves JSON-RPC using `hyper` directly rather than via `jsonrpsee::server::Server`, so that the peer address
+    /// of each connection is available to the HTTP middleware as a [`SocketAddr`] request extension.
+    fn serve_with_peer_addrs<S, B>(
+        addr: SocketAddr,
+        max_connections: usize,
+        build_service: impl Fn(StopHandle) -> S + Send + 'static,
+    ) -> anyhow::Result<(SocketAddr, ServerHandle)>
+    where
+        S: tower::Service<hyper::Request<hyper::Body>, Response = hyper::Response<B>>
+            + Send
+            + 'static,
+        S::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+        S::Future: Send + 'static,
+        B: hyper::body::HttpBody + Send + 'static,
+        B::Data: Send,
+        B::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+    {
+        let incoming = AddrIncoming::bind(&addr).context("failed binding TCP listener")?;
+        let local_addr = incoming.local_addr();
+        let (stop_handle, server_handle) = stop_channel();
+        let connection_permits = Arc::new(Semaphore::new(max_connections));
+
+        let shutdown_handle = stop_handle.clone();
+        let make_service = make_service_fn(move |conn: &AddrStream| {
+            let peer_addr = conn.remote_addr();
+            let service = connection_permits
+                .clone()
+                .try_acquire_owned()
+                .map(|permit| {
+                    let service = build_service(stop_handle.clone());
+                    tower::ServiceBuilder::new()
+                        .map_request(move |mut request: hyper::Request<hyper::Body>| {
+                            // The permit is held by the service, i.e., until the connection is closed.
+                            let _ = &permit;
+                            request.extensions_mut().insert(peer_addr);
+                            request
+                        })
+                        .service(service)
+                })
+                .with_context(|| {
+                    format!("Too many connections, dropping connection from {peer_addr}")
+                });
+            future::ready(service)
+        });
+        let server = hyper::Server::builder(incoming)
+            .serve(make_service)
+            .with_graceful_shutdown(shutdown_handle.shutdown());
+        tokio::spawn(async move {
+            if let Err(err) = server.await {
+                tracing::error!("JSON-RPC server on {local_addr} failed: {err}");
+            }
+        });
+        Ok((local_addr, server_handle))
+    }
 }
 
 async fn resolve_block(
@@ -571,7 +844,19 @@ async fn resolve_block(
     method_name: &'static str,
 ) -> Result<MiniblockNumber, Web3Error> {
     let result = connection.blocks_web3_dal().resolve_block_id(block).await;
-    result
+    let block_number = result
         .map_err(|err| internal_error(method_name, err))?
-        .ok_or(Web3Error::NoBlock)
+        .ok_or(Web3Error::NoBlock)?;
+
+    let pruning_info = connection.pruning_dal().get_pruning_info().await;
+    let earliest_available_block = pruning_info
+        .map_err(|err| internal_error(method_name, err))?
+        .earliest_available_miniblock();
+    if block_number < earliest_available_block {
+        return Err(Web3Error::PrunedBlock(
+            block_number.0,
+            earliest_available_block.0,
+        ));
+    }
+    Ok(block_number)
 }
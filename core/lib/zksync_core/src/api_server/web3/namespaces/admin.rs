@@ -0,0 +1,117 @@
+use zksync_dal::cancelled_transactions_dal::CancelledTransaction;
+use zksync_types::{api, Address, Nonce, H256};
+use zksync_web3_decl::error::Web3Error;
+
+use crate::api_server::web3::{
+    backend_jsonrpsee::internal_error, metrics::API_METRICS, state::RpcState,
+};
+
+/// Operator-only namespace allowing to manage pending transactions and inspect L1 transaction sending.
+#[derive(Debug, Clone)]
+pub struct AdminNamespace {
+    state: RpcState,
+}
+
+impl AdminNamespace {
+    pub fn new(state: RpcState) -> Self {
+        Self { state }
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub async fn cancel_transaction_impl(
+        &self,
+        hash: H256,
+        reason: Option<String>,
+    ) -> Result<Option<H256>, Web3Error> {
+        const METHOD_NAME: &str = "cancel_transaction";
+
+        let method_latency = API_METRICS.start_call(METHOD_NAME);
+        let (Some(pool), Some(mempool)) = (
+            &self.state.tx_sender.0.master_connection_pool,
+            &self.state.mempool,
+        ) else {
+            return Err(Web3Error::NotImplemented);
+        };
+        let mut mempool = mempool.clone();
+        let mut storage = pool
+            .access_storage_tagged("api")
+            .await
+            .map_err(|err| internal_error(METHOD_NAME, err))?;
+        let cancelled = storage
+            .cancelled_transactions_dal()
+            .cancel_transaction_by_hash(hash, reason.as_deref(), |account, nonce| {
+                mempool.remove_l2_transaction(account, nonce)
+            })
+            .await
+            .map_err(|err| internal_error(METHOD_NAME, err))?;
+        method_latency.observe();
+        Ok(Self::report_cancellation(cancelled))
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub async fn cancel_transaction_by_nonce_impl(
+        &self,
+        initiator: Address,
+        nonce: Nonce,
+        reason: Option<String>,
+    ) -> Result<Option<H256>, Web3Error> {
+        const METHOD_NAME: &str = "cancel_transaction_by_nonce";
+
+        let method_latency = API_METRICS.start_call(METHOD_NAME);
+        let (Some(pool), Some(mempool)) = (
+            &self.state.tx_sender.0.master_connection_pool,
+            &self.state.mempool,
+        ) else {
+            return Err(Web3Error::NotImplemented);
+        };
+        let mut mempool = mempool.clone();
+        let mut storage = pool
+            .access_storage_tagged("api")
+            .await
+            .map_err(|err| internal_error(METHOD_NAME, err))?;
+        let cancelled = storage
+            .cancelled_transactions_dal()
+            .cancel_transaction_by_nonce(initiator, nonce, reason.as_deref(), |account, nonce| {
+                mempool.remove_l2_transaction(account, nonce)
+            })
+            .await
+            .map_err(|err| internal_error(METHOD_NAME, err))?;
+        method_latency.observe();
+        Ok(Self::report_cancellation(cancelled))
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub async fn get_resubmission_policy_impl(
+        &self,
+    ) -> Result<api::ResubmissionPolicyDetails, Web3Error> {
+        const METHOD_NAME: &str = "get_resubmission_policy";
+
+        let method_latency = API_METRICS.start_call(METHOD_NAME);
+        let Some(policy) = &self.state.resubmission_policy else {
+            return Err(Web3Error::NotImplemented);
+        };
+        let mut storage = self
+            .state
+            .connection_pool
+            .access_storage_tagged("api")
+            .await
+            .map_err(|err| internal_error(METHOD_NAME, err))?;
+        let details = policy
+            .details(&mut storage)
+            .await
+            .map_err(|err| internal_error(METHOD_NAME, err))?;
+        method_latency.observe();
+        Ok(details)
+    }
+
+    fn report_cancellation(cancelled: Option<CancelledTransaction>) -> Option<H256> {
+        let cancelled = cancelled?;
+        tracing::info!(
+            "Cancelled pending transaction {:?} (initiator: {:?}, nonce: {})",
+            cancelled.tx_hash,
+            cancelled.initiator_address,
+            cancelled.nonce.0
+        );
+        Some(cancelled.tx_hash)
+    }
+}
@@ -1,22 +1,36 @@
-use std::sync::Arc;
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    sync::Arc,
+};
 
-use multivm::{interface::ExecutionResult, vm_latest::constants::BLOCK_GAS_LIMIT};
+use multivm::{
+    interface::{ExecutionResult, VmExecutionResultAndLogs},
+    vm_latest::constants::BLOCK_GAS_LIMIT,
+};
 use once_cell::sync::OnceCell;
-use zksync_dal::ConnectionPool;
+use zksync_dal::{ConnectionPool, StorageProcessor};
 use zksync_state::PostgresStorageCaches;
 use zksync_types::{
-    api::{BlockId, BlockNumber, DebugCall, ResultDebugCall, TracerConfig},
+    api::{
+        BlockId, BlockNumber, DebugCall, DebugTrace, PrestateAccount, PrestateTrace,
+        ResultDebugTrace, TracerConfig, TransactionId,
+    },
     fee_model::BatchFeeInput,
+    get_code_key, get_nonce_key,
     l2::L2Tx,
     transaction_request::CallRequest,
+    utils::{decompose_full_nonce, storage_key_for_eth_balance},
     vm_trace::Call,
-    AccountTreeId, L2ChainId, H256, USED_BOOTLOADER_MEMORY_BYTES,
+    AccountTreeId, Address, L2ChainId, MiniblockNumber, StorageKey, StorageLog, StorageLogKind,
+    Transaction, H256, U256, USED_BOOTLOADER_MEMORY_BYTES,
 };
-use zksync_web3_decl::error::Web3Error;
+use zksync_utils::{bytecode::hash_bytecode, h256_to_u256, u256_to_h256};
+use zksync_web3_decl::error::{ApiErrorCode, Web3Error};
 
 use crate::api_server::{
     execution_sandbox::{
-        execute_tx_eth_call, ApiTracer, BlockArgs, TxSharedArgs, VmConcurrencyLimiter,
+        execute_tx_eth_call, reexecute_txs_in_sandbox, ApiTracer, BlockArgs, TxExecutionArgs,
+        TxSharedArgs, VmConcurrencyLimiter,
     },
     tx_sender::ApiContracts,
     web3::{
@@ -61,38 +75,67 @@ impl DebugNamespace {
         &self,
         block_id: BlockId,
         options: Option<TracerConfig>,
-    ) -> Result<Vec<ResultDebugCall>, Web3Error> {
+    ) -> Result<Vec<ResultDebugTrace>, Web3Error> {
         const METHOD_NAME: &str = "debug_trace_block";
 
         let method_latency = API_METRICS.start_block_call(METHOD_NAME, block_id);
-        let only_top_call = options
-            .map(|options| options.tracer_config.only_top_call)
-            .unwrap_or(false);
+        let options = options.unwrap_or_default();
         let mut connection = self
             .connection_pool
             .access_storage_tagged("api")
             .await
-            .unwrap();
+            .map_err(|err| internal_error(METHOD_NAME, err))?;
         let block_number = resolve_block(&mut connection, block_id, METHOD_NAME).await?;
-        let call_trace = connection
-            .blocks_web3_dal()
-            .get_trace_for_miniblock(block_number)
+        let txs = connection
+            .transactions_web3_dal()
+            .get_raw_miniblock_transactions(block_number)
             .await
-            .unwrap();
-        let call_trace = call_trace
+            .map_err(|err| internal_error(METHOD_NAME, err))?;
+
+        let traces: Vec<DebugTrace> = match options {
+            TracerConfig::CallTracer(config) => {
+                let call_traces = connection
+                    .blocks_web3_dal()
+                    .get_trace_for_miniblock(block_number)
+                    .await
+                    .map_err(|err| internal_error(METHOD_NAME, err))?;
+                drop(connection);
+
+                // Call traces may be missing if the node doesn't save them, or if they were pruned.
+                let call_traces = if call_traces.len() == txs.len() {
+                    call_traces
+                } else {
+                    self.reexecute_txs(block_number, txs, !config.only_top_call, METHOD_NAME)
+                        .await?
+                        .iter()
+                        .map(ReexecutedTx::call_trace)
+                        .collect::<Result<_, _>>()?
+                };
+                call_traces
+                    .into_iter()
+                    .map(|call| DebugTrace::Call(debug_call(call, config.only_top_call)))
+                    .collect()
+            }
+            TracerConfig::PrestateTracer(config) => {
+                drop(connection);
+                let txs = self
+                    .reexecute_txs(block_number, txs, false, METHOD_NAME)
+                    .await?;
+                self.prestate_traces(block_number, &txs, config.diff_mode, METHOD_NAME)
+                    .await?
+                    .into_iter()
+                    .map(DebugTrace::Prestate)
+                    .collect()
+            }
+        };
+        let traces = traces
             .into_iter()
-            .map(|call_trace| {
-                let mut result: DebugCall = call_trace.into();
-                if only_top_call {
-                    result.calls = vec![];
-                }
-                ResultDebugCall { result }
-            })
+            .map(|result| ResultDebugTrace { result })
             .collect();
 
         let block_diff = self.last_sealed_miniblock.diff(block_number);
         method_latency.observe(block_diff);
-        Ok(call_trace)
+        Ok(traces)
     }
 
     #[tracing::instrument(skip(self))]
@@ -100,24 +143,71 @@ impl DebugNamespace {
         &self,
         tx_hash: H256,
         options: Option<TracerConfig>,
-    ) -> Option<DebugCall> {
-        let only_top_call = options
-            .map(|options| options.tracer_config.only_top_call)
-            .unwrap_or(false);
-        let call_trace = self
+    ) -> Result<Option<DebugTrace>, Web3Error> {
+        const METHOD_NAME: &str = "debug_trace_transaction";
+
+        let options = options.unwrap_or_default();
+        let mut connection = self
             .connection_pool
             .access_storage_tagged("api")
             .await
-            .unwrap()
-            .transactions_dal()
-            .get_call_trace(tx_hash)
-            .await;
-        call_trace.map(|call_trace| {
-            let mut result: DebugCall = call_trace.into();
-            if only_top_call {
-                result.calls = vec![];
+            .map_err(|err| internal_error(METHOD_NAME, err))?;
+        if let TracerConfig::CallTracer(config) = &options {
+            let call_trace = connection.transactions_dal().get_call_trace(tx_hash).await;
+            if let Some(call_trace) = call_trace {
+                let call = debug_call(call_trace, config.only_top_call);
+                return Ok(Some(DebugTrace::Call(call)));
+            }
+        }
+
+        let tx = connection
+            .transactions_web3_dal()
+            .get_transaction(TransactionId::Hash(tx_hash), self.chain_id)
+            .await
+            .map_err(|err| internal_error(METHOD_NAME, err))?;
+        let Some(block_number) = tx.and_then(|tx| tx.block_number) else {
+            // The transaction is unknown or is not included into a miniblock yet.
+            return Ok(None);
+        };
+        let block_number = block_number.try_into().map_err(|_| {
+            let err = format!(
+                "miniblock number {block_number} for transaction {tx_hash:?} is out of range"
+            );
+            internal_error(METHOD_NAME, err)
+        })?;
+        let block_number = MiniblockNumber(block_number);
+        let mut txs = connection
+            .transactions_web3_dal()
+            .get_raw_miniblock_transactions(block_number)
+            .await
+            .map_err(|err| internal_error(METHOD_NAME, err))?;
+        drop(connection);
+
+        let tx_index = txs.iter().position(|tx| tx.hash() == tx_hash);
+        let tx_index = tx_index.ok_or_else(|| {
+            let err = format!("transaction {tx_hash:?} is missing in miniblock #{block_number}");
+            internal_error(METHOD_NAME, err)
+        })?;
+        // Transactions after the traced one don't influence its execution.
+        txs.truncate(tx_index + 1);
+
+        Ok(match options {
+            TracerConfig::CallTracer(config) => {
+                let txs = self
+                    .reexecute_txs(block_number, txs, !config.only_top_call, METHOD_NAME)
+                    .await?;
+                let call_trace = txs.last().map(ReexecutedTx::call_trace).transpose()?;
+                call_trace.map(|call| DebugTrace::Call(debug_call(call, config.only_top_call)))
+            }
+            TracerConfig::PrestateTracer(config) => {
+                let txs = self
+                    .reexecute_txs(block_number, txs, false, METHOD_NAME)
+                    .await?;
+                let mut traces = self
+                    .prestate_traces(block_number, &txs, config.diff_mode, METHOD_NAME)
+                    .await?;
+                traces.pop().map(DebugTrace::Prestate)
             }
-            result
         })
     }
 
@@ -132,9 +222,11 @@ impl DebugNamespace {
 
         let block_id = block_id.unwrap_or(BlockId::Number(BlockNumber::Pending));
         let method_latency = API_METRICS.start_block_call(METHOD_NAME, block_id);
-        let only_top_call = options
-            .map(|options| options.tracer_config.only_top_call)
-            .unwrap_or(false);
+        let only_top_call = match options.unwrap_or_default() {
+            TracerConfig::CallTracer(config) => config.only_top_call,
+            // State diffs for calls are not supported.
+            TracerConfig::PrestateTracer(_) => return Err(Web3Error::NotImplemented),
+        };
 
         let mut connection = self
             .connection_pool
@@ -148,6 +240,7 @@ impl DebugNamespace {
         drop(connection);
 
         let tx = L2Tx::from_request(request.into(), USED_BOOTLOADER_MEMORY_BYTES)?;
+        let gas_limit = gas_limit_to_u32(tx.common_data.fee.gas_limit)?;
 
         let shared_args = self.shared_args();
         let vm_permit = self.vm_concurrency_limiter.acquire().await;
@@ -168,6 +261,7 @@ impl DebugNamespace {
             tx.clone(),
             block_args,
             self.vm_execution_cache_misses_limit,
+            None,
             custom_tracers,
         )
         .await;
@@ -176,10 +270,11 @@ impl DebugNamespace {
             ExecutionResult::Success { output, .. } => (output, None),
             ExecutionResult::Revert { output } => (vec![], Some(output.to_string())),
             ExecutionResult::Halt { reason } => {
-                return Err(Web3Error::SubmitTransactionError(
-                    reason.to_string(),
-                    vec![],
-                ))
+                return Err(Web3Error::TransactionRejected {
+                    code: ApiErrorCode::Unexecutable,
+                    message: reason.to_string(),
+                    data: None,
+                })
             }
         };
 
@@ -189,7 +284,7 @@ impl DebugNamespace {
             .take()
             .unwrap_or_default();
         let call = Call::new_high_level(
-            tx.common_data.fee.gas_limit.as_u32(),
+            gas_limit,
             result.statistics.gas_used,
             tx.execute.value,
             tx.execute.calldata,
@@ -203,6 +298,116 @@ impl DebugNamespace {
         Ok(call.into())
     }
 
+    /// Re-executes transactions from a sealed miniblock on top of the state after the previous miniblock.
+    /// `txs` must be a prefix of the miniblock transactions. Transactions are executed in the VM context
+    /// of the original miniblock (i.e., with its number, timestamp and L1 batch).
+    async fn reexecute_txs(
+        &self,
+        block_number: MiniblockNumber,
+        txs: Vec<Transaction>,
+        trace_calls: bool,
+        method_name: &'static str,
+    ) -> Result<Vec<ReexecutedTx>, Web3Error> {
+        if txs.is_empty() || block_number == MiniblockNumber(0) {
+            // The genesis miniblock can only contain the genesis upgrade transaction, which cannot be re-executed.
+            return Ok(vec![]);
+        }
+
+        let mut connection = self
+            .connection_pool
+            .access_storage_tagged("api")
+            .await
+            .map_err(|err| internal_error(method_name, err))?;
+        let header = connection
+            .blocks_dal()
+            .get_miniblock_header(block_number)
+            .await
+            .map_err(|err| internal_error(method_name, err))?
+            .ok_or(Web3Error::NoBlock)?;
+        let block_args = BlockArgs::for_reexecution(&mut connection, block_number)
+            .await
+            .map_err(|err| internal_error(method_name, err))?
+            .ok_or(Web3Error::NoBlock)?;
+        drop(connection);
+
+        let call_tracer_results: Vec<_> = txs.iter().map(|_| Arc::default()).collect();
+        let txs_with_tracers = txs
+            .iter()
+            .zip(&call_tracer_results)
+            .map(|(tx, call_tracer_result)| {
+                let tracers = if trace_calls {
+                    vec![ApiTracer::CallTracer(Arc::clone(call_tracer_result))]
+                } else {
+                    vec![]
+                };
+                (tx.clone(), tracers)
+            })
+            .collect();
+        let shared_args = TxSharedArgs {
+            fee_input: header.batch_fee_input,
+            ..self.shared_args()
+        };
+        let execution_args = TxExecutionArgs::for_reexecution(
+            header.base_fee_per_gas,
+            self.vm_execution_cache_misses_limit,
+        );
+
+        let vm_permit = self.vm_concurrency_limiter.acquire().await;
+        let vm_permit = vm_permit.ok_or(Web3Error::InternalError)?;
+        let results = reexecute_txs_in_sandbox(
+            vm_permit,
+            shared_args,
+            execution_args,
+            self.connection_pool.clone(),
+            txs_with_tracers,
+            block_args,
+        )
+        .await;
+
+        Ok(txs
+            .into_iter()
+            .zip(results)
+            .zip(call_tracer_results)
+            .map(|((tx, result), call_tracer_result)| {
+                // The VM is dropped at this point, so we hold the only copy of the `Arc`.
+                let calls = Arc::try_unwrap(call_tracer_result)
+                    .unwrap()
+                    .take()
+                    .unwrap_or_default();
+                ReexecutedTx { tx, result, calls }
+            })
+            .collect())
+    }
+
+    async fn prestate_traces(
+        &self,
+        block_number: MiniblockNumber,
+        txs: &[ReexecutedTx],
+        diff_mode: bool,
+        method_name: &'static str,
+    ) -> Result<Vec<PrestateTrace>, Web3Error> {
+        let mut connection = self
+            .connection_pool
+            .access_storage_tagged("api")
+            .await
+            .map_err(|err| internal_error(method_name, err))?;
+        let mut builder = PrestateTracesBuilder {
+            storage: &mut connection,
+            block_number,
+            overlay: HashMap::new(),
+        };
+
+        let mut traces = Vec::with_capacity(txs.len());
+        for tx in txs {
+            let trace = builder
+                .trace(tx, diff_mode)
+                .await
+                .map_err(|err| internal_error(method_name, err))?;
+            traces.push(trace);
+        }
+        Ok(traces)
+    }
+
     fn shared_args(&self) -> TxSharedArgs {
         TxSharedArgs {
             operator_account: AccountTreeId::default(),
@@ -214,3 +419,193 @@ impl DebugNamespace {
         }
     }
 }
+
+fn gas_limit_to_u32(gas_limit: U256) -> Result<u32, Web3Error> {
+    gas_limit
+        .try_into()
+        .map_err(|_| Web3Error::TransactionRejected {
+            code: ApiErrorCode::InvalidParams,
+            message: format!("gas limit {gas_limit} exceeds the maximum of {}", u32::MAX),
+            data: None,
+        })
+}
+
+fn debug_call(call: Call, only_top_call: bool) -> DebugCall {
+    let mut call = DebugCall::from(call);
+    if only_top_call {
+        call.calls = vec![];
+    }
+    call
+}
+
+/// Transaction re-executed in the API sandbox.
+#[derive(Debug)]
+struct ReexecutedTx {
+    tx: Transaction,
+    result: VmExecutionResultAndLogs,
+    calls: Vec<Call>,
+}
+
+impl ReexecutedTx {
+    /// Returns the call trace in the same format as the one persisted by the state keeper.
+    fn call_trace(&self) -> Result<Call, Web3Error> {
+        let revert_reason = match &self.result.result {
+            ExecutionResult::Success { .. } => None,
+            ExecutionResult::Revert { output } => Some(output.to_string()),
+            ExecutionResult::Halt { reason } => Some(reason.to_string()),
+        };
+        let gas_limit = gas_limit_to_u32(self.tx.gas_limit())?;
+        Ok(Call::new_high_level(
+            gas_limit,
+            gas_limit.saturating_sub(self.result.refunds.gas_refunded),
+            self.tx.execute.value,
+            self.tx.execute.calldata.clone(),
+            vec![],
+            revert_reason,
+            self.calls.clone(),
+        ))
+    }
+
+    /// Returns values of the storage slots touched by the transaction before and after its execution.
+    fn touched_slots(&self) -> BTreeMap<StorageKey, (H256, H256)> {
+        let mut slots = BTreeMap::new();
+        for log_query in &self.result.logs.storage_logs {
+            let log = StorageLog::from_log_query(log_query);
+            let (_, value_after) = slots.entry(log.key).or_insert_with(|| {
+                let value_before = u256_to_h256(log_query.log_query.read_value);
+                (value_before, value_before)
+            });
+            if log.kind == StorageLogKind::Write {
+                *value_after = log.value;
+            }
+        }
+        slots
+    }
+}
+
+/// Builds prestate traces for transactions re-executed from a single miniblock. Transactions must be supplied
+/// in the execution order.
+#[derive(Debug)]
+struct PrestateTracesBuilder<'a, 'c> {
+    storage: &'a mut StorageProcessor<'c>,
+    block_number: MiniblockNumber,
+    /// Storage changes made by the already traced transactions.
+    overlay: HashMap<StorageKey, H256>,
+}
+
+impl PrestateTracesBuilder<'_, '_> {
+    async fn read_value(&mut self, key: &StorageKey) -> anyhow::Result<H256> {
+        if let Some(value) = self.overlay.get(key) {
+            return Ok(*value);
+        }
+        Ok(self
+            .storage
+            .storage_web3_dal()
+            .get_historical_value_unchecked(key, self.block_number - 1)
+            .await?)
+    }
+
+    async fn values_before_and_after(
+        &mut self,
+        slots: &BTreeMap<StorageKey, (H256, H256)>,
+        key: &StorageKey,
+    ) -> anyhow::Result<(H256, H256)> {
+        if let Some(values) = slots.get(key) {
+            return Ok(*values);
+        }
+        let value = self.read_value(key).await?;
+        Ok((value, value))
+    }
+
+    async fn code(&mut self, tx: &Transaction, code_hash: H256) -> anyhow::Result<Option<Vec<u8>>> {
+        if code_hash == H256::zero() {
+            return Ok(None);
+        }
+        let factory_deps = tx.execute.factory_deps.as_deref().unwrap_or_default();
+        let tx_bytecode = factory_deps
+            .iter()
+            .find(|bytecode| hash_bytecode(bytecode) == code_hash);
+        if let Some(bytecode) = tx_bytecode {
+            return Ok(Some(bytecode.clone()));
+        }
+        Ok(self
+            .storage
+            .storage_web3_dal()
+            .get_factory_dep_unchecked(code_hash, self.block_number)
+            .await?)
+    }
+
+    async fn trace(&mut self, tx: &ReexecutedTx, diff_mode: bool) -> anyhow::Result<PrestateTrace> {
+        let slots = tx.touched_slots();
+        let mut accounts: BTreeSet<Address> = slots.keys().map(|key| *key.address()).collect();
+        accounts.insert(tx.tx.initiator_account());
+        accounts.insert(tx.tx.recipient_account());
+
+        let mut pre = BTreeMap::new();
+        let mut post = BTreeMap::new();
+        for address in accounts {
+            let (balance_before, balance_after) = self
+                .values_before_and_after(&slots, &storage_key_for_eth_balance(&address))
+                .await?;
+            let (nonce_before, nonce_after) = self
+                .values_before_and_after(&slots, &get_nonce_key(&address))
+                .await?;
+            let (code_hash_before, code_hash_after) = self
+                .values_before_and_after(&slots, &get_code_key(&address))
+                .await?;
+            let account_slots = slots
+                .iter()
+                .filter(|(key, _)| *key.address() == address)
+                .map(|(key, values)| (*key.key(), *values));
+
+            let to_nonce = |value: H256| decompose_full_nonce(h256_to_u256(value)).0.as_u64();
+            if !diff_mode {
+                let account = PrestateAccount {
+                    balance: Some(h256_to_u256(balance_before)),
+                    nonce: Some(to_nonce(nonce_before)),
+                    code: self.code(&tx.tx, code_hash_before).await?.map(Into::into),
+                    storage: account_slots
+                        .map(|(key, (before, _))| (key, before))
+                        .collect(),
+                };
+                pre.insert(address, account);
+                continue;
+            }
+
+            let mut account_before = PrestateAccount::default();
+            let mut account_after = PrestateAccount::default();
+            if balance_before != balance_after {
+                account_before.balance = Some(h256_to_u256(balance_before));
+                account_after.balance = Some(h256_to_u256(balance_after));
+            }
+            if nonce_before != nonce_after {
+                account_before.nonce = Some(to_nonce(nonce_before));
+                account_after.nonce = Some(to_nonce(nonce_after));
+            }
+            if code_hash_before != code_hash_after {
+                account_before.code = self.code(&tx.tx, code_hash_before).await?.map(Into::into);
+                account_after.code = self.code(&tx.tx, code_hash_after).await?.map(Into::into);
+            }
+            for (key, (before, after)) in account_slots {
+                if before != after {
+                    account_before.storage.insert(key, before);
+                    account_after.storage.insert(key, after);
+                }
+            }
+            if account_before != PrestateAccount::default()
+                || account_after != PrestateAccount::default()
+            {
+                pre.insert(address, account_before);
+                post.insert(address, account_after);
+            }
+        }
+
+        self.overlay
+            .extend(slots.into_iter().map(|(key, (_, after))| (key, after)));
+        Ok(if diff_mode {
+            PrestateTrace::Diff { pre, post }
+        } else {
+            PrestateTrace::State(pre)
+        })
+    }
+}
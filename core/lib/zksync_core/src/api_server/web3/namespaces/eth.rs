@@ -1,7 +1,8 @@
+use zksync_dal::blocks_web3_dal::{FeeHistoryTransaction, BLOCK_GAS_LIMIT};
 use zksync_types::{
     api::{
-        BlockId, BlockNumber, GetLogsFilter, Transaction, TransactionId, TransactionReceipt,
-        TransactionVariant,
+        BlockId, BlockNumber, GetLogsFilter, LogsPage, StateOverride, Transaction, TransactionId,
+        TransactionReceipt, TransactionVariant,
     },
     l2::{L2Tx, TransactionType},
     transaction_request::CallRequest,
@@ -11,7 +12,7 @@ use zksync_types::{
     AccountTreeId, Bytes, MiniblockNumber, StorageKey, H256, L2_ETH_TOKEN_ADDRESS,
     MAX_GAS_PER_PUBDATA_BYTE, U256,
 };
-use zksync_utils::u256_to_h256;
+use zksync_utils::{bytecode::validate_bytecode, u256_to_h256};
 use zksync_web3_decl::{
     error::Web3Error,
     types::{Address, Block, Filter, FilterChanges, Log, U64},
@@ -31,6 +32,23 @@ use crate::api_server::{
 pub const EVENT_TOPIC_NUMBER_LIMIT: usize = 4;
 pub const PROTOCOL_VERSION: &str = "zks/1";
 
+/// Checks that the state override set can be applied to the VM storage.
+fn validate_state_override(state_override: &StateOverride) -> Result<(), Web3Error> {
+    for (&address, account) in state_override {
+        if account.state.is_some() && account.state_diff.is_some() {
+            return Err(Web3Error::InvalidStateOverride(
+                address,
+                "`state` and `stateDiff` cannot be specified simultaneously".to_owned(),
+            ));
+        }
+        if let Some(code) = &account.code {
+            validate_bytecode(&code.0)
+                .map_err(|err| Web3Error::InvalidStateOverride(address, err.to_string()))?;
+        }
+    }
+    Ok(())
+}
+
 #[derive(Debug)]
 pub struct EthNamespace {
     state: RpcState,
@@ -62,14 +80,19 @@ impl EthNamespace {
         block_number
     }
 
-    #[tracing::instrument(skip(self, request, block_id))]
+    #[tracing::instrument(skip(self, request, block_id, state_override))]
     pub async fn call_impl(
         &self,
         request: CallRequest,
         block_id: Option<BlockId>,
+        state_override: Option<StateOverride>,
     ) -> Result<Bytes, Web3Error> {
         const METHOD_NAME: &str = "call";
 
+        if let Some(state_override) = &state_override {
+            validate_state_override(state_override)?;
+        }
+
         let block_id = block_id.unwrap_or(BlockId::Number(BlockNumber::Pending));
         let method_latency = API_METRICS.start_block_call(METHOD_NAME, block_id);
         let mut connection = self
@@ -82,13 +105,23 @@ impl EthNamespace {
             .await
             .map_err(|err| internal_error("eth_call", err))?
             .ok_or(Web3Error::NoBlock)?;
+        self.state
+            .ensure_historical_state_available(
+                &mut connection,
+                block_args.resolved_block_number(),
+                METHOD_NAME,
+            )
+            .await?;
         drop(connection);
 
         let tx = L2Tx::from_request(request.into(), self.state.api_config.max_tx_size)?;
 
-        let call_result = self.state.tx_sender.eth_call(block_args, tx).await;
-        let res_bytes = call_result
-            .map_err(|err| Web3Error::SubmitTransactionError(err.to_string(), err.data()))?;
+        let call_result = self
+            .state
+            .tx_sender
+            .eth_call(block_args, tx, state_override)
+            .await;
+        let res_bytes = call_result.map_err(Web3Error::from)?;
 
         let block_diff = self
             .state
@@ -149,10 +182,10 @@ impl EthNamespace {
             .tx_sender
             .get_txs_fee_in_wei(tx.into(), scale_factor, acceptable_overestimation)
             .await
-            .map_err(|err| Web3Error::SubmitTransactionError(err.to_string(), err.data()))?;
+            .map_err(Web3Error::from)?;
 
         method_latency.observe();
-        Ok(fee.gas_limit)
+        Ok(fee.fee.gas_limit)
     }
 
     #[tracing::instrument(skip(self))]
@@ -182,6 +215,9 @@ impl EthNamespace {
             .await
             .unwrap();
         let block_number = resolve_block(&mut connection, block_id, METHOD_NAME).await?;
+        self.state
+            .ensure_historical_state_available(&mut connection, block_number, METHOD_NAME)
+            .await?;
         let balance = connection
             .storage_web3_dal()
             .standard_token_historical_balance(
@@ -224,6 +260,67 @@ impl EthNamespace {
         })
     }
 
+    /// Returns a page of logs matching the filter, starting after the position encoded in `cursor`.
+    /// Unlike `eth_getLogs`, the number of matching logs is not limited; instead, logs are returned in pages
+    /// of at most `limit` logs (capped by `get_logs_max_results`). The block range limit still applies.
+    pub(crate) async fn get_logs_page(
+        &self,
+        mut filter: Filter,
+        cursor: Option<&str>,
+        limit: Option<u32>,
+    ) -> Result<LogsPage, Web3Error> {
+        const METHOD_NAME: &str = "get_logs_page";
+
+        self.state.resolve_filter_block_hash(&mut filter).await?;
+        let (from_block, to_block) = self.state.resolve_filter_block_range(&filter).await?;
+        filter.to_block = Some(BlockNumber::Number(to_block.0.into()));
+        let get_logs_filter = self.resolve_logs_filter(&filter, from_block).await?;
+        self.check_logs_block_range(&get_logs_filter)?;
+
+        let after = cursor.map(decode_logs_cursor).transpose()?;
+        if let Some((miniblock_number, _)) = after {
+            let block_range = get_logs_filter.from_block..=get_logs_filter.to_block;
+            if !block_range.contains(&miniblock_number) {
+                return Err(Web3Error::InvalidLogsCursor);
+            }
+        }
+        let max_results = self.state.api_config.get_logs_max_results;
+        let limit = limit
+            .map_or(max_results, |limit| (limit as usize).min(max_results))
+            .max(1);
+
+        let mut storage = self
+            .state
+            .connection_pool
+            .access_storage_tagged("api")
+            .await
+            .map_err(|err| internal_error(METHOD_NAME, err))?;
+        // Request an extra log to check whether there are more logs after the page.
+        let mut logs = if let Some(after) = after {
+            storage
+                .events_web3_dal()
+                .get_logs_after(get_logs_filter, after, limit + 1)
+                .await
+        } else {
+            storage
+                .events_web3_dal()
+                .get_logs(get_logs_filter, limit + 1)
+                .await
+        }
+        .map_err(|err| internal_error(METHOD_NAME, err))?;
+
+        let next_cursor = if logs.len() > limit {
+            logs.truncate(limit);
+            let last_log = logs.last().unwrap();
+            let miniblock_number = last_log.block_number.unwrap_or_default().as_u32();
+            let log_index = last_log.log_index.unwrap_or_default().as_u32();
+            Some(encode_logs_cursor(miniblock_number, log_index))
+        } else {
+            None
+        };
+        Ok(LogsPage { logs, next_cursor })
+    }
+
     pub async fn get_filter_logs_impl(&self, idx: U256) -> Result<FilterChanges, Web3Error> {
         const METHOD_NAME: &str = "get_filter_logs";
 
@@ -336,6 +433,9 @@ impl EthNamespace {
             .await
             .unwrap();
         let block_number = resolve_block(&mut connection, block_id, METHOD_NAME).await?;
+        self.state
+            .ensure_historical_state_available(&mut connection, block_number, METHOD_NAME)
+            .await?;
         let contract_code = connection
             .storage_web3_dal()
             .get_contract_code_unchecked(address, block_number)
@@ -370,6 +470,9 @@ impl EthNamespace {
             .await
             .unwrap();
         let block_number = resolve_block(&mut connection, block_id, METHOD_NAME).await?;
+        self.state
+            .ensure_historical_state_available(&mut connection, block_number, METHOD_NAME)
+            .await?;
         let value = connection
             .storage_web3_dal()
             .get_historical_value_unchecked(&storage_key, block_number)
@@ -397,7 +500,7 @@ impl EthNamespace {
         let mut connection = self
             .state
             .connection_pool
-            .access_storage_tagged("api")
+            .access_storage_read_after_write("api")
             .await
             .unwrap();
 
@@ -412,6 +515,9 @@ impl EthNamespace {
             }
             _ => {
                 let block_number = resolve_block(&mut connection, block_id, method_name).await?;
+                self.state
+                    .ensure_historical_state_available(&mut connection, block_number, method_name)
+                    .await?;
                 let nonce = connection
                     .storage_web3_dal()
                     .get_address_historical_nonce(address, block_number)
@@ -424,7 +530,17 @@ impl EthNamespace {
         // TODO (SMA-1612): currently account nonce is returning always, but later we will
         //  return account nonce for account abstraction and deployment nonce for non account abstraction.
         //  Strip off deployer nonce part.
-        let account_nonce = full_nonce.map(|nonce| decompose_full_nonce(nonce).0);
+        let mut account_nonce = full_nonce.map(|nonce| decompose_full_nonce(nonce).0);
+        if let (Some(proxy), BlockId::Number(BlockNumber::Pending), Ok(nonce)) =
+            (&self.state.tx_sender.0.proxy, block_id, &mut account_nonce)
+        {
+            // We're running an external node - take into account transactions proxied to the main node,
+            // but not synced back yet.
+            let pending_nonce = proxy
+                .next_nonce_by_initiator_account(address, nonce.as_u32())
+                .await;
+            *nonce = pending_nonce.0.into();
+        }
 
         let block_diff =
             block_number.map_or(0, |number| self.state.last_sealed_miniblock.diff(number));
@@ -443,7 +559,7 @@ impl EthNamespace {
         let mut transaction = self
             .state
             .connection_pool
-            .access_storage_tagged("api")
+            .access_storage_read_after_write("api")
             .await
             .unwrap()
             .transactions_web3_dal()
@@ -489,7 +605,7 @@ impl EthNamespace {
         let receipt = self
             .state
             .connection_pool
-            .access_storage_tagged("api")
+            .access_storage_read_after_write("api")
             .await
             .unwrap()
             .transactions_web3_dal()
@@ -591,7 +707,7 @@ impl EthNamespace {
                     .update(idx, filter);
                 Ok(changes)
             }
-            Err(Web3Error::LogsLimitExceeded(..)) => {
+            Err(Web3Error::LogsLimitExceeded(..) | Web3Error::LogsBlockRangeExceeded(..)) => {
                 // The filter was not being polled for a long time, so we remove it.
                 self.state.installed_filters.lock().await.remove(idx);
                 Err(Web3Error::FilterNotFound)
@@ -630,7 +746,7 @@ impl EthNamespace {
         let submit_result = submit_result.map(|_| hash).map_err(|err| {
             tracing::debug!("Send raw transaction error: {err}");
             API_METRICS.submit_tx_error[&err.prom_error_code()].inc();
-            Web3Error::SubmitTransactionError(err.to_string(), err.data())
+            Web3Error::from(err)
         });
 
         method_latency.observe();
@@ -672,6 +788,7 @@ impl EthNamespace {
 
         let method_latency =
             API_METRICS.start_block_call(METHOD_NAME, BlockId::Number(newest_block));
+        validate_reward_percentiles(&reward_percentiles)?;
         // Limit `block_count`.
         let block_count = block_count
             .as_u64()
@@ -696,13 +813,29 @@ impl EthNamespace {
         base_fee_per_gas.reverse();
 
         let oldest_block = newest_miniblock.0 + 1 - base_fee_per_gas.len() as u32;
-        // We do not store gas used ratio for blocks, returns array of zeroes as a placeholder.
-        let gas_used_ratio = vec![0.0; base_fee_per_gas.len()];
-        // Effective priority gas price is currently 0.
-        let reward = Some(vec![
-            vec![U256::zero(); reward_percentiles.len()];
-            base_fee_per_gas.len()
-        ]);
+        let transactions = connection
+            .blocks_web3_dal()
+            .get_fee_history_transactions(MiniblockNumber(oldest_block)..=newest_miniblock)
+            .await
+            .map_err(|err| internal_error(METHOD_NAME, err))?;
+        drop(connection);
+
+        let mut gas_used_ratio = Vec::with_capacity(base_fee_per_gas.len());
+        let mut reward = Vec::with_capacity(base_fee_per_gas.len());
+        for number in oldest_block..=newest_miniblock.0 {
+            let start = transactions.partition_point(|tx| tx.miniblock_number.0 < number);
+            let end = transactions.partition_point(|tx| tx.miniblock_number.0 <= number);
+            let block_transactions = &transactions[start..end];
+            let gas_used: u64 = block_transactions
+                .iter()
+                .map(|tx| tx.gas_used.low_u64())
+                .sum();
+            gas_used_ratio.push(gas_used as f64 / f64::from(BLOCK_GAS_LIMIT));
+            if !reward_percentiles.is_empty() {
+                reward.push(compute_rewards(block_transactions, &reward_percentiles));
+            }
+        }
+        let reward = (!reward_percentiles.is_empty()).then_some(reward);
 
         // `base_fee_per_gas` for next miniblock cannot be calculated, appending last fee as a placeholder.
         base_fee_per_gas.push(*base_fee_per_gas.last().unwrap());
@@ -767,42 +900,9 @@ impl EthNamespace {
             }
 
             TypedFilter::Events(filter, from_block) => {
-                let addresses = if let Some(addresses) = &filter.address {
-                    addresses.0.clone()
-                } else {
-                    vec![]
-                };
-                let topics = if let Some(topics) = &filter.topics {
-                    if topics.len() > EVENT_TOPIC_NUMBER_LIMIT {
-                        return Err(Web3Error::TooManyTopics);
-                    }
-                    let topics_by_idx = topics.iter().enumerate().filter_map(|(idx, topics)| {
-                        Some((idx as u32 + 1, topics.as_ref()?.0.clone()))
-                    });
-                    topics_by_idx.collect::<Vec<_>>()
-                } else {
-                    vec![]
-                };
-
-                let mut to_block = self
-                    .state
-                    .resolve_filter_block_number(filter.to_block)
-                    .await?;
-
-                if matches!(filter.to_block, Some(BlockNumber::Number(_))) {
-                    to_block = to_block.min(
-                        self.state
-                            .resolve_filter_block_number(Some(BlockNumber::Latest))
-                            .await?,
-                    );
-                }
-
-                let get_logs_filter = GetLogsFilter {
-                    from_block: *from_block,
-                    to_block,
-                    addresses,
-                    topics,
-                };
+                let get_logs_filter = self.resolve_logs_filter(filter, *from_block).await?;
+                self.check_logs_block_range(&get_logs_filter)?;
+                let to_block = get_logs_filter.to_block;
 
                 let mut storage = self
                     .state
@@ -811,20 +911,19 @@ impl EthNamespace {
                     .await
                     .map_err(|err| internal_error(METHOD_NAME, err))?;
 
-                // Check if there is more than one block in range and there are more than `req_entities_limit` logs that satisfies filter.
-                // In this case we should return error and suggest requesting logs with smaller block range.
+                // Check if there is more than one block in range and there are more than `get_logs_max_results` logs
+                // that satisfy the filter. In this case, we should return error and suggest requesting logs
+                // with a smaller block range.
+                let max_results = self.state.api_config.get_logs_max_results;
                 if *from_block != to_block {
                     if let Some(miniblock_number) = storage
                         .events_web3_dal()
-                        .get_log_block_number(
-                            &get_logs_filter,
-                            self.state.api_config.req_entities_limit,
-                        )
+                        .get_log_block_number(&get_logs_filter, max_results)
                         .await
                         .map_err(|err| internal_error(METHOD_NAME, err))?
                     {
                         return Err(Web3Error::LogsLimitExceeded(
-                            self.state.api_config.req_entities_limit,
+                            max_results,
                             from_block.0,
                             miniblock_number.0 - 1,
                         ));
@@ -843,6 +942,122 @@ impl EthNamespace {
 
         Ok(res)
     }
+
+    /// Converts a web3 filter into a DB filter starting from the specified block.
+    async fn resolve_logs_filter(
+        &self,
+        filter: &Filter,
+        from_block: MiniblockNumber,
+    ) -> Result<GetLogsFilter, Web3Error> {
+        let addresses = if let Some(addresses) = &filter.address {
+            addresses.0.clone()
+        } else {
+            vec![]
+        };
+        let topics = if let Some(topics) = &filter.topics {
+            if topics.len() > EVENT_TOPIC_NUMBER_LIMIT {
+                return Err(Web3Error::TooManyTopics);
+            }
+            let topics_by_idx = topics
+                .iter()
+                .enumerate()
+                .filter_map(|(idx, topics)| Some((idx as u32 + 1, topics.as_ref()?.0.clone())));
+            topics_by_idx.collect::<Vec<_>>()
+        } else {
+            vec![]
+        };
+
+        let mut to_block = self
+            .state
+            .resolve_filter_block_number(filter.to_block)
+            .await?;
+
+        if matches!(filter.to_block, Some(BlockNumber::Number(_))) {
+            to_block = to_block.min(
+                self.state
+                    .resolve_filter_block_number(Some(BlockNumber::Latest))
+                    .await?,
+            );
+        }
+
+        Ok(GetLogsFilter {
+            from_block,
+            to_block,
+            addresses,
+            topics,
+        })
+    }
+
+    /// Checks that the filter block range doesn't exceed the configured limit.
+    fn check_logs_block_range(&self, filter: &GetLogsFilter) -> Result<(), Web3Error> {
+        let Some(max_range) = self.state.api_config.get_logs_max_block_range else {
+            return Ok(());
+        };
+        let (from_block, to_block) = (filter.from_block.0, filter.to_block.0);
+        if to_block >= from_block && to_block - from_block >= max_range {
+            return Err(Web3Error::LogsBlockRangeExceeded(
+                max_range, from_block, to_block,
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Encodes a logs pagination cursor pointing to the specified log.
+fn encode_logs_cursor(miniblock_number: u32, log_index: u32) -> String {
+    format!("0x{miniblock_number:08x}{log_index:08x}")
+}
+
+/// Decodes a logs pagination cursor produced by [`encode_logs_cursor()`].
+fn decode_logs_cursor(cursor: &str) -> Result<(MiniblockNumber, u32), Web3Error> {
+    let cursor = cursor.strip_prefix("0x").unwrap_or(cursor);
+    if cursor.len() != 16 || !cursor.is_ascii() {
+        return Err(Web3Error::InvalidLogsCursor);
+    }
+    let (miniblock_number, log_index) = cursor.split_at(8);
+    let miniblock_number =
+        u32::from_str_radix(miniblock_number, 16).map_err(|_| Web3Error::InvalidLogsCursor)?;
+    let log_index = u32::from_str_radix(log_index, 16).map_err(|_| Web3Error::InvalidLogsCursor)?;
+    Ok((MiniblockNumber(miniblock_number), log_index))
+}
+
+/// Checks that reward percentiles are monotonically increasing values in the `[0, 100]` range, as required by the spec.
+fn validate_reward_percentiles(reward_percentiles: &[f32]) -> Result<(), Web3Error> {
+    let mut prev_percentile = 0.0;
+    for &percentile in reward_percentiles {
+        if !(prev_percentile..=100.0).contains(&percentile) {
+            return Err(Web3Error::InvalidFeeParams(format!(
+                "reward percentiles must be monotonically increasing values in [0, 100]; got {reward_percentiles:?}"
+            )));
+        }
+        prev_percentile = percentile;
+    }
+    Ok(())
+}
+
+/// Computes rewards (i.e., priority fees per gas) for the specified percentiles of gas used in a miniblock,
+/// similarly to Geth. `transactions` must be sorted by the priority fee.
+fn compute_rewards(
+    transactions: &[FeeHistoryTransaction],
+    reward_percentiles: &[f32],
+) -> Vec<U256> {
+    if transactions.is_empty() {
+        return vec![U256::zero(); reward_percentiles.len()];
+    }
+
+    let total_gas_used: u64 = transactions.iter().map(|tx| tx.gas_used.low_u64()).sum();
+    let mut rewards = Vec::with_capacity(reward_percentiles.len());
+    let mut tx_index = 0;
+    let mut cumulative_gas_used = transactions[0].gas_used.low_u64();
+    for &percentile in reward_percentiles {
+        let threshold = (total_gas_used as f64 * f64::from(percentile) / 100.0) as u64;
+        while cumulative_gas_used < threshold && tx_index + 1 < transactions.len() {
+            tx_index += 1;
+            cumulative_gas_used += transactions[tx_index].gas_used.low_u64();
+        }
+        rewards.push(transactions[tx_index].priority_fee_per_gas);
+    }
+    rewards
 }
 
 // Bogus methods.
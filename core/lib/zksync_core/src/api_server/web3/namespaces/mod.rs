@@ -1,6 +1,7 @@
 //! Actual implementation of Web3 API namespaces logic, not tied to the backend
 //! used to create a JSON RPC server.
 
+mod admin;
 mod debug;
 mod en;
 pub(crate) mod eth;
@@ -10,6 +11,6 @@ mod web3;
 mod zks;
 
 pub use self::{
-    debug::DebugNamespace, en::EnNamespace, eth::EthNamespace, net::NetNamespace,
-    snapshots::SnapshotsNamespace, web3::Web3Namespace, zks::ZksNamespace,
+    admin::AdminNamespace, debug::DebugNamespace, en::EnNamespace, eth::EthNamespace,
+    net::NetNamespace, snapshots::SnapshotsNamespace, web3::Web3Namespace, zks::ZksNamespace,
 };
@@ -1,5 +1,5 @@
 use zksync_types::{
-    snapshots::{AllSnapshots, SnapshotHeader, SnapshotStorageLogsChunkMetadata},
+    snapshots::{AllSnapshots, SnapshotHeader, SnapshotManifest, SnapshotStorageLogsChunkMetadata},
     L1BatchNumber,
 };
 use zksync_web3_decl::error::Web3Error;
@@ -96,13 +96,50 @@ impl SnapshotsNamespace {
                 internal_error(method_name, err)
             })?;
 
+        let manifest = if let Some(manifest_hash) = snapshot_metadata.manifest_hash {
+            let base_manifest_hash = if let Some(base) = snapshot_metadata.base_l1_batch_number {
+                storage_processor
+                    .snapshots_dal()
+                    .get_snapshot_metadata(base)
+                    .await
+                    .map_err(|err| internal_error(method_name, err))?
+                    .and_then(|base_snapshot| base_snapshot.manifest_hash)
+            } else {
+                None
+            };
+            let manifest = SnapshotManifest {
+                l1_batch_number,
+                l1_batch_root_hash: l1_batch_with_metadata.metadata.root_hash,
+                base_manifest_hash,
+                storage_logs_chunk_hashes: snapshot_metadata
+                    .storage_logs_chunk_hashes
+                    .into_iter()
+                    .flatten()
+                    .collect(),
+                signature: snapshot_metadata.manifest_signature,
+            };
+            if manifest.hash() == manifest_hash {
+                Some(manifest)
+            } else {
+                tracing::warn!(
+                    "Manifest for snapshot at L1 batch #{l1_batch_number} doesn't match the stored hash \
+                     {manifest_hash:?}; omitting it"
+                );
+                None
+            }
+        } else {
+            None
+        };
+
         method_latency.observe();
         Ok(Some(SnapshotHeader {
             l1_batch_number: snapshot_metadata.l1_batch_number,
             miniblock_number,
+            base_l1_batch_number: snapshot_metadata.base_l1_batch_number,
             last_l1_batch_with_metadata: l1_batch_with_metadata,
             storage_logs_chunks: chunks,
             factory_deps_filepath: snapshot_metadata.factory_deps_filepath,
+            manifest,
         }))
     }
 }
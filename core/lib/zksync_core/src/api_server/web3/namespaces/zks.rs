@@ -5,10 +5,12 @@ use zksync_dal::StorageProcessor;
 use zksync_mini_merkle_tree::MiniMerkleTree;
 use zksync_types::{
     api::{
-        BlockDetails, BridgeAddresses, GetLogsFilter, L1BatchDetails, L2ToL1LogProof, Proof,
-        ProtocolVersion, StorageProof, TransactionDetails,
+        BlockDetails, BridgeAddresses, ConsensusBlockCertificate, GetLogsFilter,
+        L1BatchCommitmentArtifacts, L1BatchDetails, L1BatchPaymasterStats, L1BatchPubdata,
+        L2ToL1LogProof, LogsPage, NodeStats, PaymasterUsage, PriorityOpStatus, Proof,
+        ProtocolVersion, PubdataBlobCommitment, StorageProof, TransactionDetails,
     },
-    fee::Fee,
+    fee::FeeEstimate,
     fee_model::FeeParams,
     l1::L1Tx,
     l2::L2Tx,
@@ -22,12 +24,18 @@ use zksync_types::{
 use zksync_utils::{address_to_h256, ratio_to_big_decimal_normalized};
 use zksync_web3_decl::{
     error::Web3Error,
-    types::{Address, Token, H256},
+    types::{Address, Filter, Token, H256},
 };
 
-use crate::api_server::{
-    tree::TreeApiClient,
-    web3::{backend_jsonrpsee::internal_error, metrics::API_METRICS, RpcState},
+use crate::{
+    api_server::{
+        tree::{TreeApiClient, TreeApiError},
+        web3::{
+            backend_jsonrpsee::internal_error, metrics::API_METRICS, namespaces::EthNamespace,
+            RpcState,
+        },
+    },
+    eth_sender::L1BatchBlobs,
 };
 
 #[derive(Debug)]
@@ -41,7 +49,7 @@ impl ZksNamespace {
     }
 
     #[tracing::instrument(skip(self, request))]
-    pub async fn estimate_fee_impl(&self, request: CallRequest) -> Result<Fee, Web3Error> {
+    pub async fn estimate_fee_impl(&self, request: CallRequest) -> Result<FeeEstimate, Web3Error> {
         const METHOD_NAME: &str = "estimate_fee";
 
         let method_latency = API_METRICS.start_call(METHOD_NAME);
@@ -93,10 +101,10 @@ impl ZksNamespace {
 
         let fee = self.estimate_fee(tx.into()).await?;
         method_latency.observe();
-        Ok(fee.gas_limit)
+        Ok(fee.fee.gas_limit)
     }
 
-    async fn estimate_fee(&self, tx: Transaction) -> Result<Fee, Web3Error> {
+    async fn estimate_fee(&self, tx: Transaction) -> Result<FeeEstimate, Web3Error> {
         let scale_factor = self.state.api_config.estimate_gas_scale_factor;
         let acceptable_overestimation =
             self.state.api_config.estimate_gas_acceptable_overestimation;
@@ -106,7 +114,7 @@ impl ZksNamespace {
             .tx_sender
             .get_txs_fee_in_wei(tx, scale_factor, acceptable_overestimation)
             .await
-            .map_err(|err| Web3Error::SubmitTransactionError(err.to_string(), err.data()))?;
+            .map_err(Web3Error::from)?;
 
         Ok(fee)
     }
@@ -508,7 +516,7 @@ impl ZksNamespace {
         let mut tx_details = self
             .state
             .connection_pool
-            .access_storage_tagged("api")
+            .access_storage_read_after_write("api")
             .await
             .unwrap()
             .transactions_web3_dal()
@@ -664,7 +672,11 @@ impl ZksNamespace {
             .ok_or(Web3Error::TreeApiUnavailable)?
             .get_proofs(l1_batch_number, hashed_keys)
             .await
-            .map_err(|err| internal_error(METHOD_NAME, err))?
+            .map_err(|err| match err {
+                TreeApiError::NoVersion(number) => Web3Error::ProofUnavailable(number.0),
+                TreeApiError::NotReady => Web3Error::TreeApiUnavailable,
+                TreeApiError::Internal(err) => internal_error(METHOD_NAME, format!("{err:#}")),
+            })?
             .into_iter()
             .zip(keys)
             .map(|(proof, key)| StorageProof {
@@ -680,4 +692,266 @@ impl ZksNamespace {
             storage_proof,
         })
     }
+
+    #[tracing::instrument(skip(self))]
+    pub async fn get_priority_op_status_impl(
+        &self,
+        l1_tx_hash: H256,
+    ) -> Result<Vec<PriorityOpStatus>, Web3Error> {
+        const METHOD_NAME: &str = "get_priority_op_status";
+
+        let method_latency = API_METRICS.start_call(METHOD_NAME);
+        let statuses = self
+            .state
+            .connection_pool
+            .access_storage_tagged("api")
+            .await
+            .unwrap()
+            .priority_ops_dal()
+            .get_priority_op_statuses(l1_tx_hash)
+            .await
+            .map_err(|err| internal_error(METHOD_NAME, err));
+
+        method_latency.observe();
+        statuses
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub async fn get_l1_batch_commitment_artifacts_impl(
+        &self,
+        batch: L1BatchNumber,
+    ) -> Result<Option<L1BatchCommitmentArtifacts>, Web3Error> {
+        const METHOD_NAME: &str = "get_l1_batch_commitment_artifacts";
+
+        let method_latency = API_METRICS.start_call(METHOD_NAME);
+        let artifacts = self
+            .state
+            .connection_pool
+            .access_storage_tagged("api")
+            .await
+            .unwrap()
+            .blocks_web3_dal()
+            .get_l1_batch_commitment_artifacts(batch)
+            .await
+            .map_err(|err| internal_error(METHOD_NAME, err));
+
+        method_latency.observe();
+        artifacts
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub async fn get_l1_batch_pubdata_impl(
+        &self,
+        batch: L1BatchNumber,
+    ) -> Result<Option<L1BatchPubdata>, Web3Error> {
+        const METHOD_NAME: &str = "get_l1_batch_pubdata";
+
+        let method_latency = API_METRICS.start_call(METHOD_NAME);
+        let mut storage = self
+            .state
+            .connection_pool
+            .access_storage_tagged("api")
+            .await
+            .unwrap();
+        let l1_batch = storage
+            .blocks_dal()
+            .get_l1_batch_metadata(batch)
+            .await
+            .map_err(|err| internal_error(METHOD_NAME, err))?;
+        let Some(l1_batch) = l1_batch else {
+            return Ok(None);
+        };
+        let is_pre_boojum = l1_batch
+            .header
+            .protocol_version
+            .map_or(true, |version| version.is_pre_boojum());
+        if is_pre_boojum {
+            // Pre-boojum batches publish pubdata in a different format, which is not supported.
+            return Ok(None);
+        }
+
+        let commit_tx_id = storage
+            .blocks_dal()
+            .get_eth_commit_tx_id(batch)
+            .await
+            .map_err(|err| internal_error(METHOD_NAME, err))?;
+        let (commit_tx_hash, published_blobs) = if let Some(commit_tx_id) = commit_tx_id {
+            let commit_tx_id = commit_tx_id as u32;
+            let commit_tx_hash = storage
+                .eth_sender_dal()
+                .get_confirmed_tx_hash_by_eth_tx_id(commit_tx_id)
+                .await
+                .map_err(|err| internal_error(METHOD_NAME, err))?;
+            let commit_tx = storage
+                .eth_sender_dal()
+                .get_eth_tx(commit_tx_id)
+                .await
+                .map_err(|err| internal_error(METHOD_NAME, err))?;
+            let published_blobs = commit_tx
+                .and_then(|tx| tx.blob_sidecar)
+                .map(|sidecar| sidecar.blobs);
+            (commit_tx_hash, published_blobs)
+        } else {
+            (None, None)
+        };
+        drop(storage);
+
+        let pubdata = l1_batch.pubdata();
+        let blobs = if let Some(mut published_blobs) = published_blobs {
+            // The commit transaction may carry blobs for several L1 batches. Pubdata of each batch is packed
+            // into its own blobs deterministically, so we can find the blobs for this batch (and the KZG commitments
+            // to them persisted with the transaction) by comparing blob contents; this doesn't require expensive
+            // KZG computations.
+            let blobs = L1BatchBlobs::pack(&pubdata).into_iter().map(|blob| {
+                let position = published_blobs
+                    .iter()
+                    .position(|published_blob| published_blob.blob == blob);
+                let Some(position) = position else {
+                    let err = format!(
+                        "reconstructed blobs for L1 batch #{batch} are not carried by its commit transaction"
+                    );
+                    return Err(internal_error(METHOD_NAME, err));
+                };
+                let blob = published_blobs.swap_remove(position);
+                Ok(PubdataBlobCommitment {
+                    versioned_hash: blob.versioned_hash,
+                    commitment: blob.commitment.into(),
+                    proof: blob.proof.into(),
+                })
+            });
+            Some(blobs.collect::<Result<_, _>>()?)
+        } else {
+            None
+        };
+
+        method_latency.observe();
+        Ok(Some(L1BatchPubdata {
+            number: batch,
+            pubdata_hash: l1_batch.pubdata_hash(),
+            pubdata: pubdata.into(),
+            commit_tx_hash,
+            blobs,
+        }))
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub async fn get_l1_batch_paymaster_stats_impl(
+        &self,
+        batch: L1BatchNumber,
+    ) -> Result<Option<L1BatchPaymasterStats>, Web3Error> {
+        const METHOD_NAME: &str = "get_l1_batch_paymaster_stats";
+
+        let method_latency = API_METRICS.start_call(METHOD_NAME);
+        let stats = self
+            .state
+            .connection_pool
+            .access_storage_tagged("api")
+            .await
+            .unwrap()
+            .paymaster_analytics_dal()
+            .get_l1_batch_paymaster_stats(batch)
+            .await
+            .map_err(|err| internal_error(METHOD_NAME, err));
+
+        method_latency.observe();
+        stats
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub async fn get_top_paymasters_impl(
+        &self,
+        from_batch: L1BatchNumber,
+        to_batch: L1BatchNumber,
+        limit: Option<u32>,
+    ) -> Result<Vec<PaymasterUsage>, Web3Error> {
+        const METHOD_NAME: &str = "get_top_paymasters";
+        const DEFAULT_LIMIT: u32 = 10;
+        const MAX_LIMIT: u32 = 100;
+        /// Aggregating over a larger range would scan too many rows in a single request.
+        const MAX_L1_BATCH_RANGE: u32 = 10_000;
+
+        let method_latency = API_METRICS.start_call(METHOD_NAME);
+        let range_len = to_batch.0.saturating_sub(from_batch.0).saturating_add(1);
+        if range_len > MAX_L1_BATCH_RANGE {
+            method_latency.observe();
+            return Err(Web3Error::L1BatchRangeExceeded(
+                MAX_L1_BATCH_RANGE,
+                from_batch.0,
+                to_batch.0,
+            ));
+        }
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT);
+        let paymasters = self
+            .state
+            .connection_pool
+            .access_storage_tagged("api")
+            .await
+            .unwrap()
+            .paymaster_analytics_dal()
+            .get_top_paymasters(from_batch..=to_batch, limit as usize)
+            .await
+            .map_err(|err| internal_error(METHOD_NAME, err));
+
+        method_latency.observe();
+        paymasters
+    }
+
+    #[tracing::instrument(skip(self, filter))]
+    pub async fn get_logs_paginated_impl(
+        &self,
+        filter: Filter,
+        cursor: Option<String>,
+        limit: Option<u32>,
+    ) -> Result<LogsPage, Web3Error> {
+        const METHOD_NAME: &str = "get_logs_paginated";
+
+        let method_latency = API_METRICS.start_call(METHOD_NAME);
+        let page = EthNamespace::new(self.state.clone())
+            .get_logs_page(filter, cursor.as_deref(), limit)
+            .await;
+        method_latency.observe();
+        page
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub fn get_node_stats_impl(&self) -> Option<NodeStats> {
+        const METHOD_NAME: &str = "get_node_stats";
+
+        let method_latency = API_METRICS.start_call(METHOD_NAME);
+        let stats = self.state.node_stats.get();
+        method_latency.observe();
+        stats
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub async fn get_consensus_block_certificate_impl(
+        &self,
+        block_number: MiniblockNumber,
+    ) -> Result<Option<ConsensusBlockCertificate>, Web3Error> {
+        const METHOD_NAME: &str = "get_consensus_block_certificate";
+
+        let method_latency = API_METRICS.start_call(METHOD_NAME);
+        let mut storage = self
+            .state
+            .connection_pool
+            .access_storage_tagged("api")
+            .await
+            .unwrap();
+        let certificates = storage
+            .consensus_dal()
+            .certificates_range(block_number..block_number + 1)
+            .await
+            .map_err(|err| internal_error(METHOD_NAME, format!("{err:#}")))?;
+        drop(storage);
+
+        let certificate = certificates
+            .into_iter()
+            .next()
+            .map(|fields| ConsensusBlockCertificate {
+                number: block_number,
+                justification: zksync_protobuf::encode(&fields.justification).into(),
+            });
+        method_latency.observe();
+        Ok(certificate)
+    }
 }
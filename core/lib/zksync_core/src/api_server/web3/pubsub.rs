@@ -1,5 +1,10 @@
 //! (Largely) backend-agnostic logic for dealing with Web3 subscriptions.
 
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Arc, Mutex},
+};
+
 use anyhow::Context as _;
 use futures::FutureExt;
 use tokio::{
@@ -8,16 +13,16 @@ use tokio::{
     time::{interval, Duration},
 };
 use zksync_dal::ConnectionPool;
-use zksync_types::{MiniblockNumber, H128, H256};
+use zksync_types::{Address, MiniblockNumber, H128, H256};
 use zksync_web3_decl::{
     jsonrpsee::{
         core::{server::SubscriptionMessage, SubscriptionResult},
         server::IdProvider,
         types::{error::ErrorCode, ErrorObject, SubscriptionId},
-        PendingSubscriptionSink, SendTimeoutError, SubscriptionSink,
+        PendingSubscriptionSink, SubscriptionSink,
     },
     namespaces::EthPubSubServer,
-    types::{BlockHeader, Log, PubSubFilter, PubSubResult},
+    types::{BlockHeader, L1BatchStage, L1BatchStatusUpdate, Log, PubSubFilter, PubSubResult},
 };
 
 use super::{
@@ -26,7 +31,9 @@ use super::{
 };
 
 const BROADCAST_CHANNEL_CAPACITY: usize = 1024;
-const SUBSCRIPTION_SINK_SEND_TIMEOUT: Duration = Duration::from_secs(1);
+/// Capacity of the channel with log notifications for a single subscriber, measured in notifier iterations.
+const LOGS_SUBSCRIBER_CHANNEL_CAPACITY: usize = 64;
+const DEFAULT_SUBSCRIPTION_SINK_SEND_TIMEOUT: Duration = Duration::from_secs(1);
 
 #[derive(Debug, Clone, Copy)]
 pub struct EthSubscriptionIdProvider;
@@ -45,10 +52,261 @@ pub(super) enum PubSubEvent {
     NotifyIterationFinished(SubscriptionType),
 }
 
+/// Filter applied to items sent to a subscriber.
+#[derive(Debug)]
+enum SubscriptionFilter {
+    L1BatchStage(L1BatchStage),
+}
+
+impl SubscriptionFilter {
+    fn matches(&self, item: &PubSubResult) -> bool {
+        match (self, item) {
+            (Self::L1BatchStage(stage), PubSubResult::L1BatchStatus(update)) => {
+                update.stage == *stage
+            }
+            _ => true,
+        }
+    }
+}
+
+/// Keys under which a log subscription is stored in [`LogSubscriptions`]. Only the most selective part
+/// of the subscription filter is indexed; the full filter is evaluated for the candidates found via the index.
+#[derive(Debug)]
+enum LogIndexKeys {
+    /// Subscription filters by addresses.
+    Addresses(Vec<Address>),
+    /// Subscription doesn't filter by addresses, but filters by topics at the specified position.
+    Topics(usize, Vec<H256>),
+    /// Subscription matches all logs.
+    Wildcard,
+}
+
+impl LogIndexKeys {
+    fn new(filter: &PubSubFilter) -> Self {
+        if let Some(addresses) = &filter.address {
+            return Self::Addresses(addresses.0.clone());
+        }
+        let topics = filter.topics.iter().flatten().enumerate();
+        let mut topics = topics.filter_map(|(idx, topics)| Some((idx, topics.as_ref()?)));
+        match topics.next() {
+            Some((idx, topics)) => Self::Topics(idx, topics.0.clone()),
+            None => Self::Wildcard,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct LogSubscriber {
+    filter: PubSubFilter,
+    keys: LogIndexKeys,
+    sender: mpsc::Sender<Vec<PubSubResult>>,
+}
+
+#[derive(Debug, Default)]
+struct LogSubscriptionsInner {
+    next_id: u64,
+    subscribers: HashMap<u64, LogSubscriber>,
+    by_address: HashMap<Address, HashSet<u64>>,
+    by_topic: HashMap<(usize, H256), HashSet<u64>>,
+    wildcard: HashSet<u64>,
+}
+
+impl LogSubscriptionsInner {
+    fn remove(&mut self, id: u64) -> Option<LogSubscriber> {
+        let subscriber = self.subscribers.remove(&id)?;
+        match &subscriber.keys {
+            LogIndexKeys::Addresses(addresses) => {
+                for address in addresses {
+                    remove_from_index(&mut self.by_address, address, id);
+                }
+            }
+            LogIndexKeys::Topics(idx, topics) => {
+                for topic in topics {
+                    remove_from_index(&mut self.by_topic, &(*idx, *topic), id);
+                }
+            }
+            LogIndexKeys::Wildcard => {
+                self.wildcard.remove(&id);
+            }
+        }
+        Some(subscriber)
+    }
+
+    /// Returns IDs of subscribers that may be interested in the specified log. Each ID is returned at most once.
+    fn candidates<'a>(&'a self, log: &'a Log) -> impl Iterator<Item = u64> + 'a {
+        let by_address = self.by_address.get(&log.address).into_iter().flatten();
+        let by_topic = log
+            .topics
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, topic)| self.by_topic.get(&(idx, *topic)))
+            .flatten();
+        by_address.chain(by_topic).chain(&self.wildcard).copied()
+    }
+}
+
+fn remove_from_index<K: std::hash::Hash + Eq>(
+    index: &mut HashMap<K, HashSet<u64>>,
+    key: &K,
+    id: u64,
+) {
+    if let Some(ids) = index.get_mut(key) {
+        ids.remove(&id);
+        if ids.is_empty() {
+            index.remove(key);
+        }
+    }
+}
+
+/// Registry of log subscriptions indexed by addresses and topics. New logs are matched against the index once
+/// per notifier iteration, so that the matching cost is proportional to the number of matching subscriptions
+/// rather than to the total number of subscriptions.
+#[derive(Debug, Default)]
+pub(super) struct LogSubscriptions {
+    inner: Mutex<LogSubscriptionsInner>,
+}
+
+impl LogSubscriptions {
+    pub(super) fn subscribe(self: &Arc<Self>, filter: PubSubFilter) -> LogsReceiver {
+        let (sender, receiver) = mpsc::channel(LOGS_SUBSCRIBER_CHANNEL_CAPACITY);
+        let keys = LogIndexKeys::new(&filter);
+
+        let mut inner = self.inner.lock().unwrap();
+        let id = inner.next_id;
+        inner.next_id += 1;
+        match &keys {
+            LogIndexKeys::Addresses(addresses) => {
+                for &address in addresses {
+                    inner.by_address.entry(address).or_default().insert(id);
+                }
+            }
+            LogIndexKeys::Topics(idx, topics) => {
+                for &topic in topics {
+                    inner.by_topic.entry((*idx, topic)).or_default().insert(id);
+                }
+            }
+            LogIndexKeys::Wildcard => {
+                inner.wildcard.insert(id);
+            }
+        }
+        let subscriber = LogSubscriber {
+            filter,
+            keys,
+            sender,
+        };
+        inner.subscribers.insert(id, subscriber);
+        PUB_SUB_METRICS
+            .log_subscriptions
+            .set(inner.subscribers.len());
+        drop(inner);
+
+        LogsReceiver {
+            id,
+            subscriptions: self.clone(),
+            receiver,
+        }
+    }
+
+    #[cfg(test)]
+    pub(super) fn len(&self) -> usize {
+        self.inner.lock().unwrap().subscribers.len()
+    }
+
+    /// Matches logs against all subscriptions and sends them to matching subscribers. Subscribers
+    /// that cannot keep up with notifications are dropped.
+    pub(super) fn dispatch(&self, results: &[PubSubResult]) {
+        let latency = PUB_SUB_METRICS.logs_dispatch_latency.start();
+        let mut inner = self.inner.lock().unwrap();
+        let mut notifications = HashMap::<u64, Vec<PubSubResult>>::new();
+        for result in results {
+            let PubSubResult::Log(log) = result else {
+                continue;
+            };
+            for id in inner.candidates(log) {
+                if inner.subscribers[&id].filter.matches(log) {
+                    notifications.entry(id).or_default().push(result.clone());
+                }
+            }
+        }
+
+        for (id, items) in notifications {
+            let subscriber = &inner.subscribers[&id];
+            if let Err(err) = subscriber.sender.try_send(items) {
+                if let mpsc::error::TrySendError::Full(_) = err {
+                    // The subscriber is too slow to keep up with notifications; drop it
+                    // instead of buffering notifications for it.
+                    PUB_SUB_METRICS.skipped_broadcast_messages[&SubscriptionType::Logs].observe(1);
+                }
+                inner.remove(id);
+            }
+        }
+        PUB_SUB_METRICS
+            .log_subscriptions
+            .set(inner.subscribers.len());
+        latency.observe();
+    }
+
+    fn unsubscribe(&self, id: u64) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.remove(id);
+        PUB_SUB_METRICS
+            .log_subscriptions
+            .set(inner.subscribers.len());
+    }
+}
+
+/// Receiver of log notifications for a single subscriber. Unregisters the subscription when dropped.
+#[derive(Debug)]
+pub(super) struct LogsReceiver {
+    id: u64,
+    subscriptions: Arc<LogSubscriptions>,
+    receiver: mpsc::Receiver<Vec<PubSubResult>>,
+}
+
+impl LogsReceiver {
+    pub(super) async fn recv(&mut self) -> Option<Vec<PubSubResult>> {
+        self.receiver.recv().await
+    }
+}
+
+impl Drop for LogsReceiver {
+    fn drop(&mut self) {
+        self.subscriptions.unsubscribe(self.id);
+    }
+}
+
+/// Source of notifications for a single subscriber.
+#[derive(Debug)]
+enum SubscriptionReceiver {
+    Broadcast(broadcast::Receiver<Vec<PubSubResult>>),
+    Logs(LogsReceiver),
+}
+
+impl SubscriptionReceiver {
+    async fn recv(&mut self) -> Result<Vec<PubSubResult>, broadcast::error::RecvError> {
+        match self {
+            Self::Broadcast(receiver) => receiver.recv().await,
+            // The sender is dropped either if the notifier is shut down, or if the subscriber is lagging.
+            // In both cases, the subscriber should terminate.
+            Self::Logs(receiver) => receiver
+                .recv()
+                .await
+                .ok_or(broadcast::error::RecvError::Closed),
+        }
+    }
+}
+
+/// Destination of notifications produced by a [`PubSubNotifier`].
+#[derive(Debug)]
+enum NotificationSink {
+    Broadcast(broadcast::Sender<Vec<PubSubResult>>),
+    Logs(Arc<LogSubscriptions>),
+}
+
 /// Manager of notifications for a certain type of subscriptions.
 #[derive(Debug)]
 struct PubSubNotifier {
-    sender: broadcast::Sender<Vec<PubSubResult>>,
+    sink: NotificationSink,
     connection_pool: ConnectionPool,
     polling_interval: Duration,
     events_sender: Option<mpsc::UnboundedSender<PubSubEvent>>,
@@ -59,7 +317,7 @@ impl PubSubNotifier {
         self.connection_pool
             .access_storage_tagged("api")
             .await
-            .context("access_storage_tagged")?
+            .context("access_storage_replica")?
             .blocks_web3_dal()
             .get_sealed_miniblock_number()
             .await
@@ -101,9 +359,14 @@ impl PubSubNotifier {
     }
 
     fn send_pub_sub_results(&self, results: Vec<PubSubResult>, sub_type: SubscriptionType) {
-        // Errors only on 0 receivers, but we want to go on if we have 0 subscribers so ignore the error.
-        self.sender.send(results).ok();
-        PUB_SUB_METRICS.broadcast_channel_len[&sub_type].set(self.sender.len());
+        match &self.sink {
+            NotificationSink::Broadcast(sender) => {
+                // Errors only on 0 receivers, but we want to go on if we have 0 subscribers so ignore the error.
+                sender.send(results).ok();
+                PUB_SUB_METRICS.broadcast_channel_len[&sub_type].set(sender.len());
+            }
+            NotificationSink::Logs(subscriptions) => subscriptions.dispatch(&results),
+        }
     }
 
     async fn new_blocks(
@@ -113,7 +376,7 @@ impl PubSubNotifier {
         self.connection_pool
             .access_storage_tagged("api")
             .await
-            .context("access_storage_tagged")?
+            .context("access_storage_replica")?
             .blocks_web3_dal()
             .get_block_headers_after(last_block_number)
             .await
@@ -151,7 +414,7 @@ impl PubSubNotifier {
         self.connection_pool
             .access_storage_tagged("api")
             .await
-            .context("access_storage_tagged")?
+            .context("access_storage_replica")?
             .transactions_web3_dal()
             .get_pending_txs_hashes_after(last_time, None)
             .await
@@ -182,11 +445,60 @@ impl PubSubNotifier {
         Ok(())
     }
 
+    async fn notify_l1_batch_statuses(
+        self,
+        stop_receiver: watch::Receiver<bool>,
+    ) -> anyhow::Result<()> {
+        let mut last_time = chrono::Utc::now().naive_utc();
+        let mut timer = interval(self.polling_interval);
+        loop {
+            if *stop_receiver.borrow() {
+                tracing::info!(
+                    "Stop signal received, pubsub_l1_batch_status_notifier is shutting down"
+                );
+                break;
+            }
+            timer.tick().await;
+
+            let db_latency =
+                PUB_SUB_METRICS.db_poll_latency[&SubscriptionType::L1BatchStatuses].start();
+            let (new_updates, new_last_time) = self.new_l1_batch_statuses(last_time).await?;
+            db_latency.observe();
+
+            if let Some(new_last_time) = new_last_time {
+                last_time = new_last_time;
+                let new_updates = new_updates
+                    .into_iter()
+                    .map(PubSubResult::L1BatchStatus)
+                    .collect();
+                self.send_pub_sub_results(new_updates, SubscriptionType::L1BatchStatuses);
+            }
+            self.emit_event(PubSubEvent::NotifyIterationFinished(
+                SubscriptionType::L1BatchStatuses,
+            ));
+        }
+        Ok(())
+    }
+
+    async fn new_l1_batch_statuses(
+        &self,
+        last_time: chrono::NaiveDateTime,
+    ) -> anyhow::Result<(Vec<L1BatchStatusUpdate>, Option<chrono::NaiveDateTime>)> {
+        self.connection_pool
+            .access_storage_tagged("api")
+            .await
+            .context("access_storage_replica")?
+            .blocks_web3_dal()
+            .get_l1_batch_status_updates_after(last_time, None)
+            .await
+            .context("get_l1_batch_status_updates_after()")
+    }
+
     async fn new_logs(&self, last_block_number: MiniblockNumber) -> anyhow::Result<Vec<Log>> {
         self.connection_pool
             .access_storage_tagged("api")
             .await
-            .context("access_storage_tagged")?
+            .context("access_storage_replica")?
             .events_web3_dal()
             .get_all_logs(last_block_number)
             .await
@@ -198,21 +510,26 @@ impl PubSubNotifier {
 pub(super) struct EthSubscribe {
     blocks: broadcast::Sender<Vec<PubSubResult>>,
     transactions: broadcast::Sender<Vec<PubSubResult>>,
-    logs: broadcast::Sender<Vec<PubSubResult>>,
+    logs: Arc<LogSubscriptions>,
+    l1_batch_statuses: broadcast::Sender<Vec<PubSubResult>>,
     events_sender: Option<mpsc::UnboundedSender<PubSubEvent>>,
+    /// Timeout for sending a notification to a subscriber. Slow subscribers are dropped once it is exceeded.
+    send_timeout: Duration,
 }
 
 impl EthSubscribe {
     pub fn new() -> Self {
         let (blocks, _) = broadcast::channel(BROADCAST_CHANNEL_CAPACITY);
         let (transactions, _) = broadcast::channel(BROADCAST_CHANNEL_CAPACITY);
-        let (logs, _) = broadcast::channel(BROADCAST_CHANNEL_CAPACITY);
+        let (l1_batch_statuses, _) = broadcast::channel(BROADCAST_CHANNEL_CAPACITY);
 
         Self {
             blocks,
             transactions,
-            logs,
+            logs: Arc::default(),
+            l1_batch_statuses,
             events_sender: None,
+            send_timeout: DEFAULT_SUBSCRIPTION_SINK_SEND_TIMEOUT,
         }
     }
 
@@ -220,6 +537,10 @@ impl EthSubscribe {
         self.events_sender = Some(sender);
     }
 
+    pub fn set_send_timeout(&mut self, timeout: Duration) {
+        self.send_timeout = timeout;
+    }
+
     async fn reject(sink: PendingSubscriptionSink) {
         sink.reject(ErrorObject::borrowed(
             ErrorCode::InvalidParams.code(),
@@ -232,8 +553,9 @@ impl EthSubscribe {
     async fn run_subscriber(
         sink: SubscriptionSink,
         subscription_type: SubscriptionType,
-        mut receiver: broadcast::Receiver<Vec<PubSubResult>>,
-        filter: Option<PubSubFilter>,
+        mut receiver: SubscriptionReceiver,
+        filter: Option<SubscriptionFilter>,
+        send_timeout: Duration,
     ) {
         let _guard = PUB_SUB_METRICS.active_subscribers[&subscription_type].inc_guard(1);
         let lifetime_latency = PUB_SUB_METRICS.subscriber_lifetime[&subscription_type].start();
@@ -251,6 +573,8 @@ impl EthSubscribe {
                             break;
                         }
                         Err(broadcast::error::RecvError::Lagged(message_count)) => {
+                            // The subscriber is too slow to keep up with the broadcast channel; drop it
+                            // instead of buffering messages for it.
                             PUB_SUB_METRICS
                                 .skipped_broadcast_messages[&subscription_type]
                                 .observe(message_count);
@@ -262,11 +586,14 @@ impl EthSubscribe {
                         &sink,
                         subscription_type,
                         new_items,
-                        filter.as_ref()
+                        filter.as_ref(),
+                        send_timeout,
                     )
                     .await;
-                    if handle_result.is_err() {
+                    if let Err(dropped_count) = handle_result {
                         PUB_SUB_METRICS.subscriber_send_timeouts[&subscription_type].inc();
+                        PUB_SUB_METRICS.dropped_notifications[&subscription_type]
+                            .inc_by(dropped_count as u64);
                         break;
                     }
                 }
@@ -278,29 +605,32 @@ impl EthSubscribe {
         lifetime_latency.observe();
     }
 
+    /// Sends new items to the subscriber. On a send timeout, returns the number of items
+    /// that were not delivered to the subscriber.
     async fn handle_new_items(
         sink: &SubscriptionSink,
         subscription_type: SubscriptionType,
         new_items: Vec<PubSubResult>,
-        filter: Option<&PubSubFilter>,
-    ) -> Result<(), SendTimeoutError> {
+        filter: Option<&SubscriptionFilter>,
+        send_timeout: Duration,
+    ) -> Result<(), usize> {
         let notify_latency = PUB_SUB_METRICS.notify_subscribers_latency[&subscription_type].start();
-        for item in new_items {
-            if let PubSubResult::Log(log) = &item {
-                if let Some(filter) = &filter {
-                    if !filter.matches(log) {
-                        continue;
-                    }
-                }
+        let items: Vec<_> = new_items
+            .into_iter()
+            .filter(|item| filter.map_or(true, |filter| filter.matches(item)))
+            .collect();
+        for (i, item) in items.iter().enumerate() {
+            let send_result = sink
+                .send_timeout(
+                    SubscriptionMessage::from_json(item)
+                        .expect("PubSubResult always serializable to json;qed"),
+                    send_timeout,
+                )
+                .await;
+            if send_result.is_err() {
+                return Err(items.len() - i);
             }
 
-            sink.send_timeout(
-                SubscriptionMessage::from_json(&item)
-                    .expect("PubSubResult always serializable to json;qed"),
-                SUBSCRIPTION_SINK_SEND_TIMEOUT,
-            )
-            .await?;
-
             PUB_SUB_METRICS.notify[&subscription_type].inc();
         }
 
@@ -320,12 +650,13 @@ impl EthSubscribe {
                 let Ok(sink) = pending_sink.accept().await else {
                     return;
                 };
-                let blocks_rx = self.blocks.subscribe();
+                let blocks_rx = SubscriptionReceiver::Broadcast(self.blocks.subscribe());
                 tokio::spawn(Self::run_subscriber(
                     sink,
                     SubscriptionType::Blocks,
                     blocks_rx,
                     None,
+                    self.send_timeout,
                 ));
 
                 Some(SubscriptionType::Blocks)
@@ -334,12 +665,14 @@ impl EthSubscribe {
                 let Ok(sink) = pending_sink.accept().await else {
                     return;
                 };
-                let transactions_rx = self.transactions.subscribe();
+                let transactions_rx =
+                    SubscriptionReceiver::Broadcast(self.transactions.subscribe());
                 tokio::spawn(Self::run_subscriber(
                     sink,
                     SubscriptionType::Txs,
                     transactions_rx,
                     None,
+                    self.send_timeout,
                 ));
                 Some(SubscriptionType::Txs)
             }
@@ -354,25 +687,48 @@ impl EthSubscribe {
                     let Ok(sink) = pending_sink.accept().await else {
                         return;
                     };
-                    let logs_rx = self.logs.subscribe();
+                    // Logs are filtered by `LogSubscriptions` before being sent to the subscriber.
+                    let logs_rx = SubscriptionReceiver::Logs(self.logs.subscribe(filter));
                     tokio::spawn(Self::run_subscriber(
                         sink,
                         SubscriptionType::Logs,
                         logs_rx,
-                        Some(filter),
+                        None,
+                        self.send_timeout,
                     ));
                     Some(SubscriptionType::Logs)
                 }
             }
+            "l1BatchCommitted" | "l1BatchProven" | "l1BatchExecuted" => {
+                let stage = match sub_type.as_str() {
+                    "l1BatchCommitted" => L1BatchStage::Committed,
+                    "l1BatchProven" => L1BatchStage::Proven,
+                    _ => L1BatchStage::Executed,
+                };
+                let Ok(sink) = pending_sink.accept().await else {
+                    return;
+                };
+                let l1_batch_statuses_rx =
+                    SubscriptionReceiver::Broadcast(self.l1_batch_statuses.subscribe());
+                tokio::spawn(Self::run_subscriber(
+                    sink,
+                    SubscriptionType::L1BatchStatuses,
+                    l1_batch_statuses_rx,
+                    Some(SubscriptionFilter::L1BatchStage(stage)),
+                    self.send_timeout,
+                ));
+                Some(SubscriptionType::L1BatchStatuses)
+            }
             "syncing" => {
                 let Ok(sink) = pending_sink.accept().await else {
                     return;
                 };
 
+                let send_timeout = self.send_timeout;
                 tokio::spawn(async move {
                     sink.send_timeout(
                         SubscriptionMessage::from_json(&PubSubResult::Syncing(false)).unwrap(),
-                        SUBSCRIPTION_SINK_SEND_TIMEOUT,
+                        send_timeout,
                     )
                     .await
                 });
@@ -398,10 +754,10 @@ impl EthSubscribe {
         polling_interval: Duration,
         stop_receiver: watch::Receiver<bool>,
     ) -> Vec<JoinHandle<anyhow::Result<()>>> {
-        let mut notifier_tasks = Vec::with_capacity(3);
+        let mut notifier_tasks = Vec::with_capacity(4);
 
         let notifier = PubSubNotifier {
-            sender: self.blocks.clone(),
+            sink: NotificationSink::Broadcast(self.blocks.clone()),
             connection_pool: connection_pool.clone(),
             polling_interval,
             events_sender: self.events_sender.clone(),
@@ -410,7 +766,7 @@ impl EthSubscribe {
         notifier_tasks.push(notifier_task);
 
         let notifier = PubSubNotifier {
-            sender: self.transactions.clone(),
+            sink: NotificationSink::Broadcast(self.transactions.clone()),
             connection_pool: connection_pool.clone(),
             polling_interval,
             events_sender: self.events_sender.clone(),
@@ -419,12 +775,21 @@ impl EthSubscribe {
         notifier_tasks.push(notifier_task);
 
         let notifier = PubSubNotifier {
-            sender: self.logs.clone(),
+            sink: NotificationSink::Logs(self.logs.clone()),
+            connection_pool: connection_pool.clone(),
+            polling_interval,
+            events_sender: self.events_sender.clone(),
+        };
+        let notifier_task = tokio::spawn(notifier.notify_logs(stop_receiver.clone()));
+        notifier_tasks.push(notifier_task);
+
+        let notifier = PubSubNotifier {
+            sink: NotificationSink::Broadcast(self.l1_batch_statuses.clone()),
             connection_pool,
             polling_interval,
             events_sender: self.events_sender.clone(),
         };
-        let notifier_task = tokio::spawn(notifier.notify_logs(stop_receiver));
+        let notifier_task = tokio::spawn(notifier.notify_l1_batch_statuses(stop_receiver));
 
         notifier_tasks.push(notifier_task);
         notifier_tasks
@@ -2,16 +2,16 @@ use std::{
     future::Future,
     sync::{
         atomic::{AtomicU32, Ordering},
-        Arc,
+        Arc, RwLock,
     },
-    time::{Duration, Instant},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
 
 use lru::LruCache;
 use tokio::sync::Mutex;
 use vise::GaugeGuard;
 use zksync_config::configs::{api::Web3JsonRpcConfig, chain::NetworkConfig, ContractsConfig};
-use zksync_dal::ConnectionPool;
+use zksync_dal::{ConnectionPool, StorageProcessor};
 use zksync_types::{
     api, l2::L2Tx, transaction_request::CallRequest, Address, L1ChainId, L2ChainId,
     MiniblockNumber, H256, U256, U64,
@@ -22,10 +22,12 @@ use super::metrics::{FilterType, FILTER_METRICS};
 use crate::{
     api_server::{
         execution_sandbox::BlockArgs,
-        tree::TreeApiHttpClient,
+        tree::TreeApiClient,
         tx_sender::TxSender,
         web3::{backend_jsonrpsee::internal_error, resolve_block, TypedFilter},
     },
+    eth_sender::ResubmissionPolicy,
+    state_keeper::MempoolGuard,
     sync_layer::SyncState,
 };
 
@@ -45,6 +47,12 @@ pub struct InternalApiConfig {
     pub l2_testnet_paymaster_addr: Option<Address>,
     pub req_entities_limit: usize,
     pub fee_history_limit: u64,
+    /// Maximum block range for `eth_getLogs` and event filters; `None` means no limit.
+    pub get_logs_max_block_range: Option<u32>,
+    /// Maximum number of logs returned by a single `eth_getLogs` call.
+    pub get_logs_max_results: usize,
+    /// Number of latest miniblocks for which historical state is served; `None` means archival mode.
+    pub historical_state_depth: Option<u32>,
 }
 
 impl InternalApiConfig {
@@ -70,6 +78,9 @@ impl InternalApiConfig {
             l2_testnet_paymaster_addr: contracts_config.l2_testnet_paymaster_addr,
             req_entities_limit: web3_config.req_entities_limit(),
             fee_history_limit: web3_config.fee_history_limit(),
+            get_logs_max_block_range: web3_config.get_logs_max_block_range,
+            get_logs_max_results: web3_config.get_logs_max_results(),
+            historical_state_depth: web3_config.historical_state_depth,
         }
     }
 }
@@ -151,16 +162,114 @@ impl SealedMiniblockNumber {
     }
 }
 
+/// Node-level stats (see [`api::NodeStats`]) periodically collected by a background task, so that
+/// `zks_nodeStats` doesn't query Postgres on each call.
+#[derive(Debug, Clone)]
+pub(crate) struct NodeStatsCollector(Arc<RwLock<Option<api::NodeStats>>>);
+
+impl NodeStatsCollector {
+    /// Creates a handle to the node stats together with a task that will update them on a schedule.
+    pub fn new(
+        connection_pool: ConnectionPool,
+        sync_state: Option<SyncState>,
+        update_interval: Duration,
+    ) -> (Self, impl Future<Output = ()> + Send) {
+        let this = Self(Arc::default());
+        let stats_updater = this.clone();
+        let update_task = async move {
+            loop {
+                if Arc::strong_count(&stats_updater.0) == 1 {
+                    // All handles to stats were dropped; there's no sense continuing updates.
+                    tracing::debug!("Stopping node stats updates");
+                    break;
+                }
+
+                match Self::collect(&connection_pool, sync_state.as_ref()).await {
+                    Ok(stats) => {
+                        *stats_updater.0.write().unwrap() = Some(stats);
+                    }
+                    Err(err) => tracing::warn!("Failed collecting node stats: {err:#}"),
+                }
+                tokio::time::sleep(update_interval).await;
+            }
+        };
+
+        (this, update_task)
+    }
+
+    async fn collect(
+        connection_pool: &ConnectionPool,
+        sync_state: Option<&SyncState>,
+    ) -> anyhow::Result<api::NodeStats> {
+        let mut storage = connection_pool.access_storage_tagged("api").await?;
+        let sealed_miniblock = storage
+            .blocks_web3_dal()
+            .get_sealed_miniblock_number()
+            .await?;
+        let sealed_l1_batch = storage.blocks_dal().get_sealed_l1_batch_number().await?;
+        let last_l1_batch_with_metadata = storage
+            .blocks_dal()
+            .get_last_l1_batch_number_with_metadata()
+            .await?;
+        let last_committed_l1_batch = storage
+            .blocks_dal()
+            .get_number_of_last_l1_batch_committed_on_eth()
+            .await?;
+        let last_proven_l1_batch = storage
+            .blocks_dal()
+            .get_number_of_last_l1_batch_proven_on_eth()
+            .await?;
+        let last_executed_l1_batch = storage
+            .blocks_dal()
+            .get_number_of_last_l1_batch_executed_on_eth()
+            .await?;
+        let mempool_size = storage.transactions_dal().get_mempool_size().await?;
+        let eth_sender_inflight_txs = storage.eth_sender_dal().get_inflight_txs_count().await?;
+        drop(storage);
+
+        let tree_lag = match (sealed_l1_batch, last_l1_batch_with_metadata) {
+            (Some(sealed), Some(with_metadata)) => sealed.0.saturating_sub(with_metadata.0),
+            (Some(sealed), None) => sealed.0 + 1,
+            (None, _) => 0,
+        };
+        let collected_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("current time is before Unix epoch")
+            .as_secs();
+        Ok(api::NodeStats {
+            collected_at,
+            sealed_miniblock,
+            sealed_l1_batch,
+            last_l1_batch_with_metadata,
+            tree_lag,
+            last_committed_l1_batch,
+            last_proven_l1_batch,
+            last_executed_l1_batch,
+            mempool_size,
+            eth_sender_inflight_txs,
+            sync_lag: sync_state.and_then(SyncState::get_sync_lag),
+        })
+    }
+
+    /// Returns the latest collected stats, or `None` if stats weren't collected yet.
+    pub fn get(&self) -> Option<api::NodeStats> {
+        self.0.read().unwrap().clone()
+    }
+}
+
 /// Holder for the data required for the API to be functional.
 #[derive(Debug, Clone)]
 pub struct RpcState {
     pub(crate) installed_filters: Arc<Mutex<Filters>>,
     pub connection_pool: ConnectionPool,
-    pub tree_api: Option<TreeApiHttpClient>,
+    pub(crate) tree_api: Option<Arc<dyn TreeApiClient>>,
     pub tx_sender: TxSender,
     pub sync_state: Option<SyncState>,
     pub(super) api_config: InternalApiConfig,
     pub(super) last_sealed_miniblock: SealedMiniblockNumber,
+    pub(super) node_stats: NodeStatsCollector,
+    pub(crate) resubmission_policy: Option<ResubmissionPolicy>,
+    pub(crate) mempool: Option<MempoolGuard>,
 }
 
 impl RpcState {
@@ -174,6 +283,41 @@ impl RpcState {
         ))
     }
 
+    /// Checks that state for the specified miniblock is served by this server, i.e., that the miniblock
+    /// is not pruned and is within the configured historical state depth (if any).
+    pub async fn ensure_historical_state_available(
+        &self,
+        connection: &mut StorageProcessor<'_>,
+        block_number: MiniblockNumber,
+        method_name: &'static str,
+    ) -> Result<(), Web3Error> {
+        let pruning_info = connection.pruning_dal().get_pruning_info().await;
+        let earliest_available_block = pruning_info
+            .map_err(|err| internal_error(method_name, err))?
+            .earliest_available_miniblock();
+        if block_number < earliest_available_block {
+            return Err(Web3Error::PrunedBlock(
+                block_number.0,
+                earliest_available_block.0,
+            ));
+        }
+
+        let Some(depth) = self.api_config.historical_state_depth else {
+            return Ok(());
+        };
+        // `diff()` may update the last sealed miniblock number, so it's consistent with `block_number`.
+        let block_diff = self.last_sealed_miniblock.diff(block_number);
+        if block_diff <= depth {
+            Ok(())
+        } else {
+            let oldest_available_block = block_number.0 + block_diff - depth;
+            Err(Web3Error::HistoricalStateUnavailable(
+                block_number.0,
+                oldest_available_block.max(earliest_available_block.0),
+            ))
+        }
+    }
+
     pub fn u64_to_block_number(n: U64) -> MiniblockNumber {
         if n.as_u64() > u32::MAX as u64 {
             MiniblockNumber(u32::MAX)
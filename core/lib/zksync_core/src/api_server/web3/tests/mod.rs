@@ -2,29 +2,42 @@ use std::{sync::Arc, time::Instant};
 
 use assert_matches::assert_matches;
 use async_trait::async_trait;
+use rand::Rng;
 use tokio::sync::watch;
 use zksync_config::configs::{
     api::Web3JsonRpcConfig,
     chain::{NetworkConfig, StateKeeperConfig},
     ContractsConfig,
 };
-use zksync_dal::{transactions_dal::L2TxSubmissionResult, ConnectionPool};
+use zksync_consensus_roles::validator;
+use zksync_dal::{
+    blocks_dal::ConsensusBlockFields, transactions_dal::L2TxSubmissionResult, ConnectionPool,
+};
 use zksync_health_check::CheckHealth;
 use zksync_state::PostgresStorageCaches;
 use zksync_system_constants::L1_GAS_PER_PUBDATA_BYTE;
 use zksync_types::{
-    block::MiniblockHeader, fee::TransactionExecutionMetrics, tx::IncludedTxLocation, Address,
-    L1BatchNumber, VmEvent, H256, U64,
+    aggregated_operations::AggregatedActionType,
+    block::MiniblockHeader,
+    fee::TransactionExecutionMetrics,
+    transaction_request::CallRequest,
+    tx::{
+        tx_execution_info::{ExecutionMetrics, TxExecutionStatus},
+        IncludedTxLocation, TransactionExecutionResult,
+    },
+    vm_trace::Call,
+    web3, Address, L1BatchNumber, VmEvent, H256, U256, U64,
 };
 use zksync_web3_decl::{
     jsonrpsee::{core::ClientError as RpcError, http_client::HttpClient, types::error::ErrorCode},
-    namespaces::{EthNamespaceClient, ZksNamespaceClient},
+    namespaces::{DebugNamespaceClient, EthNamespaceClient, ZksNamespaceClient},
     types::FilterChanges,
 };
 
 use super::{metrics::ApiTransportLabel, *};
 use crate::{
     api_server::tx_sender::TxSenderConfig,
+    fee_model::{main_node_fee_model_config, MainNodeFeeInputProvider},
     genesis::{ensure_genesis_state, GenesisParams},
     l1_gas_price::L1GasPriceProvider,
     utils::testonly::{create_l2_transaction, create_miniblock},
@@ -35,6 +48,7 @@ mod ws;
 
 const TEST_TIMEOUT: Duration = Duration::from_secs(10);
 const POLL_INTERVAL: Duration = Duration::from_millis(50);
+const API_KEY_HEADER: &str = "x-api-key";
 
 /// Mock [`L1GasPriceProvider`] that returns a constant value.
 #[derive(Debug)]
@@ -97,6 +111,7 @@ pub(crate) async fn spawn_http_server(
         pool,
         stop_receiver,
         None,
+        |builder| builder,
     )
     .await
     .0
@@ -107,6 +122,7 @@ async fn spawn_ws_server(
     pool: ConnectionPool,
     stop_receiver: watch::Receiver<bool>,
     websocket_requests_per_minute_limit: Option<NonZeroU32>,
+    customize: impl FnOnce(ApiBuilder) -> ApiBuilder,
 ) -> (ApiServerHandles, mpsc::UnboundedReceiver<PubSubEvent>) {
     spawn_server(
         ApiTransportLabel::Ws,
@@ -114,6 +130,7 @@ async fn spawn_ws_server(
         pool,
         stop_receiver,
         websocket_requests_per_minute_limit,
+        customize,
     )
     .await
 }
@@ -124,6 +141,7 @@ async fn spawn_server(
     pool: ConnectionPool,
     stop_receiver: watch::Receiver<bool>,
     websocket_requests_per_minute_limit: Option<NonZeroU32>,
+    customize: impl FnOnce(ApiBuilder) -> ApiBuilder,
 ) -> (ApiServerHandles, mpsc::UnboundedReceiver<PubSubEvent>) {
     let contracts_config = ContractsConfig::for_tests();
     let web3_config = Web3JsonRpcConfig::for_tests();
@@ -134,20 +152,23 @@ async fn spawn_server(
 
     let storage_caches = PostgresStorageCaches::new(1, 1);
     let gas_adjuster = Arc::new(MockL1GasPriceProvider(1));
+    let fee_model_config = main_node_fee_model_config(&state_keeper_config).unwrap();
+    let batch_fee_input_provider = MainNodeFeeInputProvider::new(gas_adjuster, fee_model_config);
     let (tx_sender, vm_barrier) = crate::build_tx_sender(
         &tx_sender_config,
         &web3_config,
         &state_keeper_config,
         pool.clone(),
         pool.clone(),
-        gas_adjuster,
+        batch_fee_input_provider,
         storage_caches,
+        None,
     )
     .await;
     let (pub_sub_events_sender, pub_sub_events_receiver) = mpsc::unbounded_channel();
 
     let mut namespaces = Namespace::DEFAULT.to_vec();
-    namespaces.push(Namespace::Snapshots);
+    namespaces.extend([Namespace::Debug, Namespace::Snapshots]);
 
     let server_builder = match transport {
         ApiTransportLabel::Http => ApiBuilder::jsonrpsee_backend(api_config, pool).http(0),
@@ -162,10 +183,11 @@ async fn spawn_server(
             builder
         }
     };
-    let server_handles = server_builder
+    let server_handles = customize(server_builder)
         .with_polling_interval(POLL_INTERVAL)
         .with_tx_sender(tx_sender, vm_barrier)
         .with_pub_sub_events(pub_sub_events_sender)
+        .with_rate_limit_api_key_header(Some(API_KEY_HEADER.to_owned()))
         .enable_api_namespaces(namespaces)
         .build(stop_receiver)
         .await
@@ -178,9 +200,7 @@ trait HttpTest {
     async fn test(&self, client: &HttpClient, pool: &ConnectionPool) -> anyhow::Result<()>;
 }
 
-async fn test_http_server(test: impl HttpTest) {
-    let pool = ConnectionPool::test_pool().await;
-    let network_config = NetworkConfig::for_tests();
+async fn prepare_storage(pool: &ConnectionPool, network_config: &NetworkConfig) {
     let mut storage = pool.access_storage().await.unwrap();
     if storage.blocks_dal().is_genesis_needed().await.unwrap() {
         ensure_genesis_state(
@@ -191,7 +211,12 @@ async fn test_http_server(test: impl HttpTest) {
         .await
         .unwrap();
     }
-    drop(storage);
+}
+
+async fn test_http_server(test: impl HttpTest) {
+    let pool = ConnectionPool::test_pool().await;
+    let network_config = NetworkConfig::for_tests();
+    prepare_storage(&pool, &network_config).await;
 
     let (stop_sender, stop_receiver) = watch::channel(false);
     let server_handles = spawn_http_server(&network_config, pool.clone(), stop_receiver).await;
@@ -514,3 +539,719 @@ impl HttpTest for LogFilterChangesWithBlockBoundariesTest {
 async fn log_filter_changes_with_block_boundaries() {
     test_http_server(LogFilterChangesWithBlockBoundariesTest).await;
 }
+
+#[derive(Debug)]
+struct FeeHistoryTest;
+
+#[async_trait]
+impl HttpTest for FeeHistoryTest {
+    async fn test(&self, client: &HttpClient, pool: &ConnectionPool) -> anyhow::Result<()> {
+        let mut storage = pool.access_storage().await?;
+        store_miniblock(&mut storage).await?;
+        drop(storage);
+
+        let history = client
+            .fee_history(10.into(), api::BlockNumber::Latest, vec![10.0, 50.0, 90.0])
+            .await?;
+        assert_eq!(
+            history.oldest_block,
+            web3::types::BlockNumber::Number(0.into())
+        );
+        // Base fee is reported for the next miniblock as well.
+        assert_eq!(history.base_fee_per_gas.len(), 3);
+        assert_eq!(history.gas_used_ratio, [0.0, 0.0]);
+        // Miniblocks don't contain executed transactions, so all rewards are zero.
+        assert_eq!(history.reward, Some(vec![vec![U256::zero(); 3]; 2]));
+
+        let history = client
+            .fee_history(1.into(), api::BlockNumber::Latest, vec![])
+            .await?;
+        assert_eq!(
+            history.oldest_block,
+            web3::types::BlockNumber::Number(1.into())
+        );
+        assert_eq!(history.reward, None);
+
+        let err = client
+            .fee_history(1.into(), api::BlockNumber::Latest, vec![50.0, 10.0])
+            .await
+            .unwrap_err();
+        assert_matches!(err, RpcError::Call(err) if err.code() == ErrorCode::InvalidParams.code());
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn fee_history() {
+    test_http_server(FeeHistoryTest).await;
+}
+
+#[derive(Debug)]
+struct ConsensusCertificateTest;
+
+#[async_trait]
+impl HttpTest for ConsensusCertificateTest {
+    async fn test(&self, client: &HttpClient, pool: &ConnectionPool) -> anyhow::Result<()> {
+        let mut storage = pool.access_storage().await?;
+        let (miniblock, _) = store_miniblock(&mut storage).await?;
+        let block: validator::FinalBlock = rand::thread_rng().gen();
+        let consensus_fields = ConsensusBlockFields {
+            parent: block.header.parent,
+            justification: block.justification,
+        };
+        storage
+            .blocks_dal()
+            .set_miniblock_consensus_fields(miniblock.number, &consensus_fields)
+            .await?;
+        drop(storage);
+
+        let certificate = client
+            .get_consensus_block_certificate(miniblock.number)
+            .await?
+            .context("no certificate")?;
+        assert_eq!(certificate.number, miniblock.number);
+        let justification: validator::CommitQC =
+            zksync_protobuf::decode(&certificate.justification.0)?;
+        assert_eq!(justification, consensus_fields.justification);
+
+        let certificate = client
+            .get_consensus_block_certificate(MiniblockNumber(0))
+            .await?;
+        assert_eq!(certificate, None);
+        let certificate = client
+            .get_consensus_block_certificate(MiniblockNumber(2))
+            .await?;
+        assert_eq!(certificate, None);
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn consensus_certificates() {
+    test_http_server(ConsensusCertificateTest).await;
+}
+
+#[derive(Debug)]
+struct L1BatchPubdataTest;
+
+#[async_trait]
+impl HttpTest for L1BatchPubdataTest {
+    async fn test(&self, client: &HttpClient, pool: &ConnectionPool) -> anyhow::Result<()> {
+        let genesis_batch = pool
+            .access_storage()
+            .await?
+            .blocks_dal()
+            .get_l1_batch_metadata(L1BatchNumber(0))
+            .await?
+            .context("no genesis batch")?;
+
+        let pubdata = client
+            .get_l1_batch_pubdata(L1BatchNumber(0))
+            .await?
+            .context("no pubdata for genesis batch")?;
+        assert_eq!(pubdata.number, L1BatchNumber(0));
+        assert_eq!(pubdata.pubdata.0, genesis_batch.pubdata());
+        assert_eq!(pubdata.pubdata_hash, genesis_batch.pubdata_hash());
+        assert_eq!(pubdata.commit_tx_hash, None);
+        assert_eq!(pubdata.blobs, None);
+
+        let pubdata = client.get_l1_batch_pubdata(L1BatchNumber(1)).await?;
+        assert_eq!(pubdata, None);
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn getting_l1_batch_pubdata() {
+    test_http_server(L1BatchPubdataTest).await;
+}
+
+#[derive(Debug)]
+struct L1BatchCommitmentArtifactsTest;
+
+#[async_trait]
+impl HttpTest for L1BatchCommitmentArtifactsTest {
+    async fn test(&self, client: &HttpClient, pool: &ConnectionPool) -> anyhow::Result<()> {
+        let artifacts = client
+            .get_l1_batch_commitment_artifacts(L1BatchNumber(0))
+            .await?
+            .context("no artifacts for genesis batch")?;
+        assert_eq!(artifacts.commit, None);
+        assert_eq!(artifacts.prove, None);
+        assert_eq!(artifacts.execute, None);
+
+        let mut storage = pool.access_storage().await?;
+        let eth_tx = storage
+            .eth_sender_dal()
+            .save_eth_tx(
+                0,
+                vec![1, 2, 3],
+                AggregatedActionType::Commit,
+                Address::repeat_byte(1),
+                100_000,
+                None,
+                Address::repeat_byte(2),
+            )
+            .await?;
+        storage
+            .blocks_dal()
+            .set_eth_tx_id(
+                L1BatchNumber(0)..=L1BatchNumber(0),
+                eth_tx.id,
+                AggregatedActionType::Commit,
+            )
+            .await?;
+        let tx_hash = H256::repeat_byte(3);
+        storage
+            .eth_sender_dal()
+            .insert_tx_history(eth_tx.id, 10, 1, None, tx_hash, &[])
+            .await?;
+        storage
+            .eth_sender_dal()
+            .confirm_tx(tx_hash, U256::from(90_000))
+            .await?;
+        drop(storage);
+
+        let artifacts = client
+            .get_l1_batch_commitment_artifacts(L1BatchNumber(0))
+            .await?
+            .context("no artifacts for genesis batch")?;
+        let commit = artifacts.commit.context("no commit tx")?;
+        assert_eq!(commit.calldata.0, [1, 2, 3]);
+        assert_eq!(commit.predicted_gas_cost, 100_000);
+        assert_eq!(commit.tx_hash, Some(tx_hash));
+        assert_eq!(commit.gas_used, Some(90_000));
+        assert_eq!(commit.base_fee_per_gas, Some(10));
+        assert_eq!(commit.priority_fee_per_gas, Some(1));
+        assert!(commit.confirmed_at.is_some());
+        assert_eq!(artifacts.prove, None);
+        assert_eq!(artifacts.execute, None);
+
+        let artifacts = client
+            .get_l1_batch_commitment_artifacts(L1BatchNumber(1))
+            .await?;
+        assert_eq!(artifacts, None);
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn getting_l1_batch_commitment_artifacts() {
+    test_http_server(L1BatchCommitmentArtifactsTest).await;
+}
+
+#[derive(Debug)]
+struct StoredCallTracesTest;
+
+#[async_trait]
+impl HttpTest for StoredCallTracesTest {
+    async fn test(&self, client: &HttpClient, pool: &ConnectionPool) -> anyhow::Result<()> {
+        let mut storage = pool.access_storage().await?;
+        let tx = create_l2_transaction(1, 2);
+        let tx_hash = tx.hash();
+        storage
+            .transactions_dal()
+            .insert_transaction_l2(tx.clone(), TransactionExecutionMetrics::default())
+            .await;
+        let new_miniblock = create_miniblock(1);
+        storage
+            .blocks_dal()
+            .insert_miniblock(&new_miniblock)
+            .await?;
+        let inner_call = Call {
+            from: Address::repeat_byte(1),
+            to: Address::repeat_byte(2),
+            input: vec![1, 2, 3],
+            ..Call::default()
+        };
+        let execution_result = TransactionExecutionResult {
+            hash: tx_hash,
+            transaction: tx.into(),
+            execution_info: ExecutionMetrics::default(),
+            execution_status: TxExecutionStatus::Success,
+            refunded_gas: 0,
+            operator_suggested_refund: 0,
+            compressed_bytecodes: vec![],
+            call_traces: vec![inner_call],
+            revert_reason: None,
+        };
+        storage
+            .transactions_dal()
+            .mark_txs_as_executed_in_miniblock(MiniblockNumber(1), &[execution_result], 1.into())
+            .await;
+        drop(storage);
+
+        let traces = client
+            .trace_block_by_number(api::BlockNumber::Number(1.into()), None)
+            .await?;
+        assert_eq!(traces.len(), 1);
+        let api::DebugTrace::Call(call) = &traces[0].result else {
+            panic!("Unexpected trace: {traces:?}");
+        };
+        assert_eq!(call.calls.len(), 1);
+        assert_eq!(call.calls[0].from, Address::repeat_byte(1));
+        assert_eq!(call.calls[0].input.0, [1, 2, 3]);
+
+        let options = api::TracerConfig::CallTracer(api::CallTracerConfig {
+            only_top_call: true,
+        });
+        let trace = client
+            .trace_transaction(tx_hash, Some(options))
+            .await?
+            .context("no trace for transaction")?;
+        let api::DebugTrace::Call(call) = trace else {
+            panic!("Unexpected trace: {trace:?}");
+        };
+        assert!(call.calls.is_empty());
+
+        let trace = client.trace_transaction(H256::repeat_byte(1), None).await?;
+        assert!(trace.is_none(), "{trace:?}");
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn stored_call_traces() {
+    test_http_server(StoredCallTracesTest).await;
+}
+
+#[derive(Debug)]
+struct TraceCallWithHugeGasLimitTest;
+
+#[async_trait]
+impl HttpTest for TraceCallWithHugeGasLimitTest {
+    async fn test(&self, client: &HttpClient, _pool: &ConnectionPool) -> anyhow::Result<()> {
+        let request = CallRequest {
+            to: Some(Address::repeat_byte(1)),
+            gas: Some(u64::MAX.into()),
+            ..CallRequest::default()
+        };
+        let err = client.trace_call(request, None, None).await.unwrap_err();
+        assert_matches!(
+            err,
+            RpcError::Call(err) if err.code() == ErrorCode::InvalidParams.code()
+                && err.message().contains("gas limit")
+        );
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn tracing_call_with_huge_gas_limit() {
+    test_http_server(TraceCallWithHugeGasLimitTest).await;
+}
+
+#[derive(Debug)]
+struct PrunedBlocksTest;
+
+#[async_trait]
+impl HttpTest for PrunedBlocksTest {
+    async fn test(&self, client: &HttpClient, pool: &ConnectionPool) -> anyhow::Result<()> {
+        let mut storage = pool.access_storage().await?;
+        for number in 1..=2 {
+            storage
+                .blocks_dal()
+                .insert_miniblock(&create_miniblock(number))
+                .await?;
+        }
+        storage
+            .pruning_dal()
+            .prune_batches_range(L1BatchNumber(1), MiniblockNumber(1))
+            .await?;
+        drop(storage);
+
+        let address = Address::repeat_byte(1);
+        for pruned_block in [0_u32, 1] {
+            let block_id =
+                api::BlockIdVariant::BlockNumber(api::BlockNumber::Number(pruned_block.into()));
+            let errors = [
+                client
+                    .get_balance(address, Some(block_id))
+                    .await
+                    .unwrap_err(),
+                client.get_code(address, Some(block_id)).await.unwrap_err(),
+                client
+                    .get_transaction_count(address, Some(block_id))
+                    .await
+                    .unwrap_err(),
+                client
+                    .get_storage_at(address, U256::zero(), Some(block_id))
+                    .await
+                    .unwrap_err(),
+            ];
+            for err in errors {
+                assert_pruned_block_error(err);
+            }
+
+            let request = CallRequest {
+                to: Some(address),
+                ..CallRequest::default()
+            };
+            let err = client
+                .call(request, Some(block_id), None)
+                .await
+                .unwrap_err();
+            assert_pruned_block_error(err);
+        }
+
+        let block_id = api::BlockIdVariant::BlockNumber(api::BlockNumber::Number(2.into()));
+        client.get_balance(address, Some(block_id)).await?;
+        // The earliest block should resolve to the earliest non-pruned block.
+        let block_id = api::BlockIdVariant::BlockNumber(api::BlockNumber::Earliest);
+        client.get_balance(address, Some(block_id)).await?;
+        Ok(())
+    }
+}
+
+fn assert_pruned_block_error(err: RpcError) {
+    assert_matches!(
+        err,
+        RpcError::Call(err) if err.code() == ErrorCode::InvalidParams.code()
+            && err.message().contains("pruned")
+    );
+}
+
+#[tokio::test]
+async fn accessing_pruned_blocks() {
+    test_http_server(PrunedBlocksTest).await;
+}
+
+#[test]
+fn parsing_tracer_config() {
+    let config: api::TracerConfig = serde_json::from_str(r#"{ "tracer": "callTracer" }"#).unwrap();
+    assert_matches!(
+        config,
+        api::TracerConfig::CallTracer(api::CallTracerConfig {
+            only_top_call: false
+        })
+    );
+
+    let config: api::TracerConfig = serde_json::from_str(
+        r#"{ "tracer": "prestateTracer", "tracerConfig": { "diffMode": true } }"#,
+    )
+    .unwrap();
+    assert_matches!(
+        config,
+        api::TracerConfig::PrestateTracer(api::PrestateTracerConfig { diff_mode: true })
+    );
+
+    let err =
+        serde_json::from_str::<api::TracerConfig>(r#"{ "tracer": "4byteTracer" }"#).unwrap_err();
+    assert!(err.to_string().contains("unknown variant"), "{err}");
+}
+
+#[tokio::test]
+async fn rate_limiting_http_requests() {
+    let pool = ConnectionPool::test_pool().await;
+    let network_config = NetworkConfig::for_tests();
+    prepare_storage(&pool, &network_config).await;
+
+    let method_quotas = MethodQuotas {
+        send_tx: NonZeroU32::new(2),
+        ..MethodQuotas::default()
+    };
+    let (stop_sender, stop_receiver) = watch::channel(false);
+    let (server_handles, _) = spawn_server(
+        ApiTransportLabel::Http,
+        &network_config,
+        pool,
+        stop_receiver,
+        None,
+        |builder| builder.with_method_quotas(method_quotas),
+    )
+    .await;
+    server_handles.wait_until_ready().await;
+
+    let url = format!("http://{}/", server_handles.local_addr);
+    let client = reqwest::Client::new();
+    let send_request = |method: &str, api_key: &str| {
+        let params = if method == "eth_sendRawTransaction" {
+            serde_json::json!(["0x00"])
+        } else {
+            serde_json::json!([])
+        };
+        client
+            .post(&url)
+            .header(API_KEY_HEADER, api_key)
+            .json(&serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": method,
+                "params": params,
+            }))
+            .send()
+    };
+
+    for _ in 0..2 {
+        let response = send_request("eth_sendRawTransaction", "alice")
+            .await
+            .unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::OK);
+    }
+    let response = send_request("eth_sendRawTransaction", "alice")
+        .await
+        .unwrap();
+    assert_eq!(response.status(), reqwest::StatusCode::TOO_MANY_REQUESTS);
+    let response: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(response["error"]["code"], 429, "{response:?}");
+
+    // Other clients and other method groups must not be affected.
+    let response = send_request("eth_sendRawTransaction", "bob").await.unwrap();
+    assert_eq!(response.status(), reqwest::StatusCode::OK);
+    let response = send_request("eth_chainId", "alice").await.unwrap();
+    assert_eq!(response.status(), reqwest::StatusCode::OK);
+
+    stop_sender.send_replace(true);
+    server_handles.shutdown().await;
+}
+
+#[tokio::test]
+async fn executing_batch_requests_concurrently() {
+    let pool = ConnectionPool::test_pool().await;
+    let network_config = NetworkConfig::for_tests();
+    prepare_storage(&pool, &network_config).await;
+
+    let (stop_sender, stop_receiver) = watch::channel(false);
+    let (server_handles, _) = spawn_server(
+        ApiTransportLabel::Http,
+        &network_config,
+        pool,
+        stop_receiver,
+        None,
+        |builder| {
+            builder
+                .with_batch_request_size_limit(5)
+                .with_batch_request_concurrency(4)
+        },
+    )
+    .await;
+    server_handles.wait_until_ready().await;
+
+    let url = format!("http://{}/", server_handles.local_addr);
+    let client = reqwest::Client::new();
+    let calls: Vec<_> = (0..5)
+        .map(|id| {
+            // Every other call is invalid.
+            let method = if id % 2 == 0 {
+                "eth_chainId"
+            } else {
+                "eth_unknownMethod"
+            };
+            serde_json::json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": [] })
+        })
+        .collect();
+    let response = client.post(&url).json(&calls).send().await.unwrap();
+    assert_eq!(response.status(), reqwest::StatusCode::OK);
+    let responses: Vec<serde_json::Value> = response.json().await.unwrap();
+
+    assert_eq!(responses.len(), 5);
+    for (id, response) in responses.iter().enumerate() {
+        assert_eq!(response["id"], id, "{response:?}");
+        if id % 2 == 0 {
+            assert_eq!(
+                response["result"],
+                format!("{:#x}", network_config.zksync_network_id.as_u64()),
+                "{response:?}"
+            );
+        } else {
+            assert_eq!(
+                response["error"]["code"],
+                ErrorCode::MethodNotFound.code(),
+                "{response:?}"
+            );
+        }
+    }
+
+    // Batches exceeding the size limit should be rejected as a whole.
+    let calls: Vec<_> = (0..6)
+        .map(|id| serde_json::json!({ "jsonrpc": "2.0", "id": id, "method": "eth_chainId" }))
+        .collect();
+    let response = client.post(&url).json(&calls).send().await.unwrap();
+    let response: serde_json::Value = response.json().await.unwrap();
+    assert!(response.get("error").is_some(), "{response:?}");
+
+    stop_sender.send_replace(true);
+    server_handles.shutdown().await;
+}
+
+#[derive(Debug)]
+struct PaginatedLogsTest;
+
+#[async_trait]
+impl HttpTest for PaginatedLogsTest {
+    async fn test(&self, client: &HttpClient, pool: &ConnectionPool) -> anyhow::Result<()> {
+        let mut storage = pool.access_storage().await?;
+        let (_, first_events) = store_events(&mut storage, 1, 0).await?;
+        let (_, second_events) = store_events(&mut storage, 2, 4).await?;
+        drop(storage);
+
+        let filter = Filter {
+            from_block: Some(api::BlockNumber::Number(0.into())),
+            to_block: Some(api::BlockNumber::Number(2.into())),
+            address: Some(Address::repeat_byte(23).into()),
+            ..Filter::default()
+        };
+        let mut all_logs = vec![];
+        let mut cursor = None;
+        let mut page_count = 0;
+        loop {
+            let page = client
+                .get_logs_paginated(filter.clone(), cursor, Some(1))
+                .await?;
+            assert!(page.logs.len() <= 1, "{page:?}");
+            all_logs.extend(page.logs);
+            page_count += 1;
+            cursor = page.next_cursor;
+            if cursor.is_none() {
+                break;
+            }
+        }
+        assert_eq!(page_count, 4);
+        let expected_events = [
+            &first_events[0],
+            &first_events[3],
+            &second_events[0],
+            &second_events[3],
+        ];
+        assert_logs_match(&all_logs, &expected_events);
+
+        let page = client
+            .get_logs_paginated(filter.clone(), None, None)
+            .await?;
+        assert_eq!(page.logs, all_logs);
+        assert_eq!(page.next_cursor, None);
+
+        let err = client
+            .get_logs_paginated(filter, Some("0x123".to_owned()), None)
+            .await
+            .unwrap_err();
+        assert_matches!(err, RpcError::Call(err) if err.code() == ErrorCode::InvalidParams.code());
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn paginating_logs() {
+    test_http_server(PaginatedLogsTest).await;
+}
+
+struct NodeStatsTest;
+
+#[async_trait]
+impl HttpTest for NodeStatsTest {
+    async fn test(&self, client: &HttpClient, pool: &ConnectionPool) -> anyhow::Result<()> {
+        let started_at = Instant::now();
+        let stats = loop {
+            if let Some(stats) = client.get_node_stats().await? {
+                break stats;
+            }
+            assert!(
+                started_at.elapsed() <= TEST_TIMEOUT,
+                "timed out waiting for node stats"
+            );
+            tokio::time::sleep(POLL_INTERVAL).await;
+        };
+        assert_eq!(stats.sealed_miniblock, MiniblockNumber(0));
+        assert_eq!(stats.sealed_l1_batch, Some(L1BatchNumber(0)));
+        assert_eq!(stats.last_l1_batch_with_metadata, Some(L1BatchNumber(0)));
+        assert_eq!(stats.tree_lag, 0);
+        assert_eq!(stats.last_committed_l1_batch, None);
+        assert_eq!(stats.mempool_size, 0);
+        assert_eq!(stats.eth_sender_inflight_txs, 0);
+        assert_eq!(stats.sync_lag, None);
+
+        // Check that stats are updated by the collector task.
+        let mut storage = pool.access_storage().await?;
+        // The transaction stored along with the miniblock is not marked as executed, so it remains in the mempool.
+        store_miniblock(&mut storage).await?;
+        drop(storage);
+
+        let (collector, update_task) = NodeStatsCollector::new(pool.clone(), None, POLL_INTERVAL);
+        let update_task = tokio::spawn(update_task);
+        let started_at = Instant::now();
+        let stats = loop {
+            if let Some(stats) = collector.get() {
+                break stats;
+            }
+            assert!(
+                started_at.elapsed() <= TEST_TIMEOUT,
+                "timed out waiting for node stats"
+            );
+            tokio::time::sleep(POLL_INTERVAL).await;
+        };
+        assert_eq!(stats.sealed_miniblock, MiniblockNumber(1));
+        assert_eq!(stats.sealed_l1_batch, Some(L1BatchNumber(0)));
+        assert_eq!(stats.mempool_size, 1);
+
+        drop(collector);
+        tokio::time::timeout(TEST_TIMEOUT, update_task).await??;
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn getting_node_stats() {
+    test_http_server(NodeStatsTest).await;
+}
+
+#[tokio::test]
+async fn caching_immutable_responses() {
+    let pool = ConnectionPool::test_pool().await;
+    let network_config = NetworkConfig::for_tests();
+    prepare_storage(&pool, &network_config).await;
+    let mut storage = pool.access_storage().await.unwrap();
+    store_miniblock(&mut storage).await.unwrap();
+    drop(storage);
+
+    let cache = ResponseCache::new(1 << 20, Duration::from_secs(60));
+    let (stop_sender, stop_receiver) = watch::channel(false);
+    let (server_handles, _) = spawn_server(
+        ApiTransportLabel::Http,
+        &network_config,
+        pool,
+        stop_receiver,
+        None,
+        |builder| builder.with_response_cache(Some(cache.clone())),
+    )
+    .await;
+    server_handles.wait_until_ready().await;
+    let client = <HttpClient>::builder()
+        .build(format!("http://{}/", server_handles.local_addr))
+        .unwrap();
+
+    let chain_id = client.chain_id().await.unwrap();
+    assert!(cache.cached_methods().contains("eth_chainId"));
+    assert_eq!(client.chain_id().await.unwrap(), chain_id);
+
+    let block_number = api::BlockNumber::Number(1.into());
+    let block = client
+        .get_block_by_number(block_number, false)
+        .await
+        .unwrap()
+        .expect("no block");
+    // The block is not finalized yet, so it must not be cached.
+    assert!(!cache.cached_methods().contains("eth_getBlockByNumber"));
+    let latest_block = client
+        .get_block_by_number(api::BlockNumber::Latest, false)
+        .await
+        .unwrap();
+    assert!(latest_block.is_some());
+    assert!(!cache.cached_methods().contains("eth_getBlockByNumber"));
+
+    cache.set_finalized_miniblock(MiniblockNumber(1));
+    client
+        .get_block_by_number(block_number, false)
+        .await
+        .unwrap();
+    assert!(cache.cached_methods().contains("eth_getBlockByNumber"));
+    let cached_block = client
+        .get_block_by_number(block_number, false)
+        .await
+        .unwrap()
+        .expect("no block");
+    assert_eq!(cached_block.hash, block.hash);
+    assert_eq!(cached_block.number, block.number);
+
+    stop_sender.send_replace(true);
+    server_handles.shutdown().await;
+}
@@ -2,7 +2,7 @@
 
 use std::collections::HashSet;
 
-use zksync_types::block::BlockGasCount;
+use zksync_types::{block::BlockGasCount, snapshots::SnapshotManifest};
 use zksync_web3_decl::namespaces::SnapshotsNamespaceClient;
 
 use super::*;
@@ -56,14 +56,37 @@ impl HttpTest for SnapshotBasicsTest {
         seal_l1_batch(&mut storage, L1BatchNumber(1)).await?;
         storage
             .snapshots_dal()
-            .add_snapshot(L1BatchNumber(1), Self::CHUNK_COUNT, "file:///factory_deps")
+            .add_snapshot(
+                L1BatchNumber(1),
+                None,
+                Self::CHUNK_COUNT,
+                "file:///factory_deps",
+            )
             .await?;
 
         for &chunk_id in &self.chunk_ids {
             let path = format!("file:///storage_logs/chunk{chunk_id}");
             storage
                 .snapshots_dal()
-                .add_storage_logs_filepath_for_snapshot(L1BatchNumber(1), chunk_id, &path)
+                .add_storage_logs_filepath_for_snapshot(
+                    L1BatchNumber(1),
+                    chunk_id,
+                    &path,
+                    H256::from_low_u64_be(chunk_id),
+                )
+                .await?;
+        }
+        let expected_manifest = SnapshotManifest {
+            l1_batch_number: L1BatchNumber(1),
+            l1_batch_root_hash: create_l1_batch_metadata(1).root_hash,
+            base_manifest_hash: None,
+            storage_logs_chunk_hashes: (0..Self::CHUNK_COUNT).map(H256::from_low_u64_be).collect(),
+            signature: None,
+        };
+        if self.is_complete_snapshot() {
+            storage
+                .snapshots_dal()
+                .set_snapshot_manifest(L1BatchNumber(1), expected_manifest.hash(), None)
                 .await?;
         }
 
@@ -99,6 +122,7 @@ impl HttpTest for SnapshotBasicsTest {
             assert!(self.chunk_ids.contains(&chunk.chunk_id));
             assert!(chunk.filepath.starts_with("file:///storage_logs/"));
         }
+        assert_eq!(snapshot_header.manifest, Some(expected_manifest));
         Ok(())
     }
 }
@@ -1,12 +1,15 @@
 //! WS-related tests.
 
 use async_trait::async_trait;
+use futures::FutureExt;
 use jsonrpsee::core::{client::ClientT, params::BatchRequestBuilder, ClientError};
 use reqwest::StatusCode;
 use tokio::sync::watch;
 use zksync_config::configs::chain::NetworkConfig;
 use zksync_dal::ConnectionPool;
-use zksync_types::{api, Address, L1BatchNumber, H256, U64};
+use zksync_types::{
+    aggregated_operations::AggregatedActionType, api, Address, L1BatchNumber, H256, U64,
+};
 use zksync_web3_decl::{
     jsonrpsee::{
         core::client::{Subscription, SubscriptionClientT},
@@ -14,11 +17,14 @@ use zksync_web3_decl::{
         ws_client::{WsClient, WsClientBuilder},
     },
     namespaces::{EthNamespaceClient, ZksNamespaceClient},
-    types::{BlockHeader, PubSubFilter},
+    types::{BlockHeader, PubSubFilter, PubSubResult, ValueOrArray},
 };
 
 use super::*;
-use crate::api_server::web3::metrics::SubscriptionType;
+use crate::api_server::web3::{
+    metrics::SubscriptionType,
+    pubsub::{LogSubscriptions, LogsReceiver},
+};
 
 #[allow(clippy::needless_pass_by_ref_mut)] // false positive
 async fn wait_for_subscription(
@@ -76,6 +82,10 @@ trait WsTest {
     fn websocket_requests_per_minute_limit(&self) -> Option<NonZeroU32> {
         None
     }
+
+    fn customize_server(&self, builder: ApiBuilder) -> ApiBuilder {
+        builder
+    }
 }
 
 async fn test_ws_server(test: impl WsTest) {
@@ -99,6 +109,7 @@ async fn test_ws_server(test: impl WsTest) {
         pool.clone(),
         stop_receiver,
         test.websocket_requests_per_minute_limit(),
+        |builder| test.customize_server(builder),
     )
     .await;
     server_handles.wait_until_ready().await;
@@ -197,6 +208,78 @@ async fn basic_subscriptions() {
     test_ws_server(BasicSubscriptionsTest).await;
 }
 
+#[derive(Debug)]
+struct L1BatchStatusSubscriptionsTest;
+
+#[async_trait]
+impl WsTest for L1BatchStatusSubscriptionsTest {
+    async fn test(
+        &self,
+        client: &WsClient,
+        pool: &ConnectionPool,
+        mut pub_sub_events: mpsc::UnboundedReceiver<PubSubEvent>,
+    ) -> anyhow::Result<()> {
+        wait_for_notifier(&mut pub_sub_events, SubscriptionType::L1BatchStatuses).await;
+
+        let params = rpc_params!["l1BatchCommitted"];
+        let mut commit_subscription = client
+            .subscribe::<api::L1BatchStatusUpdate, _>("eth_subscribe", params, "eth_unsubscribe")
+            .await?;
+        wait_for_subscription(&mut pub_sub_events, SubscriptionType::L1BatchStatuses).await;
+        let params = rpc_params!["l1BatchExecuted"];
+        let mut execute_subscription = client
+            .subscribe::<api::L1BatchStatusUpdate, _>("eth_subscribe", params, "eth_unsubscribe")
+            .await?;
+        wait_for_subscription(&mut pub_sub_events, SubscriptionType::L1BatchStatuses).await;
+
+        let mut storage = pool.access_storage().await?;
+        let commit_tx_hash = H256::repeat_byte(1);
+        storage
+            .eth_sender_dal()
+            .insert_bogus_confirmed_eth_tx(
+                L1BatchNumber(0),
+                AggregatedActionType::Commit,
+                commit_tx_hash,
+                chrono::Utc::now(),
+            )
+            .await?;
+        let execute_tx_hash = H256::repeat_byte(2);
+        storage
+            .eth_sender_dal()
+            .insert_bogus_confirmed_eth_tx(
+                L1BatchNumber(0),
+                AggregatedActionType::Execute,
+                execute_tx_hash,
+                chrono::Utc::now(),
+            )
+            .await?;
+        drop(storage);
+
+        let update = tokio::time::timeout(TEST_TIMEOUT, commit_subscription.next())
+            .await
+            .context("Timed out waiting for commit update")?
+            .context("Commit subscription terminated")??;
+        assert_eq!(update.l1_batch_number, L1BatchNumber(0));
+        assert_eq!(update.stage, api::L1BatchStage::Committed);
+        assert_eq!(update.l1_tx_hash, commit_tx_hash);
+
+        // The execute subscription must not receive the commit update.
+        let update = tokio::time::timeout(TEST_TIMEOUT, execute_subscription.next())
+            .await
+            .context("Timed out waiting for execute update")?
+            .context("Execute subscription terminated")??;
+        assert_eq!(update.l1_batch_number, L1BatchNumber(0));
+        assert_eq!(update.stage, api::L1BatchStage::Executed);
+        assert_eq!(update.l1_tx_hash, execute_tx_hash);
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn l1_batch_status_subscriptions() {
+    test_ws_server(L1BatchStatusSubscriptionsTest).await;
+}
+
 #[derive(Debug)]
 struct LogSubscriptionsTest;
 
@@ -318,6 +401,87 @@ async fn log_subscriptions() {
     test_ws_server(LogSubscriptionsTest).await;
 }
 
+fn create_log(address: Address, topics: Vec<H256>) -> api::Log {
+    api::Log {
+        address,
+        topics,
+        data: Default::default(),
+        block_hash: None,
+        block_number: Some(1.into()),
+        l1_batch_number: None,
+        transaction_hash: None,
+        transaction_index: None,
+        log_index: None,
+        transaction_log_index: None,
+        log_type: None,
+        removed: None,
+    }
+}
+
+fn received_logs(receiver: &mut LogsReceiver) -> Vec<api::Log> {
+    let Some(Some(results)) = receiver.recv().now_or_never() else {
+        return vec![];
+    };
+    results
+        .into_iter()
+        .map(|result| match result {
+            PubSubResult::Log(log) => log,
+            other => panic!("unexpected notification: {other:?}"),
+        })
+        .collect()
+}
+
+#[test]
+fn dispatching_logs_via_subscriptions_index() {
+    let subscriptions = Arc::new(LogSubscriptions::default());
+    let mut all_logs_receiver = subscriptions.subscribe(PubSubFilter::default());
+    let mut address_receiver = subscriptions.subscribe(PubSubFilter {
+        address: Some(ValueOrArray(vec![
+            Address::repeat_byte(1),
+            Address::repeat_byte(2),
+        ])),
+        topics: Some(vec![None, Some(H256::repeat_byte(3).into())]),
+    });
+    let mut topic_receiver = subscriptions.subscribe(PubSubFilter {
+        address: None,
+        topics: Some(vec![None, Some(H256::repeat_byte(3).into())]),
+    });
+    let mut empty_receiver = subscriptions.subscribe(PubSubFilter {
+        address: Some(ValueOrArray(vec![])),
+        topics: None,
+    });
+    assert_eq!(subscriptions.len(), 4);
+
+    let logs = [
+        create_log(
+            Address::repeat_byte(1),
+            vec![H256::zero(), H256::repeat_byte(3)],
+        ),
+        create_log(Address::repeat_byte(2), vec![H256::repeat_byte(3)]),
+        create_log(
+            Address::repeat_byte(5),
+            vec![H256::zero(), H256::repeat_byte(3)],
+        ),
+        create_log(Address::repeat_byte(2), vec![]),
+    ];
+    let results: Vec<_> = logs.iter().cloned().map(PubSubResult::Log).collect();
+    subscriptions.dispatch(&results);
+
+    assert_eq!(received_logs(&mut all_logs_receiver), logs);
+    assert_eq!(received_logs(&mut address_receiver), [logs[0].clone()]);
+    assert_eq!(
+        received_logs(&mut topic_receiver),
+        [logs[0].clone(), logs[2].clone()]
+    );
+    assert!(received_logs(&mut empty_receiver).is_empty());
+
+    drop(topic_receiver);
+    assert_eq!(subscriptions.len(), 3);
+    subscriptions.dispatch(&results[2..]);
+    assert_eq!(received_logs(&mut all_logs_receiver), &logs[2..]);
+    assert!(received_logs(&mut address_receiver).is_empty());
+}
+
 #[derive(Debug)]
 struct LogSubscriptionsWithNewBlockTest;
 
@@ -556,3 +720,52 @@ impl WsTest for BatchGetsRateLimitedTest {
 async fn batch_rate_limiting() {
     test_ws_server(BatchGetsRateLimitedTest).await;
 }
+
+#[derive(Debug)]
+struct SubscriptionsPerConnectionLimitTest;
+
+#[async_trait]
+impl WsTest for SubscriptionsPerConnectionLimitTest {
+    async fn test(
+        &self,
+        client: &WsClient,
+        _pool: &ConnectionPool,
+        mut pub_sub_events: mpsc::UnboundedReceiver<PubSubEvent>,
+    ) -> anyhow::Result<()> {
+        wait_for_notifier(&mut pub_sub_events, SubscriptionType::Blocks).await;
+
+        let mut subscriptions = vec![];
+        for _ in 0..2 {
+            let subscription = client
+                .subscribe::<BlockHeader, _>(
+                    "eth_subscribe",
+                    rpc_params!["newHeads"],
+                    "eth_unsubscribe",
+                )
+                .await?;
+            wait_for_subscription(&mut pub_sub_events, SubscriptionType::Blocks).await;
+            subscriptions.push(subscription);
+        }
+
+        let err = client
+            .subscribe::<BlockHeader, _>(
+                "eth_subscribe",
+                rpc_params!["newHeads"],
+                "eth_unsubscribe",
+            )
+            .await
+            .unwrap_err();
+        assert_matches!(err, ClientError::Call(_));
+        drop(subscriptions);
+        Ok(())
+    }
+
+    fn customize_server(&self, builder: ApiBuilder) -> ApiBuilder {
+        builder.with_websocket_subscriptions_per_connection_limit(2)
+    }
+}
+
+#[tokio::test]
+async fn limiting_subscriptions_per_connection() {
+    test_ws_server(SubscriptionsPerConnectionLimitTest).await;
+}
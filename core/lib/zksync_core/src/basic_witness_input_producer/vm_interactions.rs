@@ -1,6 +1,6 @@
 use anyhow::{anyhow, Context};
 use multivm::{
-    interface::{VmInterface, VmInterfaceHistoryEnabled},
+    interface::{FinishedL1Batch, L2BlockEnv, VmInterface, VmInterfaceHistoryEnabled},
     vm_latest::HistoryEnabled,
     VmInstance,
 };
@@ -11,12 +11,12 @@ use zksync_types::{L1BatchNumber, L2ChainId, Transaction};
 
 use crate::state_keeper::io::common::load_l1_batch_params;
 
-pub(super) type VmAndStorage<'a> = (
+pub(crate) type VmAndStorage<'a> = (
     VmInstance<StorageView<PostgresStorage<'a>>, HistoryEnabled>,
     StoragePtr<StorageView<PostgresStorage<'a>>>,
 );
 
-pub(super) fn create_vm(
+pub(crate) fn create_vm(
     rt_handle: Handle,
     l1_batch_number: L1BatchNumber,
     mut connection: StorageProcessor<'_>,
@@ -66,7 +66,7 @@ pub(super) fn create_vm(
     Ok((vm, storage_view))
 }
 
-pub(super) fn execute_tx<S: WriteStorage>(
+pub(crate) fn execute_tx<S: WriteStorage>(
     tx: &Transaction,
     vm: &mut VmInstance<S, HistoryEnabled>,
 ) -> anyhow::Result<()> {
@@ -92,3 +92,42 @@ pub(super) fn execute_tx<S: WriteStorage>(
     }
     Ok(())
 }
+
+/// Re-executes all transactions of a sealed L1 batch on top of the Postgres state at the end of the previous batch.
+/// `connection` is used to load the batch transactions; `vm_connection` backs the VM storage.
+///
+/// Returns the finished batch together with the number of executed transactions.
+pub(crate) fn execute_l1_batch(
+    rt_handle: Handle,
+    l1_batch_number: L1BatchNumber,
+    connection: &mut StorageProcessor<'_>,
+    vm_connection: StorageProcessor<'_>,
+    l2_chain_id: L2ChainId,
+) -> anyhow::Result<(FinishedL1Batch, usize)> {
+    let miniblocks = rt_handle.block_on(
+        connection
+            .transactions_dal()
+            .get_miniblocks_to_execute_for_l1_batch(l1_batch_number),
+    )?;
+    let (mut vm, _) = create_vm(rt_handle, l1_batch_number, vm_connection, l2_chain_id)
+        .context("failed to create VM")?;
+
+    let mut executed_transactions = 0;
+    let next_miniblocks = miniblocks.iter().skip(1).map(Some).chain([None]);
+    for (miniblock, next_miniblock) in miniblocks.iter().zip(next_miniblocks) {
+        for tx in &miniblock.txs {
+            execute_tx(tx, &mut vm).with_context(|| {
+                format!(
+                    "failed executing transaction {:?} in miniblock #{}",
+                    tx.hash(),
+                    miniblock.number
+                )
+            })?;
+            executed_transactions += 1;
+        }
+        if let Some(next_miniblock) = next_miniblock {
+            vm.start_new_l2_block(L2BlockEnv::from_miniblock_data(next_miniblock));
+        }
+    }
+    Ok((vm.finish_batch(), executed_transactions))
+}
@@ -22,6 +22,10 @@ use zksync_types::{
     L1BatchNumber, PackedEthSignature, H160, H256, U256,
 };
 
+pub use self::replay::{replay_l1_batch, L1BatchReplayReport, StorageWriteMismatch};
+
+mod replay;
+
 bitflags! {
     pub struct BlockReverterFlags: u32 {
         const POSTGRES = 0b_0001;
@@ -266,6 +270,18 @@ impl BlockReverter {
             .storage_logs_dal()
             .rollback_storage_logs(last_miniblock_to_keep)
             .await;
+        tracing::info!("rolling back paymaster analytics...");
+        transaction
+            .paymaster_analytics_dal()
+            .delete_paymaster_analytics(last_l1_batch_to_keep)
+            .await
+            .unwrap();
+        tracing::info!("rolling back transaction event stream cursor...");
+        transaction
+            .tx_event_stream_dal()
+            .reset_cursor_after_revert(last_l1_batch_to_keep, last_miniblock_to_keep)
+            .await
+            .unwrap();
         tracing::info!("rolling back l1 batches...");
         transaction
             .blocks_dal()
@@ -0,0 +1,378 @@
+//! Deterministic re-execution of a sealed L1 batch.
+//!
+//! The replay loads the transactions of an L1 batch from Postgres, executes them in the VM on top of
+//! the state at the end of the previous batch and compares the produced artifacts with the ones
+//! persisted by the state keeper, including the state root hash obtained by applying the replayed storage writes
+//! to the Merkle tree. It's intended as a debugging tool for state divergence incidents.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::Path,
+};
+
+use anyhow::Context as _;
+use multivm::interface::FinishedL1Batch;
+use serde::Serialize;
+use tokio::runtime::Handle;
+use zksync_dal::{ConnectionPool, StorageProcessor};
+use zksync_merkle_tree::{domain::ZkSyncTree, TreeInstruction};
+use zksync_storage::RocksDB;
+use zksync_types::{
+    zkevm_test_harness::witness::sort_storage_access::sort_storage_access_queries, AccountTreeId,
+    Address, L1BatchNumber, L2ChainId, StorageKey, H256,
+};
+use zksync_utils::u256_to_h256;
+
+use crate::basic_witness_input_producer::vm_interactions::execute_l1_batch;
+
+/// Storage slot for which the replayed value differs from the one persisted in Postgres.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StorageWriteMismatch {
+    pub address: Address,
+    pub key: H256,
+    /// Value persisted by the state keeper; `None` if the slot wasn't written in the batch.
+    pub stored_value: Option<H256>,
+    /// Value produced by the replay; `None` if the replay didn't write to the slot.
+    pub replayed_value: Option<H256>,
+}
+
+/// Outcome of replaying an L1 batch.
+#[derive(Debug, Clone, Serialize)]
+pub struct L1BatchReplayReport {
+    pub l1_batch_number: L1BatchNumber,
+    pub executed_transactions: usize,
+    pub storage_write_mismatches: Vec<StorageWriteMismatch>,
+    pub events_match: bool,
+    pub l2_to_l1_logs_match: bool,
+    pub system_logs_match: bool,
+    pub used_contract_hashes_match: bool,
+    /// State root hash persisted for the batch.
+    pub stored_root_hash: H256,
+    /// State root hash obtained by applying replayed storage writes to the Merkle tree at the previous batch.
+    pub replayed_root_hash: H256,
+}
+
+impl L1BatchReplayReport {
+    /// Returns `true` if the replay reproduced all persisted artifacts of the batch.
+    pub fn is_consistent(&self) -> bool {
+        self.storage_write_mismatches.is_empty()
+            && self.events_match
+            && self.l2_to_l1_logs_match
+            && self.system_logs_match
+            && self.used_contract_hashes_match
+            && self.stored_root_hash == self.replayed_root_hash
+    }
+}
+
+/// Re-executes the specified L1 batch and compares the results with the data stored in Postgres.
+/// The batch must be sealed and have metadata, and the previous batch must be present in the storage.
+/// The Merkle tree at `merkle_tree_path` must contain the version for the previous batch; the tree is not modified
+/// by the replay, but it must not be used by other processes concurrently.
+pub async fn replay_l1_batch(
+    pool: &ConnectionPool,
+    merkle_tree_path: &Path,
+    l1_batch_number: L1BatchNumber,
+    l2_chain_id: L2ChainId,
+) -> anyhow::Result<L1BatchReplayReport> {
+    anyhow::ensure!(
+        l1_batch_number > L1BatchNumber(0),
+        "genesis L1 batch cannot be replayed"
+    );
+    anyhow::ensure!(
+        merkle_tree_path.exists(),
+        "Merkle tree not found at {}",
+        merkle_tree_path.display()
+    );
+    let pool = pool.clone();
+    let merkle_tree_path = merkle_tree_path.to_owned();
+    tokio::task::spawn_blocking(move || {
+        replay_l1_batch_blocking(
+            Handle::current(),
+            &pool,
+            &merkle_tree_path,
+            l1_batch_number,
+            l2_chain_id,
+        )
+    })
+    .await
+    .context("replay task panicked")?
+}
+
+fn replay_l1_batch_blocking(
+    rt_handle: Handle,
+    pool: &ConnectionPool,
+    merkle_tree_path: &Path,
+    l1_batch_number: L1BatchNumber,
+    l2_chain_id: L2ChainId,
+) -> anyhow::Result<L1BatchReplayReport> {
+    let mut storage = rt_handle
+        .block_on(pool.access_storage_tagged("block_reverter"))
+        .context("failed to get connection")?;
+    let header = rt_handle
+        .block_on(storage.blocks_dal().get_l1_batch_header(l1_batch_number))?
+        .with_context(|| format!("L1 batch #{l1_batch_number} is not present in the storage"))?;
+    anyhow::ensure!(
+        header.is_finished,
+        "L1 batch #{l1_batch_number} is not sealed yet"
+    );
+    let vm_connection = rt_handle
+        .block_on(pool.access_storage_tagged("block_reverter"))
+        .context("failed to get connection for VM")?;
+
+    tracing::info!("Replaying L1 batch #{l1_batch_number}");
+    let (finished_batch, executed_transactions) = execute_l1_batch(
+        rt_handle.clone(),
+        l1_batch_number,
+        &mut storage,
+        vm_connection,
+        l2_chain_id,
+    )?;
+    tracing::info!(
+        "Replayed {executed_transactions} transactions in L1 batch #{l1_batch_number}, comparing results"
+    );
+
+    let stored_writes = rt_handle.block_on(
+        storage
+            .storage_logs_dal()
+            .get_touched_slots_for_l1_batch(l1_batch_number),
+    );
+    let stored_events_queue = rt_handle
+        .block_on(storage.blocks_dal().get_events_queue(l1_batch_number))?
+        .unwrap_or_default();
+    let stored_root_hash = rt_handle
+        .block_on(
+            storage
+                .blocks_dal()
+                .get_l1_batch_state_root(l1_batch_number),
+        )?
+        .with_context(|| format!("L1 batch #{l1_batch_number} has no root hash"))?;
+
+    let replayed_writes = replayed_storage_writes(&finished_batch);
+    let tree_instructions = rt_handle.block_on(tree_instructions(
+        &mut storage,
+        l1_batch_number,
+        &replayed_writes,
+    ))?;
+    let replayed_root_hash =
+        compute_root_hash(merkle_tree_path, l1_batch_number, &tree_instructions)?;
+
+    let state = &finished_batch.final_execution_state;
+    Ok(L1BatchReplayReport {
+        l1_batch_number,
+        executed_transactions,
+        storage_write_mismatches: compare_storage_writes(&stored_writes, &replayed_writes),
+        events_match: state.deduplicated_events_logs == stored_events_queue,
+        l2_to_l1_logs_match: state.user_l2_to_l1_logs == header.l2_to_l1_logs,
+        system_logs_match: state.system_logs == header.system_logs,
+        used_contract_hashes_match: state.used_contract_hashes == header.used_contract_hashes,
+        stored_root_hash,
+        replayed_root_hash,
+    })
+}
+
+/// Converts replayed storage writes to Merkle tree instructions. Leaf indices of the keys are taken from
+/// the initial writes persisted in Postgres; keys not initially written by the batch or earlier batches
+/// get new indices following the ones assigned in the batch.
+async fn tree_instructions(
+    storage: &mut StorageProcessor<'_>,
+    l1_batch_number: L1BatchNumber,
+    replayed_writes: &HashMap<StorageKey, H256>,
+) -> anyhow::Result<Vec<TreeInstruction<StorageKey>>> {
+    let prev_l1_batch_number = l1_batch_number - 1;
+    let prev_metadata = storage
+        .blocks_dal()
+        .get_l1_batch_metadata(prev_l1_batch_number)
+        .await?
+        .with_context(|| format!("L1 batch #{prev_l1_batch_number} has no metadata"))?;
+    // Leaf indices start from 1; `rollup_last_leaf_index` is the next index to be assigned.
+    let mut next_leaf_index = prev_metadata.metadata.rollup_last_leaf_index;
+
+    let hashed_keys: Vec<_> = replayed_writes.keys().map(StorageKey::hashed_key).collect();
+    let initial_writes = storage
+        .storage_logs_dal()
+        .get_l1_batches_and_indices_for_initial_writes(&hashed_keys)
+        .await;
+    for &(initial_write_batch, leaf_index) in initial_writes.values() {
+        if initial_write_batch == l1_batch_number {
+            next_leaf_index = next_leaf_index.max(leaf_index + 1);
+        }
+    }
+
+    let mut instructions = BTreeMap::new();
+    for (&key, &value) in replayed_writes {
+        let leaf_index = match initial_writes.get(&key.hashed_key()) {
+            Some(&(initial_write_batch, leaf_index)) if initial_write_batch <= l1_batch_number => {
+                leaf_index
+            }
+            // Writing a zero value to a non-existing slot is a no-op.
+            _ if value.is_zero() => continue,
+            _ => {
+                next_leaf_index += 1;
+                next_leaf_index - 1
+            }
+        };
+        instructions.insert(key, TreeInstruction::write(key, leaf_index, value));
+    }
+    Ok(instructions.into_values().collect())
+}
+
+/// Computes the root hash after applying `instructions` to the Merkle tree at the end of the previous L1 batch.
+/// Changes to the tree are kept in memory and are never flushed to RocksDB.
+fn compute_root_hash(
+    merkle_tree_path: &Path,
+    l1_batch_number: L1BatchNumber,
+    instructions: &[TreeInstruction<StorageKey>],
+) -> anyhow::Result<H256> {
+    let mut tree = ZkSyncTree::new_lightweight(RocksDB::new(merkle_tree_path).into());
+    let next_tree_l1_batch = tree.next_l1_batch_number();
+    anyhow::ensure!(
+        next_tree_l1_batch >= l1_batch_number,
+        "Merkle tree is behind L1 batch #{l1_batch_number} (next L1 batch to process: #{next_tree_l1_batch})"
+    );
+    tree.revert_logs(l1_batch_number - 1);
+    Ok(tree.process_l1_batch(instructions).root_hash)
+}
+
+/// Returns deduplicated final storage writes produced by the batch, in the same form as they are persisted
+/// by the state keeper.
+fn replayed_storage_writes(finished_batch: &FinishedL1Batch) -> HashMap<StorageKey, H256> {
+    let (_, deduped_log_queries) = sort_storage_access_queries(
+        finished_batch
+            .final_execution_state
+            .storage_log_queries
+            .iter()
+            .map(|log| &log.log_query),
+    );
+    deduped_log_queries
+        .into_iter()
+        .filter(|log_query| log_query.rw_flag)
+        .map(|log_query| {
+            let key = StorageKey::new(
+                AccountTreeId::new(log_query.address),
+                u256_to_h256(log_query.key),
+            );
+            (key, u256_to_h256(log_query.written_value))
+        })
+        .collect()
+}
+
+fn compare_storage_writes(
+    stored: &HashMap<StorageKey, H256>,
+    replayed: &HashMap<StorageKey, H256>,
+) -> Vec<StorageWriteMismatch> {
+    let mut mismatches = BTreeMap::new();
+    for (key, &replayed_value) in replayed {
+        let stored_value = stored.get(key).copied();
+        if stored_value != Some(replayed_value) {
+            mismatches.insert(key.hashed_key(), (key, stored_value, Some(replayed_value)));
+        }
+    }
+    for (key, &stored_value) in stored {
+        if !replayed.contains_key(key) {
+            mismatches.insert(key.hashed_key(), (key, Some(stored_value), None));
+        }
+    }
+
+    mismatches
+        .into_values()
+        .map(|(key, stored_value, replayed_value)| StorageWriteMismatch {
+            address: *key.address(),
+            key: *key.key(),
+            stored_value,
+            replayed_value,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+
+    fn storage_key(address: u64, key: u64) -> StorageKey {
+        StorageKey::new(
+            AccountTreeId::new(Address::from_low_u64_be(address)),
+            H256::from_low_u64_be(key),
+        )
+    }
+
+    #[test]
+    fn comparing_storage_writes() {
+        let stored = HashMap::from([
+            (storage_key(1, 1), H256::repeat_byte(1)),
+            (storage_key(1, 2), H256::repeat_byte(2)),
+            (storage_key(2, 1), H256::repeat_byte(3)),
+        ]);
+        let mut replayed = stored.clone();
+        assert!(compare_storage_writes(&stored, &replayed).is_empty());
+
+        replayed.insert(storage_key(1, 2), H256::repeat_byte(0xff));
+        replayed.remove(&storage_key(2, 1));
+        replayed.insert(storage_key(3, 3), H256::repeat_byte(4));
+        let mut mismatches = compare_storage_writes(&stored, &replayed);
+        mismatches.sort_unstable_by_key(|mismatch| (mismatch.address, mismatch.key));
+
+        assert_eq!(
+            mismatches,
+            [
+                StorageWriteMismatch {
+                    address: Address::from_low_u64_be(1),
+                    key: H256::from_low_u64_be(2),
+                    stored_value: Some(H256::repeat_byte(2)),
+                    replayed_value: Some(H256::repeat_byte(0xff)),
+                },
+                StorageWriteMismatch {
+                    address: Address::from_low_u64_be(2),
+                    key: H256::from_low_u64_be(1),
+                    stored_value: Some(H256::repeat_byte(3)),
+                    replayed_value: None,
+                },
+                StorageWriteMismatch {
+                    address: Address::from_low_u64_be(3),
+                    key: H256::from_low_u64_be(3),
+                    stored_value: None,
+                    replayed_value: Some(H256::repeat_byte(4)),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn computing_root_hash_for_replayed_batch() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut tree = ZkSyncTree::new_lightweight(RocksDB::new(temp_dir.path()).into());
+        let genesis_logs = [TreeInstruction::write(
+            storage_key(1, 1),
+            1,
+            H256::repeat_byte(1),
+        )];
+        tree.process_l1_batch(&genesis_logs);
+        let batch_logs = [
+            TreeInstruction::write(storage_key(1, 1), 1, H256::repeat_byte(2)),
+            TreeInstruction::write(storage_key(2, 1), 2, H256::repeat_byte(3)),
+        ];
+        let expected_root_hash = tree.process_l1_batch(&batch_logs).root_hash;
+        tree.save();
+        drop(tree);
+
+        let root_hash = compute_root_hash(temp_dir.path(), L1BatchNumber(1), &batch_logs).unwrap();
+        assert_eq!(root_hash, expected_root_hash);
+
+        let diverging_logs = [TreeInstruction::write(
+            storage_key(1, 1),
+            1,
+            H256::repeat_byte(2),
+        )];
+        let root_hash =
+            compute_root_hash(temp_dir.path(), L1BatchNumber(1), &diverging_logs).unwrap();
+        assert_ne!(root_hash, expected_root_hash);
+
+        // The replay must not modify the tree.
+        let tree = ZkSyncTree::new_lightweight(RocksDB::new(temp_dir.path()).into());
+        assert_eq!(tree.next_l1_batch_number(), L1BatchNumber(2));
+        assert_eq!(tree.root_hash(), expected_root_hash);
+        let err = compute_root_hash(temp_dir.path(), L1BatchNumber(3), &batch_logs).unwrap_err();
+        assert!(err.to_string().contains("behind"), "{err}");
+    }
+}
@@ -0,0 +1,185 @@
+//! Registry of known chains used to validate the node configuration on startup.
+//!
+//! The registry is embedded into the node binary and can be extended with a remote registry. If the configured
+//! L2 chain ID is present in the registry, the node checks that the L1 chain ID, the diamond proxy address
+//! and the genesis root hash match the registered values, so that a misconfigured node fails fast instead
+//! of producing state diverging from the chain.
+
+use std::{collections::HashMap, time::Duration};
+
+use anyhow::Context as _;
+use serde::Deserialize;
+use zksync_types::{
+    web3::{transports::Http, Web3},
+    Address, L1ChainId, L2ChainId, H256,
+};
+
+#[cfg(test)]
+mod tests;
+
+const EMBEDDED_REGISTRY: &str = include_str!("registry.json");
+/// Timeout for fetching the remote registry.
+const FETCH_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Fetches the L1 chain ID reported by the L1 RPC at `l1_rpc_url` (i.e., `eth_chainId`).
+pub async fn fetch_l1_chain_id(l1_rpc_url: &str) -> anyhow::Result<L1ChainId> {
+    let transport = Http::new(l1_rpc_url).context("failed creating L1 RPC transport")?;
+    let chain_id = Web3::new(transport)
+        .eth()
+        .chain_id()
+        .await
+        .context("failed fetching L1 chain ID from the L1 RPC")?;
+    let chain_id = u64::try_from(chain_id)
+        .map_err(|err| anyhow::anyhow!("L1 chain ID {chain_id} is out of range: {err}"))?;
+    Ok(L1ChainId(chain_id))
+}
+
+/// Information about a chain in the registry.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ChainRegistryEntry {
+    /// Human-readable chain name.
+    pub name: String,
+    pub chain_id: u64,
+    pub l1_chain_id: u64,
+    pub diamond_proxy_addr: Address,
+    /// Root hash of the genesis L1 batch. If not specified, the genesis root hash is not checked.
+    #[serde(default)]
+    pub genesis_root_hash: Option<H256>,
+}
+
+/// Chain-related parameters of the node checked against the registry.
+#[derive(Debug, Clone, Copy)]
+pub struct NodeChainParams {
+    pub chain_id: L2ChainId,
+    pub l1_chain_id: L1ChainId,
+    pub diamond_proxy_addr: Address,
+    /// Root hash of the genesis L1 batch; `None` if the genesis was not performed yet.
+    pub genesis_root_hash: Option<H256>,
+}
+
+/// Registry of known chains.
+#[derive(Debug, Clone)]
+pub struct ChainRegistry {
+    entries: HashMap<u64, ChainRegistryEntry>,
+}
+
+impl ChainRegistry {
+    /// Returns the registry embedded into the node binary.
+    pub fn embedded() -> Self {
+        Self::from_json(EMBEDDED_REGISTRY).expect("embedded chain registry is invalid")
+    }
+
+    /// Parses a registry from JSON, which must be an array of [entries](ChainRegistryEntry).
+    pub fn from_json(json: &str) -> anyhow::Result<Self> {
+        let entries: Vec<ChainRegistryEntry> =
+            serde_json::from_str(json).context("failed parsing chain registry")?;
+        let mut entries_by_id = HashMap::with_capacity(entries.len());
+        for entry in entries {
+            let chain_id = entry.chain_id;
+            let prev_entry = entries_by_id.insert(chain_id, entry);
+            anyhow::ensure!(
+                prev_entry.is_none(),
+                "chain ID {chain_id} is registered more than once"
+            );
+        }
+        Ok(Self {
+            entries: entries_by_id,
+        })
+    }
+
+    /// Fetches a registry from the specified URL.
+    pub async fn fetch(url: &str) -> anyhow::Result<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(FETCH_TIMEOUT)
+            .build()
+            .context("failed building HTTP client")?;
+        let json = client
+            .get(url)
+            .send()
+            .await
+            .context("failed requesting chain registry")?
+            .error_for_status()
+            .context("chain registry server returned an error")?
+            .text()
+            .await
+            .context("failed reading chain registry")?;
+        Self::from_json(&json)
+    }
+
+    /// Merges `other` into this registry. Entries in `other` take precedence.
+    #[must_use]
+    pub fn merge(mut self, other: Self) -> Self {
+        self.entries.extend(other.entries);
+        self
+    }
+
+    /// Returns the registry entry for the specified chain.
+    pub fn get(&self, chain_id: L2ChainId) -> Option<&ChainRegistryEntry> {
+        self.entries.get(&chain_id.as_u64())
+    }
+
+    /// Checks the node parameters against the registry.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error listing all detected mismatches together with hints how to fix them.
+    pub fn validate(&self, params: &NodeChainParams) -> anyhow::Result<()> {
+        let chain_id = params.chain_id.as_u64();
+        let mut issues = vec![];
+
+        if let Some(entry) = self.get(params.chain_id) {
+            let name = &entry.name;
+            if entry.l1_chain_id != params.l1_chain_id.0 {
+                issues.push(format!(
+                    "L1 chain ID {} doesn't match L1 chain ID {} of chain `{name}` (ID {chain_id}); \
+                     check `ETH_CLIENT_CHAIN_ID` and the L1 RPC URL",
+                    params.l1_chain_id.0, entry.l1_chain_id
+                ));
+            }
+            if entry.diamond_proxy_addr != params.diamond_proxy_addr {
+                issues.push(format!(
+                    "diamond proxy address {:?} doesn't match address {:?} of chain `{name}` (ID {chain_id}); \
+                     check `CONTRACTS_DIAMOND_PROXY_ADDR`",
+                    params.diamond_proxy_addr, entry.diamond_proxy_addr
+                ));
+            }
+            if let (Some(expected), Some(actual)) =
+                (entry.genesis_root_hash, params.genesis_root_hash)
+            {
+                if expected != actual {
+                    issues.push(format!(
+                        "genesis root hash {actual:?} doesn't match genesis root hash {expected:?} of chain `{name}` \
+                         (ID {chain_id}); the database was likely initialized for another chain"
+                    ));
+                }
+            }
+        } else {
+            // The chain is not registered; check whether its L1 contract belongs to a registered chain,
+            // which would indicate a misconfigured chain ID.
+            let owner = self.entries.values().find(|entry| {
+                entry.diamond_proxy_addr == params.diamond_proxy_addr
+                    && entry.l1_chain_id == params.l1_chain_id.0
+            });
+            if let Some(owner) = owner {
+                issues.push(format!(
+                    "diamond proxy address {:?} belongs to chain `{}` (ID {}), but the node is configured \
+                     with chain ID {chain_id}; check `CHAIN_ETH_ZKSYNC_NETWORK_ID`",
+                    params.diamond_proxy_addr, owner.name, owner.chain_id
+                ));
+            } else {
+                tracing::info!(
+                    "Chain ID {chain_id} is not present in the chain registry; skipping chain config validation"
+                );
+            }
+        }
+
+        if issues.is_empty() {
+            Ok(())
+        } else {
+            anyhow::bail!(
+                "node configuration doesn't match the chain registry:\n- {}",
+                issues.join("\n- ")
+            )
+        }
+    }
+}
@@ -0,0 +1,100 @@
+//! Tests for the chain registry.
+
+use super::*;
+
+const MAINNET_DIAMOND_PROXY: &str = "0x32400084c286cf3e17e7b677ea9583e60a000324";
+
+fn mainnet_params() -> NodeChainParams {
+    NodeChainParams {
+        chain_id: L2ChainId::from(324),
+        l1_chain_id: L1ChainId(1),
+        diamond_proxy_addr: MAINNET_DIAMOND_PROXY.parse().unwrap(),
+        genesis_root_hash: None,
+    }
+}
+
+#[test]
+fn embedded_registry_is_valid() {
+    let registry = ChainRegistry::embedded();
+    let entry = registry.get(L2ChainId::from(324)).unwrap();
+    assert_eq!(entry.l1_chain_id, 1);
+    registry.validate(&mainnet_params()).unwrap();
+}
+
+#[test]
+fn unknown_chains_are_not_validated() {
+    let params = NodeChainParams {
+        chain_id: L2ChainId::from(270),
+        l1_chain_id: L1ChainId(9),
+        diamond_proxy_addr: Address::repeat_byte(1),
+        genesis_root_hash: Some(H256::repeat_byte(1)),
+    };
+    ChainRegistry::embedded().validate(&params).unwrap();
+}
+
+#[test]
+fn detecting_mismatched_params() {
+    let params = NodeChainParams {
+        l1_chain_id: L1ChainId(5),
+        diamond_proxy_addr: Address::repeat_byte(1),
+        ..mainnet_params()
+    };
+    let err = ChainRegistry::embedded()
+        .validate(&params)
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("ETH_CLIENT_CHAIN_ID"), "{err}");
+    assert!(err.contains("CONTRACTS_DIAMOND_PROXY_ADDR"), "{err}");
+}
+
+#[test]
+fn detecting_misconfigured_chain_id() {
+    let params = NodeChainParams {
+        chain_id: L2ChainId::from(270),
+        ..mainnet_params()
+    };
+    let err = ChainRegistry::embedded()
+        .validate(&params)
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("CHAIN_ETH_ZKSYNC_NETWORK_ID"), "{err}");
+}
+
+#[test]
+fn checking_genesis_root_hash_from_merged_registry() {
+    let remote_registry = ChainRegistry::from_json(&format!(
+        r#"[{{
+            "name": "zkSync Era Mainnet",
+            "chain_id": 324,
+            "l1_chain_id": 1,
+            "diamond_proxy_addr": "{MAINNET_DIAMOND_PROXY}",
+            "genesis_root_hash": "0x{}"
+        }}]"#,
+        hex::encode(H256::repeat_byte(0x11))
+    ))
+    .unwrap();
+    let registry = ChainRegistry::embedded().merge(remote_registry);
+
+    let params = NodeChainParams {
+        genesis_root_hash: Some(H256::repeat_byte(0x11)),
+        ..mainnet_params()
+    };
+    registry.validate(&params).unwrap();
+
+    let params = NodeChainParams {
+        genesis_root_hash: Some(H256::repeat_byte(0x22)),
+        ..mainnet_params()
+    };
+    let err = registry.validate(&params).unwrap_err().to_string();
+    assert!(err.contains("genesis root hash"), "{err}");
+}
+
+#[test]
+fn duplicate_chain_ids_are_rejected() {
+    let json = r#"[
+        { "name": "A", "chain_id": 1, "l1_chain_id": 1, "diamond_proxy_addr": "0x0000000000000000000000000000000000000001" },
+        { "name": "B", "chain_id": 1, "l1_chain_id": 1, "diamond_proxy_addr": "0x0000000000000000000000000000000000000002" }
+    ]"#;
+    let err = ChainRegistry::from_json(json).unwrap_err().to_string();
+    assert!(err.contains("more than once"), "{err}");
+}
@@ -2,7 +2,7 @@
 use std::sync::Arc;
 
 use anyhow::Context as _;
-use zksync_concurrency::{ctx, scope};
+use zksync_concurrency::{ctx, error::Wrap as _, scope};
 use zksync_consensus_executor::{ConsensusConfig, Executor, ExecutorConfig};
 use zksync_consensus_roles::{node, validator};
 use zksync_dal::ConnectionPool;
@@ -19,6 +19,9 @@ mod tests;
 
 pub(crate) use self::{payload::Payload, storage::sync_block_to_consensus_block};
 
+/// Default limit on the size of an encoded miniblock payload, in bytes.
+pub const DEFAULT_MAX_PAYLOAD_SIZE: usize = 2_500_000;
+
 #[derive(Debug)]
 pub struct Config {
     pub executor: ExecutorConfig,
@@ -26,6 +29,9 @@ pub struct Config {
     pub node_key: node::SecretKey,
     pub validator_key: validator::SecretKey,
     pub operator_address: Address,
+    /// Maximum size of an encoded miniblock payload, in bytes. The validator doesn't propose
+    /// and rejects larger payloads. If not set, [`DEFAULT_MAX_PAYLOAD_SIZE`] is used.
+    pub max_payload_size: Option<usize>,
 }
 
 impl Config {
@@ -36,12 +42,23 @@ impl Config {
                 == validator::ValidatorSet::new(vec![self.validator_key.public()]).unwrap(),
             "currently only consensus with just 1 validator is supported"
         );
+        // Check storage compatibility upfront, so that misconfiguration is reported with a clear error
+        // instead of surfacing as a failure when storing the genesis block.
+        storage::storage(ctx, &pool)
+            .await
+            .wrap("storage()")?
+            .check_compatibility(ctx, &self.executor.validators)
+            .await
+            .wrap("check_compatibility()")?;
+
+        let max_payload_size = self.max_payload_size.unwrap_or(DEFAULT_MAX_PAYLOAD_SIZE);
         let store = Arc::new(
             storage::SignedBlockStore::new(
                 ctx,
                 pool,
                 &self.executor.genesis_block,
                 self.operator_address,
+                max_payload_size,
             )
             .await?,
         );
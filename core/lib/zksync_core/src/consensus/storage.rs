@@ -1,7 +1,8 @@
 //! Storage implementation based on DAL.
-use std::ops;
+use std::{cmp, collections::VecDeque, ops, sync::Mutex};
 
 use anyhow::Context as _;
+use futures::FutureExt as _;
 use zksync_concurrency::{ctx, error::Wrap as _, sync, time};
 use zksync_consensus_bft::PayloadSource;
 use zksync_consensus_roles::validator;
@@ -37,6 +38,43 @@ pub(crate) fn sync_block_to_consensus_block(
     })
 }
 
+async fn put_block_in_transaction(
+    txn: &mut zksync_dal::StorageProcessor<'_>,
+    n: MiniblockNumber,
+    block: &validator::FinalBlock,
+    operator_address: Address,
+) -> anyhow::Result<()> {
+    // We require the block to be already stored in Postgres when we set the consensus field.
+    let sync_block = txn
+        .sync_dal()
+        .sync_block(n, operator_address, true)
+        .await
+        .context("sync_block()")?
+        .context("unknown block")?;
+    let want = &ConsensusBlockFields {
+        parent: block.header.parent,
+        justification: block.justification.clone(),
+    };
+
+    // If consensus field is already set, just validate the stored value but don't override it.
+    if sync_block.consensus.is_some() {
+        sync_block_to_consensus_block(sync_block).context("an invalid block found in storage")?;
+        return Ok(());
+    }
+
+    // Verify that the payload matches the storage.
+    let want_payload: consensus::Payload = sync_block.try_into()?;
+    if want_payload.encode() != block.payload {
+        let got_payload = consensus::Payload::decode(&block.payload)?;
+        anyhow::bail!("payload mismatch: got {got_payload:?}, want {want_payload:?}");
+    }
+
+    txn.blocks_dal()
+        .set_miniblock_consensus_fields(n, want)
+        .await
+        .context("set_miniblock_consensus_fields()")
+}
+
 /// Context-aware `zksync_dal::StorageProcessor` wrapper.
 pub(super) struct StorageProcessor<'a>(zksync_dal::StorageProcessor<'a>);
 
@@ -50,21 +88,6 @@ pub(super) async fn storage<'a>(
 }
 
 impl<'a> StorageProcessor<'a> {
-    pub async fn start_transaction<'b, 'c: 'b>(
-        &'c mut self,
-        ctx: &ctx::Ctx,
-    ) -> ctx::Result<StorageProcessor<'b>> {
-        Ok(StorageProcessor(
-            ctx.wait(self.0.start_transaction())
-                .await?
-                .context("sqlx")?,
-        ))
-    }
-
-    pub async fn commit(self, ctx: &ctx::Ctx) -> ctx::Result<()> {
-        Ok(ctx.wait(self.0.commit()).await?.context("sqlx")?)
-    }
-
     async fn fetch_sync_block(
         &mut self,
         ctx: &ctx::Ctx,
@@ -78,6 +101,7 @@ impl<'a> StorageProcessor<'a> {
             .context("sync_block()")?)
     }
 
+    #[cfg(test)]
     pub async fn fetch_block(
         &mut self,
         ctx: &ctx::Ctx,
@@ -95,6 +119,62 @@ impl<'a> StorageProcessor<'a> {
         ))
     }
 
+    /// Fetches the contiguous run of blocks with consensus fields starting from `numbers.start`.
+    pub async fn fetch_blocks_range(
+        &mut self,
+        ctx: &ctx::Ctx,
+        numbers: ops::Range<validator::BlockNumber>,
+        operator_address: Address,
+    ) -> ctx::Result<Vec<validator::FinalBlock>> {
+        let start = MiniblockNumber(numbers.start.0.try_into().context("MiniblockNumber")?);
+        let end = MiniblockNumber(numbers.end.0.try_into().context("MiniblockNumber")?);
+        let certificates = ctx
+            .wait(self.0.consensus_dal().certificates_range(start..end))
+            .await?
+            .context("certificates_range()")?;
+        let end = start + certificates.len() as u32;
+        let payloads = ctx
+            .wait(
+                self.0
+                    .consensus_dal()
+                    .payloads_range(start..end, operator_address),
+            )
+            .await?
+            .context("payloads_range()")?;
+        if payloads.len() != certificates.len() {
+            return Err(anyhow::anyhow!(
+                "mismatch between the number of certificates ({}) and payloads ({})",
+                certificates.len(),
+                payloads.len()
+            )
+            .into());
+        }
+
+        let blocks = certificates
+            .into_iter()
+            .zip(payloads)
+            .map(|(certificate, block)| {
+                let number = validator::BlockNumber(block.number.0.into());
+                let payload: consensus::Payload = block.try_into()?;
+                let payload = payload.encode();
+                anyhow::ensure!(
+                    payload.hash() == certificate.justification.message.proposal.payload,
+                    "payload hash mismatch for block #{}",
+                    number.0
+                );
+                Ok(validator::FinalBlock {
+                    header: validator::BlockHeader {
+                        parent: certificate.parent,
+                        number,
+                        payload: payload.hash(),
+                    },
+                    payload,
+                    justification: certificate.justification,
+                })
+            });
+        Ok(blocks.collect::<anyhow::Result<_>>()?)
+    }
+
     pub async fn fetch_payload(
         &mut self,
         ctx: &ctx::Ctx,
@@ -124,42 +204,16 @@ impl<'a> StorageProcessor<'a> {
                 .try_into()
                 .context("MiniblockNumber")?,
         );
-        let mut txn = self
-            .start_transaction(ctx)
-            .await
-            .wrap("start_transaction()")?;
-
-        // We require the block to be already stored in Postgres when we set the consensus field.
-        let sync_block = txn
-            .fetch_sync_block(ctx, block.header.number, operator_address)
-            .await?
-            .context("unknown block")?;
-        let want = &ConsensusBlockFields {
-            parent: block.header.parent,
-            justification: block.justification.clone(),
-        };
-
-        // If consensus field is already set, just validate the stored value but don't override it.
-        if sync_block.consensus.is_some() {
-            sync_block_to_consensus_block(sync_block)
-                .context("an invalid block found in storage")?;
-            return Ok(());
-        }
-
-        // Verify that the payload matches the storage.
-        let want_payload: consensus::Payload = sync_block.try_into()?;
-        if want_payload.encode() != block.payload {
-            let got_payload = consensus::Payload::decode(&block.payload)?;
-            return Err(anyhow::anyhow!(
-                "payload mismatch: got {got_payload:?}, want {want_payload:?}"
-            )
-            .into());
-        }
-
-        ctx.wait(txn.0.blocks_dal().set_miniblock_consensus_fields(n, want))
-            .await?
-            .context("set_miniblock_consensus_fields()")?;
-        txn.commit(ctx).await.wrap("commit()")?;
+        // The transaction may be retried on serialization failures, so it only reads data from the DB
+        // and performs the write.
+        ctx.wait(
+            self.0
+                .with_retries("consensus_put_block", block, move |txn, block| {
+                    put_block_in_transaction(txn, n, block, operator_address).boxed()
+                }),
+        )
+        .await?
+        .context("put_block_in_transaction()")?;
         Ok(())
     }
 
@@ -179,6 +233,57 @@ impl<'a> StorageProcessor<'a> {
         Ok(validator::BlockNumber(head.0.into()))
     }
 
+    /// Checks that the storage can be used by consensus with the specified validator set: the DB schema
+    /// must include all consensus migrations, and the latest stored certificate must be signed
+    /// by `validators`.
+    pub async fn check_compatibility(
+        &mut self,
+        ctx: &ctx::Ctx,
+        validators: &validator::ValidatorSet,
+    ) -> ctx::Result<()> {
+        let is_schema_compatible = ctx
+            .wait(self.0.consensus_dal().is_schema_compatible())
+            .await?
+            .context("is_schema_compatible()")?;
+        if !is_schema_compatible {
+            return Err(anyhow::anyhow!(
+                "Postgres schema lacks migration {} required by consensus; run DB migrations before starting consensus",
+                zksync_dal::consensus_dal::ConsensusDal::REQUIRED_MIGRATION_VERSION
+            )
+            .into());
+        }
+
+        let Some(head) = ctx
+            .wait(
+                self.0
+                    .blocks_dal()
+                    .get_last_miniblock_number_with_consensus_fields(),
+            )
+            .await?
+            .context("get_last_miniblock_number_with_consensus_fields()")?
+        else {
+            return Ok(()); // No certificates are stored yet
+        };
+        let certificates = ctx
+            .wait(self.0.consensus_dal().certificates_range(head..head + 1))
+            .await?
+            .context("certificates_range()")?;
+        let certificate = certificates
+            .into_iter()
+            .next()
+            .with_context(|| format!("certificate for miniblock #{head} disappeared"))?;
+        certificate
+            .justification
+            .verify(validators, quorum_threshold(validators))
+            .with_context(|| {
+                format!(
+                    "certificate for miniblock #{head} stored in Postgres is not signed by the configured \
+                     validator set; check that consensus config matches the chain the database belongs to"
+                )
+            })?;
+        Ok(())
+    }
+
     pub async fn find_head_forward(
         &mut self,
         ctx: &ctx::Ctx,
@@ -196,6 +301,63 @@ impl<'a> StorageProcessor<'a> {
     }
 }
 
+/// Returns the minimum number of validator signatures required for a commit certificate.
+fn quorum_threshold(validators: &validator::ValidatorSet) -> usize {
+    let faulty_replicas = (validators.len() - 1) / 3;
+    validators.len() - faulty_replicas
+}
+
+/// Blocks fetched from Postgres ahead of being requested by gossip peers.
+///
+/// Peers catching up with the network request blocks one by one in ascending order, so when
+/// a block is not in the cache, [`SignedBlockStore`] fetches it together with the following
+/// blocks using range DB queries. Each fetched run of blocks forms a window that serves the sequential
+/// stream of requests from a single peer. [`BlockStore`] requests don't identify the requesting peer,
+/// so windows are keyed by the block range rather than the peer ID. Serving a block doesn't remove it,
+/// so several peers at the same height can share a window, and windows are evicted as a whole
+/// in the least recently used order, so that prefetching for one peer doesn't evict blocks
+/// buffered for other active peers.
+#[derive(Debug)]
+pub(super) struct PrefetchedBlocks {
+    depth: u64,
+    /// Windows of contiguous blocks, from the most recently used to the least recently used one.
+    windows: Mutex<VecDeque<Vec<validator::FinalBlock>>>,
+}
+
+impl PrefetchedBlocks {
+    /// Max number of windows retained in the cache, i.e., the number of peers served concurrently.
+    const MAX_WINDOWS: usize = 4;
+
+    pub(super) fn new(depth: u64) -> Self {
+        Self {
+            depth,
+            windows: Mutex::default(),
+        }
+    }
+
+    pub(super) fn get(&self, number: validator::BlockNumber) -> Option<validator::FinalBlock> {
+        let mut windows = self.windows.lock().unwrap();
+        let (window_idx, block) = windows.iter().enumerate().find_map(|(idx, window)| {
+            let first_number = window.first()?.header.number.0;
+            let offset = number.0.checked_sub(first_number)?;
+            Some((idx, window.get(offset as usize)?.clone()))
+        })?;
+        let window = windows.remove(window_idx).unwrap();
+        windows.push_front(window);
+        Some(block)
+    }
+
+    /// Inserts a window of contiguous blocks.
+    pub(super) fn insert(&self, window: Vec<validator::FinalBlock>) {
+        if window.is_empty() {
+            return;
+        }
+        let mut windows = self.windows.lock().unwrap();
+        windows.push_front(window);
+        windows.truncate(Self::MAX_WINDOWS);
+    }
+}
+
 /// Postgres-based [`BlockStore`] implementation, which
 /// considers blocks as stored <=> they have consensus field set.
 #[derive(Debug)]
@@ -204,15 +366,21 @@ pub(super) struct SignedBlockStore {
     head: sync::watch::Sender<validator::BlockNumber>,
     pool: ConnectionPool,
     operator_address: Address,
+    max_payload_size: usize,
+    prefetched: PrefetchedBlocks,
 }
 
 impl SignedBlockStore {
+    /// Default number of blocks fetched concurrently when serving a block that is not cached.
+    const DEFAULT_PREFETCH_DEPTH: u64 = 8;
+
     /// Creates a new storage handle. `pool` should have multiple connections to work efficiently.
     pub async fn new(
         ctx: &ctx::Ctx,
         pool: ConnectionPool,
         genesis: &validator::FinalBlock,
         operator_address: Address,
+        max_payload_size: usize,
     ) -> anyhow::Result<Self> {
         // Ensure that genesis block has consensus field set in Postgres.
         let head = {
@@ -235,8 +403,25 @@ impl SignedBlockStore {
             head: sync::watch::channel(head).0,
             pool,
             operator_address,
+            max_payload_size,
+            prefetched: PrefetchedBlocks::new(Self::DEFAULT_PREFETCH_DEPTH),
         })
     }
+
+    /// Fetches the contiguous run of blocks with consensus fields in the `[first, last]` range
+    /// using a constant number of DB queries.
+    async fn fetch_blocks(
+        &self,
+        ctx: &ctx::Ctx,
+        first: validator::BlockNumber,
+        last: validator::BlockNumber,
+    ) -> ctx::Result<Vec<validator::FinalBlock>> {
+        let storage = &mut storage(ctx, &self.pool).await.wrap("storage()")?;
+        storage
+            .fetch_blocks_range(ctx, first..last.next(), self.operator_address)
+            .await
+            .wrap("fetch_blocks_range()")
+    }
 }
 
 #[async_trait::async_trait]
@@ -248,6 +433,15 @@ impl WriteBlockStore for SignedBlockStore {
         block_number: validator::BlockNumber,
         payload: &validator::Payload,
     ) -> ctx::Result<()> {
+        // Check the size before decoding, so that oversized payloads are cheap to reject.
+        if payload.0.len() > self.max_payload_size {
+            return Err(anyhow::anyhow!(
+                "payload for block {block_number} is too large: {} bytes, while the limit is {} bytes",
+                payload.0.len(),
+                self.max_payload_size
+            )
+            .into());
+        }
         let storage = &mut storage(ctx, &self.pool).await.wrap("storage()")?;
         let want = storage
             .fetch_payload(ctx, block_number, self.operator_address)
@@ -320,11 +514,25 @@ impl BlockStore for SignedBlockStore {
         ctx: &ctx::Ctx,
         number: validator::BlockNumber,
     ) -> ctx::Result<Option<validator::FinalBlock>> {
-        let storage = &mut storage(ctx, &self.pool).await.wrap("storage()")?;
-        storage
-            .fetch_block(ctx, number, self.operator_address)
+        if let Some(block) = self.prefetched.get(number) {
+            return Ok(Some(block));
+        }
+
+        // Only blocks up to the known head have consensus fields, so there's no point
+        // prefetching past it. `head` may be slightly outdated, which is fine.
+        let head = *self.head.borrow();
+        let last = cmp::max(
+            number.0,
+            cmp::min(head.0, number.0 + self.prefetched.depth - 1),
+        );
+        let blocks = self
+            .fetch_blocks(ctx, number, validator::BlockNumber(last))
             .await
-            .wrap("fetch_block()")
+            .wrap("fetch_blocks()")?;
+        // The returned blocks start from `number`, so the first block (if any) is the requested one.
+        let requested_block = blocks.first().cloned();
+        self.prefetched.insert(blocks);
+        Ok(requested_block)
     }
 
     async fn missing_block_numbers(
@@ -375,6 +583,7 @@ impl PayloadSource for SignedBlockStore {
         block_number: validator::BlockNumber,
     ) -> ctx::Result<validator::Payload> {
         const POLL_INTERVAL: time::Duration = time::Duration::milliseconds(50);
+        let mut oversized_payload_reported = false;
         loop {
             let storage = &mut storage(ctx, &self.pool).await.wrap("storage()")?;
             if let Some(payload) = storage
@@ -382,7 +591,21 @@ impl PayloadSource for SignedBlockStore {
                 .await
                 .wrap("fetch_payload()")?
             {
-                return Ok(payload.encode());
+                let encoded_payload = payload.encode();
+                if encoded_payload.0.len() <= self.max_payload_size {
+                    return Ok(encoded_payload);
+                }
+                // Proposing the payload would only get it rejected by other validators, and erroring
+                // would stop the validator altogether. Instead, we keep the node running and wait
+                // for the view to time out.
+                if !oversized_payload_reported {
+                    tracing::error!(
+                        "Payload for block {block_number} is too large to propose: {} bytes, while the limit is {} bytes",
+                        encoded_payload.0.len(),
+                        self.max_payload_size
+                    );
+                    oversized_payload_reported = true;
+                }
             }
             ctx.sleep(POLL_INTERVAL).await?;
         }
@@ -5,8 +5,8 @@ use zksync_consensus_roles::validator;
 use zksync_contracts::{BaseSystemContractsHashes, SystemContractCode};
 use zksync_dal::ConnectionPool;
 use zksync_types::{
-    api, block::MiniblockHasher, Address, L1BatchNumber, L2ChainId, MiniblockNumber,
-    ProtocolVersionId, H256,
+    api, block::MiniblockHasher, Address, L1BatchNumber, L2ChainId, MiniblockNumber, PriorityOpId,
+    ProtocolVersionId, Transaction, H256,
 };
 
 use crate::{
@@ -19,7 +19,10 @@ use crate::{
         sync_action::{ActionQueue, ActionQueueSender, SyncAction},
         ExternalIO, MainNodeClient, SyncState,
     },
-    utils::testonly::{create_l1_batch_metadata, create_l2_transaction},
+    utils::testonly::{
+        create_l1_batch_metadata, create_l1_transaction, create_l2_transaction,
+        create_upgrade_transaction,
+    },
 };
 
 #[derive(Debug, Default)]
@@ -69,6 +72,7 @@ impl MockMainNodeClient {
                 timestamp: number.into(),
                 l1_gas_price: 2,
                 l2_fair_gas_price: 3,
+                fair_pubdata_price: Some(24),
                 base_system_contracts_hashes: BaseSystemContractsHashes::default(),
                 operator_address: Address::repeat_byte(2),
                 transactions: Some(transactions),
@@ -138,6 +142,7 @@ pub(crate) struct StateKeeperHandle {
     next_batch: L1BatchNumber,
     next_block: MiniblockNumber,
     next_timestamp: u64,
+    next_priority_op: PriorityOpId,
     batch_sealed: bool,
 
     fee_per_gas: u64,
@@ -160,6 +165,7 @@ impl StateKeeperHandle {
                 next_batch: L1BatchNumber(1),
                 next_block: MiniblockNumber(1),
                 next_timestamp: 124356,
+                next_priority_op: PriorityOpId(0),
                 batch_sealed: true,
                 fee_per_gas: 10,
                 gas_per_pubdata: 100,
@@ -180,6 +186,7 @@ impl StateKeeperHandle {
                 timestamp: self.next_timestamp,
                 l1_gas_price: 2,
                 l2_fair_gas_price: 3,
+                fair_pubdata_price: Some(24),
                 operator_address: self.operator_address,
                 protocol_version: ProtocolVersionId::latest(),
                 first_miniblock_info: (self.next_block, 1),
@@ -201,17 +208,58 @@ impl StateKeeperHandle {
         }
     }
 
-    pub async fn push_block(&mut self, transactions: usize) {
-        assert!(transactions > 0);
+    pub fn l2_transaction(&self) -> Transaction {
+        create_l2_transaction(self.fee_per_gas, self.gas_per_pubdata).into()
+    }
+
+    pub fn l1_transaction(&mut self) -> Transaction {
+        let tx = create_l1_transaction(self.next_priority_op, self.next_batch.0.into());
+        self.next_priority_op += 1;
+        tx.into()
+    }
+
+    pub fn upgrade_transaction(&self) -> Transaction {
+        create_upgrade_transaction(ProtocolVersionId::latest(), self.next_batch.0.into()).into()
+    }
+
+    pub async fn push_block_with_transactions(&mut self, transactions: Vec<Transaction>) {
+        assert!(!transactions.is_empty());
         let mut actions = vec![self.open_block()];
-        for _ in 0..transactions {
-            let tx = create_l2_transaction(self.fee_per_gas, self.gas_per_pubdata);
-            actions.push(SyncAction::Tx(Box::new(tx.into())));
-        }
+        actions.extend(
+            transactions
+                .into_iter()
+                .map(|tx| SyncAction::Tx(Box::new(tx))),
+        );
         actions.push(SyncAction::SealMiniblock(None));
         self.actions_sender.push_actions(actions).await;
     }
 
+    /// Pushes a block with the specified number of L2 transactions.
+    pub async fn push_block(&mut self, transactions: usize) {
+        let transactions = (0..transactions).map(|_| self.l2_transaction()).collect();
+        self.push_block_with_transactions(transactions).await;
+    }
+
+    /// Pushes a block with a random mix of L2 and priority (L1 -> L2) transactions. If the block opens
+    /// a new L1 batch, it may start with a protocol upgrade transaction, as is the case on the main node.
+    pub async fn push_random_block(&mut self, rng: &mut impl Rng) {
+        let mut transactions = vec![];
+        // 10% chance for the first block in a batch to contain an upgrade transaction.
+        if self.batch_sealed && rng.gen_range(0..100) < 10 {
+            transactions.push(self.upgrade_transaction());
+        }
+        for _ in 0..rng.gen_range(3..8) {
+            // 20% chance for a transaction to be a priority operation.
+            let tx = if rng.gen_range(0..100) < 20 {
+                self.l1_transaction()
+            } else {
+                self.l2_transaction()
+            };
+            transactions.push(tx);
+        }
+        self.push_block_with_transactions(transactions).await;
+    }
+
     pub async fn seal_batch(&mut self) {
         // Each batch ends with an empty block (aka fictive block).
         let mut actions = vec![self.open_block()];
@@ -230,7 +278,7 @@ impl StateKeeperHandle {
             if rng.gen_range(0..100) < 20 {
                 self.seal_batch().await;
             } else {
-                self.push_block(rng.gen_range(3..8)).await;
+                self.push_random_block(rng).await;
             }
         }
     }
@@ -1,8 +1,9 @@
+use rand::Rng;
 use zksync_concurrency::{ctx, scope};
 use zksync_consensus_executor::testonly::FullValidatorConfig;
 use zksync_consensus_roles::validator;
-use zksync_dal::ConnectionPool;
-use zksync_types::Address;
+use zksync_dal::{blocks_dal::ConsensusBlockFields, ConnectionPool};
+use zksync_types::{Address, ExecuteTransactionCommon, MiniblockNumber};
 
 use super::*;
 
@@ -51,6 +52,7 @@ async fn test_backfill() {
             node_key: cfg.node_key,
             validator_key: cfg.validator_key,
             operator_address: OPERATOR_ADDRESS,
+            max_payload_size: None,
         };
         s.spawn_bg(cfg.run(ctx, pool.clone()));
         sk.sync_consensus(ctx, &pool)
@@ -74,8 +76,169 @@ async fn test_backfill() {
         sk.validate_consensus(ctx, &pool, GENESIS_BLOCK, &validators)
             .await
             .context("sk.validate_consensus()")?;
+        storage::storage(ctx, &pool)
+            .await
+            .context("storage()")?
+            .check_compatibility(ctx, &validators)
+            .await
+            .context("check_compatibility()")?;
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_payloads_with_all_transaction_types() {
+    const OPERATOR_ADDRESS: Address = Address::repeat_byte(17);
+
+    zksync_concurrency::testonly::abort_on_panic();
+    let ctx = &ctx::test_root(&ctx::RealClock);
+    let rng = &mut ctx.rng();
+    let pool = ConnectionPool::test_pool().await;
+
+    scope::run!(ctx, |ctx, s| async {
+        let (mut sk, sk_runner) = testonly::StateKeeperHandle::new(OPERATOR_ADDRESS);
+        s.spawn_bg(sk_runner.run(ctx, &pool));
+
+        sk.push_random_blocks(rng, 10).await;
+        // Make sure that every transaction type is present regardless of randomness.
+        sk.seal_batch().await;
+        let transactions = vec![
+            sk.upgrade_transaction(),
+            sk.l1_transaction(),
+            sk.l2_transaction(),
+        ];
+        sk.push_block_with_transactions(transactions).await;
+        sk.sync(ctx, &pool).await.context("sk.sync()")?;
+
+        let mut storage = storage::storage(ctx, &pool).await.context("storage()")?;
+        let (mut l1_tx_count, mut upgrade_tx_count) = (0, 0);
+        for i in 1.. {
+            let Some(payload) = storage
+                .fetch_payload(ctx, validator::BlockNumber(i), OPERATOR_ADDRESS)
+                .await
+                .with_context(|| format!("fetch_payload({i})"))?
+            else {
+                break;
+            };
+            for tx in &payload.transactions {
+                match tx.common_data {
+                    ExecuteTransactionCommon::L1(_) => l1_tx_count += 1,
+                    ExecuteTransactionCommon::ProtocolUpgrade(_) => upgrade_tx_count += 1,
+                    ExecuteTransactionCommon::L2(_) => {}
+                }
+            }
+            let decoded = Payload::decode(&payload.encode()).context("Payload::decode()")?;
+            assert_eq!(decoded, payload);
+        }
+        assert!(l1_tx_count > 0);
+        assert!(upgrade_tx_count > 0);
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn incompatible_validator_set_is_rejected() {
+    const OPERATOR_ADDRESS: Address = Address::repeat_byte(17);
+
+    zksync_concurrency::testonly::abort_on_panic();
+    let ctx = &ctx::test_root(&ctx::RealClock);
+    let rng = &mut ctx.rng();
+    let pool = ConnectionPool::test_pool().await;
+
+    scope::run!(ctx, |ctx, s| async {
+        let (mut sk, sk_runner) = testonly::StateKeeperHandle::new(OPERATOR_ADDRESS);
+        s.spawn_bg(sk_runner.run(ctx, &pool));
+        sk.push_random_blocks(rng, 3).await;
+        sk.sync(ctx, &pool).await.context("sk.sync()")?;
+
+        let validator_key: validator::SecretKey = rng.gen();
+        let validators = validator::ValidatorSet::new([validator_key.public()]).unwrap();
+        let mut storage = storage::storage(ctx, &pool).await.context("storage()")?;
+        // No certificates are stored yet, so any validator set is compatible.
+        storage
+            .check_compatibility(ctx, &validators)
+            .await
+            .context("check_compatibility()")?;
+
+        // Store a certificate signed by another validator.
+        let block = rng.gen::<validator::FinalBlock>();
+        let certificate = ConsensusBlockFields {
+            parent: block.header.parent,
+            justification: block.justification,
+        };
+        pool.access_storage()
+            .await?
+            .blocks_dal()
+            .set_miniblock_consensus_fields(MiniblockNumber(1), &certificate)
+            .await?;
+
+        let Err(ctx::Error::Internal(err)) = storage.check_compatibility(ctx, &validators).await
+        else {
+            panic!("incompatible validator set was not detected");
+        };
+        let err = format!("{err:#}");
+        assert!(err.contains("validator set"), "{err}");
         Ok(())
     })
     .await
     .unwrap();
 }
+
+fn prefetch_window(
+    rng: &mut impl Rng,
+    numbers: std::ops::Range<u64>,
+) -> Vec<validator::FinalBlock> {
+    numbers
+        .map(|number| {
+            let mut block = rng.gen::<validator::FinalBlock>();
+            block.header.number = validator::BlockNumber(number);
+            block
+        })
+        .collect()
+}
+
+#[test]
+fn prefetched_blocks_are_shared_among_peers() {
+    let ctx = &ctx::test_root(&ctx::RealClock);
+    let rng = &mut ctx.rng();
+    let prefetched = storage::PrefetchedBlocks::new(4);
+    prefetched.insert(prefetch_window(rng, 10..14));
+
+    // Serving a block to one peer doesn't remove it for other peers.
+    for _ in 0..2 {
+        for number in 10..14 {
+            let block = prefetched.get(validator::BlockNumber(number)).unwrap();
+            assert_eq!(block.header.number, validator::BlockNumber(number));
+        }
+    }
+    assert!(prefetched.get(validator::BlockNumber(9)).is_none());
+    assert!(prefetched.get(validator::BlockNumber(14)).is_none());
+}
+
+#[test]
+fn prefetching_for_peer_does_not_evict_blocks_of_other_peers() {
+    let ctx = &ctx::test_root(&ctx::RealClock);
+    let rng = &mut ctx.rng();
+    let prefetched = storage::PrefetchedBlocks::new(4);
+    // Slow peer catching up from block 0.
+    prefetched.insert(prefetch_window(rng, 0..4));
+    // Other peers catching up from later blocks.
+    for start in [100, 200, 300] {
+        prefetched.insert(prefetch_window(rng, start..start + 4));
+        // The slow peer requests the next block after each prefetch for other peers.
+        let number = validator::BlockNumber(start / 100);
+        assert!(prefetched.get(number).is_some(), "{number:?}");
+    }
+
+    // The next window evicts the least recently used one, which is for the peer at block 100.
+    prefetched.insert(prefetch_window(rng, 400..404));
+    assert!(prefetched.get(validator::BlockNumber(3)).is_some());
+    assert!(prefetched.get(validator::BlockNumber(101)).is_none());
+    for number in [201, 301, 401] {
+        assert!(prefetched.get(validator::BlockNumber(number)).is_some());
+    }
+}
@@ -5,7 +5,11 @@ use tokio::sync::watch;
 use zksync_contracts::PRE_BOOJUM_COMMIT_FUNCTION;
 use zksync_dal::{ConnectionPool, StorageProcessor};
 use zksync_eth_client::{clients::QueryClient, Error as L1ClientError, EthInterface};
-use zksync_types::{web3::ethabi, L1BatchNumber, H256};
+use zksync_types::{
+    commitment::{L1BatchCommitmentMode, L1BatchWithMetadata},
+    web3::ethabi,
+    L1BatchNumber, H256,
+};
 
 use crate::{
     metrics::{CheckerComponent, EN_METRICS},
@@ -56,7 +60,11 @@ enum L1DataMismatchBehavior {
 #[derive(Debug)]
 struct LocalL1BatchCommitData {
     is_pre_boojum: bool,
+    commitment_mode: L1BatchCommitmentMode,
+    /// Encoded without the data availability inclusion data, which may be unknown locally.
     l1_commit_data: ethabi::Token,
+    /// Data availability inclusion data; only known on the main node in the validium mode.
+    da_inclusion_data: Option<Vec<u8>>,
     commit_tx_hash: H256,
 }
 
@@ -66,6 +74,7 @@ impl LocalL1BatchCommitData {
     async fn new(
         storage: &mut StorageProcessor<'_>,
         batch_number: L1BatchNumber,
+        commitment_mode: L1BatchCommitmentMode,
     ) -> anyhow::Result<Option<Self>> {
         let Some(storage_l1_batch) = storage
             .blocks_dal()
@@ -111,12 +120,53 @@ impl LocalL1BatchCommitData {
             return Ok(None);
         }
 
+        let da_inclusion_data = if commitment_mode == L1BatchCommitmentMode::Validium {
+            storage
+                .data_availability_dal()
+                .get_inclusion_data(batch_number)
+                .await?
+        } else {
+            None
+        };
+
         Ok(Some(Self {
             is_pre_boojum,
-            l1_commit_data: l1_batch.l1_commit_data(),
+            commitment_mode,
+            l1_commit_data: l1_batch
+                .l1_commit_data_for_mode(commitment_mode)
+                .context("failed encoding L1 commit data")?,
+            da_inclusion_data,
             commit_tx_hash,
         }))
     }
+
+    /// Checks whether the commitment extracted from the L1 commit transaction matches local data. In the validium mode,
+    /// `totalL2ToL1Pubdata` contains the pubdata hash followed by the data availability inclusion data; the latter
+    /// is only compared if it's known locally.
+    fn matches(&self, mut l1_commitment: ethabi::Token) -> bool {
+        if self.commitment_mode == L1BatchCommitmentMode::Validium && !self.is_pre_boojum {
+            let ethabi::Token::Tuple(fields) = &mut l1_commitment else {
+                return false;
+            };
+            let Some(ethabi::Token::Bytes(total_l2_to_l1_pubdata)) = fields.last_mut() else {
+                return false;
+            };
+            let Some((pubdata_hash, inclusion_data)) =
+                L1BatchWithMetadata::split_validium_pubdata_commitment(total_l2_to_l1_pubdata)
+            else {
+                return false;
+            };
+            let inclusion_data_matches = self
+                .da_inclusion_data
+                .as_deref()
+                .map_or(true, |local_data| local_data == inclusion_data);
+            if !inclusion_data_matches {
+                return false;
+            }
+            *total_l2_to_l1_pubdata = pubdata_hash.to_vec();
+        }
+        l1_commitment == self.l1_commit_data
+    }
 }
 
 #[derive(Debug)]
@@ -129,6 +179,7 @@ pub struct ConsistencyChecker {
     l1_client: Box<dyn EthInterface>,
     l1_batch_updater: Box<dyn UpdateCheckedBatch>,
     l1_data_mismatch_behavior: L1DataMismatchBehavior,
+    commitment_mode: L1BatchCommitmentMode,
     pool: ConnectionPool,
 }
 
@@ -144,10 +195,17 @@ impl ConsistencyChecker {
             l1_client: Box::new(web3),
             l1_batch_updater: Box::new(()),
             l1_data_mismatch_behavior: L1DataMismatchBehavior::Log,
+            commitment_mode: L1BatchCommitmentMode::Rollup,
             pool,
         }
     }
 
+    /// Sets the mode in which L1 batches are committed on L1. Defaults to the rollup mode.
+    pub fn with_commitment_mode(mut self, commitment_mode: L1BatchCommitmentMode) -> Self {
+        self.commitment_mode = commitment_mode;
+        self
+    }
+
     async fn check_commitments(
         &self,
         batch_number: L1BatchNumber,
@@ -187,7 +245,7 @@ impl ConsistencyChecker {
                 .with_context(|| {
                     format!("Failed extracting commit data for transaction {commit_tx_hash:?}")
                 })?;
-        Ok(commitment == local.l1_commit_data)
+        Ok(local.matches(commitment))
     }
 
     fn extract_commit_data(
@@ -285,7 +343,10 @@ impl ConsistencyChecker {
             // The batch might be already committed but not yet processed by the external node's tree
             // OR the batch might be processed by the external node's tree but not yet committed.
             // We need both.
-            let Some(local) = LocalL1BatchCommitData::new(&mut storage, batch_number).await? else {
+            let Some(local) =
+                LocalL1BatchCommitData::new(&mut storage, batch_number, self.commitment_mode)
+                    .await?
+            else {
                 tokio::time::sleep(self.sleep_interval).await;
                 continue;
             };
@@ -25,6 +25,7 @@ fn create_l1_batch_with_metadata(number: u32) -> L1BatchWithMetadata {
         header: create_l1_batch(number),
         metadata: create_l1_batch_metadata(number),
         factory_deps: vec![],
+        da_inclusion_data: None,
     }
 }
 
@@ -35,6 +36,7 @@ fn create_pre_boojum_l1_batch_with_metadata(number: u32) -> L1BatchWithMetadata
         header: create_l1_batch(number),
         metadata: create_l1_batch_metadata(number),
         factory_deps: vec![],
+        da_inclusion_data: None,
     };
     l1_batch.header.protocol_version = Some(PRE_BOOJUM_PROTOCOL_VERSION);
     l1_batch.metadata.bootloader_initial_content_commitment = None;
@@ -65,6 +67,7 @@ fn create_mock_checker(client: MockEthereum, pool: ConnectionPool) -> Consistenc
         l1_client: Box::new(client),
         l1_batch_updater: Box::new(()),
         l1_data_mismatch_behavior: L1DataMismatchBehavior::Bail,
+        commitment_mode: L1BatchCommitmentMode::Rollup,
         pool,
     }
 }
@@ -97,6 +100,42 @@ fn build_commit_tx_input_data_is_correct() {
     }
 }
 
+#[test]
+fn validium_commitment_is_matched_without_inclusion_data() {
+    let mut l1_batch = create_l1_batch_with_metadata(1);
+    l1_batch.header.protocol_version = Some(ProtocolVersionId::Version20);
+    let local = LocalL1BatchCommitData {
+        is_pre_boojum: false,
+        commitment_mode: L1BatchCommitmentMode::Validium,
+        l1_commit_data: l1_batch
+            .l1_commit_data_for_mode(L1BatchCommitmentMode::Validium)
+            .unwrap(),
+        da_inclusion_data: None,
+        commit_tx_hash: H256::zero(),
+    };
+
+    let l1_commitment_with_inclusion_data = |inclusion_data: Vec<u8>| {
+        let l1_batch = L1BatchWithMetadata {
+            da_inclusion_data: Some(inclusion_data),
+            ..l1_batch.clone()
+        };
+        l1_batch
+            .l1_commit_data_for_mode(L1BatchCommitmentMode::Validium)
+            .unwrap()
+    };
+    assert!(local.matches(local.l1_commit_data.clone()));
+    assert!(local.matches(l1_commitment_with_inclusion_data(vec![1; 64])));
+    // Rollup commitments contain the full pubdata instead of its hash.
+    assert!(!local.matches(l1_batch.l1_commit_data()));
+
+    let local = LocalL1BatchCommitData {
+        da_inclusion_data: Some(vec![1; 64]),
+        ..local
+    };
+    assert!(local.matches(l1_commitment_with_inclusion_data(vec![1; 64])));
+    assert!(!local.matches(l1_commitment_with_inclusion_data(vec![2; 64])));
+}
+
 #[test]
 fn extracting_commit_data_for_boojum_batch() {
     let contract = zksync_contracts::zksync_contract();
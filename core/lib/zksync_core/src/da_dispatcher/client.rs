@@ -0,0 +1,108 @@
+//! Clients for external data availability layers.
+
+use std::{fmt, time::Duration};
+
+use anyhow::Context as _;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use zksync_types::{Bytes, L1BatchNumber};
+
+/// Publisher of L1 batch pubdata to a data availability layer.
+#[async_trait]
+pub trait DataAvailabilityPublisher: 'static + fmt::Debug + Send + Sync {
+    /// Publishes pubdata of the specified L1 batch. Returns the identifier of the created blob,
+    /// which can be used to retrieve the inclusion proof.
+    async fn publish_blob(
+        &self,
+        l1_batch_number: L1BatchNumber,
+        pubdata: Vec<u8>,
+    ) -> anyhow::Result<String>;
+
+    /// Returns the proof of inclusion of the specified blob, or `None` if the blob is not included yet.
+    async fn get_inclusion_data(&self, blob_id: &str) -> anyhow::Result<Option<Vec<u8>>>;
+}
+
+#[derive(Debug, Serialize)]
+struct PublishBlobRequest {
+    l1_batch_number: L1BatchNumber,
+    data: Bytes,
+}
+
+#[derive(Debug, Deserialize)]
+struct PublishBlobResponse {
+    blob_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct InclusionDataResponse {
+    inclusion_data: Bytes,
+}
+
+/// [`DataAvailabilityPublisher`] implementation for a data availability layer exposing an HTTP API:
+///
+/// - `POST /blobs` publishes a blob and returns its ID.
+/// - `GET /blobs/{blob_id}/inclusion_data` returns the blob inclusion proof, or 404 if the blob is not included yet.
+#[derive(Debug)]
+pub struct HttpDataAvailabilityClient {
+    client: reqwest::Client,
+    api_url: String,
+}
+
+impl HttpDataAvailabilityClient {
+    pub fn new(api_url: &str, request_timeout: Duration) -> anyhow::Result<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(request_timeout)
+            .build()
+            .context("failed building HTTP client")?;
+        Ok(Self {
+            client,
+            api_url: api_url.trim_end_matches('/').to_owned(),
+        })
+    }
+}
+
+#[async_trait]
+impl DataAvailabilityPublisher for HttpDataAvailabilityClient {
+    async fn publish_blob(
+        &self,
+        l1_batch_number: L1BatchNumber,
+        pubdata: Vec<u8>,
+    ) -> anyhow::Result<String> {
+        let request = PublishBlobRequest {
+            l1_batch_number,
+            data: Bytes(pubdata),
+        };
+        let response: PublishBlobResponse = self
+            .client
+            .post(format!("{}/blobs", self.api_url))
+            .json(&request)
+            .send()
+            .await
+            .context("failed sending blob to data availability layer")?
+            .error_for_status()
+            .context("data availability layer rejected blob")?
+            .json()
+            .await
+            .context("failed parsing data availability layer response")?;
+        Ok(response.blob_id)
+    }
+
+    async fn get_inclusion_data(&self, blob_id: &str) -> anyhow::Result<Option<Vec<u8>>> {
+        let response = self
+            .client
+            .get(format!("{}/blobs/{blob_id}/inclusion_data", self.api_url))
+            .send()
+            .await
+            .context("failed requesting inclusion data from data availability layer")?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        let response: InclusionDataResponse = response
+            .error_for_status()
+            .context("data availability layer returned error for inclusion data")?
+            .json()
+            .await
+            .context("failed parsing data availability layer response")?;
+        Ok(Some(response.inclusion_data.0))
+    }
+}
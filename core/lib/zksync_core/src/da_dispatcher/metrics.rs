@@ -0,0 +1,23 @@
+//! Metrics for the data availability dispatcher.
+
+use std::time::Duration;
+
+use vise::{Buckets, Gauge, Histogram, Metrics};
+
+#[derive(Debug, Metrics)]
+#[metrics(prefix = "server_da_dispatcher")]
+pub(super) struct DataAvailabilityDispatcherMetrics {
+    /// Latency of publishing a single blob to the data availability layer.
+    #[metrics(buckets = Buckets::LATENCIES)]
+    pub blob_dispatch_latency: Histogram<Duration>,
+    /// Size of the dispatched blobs in bytes.
+    #[metrics(buckets = Buckets::exponential(1_024.0..=16.0 * 1_024.0 * 1_024.0, 4.0))]
+    pub blob_size: Histogram<usize>,
+    /// Number of the last L1 batch dispatched to the data availability layer.
+    pub last_dispatched_l1_batch: Gauge<u64>,
+    /// Number of the last L1 batch with the inclusion proof received from the data availability layer.
+    pub last_included_l1_batch: Gauge<u64>,
+}
+
+#[vise::register]
+pub(super) static METRICS: vise::Global<DataAvailabilityDispatcherMetrics> = vise::Global::new();
@@ -0,0 +1,156 @@
+//! Dispatcher of L1 batch pubdata to an external data availability layer.
+
+use std::sync::Arc;
+
+use anyhow::Context as _;
+use tokio::sync::watch;
+use zksync_config::configs::DADispatcherConfig;
+use zksync_dal::ConnectionPool;
+
+pub use self::client::{DataAvailabilityPublisher, HttpDataAvailabilityClient};
+use self::metrics::METRICS;
+
+mod client;
+mod metrics;
+#[cfg(test)]
+mod tests;
+
+/// Periodically publishes pubdata of sealed L1 batches to a data availability layer and polls the layer
+/// for inclusion proofs of the published blobs. Inclusion proofs are persisted in Postgres, so that they can
+/// be committed on L1 by `eth_sender` together with the L1 batches.
+///
+/// Blobs are dispatched and their inclusion is awaited in the order of L1 batch numbers.
+#[derive(Debug)]
+pub struct DataAvailabilityDispatcher {
+    pool: ConnectionPool,
+    config: DADispatcherConfig,
+    publisher: Arc<dyn DataAvailabilityPublisher>,
+}
+
+impl DataAvailabilityDispatcher {
+    pub fn new(
+        pool: ConnectionPool,
+        config: DADispatcherConfig,
+        publisher: Arc<dyn DataAvailabilityPublisher>,
+    ) -> Self {
+        Self {
+            pool,
+            config,
+            publisher,
+        }
+    }
+
+    /// Publishes pubdata for L1 batches not dispatched yet. Returns the number of dispatched batches.
+    async fn dispatch(&self) -> anyhow::Result<usize> {
+        let mut storage = self.pool.access_storage_tagged("da_dispatcher").await?;
+        let batches = storage
+            .data_availability_dal()
+            .get_ready_for_da_dispatch_l1_batches(self.config.max_rows_to_dispatch as usize)
+            .await
+            .context("get_ready_for_da_dispatch_l1_batches()")?;
+        drop(storage);
+
+        let batch_count = batches.len();
+        for batch in batches {
+            let l1_batch_number = batch.l1_batch_number;
+            let pubdata_len = batch.pubdata.len();
+            let latency = METRICS.blob_dispatch_latency.start();
+            let blob_id = self
+                .publisher
+                .publish_blob(l1_batch_number, batch.pubdata)
+                .await
+                .with_context(|| {
+                    format!("failed publishing pubdata for L1 batch #{l1_batch_number}")
+                })?;
+            let latency = latency.observe();
+
+            let mut storage = self.pool.access_storage_tagged("da_dispatcher").await?;
+            storage
+                .data_availability_dal()
+                .insert_l1_batch_da(l1_batch_number, &blob_id)
+                .await
+                .with_context(|| format!("insert_l1_batch_da({l1_batch_number})"))?;
+            drop(storage);
+
+            METRICS.blob_size.observe(pubdata_len);
+            METRICS
+                .last_dispatched_l1_batch
+                .set(l1_batch_number.0.into());
+            tracing::info!(
+                "Dispatched pubdata for L1 batch #{l1_batch_number} ({pubdata_len} bytes) to the data availability \
+                 layer in {latency:?}; blob ID: {blob_id}"
+            );
+        }
+        Ok(batch_count)
+    }
+
+    /// Polls the data availability layer for the inclusion proof of the first blob awaiting it.
+    /// Returns `true` if the proof was received.
+    async fn poll_for_inclusion(&self) -> anyhow::Result<bool> {
+        let mut storage = self.pool.access_storage_tagged("da_dispatcher").await?;
+        let Some(blob) = storage
+            .data_availability_dal()
+            .get_first_da_blob_awaiting_inclusion()
+            .await
+            .context("get_first_da_blob_awaiting_inclusion()")?
+        else {
+            return Ok(false);
+        };
+        drop(storage);
+
+        let l1_batch_number = blob.l1_batch_number;
+        let Some(inclusion_data) = self
+            .publisher
+            .get_inclusion_data(&blob.blob_id)
+            .await
+            .with_context(|| format!("failed getting inclusion data for blob {}", blob.blob_id))?
+        else {
+            return Ok(false);
+        };
+
+        let mut storage = self.pool.access_storage_tagged("da_dispatcher").await?;
+        storage
+            .data_availability_dal()
+            .save_l1_batch_inclusion_data(l1_batch_number, &inclusion_data)
+            .await
+            .with_context(|| format!("save_l1_batch_inclusion_data({l1_batch_number})"))?;
+        METRICS.last_included_l1_batch.set(l1_batch_number.0.into());
+        tracing::info!(
+            "Received inclusion proof for L1 batch #{l1_batch_number} (blob ID: {})",
+            blob.blob_id
+        );
+        Ok(true)
+    }
+
+    pub async fn run(self, mut stop_receiver: watch::Receiver<bool>) -> anyhow::Result<()> {
+        loop {
+            if *stop_receiver.borrow() {
+                tracing::info!(
+                    "Stop signal received, data availability dispatcher is shutting down"
+                );
+                return Ok(());
+            }
+
+            // Errors are not fatal since they are likely caused by the data availability layer being unavailable.
+            let dispatched = match self.dispatch().await {
+                Ok(count) => count > 0,
+                Err(err) => {
+                    tracing::warn!("Failed dispatching L1 batch pubdata: {err:#}");
+                    false
+                }
+            };
+            let included = match self.poll_for_inclusion().await {
+                Ok(included) => included,
+                Err(err) => {
+                    tracing::warn!("Failed polling for blob inclusion: {err:#}");
+                    false
+                }
+            };
+            if !dispatched && !included {
+                tokio::time::timeout(self.config.polling_interval(), stop_receiver.changed())
+                    .await
+                    .ok();
+            }
+        }
+    }
+}
@@ -0,0 +1,125 @@
+//! Tests for the data availability dispatcher.
+
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use zksync_types::{block::BlockGasCount, L1BatchNumber, L2ChainId};
+
+use super::*;
+use crate::{
+    genesis::{ensure_genesis_state, GenesisParams},
+    utils::testonly::create_l1_batch,
+};
+
+#[derive(Debug, Default)]
+struct MockPublisher {
+    blobs: Mutex<HashMap<String, Vec<u8>>>,
+    included_blobs: Mutex<HashSet<String>>,
+}
+
+impl MockPublisher {
+    fn include_blob(&self, blob_id: &str) {
+        self.included_blobs
+            .lock()
+            .unwrap()
+            .insert(blob_id.to_owned());
+    }
+}
+
+#[async_trait]
+impl DataAvailabilityPublisher for MockPublisher {
+    async fn publish_blob(
+        &self,
+        l1_batch_number: L1BatchNumber,
+        pubdata: Vec<u8>,
+    ) -> anyhow::Result<String> {
+        let blob_id = format!("blob-{l1_batch_number}");
+        self.blobs.lock().unwrap().insert(blob_id.clone(), pubdata);
+        Ok(blob_id)
+    }
+
+    async fn get_inclusion_data(&self, blob_id: &str) -> anyhow::Result<Option<Vec<u8>>> {
+        let is_included = self.included_blobs.lock().unwrap().contains(blob_id);
+        Ok(is_included.then(|| format!("proof-{blob_id}").into_bytes()))
+    }
+}
+
+fn test_config() -> DADispatcherConfig {
+    DADispatcherConfig {
+        api_url: "http://localhost/".to_owned(),
+        polling_interval_ms: 10,
+        max_rows_to_dispatch: 10,
+        request_timeout_ms: 1_000,
+    }
+}
+
+#[tokio::test]
+async fn dispatching_pubdata_and_receiving_inclusion_proofs() {
+    let pool = ConnectionPool::test_pool().await;
+    let mut storage = pool.access_storage().await.unwrap();
+    ensure_genesis_state(&mut storage, L2ChainId::default(), &GenesisParams::mock())
+        .await
+        .unwrap();
+    for number in 1..=2 {
+        let mut header = create_l1_batch(number);
+        header.pubdata_input = Some(vec![number as u8; 64]);
+        storage
+            .blocks_dal()
+            .insert_l1_batch(&header, &[], BlockGasCount::default(), &[], &[], 0)
+            .await
+            .unwrap();
+    }
+    drop(storage);
+
+    let publisher = Arc::new(MockPublisher::default());
+    let dispatcher =
+        DataAvailabilityDispatcher::new(pool.clone(), test_config(), publisher.clone());
+    assert_eq!(dispatcher.dispatch().await.unwrap(), 2);
+    assert_eq!(dispatcher.dispatch().await.unwrap(), 0);
+    {
+        let blobs = publisher.blobs.lock().unwrap();
+        assert_eq!(blobs.len(), 2);
+        assert_eq!(blobs["blob-1"], [1; 64]);
+        assert_eq!(blobs["blob-2"], [2; 64]);
+    }
+
+    // Blobs are not included yet.
+    assert!(!dispatcher.poll_for_inclusion().await.unwrap());
+    publisher.include_blob("blob-2");
+    // Inclusion is awaited in the order of L1 batches.
+    assert!(!dispatcher.poll_for_inclusion().await.unwrap());
+    publisher.include_blob("blob-1");
+    assert!(dispatcher.poll_for_inclusion().await.unwrap());
+    assert!(dispatcher.poll_for_inclusion().await.unwrap());
+    assert!(!dispatcher.poll_for_inclusion().await.unwrap());
+
+    let mut storage = pool.access_storage().await.unwrap();
+    for number in 1..=2 {
+        let inclusion_data = storage
+            .data_availability_dal()
+            .get_inclusion_data(L1BatchNumber(number))
+            .await
+            .unwrap();
+        let expected = format!("proof-blob-{number}").into_bytes();
+        assert_eq!(inclusion_data, Some(expected));
+    }
+}
+
+#[tokio::test]
+async fn dispatcher_stops_on_signal() {
+    let pool = ConnectionPool::test_pool().await;
+    let dispatcher =
+        DataAvailabilityDispatcher::new(pool, test_config(), Arc::new(MockPublisher::default()));
+    let (stop_sender, stop_receiver) = watch::channel(false);
+    let dispatcher_task = tokio::spawn(dispatcher.run(stop_receiver));
+    stop_sender.send_replace(true);
+    tokio::time::timeout(Duration::from_secs(5), dispatcher_task)
+        .await
+        .expect("dispatcher didn't stop")
+        .unwrap()
+        .unwrap();
+}
@@ -0,0 +1,41 @@
+//! Metrics for the DB pruner.
+
+use std::time::Duration;
+
+use vise::{Buckets, Counter, EncodeLabelSet, EncodeLabelValue, Family, Gauge, Histogram, Metrics};
+use zksync_dal::pruning_dal::PruningStats;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EncodeLabelValue, EncodeLabelSet)]
+#[metrics(label = "type", rename_all = "snake_case")]
+pub(super) enum PrunedEntityType {
+    Event,
+    CallTrace,
+    StorageLogFromPrunedBatch,
+    StorageLogFromPastBatch,
+}
+
+#[derive(Debug, Metrics)]
+#[metrics(prefix = "db_pruner")]
+pub(super) struct DbPrunerMetrics {
+    /// Latency of pruning a single chunk of L1 batches.
+    #[metrics(buckets = Buckets::LATENCIES)]
+    pub pruning_chunk_duration: Histogram<Duration>,
+    /// Number of rows deleted by the pruner.
+    pub deleted_rows: Family<PrunedEntityType, Counter>,
+    /// Number of the last pruned L1 batch.
+    pub last_pruned_l1_batch: Gauge<u64>,
+}
+
+impl DbPrunerMetrics {
+    pub fn observe_stats(&self, stats: &PruningStats) {
+        self.deleted_rows[&PrunedEntityType::Event].inc_by(stats.deleted_events);
+        self.deleted_rows[&PrunedEntityType::CallTrace].inc_by(stats.deleted_call_traces);
+        self.deleted_rows[&PrunedEntityType::StorageLogFromPrunedBatch]
+            .inc_by(stats.deleted_storage_logs_from_pruned_batches);
+        self.deleted_rows[&PrunedEntityType::StorageLogFromPastBatch]
+            .inc_by(stats.deleted_storage_logs_from_past_batches);
+    }
+}
+
+#[vise::register]
+pub(super) static METRICS: vise::Global<DbPrunerMetrics> = vise::Global::new();
@@ -0,0 +1,170 @@
+//! Pruning of old miniblock-level data (events, call traces and overwritten storage logs) on full nodes.
+
+use std::time::Duration;
+
+use anyhow::Context as _;
+use tokio::sync::watch;
+use zksync_dal::{ConnectionPool, StorageProcessor};
+use zksync_types::L1BatchNumber;
+
+use self::metrics::METRICS;
+
+mod metrics;
+#[cfg(test)]
+mod tests;
+
+/// Configuration of the [`DbPruner`].
+#[derive(Debug, Clone)]
+pub struct DbPrunerConfig {
+    /// Number of the latest sealed L1 batches for which data is retained.
+    pub retained_l1_batches: u32,
+    /// Maximum number of L1 batches pruned in a single DB transaction.
+    pub pruned_batch_chunk_size: u32,
+    /// Delay between pruning iterations if there's nothing to prune.
+    pub next_iterations_delay: Duration,
+}
+
+/// Periodically prunes miniblock-level data for old L1 batches.
+///
+/// An L1 batch is only pruned if all of the following holds:
+///
+/// - It is older than the configured retention window (counted from the last sealed L1 batch).
+/// - It is executed on L1, so it cannot be reverted.
+/// - It is processed by the Merkle tree, so the tree doesn't need its storage logs.
+///
+/// Pruning retains the latest value of each storage slot, so the node can still serve the current state
+/// and create snapshots for non-pruned L1 batches.
+#[derive(Debug)]
+pub struct DbPruner {
+    config: DbPrunerConfig,
+    pool: ConnectionPool,
+}
+
+impl DbPruner {
+    pub fn new(config: DbPrunerConfig, pool: ConnectionPool) -> Self {
+        assert!(
+            config.pruned_batch_chunk_size > 0,
+            "pruned batch chunk size must be positive"
+        );
+        Self { config, pool }
+    }
+
+    /// Returns the last L1 batch that can be safely pruned according to the interlocks.
+    async fn last_l1_batch_allowed_to_prune(
+        &self,
+        storage: &mut StorageProcessor<'_>,
+    ) -> anyhow::Result<Option<L1BatchNumber>> {
+        let Some(sealed_l1_batch) = storage
+            .blocks_dal()
+            .get_sealed_l1_batch_number()
+            .await
+            .context("get_sealed_l1_batch_number()")?
+        else {
+            return Ok(None);
+        };
+        let Some(last_executed_l1_batch) = storage
+            .blocks_dal()
+            .get_number_of_last_l1_batch_executed_on_eth()
+            .await
+            .context("get_number_of_last_l1_batch_executed_on_eth()")?
+        else {
+            return Ok(None);
+        };
+        let Some(last_l1_batch_with_metadata) = storage
+            .blocks_dal()
+            .get_last_l1_batch_number_with_metadata()
+            .await
+            .context("get_last_l1_batch_number_with_metadata()")?
+        else {
+            return Ok(None);
+        };
+        let Some(last_retained_l1_batch) = sealed_l1_batch
+            .0
+            .checked_sub(self.config.retained_l1_batches)
+        else {
+            return Ok(None);
+        };
+
+        Ok(Some(
+            L1BatchNumber(last_retained_l1_batch)
+                .min(last_executed_l1_batch)
+                .min(last_l1_batch_with_metadata),
+        ))
+    }
+
+    /// Prunes the next chunk of L1 batches if possible. Returns `true` if any data was pruned.
+    async fn run_single_iteration(&self) -> anyhow::Result<bool> {
+        let mut storage = self.pool.access_storage_tagged("db_pruner").await?;
+        let Some(last_allowed_l1_batch) = self.last_l1_batch_allowed_to_prune(&mut storage).await?
+        else {
+            return Ok(false);
+        };
+        let pruning_info = storage
+            .pruning_dal()
+            .get_pruning_info()
+            .await
+            .context("get_pruning_info()")?;
+        let first_l1_batch_to_prune = match pruning_info.last_pruned_l1_batch {
+            Some(number) => number + 1,
+            None => {
+                let earliest = storage
+                    .blocks_dal()
+                    .get_earliest_l1_batch_number()
+                    .await
+                    .context("get_earliest_l1_batch_number()")?;
+                earliest.context("no L1 batches in storage")?
+            }
+        };
+        if first_l1_batch_to_prune > last_allowed_l1_batch {
+            return Ok(false);
+        }
+        let last_l1_batch_to_prune = last_allowed_l1_batch
+            .min(first_l1_batch_to_prune + (self.config.pruned_batch_chunk_size - 1));
+
+        let (_, last_miniblock_to_prune) = storage
+            .blocks_dal()
+            .get_miniblock_range_of_l1_batch(last_l1_batch_to_prune)
+            .await
+            .context("get_miniblock_range_of_l1_batch()")?
+            .with_context(|| {
+                format!("L1 batch #{last_l1_batch_to_prune} doesn't have miniblocks")
+            })?;
+
+        let latency = METRICS.pruning_chunk_duration.start();
+        let stats = storage
+            .pruning_dal()
+            .prune_batches_range(last_l1_batch_to_prune, last_miniblock_to_prune)
+            .await
+            .with_context(|| format!("prune_batches_range({last_l1_batch_to_prune})"))?;
+        let latency = latency.observe();
+        METRICS.observe_stats(&stats);
+        METRICS
+            .last_pruned_l1_batch
+            .set(last_l1_batch_to_prune.0.into());
+
+        tracing::info!(
+            "Pruned L1 batches #{first_l1_batch_to_prune}..=#{last_l1_batch_to_prune} \
+             (up to miniblock #{last_miniblock_to_prune}) in {latency:?}: {stats:?}"
+        );
+        Ok(true)
+    }
+
+    pub async fn run(self, mut stop_receiver: watch::Receiver<bool>) -> anyhow::Result<()> {
+        loop {
+            if *stop_receiver.borrow() {
+                tracing::info!("Stop signal received, DB pruner is shutting down");
+                return Ok(());
+            }
+
+            let pruned = self
+                .run_single_iteration()
+                .await
+                .context("failed pruning L1 batches")?;
+            if !pruned {
+                tokio::time::timeout(self.config.next_iterations_delay, stop_receiver.changed())
+                    .await
+                    .ok();
+            }
+        }
+    }
+}
@@ -0,0 +1,150 @@
+//! Tests for the DB pruner.
+
+use zksync_dal::pruning_dal::PruningInfo;
+use zksync_types::{
+    aggregated_operations::AggregatedActionType, block::BlockGasCount, L2ChainId, MiniblockNumber,
+    H256,
+};
+
+use super::*;
+use crate::{
+    genesis::{ensure_genesis_state, GenesisParams},
+    utils::testonly::{create_l1_batch, create_l1_batch_metadata, create_miniblock},
+};
+
+const TEST_CONFIG: DbPrunerConfig = DbPrunerConfig {
+    retained_l1_batches: 1,
+    pruned_batch_chunk_size: 2,
+    next_iterations_delay: Duration::from_millis(10),
+};
+
+async fn seal_l1_batches(storage: &mut StorageProcessor<'_>, count: u32) {
+    for number in 1..=count {
+        storage
+            .blocks_dal()
+            .insert_miniblock(&create_miniblock(number))
+            .await
+            .unwrap();
+        storage
+            .blocks_dal()
+            .insert_l1_batch(
+                &create_l1_batch(number),
+                &[],
+                BlockGasCount::default(),
+                &[],
+                &[],
+                0,
+            )
+            .await
+            .unwrap();
+        storage
+            .blocks_dal()
+            .mark_miniblocks_as_executed_in_l1_batch(L1BatchNumber(number))
+            .await
+            .unwrap();
+    }
+}
+
+async fn save_metadata(storage: &mut StorageProcessor<'_>, numbers: impl Iterator<Item = u32>) {
+    for number in numbers {
+        storage
+            .blocks_dal()
+            .save_l1_batch_metadata(
+                L1BatchNumber(number),
+                &create_l1_batch_metadata(number),
+                H256::zero(),
+                false,
+            )
+            .await
+            .unwrap();
+    }
+}
+
+async fn mark_executed(storage: &mut StorageProcessor<'_>, numbers: impl Iterator<Item = u32>) {
+    for number in numbers {
+        storage
+            .eth_sender_dal()
+            .insert_bogus_confirmed_eth_tx(
+                L1BatchNumber(number),
+                AggregatedActionType::Execute,
+                H256::from_low_u64_be(number.into()),
+                chrono::Utc::now(),
+            )
+            .await
+            .unwrap();
+    }
+}
+
+async fn prepare_storage(pool: &ConnectionPool) {
+    let mut storage = pool.access_storage().await.unwrap();
+    ensure_genesis_state(&mut storage, L2ChainId::default(), &GenesisParams::mock())
+        .await
+        .unwrap();
+    seal_l1_batches(&mut storage, 5).await;
+}
+
+#[tokio::test]
+async fn pruning_respects_interlocks() {
+    let pool = ConnectionPool::test_pool().await;
+    prepare_storage(&pool).await;
+    let pruner = DbPruner::new(TEST_CONFIG, pool.clone());
+
+    // No L1 batches are executed yet.
+    assert!(!pruner.run_single_iteration().await.unwrap());
+
+    let mut storage = pool.access_storage().await.unwrap();
+    mark_executed(&mut storage, 1..=3).await;
+    // L1 batches are not processed by the tree yet.
+    assert!(!pruner.run_single_iteration().await.unwrap());
+
+    save_metadata(&mut storage, 1..=2).await;
+    assert!(pruner.run_single_iteration().await.unwrap());
+    assert!(pruner.run_single_iteration().await.unwrap());
+    assert!(!pruner.run_single_iteration().await.unwrap());
+    let info = storage.pruning_dal().get_pruning_info().await.unwrap();
+    assert_eq!(
+        info,
+        PruningInfo {
+            last_pruned_l1_batch: Some(L1BatchNumber(2)),
+            last_pruned_miniblock: Some(MiniblockNumber(2)),
+        }
+    );
+
+    // L1 batch #5 is within the retention window, so L1 batches #3 and #4 should be pruned in a single chunk.
+    save_metadata(&mut storage, 3..=5).await;
+    mark_executed(&mut storage, 4..=5).await;
+    assert!(pruner.run_single_iteration().await.unwrap());
+    assert!(!pruner.run_single_iteration().await.unwrap());
+    let info = storage.pruning_dal().get_pruning_info().await.unwrap();
+    assert_eq!(info.last_pruned_l1_batch, Some(L1BatchNumber(4)));
+}
+
+#[tokio::test]
+async fn pruner_stops_on_signal() {
+    let pool = ConnectionPool::test_pool().await;
+    prepare_storage(&pool).await;
+    let mut storage = pool.access_storage().await.unwrap();
+    mark_executed(&mut storage, 1..=5).await;
+    save_metadata(&mut storage, 1..=5).await;
+    drop(storage);
+
+    let (stop_sender, stop_receiver) = watch::channel(false);
+    let pruner_task = tokio::spawn(DbPruner::new(TEST_CONFIG, pool.clone()).run(stop_receiver));
+    loop {
+        let info = pool
+            .access_storage()
+            .await
+            .unwrap()
+            .pruning_dal()
+            .get_pruning_info()
+            .await
+            .unwrap();
+        if info.last_pruned_l1_batch == Some(L1BatchNumber(4)) {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+
+    stop_sender.send_replace(true);
+    pruner_task.await.unwrap().unwrap();
+}
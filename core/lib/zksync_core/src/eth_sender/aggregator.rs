@@ -1,6 +1,8 @@
 use std::sync::Arc;
 
-use zksync_config::configs::eth_sender::{ProofLoadingMode, ProofSendingMode, SenderConfig};
+use zksync_config::configs::eth_sender::{
+    ProofLoadingMode, ProofSendingMode, PubdataSendingMode, SenderConfig,
+};
 use zksync_contracts::BaseSystemContractsHashes;
 use zksync_dal::StorageProcessor;
 use zksync_object_store::{ObjectStore, ObjectStoreError};
@@ -9,15 +11,20 @@ use zksync_types::{
         AggregatedActionType, AggregatedOperation, L1BatchCommitOperation, L1BatchExecuteOperation,
         L1BatchProofForL1, L1BatchProofOperation,
     },
-    commitment::L1BatchWithMetadata,
+    commitment::{L1BatchCommitmentMode, L1BatchWithMetadata},
     helpers::unix_timestamp_ms,
     protocol_version::L1VerifierConfig,
     L1BatchNumber, ProtocolVersionId,
 };
 
-use super::publish_criterion::{
-    DataSizeCriterion, GasCriterion, L1BatchPublishCriterion, NumberCriterion,
-    TimestampDeadlineCriterion,
+use super::{
+    blobs::MAX_BLOBS_PER_TX,
+    error::ETHSenderError,
+    operators::OperatorPool,
+    publish_criterion::{
+        DataSizeCriterion, GasCriterion, L1BatchPublishCriterion, NumberCriterion,
+        TimestampDeadlineCriterion,
+    },
 };
 
 #[derive(Debug)]
@@ -27,15 +34,25 @@ pub struct Aggregator {
     execute_criteria: Vec<Box<dyn L1BatchPublishCriterion>>,
     config: SenderConfig,
     blob_store: Arc<dyn ObjectStore>,
+    commitment_mode: L1BatchCommitmentMode,
+    requires_da_inclusion: bool,
+    /// Whether proofs must wait for commit txs to be confirmed (set if they are sent from different accounts).
+    wait_for_confirmed_commits: bool,
+    /// Whether executions must wait for prove txs to be confirmed (set if they are sent from different accounts).
+    wait_for_confirmed_proofs: bool,
 }
 
 impl Aggregator {
-    pub fn new(config: SenderConfig, blob_store: Arc<dyn ObjectStore>) -> Self {
+    pub fn new(
+        config: SenderConfig,
+        blob_store: Arc<dyn ObjectStore>,
+        commitment_mode: L1BatchCommitmentMode,
+    ) -> Self {
         Self {
             commit_criteria: vec![
                 Box::from(NumberCriterion {
                     op: AggregatedActionType::Commit,
-                    limit: config.max_aggregated_blocks_to_commit,
+                    limit: Self::max_l1_batches_to_commit(&config, commitment_mode),
                 }),
                 Box::from(GasCriterion::new(
                     AggregatedActionType::Commit,
@@ -44,6 +61,7 @@ impl Aggregator {
                 Box::from(DataSizeCriterion {
                     op: AggregatedActionType::Commit,
                     data_limit: config.max_eth_tx_data_size,
+                    commitment_mode,
                 }),
                 Box::from(TimestampDeadlineCriterion {
                     op: AggregatedActionType::Commit,
@@ -86,6 +104,81 @@ impl Aggregator {
             ],
             config,
             blob_store,
+            commitment_mode,
+            requires_da_inclusion: false,
+            wait_for_confirmed_commits: false,
+            wait_for_confirmed_proofs: false,
+        }
+    }
+
+    /// Adapts the aggregator to the operator accounts sending L1 transactions. If consecutive operations
+    /// (commit and prove, or prove and execute) are sent from different accounts, their mining order is not
+    /// guaranteed by nonces, so an operation is only aggregated once the previous operation for the same
+    /// L1 batches is confirmed. Otherwise, it could be mined first and revert.
+    #[must_use]
+    pub(super) fn with_operators(mut self, operators: &OperatorPool) -> Self {
+        let sender = |action| operators.operator_for(action).sender_account();
+        let commit_sender = sender(AggregatedActionType::Commit);
+        let prove_sender = sender(AggregatedActionType::PublishProofOnchain);
+        let execute_sender = sender(AggregatedActionType::Execute);
+        self.wait_for_confirmed_commits = commit_sender != prove_sender;
+        self.wait_for_confirmed_proofs = prove_sender != execute_sender;
+        self
+    }
+
+    /// Requires L1 batches to have their pubdata included into an external data availability layer
+    /// before they are committed. Inclusion proofs are committed on L1 together with the L1 batches.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the aggregator doesn't operate in the validium mode.
+    #[must_use]
+    pub fn with_da_inclusion_required(mut self) -> Self {
+        assert_eq!(
+            self.commitment_mode,
+            L1BatchCommitmentMode::Validium,
+            "data availability layer can only be used in the validium mode"
+        );
+        self.requires_da_inclusion = true;
+        self
+    }
+
+    /// Returns the mode in which L1 batches are committed on L1.
+    pub fn commitment_mode(&self) -> L1BatchCommitmentMode {
+        self.commitment_mode
+    }
+
+    /// Checks that L1 contracts with the specified protocol version support the configured commitment mode.
+    pub(super) fn check_commitment_mode(
+        &self,
+        protocol_version: ProtocolVersionId,
+    ) -> Result<(), ETHSenderError> {
+        if self.commitment_mode == L1BatchCommitmentMode::Validium
+            && !protocol_version.supports_validium()
+        {
+            return Err(ETHSenderError::UnsupportedCommitmentMode {
+                commitment_mode: self.commitment_mode,
+                protocol_version,
+            });
+        }
+        Ok(())
+    }
+
+    /// Returns the maximum number of L1 batches in a single commit operation. If pubdata is published in blobs,
+    /// each L1 batch requires at least one blob, so the number of batches is additionally bounded by the blob limit.
+    /// Blobs are never used in the validium mode.
+    fn max_l1_batches_to_commit(
+        config: &SenderConfig,
+        commitment_mode: L1BatchCommitmentMode,
+    ) -> u32 {
+        if commitment_mode == L1BatchCommitmentMode::Validium {
+            return config.max_aggregated_blocks_to_commit;
+        }
+        match config.pubdata_sending_mode {
+            PubdataSendingMode::Calldata => config.max_aggregated_blocks_to_commit,
+            PubdataSendingMode::Blobs => config
+                .max_aggregated_blocks_to_commit
+                .min(MAX_BLOBS_PER_TX as u32),
         }
     }
 
@@ -127,7 +220,7 @@ impl Aggregator {
         } else {
             self.get_commit_operation(
                 storage,
-                self.config.max_aggregated_blocks_to_commit as usize,
+                Self::max_l1_batches_to_commit(&self.config) as usize,
                 last_sealed_l1_batch_number,
                 base_system_contracts_hashes,
                 protocol_version_id,
@@ -147,11 +240,19 @@ impl Aggregator {
             .config
             .l1_batch_min_age_before_execute_seconds
             .map(|age| unix_timestamp_ms() - age * 1_000);
-        let ready_for_execute_batches = storage
+        let mut ready_for_execute_batches = storage
             .blocks_dal()
             .get_ready_for_execute_l1_batches(limit, max_l1_batch_timestamp_millis)
             .await
             .unwrap();
+        if self.wait_for_confirmed_proofs {
+            let last_proven_l1_batch = storage
+                .blocks_dal()
+                .get_last_l1_batch_with_confirmed_prove_tx()
+                .await
+                .unwrap();
+            retain_confirmed(&mut ready_for_execute_batches, last_proven_l1_batch);
+        }
         let l1_batches = extract_ready_subrange(
             storage,
             &mut self.execute_criteria,
@@ -209,6 +310,11 @@ impl Aggregator {
                     panic!("L1 batches prepared for commit are not sequential");
                 }
             });
+        let ready_for_commit_l1_batches = if self.requires_da_inclusion {
+            Self::attach_da_inclusion_data(storage, ready_for_commit_l1_batches).await
+        } else {
+            ready_for_commit_l1_batches
+        };
 
         let batches = extract_ready_subrange(
             storage,
@@ -224,11 +330,34 @@ impl Aggregator {
         })
     }
 
+    /// Attaches inclusion proofs from the data availability layer to the L1 batches. Batches starting from
+    /// the first one without an inclusion proof are dropped, since they cannot be committed yet.
+    async fn attach_da_inclusion_data(
+        storage: &mut StorageProcessor<'_>,
+        l1_batches: Vec<L1BatchWithMetadata>,
+    ) -> Vec<L1BatchWithMetadata> {
+        let mut batches_with_inclusion_data = Vec::with_capacity(l1_batches.len());
+        for mut l1_batch in l1_batches {
+            let inclusion_data = storage
+                .data_availability_dal()
+                .get_inclusion_data(l1_batch.header.number)
+                .await
+                .unwrap();
+            let Some(inclusion_data) = inclusion_data else {
+                break;
+            };
+            l1_batch.da_inclusion_data = Some(inclusion_data);
+            batches_with_inclusion_data.push(l1_batch);
+        }
+        batches_with_inclusion_data
+    }
+
     async fn load_real_proof_operation(
         storage: &mut StorageProcessor<'_>,
         l1_verifier_config: L1VerifierConfig,
         proof_loading_mode: &ProofLoadingMode,
         blob_store: &dyn ObjectStore,
+        wait_for_confirmed_commit: bool,
     ) -> Option<L1BatchProofOperation> {
         let previous_proven_batch_number = storage
             .blocks_dal()
@@ -243,6 +372,16 @@ impl Aggregator {
             .get_eth_commit_tx_id(batch_to_prove)
             .await
             .unwrap()?;
+        if wait_for_confirmed_commit {
+            let last_committed_l1_batch = storage
+                .blocks_dal()
+                .get_last_l1_batch_with_confirmed_commit_tx()
+                .await
+                .unwrap();
+            if last_committed_l1_batch < Some(batch_to_prove) {
+                return None;
+            }
+        }
 
         if let Some(version_id) = storage
             .blocks_dal()
@@ -308,9 +447,17 @@ impl Aggregator {
     async fn prepare_dummy_proof_operation(
         &mut self,
         storage: &mut StorageProcessor<'_>,
-        ready_for_proof_l1_batches: Vec<L1BatchWithMetadata>,
+        mut ready_for_proof_l1_batches: Vec<L1BatchWithMetadata>,
         last_sealed_l1_batch: L1BatchNumber,
     ) -> Option<L1BatchProofOperation> {
+        if self.wait_for_confirmed_commits {
+            let last_committed_l1_batch = storage
+                .blocks_dal()
+                .get_last_l1_batch_with_confirmed_commit_tx()
+                .await
+                .unwrap();
+            retain_confirmed(&mut ready_for_proof_l1_batches, last_committed_l1_batch);
+        }
         let batches = extract_ready_subrange(
             storage,
             &mut self.proof_criteria,
@@ -348,6 +495,7 @@ impl Aggregator {
                     l1_verifier_config,
                     &self.config.proof_loading_mode,
                     &*self.blob_store,
+                    self.wait_for_confirmed_commits,
                 )
                 .await
             }
@@ -373,6 +521,7 @@ impl Aggregator {
                     l1_verifier_config,
                     &self.config.proof_loading_mode,
                     &*self.blob_store,
+                    self.wait_for_confirmed_commits,
                 )
                 .await
                 {
@@ -395,6 +544,18 @@ impl Aggregator {
     }
 }
 
+/// Retains only L1 batches for which the previous operation is confirmed, i.e. ones not exceeding `last_confirmed`.
+fn retain_confirmed(
+    l1_batches: &mut Vec<L1BatchWithMetadata>,
+    last_confirmed: Option<L1BatchNumber>,
+) {
+    let Some(last_confirmed) = last_confirmed else {
+        l1_batches.clear();
+        return;
+    };
+    l1_batches.retain(|l1_batch| l1_batch.header.number <= last_confirmed);
+}
+
 async fn extract_ready_subrange(
     storage: &mut StorageProcessor<'_>,
     publish_criteria: &mut [Box<dyn L1BatchPublishCriterion>],
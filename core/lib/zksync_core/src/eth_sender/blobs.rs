@@ -0,0 +1,279 @@
+//! Packing of L1 batch pubdata into EIP-4844 blobs.
+
+use anyhow::Context as _;
+use c_kzg::{Blob, Bytes32, KzgCommitment, KzgProof, BYTES_PER_BLOB, FIELD_ELEMENTS_PER_BLOB};
+use sha2::{Digest, Sha256};
+use zksync_system_constants::BLOB_HASH_KEYS;
+use zksync_types::{
+    eth_sender::EthTxBlob, l2_to_l1_log::SystemL2ToL1Log, web3::signing::keccak256, H256,
+};
+use zksync_utils::u256_to_h256;
+
+/// Maximum number of blobs that can be carried by a single L1 transaction.
+pub(super) const MAX_BLOBS_PER_TX: usize = 6;
+/// Maximum number of blobs carrying pubdata of a single L1 batch; bounded by the number of system logs
+/// the VM emits for blob hashes.
+pub(super) const MAX_BLOBS_PER_L1_BATCH: usize = BLOB_HASH_KEYS.len();
+/// Number of pubdata bytes packed into a single 32-byte field element. The most significant byte
+/// of each element is always zero, so that the element is guaranteed to be less than the BLS modulus.
+const PUBDATA_BYTES_PER_FIELD_ELEMENT: usize = 31;
+/// Maximum number of pubdata bytes packed into a single blob.
+const PUBDATA_BYTES_PER_BLOB: usize = PUBDATA_BYTES_PER_FIELD_ELEMENT * FIELD_ELEMENTS_PER_BLOB;
+/// Version byte of versioned hashes for KZG commitments (`VERSIONED_HASH_VERSION_KZG` in EIP-4844).
+const VERSIONED_HASH_VERSION_KZG: u8 = 0x01;
+/// Pubdata source marker prepended to blob commitments in the `commitBatches` calldata.
+const PUBDATA_SOURCE_BLOBS: u8 = 0x01;
+/// Size of the opening point in the blob commitment passed to L1 contracts.
+const OPENING_POINT_SIZE: usize = 16;
+
+/// Blob carrying a part of L1 batch pubdata, together with the data needed to prove its contents to L1 contracts.
+#[derive(Debug)]
+pub(crate) struct L1BatchBlob {
+    /// Blob with the KZG commitment and proof, as attached to the L1 transaction.
+    pub sidecar: EthTxBlob,
+    /// Keccak-256 hash of the pubdata packed into the blob. Must match the blob hash in the L1 batch system logs.
+    pub linear_hash: H256,
+    /// Point at which the blob polynomial is opened, derived from the linear and versioned hashes.
+    pub opening_point: [u8; OPENING_POINT_SIZE],
+    /// Value of the blob polynomial at the opening point.
+    pub opening_value: [u8; 32],
+    /// KZG proof for the opening.
+    pub opening_proof: Vec<u8>,
+}
+
+/// Blobs carrying pubdata for a single L1 batch.
+#[derive(Debug)]
+pub(crate) struct L1BatchBlobs {
+    pub blobs: Vec<L1BatchBlob>,
+}
+
+impl L1BatchBlobs {
+    /// Returns the number of blobs required to publish `pubdata_len` bytes of pubdata.
+    pub fn blob_count(pubdata_len: usize) -> usize {
+        zksync_utils::ceil_div(pubdata_len as u64, PUBDATA_BYTES_PER_BLOB as u64).max(1) as usize
+    }
+
+    /// Packs pubdata into blobs and computes KZG commitments and proofs for them.
+    pub fn new(pubdata: &[u8]) -> anyhow::Result<Self> {
+        let blobs = Self::padded_chunks(pubdata)
+            .map(|chunk| Self::new_blob(&chunk))
+            .collect::<anyhow::Result<_>>()?;
+        Ok(Self { blobs })
+    }
+
+    /// Packs pubdata into blobs without computing KZG commitments and proofs, which is much cheaper than [`Self::new()`].
+    /// The returned blobs are equal to the ones in sidecars of the created blobs.
+    pub fn pack(pubdata: &[u8]) -> Vec<Vec<u8>> {
+        Self::padded_chunks(pubdata)
+            .map(|chunk| Self::pack_blob(&chunk))
+            .collect()
+    }
+
+    /// Splits pubdata into chunks packed into separate blobs; each chunk is padded with zeros to the blob capacity.
+    fn padded_chunks(pubdata: &[u8]) -> impl Iterator<Item = Vec<u8>> + '_ {
+        let blob_count = Self::blob_count(pubdata.len());
+        (0..blob_count).map(|i| {
+            let start = (i * PUBDATA_BYTES_PER_BLOB).min(pubdata.len());
+            let end = ((i + 1) * PUBDATA_BYTES_PER_BLOB).min(pubdata.len());
+            let mut chunk = pubdata[start..end].to_vec();
+            chunk.resize(PUBDATA_BYTES_PER_BLOB, 0);
+            chunk
+        })
+    }
+
+    fn new_blob(padded_pubdata: &[u8]) -> anyhow::Result<L1BatchBlob> {
+        let kzg_settings = c_kzg::ethereum_kzg_settings();
+        let linear_hash = H256(keccak256(padded_pubdata));
+
+        let blob = Self::pack_blob(padded_pubdata);
+        let kzg_blob = Blob::from_bytes(&blob).context("invalid blob")?;
+        let commitment = KzgCommitment::blob_to_kzg_commitment(&kzg_blob, kzg_settings)
+            .context("failed computing KZG commitment")?;
+        let commitment = commitment.to_bytes();
+        let proof = KzgProof::compute_blob_kzg_proof(&kzg_blob, &commitment, kzg_settings)
+            .context("failed computing KZG proof")?;
+        let versioned_hash = Self::versioned_hash(commitment.as_slice());
+
+        let opening_point = Self::opening_point(linear_hash, versioned_hash);
+        let mut opening_point_bytes = [0_u8; 32];
+        opening_point_bytes[32 - OPENING_POINT_SIZE..].copy_from_slice(&opening_point);
+        let opening_point_bytes =
+            Bytes32::from_bytes(&opening_point_bytes).context("invalid opening point")?;
+        let (opening_proof, opening_value) =
+            KzgProof::compute_kzg_proof(&kzg_blob, &opening_point_bytes, kzg_settings)
+                .context("failed computing KZG opening proof")?;
+
+        Ok(L1BatchBlob {
+            sidecar: EthTxBlob {
+                versioned_hash,
+                blob,
+                commitment: commitment.to_vec(),
+                proof: proof.to_bytes().to_vec(),
+            },
+            linear_hash,
+            opening_point,
+            opening_value: *opening_value,
+            opening_proof: opening_proof.to_bytes().to_vec(),
+        })
+    }
+
+    fn pack_blob(pubdata: &[u8]) -> Vec<u8> {
+        let mut blob = vec![0_u8; BYTES_PER_BLOB];
+        for (element, chunk) in blob
+            .chunks_exact_mut(32)
+            .zip(pubdata.chunks(PUBDATA_BYTES_PER_FIELD_ELEMENT))
+        {
+            element[1..=chunk.len()].copy_from_slice(chunk);
+        }
+        blob
+    }
+
+    fn versioned_hash(commitment: &[u8]) -> H256 {
+        let mut hash: [u8; 32] = Sha256::digest(commitment).into();
+        hash[0] = VERSIONED_HASH_VERSION_KZG;
+        H256(hash)
+    }
+
+    /// The opening point is the lower 16 bytes of `keccak256(linear_hash || versioned_hash)`; L1 contracts
+    /// recompute it in the same way.
+    fn opening_point(linear_hash: H256, versioned_hash: H256) -> [u8; OPENING_POINT_SIZE] {
+        let hash = keccak256(&[linear_hash.as_bytes(), versioned_hash.as_bytes()].concat());
+        hash[32 - OPENING_POINT_SIZE..].try_into().unwrap()
+    }
+
+    /// Checks whether the L1 batch system logs contain linear hashes of all blobs. L1 contracts reject
+    /// the commitment otherwise, so pubdata must be published in calldata in this case.
+    pub fn matches_system_logs(&self, system_logs: &[SystemL2ToL1Log]) -> bool {
+        if self.blobs.len() > MAX_BLOBS_PER_L1_BATCH {
+            return false;
+        }
+        self.blobs.iter().zip(BLOB_HASH_KEYS).all(|(blob, key)| {
+            let key = u256_to_h256(key.into());
+            system_logs
+                .iter()
+                .any(|log| log.0.key == key && log.0.value == blob.linear_hash)
+        })
+    }
+
+    /// Encodes commitments to the blobs as expected in the `totalL2ToL1Pubdata` field of `commitBatches` calldata:
+    /// the pubdata source marker followed by the opening point, the claimed value, the KZG commitment
+    /// and the opening proof for each blob.
+    pub fn pubdata_commitments(&self) -> Vec<u8> {
+        let mut commitments = vec![PUBDATA_SOURCE_BLOBS];
+        for blob in &self.blobs {
+            commitments.extend_from_slice(&blob.opening_point);
+            commitments.extend_from_slice(&blob.opening_value);
+            commitments.extend_from_slice(&blob.sidecar.commitment);
+            commitments.extend_from_slice(&blob.opening_proof);
+        }
+        commitments
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use c_kzg::Bytes48;
+    use zksync_types::{l2_to_l1_log::L2ToL1Log, Address};
+
+    use super::*;
+
+    fn unpack_blob(blob: &[u8]) -> Vec<u8> {
+        blob.chunks_exact(32)
+            .flat_map(|element| {
+                assert_eq!(element[0], 0);
+                element[1..].iter().copied()
+            })
+            .collect()
+    }
+
+    fn system_log(key: u32, value: H256) -> SystemL2ToL1Log {
+        SystemL2ToL1Log(L2ToL1Log {
+            shard_id: 0,
+            is_service: false,
+            tx_number_in_block: 0,
+            sender: Address::zero(),
+            key: u256_to_h256(key.into()),
+            value,
+        })
+    }
+
+    #[test]
+    fn blob_count_is_computed_correctly() {
+        assert_eq!(L1BatchBlobs::blob_count(0), 1);
+        assert_eq!(L1BatchBlobs::blob_count(1), 1);
+        assert_eq!(L1BatchBlobs::blob_count(PUBDATA_BYTES_PER_BLOB), 1);
+        assert_eq!(L1BatchBlobs::blob_count(PUBDATA_BYTES_PER_BLOB + 1), 2);
+    }
+
+    #[test]
+    fn packing_pubdata_into_blobs() {
+        let pubdata: Vec<u8> = (0..PUBDATA_BYTES_PER_BLOB + 100)
+            .map(|i| (i % 251) as u8 + 1)
+            .collect();
+        let blobs = L1BatchBlobs::new(&pubdata).unwrap();
+        assert_eq!(blobs.blobs.len(), 2);
+
+        let unpacked: Vec<u8> = blobs
+            .blobs
+            .iter()
+            .flat_map(|blob| {
+                assert_eq!(blob.sidecar.blob.len(), BYTES_PER_BLOB);
+                unpack_blob(&blob.sidecar.blob)
+            })
+            .collect();
+        assert_eq!(unpacked[..pubdata.len()], pubdata);
+        assert!(unpacked[pubdata.len()..].iter().all(|&byte| byte == 0));
+
+        let packed_blobs = L1BatchBlobs::pack(&pubdata);
+        let sidecar_blobs: Vec<_> = blobs.blobs.iter().map(|blob| &blob.sidecar.blob).collect();
+        assert_eq!(packed_blobs.iter().collect::<Vec<_>>(), sidecar_blobs);
+
+        let kzg_settings = c_kzg::ethereum_kzg_settings();
+        for blob in &blobs.blobs {
+            assert_eq!(blob.sidecar.versioned_hash.0[0], VERSIONED_HASH_VERSION_KZG);
+            assert_eq!(
+                blob.linear_hash,
+                H256(keccak256(&unpack_blob(&blob.sidecar.blob)))
+            );
+            let commitment = Bytes48::from_bytes(&blob.sidecar.commitment).unwrap();
+            let is_valid = KzgProof::verify_blob_kzg_proof(
+                &Blob::from_bytes(&blob.sidecar.blob).unwrap(),
+                &commitment,
+                &Bytes48::from_bytes(&blob.sidecar.proof).unwrap(),
+                kzg_settings,
+            )
+            .unwrap();
+            assert!(is_valid);
+
+            let mut opening_point = [0_u8; 32];
+            opening_point[32 - OPENING_POINT_SIZE..].copy_from_slice(&blob.opening_point);
+            let is_valid = KzgProof::verify_kzg_proof(
+                &commitment,
+                &Bytes32::from_bytes(&opening_point).unwrap(),
+                &Bytes32::from_bytes(&blob.opening_value).unwrap(),
+                &Bytes48::from_bytes(&blob.opening_proof).unwrap(),
+                kzg_settings,
+            )
+            .unwrap();
+            assert!(is_valid);
+        }
+
+        let commitments = blobs.pubdata_commitments();
+        assert_eq!(commitments.len(), 1 + 2 * (16 + 32 + 48 + 48));
+        assert_eq!(commitments[0], PUBDATA_SOURCE_BLOBS);
+        assert_eq!(commitments[1..17], blobs.blobs[0].opening_point);
+        assert_eq!(commitments[17..49], blobs.blobs[0].opening_value);
+        assert_eq!(commitments[49..97], blobs.blobs[0].sidecar.commitment);
+        assert_eq!(commitments[97..145], blobs.blobs[0].opening_proof);
+    }
+
+    #[test]
+    fn matching_blobs_with_system_logs() {
+        let blobs = L1BatchBlobs::new(&[1; 1_000]).unwrap();
+        let linear_hash = blobs.blobs[0].linear_hash;
+        assert!(!blobs.matches_system_logs(&[]));
+        assert!(!blobs.matches_system_logs(&[system_log(BLOB_HASH_KEYS[0], H256::zero())]));
+        assert!(!blobs.matches_system_logs(&[system_log(BLOB_HASH_KEYS[1], linear_hash)]));
+        assert!(blobs.matches_system_logs(&[system_log(BLOB_HASH_KEYS[0], linear_hash)]));
+    }
+}
@@ -0,0 +1,195 @@
+//! Dry run of commit transactions via `eth_call` before they are sent to L1.
+
+use std::fmt;
+
+use zksync_eth_client::Error as EthClientError;
+use zksync_types::{
+    ethabi::{self, AbiError, ParamType, Token},
+    web3, U256,
+};
+
+/// Selector of the `Error(string)` error used by `require` / `revert` with a message.
+const ERROR_SELECTOR: [u8; 4] = [0x08, 0xc3, 0x79, 0xa0];
+/// Selector of the `Panic(uint256)` error used by failed assertions, arithmetic overflows etc.
+const PANIC_SELECTOR: [u8; 4] = [0x4e, 0x48, 0x7b, 0x71];
+
+/// Reason of a reverted `eth_call`.
+#[derive(Debug, Clone, PartialEq)]
+pub(super) enum RevertReason {
+    /// Revert with a message, e.g. via `require(condition, "message")`.
+    Message(String),
+    /// Panic with the specified code.
+    Panic(U256),
+    /// Custom error declared in the contract ABI.
+    CustomError { name: String, args: Vec<Token> },
+    /// Revert data that cannot be decoded; may be empty.
+    Unknown(Vec<u8>),
+}
+
+impl fmt::Display for RevertReason {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Message(message) => write!(formatter, "reverted with message {message:?}"),
+            Self::Panic(code) => write!(formatter, "panicked with code {code:#x}"),
+            Self::CustomError { name, args } => {
+                let args: Vec<_> = args.iter().map(Token::to_string).collect();
+                write!(formatter, "reverted with error {name}({})", args.join(", "))
+            }
+            Self::Unknown(data) if data.is_empty() => formatter.write_str("reverted without data"),
+            Self::Unknown(data) => {
+                write!(formatter, "reverted with data 0x{}", hex::encode(data))
+            }
+        }
+    }
+}
+
+impl RevertReason {
+    /// Decodes revert data returned by `eth_call`. Custom errors are resolved using `known_errors`.
+    pub fn decode(data: &[u8], known_errors: &[AbiError]) -> Self {
+        if data.len() < 4 {
+            return Self::Unknown(data.to_vec());
+        }
+        let (selector, encoded_args) = data.split_at(4);
+
+        if selector == ERROR_SELECTOR {
+            if let Ok(mut tokens) = ethabi::decode(&[ParamType::String], encoded_args) {
+                if let Some(Token::String(message)) = tokens.pop() {
+                    return Self::Message(message);
+                }
+            }
+        } else if selector == PANIC_SELECTOR {
+            if let Ok(mut tokens) = ethabi::decode(&[ParamType::Uint(256)], encoded_args) {
+                if let Some(Token::Uint(code)) = tokens.pop() {
+                    return Self::Panic(code);
+                }
+            }
+        } else {
+            let error = known_errors
+                .iter()
+                .find(|error| error.signature().as_bytes()[..4] == *selector);
+            if let Some(error) = error {
+                if let Ok(args) = error.decode(encoded_args) {
+                    return Self::CustomError {
+                        name: error.name.clone(),
+                        args,
+                    };
+                }
+            }
+        }
+        Self::Unknown(data.to_vec())
+    }
+
+    /// Extracts the revert reason from an `eth_call` error. Returns `None` if the error is not caused
+    /// by a revert (e.g., it's a network error).
+    pub fn from_call_error(err: &EthClientError, known_errors: &[AbiError]) -> Option<Self> {
+        let EthClientError::EthereumGateway(web3::Error::Rpc(rpc_error)) = err else {
+            return None;
+        };
+        // Geth-compatible nodes use the code 3 for reverts with data; some nodes only set the message.
+        let is_revert =
+            rpc_error.code.code() == 3 || rpc_error.message.starts_with("execution reverted");
+        if !is_revert {
+            return None;
+        }
+
+        let revert_data = match &rpc_error.data {
+            Some(serde_json::Value::String(data)) => Some(data.as_str()),
+            Some(serde_json::Value::Object(object)) => {
+                object.get("data").and_then(serde_json::Value::as_str)
+            }
+            _ => None,
+        };
+        let revert_data =
+            revert_data.and_then(|data| hex::decode(data.strip_prefix("0x").unwrap_or(data)).ok());
+        if let Some(data) = revert_data {
+            return Some(Self::decode(&data, known_errors));
+        }
+
+        let message = rpc_error
+            .message
+            .strip_prefix("execution reverted: ")
+            .unwrap_or(&rpc_error.message);
+        Some(Self::Message(message.to_owned()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zksync_eth_client::{clients::MockEthereum, EthInterface};
+    use zksync_types::{ethabi::Param, web3::types::CallRequest};
+
+    use super::*;
+
+    fn error_data(selector: [u8; 4], tokens: &[Token]) -> Vec<u8> {
+        let mut data = selector.to_vec();
+        data.extend(ethabi::encode(tokens));
+        data
+    }
+
+    #[test]
+    fn decoding_revert_reasons() {
+        let data = error_data(ERROR_SELECTOR, &[Token::String("h".to_owned())]);
+        assert_eq!(
+            RevertReason::decode(&data, &[]),
+            RevertReason::Message("h".to_owned())
+        );
+
+        let data = error_data(PANIC_SELECTOR, &[Token::Uint(0x11.into())]);
+        assert_eq!(
+            RevertReason::decode(&data, &[]),
+            RevertReason::Panic(0x11.into())
+        );
+
+        let custom_error = AbiError {
+            name: "BatchNumberMismatch".to_owned(),
+            inputs: vec![
+                Param {
+                    name: "expected".to_owned(),
+                    kind: ParamType::Uint(64),
+                    internal_type: None,
+                },
+                Param {
+                    name: "actual".to_owned(),
+                    kind: ParamType::Uint(64),
+                    internal_type: None,
+                },
+            ],
+        };
+        let args = vec![Token::Uint(1.into()), Token::Uint(2.into())];
+        let data = custom_error.encode(&args).unwrap();
+        let reason = RevertReason::decode(&data, &[custom_error.clone()]);
+        assert_eq!(
+            reason,
+            RevertReason::CustomError {
+                name: "BatchNumberMismatch".to_owned(),
+                args,
+            }
+        );
+        assert_eq!(
+            reason.to_string(),
+            "reverted with error BatchNumberMismatch(1, 2)"
+        );
+        assert_eq!(
+            RevertReason::decode(&data, &[]),
+            RevertReason::Unknown(data)
+        );
+    }
+
+    #[tokio::test]
+    async fn extracting_revert_reason_from_call_error() {
+        let eth_client = MockEthereum::default();
+        let data = error_data(ERROR_SELECTOR, &[Token::String("h".to_owned())]);
+        eth_client.set_call_revert_data(Some(data));
+        let err = eth_client
+            .call(CallRequest::default(), None, "test")
+            .await
+            .unwrap_err();
+        assert_eq!(
+            RevertReason::from_call_error(&err, &[]),
+            Some(RevertReason::Message("h".to_owned()))
+        );
+
+        let err = EthClientError::EthereumGateway(web3::Error::Unreachable);
+        assert_eq!(RevertReason::from_call_error(&err, &[]), None);
+    }
+}
@@ -1,4 +1,8 @@
-use zksync_types::web3::contract;
+use std::ops::RangeInclusive;
+
+use zksync_types::{
+    commitment::L1BatchCommitmentMode, web3::contract, Address, L1BatchNumber, ProtocolVersionId,
+};
 
 #[derive(Debug, thiserror::Error)]
 pub enum ETHSenderError {
@@ -6,4 +10,34 @@ pub enum ETHSenderError {
     EthereumGateWayError(#[from] zksync_eth_client::Error),
     #[error("Token parsing Error: {0}")]
     ParseError(#[from] contract::Error),
+    #[error("Blob base fee is unknown; cannot send a blob transaction")]
+    UnknownBlobBaseFee,
+    #[error("Operator account {0:?} is not configured; cannot sign its transactions")]
+    UnknownOperator(Address),
+    #[error("Max fee per gas {max_fee_per_gas} reaches the configured cap {cap}")]
+    FeeCapExceeded { max_fee_per_gas: u64, cap: u64 },
+    #[error(
+        "Dry run of commit transaction for L1 batches {l1_batches:?} (protocol version {protocol_version:?}) {reason}"
+    )]
+    CommitDryRunFailed {
+        l1_batches: RangeInclusive<L1BatchNumber>,
+        protocol_version: ProtocolVersionId,
+        reason: String,
+    },
+    #[error(
+        "Dry run of commit transaction for L1 batches {l1_batches:?} is deferred until previously saved transactions are sent"
+    )]
+    CommitDryRunDeferred {
+        l1_batches: RangeInclusive<L1BatchNumber>,
+    },
+    #[error("Proof for L1 batches {l1_batches:?} failed local verification: {reason}")]
+    ProofVerificationFailed {
+        l1_batches: RangeInclusive<L1BatchNumber>,
+        reason: String,
+    },
+    #[error("L1 contracts with protocol version {protocol_version:?} don't support the {commitment_mode:?} commitment mode")]
+    UnsupportedCommitmentMode {
+        commitment_mode: L1BatchCommitmentMode,
+        protocol_version: ProtocolVersionId,
+    },
 }
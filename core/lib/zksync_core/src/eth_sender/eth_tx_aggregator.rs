@@ -1,29 +1,38 @@
-use std::{convert::TryInto, sync::Arc};
+use std::{collections::HashMap, convert::TryInto, time::Instant};
 
 use tokio::sync::watch;
-use zksync_config::configs::eth_sender::SenderConfig;
+use zksync_config::configs::eth_sender::{PubdataSendingMode, SenderConfig};
 use zksync_contracts::BaseSystemContractsHashes;
 use zksync_dal::{ConnectionPool, StorageProcessor};
-use zksync_eth_client::{BoundEthInterface, CallFunctionArgs};
+use zksync_eth_client::CallFunctionArgs;
 use zksync_types::{
-    aggregated_operations::AggregatedOperation,
+    aggregated_operations::{
+        AggregatedActionType, AggregatedOperation, L1BatchCommitOperation, L1BatchProofOperation,
+    },
+    commitment::L1BatchCommitmentMode,
     contracts::{Multicall3Call, Multicall3Result},
-    eth_sender::EthTx,
+    eth_sender::{EthTx, EthTxBlobSidecar},
     ethabi::{Contract, Token},
     protocol_version::{L1VerifierConfig, VerifierParams},
     vk_transform::l1_vk_commitment,
-    web3::contract::{
-        tokens::{Detokenize, Tokenizable},
-        Error,
+    web3::{
+        contract::{
+            tokens::{Detokenize, Tokenizable},
+            Error,
+        },
+        types::{BlockId, BlockNumber, CallRequest},
     },
     Address, ProtocolVersionId, H256, U256,
 };
 
 use crate::{
     eth_sender::{
-        metrics::{PubdataKind, METRICS},
+        blobs::{L1BatchBlobs, MAX_BLOBS_PER_L1_BATCH, MAX_BLOBS_PER_TX},
+        commit_dry_run::RevertReason,
+        metrics::{BlobsFallbackReason, PubdataKind, METRICS},
+        proof_verifier::ProofVerifier,
         zksync_functions::ZkSyncFunctions,
-        Aggregator, ETHSenderError,
+        Aggregator, ETHSenderError, OperatorPool,
     },
     gas_tracker::agg_l1_batch_base_cost,
     metrics::BlockL1Stage,
@@ -44,38 +53,48 @@ pub struct MulticallData {
 #[derive(Debug)]
 pub struct EthTxAggregator {
     aggregator: Aggregator,
-    eth_client: Arc<dyn BoundEthInterface>,
+    operators: OperatorPool,
     config: SenderConfig,
     timelock_contract_address: Address,
     l1_multicall3_address: Address,
     pub(super) main_zksync_contract_address: Address,
     functions: ZkSyncFunctions,
-    base_nonce: u64,
+    /// Nonces of operator accounts at the time the aggregator was started.
+    base_nonces: HashMap<Address, u64>,
+    proof_verifier: Option<ProofVerifier>,
 }
 
 impl EthTxAggregator {
     pub fn new(
         config: SenderConfig,
         aggregator: Aggregator,
-        eth_client: Arc<dyn BoundEthInterface>,
+        operators: OperatorPool,
         timelock_contract_address: Address,
         l1_multicall3_address: Address,
         main_zksync_contract_address: Address,
-        base_nonce: u64,
+        base_nonces: HashMap<Address, u64>,
     ) -> Self {
         let functions = ZkSyncFunctions::default();
+        let aggregator = aggregator.with_operators(&operators);
         Self {
             config,
             aggregator,
-            eth_client,
+            operators,
             timelock_contract_address,
             l1_multicall3_address,
             main_zksync_contract_address,
             functions,
-            base_nonce,
+            base_nonces,
+            proof_verifier: None,
         }
     }
 
+    /// Enables local verification of real proofs before prove transactions are saved.
+    pub fn with_proof_verifier(mut self, verifier: ProofVerifier) -> Self {
+        self.proof_verifier = Some(verifier);
+        self
+    }
+
     pub async fn run(
         mut self,
         pool: ConnectionPool,
@@ -106,7 +125,11 @@ impl EthTxAggregator {
             self.l1_multicall3_address,
             self.functions.multicall_contract.clone(),
         );
-        let aggregate3_result = self.eth_client.call_contract_function(args).await?;
+        let aggregate3_result = self
+            .operators
+            .primary()
+            .call_contract_function(args)
+            .await?;
         self.parse_multicall_data(Token::from_tokens(aggregate3_result)?)
     }
 
@@ -309,20 +332,28 @@ impl EthTxAggregator {
             let args = CallFunctionArgs::new(&self.functions.get_verification_key.name, ())
                 .for_contract(verifier_address, abi);
 
-            let vk = self.eth_client.call_contract_function(args).await?;
+            let vk = self
+                .operators
+                .primary()
+                .call_contract_function(args)
+                .await?;
             Ok(l1_vk_commitment(Token::from_tokens(vk)?))
         } else {
             let get_vk_hash = self.functions.verification_key_hash.as_ref();
             tracing::debug!("Calling verificationKeyHash");
             let args = CallFunctionArgs::new(&get_vk_hash.unwrap().name, ())
                 .for_contract(verifier_address, self.functions.verifier_contract.clone());
-            let vk_hash = self.eth_client.call_contract_function(args).await?;
+            let vk_hash = self
+                .operators
+                .primary()
+                .call_contract_function(args)
+                .await?;
             Ok(H256::from_tokens(vk_hash)?)
         }
     }
 
     #[tracing::instrument(skip(self, storage))]
-    async fn loop_iteration(
+    pub(super) async fn loop_iteration(
         &mut self,
         storage: &mut StorageProcessor<'_>,
     ) -> Result<(), ETHSenderError> {
@@ -336,6 +367,12 @@ impl EthTxAggregator {
             err
         })?;
         let contracts_are_pre_boojum = protocol_version_id.is_pre_boojum();
+        self.aggregator
+            .check_commitment_mode(protocol_version_id)
+            .map_err(|err| {
+                tracing::error!("{err}");
+                err
+            })?;
 
         let recursion_scheduler_level_vk_hash = self
             .get_recursion_scheduler_level_vk_hash(verifier_address, contracts_are_pre_boojum)
@@ -358,14 +395,60 @@ impl EthTxAggregator {
             )
             .await
         {
-            let tx = self
+            if !self.can_send_from_assigned_operator(storage, &agg_op).await {
+                return Ok(());
+            }
+            if let AggregatedOperation::PublishProofOnchain(op) = &agg_op {
+                self.verify_proof(op)?;
+            }
+            let tx = match self
                 .save_eth_tx(storage, &agg_op, contracts_are_pre_boojum)
-                .await?;
+                .await
+            {
+                Err(err @ ETHSenderError::CommitDryRunDeferred { .. }) => {
+                    // The operation will be aggregated again on the next iteration.
+                    tracing::debug!("{err}");
+                    return Ok(());
+                }
+                result => result?,
+            };
             Self::report_eth_tx_saving(storage, agg_op, &tx).await;
         }
         Ok(())
     }
 
+    /// Checks whether an operation can be sent from the operator assigned to its type. This is not the case
+    /// if there are unconfirmed transactions of the same type sent from another operator (e.g., after the assignment
+    /// has changed because of key rotation): transactions of the same type must be mined in order, which is only
+    /// guaranteed for transactions sent from a single account.
+    pub(super) async fn can_send_from_assigned_operator(
+        &self,
+        storage: &mut StorageProcessor<'_>,
+        aggregated_op: &AggregatedOperation,
+    ) -> bool {
+        let op_type = aggregated_op.get_action_type();
+        let from_addr = self.operators.operator_for(op_type).sender_account();
+        let unconfirmed_senders = storage
+            .eth_sender_dal()
+            .get_unconfirmed_tx_senders(op_type)
+            .await
+            .unwrap();
+        let other_sender = unconfirmed_senders
+            .into_iter()
+            .map(|sender| self.operators.resolve_sender(sender))
+            .find(|&sender| sender != from_addr);
+        if let Some(other_sender) = other_sender {
+            tracing::info!(
+                "Postponing {} operation for L1 batches {:?}: waiting for unconfirmed transactions \
+                 from {other_sender:?} before sending from {from_addr:?}",
+                aggregated_op.get_action_caption(),
+                aggregated_op.l1_batch_range()
+            );
+            return false;
+        }
+        true
+    }
+
     async fn report_eth_tx_saving(
         storage: &mut StorageProcessor<'_>,
         aggregated_op: AggregatedOperation,
@@ -397,10 +480,127 @@ impl EthTxAggregator {
             .await;
     }
 
+    /// Packs pubdata for the commit operation into blobs if pubdata should be published in blobs.
+    /// Returns `None` if pubdata should be published in calldata (or not published at all in the validium mode),
+    /// either because of the configuration, or because publishing it in blobs is currently impossible or too expensive.
+    async fn blobs_for_commit(
+        &self,
+        op: &L1BatchCommitOperation,
+        contracts_are_pre_boojum: bool,
+    ) -> Option<Vec<L1BatchBlobs>> {
+        if self.config.pubdata_sending_mode != PubdataSendingMode::Blobs
+            || self.aggregator.commitment_mode() == L1BatchCommitmentMode::Validium
+            || contracts_are_pre_boojum
+        {
+            return None;
+        }
+        let l1_batch_range = op.l1_batch_range();
+
+        let all_versions_support_blobs = op
+            .l1_batches
+            .iter()
+            .all(|l1_batch| l1_batch.header.protocol_version.unwrap().supports_blobs());
+        if !all_versions_support_blobs {
+            tracing::info!(
+                "Protocol version of some L1 batches in {l1_batch_range:?} doesn't support EIP-4844 blobs; \
+                 publishing pubdata in calldata"
+            );
+            METRICS.pubdata_blobs_fallback[&BlobsFallbackReason::UnsupportedProtocolVersion].inc();
+            return None;
+        }
+
+        if let Some(threshold) = self.config.blob_base_fee_fallback_threshold() {
+            match self
+                .operators
+                .primary()
+                .get_blob_base_fee("eth_tx_aggregator")
+                .await
+            {
+                Ok(blob_base_fee) if blob_base_fee <= threshold.into() => {}
+                Ok(blob_base_fee) => {
+                    tracing::info!(
+                        "Blob base fee {blob_base_fee} exceeds threshold {threshold}; publishing pubdata \
+                         for L1 batches {l1_batch_range:?} in calldata"
+                    );
+                    METRICS.pubdata_blobs_fallback[&BlobsFallbackReason::HighBlobBaseFee].inc();
+                    return None;
+                }
+                Err(err) => {
+                    tracing::warn!(
+                        "Failed getting blob base fee: {err}; publishing pubdata for L1 batches {l1_batch_range:?} in calldata"
+                    );
+                    METRICS.pubdata_blobs_fallback[&BlobsFallbackReason::UnknownBlobBaseFee].inc();
+                    return None;
+                }
+            }
+        }
+
+        let blob_counts: Vec<_> = op
+            .l1_batches
+            .iter()
+            .map(|l1_batch| L1BatchBlobs::blob_count(l1_batch.pubdata().len()))
+            .collect();
+        let blob_count: usize = blob_counts.iter().sum();
+        let max_blob_count_per_batch = blob_counts.iter().copied().max().unwrap_or(0);
+        if blob_count > MAX_BLOBS_PER_TX || max_blob_count_per_batch > MAX_BLOBS_PER_L1_BATCH {
+            tracing::warn!(
+                "Pubdata for L1 batches {l1_batch_range:?} requires {blob_count} blobs (up to {max_blob_count_per_batch} \
+                 per batch), while at most {MAX_BLOBS_PER_TX} are allowed in a transaction and {MAX_BLOBS_PER_L1_BATCH} \
+                 per batch; publishing it in calldata"
+            );
+            METRICS.pubdata_blobs_fallback[&BlobsFallbackReason::PackingError].inc();
+            return None;
+        }
+
+        let l1_batches = op.l1_batches.clone();
+        let blobs = tokio::task::spawn_blocking(move || {
+            l1_batches
+                .iter()
+                .map(|l1_batch| L1BatchBlobs::new(&l1_batch.pubdata()))
+                .collect::<anyhow::Result<Vec<_>>>()
+        })
+        .await;
+        let blobs = match blobs {
+            Ok(Ok(blobs)) => blobs,
+            Ok(Err(err)) => {
+                tracing::warn!(
+                    "Failed packing pubdata for L1 batches {l1_batch_range:?} into blobs: {err:#}; publishing it in calldata"
+                );
+                METRICS.pubdata_blobs_fallback[&BlobsFallbackReason::PackingError].inc();
+                return None;
+            }
+            Err(err) => {
+                tracing::error!(
+                    "Task packing pubdata for L1 batches {l1_batch_range:?} into blobs failed: {err}; publishing it in calldata"
+                );
+                METRICS.pubdata_blobs_fallback[&BlobsFallbackReason::PackingError].inc();
+                return None;
+            }
+        };
+
+        // L1 contracts check blobs against their hashes in system logs; if the VM hasn't emitted them,
+        // the commit transaction would revert.
+        let blobs_match_logs = op
+            .l1_batches
+            .iter()
+            .zip(&blobs)
+            .all(|(l1_batch, blobs)| blobs.matches_system_logs(&l1_batch.header.system_logs));
+        if !blobs_match_logs {
+            tracing::warn!(
+                "System logs of L1 batches {l1_batch_range:?} don't contain hashes of the packed blobs; \
+                 publishing pubdata in calldata"
+            );
+            METRICS.pubdata_blobs_fallback[&BlobsFallbackReason::MissingBlobHashes].inc();
+            return None;
+        }
+        Some(blobs)
+    }
+
     fn encode_aggregated_op(
         &self,
         op: &AggregatedOperation,
         contracts_are_pre_boojum: bool,
+        blobs: Option<&[L1BatchBlobs]>,
     ) -> Vec<u8> {
         let operation_is_pre_boojum = op.protocol_version().is_pre_boojum();
 
@@ -417,7 +617,19 @@ impl EthTxAggregator {
                         .as_ref()
                         .expect("Missing ABI for commitBatches")
                 };
-                f.encode_input(&op.get_eth_tx_args())
+                let args = if let Some(blobs) = blobs {
+                    let pubdata_commitments = blobs
+                        .iter()
+                        .map(L1BatchBlobs::pubdata_commitments)
+                        .collect();
+                    op.get_eth_tx_args_with_pubdata_commitments(pubdata_commitments)
+                } else {
+                    // Support of the commitment mode by the contracts (and thus by the committed batches,
+                    // which have the same protocol version) is checked in `Aggregator::check_commitment_mode()`.
+                    op.get_eth_tx_args(self.aggregator.commitment_mode())
+                        .expect("L1 batches cannot be committed in the configured mode")
+                };
+                f.encode_input(&args)
             }
             AggregatedOperation::PublishProofOnchain(op) => {
                 assert_eq!(contracts_are_pre_boojum, operation_is_pre_boojum);
@@ -446,17 +658,52 @@ impl EthTxAggregator {
         .expect("Failed to encode transaction data")
     }
 
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            action = %aggregated_op.get_action_type(),
+            l1_batches = ?aggregated_op.l1_batch_range(),
+        )
+    )]
     pub(super) async fn save_eth_tx(
         &self,
         storage: &mut StorageProcessor<'_>,
         aggregated_op: &AggregatedOperation,
         contracts_are_pre_boojum: bool,
     ) -> Result<EthTx, ETHSenderError> {
+        let l1_batches = aggregated_op.l1_batch_range();
+        for l1_batch_number in l1_batches.start().0..=l1_batches.end().0 {
+            vlog::link_current_span(vlog::TraceKey::L1Batch(l1_batch_number));
+        }
+        let blobs = match aggregated_op {
+            AggregatedOperation::Commit(op) => {
+                self.blobs_for_commit(op, contracts_are_pre_boojum).await
+            }
+            _ => None,
+        };
+        let op_type = aggregated_op.get_action_type();
+        let from_addr = self.operators.operator_for(op_type).sender_account();
+        let calldata =
+            self.encode_aggregated_op(aggregated_op, contracts_are_pre_boojum, blobs.as_deref());
+        // Blobs cannot be attached to `eth_call` requests, so transactions with blobs are not simulated.
+        let should_dry_run = self.config.commit_dry_run_enabled
+            && op_type == AggregatedActionType::Commit
+            && blobs.is_none();
+        if should_dry_run {
+            self.dry_run_commit(storage, aggregated_op, from_addr, &calldata)
+                .await?;
+        }
+
         let mut transaction = storage.start_transaction().await.unwrap();
-        let nonce = self.get_next_nonce(&mut transaction).await?;
-        let calldata = self.encode_aggregated_op(aggregated_op, contracts_are_pre_boojum);
+        let nonce = self.get_next_nonce(&mut transaction, from_addr).await?;
+        let blob_sidecar = blobs.map(|blobs| EthTxBlobSidecar {
+            blobs: blobs
+                .into_iter()
+                .flat_map(|blobs| blobs.blobs)
+                .map(|blob| blob.sidecar)
+                .collect(),
+        });
         let l1_batch_number_range = aggregated_op.l1_batch_range();
-        let op_type = aggregated_op.get_action_type();
 
         let predicted_gas_for_batches = transaction
             .blocks_dal()
@@ -473,6 +720,8 @@ impl EthTxAggregator {
                 op_type,
                 self.timelock_contract_address,
                 eth_tx_predicted_gas,
+                blob_sidecar,
+                from_addr,
             )
             .await
             .unwrap();
@@ -483,21 +732,124 @@ impl EthTxAggregator {
             .await
             .unwrap();
         transaction.commit().await.unwrap();
+        vlog::record_current_span(vlog::TraceKey::EthTx(eth_tx.id));
         Ok(eth_tx)
     }
 
+    /// Simulates a commit transaction via `eth_call` in order to catch reverts (e.g., caused by calldata encoding
+    /// or protocol version mismatches) before the transaction is sent to L1. The simulation is performed against
+    /// the pending L1 state, so that it accounts for sent but unconfirmed commit transactions. If some of the previously
+    /// saved transactions are not sent yet, the pending state doesn't reflect them, so the simulation is deferred
+    /// by returning [`ETHSenderError::CommitDryRunDeferred`].
+    async fn dry_run_commit(
+        &self,
+        storage: &mut StorageProcessor<'_>,
+        aggregated_op: &AggregatedOperation,
+        from_addr: Address,
+        calldata: &[u8],
+    ) -> Result<(), ETHSenderError> {
+        let l1_batches = aggregated_op.l1_batch_range();
+        let mut eth_sender_dal = storage.eth_sender_dal();
+        let has_new_txs = !eth_sender_dal.get_new_eth_txs(1).await.unwrap().is_empty();
+        let has_unsent_txs =
+            has_new_txs || !eth_sender_dal.get_unsent_txs().await.unwrap().is_empty();
+        if has_unsent_txs {
+            return Err(ETHSenderError::CommitDryRunDeferred { l1_batches });
+        }
+
+        let request = CallRequest {
+            from: Some(from_addr),
+            to: Some(self.timelock_contract_address),
+            data: Some(calldata.to_vec().into()),
+            ..CallRequest::default()
+        };
+        let pending_block = BlockId::Number(BlockNumber::Pending);
+        let Err(err) = self
+            .operators
+            .primary()
+            .call(request, Some(pending_block), "eth_tx_aggregator")
+            .await
+        else {
+            tracing::debug!(
+                "Dry run of commit transaction for L1 batches {l1_batches:?} succeeded"
+            );
+            return Ok(());
+        };
+        let Some(reason) =
+            RevertReason::from_call_error(&err, &self.functions.zksync_contract_errors)
+        else {
+            return Err(err.into());
+        };
+
+        METRICS.commit_dry_run_failures.inc();
+        let err = ETHSenderError::CommitDryRunFailed {
+            l1_batches,
+            protocol_version: aggregated_op.protocol_version(),
+            reason: reason.to_string(),
+        };
+        tracing::error!(
+            "{err}; the transaction is not sent. Calldata (selector 0x{}, {} bytes) was sent from {from_addr:?} \
+             to {:?}",
+            hex::encode(calldata.get(..4).unwrap_or_default()),
+            calldata.len(),
+            self.timelock_contract_address
+        );
+        Err(err)
+    }
+
+    /// Verifies the real proof in a prove operation using the configured verifier (if any). Operations
+    /// with dummy proofs are not verified.
+    pub(super) fn verify_proof(&self, op: &L1BatchProofOperation) -> Result<(), ETHSenderError> {
+        let Some(verifier) = &self.proof_verifier else {
+            return Ok(());
+        };
+        if !op.should_verify {
+            return Ok(());
+        }
+
+        let l1_batches = op.l1_batch_range();
+        let started_at = Instant::now();
+        let Err(err) = verifier.verify(op) else {
+            tracing::debug!(
+                "Proof for L1 batches {l1_batches:?} was verified in {:?}",
+                started_at.elapsed()
+            );
+            return Ok(());
+        };
+
+        METRICS.proof_verification_failures.inc();
+        let err = ETHSenderError::ProofVerificationFailed {
+            l1_batches,
+            reason: err.to_string(),
+        };
+        tracing::error!("{err}; the prove transaction is not sent");
+        Err(err)
+    }
+
     async fn get_next_nonce(
         &self,
         storage: &mut StorageProcessor<'_>,
+        from_addr: Address,
     ) -> Result<u64, ETHSenderError> {
-        let db_nonce = storage
+        let mut db_nonce = storage
             .eth_sender_dal()
-            .get_next_nonce()
+            .get_next_nonce(Some(from_addr))
             .await
             .unwrap()
             .unwrap_or(0);
+        if from_addr == self.operators.primary().sender_account() {
+            // Transactions without a recorded sender are sent from the primary operator.
+            let legacy_nonce = storage
+                .eth_sender_dal()
+                .get_next_nonce(None)
+                .await
+                .unwrap()
+                .unwrap_or(0);
+            db_nonce = db_nonce.max(legacy_nonce);
+        }
         // Between server starts we can execute some txs using operator account or remove some txs from the database
         // At the start we have to consider this fact and get the max nonce.
-        Ok(db_nonce.max(self.base_nonce))
+        let base_nonce = self.base_nonces.get(&from_addr).copied().unwrap_or(0);
+        Ok(db_nonce.max(base_nonce))
     }
 }
@@ -1,30 +1,32 @@
-use std::{sync::Arc, time::Duration};
+use std::{collections::HashMap, sync::Arc, time::Duration};
 
 use anyhow::Context as _;
 use tokio::sync::watch;
 use zksync_config::configs::eth_sender::SenderConfig;
 use zksync_dal::{ConnectionPool, StorageProcessor};
 use zksync_eth_client::{
-    BoundEthInterface, Error, ExecutedTxStatus, RawTransactionBytes, SignedCallResult,
+    BlobTxParams, BoundEthInterface, Error, ExecutedTxStatus, RawTransactionBytes, SignedCallResult,
 };
 use zksync_types::{
-    eth_sender::EthTx,
+    eth_sender::{EthTx, TxHistory},
     web3::{
         contract::Options,
         error::Error as Web3Error,
         types::{BlockId, BlockNumber},
     },
-    L1BlockNumber, Nonce, H256, U256,
+    Address, L1BlockNumber, Nonce, H256, U256,
 };
 use zksync_utils::time::seconds_since_epoch;
 
-use super::{metrics::METRICS, ETHSenderError};
+use super::{metrics::METRICS, ETHSenderError, OperatorPool, ResubmissionPolicy};
 use crate::{l1_gas_price::L1TxParamsProvider, metrics::BlockL1Stage};
 
 #[derive(Debug)]
 struct EthFee {
     base_fee_per_gas: u64,
     priority_fee_per_gas: u64,
+    /// Only set for EIP-4844 transactions.
+    blob_base_fee_per_gas: Option<u64>,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -48,8 +50,9 @@ pub(super) struct L1BlockNumbers {
 /// with higher gas price
 #[derive(Debug)]
 pub struct EthTxManager {
-    ethereum_gateway: Arc<dyn BoundEthInterface>,
+    operators: OperatorPool,
     config: SenderConfig,
+    policy: ResubmissionPolicy,
     gas_adjuster: Arc<dyn L1TxParamsProvider>,
 }
 
@@ -57,11 +60,14 @@ impl EthTxManager {
     pub fn new(
         config: SenderConfig,
         gas_adjuster: Arc<dyn L1TxParamsProvider>,
-        ethereum_gateway: Arc<dyn BoundEthInterface>,
+        operators: OperatorPool,
     ) -> Self {
+        let policy = ResubmissionPolicy::new(&config);
+        policy.report_metrics();
         Self {
-            ethereum_gateway,
+            operators,
             config,
+            policy,
             gas_adjuster,
         }
     }
@@ -70,7 +76,8 @@ impl EthTxManager {
         &self,
         tx_hash: H256,
     ) -> Result<Option<ExecutedTxStatus>, ETHSenderError> {
-        self.ethereum_gateway
+        self.operators
+            .primary()
             .get_tx_status(tx_hash, "eth_tx_manager")
             .await
             .map_err(Into::into)
@@ -110,22 +117,60 @@ impl EthTxManager {
         tx: &EthTx,
         time_in_mempool: u32,
     ) -> Result<EthFee, ETHSenderError> {
-        let base_fee_per_gas = self.gas_adjuster.get_base_fee(time_in_mempool);
+        let mut base_fee_per_gas = self.gas_adjuster.get_base_fee(time_in_mempool);
+        let mut blob_base_fee_per_gas = if tx.blob_sidecar.is_some() {
+            let fee = self
+                .gas_adjuster
+                .get_blob_base_fee(time_in_mempool)
+                .ok_or(ETHSenderError::UnknownBlobBaseFee)?;
+            Some(fee)
+        } else {
+            None
+        };
 
+        let suggested_priority_fee = self
+            .policy
+            .escalate_priority_fee(self.gas_adjuster.get_priority_fee(), time_in_mempool);
         let priority_fee_per_gas = if time_in_mempool != 0 {
             METRICS.transaction_resent.inc();
-            let priority_fee_per_gas = self
-                .increase_priority_fee(storage, tx.id, base_fee_per_gas)
-                .await?;
+            let previous_sent_tx = storage
+                .eth_sender_dal()
+                .get_last_sent_eth_tx(tx.id)
+                .await
+                .unwrap()
+                .unwrap();
+            let min_priority_fee =
+                self.increase_priority_fee(&previous_sent_tx, base_fee_per_gas)?;
+            let priority_fee_per_gas = min_priority_fee.max(suggested_priority_fee);
+
+            if let Some(blob_base_fee_per_gas) = &mut blob_base_fee_per_gas {
+                // EIP-4844 transactions can only be replaced if all fees are at least doubled.
+                base_fee_per_gas = base_fee_per_gas.max(previous_sent_tx.base_fee_per_gas * 2);
+                let previous_blob_base_fee = previous_sent_tx.blob_base_fee_per_gas.unwrap_or(0);
+                *blob_base_fee_per_gas = (*blob_base_fee_per_gas).max(previous_blob_base_fee * 2);
+            }
+            let capped_priority_fee =
+                self.cap_priority_fee(tx, base_fee_per_gas, priority_fee_per_gas)?;
+            if capped_priority_fee < min_priority_fee {
+                // The replacement transaction would be rejected as under-priced, so it's not sent.
+                METRICS.fee_cap_exceeded.inc();
+                let err = ETHSenderError::FeeCapExceeded {
+                    max_fee_per_gas: base_fee_per_gas.saturating_add(min_priority_fee),
+                    cap: base_fee_per_gas.saturating_add(capped_priority_fee),
+                };
+                tracing::warn!("Cannot resend operation {}: {err}", tx.id);
+                return Err(err);
+            }
             tracing::info!(
-                "Resending operation {} with base fee {:?} and priority fee {:?}",
+                "Resending operation {} with base fee {:?}, priority fee {:?} and blob base fee {:?}",
                 tx.id,
                 base_fee_per_gas,
-                priority_fee_per_gas
+                capped_priority_fee,
+                blob_base_fee_per_gas
             );
-            priority_fee_per_gas
+            capped_priority_fee
         } else {
-            self.gas_adjuster.get_priority_fee()
+            self.cap_priority_fee(tx, base_fee_per_gas, suggested_priority_fee)?
         };
 
         // Extra check to prevent sending transaction will extremely high priority fee.
@@ -140,22 +185,31 @@ impl EthTxManager {
         Ok(EthFee {
             base_fee_per_gas,
             priority_fee_per_gas,
+            blob_base_fee_per_gas,
         })
     }
 
-    async fn increase_priority_fee(
+    fn cap_priority_fee(
         &self,
-        storage: &mut StorageProcessor<'_>,
-        eth_tx_id: u32,
+        tx: &EthTx,
         base_fee_per_gas: u64,
+        priority_fee_per_gas: u64,
     ) -> Result<u64, ETHSenderError> {
-        let previous_sent_tx = storage
-            .eth_sender_dal()
-            .get_last_sent_eth_tx(eth_tx_id)
-            .await
-            .unwrap()
-            .unwrap();
+        self.policy
+            .cap_priority_fee(base_fee_per_gas, priority_fee_per_gas)
+            .map_err(|err| {
+                tracing::warn!("Cannot send operation {}: {err}", tx.id);
+                err
+            })
+    }
 
+    /// Returns the minimum priority fee for the transaction replacing `previous_sent_tx`.
+    fn increase_priority_fee(
+        &self,
+        previous_sent_tx: &TxHistory,
+        base_fee_per_gas: u64,
+    ) -> Result<u64, ETHSenderError> {
+        let eth_tx_id = previous_sent_tx.eth_tx_id;
         let previous_base_fee = previous_sent_tx.base_fee_per_gas;
         let previous_priority_fee = previous_sent_tx.priority_fee_per_gas;
         let next_block_minimal_base_fee = self.gas_adjuster.get_next_block_minimal_base_fee();
@@ -174,11 +228,16 @@ impl EthTxManager {
             return Err(ETHSenderError::from(Error::from(Web3Error::Internal)));
         }
 
-        // Increase `priority_fee_per_gas` by at least 20% to prevent "replacement transaction under-priced" error.
-        Ok((previous_priority_fee + (previous_priority_fee / 5) + 1)
-            .max(self.gas_adjuster.get_priority_fee()))
+        let is_blob_tx = previous_sent_tx.blob_base_fee_per_gas.is_some();
+        Ok(self
+            .policy
+            .bump_priority_fee(previous_priority_fee, is_blob_tx))
     }
 
+    #[tracing::instrument(
+        skip(self, storage, tx),
+        fields(eth_tx_id = tx.id, nonce = tx.nonce.0, action = %tx.tx_type)
+    )]
     pub(crate) async fn send_eth_tx(
         &mut self,
         storage: &mut StorageProcessor<'_>,
@@ -186,19 +245,31 @@ impl EthTxManager {
         time_in_mempool: u32,
         current_block: L1BlockNumber,
     ) -> Result<H256, ETHSenderError> {
+        vlog::link_current_span(vlog::TraceKey::EthTx(tx.id));
         let EthFee {
             base_fee_per_gas,
             priority_fee_per_gas,
+            blob_base_fee_per_gas,
         } = self.calculate_fee(storage, tx, time_in_mempool).await?;
 
         METRICS.used_base_fee_per_gas.observe(base_fee_per_gas);
         METRICS
             .used_priority_fee_per_gas
             .observe(priority_fee_per_gas);
+        if let Some(blob_base_fee_per_gas) = blob_base_fee_per_gas {
+            METRICS
+                .used_blob_base_fee_per_gas
+                .observe(blob_base_fee_per_gas);
+        }
 
         let signed_tx = self
-            .sign_tx(tx, base_fee_per_gas, priority_fee_per_gas)
-            .await;
+            .sign_tx(
+                tx,
+                base_fee_per_gas,
+                priority_fee_per_gas,
+                blob_base_fee_per_gas,
+            )
+            .await?;
 
         if let Some(tx_history_id) = storage
             .eth_sender_dal()
@@ -206,6 +277,7 @@ impl EthTxManager {
                 tx.id,
                 base_fee_per_gas,
                 priority_fee_per_gas,
+                blob_base_fee_per_gas,
                 signed_tx.hash,
                 signed_tx.raw_tx.as_ref(),
             )
@@ -235,7 +307,7 @@ impl EthTxManager {
         raw_tx: RawTransactionBytes,
         current_block: L1BlockNumber,
     ) -> Result<H256, ETHSenderError> {
-        match self.ethereum_gateway.send_raw_tx(raw_tx).await {
+        match self.operators.primary().send_raw_tx(raw_tx).await {
             Ok(tx_hash) => {
                 storage
                     .eth_sender_dal()
@@ -256,18 +328,16 @@ impl EthTxManager {
     }
 
     async fn get_operator_nonce(
-        &self,
+        operator: &dyn BoundEthInterface,
         block_numbers: L1BlockNumbers,
     ) -> Result<OperatorNonce, ETHSenderError> {
-        let finalized = self
-            .ethereum_gateway
+        let finalized = operator
             .nonce_at(block_numbers.finalized.0.into(), "eth_tx_manager")
             .await?
             .as_u32()
             .into();
 
-        let latest = self
-            .ethereum_gateway
+        let latest = operator
             .nonce_at(block_numbers.latest.0.into(), "eth_tx_manager")
             .await?
             .as_u32()
@@ -278,13 +348,15 @@ impl EthTxManager {
     async fn get_l1_block_numbers(&self) -> Result<L1BlockNumbers, ETHSenderError> {
         let finalized = if let Some(confirmations) = self.config.wait_confirmations {
             let latest_block_number = self
-                .ethereum_gateway
+                .operators
+                .primary()
                 .block_number("eth_tx_manager")
                 .await?
                 .as_u64();
             (latest_block_number.saturating_sub(confirmations) as u32).into()
         } else {
-            self.ethereum_gateway
+            self.operators
+                .primary()
                 .block(BlockId::Number(BlockNumber::Finalized), "eth_tx_manager")
                 .await?
                 .expect("Finalized block must be present on L1")
@@ -295,7 +367,8 @@ impl EthTxManager {
         };
 
         let latest = self
-            .ethereum_gateway
+            .operators
+            .primary()
             .block_number("eth_tx_manager")
             .await?
             .as_u32()
@@ -304,23 +377,64 @@ impl EthTxManager {
     }
 
     // Monitors the in-flight transactions, marks mined ones as confirmed,
-    // returns the one that has to be resent (if there is one).
+    // returns the ones that have to be resent (at most one per operator account).
     pub(super) async fn monitor_inflight_transactions(
         &mut self,
         storage: &mut StorageProcessor<'_>,
         l1_block_numbers: L1BlockNumbers,
-    ) -> Result<Option<(EthTx, u32)>, ETHSenderError> {
+    ) -> Result<Vec<(EthTx, u32)>, ETHSenderError> {
         METRICS
             .last_known_l1_block
             .set(l1_block_numbers.latest.0.into());
-        let operator_nonce = self.get_operator_nonce(l1_block_numbers).await?;
         let inflight_txs = storage.eth_sender_dal().get_inflight_txs().await.unwrap();
         METRICS.number_of_inflight_txs.set(inflight_txs.len());
 
+        // Nonces are tracked independently for each operator account, so transactions are grouped by the sender.
+        let mut txs_by_sender = HashMap::<Address, Vec<EthTx>>::new();
+        for tx in inflight_txs {
+            let from_addr = self.operators.resolve_sender(tx.from_addr);
+            txs_by_sender.entry(from_addr).or_default().push(tx);
+        }
+
+        let mut txs_to_resend = vec![];
+        for (from_addr, txs) in txs_by_sender {
+            let Some(operator) = self.operators.get(Some(from_addr)).cloned() else {
+                tracing::error!(
+                    "Operator account {from_addr:?} is not configured, but it has {} in-flight txs; \
+                     its key must not be removed until all its txs are confirmed",
+                    txs.len()
+                );
+                continue;
+            };
+            let tx_to_resend = self
+                .monitor_operator_inflight_transactions(
+                    storage,
+                    operator.as_ref(),
+                    txs,
+                    l1_block_numbers,
+                )
+                .await?;
+            txs_to_resend.extend(tx_to_resend);
+        }
+        Ok(txs_to_resend)
+    }
+
+    // Monitors the in-flight transactions sent from a single operator account, marks mined ones as confirmed,
+    // returns the one that has to be resent (if there is one).
+    async fn monitor_operator_inflight_transactions(
+        &mut self,
+        storage: &mut StorageProcessor<'_>,
+        operator: &dyn BoundEthInterface,
+        inflight_txs: Vec<EthTx>,
+        l1_block_numbers: L1BlockNumbers,
+    ) -> Result<Option<(EthTx, u32)>, ETHSenderError> {
+        let operator_nonce = Self::get_operator_nonce(operator, l1_block_numbers).await?;
+
         tracing::trace!(
-            "Going through not confirmed txs. \
+            "Going through not confirmed txs from {:?}. \
              Block numbers: latest {}, finalized {}, \
              operator's nonce: latest {}, finalized {}",
+            operator.sender_account(),
             l1_block_numbers.latest,
             l1_block_numbers.finalized,
             operator_nonce.latest,
@@ -385,22 +499,46 @@ impl EthTxManager {
         tx: &EthTx,
         base_fee_per_gas: u64,
         priority_fee_per_gas: u64,
-    ) -> SignedCallResult {
-        self.ethereum_gateway
-            .sign_prepared_tx_for_addr(
-                tx.raw_tx.clone(),
-                tx.contract_address,
-                Options::with(|opt| {
-                    // TODO Calculate gas for every operation SMA-1436
-                    opt.gas = Some(self.config.max_aggregated_tx_gas.into());
-                    opt.max_fee_per_gas = Some(U256::from(base_fee_per_gas + priority_fee_per_gas));
-                    opt.max_priority_fee_per_gas = Some(U256::from(priority_fee_per_gas));
-                    opt.nonce = Some(tx.nonce.0.into());
-                }),
-                "eth_tx_manager",
-            )
-            .await
-            .expect("Failed to sign transaction")
+        blob_base_fee_per_gas: Option<u64>,
+    ) -> Result<SignedCallResult, ETHSenderError> {
+        let operator = self.operators.get(tx.from_addr).ok_or_else(|| {
+            ETHSenderError::UnknownOperator(self.operators.resolve_sender(tx.from_addr))
+        })?;
+        let options = Options::with(|opt| {
+            // TODO Calculate gas for every operation SMA-1436
+            opt.gas = Some(self.config.max_aggregated_tx_gas.into());
+            opt.max_fee_per_gas = Some(U256::from(base_fee_per_gas + priority_fee_per_gas));
+            opt.max_priority_fee_per_gas = Some(U256::from(priority_fee_per_gas));
+            opt.nonce = Some(tx.nonce.0.into());
+        });
+
+        let signed_tx = if let Some(sidecar) = &tx.blob_sidecar {
+            let blob_params = BlobTxParams {
+                max_fee_per_blob_gas: blob_base_fee_per_gas
+                    .expect("blob base fee must be set for blob transactions")
+                    .into(),
+                sidecar: sidecar.clone(),
+            };
+            operator
+                .sign_prepared_blob_tx_for_addr(
+                    tx.raw_tx.clone(),
+                    tx.contract_address,
+                    options,
+                    blob_params,
+                    "eth_tx_manager",
+                )
+                .await
+        } else {
+            operator
+                .sign_prepared_tx_for_addr(
+                    tx.raw_tx.clone(),
+                    tx.contract_address,
+                    options,
+                    "eth_tx_manager",
+                )
+                .await
+        };
+        Ok(signed_tx.expect("Failed to sign transaction"))
     }
 
     async fn send_unsent_txs(
@@ -480,7 +618,8 @@ impl EthTxManager {
             .await
             .unwrap();
         let failure_reason = self
-            .ethereum_gateway
+            .operators
+            .primary()
             .failure_reason(tx_status.receipt.transaction_hash)
             .await
             .expect(
@@ -598,8 +737,8 @@ impl EthTxManager {
             .unwrap()
             .len();
         let number_of_available_slots_for_eth_txs = self
-            .config
-            .max_txs_in_flight
+            .policy
+            .max_txs_in_flight()
             .saturating_sub(number_inflight_txs as u64);
 
         if number_of_available_slots_for_eth_txs > 0 {
@@ -632,10 +771,10 @@ impl EthTxManager {
             return Ok(previous_block);
         }
 
-        if let Some((tx, sent_at_block)) = self
+        let txs_to_resend = self
             .monitor_inflight_transactions(storage, l1_block_numbers)
-            .await?
-        {
+            .await?;
+        for (tx, sent_at_block) in txs_to_resend {
             // New gas price depends on the time this tx spent in mempool.
             let time_in_mempool = l1_block_numbers.latest.0 - sent_at_block;
 
@@ -18,6 +18,22 @@ pub(super) enum PubdataKind {
     RepeatedWritesCompressed,
 }
 
+/// Reason for publishing pubdata in calldata despite the blobs pubdata sending mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EncodeLabelSet, EncodeLabelValue)]
+#[metrics(label = "reason", rename_all = "snake_case")]
+pub(super) enum BlobsFallbackReason {
+    /// Blob base fee exceeds the configured threshold.
+    HighBlobBaseFee,
+    /// Blob base fee cannot be fetched from L1.
+    UnknownBlobBaseFee,
+    /// Pubdata cannot be packed into blobs of a single transaction.
+    PackingError,
+    /// Protocol version of committed L1 batches doesn't support blobs.
+    UnsupportedProtocolVersion,
+    /// System logs of committed L1 batches don't contain hashes of the packed blobs.
+    MissingBlobHashes,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EncodeLabelSet, EncodeLabelValue)]
 #[metrics(label = "type")]
 pub(super) struct ActionTypeLabel(AggregatedActionType);
@@ -75,6 +91,10 @@ pub(super) struct EthSenderMetrics {
     pub used_base_fee_per_gas: Histogram<u64>,
     #[metrics(buckets = FEE_BUCKETS)]
     pub used_priority_fee_per_gas: Histogram<u64>,
+    #[metrics(buckets = FEE_BUCKETS)]
+    pub used_blob_base_fee_per_gas: Histogram<u64>,
+    /// Number of commit transactions publishing pubdata in calldata even though blobs are configured.
+    pub pubdata_blobs_fallback: Family<BlobsFallbackReason, Counter>,
     /// Last L1 block observed by the Ethereum sender.
     pub last_known_l1_block: Gauge<u64>,
     /// Number of in-flight txs produced by the Ethereum sender.
@@ -87,6 +107,18 @@ pub(super) struct EthSenderMetrics {
     pub l1_blocks_waited_in_mempool: Family<ActionTypeLabel, Histogram<u64>>,
     /// Number of L1 batches aggregated for publishing with a specific reason.
     pub block_aggregation_reason: Family<AggregationReasonLabels, Counter>,
+    /// Configured cap on the max fee per gas for L1 transactions (0 if not capped).
+    pub max_fee_per_gas_cap: Gauge<u64>,
+    /// Configured minimum fee bump for resubmitted L1 transactions, in percent.
+    pub fee_bump_percent: Gauge<u64>,
+    /// Configured rate of priority fee escalation per L1 block spent in the mempool, in percent.
+    pub escalation_rate_percent: Gauge<u64>,
+    /// Number of times an L1 transaction wasn't (re)sent because its fees reached the configured cap.
+    pub fee_cap_exceeded: Counter,
+    /// Number of commit transactions that weren't saved because their dry run via `eth_call` has reverted.
+    pub commit_dry_run_failures: Counter,
+    /// Number of prove transactions that weren't saved because their proof has failed local verification.
+    pub proof_verification_failures: Counter,
 }
 
 impl EthSenderMetrics {
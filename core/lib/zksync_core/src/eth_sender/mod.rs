@@ -1,15 +1,22 @@
 mod aggregator;
+mod blobs;
+mod commit_dry_run;
 mod error;
 mod eth_tx_aggregator;
 mod eth_tx_manager;
 mod metrics;
+mod operators;
+mod proof_verifier;
 mod publish_criterion;
+mod resubmission;
 mod zksync_functions;
 
 #[cfg(test)]
 mod tests;
 
+pub(crate) use self::blobs::L1BatchBlobs;
 pub use self::{
     aggregator::Aggregator, error::ETHSenderError, eth_tx_aggregator::EthTxAggregator,
-    eth_tx_manager::EthTxManager,
+    eth_tx_manager::EthTxManager, operators::OperatorPool, proof_verifier::ProofVerifier,
+    resubmission::ResubmissionPolicy,
 };
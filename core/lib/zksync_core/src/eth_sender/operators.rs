@@ -0,0 +1,141 @@
+//! Pool of operator accounts used to send L1 transactions.
+
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+
+use anyhow::Context as _;
+use zksync_config::{ContractsConfig, ETHClientConfig, ETHSenderConfig};
+use zksync_eth_client::{clients::PKSigningClient, BoundEthInterface};
+use zksync_types::{aggregated_operations::AggregatedActionType, Address};
+
+/// Pool of operator accounts used to sign and send L1 transactions.
+///
+/// Each account has independent nonces, so transactions of different types (commit, prove and execute)
+/// can be sent in parallel from different accounts. Transactions of the same type are always sent
+/// from a single account, which guarantees that they are mined in order.
+///
+/// Retired accounts are not assigned new transactions, but are still used to finalize
+/// their in-flight transactions. This allows rotating operator keys without downtime.
+#[derive(Debug, Clone)]
+pub struct OperatorPool {
+    /// Operator clients; the first one is the primary operator.
+    operators: Vec<Arc<dyn BoundEthInterface>>,
+    retired: HashSet<Address>,
+}
+
+impl OperatorPool {
+    /// Creates a pool from the provided operator clients. The first client is considered the primary operator.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there are no operators, if operator accounts are duplicated, or if all accounts are retired.
+    pub fn new(
+        operators: Vec<Arc<dyn BoundEthInterface>>,
+        retired_addresses: &[Address],
+    ) -> anyhow::Result<Self> {
+        anyhow::ensure!(!operators.is_empty(), "no operator accounts provided");
+        let mut addresses = HashSet::with_capacity(operators.len());
+        for operator in &operators {
+            let address = operator.sender_account();
+            anyhow::ensure!(
+                addresses.insert(address),
+                "operator account {address:?} is specified multiple times"
+            );
+        }
+        let retired: HashSet<_> = retired_addresses.iter().copied().collect();
+        for address in &retired {
+            if !addresses.contains(address) {
+                tracing::warn!("Retired operator account {address:?} is not in the operator pool");
+            }
+        }
+        anyhow::ensure!(
+            addresses.iter().any(|address| !retired.contains(address)),
+            "all operator accounts are retired"
+        );
+        Ok(Self { operators, retired })
+    }
+
+    /// Creates a pool with operator accounts specified in the configuration.
+    pub fn from_config(
+        eth_sender: &ETHSenderConfig,
+        contracts_config: &ContractsConfig,
+        eth_client: &ETHClientConfig,
+    ) -> anyhow::Result<Self> {
+        let operators = eth_sender
+            .sender
+            .private_keys()
+            .into_iter()
+            .map(|private_key| {
+                let client = PKSigningClient::from_config_with_private_key(
+                    eth_sender,
+                    contracts_config,
+                    eth_client,
+                    private_key,
+                );
+                Arc::new(client) as Arc<dyn BoundEthInterface>
+            })
+            .collect();
+        Self::new(operators, &eth_sender.sender.retired_operator_addresses)
+    }
+
+    /// Returns the primary operator. It is used for L1 queries that don't depend on the account,
+    /// and to send transactions without a recorded sender.
+    pub fn primary(&self) -> &Arc<dyn BoundEthInterface> {
+        &self.operators[0]
+    }
+
+    /// Returns the operator for the specified sender address. `None` corresponds to the primary operator.
+    pub fn get(&self, from_addr: Option<Address>) -> Option<&Arc<dyn BoundEthInterface>> {
+        let Some(from_addr) = from_addr else {
+            return Some(self.primary());
+        };
+        self.operators
+            .iter()
+            .find(|operator| operator.sender_account() == from_addr)
+    }
+
+    /// Returns the address sending transactions with the specified sender address. `None` corresponds
+    /// to the primary operator.
+    pub fn resolve_sender(&self, from_addr: Option<Address>) -> Address {
+        from_addr.unwrap_or_else(|| self.primary().sender_account())
+    }
+
+    fn is_retired(&self, operator: &Arc<dyn BoundEthInterface>) -> bool {
+        self.retired.contains(&operator.sender_account())
+    }
+
+    /// Returns the operator that should send new transactions of the specified type.
+    pub fn operator_for(&self, action: AggregatedActionType) -> &Arc<dyn BoundEthInterface> {
+        let action_index = match action {
+            AggregatedActionType::Commit => 0,
+            AggregatedActionType::PublishProofOnchain => 1,
+            AggregatedActionType::Execute => 2,
+        };
+        let active_operators: Vec<_> = self
+            .operators
+            .iter()
+            .filter(|operator| !self.is_retired(operator))
+            .collect();
+        // `new()` ensures that there's at least one active operator
+        active_operators[action_index % active_operators.len()]
+    }
+
+    /// Fetches pending nonces for all operators in the pool.
+    pub async fn pending_nonces(
+        &self,
+        component: &'static str,
+    ) -> anyhow::Result<HashMap<Address, u64>> {
+        let mut nonces = HashMap::with_capacity(self.operators.len());
+        for operator in &self.operators {
+            let address = operator.sender_account();
+            let nonce = operator
+                .pending_nonce(component)
+                .await
+                .with_context(|| format!("failed getting pending nonce for {address:?}"))?;
+            nonces.insert(address, nonce.as_u64());
+        }
+        Ok(nonces)
+    }
+}
@@ -0,0 +1,144 @@
+//! Local verification of L1 batch proofs before they are sent to L1.
+
+use std::{fmt, fs, str::FromStr};
+
+use anyhow::Context as _;
+use zksync_types::{
+    aggregated_operations::L1BatchProofOperation,
+    vk_transform::generate_vk_commitment,
+    web3::signing::keccak256,
+    zkevm_test_harness::{
+        abstract_zksync_circuit::concrete_circuits::{
+            ZkSyncCircuit, ZkSyncProof, ZkSyncVerificationKey,
+        },
+        bellman::{
+            bn256::{Bn256, Fr},
+            plonk::better_better_cs::setup::VerificationKey,
+        },
+        ff::to_hex,
+        witness::oracle::VmWitnessOracle,
+    },
+    H256, U256,
+};
+
+/// Number of bits the batch proof public input is shifted by in the L1 `Executor` contract, so that it fits
+/// into a field element.
+const PUBLIC_INPUT_SHIFT: usize = 32;
+
+#[derive(Debug, thiserror::Error)]
+pub(super) enum ProofVerificationError {
+    #[error("proof operation is malformed: {0}")]
+    MalformedOperation(&'static str),
+    #[error("proof has {0} public inputs, expected 1")]
+    PublicInputCount(usize),
+    #[error("proof public input {actual:#x} doesn't match the expected {expected:#x}")]
+    PublicInputMismatch { expected: U256, actual: U256 },
+    #[error("proof is invalid for the configured verification key")]
+    InvalidProof,
+}
+
+/// Verifies SNARK wrapper proofs using the verification key loaded from a file.
+pub struct ProofVerifier {
+    vk: ZkSyncVerificationKey<Bn256>,
+}
+
+impl fmt::Debug for ProofVerifier {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter
+            .debug_struct("ProofVerifier")
+            .finish_non_exhaustive()
+    }
+}
+
+impl ProofVerifier {
+    /// Loads the JSON-serialized SNARK wrapper verification key from the specified path.
+    pub fn from_file(path: &str) -> anyhow::Result<Self> {
+        let text = fs::read_to_string(path)
+            .with_context(|| format!("failed reading verification key from {path}"))?;
+        let vk: VerificationKey<Bn256, ZkSyncCircuit<Bn256, VmWitnessOracle<Bn256>>> =
+            serde_json::from_str(&text)
+                .with_context(|| format!("failed deserializing verification key from {path}"))?;
+        let vk_hash = generate_vk_commitment(vk.clone());
+        tracing::info!("Loaded proof verification key with hash {vk_hash:?} from {path}");
+        Ok(Self {
+            vk: ZkSyncVerificationKey::from_verification_key_and_numeric_type(0, vk),
+        })
+    }
+
+    /// Checks that the proof in `operation` is valid and corresponds to the proven L1 batch.
+    pub(super) fn verify(
+        &self,
+        operation: &L1BatchProofOperation,
+    ) -> Result<(), ProofVerificationError> {
+        let ([l1_batch], [proof]) = (operation.l1_batches.as_slice(), operation.proofs.as_slice())
+        else {
+            return Err(ProofVerificationError::MalformedOperation(
+                "only operations with a single L1 batch and proof can be verified",
+            ));
+        };
+
+        let is_pre_boojum = l1_batch
+            .header
+            .protocol_version
+            .map_or(true, |version| version.is_pre_boojum());
+        // Pre-boojum proofs have a different public input layout, so only the proof itself is verified.
+        if !is_pre_boojum {
+            let expected = expected_public_input(
+                operation.prev_l1_batch.metadata.commitment,
+                l1_batch.metadata.commitment,
+            );
+            check_public_inputs(&proof.scheduler_proof.inputs, expected)?;
+        }
+
+        let proof = ZkSyncProof::from_proof_and_numeric_type(0, proof.scheduler_proof.clone());
+        if self.vk.verify_proof(&proof) {
+            Ok(())
+        } else {
+            Err(ProofVerificationError::InvalidProof)
+        }
+    }
+}
+
+/// Computes the public input of a batch proof in the same way as the L1 `Executor` contract.
+fn expected_public_input(prev_commitment: H256, commitment: H256) -> U256 {
+    let hash = keccak256(&[prev_commitment.as_bytes(), commitment.as_bytes()].concat());
+    U256::from_big_endian(&hash) >> PUBLIC_INPUT_SHIFT
+}
+
+fn check_public_inputs(inputs: &[Fr], expected: U256) -> Result<(), ProofVerificationError> {
+    let [input] = inputs else {
+        return Err(ProofVerificationError::PublicInputCount(inputs.len()));
+    };
+    let input = H256::from_str(&format!("0x{}", to_hex(input))).expect("invalid field element");
+    let actual = U256::from_big_endian(input.as_bytes());
+    if actual == expected {
+        Ok(())
+    } else {
+        Err(ProofVerificationError::PublicInputMismatch { expected, actual })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zksync_types::zkevm_test_harness::bellman::PrimeField;
+
+    use super::*;
+
+    #[test]
+    fn checking_public_inputs() {
+        let expected = expected_public_input(H256::repeat_byte(1), H256::repeat_byte(2));
+        assert!(expected.bits() <= 256 - PUBLIC_INPUT_SHIFT);
+
+        let input = <Fr as PrimeField>::from_str(&expected.to_string()).unwrap();
+        check_public_inputs(&[input], expected).unwrap();
+
+        let err = check_public_inputs(&[<Fr as PrimeField>::from_str("1").unwrap()], expected)
+            .unwrap_err();
+        assert!(
+            matches!(err, ProofVerificationError::PublicInputMismatch { actual, .. } if actual == U256::one()),
+            "{err:?}"
+        );
+        let err = check_public_inputs(&[], expected).unwrap_err();
+        assert!(matches!(err, ProofVerificationError::PublicInputCount(0)));
+    }
+}
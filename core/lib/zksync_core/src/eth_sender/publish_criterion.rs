@@ -4,7 +4,9 @@ use async_trait::async_trait;
 use chrono::Utc;
 use zksync_dal::StorageProcessor;
 use zksync_types::{
-    aggregated_operations::AggregatedActionType, commitment::L1BatchWithMetadata, L1BatchNumber,
+    aggregated_operations::AggregatedActionType,
+    commitment::{L1BatchCommitmentMode, L1BatchWithMetadata},
+    L1BatchNumber,
 };
 
 use super::metrics::METRICS;
@@ -197,6 +199,7 @@ impl L1BatchPublishCriterion for GasCriterion {
 pub struct DataSizeCriterion {
     pub op: AggregatedActionType,
     pub data_limit: usize,
+    pub commitment_mode: L1BatchCommitmentMode,
 }
 
 #[async_trait]
@@ -215,13 +218,12 @@ impl L1BatchPublishCriterion for DataSizeCriterion {
         let mut data_size_left = self.data_limit - STORED_BLOCK_INFO_SIZE;
 
         for (index, l1_batch) in consecutive_l1_batches.iter().enumerate() {
-            if data_size_left < l1_batch.l1_commit_data_size() {
+            let l1_commit_data_size = l1_batch.l1_commit_data_size(self.commitment_mode);
+            if data_size_left < l1_commit_data_size {
                 if index == 0 {
                     panic!(
                         "L1 batch #{} requires {} data, which is more than the range limit of {}",
-                        l1_batch.header.number,
-                        l1_batch.l1_commit_data_size(),
-                        self.data_limit
+                        l1_batch.header.number, l1_commit_data_size, self.data_limit
                     );
                 }
 
@@ -236,7 +238,7 @@ impl L1BatchPublishCriterion for DataSizeCriterion {
                 METRICS.block_aggregation_reason[&(self.op, "data_size").into()].inc();
                 return Some(output);
             }
-            data_size_left -= l1_batch.l1_commit_data_size();
+            data_size_left -= l1_commit_data_size;
         }
 
         None
@@ -0,0 +1,197 @@
+//! Policy for (re)submitting L1 transactions.
+
+use anyhow::Context as _;
+use zksync_config::configs::eth_sender::{FeeEscalationCurve, SenderConfig};
+use zksync_dal::StorageProcessor;
+use zksync_types::api;
+
+use super::{metrics::METRICS, ETHSenderError};
+
+/// Policy determining fees for sent and resubmitted L1 transactions.
+#[derive(Debug, Clone)]
+pub struct ResubmissionPolicy {
+    max_fee_per_gas_cap: Option<u64>,
+    fee_bump_percent: u64,
+    escalation_curve: FeeEscalationCurve,
+    escalation_rate_percent: u64,
+    max_txs_in_flight: u64,
+}
+
+impl ResubmissionPolicy {
+    pub fn new(config: &SenderConfig) -> Self {
+        Self {
+            max_fee_per_gas_cap: config.max_fee_per_gas_cap(),
+            fee_bump_percent: config.resubmission_fee_bump_percent(),
+            escalation_curve: config.resubmission_escalation_curve,
+            escalation_rate_percent: config.resubmission_escalation_rate_percent,
+            max_txs_in_flight: config.max_txs_in_flight,
+        }
+    }
+
+    pub(super) fn max_txs_in_flight(&self) -> u64 {
+        self.max_txs_in_flight
+    }
+
+    pub(super) fn report_metrics(&self) {
+        METRICS
+            .max_fee_per_gas_cap
+            .set(self.max_fee_per_gas_cap.unwrap_or(0));
+        METRICS.fee_bump_percent.set(self.fee_bump_percent);
+        METRICS
+            .escalation_rate_percent
+            .set(self.escalation_rate_percent);
+    }
+
+    /// Returns the minimum priority fee for a transaction replacing one with `previous_priority_fee`.
+    pub(super) fn bump_priority_fee(&self, previous_priority_fee: u64, is_blob_tx: bool) -> u64 {
+        if is_blob_tx {
+            // EIP-4844 transactions can only be replaced if all fees are at least doubled.
+            return previous_priority_fee.saturating_mul(2);
+        }
+        // The bump prevents "replacement transaction under-priced" errors.
+        let bump = previous_priority_fee.saturating_mul(self.fee_bump_percent) / 100;
+        previous_priority_fee.saturating_add(bump).saturating_add(1)
+    }
+
+    /// Escalates the suggested priority fee depending on the number of L1 blocks the transaction has spent in the mempool.
+    pub(super) fn escalate_priority_fee(&self, priority_fee: u64, time_in_mempool: u32) -> u64 {
+        let rate = self.escalation_rate_percent;
+        match self.escalation_curve {
+            FeeEscalationCurve::Constant => priority_fee,
+            FeeEscalationCurve::Linear => {
+                let multiplier_percent = rate
+                    .saturating_mul(time_in_mempool.into())
+                    .saturating_add(100);
+                let escalated = u128::from(priority_fee) * u128::from(multiplier_percent) / 100;
+                escalated.try_into().unwrap_or(u64::MAX)
+            }
+            FeeEscalationCurve::Exponential => {
+                let multiplier = (1.0 + rate as f64 / 100.0).powi(time_in_mempool as i32);
+                // Float-to-int casts are saturating.
+                (priority_fee as f64 * multiplier) as u64
+            }
+        }
+    }
+
+    /// Applies the fee cap to the priority fee. Returns an error if the base fee alone reaches the cap.
+    pub(super) fn cap_priority_fee(
+        &self,
+        base_fee_per_gas: u64,
+        priority_fee_per_gas: u64,
+    ) -> Result<u64, ETHSenderError> {
+        let Some(cap) = self.max_fee_per_gas_cap else {
+            return Ok(priority_fee_per_gas);
+        };
+        if base_fee_per_gas >= cap {
+            METRICS.fee_cap_exceeded.inc();
+            return Err(ETHSenderError::FeeCapExceeded {
+                max_fee_per_gas: base_fee_per_gas.saturating_add(priority_fee_per_gas),
+                cap,
+            });
+        }
+        Ok(priority_fee_per_gas.min(cap - base_fee_per_gas))
+    }
+
+    /// Returns the current policy state together with information about in-flight transactions.
+    pub async fn details(
+        &self,
+        storage: &mut StorageProcessor<'_>,
+    ) -> anyhow::Result<api::ResubmissionPolicyDetails> {
+        let inflight_txs = storage
+            .eth_sender_dal()
+            .get_inflight_txs()
+            .await
+            .context("get_inflight_txs()")?;
+        let mut inflight_tx_details = Vec::with_capacity(inflight_txs.len());
+        for tx in inflight_txs {
+            let history = storage
+                .eth_sender_dal()
+                .get_tx_history_to_check(tx.id)
+                .await
+                .with_context(|| format!("get_tx_history_to_check({})", tx.id))?;
+            let first_sent_at_block = storage
+                .eth_sender_dal()
+                .get_block_number_on_first_sent_attempt(tx.id)
+                .await
+                .with_context(|| format!("get_block_number_on_first_sent_attempt({})", tx.id))?;
+            // History items are ordered starting from the most recent one.
+            let latest_attempt = history.first();
+            inflight_tx_details.push(api::InflightEthTxDetails {
+                id: tx.id,
+                tx_type: tx.tx_type.to_string(),
+                nonce: tx.nonce.0,
+                from_addr: tx.from_addr,
+                attempts: history.len(),
+                first_sent_at_block,
+                base_fee_per_gas: latest_attempt.map(|attempt| attempt.base_fee_per_gas),
+                priority_fee_per_gas: latest_attempt.map(|attempt| attempt.priority_fee_per_gas),
+                blob_base_fee_per_gas: latest_attempt
+                    .and_then(|attempt| attempt.blob_base_fee_per_gas),
+            });
+        }
+
+        Ok(api::ResubmissionPolicyDetails {
+            max_fee_per_gas_cap: self.max_fee_per_gas_cap,
+            fee_bump_percent: self.fee_bump_percent,
+            escalation_curve: match self.escalation_curve {
+                FeeEscalationCurve::Constant => api::FeeEscalationCurve::Constant,
+                FeeEscalationCurve::Linear => api::FeeEscalationCurve::Linear,
+                FeeEscalationCurve::Exponential => api::FeeEscalationCurve::Exponential,
+            },
+            escalation_rate_percent: self.escalation_rate_percent,
+            max_txs_in_flight: self.max_txs_in_flight,
+            inflight_txs: inflight_tx_details,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zksync_config::ETHSenderConfig;
+
+    use super::*;
+
+    fn policy(curve: FeeEscalationCurve, rate: u64, cap_gwei: Option<u64>) -> ResubmissionPolicy {
+        ResubmissionPolicy::new(&SenderConfig {
+            max_fee_per_gas_cap_gwei: cap_gwei,
+            resubmission_escalation_curve: curve,
+            resubmission_escalation_rate_percent: rate,
+            ..ETHSenderConfig::for_tests().sender
+        })
+    }
+
+    #[test]
+    fn bumping_priority_fee() {
+        let policy = policy(FeeEscalationCurve::Constant, 0, None);
+        assert_eq!(policy.bump_priority_fee(100, false), 121);
+        assert_eq!(policy.bump_priority_fee(100, true), 200);
+        assert_eq!(policy.bump_priority_fee(u64::MAX, false), u64::MAX);
+    }
+
+    #[test]
+    fn escalating_priority_fee() {
+        let constant = policy(FeeEscalationCurve::Constant, 10, None);
+        assert_eq!(constant.escalate_priority_fee(1_000, 5), 1_000);
+
+        let linear = policy(FeeEscalationCurve::Linear, 10, None);
+        assert_eq!(linear.escalate_priority_fee(1_000, 0), 1_000);
+        assert_eq!(linear.escalate_priority_fee(1_000, 5), 1_500);
+
+        let exponential = policy(FeeEscalationCurve::Exponential, 100, None);
+        assert_eq!(exponential.escalate_priority_fee(1_000, 0), 1_000);
+        assert_eq!(exponential.escalate_priority_fee(1_000, 3), 8_000);
+        assert_eq!(exponential.escalate_priority_fee(1_000, 100), u64::MAX);
+    }
+
+    #[test]
+    fn capping_priority_fee() {
+        let policy = policy(FeeEscalationCurve::Constant, 0, Some(10));
+        let cap = 10_000_000_000;
+        assert_eq!(policy.cap_priority_fee(cap / 2, 100).unwrap(), 100);
+        assert_eq!(policy.cap_priority_fee(cap - 50, 100).unwrap(), 50);
+        assert!(matches!(
+            policy.cap_priority_fee(cap, 100),
+            Err(ETHSenderError::FeeCapExceeded { .. })
+        ));
+    }
+}
@@ -1,29 +1,37 @@
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc};
 
 use assert_matches::assert_matches;
 use once_cell::sync::Lazy;
+use test_casing::test_casing;
 use zksync_config::{
-    configs::eth_sender::{ProofSendingMode, SenderConfig},
+    configs::eth_sender::{ProofLoadingMode, ProofSendingMode, PubdataSendingMode, SenderConfig},
     ContractsConfig, ETHSenderConfig, GasAdjusterConfig,
 };
+use zksync_contracts::BaseSystemContractsHashes;
 use zksync_dal::{ConnectionPool, StorageProcessor};
-use zksync_eth_client::{clients::MockEthereum, EthInterface};
+use zksync_eth_client::{clients::MockEthereum, BoundEthInterface, EthInterface};
 use zksync_object_store::ObjectStoreFactory;
 use zksync_types::{
     aggregated_operations::{
-        AggregatedOperation, L1BatchCommitOperation, L1BatchExecuteOperation, L1BatchProofOperation,
+        AggregatedActionType, AggregatedOperation, L1BatchCommitOperation, L1BatchExecuteOperation,
+        L1BatchProofForL1, L1BatchProofOperation,
     },
     block::L1BatchHeader,
-    commitment::{L1BatchMetaParameters, L1BatchMetadata, L1BatchWithMetadata},
-    ethabi::Token,
+    commitment::{
+        L1BatchCommitmentMode, L1BatchMetaParameters, L1BatchMetadata, L1BatchWithMetadata,
+    },
+    ethabi::{self, Token},
     helpers::unix_timestamp_ms,
-    web3::contract::Error,
-    Address, L1BatchNumber, L1BlockNumber, ProtocolVersionId, H256,
+    protocol_version::{L1VerifierConfig, ProtocolVersion, VerifierParams},
+    web3::{contract::Error, signing::keccak256},
+    zkevm_test_harness::bellman::plonk::better_better_cs::proof::Proof,
+    Address, L1BatchNumber, L1BlockNumber, ProtocolVersionId, H256, U256,
 };
 
 use crate::{
     eth_sender::{
         eth_tx_manager::L1BlockNumbers, Aggregator, ETHSenderError, EthTxAggregator, EthTxManager,
+        OperatorPool, ProofVerifier,
     },
     l1_gas_price::GasAdjuster,
     utils::testonly::create_l1_batch,
@@ -38,6 +46,7 @@ static DUMMY_OPERATION: Lazy<AggregatedOperation> = Lazy::new(|| {
             header: create_l1_batch(1),
             metadata: default_l1_batch_metadata(),
             factory_deps: Vec::new(),
+            da_inclusion_data: None,
         }],
     })
 });
@@ -60,7 +69,29 @@ impl EthSenderTester {
         history: Vec<u64>,
         non_ordering_confirmations: bool,
     ) -> Self {
-        let eth_sender_config = ETHSenderConfig::for_tests();
+        Self::with_config(
+            connection_pool,
+            history,
+            non_ordering_confirmations,
+            ETHSenderConfig::for_tests().sender,
+            U256::one(),
+            &[],
+        )
+        .await
+    }
+
+    async fn with_config(
+        connection_pool: ConnectionPool,
+        history: Vec<u64>,
+        non_ordering_confirmations: bool,
+        sender_config: SenderConfig,
+        blob_base_fee: U256,
+        additional_operators: &[Address],
+    ) -> Self {
+        let eth_sender_config = ETHSenderConfig {
+            sender: sender_config,
+            ..ETHSenderConfig::for_tests()
+        };
         let contracts_config = ContractsConfig::for_tests();
         let aggregator_config = SenderConfig {
             aggregated_proof_sizes: vec![1],
@@ -75,10 +106,23 @@ impl EthSenderTester {
                         .chain(history)
                         .collect(),
                 )
+                .with_blob_base_fee(blob_base_fee)
                 .with_non_ordering_confirmation(non_ordering_confirmations)
                 .with_multicall_address(contracts_config.l1_multicall3_addr),
         );
         gateway.advance_block_number(Self::WAIT_CONFIRMATIONS);
+        let additional_gateways = additional_operators.iter().map(|&address| {
+            Arc::new(MockEthereum::default().with_sender_account(address))
+                as Arc<dyn BoundEthInterface>
+        });
+        let operators = OperatorPool::new(
+            [gateway.clone() as Arc<dyn BoundEthInterface>]
+                .into_iter()
+                .chain(additional_gateways)
+                .collect(),
+            &eth_sender_config.sender.retired_operator_addresses,
+        )
+        .unwrap();
 
         let gas_adjuster = Arc::new(
             GasAdjuster::new(
@@ -104,20 +148,17 @@ impl EthSenderTester {
             Aggregator::new(
                 aggregator_config.clone(),
                 store_factory.create_store().await,
+                L1BatchCommitmentMode::Rollup,
             ),
-            gateway.clone(),
+            operators.clone(),
             // zkSync contract address
             Address::random(),
             contracts_config.l1_multicall3_addr,
             Address::random(),
-            0,
+            HashMap::new(),
         );
 
-        let manager = EthTxManager::new(
-            eth_sender_config.sender,
-            gas_adjuster.clone(),
-            gateway.clone(),
-        );
+        let manager = EthTxManager::new(eth_sender_config.sender, gas_adjuster.clone(), operators);
         Self {
             gateway,
             manager,
@@ -209,7 +250,7 @@ async fn confirm_many() -> anyhow::Result<()> {
     );
 
     // also check that we didn't try to resend it
-    assert!(to_resend.is_none());
+    assert!(to_resend.is_empty());
 
     Ok(())
 }
@@ -283,6 +324,7 @@ async fn resend_each_block() -> anyhow::Result<()> {
             block_numbers,
         )
         .await?
+        .pop()
         .unwrap();
 
     let resent_hash = tester
@@ -392,7 +434,7 @@ async fn dont_resend_already_mined() -> anyhow::Result<()> {
     );
 
     // also check that we didn't try to resend it
-    assert!(to_resend.is_none());
+    assert!(to_resend.is_empty());
 
     Ok(())
 }
@@ -447,6 +489,7 @@ async fn three_scenarios() -> anyhow::Result<()> {
             tester.get_block_numbers().await,
         )
         .await?
+        .pop()
         .expect("we should be trying to resend the last tx");
 
     // check that last 2 transactions are still considered in-flight
@@ -538,6 +581,7 @@ fn l1_batch_with_metadata(header: L1BatchHeader) -> L1BatchWithMetadata {
         header,
         metadata: default_l1_batch_metadata(),
         factory_deps: vec![],
+        da_inclusion_data: None,
     }
 }
 
@@ -879,6 +923,329 @@ async fn insert_genesis_protocol_version(tester: &EthSenderTester) {
         .await;
 }
 
+#[tokio::test]
+async fn pubdata_is_published_in_calldata_if_protocol_version_does_not_support_blobs() {
+    let connection_pool = ConnectionPool::test_pool().await;
+    let sender_config = SenderConfig {
+        pubdata_sending_mode: PubdataSendingMode::Blobs,
+        blob_base_fee_fallback_threshold_gwei: Some(1),
+        ..ETHSenderConfig::for_tests().sender
+    };
+    let mut tester = EthSenderTester::with_config(
+        connection_pool,
+        vec![100; 100],
+        false,
+        sender_config,
+        1_000.into(),
+        &[],
+    )
+    .await;
+    insert_genesis_protocol_version(&tester).await;
+    let genesis_l1_batch = insert_l1_batch(&tester, L1BatchNumber(0)).await;
+    let first_l1_batch = insert_l1_batch(&tester, L1BatchNumber(1)).await;
+    assert!(!first_l1_batch.protocol_version.unwrap().supports_blobs());
+
+    let first_l1_batch = l1_batch_with_metadata(first_l1_batch);
+    let expected_pubdata = first_l1_batch.pubdata();
+    let operation = AggregatedOperation::Commit(L1BatchCommitOperation {
+        last_committed_l1_batch: l1_batch_with_metadata(genesis_l1_batch),
+        l1_batches: vec![first_l1_batch],
+    });
+    let tx = tester
+        .aggregator
+        .save_eth_tx(&mut tester.storage().await, &operation, false)
+        .await
+        .unwrap();
+    assert!(tx.blob_sidecar.is_none());
+    assert!(tx
+        .raw_tx
+        .windows(expected_pubdata.len())
+        .any(|window| window == expected_pubdata));
+
+    let current_block = L1BlockNumber(tester.gateway.block_number("").await.unwrap().as_u32());
+    tester
+        .manager
+        .send_eth_tx(&mut tester.storage().await, &tx, 0, current_block)
+        .await
+        .unwrap();
+    let tx_history = tester
+        .storage()
+        .await
+        .eth_sender_dal()
+        .get_tx_history_to_check(tx.id)
+        .await
+        .unwrap();
+    assert_eq!(tx_history.len(), 1);
+    assert_eq!(tx_history[0].blob_base_fee_per_gas, None);
+}
+
+#[test]
+fn encoding_commit_data_in_validium_mode() {
+    let mut header = create_l1_batch(1);
+    header.pubdata_input = Some(vec![1; 1_000]);
+    header.protocol_version = Some(ProtocolVersionId::Version20);
+    let mut l1_batch = l1_batch_with_metadata(header);
+
+    let Token::Tuple(rollup_commit_data) = l1_batch
+        .l1_commit_data_for_mode(L1BatchCommitmentMode::Rollup)
+        .unwrap()
+    else {
+        panic!("unexpected commit data");
+    };
+    assert_eq!(
+        rollup_commit_data.last(),
+        Some(&Token::Bytes(vec![1; 1_000]))
+    );
+
+    let Token::Tuple(validium_commit_data) = l1_batch
+        .l1_commit_data_for_mode(L1BatchCommitmentMode::Validium)
+        .unwrap()
+    else {
+        panic!("unexpected commit data");
+    };
+    assert_eq!(validium_commit_data.len(), rollup_commit_data.len());
+    let expected_pubdata_hash = keccak256(&[1; 1_000]).to_vec();
+    assert_eq!(
+        validium_commit_data.last(),
+        Some(&Token::Bytes(expected_pubdata_hash))
+    );
+    assert!(
+        l1_batch.l1_commit_data_size(L1BatchCommitmentMode::Validium)
+            < l1_batch.l1_commit_data_size(L1BatchCommitmentMode::Rollup)
+    );
+
+    // Batches with protocol versions not supporting validium (including pre-boojum ones) cannot be encoded.
+    for protocol_version in [
+        None,
+        Some(ProtocolVersionId::latest()),
+        Some(ProtocolVersionId::last_pre_boojum()),
+    ] {
+        l1_batch.header.protocol_version = protocol_version;
+        l1_batch
+            .l1_commit_data_for_mode(L1BatchCommitmentMode::Validium)
+            .unwrap_err();
+    }
+}
+
+#[tokio::test]
+async fn validium_mode_requires_supporting_contracts() {
+    let store = ObjectStoreFactory::mock().create_store().await;
+    let config = ETHSenderConfig::for_tests().sender;
+    let aggregator = Aggregator::new(config.clone(), store.clone(), L1BatchCommitmentMode::Rollup);
+    aggregator
+        .check_commitment_mode(ProtocolVersionId::latest())
+        .unwrap();
+
+    let aggregator = Aggregator::new(config, store, L1BatchCommitmentMode::Validium);
+    let err = aggregator
+        .check_commitment_mode(ProtocolVersionId::latest())
+        .unwrap_err();
+    assert_matches!(
+        err,
+        ETHSenderError::UnsupportedCommitmentMode {
+            commitment_mode: L1BatchCommitmentMode::Validium,
+            ..
+        }
+    );
+    aggregator
+        .check_commitment_mode(ProtocolVersionId::Version20)
+        .unwrap();
+}
+
+const PRIMARY_OPERATOR: Address = Address::repeat_byte(0x11);
+const SECOND_OPERATOR: Address = Address::repeat_byte(0x22);
+
+async fn create_operations_for_single_l1_batch(
+    tester: &EthSenderTester,
+) -> (AggregatedOperation, AggregatedOperation) {
+    insert_genesis_protocol_version(tester).await;
+    let genesis_l1_batch = insert_l1_batch(tester, L1BatchNumber(0)).await;
+    let first_l1_batch = insert_l1_batch(tester, L1BatchNumber(1)).await;
+
+    let commit_operation = AggregatedOperation::Commit(L1BatchCommitOperation {
+        last_committed_l1_batch: l1_batch_with_metadata(genesis_l1_batch.clone()),
+        l1_batches: vec![l1_batch_with_metadata(first_l1_batch.clone())],
+    });
+    let proof_operation = AggregatedOperation::PublishProofOnchain(L1BatchProofOperation {
+        prev_l1_batch: l1_batch_with_metadata(genesis_l1_batch),
+        l1_batches: vec![l1_batch_with_metadata(first_l1_batch)],
+        proofs: vec![],
+        should_verify: false,
+    });
+    (commit_operation, proof_operation)
+}
+
+#[tokio::test]
+async fn operations_are_sent_from_multiple_operators() {
+    let connection_pool = ConnectionPool::test_pool().await;
+    let mut tester = EthSenderTester::with_config(
+        connection_pool,
+        vec![100; 100],
+        false,
+        ETHSenderConfig::for_tests().sender,
+        U256::one(),
+        &[SECOND_OPERATOR],
+    )
+    .await;
+    let (commit_operation, proof_operation) = create_operations_for_single_l1_batch(&tester).await;
+
+    let commit_tx = tester
+        .aggregator
+        .save_eth_tx(&mut tester.storage().await, &commit_operation, false)
+        .await
+        .unwrap();
+    assert_eq!(commit_tx.from_addr, Some(PRIMARY_OPERATOR));
+    assert_eq!(commit_tx.nonce.0, 0);
+    let proof_tx = tester
+        .aggregator
+        .save_eth_tx(&mut tester.storage().await, &proof_operation, false)
+        .await
+        .unwrap();
+    // Nonces are tracked independently for each operator.
+    assert_eq!(proof_tx.from_addr, Some(SECOND_OPERATOR));
+    assert_eq!(proof_tx.nonce.0, 0);
+
+    let current_block = L1BlockNumber(tester.gateway.block_number("").await.unwrap().as_u32());
+    for tx in [&commit_tx, &proof_tx] {
+        tester
+            .manager
+            .send_eth_tx(&mut tester.storage().await, tx, 0, current_block)
+            .await
+            .unwrap();
+    }
+    assert_eq!(tester.gateway.sent_tx_count(), 2);
+
+    // Both transactions are not mined, so each of them should be resent from its operator.
+    let block_numbers = tester.get_block_numbers().await;
+    let mut to_resend = tester
+        .manager
+        .monitor_inflight_transactions(&mut tester.storage().await, block_numbers)
+        .await
+        .unwrap();
+    to_resend.sort_unstable_by_key(|(tx, _)| tx.id);
+    let to_resend_ids: Vec<_> = to_resend.iter().map(|(tx, _)| tx.id).collect();
+    assert_eq!(to_resend_ids, [commit_tx.id, proof_tx.id]);
+}
+
+#[test_casing(2, [false, true])]
+#[tokio::test]
+async fn proofs_wait_for_confirmed_commits_from_other_operator(separate_operators: bool) {
+    let connection_pool = ConnectionPool::test_pool().await;
+    let additional_operators = if separate_operators {
+        &[SECOND_OPERATOR][..]
+    } else {
+        &[]
+    };
+    let mut tester = EthSenderTester::with_config(
+        connection_pool,
+        vec![100; 100],
+        false,
+        ETHSenderConfig::for_tests().sender,
+        U256::one(),
+        additional_operators,
+    )
+    .await;
+    let (commit_operation, _) = create_operations_for_single_l1_batch(&tester).await;
+    let commit_tx_hash = send_operation(&mut tester, commit_operation, false).await;
+
+    let config = SenderConfig {
+        proof_sending_mode: ProofSendingMode::SkipEveryProof,
+        aggregated_proof_sizes: vec![1],
+        ..ETHSenderConfig::for_tests().sender
+    };
+    let operators = OperatorPool::new(
+        [tester.gateway.clone() as Arc<dyn BoundEthInterface>]
+            .into_iter()
+            .chain(additional_operators.iter().map(|&address| {
+                Arc::new(MockEthereum::default().with_sender_account(address))
+                    as Arc<dyn BoundEthInterface>
+            }))
+            .collect(),
+        &[],
+    )
+    .unwrap();
+    let store = ObjectStoreFactory::mock().create_store().await;
+    let mut aggregator =
+        Aggregator::new(config, store, L1BatchCommitmentMode::Rollup).with_operators(&operators);
+
+    let operation = aggregator
+        .get_next_ready_operation(
+            &mut tester.storage().await,
+            BaseSystemContractsHashes::default(),
+            ProtocolVersionId::latest(),
+            L1VerifierConfig::default(),
+        )
+        .await;
+    if separate_operators {
+        // The proof could be mined before the commit tx, so it must wait.
+        assert!(operation.is_none(), "{operation:?}");
+    } else {
+        assert_matches!(operation, Some(AggregatedOperation::PublishProofOnchain(_)));
+    }
+
+    confirm_tx(&mut tester, commit_tx_hash).await;
+    let operation = aggregator
+        .get_next_ready_operation(
+            &mut tester.storage().await,
+            BaseSystemContractsHashes::default(),
+            ProtocolVersionId::latest(),
+            L1VerifierConfig::default(),
+        )
+        .await;
+    assert_matches!(operation, Some(AggregatedOperation::PublishProofOnchain(op)) if op.l1_batches.len() == 1);
+}
+
+#[tokio::test]
+async fn retired_operator_is_not_used_for_new_operations() {
+    let connection_pool = ConnectionPool::test_pool().await;
+    let sender_config = SenderConfig {
+        retired_operator_addresses: vec![PRIMARY_OPERATOR],
+        ..ETHSenderConfig::for_tests().sender
+    };
+    let mut tester = EthSenderTester::with_config(
+        connection_pool,
+        vec![100; 100],
+        false,
+        sender_config,
+        U256::one(),
+        &[SECOND_OPERATOR],
+    )
+    .await;
+    let (commit_operation, _) = create_operations_for_single_l1_batch(&tester).await;
+
+    // Emulate an in-flight commit transaction sent from the retired operator.
+    let legacy_tx = tester
+        .storage()
+        .await
+        .eth_sender_dal()
+        .save_eth_tx(
+            0,
+            vec![],
+            AggregatedActionType::Commit,
+            Address::random(),
+            0,
+            None,
+            PRIMARY_OPERATOR,
+        )
+        .await
+        .unwrap();
+    let can_send = tester
+        .aggregator
+        .can_send_from_assigned_operator(&mut tester.storage().await, &commit_operation)
+        .await;
+    assert!(!can_send);
+
+    // Nonces of the retired operator don't influence nonces of the active one.
+    let commit_tx = tester
+        .aggregator
+        .save_eth_tx(&mut tester.storage().await, &commit_operation, false)
+        .await
+        .unwrap();
+    assert_eq!(commit_tx.from_addr, Some(SECOND_OPERATOR));
+    assert_eq!(commit_tx.nonce.0, 0);
+    assert_ne!(commit_tx.id, legacy_tx.id);
+}
+
 async fn insert_l1_batch(tester: &EthSenderTester, number: L1BatchNumber) -> L1BatchHeader {
     let header = create_l1_batch(number.0);
 
@@ -989,3 +1356,216 @@ async fn confirm_tx(tester: &mut EthSenderTester, hash: H256) {
         .await
         .unwrap();
 }
+
+#[tokio::test]
+async fn dry_running_commit_transactions() {
+    let connection_pool = ConnectionPool::test_pool().await;
+    let sender_config = SenderConfig {
+        commit_dry_run_enabled: true,
+        ..ETHSenderConfig::for_tests().sender
+    };
+    let mut tester = EthSenderTester::with_config(
+        connection_pool,
+        vec![100; 100],
+        false,
+        sender_config,
+        U256::one(),
+        &[],
+    )
+    .await;
+    let (commit_operation, _) = create_operations_for_single_l1_batch(&tester).await;
+
+    // `Error(string)` revert data
+    let mut revert_data = vec![0x08, 0xc3, 0x79, 0xa0];
+    revert_data.extend(ethabi::encode(&[Token::String(
+        "Incorrect protocol version".to_owned(),
+    )]));
+    tester.gateway.set_call_revert_data(Some(revert_data));
+    let err = tester
+        .aggregator
+        .save_eth_tx(&mut tester.storage().await, &commit_operation, false)
+        .await
+        .unwrap_err();
+    assert_matches!(
+        err,
+        ETHSenderError::CommitDryRunFailed { l1_batches, reason, .. }
+            if l1_batches == (L1BatchNumber(1)..=L1BatchNumber(1))
+                && reason.contains("Incorrect protocol version")
+    );
+    let call_requests = tester.gateway.call_requests();
+    assert_eq!(call_requests.len(), 1);
+    assert_eq!(call_requests[0].from, Some(PRIMARY_OPERATOR));
+    let unconfirmed_senders = tester
+        .storage()
+        .await
+        .eth_sender_dal()
+        .get_unconfirmed_tx_senders(AggregatedActionType::Commit)
+        .await
+        .unwrap();
+    assert!(unconfirmed_senders.is_empty());
+
+    tester.gateway.set_call_revert_data(None);
+    let commit_tx = tester
+        .aggregator
+        .save_eth_tx(&mut tester.storage().await, &commit_operation, false)
+        .await
+        .unwrap();
+    assert_eq!(commit_tx.from_addr, Some(PRIMARY_OPERATOR));
+    assert_eq!(tester.gateway.call_requests().len(), 2);
+
+    // The next commit cannot be simulated until the previous one is sent, since the pending L1 state
+    // doesn't reflect it yet.
+    let second_l1_batch = insert_l1_batch(&tester, L1BatchNumber(2)).await;
+    let AggregatedOperation::Commit(first_commit) = &commit_operation else {
+        unreachable!();
+    };
+    let commit_operation = AggregatedOperation::Commit(L1BatchCommitOperation {
+        last_committed_l1_batch: first_commit.l1_batches[0].clone(),
+        l1_batches: vec![l1_batch_with_metadata(second_l1_batch)],
+    });
+    let err = tester
+        .aggregator
+        .save_eth_tx(&mut tester.storage().await, &commit_operation, false)
+        .await
+        .unwrap_err();
+    assert_matches!(
+        err,
+        ETHSenderError::CommitDryRunDeferred { l1_batches }
+            if l1_batches == (L1BatchNumber(2)..=L1BatchNumber(2))
+    );
+    assert_eq!(tester.gateway.call_requests().len(), 2);
+
+    // Once the previous commit is sent, the next one is simulated against the pending state even though
+    // the previous commit is unconfirmed.
+    let mut storage = tester.storage().await;
+    let history_id = storage
+        .eth_sender_dal()
+        .insert_tx_history(commit_tx.id, 100, 100, None, H256::repeat_byte(1), &[])
+        .await
+        .unwrap()
+        .unwrap();
+    storage
+        .eth_sender_dal()
+        .set_sent_at_block(history_id, 1)
+        .await
+        .unwrap();
+    drop(storage);
+    tester
+        .aggregator
+        .save_eth_tx(&mut tester.storage().await, &commit_operation, false)
+        .await
+        .unwrap();
+    assert_eq!(tester.gateway.call_requests().len(), 3);
+}
+
+const VERIFICATION_KEY_PATH: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/../../../prover/vk_setup_data_generator_server_fri/data/snark_verification_scheduler_key.json"
+);
+
+#[tokio::test]
+async fn invalid_proofs_are_rejected() {
+    let connection_pool = ConnectionPool::test_pool().await;
+    let tester = EthSenderTester::new(connection_pool, vec![100; 100], false).await;
+    let verifier = ProofVerifier::from_file(VERIFICATION_KEY_PATH).unwrap();
+    let aggregator = tester.aggregator.with_proof_verifier(verifier);
+
+    let mut operation = L1BatchProofOperation {
+        prev_l1_batch: l1_batch_with_metadata(create_l1_batch(0)),
+        l1_batches: vec![l1_batch_with_metadata(create_l1_batch(1))],
+        proofs: vec![],
+        should_verify: true,
+    };
+    let err = aggregator.verify_proof(&operation).unwrap_err();
+    assert_matches!(err, ETHSenderError::ProofVerificationFailed { .. });
+
+    // The proof doesn't have the public input corresponding to the proven L1 batch.
+    operation.proofs.push(L1BatchProofForL1 {
+        aggregation_result_coords: [[0; 32]; 4],
+        scheduler_proof: Proof::empty(),
+    });
+    let err = aggregator.verify_proof(&operation).unwrap_err();
+    assert_matches!(
+        err,
+        ETHSenderError::ProofVerificationFailed { l1_batches, reason }
+            if l1_batches == (L1BatchNumber(1)..=L1BatchNumber(1)) && reason.contains("public inputs")
+    );
+
+    // Dummy proofs are not verified.
+    operation.should_verify = false;
+    aggregator.verify_proof(&operation).unwrap();
+}
+
+#[tokio::test]
+async fn invalid_proofs_are_not_sent() {
+    let connection_pool = ConnectionPool::test_pool().await;
+    let mut tester = EthSenderTester::new(connection_pool, vec![100; 100], false).await;
+    // The verifier config must match the one returned by the mock L1 contracts; otherwise, the proof isn't loaded.
+    let l1_verifier_config = L1VerifierConfig {
+        params: VerifierParams {
+            recursion_node_level_vk_hash: H256::repeat_byte(3),
+            recursion_leaf_level_vk_hash: H256::repeat_byte(3),
+            recursion_circuits_set_vks_hash: H256::repeat_byte(3),
+        },
+        recursion_scheduler_level_vk_hash: H256::zero(),
+    };
+    tester
+        .storage()
+        .await
+        .protocol_versions_dal()
+        .save_protocol_version_with_tx(ProtocolVersion {
+            l1_verifier_config,
+            ..ProtocolVersion::default()
+        })
+        .await;
+    let genesis_l1_batch = insert_l1_batch(&tester, L1BatchNumber(0)).await;
+    let first_l1_batch = insert_l1_batch(&tester, L1BatchNumber(1)).await;
+    commit_l1_batch(&mut tester, genesis_l1_batch, first_l1_batch, false).await;
+
+    let store = ObjectStoreFactory::mock().create_store().await;
+    let invalid_proof = L1BatchProofForL1 {
+        aggregation_result_coords: [[0; 32]; 4],
+        scheduler_proof: Proof::empty(),
+    };
+    store.put(L1BatchNumber(1), &invalid_proof).await.unwrap();
+    let config = SenderConfig {
+        proof_sending_mode: ProofSendingMode::OnlyRealProofs,
+        proof_loading_mode: ProofLoadingMode::FriProofFromGcs,
+        aggregated_proof_sizes: vec![1],
+        ..ETHSenderConfig::for_tests().sender
+    };
+    let operators = OperatorPool::new(
+        vec![tester.gateway.clone() as Arc<dyn BoundEthInterface>],
+        &[],
+    )
+    .unwrap();
+    let verifier = ProofVerifier::from_file(VERIFICATION_KEY_PATH).unwrap();
+    let mut aggregator = EthTxAggregator::new(
+        config.clone(),
+        Aggregator::new(config, store, L1BatchCommitmentMode::Rollup),
+        operators,
+        Address::random(),
+        ContractsConfig::for_tests().l1_multicall3_addr,
+        Address::random(),
+        HashMap::new(),
+    )
+    .with_proof_verifier(verifier);
+
+    let err = aggregator
+        .loop_iteration(&mut tester.storage().await)
+        .await
+        .unwrap_err();
+    assert_matches!(
+        err,
+        ETHSenderError::ProofVerificationFailed { l1_batches, .. }
+            if l1_batches == (L1BatchNumber(1)..=L1BatchNumber(1))
+    );
+    let last_proven_l1_batch = tester
+        .storage()
+        .await
+        .blocks_dal()
+        .get_last_l1_batch_with_prove_tx()
+        .await
+        .unwrap();
+    assert_eq!(last_proven_l1_batch, L1BatchNumber(0));
+}
@@ -2,7 +2,7 @@ use zksync_contracts::{
     multicall_contract, verifier_contract, zksync_contract, PRE_BOOJUM_COMMIT_FUNCTION,
     PRE_BOOJUM_EXECUTE_FUNCTION, PRE_BOOJUM_GET_VK_FUNCTION, PRE_BOOJUM_PROVE_FUNCTION,
 };
-use zksync_types::ethabi::{Contract, Function};
+use zksync_types::ethabi::{AbiError, Contract, Function};
 
 #[derive(Debug)]
 pub(super) struct ZkSyncFunctions {
@@ -17,6 +17,8 @@ pub(super) struct ZkSyncFunctions {
     pub(super) get_verifier: Function,
     pub(super) get_verifier_params: Function,
     pub(super) get_protocol_version: Function,
+    /// Custom errors declared in the main zkSync contract; used to decode revert reasons.
+    pub(super) zksync_contract_errors: Vec<AbiError>,
 
     pub(super) verifier_contract: Contract,
     pub(super) get_verification_key: Function,
@@ -63,6 +65,7 @@ impl Default for ZkSyncFunctions {
         let get_verifier = get_function(&zksync_contract, "getVerifier");
         let get_verifier_params = get_function(&zksync_contract, "getVerifierParams");
         let get_protocol_version = get_function(&zksync_contract, "getProtocolVersion");
+        let zksync_contract_errors = zksync_contract.errors.values().flatten().cloned().collect();
         let get_verification_key = PRE_BOOJUM_GET_VK_FUNCTION.clone();
         let aggregate3 = get_function(&multicall_contract, "aggregate3");
         let verification_key_hash =
@@ -80,6 +83,7 @@ impl Default for ZkSyncFunctions {
             get_verifier,
             get_verifier_params,
             get_protocol_version,
+            zksync_contract_errors,
             verifier_contract,
             get_verification_key,
             verification_key_hash,
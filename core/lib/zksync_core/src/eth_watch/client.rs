@@ -1,6 +1,7 @@
 use std::fmt;
 
 use zksync_contracts::verifier_contract;
+use zksync_dal::SqlxError;
 use zksync_eth_client::{CallFunctionArgs, Error as EthClientError, EthInterface};
 use zksync_types::{
     ethabi::{Contract, Token},
@@ -10,7 +11,7 @@ use zksync_types::{
         contract::tokens::Detokenize,
         types::{BlockId, BlockNumber, FilterBuilder, Log},
     },
-    Address, H256,
+    Address, PriorityOpId, H256,
 };
 
 use super::metrics::METRICS;
@@ -23,6 +24,16 @@ pub enum Error {
     EthClient(#[from] EthClientError),
     #[error("Infinite recursion caused by too many responses")]
     InfiniteRecursion,
+    #[error(
+        "Priority operation #{serial_id} has hash {received:?} on L1, but {stored:?} is stored"
+    )]
+    PriorityOpMismatch {
+        serial_id: PriorityOpId,
+        stored: H256,
+        received: H256,
+    },
+    #[error("Storage error: {0}")]
+    Storage(#[from] SqlxError),
 }
 
 impl From<web3::contract::Error> for Error {
@@ -42,6 +53,8 @@ pub trait EthClient: 'static + fmt::Debug + Send + Sync {
     ) -> Result<Vec<Log>, Error>;
     /// Returns finalized L1 block number.
     async fn finalized_block_number(&self) -> Result<u64, Error>;
+    /// Returns the hash of the L1 block with the specified number, or `None` if the block is not present.
+    async fn block_hash(&self, number: u64) -> Result<Option<H256>, Error>;
     /// Returns scheduler verification key hash by verifier address.
     async fn scheduler_vk_hash(&self, verifier_address: Address) -> Result<H256, Error>;
     /// Sets list of topics to return events for.
@@ -228,6 +241,14 @@ impl EthClient for EthHttpQueryClient {
         }
     }
 
+    async fn block_hash(&self, number: u64) -> Result<Option<H256>, Error> {
+        let block = self
+            .client
+            .block(BlockId::Number(BlockNumber::Number(number.into())), "watch")
+            .await?;
+        Ok(block.and_then(|block| block.hash))
+    }
+
     fn set_topics(&mut self, topics: Vec<H256>) {
         self.topics = topics;
     }
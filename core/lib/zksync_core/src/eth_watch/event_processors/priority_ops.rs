@@ -68,10 +68,27 @@ impl EventProcessor for PriorityOpsEventProcessor {
             "There is a gap in priority ops received"
         );
 
-        let new_ops: Vec<_> = priority_ops
+        let (processed_ops, new_ops): (Vec<_>, Vec<_>) = priority_ops
             .into_iter()
-            .skip_while(|tx| tx.serial_id() < self.next_expected_priority_id)
-            .collect();
+            .partition(|tx| tx.serial_id() < self.next_expected_priority_id);
+        // Already processed operations can be re-fetched, e.g. after an L1 reorg. They must not be
+        // processed again, and must not change their contents.
+        for op in &processed_ops {
+            let serial_id = op.serial_id();
+            let stored_hash = storage
+                .transactions_dal()
+                .get_priority_op_hash(serial_id)
+                .await;
+            if let Some(stored) = stored_hash {
+                if stored != op.hash() {
+                    return Err(Error::PriorityOpMismatch {
+                        serial_id,
+                        stored,
+                        received: op.hash(),
+                    });
+                }
+            }
+        }
         if new_ops.is_empty() {
             return Ok(());
         }
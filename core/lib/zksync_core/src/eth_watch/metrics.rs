@@ -2,7 +2,9 @@
 
 use std::time::Duration;
 
-use vise::{Buckets, Counter, EncodeLabelSet, EncodeLabelValue, Family, Histogram, Metrics};
+use vise::{
+    Buckets, Counter, EncodeLabelSet, EncodeLabelValue, Family, Gauge, Histogram, Metrics, Unit,
+};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EncodeLabelValue, EncodeLabelSet)]
 #[metrics(label = "stage", rename_all = "snake_case")]
@@ -20,7 +22,27 @@ pub(super) struct EthWatcherMetrics {
     pub poll_eth_node: Family<PollStage, Histogram<Duration>>,
     #[metrics(buckets = Buckets::LATENCIES)]
     pub get_priority_op_events: Histogram<Duration>,
+    /// Number of detected L1 reorgs affecting processed blocks.
+    pub l1_reorgs: Counter,
 }
 
+#[derive(Debug, Metrics)]
+#[metrics(prefix = "server_priority_ops")]
+pub(super) struct PriorityOpsMetrics {
+    /// Latency between receiving a priority operation from L1 and including it into a miniblock.
+    #[metrics(buckets = Buckets::exponential(1.0..=3_600.0, 2.0), unit = Unit::Seconds)]
+    pub inclusion_latency: Histogram<Duration>,
+    /// Number of priority operations received from L1, but not included into a miniblock yet.
+    pub pending: Gauge<usize>,
+    /// Age of the oldest pending priority operation.
+    #[metrics(unit = Unit::Seconds)]
+    pub oldest_pending_age: Gauge<Duration>,
+    /// Serial ID of the last priority operation included into a miniblock.
+    pub last_included_serial_id: Gauge<u64>,
+}
+
+#[vise::register]
+pub(super) static PRIORITY_OPS_METRICS: vise::Global<PriorityOpsMetrics> = vise::Global::new();
+
 #[vise::register]
 pub(super) static METRICS: vise::Global<EthWatcherMetrics> = vise::Global::new();
@@ -3,8 +3,11 @@
 //!
 //! Poll interval is configured using the `ETH_POLL_INTERVAL` constant.
 //! Number of confirmations is configured using the `CONFIRMATIONS_FOR_ETH_EVENT` environment variable.
+//!
+//! Hashes of processed L1 blocks are persisted, so that L1 reorgs can be detected. If a reorg is detected,
+//! the watcher rewinds to the last block unaffected by it and re-fetches events from there.
 
-use std::time::Duration;
+use std::{collections::BTreeMap, time::Duration};
 
 use tokio::{sync::watch, task::JoinHandle};
 use zksync_config::ETHWatchConfig;
@@ -12,10 +15,12 @@ use zksync_dal::{ConnectionPool, StorageProcessor};
 use zksync_eth_client::EthInterface;
 use zksync_system_constants::PRIORITY_EXPIRATION;
 use zksync_types::{
-    ethabi::Contract, web3::types::BlockNumber as Web3BlockNumber, Address, PriorityOpId,
-    ProtocolVersionId,
+    ethabi::Contract,
+    web3::types::{BlockNumber as Web3BlockNumber, Log},
+    Address, PriorityOpId, ProtocolVersionId,
 };
 
+pub use self::priority_ops_monitor::PriorityOpsMonitor;
 use self::{
     client::{Error, EthClient, EthHttpQueryClient, RETRY_LIMIT},
     event_processors::{
@@ -28,6 +33,7 @@ use self::{
 mod client;
 mod event_processors;
 mod metrics;
+mod priority_ops_monitor;
 #[cfg(test)]
 mod tests;
 
@@ -43,6 +49,7 @@ pub struct EthWatch {
     client: Box<dyn EthClient>,
     poll_interval: Duration,
     event_processors: Vec<Box<dyn EventProcessor>>,
+    reorg_detection_window: u64,
 
     last_processed_ethereum_block: u64,
 }
@@ -54,6 +61,7 @@ impl EthWatch {
         mut client: Box<dyn EthClient>,
         pool: &ConnectionPool,
         poll_interval: Duration,
+        reorg_detection_window: u64,
     ) -> Self {
         let mut storage = pool.access_storage_tagged("eth_watch").await.unwrap();
 
@@ -88,6 +96,7 @@ impl EthWatch {
             client,
             poll_interval,
             event_processors,
+            reorg_detection_window,
             last_processed_ethereum_block: state.last_processed_ethereum_block,
         }
     }
@@ -162,6 +171,8 @@ impl EthWatch {
 
     #[tracing::instrument(skip(self, storage))]
     async fn loop_iteration(&mut self, storage: &mut StorageProcessor<'_>) -> Result<(), Error> {
+        self.handle_reorg(storage).await?;
+
         let stage_latency = METRICS.poll_eth_node[&PollStage::Request].start();
         let to_block = self.client.finalized_block_number().await?;
         if to_block <= self.last_processed_ethereum_block {
@@ -183,9 +194,76 @@ impl EthWatch {
                 .process_events(storage, &*self.client, events.clone())
                 .await?;
         }
+        self.record_processed_blocks(storage, &events, to_block)
+            .await?;
         self.last_processed_ethereum_block = to_block;
         Ok(())
     }
+
+    /// Checks whether recently processed L1 blocks were reorged. If they were, rewinds the watcher
+    /// to the last block not affected by the reorg, so that events from the following blocks are re-fetched.
+    async fn handle_reorg(&mut self, storage: &mut StorageProcessor<'_>) -> Result<(), Error> {
+        // Blocks are ordered starting from the most recent one.
+        let blocks = storage.eth_watcher_dal().get_blocks().await?;
+        let (Some(&(latest_block, _)), Some(&(oldest_block, _))) = (blocks.first(), blocks.last())
+        else {
+            return Ok(());
+        };
+
+        let mut common_block = None;
+        for &(number, hash) in &blocks {
+            if self.client.block_hash(number).await? == Some(hash) {
+                common_block = Some(number);
+                break;
+            }
+        }
+        if common_block == Some(latest_block) {
+            return Ok(());
+        }
+
+        // If none of the recorded blocks are on the canonical chain, the reorg is deeper than the detection window.
+        let rewind_to = common_block.unwrap_or_else(|| oldest_block.saturating_sub(1));
+        tracing::warn!(
+            "Detected L1 reorg affecting processed blocks after #{rewind_to} (latest processed block: #{latest_block}); \
+             re-fetching events"
+        );
+        METRICS.l1_reorgs.inc();
+        storage
+            .eth_watcher_dal()
+            .delete_blocks_after(rewind_to)
+            .await?;
+        self.last_processed_ethereum_block = self.last_processed_ethereum_block.min(rewind_to);
+        Ok(())
+    }
+
+    /// Records hashes of L1 blocks with processed events and of the last processed block, and prunes blocks
+    /// outside the reorg detection window.
+    async fn record_processed_blocks(
+        &self,
+        storage: &mut StorageProcessor<'_>,
+        events: &[Log],
+        to_block: u64,
+    ) -> Result<(), Error> {
+        // Hashes of event blocks are taken from the events themselves, so that they correspond to the processed data
+        // even if L1 was reorged after the events were fetched.
+        let mut block_hashes: BTreeMap<_, _> = events
+            .iter()
+            .filter_map(|event| Some((event.block_number?.as_u64(), event.block_hash?)))
+            .collect();
+        if !block_hashes.contains_key(&to_block) {
+            if let Some(hash) = self.client.block_hash(to_block).await? {
+                block_hashes.insert(to_block, hash);
+            }
+        }
+
+        let mut dal = storage.eth_watcher_dal();
+        for (number, hash) in block_hashes {
+            dal.insert_block(number, hash).await?;
+        }
+        dal.delete_blocks_before(to_block.saturating_sub(self.reorg_detection_window))
+            .await?;
+        Ok(())
+    }
 }
 
 pub async fn start_eth_watch(
@@ -209,6 +287,7 @@ pub async fn start_eth_watch(
         Box::new(eth_client),
         &pool,
         config.poll_interval(),
+        config.reorg_detection_window(),
     )
     .await;
 
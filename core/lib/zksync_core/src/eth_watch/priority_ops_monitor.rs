@@ -0,0 +1,206 @@
+//! Monitoring of L1 -> L2 priority operations from their receipt from L1 to inclusion on L2.
+
+use std::time::Duration;
+
+use anyhow::Context as _;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use tokio::sync::watch;
+use zksync_config::ETHWatchConfig;
+use zksync_dal::{priority_ops_dal::PendingPriorityOp, ConnectionPool};
+use zksync_health_check::{Health, HealthStatus, HealthUpdater, ReactiveHealthCheck};
+use zksync_types::{MiniblockNumber, H256};
+
+use super::metrics::PRIORITY_OPS_METRICS;
+
+/// Information about the oldest pending priority operation included into health details.
+#[derive(Debug, Serialize)]
+struct PendingOpDetails {
+    serial_id: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    l1_tx_hash: Option<H256>,
+    received_at: DateTime<Utc>,
+    age_sec: u64,
+}
+
+#[derive(Debug, Serialize)]
+struct PriorityOpsHealthDetails {
+    pending_ops: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    oldest_pending_op: Option<PendingOpDetails>,
+}
+
+/// Tracks priority operations received from L1, reports their inclusion latency and raises a health alert
+/// if an operation isn't included into a miniblock before the configured deadline.
+#[derive(Debug)]
+pub struct PriorityOpsMonitor {
+    pool: ConnectionPool,
+    poll_interval: Duration,
+    deadline: Duration,
+    health_updater: HealthUpdater,
+    /// Last miniblock for which inclusion latencies were reported.
+    last_processed_miniblock: Option<MiniblockNumber>,
+}
+
+impl PriorityOpsMonitor {
+    pub fn new(pool: ConnectionPool, config: &ETHWatchConfig) -> Self {
+        let (_, health_updater) = ReactiveHealthCheck::new("priority_ops");
+        Self {
+            pool,
+            poll_interval: config.poll_interval(),
+            deadline: config.priority_op_deadline(),
+            health_updater,
+            last_processed_miniblock: None,
+        }
+    }
+
+    /// Returns a health check for this monitor.
+    pub fn health_check(&self) -> ReactiveHealthCheck {
+        self.health_updater.subscribe()
+    }
+
+    pub async fn run(mut self, stop_receiver: watch::Receiver<bool>) -> anyhow::Result<()> {
+        let mut timer = tokio::time::interval(self.poll_interval);
+        while !*stop_receiver.borrow() {
+            timer.tick().await;
+            if let Err(err) = self.loop_iteration().await {
+                tracing::warn!("Failed monitoring priority operations: {err:#}");
+            }
+        }
+        tracing::info!("Stop signal received, priority operations monitor is shutting down");
+        Ok(())
+    }
+
+    async fn loop_iteration(&mut self) -> anyhow::Result<()> {
+        let mut storage = self.pool.access_storage_tagged("eth_watch").await?;
+        let last_miniblock = storage
+            .blocks_dal()
+            .get_sealed_miniblock_number()
+            .await
+            .context("get_sealed_miniblock_number()")?;
+        // On start, only operations included after the monitor has started are reported.
+        let first_miniblock = self
+            .last_processed_miniblock
+            .map_or(last_miniblock, |number| number + 1);
+        if first_miniblock <= last_miniblock {
+            let included_ops = storage
+                .priority_ops_dal()
+                .get_included_priority_ops(first_miniblock..=last_miniblock)
+                .await
+                .context("get_included_priority_ops()")?;
+            for op in &included_ops {
+                let latency = (op.included_at - op.received_at)
+                    .to_std()
+                    .unwrap_or_default();
+                PRIORITY_OPS_METRICS.inclusion_latency.observe(latency);
+                tracing::debug!(
+                    "Priority operation #{} was included into miniblock #{} in {latency:?}",
+                    op.serial_id.0,
+                    op.miniblock_number
+                );
+            }
+            if let Some(op) = included_ops.last() {
+                PRIORITY_OPS_METRICS
+                    .last_included_serial_id
+                    .set(op.serial_id.0);
+            }
+            self.last_processed_miniblock = Some(last_miniblock);
+        }
+
+        let pending_ops = storage
+            .priority_ops_dal()
+            .get_pending_priority_ops_count()
+            .await
+            .context("get_pending_priority_ops_count()")?;
+        let oldest_pending_op = storage
+            .priority_ops_dal()
+            .get_first_pending_priority_op()
+            .await
+            .context("get_first_pending_priority_op()")?;
+        drop(storage);
+
+        PRIORITY_OPS_METRICS.pending.set(pending_ops);
+        let health = self.health(pending_ops, oldest_pending_op, Utc::now());
+        self.health_updater.update(health);
+        Ok(())
+    }
+
+    fn health(
+        &self,
+        pending_ops: usize,
+        oldest_pending_op: Option<PendingPriorityOp>,
+        now: DateTime<Utc>,
+    ) -> Health {
+        let oldest_pending_op = oldest_pending_op.map(|op| {
+            let age = (now - op.received_at).to_std().unwrap_or_default();
+            (op, age)
+        });
+        let oldest_age = oldest_pending_op
+            .as_ref()
+            .map_or(Duration::ZERO, |(_, age)| *age);
+        PRIORITY_OPS_METRICS.oldest_pending_age.set(oldest_age);
+
+        let is_overdue = oldest_age > self.deadline;
+        if let Some((op, age)) = &oldest_pending_op {
+            if is_overdue {
+                tracing::warn!(
+                    "Priority operation #{} (L1 tx: {:?}) is pending for {age:?}, exceeding the deadline {:?}",
+                    op.serial_id.0,
+                    op.l1_tx_hash,
+                    self.deadline
+                );
+            }
+        }
+
+        let details = PriorityOpsHealthDetails {
+            pending_ops,
+            oldest_pending_op: oldest_pending_op.map(|(op, age)| PendingOpDetails {
+                serial_id: op.serial_id.0,
+                l1_tx_hash: op.l1_tx_hash,
+                received_at: op.received_at,
+                age_sec: age.as_secs(),
+            }),
+        };
+        let status = if is_overdue {
+            HealthStatus::Affected
+        } else {
+            HealthStatus::Ready
+        };
+        Health::from(status).with_details(details)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zksync_types::PriorityOpId;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn health_is_affected_for_overdue_ops() {
+        let pool = ConnectionPool::test_pool().await;
+        let config = ETHWatchConfig {
+            confirmations_for_eth_event: None,
+            eth_node_poll_interval: 100,
+            reorg_detection_window: None,
+            priority_op_deadline_sec: Some(60),
+        };
+        let monitor = PriorityOpsMonitor::new(pool, &config);
+        let now = Utc::now();
+
+        let health = monitor.health(0, None, now);
+        assert_eq!(health.status(), HealthStatus::Ready);
+
+        let op = PendingPriorityOp {
+            serial_id: PriorityOpId(1),
+            l1_tx_hash: Some(H256::repeat_byte(1)),
+            received_at: now - chrono::Duration::seconds(30),
+        };
+        let health = monitor.health(1, Some(op.clone()), now);
+        assert_eq!(health.status(), HealthStatus::Ready);
+
+        let later = now + chrono::Duration::seconds(60);
+        let health = monitor.health(1, Some(op), later);
+        assert_eq!(health.status(), HealthStatus::Affected);
+    }
+}
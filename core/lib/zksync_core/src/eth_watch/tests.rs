@@ -23,6 +23,7 @@ struct FakeEthClientData {
     diamond_upgrades: HashMap<u64, Vec<Log>>,
     governance_upgrades: HashMap<u64, Vec<Log>>,
     last_finalized_block_number: u64,
+    reorged_from_block: Option<u64>,
 }
 
 impl FakeEthClientData {
@@ -32,6 +33,7 @@ impl FakeEthClientData {
             diamond_upgrades: Default::default(),
             governance_upgrades: Default::default(),
             last_finalized_block_number: 0,
+            reorged_from_block: None,
         }
     }
 
@@ -66,6 +68,25 @@ impl FakeEthClientData {
     fn set_last_finalized_block_number(&mut self, number: u64) {
         self.last_finalized_block_number = number;
     }
+
+    fn block_hash(&self, number: u64) -> H256 {
+        match self.reorged_from_block {
+            Some(reorged_from_block) if number >= reorged_from_block => {
+                H256::from_low_u64_le(number)
+            }
+            _ => H256::from_low_u64_be(number),
+        }
+    }
+
+    /// Replaces blocks starting from `first_block` with new ones that have no events.
+    fn reorg(&mut self, first_block: u64) {
+        self.transactions.retain(|&number, _| number < first_block);
+        self.diamond_upgrades
+            .retain(|&number, _| number < first_block);
+        self.governance_upgrades
+            .retain(|&number, _| number < first_block);
+        self.reorged_from_block = Some(first_block);
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -92,6 +113,10 @@ impl FakeEthClient {
         self.inner.write().await.add_governance_upgrades(upgrades);
     }
 
+    async fn reorg(&mut self, first_block: u64) {
+        self.inner.write().await.reorg(first_block);
+    }
+
     async fn set_last_finalized_block_number(&mut self, number: u64) {
         self.inner
             .write()
@@ -122,16 +147,21 @@ impl EthClient for FakeEthClient {
         let from = self.block_to_number(from).await;
         let to = self.block_to_number(to).await;
         let mut logs = vec![];
+        let inner = self.inner.read().await;
         for number in from..=to {
-            if let Some(ops) = self.inner.read().await.transactions.get(&number) {
+            let block_logs_start = logs.len();
+            if let Some(ops) = inner.transactions.get(&number) {
                 logs.extend_from_slice(ops);
             }
-            if let Some(ops) = self.inner.read().await.diamond_upgrades.get(&number) {
+            if let Some(ops) = inner.diamond_upgrades.get(&number) {
                 logs.extend_from_slice(ops);
             }
-            if let Some(ops) = self.inner.read().await.governance_upgrades.get(&number) {
+            if let Some(ops) = inner.governance_upgrades.get(&number) {
                 logs.extend_from_slice(ops);
             }
+            for log in &mut logs[block_logs_start..] {
+                log.block_hash = Some(inner.block_hash(number));
+            }
         }
         Ok(logs)
     }
@@ -145,6 +175,10 @@ impl EthClient for FakeEthClient {
     async fn finalized_block_number(&self) -> Result<u64, Error> {
         Ok(self.inner.read().await.last_finalized_block_number)
     }
+
+    async fn block_hash(&self, number: u64) -> Result<Option<H256>, Error> {
+        Ok(Some(self.inner.read().await.block_hash(number)))
+    }
 }
 
 fn build_l1_tx(serial_id: u64, eth_block: u64) -> L1Tx {
@@ -212,6 +246,7 @@ async fn test_normal_operation_l1_txs() {
         Box::new(client.clone()),
         &connection_pool,
         std::time::Duration::from_nanos(1),
+        100,
     )
     .await;
 
@@ -260,6 +295,7 @@ async fn test_normal_operation_upgrades() {
         Box::new(client.clone()),
         &connection_pool,
         std::time::Duration::from_nanos(1),
+        100,
     )
     .await;
 
@@ -321,6 +357,7 @@ async fn test_gap_in_upgrades() {
         Box::new(client.clone()),
         &connection_pool,
         std::time::Duration::from_nanos(1),
+        100,
     )
     .await;
 
@@ -360,6 +397,7 @@ async fn test_normal_operation_governance_upgrades() {
         Box::new(client.clone()),
         &connection_pool,
         std::time::Duration::from_nanos(1),
+        100,
     )
     .await;
 
@@ -422,6 +460,7 @@ async fn test_gap_in_single_batch() {
         Box::new(client.clone()),
         &connection_pool,
         std::time::Duration::from_nanos(1),
+        100,
     )
     .await;
 
@@ -452,6 +491,7 @@ async fn test_gap_between_batches() {
         Box::new(client.clone()),
         &connection_pool,
         std::time::Duration::from_nanos(1),
+        100,
     )
     .await;
 
@@ -487,6 +527,7 @@ async fn test_overlapping_batches() {
         Box::new(client.clone()),
         &connection_pool,
         std::time::Duration::from_nanos(1),
+        100,
     )
     .await;
 
@@ -523,6 +564,99 @@ async fn test_overlapping_batches() {
     assert_eq!(tx.common_data.serial_id.0, 4);
 }
 
+#[tokio::test]
+async fn events_are_refetched_after_l1_reorg() {
+    let connection_pool = ConnectionPool::test_pool().await;
+    setup_db(&connection_pool).await;
+
+    let mut client = FakeEthClient::new();
+    let mut watcher = EthWatch::new(
+        Address::default(),
+        None,
+        Box::new(client.clone()),
+        &connection_pool,
+        std::time::Duration::from_nanos(1),
+        100,
+    )
+    .await;
+
+    let mut storage = connection_pool.access_storage().await.unwrap();
+    client
+        .add_transactions(&[build_l1_tx(0, 10), build_l1_tx(1, 14)])
+        .await;
+    client.set_last_finalized_block_number(15).await;
+    watcher.loop_iteration(&mut storage).await.unwrap();
+    assert_eq!(get_all_db_txs(&mut storage).await.len(), 2);
+    let blocks = storage.eth_watcher_dal().get_blocks().await.unwrap();
+    let block_numbers: Vec<_> = blocks.iter().map(|&(number, _)| number).collect();
+    assert_eq!(block_numbers, [15, 14, 10]);
+
+    // The second priority op is moved to another block by the reorg.
+    client.reorg(12).await;
+    client
+        .add_transactions(&[build_l1_tx(1, 16), build_l1_tx(2, 18)])
+        .await;
+    client.set_last_finalized_block_number(20).await;
+    watcher.loop_iteration(&mut storage).await.unwrap();
+
+    let mut db_txs: Vec<L1Tx> = get_all_db_txs(&mut storage)
+        .await
+        .into_iter()
+        .map(|tx| tx.try_into().unwrap())
+        .collect();
+    db_txs.sort_by_key(|tx| tx.common_data.serial_id);
+    let serial_ids: Vec<_> = db_txs.iter().map(|tx| tx.serial_id().0).collect();
+    assert_eq!(serial_ids, [0, 1, 2]);
+
+    let blocks = storage.eth_watcher_dal().get_blocks().await.unwrap();
+    assert_eq!(
+        blocks,
+        [
+            (20, H256::from_low_u64_le(20)),
+            (18, H256::from_low_u64_le(18)),
+            (16, H256::from_low_u64_le(16)),
+            (10, H256::from_low_u64_be(10)),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn changed_priority_op_after_l1_reorg_is_rejected() {
+    let connection_pool = ConnectionPool::test_pool().await;
+    setup_db(&connection_pool).await;
+
+    let mut client = FakeEthClient::new();
+    let mut watcher = EthWatch::new(
+        Address::default(),
+        None,
+        Box::new(client.clone()),
+        &connection_pool,
+        std::time::Duration::from_nanos(1),
+        100,
+    )
+    .await;
+
+    let mut storage = connection_pool.access_storage().await.unwrap();
+    client
+        .add_transactions(&[build_l1_tx(0, 10), build_l1_tx(1, 14)])
+        .await;
+    client.set_last_finalized_block_number(15).await;
+    watcher.loop_iteration(&mut storage).await.unwrap();
+
+    client.reorg(12).await;
+    let mut changed_tx = build_l1_tx(1, 16);
+    changed_tx.common_data.canonical_tx_hash = H256::repeat_byte(0xff);
+    client.add_transactions(&[changed_tx]).await;
+    client.set_last_finalized_block_number(20).await;
+    let err = watcher.loop_iteration(&mut storage).await.unwrap_err();
+    assert!(
+        matches!(err, Error::PriorityOpMismatch { serial_id, .. } if serial_id == PriorityOpId(1)),
+        "{err:?}"
+    );
+    // The changed operation must not be persisted.
+    assert_eq!(get_all_db_txs(&mut storage).await.len(), 2);
+}
+
 async fn get_all_db_txs(storage: &mut StorageProcessor<'_>) -> Vec<Transaction> {
     storage.transactions_dal().reset_mempool().await;
     storage
@@ -561,7 +695,7 @@ fn tx_into_log(tx: L1Tx) -> Log {
 
     let data = encode(&[
         Token::Uint(tx.common_data.serial_id.0.into()),
-        Token::FixedBytes(H256::random().0.to_vec()),
+        Token::FixedBytes(tx.common_data.canonical_tx_hash.0.to_vec()),
         Token::Uint(u64::MAX.into()),
         tx_data_token,
         Token::Array(Vec::new()),
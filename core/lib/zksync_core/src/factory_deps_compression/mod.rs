@@ -0,0 +1,81 @@
+//! Background compression of factory deps stored before bytecode compression was introduced.
+
+use std::time::Instant;
+
+use anyhow::Context as _;
+use tokio::sync::watch;
+use zksync_dal::ConnectionPool;
+
+#[cfg(test)]
+mod tests;
+
+/// Compresses bytecodes of factory deps that are stored raw. Newly inserted factory deps are compressed
+/// on insertion, so the task terminates once all legacy factory deps are processed (the task itself idles
+/// until the stop signal). Until then, DAL methods transparently read both raw and compressed bytecodes.
+#[derive(Debug)]
+pub struct FactoryDepsCompression {
+    pool: ConnectionPool,
+    chunk_size: usize,
+}
+
+impl FactoryDepsCompression {
+    /// Default number of factory deps processed in a single DB transaction.
+    pub const DEFAULT_CHUNK_SIZE: usize = 500;
+
+    pub fn new(pool: ConnectionPool) -> Self {
+        Self {
+            pool,
+            chunk_size: Self::DEFAULT_CHUNK_SIZE,
+        }
+    }
+
+    /// Sets the number of factory deps processed in a single DB transaction.
+    pub fn with_chunk_size(mut self, chunk_size: usize) -> Self {
+        assert!(chunk_size > 0, "chunk size must be positive");
+        self.chunk_size = chunk_size;
+        self
+    }
+
+    /// Compresses the next chunk of factory deps. Returns `false` if there are no uncompressed factory deps left.
+    async fn run_single_iteration(&self) -> anyhow::Result<bool> {
+        let mut storage = self
+            .pool
+            .access_storage_tagged("factory_deps_compression")
+            .await?;
+        let started_at = Instant::now();
+        let compressed_count = storage
+            .storage_dal()
+            .compress_factory_deps(self.chunk_size)
+            .await
+            .context("compress_factory_deps()")?;
+        if compressed_count == 0 {
+            return Ok(false);
+        }
+        tracing::info!(
+            "Compressed {compressed_count} factory deps in {:?}",
+            started_at.elapsed()
+        );
+        Ok(true)
+    }
+
+    pub async fn run(self, mut stop_receiver: watch::Receiver<bool>) -> anyhow::Result<()> {
+        loop {
+            if *stop_receiver.borrow() {
+                tracing::info!("Stop signal received, factory deps compression is shutting down");
+                return Ok(());
+            }
+            let has_more_deps = self
+                .run_single_iteration()
+                .await
+                .context("failed compressing factory deps")?;
+            if !has_more_deps {
+                tracing::info!(
+                    "All factory deps are compressed; factory deps compression is finished"
+                );
+                // Tasks are not expected to finish before the node is stopped.
+                stop_receiver.changed().await.ok();
+                return Ok(());
+            }
+        }
+    }
+}
@@ -0,0 +1,36 @@
+//! Tests for the factory deps compression.
+
+use std::collections::HashMap;
+
+use zksync_types::{L2ChainId, MiniblockNumber, H256};
+
+use super::*;
+use crate::genesis::{ensure_genesis_state, GenesisParams};
+
+#[tokio::test]
+async fn compression_finishes_for_compressed_factory_deps() {
+    let pool = ConnectionPool::test_pool().await;
+    let mut storage = pool.access_storage().await.unwrap();
+    ensure_genesis_state(&mut storage, L2ChainId::default(), &GenesisParams::mock())
+        .await
+        .unwrap();
+    let deps = HashMap::from([(H256::repeat_byte(1), vec![0xfe; 64])]);
+    storage
+        .storage_dal()
+        .insert_factory_deps(MiniblockNumber(0), &deps)
+        .await;
+
+    let compression = FactoryDepsCompression::new(pool.clone()).with_chunk_size(2);
+    assert!(!compression.run_single_iteration().await.unwrap());
+
+    let (stop_sender, stop_receiver) = watch::channel(false);
+    let compression_task = tokio::spawn(compression.run(stop_receiver));
+    stop_sender.send_replace(true);
+    compression_task.await.unwrap().unwrap();
+
+    let bytecode = storage
+        .storage_dal()
+        .get_factory_dep(H256::repeat_byte(1))
+        .await;
+    assert_eq!(bytecode, Some(vec![0xfe; 64]));
+}
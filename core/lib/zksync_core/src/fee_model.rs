@@ -1,13 +1,23 @@
-use std::{fmt, sync::Arc};
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
 
+use anyhow::Context as _;
+use serde::Deserialize;
+use tokio::sync::watch;
+use zksync_config::configs::chain::{FeeModelVersion, StateKeeperConfig};
 use zksync_types::{
+    commitment::L1BatchCommitmentMode,
     fee_model::{
-        BatchFeeInput, FeeModelConfig, FeeModelConfigV2, FeeParams, FeeParamsV1, FeeParamsV2,
-        L1PeggedBatchFeeModelInput, PubdataIndependentBatchFeeModelInput,
+        BatchFeeInput, FeeModelConfig, FeeModelConfigV1, FeeModelConfigV2, FeeParams, FeeParamsV1,
+        FeeParamsV2, L1PeggedBatchFeeModelInput, PubdataIndependentBatchFeeModelInput,
     },
     U256,
 };
-use zksync_utils::ceil_div_u256;
+use zksync_utils::{ceil_div_u256, time::seconds_since_epoch};
 
 use crate::l1_gas_price::L1GasPriceProvider;
 
@@ -53,10 +63,47 @@ pub trait BatchFeeModelInputProvider: fmt::Debug + 'static + Send + Sync {
 pub(crate) struct MainNodeFeeInputProvider {
     provider: Arc<dyn L1GasPriceProvider>,
     config: FeeModelConfig,
+    pricing_curve: Option<watch::Receiver<FeePricingCurve>>,
+    commitment_mode: L1BatchCommitmentMode,
 }
 
 impl BatchFeeModelInputProvider for MainNodeFeeInputProvider {
     fn get_fee_model_params(&self) -> FeeParams {
+        let params = self.get_raw_fee_model_params();
+        match &self.pricing_curve {
+            Some(curve) => curve.borrow().apply(params, seconds_since_epoch()),
+            None => params,
+        }
+    }
+}
+
+impl MainNodeFeeInputProvider {
+    pub(crate) fn new(provider: Arc<dyn L1GasPriceProvider>, config: FeeModelConfig) -> Self {
+        Self {
+            provider,
+            config,
+            pricing_curve: None,
+            commitment_mode: L1BatchCommitmentMode::Rollup,
+        }
+    }
+
+    /// Sets the L1 batch commitment mode. In the validium mode, pubdata is not published on L1,
+    /// so the L1 pubdata price is considered to be zero.
+    pub(crate) fn with_commitment_mode(mut self, commitment_mode: L1BatchCommitmentMode) -> Self {
+        self.commitment_mode = commitment_mode;
+        self
+    }
+
+    /// Adjusts fee model params using the specified pricing curve. The curve may be updated at runtime.
+    pub(crate) fn with_pricing_curve(
+        mut self,
+        pricing_curve: Option<watch::Receiver<FeePricingCurve>>,
+    ) -> Self {
+        self.pricing_curve = pricing_curve;
+        self
+    }
+
+    fn get_raw_fee_model_params(&self) -> FeeParams {
         match self.config {
             FeeModelConfig::V1(config) => FeeParams::V1(FeeParamsV1 {
                 config,
@@ -65,15 +112,207 @@ impl BatchFeeModelInputProvider for MainNodeFeeInputProvider {
             FeeModelConfig::V2(config) => FeeParams::V2(FeeParamsV2 {
                 config,
                 l1_gas_price: self.provider.estimate_effective_gas_price(),
-                l1_pubdata_price: self.provider.estimate_effective_pubdata_price(),
+                l1_pubdata_price: match self.commitment_mode {
+                    L1BatchCommitmentMode::Rollup => {
+                        self.provider.estimate_effective_pubdata_price()
+                    }
+                    L1BatchCommitmentMode::Validium => 0,
+                },
             }),
         }
     }
 }
 
-impl MainNodeFeeInputProvider {
-    pub(crate) fn new(provider: Arc<dyn L1GasPriceProvider>, config: FeeModelConfig) -> Self {
-        Self { provider, config }
+/// Builds the fee model config for the main node based on the state keeper config.
+pub(crate) fn main_node_fee_model_config(
+    config: &StateKeeperConfig,
+) -> anyhow::Result<FeeModelConfig> {
+    // The `V1` fee model pegs the pubdata price to the L1 gas price, so it would charge for pubdata that is never published.
+    anyhow::ensure!(
+        config.l1_batch_commitment_mode == L1BatchCommitmentMode::Rollup
+            || config.fee_model_version == FeeModelVersion::V2,
+        "the validium commitment mode requires the V2 fee model"
+    );
+    Ok(match config.fee_model_version {
+        FeeModelVersion::V1 => FeeModelConfig::V1(FeeModelConfigV1 {
+            minimal_l2_gas_price: config.fair_l2_gas_price,
+        }),
+        FeeModelVersion::V2 => {
+            anyhow::ensure!(
+                config.max_gas_per_batch > 0 && config.max_pubdata_per_batch > 0,
+                "`max_gas_per_batch` and `max_pubdata_per_batch` must be positive for the V2 fee model"
+            );
+            FeeModelConfig::V2(FeeModelConfigV2 {
+                minimal_l2_gas_price: config.fair_l2_gas_price,
+                compute_overhead_part: config.compute_overhead_part,
+                pubdata_overhead_part: config.pubdata_overhead_part,
+                batch_overhead_l1_gas: config.batch_overhead_l1_gas,
+                max_gas_per_batch: config.max_gas_per_batch,
+                max_pubdata_per_batch: config.max_pubdata_per_batch,
+            })
+        }
+    })
+}
+
+/// Multiplier of the minimal L2 gas price applied when the L1 gas price is high.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub(crate) struct CongestionMultiplier {
+    /// Minimum L1 gas price (in wei) at which the multiplier applies.
+    pub min_l1_gas_price: u64,
+    pub multiplier: f64,
+}
+
+/// Minimal L2 gas price effective from a certain time.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub(crate) struct MinimalL2GasPriceStep {
+    /// UNIX timestamp (in seconds) from which the price is effective.
+    pub from_timestamp: u64,
+    pub minimal_l2_gas_price: u64,
+}
+
+/// Pricing curve adjusting fee model params on the main node. Loaded from a JSON file.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub(crate) struct FeePricingCurve {
+    /// Cap on the L1 gas price (in wei) used in the fee model.
+    #[serde(default)]
+    pub max_l1_gas_price: Option<u64>,
+    /// Cap on the L1 pubdata price (in wei) used in the fee model. Only applies to the V2 fee model.
+    #[serde(default)]
+    pub max_l1_pubdata_price: Option<u64>,
+    /// Multipliers of the minimal L2 gas price. If several multipliers apply, the one with the greatest
+    /// `min_l1_gas_price` is used.
+    #[serde(default)]
+    pub congestion_multipliers: Vec<CongestionMultiplier>,
+    /// Schedule of the minimal L2 gas price overriding the configured value. The step with the greatest
+    /// `from_timestamp` not exceeding the current time is used.
+    #[serde(default)]
+    pub minimal_l2_gas_price_schedule: Vec<MinimalL2GasPriceStep>,
+}
+
+impl FeePricingCurve {
+    fn validate(&self) -> anyhow::Result<()> {
+        for congestion in &self.congestion_multipliers {
+            anyhow::ensure!(
+                congestion.multiplier.is_finite() && congestion.multiplier > 0.0,
+                "congestion multiplier must be positive, got {congestion:?}"
+            );
+        }
+        Ok(())
+    }
+
+    /// Applies this curve to fee model params at the specified UNIX timestamp (in seconds).
+    fn apply(&self, params: FeeParams, timestamp: u64) -> FeeParams {
+        match params {
+            FeeParams::V1(mut params) => {
+                params.config.minimal_l2_gas_price = self.minimal_l2_gas_price(
+                    params.config.minimal_l2_gas_price,
+                    params.l1_gas_price,
+                    timestamp,
+                );
+                params.l1_gas_price = cap(params.l1_gas_price, self.max_l1_gas_price);
+                FeeParams::V1(params)
+            }
+            FeeParams::V2(mut params) => {
+                params.config.minimal_l2_gas_price = self.minimal_l2_gas_price(
+                    params.config.minimal_l2_gas_price,
+                    params.l1_gas_price,
+                    timestamp,
+                );
+                params.l1_gas_price = cap(params.l1_gas_price, self.max_l1_gas_price);
+                params.l1_pubdata_price = cap(params.l1_pubdata_price, self.max_l1_pubdata_price);
+                FeeParams::V2(params)
+            }
+        }
+    }
+
+    fn minimal_l2_gas_price(
+        &self,
+        configured_price: u64,
+        l1_gas_price: u64,
+        timestamp: u64,
+    ) -> u64 {
+        let base_price = self
+            .minimal_l2_gas_price_schedule
+            .iter()
+            .filter(|step| step.from_timestamp <= timestamp)
+            .max_by_key(|step| step.from_timestamp)
+            .map_or(configured_price, |step| step.minimal_l2_gas_price);
+        let multiplier = self
+            .congestion_multipliers
+            .iter()
+            .filter(|congestion| congestion.min_l1_gas_price <= l1_gas_price)
+            .max_by_key(|congestion| congestion.min_l1_gas_price)
+            .map_or(1.0, |congestion| congestion.multiplier);
+        (base_price as f64 * multiplier) as u64
+    }
+}
+
+fn cap(value: u64, max_value: Option<u64>) -> u64 {
+    max_value.map_or(value, |max_value| value.min(max_value))
+}
+
+/// Periodically reloads [`FeePricingCurve`] from a file, so that it can be changed without restarting the node.
+#[derive(Debug)]
+pub(crate) struct FeePricingCurveLoader {
+    path: PathBuf,
+    curve_sender: watch::Sender<FeePricingCurve>,
+}
+
+impl FeePricingCurveLoader {
+    const RELOAD_INTERVAL: Duration = Duration::from_secs(30);
+
+    /// Creates a loader and loads the initial curve. Fails if the curve cannot be loaded.
+    pub async fn new(path: impl Into<PathBuf>) -> anyhow::Result<Self> {
+        let path = path.into();
+        let curve = Self::load(&path).await?;
+        tracing::info!("Loaded fee pricing curve from {path:?}: {curve:?}");
+        Ok(Self {
+            path,
+            curve_sender: watch::channel(curve).0,
+        })
+    }
+
+    async fn load(path: &Path) -> anyhow::Result<FeePricingCurve> {
+        let contents = tokio::fs::read_to_string(path)
+            .await
+            .with_context(|| format!("failed reading fee pricing curve from {path:?}"))?;
+        let curve: FeePricingCurve = serde_json::from_str(&contents)
+            .with_context(|| format!("failed parsing fee pricing curve from {path:?}"))?;
+        curve.validate()?;
+        Ok(curve)
+    }
+
+    pub fn subscribe(&self) -> watch::Receiver<FeePricingCurve> {
+        self.curve_sender.subscribe()
+    }
+
+    pub async fn run(self, mut stop_receiver: watch::Receiver<bool>) -> anyhow::Result<()> {
+        loop {
+            tokio::time::timeout(Self::RELOAD_INTERVAL, stop_receiver.changed())
+                .await
+                .ok();
+            if *stop_receiver.borrow() {
+                tracing::info!("Stop signal received, fee pricing curve loader is shutting down");
+                return Ok(());
+            }
+
+            match Self::load(&self.path).await {
+                Ok(curve) => {
+                    self.curve_sender.send_if_modified(|current_curve| {
+                        if *current_curve == curve {
+                            return false;
+                        }
+                        tracing::info!("Reloaded fee pricing curve: {curve:?}");
+                        *current_curve = curve;
+                        true
+                    });
+                }
+                Err(err) => {
+                    // Keep using the previously loaded curve.
+                    tracing::warn!("Failed reloading fee pricing curve: {err:#}");
+                }
+            }
+        }
     }
 }
 
@@ -383,4 +622,108 @@ mod tests {
             "Max pubdata increase lowers pubdata price"
         );
     }
+
+    #[test]
+    fn applying_pricing_curve() {
+        let curve: FeePricingCurve = serde_json::from_value(serde_json::json!({
+            "max_l1_gas_price": 100_000_000_000_u64,
+            "max_l1_pubdata_price": 1_000_000_000_000_u64,
+            "congestion_multipliers": [
+                { "min_l1_gas_price": 50_000_000_000_u64, "multiplier": 1.5 },
+                { "min_l1_gas_price": 80_000_000_000_u64, "multiplier": 2.0 },
+            ],
+            "minimal_l2_gas_price_schedule": [
+                { "from_timestamp": 1_000, "minimal_l2_gas_price": 200_000_000 },
+                { "from_timestamp": 2_000, "minimal_l2_gas_price": 300_000_000 },
+            ],
+        }))
+        .unwrap();
+        curve.validate().unwrap();
+
+        let params = FeeParams::V2(FeeParamsV2 {
+            config: FeeModelConfigV2 {
+                minimal_l2_gas_price: 100_000_000,
+                compute_overhead_part: 0.0,
+                pubdata_overhead_part: 1.0,
+                batch_overhead_l1_gas: 700_000,
+                max_gas_per_batch: 500_000_000,
+                max_pubdata_per_batch: 100_000,
+            },
+            l1_gas_price: 10_000_000_000,
+            l1_pubdata_price: 2_000_000_000_000,
+        });
+        let FeeParams::V2(adjusted) = curve.apply(params, 500) else {
+            unreachable!();
+        };
+        assert_eq!(adjusted.config.minimal_l2_gas_price, 100_000_000);
+        assert_eq!(adjusted.l1_gas_price, 10_000_000_000);
+        assert_eq!(adjusted.l1_pubdata_price, 1_000_000_000_000);
+
+        let FeeParams::V2(adjusted) = curve.apply(params, 1_500) else {
+            unreachable!();
+        };
+        assert_eq!(adjusted.config.minimal_l2_gas_price, 200_000_000);
+
+        let congested_params = FeeParams::V1(FeeParamsV1 {
+            config: FeeModelConfigV1 {
+                minimal_l2_gas_price: 100_000_000,
+            },
+            l1_gas_price: 150_000_000_000,
+        });
+        let FeeParams::V1(adjusted) = curve.apply(congested_params, 2_500) else {
+            unreachable!();
+        };
+        assert_eq!(adjusted.config.minimal_l2_gas_price, 600_000_000);
+        assert_eq!(adjusted.l1_gas_price, 100_000_000_000);
+    }
+
+    #[test]
+    fn invalid_pricing_curve() {
+        let curve = FeePricingCurve {
+            congestion_multipliers: vec![CongestionMultiplier {
+                min_l1_gas_price: 0,
+                multiplier: -1.0,
+            }],
+            ..FeePricingCurve::default()
+        };
+        curve.validate().unwrap_err();
+    }
+
+    #[derive(Debug)]
+    struct MockL1GasPriceProvider;
+
+    impl L1GasPriceProvider for MockL1GasPriceProvider {
+        fn estimate_effective_gas_price(&self) -> u64 {
+            1_000_000_000
+        }
+
+        fn estimate_effective_pubdata_price(&self) -> u64 {
+            17_000_000_000
+        }
+    }
+
+    #[test]
+    fn pubdata_price_in_validium_mode() {
+        let mut state_keeper_config = StateKeeperConfig::for_tests();
+        state_keeper_config.l1_batch_commitment_mode = L1BatchCommitmentMode::Validium;
+        main_node_fee_model_config(&state_keeper_config).unwrap_err();
+
+        state_keeper_config.fee_model_version = FeeModelVersion::V2;
+        let config = main_node_fee_model_config(&state_keeper_config).unwrap();
+        let rollup_provider =
+            MainNodeFeeInputProvider::new(Arc::new(MockL1GasPriceProvider), config);
+        let FeeParams::V2(params) = rollup_provider.get_fee_model_params() else {
+            panic!("unexpected fee params");
+        };
+        assert_eq!(params.l1_pubdata_price, 17_000_000_000);
+
+        let validium_provider =
+            MainNodeFeeInputProvider::new(Arc::new(MockL1GasPriceProvider), config)
+                .with_commitment_mode(L1BatchCommitmentMode::Validium);
+        let FeeParams::V2(params) = validium_provider.get_fee_model_params() else {
+            panic!("unexpected fee params");
+        };
+        assert_eq!(params.l1_gas_price, 1_000_000_000);
+        assert_eq!(params.l1_pubdata_price, 0);
+    }
 }
@@ -1,6 +1,12 @@
 //! This module aims to provide a genesis setup for the zkSync Era network.
 //! It initializes the Merkle tree with the basic setup (such as fields of special service accounts),
 //! setups the required databases, and outputs the data required to initialize a smart contract.
+//!
+//! Besides system contracts, the genesis state may include [custom allocations](GenesisAllocations):
+//! prefunded accounts, contracts with their initial storage and tokens. Use [`create_genesis()`]
+//! to create the genesis state programmatically.
+
+use std::collections::HashSet;
 
 use anyhow::Context as _;
 use zksync_contracts::BaseSystemContracts;
@@ -10,17 +16,142 @@ use zksync_types::{
     block::{BlockGasCount, DeployedContract, L1BatchHeader, MiniblockHasher, MiniblockHeader},
     commitment::{L1BatchCommitment, L1BatchMetadata},
     fee_model::BatchFeeInput,
-    get_code_key, get_system_context_init_logs,
+    get_code_key, get_known_code_key, get_system_context_init_logs,
     protocol_version::{L1VerifierConfig, ProtocolVersion},
     tokens::{TokenInfo, TokenMetadata, ETHEREUM_ADDRESS},
+    utils::storage_key_for_eth_balance,
     zkevm_test_harness::witness::sort_storage_access::sort_storage_access_queries,
     AccountTreeId, Address, L1BatchNumber, L2ChainId, LogQuery, MiniblockNumber, ProtocolVersionId,
-    StorageKey, StorageLog, StorageLogKind, Timestamp, H256,
+    StorageKey, StorageLog, StorageLogKind, Timestamp, H256, L2_ETH_TOKEN_ADDRESS, U256,
+};
+use zksync_utils::{
+    be_words_to_bytes,
+    bytecode::{hash_bytecode, validate_bytecode},
+    h256_to_u256, u256_to_h256,
 };
-use zksync_utils::{be_words_to_bytes, bytecode::hash_bytecode, h256_to_u256, u256_to_h256};
 
 use crate::metadata_calculator::L1BatchWithLogs;
 
+/// Storage slot of `totalSupply` in the `L2EthToken` system contract.
+const ETH_TOTAL_SUPPLY_SLOT: u64 = 1;
+
+/// Contract deployed at genesis together with its initial storage.
+#[derive(Debug, Clone)]
+pub struct GenesisContract {
+    pub address: Address,
+    pub bytecode: Vec<u8>,
+    /// Initial values of storage slots of the contract.
+    pub storage: Vec<(H256, H256)>,
+}
+
+/// Custom state included into the genesis L1 batch in addition to system contracts.
+///
+/// External nodes cannot recover custom allocations from the main node, so a node with a genesis
+/// containing allocations cannot be used as a main node for external nodes yet.
+#[derive(Debug, Clone, Default)]
+pub struct GenesisAllocations {
+    /// Accounts prefunded with ETH.
+    pub prefunded_accounts: Vec<(Address, U256)>,
+    /// Contracts deployed at genesis.
+    pub contracts: Vec<GenesisContract>,
+    /// Tokens registered at genesis in addition to ETH.
+    pub tokens: Vec<TokenInfo>,
+}
+
+impl GenesisAllocations {
+    fn is_empty(&self) -> bool {
+        self.prefunded_accounts.is_empty() && self.contracts.is_empty() && self.tokens.is_empty()
+    }
+
+    fn validate(&self, system_contracts: &[DeployedContract]) -> anyhow::Result<()> {
+        let mut prefunded_accounts = HashSet::with_capacity(self.prefunded_accounts.len());
+        for (address, _) in &self.prefunded_accounts {
+            anyhow::ensure!(
+                prefunded_accounts.insert(*address),
+                "account {address:?} is prefunded more than once"
+            );
+        }
+        self.prefunded_accounts
+            .iter()
+            .try_fold(U256::zero(), |acc, (_, balance)| acc.checked_add(*balance))
+            .context("total prefunded balance overflows")?;
+
+        // System contracts may be overridden, so their bytecodes are validated as well.
+        for contract in system_contracts {
+            let address = contract.account_id.address();
+            validate_bytecode(&contract.bytecode)
+                .with_context(|| format!("system contract {address:?} has invalid bytecode"))?;
+        }
+        let system_addresses: HashSet<_> = system_contracts
+            .iter()
+            .map(|contract| *contract.account_id.address())
+            .collect();
+        let mut contract_addresses = HashSet::with_capacity(self.contracts.len());
+        for contract in &self.contracts {
+            let address = contract.address;
+            anyhow::ensure!(
+                !system_addresses.contains(&address),
+                "contract {address:?} clashes with a system contract; use system contract overrides instead"
+            );
+            anyhow::ensure!(
+                contract_addresses.insert(address),
+                "contract {address:?} is deployed more than once"
+            );
+            validate_bytecode(&contract.bytecode)
+                .with_context(|| format!("contract {address:?} has invalid bytecode"))?;
+        }
+
+        for token in &self.tokens {
+            anyhow::ensure!(
+                token.l1_address != ETHEREUM_ADDRESS && token.l2_address != ETHEREUM_ADDRESS,
+                "ETH is registered at genesis automatically"
+            );
+        }
+        Ok(())
+    }
+
+    fn storage_logs(&self) -> Vec<StorageLog> {
+        let mut logs = vec![];
+        let mut total_supply = U256::zero();
+        for &(address, balance) in &self.prefunded_accounts {
+            let balance_key = storage_key_for_eth_balance(&address);
+            logs.push(StorageLog::new_write_log(
+                balance_key,
+                u256_to_h256(balance),
+            ));
+            total_supply += balance;
+        }
+        if !self.prefunded_accounts.is_empty() {
+            let total_supply_key = StorageKey::new(
+                AccountTreeId::new(L2_ETH_TOKEN_ADDRESS),
+                H256::from_low_u64_be(ETH_TOTAL_SUPPLY_SLOT),
+            );
+            logs.push(StorageLog::new_write_log(
+                total_supply_key,
+                u256_to_h256(total_supply),
+            ));
+        }
+
+        for contract in &self.contracts {
+            let hash = hash_bytecode(&contract.bytecode);
+            logs.push(StorageLog::new_write_log(
+                get_code_key(&contract.address),
+                hash,
+            ));
+            // Mark the bytecode as known, so that the VM can decommit it.
+            logs.push(StorageLog::new_write_log(
+                get_known_code_key(&hash),
+                H256::from_low_u64_be(1),
+            ));
+            let account = AccountTreeId::new(contract.address);
+            logs.extend(contract.storage.iter().map(|&(slot, value)| {
+                StorageLog::new_write_log(StorageKey::new(account, slot), value)
+            }));
+        }
+        logs
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct GenesisParams {
     pub first_validator: Address,
@@ -29,9 +160,52 @@ pub struct GenesisParams {
     pub system_contracts: Vec<DeployedContract>,
     pub first_verifier_address: Address,
     pub first_l1_verifier_config: L1VerifierConfig,
+    pub allocations: GenesisAllocations,
 }
 
 impl GenesisParams {
+    /// Replaces system contracts deployed at the same addresses as `overrides`. Contracts at other addresses
+    /// are added to system contracts.
+    #[must_use]
+    pub fn with_system_contract_overrides(
+        mut self,
+        overrides: impl IntoIterator<Item = DeployedContract>,
+    ) -> Self {
+        for contract in overrides {
+            let existing = self
+                .system_contracts
+                .iter_mut()
+                .find(|existing| existing.account_id == contract.account_id);
+            if let Some(existing) = existing {
+                *existing = contract;
+            } else {
+                self.system_contracts.push(contract);
+            }
+        }
+        self
+    }
+
+    /// Prefunds the specified account with ETH at genesis.
+    #[must_use]
+    pub fn with_prefunded_account(mut self, address: Address, balance: U256) -> Self {
+        self.allocations.prefunded_accounts.push((address, balance));
+        self
+    }
+
+    /// Deploys a contract at genesis.
+    #[must_use]
+    pub fn with_contract(mut self, contract: GenesisContract) -> Self {
+        self.allocations.contracts.push(contract);
+        self
+    }
+
+    /// Registers a token at genesis.
+    #[must_use]
+    pub fn with_token(mut self, token: TokenInfo) -> Self {
+        self.allocations.tokens.push(token);
+        self
+    }
+
     #[cfg(test)]
     pub(crate) fn mock() -> Self {
         use zksync_types::system_contracts::get_system_smart_contracts;
@@ -43,10 +217,21 @@ impl GenesisParams {
             system_contracts: get_system_smart_contracts(),
             first_l1_verifier_config: L1VerifierConfig::default(),
             first_verifier_address: Address::zero(),
+            allocations: GenesisAllocations::default(),
         }
     }
 }
 
+/// Information about the genesis L1 batch required to initialize L1 contracts.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GenesisOutput {
+    pub root_hash: H256,
+    pub commitment: H256,
+    pub rollup_last_leaf_index: u64,
+    pub bootloader_hash: H256,
+    pub default_aa_hash: H256,
+}
+
 pub async fn ensure_genesis_state(
     storage: &mut StorageProcessor<'_>,
     zksync_chain_id: L2ChainId,
@@ -66,6 +251,40 @@ pub async fn ensure_genesis_state(
     }
 
     tracing::info!("running regenesis");
+    let output = create_genesis(&mut transaction, zksync_chain_id, genesis_params).await?;
+    transaction.commit().await.unwrap();
+
+    // We need to `println` this value because it will be used to initialize the smart contract.
+    println!("CONTRACTS_GENESIS_ROOT={:?}", output.root_hash);
+    println!("CONTRACTS_GENESIS_BATCH_COMMITMENT={:?}", output.commitment);
+    println!(
+        "CONTRACTS_GENESIS_ROLLUP_LEAF_INDEX={}",
+        output.rollup_last_leaf_index
+    );
+    println!(
+        "CHAIN_STATE_KEEPER_BOOTLOADER_HASH={:?}",
+        output.bootloader_hash
+    );
+    println!(
+        "CHAIN_STATE_KEEPER_DEFAULT_AA_HASH={:?}",
+        output.default_aa_hash
+    );
+
+    Ok(output.root_hash)
+}
+
+/// Creates the genesis L1 batch with the specified params in the provided storage. Unlike [`ensure_genesis_state()`],
+/// this function doesn't check whether the genesis is needed and doesn't output anything; it is intended to be used
+/// by tooling creating the genesis state programmatically.
+///
+/// # Errors
+///
+/// Returns an error if the genesis params are invalid (e.g., allocations clash with system contracts).
+pub async fn create_genesis(
+    storage: &mut StorageProcessor<'_>,
+    zksync_chain_id: L2ChainId,
+    genesis_params: &GenesisParams,
+) -> anyhow::Result<GenesisOutput> {
     let GenesisParams {
         first_validator,
         protocol_version,
@@ -73,9 +292,22 @@ pub async fn ensure_genesis_state(
         system_contracts,
         first_verifier_address,
         first_l1_verifier_config,
+        allocations,
     } = genesis_params;
+    allocations
+        .validate(system_contracts)
+        .context("invalid genesis allocations")?;
+    if !allocations.is_empty() {
+        tracing::info!(
+            "Genesis includes custom allocations: {} prefunded accounts, {} contracts, {} tokens",
+            allocations.prefunded_accounts.len(),
+            allocations.contracts.len(),
+            allocations.tokens.len()
+        );
+    }
 
     let base_system_contracts_hashes = base_system_contracts.hashes();
+    let mut transaction = storage.start_transaction().await?;
 
     create_genesis_l1_batch(
         &mut transaction,
@@ -84,6 +316,7 @@ pub async fn ensure_genesis_state(
         *protocol_version,
         base_system_contracts,
         system_contracts,
+        allocations,
         *first_l1_verifier_config,
         *first_verifier_address,
     )
@@ -91,7 +324,7 @@ pub async fn ensure_genesis_state(
     tracing::info!("chain_schema_genesis is complete");
 
     let storage_logs = L1BatchWithLogs::new(&mut transaction, L1BatchNumber(0)).await;
-    let storage_logs = storage_logs.unwrap().storage_logs;
+    let storage_logs = storage_logs.unwrap().unwrap().storage_logs;
     let metadata = ZkSyncTree::process_genesis_batch(&storage_logs);
     let genesis_root_hash = metadata.root_hash;
     let rollup_last_leaf_index = metadata.leaf_count + 1;
@@ -119,29 +352,15 @@ pub async fn ensure_genesis_state(
     )
     .await;
     tracing::info!("operations_schema_genesis is complete");
+    transaction.commit().await?;
 
-    transaction.commit().await.unwrap();
-
-    // We need to `println` this value because it will be used to initialize the smart contract.
-    println!("CONTRACTS_GENESIS_ROOT={:?}", genesis_root_hash);
-    println!(
-        "CONTRACTS_GENESIS_BATCH_COMMITMENT={:?}",
-        block_commitment.hash().commitment
-    );
-    println!(
-        "CONTRACTS_GENESIS_ROLLUP_LEAF_INDEX={}",
-        rollup_last_leaf_index
-    );
-    println!(
-        "CHAIN_STATE_KEEPER_BOOTLOADER_HASH={:?}",
-        base_system_contracts_hashes.bootloader
-    );
-    println!(
-        "CHAIN_STATE_KEEPER_DEFAULT_AA_HASH={:?}",
-        base_system_contracts_hashes.default_aa
-    );
-
-    Ok(genesis_root_hash)
+    Ok(GenesisOutput {
+        root_hash: genesis_root_hash,
+        commitment: block_commitment.hash().commitment,
+        rollup_last_leaf_index,
+        bootloader_hash: base_system_contracts_hashes.bootloader,
+        default_aa_hash: base_system_contracts_hashes.default_aa,
+    })
 }
 
 // Default account and bootloader are not a regular system contracts
@@ -168,9 +387,12 @@ async fn insert_base_system_contracts_to_factory_deps(
 async fn insert_system_contracts(
     storage: &mut StorageProcessor<'_>,
     contracts: &[DeployedContract],
+    allocations: &GenesisAllocations,
     chain_id: L2ChainId,
 ) {
     let system_context_init_logs = (H256::default(), get_system_context_init_logs(chain_id));
+    let allocation_logs = allocations.storage_logs();
+    let allocation_logs = (!allocation_logs.is_empty()).then(|| (H256::default(), allocation_logs));
 
     let storage_logs: Vec<(H256, Vec<StorageLog>)> = contracts
         .iter()
@@ -184,6 +406,7 @@ async fn insert_system_contracts(
             )
         })
         .chain(Some(system_context_init_logs))
+        .chain(allocation_logs)
         .collect();
 
     let mut transaction = storage.start_transaction().await.unwrap();
@@ -247,9 +470,15 @@ async fn insert_system_contracts(
         .apply_storage_logs(&storage_logs)
         .await;
 
+    let allocated_bytecodes = allocations
+        .contracts
+        .iter()
+        .map(|contract| &contract.bytecode);
     let factory_deps = contracts
         .iter()
-        .map(|c| (hash_bytecode(&c.bytecode), c.bytecode.clone()))
+        .map(|c| &c.bytecode)
+        .chain(allocated_bytecodes)
+        .map(|bytecode| (hash_bytecode(bytecode), bytecode.clone()))
         .collect();
     transaction
         .storage_dal()
@@ -267,6 +496,7 @@ pub(crate) async fn create_genesis_l1_batch(
     protocol_version: ProtocolVersionId,
     base_system_contracts: &BaseSystemContracts,
     system_contracts: &[DeployedContract],
+    allocations: &GenesisAllocations,
     l1_verifier_config: L1VerifierConfig,
     verifier_address: Address,
 ) {
@@ -331,9 +561,12 @@ pub(crate) async fn create_genesis_l1_batch(
         .unwrap();
 
     insert_base_system_contracts_to_factory_deps(&mut transaction, base_system_contracts).await;
-    insert_system_contracts(&mut transaction, system_contracts, chain_id).await;
+    insert_system_contracts(&mut transaction, system_contracts, allocations, chain_id).await;
 
     add_eth_token(&mut transaction).await;
+    if !allocations.tokens.is_empty() {
+        add_tokens(&mut transaction, &allocations.tokens).await;
+    }
 
     transaction.commit().await.unwrap();
 }
@@ -363,6 +596,20 @@ pub(crate) async fn add_eth_token(storage: &mut StorageProcessor<'_>) {
     transaction.commit().await.unwrap();
 }
 
+async fn add_tokens(storage: &mut StorageProcessor<'_>, tokens: &[TokenInfo]) {
+    let mut transaction = storage.start_transaction().await.unwrap();
+
+    transaction.tokens_dal().add_tokens(tokens.to_vec()).await;
+    for token in tokens {
+        transaction
+            .tokens_dal()
+            .update_well_known_l1_token(&token.l1_address, token.metadata.clone())
+            .await;
+    }
+
+    transaction.commit().await.unwrap();
+}
+
 pub(crate) async fn save_genesis_l1_batch_metadata(
     storage: &mut StorageProcessor<'_>,
     commitment: &L1BatchCommitment,
@@ -415,6 +662,7 @@ mod tests {
             system_contracts: get_system_smart_contracts(),
             first_l1_verifier_config: L1VerifierConfig::default(),
             first_verifier_address: Address::random(),
+            allocations: GenesisAllocations::default(),
         };
         ensure_genesis_state(&mut conn, L2ChainId::from(270), &params)
             .await
@@ -448,6 +696,7 @@ mod tests {
             system_contracts: get_system_smart_contracts(),
             first_l1_verifier_config: L1VerifierConfig::default(),
             first_verifier_address: Address::random(),
+            allocations: GenesisAllocations::default(),
         };
         ensure_genesis_state(&mut conn, L2ChainId::max(), &params)
             .await
@@ -476,4 +725,102 @@ mod tests {
             .unwrap();
         assert!(!conn.blocks_dal().is_genesis_needed().await.unwrap());
     }
+
+    #[tokio::test]
+    async fn running_genesis_with_allocations() {
+        let pool = ConnectionPool::test_pool().await;
+        let mut conn = pool.access_storage().await.unwrap();
+
+        let prefunded_account = Address::repeat_byte(0x23);
+        let contract_address = Address::repeat_byte(0x42);
+        let contract = GenesisContract {
+            address: contract_address,
+            bytecode: vec![0; 32],
+            storage: vec![(H256::zero(), H256::repeat_byte(1))],
+        };
+        let token = TokenInfo {
+            l1_address: Address::repeat_byte(0x10),
+            l2_address: Address::repeat_byte(0x20),
+            metadata: TokenMetadata {
+                name: "Test".to_string(),
+                symbol: "TST".to_string(),
+                decimals: 18,
+            },
+        };
+        let params = GenesisParams::mock()
+            .with_prefunded_account(prefunded_account, 1_000.into())
+            .with_contract(contract.clone())
+            .with_token(token.clone());
+        let output = create_genesis(&mut conn, L2ChainId::default(), &params)
+            .await
+            .unwrap();
+        assert_ne!(output.root_hash, H256::zero());
+
+        let balance = conn
+            .storage_dal()
+            .get_by_key(&storage_key_for_eth_balance(&prefunded_account))
+            .await
+            .unwrap();
+        assert_eq!(balance, u256_to_h256(1_000.into()));
+        let code_hash = conn
+            .storage_dal()
+            .get_by_key(&get_code_key(&contract_address))
+            .await
+            .unwrap();
+        assert_eq!(code_hash, hash_bytecode(&contract.bytecode));
+        let slot_key = StorageKey::new(AccountTreeId::new(contract_address), H256::zero());
+        let slot_value = conn.storage_dal().get_by_key(&slot_key).await.unwrap();
+        assert_eq!(slot_value, H256::repeat_byte(1));
+        let bytecode = conn.storage_dal().get_factory_dep(code_hash).await.unwrap();
+        assert_eq!(bytecode, contract.bytecode);
+
+        let tokens = conn
+            .tokens_web3_dal()
+            .get_well_known_tokens()
+            .await
+            .unwrap();
+        assert!(tokens.contains(&token), "{tokens:?}");
+    }
+
+    #[tokio::test]
+    async fn genesis_allocations_cannot_clash_with_system_contracts() {
+        let pool = ConnectionPool::test_pool().await;
+        let mut conn = pool.access_storage().await.unwrap();
+        conn.blocks_dal().delete_genesis().await.unwrap();
+
+        let params = GenesisParams::mock();
+        let system_contract = params.system_contracts[0].clone();
+        let params = params.with_contract(GenesisContract {
+            address: *system_contract.account_id.address(),
+            bytecode: system_contract.bytecode,
+            storage: vec![],
+        });
+        let err = create_genesis(&mut conn, L2ChainId::default(), &params)
+            .await
+            .unwrap_err();
+        assert!(
+            format!("{err:#}").contains("clashes with a system contract"),
+            "{err:#}"
+        );
+    }
+
+    #[tokio::test]
+    async fn genesis_allocations_with_invalid_bytecode() {
+        let pool = ConnectionPool::test_pool().await;
+        let mut conn = pool.access_storage().await.unwrap();
+        conn.blocks_dal().delete_genesis().await.unwrap();
+
+        let params = GenesisParams::mock().with_contract(GenesisContract {
+            address: Address::repeat_byte(0xc0),
+            bytecode: vec![1; 64], // even number of words
+            storage: vec![],
+        });
+        let err = create_genesis(&mut conn, L2ChainId::default(), &params)
+            .await
+            .unwrap_err();
+        assert!(
+            format!("{err:#}").contains("has invalid bytecode"),
+            "{err:#}"
+        );
+    }
 }
@@ -6,5 +6,7 @@ pub mod fri_prover_queue_monitor;
 pub mod fri_scheduler_circuit_queuer;
 pub mod fri_witness_generator_jobs_retry_manager;
 pub mod fri_witness_generator_queue_monitor;
+pub mod paymaster_analytics_aggregator;
 pub mod periodic_job;
+pub mod storage_partitions_manager;
 pub mod waiting_to_queued_fri_witness_job_mover;
@@ -0,0 +1,172 @@
+use anyhow::Context as _;
+use async_trait::async_trait;
+use zksync_dal::ConnectionPool;
+use zksync_types::L1BatchNumber;
+
+use crate::house_keeper::periodic_job::PeriodicJob;
+
+/// Incrementally aggregates paymaster usage for sealed L1 batches, so that it can be served by the `zks_` API
+/// without scanning transactions. Batches are aggregated in order, starting from the first batch after genesis.
+#[derive(Debug)]
+pub struct PaymasterAnalyticsAggregator {
+    aggregation_interval_ms: u64,
+    connection_pool: ConnectionPool,
+}
+
+impl PaymasterAnalyticsAggregator {
+    /// Maximum number of L1 batches aggregated in a single run.
+    const MAX_L1_BATCHES_PER_RUN: u32 = 100;
+
+    /// Creates a new aggregator. `connection_pool` must point to the master DB since the aggregator writes data.
+    pub fn new(aggregation_interval_ms: u64, connection_pool: ConnectionPool) -> Self {
+        Self {
+            aggregation_interval_ms,
+            connection_pool,
+        }
+    }
+
+    /// Returns the number of aggregated L1 batches.
+    async fn aggregate_new_batches(&self) -> anyhow::Result<u32> {
+        let mut storage = self
+            .connection_pool
+            .access_storage_tagged("house_keeper")
+            .await?;
+        let Some(sealed_l1_batch) = storage
+            .blocks_dal()
+            .get_sealed_l1_batch_number()
+            .await
+            .context("get_sealed_l1_batch_number()")?
+        else {
+            return Ok(0);
+        };
+        let mut last_aggregated_l1_batch = storage
+            .paymaster_analytics_dal()
+            .get_last_aggregated_l1_batch()
+            .await
+            .context("get_last_aggregated_l1_batch()")?;
+        let reverted_l1_batch = last_aggregated_l1_batch.filter(|&number| number > sealed_l1_batch);
+        if let Some(reverted_l1_batch) = reverted_l1_batch {
+            // Should be handled by the block reverter, but we don't want to serve stats for reverted batches
+            // and skip the batches re-sealed in their place if it wasn't.
+            tracing::warn!(
+                "Paymaster usage is aggregated up to L1 batch #{reverted_l1_batch}, while the last sealed batch \
+                 is #{sealed_l1_batch}; removing stats for reverted batches"
+            );
+            storage
+                .paymaster_analytics_dal()
+                .delete_paymaster_analytics(sealed_l1_batch)
+                .await
+                .context("delete_paymaster_analytics()")?;
+            last_aggregated_l1_batch = Some(sealed_l1_batch);
+        }
+        // The genesis batch doesn't contain transactions, so it's skipped.
+        let first_l1_batch = last_aggregated_l1_batch.map_or(1, |number| number.0 + 1);
+        let last_l1_batch = sealed_l1_batch
+            .0
+            .min(first_l1_batch + Self::MAX_L1_BATCHES_PER_RUN - 1);
+
+        for number in first_l1_batch..=last_l1_batch {
+            let number = L1BatchNumber(number);
+            storage
+                .paymaster_analytics_dal()
+                .aggregate_l1_batch(number)
+                .await
+                .with_context(|| format!("aggregate_l1_batch({number})"))?;
+        }
+        Ok((last_l1_batch + 1).saturating_sub(first_l1_batch))
+    }
+}
+
+#[async_trait]
+impl PeriodicJob for PaymasterAnalyticsAggregator {
+    const SERVICE_NAME: &'static str = "PaymasterAnalyticsAggregator";
+
+    async fn run_routine_task(&mut self) -> anyhow::Result<()> {
+        let aggregated_count = self.aggregate_new_batches().await?;
+        if aggregated_count > 0 {
+            tracing::debug!("Aggregated paymaster usage for {aggregated_count} L1 batches");
+        }
+        Ok(())
+    }
+
+    fn polling_interval_ms(&self) -> u64 {
+        self.aggregation_interval_ms
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zksync_types::{block::BlockGasCount, L2ChainId};
+
+    use super::*;
+    use crate::{
+        genesis::{ensure_genesis_state, GenesisParams},
+        utils::testonly::create_l1_batch,
+    };
+
+    #[tokio::test]
+    async fn aggregating_sealed_l1_batches() {
+        let pool = ConnectionPool::test_pool().await;
+        let mut storage = pool.access_storage().await.unwrap();
+        ensure_genesis_state(&mut storage, L2ChainId::default(), &GenesisParams::mock())
+            .await
+            .unwrap();
+        drop(storage);
+
+        let aggregator = PaymasterAnalyticsAggregator::new(1_000, pool.clone());
+        assert_eq!(aggregator.aggregate_new_batches().await.unwrap(), 0);
+
+        let mut storage = pool.access_storage().await.unwrap();
+        for number in 1..=3 {
+            storage
+                .blocks_dal()
+                .insert_l1_batch(
+                    &create_l1_batch(number),
+                    &[],
+                    BlockGasCount::default(),
+                    &[],
+                    &[],
+                    0,
+                )
+                .await
+                .unwrap();
+        }
+        assert_eq!(aggregator.aggregate_new_batches().await.unwrap(), 3);
+        assert_eq!(aggregator.aggregate_new_batches().await.unwrap(), 0);
+
+        let last_aggregated_l1_batch = storage
+            .paymaster_analytics_dal()
+            .get_last_aggregated_l1_batch()
+            .await
+            .unwrap();
+        assert_eq!(last_aggregated_l1_batch, Some(L1BatchNumber(3)));
+        let stats = storage
+            .paymaster_analytics_dal()
+            .get_l1_batch_paymaster_stats(L1BatchNumber(2))
+            .await
+            .unwrap()
+            .expect("no stats for L1 batch");
+        assert_eq!(stats.sponsored_tx_count, 0);
+        assert!(stats.paymasters.is_empty());
+
+        // Emulate reverting L1 batch #3 without cleaning up the stats.
+        storage
+            .blocks_dal()
+            .delete_l1_batches(L1BatchNumber(2))
+            .await
+            .unwrap();
+        assert_eq!(aggregator.aggregate_new_batches().await.unwrap(), 0);
+        let last_aggregated_l1_batch = storage
+            .paymaster_analytics_dal()
+            .get_last_aggregated_l1_batch()
+            .await
+            .unwrap();
+        assert_eq!(last_aggregated_l1_batch, Some(L1BatchNumber(2)));
+        let stats = storage
+            .paymaster_analytics_dal()
+            .get_l1_batch_paymaster_stats(L1BatchNumber(3))
+            .await
+            .unwrap();
+        assert!(stats.is_none());
+    }
+}
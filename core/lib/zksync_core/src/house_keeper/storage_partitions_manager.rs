@@ -0,0 +1,150 @@
+use anyhow::Context as _;
+use async_trait::async_trait;
+use zksync_dal::{storage_partitions_dal::PartitionedTable, ConnectionPool};
+use zksync_types::MiniblockNumber;
+
+use crate::house_keeper::periodic_job::PeriodicJob;
+
+/// Manages partitions of the `storage_logs` and `events` tables. Creates new partitions ahead of the sealed miniblock,
+/// so that new data doesn't land in the default partition, and (optionally) detaches `events` partitions
+/// for old L1 batches.
+#[derive(Debug)]
+pub struct StoragePartitionsManager {
+    managing_interval_ms: u64,
+    partition_size: u32,
+    events_retention_l1_batches: Option<u32>,
+    connection_pool: ConnectionPool,
+}
+
+impl StoragePartitionsManager {
+    /// Default number of miniblocks covered by a single partition.
+    pub const DEFAULT_PARTITION_SIZE: u32 = 1_000_000;
+
+    /// Creates a new manager. `connection_pool` must point to the master DB since the manager executes DDL statements.
+    pub fn new(
+        managing_interval_ms: u64,
+        partition_size: u32,
+        events_retention_l1_batches: Option<u32>,
+        connection_pool: ConnectionPool,
+    ) -> Self {
+        assert!(partition_size > 0, "partition size must be positive");
+        Self {
+            managing_interval_ms,
+            partition_size,
+            events_retention_l1_batches,
+            connection_pool,
+        }
+    }
+
+    async fn manage_partitions(&self) -> anyhow::Result<()> {
+        let mut storage = self
+            .connection_pool
+            .access_storage_tagged("house_keeper")
+            .await?;
+        let sealed_miniblock = storage
+            .blocks_dal()
+            .get_sealed_miniblock_number()
+            .await
+            .context("get_sealed_miniblock_number()")?;
+        // Keep at least one partition ahead of the sealed miniblock.
+        let required_end = sealed_miniblock + self.partition_size;
+
+        for table in PartitionedTable::ALL {
+            let mut start = storage
+                .storage_partitions_dal()
+                .next_partition_start(table)
+                .await
+                .with_context(|| format!("next_partition_start({table:?})"))?;
+            while start <= required_end {
+                let end = start + self.partition_size;
+                storage
+                    .storage_partitions_dal()
+                    .create_partition(table, start..end)
+                    .await
+                    .with_context(|| format!("create_partition({table:?}, {start}..{end})"))?;
+                start = end;
+            }
+        }
+
+        if let Some(retention) = self.events_retention_l1_batches {
+            let sealed_l1_batch = storage
+                .blocks_dal()
+                .get_sealed_l1_batch_number()
+                .await
+                .context("get_sealed_l1_batch_number()")?;
+            let last_l1_batch_to_detach =
+                sealed_l1_batch.and_then(|number| number.0.checked_sub(retention));
+            if let Some(last_l1_batch) = last_l1_batch_to_detach {
+                storage
+                    .storage_partitions_dal()
+                    .detach_partitions_before(PartitionedTable::Events, last_l1_batch.into())
+                    .await
+                    .context("detach_partitions_before()")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl PeriodicJob for StoragePartitionsManager {
+    const SERVICE_NAME: &'static str = "StoragePartitionsManager";
+
+    async fn run_routine_task(&mut self) -> anyhow::Result<()> {
+        self.manage_partitions().await
+    }
+
+    fn polling_interval_ms(&self) -> u64 {
+        self.managing_interval_ms
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zksync_types::L2ChainId;
+
+    use super::*;
+    use crate::genesis::{ensure_genesis_state, GenesisParams};
+
+    #[tokio::test]
+    async fn creating_partitions_ahead() {
+        let pool = ConnectionPool::test_pool().await;
+        let mut storage = pool.access_storage().await.unwrap();
+        ensure_genesis_state(&mut storage, L2ChainId::default(), &GenesisParams::mock())
+            .await
+            .unwrap();
+        drop(storage);
+
+        let manager = StoragePartitionsManager::new(1_000, 10, None, pool.clone());
+        manager.manage_partitions().await.unwrap();
+        // Should be idempotent.
+        manager.manage_partitions().await.unwrap();
+
+        let mut storage = pool.access_storage().await.unwrap();
+        let partitions = storage
+            .storage_partitions_dal()
+            .get_partitions(PartitionedTable::StorageLogs)
+            .await
+            .unwrap();
+        let ranges: Vec<_> = partitions
+            .iter()
+            .map(|partition| partition.miniblocks.clone())
+            .collect();
+        // The legacy partition is empty; genesis storage logs are in the default partition.
+        assert_eq!(
+            ranges,
+            [
+                MiniblockNumber(0)..MiniblockNumber(0),
+                MiniblockNumber(1)..MiniblockNumber(11),
+            ]
+        );
+
+        let partitions = storage
+            .storage_partitions_dal()
+            .get_partitions(PartitionedTable::Events)
+            .await
+            .unwrap();
+        let last_partition = partitions.last().unwrap();
+        assert!(last_partition.miniblocks.end > MiniblockNumber(10));
+    }
+}
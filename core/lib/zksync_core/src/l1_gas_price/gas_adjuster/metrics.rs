@@ -1,12 +1,37 @@
 //! Gas adjuster metrics.
 
-use vise::{Gauge, Metrics};
+use vise::{Counter, EncodeLabelSet, Family, Gauge, Metrics};
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, EncodeLabelSet)]
+pub(super) struct FeeProviderLabels {
+    pub provider: String,
+}
+
+impl FeeProviderLabels {
+    pub fn new(provider: &str) -> Self {
+        Self {
+            provider: provider.to_owned(),
+        }
+    }
+}
 
 #[derive(Debug, Metrics)]
 #[metrics(prefix = "server_gas_adjuster")]
 pub(super) struct GasAdjusterMetrics {
     pub current_base_fee_per_gas: Gauge<u64>,
     pub median_base_fee_per_gas: Gauge<u64>,
+    pub current_blob_base_fee_per_gas: Gauge<u64>,
+    pub median_blob_base_fee_per_gas: Gauge<u64>,
+    /// Number of requests to L1 fee providers.
+    pub provider_requests: Family<FeeProviderLabels, Counter>,
+    /// Number of failed requests to L1 fee providers.
+    pub provider_errors: Family<FeeProviderLabels, Counter>,
+    /// Number of responses from L1 fee providers that contained outlier values.
+    pub provider_outliers: Family<FeeProviderLabels, Counter>,
+    /// Whether the last response of an L1 fee provider was successful and didn't contain outliers (1 or 0).
+    pub provider_healthy: Family<FeeProviderLabels, Gauge<u64>>,
+    /// Latest base fee per gas reported by an L1 fee provider.
+    pub provider_base_fee_per_gas: Family<FeeProviderLabels, Gauge<u64>>,
 }
 
 #[vise::register]
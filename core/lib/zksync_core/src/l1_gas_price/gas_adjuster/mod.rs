@@ -5,58 +5,113 @@ use std::{
     sync::{Arc, RwLock},
 };
 
+use anyhow::Context as _;
 use tokio::sync::watch;
 use zksync_config::GasAdjusterConfig;
-use zksync_eth_client::{Error, EthInterface};
+use zksync_eth_client::EthInterface;
 use zksync_system_constants::L1_GAS_PER_PUBDATA_BYTE;
 
-use self::metrics::METRICS;
+pub use self::providers::{HttpFeeOracle, L1FeeData, L1FeeProvider};
+use self::{
+    metrics::METRICS,
+    providers::{fetch_aggregated, NodeFeeProvider},
+};
 use super::{L1GasPriceProvider, L1TxParamsProvider};
 use crate::state_keeper::metrics::KEEPER_METRICS;
 
 mod metrics;
+mod providers;
 #[cfg(test)]
 mod tests;
 
 /// This component keeps track of the median base_fee from the last `max_base_fee_samples` blocks.
 /// It is used to adjust the base_fee of transactions sent to L1.
+///
+/// Fees are sampled from one or more [`L1FeeProvider`]s: the L1 node and (optionally) external fee oracles.
+/// Values reported by providers are aggregated by taking their median after rejecting outliers.
 #[derive(Debug)]
 pub struct GasAdjuster<E> {
     pub(super) statistics: GasStatistics,
+    /// Statistics for the blob base fee (EIP-4844); `None` if L1 doesn't report the blob base fee.
+    pub(super) blob_base_fee_statistics: Option<GasStatistics>,
     pub(super) config: GasAdjusterConfig,
-    eth_client: E,
+    eth_client: Arc<E>,
+    providers: Vec<Arc<dyn L1FeeProvider>>,
 }
 
 impl<E: EthInterface> GasAdjuster<E> {
-    pub async fn new(eth_client: E, config: GasAdjusterConfig) -> Result<Self, Error> {
+    /// Creates an adjuster sampling fees from the L1 node and external fee oracles specified in `config`.
+    pub async fn new(eth_client: E, config: GasAdjusterConfig) -> anyhow::Result<Self> {
+        let eth_client = Arc::new(eth_client);
+        let mut providers: Vec<Arc<dyn L1FeeProvider>> =
+            vec![Arc::new(NodeFeeProvider::new(eth_client.clone()))];
+        for url in &config.fee_oracle_urls {
+            let oracle = HttpFeeOracle::new(url.clone())
+                .with_context(|| format!("failed creating fee oracle for {url}"))?;
+            providers.push(Arc::new(oracle));
+        }
+        Self::with_providers(eth_client, config, providers).await
+    }
+
+    /// Creates an adjuster sampling fees from the specified providers. `eth_client` is only used
+    /// to track the latest L1 block.
+    pub async fn with_providers(
+        eth_client: Arc<E>,
+        config: GasAdjusterConfig,
+        providers: Vec<Arc<dyn L1FeeProvider>>,
+    ) -> anyhow::Result<Self> {
+        anyhow::ensure!(!providers.is_empty(), "no L1 fee providers specified");
         // Subtracting 1 from the "latest" block number to prevent errors in case
         // the info about the latest block is not yet present on the node.
         // This sometimes happens on Infura.
         let current_block = eth_client
             .block_number("gas_adjuster")
-            .await?
+            .await
+            .context("failed getting L1 block number")?
             .as_usize()
             .saturating_sub(1);
-        let history = eth_client
-            .base_fee_history(current_block, config.max_base_fee_samples, "gas_adjuster")
-            .await?;
+        let fee_data = fetch_aggregated(
+            &providers,
+            current_block,
+            config.max_base_fee_samples,
+            config.fee_outlier_threshold_percent(),
+        )
+        .await?;
+
+        let blob_base_fee_statistics = if let Some(blob_base_fee) = fee_data.blob_base_fee {
+            Some(GasStatistics::new(
+                config.max_base_fee_samples,
+                current_block,
+                &[blob_base_fee],
+            ))
+        } else {
+            tracing::info!("Blob base fee is not available on L1; it will not be tracked");
+            None
+        };
         Ok(Self {
-            statistics: GasStatistics::new(config.max_base_fee_samples, current_block, &history),
+            statistics: GasStatistics::new(
+                config.max_base_fee_samples,
+                current_block,
+                &fee_data.base_fee_history,
+            ),
+            blob_base_fee_statistics,
             eth_client,
+            providers,
             config,
         })
     }
 
     /// Performs an actualization routine for `GasAdjuster`.
     /// This method is intended to be invoked periodically.
-    pub async fn keep_updated(&self) -> Result<(), Error> {
+    pub async fn keep_updated(&self) -> anyhow::Result<()> {
         // Subtracting 1 from the "latest" block number to prevent errors in case
         // the info about the latest block is not yet present on the node.
         // This sometimes happens on Infura.
         let current_block = self
             .eth_client
             .block_number("gas_adjuster")
-            .await?
+            .await
+            .context("failed getting L1 block number")?
             .as_usize()
             .saturating_sub(1);
 
@@ -64,19 +119,29 @@ impl<E: EthInterface> GasAdjuster<E> {
 
         if current_block > last_processed_block {
             // Report the current price to be gathered by the statistics module.
-            let history = self
-                .eth_client
-                .base_fee_history(
-                    current_block,
-                    current_block - last_processed_block,
-                    "gas_adjuster",
-                )
-                .await?;
-
-            METRICS
-                .current_base_fee_per_gas
-                .set(*history.last().unwrap());
-            self.statistics.add_samples(&history);
+            let fee_data = fetch_aggregated(
+                &self.providers,
+                current_block,
+                current_block - last_processed_block,
+                self.config.fee_outlier_threshold_percent(),
+            )
+            .await?;
+
+            if let Some(&base_fee) = fee_data.base_fee_history.last() {
+                METRICS.current_base_fee_per_gas.set(base_fee);
+            }
+            self.statistics.add_samples(&fee_data.base_fee_history);
+            // Some providers may not report fees for all blocks, so the block is set explicitly.
+            self.statistics.set_last_processed_block(current_block);
+
+            // Unlike base fees, the blob base fee is sampled once per update rather than for each block.
+            if let Some(blob_base_fee_statistics) = &self.blob_base_fee_statistics {
+                let blob_base_fee = fee_data
+                    .blob_base_fee
+                    .context("L1 fee providers didn't report blob base fee")?;
+                METRICS.current_blob_base_fee_per_gas.set(blob_base_fee);
+                blob_base_fee_statistics.add_samples(&[blob_base_fee]);
+            }
         }
         Ok(())
     }
@@ -102,7 +167,7 @@ impl<E: EthInterface> GasAdjuster<E> {
             }
 
             if let Err(err) = self.keep_updated().await {
-                tracing::warn!("Cannot add the base fee to gas statistics: {}", err);
+                tracing::warn!("Cannot add the base fee to gas statistics: {err:#}");
             }
 
             tokio::time::sleep(self.config.poll_period()).await;
@@ -111,6 +176,20 @@ impl<E: EthInterface> GasAdjuster<E> {
     }
 }
 
+impl<E> GasAdjuster<E> {
+    fn scale_fee(&self, median: u64, time_in_mempool: u32) -> u64 {
+        let a = self.config.pricing_formula_parameter_a;
+        let b = self.config.pricing_formula_parameter_b;
+
+        // Currently we use an exponential formula.
+        // The alternative is a linear one:
+        // `let scale_factor = a + b * time_in_mempool as f64;`
+        let scale_factor = a * b.powf(time_in_mempool as f64);
+        let new_fee = median as f64 * scale_factor;
+        new_fee as u64
+    }
+}
+
 impl<E: EthInterface> L1GasPriceProvider for GasAdjuster<E> {
     /// Returns the sum of base and priority fee, in wei, not considering time in mempool.
     /// Can be used to get an estimate of current gas price.
@@ -142,17 +221,16 @@ impl<E: EthInterface> L1TxParamsProvider for GasAdjuster<E> {
     // In other words, in order to pay less fees, we are ready to wait longer.
     // But the longer we wait, the more we are ready to pay.
     fn get_base_fee(&self, time_in_mempool: u32) -> u64 {
-        let a = self.config.pricing_formula_parameter_a;
-        let b = self.config.pricing_formula_parameter_b;
-
-        // Currently we use an exponential formula.
-        // The alternative is a linear one:
-        // `let scale_factor = a + b * time_in_mempool as f64;`
-        let scale_factor = a * b.powf(time_in_mempool as f64);
         let median = self.statistics.median();
         METRICS.median_base_fee_per_gas.set(median);
-        let new_fee = median as f64 * scale_factor;
-        new_fee as u64
+        self.scale_fee(median, time_in_mempool)
+    }
+
+    // The blob base fee is priced using the same formula as the base fee.
+    fn get_blob_base_fee(&self, time_in_mempool: u32) -> Option<u64> {
+        let median = self.blob_base_fee_statistics.as_ref()?.median();
+        METRICS.median_blob_base_fee_per_gas.set(median);
+        Some(self.scale_fee(median, time_in_mempool))
     }
 
     fn get_next_block_minimal_base_fee(&self) -> u64 {
@@ -210,6 +288,9 @@ impl GasStatisticsInner {
     }
 
     fn add_samples(&mut self, fees: &[u64]) {
+        if fees.is_empty() {
+            return;
+        }
         self.samples.extend(fees);
         self.last_processed_block += fees.len();
 
@@ -250,4 +331,8 @@ impl GasStatistics {
     pub fn last_processed_block(&self) -> usize {
         self.0.read().unwrap().last_processed_block
     }
+
+    pub fn set_last_processed_block(&self, block: usize) {
+        self.0.write().unwrap().last_processed_block = block;
+    }
 }
@@ -0,0 +1,274 @@
+//! Sources of L1 fee data for the gas adjuster.
+
+use std::{collections::HashSet, fmt, sync::Arc, time::Duration};
+
+use anyhow::Context as _;
+use serde::Deserialize;
+use zksync_eth_client::EthInterface;
+
+use super::metrics::{FeeProviderLabels, METRICS};
+
+/// L1 fee data reported by an [`L1FeeProvider`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct L1FeeData {
+    /// Base fees per gas for consecutive L1 blocks, ordered by block number. The last value corresponds
+    /// to the most recent block.
+    pub base_fee_history: Vec<u64>,
+    /// Current blob base fee (EIP-4844), or `None` if it's not reported by the provider.
+    pub blob_base_fee: Option<u64>,
+}
+
+/// Source of L1 fee data used by the [`GasAdjuster`](super::GasAdjuster).
+#[async_trait::async_trait]
+pub trait L1FeeProvider: fmt::Debug + Send + Sync + 'static {
+    /// Name of the provider used in logs and metrics.
+    fn name(&self) -> &str;
+
+    /// Fetches fee data for up to `block_count` L1 blocks ending with `upto_block` (inclusive).
+    /// Providers without access to historical data may return fewer base fees; in this case, the returned
+    /// values are considered to correspond to the most recent blocks.
+    async fn fetch(&self, upto_block: usize, block_count: usize) -> anyhow::Result<L1FeeData>;
+}
+
+/// Provider using `eth_feeHistory` and `eth_blobBaseFee` methods of the L1 node.
+#[derive(Debug)]
+pub(super) struct NodeFeeProvider<E> {
+    eth_client: Arc<E>,
+}
+
+impl<E> NodeFeeProvider<E> {
+    pub fn new(eth_client: Arc<E>) -> Self {
+        Self { eth_client }
+    }
+}
+
+#[async_trait::async_trait]
+impl<E: EthInterface> L1FeeProvider for NodeFeeProvider<E> {
+    fn name(&self) -> &str {
+        "node"
+    }
+
+    async fn fetch(&self, upto_block: usize, block_count: usize) -> anyhow::Result<L1FeeData> {
+        let base_fee_history = self
+            .eth_client
+            .base_fee_history(upto_block, block_count, "gas_adjuster")
+            .await
+            .context("base_fee_history()")?;
+        // Not all L1 networks support blobs, so an error is not propagated.
+        let blob_base_fee = match self.eth_client.get_blob_base_fee("gas_adjuster").await {
+            Ok(fee) => Some(u64::try_from(fee).unwrap_or(u64::MAX)),
+            Err(err) => {
+                tracing::debug!("Blob base fee is not available from L1 node: {err}");
+                None
+            }
+        };
+        Ok(L1FeeData {
+            base_fee_history,
+            blob_base_fee,
+        })
+    }
+}
+
+/// Response of an external HTTP fee oracle. Fees are specified in wei.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FeeOracleResponse {
+    base_fee_per_gas: u64,
+    #[serde(default)]
+    blob_base_fee_per_gas: Option<u64>,
+}
+
+/// External fee oracle queried via HTTP. The oracle must respond to `GET` requests with a JSON object
+/// containing the current `baseFeePerGas` and (optionally) `blobBaseFeePerGas` in wei.
+#[derive(Debug)]
+pub struct HttpFeeOracle {
+    name: String,
+    url: String,
+    client: reqwest::Client,
+}
+
+impl HttpFeeOracle {
+    const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+    pub fn new(url: String) -> anyhow::Result<Self> {
+        let parsed_url =
+            reqwest::Url::parse(&url).with_context(|| format!("invalid URL: {url}"))?;
+        // Only the host is used in the name since the URL may contain secrets, e.g. API keys.
+        let host = parsed_url
+            .host_str()
+            .with_context(|| format!("URL {url} has no host"))?;
+        Ok(Self {
+            name: format!("oracle:{host}"),
+            url,
+            client: reqwest::Client::builder()
+                .timeout(Self::REQUEST_TIMEOUT)
+                .build()
+                .context("failed building HTTP client")?,
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl L1FeeProvider for HttpFeeOracle {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn fetch(&self, _upto_block: usize, _block_count: usize) -> anyhow::Result<L1FeeData> {
+        let response: FeeOracleResponse = self
+            .client
+            .get(&self.url)
+            .send()
+            .await
+            .context("failed sending request")?
+            .error_for_status()
+            .context("oracle responded with error")?
+            .json()
+            .await
+            .context("failed parsing response")?;
+        Ok(L1FeeData {
+            base_fee_history: vec![response.base_fee_per_gas],
+            blob_base_fee: response.blob_base_fee_per_gas,
+        })
+    }
+}
+
+/// Minimum number of values for which outliers can be determined. With fewer values, it's impossible
+/// to decide which of them is an outlier.
+const MIN_VALUES_FOR_OUTLIER_REJECTION: usize = 3;
+
+/// Aggregates values reported by different providers. Values deviating from their median by more than
+/// `outlier_threshold_percent` are rejected as outliers; the median of the remaining values is returned
+/// together with indices of the rejected values. Returns `None` if `values` are empty.
+///
+/// Outliers are only rejected if there are at least [`MIN_VALUES_FOR_OUTLIER_REJECTION`] values, and
+/// if not all values are outliers.
+pub(super) fn aggregate_values(
+    values: &[u64],
+    outlier_threshold_percent: u64,
+) -> Option<(u64, Vec<usize>)> {
+    let initial_median = median(values.iter().copied())?;
+    if values.len() < MIN_VALUES_FOR_OUTLIER_REJECTION {
+        return Some((initial_median, vec![]));
+    }
+    let is_outlier = |value: u64| {
+        let deviation = u128::from(value.abs_diff(initial_median)) * 100;
+        deviation > u128::from(initial_median) * u128::from(outlier_threshold_percent)
+    };
+    let outliers: Vec<_> = (0..values.len())
+        .filter(|&i| is_outlier(values[i]))
+        .collect();
+    let accepted = values.iter().copied().filter(|&value| !is_outlier(value));
+    // For an even number of values, all of them may deviate from the median (e.g., if values form
+    // 2 equally sized clusters); in this case, outliers cannot be determined.
+    match median(accepted) {
+        Some(median) => Some((median, outliers)),
+        None => Some((initial_median, vec![])),
+    }
+}
+
+/// Computes the median of `values`. For an even number of values, the two middle values are averaged.
+fn median(values: impl Iterator<Item = u64>) -> Option<u64> {
+    let mut values: Vec<_> = values.collect();
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_unstable();
+    let mid = values.len() / 2;
+    if values.len() % 2 == 1 {
+        Some(values[mid])
+    } else {
+        let sum = u128::from(values[mid - 1]) + u128::from(values[mid]);
+        Some((sum / 2) as u64) // cannot overflow since the average is not greater than its operands
+    }
+}
+
+/// Fee data fetched from providers and aggregated.
+#[derive(Debug, Default, PartialEq)]
+pub(super) struct AggregatedFeeData {
+    pub base_fee_history: Vec<u64>,
+    pub blob_base_fee: Option<u64>,
+}
+
+/// Fetches fee data from all `providers`, updates their health metrics and aggregates the data.
+/// Returns an error if all providers have failed.
+pub(super) async fn fetch_aggregated(
+    providers: &[Arc<dyn L1FeeProvider>],
+    upto_block: usize,
+    block_count: usize,
+    outlier_threshold_percent: u64,
+) -> anyhow::Result<AggregatedFeeData> {
+    let fetches = providers
+        .iter()
+        .map(|provider| provider.fetch(upto_block, block_count));
+    let results = futures::future::join_all(fetches).await;
+
+    let mut responses = Vec::with_capacity(providers.len());
+    for (provider, result) in providers.iter().zip(results) {
+        let labels = FeeProviderLabels::new(provider.name());
+        METRICS.provider_requests[&labels].inc();
+        match result {
+            Ok(mut data) => {
+                let excess_len = data.base_fee_history.len().saturating_sub(block_count);
+                data.base_fee_history.drain(..excess_len);
+                if let Some(&base_fee) = data.base_fee_history.last() {
+                    METRICS.provider_base_fee_per_gas[&labels].set(base_fee);
+                }
+                responses.push((labels, data));
+            }
+            Err(err) => {
+                tracing::warn!("L1 fee provider `{}` failed: {err:#}", provider.name());
+                METRICS.provider_errors[&labels].inc();
+                METRICS.provider_healthy[&labels].set(0);
+            }
+        }
+    }
+    anyhow::ensure!(!responses.is_empty(), "all L1 fee providers have failed");
+
+    let mut providers_with_outliers = HashSet::new();
+    let mut base_fee_history = Vec::with_capacity(block_count);
+    for position in 0..block_count {
+        let (labels, values): (Vec<_>, Vec<_>) = responses
+            .iter()
+            .filter_map(|(labels, data)| {
+                // Base fee histories are aligned by their last value.
+                let offset = block_count - data.base_fee_history.len();
+                let value = data.base_fee_history.get(position.checked_sub(offset)?)?;
+                Some((labels, *value))
+            })
+            .unzip();
+        let Some((base_fee, outliers)) = aggregate_values(&values, outlier_threshold_percent)
+        else {
+            continue;
+        };
+        base_fee_history.push(base_fee);
+        providers_with_outliers.extend(outliers.into_iter().map(|i| labels[i]));
+    }
+
+    let (labels, values): (Vec<_>, Vec<_>) = responses
+        .iter()
+        .filter_map(|(labels, data)| Some((labels, data.blob_base_fee?)))
+        .unzip();
+    let blob_base_fee =
+        aggregate_values(&values, outlier_threshold_percent).map(|(blob_base_fee, outliers)| {
+            providers_with_outliers.extend(outliers.into_iter().map(|i| labels[i]));
+            blob_base_fee
+        });
+
+    for (labels, _) in &responses {
+        let is_outlier = providers_with_outliers.contains(labels);
+        if is_outlier {
+            tracing::info!(
+                "L1 fee provider `{}` reported outlier values; they were ignored",
+                labels.provider
+            );
+            METRICS.provider_outliers[labels].inc();
+        }
+        METRICS.provider_healthy[labels].set(u64::from(!is_outlier));
+    }
+
+    Ok(AggregatedFeeData {
+        base_fee_history,
+        blob_base_fee,
+    })
+}
@@ -1,9 +1,66 @@
-use std::{collections::VecDeque, sync::Arc};
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+};
 
 use zksync_config::GasAdjusterConfig;
 use zksync_eth_client::clients::MockEthereum;
 
-use super::{GasAdjuster, GasStatisticsInner};
+use super::{
+    providers::{aggregate_values, NodeFeeProvider},
+    GasAdjuster, GasStatisticsInner, L1FeeData, L1FeeProvider,
+};
+use crate::l1_gas_price::L1TxParamsProvider;
+
+/// Provider reporting a single base fee, or an error if the fee is not set.
+#[derive(Debug)]
+struct FixedFeeProvider {
+    name: &'static str,
+    base_fee: Mutex<Option<u64>>,
+}
+
+impl FixedFeeProvider {
+    fn new(name: &'static str, base_fee: Option<u64>) -> Self {
+        Self {
+            name,
+            base_fee: Mutex::new(base_fee),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl L1FeeProvider for FixedFeeProvider {
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    async fn fetch(&self, _upto_block: usize, _block_count: usize) -> anyhow::Result<L1FeeData> {
+        let base_fee = self
+            .base_fee
+            .lock()
+            .unwrap()
+            .ok_or_else(|| anyhow::anyhow!("provider is down"))?;
+        Ok(L1FeeData {
+            base_fee_history: vec![base_fee],
+            blob_base_fee: None,
+        })
+    }
+}
+
+fn test_config(max_base_fee_samples: usize) -> GasAdjusterConfig {
+    GasAdjusterConfig {
+        default_priority_fee_per_gas: 5,
+        max_base_fee_samples,
+        pricing_formula_parameter_a: 1.5,
+        pricing_formula_parameter_b: 1.0005,
+        internal_l1_pricing_multiplier: 0.8,
+        internal_enforced_l1_gas_price: None,
+        poll_period: 5,
+        max_l1_gas_price: None,
+        fee_oracle_urls: vec![],
+        fee_outlier_threshold_percent: None,
+    }
+}
 
 /// Check that we compute the median correctly
 #[test]
@@ -44,6 +101,8 @@ async fn kept_updated() {
             internal_enforced_l1_gas_price: None,
             poll_period: 5,
             max_l1_gas_price: None,
+            fee_oracle_urls: vec![],
+            fee_outlier_threshold_percent: None,
         },
     )
     .await
@@ -58,3 +117,123 @@ async fn kept_updated() {
     assert_eq!(adjuster.statistics.0.read().unwrap().samples.len(), 5);
     assert_eq!(adjuster.statistics.0.read().unwrap().median(), 7);
 }
+
+/// Check that the blob base fee is tracked and priced alongside the base fee
+#[tokio::test]
+async fn blob_base_fee_is_tracked() {
+    let eth_client = Arc::new(
+        MockEthereum::default()
+            .with_fee_history(vec![0, 4, 6, 8, 7, 5, 5, 8, 10, 9])
+            .with_blob_base_fee(20.into()),
+    );
+    eth_client.advance_block_number(5);
+
+    let adjuster = GasAdjuster::new(
+        Arc::clone(&eth_client),
+        GasAdjusterConfig {
+            default_priority_fee_per_gas: 5,
+            max_base_fee_samples: 5,
+            pricing_formula_parameter_a: 1.5,
+            pricing_formula_parameter_b: 1.0005,
+            internal_l1_pricing_multiplier: 0.8,
+            internal_enforced_l1_gas_price: None,
+            poll_period: 5,
+            max_l1_gas_price: None,
+            fee_oracle_urls: vec![],
+            fee_outlier_threshold_percent: None,
+        },
+    )
+    .await
+    .unwrap();
+
+    let blob_statistics = adjuster.blob_base_fee_statistics.as_ref().unwrap();
+    assert_eq!(blob_statistics.median(), 20);
+    assert_eq!(adjuster.get_blob_base_fee(0), Some(30));
+
+    eth_client.advance_block_number(3);
+    adjuster.keep_updated().await.unwrap();
+    let blob_statistics = adjuster.blob_base_fee_statistics.as_ref().unwrap();
+    assert_eq!(blob_statistics.0.read().unwrap().samples.len(), 2);
+    assert_eq!(blob_statistics.median(), 20);
+}
+
+#[test]
+fn aggregating_values_from_providers() {
+    assert_eq!(aggregate_values(&[], 50), None);
+    assert_eq!(aggregate_values(&[42], 50), Some((42, vec![])));
+    // The median is 105; 500 deviates from it by more than 50%.
+    assert_eq!(
+        aggregate_values(&[100, 110, 90, 500], 50),
+        Some((100, vec![3]))
+    );
+    assert_eq!(
+        aggregate_values(&[100, 110, 90, 500], 500),
+        Some((105, vec![]))
+    );
+    // With 2 values, it's impossible to determine which one is an outlier.
+    assert_eq!(aggregate_values(&[100, 1_000], 50), Some((550, vec![])));
+    assert_eq!(
+        aggregate_values(&[100, 1_000, 900], 50),
+        Some((950, vec![0]))
+    );
+    // All values deviate from the median.
+    assert_eq!(
+        aggregate_values(&[10, 10, 100, 100], 50),
+        Some((55, vec![]))
+    );
+    assert_eq!(
+        aggregate_values(&[u64::MAX, u64::MAX], 50),
+        Some((u64::MAX, vec![]))
+    );
+}
+
+/// Check that fees are aggregated across multiple providers, ignoring outliers and failed providers
+#[tokio::test]
+async fn fees_are_aggregated_across_providers() {
+    let eth_client =
+        Arc::new(MockEthereum::default().with_fee_history(vec![0, 4, 6, 8, 7, 5, 5, 8, 10, 9]));
+    eth_client.advance_block_number(5);
+    let outlier_provider = Arc::new(FixedFeeProvider::new("outlier", Some(1_000)));
+    let providers: Vec<Arc<dyn L1FeeProvider>> = vec![
+        Arc::new(NodeFeeProvider::new(eth_client.clone())),
+        Arc::new(FixedFeeProvider::new("oracle", Some(9))),
+        outlier_provider.clone(),
+        Arc::new(FixedFeeProvider::new("failing", None)),
+    ];
+
+    let adjuster = GasAdjuster::with_providers(eth_client.clone(), test_config(5), providers)
+        .await
+        .unwrap();
+    // The last sample is the median of 7 (node) and 9 (oracle); 1000 is rejected as an outlier.
+    let samples = adjuster.statistics.0.read().unwrap().samples.clone();
+    assert_eq!(samples, VecDeque::from([0, 4, 6, 8, 8]));
+
+    eth_client.advance_block_number(3);
+    *outlier_provider.base_fee.lock().unwrap() = Some(10);
+    adjuster.keep_updated().await.unwrap();
+    let samples = adjuster.statistics.0.read().unwrap().samples.clone();
+    // Node reports 5, 5, 8 for new blocks; the last one is aggregated with 9 (oracle) and 10.
+    assert_eq!(samples, VecDeque::from([8, 8, 5, 5, 9]));
+    assert_eq!(adjuster.statistics.last_processed_block(), 7);
+}
+
+/// Check that updating fails if all providers are down
+#[tokio::test]
+async fn updating_fails_if_all_providers_fail() {
+    let eth_client =
+        Arc::new(MockEthereum::default().with_fee_history(vec![0, 4, 6, 8, 7, 5, 5, 8, 10, 9]));
+    eth_client.advance_block_number(5);
+    let provider = Arc::new(FixedFeeProvider::new("oracle", Some(6)));
+
+    let adjuster =
+        GasAdjuster::with_providers(eth_client.clone(), test_config(5), vec![provider.clone()])
+            .await
+            .unwrap();
+    assert_eq!(adjuster.statistics.median(), 6);
+
+    *provider.base_fee.lock().unwrap() = None;
+    eth_client.advance_block_number(3);
+    adjuster.keep_updated().await.unwrap_err();
+    assert_eq!(adjuster.statistics.median(), 6);
+    assert_eq!(adjuster.statistics.last_processed_block(), 4);
+}
@@ -2,7 +2,7 @@
 
 use std::fmt;
 
-pub use gas_adjuster::GasAdjuster;
+pub use gas_adjuster::{GasAdjuster, HttpFeeOracle, L1FeeData, L1FeeProvider};
 pub use main_node_fetcher::MainNodeFeeParamsFetcher;
 pub use singleton::GasAdjusterSingleton;
 
@@ -30,6 +30,9 @@ pub trait L1TxParamsProvider: L1GasPriceProvider {
     /// Returns the recommended `max_fee_per_gas` value (EIP1559).
     fn get_base_fee(&self, time_in_mempool: u32) -> u64;
 
+    /// Returns the recommended `max_fee_per_blob_gas` value (EIP4844), or `None` if L1 doesn't support blobs.
+    fn get_blob_base_fee(&self, time_in_mempool: u32) -> Option<u64>;
+
     /// Returns the recommended `max_priority_fee_per_gas` value (EIP1559).
     fn get_priority_fee(&self) -> u64;
 
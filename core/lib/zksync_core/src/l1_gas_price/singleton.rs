@@ -44,9 +44,10 @@ impl GasAdjusterSingleton {
             .get_or_init(|| async {
                 let query_client =
                     QueryClient::new(&self.web3_url).context("QueryClient::new()")?;
-                let adjuster = GasAdjuster::new(query_client.clone(), self.gas_adjuster_config)
-                    .await
-                    .context("GasAdjuster::new()")?;
+                let adjuster =
+                    GasAdjuster::new(query_client.clone(), self.gas_adjuster_config.clone())
+                        .await
+                        .context("GasAdjuster::new()")?;
                 Ok(Arc::new(adjuster))
             })
             .await;
@@ -1,9 +1,16 @@
 #![allow(clippy::upper_case_acronyms, clippy::derive_partial_eq_without_eq)]
 
-use std::{net::Ipv4Addr, str::FromStr, sync::Arc, time::Instant};
+use std::{
+    net::Ipv4Addr,
+    str::FromStr,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use anyhow::Context as _;
-use fee_model::MainNodeFeeInputProvider;
+use fee_model::{
+    main_node_fee_model_config, FeePricingCurve, FeePricingCurveLoader, MainNodeFeeInputProvider,
+};
 use futures::channel::oneshot;
 use prometheus_exporter::PrometheusExporterConfig;
 use temp_config_store::TempConfigStore;
@@ -21,39 +28,51 @@ use zksync_config::{
         },
         contracts::ProverAtGenesis,
         database::{MerkleTreeConfig, MerkleTreeMode},
+        TxEventSinkKind,
     },
-    ApiConfig, ContractsConfig, DBConfig, ETHSenderConfig, PostgresConfig,
+    ApiConfig, ContractsConfig, DBConfig, ETHClientConfig, ETHSenderConfig, PostgresConfig,
 };
 use zksync_contracts::{governance_contract, BaseSystemContracts};
 use zksync_dal::{healthcheck::ConnectionPoolHealthCheck, ConnectionPool};
-use zksync_eth_client::{
-    clients::{PKSigningClient, QueryClient},
-    BoundEthInterface, CallFunctionArgs, EthInterface,
-};
+use zksync_eth_client::{clients::QueryClient, CallFunctionArgs, EthInterface};
 use zksync_health_check::{CheckHealth, HealthStatus, ReactiveHealthCheck};
 use zksync_object_store::{ObjectStore, ObjectStoreFactory};
 use zksync_queued_job_processor::JobProcessor;
 use zksync_state::PostgresStorageCaches;
 use zksync_types::{
-    fee_model::FeeModelConfigV1,
+    commitment::L1BatchCommitmentMode,
     protocol_version::{L1VerifierConfig, VerifierParams},
     system_contracts::get_system_smart_contracts,
     web3::contract::tokens::Detokenize,
-    L2ChainId, PackedEthSignature, ProtocolVersionId,
+    Address, L1BatchNumber, L1ChainId, L2ChainId, PackedEthSignature, ProtocolVersionId,
 };
 
 use crate::{
     api_server::{
         contract_verification,
         execution_sandbox::{VmConcurrencyBarrier, VmConcurrencyLimiter},
+        explorer::ExplorerApi,
         healthcheck::HealthCheckHandle,
-        tx_sender::{ApiContracts, TxSender, TxSenderBuilder, TxSenderConfig},
+        tx_sender::{
+            ApiContracts, DbTxSubmissionPolicy, TxSender, TxSenderBuilder, TxSenderConfig,
+            TxSubmissionPolicy,
+        },
         web3,
-        web3::{state::InternalApiConfig, ApiServerHandles, Namespace},
+        web3::{
+            backend_jsonrpsee::{
+                rate_limit_middleware::MethodQuotas, response_cache_middleware::ResponseCache,
+            },
+            state::InternalApiConfig,
+            ApiServerHandles, Namespace,
+        },
     },
     basic_witness_input_producer::BasicWitnessInputProducer,
-    eth_sender::{Aggregator, EthTxAggregator, EthTxManager},
-    eth_watch::start_eth_watch,
+    da_dispatcher::{DataAvailabilityDispatcher, HttpDataAvailabilityClient},
+    eth_sender::{
+        Aggregator, EthTxAggregator, EthTxManager, OperatorPool, ProofVerifier, ResubmissionPolicy,
+    },
+    eth_watch::{start_eth_watch, PriorityOpsMonitor},
+    factory_deps_compression::FactoryDepsCompression,
     house_keeper::{
         blocks_state_reporter::L1BatchMetricsReporter,
         fri_proof_compressor_job_retry_manager::FriProofCompressorJobRetryManager,
@@ -63,37 +82,55 @@ use crate::{
         fri_scheduler_circuit_queuer::SchedulerCircuitQueuer,
         fri_witness_generator_jobs_retry_manager::FriWitnessGeneratorJobRetryManager,
         fri_witness_generator_queue_monitor::FriWitnessGeneratorStatsReporter,
-        periodic_job::PeriodicJob,
+        paymaster_analytics_aggregator::PaymasterAnalyticsAggregator, periodic_job::PeriodicJob,
+        storage_partitions_manager::StoragePartitionsManager,
         waiting_to_queued_fri_witness_job_mover::WaitingToQueuedFriWitnessJobMover,
     },
     l1_gas_price::{GasAdjusterSingleton, L1GasPriceProvider},
+    logs_bloom_backfill::LogsBloomBackfill,
     metadata_calculator::{MetadataCalculator, MetadataCalculatorConfig},
     metrics::{InitStage, APP_METRICS},
+    rocksdb_backup::RocksDBBackups,
     state_keeper::{
-        create_state_keeper, MempoolFetcher, MempoolGuard, MiniblockSealer, SequencerSealer,
+        create_state_keeper, mempool_ordering_policy, MempoolFetcher, MempoolGuard,
+        MiniblockSealer, ProtectiveReadsWriter, SequencerSealer,
     },
+    token_fetcher::{Erc20MetadataClient, HttpTokenPriceOracle, TokenFetcher},
+    tx_event_stream::{KafkaRestSink, NatsSink, StdoutJsonSink, TxEventSink, TxEventStream},
+    upgrade_orchestrator::ProtocolUpgradeOrchestrator,
 };
 
 pub mod api_server;
 pub mod basic_witness_input_producer;
 pub mod block_reverter;
+pub mod chain_registry;
 mod consensus;
 pub mod consistency_checker;
+pub mod da_dispatcher;
+pub mod db_pruner;
 pub mod eth_sender;
 pub mod eth_watch;
+pub mod factory_deps_compression;
 mod fee_model;
 pub mod gas_tracker;
 pub mod genesis;
 pub mod house_keeper;
 pub mod l1_gas_price;
+pub mod logs_bloom_backfill;
 pub mod metadata_calculator;
 mod metrics;
 pub mod proof_data_handler;
 pub mod reorg_detector;
+pub mod rocksdb_backup;
+pub mod snapshots_applier;
 pub mod state_keeper;
 pub mod sync_layer;
 pub mod temp_config_store;
+pub mod token_fetcher;
+pub mod tx_event_stream;
+pub mod upgrade_orchestrator;
 mod utils;
+pub mod witness_bundle_exporter;
 
 /// Inserts the initial information about zkSync tokens into the database.
 pub async fn genesis_init(
@@ -170,6 +207,7 @@ pub async fn genesis_init(
             system_contracts: get_system_smart_contracts(),
             first_verifier_address: contracts_config.verifier_addr,
             first_l1_verifier_config,
+            allocations: genesis::GenesisAllocations::default(),
         },
     )
     .await?;
@@ -186,6 +224,52 @@ pub async fn is_genesis_needed(postgres_config: &PostgresConfig) -> bool {
     storage.blocks_dal().is_genesis_needed().await.unwrap()
 }
 
+/// Validates chain-related configuration of the node (either the main node or an external node) against
+/// the [chain registry](chain_registry::ChainRegistry). The embedded registry is extended with the remote one
+/// if `chain_registry_url` is set.
+///
+/// The L1 chain ID is checked using the L1 RPC at `l1_rpc_url`, so that an RPC pointing to a wrong L1 network
+/// is detected as well.
+pub async fn validate_chain_config(
+    pool: &ConnectionPool,
+    chain_id: L2ChainId,
+    l1_chain_id: L1ChainId,
+    diamond_proxy_addr: Address,
+    l1_rpc_url: &str,
+    chain_registry_url: Option<&str>,
+) -> anyhow::Result<()> {
+    let mut registry = chain_registry::ChainRegistry::embedded();
+    if let Some(url) = chain_registry_url {
+        let remote_registry = chain_registry::ChainRegistry::fetch(url)
+            .await
+            .with_context(|| format!("failed fetching chain registry from `{url}`"))?;
+        registry = registry.merge(remote_registry);
+    }
+
+    let rpc_l1_chain_id = chain_registry::fetch_l1_chain_id(l1_rpc_url).await?;
+    anyhow::ensure!(
+        rpc_l1_chain_id == l1_chain_id,
+        "L1 RPC reports L1 chain ID {}, but the node is configured with L1 chain ID {}; check the L1 RPC URL",
+        rpc_l1_chain_id.0,
+        l1_chain_id.0
+    );
+
+    let mut storage = pool.access_storage().await.context("access_storage()")?;
+    let genesis_root_hash = storage
+        .blocks_dal()
+        .get_l1_batch_state_root(L1BatchNumber(0))
+        .await
+        .context("failed getting genesis root hash")?;
+    drop(storage);
+
+    registry.validate(&chain_registry::NodeChainParams {
+        chain_id,
+        l1_chain_id: rpc_l1_chain_id,
+        diamond_proxy_addr,
+        genesis_root_hash,
+    })
+}
+
 /// Sets up an interrupt handler and returns a future that resolves once an interrupt signal
 /// is received.
 pub fn setup_sigint_handler() -> oneshot::Receiver<()> {
@@ -230,6 +314,14 @@ pub enum Component {
     Housekeeper,
     /// Component for exposing APIs to prover for providing proof generation data and accepting proofs.
     ProofDataHandler,
+    /// Dispatcher of L1 batch pubdata to an external data availability layer.
+    DADispatcher,
+    /// Publisher of transaction lifecycle events to an external sink.
+    TxEventStream,
+    /// Fetcher of metadata and prices for bridged tokens.
+    TokenFetcher,
+    /// Etherscan-compatible explorer API.
+    ExplorerApi,
 }
 
 #[derive(Debug)]
@@ -264,6 +356,10 @@ impl FromStr for Components {
             "eth_tx_aggregator" => Ok(Components(vec![Component::EthTxAggregator])),
             "eth_tx_manager" => Ok(Components(vec![Component::EthTxManager])),
             "proof_data_handler" => Ok(Components(vec![Component::ProofDataHandler])),
+            "da_dispatcher" => Ok(Components(vec![Component::DADispatcher])),
+            "tx_event_stream" => Ok(Components(vec![Component::TxEventStream])),
+            "token_fetcher" => Ok(Components(vec![Component::TokenFetcher])),
+            "explorer_api" => Ok(Components(vec![Component::ExplorerApi])),
             other => Err(format!("{} is not a valid component name", other)),
         }
     }
@@ -283,18 +379,27 @@ pub async fn initialize_components(
     let db_config = configs.db_config.clone().context("db_config")?;
     let postgres_config = configs.postgres_config.clone().context("postgres_config")?;
 
+    if let Some(threshold) = postgres_config.slow_query_threshold() {
+        ConnectionPool::global_config().set_slow_query_threshold(threshold)?;
+    }
     let statement_timeout = postgres_config.statement_timeout();
     let pool_size = postgres_config.max_connections()?;
     let connection_pool = ConnectionPool::builder(postgres_config.master_url()?, pool_size)
         .build()
         .await
         .context("failed to build connection_pool")?;
-    let replica_connection_pool =
+    let mut replica_connection_pool =
         ConnectionPool::builder(postgres_config.replica_url()?, pool_size)
             .set_statement_timeout(statement_timeout)
             .build()
             .await
             .context("failed to build replica_connection_pool")?;
+    // If the replication lag threshold is set, read-after-write API queries fall back to master
+    // while the replica is lagging.
+    if let Some(max_replication_lag) = postgres_config.max_replication_lag() {
+        replica_connection_pool = replica_connection_pool
+            .with_master_fallback(connection_pool.clone(), max_replication_lag);
+    }
 
     let mut healthchecks: Vec<Box<dyn CheckHealth>> = Vec::new();
     let contracts_config = configs
@@ -321,7 +426,10 @@ pub async fn initialize_components(
     });
 
     let query_client = QueryClient::new(&eth_client_config.web3_url).unwrap();
-    let gas_adjuster_config = configs.gas_adjuster_config.context("gas_adjuster_config")?;
+    let gas_adjuster_config = configs
+        .gas_adjuster_config
+        .clone()
+        .context("gas_adjuster_config")?;
     let mut gas_adjuster =
         GasAdjusterSingleton::new(eth_client_config.web3_url.clone(), gas_adjuster_config);
 
@@ -351,6 +459,41 @@ pub async fn initialize_components(
         tokio::spawn(circuit_breaker_checker.run(cb_sender, stop_receiver.clone())),
     ];
 
+    // The fee pricing curve is shared by all components computing batch fee inputs and is reloaded at runtime.
+    let fee_pricing_curve_path = configs
+        .state_keeper_config
+        .as_ref()
+        .and_then(|config| config.fee_pricing_curve_path.as_ref());
+    let fee_pricing_curve = if let Some(path) = fee_pricing_curve_path {
+        let loader = FeePricingCurveLoader::new(path)
+            .await
+            .context("failed loading fee pricing curve")?;
+        let curve = loader.subscribe();
+        task_futures.push(tokio::spawn(loader.run(stop_receiver.clone())));
+        Some(curve)
+    } else {
+        None
+    };
+
+    // The state keeper mempool is shared with the HTTP API server, so that the operator can cancel transactions
+    // already loaded into it.
+    let mempool = if components.contains(&Component::StateKeeper) {
+        let mempool_config = configs.mempool_config.as_ref().context("mempool_config")?;
+        let next_priority_id = connection_pool
+            .access_storage()
+            .await
+            .unwrap()
+            .transactions_dal()
+            .next_priority_id()
+            .await;
+        let mempool = MempoolGuard::new(next_priority_id, mempool_config.capacity);
+        mempool.set_ordering_policy(mempool_ordering_policy(mempool_config));
+        mempool.register_metrics();
+        Some(mempool)
+    } else {
+        None
+    };
+
     if components.contains(&Component::WsApi)
         || components.contains(&Component::HttpApi)
         || components.contains(&Component::ContractVerificationApi)
@@ -378,6 +521,34 @@ pub async fn initialize_components(
         // program termination.
         let mut storage_caches = None;
 
+        let tx_submission_policy =
+            if components.contains(&Component::HttpApi) || components.contains(&Component::WsApi) {
+                let policy = Arc::new(DbTxSubmissionPolicy::default());
+                policy
+                    .reload(&replica_connection_pool)
+                    .await
+                    .context("failed loading tx submission rules")?;
+                let update_task = policy
+                    .clone()
+                    .run(replica_connection_pool.clone(), stop_receiver.clone());
+                task_futures.push(tokio::spawn(update_task));
+                Some(policy as Arc<dyn TxSubmissionPolicy>)
+            } else {
+                None
+            };
+        // The response cache is shared among HTTP and WS servers.
+        let response_cache =
+            if components.contains(&Component::HttpApi) || components.contains(&Component::WsApi) {
+                build_response_cache(
+                    &api_config.web3_json_rpc,
+                    &replica_connection_pool,
+                    &stop_receiver,
+                    &mut task_futures,
+                )
+            } else {
+                None
+            };
+
         if components.contains(&Component::HttpApi) {
             storage_caches = Some(
                 build_storage_caches(configs, &replica_connection_pool, &mut task_futures)
@@ -402,6 +573,14 @@ pub async fn initialize_components(
                 bounded_gas_adjuster.clone(),
                 state_keeper_config.save_call_traces,
                 storage_caches.clone().unwrap(),
+                tx_submission_policy.clone(),
+                configs
+                    .eth_sender_config
+                    .as_ref()
+                    .map(|config| ResubmissionPolicy::new(&config.sender)),
+                fee_pricing_curve.clone(),
+                response_cache.clone(),
+                mempool.clone(),
             )
             .await
             .context("run_http_api")?;
@@ -440,6 +619,9 @@ pub async fn initialize_components(
                 replica_connection_pool.clone(),
                 stop_receiver.clone(),
                 storage_caches,
+                tx_submission_policy,
+                fee_pricing_curve.clone(),
+                response_cache,
             )
             .await
             .context("run_ws_api")?;
@@ -493,13 +675,32 @@ pub async fn initialize_components(
             &configs.network_config.clone().context("network_config")?,
             &db_config,
             &configs.mempool_config.clone().context("mempool_config")?,
+            mempool.context("mempool")?,
             bounded_gas_adjuster,
             store_factory.create_store().await,
+            fee_pricing_curve.clone(),
+            &mut healthchecks,
             stop_receiver.clone(),
         )
         .await
         .context("add_state_keeper_to_task_futures()")?;
 
+        let backfill_pool = ConnectionPool::singleton(postgres_config.master_url()?)
+            .build()
+            .await
+            .context("failed to build backfill_pool")?;
+        let logs_bloom_backfill = LogsBloomBackfill::new(backfill_pool);
+        task_futures.push(tokio::spawn(logs_bloom_backfill.run(stop_receiver.clone())));
+
+        let compression_pool = ConnectionPool::singleton(postgres_config.master_url()?)
+            .build()
+            .await
+            .context("failed to build compression_pool")?;
+        let factory_deps_compression = FactoryDepsCompression::new(compression_pool);
+        task_futures.push(tokio::spawn(
+            factory_deps_compression.run(stop_receiver.clone()),
+        ));
+
         let elapsed = started_at.elapsed();
         APP_METRICS.init_latency[&InitStage::StateKeeper].set(elapsed);
         tracing::info!("initialized State Keeper in {elapsed:?}");
@@ -520,7 +721,7 @@ pub async fn initialize_components(
             .context("eth_watch_config")?;
         task_futures.push(
             start_eth_watch(
-                eth_watch_config,
+                eth_watch_config.clone(),
                 eth_watch_pool,
                 Box::new(query_client.clone()),
                 main_zksync_contract_address,
@@ -530,6 +731,17 @@ pub async fn initialize_components(
             .await
             .context("start_eth_watch()")?,
         );
+
+        let priority_ops_monitor_pool = ConnectionPool::singleton(postgres_config.master_url()?)
+            .build()
+            .await
+            .context("failed to build priority_ops_monitor_pool")?;
+        let priority_ops_monitor =
+            PriorityOpsMonitor::new(priority_ops_monitor_pool, &eth_watch_config);
+        healthchecks.push(Box::new(priority_ops_monitor.health_check()));
+        task_futures.push(tokio::spawn(
+            priority_ops_monitor.run(stop_receiver.clone()),
+        ));
         let elapsed = started_at.elapsed();
         APP_METRICS.init_latency[&InitStage::EthWatcher].set(elapsed);
         tracing::info!("initialized ETH-Watcher in {elapsed:?}");
@@ -547,21 +759,42 @@ pub async fn initialize_components(
             .eth_sender_config
             .clone()
             .context("eth_sender_config")?;
-        let eth_client =
-            PKSigningClient::from_config(&eth_sender, &contracts_config, &eth_client_config);
-        let nonce = eth_client.pending_nonce("eth_sender").await.unwrap();
-        let eth_tx_aggregator_actor = EthTxAggregator::new(
+        let operators =
+            OperatorPool::from_config(&eth_sender, &contracts_config, &eth_client_config)
+                .context("failed creating operator pool")?;
+        let base_nonces = operators
+            .pending_nonces("eth_sender")
+            .await
+            .context("failed getting operator nonces")?;
+        let commitment_mode = configs
+            .state_keeper_config
+            .as_ref()
+            .context("state_keeper_config")?
+            .l1_batch_commitment_mode;
+        let mut aggregator = Aggregator::new(
             eth_sender.sender.clone(),
-            Aggregator::new(
-                eth_sender.sender.clone(),
-                store_factory.create_store().await,
-            ),
-            Arc::new(eth_client),
+            store_factory.create_store().await,
+            commitment_mode,
+        );
+        if commitment_mode == L1BatchCommitmentMode::Validium
+            && configs.da_dispatcher_config.is_some()
+        {
+            aggregator = aggregator.with_da_inclusion_required();
+        }
+        let mut eth_tx_aggregator_actor = EthTxAggregator::new(
+            eth_sender.sender.clone(),
+            aggregator,
+            operators,
             contracts_config.validator_timelock_addr,
             contracts_config.l1_multicall3_addr,
             main_zksync_contract_address,
-            nonce.as_u64(),
+            base_nonces,
         );
+        if let Some(path) = &eth_sender.sender.proof_verification_key_path {
+            let verifier =
+                ProofVerifier::from_file(path).context("failed loading proof verifier")?;
+            eth_tx_aggregator_actor = eth_tx_aggregator_actor.with_proof_verifier(verifier);
+        }
         task_futures.push(tokio::spawn(
             eth_tx_aggregator_actor.run(eth_sender_pool, stop_receiver.clone()),
         ));
@@ -581,15 +814,16 @@ pub async fn initialize_components(
             .eth_sender_config
             .clone()
             .context("eth_sender_config")?;
-        let eth_client =
-            PKSigningClient::from_config(&eth_sender, &contracts_config, &eth_client_config);
+        let operators =
+            OperatorPool::from_config(&eth_sender, &contracts_config, &eth_client_config)
+                .context("failed creating operator pool")?;
         let eth_tx_manager_actor = EthTxManager::new(
             eth_sender.sender,
             gas_adjuster
                 .get_or_init()
                 .await
                 .context("gas_adjuster.get_or_init()")?,
-            Arc::new(eth_client),
+            operators,
         );
         task_futures.extend([tokio::spawn(
             eth_tx_manager_actor.run(eth_manager_pool, stop_receiver.clone()),
@@ -649,6 +883,102 @@ pub async fn initialize_components(
         )));
     }
 
+    if components.contains(&Component::DADispatcher) {
+        let da_config = configs
+            .da_dispatcher_config
+            .clone()
+            .context("da_dispatcher_config")?;
+        let commitment_mode = configs
+            .state_keeper_config
+            .as_ref()
+            .context("state_keeper_config")?
+            .l1_batch_commitment_mode;
+        anyhow::ensure!(
+            commitment_mode == L1BatchCommitmentMode::Validium,
+            "data availability dispatcher can only be run in the validium mode"
+        );
+        let da_dispatcher_pool = ConnectionPool::singleton(postgres_config.master_url()?)
+            .build()
+            .await
+            .context("failed to build da_dispatcher_pool")?;
+        let publisher = Arc::new(HttpDataAvailabilityClient::new(
+            &da_config.api_url,
+            da_config.request_timeout(),
+        )?);
+        let da_dispatcher =
+            DataAvailabilityDispatcher::new(da_dispatcher_pool, da_config, publisher);
+        task_futures.push(tokio::spawn(da_dispatcher.run(stop_receiver.clone())));
+    }
+
+    if components.contains(&Component::TxEventStream) {
+        let config = configs
+            .tx_event_stream_config
+            .clone()
+            .context("tx_event_stream_config")?;
+        let sink: Arc<dyn TxEventSink> = match config.sink {
+            TxEventSinkKind::StdoutJson => Arc::new(StdoutJsonSink::default()),
+            TxEventSinkKind::Nats => {
+                let nats_url = config
+                    .nats_url
+                    .as_deref()
+                    .context("NATS URL is required for the NATS transaction event sink")?;
+                Arc::new(NatsSink::connect(nats_url, config.nats_subject.clone()).await?)
+            }
+            TxEventSinkKind::Kafka => {
+                let proxy_url = config.kafka_rest_proxy_url.as_deref().context(
+                    "Kafka REST proxy URL is required for the Kafka transaction event sink",
+                )?;
+                Arc::new(KafkaRestSink::new(proxy_url, &config.kafka_topic)?)
+            }
+        };
+        let tx_event_stream_pool = ConnectionPool::singleton(postgres_config.master_url()?)
+            .build()
+            .await
+            .context("failed to build tx_event_stream_pool")?;
+        let tx_event_stream = TxEventStream::new(tx_event_stream_pool, config, sink);
+        task_futures.push(tokio::spawn(tx_event_stream.run(stop_receiver.clone())));
+    }
+
+    if components.contains(&Component::ExplorerApi) {
+        let config = configs
+            .explorer_api_config
+            .clone()
+            .context("explorer_api_config")?;
+        let network_config = configs.network_config.clone().context("network_config")?;
+        // Explorer queries may be expensive, so they use a dedicated pool with a separate statement timeout.
+        let explorer_pool = ConnectionPool::builder(postgres_config.replica_url()?, pool_size)
+            .set_statement_timeout(Some(config.statement_timeout()))
+            .build()
+            .await
+            .context("failed to build explorer_pool")?;
+        let api = ExplorerApi::new(explorer_pool, network_config.zksync_network_id, &config);
+        task_futures.push(tokio::spawn(
+            api.run_server(config.bind_addr(), stop_receiver.clone()),
+        ));
+    }
+
+    if components.contains(&Component::TokenFetcher) {
+        let config = configs
+            .token_fetcher_config
+            .clone()
+            .context("token_fetcher_config")?;
+        let token_fetcher_pool = ConnectionPool::singleton(postgres_config.master_url()?)
+            .build()
+            .await
+            .context("failed to build token_fetcher_pool")?;
+        let price_oracle_url = config.price_oracle_url.clone();
+        let l1_client = Arc::new(Erc20MetadataClient::new(
+            query_client.clone(),
+            contracts_config.l1_erc20_bridge_proxy_addr,
+        ));
+        let mut token_fetcher = TokenFetcher::new(token_fetcher_pool, config, l1_client);
+        if let Some(url) = price_oracle_url {
+            token_fetcher =
+                token_fetcher.with_price_oracle(Arc::new(HttpTokenPriceOracle::new(&url)));
+        }
+        task_futures.push(tokio::spawn(token_fetcher.run(stop_receiver.clone())));
+    }
+
     // Run healthcheck server for all components.
     healthchecks.push(Box::new(ConnectionPoolHealthCheck::new(
         replica_connection_pool,
@@ -658,8 +988,11 @@ pub async fn initialize_components(
         .health_check_config
         .clone()
         .context("health_check_config")?;
-    let health_check_handle =
-        HealthCheckHandle::spawn_server(healtcheck_api_config.bind_addr(), healthchecks);
+    let health_check_handle = HealthCheckHandle::spawn_server(
+        healtcheck_api_config.bind_addr(),
+        healtcheck_api_config.admin_bind_addr(),
+        healthchecks,
+    );
 
     if let Some(task) = gas_adjuster.run_if_initialized(stop_receiver.clone()) {
         task_futures.push(task);
@@ -676,8 +1009,11 @@ async fn add_state_keeper_to_task_futures<E: L1GasPriceProvider + Send + Sync +
     network_config: &NetworkConfig,
     db_config: &DBConfig,
     mempool_config: &MempoolConfig,
+    mempool: MempoolGuard,
     gas_adjuster: Arc<E>,
     object_store: Arc<dyn ObjectStore>,
+    fee_pricing_curve: Option<watch::Receiver<FeePricingCurve>>,
+    healthchecks: &mut Vec<Box<dyn CheckHealth>>,
     stop_receiver: watch::Receiver<bool>,
 ) -> anyhow::Result<()> {
     let pool_builder = ConnectionPool::singleton(postgres_config.master_url()?);
@@ -685,22 +1021,13 @@ async fn add_state_keeper_to_task_futures<E: L1GasPriceProvider + Send + Sync +
         .build()
         .await
         .context("failed to build state_keeper_pool")?;
-    let next_priority_id = state_keeper_pool
-        .access_storage()
-        .await
-        .unwrap()
-        .transactions_dal()
-        .next_priority_id()
-        .await;
-    let mempool = MempoolGuard::new(next_priority_id, mempool_config.capacity);
-    mempool.register_metrics();
-
-    let batch_fee_input_provider = Arc::new(MainNodeFeeInputProvider::new(
-        gas_adjuster,
-        zksync_types::fee_model::FeeModelConfig::V1(FeeModelConfigV1 {
-            minimal_l2_gas_price: state_keeper_config.fair_l2_gas_price,
-        }),
-    ));
+    let fee_model_config =
+        main_node_fee_model_config(&state_keeper_config).context("invalid fee model config")?;
+    let batch_fee_input_provider = Arc::new(
+        MainNodeFeeInputProvider::new(gas_adjuster, fee_model_config)
+            .with_pricing_curve(fee_pricing_curve)
+            .with_commitment_mode(state_keeper_config.l1_batch_commitment_mode),
+    );
 
     let miniblock_sealer_pool = pool_builder
         .build()
@@ -712,6 +1039,35 @@ async fn add_state_keeper_to_task_futures<E: L1GasPriceProvider + Send + Sync +
     );
     task_futures.push(tokio::spawn(miniblock_sealer.run()));
 
+    if state_keeper_config.async_protective_reads {
+        let protective_reads_pool = ConnectionPool::builder(postgres_config.master_url()?, 2)
+            .build()
+            .await
+            .context("failed to build protective_reads_pool")?;
+        let protective_reads_writer =
+            ProtectiveReadsWriter::new(protective_reads_pool, network_config.zksync_network_id);
+        task_futures.push(tokio::spawn(
+            protective_reads_writer.run(stop_receiver.clone()),
+        ));
+    }
+
+    let upgrade_gate = if state_keeper_config.upgrade_orchestration_enabled {
+        let orchestrator_pool = pool_builder
+            .build()
+            .await
+            .context("failed to build upgrade_orchestrator_pool")?;
+        let orchestrator =
+            ProtocolUpgradeOrchestrator::new(orchestrator_pool, &state_keeper_config)
+                .await
+                .context("failed initializing protocol upgrade orchestrator")?;
+        let gate = orchestrator.gate();
+        healthchecks.push(Box::new(orchestrator.health_check()));
+        task_futures.push(tokio::spawn(orchestrator.run(stop_receiver.clone())));
+        Some(gate)
+    } else {
+        None
+    };
+
     let state_keeper = create_state_keeper(
         contracts_config,
         state_keeper_config,
@@ -723,9 +1079,10 @@ async fn add_state_keeper_to_task_futures<E: L1GasPriceProvider + Send + Sync +
         batch_fee_input_provider.clone(),
         miniblock_sealer_handle,
         object_store,
+        upgrade_gate,
         stop_receiver.clone(),
     )
-    .await;
+    .await?;
     task_futures.push(tokio::spawn(state_keeper.run()));
 
     let mempool_fetcher_pool = pool_builder
@@ -778,6 +1135,17 @@ async fn add_trees_to_task_futures(
         MerkleTreeMode::Lightweight => None,
         MerkleTreeMode::Full => Some(store_factory.create_store().await),
     };
+    let backups = match db_config.rocksdb_backup_interval() {
+        Some(interval) => {
+            let backups = RocksDBBackups::new(
+                store_factory.create_store().await,
+                "merkle_tree",
+                &db_config.merkle_tree.path,
+            );
+            Some((backups, interval))
+        }
+        None => None,
+    };
 
     run_tree(
         task_futures,
@@ -787,6 +1155,7 @@ async fn add_trees_to_task_futures(
         api_config,
         &operation_config,
         object_store,
+        backups,
         stop_receiver,
     )
     .await
@@ -802,6 +1171,7 @@ async fn run_tree(
     api_config: Option<&MerkleTreeApiConfig>,
     operation_manager: &OperationsManagerConfig,
     object_store: Option<Arc<dyn ObjectStore>>,
+    backups: Option<(RocksDBBackups, Duration)>,
     stop_receiver: watch::Receiver<bool>,
 ) -> anyhow::Result<()> {
     let started_at = Instant::now();
@@ -813,7 +1183,16 @@ async fn run_tree(
     tracing::info!("Initializing Merkle tree in {mode_str} mode");
 
     let config = MetadataCalculatorConfig::for_main_node(merkle_tree_config, operation_manager);
-    let metadata_calculator = MetadataCalculator::new(config, object_store).await;
+    if let Some((backups, _)) = &backups {
+        backups
+            .restore_if_needed()
+            .await
+            .context("failed restoring Merkle tree from backup")?;
+    }
+    let mut metadata_calculator = MetadataCalculator::new(config, object_store).await;
+    if let Some((backups, interval)) = backups {
+        metadata_calculator = metadata_calculator.with_backups(backups, interval);
+    }
     if let Some(api_config) = api_config {
         let address = (Ipv4Addr::UNSPECIFIED, api_config.port).into();
         let tree_reader = metadata_calculator.tree_reader();
@@ -828,6 +1207,9 @@ async fn run_tree(
 
     let tree_health_check = metadata_calculator.tree_health_check();
     healthchecks.push(Box::new(tree_health_check));
+    if let Some(health_check) = metadata_calculator.consistency_health_check() {
+        healthchecks.push(Box::new(health_check));
+    }
     let pool = ConnectionPool::singleton(postgres_config.master_url()?)
         .build()
         .await
@@ -897,6 +1279,32 @@ async fn add_house_keeper_to_task_futures(
     .context("failed to build a prover_connection_pool")?;
     task_futures.push(tokio::spawn(l1_batch_metrics_reporter.run()));
 
+    if let Some(interval_ms) = house_keeper_config.storage_partitions_managing_interval_ms {
+        // The partitions manager executes DDL statements, so it needs the master DB.
+        let master_pool = ConnectionPool::singleton(postgres_config.master_url()?)
+            .build()
+            .await
+            .context("failed to build a connection pool for partitions manager")?;
+        let partitions_manager = StoragePartitionsManager::new(
+            interval_ms,
+            house_keeper_config
+                .storage_partition_size_in_miniblocks
+                .unwrap_or(StoragePartitionsManager::DEFAULT_PARTITION_SIZE),
+            house_keeper_config.events_retention_l1_batches,
+            master_pool,
+        );
+        task_futures.push(tokio::spawn(partitions_manager.run()));
+    }
+
+    if let Some(interval_ms) = house_keeper_config.paymaster_analytics_interval_ms {
+        let master_pool = ConnectionPool::singleton(postgres_config.master_url()?)
+            .build()
+            .await
+            .context("failed to build a connection pool for paymaster analytics aggregator")?;
+        let aggregator = PaymasterAnalyticsAggregator::new(interval_ms, master_pool);
+        task_futures.push(tokio::spawn(aggregator.run()));
+    }
+
     // All FRI Prover related components are configured below.
     let fri_prover_config = configs
         .fri_prover_config
@@ -998,30 +1406,45 @@ fn build_storage_caches(
     Ok(storage_caches)
 }
 
+fn build_response_cache(
+    web3_config: &Web3JsonRpcConfig,
+    replica_connection_pool: &ConnectionPool,
+    stop_receiver: &watch::Receiver<bool>,
+    task_futures: &mut Vec<JoinHandle<anyhow::Result<()>>>,
+) -> Option<ResponseCache> {
+    let capacity = web3_config.response_cache_size() as u64;
+    if capacity == 0 {
+        return None;
+    }
+    let cache = ResponseCache::new(capacity, web3_config.response_cache_ttl());
+    let update_task = cache
+        .clone()
+        .run_finality_updates(replica_connection_pool.clone(), stop_receiver.clone());
+    task_futures.push(tokio::spawn(update_task));
+    Some(cache)
+}
+
 async fn build_tx_sender(
     tx_sender_config: &TxSenderConfig,
     web3_json_config: &Web3JsonRpcConfig,
     state_keeper_config: &StateKeeperConfig,
     replica_pool: ConnectionPool,
     master_pool: ConnectionPool,
-    l1_gas_price_provider: Arc<dyn L1GasPriceProvider>,
+    batch_fee_input_provider: MainNodeFeeInputProvider,
     storage_caches: PostgresStorageCaches,
+    submission_policy: Option<Arc<dyn TxSubmissionPolicy>>,
 ) -> (TxSender, VmConcurrencyBarrier) {
     let sequencer_sealer = SequencerSealer::new(state_keeper_config.clone());
-    let tx_sender_builder = TxSenderBuilder::new(tx_sender_config.clone(), replica_pool)
+    let mut tx_sender_builder = TxSenderBuilder::new(tx_sender_config.clone(), replica_pool)
         .with_main_connection_pool(master_pool)
         .with_sealer(Arc::new(sequencer_sealer));
+    if let Some(policy) = submission_policy {
+        tx_sender_builder = tx_sender_builder.with_submission_policy(policy);
+    }
 
     let max_concurrency = web3_json_config.vm_concurrency_limit();
     let (vm_concurrency_limiter, vm_barrier) = VmConcurrencyLimiter::new(max_concurrency);
 
-    let batch_fee_input_provider = MainNodeFeeInputProvider::new(
-        l1_gas_price_provider,
-        zksync_types::fee_model::FeeModelConfig::V1(FeeModelConfigV1 {
-            minimal_l2_gas_price: state_keeper_config.fair_l2_gas_price,
-        }),
-    );
-
     let tx_sender = tx_sender_builder
         .build(
             Arc::new(batch_fee_input_provider),
@@ -1046,15 +1469,26 @@ async fn run_http_api<G: L1GasPriceProvider + Send + Sync + 'static>(
     gas_adjuster: Arc<G>,
     with_debug_namespace: bool,
     storage_caches: PostgresStorageCaches,
+    submission_policy: Option<Arc<dyn TxSubmissionPolicy>>,
+    resubmission_policy: Option<ResubmissionPolicy>,
+    fee_pricing_curve: Option<watch::Receiver<FeePricingCurve>>,
+    response_cache: Option<ResponseCache>,
+    mempool: Option<MempoolGuard>,
 ) -> anyhow::Result<ApiServerHandles> {
+    let fee_model_config =
+        main_node_fee_model_config(state_keeper_config).context("invalid fee model config")?;
+    let batch_fee_input_provider = MainNodeFeeInputProvider::new(gas_adjuster, fee_model_config)
+        .with_pricing_curve(fee_pricing_curve)
+        .with_commitment_mode(state_keeper_config.l1_batch_commitment_mode);
     let (tx_sender, vm_barrier) = build_tx_sender(
         tx_sender_config,
         &api_config.web3_json_rpc,
         state_keeper_config,
         replica_connection_pool.clone(),
         master_connection_pool,
-        gas_adjuster,
+        batch_fee_input_provider,
         storage_caches,
+        submission_policy,
     )
     .await;
 
@@ -1063,20 +1497,42 @@ async fn run_http_api<G: L1GasPriceProvider + Send + Sync + 'static>(
         namespaces.push(Namespace::Debug)
     }
     namespaces.push(Namespace::Snapshots);
+    if api_config.web3_json_rpc.admin_namespace_enabled() {
+        namespaces.push(Namespace::Admin);
+    }
 
     let last_miniblock_pool = ConnectionPool::singleton(postgres_config.replica_url()?)
         .build()
         .await
         .context("failed to build last_miniblock_pool")?;
+    let node_stats_pool = ConnectionPool::singleton(postgres_config.replica_url()?)
+        .build()
+        .await
+        .context("failed to build node_stats_pool")?;
 
     let api_builder =
         web3::ApiBuilder::jsonrpsee_backend(internal_api.clone(), replica_connection_pool)
             .http(api_config.web3_json_rpc.http_port)
+            .with_batch_request_concurrency(api_config.web3_json_rpc.batch_request_concurrency())
             .with_last_miniblock_pool(last_miniblock_pool)
+            .with_node_stats_pool(node_stats_pool)
             .with_filter_limit(api_config.web3_json_rpc.filters_limit())
             .with_tree_api(api_config.web3_json_rpc.tree_api_url())
             .with_batch_request_size_limit(api_config.web3_json_rpc.max_batch_request_size())
             .with_response_body_size_limit(api_config.web3_json_rpc.max_response_body_size())
+            .with_method_quotas(method_quotas(&api_config.web3_json_rpc))
+            .with_rate_limit_api_key_header(
+                api_config.web3_json_rpc.rate_limit_api_key_header.clone(),
+            )
+            .with_rate_limit_trusted_proxy_count(
+                api_config
+                    .web3_json_rpc
+                    .rate_limit_trusted_proxy_count
+                    .unwrap_or(0),
+            )
+            .with_resubmission_policy(resubmission_policy)
+            .with_mempool(mempool)
+            .with_response_cache(response_cache)
             .with_tx_sender(tx_sender, vm_barrier)
             .enable_api_namespaces(namespaces);
     api_builder.build(stop_receiver).await
@@ -1094,21 +1550,34 @@ async fn run_ws_api<G: L1GasPriceProvider + Send + Sync + 'static>(
     replica_connection_pool: ConnectionPool,
     stop_receiver: watch::Receiver<bool>,
     storage_caches: PostgresStorageCaches,
+    submission_policy: Option<Arc<dyn TxSubmissionPolicy>>,
+    fee_pricing_curve: Option<watch::Receiver<FeePricingCurve>>,
+    response_cache: Option<ResponseCache>,
 ) -> anyhow::Result<ApiServerHandles> {
+    let fee_model_config =
+        main_node_fee_model_config(state_keeper_config).context("invalid fee model config")?;
+    let batch_fee_input_provider = MainNodeFeeInputProvider::new(gas_adjuster, fee_model_config)
+        .with_pricing_curve(fee_pricing_curve)
+        .with_commitment_mode(state_keeper_config.l1_batch_commitment_mode);
     let (tx_sender, vm_barrier) = build_tx_sender(
         tx_sender_config,
         &api_config.web3_json_rpc,
         state_keeper_config,
         replica_connection_pool.clone(),
         master_connection_pool,
-        gas_adjuster,
+        batch_fee_input_provider,
         storage_caches,
+        submission_policy,
     )
     .await;
     let last_miniblock_pool = ConnectionPool::singleton(postgres_config.replica_url()?)
         .build()
         .await
         .context("failed to build last_miniblock_pool")?;
+    let node_stats_pool = ConnectionPool::singleton(postgres_config.replica_url()?)
+        .build()
+        .await
+        .context("failed to build node_stats_pool")?;
 
     let mut namespaces = Namespace::DEFAULT.to_vec();
     namespaces.push(Namespace::Snapshots);
@@ -1117,23 +1586,50 @@ async fn run_ws_api<G: L1GasPriceProvider + Send + Sync + 'static>(
         web3::ApiBuilder::jsonrpsee_backend(internal_api.clone(), replica_connection_pool)
             .ws(api_config.web3_json_rpc.ws_port)
             .with_last_miniblock_pool(last_miniblock_pool)
+            .with_node_stats_pool(node_stats_pool)
             .with_filter_limit(api_config.web3_json_rpc.filters_limit())
             .with_subscriptions_limit(api_config.web3_json_rpc.subscriptions_limit())
             .with_batch_request_size_limit(api_config.web3_json_rpc.max_batch_request_size())
             .with_response_body_size_limit(api_config.web3_json_rpc.max_response_body_size())
+            .with_method_quotas(method_quotas(&api_config.web3_json_rpc))
+            .with_rate_limit_api_key_header(
+                api_config.web3_json_rpc.rate_limit_api_key_header.clone(),
+            )
+            .with_rate_limit_trusted_proxy_count(
+                api_config
+                    .web3_json_rpc
+                    .rate_limit_trusted_proxy_count
+                    .unwrap_or(0),
+            )
             .with_websocket_requests_per_minute_limit(
                 api_config
                     .web3_json_rpc
                     .websocket_requests_per_minute_limit(),
             )
+            .with_websocket_subscriptions_per_connection_limit(
+                api_config
+                    .web3_json_rpc
+                    .websocket_subscriptions_per_connection_limit(),
+            )
+            .with_websocket_idle_timeout(api_config.web3_json_rpc.websocket_idle_timeout())
+            .with_pub_sub_send_timeout(api_config.web3_json_rpc.pubsub_send_timeout())
             .with_polling_interval(api_config.web3_json_rpc.pubsub_interval())
             .with_tree_api(api_config.web3_json_rpc.tree_api_url())
+            .with_response_cache(response_cache)
             .with_tx_sender(tx_sender, vm_barrier)
             .enable_api_namespaces(namespaces);
 
     api_builder.build(stop_receiver.clone()).await
 }
 
+fn method_quotas(config: &Web3JsonRpcConfig) -> MethodQuotas {
+    MethodQuotas {
+        read: config.read_requests_per_minute_limit,
+        send_tx: config.send_tx_requests_per_minute_limit,
+        debug: config.debug_requests_per_minute_limit,
+    }
+}
+
 async fn circuit_breakers_for_components(
     components: &[Component],
     postgres_config: &PostgresConfig,
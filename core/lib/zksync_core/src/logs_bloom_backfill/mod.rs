@@ -0,0 +1,97 @@
+//! Background backfilling of logs blooms for miniblocks sealed before the blooms were introduced.
+
+use std::time::Instant;
+
+use anyhow::Context as _;
+use tokio::sync::watch;
+use zksync_dal::ConnectionPool;
+use zksync_types::MiniblockNumber;
+
+#[cfg(test)]
+mod tests;
+
+/// Computes logs blooms for miniblocks that don't have them, walking from the latest such miniblock
+/// towards genesis. Newly sealed miniblocks get their blooms during sealing, so the backfill terminates
+/// once all old miniblocks are processed (the task itself idles until the stop signal). Until then, `eth_getLogs` treats miniblocks without a bloom
+/// as possibly matching any filter.
+#[derive(Debug)]
+pub struct LogsBloomBackfill {
+    pool: ConnectionPool,
+    chunk_size: u32,
+}
+
+impl LogsBloomBackfill {
+    /// Default number of miniblocks processed in a single DB transaction.
+    pub const DEFAULT_CHUNK_SIZE: u32 = 1_000;
+
+    pub fn new(pool: ConnectionPool) -> Self {
+        Self {
+            pool,
+            chunk_size: Self::DEFAULT_CHUNK_SIZE,
+        }
+    }
+
+    /// Sets the number of miniblocks processed in a single DB transaction.
+    pub fn with_chunk_size(mut self, chunk_size: u32) -> Self {
+        assert!(chunk_size > 0, "chunk size must be positive");
+        self.chunk_size = chunk_size;
+        self
+    }
+
+    /// Backfills logs blooms for the next chunk of miniblocks. Returns `false` if there are no miniblocks
+    /// without logs blooms left.
+    async fn run_single_iteration(&self) -> anyhow::Result<bool> {
+        let mut storage = self
+            .pool
+            .access_storage_tagged("logs_bloom_backfill")
+            .await?;
+        let mut transaction = storage.start_transaction().await?;
+        let Some(last_miniblock) = transaction
+            .blocks_dal()
+            .get_last_miniblock_without_logs_bloom()
+            .await
+            .context("get_last_miniblock_without_logs_bloom()")?
+        else {
+            return Ok(false);
+        };
+        let first_miniblock = MiniblockNumber(last_miniblock.0.saturating_sub(self.chunk_size - 1));
+
+        let started_at = Instant::now();
+        let logs_blooms = transaction
+            .events_dal()
+            .compute_logs_blooms(first_miniblock..=last_miniblock)
+            .await
+            .context("compute_logs_blooms()")?;
+        transaction
+            .blocks_dal()
+            .set_miniblock_logs_blooms(&logs_blooms)
+            .await
+            .context("set_miniblock_logs_blooms()")?;
+        transaction.commit().await?;
+
+        tracing::info!(
+            "Backfilled logs blooms for miniblocks #{first_miniblock}..=#{last_miniblock} in {:?}",
+            started_at.elapsed()
+        );
+        Ok(true)
+    }
+
+    pub async fn run(self, mut stop_receiver: watch::Receiver<bool>) -> anyhow::Result<()> {
+        loop {
+            if *stop_receiver.borrow() {
+                tracing::info!("Stop signal received, logs bloom backfill is shutting down");
+                return Ok(());
+            }
+            let has_more_miniblocks = self
+                .run_single_iteration()
+                .await
+                .context("failed backfilling logs blooms")?;
+            if !has_more_miniblocks {
+                tracing::info!("All miniblocks have logs blooms; logs bloom backfill is finished");
+                // Tasks are not expected to finish before the node is stopped.
+                stop_receiver.changed().await.ok();
+                return Ok(());
+            }
+        }
+    }
+}
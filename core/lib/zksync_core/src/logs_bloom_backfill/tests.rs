@@ -0,0 +1,122 @@
+//! Tests for the logs bloom backfill.
+
+use zksync_dal::StorageProcessor;
+use zksync_types::{
+    block::build_logs_bloom, tx::IncludedTxLocation, Address, L1BatchNumber, L2ChainId, VmEvent,
+    H2048, H256,
+};
+
+use super::*;
+use crate::{
+    genesis::{ensure_genesis_state, GenesisParams},
+    utils::testonly::create_miniblock,
+};
+
+fn create_event(number: u32) -> VmEvent {
+    VmEvent {
+        location: (L1BatchNumber(1), 0),
+        address: Address::repeat_byte(number as u8),
+        indexed_topics: vec![H256::repeat_byte(number as u8)],
+        value: vec![],
+    }
+}
+
+/// Stores miniblocks #1..=`count`; odd miniblocks have a single event, even ones have no events.
+async fn store_miniblocks(storage: &mut StorageProcessor<'_>, count: u32) {
+    for number in 1..=count {
+        storage
+            .blocks_dal()
+            .insert_miniblock(&create_miniblock(number))
+            .await
+            .unwrap();
+        if number % 2 == 1 {
+            let location = IncludedTxLocation {
+                tx_hash: H256::repeat_byte(number as u8),
+                tx_index_in_miniblock: 0,
+                tx_initiator_address: Address::default(),
+            };
+            let event = create_event(number);
+            storage
+                .events_dal()
+                .save_events(MiniblockNumber(number), &[(location, vec![&event])])
+                .await;
+        }
+    }
+}
+
+#[tokio::test]
+async fn backfilling_logs_blooms() {
+    let pool = ConnectionPool::test_pool().await;
+    let mut storage = pool.access_storage().await.unwrap();
+    ensure_genesis_state(&mut storage, L2ChainId::default(), &GenesisParams::mock())
+        .await
+        .unwrap();
+    store_miniblocks(&mut storage, 5).await;
+
+    let backfill = LogsBloomBackfill::new(pool.clone()).with_chunk_size(2);
+    let mut iteration_count = 0;
+    while backfill.run_single_iteration().await.unwrap() {
+        iteration_count += 1;
+    }
+    assert_eq!(iteration_count, 3); // miniblocks #0..=#5 processed in 2-miniblock chunks
+
+    for number in 1..=5 {
+        let bloom = storage
+            .blocks_dal()
+            .get_miniblock_logs_bloom(MiniblockNumber(number))
+            .await
+            .unwrap()
+            .unwrap();
+        let expected_bloom = if number % 2 == 1 {
+            build_logs_bloom([&create_event(number)])
+        } else {
+            H2048::zero()
+        };
+        assert_eq!(bloom, expected_bloom, "miniblock #{number}");
+    }
+}
+
+#[tokio::test]
+async fn backfill_does_not_overwrite_existing_blooms() {
+    let pool = ConnectionPool::test_pool().await;
+    let mut storage = pool.access_storage().await.unwrap();
+    ensure_genesis_state(&mut storage, L2ChainId::default(), &GenesisParams::mock())
+        .await
+        .unwrap();
+    store_miniblocks(&mut storage, 3).await;
+    let custom_bloom = H2048::repeat_byte(0xff);
+    storage
+        .blocks_dal()
+        .set_miniblock_logs_bloom(MiniblockNumber(2), &custom_bloom)
+        .await
+        .unwrap();
+
+    let (stop_sender, stop_receiver) = watch::channel(false);
+    let backfill_task = tokio::spawn(LogsBloomBackfill::new(pool.clone()).run(stop_receiver));
+    loop {
+        let last_miniblock = storage
+            .blocks_dal()
+            .get_last_miniblock_without_logs_bloom()
+            .await
+            .unwrap();
+        if last_miniblock.is_none() {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+    stop_sender.send_replace(true);
+    backfill_task.await.unwrap().unwrap();
+
+    let bloom = storage
+        .blocks_dal()
+        .get_miniblock_logs_bloom(MiniblockNumber(2))
+        .await
+        .unwrap();
+    assert_eq!(bloom, Some(custom_bloom));
+    let bloom = storage
+        .blocks_dal()
+        .get_miniblock_logs_bloom(MiniblockNumber(1))
+        .await
+        .unwrap();
+    assert_eq!(bloom, Some(build_logs_bloom([&create_event(1)])));
+}
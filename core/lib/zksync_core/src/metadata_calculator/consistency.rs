@@ -0,0 +1,241 @@
+//! Background consistency checks for the Merkle tree.
+
+use std::time::Duration;
+
+use anyhow::Context as _;
+use serde::Serialize;
+use tokio::sync::watch;
+use zksync_dal::ConnectionPool;
+use zksync_health_check::{Health, HealthStatus, HealthUpdater, ReactiveHealthCheck};
+use zksync_merkle_tree::ConsistencyError;
+use zksync_types::{L1BatchNumber, H256};
+
+use super::{helpers::AsyncTreeReader, metrics::CONSISTENCY_METRICS};
+
+/// Inconsistency of the Merkle tree RocksDB instance detected by [`TreeConsistencyChecker`].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub(super) enum TreeInconsistency {
+    /// Tree nodes for the L1 batch are internally inconsistent (e.g., a node is missing or has an unexpected hash).
+    InvalidNodes {
+        l1_batch_number: L1BatchNumber,
+        error: String,
+    },
+    /// Tree is internally consistent, but its root hash differs from the one stored in Postgres.
+    RootHashMismatch {
+        l1_batch_number: L1BatchNumber,
+        stored_root_hash: H256,
+        tree_root_hash: H256,
+    },
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+struct ConsistencyCheckerHealthDetails {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    last_checked_l1_batch: Option<L1BatchNumber>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    inconsistency: Option<TreeInconsistency>,
+}
+
+impl From<ConsistencyCheckerHealthDetails> for Health {
+    fn from(details: ConsistencyCheckerHealthDetails) -> Self {
+        let status = if details.inconsistency.is_some() {
+            HealthStatus::Affected
+        } else {
+            HealthStatus::Ready
+        };
+        Health::from(status).with_details(details)
+    }
+}
+
+/// Task that periodically verifies consistency of the latest tree version directly in RocksDB
+/// (i.e., that all nodes are present and their hashes match) and compares the root hash of this version
+/// with the root hash stored in the L1 batch metadata. This allows catching silent corruption
+/// of the tree RocksDB instance, which would otherwise only manifest itself in the L1 batch commitments.
+///
+/// Inconsistencies are reported via a dedicated health check and are sticky: once an inconsistency is detected,
+/// the health check remains affected until the node is restarted. Errors encountered during checks
+/// (e.g., Postgres connectivity issues) are logged, and the check is retried on the next iteration.
+#[derive(Debug)]
+pub(super) struct TreeConsistencyChecker {
+    interval: Duration,
+    pruning_past_versions_to_keep: Option<u64>,
+    health_updater: HealthUpdater,
+}
+
+impl TreeConsistencyChecker {
+    pub fn new(interval: Duration, pruning_past_versions_to_keep: Option<u64>) -> Self {
+        let (_, health_updater) = ReactiveHealthCheck::new("tree_consistency");
+        Self {
+            interval,
+            pruning_past_versions_to_keep,
+            health_updater,
+        }
+    }
+
+    pub fn health_check(&self) -> ReactiveHealthCheck {
+        self.health_updater.subscribe()
+    }
+
+    /// Chooses the latest L1 batch both processed by the tree and having metadata in Postgres.
+    /// Older tree versions may be pruned, so they cannot be checked reliably.
+    async fn choose_l1_batch(
+        tree_reader: &AsyncTreeReader,
+        pool: &ConnectionPool,
+    ) -> anyhow::Result<Option<L1BatchNumber>> {
+        let next_tree_l1_batch = tree_reader.clone().info().await.next_l1_batch_number;
+        let Some(last_tree_l1_batch) = next_tree_l1_batch.0.checked_sub(1) else {
+            return Ok(None);
+        };
+
+        let mut storage = pool.access_storage_tagged("metadata_calculator").await?;
+        let last_l1_batch_with_metadata = storage
+            .blocks_dal()
+            .get_last_l1_batch_number_with_metadata()
+            .await
+            .context("get_last_l1_batch_number_with_metadata()")?;
+        Ok(last_l1_batch_with_metadata.map(|number| number.min(L1BatchNumber(last_tree_l1_batch))))
+    }
+
+    /// Verifies consistency of the tree version for the specified L1 batch in RocksDB and compares
+    /// its root hash to the one stored in Postgres.
+    pub(super) async fn check_l1_batch(
+        &self,
+        tree_reader: &AsyncTreeReader,
+        pool: &ConnectionPool,
+        l1_batch_number: L1BatchNumber,
+    ) -> anyhow::Result<Option<TreeInconsistency>> {
+        let mut storage = pool.access_storage_tagged("metadata_calculator").await?;
+        let stored_root_hash = storage
+            .blocks_dal()
+            .get_l1_batch_state_root(l1_batch_number)
+            .await
+            .with_context(|| format!("Failed getting root hash for L1 batch #{l1_batch_number}"))?
+            .with_context(|| format!("L1 batch #{l1_batch_number} has no root hash"))?;
+        drop(storage);
+
+        let tree_root_hash = match tree_reader
+            .clone()
+            .verify_consistency(l1_batch_number)
+            .await
+        {
+            Ok(root_hash) => root_hash,
+            Err(err) => {
+                self.ensure_version_is_retained(tree_reader, l1_batch_number)
+                    .await?;
+                if let ConsistencyError::MissingVersion(_) = err {
+                    // The tree was reverted while we were checking it
+                    anyhow::bail!("Tree version for L1 batch #{l1_batch_number} is missing");
+                }
+                return Ok(Some(TreeInconsistency::InvalidNodes {
+                    l1_batch_number,
+                    error: err.to_string(),
+                }));
+            }
+        };
+
+        Ok(
+            (tree_root_hash != stored_root_hash).then_some(TreeInconsistency::RootHashMismatch {
+                l1_batch_number,
+                stored_root_hash,
+                tree_root_hash,
+            }),
+        )
+    }
+
+    /// Checks that the tree version couldn't have been pruned while it was being checked; otherwise,
+    /// missing nodes are expected and don't indicate an inconsistency.
+    async fn ensure_version_is_retained(
+        &self,
+        tree_reader: &AsyncTreeReader,
+        l1_batch_number: L1BatchNumber,
+    ) -> anyhow::Result<()> {
+        let Some(past_versions_to_keep) = self.pruning_past_versions_to_keep else {
+            return Ok(());
+        };
+        let next_tree_l1_batch = tree_reader.clone().info().await.next_l1_batch_number;
+        let retained_l1_batch_count = next_tree_l1_batch.0.saturating_sub(l1_batch_number.0);
+        if u64::from(retained_l1_batch_count) > past_versions_to_keep {
+            anyhow::bail!(
+                "Tree version for L1 batch #{l1_batch_number} may have been pruned during the check \
+                 (tree is at L1 batch #{next_tree_l1_batch}, {past_versions_to_keep} past versions are kept)"
+            );
+        }
+        Ok(())
+    }
+
+    pub async fn run(
+        self,
+        tree_reader: AsyncTreeReader,
+        pool: ConnectionPool,
+        mut stop_receiver: watch::Receiver<bool>,
+    ) -> anyhow::Result<()> {
+        let mut details = ConsistencyCheckerHealthDetails::default();
+        self.health_updater
+            .update(Health::from(HealthStatus::Ready));
+        tracing::info!(
+            "Starting Merkle tree consistency checker with {:?} interval",
+            self.interval
+        );
+
+        loop {
+            if tokio::time::timeout(self.interval, stop_receiver.changed())
+                .await
+                .is_ok()
+            {
+                break;
+            }
+
+            let l1_batch_number = match Self::choose_l1_batch(&tree_reader, &pool).await {
+                Ok(Some(number)) => number,
+                Ok(None) => {
+                    tracing::debug!(
+                        "No L1 batches processed by the tree have metadata in Postgres; skipping consistency check"
+                    );
+                    continue;
+                }
+                Err(err) => {
+                    tracing::warn!("Failed choosing L1 batch for consistency check: {err:#}");
+                    continue;
+                }
+            };
+            tracing::info!("Checking Merkle tree consistency for L1 batch #{l1_batch_number}");
+            let latency = CONSISTENCY_METRICS.check_latency.start();
+            let check_result = tokio::select! {
+                result = self.check_l1_batch(&tree_reader, &pool, l1_batch_number) => result,
+                _ = stop_receiver.changed() => break,
+            };
+            let inconsistency = match check_result {
+                Ok(inconsistency) => inconsistency,
+                Err(err) => {
+                    tracing::warn!(
+                        "Failed checking Merkle tree consistency for L1 batch #{l1_batch_number}: {err:#}"
+                    );
+                    continue;
+                }
+            };
+            let latency = latency.observe();
+            CONSISTENCY_METRICS
+                .last_checked_l1_batch
+                .set(l1_batch_number.0.into());
+
+            if let Some(inconsistency) = inconsistency {
+                tracing::error!(
+                    "Merkle tree is inconsistent for L1 batch #{l1_batch_number}: {inconsistency:?}; \
+                     the tree RocksDB instance may be corrupted"
+                );
+                CONSISTENCY_METRICS.inconsistencies.inc();
+                details.inconsistency.get_or_insert(inconsistency);
+            } else {
+                tracing::info!(
+                    "Merkle tree is consistent for L1 batch #{l1_batch_number}; check took {latency:?}"
+                );
+            }
+            details.last_checked_l1_batch = Some(l1_batch_number);
+            self.health_updater.update(Health::from(details.clone()));
+        }
+
+        tracing::info!("Stop signal received, Merkle tree consistency checker is shutting down");
+        Ok(())
+    }
+}
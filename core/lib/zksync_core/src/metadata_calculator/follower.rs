@@ -0,0 +1,103 @@
+//! Read-only follower tree used to serve proof queries.
+
+use std::{path::PathBuf, time::Duration};
+
+use anyhow::Context as _;
+use tokio::sync::watch;
+use zksync_config::configs::database::MerkleTreeMode;
+use zksync_merkle_tree::MerkleTreeColumnFamily;
+use zksync_storage::RocksDB;
+
+use super::helpers::AsyncTreeReader;
+
+/// Read-only follower of the Merkle tree based on a secondary RocksDB instance. The follower periodically
+/// catches up with the tree writer and serves proof queries without contending with it. To only serve
+/// queries for L1 batches unlikely to be reverted, the follower treats `version_lag` latest tree versions
+/// as missing.
+#[derive(Debug)]
+pub(super) struct MerkleTreeFollower {
+    db_path: PathBuf,
+    secondary_path: PathBuf,
+    mode: MerkleTreeMode,
+    version_lag: u64,
+    poll_interval: Duration,
+}
+
+impl MerkleTreeFollower {
+    pub fn new(
+        db_path: PathBuf,
+        mode: MerkleTreeMode,
+        version_lag: u64,
+        poll_interval: Duration,
+    ) -> Self {
+        let mut secondary_path = db_path.clone().into_os_string();
+        secondary_path.push("-follower");
+        Self {
+            db_path,
+            secondary_path: secondary_path.into(),
+            mode,
+            version_lag,
+            poll_interval,
+        }
+    }
+
+    /// Opens the follower tree, publishes its reader via `reader_sender` and keeps the follower
+    /// synchronized with the primary tree until a stop signal is received.
+    pub async fn run(
+        self,
+        reader_sender: watch::Sender<Option<AsyncTreeReader>>,
+        mut stop_receiver: watch::Receiver<bool>,
+    ) -> anyhow::Result<()> {
+        let Self {
+            db_path,
+            secondary_path,
+            mode,
+            version_lag,
+            poll_interval,
+        } = self;
+
+        let db = tokio::task::spawn_blocking(move || {
+            std::fs::create_dir_all(&secondary_path).with_context(|| {
+                format!(
+                    "failed creating directory `{}` for Merkle tree follower",
+                    secondary_path.display()
+                )
+            })?;
+            anyhow::Ok(RocksDB::<MerkleTreeColumnFamily>::secondary(
+                &db_path,
+                &secondary_path,
+            ))
+        })
+        .await
+        .context("panicked opening Merkle tree follower")??;
+        tracing::info!(
+            "Initialized Merkle tree follower lagging by {version_lag} versions; it will serve proof queries"
+        );
+        reader_sender.send_replace(Some(AsyncTreeReader::follower(
+            db.clone(),
+            mode,
+            version_lag,
+        )));
+
+        loop {
+            if *stop_receiver.borrow() {
+                break;
+            }
+            if tokio::time::timeout(poll_interval, stop_receiver.changed())
+                .await
+                .is_ok()
+            {
+                break;
+            }
+
+            let db = db.clone();
+            tokio::task::spawn_blocking(move || db.try_catch_up_with_primary())
+                .await
+                .context("panicked catching up Merkle tree follower")?
+                .context("failed catching up Merkle tree follower with the primary tree")?;
+        }
+
+        tracing::info!("Stop signal received, Merkle tree follower is shutting down");
+        Ok(())
+    }
+}
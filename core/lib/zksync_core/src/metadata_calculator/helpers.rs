@@ -7,6 +7,7 @@ use std::{
     time::Duration,
 };
 
+use anyhow::Context as _;
 use serde::{Deserialize, Serialize};
 #[cfg(test)]
 use tokio::sync::mpsc;
@@ -16,7 +17,9 @@ use zksync_health_check::{Health, HealthStatus};
 use zksync_merkle_tree::{
     domain::{TreeMetadata, ZkSyncTree, ZkSyncTreeReader},
     recovery::MerkleTreeRecovery,
-    Database, Key, NoVersionError, RocksDBWrapper, TreeEntry, TreeEntryWithProof, TreeInstruction,
+    ConsistencyError, Database, Key, MerkleTreeColumnFamily, MerkleTreePruner,
+    MerkleTreePrunerHandle, NoVersionError, RocksDBWrapper, TreeEntry, TreeEntryWithProof,
+    TreeInstruction,
 };
 use zksync_storage::{RocksDB, RocksDBOptions, StalledWritesRetries};
 use zksync_types::{block::L1BatchHeader, L1BatchNumber, StorageKey, H256};
@@ -124,6 +127,19 @@ impl AsyncTree {
         self.inner.as_ref().expect(Self::INCONSISTENT_MSG)
     }
 
+    pub fn pruner(
+        &self,
+        past_versions_to_keep: u64,
+    ) -> (MerkleTreePruner<RocksDBWrapper>, MerkleTreePrunerHandle) {
+        self.as_ref().pruner(past_versions_to_keep)
+    }
+
+    /// Makes the tree use a dedicated `rayon` thread pool for hash computations.
+    /// If `thread_count` is 0, the default number of threads will be used.
+    pub fn use_dedicated_thread_pool(&mut self, thread_count: usize) {
+        self.as_mut().use_dedicated_thread_pool(thread_count);
+    }
+
     fn as_mut(&mut self) -> &mut ZkSyncTree {
         self.inner.as_mut().expect(Self::INCONSISTENT_MSG)
     }
@@ -136,6 +152,7 @@ impl AsyncTree {
         AsyncTreeReader {
             inner: self.inner.as_ref().expect(Self::INCONSISTENT_MSG).reader(),
             mode: self.mode,
+            version_lag: 0,
         }
     }
 
@@ -189,9 +206,24 @@ impl AsyncTree {
 pub(crate) struct AsyncTreeReader {
     inner: ZkSyncTreeReader,
     mode: MerkleTreeMode,
+    /// Number of latest tree versions treated as missing when serving proofs.
+    version_lag: u64,
 }
 
 impl AsyncTreeReader {
+    /// Creates a reader for a read-only follower tree based on a secondary RocksDB instance.
+    pub fn follower(
+        db: RocksDB<MerkleTreeColumnFamily>,
+        mode: MerkleTreeMode,
+        version_lag: u64,
+    ) -> Self {
+        Self {
+            inner: ZkSyncTreeReader::new(db.into()),
+            mode,
+            version_lag,
+        }
+    }
+
     pub async fn info(self) -> MerkleTreeInfo {
         tokio::task::spawn_blocking(move || MerkleTreeInfo {
             mode: self.mode,
@@ -208,9 +240,28 @@ impl AsyncTreeReader {
         l1_batch_number: L1BatchNumber,
         keys: Vec<Key>,
     ) -> Result<Vec<TreeEntryWithProof>, NoVersionError> {
-        tokio::task::spawn_blocking(move || self.inner.entries_with_proofs(l1_batch_number, &keys))
-            .await
-            .unwrap()
+        tokio::task::spawn_blocking(move || {
+            self.inner
+                .lagging_entries_with_proofs(l1_batch_number, &keys, self.version_lag)
+        })
+        .await
+        .unwrap()
+    }
+
+    /// Verifies consistency of the tree version corresponding to the specified L1 batch
+    /// and returns the root hash of this version.
+    pub async fn verify_consistency(
+        self,
+        l1_batch_number: L1BatchNumber,
+    ) -> Result<H256, ConsistencyError> {
+        tokio::task::spawn_blocking(move || {
+            self.inner.verify_consistency(l1_batch_number)?;
+            self.inner
+                .l1_batch_root_hash(l1_batch_number)
+                .ok_or(ConsistencyError::MissingVersion(l1_batch_number.0.into()))
+        })
+        .await
+        .unwrap()
     }
 }
 
@@ -358,7 +409,7 @@ impl L1BatchWithLogs {
     pub async fn new(
         storage: &mut StorageProcessor<'_>,
         l1_batch_number: L1BatchNumber,
-    ) -> Option<Self> {
+    ) -> anyhow::Result<Option<Self>> {
         tracing::debug!("Loading storage logs data for L1 batch #{l1_batch_number}");
         let load_changes_latency = METRICS.start_stage(TreeUpdateStage::LoadChanges);
 
@@ -367,9 +418,28 @@ impl L1BatchWithLogs {
             .blocks_dal()
             .get_l1_batch_header(l1_batch_number)
             .await
-            .unwrap()?;
+            .with_context(|| format!("failed getting header for L1 batch #{l1_batch_number}"))?;
+        let Some(header) = header else {
+            return Ok(None);
+        };
         header_latency.observe();
 
+        // Protective reads may be persisted asynchronously after the L1 batch is sealed; we cannot process
+        // the batch until they are available.
+        let protective_reads_persisted = storage
+            .storage_logs_dedup_dal()
+            .are_protective_reads_persisted(l1_batch_number)
+            .await
+            .with_context(|| {
+                format!("failed checking protective reads status for L1 batch #{l1_batch_number}")
+            })?;
+        if protective_reads_persisted == Some(false) {
+            tracing::debug!(
+                "Protective reads for L1 batch #{l1_batch_number} are not persisted yet; postponing its processing"
+            );
+            return Ok(None);
+        }
+
         let protective_reads_latency =
             METRICS.start_load_stage(LoadChangesStage::LoadProtectiveReads);
         let protective_reads = storage
@@ -396,7 +466,19 @@ impl L1BatchWithLogs {
 
         let mut storage_logs = BTreeMap::new();
         for storage_key in protective_reads {
-            touched_slots.remove(&storage_key);
+            if touched_slots.remove(&storage_key).is_some() {
+                // Sanity check: protective reads and deduplicated writes are disjoint, so a slot
+                // cannot be initially written in the same L1 batch it's protectively read in. This guards against
+                // inconsistent protective reads (e.g., ones persisted asynchronously) making their way into the tree.
+                if let Some(&(initial_write_batch, _)) =
+                    l1_batches_for_initial_writes.get(&storage_key.hashed_key())
+                {
+                    anyhow::ensure!(
+                        initial_write_batch != l1_batch_number,
+                        "Slot {storage_key:?} is both protectively read and initially written in L1 batch #{l1_batch_number}"
+                    );
+                }
+            }
             // ^ As per deduplication rules, all keys in `protective_reads` haven't *really* changed
             // in the considered L1 batch. Thus, we can remove them from `touched_slots` in order to simplify
             // their further processing.
@@ -423,10 +505,10 @@ impl L1BatchWithLogs {
         }
 
         load_changes_latency.observe();
-        Some(Self {
+        Ok(Some(Self {
             header,
             storage_logs: storage_logs.into_values().collect(),
-        })
+        }))
     }
 }
 
@@ -526,6 +608,7 @@ mod tests {
             let l1_batch_number = L1BatchNumber(l1_batch_number);
             let batch_with_logs = L1BatchWithLogs::new(&mut storage, l1_batch_number)
                 .await
+                .unwrap()
                 .unwrap();
             let slow_batch_with_logs = L1BatchWithLogs::slow(&mut storage, l1_batch_number)
                 .await
@@ -577,6 +660,7 @@ mod tests {
     ) {
         let l1_batch_with_logs = L1BatchWithLogs::new(storage, l1_batch_number)
             .await
+            .unwrap()
             .unwrap();
         let slow_l1_batch_with_logs = L1BatchWithLogs::slow(storage, l1_batch_number)
             .await
@@ -695,6 +779,7 @@ mod tests {
 
         let l1_batch_with_logs = L1BatchWithLogs::new(&mut storage, L1BatchNumber(2))
             .await
+            .unwrap()
             .unwrap();
         // Check that we have protective reads transformed into read logs
         let read_logs_count = l1_batch_with_logs
@@ -710,4 +795,65 @@ mod tests {
             assert_log_equivalence(&mut storage, &mut tree, L1BatchNumber(batch_number)).await;
         }
     }
+
+    #[tokio::test]
+    async fn loading_logs_waits_for_pending_protective_reads() {
+        let pool = ConnectionPool::test_pool().await;
+        let mut storage = pool.access_storage().await.unwrap();
+        ensure_genesis_state(&mut storage, L2ChainId::from(270), &GenesisParams::mock())
+            .await
+            .unwrap();
+        let logs = gen_storage_logs(100..120, 1);
+        extend_db_state(&mut storage, logs).await;
+
+        storage
+            .storage_logs_dedup_dal()
+            .mark_protective_reads_as_pending(L1BatchNumber(1))
+            .await
+            .unwrap();
+        let l1_batch_with_logs = L1BatchWithLogs::new(&mut storage, L1BatchNumber(1))
+            .await
+            .unwrap();
+        assert!(l1_batch_with_logs.is_none());
+
+        storage
+            .storage_logs_dedup_dal()
+            .mark_protective_reads_as_persisted(L1BatchNumber(1))
+            .await
+            .unwrap();
+        let l1_batch_with_logs = L1BatchWithLogs::new(&mut storage, L1BatchNumber(1))
+            .await
+            .unwrap();
+        assert!(l1_batch_with_logs.is_some());
+    }
+
+    #[tokio::test]
+    async fn loading_logs_with_inconsistent_protective_reads() {
+        let pool = ConnectionPool::test_pool().await;
+        let mut storage = pool.access_storage().await.unwrap();
+        ensure_genesis_state(&mut storage, L2ChainId::from(270), &GenesisParams::mock())
+            .await
+            .unwrap();
+        let logs = gen_storage_logs(100..120, 1);
+        // Slots initially written in L1 batch #1 cannot be protectively read in the same batch.
+        let read_logs: Vec<_> = logs[0]
+            .iter()
+            .take(3)
+            .map(StorageLog::to_test_log_query)
+            .collect();
+        extend_db_state(&mut storage, logs).await;
+        storage
+            .storage_logs_dedup_dal()
+            .insert_protective_reads(L1BatchNumber(1), &read_logs)
+            .await;
+
+        let err = L1BatchWithLogs::new(&mut storage, L1BatchNumber(1))
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.contains("both protectively read and initially written"),
+            "{err}"
+        );
+    }
 }
@@ -3,8 +3,8 @@
 use std::time::{Duration, Instant};
 
 use vise::{
-    Buckets, EncodeLabelSet, EncodeLabelValue, Family, Gauge, Histogram, LatencyObserver, Metrics,
-    Unit,
+    Buckets, Counter, EncodeLabelSet, EncodeLabelValue, Family, Gauge, Histogram, LatencyObserver,
+    Metrics, Unit,
 };
 use zksync_types::block::L1BatchHeader;
 use zksync_utils::time::seconds_since_epoch;
@@ -210,3 +210,19 @@ pub(super) struct MetadataCalculatorRecoveryMetrics {
 #[vise::register]
 pub(super) static RECOVERY_METRICS: vise::Global<MetadataCalculatorRecoveryMetrics> =
     vise::Global::new();
+
+/// Metrics for the tree consistency checker.
+#[derive(Debug, Metrics)]
+#[metrics(prefix = "server_metadata_calculator_consistency")]
+pub(super) struct ConsistencyCheckMetrics {
+    /// Number of the last L1 batch checked by the consistency checker.
+    pub last_checked_l1_batch: Gauge<u64>,
+    /// Latency of verifying tree consistency for an L1 batch.
+    #[metrics(buckets = Buckets::LATENCIES, unit = Unit::Seconds)]
+    pub check_latency: Histogram<Duration>,
+    /// Number of L1 batches with tree inconsistencies detected by the checker.
+    pub inconsistencies: Counter,
+}
+
+#[vise::register]
+pub(super) static CONSISTENCY_METRICS: vise::Global<ConsistencyCheckMetrics> = vise::Global::new();
@@ -14,24 +14,35 @@ use zksync_config::configs::{
 };
 use zksync_dal::{ConnectionPool, StorageProcessor};
 use zksync_health_check::{HealthUpdater, ReactiveHealthCheck};
-use zksync_merkle_tree::domain::TreeMetadata;
+use zksync_merkle_tree::{domain::TreeMetadata, MerkleTreeColumnFamily};
 use zksync_object_store::ObjectStore;
+use zksync_storage::RocksDB;
 use zksync_types::{
     block::L1BatchHeader,
     commitment::{L1BatchCommitment, L1BatchMetadata},
-    H256,
+    L1BatchNumber, H256,
 };
 
 pub(crate) use self::helpers::{AsyncTreeReader, L1BatchWithLogs, MerkleTreeInfo};
+pub use self::recovery::StorageLogsRecoveryTarget;
 use self::{
+    consistency::TreeConsistencyChecker,
+    follower::MerkleTreeFollower,
     helpers::{create_db, Delayer, GenericAsyncTree},
     metrics::{TreeUpdateStage, METRICS},
+    pruning::MerkleTreePruningTask,
     updater::TreeUpdater,
 };
-use crate::gas_tracker::commit_gas_count_for_l1_batch;
+use crate::{
+    gas_tracker::commit_gas_count_for_l1_batch,
+    rocksdb_backup::{RocksDBBackupTask, RocksDBBackups},
+};
 
+mod consistency;
+mod follower;
 mod helpers;
 mod metrics;
+mod pruning;
 mod recovery;
 #[cfg(test)]
 pub(crate) mod tests;
@@ -58,6 +69,22 @@ pub struct MetadataCalculatorConfig {
     pub memtable_capacity: usize,
     /// Timeout to wait for the Merkle tree database to run compaction on stalled writes.
     pub stalled_writes_timeout: Duration,
+    /// Number of threads in a dedicated thread pool used to hash tree subtrees in parallel.
+    /// If not set, the global `rayon` thread pool is used.
+    pub hashing_thread_count: Option<usize>,
+    /// Number of past tree versions (i.e., L1 batches) to keep when pruning the tree. If not set, the tree
+    /// is not pruned. Versions for L1 batches not executed on L1 are never pruned.
+    pub pruning_past_versions_to_keep: Option<u64>,
+    /// If set, an empty tree will be recovered from the storage logs in Postgres as of the specified L1 batch
+    /// instead of processing all L1 batches starting from genesis.
+    pub storage_logs_recovery: Option<StorageLogsRecoveryTarget>,
+    /// If set, proof queries are served by a read-only follower tree (a secondary RocksDB instance) instead of
+    /// the main tree, so that they don't contend with the tree writer. The follower treats the specified number
+    /// of latest tree versions (i.e., L1 batches) as missing.
+    pub follower_version_lag: Option<u64>,
+    /// If set, consistency of the latest tree version is periodically verified in RocksDB, and its root hash
+    /// is compared to the stored L1 batch metadata. Inconsistencies are reported via a dedicated health check.
+    pub consistency_check_interval: Option<Duration>,
 }
 
 impl MetadataCalculatorConfig {
@@ -74,10 +101,29 @@ impl MetadataCalculatorConfig {
             block_cache_capacity: merkle_tree_config.block_cache_size(),
             memtable_capacity: merkle_tree_config.memtable_capacity(),
             stalled_writes_timeout: merkle_tree_config.stalled_writes_timeout(),
+            hashing_thread_count: merkle_tree_config.hashing_thread_count,
+            pruning_past_versions_to_keep: merkle_tree_config.pruning_past_versions_to_keep,
+            storage_logs_recovery: merkle_tree_config.recover_from_storage_logs.then(|| {
+                let l1_batch = merkle_tree_config.storage_logs_recovery_l1_batch;
+                StorageLogsRecoveryTarget::new(l1_batch.map(L1BatchNumber))
+            }),
+            follower_version_lag: merkle_tree_config.follower_version_lag,
+            consistency_check_interval: merkle_tree_config.consistency_check_interval(),
         }
     }
 }
 
+/// Lazily initialized [`AsyncTreeReader`] returned by [`MetadataCalculator::lazy_tree_reader()`].
+#[derive(Debug, Clone)]
+pub struct LazyAsyncTreeReader(watch::Receiver<Option<AsyncTreeReader>>);
+
+impl LazyAsyncTreeReader {
+    /// Returns the tree reader if the tree is initialized.
+    pub(crate) fn read(&self) -> Option<AsyncTreeReader> {
+        self.0.borrow().clone()
+    }
+}
+
 #[derive(Debug)]
 pub struct MetadataCalculator {
     tree: GenericAsyncTree,
@@ -86,6 +132,13 @@ pub struct MetadataCalculator {
     delayer: Delayer,
     health_updater: HealthUpdater,
     max_l1_batches_per_iter: usize,
+    hashing_thread_count: Option<usize>,
+    pruning_past_versions_to_keep: Option<u64>,
+    storage_logs_recovery: Option<StorageLogsRecoveryTarget>,
+    follower: Option<MerkleTreeFollower>,
+    consistency_checker: Option<TreeConsistencyChecker>,
+    db: RocksDB<MerkleTreeColumnFamily>,
+    backups: Option<(RocksDBBackups, Duration)>,
 }
 
 impl MetadataCalculator {
@@ -107,6 +160,7 @@ impl MetadataCalculator {
             config.multi_get_chunk_size,
         )
         .await;
+        let raw_db = db.clone().into_inner();
         let tree = GenericAsyncTree::new(db, config.mode).await;
 
         let (_, health_updater) = ReactiveHealthCheck::new("tree");
@@ -117,15 +171,46 @@ impl MetadataCalculator {
             delayer: Delayer::new(config.delay_interval),
             health_updater,
             max_l1_batches_per_iter: config.max_l1_batches_per_iter,
+            hashing_thread_count: config.hashing_thread_count,
+            pruning_past_versions_to_keep: config.pruning_past_versions_to_keep,
+            storage_logs_recovery: config.storage_logs_recovery,
+            follower: config.follower_version_lag.map(|version_lag| {
+                MerkleTreeFollower::new(
+                    config.db_path.into(),
+                    config.mode,
+                    version_lag,
+                    config.delay_interval,
+                )
+            }),
+            consistency_checker: config.consistency_check_interval.map(|interval| {
+                TreeConsistencyChecker::new(interval, config.pruning_past_versions_to_keep)
+            }),
+            db: raw_db,
+            backups: None,
         }
     }
 
+    /// Enables periodic backups of the tree RocksDB instance to the object store.
+    #[must_use]
+    pub fn with_backups(mut self, backups: RocksDBBackups, interval: Duration) -> Self {
+        self.backups = Some((backups, interval));
+        self
+    }
+
     /// Returns a health check for this calculator.
     pub fn tree_health_check(&self) -> ReactiveHealthCheck {
         self.health_updater.subscribe()
     }
 
-    /// Returns a reference to the tree reader.
+    /// Returns a health check for the tree consistency checker, if it's enabled.
+    pub fn consistency_health_check(&self) -> Option<ReactiveHealthCheck> {
+        self.consistency_checker
+            .as_ref()
+            .map(TreeConsistencyChecker::health_check)
+    }
+
+    /// Returns a reference to the tree reader. If a follower tree is configured, the reader
+    /// is based on the follower rather than the main tree.
     pub(crate) fn tree_reader(&self) -> impl Future<Output = AsyncTreeReader> {
         let mut receiver = self.tree_reader.subscribe();
         async move {
@@ -141,6 +226,12 @@ impl MetadataCalculator {
         }
     }
 
+    /// Returns a lazily initialized tree reader. Unlike [`Self::tree_reader()`], the returned reader
+    /// can be used to serve API requests even before the tree is initialized.
+    pub fn lazy_tree_reader(&self) -> LazyAsyncTreeReader {
+        LazyAsyncTreeReader(self.tree_reader.subscribe())
+    }
+
     pub async fn run(
         self,
         pool: ConnectionPool,
@@ -148,17 +239,84 @@ impl MetadataCalculator {
     ) -> anyhow::Result<()> {
         let tree = self
             .tree
-            .ensure_ready(&pool, &stop_receiver, &self.health_updater)
+            .ensure_ready(
+                &pool,
+                &stop_receiver,
+                &self.health_updater,
+                self.storage_logs_recovery,
+            )
             .await?;
-        let Some(tree) = tree else {
+        let Some(mut tree) = tree else {
             return Ok(()); // recovery was aborted because a stop signal was received
         };
-        self.tree_reader.send_replace(Some(tree.reader()));
+        if let Some(thread_count) = self.hashing_thread_count {
+            tracing::info!(
+                "Using dedicated thread pool with {thread_count} threads for tree hashing"
+            );
+            tree.use_dedicated_thread_pool(thread_count);
+        }
+        if self.follower.is_none() {
+            self.tree_reader.send_replace(Some(tree.reader()));
+        }
+        let pruning_task = self
+            .pruning_past_versions_to_keep
+            .map(|past_versions_to_keep| {
+                tracing::info!(
+                    "Starting Merkle tree pruning keeping {past_versions_to_keep} past versions"
+                );
+                MerkleTreePruningTask::new(&tree, past_versions_to_keep)
+            });
 
+        let consistency_checker = self
+            .consistency_checker
+            .map(|checker| (checker, tree.reader()));
         let updater = TreeUpdater::new(tree, self.max_l1_batches_per_iter, self.object_store);
-        updater
-            .loop_updating_tree(self.delayer, &pool, stop_receiver, self.health_updater)
-            .await
+        let updater_future = updater.loop_updating_tree(
+            self.delayer,
+            &pool,
+            stop_receiver.clone(),
+            self.health_updater,
+        );
+        let pruning_future = async {
+            if let Some(pruning_task) = pruning_task {
+                pruning_task
+                    .run(pool.clone(), stop_receiver.clone())
+                    .await?;
+            }
+            anyhow::Ok(())
+        };
+        let (follower, tree_reader) = (self.follower, self.tree_reader);
+        let follower_future = async {
+            if let Some(follower) = follower {
+                follower.run(tree_reader, stop_receiver.clone()).await?;
+            }
+            anyhow::Ok(())
+        };
+        let (db, backups) = (self.db, self.backups);
+        let backup_future = async {
+            if let Some((backups, interval)) = backups {
+                RocksDBBackupTask::new(db, backups, interval)
+                    .run(stop_receiver.clone())
+                    .await?;
+            }
+            anyhow::Ok(())
+        };
+        let consistency_future = async {
+            if let Some((checker, tree_reader)) = consistency_checker {
+                checker
+                    .run(tree_reader, pool.clone(), stop_receiver.clone())
+                    .await?;
+            }
+            anyhow::Ok(())
+        };
+        tokio::try_join!(
+            updater_future,
+            pruning_future,
+            follower_future,
+            backup_future,
+            consistency_future
+        )?;
+        Ok(())
     }
 
     /// This is used to improve L1 gas estimation for the commit operation. The estimations are computed
@@ -0,0 +1,89 @@
+//! Merkle tree pruning integration for the metadata calculator.
+
+use std::time::Duration;
+
+use anyhow::Context as _;
+use tokio::sync::watch;
+use zksync_dal::ConnectionPool;
+use zksync_merkle_tree::{MerkleTreePruner, MerkleTreePrunerHandle, RocksDBWrapper};
+
+use super::helpers::AsyncTree;
+
+/// Runs a [`MerkleTreePruner`] for the Merkle tree in the background and limits pruned versions
+/// so that it never removes tree versions for L1 batches not executed on L1. Such batches can be reverted,
+/// and reverting the tree requires the corresponding version to be present.
+#[derive(Debug)]
+pub(super) struct MerkleTreePruningTask {
+    pruner: MerkleTreePruner<RocksDBWrapper>,
+    handle: MerkleTreePrunerHandle,
+    poll_interval: Duration,
+}
+
+impl MerkleTreePruningTask {
+    const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(60);
+
+    pub fn new(tree: &AsyncTree, past_versions_to_keep: u64) -> Self {
+        let (pruner, handle) = tree.pruner(past_versions_to_keep);
+        Self {
+            pruner,
+            handle,
+            poll_interval: Self::DEFAULT_POLL_INTERVAL,
+        }
+    }
+
+    #[cfg(test)]
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self.pruner.set_poll_interval(poll_interval);
+        self
+    }
+
+    /// Returns the first tree version that must be retained, i.e. the version corresponding
+    /// to the last L1 batch executed on L1.
+    async fn retention_limit(pool: &ConnectionPool) -> anyhow::Result<u64> {
+        let mut storage = pool.access_storage_tagged("metadata_calculator").await?;
+        let last_executed_l1_batch = storage
+            .blocks_dal()
+            .get_number_of_last_l1_batch_executed_on_eth()
+            .await
+            .context("get_number_of_last_l1_batch_executed_on_eth()")?;
+        Ok(last_executed_l1_batch.map_or(0, |number| number.0.into()))
+    }
+
+    pub async fn run(
+        self,
+        pool: ConnectionPool,
+        mut stop_receiver: watch::Receiver<bool>,
+    ) -> anyhow::Result<()> {
+        let Self {
+            pruner,
+            handle,
+            poll_interval,
+        } = self;
+        // Set the limit before starting the pruner so that it doesn't prune anything unsafe on the first iteration.
+        handle.set_retention_limit(Self::retention_limit(&pool).await?);
+        let pruner_task = tokio::task::spawn_blocking(|| pruner.run());
+
+        loop {
+            if *stop_receiver.borrow() {
+                break;
+            }
+            if tokio::time::timeout(poll_interval, stop_receiver.changed())
+                .await
+                .is_ok()
+            {
+                break;
+            }
+
+            let retention_limit = Self::retention_limit(&pool).await?;
+            tracing::debug!(
+                "Updated Merkle tree pruning retention limit to version {retention_limit}"
+            );
+            handle.set_retention_limit(retention_limit);
+        }
+
+        tracing::info!("Stop signal received, Merkle tree pruning is shutting down");
+        handle.abort();
+        pruner_task.await.context("Merkle tree pruner panicked")
+    }
+}
@@ -7,6 +7,8 @@
 //!
 //! - Tree is recovering.
 //! - Tree is empty and should be recovered (i.e., there's a snapshot in Postgres).
+//! - Tree is empty and should be recovered from the storage logs in Postgres (if enabled in the config;
+//!   see [`StorageLogsRecoveryTarget`]). This allows to restore a lost tree without replaying all L1 batches.
 //! - Tree is empty and should be built from scratch.
 //! - Tree is ready for normal operation (i.e., it's not empty and is not recovering).
 //!
@@ -38,7 +40,7 @@ use tokio::sync::{watch, Mutex, Semaphore};
 use zksync_dal::{ConnectionPool, StorageProcessor};
 use zksync_health_check::{Health, HealthStatus, HealthUpdater};
 use zksync_merkle_tree::TreeEntry;
-use zksync_types::{snapshots::SnapshotRecoveryStatus, MiniblockNumber, H256, U256};
+use zksync_types::{snapshots::SnapshotRecoveryStatus, L1BatchNumber, MiniblockNumber, H256, U256};
 use zksync_utils::u256_to_h256;
 
 use super::{
@@ -116,8 +118,51 @@ impl HandleRecoveryEvent for RecoveryHealthUpdater<'_> {
     }
 }
 
+/// Target L1 batch for recovering an empty Merkle tree from the storage logs in Postgres.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StorageLogsRecoveryTarget {
+    /// Recover the tree as of the latest L1 batch with metadata in Postgres.
+    LatestWithMetadata,
+    /// Recover the tree as of the specified L1 batch. The L1 batch must have metadata in Postgres.
+    L1Batch(L1BatchNumber),
+}
+
+impl StorageLogsRecoveryTarget {
+    /// Creates a target for the specified L1 batch, or for the latest L1 batch with metadata if `l1_batch` is `None`.
+    pub fn new(l1_batch: Option<L1BatchNumber>) -> Self {
+        l1_batch.map_or(Self::LatestWithMetadata, Self::L1Batch)
+    }
+
+    /// Resolves the L1 batch to recover the tree to. Returns `None` if recovery doesn't make sense,
+    /// e.g. if Postgres only contains the genesis L1 batch.
+    async fn resolve(self, pool: &ConnectionPool) -> anyhow::Result<Option<L1BatchNumber>> {
+        let l1_batch = match self {
+            Self::LatestWithMetadata => {
+                let mut storage = pool.access_storage_tagged("metadata_calculator").await?;
+                storage
+                    .blocks_dal()
+                    .get_last_l1_batch_number_with_metadata()
+                    .await
+                    .context("get_last_l1_batch_number_with_metadata()")?
+            }
+            Self::L1Batch(number) => Some(number),
+        };
+        Ok(l1_batch.filter(|&number| number > L1BatchNumber(0)))
+    }
+}
+
+/// Source of storage logs for tree recovery.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RecoverySource {
+    /// Snapshot applied to Postgres during snapshot recovery. All snapshot logs belong to a single miniblock.
+    Snapshot,
+    /// Full storage log history in Postgres. Tree entries are restored as of the end of the recovery miniblock.
+    StorageLogs,
+}
+
 #[derive(Debug, Clone, Copy)]
 struct SnapshotParameters {
+    source: RecoverySource,
     miniblock: MiniblockNumber,
     expected_root_hash: H256,
     log_count: u64,
@@ -140,12 +185,45 @@ impl SnapshotParameters {
             .with_context(|| format!("Failed getting number of logs for miniblock #{miniblock}"))?;
 
         Ok(Self {
+            source: RecoverySource::Snapshot,
             miniblock,
             expected_root_hash,
             log_count,
         })
     }
 
+    /// Creates parameters for recovering the tree from the storage logs as of the end of the specified L1 batch.
+    async fn from_storage_logs(
+        pool: &ConnectionPool,
+        l1_batch_number: L1BatchNumber,
+    ) -> anyhow::Result<Self> {
+        let mut storage = pool.access_storage_tagged("metadata_calculator").await?;
+        let l1_batch = storage
+            .blocks_dal()
+            .get_l1_batch_metadata(l1_batch_number)
+            .await
+            .with_context(|| format!("Failed getting metadata for L1 batch #{l1_batch_number}"))?
+            .with_context(|| {
+                format!("L1 batch #{l1_batch_number} to recover the tree to has no metadata")
+            })?;
+        let (_, miniblock) = storage
+            .blocks_dal()
+            .get_miniblock_range_of_l1_batch(l1_batch_number)
+            .await
+            .with_context(|| {
+                format!("Failed getting miniblock range for L1 batch #{l1_batch_number}")
+            })?
+            .with_context(|| format!("L1 batch #{l1_batch_number} doesn't have miniblocks"))?;
+
+        Ok(Self {
+            source: RecoverySource::StorageLogs,
+            miniblock,
+            expected_root_hash: l1_batch.metadata.root_hash,
+            // The leaf index is 1-based, and each tree leaf corresponds to a single entry
+            log_count: l1_batch.metadata.rollup_last_leaf_index.saturating_sub(1),
+        })
+    }
+
     fn chunk_count(&self) -> usize {
         zksync_utils::ceil_div(self.log_count, Self::DESIRED_CHUNK_SIZE) as usize
     }
@@ -161,36 +239,73 @@ struct RecoveryOptions<'a> {
 
 impl GenericAsyncTree {
     /// Ensures that the tree is ready for the normal operation, recovering it from a Postgres snapshot
-    /// if necessary.
+    /// or from the storage logs in Postgres (if `storage_logs_recovery` is set) if necessary.
     pub async fn ensure_ready(
         self,
         pool: &ConnectionPool,
         stop_receiver: &watch::Receiver<bool>,
         health_updater: &HealthUpdater,
+        storage_logs_recovery: Option<StorageLogsRecoveryTarget>,
     ) -> anyhow::Result<Option<AsyncTree>> {
-        let (tree, snapshot_recovery) = match self {
+        let (tree, snapshot) = match self {
             Self::Ready(tree) => return Ok(Some(tree)),
             Self::Recovering(tree) => {
-                let snapshot_recovery = get_snapshot_recovery(pool).await?.context(
-                    "Merkle tree is recovering, but Postgres doesn't contain snapshot recovery information",
-                )?;
                 let recovered_version = tree.recovered_version();
-                anyhow::ensure!(
-                    u64::from(snapshot_recovery.l1_batch_number.0) == recovered_version,
-                    "Snapshot L1 batch in Postgres ({snapshot_recovery:?}) differs from the recovered Merkle tree version \
-                     ({recovered_version})"
-                );
-                tracing::info!("Resuming tree recovery with status: {snapshot_recovery:?}");
-                (tree, snapshot_recovery)
+                if let Some(snapshot_recovery) = get_snapshot_recovery(pool).await? {
+                    anyhow::ensure!(
+                        u64::from(snapshot_recovery.l1_batch_number.0) == recovered_version,
+                        "Snapshot L1 batch in Postgres ({snapshot_recovery:?}) differs from the recovered Merkle tree version \
+                         ({recovered_version})"
+                    );
+                    tracing::info!("Resuming tree recovery with status: {snapshot_recovery:?}");
+                    (
+                        tree,
+                        SnapshotParameters::new(pool, &snapshot_recovery).await?,
+                    )
+                } else if let Some(target) = storage_logs_recovery {
+                    let l1_batch = u32::try_from(recovered_version)
+                        .map(L1BatchNumber)
+                        .context("recovered Merkle tree version overflow")?;
+                    if let StorageLogsRecoveryTarget::L1Batch(target_l1_batch) = target {
+                        anyhow::ensure!(
+                            target_l1_batch == l1_batch,
+                            "L1 batch to recover the tree to (#{target_l1_batch}) differs from the recovered Merkle tree \
+                             version ({recovered_version})"
+                        );
+                    }
+                    tracing::info!(
+                        "Resuming tree recovery from storage logs for L1 batch #{l1_batch}"
+                    );
+                    (
+                        tree,
+                        SnapshotParameters::from_storage_logs(pool, l1_batch).await?,
+                    )
+                } else {
+                    anyhow::bail!(
+                        "Merkle tree is recovering, but Postgres doesn't contain snapshot recovery information \
+                         and recovery from storage logs is disabled"
+                    );
+                }
             }
             Self::Empty { db, mode } => {
                 if let Some(snapshot_recovery) = get_snapshot_recovery(pool).await? {
                     tracing::info!(
                         "Starting Merkle tree recovery with status {snapshot_recovery:?}"
                     );
+                    let snapshot = SnapshotParameters::new(pool, &snapshot_recovery).await?;
                     let l1_batch = snapshot_recovery.l1_batch_number;
                     let tree = AsyncTreeRecovery::new(db, l1_batch.0.into(), mode);
-                    (tree, snapshot_recovery)
+                    (tree, snapshot)
+                } else if let Some(l1_batch) = match storage_logs_recovery {
+                    Some(target) => target.resolve(pool).await?,
+                    None => None,
+                } {
+                    tracing::info!(
+                        "Starting Merkle tree recovery from storage logs for L1 batch #{l1_batch}"
+                    );
+                    let snapshot = SnapshotParameters::from_storage_logs(pool, l1_batch).await?;
+                    let tree = AsyncTreeRecovery::new(db, l1_batch.0.into(), mode);
+                    (tree, snapshot)
                 } else {
                     // Start the tree from scratch. The genesis block will be filled in `TreeUpdater::loop_updating_tree()`.
                     return Ok(Some(AsyncTree::new(db, mode)));
@@ -198,7 +313,6 @@ impl GenericAsyncTree {
             }
         };
 
-        let snapshot = SnapshotParameters::new(pool, &snapshot_recovery).await?;
         tracing::debug!("Obtained snapshot parameters: {snapshot:?}");
         let recovery_options = RecoveryOptions {
             chunk_count: snapshot.chunk_count(),
@@ -221,13 +335,12 @@ impl AsyncTreeRecovery {
         let chunk_count = options.chunk_count;
         let chunks: Vec<_> = Self::hashed_key_ranges(chunk_count).collect();
         tracing::info!(
-            "Recovering Merkle tree from Postgres snapshot in {chunk_count} concurrent chunks"
+            "Recovering Merkle tree from {:?} in Postgres in {chunk_count} concurrent chunks",
+            snapshot.source
         );
 
         let mut storage = pool.access_storage().await?;
-        let remaining_chunks = self
-            .filter_chunks(&mut storage, snapshot.miniblock, &chunks)
-            .await?;
+        let remaining_chunks = self.filter_chunks(&mut storage, &snapshot, &chunks).await?;
         drop(storage);
         options
             .events
@@ -245,7 +358,7 @@ impl AsyncTreeRecovery {
                 .await
                 .context("semaphore is never closed")?;
             options.events.chunk_started().await;
-            Self::recover_key_chunk(&tree, snapshot.miniblock, chunk, pool, stop_receiver).await?;
+            Self::recover_key_chunk(&tree, &snapshot, chunk, pool, stop_receiver).await?;
             options.events.chunk_recovered().await;
             anyhow::Ok(())
         });
@@ -295,16 +408,26 @@ impl AsyncTreeRecovery {
     async fn filter_chunks(
         &mut self,
         storage: &mut StorageProcessor<'_>,
-        snapshot_miniblock: MiniblockNumber,
+        snapshot: &SnapshotParameters,
         key_chunks: &[ops::RangeInclusive<H256>],
     ) -> anyhow::Result<Vec<ops::RangeInclusive<H256>>> {
+        let snapshot_miniblock = snapshot.miniblock;
         let chunk_starts_latency =
             RECOVERY_METRICS.latency[&RecoveryStage::LoadChunkStarts].start();
-        let chunk_starts = storage
-            .storage_logs_dal()
-            .get_chunk_starts_for_miniblock(snapshot_miniblock, key_chunks)
-            .await
-            .context("Failed getting chunk starts")?;
+        let mut storage_logs_dal = storage.storage_logs_dal();
+        let chunk_starts = match snapshot.source {
+            RecoverySource::Snapshot => {
+                storage_logs_dal
+                    .get_chunk_starts_for_miniblock(snapshot_miniblock, key_chunks)
+                    .await
+            }
+            RecoverySource::StorageLogs => {
+                storage_logs_dal
+                    .get_chunk_starts_up_to_miniblock(snapshot_miniblock, key_chunks)
+                    .await
+            }
+        };
+        let chunk_starts = chunk_starts.context("Failed getting chunk starts")?;
         let chunk_starts_latency = chunk_starts_latency.observe();
         tracing::debug!(
             "Loaded start entries for {} chunks in {chunk_starts_latency:?}",
@@ -339,7 +462,7 @@ impl AsyncTreeRecovery {
 
     async fn recover_key_chunk(
         tree: &Mutex<AsyncTreeRecovery>,
-        snapshot_miniblock: MiniblockNumber,
+        snapshot: &SnapshotParameters,
         key_chunk: ops::RangeInclusive<H256>,
         pool: &ConnectionPool,
         stop_receiver: &watch::Receiver<bool>,
@@ -353,13 +476,23 @@ impl AsyncTreeRecovery {
             return Ok(());
         }
 
+        let snapshot_miniblock = snapshot.miniblock;
         let entries_latency =
             RECOVERY_METRICS.chunk_latency[&ChunkRecoveryStage::LoadEntries].start();
-        let all_entries = storage
-            .storage_logs_dal()
-            .get_tree_entries_for_miniblock(snapshot_miniblock, key_chunk.clone())
-            .await
-            .with_context(|| {
+        let mut storage_logs_dal = storage.storage_logs_dal();
+        let all_entries = match snapshot.source {
+            RecoverySource::Snapshot => {
+                storage_logs_dal
+                    .get_tree_entries_for_miniblock(snapshot_miniblock, key_chunk.clone())
+                    .await
+            }
+            RecoverySource::StorageLogs => {
+                storage_logs_dal
+                    .get_tree_entries_up_to_miniblock(snapshot_miniblock, key_chunk.clone())
+                    .await
+            }
+        };
+        let all_entries = all_entries.with_context(|| {
                 format!("Failed getting entries for chunk {key_chunk:?} in snapshot for miniblock #{snapshot_miniblock}")
             })?;
         drop(storage);
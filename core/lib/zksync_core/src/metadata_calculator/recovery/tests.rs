@@ -25,8 +25,8 @@ use crate::{
     metadata_calculator::{
         helpers::create_db,
         tests::{
-            extend_db_state, extend_db_state_from_l1_batch, gen_storage_logs, run_calculator,
-            setup_calculator,
+            extend_db_state, extend_db_state_from_l1_batch, gen_storage_logs, reset_db_state,
+            run_calculator, setup_calculator,
         },
         MetadataCalculator, MetadataCalculatorConfig,
     },
@@ -74,6 +74,7 @@ fn calculating_hashed_key_ranges_for_arbitrary_chunks(chunk_count: usize) {
 #[test]
 fn calculating_chunk_count() {
     let mut snapshot = SnapshotParameters {
+        source: RecoverySource::Snapshot,
         miniblock: MiniblockNumber(1),
         log_count: 160_000_000,
         expected_root_hash: H256::zero(),
@@ -134,6 +135,85 @@ async fn basic_recovery_workflow() {
     }
 }
 
+#[tokio::test]
+async fn recovery_workflow_from_storage_logs() {
+    let pool = ConnectionPool::test_pool().await;
+    let temp_dir = TempDir::new().expect("failed get temporary directory for RocksDB");
+    let (calculator, _) = setup_calculator(&temp_dir.path().join("original"), &pool).await;
+    reset_db_state(&pool, 5).await;
+    let root_hash = run_calculator(calculator, pool.clone()).await;
+
+    let snapshot = SnapshotParameters::from_storage_logs(&pool, L1BatchNumber(5))
+        .await
+        .unwrap();
+    assert_eq!(snapshot.source, RecoverySource::StorageLogs);
+    assert_eq!(snapshot.expected_root_hash, root_hash);
+
+    let (_stop_sender, stop_receiver) = watch::channel(false);
+    for chunk_count in [1, 4, 9, 16, 60, 256] {
+        println!("Recovering tree with {chunk_count} chunks");
+
+        let tree_path = temp_dir.path().join(format!("recovery-{chunk_count}"));
+        let tree = create_tree_recovery(tree_path, L1BatchNumber(5)).await;
+        let (_health_check, health_updater) = ReactiveHealthCheck::new("tree");
+        let recovery_options = RecoveryOptions {
+            chunk_count,
+            concurrency_limit: 1,
+            events: Box::new(RecoveryHealthUpdater::new(&health_updater)),
+        };
+        let tree = tree
+            .recover(snapshot, recovery_options, &pool, &stop_receiver)
+            .await
+            .unwrap()
+            .expect("Tree recovery unexpectedly aborted");
+        assert_eq!(tree.root_hash(), root_hash);
+    }
+}
+
+#[test_casing(2, [None, Some(3)])]
+#[tokio::test]
+async fn entire_recovery_workflow_from_storage_logs(l1_batch: Option<u32>) {
+    let pool = ConnectionPool::test_pool().await;
+    let temp_dir = TempDir::new().expect("failed get temporary directory for RocksDB");
+    let (calculator, _) = setup_calculator(&temp_dir.path().join("original"), &pool).await;
+    reset_db_state(&pool, 5).await;
+    let root_hash = run_calculator(calculator, pool.clone()).await;
+
+    let merkle_tree_config = MerkleTreeConfig {
+        path: temp_dir
+            .path()
+            .join("recovered")
+            .to_str()
+            .unwrap()
+            .to_owned(),
+        recover_from_storage_logs: true,
+        storage_logs_recovery_l1_batch: l1_batch,
+        ..MerkleTreeConfig::default()
+    };
+    let calculator_config = MetadataCalculatorConfig::for_main_node(
+        &merkle_tree_config,
+        &OperationsManagerConfig { delay_interval: 50 },
+    );
+    let expected_target = match l1_batch {
+        None => StorageLogsRecoveryTarget::LatestWithMetadata,
+        Some(number) => StorageLogsRecoveryTarget::L1Batch(L1BatchNumber(number)),
+    };
+    assert_eq!(
+        calculator_config.storage_logs_recovery,
+        Some(expected_target)
+    );
+
+    let calculator = MetadataCalculator::new(calculator_config, None).await;
+    let tree_reader = calculator.tree_reader();
+    // If the tree is recovered to an earlier L1 batch, the remaining batches are processed as usual.
+    let recovered_root_hash = run_calculator(calculator, pool.clone()).await;
+    assert_eq!(recovered_root_hash, root_hash);
+
+    let tree_info = tree_reader.await.info().await;
+    assert_eq!(tree_info.root_hash, root_hash);
+    assert_eq!(tree_info.next_l1_batch_number, L1BatchNumber(6));
+}
+
 async fn prepare_recovery_snapshot(
     pool: &ConnectionPool,
     temp_dir: &TempDir,
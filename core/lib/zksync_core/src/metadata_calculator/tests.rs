@@ -15,14 +15,19 @@ use zksync_health_check::{CheckHealth, HealthStatus};
 use zksync_merkle_tree::domain::ZkSyncTree;
 use zksync_object_store::{ObjectStore, ObjectStoreFactory};
 use zksync_types::{
+    aggregated_operations::AggregatedActionType,
     block::{BlockGasCount, L1BatchHeader},
-    proofs::PrepareBasicCircuitsJob,
     AccountTreeId, Address, L1BatchNumber, L2ChainId, MiniblockNumber, StorageKey, StorageLog,
     H256,
 };
 use zksync_utils::u32_to_h256;
 
-use super::{GenericAsyncTree, L1BatchWithLogs, MetadataCalculator, MetadataCalculatorConfig};
+use super::{
+    consistency::{TreeConsistencyChecker, TreeInconsistency},
+    helpers::{create_db, AsyncTree},
+    GenericAsyncTree, L1BatchWithLogs, MerkleTreePruningTask, MetadataCalculator,
+    MetadataCalculatorConfig,
+};
 use crate::{
     genesis::{ensure_genesis_state, GenesisParams},
     utils::testonly::{create_l1_batch, create_miniblock},
@@ -70,7 +75,10 @@ async fn basic_workflow() {
     let expected_tree_hash = expected_tree_hash(&pool).await;
     assert_eq!(merkle_tree_hash, expected_tree_hash);
 
-    let job: PrepareBasicCircuitsJob = object_store.get(L1BatchNumber(1)).await.unwrap();
+    let job = object_store
+        .get_witness_input(L1BatchNumber(1))
+        .await
+        .unwrap();
     assert!(job.next_enumeration_index() > 0);
     let merkle_paths: Vec<_> = job.clone().into_merkle_paths().collect();
     assert!(!merkle_paths.is_empty() && merkle_paths.len() <= 100);
@@ -95,7 +103,7 @@ async fn expected_tree_hash(pool: &ConnectionPool) -> H256 {
     let mut all_logs = vec![];
     for i in 0..=sealed_l1_batch_number.0 {
         let logs = L1BatchWithLogs::new(&mut storage, L1BatchNumber(i)).await;
-        let logs = logs.unwrap().storage_logs;
+        let logs = logs.unwrap().unwrap().storage_logs;
         all_logs.extend(logs);
     }
     ZkSyncTree::process_genesis_batch(&all_logs).root_hash
@@ -169,7 +177,10 @@ async fn multi_l1_batch_workflow() {
     let mut prev_index = None;
     for l1_batch_number in 1..=10 {
         let l1_batch_number = L1BatchNumber(l1_batch_number);
-        let job: PrepareBasicCircuitsJob = object_store.get(l1_batch_number).await.unwrap();
+        let job = object_store
+            .get_witness_input(l1_batch_number)
+            .await
+            .unwrap();
         let next_enumeration_index = job.next_enumeration_index();
         let merkle_paths: Vec<_> = job.into_merkle_paths().collect();
         assert!(!merkle_paths.is_empty() && merkle_paths.len() <= 10);
@@ -358,6 +369,175 @@ async fn postgres_backup_recovery_with_excluded_metadata() {
     test_postgres_backup_recovery(false, true).await;
 }
 
+#[tokio::test]
+async fn pruning_retains_versions_for_unexecuted_l1_batches() {
+    let pool = ConnectionPool::test_pool().await;
+    let temp_dir = TempDir::new().expect("failed get temporary directory for RocksDB");
+    let calculator = setup_lightweight_calculator(temp_dir.path(), &pool).await;
+    reset_db_state(&pool, 5).await;
+    run_calculator(calculator, pool.clone()).await;
+
+    let mut storage = pool.access_storage().await.unwrap();
+    for number in 1..=3 {
+        storage
+            .eth_sender_dal()
+            .insert_bogus_confirmed_eth_tx(
+                L1BatchNumber(number),
+                AggregatedActionType::Execute,
+                H256::from_low_u64_be(number.into()),
+                chrono::Utc::now(),
+            )
+            .await
+            .unwrap();
+    }
+    drop(storage);
+
+    let calculator = setup_lightweight_calculator(temp_dir.path(), &pool).await;
+    let GenericAsyncTree::Ready(tree) = &calculator.tree else {
+        panic!("Unexpected tree state: {:?}", calculator.tree);
+    };
+    assert_eq!(tree.next_l1_batch_number(), L1BatchNumber(6));
+    let reader = tree.reader();
+    // Pruning is configured to keep only the latest version, but it should retain versions
+    // starting from the last executed L1 batch.
+    let pruning_task =
+        MerkleTreePruningTask::new(tree, 0).with_poll_interval(Duration::from_millis(10));
+    let (stop_sender, stop_receiver) = watch::channel(false);
+    let pruning_task_handle = tokio::spawn(pruning_task.run(pool.clone(), stop_receiver));
+
+    run_with_timeout(RUN_TIMEOUT, async {
+        while reader
+            .clone()
+            .entries_with_proofs(L1BatchNumber(2), vec![])
+            .await
+            .is_ok()
+        {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await;
+
+    stop_sender.send_replace(true);
+    pruning_task_handle.await.unwrap().unwrap();
+
+    for number in 0..3 {
+        let result = reader
+            .clone()
+            .entries_with_proofs(L1BatchNumber(number), vec![])
+            .await;
+        assert!(result.is_err(), "L1 batch #{number} is not pruned");
+    }
+    for number in 3..=5 {
+        reader
+            .clone()
+            .entries_with_proofs(L1BatchNumber(number), vec![])
+            .await
+            .unwrap();
+    }
+}
+
+#[tokio::test]
+async fn follower_tree_serves_lagging_proofs() {
+    let pool = ConnectionPool::test_pool().await;
+    let temp_dir = TempDir::new().expect("failed get temporary directory for RocksDB");
+    let (merkle_tree_config, operation_config) =
+        create_config(temp_dir.path(), MerkleTreeMode::Lightweight);
+    let merkle_tree_config = MerkleTreeConfig {
+        follower_version_lag: Some(1),
+        ..merkle_tree_config
+    };
+    let calculator =
+        setup_calculator_with_options(&merkle_tree_config, &operation_config, &pool, None).await;
+    reset_db_state(&pool, 5).await;
+
+    let tree_reader = calculator.tree_reader();
+    let (stop_sender, stop_receiver) = watch::channel(false);
+    let calculator_task = tokio::spawn(calculator.run(pool.clone(), stop_receiver));
+    let tree_reader = run_with_timeout(RUN_TIMEOUT, tree_reader).await;
+    // Wait until the follower catches up with the main tree.
+    run_with_timeout(RUN_TIMEOUT, async {
+        while tree_reader.clone().info().await.next_l1_batch_number < L1BatchNumber(6) {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await;
+
+    for number in 0..5 {
+        tree_reader
+            .clone()
+            .entries_with_proofs(L1BatchNumber(number), vec![])
+            .await
+            .unwrap();
+    }
+    // The latest L1 batch should not be served because of the configured lag.
+    let err = tree_reader
+        .clone()
+        .entries_with_proofs(L1BatchNumber(5), vec![])
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("does not exist"), "{err}");
+
+    stop_sender.send_replace(true);
+    calculator_task.await.unwrap().unwrap();
+}
+
+#[tokio::test]
+async fn consistency_checker_detects_root_hash_mismatch() {
+    let pool = ConnectionPool::test_pool().await;
+    let temp_dir = TempDir::new().expect("failed get temporary directory for RocksDB");
+    let calculator = setup_lightweight_calculator(temp_dir.path(), &pool).await;
+    reset_db_state(&pool, 5).await;
+    run_calculator(calculator, pool.clone()).await;
+
+    let db = create_db(
+        temp_dir.path().join("new"),
+        0,
+        16 << 20,       // 16 MiB,
+        Duration::ZERO, // writes should never be stalled in tests
+        500,
+    )
+    .await;
+    let mut tree = AsyncTree::new(db, MerkleTreeMode::Lightweight);
+    let tree_reader = tree.reader();
+    let checker = TreeConsistencyChecker::new(Duration::from_secs(1), None);
+    for number in 0..=5 {
+        let inconsistency = checker
+            .check_l1_batch(&tree_reader, &pool, L1BatchNumber(number))
+            .await
+            .unwrap();
+        assert_eq!(inconsistency, None, "L1 batch #{number}");
+    }
+
+    // Make the tree diverge from Postgres starting from L1 batch #3.
+    tree.revert_logs(L1BatchNumber(2));
+    tree.process_l1_batch(vec![]).await;
+    tree.save().await;
+
+    let inconsistency = checker
+        .check_l1_batch(&tree_reader, &pool, L1BatchNumber(2))
+        .await
+        .unwrap();
+    assert_eq!(inconsistency, None);
+    let inconsistency = checker
+        .check_l1_batch(&tree_reader, &pool, L1BatchNumber(3))
+        .await
+        .unwrap()
+        .expect("no inconsistency detected");
+    assert_matches!(
+        inconsistency,
+        TreeInconsistency::RootHashMismatch {
+            l1_batch_number: L1BatchNumber(3),
+            stored_root_hash,
+            tree_root_hash,
+        } if stored_root_hash != tree_root_hash
+    );
+    // The tree version for L1 batch #4 is removed by the revert.
+    checker
+        .check_l1_batch(&tree_reader, &pool, L1BatchNumber(4))
+        .await
+        .unwrap_err();
+}
+
 pub(crate) async fn setup_calculator(
     db_path: &Path,
     pool: &ConnectionPool,
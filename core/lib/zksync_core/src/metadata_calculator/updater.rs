@@ -20,6 +20,9 @@ use super::{
 };
 use crate::utils::wait_for_l1_batch;
 
+/// Number of Merkle paths in a single chunk of the witness input uploaded to the object store.
+const WITNESS_INPUT_PATHS_PER_CHUNK: usize = 256;
+
 #[derive(Debug)]
 pub(super) struct TreeUpdater {
     tree: AsyncTree,
@@ -55,7 +58,11 @@ impl TreeUpdater {
                 witness_input.expect("No witness input provided by tree; this is a bug");
             let save_witnesses_latency = METRICS.start_stage(TreeUpdateStage::SaveGcs);
             let object_key = object_store
-                .put(l1_batch_number, &witness_input)
+                .put_chunked_witness_input(
+                    l1_batch_number,
+                    witness_input,
+                    WITNESS_INPUT_PATHS_PER_CHUNK,
+                )
                 .await
                 .unwrap();
             save_witnesses_latency.observe();
@@ -86,12 +93,12 @@ impl TreeUpdater {
         &mut self,
         storage: &mut StorageProcessor<'_>,
         l1_batch_numbers: ops::RangeInclusive<u32>,
-    ) -> L1BatchNumber {
+    ) -> anyhow::Result<L1BatchNumber> {
         let start = Instant::now();
         tracing::info!("Processing L1 batches #{l1_batch_numbers:?}");
         let first_l1_batch_number = L1BatchNumber(*l1_batch_numbers.start());
         let last_l1_batch_number = L1BatchNumber(*l1_batch_numbers.end());
-        let mut l1_batch_data = L1BatchWithLogs::new(storage, first_l1_batch_number).await;
+        let mut l1_batch_data = L1BatchWithLogs::new(storage, first_l1_batch_number).await?;
 
         let mut previous_root_hash = self.tree.root_hash();
         let mut total_logs = 0;
@@ -99,7 +106,7 @@ impl TreeUpdater {
         for l1_batch_number in l1_batch_numbers {
             let l1_batch_number = L1BatchNumber(l1_batch_number);
             let Some(current_l1_batch_data) = l1_batch_data else {
-                return l1_batch_number;
+                return Ok(l1_batch_number);
             };
             total_logs += current_l1_batch_data.storage_logs.len();
 
@@ -108,11 +115,12 @@ impl TreeUpdater {
                 if l1_batch_number < last_l1_batch_number {
                     L1BatchWithLogs::new(storage, l1_batch_number + 1).await
                 } else {
-                    None // Don't need to load the next L1 batch after the last one we're processing.
+                    Ok(None) // Don't need to load the next L1 batch after the last one we're processing.
                 }
             };
             let ((header, metadata, object_key), next_l1_batch_data) =
                 future::join(process_l1_batch_task, load_next_l1_batch_task).await;
+            let next_l1_batch_data = next_l1_batch_data?;
 
             let check_consistency_latency = METRICS.start_stage(TreeUpdateStage::CheckConsistency);
             Self::check_initial_writes_consistency(
@@ -188,7 +196,7 @@ impl TreeUpdater {
         save_rocksdb_latency.observe();
         MetadataCalculator::update_metrics(&updated_headers, total_logs, start);
 
-        last_l1_batch_number + 1
+        Ok(last_l1_batch_number + 1)
     }
 
     async fn calculate_commitments(
@@ -238,7 +246,7 @@ impl TreeUpdater {
         &mut self,
         mut storage: StorageProcessor<'_>,
         next_l1_batch_to_seal: &mut L1BatchNumber,
-    ) {
+    ) -> anyhow::Result<()> {
         let Some(last_sealed_l1_batch) = storage
             .blocks_dal()
             .get_sealed_l1_batch_number()
@@ -246,7 +254,7 @@ impl TreeUpdater {
             .unwrap()
         else {
             tracing::trace!("No L1 batches to seal: Postgres storage is empty");
-            return;
+            return Ok(());
         };
         let last_requested_l1_batch =
             next_l1_batch_to_seal.0 + self.max_l1_batches_per_iter as u32 - 1;
@@ -260,8 +268,9 @@ impl TreeUpdater {
             tracing::info!("Updating Merkle tree with L1 batches #{l1_batch_numbers:?}");
             *next_l1_batch_to_seal = self
                 .process_multiple_batches(&mut storage, l1_batch_numbers)
-                .await;
+                .await?;
         }
+        Ok(())
     }
 
     /// The processing loop for this updater.
@@ -288,7 +297,7 @@ impl TreeUpdater {
                 "Non-zero earliest L1 batch is not supported without previous tree recovery"
             );
             let logs = L1BatchWithLogs::new(&mut storage, earliest_l1_batch)
-                .await
+                .await?
                 .context("Missing storage logs for the genesis L1 batch")?;
             tree.process_l1_batch(logs.storage_logs).await;
             tree.save().await;
@@ -349,7 +358,7 @@ impl TreeUpdater {
             let storage = pool.access_storage_tagged("metadata_calculator").await?;
 
             let snapshot = *next_l1_batch_to_seal;
-            self.step(storage, &mut next_l1_batch_to_seal).await;
+            self.step(storage, &mut next_l1_batch_to_seal).await?;
             let delay = if snapshot == *next_l1_batch_to_seal {
                 tracing::trace!(
                     "Metadata calculator (next L1 batch: #{next_l1_batch_to_seal}) \
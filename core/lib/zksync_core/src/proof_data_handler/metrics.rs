@@ -0,0 +1,46 @@
+//! Metrics for the proof data handler.
+
+use vise::{EncodeLabelSet, EncodeLabelValue, Family, Gauge, Metrics};
+use zksync_types::prover_server_api::{ProofGenerationPriority, ProofGenerationQueueStats};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EncodeLabelValue, EncodeLabelSet)]
+#[metrics(label = "priority", rename_all = "snake_case")]
+pub(super) enum PriorityClass {
+    Low,
+    Normal,
+    High,
+}
+
+impl From<ProofGenerationPriority> for PriorityClass {
+    fn from(priority: ProofGenerationPriority) -> Self {
+        match priority {
+            ProofGenerationPriority::Low => Self::Low,
+            ProofGenerationPriority::Normal => Self::Normal,
+            ProofGenerationPriority::High => Self::High,
+        }
+    }
+}
+
+#[derive(Debug, Metrics)]
+#[metrics(prefix = "server_proof_data_handler")]
+pub(super) struct ProofDataHandlerMetrics {
+    /// Number of L1 batches waiting to be picked by provers, grouped by the priority class.
+    pub queued_jobs: Family<PriorityClass, Gauge<usize>>,
+    /// Number of the oldest L1 batch waiting to be picked by provers.
+    pub oldest_queued_l1_batch: Gauge<u64>,
+}
+
+impl ProofDataHandlerMetrics {
+    pub fn observe_queue_stats(&self, stats: &ProofGenerationQueueStats) {
+        for priority in ProofGenerationPriority::ALL {
+            let count = stats.queued_jobs.get(&priority).copied().unwrap_or(0);
+            self.queued_jobs[&priority.into()].set(count);
+        }
+        if let Some(number) = stats.oldest_queued_l1_batch {
+            self.oldest_queued_l1_batch.set(number.0.into());
+        }
+    }
+}
+
+#[vise::register]
+pub(super) static METRICS: vise::Global<ProofDataHandlerMetrics> = vise::Global::new();
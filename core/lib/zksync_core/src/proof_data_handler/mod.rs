@@ -1,7 +1,14 @@
-use std::{net::SocketAddr, sync::Arc};
+use std::{
+    net::{Ipv4Addr, SocketAddr},
+    sync::Arc,
+};
 
 use anyhow::Context as _;
-use axum::{extract::Path, routing::post, Json, Router};
+use axum::{
+    extract::Path,
+    routing::{get, post},
+    Json, Router,
+};
 use tokio::sync::watch;
 use zksync_config::{
     configs::{proof_data_handler::ProtocolVersionLoadingMode, ProofDataHandlerConfig},
@@ -11,12 +18,15 @@ use zksync_dal::ConnectionPool;
 use zksync_object_store::ObjectStore;
 use zksync_types::{
     protocol_version::{L1VerifierConfig, VerifierParams},
-    prover_server_api::{ProofGenerationDataRequest, SubmitProofRequest},
+    prover_server_api::{
+        ProofGenerationDataRequest, SetProofGenerationPriorityRequest, SubmitProofRequest,
+    },
     H256,
 };
 
 use crate::proof_data_handler::request_processor::RequestProcessor;
 
+mod metrics;
 mod request_processor;
 
 fn fri_l1_verifier_config(contracts_config: &ContractsConfig) -> L1VerifierConfig {
@@ -44,9 +54,12 @@ pub(crate) async fn run_server(
         ProtocolVersionLoadingMode::FromDb => None,
         ProtocolVersionLoadingMode::FromEnvVar => Some(fri_l1_verifier_config(&contracts_config)),
     };
+    let admin_port = config.admin_http_port;
     let get_proof_gen_processor =
         RequestProcessor::new(blob_store, pool, config, l1_verifier_config);
     let submit_proof_processor = get_proof_gen_processor.clone();
+    let set_priority_processor = get_proof_gen_processor.clone();
+    let queue_stats_processor = get_proof_gen_processor.clone();
     let app = Router::new()
         .route(
             "/proof_generation_data",
@@ -70,17 +83,54 @@ pub(crate) async fn run_server(
                 },
             ),
         );
+    // Admin endpoints are served by a separate server listening on localhost only, so that they are not exposed
+    // to provers.
+    let admin_app = Router::new()
+        .route(
+            "/admin/priority/:l1_batch_number",
+            post(
+                move |l1_batch_number: Path<u32>,
+                      payload: Json<SetProofGenerationPriorityRequest>| async move {
+                    set_priority_processor
+                        .set_proof_generation_priority(l1_batch_number, payload)
+                        .await
+                },
+            ),
+        )
+        .route(
+            "/admin/queue_stats",
+            get(move || async move { queue_stats_processor.get_queue_stats().await }),
+        );
 
-    axum::Server::bind(&bind_address)
+    let mut admin_stop_receiver = stop_receiver.clone();
+    let admin_server = async move {
+        let Some(admin_port) = admin_port else {
+            tracing::info!(
+                "Admin port is not configured; proof data handler admin endpoints are disabled"
+            );
+            return Ok(());
+        };
+        let admin_bind_address = SocketAddr::from((Ipv4Addr::LOCALHOST, admin_port));
+        tracing::debug!("Starting proof data handler admin server on {admin_bind_address}");
+        axum::Server::bind(&admin_bind_address)
+            .serve(admin_app.into_make_service())
+            .with_graceful_shutdown(async move {
+                admin_stop_receiver.changed().await.ok();
+            })
+            .await
+            .context("Proof data handler admin server failed")
+    };
+    let server = axum::Server::bind(&bind_address)
         .serve(app.into_make_service())
         .with_graceful_shutdown(async move {
             if stop_receiver.changed().await.is_err() {
                 tracing::warn!("Stop signal sender for proof data handler server was dropped without sending a signal");
             }
             tracing::info!("Stop signal received, proof data handler server is shutting down");
-        })
-        .await
-        .context("Proof data handler server failed")?;
+        });
+    let server = async { server.await.context("Proof data handler server failed") };
+
+    tokio::try_join!(server, admin_server)?;
     tracing::info!("Proof data handler server shut down");
     Ok(())
 }
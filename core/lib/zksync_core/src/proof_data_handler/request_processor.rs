@@ -1,34 +1,64 @@
-use std::{convert::TryFrom, sync::Arc};
+use std::{
+    convert::TryFrom,
+    io, mem,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use axum::{
+    body::StreamBody,
     extract::Path,
-    http::StatusCode,
+    http::{header, StatusCode},
     response::{IntoResponse, Response},
     Json,
 };
+use serde::{
+    ser::{self, SerializeSeq, SerializeStruct},
+    Serialize, Serializer,
+};
+use tokio::{
+    runtime::Handle,
+    sync::{mpsc, Mutex},
+};
 use zksync_config::configs::{
     proof_data_handler::ProtocolVersionLoadingMode, ProofDataHandlerConfig,
 };
 use zksync_dal::{ConnectionPool, SqlxError};
-use zksync_object_store::{ObjectStore, ObjectStoreError};
+use zksync_object_store::{ObjectStore, ObjectStoreError, PrepareBasicCircuitsJobChunkKey};
 use zksync_types::{
     commitment::serialize_commitments,
+    proofs::{
+        PrepareBasicCircuitsJob, PrepareBasicCircuitsJobChunk, PrepareBasicCircuitsJobHeader,
+    },
     protocol_version::{FriProtocolVersionId, L1VerifierConfig},
     prover_server_api::{
         ProofGenerationData, ProofGenerationDataRequest, ProofGenerationDataResponse,
-        SubmitProofRequest, SubmitProofResponse,
+        ProofGenerationQueueStats, SetProofGenerationPriorityRequest, SubmitProofRequest,
+        SubmitProofResponse,
     },
     web3::signing::keccak256,
     L1BatchNumber, H256,
 };
 use zksync_utils::u256_to_h256;
 
+use super::metrics::METRICS;
+
+/// Approximate size of a single chunk of the streamed proof generation data.
+const STREAMED_CHUNK_SIZE: usize = 1 << 20;
+/// Maximum number of serialized chunks buffered before being sent to the client. If the client doesn't keep up,
+/// serialization is paused.
+const MAX_BUFFERED_CHUNKS: usize = 4;
+/// Time for which proof generation queue stats are cached. Stats are requested on each proof generation data
+/// request, and computing them requires scanning the queue.
+const QUEUE_STATS_CACHE_TTL: Duration = Duration::from_secs(10);
+
 #[derive(Clone)]
 pub(crate) struct RequestProcessor {
     blob_store: Arc<dyn ObjectStore>,
     pool: ConnectionPool,
     config: ProofDataHandlerConfig,
     l1_verifier_config: Option<L1VerifierConfig>,
+    cached_queue_stats: Arc<Mutex<Option<(Instant, ProofGenerationQueueStats)>>>,
 }
 
 pub(crate) enum RequestProcessorError {
@@ -75,34 +105,70 @@ impl RequestProcessor {
             pool,
             config,
             l1_verifier_config,
+            cached_queue_stats: Arc::default(),
         }
     }
 
-    pub(crate) async fn get_proof_generation_data(
-        &self,
-        request: Json<ProofGenerationDataRequest>,
-    ) -> Result<Json<ProofGenerationDataResponse>, RequestProcessorError> {
-        tracing::info!("Received request for proof generation data: {:?}", request);
+    /// Returns proof generation queue stats, refreshing them if the cached value is stale. The lock is held
+    /// while refreshing, so that concurrent requests don't query the database at the same time.
+    async fn queue_stats(&self) -> Result<ProofGenerationQueueStats, SqlxError> {
+        let mut cached_queue_stats = self.cached_queue_stats.lock().await;
+        if let Some((updated_at, stats)) = &*cached_queue_stats {
+            if updated_at.elapsed() < QUEUE_STATS_CACHE_TTL {
+                return Ok(stats.clone());
+            }
+        }
 
-        let l1_batch_number_result = self
+        let stats = self
             .pool
             .access_storage()
             .await
             .unwrap()
             .proof_generation_dal()
-            .get_next_block_to_be_proven(self.config.proof_generation_timeout())
+            .get_queue_stats()
+            .await?;
+        METRICS.observe_queue_stats(&stats);
+        *cached_queue_stats = Some((Instant::now(), stats.clone()));
+        Ok(stats)
+    }
+
+    pub(crate) async fn get_proof_generation_data(
+        &self,
+        request: Json<ProofGenerationDataRequest>,
+    ) -> Result<Response, RequestProcessorError> {
+        tracing::info!("Received request for proof generation data: {:?}", request);
+
+        let mut storage = self.pool.access_storage().await.unwrap();
+        let l1_batch_number_result = storage
+            .proof_generation_dal()
+            .get_next_block_to_be_proven(
+                self.config.proof_generation_timeout(),
+                self.config.job_aging_timeout(),
+            )
             .await;
+        drop(storage);
+        // Queue stats are only used for metrics, so errors are not propagated.
+        if let Err(err) = self.queue_stats().await {
+            tracing::warn!("Failed getting proof generation queue stats: {err}");
+        }
 
         let l1_batch_number = match l1_batch_number_result {
             Some(number) => number,
-            None => return Ok(Json(ProofGenerationDataResponse::Success(None))), // no batches pending to be proven
+            None => {
+                // no batches pending to be proven
+                let response =
+                    ProofGenerationDataResponse::<PrepareBasicCircuitsJob>::Success(None);
+                return Ok(Json(response).into_response());
+            }
         };
 
-        let blob = self
-            .blob_store
-            .get(l1_batch_number)
-            .await
-            .map_err(RequestProcessorError::ObjectStore)?;
+        let witness_input_header: Option<PrepareBasicCircuitsJobHeader> =
+            match self.blob_store.get(l1_batch_number).await {
+                Ok(header) => Some(header),
+                // Witness input was stored as a single object before chunked storage was introduced.
+                Err(ObjectStoreError::KeyNotFound(_)) => None,
+                Err(err) => return Err(RequestProcessorError::ObjectStore(err)),
+            };
 
         let fri_protocol_version_id =
             FriProtocolVersionId::try_from(self.config.fri_protocol_version_id)
@@ -118,16 +184,69 @@ impl RequestProcessor {
             }
         };
 
+        let Some(header) = witness_input_header else {
+            let blob: PrepareBasicCircuitsJob = self
+                .blob_store
+                .get(l1_batch_number)
+                .await
+                .map_err(RequestProcessorError::ObjectStore)?;
+            let proof_gen_data = ProofGenerationData {
+                l1_batch_number,
+                data: blob,
+                fri_protocol_version_id,
+                l1_verifier_config,
+            };
+            let response = ProofGenerationDataResponse::Success(Some(proof_gen_data));
+            return Ok(Json(response).into_response());
+        };
+
         let proof_gen_data = ProofGenerationData {
             l1_batch_number,
-            data: blob,
+            data: ChunkedWitnessInput {
+                blob_store: self.blob_store.clone(),
+                l1_batch_number,
+                header,
+                runtime: Handle::current(),
+            },
             fri_protocol_version_id,
             l1_verifier_config,
         };
+        Ok(stream_proof_generation_data(proof_gen_data))
+    }
+
+    pub(crate) async fn set_proof_generation_priority(
+        &self,
+        Path(l1_batch_number): Path<u32>,
+        Json(payload): Json<SetProofGenerationPriorityRequest>,
+    ) -> Result<StatusCode, RequestProcessorError> {
+        tracing::info!(
+            "Setting proof generation priority for L1 batch #{l1_batch_number}: {payload:?}"
+        );
+        self.pool
+            .access_storage()
+            .await
+            .unwrap()
+            .proof_generation_dal()
+            .set_proof_generation_priority(
+                L1BatchNumber(l1_batch_number),
+                payload.priority,
+                payload.deadline_in_secs.map(Duration::from_secs),
+            )
+            .await
+            .map_err(RequestProcessorError::Sqlx)?;
+        // Priorities affect queue stats, so the cached stats are invalidated.
+        *self.cached_queue_stats.lock().await = None;
+        Ok(StatusCode::NO_CONTENT)
+    }
 
-        Ok(Json(ProofGenerationDataResponse::Success(Some(
-            proof_gen_data,
-        ))))
+    pub(crate) async fn get_queue_stats(
+        &self,
+    ) -> Result<Json<ProofGenerationQueueStats>, RequestProcessorError> {
+        let stats = self
+            .queue_stats()
+            .await
+            .map_err(RequestProcessorError::Sqlx)?;
+        Ok(Json(stats))
     }
 
     pub(crate) async fn submit_proof(
@@ -236,3 +355,215 @@ impl RequestProcessor {
         Ok(Json(SubmitProofResponse::Success))
     }
 }
+
+/// Witness input stored in chunks (see `ObjectStore::put_chunked_witness_input()`). Chunks are lazily fetched
+/// from the object store during serialization, which must thus happen on a blocking thread. Serializes identically
+/// to the [`PrepareBasicCircuitsJob`] restored from the chunks.
+#[derive(Debug)]
+struct ChunkedWitnessInput {
+    blob_store: Arc<dyn ObjectStore>,
+    l1_batch_number: L1BatchNumber,
+    header: PrepareBasicCircuitsJobHeader,
+    runtime: Handle,
+}
+
+impl ChunkedWitnessInput {
+    fn fetch_chunk(
+        &self,
+        chunk_idx: usize,
+    ) -> Result<PrepareBasicCircuitsJobChunk, ObjectStoreError> {
+        let key = PrepareBasicCircuitsJobChunkKey {
+            l1_batch_number: self.l1_batch_number,
+            chunk_idx,
+        };
+        self.runtime.block_on(self.blob_store.get(key))
+    }
+}
+
+impl Serialize for ChunkedWitnessInput {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        struct MerklePaths<'a>(&'a ChunkedWitnessInput);
+
+        impl Serialize for MerklePaths<'_> {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                let mut seq = serializer.serialize_seq(None)?;
+                for chunk_idx in 0..self.0.header.chunk_count {
+                    let chunk = self.0.fetch_chunk(chunk_idx).map_err(|err| {
+                        ser::Error::custom(format!("failed fetching chunk #{chunk_idx}: {err}"))
+                    })?;
+                    for path in &chunk.merkle_paths {
+                        seq.serialize_element(path)?;
+                    }
+                }
+                seq.end()
+            }
+        }
+
+        // Must correspond to the `PrepareBasicCircuitsJob` layout.
+        let mut job = serializer.serialize_struct("PrepareBasicCircuitsJob", 2)?;
+        job.serialize_field("merkle_paths", &MerklePaths(self))?;
+        job.serialize_field(
+            "next_enumeration_index",
+            &self.header.next_enumeration_index,
+        )?;
+        job.end()
+    }
+}
+
+/// [`io::Write`] implementation sending written data to a channel in chunks. Blocks if the channel is full,
+/// i.e., if the client doesn't keep up with the data.
+#[derive(Debug)]
+struct ChannelWriter {
+    buffer: Vec<u8>,
+    sender: mpsc::Sender<io::Result<Vec<u8>>>,
+}
+
+impl ChannelWriter {
+    fn new(sender: mpsc::Sender<io::Result<Vec<u8>>>) -> Self {
+        Self {
+            buffer: Vec::with_capacity(STREAMED_CHUNK_SIZE),
+            sender,
+        }
+    }
+}
+
+impl io::Write for ChannelWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+        if self.buffer.len() >= STREAMED_CHUNK_SIZE {
+            self.flush()?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+        let chunk = mem::replace(&mut self.buffer, Vec::with_capacity(STREAMED_CHUNK_SIZE));
+        self.sender
+            .blocking_send(Ok(chunk))
+            .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "client has disconnected"))
+    }
+}
+
+/// Streams the JSON-serialized `ProofGenerationDataResponse::Success(Some(data))`. Serialization happens
+/// on a blocking thread, with at most [`MAX_BUFFERED_CHUNKS`] serialized chunks and a single chunk of the witness input
+/// held in memory at a time.
+fn stream_proof_generation_data(data: ProofGenerationData<ChunkedWitnessInput>) -> Response {
+    let (chunks_sender, mut chunks_receiver) = mpsc::channel(MAX_BUFFERED_CHUNKS);
+    tokio::task::spawn_blocking(move || {
+        let l1_batch_number = data.l1_batch_number;
+        let response = ProofGenerationDataResponse::Success(Some(data));
+        let mut writer = ChannelWriter::new(chunks_sender.clone());
+        let result = serde_json::to_writer(&mut writer, &response)
+            .map_err(io::Error::from)
+            .and_then(|()| io::Write::flush(&mut writer));
+        if let Err(err) = result {
+            tracing::warn!(
+                "Failed streaming proof generation data for L1 batch #{l1_batch_number}: {err}"
+            );
+            // Abort the response, so that the client doesn't mistake it for a complete one.
+            chunks_sender.blocking_send(Err(err)).ok();
+        }
+    });
+
+    let chunks = futures::stream::poll_fn(move |cx| chunks_receiver.poll_recv(cx));
+    (
+        [(header::CONTENT_TYPE, "application/json")],
+        StreamBody::new(chunks),
+    )
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use zksync_object_store::{Bucket, ObjectStoreFactory, StoredObject};
+    use zksync_types::{proofs::StorageLogMetadata, U256};
+
+    use super::*;
+
+    async fn prepare_streamed_data() -> (
+        ProofGenerationData<ChunkedWitnessInput>,
+        ProofGenerationDataResponse,
+    ) {
+        let mut job = PrepareBasicCircuitsJob::new(10);
+        for i in 0..1_000_u64 {
+            job.push_merkle_path(StorageLogMetadata {
+                root_hash: [1; 32],
+                is_write: i % 2 == 0,
+                first_write: false,
+                merkle_paths: vec![[i as u8; 32]; 256],
+                leaf_hashed_key: U256::from(i),
+                leaf_enumeration_index: i + 1,
+                value_written: [2; 32],
+                value_read: [3; 32],
+            });
+        }
+        let l1_batch_number = L1BatchNumber(5);
+        let blob_store = ObjectStoreFactory::mock().create_store().await;
+        blob_store
+            .put_chunked_witness_input(l1_batch_number, job.clone(), 64)
+            .await
+            .unwrap();
+        let header = blob_store.get(l1_batch_number).await.unwrap();
+
+        let streamed_data = ProofGenerationData {
+            l1_batch_number,
+            data: ChunkedWitnessInput {
+                blob_store,
+                l1_batch_number,
+                header,
+                runtime: Handle::current(),
+            },
+            fri_protocol_version_id: FriProtocolVersionId::latest(),
+            l1_verifier_config: L1VerifierConfig::default(),
+        };
+        let expected_response = ProofGenerationDataResponse::Success(Some(ProofGenerationData {
+            l1_batch_number,
+            data: job,
+            fri_protocol_version_id: FriProtocolVersionId::latest(),
+            l1_verifier_config: L1VerifierConfig::default(),
+        }));
+        (streamed_data, expected_response)
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn streamed_proof_generation_data_is_valid_json() {
+        let (data, expected_response) = prepare_streamed_data().await;
+        let response = stream_proof_generation_data(data);
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        assert!(body.len() > STREAMED_CHUNK_SIZE);
+
+        let streamed: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(streamed, serde_json::to_value(&expected_response).unwrap());
+        let streamed: ProofGenerationDataResponse = serde_json::from_slice(&body).unwrap();
+        let ProofGenerationDataResponse::Success(Some(streamed)) = streamed else {
+            panic!("Unexpected response: {streamed:?}");
+        };
+        assert_eq!(streamed.data.into_merkle_paths().len(), 1_000);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn streaming_is_aborted_on_missing_chunk() {
+        let (data, _) = prepare_streamed_data().await;
+        let missing_chunk_key = PrepareBasicCircuitsJobChunkKey {
+            l1_batch_number: data.l1_batch_number,
+            chunk_idx: 10,
+        };
+        data.data
+            .blob_store
+            .remove_raw(
+                Bucket::WitnessInput,
+                &PrepareBasicCircuitsJobChunk::encode_key(missing_chunk_key),
+            )
+            .await
+            .unwrap();
+
+        let response = stream_proof_generation_data(data);
+        let err = hyper::body::to_bytes(response.into_body())
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("chunk #10"), "{err}");
+    }
+}
@@ -32,6 +32,15 @@ enum HashMatchError {
         Using an earlier snapshot could help."
     )]
     EarliestHashMismatch(L1BatchNumber),
+    #[error(
+        "Unrecoverable error: reorg requires rolling back {depth} L1 batches (last correct L1 batch: #{last_correct_l1_batch}), \
+         which exceeds the configured maximum rollback depth {max_depth}. Manual intervention is required."
+    )]
+    TooDeepReorg {
+        last_correct_l1_batch: L1BatchNumber,
+        depth: u32,
+        max_depth: u32,
+    },
     #[error("Internal error")]
     Internal(#[from] anyhow::Error),
 }
@@ -104,8 +113,9 @@ impl UpdateCorrectBlock for () {
 /// and revert all batches after it, to keep being consistent with the main node.
 ///
 /// This is the only component that is expected to finish its execution
-/// in the even of re-org, since we have to restart the node after a rollback is performed,
-/// and is special-cased in the `zksync_external_node` crate.
+/// in the even of re-org, since node components have to be restarted after a rollback is performed,
+/// and is special-cased in the `zksync_external_node` crate. If the maximum rollback depth is set
+/// (see [`Self::with_max_rollback_depth()`]), reorgs requiring deeper rollbacks are reported as errors instead.
 #[derive(Debug)]
 pub struct ReorgDetector {
     client: Box<dyn MainNodeClient>,
@@ -113,6 +123,7 @@ pub struct ReorgDetector {
     pool: ConnectionPool,
     stop_receiver: watch::Receiver<bool>,
     sleep_interval: Duration,
+    max_rollback_depth: Option<u32>,
 }
 
 impl ReorgDetector {
@@ -128,9 +139,16 @@ impl ReorgDetector {
             pool,
             stop_receiver,
             sleep_interval: Self::DEFAULT_SLEEP_INTERVAL,
+            max_rollback_depth: None,
         }
     }
 
+    /// Sets the maximum number of L1 batches that can be rolled back as a result of a detected reorg.
+    pub fn with_max_rollback_depth(mut self, max_depth: u32) -> Self {
+        self.max_rollback_depth = Some(max_depth);
+        self
+    }
+
     /// Compares hashes of the given local miniblock and the same miniblock from main node.
     async fn miniblock_hashes_match(
         &self,
@@ -209,6 +227,24 @@ impl ReorgDetector {
         .map(L1BatchNumber)
     }
 
+    fn check_rollback_depth(
+        &self,
+        last_correct_l1_batch: L1BatchNumber,
+        sealed_l1_batch_number: L1BatchNumber,
+    ) -> Result<(), HashMatchError> {
+        let depth = sealed_l1_batch_number
+            .0
+            .saturating_sub(last_correct_l1_batch.0);
+        match self.max_rollback_depth {
+            Some(max_depth) if depth > max_depth => Err(HashMatchError::TooDeepReorg {
+                last_correct_l1_batch,
+                depth,
+                max_depth,
+            }),
+            _ => Ok(()),
+        }
+    }
+
     pub async fn run(mut self) -> anyhow::Result<Option<L1BatchNumber>> {
         loop {
             match self.run_inner().await {
@@ -290,6 +326,7 @@ impl ReorgDetector {
                 tracing::info!(
                     "Reorg localized: last correct L1 batch is #{last_correct_l1_batch}"
                 );
+                self.check_rollback_depth(last_correct_l1_batch, sealed_l1_batch_number)?;
                 return Ok(Some(last_correct_l1_batch));
             }
 
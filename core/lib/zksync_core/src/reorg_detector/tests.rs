@@ -179,6 +179,7 @@ async fn normal_reorg_function(snapshot_recovery: bool, with_transient_errors: b
         pool: pool.clone(),
         stop_receiver,
         sleep_interval: Duration::from_millis(10),
+        max_rollback_depth: None,
     };
     let detector_task = tokio::spawn(detector.run());
 
@@ -221,6 +222,7 @@ async fn detector_stops_on_fatal_rpc_error() {
         pool: pool.clone(),
         stop_receiver,
         sleep_interval: Duration::from_millis(10),
+        max_rollback_depth: None,
     };
     // Check that the detector stops when a fatal RPC error is encountered.
     detector.run().await.unwrap_err();
@@ -256,6 +258,7 @@ async fn reorg_is_detected_on_batch_hash_mismatch() {
         pool: pool.clone(),
         stop_receiver,
         sleep_interval: Duration::from_millis(10),
+        max_rollback_depth: None,
     };
     let detector_task = tokio::spawn(detector.run());
 
@@ -300,6 +303,7 @@ async fn reorg_is_detected_on_miniblock_hash_mismatch() {
         pool: pool.clone(),
         stop_receiver,
         sleep_interval: Duration::from_millis(10),
+        max_rollback_depth: None,
     };
     let detector_task = tokio::spawn(detector.run());
 
@@ -386,6 +390,7 @@ async fn reorg_is_detected_on_historic_batch_hash_mismatch(
         pool: pool.clone(),
         stop_receiver,
         sleep_interval: Duration::from_millis(10),
+        max_rollback_depth: None,
     };
     let detector_task = tokio::spawn(detector.run());
 
@@ -411,6 +416,60 @@ async fn reorg_is_detected_on_historic_batch_hash_mismatch(
     );
 }
 
+#[test_casing(2, [6, 7])]
+#[tokio::test]
+async fn reorg_rollback_depth_is_limited(max_rollback_depth: u32) {
+    const LAST_CORRECT_BATCH: u32 = 3;
+
+    let pool = ConnectionPool::test_pool().await;
+    let mut storage = pool.access_storage().await.unwrap();
+    storage
+        .protocol_versions_dal()
+        .save_protocol_version_with_tx(ProtocolVersion::default())
+        .await;
+    store_miniblock(&mut storage, 0, H256::zero()).await;
+    seal_l1_batch(&mut storage, 0, H256::zero()).await;
+
+    let mut client = MockMainNodeClient::default();
+    for number in 1_u32..=10 {
+        let remote_miniblock_hash = H256::from_low_u64_be(number.into());
+        let remote_l1_batch_hash = H256::repeat_byte(number as u8);
+        client
+            .miniblock_hash_responses
+            .insert(MiniblockNumber(number), remote_miniblock_hash);
+        client
+            .l1_batch_root_hash_responses
+            .insert(L1BatchNumber(number), remote_l1_batch_hash);
+
+        let (miniblock_hash, l1_batch_hash) = if number > LAST_CORRECT_BATCH {
+            (H256::zero(), H256::zero())
+        } else {
+            (remote_miniblock_hash, remote_l1_batch_hash)
+        };
+        store_miniblock(&mut storage, number, miniblock_hash).await;
+        seal_l1_batch(&mut storage, number, l1_batch_hash).await;
+    }
+
+    let (_stop_sender, stop_receiver) = watch::channel(false);
+    let detector = ReorgDetector {
+        client: Box::new(client),
+        block_updater: Box::new(()),
+        pool: pool.clone(),
+        stop_receiver,
+        sleep_interval: Duration::from_millis(10),
+        max_rollback_depth: Some(max_rollback_depth),
+    };
+    let result = detector.run().await;
+
+    // Rolling back to L1 batch #3 requires reverting 7 L1 batches.
+    if max_rollback_depth < 7 {
+        let err = result.unwrap_err().to_string();
+        assert!(err.contains("maximum rollback depth"), "{err}");
+    } else {
+        assert_eq!(result.unwrap(), Some(L1BatchNumber(LAST_CORRECT_BATCH)));
+    }
+}
+
 #[tokio::test]
 async fn stopping_reorg_detector_while_waiting_for_l1_batch() {
     let pool = ConnectionPool::test_pool().await;
@@ -425,6 +484,7 @@ async fn stopping_reorg_detector_while_waiting_for_l1_batch() {
         pool,
         stop_receiver,
         sleep_interval: Duration::from_millis(10),
+        max_rollback_depth: None,
     };
     let detector_task = tokio::spawn(detector.run());
 
@@ -457,6 +517,7 @@ async fn detector_errors_on_earliest_batch_hash_mismatch() {
         pool: pool.clone(),
         stop_receiver,
         sleep_interval: Duration::from_millis(10),
+        max_rollback_depth: None,
     };
 
     let err = detector.run_inner().await.unwrap_err();
@@ -478,6 +539,7 @@ async fn detector_errors_on_earliest_batch_hash_mismatch_with_snapshot_recovery(
         pool: pool.clone(),
         stop_receiver,
         sleep_interval: Duration::from_millis(10),
+        max_rollback_depth: None,
     };
 
     tokio::spawn(async move {
@@ -0,0 +1,403 @@
+//! Backups of RocksDB instances (the Merkle tree and the state keeper cache) in the object store.
+//!
+//! A backup is created from a RocksDB checkpoint; each checkpoint file is uploaded in chunks of bounded size,
+//! and the list of files is recorded in a manifest object. SST files are immutable, so they are shared
+//! among backups: only SST files not present in the previous backup are uploaded. Only the latest backup is retained.
+
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::Context as _;
+use serde::{Deserialize, Serialize};
+use tokio::{
+    fs,
+    io::{AsyncReadExt, AsyncWriteExt},
+    sync::watch,
+};
+use zksync_object_store::{
+    serialize_using_bincode, Bucket, ObjectStore, ObjectStoreError, StoredObject,
+};
+use zksync_storage::{db, rocksdb, RocksDB};
+
+#[cfg(test)]
+mod tests;
+
+/// Maximum size of a single object a backup file is split into.
+const DEFAULT_CHUNK_SIZE: u64 = 64 << 20; // 64 MiB
+
+/// Information about a single file in a backup.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct BackupFile {
+    name: String,
+    size: u64,
+    chunk_count: u64,
+    /// Identifier of the backup in which the file was uploaded. May differ from the backup identifier
+    /// for SST files shared with previous backups.
+    uploaded_in: u64,
+}
+
+impl BackupFile {
+    fn is_shared(&self) -> bool {
+        Path::new(&self.name)
+            .extension()
+            .map_or(false, |ext| ext == "sst")
+    }
+}
+
+/// Manifest of the latest backup for a RocksDB instance.
+#[derive(Debug, Serialize, Deserialize)]
+struct BackupManifest {
+    /// Backup identifier (UNIX timestamp in milliseconds when the backup checkpoint was created).
+    backup_id: u64,
+    /// Files in the backup.
+    files: Vec<BackupFile>,
+}
+
+impl StoredObject for BackupManifest {
+    const BUCKET: Bucket = Bucket::RocksDBBackups;
+    type Key<'a> = &'a str;
+
+    fn encode_key(db_name: Self::Key<'_>) -> String {
+        format!("{db_name}_latest_backup.bin")
+    }
+
+    serialize_using_bincode!();
+}
+
+/// Local RocksDB checkpoint that should be uploaded to the object store.
+#[derive(Debug)]
+pub(crate) struct LocalCheckpoint {
+    backup_id: u64,
+    path: PathBuf,
+}
+
+/// Creates, uploads and restores backups for a single RocksDB instance.
+#[derive(Debug, Clone)]
+pub struct RocksDBBackups {
+    object_store: Arc<dyn ObjectStore>,
+    db_name: &'static str,
+    db_path: PathBuf,
+    chunk_size: u64,
+}
+
+impl RocksDBBackups {
+    /// Creates backups for the RocksDB instance at `db_path`. `db_name` is used to distinguish backups
+    /// of different instances in the object store.
+    pub fn new(
+        object_store: Arc<dyn ObjectStore>,
+        db_name: &'static str,
+        db_path: impl Into<PathBuf>,
+    ) -> Self {
+        Self {
+            object_store,
+            db_name,
+            db_path: db_path.into(),
+            chunk_size: DEFAULT_CHUNK_SIZE,
+        }
+    }
+
+    fn sibling_path(&self, suffix: &str) -> PathBuf {
+        let mut path = self.db_path.clone().into_os_string();
+        path.push(suffix);
+        path.into()
+    }
+
+    fn chunk_key(&self, file: &BackupFile, chunk_idx: u64) -> String {
+        format!(
+            "{}_{}_{}_{chunk_idx}",
+            self.db_name, file.uploaded_in, file.name
+        )
+    }
+
+    /// Creates a local checkpoint using the provided closure. This is a blocking operation.
+    pub(crate) fn create_checkpoint(
+        &self,
+        create: impl FnOnce(&Path) -> Result<(), rocksdb::Error>,
+    ) -> anyhow::Result<LocalCheckpoint> {
+        let backup_id = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .context("invalid system time")?
+            .as_millis() as u64;
+        let path = self.sibling_path(&format!(".checkpoint-{backup_id}"));
+        create(&path).with_context(|| {
+            format!(
+                "failed creating checkpoint for RocksDB `{}` at `{}`",
+                self.db_name,
+                path.display()
+            )
+        })?;
+        Ok(LocalCheckpoint { backup_id, path })
+    }
+
+    /// Uploads a local checkpoint to the object store and removes it locally. SST files present in the previous
+    /// backup are not re-uploaded. Once the new backup is uploaded, files of the previous backup
+    /// not referenced by the new one are removed from the store.
+    pub(crate) async fn upload(&self, checkpoint: LocalCheckpoint) -> anyhow::Result<()> {
+        let LocalCheckpoint { backup_id, path } = checkpoint;
+        let prev_manifest = self.latest_manifest().await?;
+        let prev_shared_files: HashMap<_, _> = prev_manifest
+            .iter()
+            .flat_map(|manifest| &manifest.files)
+            .filter(|file| file.is_shared())
+            .map(|file| (file.name.as_str(), file))
+            .collect();
+
+        let mut files = vec![];
+        let mut uploaded_bytes = 0;
+        let mut entries = fs::read_dir(&path)
+            .await
+            .with_context(|| format!("failed reading checkpoint dir `{}`", path.display()))?;
+        while let Some(entry) = entries.next_entry().await? {
+            let name = entry
+                .file_name()
+                .into_string()
+                .map_err(|name| anyhow::anyhow!("non-UTF8 checkpoint file name: {name:?}"))?;
+            let size = entry
+                .metadata()
+                .await
+                .with_context(|| format!("failed getting metadata for checkpoint file `{name}`"))?
+                .len();
+            let mut file = BackupFile {
+                chunk_count: (size + self.chunk_size - 1) / self.chunk_size,
+                name,
+                size,
+                uploaded_in: backup_id,
+            };
+
+            // SST file names are never reused within a RocksDB instance; the size check guards against
+            // the instance being recreated from scratch.
+            let prev_file = prev_shared_files.get(file.name.as_str());
+            match prev_file {
+                Some(prev_file) if file.is_shared() && prev_file.size == file.size => {
+                    file.uploaded_in = prev_file.uploaded_in;
+                    file.chunk_count = prev_file.chunk_count;
+                }
+                _ => {
+                    self.upload_file(&entry.path(), &file).await?;
+                    uploaded_bytes += file.size;
+                }
+            }
+            files.push(file);
+        }
+
+        let manifest = BackupManifest { backup_id, files };
+        self.object_store
+            .put(self.db_name, &manifest)
+            .await
+            .context("failed uploading backup manifest")?;
+        tracing::info!(
+            "Uploaded backup #{backup_id} for RocksDB `{}` with {} files ({uploaded_bytes} bytes uploaded)",
+            self.db_name,
+            manifest.files.len()
+        );
+
+        if let Some(prev_manifest) = prev_manifest {
+            self.remove_obsolete_files(&prev_manifest, &manifest).await;
+        }
+        fs::remove_dir_all(&path)
+            .await
+            .with_context(|| format!("failed removing checkpoint dir `{}`", path.display()))
+    }
+
+    /// Uploads a file in chunks, so that the file is never loaded into memory in its entirety.
+    async fn upload_file(&self, path: &Path, file: &BackupFile) -> anyhow::Result<()> {
+        let mut reader = fs::File::open(path)
+            .await
+            .with_context(|| format!("failed opening checkpoint file `{}`", file.name))?;
+        for chunk_idx in 0..file.chunk_count {
+            let mut chunk = vec![];
+            (&mut reader)
+                .take(self.chunk_size)
+                .read_to_end(&mut chunk)
+                .await
+                .with_context(|| format!("failed reading checkpoint file `{}`", file.name))?;
+            let key = self.chunk_key(file, chunk_idx);
+            self.object_store
+                .put_raw(Bucket::RocksDBBackups, &key, chunk)
+                .await
+                .with_context(|| format!("failed uploading checkpoint file chunk `{key}`"))?;
+        }
+        Ok(())
+    }
+
+    async fn remove_obsolete_files(
+        &self,
+        prev_manifest: &BackupManifest,
+        manifest: &BackupManifest,
+    ) {
+        let retained_files: HashSet<_> = manifest
+            .files
+            .iter()
+            .map(|file| (file.uploaded_in, file.name.as_str()))
+            .collect();
+        let obsolete_files = prev_manifest
+            .files
+            .iter()
+            .filter(|file| !retained_files.contains(&(file.uploaded_in, file.name.as_str())));
+        for file in obsolete_files {
+            for chunk_idx in 0..file.chunk_count {
+                let key = self.chunk_key(file, chunk_idx);
+                if let Err(err) = self
+                    .object_store
+                    .remove_raw(Bucket::RocksDBBackups, &key)
+                    .await
+                {
+                    tracing::warn!("Failed removing obsolete backup file chunk `{key}`: {err}");
+                }
+            }
+        }
+    }
+
+    async fn latest_manifest(&self) -> anyhow::Result<Option<BackupManifest>> {
+        match self.object_store.get(self.db_name).await {
+            Ok(manifest) => Ok(Some(manifest)),
+            Err(ObjectStoreError::KeyNotFound(_)) => Ok(None),
+            Err(err) => Err(anyhow::Error::from(err).context("failed getting backup manifest")),
+        }
+    }
+
+    /// Restores the RocksDB instance from the latest backup if local data is missing or corrupted.
+    /// Corrupted data is moved aside rather than deleted.
+    ///
+    /// Returns `true` if the instance was restored.
+    pub async fn restore_if_needed(&self) -> anyhow::Result<bool> {
+        let db_path = self.db_path.clone();
+        let integrity_check = tokio::task::spawn_blocking(move || db::check_integrity(&db_path))
+            .await
+            .context("panicked checking RocksDB integrity")?;
+        let Err(err) = integrity_check else {
+            return Ok(false);
+        };
+        let Some(manifest) = self.latest_manifest().await? else {
+            tracing::info!(
+                "RocksDB `{}` at `{}` cannot be opened ({err}), but there are no backups to restore it from",
+                self.db_name,
+                self.db_path.display()
+            );
+            return Ok(false);
+        };
+
+        if fs::try_exists(&self.db_path).await? {
+            let corrupted_path = self.sibling_path(".corrupted");
+            tracing::warn!(
+                "RocksDB `{}` at `{}` is corrupted ({err}); moving it to `{}` and restoring from backup",
+                self.db_name,
+                self.db_path.display(),
+                corrupted_path.display()
+            );
+            if fs::try_exists(&corrupted_path).await? {
+                fs::remove_dir_all(&corrupted_path).await?;
+            }
+            fs::rename(&self.db_path, &corrupted_path)
+                .await
+                .context("failed moving corrupted RocksDB")?;
+        }
+
+        let restore_path = self.sibling_path(".restoring");
+        if fs::try_exists(&restore_path).await? {
+            fs::remove_dir_all(&restore_path).await?;
+        }
+        fs::create_dir_all(&restore_path).await?;
+        for file in &manifest.files {
+            self.download_file(&restore_path.join(&file.name), file)
+                .await?;
+        }
+        if let Some(parent) = self.db_path.parent() {
+            fs::create_dir_all(parent).await?;
+        }
+        fs::rename(&restore_path, &self.db_path)
+            .await
+            .context("failed moving restored RocksDB into place")?;
+
+        tracing::info!(
+            "Restored RocksDB `{}` at `{}` from backup #{}",
+            self.db_name,
+            self.db_path.display(),
+            manifest.backup_id
+        );
+        Ok(true)
+    }
+
+    /// Downloads a file chunk by chunk, so that the file is never loaded into memory in its entirety.
+    async fn download_file(&self, path: &Path, file: &BackupFile) -> anyhow::Result<()> {
+        let mut writer = fs::File::create(path)
+            .await
+            .with_context(|| format!("failed creating backup file `{}`", file.name))?;
+        for chunk_idx in 0..file.chunk_count {
+            let key = self.chunk_key(file, chunk_idx);
+            let chunk = self
+                .object_store
+                .get_raw(Bucket::RocksDBBackups, &key)
+                .await
+                .with_context(|| format!("failed downloading backup file chunk `{key}`"))?;
+            writer
+                .write_all(&chunk)
+                .await
+                .with_context(|| format!("failed writing backup file `{}`", file.name))?;
+        }
+        writer
+            .sync_all()
+            .await
+            .with_context(|| format!("failed syncing backup file `{}`", file.name))
+    }
+}
+
+/// Task periodically backing up a RocksDB instance to the object store.
+#[derive(Debug)]
+pub(crate) struct RocksDBBackupTask<CF> {
+    db: RocksDB<CF>,
+    backups: RocksDBBackups,
+    interval: Duration,
+}
+
+impl<CF: db::NamedColumnFamily + Send + Sync + 'static> RocksDBBackupTask<CF> {
+    pub fn new(db: RocksDB<CF>, backups: RocksDBBackups, interval: Duration) -> Self {
+        Self {
+            db,
+            backups,
+            interval,
+        }
+    }
+
+    async fn create_backup(&self) -> anyhow::Result<()> {
+        let db = self.db.clone();
+        let backups = self.backups.clone();
+        let checkpoint = tokio::task::spawn_blocking(move || {
+            backups.create_checkpoint(|path| db.create_checkpoint(path))
+        })
+        .await
+        .context("panicked creating RocksDB checkpoint")??;
+        self.backups.upload(checkpoint).await
+    }
+
+    pub async fn run(self, mut stop_receiver: watch::Receiver<bool>) -> anyhow::Result<()> {
+        loop {
+            if *stop_receiver.borrow() {
+                break;
+            }
+            if tokio::time::timeout(self.interval, stop_receiver.changed())
+                .await
+                .is_ok()
+            {
+                break;
+            }
+
+            // Backup errors are not fatal; the next backup may succeed.
+            if let Err(err) = self.create_backup().await {
+                tracing::warn!(
+                    "Failed backing up RocksDB `{}`: {err:#}",
+                    self.backups.db_name
+                );
+            }
+        }
+        tracing::info!(
+            "Stop signal received, RocksDB `{}` backups are shutting down",
+            self.backups.db_name
+        );
+        Ok(())
+    }
+}
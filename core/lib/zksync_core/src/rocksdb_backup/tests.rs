@@ -0,0 +1,157 @@
+//! Tests for RocksDB backups.
+
+use std::sync::Mutex;
+
+use async_trait::async_trait;
+use tempfile::TempDir;
+use zksync_merkle_tree::MerkleTreeColumnFamily;
+use zksync_object_store::ObjectStoreFactory;
+
+use super::*;
+
+/// Object store recording keys of uploaded objects.
+#[derive(Debug)]
+struct RecordingObjectStore {
+    inner: Arc<dyn ObjectStore>,
+    uploaded_keys: Mutex<Vec<String>>,
+}
+
+impl RecordingObjectStore {
+    async fn new() -> Arc<Self> {
+        Arc::new(Self {
+            inner: ObjectStoreFactory::mock().create_store().await,
+            uploaded_keys: Mutex::default(),
+        })
+    }
+
+    fn take_uploaded_keys(&self) -> Vec<String> {
+        std::mem::take(&mut *self.uploaded_keys.lock().unwrap())
+    }
+}
+
+#[async_trait]
+impl ObjectStore for RecordingObjectStore {
+    async fn get_raw(&self, bucket: Bucket, key: &str) -> Result<Vec<u8>, ObjectStoreError> {
+        self.inner.get_raw(bucket, key).await
+    }
+
+    async fn put_raw(
+        &self,
+        bucket: Bucket,
+        key: &str,
+        value: Vec<u8>,
+    ) -> Result<(), ObjectStoreError> {
+        self.uploaded_keys.lock().unwrap().push(key.to_owned());
+        self.inner.put_raw(bucket, key, value).await
+    }
+
+    async fn remove_raw(&self, bucket: Bucket, key: &str) -> Result<(), ObjectStoreError> {
+        self.inner.remove_raw(bucket, key).await
+    }
+
+    fn storage_prefix_raw(&self, bucket: Bucket) -> String {
+        self.inner.storage_prefix_raw(bucket)
+    }
+}
+
+fn write_value(db: &RocksDB<MerkleTreeColumnFamily>, key: &[u8], value: &[u8]) {
+    let mut batch = db.new_write_batch();
+    batch.put_cf(MerkleTreeColumnFamily::Tree, key, value);
+    db.write(batch).unwrap();
+}
+
+#[tokio::test]
+async fn backup_and_restore_workflow() {
+    let temp_dir = TempDir::new().expect("failed get temporary directory for RocksDB");
+    let db_path = temp_dir.path().join("db");
+    let object_store = ObjectStoreFactory::mock().create_store().await;
+    let backups = RocksDBBackups::new(object_store, "test", &db_path);
+    // There are no backups yet.
+    assert!(!backups.restore_if_needed().await.unwrap());
+
+    let db = RocksDB::<MerkleTreeColumnFamily>::new(&db_path).with_sync_writes();
+    write_value(&db, b"test", b"value");
+    let task = RocksDBBackupTask::new(db.clone(), backups.clone(), Duration::from_secs(60));
+    task.create_backup().await.unwrap();
+    tokio::time::sleep(Duration::from_millis(5)).await;
+    write_value(&db, b"other", b"other_value");
+    task.create_backup().await.unwrap();
+    drop((task, db));
+
+    // Emulate losing local data.
+    std::fs::remove_dir_all(&db_path).unwrap();
+    assert!(backups.restore_if_needed().await.unwrap());
+    let db = RocksDB::<MerkleTreeColumnFamily>::new(&db_path);
+    for (key, value) in [(&b"test"[..], &b"value"[..]), (b"other", b"other_value")] {
+        let restored_value = db.get_cf(MerkleTreeColumnFamily::Tree, key).unwrap();
+        assert_eq!(restored_value.unwrap(), value);
+    }
+    drop(db);
+    // Intact data should not be restored.
+    assert!(!backups.restore_if_needed().await.unwrap());
+
+    // Emulate data corruption.
+    std::fs::write(db_path.join("CURRENT"), "garbage").unwrap();
+    assert!(backups.restore_if_needed().await.unwrap());
+    assert!(temp_dir.path().join("db.corrupted").exists());
+    let db = RocksDB::<MerkleTreeColumnFamily>::new(&db_path);
+    let restored_value = db.get_cf(MerkleTreeColumnFamily::Tree, b"test").unwrap();
+    assert_eq!(restored_value.unwrap(), b"value");
+}
+
+#[tokio::test]
+async fn backups_are_incremental() {
+    let temp_dir = TempDir::new().expect("failed get temporary directory for RocksDB");
+    let db_path = temp_dir.path().join("db");
+    let object_store = RecordingObjectStore::new().await;
+    let mut backups = RocksDBBackups::new(object_store.clone(), "test", &db_path);
+    // Use small chunks so that files are split into multiple objects.
+    backups.chunk_size = 256;
+
+    let db = RocksDB::<MerkleTreeColumnFamily>::new(&db_path).with_sync_writes();
+    write_value(&db, b"test", &[1; 1_000]);
+    // Creating a checkpoint flushes memtables, so each backup will contain a new SST file.
+    let task = RocksDBBackupTask::new(db.clone(), backups.clone(), Duration::from_secs(60));
+    task.create_backup().await.unwrap();
+    let manifest = backups.latest_manifest().await.unwrap().unwrap();
+    let first_ssts: Vec<_> = manifest
+        .files
+        .iter()
+        .filter(|file| file.is_shared())
+        .cloned()
+        .collect();
+    assert!(!first_ssts.is_empty());
+    assert!(first_ssts.iter().any(|file| file.chunk_count > 1));
+    object_store.take_uploaded_keys();
+
+    tokio::time::sleep(Duration::from_millis(5)).await;
+    write_value(&db, b"other", &[2; 1_000]);
+    task.create_backup().await.unwrap();
+    drop((task, db));
+
+    // SST files from the first backup must be shared rather than re-uploaded.
+    let uploaded_keys = object_store.take_uploaded_keys();
+    for file in &first_ssts {
+        let key_prefix = format!("test_{}_{}_", file.uploaded_in, file.name);
+        assert!(
+            !uploaded_keys.iter().any(|key| key.starts_with(&key_prefix)),
+            "{file:?} was re-uploaded: {uploaded_keys:?}"
+        );
+    }
+    let manifest = backups.latest_manifest().await.unwrap().unwrap();
+    for file in &first_ssts {
+        assert!(manifest.files.contains(file), "{file:?}");
+    }
+    assert!(manifest
+        .files
+        .iter()
+        .any(|file| file.is_shared() && file.uploaded_in == manifest.backup_id));
+
+    std::fs::remove_dir_all(&db_path).unwrap();
+    assert!(backups.restore_if_needed().await.unwrap());
+    let db = RocksDB::<MerkleTreeColumnFamily>::new(&db_path);
+    for (key, value) in [(&b"test"[..], [1; 1_000]), (b"other", [2; 1_000])] {
+        let restored_value = db.get_cf(MerkleTreeColumnFamily::Tree, key).unwrap();
+        assert_eq!(restored_value.unwrap(), value);
+    }
+}
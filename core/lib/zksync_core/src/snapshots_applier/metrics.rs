@@ -0,0 +1,23 @@
+//! Metrics for snapshot recovery.
+
+use std::time::Duration;
+
+use vise::{Buckets, Gauge, Histogram, Metrics, Unit};
+
+#[derive(Debug, Metrics)]
+#[metrics(prefix = "snapshots_applier")]
+pub(super) struct SnapshotsApplierMetrics {
+    /// Total number of storage logs chunks in the snapshot chain being recovered.
+    pub storage_logs_chunks_count: Gauge<u64>,
+    /// Number of storage logs chunks left to apply.
+    pub storage_logs_chunks_left_to_process: Gauge<u64>,
+    /// Latency of applying a single storage logs chunk, including loading it from the object store.
+    #[metrics(buckets = Buckets::LATENCIES, unit = Unit::Seconds)]
+    pub storage_logs_chunk_duration: Histogram<Duration>,
+    /// Estimated time until snapshot recovery is completed.
+    #[metrics(unit = Unit::Seconds)]
+    pub eta: Gauge<Duration>,
+}
+
+#[vise::register]
+pub(super) static METRICS: vise::Global<SnapshotsApplierMetrics> = vise::Global::new();
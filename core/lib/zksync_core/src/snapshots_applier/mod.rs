@@ -0,0 +1,823 @@
+//! Recovery of the node state from a snapshot produced by the main node.
+//!
+//! Snapshot recovery is performed instead of genesis for an empty node. It loads the snapshot header
+//! from the main node, persists the last L1 batch and miniblock of the snapshot together with factory
+//! dependencies, and then loads storage log chunks from the object store one by one. Each chunk is applied
+//! in a separate Postgres transaction together with a record in the `snapshot_recovery_chunks` table, so recovery
+//! can be resumed after a restart by applying only the non-applied chunks. Recovery progress (including the estimated
+//! time left) is reported via metrics and the health check.
+//!
+//! A snapshot may be a delta snapshot containing only storage changes since its base snapshot. In this case,
+//! the chain of snapshots ending with a full snapshot is fetched, and storage log chunks of all snapshots
+//! in the chain are applied in order, starting from the full snapshot. Chunks are numbered sequentially
+//! across the chain in the recovery status.
+//!
+//! If a snapshot has an integrity manifest, the manifest is checked against the snapshot header (and, optionally,
+//! its signer), and each storage logs chunk is checked against its hash in the manifest before being applied.
+//! Additionally, the snapshot L1 batch can be checked against the batch committed on L1.
+//!
+//! The Merkle tree is not recovered here; it's recovered from Postgres by the Metadata calculator,
+//! which also checks that the resulting root hash matches the one in the snapshot.
+
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    time::{Duration, Instant},
+};
+
+use anyhow::Context as _;
+use async_trait::async_trait;
+use serde::Serialize;
+use zksync_contracts::zksync_contract;
+use zksync_dal::{snapshot_recovery_dal::AppliedSnapshotChunk, ConnectionPool, StorageProcessor};
+use zksync_eth_client::{clients::QueryClient, CallFunctionArgs, EthInterface};
+use zksync_health_check::{Health, HealthStatus, HealthUpdater, ReactiveHealthCheck};
+use zksync_object_store::ObjectStore;
+use zksync_types::{
+    api::{self, en::SyncBlock},
+    block::{BlockGasCount, MiniblockHeader},
+    ethabi::{self, Contract},
+    fee_model::BatchFeeInput,
+    snapshots::{
+        SnapshotFactoryDependencies, SnapshotHeader, SnapshotManifest, SnapshotRecoveryStatus,
+        SnapshotStorageLogsChunk, SnapshotStorageLogsStorageKey,
+    },
+    web3::{contract::tokens::Detokenize, signing::keccak256},
+    Address, L1BatchNumber, MiniblockNumber, ProtocolVersionId, H256, U256,
+};
+use zksync_utils::bytecode::hash_bytecode;
+use zksync_web3_decl::{
+    jsonrpsee::http_client::HttpClient,
+    namespaces::{EnNamespaceClient, SnapshotsNamespaceClient, ZksNamespaceClient},
+};
+
+use self::metrics::METRICS;
+
+mod metrics;
+#[cfg(test)]
+mod tests;
+
+/// Main node API used by [`SnapshotsApplier`].
+#[async_trait]
+pub trait SnapshotsApplierMainNodeClient: fmt::Debug + Send + Sync {
+    async fn fetch_newest_snapshot(&self) -> anyhow::Result<Option<SnapshotHeader>>;
+
+    async fn fetch_snapshot(
+        &self,
+        l1_batch_number: L1BatchNumber,
+    ) -> anyhow::Result<Option<SnapshotHeader>>;
+
+    async fn fetch_l2_block(&self, number: MiniblockNumber) -> anyhow::Result<Option<SyncBlock>>;
+
+    async fn fetch_protocol_version(
+        &self,
+        id: ProtocolVersionId,
+    ) -> anyhow::Result<Option<api::ProtocolVersion>>;
+}
+
+#[async_trait]
+impl SnapshotsApplierMainNodeClient for HttpClient {
+    async fn fetch_newest_snapshot(&self) -> anyhow::Result<Option<SnapshotHeader>> {
+        let snapshots = self.get_all_snapshots().await?;
+        let Some(&newest_l1_batch_number) = snapshots.snapshots_l1_batch_numbers.first() else {
+            return Ok(None);
+        };
+        self.fetch_snapshot(newest_l1_batch_number).await
+    }
+
+    async fn fetch_snapshot(
+        &self,
+        l1_batch_number: L1BatchNumber,
+    ) -> anyhow::Result<Option<SnapshotHeader>> {
+        Ok(self
+            .get_snapshot_by_l1_batch_number(l1_batch_number)
+            .await?)
+    }
+
+    async fn fetch_l2_block(&self, number: MiniblockNumber) -> anyhow::Result<Option<SyncBlock>> {
+        Ok(self.sync_l2_block(number, false).await?)
+    }
+
+    async fn fetch_protocol_version(
+        &self,
+        id: ProtocolVersionId,
+    ) -> anyhow::Result<Option<api::ProtocolVersion>> {
+        Ok(self.get_protocol_version(Some(id as u16)).await?)
+    }
+}
+
+/// L1 API used by [`SnapshotsApplier`] to check the snapshot L1 batch against the batch committed on L1.
+#[async_trait]
+pub trait SnapshotsApplierL1Client: fmt::Debug + Send + Sync {
+    /// Returns the hash of the `StoredBatchInfo` for the specified L1 batch recorded by the diamond proxy,
+    /// or `None` if the batch is not committed.
+    async fn fetch_stored_batch_hash(
+        &self,
+        l1_batch_number: L1BatchNumber,
+    ) -> anyhow::Result<Option<H256>>;
+}
+
+/// [`SnapshotsApplierL1Client`] querying the diamond proxy contract on L1.
+#[derive(Debug)]
+pub struct DiamondProxyL1Client {
+    client: QueryClient,
+    diamond_proxy_addr: Address,
+    contract: Contract,
+}
+
+impl DiamondProxyL1Client {
+    pub fn new(l1_url: &str, diamond_proxy_addr: Address) -> anyhow::Result<Self> {
+        Ok(Self {
+            client: QueryClient::new(l1_url).context("failed creating L1 client")?,
+            diamond_proxy_addr,
+            contract: zksync_contract(),
+        })
+    }
+}
+
+#[async_trait]
+impl SnapshotsApplierL1Client for DiamondProxyL1Client {
+    async fn fetch_stored_batch_hash(
+        &self,
+        l1_batch_number: L1BatchNumber,
+    ) -> anyhow::Result<Option<H256>> {
+        let args = CallFunctionArgs::new("storedBatchHash", U256::from(l1_batch_number.0))
+            .for_contract(self.diamond_proxy_addr, self.contract.clone());
+        let output = self
+            .client
+            .call_contract_function(args)
+            .await
+            .context("storedBatchHash()")?;
+        let hash = H256::from_tokens(output).context("failed decoding stored batch hash")?;
+        // The contract returns zero hash for batches that are not committed.
+        Ok(Some(hash).filter(|hash| !hash.is_zero()))
+    }
+}
+
+/// Applies a snapshot produced by the main node to an empty Postgres database.
+#[derive(Debug)]
+pub struct SnapshotsApplier<'a> {
+    pool: &'a ConnectionPool,
+    main_node_client: &'a dyn SnapshotsApplierMainNodeClient,
+    blob_store: &'a dyn ObjectStore,
+    l1_client: Option<&'a dyn SnapshotsApplierL1Client>,
+    manifest_signer: Option<Address>,
+    health_updater: HealthUpdater,
+}
+
+impl<'a> SnapshotsApplier<'a> {
+    pub fn new(
+        pool: &'a ConnectionPool,
+        main_node_client: &'a dyn SnapshotsApplierMainNodeClient,
+        blob_store: &'a dyn ObjectStore,
+    ) -> Self {
+        let (_, health_updater) = ReactiveHealthCheck::new("snapshot_recovery");
+        Self {
+            pool,
+            main_node_client,
+            blob_store,
+            l1_client: None,
+            manifest_signer: None,
+            health_updater,
+        }
+    }
+
+    /// Returns a health check reporting recovery progress.
+    pub fn health_check(&self) -> ReactiveHealthCheck {
+        self.health_updater.subscribe()
+    }
+
+    /// Enables checking the snapshot L1 batch against the batch committed on L1.
+    pub fn with_l1_client(mut self, l1_client: &'a dyn SnapshotsApplierL1Client) -> Self {
+        self.l1_client = Some(l1_client);
+        self
+    }
+
+    /// Requires manifests of all applied snapshots to be signed by the specified address.
+    pub fn with_manifest_signer(mut self, signer: Address) -> Self {
+        self.manifest_signer = Some(signer);
+        self
+    }
+
+    /// Recovers Postgres from the newest snapshot on the main node, or resumes recovery if it was interrupted.
+    /// Does nothing if recovery is already completed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if Postgres is not empty and doesn't contain recovery information, if the main node
+    /// has no snapshots, or if the snapshot data is inconsistent.
+    pub async fn run(self) -> anyhow::Result<()> {
+        let mut storage = self.pool.access_storage_tagged("snapshots_applier").await?;
+        let applied_status = storage
+            .snapshot_recovery_dal()
+            .get_applied_snapshot_status()
+            .await?;
+
+        let (mut status, chain) = if let Some(status) = applied_status {
+            if is_recovery_completed(&status) {
+                tracing::info!("Snapshot recovery is already completed: {status:?}");
+                self.health_updater.update(HealthStatus::Ready.into());
+                return Ok(());
+            }
+            tracing::info!("Resuming snapshot recovery with status: {status:?}");
+            let header = self
+                .main_node_client
+                .fetch_snapshot(status.l1_batch_number)
+                .await?
+                .with_context(|| {
+                    format!(
+                        "snapshot for L1 batch #{} being recovered is not present on the main node",
+                        status.l1_batch_number
+                    )
+                })?;
+            let chain = self.fetch_snapshot_chain(header).await?;
+            self.verify_chain(&chain).await?;
+            (status, chain)
+        } else {
+            anyhow::ensure!(
+                storage.blocks_dal().is_genesis_needed().await?,
+                "Postgres is not empty, but doesn't contain snapshot recovery information"
+            );
+            let header = self
+                .main_node_client
+                .fetch_newest_snapshot()
+                .await?
+                .context("main node doesn't have any snapshots")?;
+            tracing::info!(
+                "Starting snapshot recovery from L1 batch #{}, miniblock #{}",
+                header.l1_batch_number,
+                header.miniblock_number
+            );
+            let chain = self.fetch_snapshot_chain(header).await?;
+            self.verify_chain(&chain).await?;
+            let status = self.initialize(&mut storage, &chain).await?;
+            (status, chain)
+        };
+        let total_chunk_count: usize = chain
+            .iter()
+            .map(|header| header.storage_logs_chunks.len())
+            .sum();
+        anyhow::ensure!(
+            status.total_chunk_count == total_chunk_count as u64,
+            "mismatch between chunk count in recovery status {status:?} and snapshot headers"
+        );
+
+        let applied_chunk_ids: HashSet<_> = storage
+            .snapshot_recovery_dal()
+            .get_applied_chunks()
+            .await?
+            .into_iter()
+            .map(|chunk| chunk.chunk_id)
+            .collect();
+        // Recovery may have been started by an applier not recording applied chunks; it applied chunks sequentially
+        // up to `last_finished_chunk_id`.
+        let legacy_last_finished_chunk_id = status.last_finished_chunk_id;
+        let is_applied = |chain_chunk_id: u64| {
+            applied_chunk_ids.contains(&chain_chunk_id)
+                || legacy_last_finished_chunk_id.map_or(false, |id| chain_chunk_id <= id)
+        };
+        let mut progress = RecoveryProgress {
+            l1_batch_number: status.l1_batch_number,
+            total_chunk_count: status.total_chunk_count,
+            applied_chunk_count: (0..status.total_chunk_count)
+                .filter(|&id| is_applied(id))
+                .count() as u64,
+            started_at: Instant::now(),
+            chunks_applied_since_start: 0,
+        };
+        tracing::info!(
+            "{} / {} storage logs chunks are already applied",
+            progress.applied_chunk_count,
+            progress.total_chunk_count
+        );
+        self.report_progress(&progress);
+
+        let mut chain_chunk_id = 0;
+        for (i, header) in chain.iter().enumerate() {
+            let base_l1_batch_number = i.checked_sub(1).map(|j| chain[j].l1_batch_number);
+            for chunk_id in 0..header.storage_logs_chunks.len() as u64 {
+                if !is_applied(chain_chunk_id) {
+                    let chunk = ChainChunk {
+                        header,
+                        base_l1_batch_number,
+                        chunk_id,
+                        chain_chunk_id,
+                    };
+                    let latency = METRICS.storage_logs_chunk_duration.start();
+                    self.apply_storage_logs_chunk(&mut storage, &chain, chunk, &mut status)
+                        .await?;
+                    latency.observe();
+                    progress.applied_chunk_count += 1;
+                    progress.chunks_applied_since_start += 1;
+                    self.report_progress(&progress);
+                }
+                chain_chunk_id += 1;
+            }
+        }
+        tracing::info!("Snapshot recovery is completed: {status:?}");
+        Ok(())
+    }
+
+    fn report_progress(&self, progress: &RecoveryProgress) {
+        let eta = progress.eta();
+        METRICS
+            .storage_logs_chunks_count
+            .set(progress.total_chunk_count);
+        METRICS
+            .storage_logs_chunks_left_to_process
+            .set(progress.total_chunk_count - progress.applied_chunk_count);
+        METRICS.eta.set(eta.unwrap_or_default());
+        self.health_updater.update(progress.health(eta));
+    }
+
+    /// Fetches headers of all snapshots needed to recover from the snapshot with the specified `header`.
+    /// The returned chain starts with a full snapshot and ends with `header`.
+    async fn fetch_snapshot_chain(
+        &self,
+        header: SnapshotHeader,
+    ) -> anyhow::Result<Vec<SnapshotHeader>> {
+        validate_header(&header)?;
+        let mut chain = vec![header];
+        loop {
+            let header = chain.last().unwrap();
+            let Some(base_l1_batch_number) = header.base_l1_batch_number else {
+                break;
+            };
+            anyhow::ensure!(
+                base_l1_batch_number < header.l1_batch_number,
+                "base snapshot L1 batch #{base_l1_batch_number} for snapshot at L1 batch #{} \
+                 is not older than the snapshot",
+                header.l1_batch_number
+            );
+            let base_header = self
+                .main_node_client
+                .fetch_snapshot(base_l1_batch_number)
+                .await?
+                .with_context(|| {
+                    format!(
+                        "base snapshot for L1 batch #{base_l1_batch_number} is not present on the main node"
+                    )
+                })?;
+            validate_header(&base_header)?;
+            chain.push(base_header);
+        }
+        chain.reverse();
+        Ok(chain)
+    }
+
+    /// Verifies manifests of all snapshots in the chain and, if an L1 client is configured, checks that
+    /// the newest snapshot L1 batch is committed on L1.
+    async fn verify_chain(&self, chain: &[SnapshotHeader]) -> anyhow::Result<()> {
+        let mut prev_manifest: Option<&SnapshotManifest> = None;
+        for (i, header) in chain.iter().enumerate() {
+            let Some(manifest) = &header.manifest else {
+                anyhow::ensure!(
+                    self.manifest_signer.is_none(),
+                    "snapshot at L1 batch #{} has no manifest, while manifests are required to be signed",
+                    header.l1_batch_number
+                );
+                tracing::warn!(
+                    "Snapshot at L1 batch #{} has no manifest; its storage logs chunks won't be verified",
+                    header.l1_batch_number
+                );
+                prev_manifest = None;
+                continue;
+            };
+            validate_manifest(header, manifest)?;
+            let expected_base_manifest_hash = if i == 0 {
+                None
+            } else {
+                // The base manifest cannot be checked if the base snapshot has no manifest.
+                prev_manifest.map(SnapshotManifest::hash)
+            };
+            if i == 0 || expected_base_manifest_hash.is_some() {
+                anyhow::ensure!(
+                    manifest.base_manifest_hash == expected_base_manifest_hash,
+                    "base manifest hash {:?} for snapshot at L1 batch #{} doesn't match the expected hash {:?}",
+                    manifest.base_manifest_hash,
+                    header.l1_batch_number,
+                    expected_base_manifest_hash
+                );
+            }
+
+            if let Some(expected_signer) = self.manifest_signer {
+                let signer = manifest.recover_signer().with_context(|| {
+                    format!(
+                        "failed recovering signer of manifest for snapshot at L1 batch #{}",
+                        header.l1_batch_number
+                    )
+                })?;
+                anyhow::ensure!(
+                    signer == Some(expected_signer),
+                    "manifest for snapshot at L1 batch #{} is signed by {signer:?}, expected {expected_signer:?}",
+                    header.l1_batch_number
+                );
+            }
+            prev_manifest = Some(manifest);
+        }
+
+        let Some(l1_client) = self.l1_client else {
+            return Ok(());
+        };
+        let header = chain.last().context("empty snapshot chain")?;
+        let l1_batch_number = header.l1_batch_number;
+        let stored_batch_hash = l1_client
+            .fetch_stored_batch_hash(l1_batch_number)
+            .await?
+            .with_context(|| {
+                format!("snapshot L1 batch #{l1_batch_number} is not committed on L1")
+            })?;
+        let l1_header_data = header.last_l1_batch_with_metadata.l1_header_data();
+        let expected_batch_hash = H256(keccak256(&ethabi::encode(&[l1_header_data])));
+        anyhow::ensure!(
+            stored_batch_hash == expected_batch_hash,
+            "snapshot L1 batch #{l1_batch_number} doesn't match the batch committed on L1: \
+             expected stored batch hash {expected_batch_hash:?}, got {stored_batch_hash:?}"
+        );
+        tracing::info!("Checked snapshot L1 batch #{l1_batch_number} against L1");
+        Ok(())
+    }
+
+    /// Persists snapshot data not contained in storage log chunks: protocol version, last L1 batch and miniblock
+    /// of the snapshot, and factory dependencies from all snapshots in the chain.
+    async fn initialize(
+        &self,
+        storage: &mut StorageProcessor<'_>,
+        chain: &[SnapshotHeader],
+    ) -> anyhow::Result<SnapshotRecoveryStatus> {
+        let header = chain.last().context("empty snapshot chain")?;
+        let l1_batch = &header.last_l1_batch_with_metadata;
+        let miniblock = self
+            .main_node_client
+            .fetch_l2_block(header.miniblock_number)
+            .await?
+            .with_context(|| {
+                format!(
+                    "snapshot miniblock #{} is not present on the main node",
+                    header.miniblock_number
+                )
+            })?;
+        anyhow::ensure!(
+            miniblock.l1_batch_number == header.l1_batch_number && miniblock.last_in_batch,
+            "snapshot miniblock #{} is not the last miniblock in L1 batch #{}",
+            header.miniblock_number,
+            header.l1_batch_number
+        );
+        let miniblock_hash = miniblock
+            .hash
+            .context("main node didn't return snapshot miniblock hash")?;
+        let protocol_version = self
+            .main_node_client
+            .fetch_protocol_version(miniblock.protocol_version)
+            .await?
+            .with_context(|| {
+                format!(
+                    "protocol version {:?} is not present on the main node",
+                    miniblock.protocol_version
+                )
+            })?;
+
+        let mut factory_deps = HashMap::new();
+        for header in chain {
+            let snapshot_deps = self
+                .blob_store
+                .get::<SnapshotFactoryDependencies>(header.l1_batch_number)
+                .await
+                .with_context(|| {
+                    format!(
+                        "failed loading factory dependencies for snapshot at L1 batch #{} from object store",
+                        header.l1_batch_number
+                    )
+                })?;
+            factory_deps.extend(
+                snapshot_deps
+                    .factory_deps
+                    .into_iter()
+                    .map(|dep| (hash_bytecode(&dep.bytecode.0), dep.bytecode.0)),
+            );
+        }
+        let total_chunk_count: usize = chain
+            .iter()
+            .map(|header| header.storage_logs_chunks.len())
+            .sum();
+
+        let status = SnapshotRecoveryStatus {
+            l1_batch_number: header.l1_batch_number,
+            l1_batch_root_hash: l1_batch.metadata.root_hash,
+            miniblock_number: header.miniblock_number,
+            miniblock_root_hash: miniblock_hash,
+            last_finished_chunk_id: None,
+            total_chunk_count: total_chunk_count as u64,
+        };
+
+        let mut transaction = storage.start_transaction().await?;
+        transaction
+            .protocol_versions_dal()
+            .save_protocol_version(
+                miniblock.protocol_version,
+                protocol_version.timestamp,
+                protocol_version.verification_keys_hashes,
+                protocol_version.base_system_contracts,
+                // Verifier is not used in the external node, so we can pass an empty
+                Default::default(),
+                protocol_version.l2_system_upgrade_tx_hash,
+            )
+            .await;
+        transaction
+            .blocks_dal()
+            .insert_l1_batch(&l1_batch.header, &[], BlockGasCount::default(), &[], &[], 0)
+            .await?;
+        // The root hash of the previous L1 batch is unknown; it's not used by the node anyway.
+        let is_pre_boojum = l1_batch
+            .header
+            .protocol_version
+            .map_or(false, |version| version.is_pre_boojum());
+        transaction
+            .blocks_dal()
+            .save_l1_batch_metadata(
+                header.l1_batch_number,
+                &l1_batch.metadata,
+                H256::zero(),
+                is_pre_boojum,
+            )
+            .await?;
+        transaction
+            .blocks_dal()
+            .insert_miniblock(&MiniblockHeader {
+                number: miniblock.number,
+                timestamp: miniblock.timestamp,
+                hash: miniblock_hash,
+                l1_tx_count: 0,
+                l2_tx_count: 0,
+                base_fee_per_gas: l1_batch.header.base_fee_per_gas,
+                batch_fee_input: BatchFeeInput::l1_pegged(
+                    miniblock.l1_gas_price,
+                    miniblock.l2_fair_gas_price,
+                ),
+                base_system_contracts_hashes: miniblock.base_system_contracts_hashes,
+                protocol_version: Some(miniblock.protocol_version),
+                virtual_blocks: miniblock.virtual_blocks.unwrap_or(0),
+            })
+            .await?;
+        transaction
+            .blocks_dal()
+            .mark_miniblocks_as_executed_in_l1_batch(header.l1_batch_number)
+            .await?;
+        transaction
+            .storage_dal()
+            .insert_factory_deps(header.miniblock_number, &factory_deps)
+            .await;
+        transaction
+            .snapshot_recovery_dal()
+            .set_applied_snapshot_status(&status)
+            .await?;
+        transaction.commit().await?;
+
+        tracing::info!(
+            "Initialized snapshot recovery from {} snapshot(s) with {} factory deps; status: {status:?}",
+            chain.len(),
+            factory_deps.len()
+        );
+        Ok(status)
+    }
+
+    async fn apply_storage_logs_chunk(
+        &self,
+        storage: &mut StorageProcessor<'_>,
+        chain: &[SnapshotHeader],
+        chunk: ChainChunk<'_>,
+        status: &mut SnapshotRecoveryStatus,
+    ) -> anyhow::Result<()> {
+        let ChainChunk {
+            header,
+            base_l1_batch_number,
+            chunk_id,
+            chain_chunk_id,
+        } = chunk;
+        // Logs are always inserted for the last miniblock of the newest snapshot in the chain.
+        let miniblock_number = chain.last().unwrap().miniblock_number;
+        let storage_key = SnapshotStorageLogsStorageKey {
+            l1_batch_number: header.l1_batch_number,
+            chunk_id,
+        };
+        let chunk = self
+            .blob_store
+            .get::<SnapshotStorageLogsChunk>(storage_key)
+            .await
+            .with_context(|| {
+                format!(
+                    "failed loading storage logs chunk {chunk_id} for snapshot at L1 batch #{} from object store",
+                    header.l1_batch_number
+                )
+            })?;
+        validate_storage_logs_chunk(header, chunk_id, &chunk).with_context(|| {
+            format!(
+                "storage logs chunk {chunk_id} for snapshot at L1 batch #{} is invalid",
+                header.l1_batch_number
+            )
+        })?;
+
+        let storage_logs_count = chunk.storage_logs.len() as u64;
+        let mut transaction = storage.start_transaction().await?;
+        let new_logs: Vec<_>;
+        let initial_writes = if let Some(base_l1_batch_number) = base_l1_batch_number {
+            // Values of keys changed in a delta snapshot overwrite the values from the base snapshots.
+            // Keys initially written before the base snapshot are already recovered from it.
+            let hashed_keys: Vec<_> = chunk
+                .storage_logs
+                .iter()
+                .map(|log| log.key.hashed_key())
+                .collect();
+            transaction
+                .storage_logs_dal()
+                .delete_storage_logs_from_snapshot(miniblock_number, &hashed_keys)
+                .await?;
+            new_logs = chunk
+                .storage_logs
+                .iter()
+                .filter(|log| log.l1_batch_number_of_initial_write > base_l1_batch_number)
+                .cloned()
+                .collect();
+            &new_logs
+        } else {
+            &chunk.storage_logs
+        };
+        transaction
+            .storage_logs_dal()
+            .insert_storage_logs_from_snapshot(miniblock_number, &chunk.storage_logs)
+            .await?;
+        transaction
+            .storage_logs_dedup_dal()
+            .insert_initial_writes_from_snapshot(initial_writes)
+            .await?;
+        transaction
+            .snapshot_recovery_dal()
+            .mark_chunk_as_applied(&AppliedSnapshotChunk {
+                chunk_id: chain_chunk_id,
+                snapshot_l1_batch_number: header.l1_batch_number,
+                snapshot_chunk_id: chunk_id,
+                storage_logs_count,
+            })
+            .await?;
+        status.last_finished_chunk_id = Some(chain_chunk_id);
+        transaction
+            .snapshot_recovery_dal()
+            .set_applied_snapshot_status(status)
+            .await?;
+        transaction.commit().await?;
+
+        tracing::info!(
+            "Applied storage logs chunk {chunk_id} for snapshot at L1 batch #{} \
+             (overall progress {} / {}) with {} logs",
+            header.l1_batch_number,
+            chain_chunk_id + 1,
+            status.total_chunk_count,
+            chunk.storage_logs.len()
+        );
+        Ok(())
+    }
+}
+
+/// Storage logs chunk of a snapshot in the snapshot chain.
+#[derive(Debug, Clone, Copy)]
+struct ChainChunk<'a> {
+    header: &'a SnapshotHeader,
+    /// L1 batch of the previous snapshot in the chain, or `None` for the full snapshot.
+    base_l1_batch_number: Option<L1BatchNumber>,
+    /// Chunk ID within the snapshot.
+    chunk_id: u64,
+    /// Sequential chunk ID across the entire chain; used in the recovery status.
+    chain_chunk_id: u64,
+}
+
+/// Health details reported by [`SnapshotsApplier`].
+#[derive(Debug, Serialize)]
+struct SnapshotRecoveryHealthDetails {
+    snapshot_l1_batch: L1BatchNumber,
+    applied_chunks: u64,
+    total_chunks: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    eta_sec: Option<u64>,
+}
+
+/// Progress of applying storage logs chunks.
+#[derive(Debug)]
+struct RecoveryProgress {
+    l1_batch_number: L1BatchNumber,
+    total_chunk_count: u64,
+    applied_chunk_count: u64,
+    /// Start of applying chunks by this applier; used together with `chunks_applied_since_start`
+    /// to estimate the time left.
+    started_at: Instant,
+    chunks_applied_since_start: u64,
+}
+
+impl RecoveryProgress {
+    fn eta(&self) -> Option<Duration> {
+        if self.chunks_applied_since_start == 0 {
+            return None;
+        }
+        let remaining_chunk_count = self.total_chunk_count - self.applied_chunk_count;
+        let ratio = remaining_chunk_count as f64 / self.chunks_applied_since_start as f64;
+        Some(self.started_at.elapsed().mul_f64(ratio))
+    }
+
+    fn health(&self, eta: Option<Duration>) -> Health {
+        let status = if self.applied_chunk_count == self.total_chunk_count {
+            HealthStatus::Ready
+        } else {
+            HealthStatus::NotReady
+        };
+        Health::from(status).with_details(SnapshotRecoveryHealthDetails {
+            snapshot_l1_batch: self.l1_batch_number,
+            applied_chunks: self.applied_chunk_count,
+            total_chunks: self.total_chunk_count,
+            eta_sec: eta.map(|eta| eta.as_secs()),
+        })
+    }
+}
+
+fn is_recovery_completed(status: &SnapshotRecoveryStatus) -> bool {
+    status.last_finished_chunk_id.map_or(0, |id| id + 1) == status.total_chunk_count
+}
+
+fn validate_header(header: &SnapshotHeader) -> anyhow::Result<()> {
+    let l1_batch_header = &header.last_l1_batch_with_metadata.header;
+    anyhow::ensure!(
+        l1_batch_header.number == header.l1_batch_number,
+        "snapshot L1 batch #{} doesn't match the L1 batch #{} in the snapshot header",
+        l1_batch_header.number,
+        header.l1_batch_number
+    );
+    anyhow::ensure!(
+        l1_batch_header.is_finished,
+        "snapshot L1 batch #{} is not sealed",
+        header.l1_batch_number
+    );
+    for (i, chunk) in header.storage_logs_chunks.iter().enumerate() {
+        anyhow::ensure!(
+            chunk.chunk_id == i as u64,
+            "storage logs chunks in the snapshot header are not ordered by ID: {:?}",
+            header.storage_logs_chunks
+        );
+    }
+    Ok(())
+}
+
+fn validate_manifest(header: &SnapshotHeader, manifest: &SnapshotManifest) -> anyhow::Result<()> {
+    anyhow::ensure!(
+        manifest.l1_batch_number == header.l1_batch_number,
+        "snapshot manifest L1 batch #{} doesn't match the L1 batch #{} in the snapshot header",
+        manifest.l1_batch_number,
+        header.l1_batch_number
+    );
+    let root_hash = header.last_l1_batch_with_metadata.metadata.root_hash;
+    anyhow::ensure!(
+        manifest.l1_batch_root_hash == root_hash,
+        "snapshot manifest root hash {:?} doesn't match the root hash {root_hash:?} of L1 batch #{}",
+        manifest.l1_batch_root_hash,
+        header.l1_batch_number
+    );
+    anyhow::ensure!(
+        manifest.storage_logs_chunk_hashes.len() == header.storage_logs_chunks.len(),
+        "snapshot manifest for L1 batch #{} has {} chunk hashes, while the snapshot has {} chunks",
+        header.l1_batch_number,
+        manifest.storage_logs_chunk_hashes.len(),
+        header.storage_logs_chunks.len()
+    );
+    Ok(())
+}
+
+fn validate_storage_logs_chunk(
+    header: &SnapshotHeader,
+    chunk_id: u64,
+    chunk: &SnapshotStorageLogsChunk,
+) -> anyhow::Result<()> {
+    if let Some(manifest) = &header.manifest {
+        // The number of chunk hashes is checked in `validate_manifest()`.
+        let expected_hash = manifest.storage_logs_chunk_hashes[chunk_id as usize];
+        let hash = chunk.hash();
+        anyhow::ensure!(
+            hash == expected_hash,
+            "chunk hash {hash:?} doesn't match the hash {expected_hash:?} in the snapshot manifest"
+        );
+    }
+
+    // Leaf indices start from 1; `rollup_last_leaf_index` is the next index to be assigned.
+    let next_enumeration_index = header
+        .last_l1_batch_with_metadata
+        .metadata
+        .rollup_last_leaf_index;
+    for log in &chunk.storage_logs {
+        anyhow::ensure!(
+            log.l1_batch_number_of_initial_write <= header.l1_batch_number,
+            "storage log {log:?} is initially written after the snapshot L1 batch"
+        );
+        anyhow::ensure!(
+            log.enumeration_index > 0 && log.enumeration_index < next_enumeration_index,
+            "storage log {log:?} has enumeration index outside the expected range \
+             1..{next_enumeration_index}"
+        );
+    }
+    Ok(())
+}
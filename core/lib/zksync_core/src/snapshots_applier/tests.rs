@@ -0,0 +1,569 @@
+//! Tests for the snapshots applier.
+
+use std::collections::HashMap;
+
+use zksync_object_store::ObjectStoreFactory;
+use zksync_types::{
+    api::en::SyncBlock,
+    commitment::L1BatchWithMetadata,
+    protocol_version::L1VerifierConfig,
+    snapshots::{SnapshotFactoryDependency, SnapshotStorageLog, SnapshotStorageLogsChunkMetadata},
+    AccountTreeId, Bytes, PackedEthSignature, StorageKey,
+};
+
+use super::*;
+use crate::utils::testonly::{create_l1_batch, create_l1_batch_metadata};
+
+const SNAPSHOT_L1_BATCH: L1BatchNumber = L1BatchNumber(5);
+const SNAPSHOT_MINIBLOCK: MiniblockNumber = MiniblockNumber(10);
+
+#[derive(Debug)]
+struct MockMainNodeClient {
+    snapshot: SnapshotHeader,
+    /// Snapshots older than `snapshot`, e.g. bases for a delta snapshot.
+    older_snapshots: Vec<SnapshotHeader>,
+}
+
+impl MockMainNodeClient {
+    fn new(snapshot: SnapshotHeader) -> Self {
+        Self {
+            snapshot,
+            older_snapshots: vec![],
+        }
+    }
+}
+
+#[async_trait]
+impl SnapshotsApplierMainNodeClient for MockMainNodeClient {
+    async fn fetch_newest_snapshot(&self) -> anyhow::Result<Option<SnapshotHeader>> {
+        Ok(Some(self.snapshot.clone()))
+    }
+
+    async fn fetch_snapshot(
+        &self,
+        l1_batch_number: L1BatchNumber,
+    ) -> anyhow::Result<Option<SnapshotHeader>> {
+        let snapshot = std::iter::once(&self.snapshot)
+            .chain(&self.older_snapshots)
+            .find(|snapshot| snapshot.l1_batch_number == l1_batch_number);
+        Ok(snapshot.cloned())
+    }
+
+    async fn fetch_l2_block(&self, number: MiniblockNumber) -> anyhow::Result<Option<SyncBlock>> {
+        if number != SNAPSHOT_MINIBLOCK {
+            return Ok(None);
+        }
+        Ok(Some(SyncBlock {
+            number,
+            l1_batch_number: SNAPSHOT_L1_BATCH,
+            last_in_batch: true,
+            timestamp: 100,
+            l1_gas_price: 2,
+            l2_fair_gas_price: 3,
+            fair_pubdata_price: Some(24),
+            base_system_contracts_hashes: Default::default(),
+            operator_address: Address::repeat_byte(1),
+            transactions: None,
+            virtual_blocks: Some(0),
+            hash: Some(H256::repeat_byte(1)),
+            protocol_version: ProtocolVersionId::latest(),
+            consensus: None,
+        }))
+    }
+
+    async fn fetch_protocol_version(
+        &self,
+        id: ProtocolVersionId,
+    ) -> anyhow::Result<Option<api::ProtocolVersion>> {
+        Ok(Some(api::ProtocolVersion {
+            version_id: id as u16,
+            timestamp: 0,
+            verification_keys_hashes: L1VerifierConfig::default(),
+            base_system_contracts: Default::default(),
+            l2_system_upgrade_tx_hash: None,
+        }))
+    }
+}
+
+fn mock_snapshot_header(chunk_count: u64) -> SnapshotHeader {
+    SnapshotHeader {
+        l1_batch_number: SNAPSHOT_L1_BATCH,
+        miniblock_number: SNAPSHOT_MINIBLOCK,
+        base_l1_batch_number: None,
+        storage_logs_chunks: (0..chunk_count)
+            .map(|chunk_id| SnapshotStorageLogsChunkMetadata {
+                chunk_id,
+                filepath: format!("chunk{chunk_id}"),
+            })
+            .collect(),
+        factory_deps_filepath: "factory_deps".to_owned(),
+        last_l1_batch_with_metadata: L1BatchWithMetadata {
+            header: create_l1_batch(SNAPSHOT_L1_BATCH.0),
+            metadata: create_l1_batch_metadata(SNAPSHOT_L1_BATCH.0),
+            factory_deps: vec![],
+            da_inclusion_data: None,
+        },
+        manifest: None,
+    }
+}
+
+/// Creates a manifest for a full snapshot with the `mock_storage_logs_chunk()` chunks.
+fn mock_manifest(header: &SnapshotHeader) -> SnapshotManifest {
+    SnapshotManifest {
+        l1_batch_number: header.l1_batch_number,
+        l1_batch_root_hash: header.last_l1_batch_with_metadata.metadata.root_hash,
+        base_manifest_hash: None,
+        storage_logs_chunk_hashes: (0..header.storage_logs_chunks.len() as u64)
+            .map(|chunk_id| mock_storage_logs_chunk(chunk_id).hash())
+            .collect(),
+        signature: None,
+    }
+}
+
+#[derive(Debug)]
+struct MockL1Client {
+    stored_batch_hashes: HashMap<L1BatchNumber, H256>,
+}
+
+#[async_trait]
+impl SnapshotsApplierL1Client for MockL1Client {
+    async fn fetch_stored_batch_hash(
+        &self,
+        l1_batch_number: L1BatchNumber,
+    ) -> anyhow::Result<Option<H256>> {
+        Ok(self.stored_batch_hashes.get(&l1_batch_number).copied())
+    }
+}
+
+fn mock_storage_logs_chunk(chunk_id: u64) -> SnapshotStorageLogsChunk {
+    let storage_logs = (0..5)
+        .map(|i| {
+            let enumeration_index = chunk_id * 5 + i + 1;
+            SnapshotStorageLog {
+                key: StorageKey::new(
+                    AccountTreeId::new(Address::repeat_byte(1)),
+                    H256::from_low_u64_be(enumeration_index),
+                ),
+                value: H256::from_low_u64_be(enumeration_index + 1000),
+                l1_batch_number_of_initial_write: L1BatchNumber(1),
+                enumeration_index,
+            }
+        })
+        .collect();
+    SnapshotStorageLogsChunk { storage_logs }
+}
+
+async fn prepare_blob_store(blob_store: &dyn ObjectStore) -> Vec<u8> {
+    let bytecode = vec![1; 32];
+    let factory_deps = SnapshotFactoryDependencies {
+        factory_deps: vec![SnapshotFactoryDependency {
+            bytecode: Bytes(bytecode.clone()),
+        }],
+    };
+    blob_store
+        .put(SNAPSHOT_L1_BATCH, &factory_deps)
+        .await
+        .unwrap();
+    bytecode
+}
+
+async fn put_storage_logs_chunk(blob_store: &dyn ObjectStore, chunk_id: u64) {
+    let key = SnapshotStorageLogsStorageKey {
+        l1_batch_number: SNAPSHOT_L1_BATCH,
+        chunk_id,
+    };
+    blob_store
+        .put(key, &mock_storage_logs_chunk(chunk_id))
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn snapshot_recovery_is_resumed_after_failure() {
+    let pool = ConnectionPool::test_pool().await;
+    let blob_store = ObjectStoreFactory::mock().create_store().await;
+    let bytecode = prepare_blob_store(&*blob_store).await;
+    put_storage_logs_chunk(&*blob_store, 0).await;
+    let client = MockMainNodeClient::new(mock_snapshot_header(2));
+
+    // The second chunk is missing, so recovery should fail after applying the first one.
+    SnapshotsApplier::new(&pool, &client, &*blob_store)
+        .run()
+        .await
+        .unwrap_err();
+    let mut storage = pool.access_storage().await.unwrap();
+    let status = storage
+        .snapshot_recovery_dal()
+        .get_applied_snapshot_status()
+        .await
+        .unwrap()
+        .expect("no recovery status");
+    assert_eq!(status.l1_batch_number, SNAPSHOT_L1_BATCH);
+    assert_eq!(status.miniblock_number, SNAPSHOT_MINIBLOCK);
+    assert_eq!(status.miniblock_root_hash, H256::repeat_byte(1));
+    assert_eq!(status.last_finished_chunk_id, Some(0));
+    assert_eq!(status.total_chunk_count, 2);
+    let applied_chunks = storage
+        .snapshot_recovery_dal()
+        .get_applied_chunks()
+        .await
+        .unwrap();
+    assert_eq!(
+        applied_chunks,
+        [AppliedSnapshotChunk {
+            chunk_id: 0,
+            snapshot_l1_batch_number: SNAPSHOT_L1_BATCH,
+            snapshot_chunk_id: 0,
+            storage_logs_count: 5,
+        }]
+    );
+
+    let factory_dep = storage
+        .storage_dal()
+        .get_factory_dep(hash_bytecode(&bytecode))
+        .await;
+    assert_eq!(factory_dep, Some(bytecode));
+    let miniblock_header = storage
+        .blocks_dal()
+        .get_miniblock_header(SNAPSHOT_MINIBLOCK)
+        .await
+        .unwrap()
+        .expect("no snapshot miniblock");
+    assert_eq!(miniblock_header.hash, H256::repeat_byte(1));
+
+    put_storage_logs_chunk(&*blob_store, 1).await;
+    SnapshotsApplier::new(&pool, &client, &*blob_store)
+        .run()
+        .await
+        .unwrap();
+    let status = storage
+        .snapshot_recovery_dal()
+        .get_applied_snapshot_status()
+        .await
+        .unwrap()
+        .expect("no recovery status");
+    assert_eq!(status.last_finished_chunk_id, Some(1));
+    assert!(is_recovery_completed(&status));
+
+    let all_logs: Vec<_> = (0..2)
+        .flat_map(|chunk_id| mock_storage_logs_chunk(chunk_id).storage_logs)
+        .collect();
+    let hashed_keys: Vec<_> = all_logs.iter().map(|log| log.key.hashed_key()).collect();
+    let values = storage
+        .storage_logs_dal()
+        .get_storage_values(&hashed_keys, SNAPSHOT_MINIBLOCK)
+        .await;
+    let expected_values: HashMap<_, _> = all_logs
+        .iter()
+        .map(|log| (log.key.hashed_key(), Some(log.value)))
+        .collect();
+    assert_eq!(values, expected_values);
+    for log in &all_logs {
+        let enumeration_index = storage
+            .storage_logs_dedup_dal()
+            .get_enumeration_index_for_key(log.key)
+            .await;
+        assert_eq!(enumeration_index, Some(log.enumeration_index));
+    }
+
+    // Repeated recovery should be a no-op.
+    SnapshotsApplier::new(&pool, &client, &*blob_store)
+        .run()
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn recovering_from_delta_snapshot() {
+    const BASE_L1_BATCH: L1BatchNumber = L1BatchNumber(3);
+
+    let pool = ConnectionPool::test_pool().await;
+    let blob_store = ObjectStoreFactory::mock().create_store().await;
+    let bytecode = prepare_blob_store(&*blob_store).await;
+    let base_bytecode = vec![2; 32];
+    let base_factory_deps = SnapshotFactoryDependencies {
+        factory_deps: vec![SnapshotFactoryDependency {
+            bytecode: Bytes(base_bytecode.clone()),
+        }],
+    };
+    blob_store
+        .put(BASE_L1_BATCH, &base_factory_deps)
+        .await
+        .unwrap();
+
+    let base_chunk = mock_storage_logs_chunk(0);
+    let key = SnapshotStorageLogsStorageKey {
+        l1_batch_number: BASE_L1_BATCH,
+        chunk_id: 0,
+    };
+    blob_store.put(key, &base_chunk).await.unwrap();
+    // The delta snapshot updates a key from the base snapshot and adds a new key.
+    let updated_log = SnapshotStorageLog {
+        value: H256::repeat_byte(0xff),
+        ..base_chunk.storage_logs[0].clone()
+    };
+    let new_log = SnapshotStorageLog {
+        key: StorageKey::new(AccountTreeId::new(Address::repeat_byte(2)), H256::zero()),
+        value: H256::repeat_byte(0xfe),
+        l1_batch_number_of_initial_write: BASE_L1_BATCH + 1,
+        enumeration_index: 6,
+    };
+    let delta_chunk = SnapshotStorageLogsChunk {
+        storage_logs: vec![updated_log.clone(), new_log.clone()],
+    };
+    let key = SnapshotStorageLogsStorageKey {
+        l1_batch_number: SNAPSHOT_L1_BATCH,
+        chunk_id: 0,
+    };
+    blob_store.put(key, &delta_chunk).await.unwrap();
+
+    let mut base_header = mock_snapshot_header(1);
+    base_header.l1_batch_number = BASE_L1_BATCH;
+    base_header.last_l1_batch_with_metadata = L1BatchWithMetadata {
+        header: create_l1_batch(BASE_L1_BATCH.0),
+        metadata: create_l1_batch_metadata(BASE_L1_BATCH.0),
+        factory_deps: vec![],
+        da_inclusion_data: None,
+    };
+    let mut delta_header = mock_snapshot_header(1);
+    delta_header.base_l1_batch_number = Some(BASE_L1_BATCH);
+    let client = MockMainNodeClient {
+        snapshot: delta_header,
+        older_snapshots: vec![base_header],
+    };
+
+    SnapshotsApplier::new(&pool, &client, &*blob_store)
+        .run()
+        .await
+        .unwrap();
+
+    let mut storage = pool.access_storage().await.unwrap();
+    let status = storage
+        .snapshot_recovery_dal()
+        .get_applied_snapshot_status()
+        .await
+        .unwrap()
+        .expect("no recovery status");
+    assert_eq!(status.l1_batch_number, SNAPSHOT_L1_BATCH);
+    assert_eq!(status.total_chunk_count, 2);
+    assert!(is_recovery_completed(&status));
+
+    for bytecode in [bytecode, base_bytecode] {
+        let factory_dep = storage
+            .storage_dal()
+            .get_factory_dep(hash_bytecode(&bytecode))
+            .await;
+        assert_eq!(factory_dep, Some(bytecode));
+    }
+
+    let mut expected_logs = base_chunk.storage_logs;
+    expected_logs[0] = updated_log;
+    expected_logs.push(new_log);
+    let hashed_keys: Vec<_> = expected_logs
+        .iter()
+        .map(|log| log.key.hashed_key())
+        .collect();
+    let values = storage
+        .storage_logs_dal()
+        .get_storage_values(&hashed_keys, SNAPSHOT_MINIBLOCK)
+        .await;
+    let expected_values: HashMap<_, _> = expected_logs
+        .iter()
+        .map(|log| (log.key.hashed_key(), Some(log.value)))
+        .collect();
+    assert_eq!(values, expected_values);
+    for log in &expected_logs {
+        let enumeration_index = storage
+            .storage_logs_dedup_dal()
+            .get_enumeration_index_for_key(log.key)
+            .await;
+        assert_eq!(enumeration_index, Some(log.enumeration_index));
+    }
+}
+
+#[tokio::test]
+async fn recovering_snapshot_with_manifest() {
+    let pool = ConnectionPool::test_pool().await;
+    let blob_store = ObjectStoreFactory::mock().create_store().await;
+    prepare_blob_store(&*blob_store).await;
+    for chunk_id in 0..2 {
+        put_storage_logs_chunk(&*blob_store, chunk_id).await;
+    }
+
+    let signing_key = H256::repeat_byte(0x42);
+    let signer = PackedEthSignature::address_from_private_key(&signing_key).unwrap();
+    let mut header = mock_snapshot_header(2);
+    let mut manifest = mock_manifest(&header);
+    manifest.signature =
+        Some(PackedEthSignature::sign_raw(&signing_key, &manifest.hash()).unwrap());
+    header.manifest = Some(manifest);
+    let client = MockMainNodeClient::new(header.clone());
+
+    // Manifest signed by an unexpected address should be rejected before any changes to Postgres.
+    let err = SnapshotsApplier::new(&pool, &client, &*blob_store)
+        .with_manifest_signer(Address::repeat_byte(0x23))
+        .run()
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("signed by"), "{err}");
+    let mut storage = pool.access_storage().await.unwrap();
+    assert!(storage.blocks_dal().is_genesis_needed().await.unwrap());
+
+    // Snapshot L1 batch not committed on L1 should be rejected as well.
+    let l1_client = MockL1Client {
+        stored_batch_hashes: HashMap::new(),
+    };
+    let err = SnapshotsApplier::new(&pool, &client, &*blob_store)
+        .with_l1_client(&l1_client)
+        .run()
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("not committed on L1"), "{err}");
+
+    let l1_header_data = header.last_l1_batch_with_metadata.l1_header_data();
+    let stored_batch_hash = H256(keccak256(&ethabi::encode(&[l1_header_data])));
+    let l1_client = MockL1Client {
+        stored_batch_hashes: HashMap::from([(SNAPSHOT_L1_BATCH, stored_batch_hash)]),
+    };
+    SnapshotsApplier::new(&pool, &client, &*blob_store)
+        .with_l1_client(&l1_client)
+        .with_manifest_signer(signer)
+        .run()
+        .await
+        .unwrap();
+    let status = storage
+        .snapshot_recovery_dal()
+        .get_applied_snapshot_status()
+        .await
+        .unwrap()
+        .expect("no recovery status");
+    assert!(is_recovery_completed(&status));
+}
+
+#[tokio::test]
+async fn corrupted_chunk_is_rejected_by_manifest() {
+    let pool = ConnectionPool::test_pool().await;
+    let blob_store = ObjectStoreFactory::mock().create_store().await;
+    prepare_blob_store(&*blob_store).await;
+    put_storage_logs_chunk(&*blob_store, 0).await;
+    let mut corrupted_chunk = mock_storage_logs_chunk(1);
+    corrupted_chunk.storage_logs[0].value = H256::repeat_byte(0xff);
+    let key = SnapshotStorageLogsStorageKey {
+        l1_batch_number: SNAPSHOT_L1_BATCH,
+        chunk_id: 1,
+    };
+    blob_store.put(key, &corrupted_chunk).await.unwrap();
+
+    let mut header = mock_snapshot_header(2);
+    header.manifest = Some(mock_manifest(&header));
+    let client = MockMainNodeClient::new(header);
+    let err = SnapshotsApplier::new(&pool, &client, &*blob_store)
+        .run()
+        .await
+        .unwrap_err();
+    assert!(
+        format!("{err:#}").contains("doesn't match the hash"),
+        "{err:#}"
+    );
+
+    let mut storage = pool.access_storage().await.unwrap();
+    let status = storage
+        .snapshot_recovery_dal()
+        .get_applied_snapshot_status()
+        .await
+        .unwrap()
+        .expect("no recovery status");
+    assert_eq!(status.last_finished_chunk_id, Some(0));
+}
+
+#[test]
+fn reporting_recovery_progress() {
+    let mut progress = RecoveryProgress {
+        l1_batch_number: SNAPSHOT_L1_BATCH,
+        total_chunk_count: 4,
+        applied_chunk_count: 1,
+        started_at: Instant::now() - Duration::from_secs(10),
+        chunks_applied_since_start: 0,
+    };
+    assert_eq!(progress.eta(), None);
+    let expected_health = Health::from(HealthStatus::NotReady).with_details(serde_json::json!({
+        "snapshot_l1_batch": SNAPSHOT_L1_BATCH.0,
+        "applied_chunks": 1,
+        "total_chunks": 4,
+    }));
+    assert_eq!(progress.health(None), expected_health);
+
+    progress.applied_chunk_count = 2;
+    progress.chunks_applied_since_start = 1;
+    // 2 chunks are remaining, and applying a chunk takes ~10s.
+    let eta = progress.eta().unwrap();
+    assert!(eta >= Duration::from_secs(20), "{eta:?}");
+    let expected_health = Health::from(HealthStatus::NotReady).with_details(serde_json::json!({
+        "snapshot_l1_batch": SNAPSHOT_L1_BATCH.0,
+        "applied_chunks": 2,
+        "total_chunks": 4,
+        "eta_sec": eta.as_secs(),
+    }));
+    assert_eq!(progress.health(Some(eta)), expected_health);
+
+    progress.applied_chunk_count = 4;
+    progress.chunks_applied_since_start = 3;
+    assert_eq!(progress.eta(), Some(Duration::ZERO));
+    assert_eq!(
+        progress.health(Some(Duration::ZERO)).status(),
+        HealthStatus::Ready
+    );
+}
+
+#[test]
+fn invalid_manifest_is_rejected() {
+    let header = mock_snapshot_header(2);
+    let manifest = mock_manifest(&header);
+    validate_manifest(&header, &manifest).unwrap();
+
+    let mut invalid_manifest = manifest.clone();
+    invalid_manifest.l1_batch_root_hash = H256::repeat_byte(0xff);
+    validate_manifest(&header, &invalid_manifest).unwrap_err();
+
+    let mut invalid_manifest = manifest;
+    invalid_manifest.storage_logs_chunk_hashes.pop();
+    validate_manifest(&header, &invalid_manifest).unwrap_err();
+}
+
+#[tokio::test]
+async fn snapshot_recovery_fails_for_non_empty_storage() {
+    let pool = ConnectionPool::test_pool().await;
+    let mut storage = pool.access_storage().await.unwrap();
+    storage
+        .protocol_versions_dal()
+        .save_protocol_version_with_tx(Default::default())
+        .await;
+    storage
+        .blocks_dal()
+        .insert_l1_batch(&create_l1_batch(0), &[], Default::default(), &[], &[], 0)
+        .await
+        .unwrap();
+    drop(storage);
+
+    let blob_store = ObjectStoreFactory::mock().create_store().await;
+    let client = MockMainNodeClient::new(mock_snapshot_header(1));
+    let err = SnapshotsApplier::new(&pool, &client, &*blob_store)
+        .run()
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("not empty"), "{err}");
+}
+
+#[test]
+fn invalid_storage_logs_are_rejected() {
+    let header = mock_snapshot_header(1);
+    let mut chunk = mock_storage_logs_chunk(0);
+    validate_storage_logs_chunk(&header, 0, &chunk).unwrap();
+
+    chunk.storage_logs[0].l1_batch_number_of_initial_write = SNAPSHOT_L1_BATCH + 1;
+    validate_storage_logs_chunk(&header, 0, &chunk).unwrap_err();
+
+    let mut chunk = mock_storage_logs_chunk(0);
+    chunk.storage_logs[0].enumeration_index = 0;
+    validate_storage_logs_chunk(&header, 0, &chunk).unwrap_err();
+}
@@ -1,6 +1,11 @@
-use std::{fmt, sync::Arc};
+use std::{
+    fmt, mem,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use async_trait::async_trait;
+use chrono::{Timelike, Utc};
 use multivm::{
     interface::{
         ExecutionResult, FinishedL1Batch, Halt, L1BatchEnv, L2BlockEnv, SystemEnv, VmExecutionMode,
@@ -16,12 +21,18 @@ use tokio::{
     task::JoinHandle,
 };
 use zksync_dal::ConnectionPool;
-use zksync_state::{RocksdbStorage, StorageView, WriteStorage};
-use zksync_types::{vm_trace::Call, witness_block_state::WitnessBlockState, Transaction, U256};
+use zksync_state::{
+    RocksdbStorage, StorageOverlay, StorageOverlayCache, StorageView, WriteStorage,
+};
+use zksync_types::{
+    vm_trace::Call, witness_block_state::WitnessBlockState, L1BatchNumber, MiniblockNumber,
+    Transaction, U256,
+};
 use zksync_utils::bytecode::CompressedBytecodeInfo;
 
 use crate::{
     metrics::{InteractionType, TxStage, APP_METRICS},
+    rocksdb_backup::RocksDBBackups,
     state_keeper::{
         metrics::{ExecutorCommand, TxExecutionStage, EXECUTOR_METRICS, KEEPER_METRICS},
         types::ExecutionMetricsForCriteria,
@@ -66,6 +77,34 @@ impl TxExecutionResult {
     }
 }
 
+/// Information about a transaction executed by the batch executor, which is reported to [`TxExecutionHook`]s.
+#[derive(Debug, Clone, Copy)]
+pub struct ExecutedTx<'a> {
+    /// Number of the L1 batch the transaction was executed in.
+    pub l1_batch_number: L1BatchNumber,
+    /// Executed transaction.
+    pub tx: &'a Transaction,
+    /// VM execution result, including gas statistics, the revert reason (if any) and storage logs.
+    pub result: &'a VmExecutionResultAndLogs,
+}
+
+/// Hook receiving results of transactions executed by the batch executor in real time, i.e.,
+/// before the containing miniblock is sealed and persisted.
+///
+/// Hooks are invoked on the batch executor thread, so they must not block; heavy processing
+/// (e.g., streaming results to an external system) should be offloaded, for example, via a channel.
+pub trait TxExecutionHook: 'static + fmt::Debug + Send + Sync {
+    /// Called after a transaction is successfully executed (including transactions reverted
+    /// by the VM). Transactions rejected by the VM are not reported.
+    fn on_tx_executed(&self, tx: ExecutedTx<'_>);
+
+    /// Called after the last reported transaction is rolled back, e.g. because it doesn't fit
+    /// into the current L1 batch. The transaction may be executed again in the next batch.
+    fn on_last_tx_rolled_back(&self, _l1_batch_number: L1BatchNumber) {
+        // Do nothing by default
+    }
+}
+
 /// An abstraction that allows us to create different kinds of batch executors.
 /// The only requirement is to return a [`BatchExecutorHandle`], which does its work
 /// by communicating with the externally initialized thread.
@@ -80,7 +119,7 @@ pub trait L1BatchExecutorBuilder: 'static + Send + Sync + fmt::Debug {
 
 /// The default implementation of [`L1BatchExecutorBuilder`].
 /// Creates a "real" batch executor which maintains the VM (as opposed to the test builder which doesn't use the VM).
-#[derive(Debug, Clone)]
+#[derive(Debug)]
 pub struct MainBatchExecutorBuilder {
     state_keeper_db_path: String,
     pool: ConnectionPool,
@@ -89,6 +128,119 @@ pub struct MainBatchExecutorBuilder {
     upload_witness_inputs_to_gcs: bool,
     enum_index_migration_chunk_size: usize,
     optional_bytecode_compression: bool,
+    tx_execution_hooks: Vec<Arc<dyn TxExecutionHook>>,
+    backups: Option<StateKeeperCacheBackups>,
+    /// Number of latest L1 batches used to warm up the cache; reset after the first warm-up.
+    cache_warm_up_l1_batches: Option<u32>,
+    compaction: Option<StateKeeperCacheCompaction>,
+    /// Warm-up or compaction of the state keeper cache running concurrently with the current batch executor.
+    /// It must finish before the cache is opened for the next L1 batch.
+    cache_maintenance_task: Option<JoinHandle<()>>,
+    storage_overlay_cache: Option<StorageOverlayCache>,
+}
+
+/// Periodic backups of the state keeper cache. Backups are started when initializing a batch executor,
+/// since the cache is exclusively opened by the executor otherwise, and are created and uploaded in the background.
+#[derive(Debug)]
+struct StateKeeperCacheBackups {
+    backups: RocksDBBackups,
+    interval: Duration,
+    last_backup_at: Instant,
+    /// Backup being created or uploaded. A new backup is not started until the previous one is finished.
+    task: Option<JoinHandle<()>>,
+}
+
+impl StateKeeperCacheBackups {
+    fn backup_if_due(&mut self, storage: &RocksdbStorage) {
+        if self.last_backup_at.elapsed() < self.interval {
+            return;
+        }
+        if self.task.as_ref().map_or(false, |task| !task.is_finished()) {
+            tracing::info!(
+                "Previous state keeper cache backup is still in progress; postponing the next one"
+            );
+            return;
+        }
+        self.last_backup_at = Instant::now();
+
+        let handle = storage.maintenance_handle();
+        let backups = self.backups.clone();
+        self.task = Some(tokio::spawn(async move {
+            let checkpoint_backups = backups.clone();
+            let checkpoint = tokio::task::spawn_blocking(move || {
+                checkpoint_backups.create_checkpoint(|path| handle.create_checkpoint(path))
+            })
+            .await
+            .unwrap();
+            let result = match checkpoint {
+                Ok(checkpoint) => backups.upload(checkpoint).await,
+                Err(err) => Err(err),
+            };
+            if let Err(err) = result {
+                tracing::warn!("Failed backing up state keeper cache: {err:#}");
+            }
+        }));
+    }
+}
+
+/// Incremental compaction of the state keeper cache. Compaction is started when initializing a batch executor
+/// and runs in the background while the L1 batch is processed; a single slice of keys is compacted per L1 batch,
+/// so compaction normally finishes before the next L1 batch is started.
+#[derive(Debug, Clone)]
+struct StateKeeperCacheCompaction {
+    interval: Duration,
+    /// Off-peak window as `(start_hour, end_hour)` in UTC.
+    window: Option<(u32, u32)>,
+    /// Next slice to compact, or `None` if no compaction cycle is in progress.
+    next_slice: Option<u8>,
+    last_completed_at: Option<Instant>,
+}
+
+impl StateKeeperCacheCompaction {
+    fn new(interval: Duration, window: Option<(u32, u32)>) -> Self {
+        Self {
+            interval,
+            window,
+            next_slice: None,
+            last_completed_at: None,
+        }
+    }
+
+    fn is_within_window(&self, hour: u32) -> bool {
+        match self.window {
+            None => true,
+            Some((start, end)) if start <= end => (start..end).contains(&hour),
+            // The window wraps around midnight.
+            Some((start, end)) => hour >= start || hour < end,
+        }
+    }
+
+    /// Returns the slice to compact now, if any.
+    fn next_slice_to_compact(&mut self, now: Instant, hour: u32) -> Option<u8> {
+        if !self.is_within_window(hour) {
+            return None;
+        }
+        let slice = match self.next_slice {
+            Some(slice) => slice,
+            None => {
+                let is_due = self
+                    .last_completed_at
+                    .map_or(true, |completed_at| now - completed_at >= self.interval);
+                if !is_due {
+                    return None;
+                }
+                0
+            }
+        };
+
+        if slice + 1 == RocksdbStorage::COMPACTION_SLICE_COUNT {
+            self.next_slice = None;
+            self.last_completed_at = Some(now);
+        } else {
+            self.next_slice = Some(slice + 1);
+        }
+        Some(slice)
+    }
 }
 
 impl MainBatchExecutorBuilder {
@@ -109,7 +261,89 @@ impl MainBatchExecutorBuilder {
             upload_witness_inputs_to_gcs,
             enum_index_migration_chunk_size,
             optional_bytecode_compression,
+            tx_execution_hooks: vec![],
+            backups: None,
+            cache_warm_up_l1_batches: None,
+            compaction: None,
+            cache_maintenance_task: None,
+            storage_overlay_cache: None,
+        }
+    }
+
+    /// Enables periodic backups of the state keeper cache to the object store.
+    #[must_use]
+    pub fn with_backups(mut self, backups: RocksDBBackups, interval: Duration) -> Self {
+        self.backups = Some(StateKeeperCacheBackups {
+            backups,
+            interval,
+            last_backup_at: Instant::now(),
+            task: None,
+        });
+        self
+    }
+
+    /// Enables warming up the state keeper cache using keys touched in the specified number of latest L1 batches.
+    /// The cache is warmed up once, when the first batch executor is created.
+    #[must_use]
+    pub fn with_cache_warm_up(mut self, l1_batch_count: u32) -> Self {
+        self.cache_warm_up_l1_batches = Some(l1_batch_count);
+        self
+    }
+
+    /// Enables periodic incremental compaction of the state keeper cache, optionally restricted
+    /// to an off-peak window specified as `(start_hour, end_hour)` in UTC.
+    #[must_use]
+    pub fn with_cache_compaction(mut self, interval: Duration, window: Option<(u32, u32)>) -> Self {
+        self.compaction = Some(StateKeeperCacheCompaction::new(interval, window));
+        self
+    }
+
+    /// Enables the in-memory cache of storage values shared among consecutive batch executors, so that
+    /// hot storage slots are not re-read from the state keeper cache for each L1 batch. The capacity is measured in bytes.
+    #[must_use]
+    pub fn with_storage_overlay_cache(mut self, capacity: u64) -> Self {
+        self.storage_overlay_cache = Some(StorageOverlayCache::new(capacity));
+        self
+    }
+
+    /// Adds a hook receiving results of transactions executed by created batch executors.
+    #[must_use]
+    pub fn with_tx_execution_hook(mut self, hook: Arc<dyn TxExecutionHook>) -> Self {
+        self.tx_execution_hooks.push(hook);
+        self
+    }
+
+    /// Starts warm-up and / or compaction of the state keeper cache if they are due. These tasks are executed
+    /// in the background so that they don't delay the start of the L1 batch.
+    fn start_cache_maintenance(&mut self, storage: &RocksdbStorage) {
+        let warm_up_l1_batches = self.cache_warm_up_l1_batches.take();
+        let compacted_slice = self.compaction.as_mut().and_then(|compaction| {
+            compaction.next_slice_to_compact(Instant::now(), Utc::now().hour())
+        });
+        if warm_up_l1_batches.is_none() && compacted_slice.is_none() {
+            return;
         }
+
+        let handle = storage.maintenance_handle();
+        let pool = self.pool.clone();
+        self.cache_maintenance_task = Some(tokio::spawn(async move {
+            if let Some(l1_batch_count) = warm_up_l1_batches {
+                let mut conn = pool.access_storage_tagged("state_keeper").await.unwrap();
+                handle
+                    .warm_up_from_postgres(&mut conn, l1_batch_count)
+                    .await;
+            }
+            if let Some(slice) = compacted_slice {
+                tokio::task::spawn_blocking(move || {
+                    handle.compact_slice(slice);
+                    if slice + 1 == RocksdbStorage::COMPACTION_SLICE_COUNT {
+                        tracing::info!("Finished compacting state keeper cache");
+                    }
+                })
+                .await
+                .unwrap();
+            }
+        }));
     }
 }
 
@@ -120,6 +354,12 @@ impl L1BatchExecutorBuilder for MainBatchExecutorBuilder {
         l1_batch_params: L1BatchEnv,
         system_env: SystemEnv,
     ) -> BatchExecutorHandle {
+        if let Some(task) = self.cache_maintenance_task.take() {
+            if let Err(err) = task.await {
+                tracing::warn!("State keeper cache maintenance failed: {err}");
+            }
+        }
+
         let mut secondary_storage = RocksdbStorage::new(self.state_keeper_db_path.as_ref());
         secondary_storage.enable_enum_index_migration(self.enum_index_migration_chunk_size);
         let mut conn = self
@@ -129,6 +369,10 @@ impl L1BatchExecutorBuilder for MainBatchExecutorBuilder {
             .unwrap();
         secondary_storage.update_from_postgres(&mut conn).await;
         drop(conn);
+        if let Some(backups) = &mut self.backups {
+            backups.backup_if_due(&secondary_storage);
+        }
+        self.start_cache_maintenance(&secondary_storage);
 
         BatchExecutorHandle::new(
             self.save_call_traces,
@@ -138,6 +382,8 @@ impl L1BatchExecutorBuilder for MainBatchExecutorBuilder {
             system_env,
             self.upload_witness_inputs_to_gcs,
             self.optional_bytecode_compression,
+            self.tx_execution_hooks.clone(),
+            self.storage_overlay_cache.clone(),
         )
     }
 }
@@ -162,6 +408,8 @@ impl BatchExecutorHandle {
         system_env: SystemEnv,
         upload_witness_inputs_to_gcs: bool,
         optional_bytecode_compression: bool,
+        tx_execution_hooks: Vec<Arc<dyn TxExecutionHook>>,
+        storage_overlay_cache: Option<StorageOverlayCache>,
     ) -> Self {
         // Since we process `BatchExecutor` commands one-by-one (the next command is never enqueued
         // until a previous command is processed), capacity 1 is enough for the commands channel.
@@ -170,6 +418,7 @@ impl BatchExecutorHandle {
             save_call_traces,
             max_allowed_tx_gas_limit,
             optional_bytecode_compression,
+            tx_execution_hooks,
             commands: commands_receiver,
         };
 
@@ -179,6 +428,7 @@ impl BatchExecutorHandle {
                 l1_batch_env,
                 system_env,
                 upload_witness_inputs_to_gcs,
+                storage_overlay_cache,
             )
         });
         Self {
@@ -291,6 +541,7 @@ pub(super) struct BatchExecutor {
     save_call_traces: bool,
     max_allowed_tx_gas_limit: U256,
     optional_bytecode_compression: bool,
+    tx_execution_hooks: Vec<Arc<dyn TxExecutionHook>>,
     commands: mpsc::Receiver<Command>,
 }
 
@@ -301,10 +552,25 @@ impl BatchExecutor {
         l1_batch_params: L1BatchEnv,
         system_env: SystemEnv,
         upload_witness_inputs_to_gcs: bool,
+        storage_overlay_cache: Option<StorageOverlayCache>,
     ) {
         tracing::info!("Starting executing batch #{:?}", &l1_batch_params.number);
 
-        let storage_view = StorageView::new(secondary_storage).to_rc_ptr();
+        let l1_batch_number = l1_batch_params.number;
+        // Whether the last executed transaction was reported to hooks; used to report rollbacks.
+        let mut is_last_tx_reported = false;
+        // The state keeper cache corresponds to the state after the last miniblock of the previous L1 batch.
+        let first_miniblock = &l1_batch_params.first_l2_block;
+        let mut last_miniblock_number = MiniblockNumber(first_miniblock.number);
+        let mut storage = StorageOverlay::new(secondary_storage);
+        if let Some(cache) = &storage_overlay_cache {
+            storage = storage.with_cache(
+                cache.clone(),
+                last_miniblock_number - 1,
+                first_miniblock.prev_block_hash,
+            );
+        }
+        let storage_view = StorageView::new(storage).to_rc_ptr();
 
         let mut vm = VmInstance::new(l1_batch_params, system_env, storage_view.clone());
 
@@ -312,13 +578,31 @@ impl BatchExecutor {
             match cmd {
                 Command::ExecuteTx(tx, resp) => {
                     let result = self.execute_tx(&tx, &mut vm);
+                    is_last_tx_reported = false;
+                    if let TxExecutionResult::Success { tx_result, .. } = &result {
+                        let executed_tx = ExecutedTx {
+                            l1_batch_number,
+                            tx: &tx,
+                            result: tx_result,
+                        };
+                        for hook in &self.tx_execution_hooks {
+                            hook.on_tx_executed(executed_tx);
+                        }
+                        is_last_tx_reported = true;
+                    }
                     resp.send(result).unwrap();
                 }
                 Command::RollbackLastTx(resp) => {
                     self.rollback_last_tx(&mut vm);
+                    if mem::take(&mut is_last_tx_reported) {
+                        for hook in &self.tx_execution_hooks {
+                            hook.on_last_tx_rolled_back(l1_batch_number);
+                        }
+                    }
                     resp.send(()).unwrap();
                 }
                 Command::StartNextMiniblock(l2_block_env, resp) => {
+                    last_miniblock_number = MiniblockNumber(l2_block_env.number);
                     self.start_next_miniblock(l2_block_env, &mut vm);
                     resp.send(()).unwrap();
                 }
@@ -331,6 +615,14 @@ impl BatchExecutor {
                     };
                     resp.send((vm_block_result, witness_block_state)).unwrap();
 
+                    if let Some(cache) = &storage_overlay_cache {
+                        let storage_view = storage_view.borrow();
+                        cache.advance(
+                            last_miniblock_number,
+                            storage_view.modified_storage_keys().keys(),
+                        );
+                    }
+
                     // `storage_view` cannot be accessed while borrowed by the VM,
                     // so this is the only point at which storage metrics can be obtained
                     let metrics = storage_view.as_ref().borrow_mut().metrics();
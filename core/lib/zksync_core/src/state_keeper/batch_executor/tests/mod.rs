@@ -1,11 +1,30 @@
+use std::{
+    collections::HashSet,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
 use assert_matches::assert_matches;
+use tokio::runtime::Handle;
 use zksync_dal::ConnectionPool;
+use zksync_state::RocksdbStorage;
 use zksync_test_account::Account;
-use zksync_types::PriorityOpId;
+use zksync_types::{
+    AccountTreeId, Address, L1BatchNumber, MiniblockNumber, PriorityOpId, StorageKey, H256,
+};
+use zksync_utils::u256_to_h256;
 
 use self::tester::Tester;
-use super::TxExecutionResult;
-use crate::state_keeper::batch_executor::tests::tester::{AccountLoadNextExecutable, TestConfig};
+use super::{ExecutedTx, StateKeeperCacheCompaction, TxExecutionHook, TxExecutionResult};
+use crate::{
+    state_keeper::{
+        batch_executor::tests::tester::{AccountLoadNextExecutable, TestConfig},
+        io::MiniblockParams,
+        protective_reads::compute_protective_reads,
+        updates::UpdatesManager,
+    },
+    utils::testonly::create_l1_batch_metadata,
+};
 
 mod tester;
 
@@ -127,6 +146,69 @@ async fn rollback() {
     executor.finish_batch().await;
 }
 
+#[derive(Debug, Clone, PartialEq)]
+enum HookEvent {
+    Executed(H256),
+    RolledBack,
+}
+
+#[derive(Debug, Default)]
+struct RecordingHook(Mutex<Vec<HookEvent>>);
+
+impl TxExecutionHook for RecordingHook {
+    fn on_tx_executed(&self, tx: ExecutedTx<'_>) {
+        assert_eq!(tx.l1_batch_number, L1BatchNumber(1));
+        assert!(!tx.result.result.is_failed());
+        self.0
+            .lock()
+            .unwrap()
+            .push(HookEvent::Executed(tx.tx.hash()));
+    }
+
+    fn on_last_tx_rolled_back(&self, l1_batch_number: L1BatchNumber) {
+        assert_eq!(l1_batch_number, L1BatchNumber(1));
+        self.0.lock().unwrap().push(HookEvent::RolledBack);
+    }
+}
+
+/// Checks that transaction execution hooks are notified about executed and rolled back transactions.
+#[tokio::test]
+async fn tx_execution_hooks() {
+    let connection_pool = ConnectionPool::test_pool().await;
+    let mut alice = Account::random();
+    let hook = Arc::<RecordingHook>::default();
+
+    let mut config = TestConfig::new();
+    config.tx_execution_hooks = vec![hook.clone()];
+    let tester = Tester::with_config(connection_pool, config);
+    tester.genesis().await;
+    tester.fund(&[alice.address()]).await;
+    let executor = tester.create_batch_executor().await;
+
+    let tx = alice.execute();
+    let tx_hash = tx.hash();
+    assert_executed(&executor.execute_tx(tx.clone()).await);
+    executor.rollback_last_tx().await;
+    assert_executed(&executor.execute_tx(tx).await);
+
+    // A rejected transaction must not be reported, and neither should its rollback.
+    // The transaction is rejected because its initiator is not funded.
+    let mut bob = Account::random();
+    assert_rejected(&executor.execute_tx(bob.execute()).await);
+    executor.rollback_last_tx().await;
+    executor.finish_batch().await;
+
+    let events = hook.0.lock().unwrap().clone();
+    assert_eq!(
+        events,
+        [
+            HookEvent::Executed(tx_hash),
+            HookEvent::RolledBack,
+            HookEvent::Executed(tx_hash),
+        ]
+    );
+}
+
 /// Checks that incorrect transactions are marked as rejected.
 #[tokio::test]
 async fn reject_tx() {
@@ -327,6 +409,7 @@ async fn bootloader_out_of_gas_for_any_tx() {
             max_allowed_tx_gas_limit: u32::MAX,
             validation_computational_gas_limit: u32::MAX,
             upload_witness_inputs_to_gcs: false,
+            tx_execution_hooks: vec![],
         },
     );
 
@@ -364,6 +447,7 @@ async fn bootloader_tip_out_of_gas() {
         max_allowed_tx_gas_limit: u32::MAX,
         validation_computational_gas_limit: u32::MAX,
         upload_witness_inputs_to_gcs: false,
+        tx_execution_hooks: vec![],
     });
 
     let second_executor = tester.create_batch_executor().await;
@@ -371,3 +455,153 @@ async fn bootloader_tip_out_of_gas() {
     let res = second_executor.execute_tx(alice.execute()).await;
     assert_matches!(res, TxExecutionResult::BootloaderOutOfGasForTx);
 }
+
+/// Checks that protective reads recomputed by re-executing an L1 batch (as done by the asynchronous writer)
+/// match the protective reads persisted synchronously when sealing the batch.
+#[tokio::test]
+async fn async_protective_reads_match_sync_ones() {
+    let connection_pool = ConnectionPool::test_pool().await;
+    let mut alice = Account::random();
+
+    let tester = Tester::new(connection_pool.clone());
+    tester.genesis().await;
+    tester.fund(&[alice.address()]).await;
+
+    // Re-execution needs the root hash of the previous L1 batch.
+    let genesis_metadata = create_l1_batch_metadata(0);
+    let mut storage = connection_pool.access_storage().await.unwrap();
+    storage
+        .blocks_dal()
+        .save_l1_batch_metadata(L1BatchNumber(0), &genesis_metadata, H256::zero(), false)
+        .await
+        .unwrap();
+
+    let (mut l1_batch_env, system_env) = tester.default_batch_params();
+    l1_batch_env.previous_batch_hash = Some(genesis_metadata.root_hash);
+    let l2_chain_id = system_env.chain_id;
+    let mut updates_manager = UpdatesManager::new(
+        l1_batch_env.clone(),
+        system_env.base_system_smart_contracts.hashes(),
+        system_env.version,
+    );
+    let executor = tester
+        .create_batch_executor_with_env(l1_batch_env.clone(), system_env)
+        .await;
+
+    for tx in [alice.execute(), alice.execute()] {
+        let res = executor.execute_tx(tx.clone()).await;
+        let TxExecutionResult::Success {
+            tx_result,
+            tx_metrics,
+            compressed_bytecodes,
+            call_tracer_result,
+            ..
+        } = res
+        else {
+            panic!("Unexpected tx execution result: {res:?}");
+        };
+        updates_manager.extend_from_executed_transaction(
+            tx,
+            *tx_result,
+            compressed_bytecodes,
+            tx_metrics.l1_gas,
+            tx_metrics.execution_metrics,
+            call_tracer_result,
+        );
+    }
+
+    // Seal the L1 batch in the same way as the state keeper does, with protective reads persisted synchronously.
+    let miniblock_number = MiniblockNumber(l1_batch_env.first_l2_block.number);
+    updates_manager
+        .seal_miniblock_command(
+            l1_batch_env.number,
+            miniblock_number,
+            Address::default(),
+            None,
+            true,
+        )
+        .seal(&mut storage)
+        .await;
+    updates_manager.push_miniblock(MiniblockParams {
+        timestamp: l1_batch_env.timestamp + 1,
+        virtual_blocks: 1,
+    });
+    executor
+        .start_next_miniblock(updates_manager.miniblock.get_miniblock_env())
+        .await;
+    let (finished_batch, _) = executor.finish_batch().await;
+    updates_manager
+        .seal_l1_batch(
+            &mut storage,
+            miniblock_number + 1,
+            &l1_batch_env,
+            finished_batch,
+            Address::default(),
+            None,
+            true,
+            false,
+        )
+        .await;
+    let sync_protective_reads = storage
+        .storage_logs_dedup_dal()
+        .get_protective_reads_for_l1_batch(l1_batch_env.number)
+        .await;
+    drop(storage);
+    assert!(!sync_protective_reads.is_empty());
+
+    let l1_batch_number = l1_batch_env.number;
+    let async_protective_reads = tokio::task::spawn_blocking(move || {
+        compute_protective_reads(
+            Handle::current(),
+            &connection_pool,
+            l1_batch_number,
+            l2_chain_id,
+        )
+    })
+    .await
+    .unwrap()
+    .unwrap();
+    let async_protective_reads: HashSet<_> = async_protective_reads
+        .into_iter()
+        .map(|log_query| {
+            StorageKey::new(
+                AccountTreeId::new(log_query.address),
+                u256_to_h256(log_query.key),
+            )
+        })
+        .collect();
+    assert_eq!(async_protective_reads, sync_protective_reads);
+}
+
+#[test]
+fn scheduling_cache_compaction() {
+    const INTERVAL: Duration = Duration::from_secs(3_600);
+
+    let mut compaction = StateKeeperCacheCompaction::new(INTERVAL, Some((22, 4)));
+    let start = Instant::now();
+    // Outside of the off-peak window.
+    assert_eq!(compaction.next_slice_to_compact(start, 12), None);
+    assert_eq!(compaction.next_slice_to_compact(start, 4), None);
+
+    for expected_slice in 0..RocksdbStorage::COMPACTION_SLICE_COUNT / 2 {
+        assert_eq!(
+            compaction.next_slice_to_compact(start, 23),
+            Some(expected_slice)
+        );
+    }
+    // Compaction is paused outside of the window and then resumed.
+    assert_eq!(compaction.next_slice_to_compact(start, 5), None);
+    for expected_slice in
+        RocksdbStorage::COMPACTION_SLICE_COUNT / 2..RocksdbStorage::COMPACTION_SLICE_COUNT
+    {
+        assert_eq!(
+            compaction.next_slice_to_compact(start, 1),
+            Some(expected_slice)
+        );
+    }
+
+    // The next compaction cycle starts only after the interval.
+    assert_eq!(compaction.next_slice_to_compact(start, 1), None);
+    let later = start + INTERVAL;
+    assert_eq!(compaction.next_slice_to_compact(later, 1), Some(0));
+}
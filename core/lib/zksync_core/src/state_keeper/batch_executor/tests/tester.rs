@@ -1,6 +1,8 @@
 //! Testing harness for the batch executor.
 //! Contains helper functionality to initialize test context and perform tests without too much boilerplate.
 
+use std::sync::Arc;
+
 use multivm::{
     interface::{L1BatchEnv, SystemEnv},
     vm_latest::constants::INITIAL_STORAGE_WRITE_PUBDATA_BYTES,
@@ -22,7 +24,7 @@ use zksync_utils::u256_to_h256;
 use crate::{
     genesis::create_genesis_l1_batch,
     state_keeper::{
-        batch_executor::BatchExecutorHandle,
+        batch_executor::{BatchExecutorHandle, TxExecutionHook},
         tests::{default_l1_batch_env, default_system_env, BASE_SYSTEM_CONTRACTS},
     },
 };
@@ -39,6 +41,7 @@ pub(super) struct TestConfig {
     pub(super) max_allowed_tx_gas_limit: u32,
     pub(super) validation_computational_gas_limit: u32,
     pub(super) upload_witness_inputs_to_gcs: bool,
+    pub(super) tx_execution_hooks: Vec<Arc<dyn TxExecutionHook>>,
 }
 
 impl TestConfig {
@@ -51,6 +54,7 @@ impl TestConfig {
             max_allowed_tx_gas_limit: config.max_allowed_l2_tx_gas_limit,
             validation_computational_gas_limit: config.validation_computational_gas_limit,
             upload_witness_inputs_to_gcs: false,
+            tx_execution_hooks: vec![],
         }
     }
 }
@@ -86,13 +90,17 @@ impl Tester {
     /// Creates a batch executor instance.
     /// This function intentionally uses sensible defaults to not introduce boilerplate.
     pub(super) async fn create_batch_executor(&self) -> BatchExecutorHandle {
-        // Not really important for the batch executor - it operates over a single batch.
-        let (l1_batch, system_env) = self.batch_params(
-            L1BatchNumber(1),
-            100,
-            self.config.validation_computational_gas_limit,
-        );
+        let (l1_batch, system_env) = self.default_batch_params();
+        self.create_batch_executor_with_env(l1_batch, system_env)
+            .await
+    }
 
+    /// Creates a batch executor instance operating in the specified environment.
+    pub(super) async fn create_batch_executor_with_env(
+        &self,
+        l1_batch: L1BatchEnv,
+        system_env: SystemEnv,
+    ) -> BatchExecutorHandle {
         let mut secondary_storage = RocksdbStorage::new(self.db_dir.path());
         let mut conn = self
             .pool
@@ -113,6 +121,18 @@ impl Tester {
             system_env,
             self.config.upload_witness_inputs_to_gcs,
             false,
+            self.config.tx_execution_hooks.clone(),
+            None,
+        )
+    }
+
+    /// Returns batch params used by [`Self::create_batch_executor()`].
+    pub(super) fn default_batch_params(&self) -> (L1BatchEnv, SystemEnv) {
+        // Not really important for the batch executor - it operates over a single batch.
+        self.batch_params(
+            L1BatchNumber(1),
+            100,
+            self.config.validation_computational_gas_limit,
         )
     }
 
@@ -16,8 +16,8 @@ use zksync_mempool::L2TxFilter;
 use zksync_object_store::ObjectStore;
 use zksync_types::{
     block::MiniblockHeader, protocol_version::ProtocolUpgradeTx,
-    witness_block_state::WitnessBlockState, Address, L1BatchNumber, L2ChainId, MiniblockNumber,
-    ProtocolVersionId, Transaction, U256,
+    tx::tx_execution_info::ExecutionMetrics, witness_block_state::WitnessBlockState, Address,
+    L1BatchNumber, L2ChainId, MiniblockNumber, ProtocolVersionId, Transaction, U256,
 };
 // TODO (SMA-1206): use seconds instead of milliseconds.
 use zksync_utils::time::millis_since_epoch;
@@ -32,10 +32,13 @@ use crate::{
         },
         mempool_actor::l2_tx_filter,
         metrics::KEEPER_METRICS,
-        seal_criteria::{IoSealCriteria, TimeoutSealer},
+        seal_criteria::{
+            IoSealCriteria, MiniblockLimitsSealer, MiniblockSealTrigger, TimeoutSealer,
+        },
         updates::UpdatesManager,
         MempoolGuard,
     },
+    upgrade_orchestrator::ProtocolUpgradeGate,
 };
 
 /// Mempool-based IO for the state keeper.
@@ -48,6 +51,7 @@ pub(crate) struct MempoolIO {
     pool: ConnectionPool,
     object_store: Arc<dyn ObjectStore>,
     timeout_sealer: TimeoutSealer,
+    limits_sealer: MiniblockLimitsSealer,
     filter: L2TxFilter,
     current_miniblock_number: MiniblockNumber,
     miniblock_sealer_handle: MiniblockSealerHandle,
@@ -62,6 +66,9 @@ pub(crate) struct MempoolIO {
 
     virtual_blocks_interval: u32,
     virtual_blocks_per_miniblock: u32,
+    async_protective_reads: bool,
+    use_copy_for_bulk_inserts: bool,
+    upgrade_gate: Option<ProtocolUpgradeGate>,
 }
 
 impl IoSealCriteria for MempoolIO {
@@ -70,8 +77,19 @@ impl IoSealCriteria for MempoolIO {
             .should_seal_l1_batch_unconditionally(manager)
     }
 
-    fn should_seal_miniblock(&mut self, manager: &UpdatesManager) -> bool {
-        self.timeout_sealer.should_seal_miniblock(manager)
+    fn should_seal_miniblock(&mut self, manager: &UpdatesManager) -> Option<MiniblockSealTrigger> {
+        self.timeout_sealer
+            .should_seal_miniblock(manager)
+            .or_else(|| self.limits_sealer.should_seal_miniblock(manager))
+    }
+
+    fn should_seal_miniblock_before_tx(
+        &mut self,
+        manager: &UpdatesManager,
+        tx_metrics: &ExecutionMetrics,
+    ) -> Option<MiniblockSealTrigger> {
+        self.limits_sealer
+            .should_seal_miniblock_before_tx(manager, tx_metrics)
     }
 }
 
@@ -153,10 +171,31 @@ impl StateKeeperIO for MempoolIO {
                 self.filter.fee_input
             );
             let mut storage = self.pool.access_storage().await.unwrap();
-            let (base_system_contracts, protocol_version) = storage
+            let protocol_version = storage
                 .protocol_versions_dal()
-                .base_system_contracts_by_timestamp(current_timestamp)
+                .protocol_version_id_by_timestamp(current_timestamp)
                 .await;
+            if let Some(gate) = &self.upgrade_gate {
+                if !gate.is_allowed(protocol_version) {
+                    tracing::info!(
+                        "Protocol version {protocol_version:?} for L1 batch #{} is not allowed yet; \
+                         waiting for upgrade prerequisites",
+                        self.current_l1_batch_number
+                    );
+                    drop(storage);
+                    tokio::time::sleep(self.delay_interval).await;
+                    continue;
+                }
+            }
+            let base_system_contracts = storage
+                .protocol_versions_dal()
+                .load_base_system_contracts_by_version_id(protocol_version as u16)
+                .await
+                .unwrap_or_else(|| {
+                    panic!(
+                        "Missing base system contracts for protocol version {protocol_version:?}"
+                    )
+                });
 
             // We create a new filter each time, since parameters may change and a previously
             // ignored transaction in the mempool may be scheduled for the execution.
@@ -262,13 +301,14 @@ impl StateKeeperIO for MempoolIO {
     }
 
     async fn seal_miniblock(&mut self, updates_manager: &UpdatesManager) {
-        let command = updates_manager.seal_miniblock_command(
+        let mut command = updates_manager.seal_miniblock_command(
             self.current_l1_batch_number,
             self.current_miniblock_number,
             self.l2_erc20_bridge_addr,
             None,
             false,
         );
+        command.use_copy_for_bulk_inserts = self.use_copy_for_bulk_inserts;
         self.miniblock_sealer_handle.submit(command).await;
         self.current_miniblock_number += 1;
     }
@@ -318,6 +358,8 @@ impl StateKeeperIO for MempoolIO {
                 finished_batch,
                 self.l2_erc20_bridge_addr,
                 None,
+                !self.async_protective_reads,
+                self.use_copy_for_bulk_inserts,
             )
             .await;
         self.current_miniblock_number += 1; // Due to fictive miniblock being sealed.
@@ -435,6 +477,7 @@ impl MempoolIO {
             object_store,
             pool,
             timeout_sealer: TimeoutSealer::new(config),
+            limits_sealer: MiniblockLimitsSealer::new(config),
             filter: L2TxFilter::default(),
             // ^ Will be initialized properly on the first newly opened batch
             current_l1_batch_number: last_sealed_l1_batch_header.number + 1,
@@ -448,9 +491,18 @@ impl MempoolIO {
             chain_id,
             virtual_blocks_interval: config.virtual_blocks_interval,
             virtual_blocks_per_miniblock: config.virtual_blocks_per_miniblock,
+            async_protective_reads: config.async_protective_reads,
+            use_copy_for_bulk_inserts: config.use_copy_for_bulk_inserts,
+            upgrade_gate: None,
         }
     }
 
+    /// Makes the IO wait until a new protocol version is allowed by the upgrade orchestrator
+    /// before opening L1 batches with this version.
+    pub(in crate::state_keeper) fn set_upgrade_gate(&mut self, gate: ProtocolUpgradeGate) {
+        self.upgrade_gate = Some(gate);
+    }
+
     async fn load_previous_l1_batch_hash(&self) -> U256 {
         tracing::info!(
             "Getting previous L1 batch hash for L1 batch #{}",
@@ -6,6 +6,7 @@ use std::{
     time::{Duration, Instant},
 };
 
+use anyhow::Context as _;
 use itertools::Itertools;
 use multivm::{
     interface::{FinishedL1Batch, L1BatchEnv},
@@ -14,7 +15,7 @@ use multivm::{
 use zksync_dal::{blocks_dal::ConsensusBlockFields, StorageProcessor};
 use zksync_system_constants::ACCOUNT_CODE_STORAGE_ADDRESS;
 use zksync_types::{
-    block::{unpack_block_info, L1BatchHeader, MiniblockHeader},
+    block::{build_logs_bloom, unpack_block_info, L1BatchHeader, MiniblockHeader},
     event::{extract_added_tokens, extract_long_l2_to_l1_messages},
     fee_model::BatchFeeInput,
     l1::L1Tx,
@@ -48,6 +49,15 @@ impl UpdatesManager {
     /// Persists an L1 batch in the storage.
     /// This action includes a creation of an empty "fictive" miniblock that contains
     /// the events generated during the bootloader "tip phase".
+    ///
+    /// If `persist_protective_reads` is `false`, protective reads are not inserted; instead, the batch
+    /// is marked as having pending protective reads, which are expected to be persisted asynchronously
+    /// by [`ProtectiveReadsWriter`](crate::state_keeper::ProtectiveReadsWriter).
+    ///
+    /// If `use_copy_for_bulk_inserts` is set, initial writes and fictive miniblock data are inserted
+    /// using `COPY FROM STDIN`.
+    #[allow(clippy::too_many_arguments)]
+    #[tracing::instrument(skip_all, fields(l1_batch_number = %l1_batch_env.number))]
     pub(crate) async fn seal_l1_batch(
         mut self,
         storage: &mut StorageProcessor<'_>,
@@ -56,7 +66,10 @@ impl UpdatesManager {
         finished_batch: FinishedL1Batch,
         l2_erc20_bridge_addr: Address,
         consensus: Option<ConsensusBlockFields>,
+        persist_protective_reads: bool,
+        use_copy_for_bulk_inserts: bool,
     ) {
+        vlog::link_current_span(vlog::TraceKey::L1Batch(l1_batch_env.number.0));
         let started_at = Instant::now();
         let progress = L1_BATCH_METRICS.start(L1BatchSealStage::VmFinalization);
         let mut transaction = storage.start_transaction().await.unwrap();
@@ -73,13 +86,14 @@ impl UpdatesManager {
             batch_tip_execution_metrics,
         );
         // Seal fictive miniblock with last events and storage logs.
-        let miniblock_command = self.seal_miniblock_command(
+        let mut miniblock_command = self.seal_miniblock_command(
             l1_batch_env.number,
             current_miniblock_number,
             l2_erc20_bridge_addr,
             consensus,
             false, // fictive miniblocks don't have txs, so it's fine to pass `false` here.
         );
+        miniblock_command.use_copy_for_bulk_inserts = use_copy_for_bulk_inserts;
         miniblock_command.seal_inner(&mut transaction, true).await;
         progress.observe(None);
 
@@ -192,11 +206,20 @@ impl UpdatesManager {
         let (deduplicated_writes, protective_reads): (Vec<_>, Vec<_>) = deduped_log_queries
             .into_iter()
             .partition(|log_query| log_query.rw_flag);
-        transaction
-            .storage_logs_dedup_dal()
-            .insert_protective_reads(l1_batch_env.number, &protective_reads)
-            .await;
-        progress.observe(protective_reads.len());
+        if persist_protective_reads {
+            transaction
+                .storage_logs_dedup_dal()
+                .insert_protective_reads(l1_batch_env.number, &protective_reads)
+                .await;
+            progress.observe(protective_reads.len());
+        } else {
+            transaction
+                .storage_logs_dedup_dal()
+                .mark_protective_reads_as_pending(l1_batch_env.number)
+                .await
+                .unwrap();
+            progress.observe(0);
+        }
 
         let progress = L1_BATCH_METRICS.start(L1BatchSealStage::FilterWrittenSlots);
         let deduplicated_writes_hashed_keys: Vec<_> = deduplicated_writes
@@ -223,10 +246,18 @@ impl UpdatesManager {
             })
             .collect();
 
-        transaction
-            .storage_logs_dedup_dal()
-            .insert_initial_writes(l1_batch_env.number, &written_storage_keys)
-            .await;
+        if use_copy_for_bulk_inserts {
+            transaction
+                .storage_logs_dedup_dal()
+                .insert_initial_writes_via_copy(l1_batch_env.number, &written_storage_keys)
+                .await
+                .unwrap();
+        } else {
+            transaction
+                .storage_logs_dedup_dal()
+                .insert_initial_writes(l1_batch_env.number, &written_storage_keys)
+                .await;
+        }
         progress.observe(deduplicated_writes.len());
 
         let progress = L1_BATCH_METRICS.start(L1BatchSealStage::CommitL1Batch);
@@ -291,10 +322,38 @@ impl MiniblockSealCommand {
     /// one for sending fees to the operator).
     ///
     /// `l2_erc20_bridge_addr` is required to extract the information on newly added tokens.
+    #[tracing::instrument(
+        skip(self, storage),
+        fields(
+            miniblock_number = %self.miniblock_number,
+            l1_batch_number = %self.l1_batch_number,
+        )
+    )]
     async fn seal_inner(&self, storage: &mut StorageProcessor<'_>, is_fictive: bool) {
         self.assert_valid_miniblock(is_fictive);
 
+        let started_at = Instant::now();
+        // The miniblock is not sealed via `StorageProcessor::with_retries()`: most DAL methods used
+        // in `insert_miniblock_data()` panic on DB errors, so a serialization failure or deadlock would not be
+        // propagated to the retry loop anyway. The state keeper restarts and re-executes the miniblock in this case.
         let mut transaction = storage.start_transaction().await.unwrap();
+        let current_l2_virtual_block_number = self
+            .insert_miniblock_data(&mut transaction, is_fictive)
+            .await
+            .unwrap();
+        transaction.commit().await.unwrap();
+        if let Some(trigger) = self.miniblock.seal_trigger {
+            MINIBLOCK_METRICS.seal_trigger[&trigger].inc();
+        }
+        self.report_miniblock_metrics(started_at, current_l2_virtual_block_number);
+    }
+
+    /// Inserts miniblock data into the storage. Returns the current L2 virtual block number.
+    async fn insert_miniblock_data(
+        &self,
+        transaction: &mut StorageProcessor<'_>,
+        is_fictive: bool,
+    ) -> anyhow::Result<u64> {
         if self.pre_insert_txs {
             let progress = MINIBLOCK_METRICS.start(MiniblockSealStage::PreInsertTxs, is_fictive);
             for tx in &self.miniblock.executed_transactions {
@@ -326,7 +385,6 @@ impl MiniblockSealCommand {
 
         let l1_batch_number = self.l1_batch_number;
         let miniblock_number = self.miniblock_number;
-        let started_at = Instant::now();
         let progress =
             MINIBLOCK_METRICS.start(MiniblockSealStage::InsertMiniblockHeader, is_fictive);
 
@@ -358,7 +416,14 @@ impl MiniblockSealCommand {
             .blocks_dal()
             .insert_miniblock(&miniblock_header)
             .await
-            .unwrap();
+            .context("insert_miniblock()")?;
+        if let Some(trigger) = self.miniblock.seal_trigger {
+            transaction
+                .blocks_dal()
+                .set_miniblock_seal_trigger(miniblock_number, trigger.as_str())
+                .await
+                .context("set_miniblock_seal_trigger()")?;
+        }
         progress.observe(None);
 
         let progress =
@@ -383,16 +448,30 @@ impl MiniblockSealCommand {
         progress.observe(write_log_count);
 
         let progress = MINIBLOCK_METRICS.start(MiniblockSealStage::ApplyStorageLogs, is_fictive);
-        let unique_updates = transaction
-            .storage_dal()
-            .apply_storage_logs(&write_logs)
-            .await;
+        let unique_updates = if self.use_copy_for_bulk_inserts {
+            transaction
+                .storage_dal()
+                .apply_storage_logs_via_copy(&write_logs)
+                .await
+                .context("apply_storage_logs_via_copy()")?
+        } else {
+            transaction
+                .storage_dal()
+                .apply_storage_logs(&write_logs)
+                .await
+        };
         progress.observe(write_log_count);
 
         let progress = MINIBLOCK_METRICS.start(MiniblockSealStage::InsertFactoryDeps, is_fictive);
         let new_factory_deps = &self.miniblock.new_factory_deps;
         let new_factory_deps_count = new_factory_deps.len();
-        if !new_factory_deps.is_empty() {
+        if !new_factory_deps.is_empty() && self.use_copy_for_bulk_inserts {
+            transaction
+                .storage_dal()
+                .insert_factory_deps_via_copy(miniblock_number, new_factory_deps)
+                .await
+                .context("insert_factory_deps_via_copy()")?;
+        } else if !new_factory_deps.is_empty() {
             transaction
                 .storage_dal()
                 .insert_factory_deps(miniblock_number, new_factory_deps)
@@ -429,6 +508,19 @@ impl MiniblockSealCommand {
             .await;
         progress.observe(miniblock_event_count);
 
+        let progress = MINIBLOCK_METRICS.start(MiniblockSealStage::InsertLogsBloom, is_fictive);
+        let logs_bloom = build_logs_bloom(
+            miniblock_events
+                .iter()
+                .flat_map(|(_, events)| events.iter().copied()),
+        );
+        transaction
+            .blocks_dal()
+            .set_miniblock_logs_bloom(miniblock_number, &logs_bloom)
+            .await
+            .context("set_miniblock_logs_bloom()")?;
+        progress.observe(None);
+
         let progress = MINIBLOCK_METRICS.start(MiniblockSealStage::ExtractL2ToL1Logs, is_fictive);
 
         let system_l2_to_l1_logs = self.extract_system_l2_to_l1_logs(is_fictive);
@@ -460,7 +552,7 @@ impl MiniblockSealCommand {
                 .blocks_dal()
                 .set_miniblock_consensus_fields(self.miniblock_number, consensus)
                 .await
-                .unwrap();
+                .context("set_miniblock_consensus_fields()")?;
         }
         progress.observe(None);
 
@@ -475,10 +567,8 @@ impl MiniblockSealCommand {
             .unwrap_or_default();
         let (current_l2_virtual_block_number, _) =
             unpack_block_info(h256_to_u256(current_l2_virtual_block_info));
-
-        transaction.commit().await.unwrap();
         progress.observe(None);
-        self.report_miniblock_metrics(started_at, current_l2_virtual_block_number);
+        Ok(current_l2_virtual_block_number)
     }
 
     /// Performs several sanity checks to make sure that the miniblock is valid.
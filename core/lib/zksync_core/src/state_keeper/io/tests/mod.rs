@@ -16,6 +16,7 @@ use crate::{
     state_keeper::{
         io::{MiniblockParams, MiniblockSealer, StateKeeperIO},
         mempool_actor::l2_tx_filter,
+        seal_criteria::MiniblockSealTrigger,
         tests::{
             create_execution_result, create_transaction, create_updates_manager,
             default_l1_batch_env, default_vm_block_result, Query,
@@ -241,6 +242,7 @@ async fn processing_storage_logs_when_sealing_miniblock() {
         l2_erc20_bridge_addr: Address::default(),
         consensus: None,
         pre_insert_txs: false,
+        use_copy_for_bulk_inserts: false,
     };
     let mut conn = connection_pool
         .access_storage_tagged("state_keeper")
@@ -318,6 +320,7 @@ async fn processing_events_when_sealing_miniblock() {
         l2_erc20_bridge_addr: Address::default(),
         consensus: None,
         pre_insert_txs: false,
+        use_copy_for_bulk_inserts: false,
     };
     let mut conn = pool.access_storage_tagged("state_keeper").await.unwrap();
     conn.protocol_versions_dal()
@@ -375,6 +378,7 @@ async fn test_miniblock_and_l1_batch_processing(
         ExecutionMetrics::default(),
         vec![],
     );
+    updates.miniblock.seal_trigger = Some(MiniblockSealTrigger::Timeout);
     mempool.seal_miniblock(&updates).await;
     updates.push_miniblock(MiniblockParams {
         timestamp: 1,
@@ -406,6 +410,19 @@ async fn test_miniblock_and_l1_batch_processing(
         .unwrap();
     assert_eq!(l1_batch_header.l2_tx_count, 1);
     assert!(l1_batch_header.is_finished);
+    let seal_trigger = conn
+        .blocks_dal()
+        .get_miniblock_seal_trigger(MiniblockNumber(1))
+        .await
+        .unwrap();
+    assert_eq!(seal_trigger.as_deref(), Some("timeout"));
+    // Protective reads are persisted synchronously by default.
+    let protective_reads_persisted = conn
+        .storage_logs_dedup_dal()
+        .are_protective_reads_persisted(L1BatchNumber(1))
+        .await
+        .unwrap();
+    assert_eq!(protective_reads_persisted, Some(true));
 }
 
 #[tokio::test]
@@ -52,6 +52,8 @@ impl Tester {
             internal_enforced_l1_gas_price: None,
             poll_period: 10,
             max_l1_gas_price: None,
+            fee_oracle_urls: vec![],
+            fee_outlier_threshold_percent: None,
         };
 
         GasAdjuster::new(eth_client, gas_adjuster_config)
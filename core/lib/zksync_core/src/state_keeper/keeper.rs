@@ -6,6 +6,7 @@ use std::{
 use anyhow::Context as _;
 use multivm::interface::{Halt, L1BatchEnv, SystemEnv};
 use tokio::sync::watch;
+use tracing::Instrument;
 use zksync_types::{
     block::MiniblockExecutionData, l2::TransactionType, protocol_version::ProtocolUpgradeTx,
     storage_writes_deduplicator::StorageWritesDeduplicator, Transaction,
@@ -15,8 +16,8 @@ use super::{
     batch_executor::{BatchExecutorHandle, L1BatchExecutorBuilder, TxExecutionResult},
     extractors,
     io::{MiniblockParams, PendingBatchData, StateKeeperIO},
-    metrics::{AGGREGATION_METRICS, KEEPER_METRICS, L1_BATCH_METRICS},
-    seal_criteria::{ConditionalSealer, SealData, SealResolution},
+    metrics::{AGGREGATION_METRICS, KEEPER_METRICS, L1_BATCH_METRICS, MINIBLOCK_METRICS},
+    seal_criteria::{ConditionalSealer, MiniblockSealTrigger, SealData, SealResolution},
     types::ExecutionMetricsForCriteria,
     updates::UpdatesManager,
 };
@@ -167,11 +168,18 @@ impl ZkSyncStateKeeper {
         let mut l1_batch_seal_delta: Option<Instant> = None;
         while !self.is_canceled() {
             // This function will run until the batch can be sealed.
+            let l1_batch_span =
+                tracing::info_span!("process_l1_batch", l1_batch_number = %l1_batch_env.number);
+            l1_batch_span.in_scope(|| {
+                vlog::record_current_span(vlog::TraceKey::L1Batch(l1_batch_env.number.0));
+            });
             self.process_l1_batch(&batch_executor, &mut updates_manager, protocol_upgrade_tx)
+                .instrument(l1_batch_span)
                 .await?;
 
             // Finish current batch.
             if !updates_manager.miniblock.executed_transactions.is_empty() {
+                updates_manager.miniblock.seal_trigger = Some(MiniblockSealTrigger::L1Batch);
                 self.io.seal_miniblock(&updates_manager).await;
                 // We've sealed the miniblock that we had, but we still need to setup the timestamp
                 // for the fictive miniblock.
@@ -382,26 +390,14 @@ impl ZkSyncStateKeeper {
                 return Ok(());
             }
 
-            if self.io.should_seal_miniblock(updates_manager) {
+            if let Some(trigger) = self.io.should_seal_miniblock(updates_manager) {
                 tracing::debug!(
-                    "Miniblock #{} (L1 batch #{}) should be sealed as per sealing rules",
+                    "Miniblock #{} (L1 batch #{}) should be sealed as per sealing rules; trigger: {trigger:?}",
                     self.io.current_miniblock_number(),
                     self.io.current_l1_batch_number()
                 );
-                self.io.seal_miniblock(updates_manager).await;
-
-                let new_miniblock_params = self
-                    .wait_for_new_miniblock_params(updates_manager.miniblock.timestamp)
-                    .await
-                    .map_err(|e| e.context("wait_for_new_miniblock_params"))?;
-                tracing::debug!(
-                    "Initialized new miniblock #{} (L1 batch #{}) with timestamp {}",
-                    self.io.current_miniblock_number(),
-                    self.io.current_l1_batch_number(),
-                    extractors::display_timestamp(new_miniblock_params.timestamp)
-                );
-                Self::start_next_miniblock(new_miniblock_params, updates_manager, batch_executor)
-                    .await;
+                self.seal_miniblock_and_start_next(trigger, batch_executor, updates_manager)
+                    .await?;
             }
 
             let waiting_latency = KEEPER_METRICS.waiting_for_tx.start();
@@ -435,6 +431,31 @@ impl ZkSyncStateKeeper {
                         l1_gas: tx_l1_gas_this_tx,
                         execution_metrics: tx_execution_metrics,
                     } = tx_metrics;
+
+                    if let Some(trigger) = self
+                        .io
+                        .should_seal_miniblock_before_tx(updates_manager, &tx_execution_metrics)
+                    {
+                        // The transaction doesn't fit into the current miniblock; it will be re-executed
+                        // as the first transaction in the next miniblock.
+                        tracing::debug!(
+                            "Miniblock #{} (L1 batch #{}) should be sealed before transaction {tx_hash} \
+                             as per sealing rules; trigger: {trigger:?}",
+                            self.io.current_miniblock_number(),
+                            self.io.current_l1_batch_number()
+                        );
+                        MINIBLOCK_METRICS.excluded_transactions[&trigger].inc();
+                        batch_executor.rollback_last_tx().await;
+                        self.io.rollback(tx).await;
+                        self.seal_miniblock_and_start_next(
+                            trigger,
+                            batch_executor,
+                            updates_manager,
+                        )
+                        .await?;
+                        continue;
+                    }
+
                     updates_manager.extend_from_executed_transaction(
                         tx,
                         *tx_result,
@@ -466,6 +487,29 @@ impl ZkSyncStateKeeper {
         Err(Error::Canceled)
     }
 
+    async fn seal_miniblock_and_start_next(
+        &mut self,
+        trigger: MiniblockSealTrigger,
+        batch_executor: &BatchExecutorHandle,
+        updates_manager: &mut UpdatesManager,
+    ) -> Result<(), Error> {
+        updates_manager.miniblock.seal_trigger = Some(trigger);
+        self.io.seal_miniblock(updates_manager).await;
+
+        let new_miniblock_params = self
+            .wait_for_new_miniblock_params(updates_manager.miniblock.timestamp)
+            .await
+            .map_err(|e| e.context("wait_for_new_miniblock_params"))?;
+        tracing::debug!(
+            "Initialized new miniblock #{} (L1 batch #{}) with timestamp {}",
+            self.io.current_miniblock_number(),
+            self.io.current_l1_batch_number(),
+            extractors::display_timestamp(new_miniblock_params.timestamp)
+        );
+        Self::start_next_miniblock(new_miniblock_params, updates_manager, batch_executor).await;
+        Ok(())
+    }
+
     async fn process_upgrade_tx(
         &mut self,
         batch_executor: &BatchExecutorHandle,
@@ -534,12 +578,14 @@ impl ZkSyncStateKeeper {
     /// 2. Seal manager decided that batch is ready to be sealed.
     /// Note: this method doesn't mutate `updates_manager` in the end. However, reference should be mutable
     /// because we use `apply_and_rollback` method of `updates_manager.storage_writes_deduplicator`.
+    #[tracing::instrument(skip_all, fields(tx.hash = ?tx.hash()))]
     async fn process_one_tx(
         &mut self,
         batch_executor: &BatchExecutorHandle,
         updates_manager: &mut UpdatesManager,
         tx: Transaction,
     ) -> (SealResolution, TxExecutionResult) {
+        vlog::link_current_span(vlog::TraceKey::Transaction(tx.hash().0));
         let exec_result = batch_executor.execute_tx(tx.clone()).await;
         let resolution = match &exec_result {
             TxExecutionResult::BootloaderOutOfGasForTx => {
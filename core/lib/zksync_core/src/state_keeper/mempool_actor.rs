@@ -2,9 +2,9 @@ use std::{sync::Arc, time::Duration};
 
 use multivm::utils::derive_base_fee_and_gas_per_pubdata;
 use tokio::sync::watch;
-use zksync_config::configs::chain::MempoolConfig;
+use zksync_config::configs::chain::{MempoolConfig, MempoolOrdering};
 use zksync_dal::ConnectionPool;
-use zksync_mempool::L2TxFilter;
+use zksync_mempool::{FeeOrdering, L2TxFilter, MempoolOrderingPolicy};
 use zksync_types::{ProtocolVersionId, VmVersion};
 
 use super::{metrics::KEEPER_METRICS, types::MempoolGuard};
@@ -27,6 +27,18 @@ pub fn l2_tx_filter(
     }
 }
 
+/// Creates a policy used to order L2 transactions in the mempool from the configuration.
+pub fn mempool_ordering_policy(config: &MempoolConfig) -> MempoolOrderingPolicy {
+    let fee_ordering = match config.ordering.unwrap_or_default() {
+        MempoolOrdering::ReceivedAt => FeeOrdering::ReceivedAt,
+        MempoolOrdering::PriorityFee => FeeOrdering::PriorityFee,
+    };
+    MempoolOrderingPolicy {
+        fee_ordering,
+        boosted_accounts: config.boosted_accounts.iter().flatten().copied().collect(),
+    }
+}
+
 #[derive(Debug)]
 pub struct MempoolFetcher<G> {
     mempool: MempoolGuard,
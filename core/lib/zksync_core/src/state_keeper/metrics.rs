@@ -11,7 +11,7 @@ use vise::{
 };
 use zksync_mempool::MempoolStore;
 
-use super::seal_criteria::SealResolution;
+use super::seal_criteria::{MiniblockSealTrigger, SealResolution};
 use crate::metrics::InteractionType;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EncodeLabelValue, EncodeLabelSet)]
@@ -246,6 +246,7 @@ pub(super) enum MiniblockSealStage {
     InsertTokens,
     ExtractEvents,
     InsertEvents,
+    InsertLogsBloom,
     ExtractL2ToL1Logs,
     InsertL2ToL1Logs,
     InsertConsensus,
@@ -272,6 +273,11 @@ pub(super) struct MiniblockMetrics {
     /// Number of transactions in a single miniblock.
     #[metrics(buckets = Buckets::linear(0.0..=50.0, 5.0))]
     pub transactions_in_miniblock: Histogram<usize>,
+    /// Number of sealed miniblocks split by the sealing trigger.
+    pub seal_trigger: Family<MiniblockSealTrigger, Counter>,
+    /// Number of transactions moved to the next miniblock because they would make the miniblock exceed
+    /// its gas or pubdata limit, split by the exceeded limit.
+    pub excluded_transactions: Family<MiniblockSealTrigger, Counter>,
     /// Total latency of sealing a miniblock.
     #[metrics(buckets = Buckets::LATENCIES)]
     pub sealed_time: Histogram<Duration>,
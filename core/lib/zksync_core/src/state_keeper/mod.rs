@@ -1,5 +1,6 @@
 use std::sync::Arc;
 
+use anyhow::Context as _;
 use tokio::sync::watch;
 use zksync_config::{
     configs::chain::{MempoolConfig, NetworkConfig, StateKeeperConfig},
@@ -10,14 +11,22 @@ use zksync_object_store::ObjectStore;
 
 use self::io::MempoolIO;
 pub use self::{
-    batch_executor::{L1BatchExecutorBuilder, MainBatchExecutorBuilder},
+    batch_executor::{
+        ExecutedTx, L1BatchExecutorBuilder, MainBatchExecutorBuilder, TxExecutionHook,
+    },
     io::{MiniblockSealer, MiniblockSealerHandle},
     keeper::ZkSyncStateKeeper,
+    protective_reads::ProtectiveReadsWriter,
 };
 pub(crate) use self::{
-    mempool_actor::MempoolFetcher, seal_criteria::SequencerSealer, types::MempoolGuard,
+    mempool_actor::{mempool_ordering_policy, MempoolFetcher},
+    seal_criteria::SequencerSealer,
+    types::MempoolGuard,
+};
+use crate::{
+    fee_model::BatchFeeModelInputProvider, rocksdb_backup::RocksDBBackups,
+    upgrade_orchestrator::ProtocolUpgradeGate,
 };
-use crate::fee_model::BatchFeeModelInputProvider;
 
 mod batch_executor;
 pub(crate) mod extractors;
@@ -25,6 +34,7 @@ pub(crate) mod io;
 mod keeper;
 mod mempool_actor;
 pub(crate) mod metrics;
+mod protective_reads;
 pub mod seal_criteria;
 #[cfg(test)]
 pub(crate) mod tests;
@@ -43,9 +53,10 @@ pub(crate) async fn create_state_keeper(
     batch_fee_input_provider: Arc<dyn BatchFeeModelInputProvider>,
     miniblock_sealer_handle: MiniblockSealerHandle,
     object_store: Arc<dyn ObjectStore>,
+    upgrade_gate: Option<ProtocolUpgradeGate>,
     stop_receiver: watch::Receiver<bool>,
-) -> ZkSyncStateKeeper {
-    let batch_executor_base = MainBatchExecutorBuilder::new(
+) -> anyhow::Result<ZkSyncStateKeeper> {
+    let mut batch_executor_base = MainBatchExecutorBuilder::new(
         db_config.state_keeper_db_path.clone(),
         pool.clone(),
         state_keeper_config.max_allowed_l2_tx_gas_limit.into(),
@@ -54,8 +65,32 @@ pub(crate) async fn create_state_keeper(
         state_keeper_config.enum_index_migration_chunk_size(),
         false,
     );
+    if let Some(interval) = db_config.rocksdb_backup_interval() {
+        let backups = RocksDBBackups::new(
+            object_store.clone(),
+            "state_keeper",
+            &db_config.state_keeper_db_path,
+        );
+        // The state keeper cache can always be rebuilt from Postgres, so restoration errors are not fatal.
+        if let Err(err) = backups.restore_if_needed().await {
+            tracing::warn!("Failed restoring state keeper cache from backup: {err:#}");
+        }
+        batch_executor_base = batch_executor_base.with_backups(backups, interval);
+    }
+    if let Some(l1_batch_count) = db_config.state_keeper_cache_warm_up_l1_batches {
+        batch_executor_base = batch_executor_base.with_cache_warm_up(l1_batch_count);
+    }
+    if let Some(interval) = db_config.state_keeper_cache_compaction_interval() {
+        let window = db_config
+            .state_keeper_cache_compaction_window()
+            .context("invalid state keeper cache compaction window")?;
+        batch_executor_base = batch_executor_base.with_cache_compaction(interval, window);
+    }
+    if let Some(capacity) = db_config.state_keeper_overlay_cache_size() {
+        batch_executor_base = batch_executor_base.with_storage_overlay_cache(capacity as u64);
+    }
 
-    let io = MempoolIO::new(
+    let mut io = MempoolIO::new(
         mempool,
         object_store,
         miniblock_sealer_handle,
@@ -68,12 +103,15 @@ pub(crate) async fn create_state_keeper(
         network_config.zksync_network_id,
     )
     .await;
+    if let Some(gate) = upgrade_gate {
+        io.set_upgrade_gate(gate);
+    }
 
     let sealer = SequencerSealer::new(state_keeper_config);
-    ZkSyncStateKeeper::new(
+    Ok(ZkSyncStateKeeper::new(
         stop_receiver,
         Box::new(io),
         Box::new(batch_executor_base),
         Box::new(sealer),
-    )
+    ))
 }
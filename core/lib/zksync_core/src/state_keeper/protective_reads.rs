@@ -0,0 +1,135 @@
+//! Asynchronous persistence of protective reads for sealed L1 batches.
+
+use std::time::{Duration, Instant};
+
+use anyhow::Context as _;
+use tokio::{runtime::Handle, sync::watch};
+use zksync_dal::ConnectionPool;
+use zksync_types::{
+    zkevm_test_harness::witness::sort_storage_access::sort_storage_access_queries, L1BatchNumber,
+    L2ChainId, LogQuery,
+};
+
+use crate::basic_witness_input_producer::vm_interactions::execute_l1_batch;
+
+/// Persists protective reads for L1 batches sealed with asynchronous protective reads enabled
+/// (see [`StateKeeperConfig::async_protective_reads`](zksync_config::configs::chain::StateKeeperConfig)).
+///
+/// The writer uses the `protective_reads_persisted` flag of L1 batches as its cursor: it picks the earliest
+/// L1 batch with pending protective reads, recomputes them by re-executing the batch in the VM on top
+/// of the Postgres state, and persists them together with flipping the flag in a single DB transaction.
+/// Since the cursor is stored in Postgres, the writer naturally resumes after a restart.
+#[derive(Debug)]
+pub struct ProtectiveReadsWriter {
+    pool: ConnectionPool,
+    l2_chain_id: L2ChainId,
+    poll_interval: Duration,
+}
+
+impl ProtectiveReadsWriter {
+    const DEFAULT_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+    /// Creates a new writer. The pool should have at least 2 connections.
+    pub fn new(pool: ConnectionPool, l2_chain_id: L2ChainId) -> Self {
+        Self {
+            pool,
+            l2_chain_id,
+            poll_interval: Self::DEFAULT_POLL_INTERVAL,
+        }
+    }
+
+    pub async fn run(self, mut stop_receiver: watch::Receiver<bool>) -> anyhow::Result<()> {
+        loop {
+            if *stop_receiver.borrow() {
+                tracing::info!("Stop signal received, protective reads writer is shutting down");
+                return Ok(());
+            }
+
+            let mut storage = self
+                .pool
+                .access_storage_tagged("protective_reads_writer")
+                .await?;
+            let next_l1_batch = storage
+                .storage_logs_dedup_dal()
+                .get_next_l1_batch_with_pending_protective_reads()
+                .await?;
+            drop(storage);
+
+            if let Some(l1_batch_number) = next_l1_batch {
+                self.process_l1_batch(l1_batch_number).await?;
+            } else {
+                tokio::time::timeout(self.poll_interval, stop_receiver.changed())
+                    .await
+                    .ok();
+            }
+        }
+    }
+
+    async fn process_l1_batch(&self, l1_batch_number: L1BatchNumber) -> anyhow::Result<()> {
+        let started_at = Instant::now();
+        let pool = self.pool.clone();
+        let l2_chain_id = self.l2_chain_id;
+        let protective_reads = tokio::task::spawn_blocking(move || {
+            compute_protective_reads(Handle::current(), &pool, l1_batch_number, l2_chain_id)
+        })
+        .await
+        .context("protective reads computation panicked")??;
+
+        let mut storage = self
+            .pool
+            .access_storage_tagged("protective_reads_writer")
+            .await?;
+        let mut transaction = storage.start_transaction().await?;
+        transaction
+            .storage_logs_dedup_dal()
+            .insert_protective_reads(l1_batch_number, &protective_reads)
+            .await;
+        transaction
+            .storage_logs_dedup_dal()
+            .mark_protective_reads_as_persisted(l1_batch_number)
+            .await?;
+        transaction.commit().await?;
+
+        tracing::info!(
+            "Persisted {} protective reads for L1 batch #{l1_batch_number} in {:?}",
+            protective_reads.len(),
+            started_at.elapsed()
+        );
+        Ok(())
+    }
+}
+
+pub(super) fn compute_protective_reads(
+    rt_handle: Handle,
+    pool: &ConnectionPool,
+    l1_batch_number: L1BatchNumber,
+    l2_chain_id: L2ChainId,
+) -> anyhow::Result<Vec<LogQuery>> {
+    let mut storage = rt_handle
+        .block_on(pool.access_storage_tagged("protective_reads_writer"))
+        .context("failed to get connection")?;
+    let vm_connection = rt_handle
+        .block_on(pool.access_storage_tagged("protective_reads_writer"))
+        .context("failed to get connection for VM")?;
+    let (finished_batch, _) = execute_l1_batch(
+        rt_handle,
+        l1_batch_number,
+        &mut storage,
+        vm_connection,
+        l2_chain_id,
+    )
+    .with_context(|| format!("failed re-executing L1 batch #{l1_batch_number}"))?;
+
+    // Deduplication must be performed in the same way as during L1 batch sealing.
+    let (_, deduped_log_queries) = sort_storage_access_queries(
+        finished_batch
+            .final_execution_state
+            .storage_log_queries
+            .iter()
+            .map(|log| &log.log_query),
+    );
+    Ok(deduped_log_queries
+        .into_iter()
+        .filter(|log_query| !log_query.rw_flag)
+        .collect())
+}
@@ -115,11 +115,30 @@ impl ConditionalSealer for SequencerSealer {
 }
 
 impl SequencerSealer {
-    pub(crate) fn new(config: StateKeeperConfig) -> Self {
-        let sealers = Self::default_sealers();
+    /// Built-in criteria that cannot be disabled in the config. They enforce bootloader, circuit and pubdata limits;
+    /// an L1 batch violating any of these limits cannot be proven.
+    pub const MANDATORY_CRITERIA: &'static [&'static str] =
+        &["slots", "pub_data_size", "circuits", "tx_encoding_size"];
+
+    /// Creates a sealer with the built-in criteria enabled in [`StateKeeperConfig::seal_criteria`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the config references an unknown seal criterion, or doesn't enable one of
+    /// the [mandatory criteria](Self::MANDATORY_CRITERIA).
+    pub fn new(config: StateKeeperConfig) -> Self {
+        let sealers = Self::default_sealers(config.seal_criteria.as_deref());
         Self { config, sealers }
     }
 
+    /// Adds a custom seal criterion to this sealer. The criterion is checked after the built-in ones;
+    /// seal decisions made by it are reported in metrics under [`SealCriterion::prom_criterion_name()`].
+    #[must_use]
+    pub fn with_criterion(mut self, criterion: impl SealCriterion) -> Self {
+        self.sealers.push(Box::new(criterion));
+        self
+    }
+
     #[cfg(test)]
     pub(in crate::state_keeper) fn with_sealers(
         config: StateKeeperConfig,
@@ -128,14 +147,46 @@ impl SequencerSealer {
         Self { config, sealers }
     }
 
-    fn default_sealers() -> Vec<Box<dyn SealCriterion>> {
-        vec![
+    fn default_sealers(enabled_names: Option<&[String]>) -> Vec<Box<dyn SealCriterion>> {
+        let sealers: Vec<Box<dyn SealCriterion>> = vec![
             Box::new(criteria::SlotsCriterion),
             Box::new(criteria::GasCriterion),
             Box::new(criteria::PubDataBytesCriterion),
             Box::new(criteria::CircuitsCriterion),
             Box::new(criteria::TxEncodingSizeCriterion),
-        ]
+        ];
+        let Some(enabled_names) = enabled_names else {
+            return sealers;
+        };
+
+        for name in enabled_names {
+            let is_known = sealers
+                .iter()
+                .any(|sealer| sealer.prom_criterion_name() == name);
+            assert!(
+                is_known,
+                "Unknown seal criterion `{name}` in state keeper config; known criteria: {:?}",
+                sealers
+                    .iter()
+                    .map(|sealer| sealer.prom_criterion_name())
+                    .collect::<Vec<_>>()
+            );
+        }
+        for &name in Self::MANDATORY_CRITERIA {
+            assert!(
+                enabled_names.iter().any(|enabled_name| enabled_name == name),
+                "Mandatory seal criterion `{name}` is disabled in state keeper config; mandatory criteria: {:?}",
+                Self::MANDATORY_CRITERIA
+            );
+        }
+        sealers
+            .into_iter()
+            .filter(|sealer| {
+                enabled_names
+                    .iter()
+                    .any(|name| name == sealer.prom_criterion_name())
+            })
+            .collect()
     }
 }
 
@@ -166,3 +217,122 @@ impl ConditionalSealer for NoopSealer {
         SealResolution::NoSeal
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sequencer_sealer_uses_all_criteria_by_default() {
+        let sealer = SequencerSealer::new(StateKeeperConfig::for_tests());
+        let names: Vec<_> = sealer
+            .sealers
+            .iter()
+            .map(|sealer| sealer.prom_criterion_name())
+            .collect();
+        assert_eq!(
+            names,
+            [
+                "slots",
+                "gas",
+                "pub_data_size",
+                "circuits",
+                "tx_encoding_size"
+            ]
+        );
+    }
+
+    #[test]
+    fn sequencer_sealer_with_configured_criteria() {
+        let seal_criteria = SequencerSealer::MANDATORY_CRITERIA
+            .iter()
+            .map(|&name| name.to_owned())
+            .collect();
+        let config = StateKeeperConfig {
+            transaction_slots: 2,
+            seal_criteria: Some(seal_criteria),
+            ..StateKeeperConfig::for_tests()
+        };
+        let sealer = SequencerSealer::new(config);
+        let names: Vec<_> = sealer
+            .sealers
+            .iter()
+            .map(|sealer| sealer.prom_criterion_name())
+            .collect();
+        assert_eq!(
+            names,
+            ["slots", "pub_data_size", "circuits", "tx_encoding_size"]
+        );
+
+        let resolution = sealer.should_seal_l1_batch(
+            1,
+            0,
+            2,
+            &SealData::default(),
+            &SealData::default(),
+            ProtocolVersionId::latest(),
+        );
+        assert_eq!(resolution, SealResolution::IncludeAndSeal);
+    }
+
+    #[test]
+    #[should_panic(expected = "Unknown seal criterion")]
+    fn sequencer_sealer_with_unknown_criterion() {
+        let config = StateKeeperConfig {
+            seal_criteria: Some(vec!["unknown".to_owned()]),
+            ..StateKeeperConfig::for_tests()
+        };
+        SequencerSealer::new(config);
+    }
+
+    #[test]
+    #[should_panic(expected = "Mandatory seal criterion `circuits` is disabled")]
+    fn sequencer_sealer_with_disabled_mandatory_criterion() {
+        let config = StateKeeperConfig {
+            seal_criteria: Some(vec![
+                "slots".to_owned(),
+                "gas".to_owned(),
+                "pub_data_size".to_owned(),
+                "tx_encoding_size".to_owned(),
+            ]),
+            ..StateKeeperConfig::for_tests()
+        };
+        SequencerSealer::new(config);
+    }
+
+    #[derive(Debug)]
+    struct AlwaysSealCriterion;
+
+    impl SealCriterion for AlwaysSealCriterion {
+        fn should_seal(
+            &self,
+            _config: &StateKeeperConfig,
+            _block_open_timestamp_ms: u128,
+            _tx_count: usize,
+            _block_data: &SealData,
+            _tx_data: &SealData,
+            _protocol_version: ProtocolVersionId,
+        ) -> SealResolution {
+            SealResolution::IncludeAndSeal
+        }
+
+        fn prom_criterion_name(&self) -> &'static str {
+            "always"
+        }
+    }
+
+    #[test]
+    fn sequencer_sealer_with_custom_criterion() {
+        let sealer = SequencerSealer::new(StateKeeperConfig::for_tests())
+            .with_criterion(AlwaysSealCriterion);
+        let resolution = sealer.should_seal_l1_batch(
+            1,
+            0,
+            1,
+            &SealData::default(),
+            &SealData::default(),
+            ProtocolVersionId::latest(),
+        );
+        assert_eq!(resolution, SealResolution::IncludeAndSeal);
+    }
+}
@@ -1,4 +1,6 @@
-use zksync_types::{ProtocolVersionId, MAX_PUBDATA_PER_L1_BATCH};
+use zksync_types::{
+    commitment::L1BatchCommitmentMode, ProtocolVersionId, MAX_PUBDATA_PER_L1_BATCH,
+};
 
 use crate::state_keeper::seal_criteria::{
     SealCriterion, SealData, SealResolution, StateKeeperConfig,
@@ -17,7 +19,12 @@ impl SealCriterion for PubDataBytesCriterion {
         tx_data: &SealData,
         protocol_version: ProtocolVersionId,
     ) -> SealResolution {
-        let max_pubdata_per_l1_batch = MAX_PUBDATA_PER_L1_BATCH as usize;
+        let max_pubdata_per_l1_batch = match config.l1_batch_commitment_mode {
+            L1BatchCommitmentMode::Rollup => MAX_PUBDATA_PER_L1_BATCH as usize,
+            // Pubdata is not published on L1 in the validium mode, so it's only bounded by the configured limit
+            // (which is also used by the fee model).
+            L1BatchCommitmentMode::Validium => config.max_pubdata_per_batch as usize,
+        };
         let reject_bound =
             (max_pubdata_per_l1_batch as f64 * config.reject_tx_at_eth_params_percentage).round();
         let include_and_seal_bound =
@@ -126,4 +133,51 @@ mod tests {
         );
         assert_eq!(full_block_resolution, SealResolution::ExcludeAndSeal);
     }
+
+    #[test]
+    fn seal_criterion_in_validium_mode() {
+        let config = StateKeeperConfig {
+            reject_tx_at_eth_params_percentage: 0.95,
+            close_block_at_eth_params_percentage: 0.95,
+            max_pubdata_per_batch: 500_000,
+            l1_batch_commitment_mode: L1BatchCommitmentMode::Validium,
+            ..Default::default()
+        };
+        let criterion = PubDataBytesCriterion;
+
+        // This block would be sealed in the rollup mode.
+        let block_execution_metrics = ExecutionMetrics {
+            l2_l1_long_messages: MAX_PUBDATA_PER_L1_BATCH as usize + 1,
+            ..ExecutionMetrics::default()
+        };
+        let resolution = criterion.should_seal(
+            &config,
+            0,
+            0,
+            &SealData {
+                execution_metrics: block_execution_metrics,
+                ..SealData::default()
+            },
+            &SealData::default(),
+            ProtocolVersionId::latest(),
+        );
+        assert_eq!(resolution, SealResolution::NoSeal);
+
+        let block_execution_metrics = ExecutionMetrics {
+            l2_l1_long_messages: 500_001,
+            ..ExecutionMetrics::default()
+        };
+        let resolution = criterion.should_seal(
+            &config,
+            0,
+            0,
+            &SealData {
+                execution_metrics: block_execution_metrics,
+                ..SealData::default()
+            },
+            &SealData::default(),
+            ProtocolVersionId::latest(),
+        );
+        assert_eq!(resolution, SealResolution::ExcludeAndSeal);
+    }
 }
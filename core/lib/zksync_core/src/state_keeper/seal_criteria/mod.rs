@@ -13,6 +13,7 @@
 use std::fmt;
 
 use multivm::vm_latest::TransactionVmExt;
+use vise::{EncodeLabelSet, EncodeLabelValue};
 use zksync_config::configs::chain::StateKeeperConfig;
 use zksync_types::{
     block::BlockGasCount,
@@ -102,9 +103,32 @@ impl SealData {
             writes_metrics,
         }
     }
+
+    /// Returns VM execution metrics.
+    pub fn execution_metrics(&self) -> &ExecutionMetrics {
+        &self.execution_metrics
+    }
+
+    /// Returns gas consumed on L1 for committing, proving and executing.
+    pub fn gas_count(&self) -> &BlockGasCount {
+        &self.gas_count
+    }
+
+    /// Returns cumulative bootloader encoding size of transactions in bytes.
+    pub fn cumulative_size(&self) -> usize {
+        self.cumulative_size
+    }
+
+    /// Returns metrics for deduplicated storage writes.
+    pub fn writes_metrics(&self) -> &DeduplicatedWritesMetrics {
+        &self.writes_metrics
+    }
 }
 
-pub(super) trait SealCriterion: fmt::Debug + Send + Sync + 'static {
+/// Deterministic criterion deciding whether an L1 batch should be sealed after executing a transaction.
+///
+/// Besides built-in criteria, custom ones can be added to the sequencer via [`SequencerSealer::with_criterion()`].
+pub trait SealCriterion: fmt::Debug + Send + Sync + 'static {
     fn should_seal(
         &self,
         config: &StateKeeperConfig,
@@ -115,18 +139,61 @@ pub(super) trait SealCriterion: fmt::Debug + Send + Sync + 'static {
         protocol_version: ProtocolVersionId,
     ) -> SealResolution;
 
+    /// Name of the criterion used in metrics and in the state keeper config.
     // We need self here only for rust restrictions for creating an object from trait
     // https://doc.rust-lang.org/reference/items/traits.html#object-safety
     fn prom_criterion_name(&self) -> &'static str;
 }
 
+/// Reason for sealing a miniblock.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EncodeLabelValue, EncodeLabelSet)]
+#[metrics(label = "trigger", rename_all = "snake_case")]
+pub enum MiniblockSealTrigger {
+    /// Miniblock commit deadline has passed.
+    Timeout,
+    /// Execution gas used by miniblock transactions has reached the configured limit.
+    Gas,
+    /// Pubdata produced by miniblock transactions has reached the configured limit.
+    Pubdata,
+    /// Miniblock is sealed because the L1 batch it belongs to is sealed.
+    L1Batch,
+    /// Miniblock is sealed as instructed by the main node (used on external nodes).
+    MainNode,
+}
+
+impl MiniblockSealTrigger {
+    /// Returns the string representation of this trigger persisted in Postgres.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Timeout => "timeout",
+            Self::Gas => "gas",
+            Self::Pubdata => "pubdata",
+            Self::L1Batch => "l1_batch",
+            Self::MainNode => "main_node",
+        }
+    }
+}
+
 /// I/O-dependent seal criteria.
 pub trait IoSealCriteria {
     /// Checks whether an L1 batch should be sealed unconditionally (i.e., regardless of metrics
     /// related to transaction execution) given the provided `manager` state.
     fn should_seal_l1_batch_unconditionally(&mut self, manager: &UpdatesManager) -> bool;
     /// Checks whether a miniblock should be sealed given the provided `manager` state.
-    fn should_seal_miniblock(&mut self, manager: &UpdatesManager) -> bool;
+    /// Returns the reason for sealing, or `None` if the miniblock shouldn't be sealed.
+    fn should_seal_miniblock(&mut self, manager: &UpdatesManager) -> Option<MiniblockSealTrigger>;
+
+    /// Checks whether a miniblock should be sealed *before* including an executed transaction with the specified
+    /// metrics into it, i.e., whether the transaction should be rolled back and become the first transaction
+    /// in the next miniblock. Returns the reason for sealing, or `None` if the transaction can be included
+    /// into the current miniblock.
+    fn should_seal_miniblock_before_tx(
+        &mut self,
+        _manager: &UpdatesManager,
+        _tx_metrics: &ExecutionMetrics,
+    ) -> Option<MiniblockSealTrigger> {
+        None
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -169,9 +236,66 @@ impl IoSealCriteria for TimeoutSealer {
         should_seal_timeout
     }
 
-    fn should_seal_miniblock(&mut self, manager: &UpdatesManager) -> bool {
-        !manager.miniblock.executed_transactions.is_empty()
-            && millis_since(manager.miniblock.timestamp) > self.miniblock_commit_deadline_ms
+    fn should_seal_miniblock(&mut self, manager: &UpdatesManager) -> Option<MiniblockSealTrigger> {
+        let should_seal = !manager.miniblock.executed_transactions.is_empty()
+            && millis_since(manager.miniblock.timestamp) > self.miniblock_commit_deadline_ms;
+        should_seal.then_some(MiniblockSealTrigger::Timeout)
+    }
+}
+
+/// Enforces limits on the execution gas and pubdata produced by miniblock transactions. A transaction
+/// that would make the miniblock exceed a limit is excluded from it and becomes the first transaction
+/// in the next miniblock, so a miniblock can only exceed a limit if it consists of a single transaction.
+#[derive(Debug, Clone, Copy)]
+pub(super) struct MiniblockLimitsSealer {
+    max_gas: Option<u64>,
+    max_pubdata_bytes: Option<usize>,
+}
+
+impl MiniblockLimitsSealer {
+    pub fn new(config: &StateKeeperConfig) -> Self {
+        Self {
+            max_gas: config.miniblock_max_gas,
+            max_pubdata_bytes: config.miniblock_max_pubdata_bytes,
+        }
+    }
+
+    /// Checks whether the miniblock has reached a limit, so that no more transactions can be included into it.
+    pub fn should_seal_miniblock(&self, manager: &UpdatesManager) -> Option<MiniblockSealTrigger> {
+        let metrics = &manager.miniblock.block_execution_metrics;
+        self.check_limits(metrics, |value, limit| value >= limit)
+    }
+
+    /// Checks whether including a transaction with the specified metrics would make the miniblock exceed a limit.
+    /// A transaction is always included into an empty miniblock, since it wouldn't fit into any other miniblock either.
+    pub fn should_seal_miniblock_before_tx(
+        &self,
+        manager: &UpdatesManager,
+        tx_metrics: &ExecutionMetrics,
+    ) -> Option<MiniblockSealTrigger> {
+        if manager.miniblock.executed_transactions.is_empty() {
+            return None;
+        }
+        let metrics = manager.miniblock.block_execution_metrics + *tx_metrics;
+        self.check_limits(&metrics, |value, limit| value > limit)
+    }
+
+    fn check_limits(
+        &self,
+        metrics: &ExecutionMetrics,
+        is_limit_reached: impl Fn(u64, u64) -> bool,
+    ) -> Option<MiniblockSealTrigger> {
+        if let Some(max_gas) = self.max_gas {
+            if is_limit_reached(metrics.gas_used as u64, max_gas) {
+                return Some(MiniblockSealTrigger::Gas);
+            }
+        }
+        if let Some(max_pubdata_bytes) = self.max_pubdata_bytes {
+            if is_limit_reached(metrics.pubdata_published.into(), max_pubdata_bytes as u64) {
+                return Some(MiniblockSealTrigger::Pubdata);
+            }
+        }
+        None
     }
 }
 
@@ -207,15 +331,17 @@ mod tests {
         let mut manager = create_updates_manager();
         // Empty miniblock should not trigger.
         manager.miniblock.timestamp = seconds_since_epoch() - 10;
-        assert!(
-            !timeout_miniblock_sealer.should_seal_miniblock(&manager),
+        assert_eq!(
+            timeout_miniblock_sealer.should_seal_miniblock(&manager),
+            None,
             "Empty miniblock shouldn't be sealed"
         );
 
         // Non-empty miniblock should trigger.
         apply_tx_to_manager(&mut manager);
-        assert!(
+        assert_eq!(
             timeout_miniblock_sealer.should_seal_miniblock(&manager),
+            Some(MiniblockSealTrigger::Timeout),
             "Non-empty miniblock with old timestamp should be sealed"
         );
 
@@ -223,9 +349,90 @@ mod tests {
         // for more than 10 seconds (while the test itself is trivial, it may be preempted
         // by other tests).
         manager.miniblock.timestamp = seconds_since_epoch();
-        assert!(
-            !timeout_miniblock_sealer.should_seal_miniblock(&manager),
+        assert_eq!(
+            timeout_miniblock_sealer.should_seal_miniblock(&manager),
+            None,
             "Non-empty miniblock with too recent timestamp shouldn't be sealed"
         );
     }
+
+    #[test]
+    fn limits_miniblock_sealer() {
+        let sealer = MiniblockLimitsSealer {
+            max_gas: Some(1_000),
+            max_pubdata_bytes: Some(100),
+        };
+        let mut manager = create_updates_manager();
+        assert_eq!(sealer.should_seal_miniblock(&manager), None);
+
+        manager.miniblock.block_execution_metrics.gas_used = 999;
+        manager.miniblock.block_execution_metrics.pubdata_published = 99;
+        assert_eq!(sealer.should_seal_miniblock(&manager), None);
+
+        manager.miniblock.block_execution_metrics.pubdata_published = 100;
+        assert_eq!(
+            sealer.should_seal_miniblock(&manager),
+            Some(MiniblockSealTrigger::Pubdata)
+        );
+        manager.miniblock.block_execution_metrics.gas_used = 1_000;
+        assert_eq!(
+            sealer.should_seal_miniblock(&manager),
+            Some(MiniblockSealTrigger::Gas)
+        );
+
+        let sealer = MiniblockLimitsSealer {
+            max_gas: None,
+            max_pubdata_bytes: None,
+        };
+        assert_eq!(sealer.should_seal_miniblock(&manager), None);
+    }
+
+    #[test]
+    fn limits_miniblock_sealer_excludes_tx_exceeding_limits() {
+        let sealer = MiniblockLimitsSealer {
+            max_gas: Some(1_000),
+            max_pubdata_bytes: Some(100),
+        };
+        let tx_metrics = ExecutionMetrics {
+            gas_used: 600,
+            pubdata_published: 10,
+            ..ExecutionMetrics::default()
+        };
+        let mut manager = create_updates_manager();
+        // A transaction exceeding the limits is included into an empty miniblock.
+        let huge_tx_metrics = ExecutionMetrics {
+            gas_used: 10_000,
+            pubdata_published: 1_000,
+            ..ExecutionMetrics::default()
+        };
+        assert_eq!(
+            sealer.should_seal_miniblock_before_tx(&manager, &huge_tx_metrics),
+            None
+        );
+
+        apply_tx_to_manager(&mut manager);
+        manager.miniblock.block_execution_metrics = tx_metrics;
+        assert_eq!(
+            sealer.should_seal_miniblock_before_tx(&manager, &tx_metrics),
+            Some(MiniblockSealTrigger::Gas)
+        );
+        let small_tx_metrics = ExecutionMetrics {
+            gas_used: 400,
+            pubdata_published: 90,
+            ..ExecutionMetrics::default()
+        };
+        assert_eq!(
+            sealer.should_seal_miniblock_before_tx(&manager, &small_tx_metrics),
+            None
+        );
+        let pubdata_heavy_tx_metrics = ExecutionMetrics {
+            gas_used: 1,
+            pubdata_published: 91,
+            ..ExecutionMetrics::default()
+        };
+        assert_eq!(
+            sealer.should_seal_miniblock_before_tx(&manager, &pubdata_heavy_tx_metrics),
+            Some(MiniblockSealTrigger::Pubdata)
+        );
+    }
 }
@@ -39,7 +39,7 @@ use crate::{
         keeper::POLL_WAIT_DURATION,
         seal_criteria::{
             criteria::{GasCriterion, SlotsCriterion},
-            SequencerSealer,
+            MiniblockSealTrigger, SequencerSealer,
         },
         types::ExecutionMetricsForCriteria,
         updates::UpdatesManager,
@@ -384,6 +384,55 @@ async fn bootloader_tip_out_of_gas_flow() {
         .await;
 }
 
+#[tokio::test]
+async fn miniblock_sealed_before_tx_exceeding_limits() {
+    const MAX_MINIBLOCK_GAS: usize = 1_000;
+
+    let config = StateKeeperConfig {
+        transaction_slots: 2,
+        ..StateKeeperConfig::default()
+    };
+    let sealer = SequencerSealer::with_sealers(config, vec![Box::new(SlotsCriterion)]);
+
+    let execution_result = successful_exec_with_metrics(ExecutionMetricsForCriteria {
+        l1_gas: BlockGasCount::default(),
+        execution_metrics: ExecutionMetrics {
+            gas_used: 600,
+            ..ExecutionMetrics::default()
+        },
+    });
+    let second_tx = random_tx(2);
+    TestScenario::new()
+        .seal_miniblock_before_tx_when(|updates, tx_metrics| {
+            let gas_used = updates.miniblock.block_execution_metrics.gas_used;
+            !updates.miniblock.executed_transactions.is_empty()
+                && gas_used + tx_metrics.gas_used > MAX_MINIBLOCK_GAS
+        })
+        .next_tx("First tx", random_tx(1), execution_result.clone())
+        .next_tx(
+            "Tx exceeding miniblock limits",
+            second_tx.clone(),
+            execution_result.clone(),
+        )
+        .tx_rollback("Tx rolled back to seal the miniblock", second_tx.clone())
+        .miniblock_sealed_with("Miniblock with 1st tx", |updates| {
+            assert_eq!(updates.miniblock.executed_transactions.len(), 1);
+            assert_eq!(
+                updates.miniblock.seal_trigger,
+                Some(MiniblockSealTrigger::Gas)
+            );
+        })
+        .next_tx(
+            "Same tx is included into the next miniblock",
+            second_tx,
+            execution_result,
+        )
+        .miniblock_sealed("Miniblock with 2nd tx")
+        .batch_sealed("Batch with 2 txs")
+        .run(sealer)
+        .await;
+}
+
 #[tokio::test]
 async fn pending_batch_is_applied() {
     let config = StateKeeperConfig {
@@ -16,15 +16,15 @@ use multivm::{
 use tokio::sync::{mpsc, watch};
 use zksync_types::{
     block::MiniblockExecutionData, fee_model::BatchFeeInput, protocol_version::ProtocolUpgradeTx,
-    witness_block_state::WitnessBlockState, Address, L1BatchNumber, L2ChainId, MiniblockNumber,
-    ProtocolVersionId, Transaction, H256,
+    tx::tx_execution_info::ExecutionMetrics, witness_block_state::WitnessBlockState, Address,
+    L1BatchNumber, L2ChainId, MiniblockNumber, ProtocolVersionId, Transaction, H256,
 };
 
 use crate::{
     state_keeper::{
         batch_executor::{BatchExecutorHandle, Command, L1BatchExecutorBuilder, TxExecutionResult},
         io::{MiniblockParams, PendingBatchData, StateKeeperIO},
-        seal_criteria::{IoSealCriteria, SequencerSealer},
+        seal_criteria::{IoSealCriteria, MiniblockSealTrigger, SequencerSealer},
         tests::{default_l1_batch_env, default_vm_block_result, BASE_SYSTEM_CONTRACTS},
         types::ExecutionMetricsForCriteria,
         updates::UpdatesManager,
@@ -52,9 +52,11 @@ pub(crate) struct TestScenario {
     pending_batch: Option<PendingBatchData>,
     l1_batch_seal_fn: Box<SealFn>,
     miniblock_seal_fn: Box<SealFn>,
+    miniblock_seal_before_tx_fn: Box<SealBeforeTxFn>,
 }
 
 type SealFn = dyn FnMut(&UpdatesManager) -> bool + Send;
+type SealBeforeTxFn = dyn FnMut(&UpdatesManager, &ExecutionMetrics) -> bool + Send;
 
 impl fmt::Debug for TestScenario {
     fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -73,6 +75,7 @@ impl TestScenario {
             pending_batch: None,
             l1_batch_seal_fn: Box::new(|_| false),
             miniblock_seal_fn: Box::new(|_| false),
+            miniblock_seal_before_tx_fn: Box::new(|_, _| false),
         }
     }
 
@@ -188,6 +191,16 @@ impl TestScenario {
         self
     }
 
+    /// Configures the scenario to seal miniblocks *before* including a transaction, i.e., to exclude
+    /// the transaction and re-execute it in the next miniblock.
+    pub(crate) fn seal_miniblock_before_tx_when<F>(mut self, seal_fn: F) -> Self
+    where
+        F: FnMut(&UpdatesManager, &ExecutionMetrics) -> bool + Send + 'static,
+    {
+        self.miniblock_seal_before_tx_fn = Box::new(seal_fn);
+        self
+    }
+
     /// Launches the test.
     /// Provided `SealManager` is expected to be externally configured to adhere the written scenario logic.
     pub(crate) async fn run(self, sealer: SequencerSealer) {
@@ -608,8 +621,17 @@ impl IoSealCriteria for TestIO {
         (self.scenario.l1_batch_seal_fn)(manager)
     }
 
-    fn should_seal_miniblock(&mut self, manager: &UpdatesManager) -> bool {
-        (self.scenario.miniblock_seal_fn)(manager)
+    fn should_seal_miniblock(&mut self, manager: &UpdatesManager) -> Option<MiniblockSealTrigger> {
+        (self.scenario.miniblock_seal_fn)(manager).then_some(MiniblockSealTrigger::Timeout)
+    }
+
+    fn should_seal_miniblock_before_tx(
+        &mut self,
+        manager: &UpdatesManager,
+        tx_metrics: &ExecutionMetrics,
+    ) -> Option<MiniblockSealTrigger> {
+        (self.scenario.miniblock_seal_before_tx_fn)(manager, tx_metrics)
+            .then_some(MiniblockSealTrigger::Gas)
     }
 }
 
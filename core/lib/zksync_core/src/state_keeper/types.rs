@@ -4,7 +4,7 @@ use std::{
 };
 
 use multivm::interface::VmExecutionResultAndLogs;
-use zksync_mempool::{L2TxFilter, MempoolInfo, MempoolStore};
+use zksync_mempool::{L2TxFilter, MempoolInfo, MempoolOrderingPolicy, MempoolStore};
 use zksync_types::{
     block::BlockGasCount, tx::ExecutionMetrics, Address, Nonce, PriorityOpId, Transaction,
 };
@@ -21,6 +21,14 @@ impl MempoolGuard {
         Self(Arc::new(Mutex::new(store)))
     }
 
+    /// Changes the policy used to order L2 transactions; can be called while the mempool is in use.
+    pub fn set_ordering_policy(&self, policy: MempoolOrderingPolicy) {
+        self.0
+            .lock()
+            .expect("failed to acquire mempool lock")
+            .set_ordering_policy(policy);
+    }
+
     pub fn insert(&mut self, transactions: Vec<Transaction>, nonces: HashMap<Address, Nonce>) {
         self.0
             .lock()
@@ -49,6 +57,13 @@ impl MempoolGuard {
             .rollback(rejected);
     }
 
+    pub fn remove_l2_transaction(&mut self, account: Address, nonce: Nonce) -> bool {
+        self.0
+            .lock()
+            .expect("failed to acquire mempool lock")
+            .remove_l2_transaction(account, nonce)
+    }
+
     pub fn get_mempool_info(&mut self) -> MempoolInfo {
         self.0
             .lock()
@@ -14,6 +14,8 @@ use zksync_types::{
 };
 use zksync_utils::bytecode::{hash_bytecode, CompressedBytecodeInfo};
 
+use crate::state_keeper::seal_criteria::MiniblockSealTrigger;
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct MiniblockUpdates {
     pub executed_transactions: Vec<TransactionExecutionResult>,
@@ -31,6 +33,8 @@ pub struct MiniblockUpdates {
     pub prev_block_hash: H256,
     pub virtual_blocks: u32,
     pub protocol_version: ProtocolVersionId,
+    /// Reason for sealing the miniblock; set by the state keeper immediately before sealing.
+    pub seal_trigger: Option<MiniblockSealTrigger>,
 }
 
 impl MiniblockUpdates {
@@ -56,6 +60,7 @@ impl MiniblockUpdates {
             prev_block_hash,
             virtual_blocks,
             protocol_version,
+            seal_trigger: None,
         }
     }
 
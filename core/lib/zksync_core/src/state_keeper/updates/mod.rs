@@ -98,6 +98,7 @@ impl UpdatesManager {
             l2_erc20_bridge_addr,
             consensus,
             pre_insert_txs,
+            use_copy_for_bulk_inserts: false,
         }
     }
 
@@ -188,6 +189,9 @@ pub(crate) struct MiniblockSealCommand {
     /// Should be set to `true` for EN's IO as EN doesn't store transactions in DB
     /// before they are included into miniblocks.
     pub pre_insert_txs: bool,
+    /// Whether factory deps and storage updates should be inserted using `COPY FROM STDIN`
+    /// instead of multi-row `INSERT`s.
+    pub use_copy_for_bulk_inserts: bool,
 }
 
 #[cfg(test)]
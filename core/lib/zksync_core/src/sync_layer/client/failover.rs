@@ -0,0 +1,395 @@
+//! Main node client that distributes requests among several upstream sources.
+
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+use futures::future::BoxFuture;
+use zksync_contracts::SystemContractCode;
+use zksync_types::{
+    api::{self, en::SyncBlock},
+    Address, MiniblockNumber, ProtocolVersionId, H256,
+};
+
+use super::MainNodeClient;
+
+/// Maximum health score of a source. The score is increased by 1 on each successful request.
+const MAX_HEALTH_SCORE: i32 = 10;
+/// Health score decrease on each failed request.
+const FAILURE_PENALTY: i32 = 5;
+/// Minimum health score of a source. Capped so that a source that was unavailable for a long time
+/// can become preferred again reasonably quickly after it recovers.
+const MIN_HEALTH_SCORE: i32 = -MAX_HEALTH_SCORE;
+/// Default interval between a failed request to a demoted source and its probe.
+const DEFAULT_PROBE_INTERVAL: Duration = Duration::from_secs(30);
+
+#[derive(Debug)]
+struct Source {
+    name: String,
+    client: Box<dyn MainNodeClient>,
+    health_score: Mutex<i32>,
+    last_failure: Mutex<Option<Instant>>,
+}
+
+impl Source {
+    fn health_score(&self) -> i32 {
+        *self.health_score.lock().unwrap()
+    }
+
+    fn report_success(&self) {
+        let mut score = self.health_score.lock().unwrap();
+        *score = (*score + 1).min(MAX_HEALTH_SCORE);
+    }
+
+    /// Restores the health of a demoted source after a successful probe.
+    fn report_recovery(&self) {
+        *self.health_score.lock().unwrap() = MAX_HEALTH_SCORE;
+    }
+
+    /// Checks whether the source is due for a probe, and if so, marks it as probed.
+    fn start_probe_if_due(&self, interval: Duration) -> bool {
+        let mut last_failure = self.last_failure.lock().unwrap();
+        let is_due = last_failure.map_or(false, |timestamp| timestamp.elapsed() >= interval);
+        if is_due {
+            // Prevents concurrent requests from probing the source as well.
+            *last_failure = Some(Instant::now());
+        }
+        is_due
+    }
+
+    fn report_failure(&self) {
+        let mut score = self.health_score.lock().unwrap();
+        *score = (*score - FAILURE_PENALTY).max(MIN_HEALTH_SCORE);
+        // The next probe is scheduled relative to the latest failure.
+        *self.last_failure.lock().unwrap() = Some(Instant::now());
+    }
+}
+
+/// [`MainNodeClient`] backed by several upstream sources (e.g., JSON-RPC endpoints of the main node
+/// and its replicas). Each source has a health score based on the outcome of recent requests. Requests
+/// are sent to the healthiest source; if a request fails, it's retried with the other sources in the order
+/// of decreasing health. Sources with equal health are tried in the order they were added.
+///
+/// A source demoted below a source added after it (e.g., the main node after it became unavailable) is periodically
+/// probed: a request is sent to it first, and if the request succeeds, the source is fully restored.
+#[derive(Debug)]
+pub struct FailoverMainNodeClient {
+    sources: Vec<Source>,
+    probe_interval: Duration,
+}
+
+impl Default for FailoverMainNodeClient {
+    fn default() -> Self {
+        Self {
+            sources: Vec::new(),
+            probe_interval: DEFAULT_PROBE_INTERVAL,
+        }
+    }
+}
+
+impl FailoverMainNodeClient {
+    /// Sets the interval between a failed request to a demoted source and its probe. The default value is 30 seconds.
+    pub fn with_probe_interval(mut self, interval: Duration) -> Self {
+        self.probe_interval = interval;
+        self
+    }
+
+    /// Adds a source to this client. The name of the source is only used for logging.
+    pub fn with_source(mut self, name: impl Into<String>, client: Box<dyn MainNodeClient>) -> Self {
+        self.sources.push(Source {
+            name: name.into(),
+            client,
+            health_score: Mutex::new(MAX_HEALTH_SCORE),
+            last_failure: Mutex::new(None),
+        });
+        self
+    }
+
+    /// Returns source indices in the order they should be queried.
+    fn source_order(&self) -> Vec<usize> {
+        let mut order: Vec<_> = (0..self.sources.len()).collect();
+        // `sort_by_key` is stable, so sources with equal health retain their relative order.
+        order.sort_by_key(|&idx| -self.sources[idx].health_score());
+        order
+    }
+
+    /// Returns source indices in the order they should be queried, together with the index of the probed source
+    /// (if any). The probed source is the first demoted source preceding the currently preferred one that is due
+    /// for a probe.
+    fn source_order_with_probe(&self) -> (Vec<usize>, Option<usize>) {
+        let mut order = self.source_order();
+        let Some(&preferred_idx) = order.first() else {
+            return (order, None);
+        };
+        let probed_idx = (0..preferred_idx)
+            .find(|&idx| self.sources[idx].start_probe_if_due(self.probe_interval));
+        if let Some(probed_idx) = probed_idx {
+            order.retain(|&idx| idx != probed_idx);
+            order.insert(0, probed_idx);
+        }
+        (order, probed_idx)
+    }
+
+    async fn call<T>(
+        &self,
+        method: &'static str,
+        request: impl for<'a> Fn(&'a dyn MainNodeClient) -> BoxFuture<'a, anyhow::Result<T>>,
+    ) -> anyhow::Result<T> {
+        let mut last_err = None;
+        let (order, probed_idx) = self.source_order_with_probe();
+        for idx in order {
+            let source = &self.sources[idx];
+            match request(source.client.as_ref()).await {
+                Ok(response) if probed_idx == Some(idx) => {
+                    tracing::info!("Probe of source `{}` succeeded; restoring it", source.name);
+                    source.report_recovery();
+                    return Ok(response);
+                }
+                Ok(response) => {
+                    source.report_success();
+                    return Ok(response);
+                }
+                Err(err) => {
+                    source.report_failure();
+                    tracing::warn!(
+                        "Request `{method}` to source `{}` failed: {err:#}. New health score: {}",
+                        source.name,
+                        source.health_score()
+                    );
+                    last_err = Some(err);
+                }
+            }
+        }
+        // Return the original error so that the caller can inspect it (e.g., whether it's a transport error).
+        Err(last_err
+            .unwrap_or_else(|| anyhow::anyhow!("no sources configured for main node client")))
+    }
+}
+
+#[async_trait]
+impl MainNodeClient for FailoverMainNodeClient {
+    async fn fetch_system_contract_by_hash(
+        &self,
+        hash: H256,
+    ) -> anyhow::Result<SystemContractCode> {
+        self.call("fetch_system_contract_by_hash", |client| {
+            client.fetch_system_contract_by_hash(hash)
+        })
+        .await
+    }
+
+    async fn fetch_genesis_contract_bytecode(
+        &self,
+        address: Address,
+    ) -> anyhow::Result<Option<Vec<u8>>> {
+        self.call("fetch_genesis_contract_bytecode", |client| {
+            client.fetch_genesis_contract_bytecode(address)
+        })
+        .await
+    }
+
+    async fn fetch_protocol_version(
+        &self,
+        protocol_version: ProtocolVersionId,
+    ) -> anyhow::Result<api::ProtocolVersion> {
+        self.call("fetch_protocol_version", |client| {
+            client.fetch_protocol_version(protocol_version)
+        })
+        .await
+    }
+
+    async fn fetch_genesis_l1_batch_hash(&self) -> anyhow::Result<H256> {
+        self.call("fetch_genesis_l1_batch_hash", |client| {
+            client.fetch_genesis_l1_batch_hash()
+        })
+        .await
+    }
+
+    async fn fetch_l2_block_number(&self) -> anyhow::Result<MiniblockNumber> {
+        self.call("fetch_l2_block_number", |client| {
+            client.fetch_l2_block_number()
+        })
+        .await
+    }
+
+    async fn fetch_l2_block(
+        &self,
+        number: MiniblockNumber,
+        with_transactions: bool,
+    ) -> anyhow::Result<Option<SyncBlock>> {
+        self.call("fetch_l2_block", |client| {
+            client.fetch_l2_block(number, with_transactions)
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc,
+    };
+
+    use super::*;
+
+    #[derive(Debug, Default)]
+    struct MockSource {
+        block_number: u32,
+        is_failing: Arc<AtomicBool>,
+        request_count: Arc<AtomicUsize>,
+    }
+
+    #[async_trait]
+    impl MainNodeClient for MockSource {
+        async fn fetch_system_contract_by_hash(
+            &self,
+            _hash: H256,
+        ) -> anyhow::Result<SystemContractCode> {
+            anyhow::bail!("Not implemented");
+        }
+
+        async fn fetch_genesis_contract_bytecode(
+            &self,
+            _address: Address,
+        ) -> anyhow::Result<Option<Vec<u8>>> {
+            anyhow::bail!("Not implemented");
+        }
+
+        async fn fetch_protocol_version(
+            &self,
+            _protocol_version: ProtocolVersionId,
+        ) -> anyhow::Result<api::ProtocolVersion> {
+            anyhow::bail!("Not implemented");
+        }
+
+        async fn fetch_genesis_l1_batch_hash(&self) -> anyhow::Result<H256> {
+            anyhow::bail!("Not implemented");
+        }
+
+        async fn fetch_l2_block_number(&self) -> anyhow::Result<MiniblockNumber> {
+            self.request_count.fetch_add(1, Ordering::SeqCst);
+            if self.is_failing.load(Ordering::SeqCst) {
+                anyhow::bail!("source is unavailable");
+            }
+            Ok(MiniblockNumber(self.block_number))
+        }
+
+        async fn fetch_l2_block(
+            &self,
+            _number: MiniblockNumber,
+            _with_transactions: bool,
+        ) -> anyhow::Result<Option<SyncBlock>> {
+            Ok(None)
+        }
+    }
+
+    #[tokio::test]
+    async fn failover_between_sources() {
+        let primary = MockSource {
+            block_number: 1,
+            ..MockSource::default()
+        };
+        let primary_is_failing = primary.is_failing.clone();
+        let primary_request_count = primary.request_count.clone();
+        let fallback = MockSource {
+            block_number: 2,
+            ..MockSource::default()
+        };
+        let fallback_request_count = fallback.request_count.clone();
+        let client = FailoverMainNodeClient::default()
+            .with_source("primary", Box::new(primary))
+            .with_source("fallback", Box::new(fallback));
+
+        let number = client.fetch_l2_block_number().await.unwrap();
+        assert_eq!(number, MiniblockNumber(1));
+        assert_eq!(fallback_request_count.load(Ordering::SeqCst), 0);
+
+        primary_is_failing.store(true, Ordering::SeqCst);
+        let number = client.fetch_l2_block_number().await.unwrap();
+        assert_eq!(number, MiniblockNumber(2));
+        assert_eq!(primary_request_count.load(Ordering::SeqCst), 2);
+        assert_eq!(client.source_order(), [1, 0]);
+
+        // The fallback source should be preferred now.
+        let number = client.fetch_l2_block_number().await.unwrap();
+        assert_eq!(number, MiniblockNumber(2));
+        assert_eq!(primary_request_count.load(Ordering::SeqCst), 2);
+
+        // Once the primary recovers, it's queried only if the fallback fails.
+        primary_is_failing.store(false, Ordering::SeqCst);
+        client.fetch_l2_block_number().await.unwrap();
+        assert_eq!(primary_request_count.load(Ordering::SeqCst), 2);
+        assert_eq!(fallback_request_count.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn demoted_source_is_probed() {
+        let primary = MockSource {
+            block_number: 1,
+            ..MockSource::default()
+        };
+        let primary_is_failing = primary.is_failing.clone();
+        let primary_request_count = primary.request_count.clone();
+        let fallback = MockSource {
+            block_number: 2,
+            ..MockSource::default()
+        };
+        let fallback_request_count = fallback.request_count.clone();
+        let client = FailoverMainNodeClient::default()
+            .with_probe_interval(Duration::ZERO)
+            .with_source("primary", Box::new(primary))
+            .with_source("fallback", Box::new(fallback));
+
+        primary_is_failing.store(true, Ordering::SeqCst);
+        let number = client.fetch_l2_block_number().await.unwrap();
+        assert_eq!(number, MiniblockNumber(2));
+        assert_eq!(client.source_order(), [1, 0]);
+
+        // The primary is probed on each request, but the fallback serves the requests while the primary is failing.
+        let number = client.fetch_l2_block_number().await.unwrap();
+        assert_eq!(number, MiniblockNumber(2));
+        assert_eq!(primary_request_count.load(Ordering::SeqCst), 2);
+        assert_eq!(fallback_request_count.load(Ordering::SeqCst), 2);
+
+        // Once the primary recovers, the probe restores it.
+        primary_is_failing.store(false, Ordering::SeqCst);
+        let number = client.fetch_l2_block_number().await.unwrap();
+        assert_eq!(number, MiniblockNumber(1));
+        assert_eq!(client.sources[0].health_score(), MAX_HEALTH_SCORE);
+        assert_eq!(client.source_order(), [0, 1]);
+        let number = client.fetch_l2_block_number().await.unwrap();
+        assert_eq!(number, MiniblockNumber(1));
+        assert_eq!(fallback_request_count.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn probes_are_rate_limited() {
+        let client = FailoverMainNodeClient::default()
+            .with_source("primary", Box::new(MockSource::default()))
+            .with_source("fallback", Box::new(MockSource::default()));
+        client.sources[0].report_failure();
+        assert_eq!(client.source_order_with_probe(), (vec![1, 0], None));
+
+        let probe_time = Instant::now() - DEFAULT_PROBE_INTERVAL;
+        *client.sources[0].last_failure.lock().unwrap() = Some(probe_time);
+        assert_eq!(client.source_order_with_probe(), (vec![0, 1], Some(0)));
+        assert_eq!(client.source_order_with_probe(), (vec![1, 0], None));
+    }
+
+    #[tokio::test]
+    async fn error_is_returned_if_all_sources_fail() {
+        let source = MockSource::default();
+        source.is_failing.store(true, Ordering::SeqCst);
+        let client = FailoverMainNodeClient::default().with_source("source", Box::new(source));
+
+        let err = client.fetch_l2_block_number().await.unwrap_err();
+        assert!(err.to_string().contains("unavailable"), "{err}");
+        assert_eq!(
+            client.sources[0].health_score(),
+            MAX_HEALTH_SCORE - FAILURE_PENALTY
+        );
+    }
+}
@@ -15,8 +15,11 @@ use zksync_web3_decl::{
     namespaces::{EnNamespaceClient, EthNamespaceClient, ZksNamespaceClient},
 };
 
+pub use self::failover::FailoverMainNodeClient;
 use super::metrics::{CachedMethod, FETCHER_METRICS};
 
+mod failover;
+
 /// Maximum number of concurrent requests to the main node.
 const MAX_CONCURRENT_REQUESTS: usize = 100;
 
@@ -26,7 +26,7 @@ use crate::{
             MiniblockParams, MiniblockSealerHandle, PendingBatchData, StateKeeperIO,
         },
         metrics::KEEPER_METRICS,
-        seal_criteria::IoSealCriteria,
+        seal_criteria::{IoSealCriteria, MiniblockSealTrigger},
         updates::UpdatesManager,
     },
 };
@@ -224,11 +224,12 @@ impl IoSealCriteria for ExternalIO {
         )
     }
 
-    fn should_seal_miniblock(&mut self, _manager: &UpdatesManager) -> bool {
-        matches!(
+    fn should_seal_miniblock(&mut self, _manager: &UpdatesManager) -> Option<MiniblockSealTrigger> {
+        let should_seal = matches!(
             self.actions.peek_action(),
             Some(SyncAction::SealMiniblock(_))
-        )
+        );
+        should_seal.then_some(MiniblockSealTrigger::MainNode)
     }
 }
 
@@ -314,6 +315,7 @@ impl StateKeeperIO for ExternalIO {
                     timestamp,
                     l1_gas_price,
                     l2_fair_gas_price,
+                    fair_pubdata_price,
                     operator_address,
                     protocol_version,
                     first_miniblock_info: (miniblock_number, virtual_blocks),
@@ -335,12 +337,20 @@ impl StateKeeperIO for ExternalIO {
                     let base_system_contracts = self
                         .load_base_system_contracts_by_version_id(protocol_version)
                         .await;
+                    let fee_input = match fair_pubdata_price {
+                        Some(fair_pubdata_price) => BatchFeeInput::pubdata_independent(
+                            l1_gas_price,
+                            l2_fair_gas_price,
+                            fair_pubdata_price,
+                        ),
+                        None => BatchFeeInput::l1_pegged(l1_gas_price, l2_fair_gas_price),
+                    };
                     return Some(l1_batch_params(
                         number,
                         operator_address,
                         timestamp,
                         previous_l1_batch_hash,
-                        BatchFeeInput::l1_pegged(l1_gas_price, l2_fair_gas_price),
+                        fee_input,
                         miniblock_number,
                         previous_miniblock_hash,
                         base_system_contracts,
@@ -500,6 +510,8 @@ impl StateKeeperIO for ExternalIO {
                 finished_batch,
                 self.l2_erc20_bridge_addr,
                 consensus,
+                true,
+                false,
             )
             .await;
         transaction.commit().await.unwrap();
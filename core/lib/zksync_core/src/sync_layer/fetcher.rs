@@ -11,6 +11,7 @@ use zksync_web3_decl::jsonrpsee::core::ClientError as RpcError;
 
 use super::{
     client::{CachingMainNodeClient, MainNodeClient},
+    journal::FetchedBlocksJournal,
     metrics::{FetchStage, L1BatchStage, FETCHER_METRICS},
     sync_action::{ActionQueueSender, SyncAction},
     SyncState,
@@ -31,6 +32,7 @@ pub(super) struct FetchedBlock {
     pub reference_hash: Option<H256>,
     pub l1_gas_price: u64,
     pub l2_fair_gas_price: u64,
+    pub fair_pubdata_price: Option<u64>,
     pub virtual_blocks: u32,
     pub operator_address: Address,
     pub transactions: Vec<zksync_types::Transaction>,
@@ -60,6 +62,7 @@ impl TryFrom<SyncBlock> for FetchedBlock {
             reference_hash: block.hash,
             l1_gas_price: block.l1_gas_price,
             l2_fair_gas_price: block.l2_fair_gas_price,
+            fair_pubdata_price: block.fair_pubdata_price,
             virtual_blocks: block.virtual_blocks.unwrap_or(0),
             operator_address: block.operator_address,
             transactions: block
@@ -160,6 +163,7 @@ impl FetcherCursor {
                 timestamp: block.timestamp,
                 l1_gas_price: block.l1_gas_price,
                 l2_fair_gas_price: block.l2_fair_gas_price,
+                fair_pubdata_price: block.fair_pubdata_price,
                 operator_address: block.operator_address,
                 protocol_version: block.protocol_version,
                 // `block.virtual_blocks` can be `None` only for old VM versions where it's not used, so it's fine to provide any number.
@@ -214,6 +218,7 @@ impl FetcherCursor {
             actions,
             sync_state,
             stop_receiver,
+            journal: None,
         }
     }
 }
@@ -226,15 +231,25 @@ pub struct MainNodeFetcher {
     actions: ActionQueueSender,
     sync_state: SyncState,
     stop_receiver: watch::Receiver<bool>,
+    journal: Option<FetchedBlocksJournal>,
 }
 
 impl MainNodeFetcher {
+    /// Persists fetched miniblocks in the provided journal, so that miniblocks fetched but not applied
+    /// by the state keeper are replayed after a restart instead of being fetched from the main node again.
+    pub fn with_journal(mut self, journal: FetchedBlocksJournal) -> Self {
+        self.journal = Some(journal);
+        self
+    }
+
     pub async fn run(mut self) -> anyhow::Result<()> {
         tracing::info!(
             "Starting the fetcher routine. Initial miniblock: {}, initial l1 batch: {}",
             self.cursor.next_miniblock,
             self.cursor.l1_batch
         );
+        self.replay_journal().await?;
+
         // Run the main routine and reconnect upon the network errors.
         loop {
             match self.run_inner().await {
@@ -257,6 +272,31 @@ impl MainNodeFetcher {
         }
     }
 
+    /// Pushes actions for miniblocks persisted in the journal (if any) to the action queue.
+    async fn replay_journal(&mut self) -> anyhow::Result<()> {
+        let Some(journal) = &self.journal else {
+            return Ok(());
+        };
+        let journal = journal.clone();
+        let next_miniblock = self.cursor.next_miniblock;
+        let blocks = tokio::task::spawn_blocking(move || journal.load_from(next_miniblock))
+            .await
+            .context("panicked loading journaled miniblocks")??;
+        if blocks.is_empty() {
+            return Ok(());
+        }
+
+        tracing::info!(
+            "Replaying {} miniblocks from the journal starting from #{next_miniblock}",
+            blocks.len()
+        );
+        for block in blocks {
+            let new_actions = self.cursor.advance(block.try_into()?);
+            self.actions.push_actions(new_actions).await;
+        }
+        Ok(())
+    }
+
     fn check_if_cancelled(&self) -> bool {
         *self.stop_receiver.borrow()
     }
@@ -307,6 +347,18 @@ impl MainNodeFetcher {
         request_latency.observe();
 
         let block_number = block.number;
+        if let Some(journal) = &self.journal {
+            // Miniblocks up to and including the local block are already persisted in Postgres.
+            let first_unapplied_miniblock = self.sync_state.get_local_block() + 1;
+            let journal = journal.clone();
+            let persisted_block = block.clone();
+            tokio::task::spawn_blocking(move || {
+                journal.persist(&persisted_block)?;
+                journal.prune_before(first_unapplied_miniblock)
+            })
+            .await
+            .context("panicked updating miniblocks journal")??;
+        }
         let new_actions = self.cursor.advance(block.try_into()?);
 
         tracing::info!(
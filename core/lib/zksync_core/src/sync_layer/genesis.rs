@@ -7,7 +7,7 @@ use zksync_types::{
 };
 
 use super::client::MainNodeClient;
-use crate::genesis::{ensure_genesis_state, GenesisParams};
+use crate::genesis::{ensure_genesis_state, GenesisAllocations, GenesisParams};
 
 pub async fn perform_genesis_if_needed(
     storage: &mut StorageProcessor<'_>,
@@ -95,6 +95,9 @@ async fn create_genesis_params(client: &dyn MainNodeClient) -> anyhow::Result<Ge
         first_validator,
         first_l1_verifier_config,
         first_verifier_address,
+        // Custom allocations cannot be recovered from the main node; if the main node genesis has them,
+        // validating the genesis state against the main node will fail.
+        allocations: GenesisAllocations::default(),
     })
 }
 
@@ -142,13 +142,15 @@ pub(super) struct Buffered<T> {
     inner_subscriber: watch::Receiver<BlockNumber>,
     block_writes_sender: watch::Sender<BlockNumber>,
     buffer: Mutex<BlockBuffer>,
+    max_payload_size: usize,
     #[cfg(test)]
     events_sender: channel::UnboundedSender<BufferedStorageEvent>,
 }
 
 impl<T: ContiguousBlockStore> Buffered<T> {
-    /// Creates a new buffered storage. The buffer is initially empty.
-    pub fn new(store: T) -> Self {
+    /// Creates a new buffered storage. The buffer is initially empty. Blocks with payloads larger than
+    /// `max_payload_size` bytes are rejected without being buffered.
+    pub fn new(store: T, max_payload_size: usize) -> Self {
         let inner_subscriber = store.subscribe_to_block_writes();
         let store_block_number = *inner_subscriber.borrow();
         tracing::debug!(
@@ -160,6 +162,7 @@ impl<T: ContiguousBlockStore> Buffered<T> {
             inner_subscriber,
             block_writes_sender: watch::channel(store_block_number).0,
             buffer: Mutex::new(BlockBuffer::new(store_block_number)),
+            max_payload_size,
             #[cfg(test)]
             events_sender: channel::unbounded().0,
         }
@@ -333,6 +336,19 @@ impl<T: ContiguousBlockStore> WriteBlockStore for Buffered<T> {
     }
 
     async fn put_block(&self, ctx: &ctx::Ctx, block: &FinalBlock) -> ctx::Result<()> {
+        let payload_size = block.payload.0.len();
+        if payload_size > self.max_payload_size {
+            // Skip the block without buffering it, so that it remains missing and is re-requested
+            // from peers. Returning an error would stop the fetcher altogether.
+            tracing::warn!(
+                "Skipping block #{} with too large payload: {payload_size} bytes, while the limit is {} bytes",
+                block.header.number,
+                self.max_payload_size
+            );
+            METRICS.rejected_blocks.inc();
+            return Ok(());
+        }
+
         let buffer_block_latency = METRICS.buffer_block_latency.start();
         {
             let mut buffer = sync::lock(ctx, &self.buffer).await?;
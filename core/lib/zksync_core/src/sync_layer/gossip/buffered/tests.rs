@@ -139,7 +139,7 @@ async fn test_buffered_storage(
     initial_blocks.insert(0, genesis_block.clone());
 
     let (block_store, block_receiver) = MockContiguousStore::new(block_store);
-    let mut buffered_store = Buffered::new(block_store);
+    let mut buffered_store = Buffered::new(block_store, usize::MAX);
     let (events_sender, mut events_receiver) = channel::unbounded();
     buffered_store.set_events_sender(events_sender);
 
@@ -282,3 +282,42 @@ async fn buffered_storage_with_initial_blocks_and_slight_shuffling(block_interva
     })
     .await;
 }
+
+#[tokio::test]
+async fn buffered_storage_skips_oversized_payloads() {
+    abort_on_panic();
+    let ctx = &ctx::test_root(&ctx::RealClock);
+    let rng = &mut ctx.rng();
+
+    let (genesis_block, block_store) = init_store(rng);
+    let (block_store, _block_receiver) = MockContiguousStore::new(block_store);
+    let buffered_store = Buffered::new(block_store, 32);
+
+    let payload = Payload(vec![0; 64]);
+    let oversized_block = FinalBlock {
+        header: BlockHeader {
+            parent: genesis_block.header.hash(),
+            number: genesis_block.header.number.next(),
+            payload: payload.hash(),
+        },
+        payload,
+        justification: rng.gen(),
+    };
+    buffered_store
+        .put_block(ctx, &oversized_block)
+        .await
+        .unwrap();
+    assert_eq!(buffered_store.buffer_len().await, 0);
+    let missing_block_numbers = buffered_store
+        .missing_block_numbers(
+            ctx,
+            oversized_block.header.number..oversized_block.header.number.next(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(missing_block_numbers, [oversized_block.header.number]);
+
+    let block = gen_blocks(rng, genesis_block, 1).pop().unwrap();
+    buffered_store.put_block(ctx, &block).await.unwrap();
+    assert_eq!(buffered_store.buffer_len().await, 1);
+}
@@ -25,6 +25,7 @@ impl FetchedBlock {
             reference_hash: Some(payload.hash),
             l1_gas_price: payload.l1_gas_price,
             l2_fair_gas_price: payload.l2_fair_gas_price,
+            fair_pubdata_price: payload.fair_pubdata_price,
             virtual_blocks: payload.virtual_blocks,
             operator_address: payload.operator_address,
             transactions: payload.transactions,
@@ -2,7 +2,9 @@
 
 use std::time::Duration;
 
-use vise::{Buckets, EncodeLabelSet, EncodeLabelValue, Family, Gauge, Histogram, Metrics, Unit};
+use vise::{
+    Buckets, Counter, EncodeLabelSet, EncodeLabelValue, Family, Gauge, Histogram, Metrics, Unit,
+};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EncodeLabelValue, EncodeLabelSet)]
 #[metrics(label = "kind", rename_all = "snake_case")]
@@ -23,6 +25,8 @@ pub(super) struct GossipFetcherMetrics {
     /// block actions, but does not include block execution.
     #[metrics(unit = Unit::Seconds, buckets = Buckets::LATENCIES)]
     pub buffer_block_latency: Histogram<Duration>,
+    /// Number of blocks skipped because their payload exceeds the size limit.
+    pub rejected_blocks: Counter,
 }
 
 #[vise::register]
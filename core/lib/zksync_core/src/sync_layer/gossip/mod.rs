@@ -12,6 +12,7 @@ use zksync_types::Address;
 
 use self::{buffered::Buffered, storage::PostgresBlockStorage};
 use super::{fetcher::FetcherCursor, sync_action::ActionQueueSender};
+use crate::consensus::DEFAULT_MAX_PAYLOAD_SIZE;
 
 mod buffered;
 mod conversions;
@@ -21,7 +22,8 @@ mod storage;
 mod tests;
 mod utils;
 
-/// Starts fetching L2 blocks using peer-to-peer gossip network.
+/// Starts fetching L2 blocks using peer-to-peer gossip network. Blocks with payloads larger than
+/// `max_payload_size` bytes (by default, [`DEFAULT_MAX_PAYLOAD_SIZE`]) are skipped.
 pub async fn run_gossip_fetcher(
     pool: ConnectionPool,
     actions: ActionQueueSender,
@@ -29,7 +31,9 @@ pub async fn run_gossip_fetcher(
     node_key: node::SecretKey,
     mut stop_receiver: watch::Receiver<bool>,
     operator_address: Address,
+    max_payload_size: Option<usize>,
 ) -> anyhow::Result<()> {
+    let max_payload_size = max_payload_size.unwrap_or(DEFAULT_MAX_PAYLOAD_SIZE);
     scope::run!(&ctx::root(), |ctx, s| async {
         s.spawn_bg(run_gossip_fetcher_inner(
             ctx,
@@ -38,6 +42,7 @@ pub async fn run_gossip_fetcher(
             executor_config,
             node_key,
             operator_address,
+            max_payload_size,
         ));
         if stop_receiver.changed().await.is_err() {
             tracing::warn!(
@@ -57,6 +62,7 @@ async fn run_gossip_fetcher_inner(
     executor_config: ExecutorConfig,
     node_key: node::SecretKey,
     operator_address: Address,
+    max_payload_size: usize,
 ) -> anyhow::Result<()> {
     tracing::info!(
         "Starting gossip fetcher with {executor_config:?} and node key {:?}",
@@ -82,7 +88,7 @@ async fn run_gossip_fetcher_inner(
     )
     .await
     .wrap("PostgresBlockStorage::new()")?;
-    let buffered = Arc::new(Buffered::new(store));
+    let buffered = Arc::new(Buffered::new(store, max_payload_size));
     let store = buffered.inner();
 
     scope::run!(ctx, |ctx, s| async {
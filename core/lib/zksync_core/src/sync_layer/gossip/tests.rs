@@ -231,6 +231,7 @@ async fn syncing_via_gossip_fetcher(delay_first_block: bool, delay_second_block:
             external_node.node_config,
             external_node.node_key,
             OPERATOR_ADDRESS,
+            consensus::DEFAULT_MAX_PAYLOAD_SIZE,
         ));
 
         if delay_first_block {
@@ -367,6 +368,7 @@ async fn syncing_via_gossip_fetcher_with_multiple_l1_batches(initial_block_count
             external_node.node_config,
             external_node.node_key,
             OPERATOR_ADDRESS,
+            consensus::DEFAULT_MAX_PAYLOAD_SIZE,
         ));
 
         state_keeper
@@ -479,6 +481,7 @@ async fn syncing_from_non_zero_block(first_block_number: u32) {
                 external_node.node_config,
                 external_node.node_key,
                 OPERATOR_ADDRESS,
+                consensus::DEFAULT_MAX_PAYLOAD_SIZE,
             )
             .await
             .context("run_gossip_fetcher_inner()")
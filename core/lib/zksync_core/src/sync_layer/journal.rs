@@ -0,0 +1,233 @@
+//! On-disk journal of miniblocks fetched from the main node.
+
+use std::{
+    collections::BTreeSet,
+    fs,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use anyhow::Context as _;
+use zksync_types::{api::en::SyncBlock, MiniblockNumber};
+
+/// Journal of miniblocks fetched by [`MainNodeFetcher`](super::fetcher::MainNodeFetcher) that may not be
+/// applied by the state keeper yet. Each miniblock is stored in a separate JSON file, so that
+/// fetched but unapplied miniblocks can be replayed after a restart without re-fetching them
+/// from the main node.
+///
+/// The journal is an optimization: losing it (or some of its entries) only means that the corresponding
+/// miniblocks will be fetched from the main node again.
+///
+/// Numbers of journaled miniblocks are tracked in memory, so the journal directory is only listed once
+/// when the journal is opened. Thus, the directory must not be shared among several journal instances
+/// that are not clones of each other.
+#[derive(Debug, Clone)]
+pub struct FetchedBlocksJournal {
+    dir: PathBuf,
+    block_numbers: Arc<Mutex<BTreeSet<MiniblockNumber>>>,
+}
+
+impl FetchedBlocksJournal {
+    /// Opens a journal in the specified directory, creating the directory if necessary.
+    pub fn new(dir: impl Into<PathBuf>) -> anyhow::Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)
+            .with_context(|| format!("failed creating journal directory {dir:?}"))?;
+        let entries = fs::read_dir(&dir)
+            .with_context(|| format!("failed reading journal directory {dir:?}"))?;
+        let mut block_numbers = BTreeSet::new();
+        for entry in entries {
+            let entry = entry.context("failed reading journal directory entry")?;
+            block_numbers.extend(Self::parse_block_number(&entry.path()));
+        }
+        Ok(Self {
+            dir,
+            block_numbers: Arc::new(Mutex::new(block_numbers)),
+        })
+    }
+
+    fn block_path(&self, number: MiniblockNumber) -> PathBuf {
+        self.dir.join(format!("{:010}.json", number.0))
+    }
+
+    fn parse_block_number(path: &Path) -> Option<MiniblockNumber> {
+        if path.extension()? != "json" {
+            return None;
+        }
+        let number = path.file_stem()?.to_str()?.parse().ok()?;
+        Some(MiniblockNumber(number))
+    }
+
+    /// Persists a fetched miniblock. The write is atomic, i.e., a crash cannot leave a partially written entry.
+    pub(super) fn persist(&self, block: &SyncBlock) -> anyhow::Result<()> {
+        let path = self.block_path(block.number);
+        let tmp_path = path.with_extension("json.tmp");
+        let serialized = serde_json::to_vec(block).context("failed serializing miniblock")?;
+        fs::write(&tmp_path, serialized).with_context(|| format!("failed writing {tmp_path:?}"))?;
+        fs::rename(&tmp_path, &path)
+            .with_context(|| format!("failed renaming {tmp_path:?} to {path:?}"))?;
+        self.block_numbers.lock().unwrap().insert(block.number);
+        Ok(())
+    }
+
+    fn block_numbers(&self) -> Vec<MiniblockNumber> {
+        self.block_numbers.lock().unwrap().iter().copied().collect()
+    }
+
+    fn remove_blocks(&self, numbers: BTreeSet<MiniblockNumber>) -> anyhow::Result<()> {
+        for (i, &number) in numbers.iter().enumerate() {
+            let path = self.block_path(number);
+            if let Err(err) = fs::remove_file(&path) {
+                // Return the entries that weren't removed to the index, so that their removal is retried later.
+                self.block_numbers
+                    .lock()
+                    .unwrap()
+                    .extend(numbers.into_iter().skip(i));
+                return Err(err).with_context(|| format!("failed removing {path:?}"));
+            }
+        }
+        Ok(())
+    }
+
+    /// Removes all miniblocks with numbers lesser than `number` from the journal.
+    pub(super) fn prune_before(&self, number: MiniblockNumber) -> anyhow::Result<()> {
+        let pruned_numbers = {
+            let mut block_numbers = self.block_numbers.lock().unwrap();
+            if block_numbers
+                .first()
+                .map_or(true, |&oldest| oldest >= number)
+            {
+                return Ok(());
+            }
+            let retained_numbers = block_numbers.split_off(&number);
+            std::mem::replace(&mut *block_numbers, retained_numbers)
+        };
+        self.remove_blocks(pruned_numbers)
+    }
+
+    /// Removes all miniblocks with numbers greater than `number` from the journal. This must be called
+    /// after the node storage is rolled back, since journaled miniblocks after the rollback point may be
+    /// from a reorganized chain.
+    pub fn truncate_after(&self, number: MiniblockNumber) -> anyhow::Result<()> {
+        let removed_numbers = self.block_numbers.lock().unwrap().split_off(&(number + 1));
+        if !removed_numbers.is_empty() {
+            tracing::info!(
+                "Removing {} miniblocks after #{number} from the journal",
+                removed_numbers.len()
+            );
+        }
+        self.remove_blocks(removed_numbers)
+    }
+
+    /// Loads a contiguous sequence of journaled miniblocks starting from `first_number`. Entries before
+    /// `first_number` are pruned.
+    pub(super) fn load_from(
+        &self,
+        first_number: MiniblockNumber,
+    ) -> anyhow::Result<Vec<SyncBlock>> {
+        self.prune_before(first_number)?;
+
+        let mut blocks = vec![];
+        let mut expected_number = first_number;
+        for number in self.block_numbers() {
+            if number != expected_number {
+                tracing::warn!(
+                    "Miniblock #{expected_number} is missing in the journal; journaled miniblocks \
+                     starting from #{number} will be fetched from the main node"
+                );
+                break;
+            }
+            let path = self.block_path(number);
+            let raw = fs::read(&path).with_context(|| format!("failed reading {path:?}"))?;
+            let block: SyncBlock = match serde_json::from_slice(&raw) {
+                Ok(block) => block,
+                Err(err) => {
+                    tracing::warn!("Failed deserializing journaled miniblock #{number}: {err}");
+                    break;
+                }
+            };
+            anyhow::ensure!(
+                block.number == number,
+                "journal entry {path:?} contains miniblock #{}",
+                block.number
+            );
+            blocks.push(block);
+            expected_number += 1;
+        }
+        Ok(blocks)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zksync_types::{Address, L1BatchNumber, ProtocolVersionId};
+
+    use super::*;
+
+    fn mock_block(number: u32) -> SyncBlock {
+        SyncBlock {
+            number: MiniblockNumber(number),
+            l1_batch_number: L1BatchNumber(1),
+            last_in_batch: false,
+            timestamp: number.into(),
+            l1_gas_price: 2,
+            l2_fair_gas_price: 3,
+            fair_pubdata_price: Some(24),
+            base_system_contracts_hashes: Default::default(),
+            operator_address: Address::repeat_byte(1),
+            transactions: Some(vec![]),
+            virtual_blocks: Some(1),
+            hash: None,
+            protocol_version: ProtocolVersionId::latest(),
+            consensus: None,
+        }
+    }
+
+    #[test]
+    fn journal_basics() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let journal = FetchedBlocksJournal::new(temp_dir.path()).unwrap();
+        assert!(journal.load_from(MiniblockNumber(1)).unwrap().is_empty());
+
+        for number in [1, 2, 3, 5] {
+            journal.persist(&mock_block(number)).unwrap();
+        }
+        let blocks = journal.load_from(MiniblockNumber(2)).unwrap();
+        let block_numbers: Vec<_> = blocks.iter().map(|block| block.number.0).collect();
+        // Miniblock #1 is pruned; #5 isn't loaded because of the gap.
+        assert_eq!(block_numbers, [2, 3]);
+        assert_eq!(journal.block_numbers(), [2, 3, 5].map(MiniblockNumber));
+
+        // Journal should be persisted across instances.
+        let journal = FetchedBlocksJournal::new(temp_dir.path()).unwrap();
+        assert_eq!(journal.block_numbers(), [2, 3, 5].map(MiniblockNumber));
+        journal.prune_before(MiniblockNumber(4)).unwrap();
+        assert_eq!(journal.block_numbers(), [MiniblockNumber(5)]);
+        let blocks = journal.load_from(MiniblockNumber(5)).unwrap();
+        assert_eq!(blocks.len(), 1);
+        assert_eq!(blocks[0].timestamp, 5);
+    }
+
+    #[test]
+    fn truncating_journal_after_rollback() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let journal = FetchedBlocksJournal::new(temp_dir.path()).unwrap();
+        for number in 1..=5 {
+            journal.persist(&mock_block(number)).unwrap();
+        }
+
+        journal.truncate_after(MiniblockNumber(3)).unwrap();
+        assert_eq!(journal.block_numbers(), [1, 2, 3].map(MiniblockNumber));
+        assert!(!temp_dir.path().join("0000000004.json").exists());
+        let journal = FetchedBlocksJournal::new(temp_dir.path()).unwrap();
+        assert_eq!(journal.block_numbers(), [1, 2, 3].map(MiniblockNumber));
+
+        // Miniblocks fetched after the rollback should overwrite the journaled ones.
+        let mut block = mock_block(3);
+        block.timestamp = 100;
+        journal.persist(&block).unwrap();
+        let blocks = journal.load_from(MiniblockNumber(2)).unwrap();
+        let timestamps: Vec<_> = blocks.iter().map(|block| block.timestamp).collect();
+        assert_eq!(timestamps, [2, 100]);
+    }
+}
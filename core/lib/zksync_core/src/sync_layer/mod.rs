@@ -4,6 +4,7 @@ pub mod external_io;
 pub mod fetcher;
 pub mod genesis;
 mod gossip;
+mod journal;
 mod metrics;
 pub(crate) mod sync_action;
 mod sync_state;
@@ -11,6 +12,10 @@ mod sync_state;
 mod tests;
 
 pub use self::{
-    client::MainNodeClient, external_io::ExternalIO, gossip::run_gossip_fetcher,
-    sync_action::ActionQueue, sync_state::SyncState,
+    client::{FailoverMainNodeClient, MainNodeClient},
+    external_io::ExternalIO,
+    gossip::run_gossip_fetcher,
+    journal::FetchedBlocksJournal,
+    sync_action::ActionQueue,
+    sync_state::SyncState,
 };
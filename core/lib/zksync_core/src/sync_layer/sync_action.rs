@@ -130,6 +130,8 @@ pub(crate) enum SyncAction {
         timestamp: u64,
         l1_gas_price: u64,
         l2_fair_gas_price: u64,
+        /// Set only if the batch uses the pubdata-independent fee input.
+        fair_pubdata_price: Option<u64>,
         operator_address: Address,
         protocol_version: ProtocolVersionId,
         // Miniblock number and virtual blocks count.
@@ -173,6 +175,7 @@ mod tests {
             timestamp: 1,
             l1_gas_price: 1,
             l2_fair_gas_price: 1,
+            fair_pubdata_price: Some(1),
             operator_address: Default::default(),
             protocol_version: ProtocolVersionId::latest(),
             first_miniblock_info: (1.into(), 1),
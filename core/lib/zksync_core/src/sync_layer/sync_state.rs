@@ -73,6 +73,12 @@ impl SyncState {
         self.is_synced_inner(&inner).0
     }
 
+    /// Returns the lag of the local node behind the main node in miniblocks, or `None` if it's not known yet.
+    pub(crate) fn get_sync_lag(&self) -> Option<u32> {
+        let inner = self.inner.read().unwrap();
+        self.is_synced_inner(&inner).1
+    }
+
     fn update_sync_metric(&self, inner: &SyncStateInner) {
         let (is_synced, lag) = self.is_synced_inner(inner);
         EN_METRICS.synced.set(is_synced.into());
@@ -6,11 +6,13 @@ use std::{
     time::{Duration, Instant},
 };
 
+use assert_matches::assert_matches;
 use tokio::{sync::watch, task::JoinHandle};
 use zksync_config::configs::chain::NetworkConfig;
 use zksync_dal::{ConnectionPool, StorageProcessor};
 use zksync_types::{
-    Address, L1BatchNumber, L2ChainId, MiniblockNumber, ProtocolVersionId, Transaction, H256,
+    fee_model::BatchFeeInput, Address, L1BatchNumber, L2ChainId, MiniblockNumber,
+    ProtocolVersionId, Transaction, H256,
 };
 
 use super::{fetcher::FetcherCursor, sync_action::SyncAction, *};
@@ -35,6 +37,7 @@ fn open_l1_batch(number: u32, timestamp: u64, first_miniblock_number: u32) -> Sy
         timestamp,
         l1_gas_price: 2,
         l2_fair_gas_price: 3,
+        fair_pubdata_price: Some(24),
         operator_address: OPERATOR_ADDRESS,
         protocol_version: ProtocolVersionId::latest(),
         first_miniblock_info: (MiniblockNumber(first_miniblock_number), 1),
@@ -167,6 +170,10 @@ async fn external_io_basics() {
     assert_eq!(miniblock.timestamp, 1);
     assert_eq!(miniblock.batch_fee_input.l1_gas_price(), 2);
     assert_eq!(miniblock.batch_fee_input.fair_l2_gas_price(), 3);
+    assert_matches!(
+        miniblock.batch_fee_input,
+        BatchFeeInput::PubdataIndependent(input) if input.fair_pubdata_price == 24
+    );
     assert_eq!(miniblock.l1_tx_count, 0);
     assert_eq!(miniblock.l2_tx_count, 1);
 
@@ -441,7 +448,12 @@ async fn fetcher_basics() {
             .await
             .unwrap();
         match action {
-            SyncAction::OpenBatch { number, .. } => {
+            SyncAction::OpenBatch {
+                number,
+                fair_pubdata_price,
+                ..
+            } => {
+                assert_eq!(fair_pubdata_price, Some(24));
                 current_l1_batch_number += 1;
                 current_miniblock_number += 1; // First miniblock is implicitly opened
                 tx_count_in_miniblock = 0;
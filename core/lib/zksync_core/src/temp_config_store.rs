@@ -1,14 +1,15 @@
 use zksync_config::{
     configs::{
-        api::{HealthCheckConfig, MerkleTreeApiConfig, Web3JsonRpcConfig},
+        api::{ExplorerApiConfig, HealthCheckConfig, MerkleTreeApiConfig, Web3JsonRpcConfig},
         chain::{
             CircuitBreakerConfig, MempoolConfig, NetworkConfig, OperationsManagerConfig,
             StateKeeperConfig,
         },
         fri_prover_group::FriProverGroupConfig,
         house_keeper::HouseKeeperConfig,
-        FriProofCompressorConfig, FriProverConfig, FriWitnessGeneratorConfig, PrometheusConfig,
-        ProofDataHandlerConfig, WitnessGeneratorConfig,
+        DADispatcherConfig, FriProofCompressorConfig, FriProverConfig, FriWitnessGeneratorConfig,
+        PrometheusConfig, ProofDataHandlerConfig, TokenFetcherConfig, TxEventStreamConfig,
+        WitnessGeneratorConfig,
     },
     ApiConfig, ContractsConfig, DBConfig, ETHClientConfig, ETHSenderConfig, ETHWatchConfig,
     GasAdjusterConfig, ObjectStoreConfig, PostgresConfig,
@@ -44,4 +45,8 @@ pub struct TempConfigStore {
     pub eth_watch_config: Option<ETHWatchConfig>,
     pub gas_adjuster_config: Option<GasAdjusterConfig>,
     pub object_store_config: Option<ObjectStoreConfig>,
+    pub da_dispatcher_config: Option<DADispatcherConfig>,
+    pub tx_event_stream_config: Option<TxEventStreamConfig>,
+    pub token_fetcher_config: Option<TokenFetcherConfig>,
+    pub explorer_api_config: Option<ExplorerApiConfig>,
 }
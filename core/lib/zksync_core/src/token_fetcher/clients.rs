@@ -0,0 +1,277 @@
+//! Clients used by the token fetcher.
+
+use std::{collections::HashMap, fmt, time::Duration};
+
+use anyhow::Context as _;
+use async_trait::async_trait;
+use num::{rational::Ratio, BigUint};
+use serde::Deserialize;
+use zksync_eth_client::{Error as EthClientError, EthInterface};
+use zksync_types::{
+    ethabi::{self, ParamType, Token},
+    tokens::TokenMetadata,
+    web3::{
+        self,
+        types::{BlockNumber, CallRequest, FilterBuilder},
+    },
+    Address, H256,
+};
+use zksync_utils::UnsignedRatioSerializeAsDecimal;
+
+/// ABI of the ERC-20 metadata methods.
+const ERC20_METADATA_ABI: &str = r#"[
+    {"type":"function","name":"name","inputs":[],"outputs":[{"name":"","type":"string"}],"stateMutability":"view"},
+    {"type":"function","name":"symbol","inputs":[],"outputs":[{"name":"","type":"string"}],"stateMutability":"view"},
+    {"type":"function","name":"decimals","inputs":[],"outputs":[{"name":"","type":"uint8"}],"stateMutability":"view"}
+]"#;
+
+/// `DepositInitiated(bytes32 indexed l2DepositTxHash, address indexed from, address indexed to, address l1Token, uint256 amount)`
+/// event emitted by the L1 ERC-20 bridge.
+fn deposit_initiated_topic() -> H256 {
+    ethabi::long_signature(
+        "DepositInitiated",
+        &[
+            ParamType::FixedBytes(32),
+            ParamType::Address,
+            ParamType::Address,
+            ParamType::Address,
+            ParamType::Uint(256),
+        ],
+    )
+}
+
+/// L1 client used by the token fetcher.
+#[async_trait]
+pub trait TokenMetadataL1Client: 'static + fmt::Debug + Send + Sync {
+    /// Returns the latest L1 block number.
+    async fn block_number(&self) -> anyhow::Result<u64>;
+
+    /// Returns L1 addresses of tokens deposited via the L1 ERC-20 bridge in the specified inclusive range
+    /// of L1 blocks. Addresses may repeat.
+    async fn fetch_bridged_tokens(
+        &self,
+        from_block: u64,
+        to_block: u64,
+    ) -> anyhow::Result<Vec<Address>>;
+
+    /// Fetches ERC-20 metadata for the specified token. Errors are only returned for failures not caused
+    /// by the token contract itself (e.g., L1 being unreachable); otherwise, placeholders are used
+    /// for the missing metadata.
+    async fn fetch_metadata(&self, l1_address: Address) -> anyhow::Result<TokenMetadata>;
+}
+
+/// [`TokenMetadataL1Client`] using the L1 JSON-RPC API.
+#[derive(Debug)]
+pub struct Erc20MetadataClient<C> {
+    client: C,
+    l1_erc20_bridge_addr: Address,
+    contract: ethabi::Contract,
+}
+
+impl<C: EthInterface> Erc20MetadataClient<C> {
+    pub fn new(client: C, l1_erc20_bridge_addr: Address) -> Self {
+        Self {
+            client,
+            l1_erc20_bridge_addr,
+            contract: ethabi::Contract::load(ERC20_METADATA_ABI.as_bytes())
+                .expect("ERC-20 metadata ABI is invalid"),
+        }
+    }
+
+    /// Calls the specified argument-less method. Returns `Ok(None)` if the call has failed because
+    /// of the token contract (e.g., the method is not implemented or has reverted).
+    async fn call_method(
+        &self,
+        l1_address: Address,
+        method: &'static str,
+    ) -> anyhow::Result<Option<Vec<u8>>> {
+        let function = self
+            .contract
+            .function(method)
+            .expect("method missing from ERC-20 metadata ABI");
+        let request = CallRequest {
+            to: Some(l1_address),
+            data: Some(function.short_signature().to_vec().into()),
+            ..CallRequest::default()
+        };
+        match self.client.call(request, None, "token_fetcher").await {
+            Ok(output) => Ok(Some(output.0)),
+            Err(EthClientError::EthereumGateway(web3::Error::Rpc(err))) => {
+                tracing::info!("`{method}()` call for L1 token {l1_address:?} failed: {err}");
+                Ok(None)
+            }
+            Err(err) => Err(anyhow::Error::new(err).context(format!("{method}()"))),
+        }
+    }
+}
+
+/// Decodes a `name()` / `symbol()` output, which is a `string` for most tokens, but is `bytes32`
+/// for some older ones (e.g., MKR).
+pub(super) fn decode_string(output: &[u8]) -> Option<String> {
+    if let Ok(tokens) = ethabi::decode(&[ParamType::String], output) {
+        if let Some(Token::String(value)) = tokens.into_iter().next() {
+            return Some(value);
+        }
+    }
+    if output.len() == 32 {
+        let len = output.iter().position(|&byte| byte == 0).unwrap_or(32);
+        let value = std::str::from_utf8(&output[..len]).ok()?;
+        return (!value.is_empty()).then(|| value.to_owned());
+    }
+    None
+}
+
+pub(super) fn decode_decimals(output: &[u8]) -> Option<u8> {
+    let tokens = ethabi::decode(&[ParamType::Uint(8)], output).ok()?;
+    match tokens.into_iter().next()? {
+        Token::Uint(decimals) => u8::try_from(decimals).ok(),
+        _ => None,
+    }
+}
+
+#[async_trait]
+impl<C: EthInterface> TokenMetadataL1Client for Erc20MetadataClient<C> {
+    async fn block_number(&self) -> anyhow::Result<u64> {
+        let block_number = self
+            .client
+            .block_number("token_fetcher")
+            .await
+            .context("block_number()")?;
+        Ok(block_number.as_u64())
+    }
+
+    async fn fetch_bridged_tokens(
+        &self,
+        from_block: u64,
+        to_block: u64,
+    ) -> anyhow::Result<Vec<Address>> {
+        let filter = FilterBuilder::default()
+            .address(vec![self.l1_erc20_bridge_addr])
+            .from_block(BlockNumber::Number(from_block.into()))
+            .to_block(BlockNumber::Number(to_block.into()))
+            .topics(Some(vec![deposit_initiated_topic()]), None, None, None)
+            .build();
+        let logs = self
+            .client
+            .logs(filter, "token_fetcher")
+            .await
+            .context("logs()")?;
+
+        logs.into_iter()
+            .map(|log| {
+                // `l1Token` is the first non-indexed event param.
+                anyhow::ensure!(
+                    log.data.0.len() == 64,
+                    "unexpected `DepositInitiated` event data: {log:?}"
+                );
+                Ok(Address::from_slice(&log.data.0[12..32]))
+            })
+            .collect()
+    }
+
+    async fn fetch_metadata(&self, l1_address: Address) -> anyhow::Result<TokenMetadata> {
+        let default_metadata = TokenMetadata::default(l1_address);
+        // Some tokens don't implement `name()` / `symbol()`; we use placeholders for such tokens.
+        let name = self
+            .call_method(l1_address, "name")
+            .await?
+            .and_then(|output| decode_string(&output));
+        let symbol = self
+            .call_method(l1_address, "symbol")
+            .await?
+            .and_then(|output| decode_string(&output));
+        // `decimals()` is optional in ERC-20 as well; the bridge uses 18 decimals for such tokens.
+        let decimals = self
+            .call_method(l1_address, "decimals")
+            .await?
+            .and_then(|output| decode_decimals(&output));
+        if decimals.is_none() {
+            tracing::warn!(
+                "Cannot get `decimals()` for L1 token {l1_address:?}; using {}",
+                default_metadata.decimals
+            );
+        }
+
+        Ok(TokenMetadata {
+            name: name.unwrap_or(default_metadata.name),
+            symbol: symbol.unwrap_or(default_metadata.symbol),
+            decimals: decimals.unwrap_or(default_metadata.decimals),
+        })
+    }
+}
+
+/// Oracle providing USD prices of tokens.
+#[async_trait]
+pub trait TokenPriceOracle: 'static + fmt::Debug + Send + Sync {
+    /// Fetches USD prices for the specified L1 tokens. Tokens unknown to the oracle may be omitted from the output.
+    async fn fetch_prices(
+        &self,
+        l1_addresses: &[Address],
+    ) -> anyhow::Result<HashMap<Address, Ratio<BigUint>>>;
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenPriceEntry {
+    l1_address: Address,
+    #[serde(with = "UnsignedRatioSerializeAsDecimal")]
+    usd_price: Ratio<BigUint>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenPricesResponse {
+    prices: Vec<TokenPriceEntry>,
+}
+
+/// [`TokenPriceOracle`] implementation for an oracle exposing an HTTP API:
+///
+/// - `GET /prices?addresses=0x...,0x...` returns `{ "prices": [{ "l1_address": "0x...", "usd_price": "1.23" }] }`.
+#[derive(Debug)]
+pub struct HttpTokenPriceOracle {
+    client: reqwest::Client,
+    api_url: String,
+}
+
+impl HttpTokenPriceOracle {
+    const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+    pub fn new(api_url: &str) -> Self {
+        Self {
+            client: reqwest::Client::builder()
+                .timeout(Self::REQUEST_TIMEOUT)
+                .build()
+                .expect("failed building HTTP client"),
+            api_url: api_url.trim_end_matches('/').to_owned(),
+        }
+    }
+}
+
+#[async_trait]
+impl TokenPriceOracle for HttpTokenPriceOracle {
+    async fn fetch_prices(
+        &self,
+        l1_addresses: &[Address],
+    ) -> anyhow::Result<HashMap<Address, Ratio<BigUint>>> {
+        let addresses: Vec<_> = l1_addresses
+            .iter()
+            .map(|address| format!("{address:?}"))
+            .collect();
+        let response = self
+            .client
+            .get(format!("{}/prices", self.api_url))
+            .query(&[("addresses", addresses.join(","))])
+            .send()
+            .await
+            .context("failed sending request to price oracle")?
+            .error_for_status()
+            .context("price oracle returned error")?;
+        let response: TokenPricesResponse = response
+            .json()
+            .await
+            .context("failed parsing price oracle response")?;
+        Ok(response
+            .prices
+            .into_iter()
+            .map(|entry| (entry.l1_address, entry.usd_price))
+            .collect())
+    }
+}
@@ -0,0 +1,215 @@
+//! Fetcher of metadata and prices for tokens bridged to L2.
+
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+
+use anyhow::Context as _;
+use chrono::Utc;
+use tokio::sync::watch;
+use zksync_config::configs::TokenFetcherConfig;
+use zksync_dal::ConnectionPool;
+use zksync_types::{
+    tokens::{TokenMetadata, TokenPrice},
+    Address,
+};
+
+pub use self::clients::{
+    Erc20MetadataClient, HttpTokenPriceOracle, TokenMetadataL1Client, TokenPriceOracle,
+};
+
+mod clients;
+#[cfg(test)]
+mod tests;
+
+/// Maximum number of L1 blocks queried for bridge events at once.
+const MAX_L1_BLOCK_RANGE: u64 = 1_000;
+
+/// Maintains the `tokens` table:
+///
+/// - The fetcher discovers newly bridged tokens from `DepositInitiated` events of the L1 ERC-20 bridge
+///   and prefetches their ERC-20 metadata from L1.
+/// - Tokens are added to the table by the state keeper once they are deployed on L2 by the bridge. The fetcher
+///   sets metadata for such tokens (using prefetched metadata if available) and marks them as well-known.
+///   Ether is marked as well-known during genesis, so it's never processed by the fetcher.
+/// - If a price oracle is configured, the fetcher periodically updates USD prices of well-known tokens.
+#[derive(Debug)]
+pub struct TokenFetcher {
+    pool: ConnectionPool,
+    config: TokenFetcherConfig,
+    l1_client: Arc<dyn TokenMetadataL1Client>,
+    price_oracle: Option<Arc<dyn TokenPriceOracle>>,
+    /// Next L1 block to check for bridge events. Initialized with the L1 head on the first iteration;
+    /// tokens bridged earlier are still processed once they are added to the `tokens` table.
+    next_l1_block: Option<u64>,
+    /// Metadata prefetched for discovered tokens not yet marked as well-known.
+    prefetched_metadata: HashMap<Address, TokenMetadata>,
+}
+
+impl TokenFetcher {
+    pub fn new(
+        pool: ConnectionPool,
+        config: TokenFetcherConfig,
+        l1_client: Arc<dyn TokenMetadataL1Client>,
+    ) -> Self {
+        Self {
+            pool,
+            config,
+            l1_client,
+            price_oracle: None,
+            next_l1_block: None,
+            prefetched_metadata: HashMap::new(),
+        }
+    }
+
+    pub fn with_price_oracle(mut self, price_oracle: Arc<dyn TokenPriceOracle>) -> Self {
+        self.price_oracle = Some(price_oracle);
+        self
+    }
+
+    /// Discovers tokens from L1 bridge events and prefetches their metadata. Returns the number of discovered tokens.
+    async fn discover_tokens(&mut self) -> anyhow::Result<usize> {
+        let l1_head = self.l1_client.block_number().await?;
+        let from_block = *self.next_l1_block.get_or_insert(l1_head);
+        if from_block > l1_head {
+            return Ok(0);
+        }
+        let to_block = l1_head.min(from_block + MAX_L1_BLOCK_RANGE - 1);
+        let bridged_tokens = self
+            .l1_client
+            .fetch_bridged_tokens(from_block, to_block)
+            .await?;
+
+        let mut storage = self.pool.access_storage_tagged("token_fetcher").await?;
+        let well_known_tokens: HashSet<_> = storage
+            .tokens_web3_dal()
+            .get_well_known_tokens()
+            .await
+            .context("get_well_known_tokens()")?
+            .into_iter()
+            .map(|token| token.l1_address)
+            .collect();
+        drop(storage);
+
+        let mut discovered_count = 0;
+        for l1_address in bridged_tokens {
+            if well_known_tokens.contains(&l1_address)
+                || self.prefetched_metadata.contains_key(&l1_address)
+            {
+                continue;
+            }
+            let metadata = self.l1_client.fetch_metadata(l1_address).await?;
+            tracing::info!("Discovered bridged L1 token {l1_address:?}: {metadata:?}");
+            self.prefetched_metadata.insert(l1_address, metadata);
+            discovered_count += 1;
+        }
+        // Only advance the cursor once all tokens in the range are processed.
+        self.next_l1_block = Some(to_block + 1);
+        Ok(discovered_count)
+    }
+
+    /// Sets metadata for tokens not marked as well-known yet. Returns the number of updated tokens.
+    async fn update_metadata(&mut self) -> anyhow::Result<usize> {
+        let mut storage = self.pool.access_storage_tagged("token_fetcher").await?;
+        let unknown_tokens = storage.tokens_dal().get_unknown_l1_token_addresses().await;
+        drop(storage);
+
+        let mut updated_count = 0;
+        for l1_address in unknown_tokens {
+            let metadata = if let Some(metadata) = self.prefetched_metadata.remove(&l1_address) {
+                metadata
+            } else {
+                match self.l1_client.fetch_metadata(l1_address).await {
+                    Ok(metadata) => metadata,
+                    Err(err) => {
+                        // Proceed with other tokens; the failed token will be retried on the next iteration.
+                        tracing::warn!(
+                            "Failed fetching metadata for L1 token {l1_address:?}: {err:#}"
+                        );
+                        continue;
+                    }
+                }
+            };
+
+            tracing::info!("Setting metadata for L1 token {l1_address:?}: {metadata:?}");
+            let mut storage = self.pool.access_storage_tagged("token_fetcher").await?;
+            storage
+                .tokens_dal()
+                .update_well_known_l1_token(&l1_address, metadata)
+                .await;
+            updated_count += 1;
+        }
+        Ok(updated_count)
+    }
+
+    /// Fetches prices for well-known tokens from the oracle. Returns the number of updated prices.
+    async fn update_prices(&self, price_oracle: &dyn TokenPriceOracle) -> anyhow::Result<usize> {
+        let mut storage = self.pool.access_storage_tagged("token_fetcher").await?;
+        let tokens = storage
+            .tokens_web3_dal()
+            .get_well_known_tokens()
+            .await
+            .context("get_well_known_tokens()")?;
+        if tokens.is_empty() {
+            return Ok(0);
+        }
+        let l1_addresses: Vec<_> = tokens.iter().map(|token| token.l1_address).collect();
+        drop(storage);
+
+        let prices = price_oracle
+            .fetch_prices(&l1_addresses)
+            .await
+            .context("failed fetching token prices")?;
+        let last_updated = Utc::now();
+        let mut storage = self.pool.access_storage_tagged("token_fetcher").await?;
+        for (l1_address, usd_price) in &prices {
+            let price = TokenPrice {
+                usd_price: usd_price.clone(),
+                last_updated,
+            };
+            storage
+                .tokens_dal()
+                .set_l1_token_price(l1_address, price)
+                .await;
+        }
+        Ok(prices.len())
+    }
+
+    async fn run_iteration(&mut self) -> anyhow::Result<()> {
+        // Discovery errors shouldn't block processing tokens already added to the `tokens` table.
+        match self.discover_tokens().await {
+            Ok(discovered_count) if discovered_count > 0 => {
+                tracing::info!("Discovered {discovered_count} bridged tokens from L1 events");
+            }
+            Ok(_) => {}
+            Err(err) => tracing::warn!("Failed discovering bridged tokens: {err:#}"),
+        }
+        let updated_count = self.update_metadata().await?;
+        if updated_count > 0 {
+            tracing::info!("Updated metadata for {updated_count} tokens");
+        }
+        if let Some(price_oracle) = &self.price_oracle {
+            let updated_count = self.update_prices(price_oracle.as_ref()).await?;
+            tracing::debug!("Updated prices for {updated_count} tokens");
+        }
+        Ok(())
+    }
+
+    pub async fn run(mut self, mut stop_receiver: watch::Receiver<bool>) -> anyhow::Result<()> {
+        loop {
+            if *stop_receiver.borrow() {
+                tracing::info!("Stop signal received, token fetcher is shutting down");
+                return Ok(());
+            }
+
+            // Errors are not fatal since they are likely caused by L1 or the price oracle being unavailable.
+            if let Err(err) = self.run_iteration().await {
+                tracing::warn!("Failed updating tokens: {err:#}");
+            }
+            tokio::time::timeout(self.config.polling_interval(), stop_receiver.changed())
+                .await
+                .ok();
+        }
+    }
+}
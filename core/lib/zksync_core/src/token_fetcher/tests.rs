@@ -0,0 +1,190 @@
+//! Tests for the token fetcher.
+
+use std::{collections::HashMap, sync::Mutex};
+
+use async_trait::async_trait;
+use num::{rational::Ratio, BigUint};
+use zksync_types::{
+    ethabi::{self, Token},
+    tokens::{TokenInfo, TokenMetadata},
+    Address, L2ChainId,
+};
+
+use super::*;
+use crate::genesis::{ensure_genesis_state, GenesisParams};
+
+const L1_TOKEN_ADDRESS: Address = Address::repeat_byte(1);
+const L2_TOKEN_ADDRESS: Address = Address::repeat_byte(2);
+const BROKEN_L1_TOKEN_ADDRESS: Address = Address::repeat_byte(3);
+const BRIDGED_L1_TOKEN_ADDRESS: Address = Address::repeat_byte(5);
+
+#[derive(Debug, Default)]
+struct MockL1Client {
+    /// Bridged L1 tokens keyed by the L1 block number.
+    bridged_tokens: HashMap<u64, Address>,
+    metadata_requests: Mutex<Vec<Address>>,
+}
+
+#[async_trait]
+impl TokenMetadataL1Client for MockL1Client {
+    async fn block_number(&self) -> anyhow::Result<u64> {
+        Ok(10)
+    }
+
+    async fn fetch_bridged_tokens(
+        &self,
+        from_block: u64,
+        to_block: u64,
+    ) -> anyhow::Result<Vec<Address>> {
+        Ok((from_block..=to_block)
+            .filter_map(|block| self.bridged_tokens.get(&block).copied())
+            .collect())
+    }
+
+    async fn fetch_metadata(&self, l1_address: Address) -> anyhow::Result<TokenMetadata> {
+        self.metadata_requests.lock().unwrap().push(l1_address);
+        if l1_address == BROKEN_L1_TOKEN_ADDRESS {
+            anyhow::bail!("L1 is unavailable");
+        }
+        Ok(TokenMetadata {
+            name: "Test token".to_owned(),
+            symbol: "TEST".to_owned(),
+            decimals: 6,
+        })
+    }
+}
+
+#[derive(Debug)]
+struct MockPriceOracle;
+
+#[async_trait]
+impl TokenPriceOracle for MockPriceOracle {
+    async fn fetch_prices(
+        &self,
+        l1_addresses: &[Address],
+    ) -> anyhow::Result<HashMap<Address, Ratio<BigUint>>> {
+        Ok(l1_addresses
+            .iter()
+            .map(|&address| (address, Ratio::from_integer(BigUint::from(2_u32))))
+            .collect())
+    }
+}
+
+fn test_config() -> TokenFetcherConfig {
+    TokenFetcherConfig {
+        polling_interval_ms: 10,
+        price_oracle_url: None,
+    }
+}
+
+async fn prepare_storage(pool: &ConnectionPool) {
+    let mut storage = pool.access_storage().await.unwrap();
+    ensure_genesis_state(&mut storage, L2ChainId::default(), &GenesisParams::mock())
+        .await
+        .unwrap();
+    let bridged_tokens = [
+        (L1_TOKEN_ADDRESS, L2_TOKEN_ADDRESS),
+        (BROKEN_L1_TOKEN_ADDRESS, Address::repeat_byte(4)),
+    ];
+    let bridged_tokens = bridged_tokens
+        .into_iter()
+        .map(|(l1_address, l2_address)| TokenInfo {
+            l1_address,
+            l2_address,
+            metadata: TokenMetadata::default(l1_address),
+        })
+        .collect();
+    storage.tokens_dal().add_tokens(bridged_tokens).await;
+}
+
+#[test]
+fn decoding_metadata_outputs() {
+    let output = ethabi::encode(&[Token::String("Test token".to_owned())]);
+    assert_eq!(clients::decode_string(&output).unwrap(), "Test token");
+
+    // MKR returns its symbol as `bytes32`.
+    let mut output = [0_u8; 32];
+    output[..3].copy_from_slice(b"MKR");
+    assert_eq!(clients::decode_string(&output).unwrap(), "MKR");
+    assert_eq!(clients::decode_string(&[0; 32]), None);
+    assert_eq!(clients::decode_string(&[]), None);
+
+    let output = ethabi::encode(&[Token::Uint(6.into())]);
+    assert_eq!(clients::decode_decimals(&output), Some(6));
+    let output = ethabi::encode(&[Token::Uint(256.into())]);
+    assert_eq!(clients::decode_decimals(&output), None);
+    assert_eq!(clients::decode_decimals(&[]), None);
+}
+
+#[tokio::test]
+async fn fetching_token_metadata_and_prices() {
+    let pool = ConnectionPool::test_pool().await;
+    prepare_storage(&pool).await;
+
+    let mut fetcher =
+        TokenFetcher::new(pool.clone(), test_config(), Arc::<MockL1Client>::default())
+            .with_price_oracle(Arc::new(MockPriceOracle));
+    assert_eq!(fetcher.update_metadata().await.unwrap(), 1);
+
+    let mut storage = pool.access_storage().await.unwrap();
+    let unknown_tokens = storage.tokens_dal().get_unknown_l1_token_addresses().await;
+    assert_eq!(unknown_tokens, [BROKEN_L1_TOKEN_ADDRESS]);
+    let well_known_tokens = storage
+        .tokens_web3_dal()
+        .get_well_known_tokens()
+        .await
+        .unwrap();
+    let well_known_addresses: Vec<_> = well_known_tokens
+        .iter()
+        .map(|token| token.l1_address)
+        .collect();
+    assert!(well_known_addresses.contains(&L1_TOKEN_ADDRESS));
+
+    // Ether and the test token are well-known.
+    assert_eq!(fetcher.update_prices(&MockPriceOracle).await.unwrap(), 2);
+    let price = storage
+        .tokens_web3_dal()
+        .get_token_price(&L2_TOKEN_ADDRESS)
+        .await
+        .unwrap()
+        .expect("no price for token");
+    assert_eq!(price.usd_price, Ratio::from_integer(BigUint::from(2_u32)));
+}
+
+#[tokio::test]
+async fn discovering_tokens_from_l1_events() {
+    let pool = ConnectionPool::test_pool().await;
+    prepare_storage(&pool).await;
+
+    let l1_client = Arc::new(MockL1Client {
+        // The token bridged before the fetcher has started shouldn't be discovered.
+        bridged_tokens: HashMap::from([(5, L1_TOKEN_ADDRESS), (10, BRIDGED_L1_TOKEN_ADDRESS)]),
+        ..MockL1Client::default()
+    });
+    let mut fetcher = TokenFetcher::new(pool.clone(), test_config(), l1_client.clone());
+    assert_eq!(fetcher.discover_tokens().await.unwrap(), 1);
+    assert_eq!(fetcher.next_l1_block, Some(11));
+    assert!(fetcher
+        .prefetched_metadata
+        .contains_key(&BRIDGED_L1_TOKEN_ADDRESS));
+    // The L1 head hasn't changed, so no new tokens should be discovered.
+    assert_eq!(fetcher.discover_tokens().await.unwrap(), 0);
+
+    // Emulate the state keeper adding the bridged token.
+    let mut storage = pool.access_storage().await.unwrap();
+    let token = TokenInfo {
+        l1_address: BRIDGED_L1_TOKEN_ADDRESS,
+        l2_address: Address::repeat_byte(6),
+        metadata: TokenMetadata::default(BRIDGED_L1_TOKEN_ADDRESS),
+    };
+    storage.tokens_dal().add_tokens(vec![token]).await;
+    l1_client.metadata_requests.lock().unwrap().clear();
+
+    assert_eq!(fetcher.update_metadata().await.unwrap(), 2);
+    assert!(fetcher.prefetched_metadata.is_empty());
+    // Metadata for the bridged token must be taken from the prefetched metadata.
+    let metadata_requests = l1_client.metadata_requests.lock().unwrap().clone();
+    assert!(!metadata_requests.contains(&BRIDGED_L1_TOKEN_ADDRESS));
+    let unknown_tokens = storage.tokens_dal().get_unknown_l1_token_addresses().await;
+    assert_eq!(unknown_tokens, [BROKEN_L1_TOKEN_ADDRESS]);
+}
@@ -0,0 +1,29 @@
+//! Metrics for the transaction lifecycle event stream.
+
+use std::time::Duration;
+
+use vise::{Buckets, Counter, EncodeLabelSet, EncodeLabelValue, Family, Histogram, Metrics};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EncodeLabelValue, EncodeLabelSet)]
+#[metrics(label = "stage", rename_all = "snake_case")]
+pub(super) enum TxLifecycleStage {
+    Received,
+    IncludedInMiniblock,
+    SealedInBatch,
+    Committed,
+    Proven,
+    Executed,
+}
+
+#[derive(Debug, Metrics)]
+#[metrics(prefix = "server_tx_event_stream")]
+pub(super) struct TxEventStreamMetrics {
+    /// Number of published events for each transaction lifecycle stage.
+    pub published_events: Family<TxLifecycleStage, Counter>,
+    /// Latency of publishing a single chunk of events to the sink.
+    #[metrics(buckets = Buckets::LATENCIES)]
+    pub publish_latency: Histogram<Duration>,
+}
+
+#[vise::register]
+pub(super) static METRICS: vise::Global<TxEventStreamMetrics> = vise::Global::new();
@@ -0,0 +1,468 @@
+//! Stream of transaction lifecycle events published to an external sink (e.g., a message broker).
+
+use std::{ops, sync::Arc};
+
+use anyhow::Context as _;
+use chrono::NaiveDateTime;
+use serde::Serialize;
+use tokio::sync::watch;
+use zksync_config::configs::TxEventStreamConfig;
+use zksync_dal::{tx_event_stream_dal::TxEventStreamCursor, ConnectionPool, StorageProcessor};
+use zksync_types::{Address, L1BatchNumber, MiniblockNumber, H256};
+
+use self::metrics::{TxLifecycleStage, METRICS};
+pub use self::sinks::{KafkaRestSink, NatsSink, StdoutJsonSink, TxEventSink};
+
+mod metrics;
+mod sinks;
+#[cfg(test)]
+mod tests;
+
+/// Event in the lifecycle of a transaction.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum TxLifecycleEvent {
+    /// Transaction was received by the node.
+    Received {
+        tx_hash: H256,
+        initiator_address: Address,
+        /// Timestamp of receiving the transaction in milliseconds since UNIX epoch.
+        received_at_ms: i64,
+    },
+    /// Transaction was included into a sealed miniblock.
+    IncludedInMiniblock {
+        tx_hash: H256,
+        miniblock_number: MiniblockNumber,
+    },
+    /// L1 batch containing the transaction was sealed.
+    SealedInBatch {
+        tx_hash: H256,
+        l1_batch_number: L1BatchNumber,
+    },
+    /// L1 batch containing the transaction was committed on L1.
+    Committed {
+        tx_hash: H256,
+        l1_batch_number: L1BatchNumber,
+    },
+    /// L1 batch containing the transaction was proven on L1.
+    Proven {
+        tx_hash: H256,
+        l1_batch_number: L1BatchNumber,
+    },
+    /// L1 batch containing the transaction was executed on L1.
+    Executed {
+        tx_hash: H256,
+        l1_batch_number: L1BatchNumber,
+    },
+}
+
+impl TxLifecycleEvent {
+    fn tx_hash(&self) -> H256 {
+        match self {
+            Self::Received { tx_hash, .. }
+            | Self::IncludedInMiniblock { tx_hash, .. }
+            | Self::SealedInBatch { tx_hash, .. }
+            | Self::Committed { tx_hash, .. }
+            | Self::Proven { tx_hash, .. }
+            | Self::Executed { tx_hash, .. } => *tx_hash,
+        }
+    }
+
+    fn stage(&self) -> TxLifecycleStage {
+        match self {
+            Self::Received { .. } => TxLifecycleStage::Received,
+            Self::IncludedInMiniblock { .. } => TxLifecycleStage::IncludedInMiniblock,
+            Self::SealedInBatch { .. } => TxLifecycleStage::SealedInBatch,
+            Self::Committed { .. } => TxLifecycleStage::Committed,
+            Self::Proven { .. } => TxLifecycleStage::Proven,
+            Self::Executed { .. } => TxLifecycleStage::Executed,
+        }
+    }
+}
+
+/// Returns the range of blocks following `last_processed` up to `latest` (inclusive).
+fn next_range(
+    last_processed: Option<u32>,
+    latest: Option<u32>,
+) -> Option<ops::RangeInclusive<u32>> {
+    let latest = latest?;
+    let start = last_processed.map_or(0, |number| number + 1);
+    (start <= latest).then_some(start..=latest)
+}
+
+/// Truncates `transactions` fetched for the `range` of blocks with the specified `limit`, so that blocks
+/// are never split across iterations. Returns the last block for which all transactions are retained, or `None`
+/// if the first block in the range alone doesn't fit into the limit (in which case, `transactions` are not modified).
+fn truncate_to_complete_blocks<N>(
+    transactions: &mut Vec<(H256, N)>,
+    range: &ops::RangeInclusive<N>,
+    limit: usize,
+) -> Option<N>
+where
+    N: Copy + PartialEq + ops::Sub<u32, Output = N>,
+{
+    if transactions.len() < limit {
+        return Some(*range.end());
+    }
+    let Some(&(_, last_block)) = transactions.last() else {
+        return Some(*range.end());
+    };
+    if last_block == *range.start() {
+        return None;
+    }
+    // The last block may be incomplete, so it is deferred to the next iteration.
+    transactions.retain(|&(_, number)| number != last_block);
+    Some(last_block - 1)
+}
+
+/// Periodically polls Postgres for transaction lifecycle changes and publishes the corresponding events
+/// to a [`TxEventSink`].
+///
+/// Progress is persisted in Postgres only after events are accepted by the sink, so events are delivered
+/// at least once. On the first start, the stream begins from the current state of the node; historical events
+/// are not published.
+#[derive(Debug)]
+pub struct TxEventStream {
+    pool: ConnectionPool,
+    config: TxEventStreamConfig,
+    sink: Arc<dyn TxEventSink>,
+}
+
+impl TxEventStream {
+    pub fn new(
+        pool: ConnectionPool,
+        config: TxEventStreamConfig,
+        sink: Arc<dyn TxEventSink>,
+    ) -> Self {
+        Self { pool, config, sink }
+    }
+
+    async fn initial_cursor(
+        storage: &mut StorageProcessor<'_>,
+    ) -> anyhow::Result<TxEventStreamCursor> {
+        let last_received_tx = storage
+            .tx_event_stream_dal()
+            .get_last_received_transaction()
+            .await
+            .context("get_last_received_transaction()")?;
+        let (last_received_at, last_received_tx_hash) = match last_received_tx {
+            Some((received_at, tx_hash)) => (received_at, Some(tx_hash)),
+            None => (NaiveDateTime::default(), None),
+        };
+        let last_included_miniblock = storage
+            .blocks_dal()
+            .get_sealed_miniblock_number()
+            .await
+            .context("get_sealed_miniblock_number()")?;
+        let last_sealed_l1_batch = storage
+            .blocks_dal()
+            .get_sealed_l1_batch_number()
+            .await
+            .context("get_sealed_l1_batch_number()")?
+            .unwrap_or_default();
+        let last_committed_l1_batch = storage
+            .blocks_dal()
+            .get_number_of_last_l1_batch_committed_on_eth()
+            .await
+            .context("get_number_of_last_l1_batch_committed_on_eth()")?;
+        let last_proven_l1_batch = storage
+            .blocks_dal()
+            .get_number_of_last_l1_batch_proven_on_eth()
+            .await
+            .context("get_number_of_last_l1_batch_proven_on_eth()")?;
+        let last_executed_l1_batch = storage
+            .blocks_dal()
+            .get_number_of_last_l1_batch_executed_on_eth()
+            .await
+            .context("get_number_of_last_l1_batch_executed_on_eth()")?;
+
+        Ok(TxEventStreamCursor {
+            last_received_at,
+            last_received_tx_hash,
+            last_included_miniblock,
+            last_sealed_l1_batch,
+            last_committed_l1_batch,
+            last_proven_l1_batch,
+            last_executed_l1_batch,
+        })
+    }
+
+    /// Collects events for transactions included into miniblocks following `last_processed`. Returns
+    /// the number of the last processed miniblock, or `None` if there are no new miniblocks.
+    async fn collect_miniblock_events(
+        &self,
+        storage: &mut StorageProcessor<'_>,
+        last_processed: MiniblockNumber,
+        latest: MiniblockNumber,
+        events: &mut Vec<TxLifecycleEvent>,
+    ) -> anyhow::Result<Option<MiniblockNumber>> {
+        let Some(range) = next_range(Some(last_processed.0), Some(latest.0)) else {
+            return Ok(None);
+        };
+        let numbers = MiniblockNumber(*range.start())..=MiniblockNumber(*range.end());
+        let limit = self.config.max_events_per_iteration as usize;
+        let mut transactions = storage
+            .tx_event_stream_dal()
+            .get_miniblock_transactions(numbers.clone(), Some(limit))
+            .await
+            .with_context(|| format!("get_miniblock_transactions({numbers:?})"))?;
+        let last_processed = match truncate_to_complete_blocks(&mut transactions, &numbers, limit) {
+            Some(number) => number,
+            None => {
+                let first_miniblock = *numbers.start();
+                transactions = storage
+                    .tx_event_stream_dal()
+                    .get_miniblock_transactions(first_miniblock..=first_miniblock, None)
+                    .await
+                    .with_context(|| {
+                        format!("get_miniblock_transactions({first_miniblock}..={first_miniblock})")
+                    })?;
+                first_miniblock
+            }
+        };
+        events.extend(transactions.into_iter().map(|(tx_hash, miniblock_number)| {
+            TxLifecycleEvent::IncludedInMiniblock {
+                tx_hash,
+                miniblock_number,
+            }
+        }));
+        Ok(Some(last_processed))
+    }
+
+    /// Collects events for transactions included into L1 batches following `last_processed`. Returns
+    /// the number of the last processed L1 batch, or `None` if there are no new batches.
+    async fn collect_l1_batch_events(
+        &self,
+        storage: &mut StorageProcessor<'_>,
+        last_processed: Option<L1BatchNumber>,
+        latest: Option<L1BatchNumber>,
+        create_event: fn(H256, L1BatchNumber) -> TxLifecycleEvent,
+        events: &mut Vec<TxLifecycleEvent>,
+    ) -> anyhow::Result<Option<L1BatchNumber>> {
+        let range = next_range(
+            last_processed.map(|number| number.0),
+            latest.map(|number| number.0),
+        );
+        let Some(range) = range else {
+            return Ok(None);
+        };
+        let numbers = L1BatchNumber(*range.start())..=L1BatchNumber(*range.end());
+        let limit = self.config.max_events_per_iteration as usize;
+        let mut transactions = storage
+            .tx_event_stream_dal()
+            .get_l1_batch_transactions(numbers.clone(), Some(limit))
+            .await
+            .with_context(|| format!("get_l1_batch_transactions({numbers:?})"))?;
+        let last_processed = match truncate_to_complete_blocks(&mut transactions, &numbers, limit) {
+            Some(number) => number,
+            None => {
+                let first_l1_batch = *numbers.start();
+                transactions = storage
+                    .tx_event_stream_dal()
+                    .get_l1_batch_transactions(first_l1_batch..=first_l1_batch, None)
+                    .await
+                    .with_context(|| {
+                        format!("get_l1_batch_transactions({first_l1_batch}..={first_l1_batch})")
+                    })?;
+                first_l1_batch
+            }
+        };
+        events.extend(
+            transactions
+                .into_iter()
+                .map(|(tx_hash, number)| create_event(tx_hash, number)),
+        );
+        Ok(Some(last_processed))
+    }
+
+    /// Collects new events and publishes them to the sink. Returns `true` if any progress was made.
+    async fn publish_new_events(&self) -> anyhow::Result<bool> {
+        let mut storage = self.pool.access_storage_tagged("tx_event_stream").await?;
+        let cursor = storage
+            .tx_event_stream_dal()
+            .get_cursor()
+            .await
+            .context("get_cursor()")?;
+        let Some(prev_cursor) = cursor else {
+            let cursor = Self::initial_cursor(&mut storage).await?;
+            storage
+                .tx_event_stream_dal()
+                .set_cursor(&cursor)
+                .await
+                .context("set_cursor()")?;
+            tracing::info!("Initialized transaction event stream cursor: {cursor:?}");
+            return Ok(false);
+        };
+
+        let limit = self.config.max_events_per_iteration as usize;
+        let mut cursor = prev_cursor.clone();
+        let mut events = vec![];
+
+        let received_txs = storage
+            .tx_event_stream_dal()
+            .get_received_transactions(cursor.last_received_at, cursor.last_received_tx_hash, limit)
+            .await
+            .context("get_received_transactions()")?;
+        if let Some(last_tx) = received_txs.last() {
+            cursor.last_received_at = last_tx.received_at;
+            cursor.last_received_tx_hash = Some(last_tx.tx_hash);
+        }
+        events.extend(
+            received_txs
+                .into_iter()
+                .map(|tx| TxLifecycleEvent::Received {
+                    tx_hash: tx.tx_hash,
+                    initiator_address: tx.initiator_address,
+                    received_at_ms: tx.received_at.timestamp_millis(),
+                }),
+        );
+
+        let sealed_miniblock = storage
+            .blocks_dal()
+            .get_sealed_miniblock_number()
+            .await
+            .context("get_sealed_miniblock_number()")?;
+        let last_included_miniblock = self
+            .collect_miniblock_events(
+                &mut storage,
+                cursor.last_included_miniblock,
+                sealed_miniblock,
+                &mut events,
+            )
+            .await?;
+        if let Some(number) = last_included_miniblock {
+            cursor.last_included_miniblock = number;
+        }
+
+        let sealed_l1_batch = storage
+            .blocks_dal()
+            .get_sealed_l1_batch_number()
+            .await
+            .context("get_sealed_l1_batch_number()")?;
+        let last_sealed_l1_batch = self
+            .collect_l1_batch_events(
+                &mut storage,
+                Some(cursor.last_sealed_l1_batch),
+                sealed_l1_batch,
+                |tx_hash, l1_batch_number| TxLifecycleEvent::SealedInBatch {
+                    tx_hash,
+                    l1_batch_number,
+                },
+                &mut events,
+            )
+            .await?;
+        if let Some(number) = last_sealed_l1_batch {
+            cursor.last_sealed_l1_batch = number;
+        }
+
+        let committed_l1_batch = storage
+            .blocks_dal()
+            .get_number_of_last_l1_batch_committed_on_eth()
+            .await
+            .context("get_number_of_last_l1_batch_committed_on_eth()")?;
+        let last_committed_l1_batch = self
+            .collect_l1_batch_events(
+                &mut storage,
+                cursor.last_committed_l1_batch,
+                committed_l1_batch,
+                |tx_hash, l1_batch_number| TxLifecycleEvent::Committed {
+                    tx_hash,
+                    l1_batch_number,
+                },
+                &mut events,
+            )
+            .await?;
+        cursor.last_committed_l1_batch = last_committed_l1_batch.or(cursor.last_committed_l1_batch);
+
+        let proven_l1_batch = storage
+            .blocks_dal()
+            .get_number_of_last_l1_batch_proven_on_eth()
+            .await
+            .context("get_number_of_last_l1_batch_proven_on_eth()")?;
+        let last_proven_l1_batch = self
+            .collect_l1_batch_events(
+                &mut storage,
+                cursor.last_proven_l1_batch,
+                proven_l1_batch,
+                |tx_hash, l1_batch_number| TxLifecycleEvent::Proven {
+                    tx_hash,
+                    l1_batch_number,
+                },
+                &mut events,
+            )
+            .await?;
+        cursor.last_proven_l1_batch = last_proven_l1_batch.or(cursor.last_proven_l1_batch);
+
+        let executed_l1_batch = storage
+            .blocks_dal()
+            .get_number_of_last_l1_batch_executed_on_eth()
+            .await
+            .context("get_number_of_last_l1_batch_executed_on_eth()")?;
+        let last_executed_l1_batch = self
+            .collect_l1_batch_events(
+                &mut storage,
+                cursor.last_executed_l1_batch,
+                executed_l1_batch,
+                |tx_hash, l1_batch_number| TxLifecycleEvent::Executed {
+                    tx_hash,
+                    l1_batch_number,
+                },
+                &mut events,
+            )
+            .await?;
+        cursor.last_executed_l1_batch = last_executed_l1_batch.or(cursor.last_executed_l1_batch);
+        drop(storage);
+
+        if cursor == prev_cursor {
+            return Ok(false);
+        }
+
+        if !events.is_empty() {
+            let latency = METRICS.publish_latency.start();
+            self.sink
+                .publish(&events)
+                .await
+                .context("failed publishing transaction lifecycle events")?;
+            let latency = latency.observe();
+            for event in &events {
+                METRICS.published_events[&event.stage()].inc();
+            }
+            tracing::debug!(
+                "Published {} transaction lifecycle events in {latency:?}",
+                events.len()
+            );
+        }
+
+        // The cursor is only persisted after the events are accepted by the sink, which provides at-least-once delivery.
+        let mut storage = self.pool.access_storage_tagged("tx_event_stream").await?;
+        storage
+            .tx_event_stream_dal()
+            .set_cursor(&cursor)
+            .await
+            .context("set_cursor()")?;
+        Ok(true)
+    }
+
+    pub async fn run(self, mut stop_receiver: watch::Receiver<bool>) -> anyhow::Result<()> {
+        loop {
+            if *stop_receiver.borrow() {
+                tracing::info!("Stop signal received, transaction event stream is shutting down");
+                return Ok(());
+            }
+
+            // Errors are not fatal since they are likely caused by the sink being temporarily unavailable.
+            let made_progress = match self.publish_new_events().await {
+                Ok(made_progress) => made_progress,
+                Err(err) => {
+                    tracing::warn!("Failed publishing transaction lifecycle events: {err:#}");
+                    false
+                }
+            };
+            if !made_progress {
+                tokio::time::timeout(self.config.polling_interval(), stop_receiver.changed())
+                    .await
+                    .ok();
+            }
+        }
+    }
+}
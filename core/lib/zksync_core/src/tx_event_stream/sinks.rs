@@ -0,0 +1,183 @@
+//! Sinks for transaction lifecycle events.
+
+use std::{
+    fmt,
+    io::{self, Write as _},
+    time::Duration,
+};
+
+use anyhow::Context as _;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use super::TxLifecycleEvent;
+
+/// Sink to which transaction lifecycle events are published. Events may be delivered more than once
+/// (e.g., if the node is restarted after publishing events, but before persisting the progress), so consumers
+/// should be idempotent.
+#[async_trait]
+pub trait TxEventSink: 'static + fmt::Debug + Send + Sync {
+    /// Publishes the provided events. Events must be published in order; the method must return only
+    /// after all events are durably accepted by the sink.
+    async fn publish(&self, events: &[TxLifecycleEvent]) -> anyhow::Result<()>;
+}
+
+/// [`TxEventSink`] printing events to stdout as JSON lines.
+#[derive(Debug, Default)]
+pub struct StdoutJsonSink(());
+
+#[async_trait]
+impl TxEventSink for StdoutJsonSink {
+    async fn publish(&self, events: &[TxLifecycleEvent]) -> anyhow::Result<()> {
+        let mut stdout = io::stdout().lock();
+        for event in events {
+            serde_json::to_writer(&mut stdout, event).context("failed serializing event")?;
+            writeln!(stdout).context("failed writing to stdout")?;
+        }
+        stdout.flush().context("failed flushing stdout")
+    }
+}
+
+/// [`TxEventSink`] publishing events to a NATS JetStream subject as JSON messages.
+pub struct NatsSink {
+    context: async_nats::jetstream::Context,
+    subject: String,
+}
+
+impl fmt::Debug for NatsSink {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter
+            .debug_struct("NatsSink")
+            .field("subject", &self.subject)
+            .finish_non_exhaustive()
+    }
+}
+
+impl NatsSink {
+    pub async fn connect(url: &str, subject: String) -> anyhow::Result<Self> {
+        let client = async_nats::connect(url)
+            .await
+            .with_context(|| format!("failed connecting to NATS server at {url}"))?;
+        Ok(Self {
+            context: async_nats::jetstream::new(client),
+            subject,
+        })
+    }
+}
+
+#[async_trait]
+impl TxEventSink for NatsSink {
+    async fn publish(&self, events: &[TxLifecycleEvent]) -> anyhow::Result<()> {
+        let mut acks = Vec::with_capacity(events.len());
+        for event in events {
+            let payload = serde_json::to_vec(event).context("failed serializing event")?;
+            let ack = self
+                .context
+                .publish(self.subject.clone(), payload.into())
+                .await
+                .context("failed publishing event to NATS")?;
+            acks.push(ack);
+        }
+        // Acknowledgements are awaited after sending all messages so that messages are pipelined.
+        for ack in acks {
+            ack.await
+                .context("event was not acknowledged by NATS JetStream")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct KafkaRecord<'a> {
+    key: String,
+    value: &'a TxLifecycleEvent,
+}
+
+#[derive(Debug, Serialize)]
+struct KafkaProduceRequest<'a> {
+    records: Vec<KafkaRecord<'a>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct KafkaRecordOffset {
+    #[serde(default)]
+    error_code: Option<i64>,
+    #[serde(default)]
+    error: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct KafkaProduceResponse {
+    offsets: Vec<KafkaRecordOffset>,
+}
+
+/// [`TxEventSink`] publishing events to a Kafka topic as JSON messages via the Kafka REST proxy (v2 API).
+/// Messages are keyed by the transaction hash, so all events for a transaction end up in the same partition
+/// and are consumed in order.
+#[derive(Debug)]
+pub struct KafkaRestSink {
+    client: reqwest::Client,
+    topic_url: String,
+}
+
+impl KafkaRestSink {
+    const CONTENT_TYPE: &'static str = "application/vnd.kafka.json.v2+json";
+    const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+    pub fn new(proxy_url: &str, topic: &str) -> anyhow::Result<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(Self::REQUEST_TIMEOUT)
+            .build()
+            .context("failed building HTTP client")?;
+        Ok(Self {
+            client,
+            topic_url: format!("{}/topics/{topic}", proxy_url.trim_end_matches('/')),
+        })
+    }
+}
+
+#[async_trait]
+impl TxEventSink for KafkaRestSink {
+    async fn publish(&self, events: &[TxLifecycleEvent]) -> anyhow::Result<()> {
+        let records = events
+            .iter()
+            .map(|event| KafkaRecord {
+                key: format!("{:?}", event.tx_hash()),
+                value: event,
+            })
+            .collect();
+        let body = serde_json::to_vec(&KafkaProduceRequest { records })
+            .context("failed serializing events")?;
+        let response = self
+            .client
+            .post(&self.topic_url)
+            .header(reqwest::header::CONTENT_TYPE, Self::CONTENT_TYPE)
+            .body(body)
+            .send()
+            .await
+            .context("failed publishing events to Kafka REST proxy")?
+            .error_for_status()
+            .context("Kafka REST proxy returned error status")?;
+        let response: KafkaProduceResponse = response
+            .json()
+            .await
+            .context("failed parsing Kafka REST proxy response")?;
+
+        anyhow::ensure!(
+            response.offsets.len() == events.len(),
+            "Kafka REST proxy returned {} offsets for {} events",
+            response.offsets.len(),
+            events.len()
+        );
+        for offset in &response.offsets {
+            if offset.error_code.is_some() || offset.error.is_some() {
+                anyhow::bail!(
+                    "event was not accepted by Kafka (error code: {:?}): {}",
+                    offset.error_code,
+                    offset.error.as_deref().unwrap_or("unknown error")
+                );
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,330 @@
+//! Tests for the transaction lifecycle event stream.
+
+use std::{
+    slice,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+};
+
+use assert_matches::assert_matches;
+use async_trait::async_trait;
+use zksync_types::{
+    block::BlockGasCount,
+    fee::TransactionExecutionMetrics,
+    tx::{
+        tx_execution_info::{ExecutionMetrics, TxExecutionStatus},
+        TransactionExecutionResult,
+    },
+    L2ChainId,
+};
+
+use super::*;
+use crate::{
+    genesis::{ensure_genesis_state, GenesisParams},
+    utils::testonly::{create_l1_batch, create_l2_transaction, create_miniblock},
+};
+
+#[derive(Debug, Default)]
+struct MockSink {
+    events: Mutex<Vec<TxLifecycleEvent>>,
+    fail_next_publish: AtomicBool,
+}
+
+impl MockSink {
+    fn take_events(&self) -> Vec<TxLifecycleEvent> {
+        std::mem::take(&mut *self.events.lock().unwrap())
+    }
+}
+
+#[async_trait]
+impl TxEventSink for MockSink {
+    async fn publish(&self, events: &[TxLifecycleEvent]) -> anyhow::Result<()> {
+        if self.fail_next_publish.swap(false, Ordering::SeqCst) {
+            anyhow::bail!("sink is unavailable");
+        }
+        self.events.lock().unwrap().extend_from_slice(events);
+        Ok(())
+    }
+}
+
+fn test_config() -> TxEventStreamConfig {
+    TxEventStreamConfig {
+        sink: Default::default(),
+        nats_url: None,
+        nats_subject: "zksync.tx_events".to_owned(),
+        kafka_rest_proxy_url: None,
+        kafka_topic: "zksync.tx_events".to_owned(),
+        polling_interval_ms: 10,
+        max_events_per_iteration: 100,
+    }
+}
+
+async fn prepare_storage(pool: &ConnectionPool) {
+    let mut storage = pool.access_storage().await.unwrap();
+    ensure_genesis_state(&mut storage, L2ChainId::default(), &GenesisParams::mock())
+        .await
+        .unwrap();
+}
+
+async fn insert_transaction(pool: &ConnectionPool) -> TransactionExecutionResult {
+    let mut storage = pool.access_storage().await.unwrap();
+    let tx = create_l2_transaction(1, 2);
+    storage
+        .transactions_dal()
+        .insert_transaction_l2(tx.clone(), TransactionExecutionMetrics::default())
+        .await;
+    TransactionExecutionResult {
+        hash: tx.hash(),
+        transaction: tx.into(),
+        execution_info: ExecutionMetrics::default(),
+        execution_status: TxExecutionStatus::Success,
+        refunded_gas: 0,
+        operator_suggested_refund: 0,
+        compressed_bytecodes: vec![],
+        call_traces: vec![],
+        revert_reason: None,
+    }
+}
+
+async fn seal_miniblock_and_batch(pool: &ConnectionPool, tx: &TransactionExecutionResult) {
+    seal_miniblock(pool, 1, slice::from_ref(tx)).await;
+    let mut storage = pool.access_storage().await.unwrap();
+    storage
+        .blocks_dal()
+        .insert_l1_batch(
+            &create_l1_batch(1),
+            &[],
+            BlockGasCount::default(),
+            &[],
+            &[],
+            0,
+        )
+        .await
+        .unwrap();
+    storage
+        .blocks_dal()
+        .mark_miniblocks_as_executed_in_l1_batch(L1BatchNumber(1))
+        .await
+        .unwrap();
+    storage
+        .transactions_dal()
+        .mark_txs_as_executed_in_l1_batch(L1BatchNumber(1), &[tx.clone()])
+        .await;
+}
+
+async fn seal_miniblock(pool: &ConnectionPool, number: u32, txs: &[TransactionExecutionResult]) {
+    let mut storage = pool.access_storage().await.unwrap();
+    storage
+        .blocks_dal()
+        .insert_miniblock(&create_miniblock(number))
+        .await
+        .unwrap();
+    storage
+        .transactions_dal()
+        .mark_txs_as_executed_in_miniblock(MiniblockNumber(number), txs, 1.into())
+        .await;
+}
+
+#[test]
+fn computing_next_range() {
+    assert_eq!(next_range(None, None), None);
+    assert_eq!(next_range(None, Some(3)), Some(0..=3));
+    assert_eq!(next_range(Some(3), Some(3)), None);
+    assert_eq!(next_range(Some(3), Some(5)), Some(4..=5));
+    assert_eq!(next_range(Some(5), Some(3)), None);
+}
+
+#[test]
+fn truncating_transactions_to_complete_blocks() {
+    let range = MiniblockNumber(1)..=MiniblockNumber(10);
+    let tx = |number| (H256::zero(), MiniblockNumber(number));
+
+    let mut transactions = vec![tx(1), tx(3)];
+    assert_eq!(
+        truncate_to_complete_blocks(&mut transactions, &range, 3),
+        Some(MiniblockNumber(10))
+    );
+    assert_eq!(transactions, [tx(1), tx(3)]);
+
+    let mut transactions = vec![tx(1), tx(3), tx(3)];
+    assert_eq!(
+        truncate_to_complete_blocks(&mut transactions, &range, 3),
+        Some(MiniblockNumber(2))
+    );
+    assert_eq!(transactions, [tx(1)]);
+
+    let mut transactions = vec![tx(2), tx(3), tx(3)];
+    assert_eq!(
+        truncate_to_complete_blocks(&mut transactions, &range, 3),
+        Some(MiniblockNumber(2))
+    );
+    assert_eq!(transactions, [tx(2)]);
+
+    let mut transactions = vec![tx(1), tx(1), tx(1)];
+    assert_eq!(
+        truncate_to_complete_blocks(&mut transactions, &range, 3),
+        None
+    );
+    assert_eq!(transactions.len(), 3);
+}
+
+#[test]
+fn serializing_events() {
+    let event = TxLifecycleEvent::SealedInBatch {
+        tx_hash: H256::zero(),
+        l1_batch_number: L1BatchNumber(3),
+    };
+    let json = serde_json::to_value(event).unwrap();
+    assert_eq!(
+        json,
+        serde_json::json!({
+            "event": "sealed_in_batch",
+            "tx_hash": format!("{:?}", H256::zero()),
+            "l1_batch_number": 3,
+        })
+    );
+}
+
+#[tokio::test]
+async fn publishing_transaction_lifecycle_events() {
+    let pool = ConnectionPool::test_pool().await;
+    prepare_storage(&pool).await;
+    let sink = Arc::new(MockSink::default());
+    let stream = TxEventStream::new(pool.clone(), test_config(), sink.clone());
+
+    // The first iteration only initializes the cursor.
+    assert!(!stream.publish_new_events().await.unwrap());
+    assert!(sink.take_events().is_empty());
+    assert!(!stream.publish_new_events().await.unwrap());
+
+    let tx = insert_transaction(&pool).await;
+    assert!(stream.publish_new_events().await.unwrap());
+    let events = sink.take_events();
+    assert_eq!(events.len(), 1);
+    assert_matches!(
+        events[0],
+        TxLifecycleEvent::Received { tx_hash, .. } if tx_hash == tx.hash
+    );
+
+    seal_miniblock_and_batch(&pool, &tx).await;
+    assert!(stream.publish_new_events().await.unwrap());
+    let events = sink.take_events();
+    assert_eq!(
+        events,
+        [
+            TxLifecycleEvent::IncludedInMiniblock {
+                tx_hash: tx.hash,
+                miniblock_number: MiniblockNumber(1),
+            },
+            TxLifecycleEvent::SealedInBatch {
+                tx_hash: tx.hash,
+                l1_batch_number: L1BatchNumber(1),
+            },
+        ]
+    );
+    assert!(!stream.publish_new_events().await.unwrap());
+    assert!(sink.take_events().is_empty());
+}
+
+#[tokio::test]
+async fn events_are_republished_after_sink_failure() {
+    let pool = ConnectionPool::test_pool().await;
+    prepare_storage(&pool).await;
+    let sink = Arc::new(MockSink::default());
+    let stream = TxEventStream::new(pool.clone(), test_config(), sink.clone());
+    stream.publish_new_events().await.unwrap();
+
+    let tx = insert_transaction(&pool).await;
+    sink.fail_next_publish.store(true, Ordering::SeqCst);
+    stream.publish_new_events().await.unwrap_err();
+    assert!(sink.take_events().is_empty());
+
+    assert!(stream.publish_new_events().await.unwrap());
+    let events = sink.take_events();
+    assert_eq!(events.len(), 1);
+    assert_matches!(
+        events[0],
+        TxLifecycleEvent::Received { tx_hash, .. } if tx_hash == tx.hash
+    );
+}
+
+#[tokio::test]
+async fn events_are_limited_per_iteration() {
+    let pool = ConnectionPool::test_pool().await;
+    prepare_storage(&pool).await;
+    let sink = Arc::new(MockSink::default());
+    let config = TxEventStreamConfig {
+        max_events_per_iteration: 2,
+        ..test_config()
+    };
+    let stream = TxEventStream::new(pool.clone(), config, sink.clone());
+    stream.publish_new_events().await.unwrap();
+
+    let mut txs = vec![];
+    for _ in 0..3 {
+        txs.push(insert_transaction(&pool).await);
+    }
+    assert!(stream.publish_new_events().await.unwrap());
+    let events = sink.take_events();
+    assert_eq!(events.len(), 2);
+    assert!(stream.publish_new_events().await.unwrap());
+    let events = sink.take_events();
+    assert_eq!(events.len(), 1);
+    assert!(!stream.publish_new_events().await.unwrap());
+
+    seal_miniblock(&pool, 1, &txs[..1]).await;
+    seal_miniblock(&pool, 2, &txs[1..]).await;
+    // Miniblock #2 doesn't fit into the limit together with miniblock #1, so it is deferred.
+    assert!(stream.publish_new_events().await.unwrap());
+    let events = sink.take_events();
+    assert_eq!(
+        events,
+        [TxLifecycleEvent::IncludedInMiniblock {
+            tx_hash: txs[0].hash,
+            miniblock_number: MiniblockNumber(1),
+        }]
+    );
+    assert!(stream.publish_new_events().await.unwrap());
+    let events = sink.take_events();
+    let expected_events: Vec<_> = txs[1..]
+        .iter()
+        .map(|tx| TxLifecycleEvent::IncludedInMiniblock {
+            tx_hash: tx.hash,
+            miniblock_number: MiniblockNumber(2),
+        })
+        .collect();
+    assert_eq!(events, expected_events);
+    assert!(!stream.publish_new_events().await.unwrap());
+}
+
+#[tokio::test]
+async fn miniblock_exceeding_limit_is_published_whole() {
+    let pool = ConnectionPool::test_pool().await;
+    prepare_storage(&pool).await;
+    let sink = Arc::new(MockSink::default());
+    let config = TxEventStreamConfig {
+        max_events_per_iteration: 2,
+        ..test_config()
+    };
+    let stream = TxEventStream::new(pool.clone(), config, sink.clone());
+    stream.publish_new_events().await.unwrap();
+
+    let mut txs = vec![];
+    for _ in 0..3 {
+        txs.push(insert_transaction(&pool).await);
+    }
+    stream.publish_new_events().await.unwrap();
+    stream.publish_new_events().await.unwrap();
+    sink.take_events();
+
+    seal_miniblock(&pool, 1, &txs).await;
+    assert!(stream.publish_new_events().await.unwrap());
+    let events = sink.take_events();
+    assert_eq!(events.len(), 3);
+    assert!(events
+        .iter()
+        .all(|event| matches!(event, TxLifecycleEvent::IncludedInMiniblock { .. })));
+    assert!(!stream.publish_new_events().await.unwrap());
+}
@@ -0,0 +1,17 @@
+//! Metrics for the protocol upgrade orchestrator.
+
+use vise::{Gauge, Metrics};
+
+#[derive(Debug, Metrics)]
+#[metrics(prefix = "server_protocol_upgrades")]
+pub(super) struct ProtocolUpgradeMetrics {
+    /// Protocol version used by the latest L1 batch.
+    pub current_version: Gauge<u64>,
+    /// Latest protocol version the state keeper is allowed to switch to.
+    pub max_allowed_version: Gauge<u64>,
+    /// Set to 1 if a protocol upgrade is due, but is blocked by missing prerequisites.
+    pub blocked: Gauge<u64>,
+}
+
+#[vise::register]
+pub(super) static METRICS: vise::Global<ProtocolUpgradeMetrics> = vise::Global::new();
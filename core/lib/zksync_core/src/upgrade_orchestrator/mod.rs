@@ -0,0 +1,272 @@
+//! Orchestration of protocol upgrades on the main node.
+//!
+//! Protocol upgrades are scheduled on L1 and persisted to Postgres by the Ethereum watcher. The state keeper switches
+//! to a new protocol version once the upgrade timestamp is reached. This module makes sure that the switch only happens
+//! after the upgrade prerequisites are met: the new base system contracts are available, and (optionally)
+//! the prover supports verification keys of the new version. Until then, the state keeper doesn't open new L1 batches.
+
+use std::{collections::HashSet, time::Duration};
+
+use anyhow::Context as _;
+use serde::Serialize;
+use tokio::sync::watch;
+use zksync_config::configs::chain::StateKeeperConfig;
+use zksync_dal::{ConnectionPool, StorageProcessor};
+use zksync_health_check::{Health, HealthStatus, HealthUpdater, ReactiveHealthCheck};
+use zksync_types::{protocol_version::ProtocolVersion, ProtocolVersionId, H256};
+use zksync_utils::{bytecode::hash_bytecode, time::seconds_since_epoch};
+
+use self::metrics::METRICS;
+
+mod metrics;
+#[cfg(test)]
+mod tests;
+
+/// Interval between checks of upgrade prerequisites.
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Prerequisite of a protocol upgrade that must be met before the state keeper switches to the new version.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UpgradePrerequisite {
+    /// Bootloader bytecode of the new version is present in the DB or in the upgrade transaction.
+    Bootloader,
+    /// Default account bytecode of the new version is present in the DB or in the upgrade transaction.
+    DefaultAccount,
+    /// Prover supports verification keys of the new version.
+    ProverSupport,
+}
+
+/// Shared handle allowing the state keeper to check whether it can switch to a certain protocol version.
+#[derive(Debug, Clone)]
+pub struct ProtocolUpgradeGate {
+    max_allowed_version: watch::Receiver<ProtocolVersionId>,
+}
+
+impl ProtocolUpgradeGate {
+    /// Checks whether L1 batches with the specified protocol version can be created.
+    pub fn is_allowed(&self, version: ProtocolVersionId) -> bool {
+        version <= *self.max_allowed_version.borrow()
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct PendingUpgradeDetails {
+    version: u16,
+    timestamp: u64,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    missing_prerequisites: Vec<UpgradePrerequisite>,
+}
+
+#[derive(Debug, Serialize)]
+struct UpgradesHealthDetails {
+    current_version: u16,
+    max_allowed_version: u16,
+    pending_upgrades: Vec<PendingUpgradeDetails>,
+}
+
+/// Checks prerequisites of scheduled protocol upgrades, allowing the state keeper to switch
+/// to new protocol versions via [`ProtocolUpgradeGate`].
+#[derive(Debug)]
+pub struct ProtocolUpgradeOrchestrator {
+    pool: ConnectionPool,
+    requires_prover_support: bool,
+    max_allowed_version: watch::Sender<ProtocolVersionId>,
+    health_updater: HealthUpdater,
+}
+
+impl ProtocolUpgradeOrchestrator {
+    /// Creates a new orchestrator. Protocol versions already used by L1 batches are always allowed.
+    pub async fn new(pool: ConnectionPool, config: &StateKeeperConfig) -> anyhow::Result<Self> {
+        let mut storage = pool.access_storage_tagged("upgrade_orchestrator").await?;
+        let current_version = storage
+            .protocol_versions_dal()
+            .last_used_version_id()
+            .await
+            .context("no L1 batches in the DB; was genesis performed?")?;
+        drop(storage);
+
+        let (_, health_updater) = ReactiveHealthCheck::new("protocol_upgrades");
+        Ok(Self {
+            pool,
+            requires_prover_support: config.upgrade_requires_prover_support,
+            max_allowed_version: watch::channel(current_version).0,
+            health_updater,
+        })
+    }
+
+    /// Returns a gate for the state keeper.
+    pub fn gate(&self) -> ProtocolUpgradeGate {
+        ProtocolUpgradeGate {
+            max_allowed_version: self.max_allowed_version.subscribe(),
+        }
+    }
+
+    /// Returns a health check for this orchestrator.
+    pub fn health_check(&self) -> ReactiveHealthCheck {
+        self.health_updater.subscribe()
+    }
+
+    pub async fn run(self, stop_receiver: watch::Receiver<bool>) -> anyhow::Result<()> {
+        let mut timer = tokio::time::interval(POLL_INTERVAL);
+        while !*stop_receiver.borrow() {
+            timer.tick().await;
+            if let Err(err) = self.loop_iteration().await {
+                tracing::warn!("Failed checking protocol upgrade prerequisites: {err:#}");
+            }
+        }
+        tracing::info!("Stop signal received, protocol upgrade orchestrator is shutting down");
+        Ok(())
+    }
+
+    async fn loop_iteration(&self) -> anyhow::Result<()> {
+        let mut storage = self
+            .pool
+            .access_storage_tagged("upgrade_orchestrator")
+            .await?;
+        let current_version = storage
+            .protocol_versions_dal()
+            .last_used_version_id()
+            .await
+            .context("no L1 batches in the DB")?;
+        let mut pending_versions: Vec<_> = storage
+            .protocol_versions_dal()
+            .all_version_ids()
+            .await
+            .into_iter()
+            .filter(|&id| id > current_version)
+            .collect();
+        pending_versions.sort_unstable();
+
+        let mut max_allowed_version = current_version;
+        let mut is_blocked = false;
+        let mut pending_upgrades = Vec::with_capacity(pending_versions.len());
+        for version_id in pending_versions {
+            let version = storage
+                .protocol_versions_dal()
+                .get_protocol_version(version_id)
+                .await
+                .with_context(|| format!("protocol version {version_id:?} disappeared"))?;
+            let missing_prerequisites = self.check_prerequisites(&mut storage, &version).await?;
+            // Versions must be activated in order, so a version is blocked if any previous one is blocked.
+            is_blocked |= !missing_prerequisites.is_empty();
+            if !is_blocked {
+                max_allowed_version = version_id;
+            }
+            pending_upgrades.push(PendingUpgradeDetails {
+                version: version_id as u16,
+                timestamp: version.timestamp,
+                missing_prerequisites,
+            });
+        }
+        drop(storage);
+
+        let prev_max_allowed_version = self.max_allowed_version.send_replace(max_allowed_version);
+        if prev_max_allowed_version != max_allowed_version {
+            tracing::info!(
+                "Allowed switching to protocol version {max_allowed_version:?} (current version: {current_version:?})"
+            );
+        }
+        METRICS.current_version.set(current_version as u16 as u64);
+        METRICS
+            .max_allowed_version
+            .set(max_allowed_version as u16 as u64);
+
+        let health = Self::health(
+            current_version,
+            max_allowed_version,
+            pending_upgrades,
+            seconds_since_epoch(),
+        );
+        self.health_updater.update(health);
+        Ok(())
+    }
+
+    /// Checks prerequisites for the specified upgrade. Returns the list of prerequisites that are not met.
+    async fn check_prerequisites(
+        &self,
+        storage: &mut StorageProcessor<'_>,
+        version: &ProtocolVersion,
+    ) -> anyhow::Result<Vec<UpgradePrerequisite>> {
+        let mut missing_prerequisites = check_base_system_contracts(storage, version).await;
+        if self.requires_prover_support {
+            let prover_versions = storage
+                .fri_protocol_versions_dal()
+                .protocol_version_for(&version.l1_verifier_config)
+                .await;
+            if prover_versions.is_empty() {
+                missing_prerequisites.push(UpgradePrerequisite::ProverSupport);
+            }
+        }
+        Ok(missing_prerequisites)
+    }
+
+    fn health(
+        current_version: ProtocolVersionId,
+        max_allowed_version: ProtocolVersionId,
+        pending_upgrades: Vec<PendingUpgradeDetails>,
+        now: u64,
+    ) -> Health {
+        let blocked_upgrades = pending_upgrades.iter().filter(|upgrade| {
+            upgrade.timestamp <= now && upgrade.version > max_allowed_version as u16
+        });
+        let mut is_blocked = false;
+        for upgrade in blocked_upgrades {
+            is_blocked = true;
+            tracing::warn!(
+                "Protocol upgrade to version {} is due since {}, but is blocked by missing prerequisites: {:?}",
+                upgrade.version,
+                upgrade.timestamp,
+                upgrade.missing_prerequisites
+            );
+        }
+        METRICS.blocked.set(u64::from(is_blocked));
+
+        let details = UpgradesHealthDetails {
+            current_version: current_version as u16,
+            max_allowed_version: max_allowed_version as u16,
+            pending_upgrades,
+        };
+        let status = if is_blocked {
+            HealthStatus::Affected
+        } else {
+            HealthStatus::Ready
+        };
+        Health::from(status).with_details(details)
+    }
+}
+
+/// Checks that base system contracts of the `version` are available, either in the DB or in factory deps
+/// of the upgrade transaction. Returns prerequisites that are missing.
+///
+/// Bytecodes from the upgrade transaction are not persisted here: they are inserted as factory deps of
+/// the miniblock with the upgrade transaction when it is sealed, and are loaded from the transaction until then.
+async fn check_base_system_contracts(
+    storage: &mut StorageProcessor<'_>,
+    version: &ProtocolVersion,
+) -> Vec<UpgradePrerequisite> {
+    let hashes = version.base_system_contracts_hashes;
+    let upgrade_factory_deps = version
+        .tx
+        .as_ref()
+        .and_then(|tx| tx.execute.factory_deps.as_deref())
+        .unwrap_or_default();
+    let upgrade_bytecode_hashes: HashSet<H256> = upgrade_factory_deps
+        .iter()
+        .map(|bytecode| hash_bytecode(bytecode))
+        .collect();
+
+    let mut missing = vec![];
+    for (prerequisite, hash) in [
+        (UpgradePrerequisite::Bootloader, hashes.bootloader),
+        (UpgradePrerequisite::DefaultAccount, hashes.default_aa),
+    ] {
+        if upgrade_bytecode_hashes.contains(&hash) {
+            continue;
+        }
+        if storage.storage_dal().get_factory_dep(hash).await.is_none() {
+            missing.push(prerequisite);
+        }
+    }
+    missing
+}
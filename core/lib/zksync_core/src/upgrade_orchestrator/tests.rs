@@ -0,0 +1,163 @@
+//! Tests for the protocol upgrade orchestrator.
+
+use std::collections::HashMap;
+
+use zksync_contracts::BaseSystemContractsHashes;
+use zksync_health_check::CheckHealth;
+use zksync_types::{
+    protocol_version::{
+        FriProtocolVersionId, L1VerifierConfig, ProtocolUpgradeTx, ProtocolUpgradeTxCommonData,
+    },
+    Address, Execute, L2ChainId, MiniblockNumber, U256,
+};
+
+use super::*;
+use crate::genesis::{ensure_genesis_state, GenesisParams};
+
+fn mock_bootloader_bytecode() -> Vec<u8> {
+    vec![1; 32]
+}
+
+fn mock_upgrade_tx(factory_deps: Vec<Vec<u8>>) -> ProtocolUpgradeTx {
+    ProtocolUpgradeTx {
+        execute: Execute {
+            contract_address: Address::repeat_byte(0x11),
+            calldata: vec![1, 2, 3],
+            factory_deps: Some(factory_deps),
+            value: U256::zero(),
+        },
+        common_data: ProtocolUpgradeTxCommonData {
+            upgrade_id: ProtocolVersionId::next(),
+            sender: Address::repeat_byte(1),
+            eth_hash: H256::repeat_byte(2),
+            eth_block: 1,
+            gas_limit: Default::default(),
+            max_fee_per_gas: Default::default(),
+            gas_per_pubdata_limit: 1_u32.into(),
+            refund_recipient: Address::zero(),
+            to_mint: Default::default(),
+            canonical_tx_hash: H256::repeat_byte(3),
+        },
+        received_timestamp_ms: 0,
+    }
+}
+
+async fn prepare_storage(pool: &ConnectionPool, upgrade_tx: Option<ProtocolUpgradeTx>) {
+    let mut storage = pool.access_storage().await.unwrap();
+    let genesis_params = GenesisParams::mock();
+    ensure_genesis_state(&mut storage, L2ChainId::default(), &genesis_params)
+        .await
+        .unwrap();
+
+    let new_version = ProtocolVersion {
+        id: ProtocolVersionId::next(),
+        timestamp: 0,
+        base_system_contracts_hashes: BaseSystemContractsHashes {
+            bootloader: hash_bytecode(&mock_bootloader_bytecode()),
+            default_aa: genesis_params.base_system_contracts.hashes().default_aa,
+        },
+        tx: upgrade_tx,
+        ..ProtocolVersion::default()
+    };
+    storage
+        .protocol_versions_dal()
+        .save_protocol_version_with_tx(new_version)
+        .await;
+}
+
+async fn orchestrator(
+    pool: &ConnectionPool,
+    config: &StateKeeperConfig,
+) -> ProtocolUpgradeOrchestrator {
+    ProtocolUpgradeOrchestrator::new(pool.clone(), config)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn upgrade_is_blocked_until_base_system_contracts_are_available() {
+    let pool = ConnectionPool::test_pool().await;
+    prepare_storage(&pool, None).await;
+    let orchestrator = orchestrator(&pool, &StateKeeperConfig::default()).await;
+    let gate = orchestrator.gate();
+    let health_check = orchestrator.health_check();
+    assert!(gate.is_allowed(ProtocolVersionId::latest()));
+    assert!(!gate.is_allowed(ProtocolVersionId::next()));
+
+    orchestrator.loop_iteration().await.unwrap();
+    assert!(!gate.is_allowed(ProtocolVersionId::next()));
+    let health = health_check.check_health().await;
+    assert_eq!(health.status(), HealthStatus::Affected);
+    let health = serde_json::to_value(health).unwrap();
+    assert_eq!(
+        health["details"]["pending_upgrades"][0]["missing_prerequisites"],
+        serde_json::json!(["bootloader"])
+    );
+
+    let mut storage = pool.access_storage().await.unwrap();
+    let bootloader = mock_bootloader_bytecode();
+    let factory_deps = HashMap::from([(hash_bytecode(&bootloader), bootloader)]);
+    storage
+        .storage_dal()
+        .insert_factory_deps(MiniblockNumber(0), &factory_deps)
+        .await;
+    drop(storage);
+
+    orchestrator.loop_iteration().await.unwrap();
+    assert!(gate.is_allowed(ProtocolVersionId::next()));
+    let health = health_check.check_health().await;
+    assert_eq!(health.status(), HealthStatus::Ready);
+}
+
+#[tokio::test]
+async fn base_system_contracts_are_taken_from_upgrade_tx() {
+    let pool = ConnectionPool::test_pool().await;
+    let upgrade_tx = mock_upgrade_tx(vec![mock_bootloader_bytecode()]);
+    prepare_storage(&pool, Some(upgrade_tx)).await;
+    let orchestrator = orchestrator(&pool, &StateKeeperConfig::default()).await;
+    let gate = orchestrator.gate();
+
+    orchestrator.loop_iteration().await.unwrap();
+    assert!(gate.is_allowed(ProtocolVersionId::next()));
+
+    // The bytecode must not be attached to an already sealed miniblock; it will be inserted
+    // together with the miniblock containing the upgrade transaction.
+    let mut storage = pool.access_storage().await.unwrap();
+    let bootloader_hash = hash_bytecode(&mock_bootloader_bytecode());
+    let stored_bytecode = storage.storage_dal().get_factory_dep(bootloader_hash).await;
+    assert_eq!(stored_bytecode, None);
+
+    let base_system_contracts = storage
+        .protocol_versions_dal()
+        .load_base_system_contracts_by_version_id(ProtocolVersionId::next() as u16)
+        .await
+        .unwrap();
+    assert_eq!(base_system_contracts.bootloader.hash, bootloader_hash);
+}
+
+#[tokio::test]
+async fn upgrade_waits_for_prover_support() {
+    let pool = ConnectionPool::test_pool().await;
+    let upgrade_tx = mock_upgrade_tx(vec![mock_bootloader_bytecode()]);
+    prepare_storage(&pool, Some(upgrade_tx)).await;
+    let config = StateKeeperConfig {
+        upgrade_orchestration_enabled: true,
+        upgrade_requires_prover_support: true,
+        ..StateKeeperConfig::default()
+    };
+    let orchestrator = orchestrator(&pool, &config).await;
+    let gate = orchestrator.gate();
+
+    orchestrator.loop_iteration().await.unwrap();
+    assert!(!gate.is_allowed(ProtocolVersionId::next()));
+
+    let mut storage = pool.access_storage().await.unwrap();
+    storage
+        .fri_protocol_versions_dal()
+        .save_prover_protocol_version(FriProtocolVersionId::next(), L1VerifierConfig::default())
+        .await;
+    drop(storage);
+
+    orchestrator.loop_iteration().await.unwrap();
+    assert!(gate.is_allowed(ProtocolVersionId::next()));
+}
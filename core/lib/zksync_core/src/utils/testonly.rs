@@ -7,9 +7,12 @@ use zksync_types::{
     commitment::{L1BatchMetaParameters, L1BatchMetadata},
     fee::Fee,
     fee_model::BatchFeeInput,
+    l1::{L1Tx, OpProcessingType, PriorityQueueType},
     l2::L2Tx,
+    protocol_version::{ProtocolUpgradeTx, ProtocolUpgradeTxCommonData},
     transaction_request::PaymasterParams,
-    Address, L1BatchNumber, L2ChainId, MiniblockNumber, Nonce, ProtocolVersionId, H256, U256,
+    Address, Execute, L1BatchNumber, L1TxCommonData, L2ChainId, MiniblockNumber, Nonce,
+    PriorityOpId, ProtocolVersionId, H256, U256,
 };
 
 /// Creates a miniblock header with the specified number and deterministic contents.
@@ -92,3 +95,60 @@ pub(crate) fn create_l2_transaction(fee_per_gas: u64, gas_per_pubdata: u32) -> L
     tx.set_input(H256::random().0.to_vec(), H256::random());
     tx
 }
+
+fn create_execute() -> Execute {
+    Execute {
+        contract_address: Address::random(),
+        calldata: vec![1, 2, 3],
+        factory_deps: None,
+        value: U256::zero(),
+    }
+}
+
+/// Creates a priority (L1 -> L2) transaction with the specified serial ID and a random hash.
+pub(crate) fn create_l1_transaction(serial_id: PriorityOpId, eth_block: u64) -> L1Tx {
+    L1Tx {
+        execute: create_execute(),
+        common_data: L1TxCommonData {
+            serial_id,
+            sender: Address::random(),
+            deadline_block: 0,
+            eth_hash: H256::random(),
+            eth_block,
+            gas_limit: 1000_u64.into(),
+            max_fee_per_gas: 10_u64.into(),
+            gas_per_pubdata_limit: 100_u32.into(),
+            full_fee: U256::zero(),
+            layer_2_tip_fee: U256::zero(),
+            refund_recipient: Address::zero(),
+            to_mint: U256::zero(),
+            priority_queue_type: PriorityQueueType::Deque,
+            op_processing_type: OpProcessingType::Common,
+            canonical_tx_hash: H256::random(),
+        },
+        received_timestamp_ms: 0,
+    }
+}
+
+/// Creates a protocol upgrade transaction for the specified protocol version with a random hash.
+pub(crate) fn create_upgrade_transaction(
+    upgrade_id: ProtocolVersionId,
+    eth_block: u64,
+) -> ProtocolUpgradeTx {
+    ProtocolUpgradeTx {
+        execute: create_execute(),
+        common_data: ProtocolUpgradeTxCommonData {
+            upgrade_id,
+            sender: Address::random(),
+            eth_hash: H256::random(),
+            eth_block,
+            gas_limit: 1000_u64.into(),
+            max_fee_per_gas: 10_u64.into(),
+            gas_per_pubdata_limit: 100_u32.into(),
+            refund_recipient: Address::zero(),
+            to_mint: U256::zero(),
+            canonical_tx_hash: H256::random(),
+        },
+        received_timestamp_ms: 0,
+    }
+}
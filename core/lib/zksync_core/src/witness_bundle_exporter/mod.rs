@@ -0,0 +1,90 @@
+//! Export of self-contained L1 batch witness bundles for external re-proving and auditing.
+
+use std::sync::Arc;
+
+use anyhow::Context as _;
+use zksync_dal::ConnectionPool;
+use zksync_object_store::ObjectStore;
+use zksync_types::{
+    proofs::L1BatchWitnessBundle, storage::witness_block_state::WitnessBlockState, L1BatchNumber,
+};
+
+#[cfg(test)]
+mod tests;
+
+/// Assembles [`L1BatchWitnessBundle`]s from the artifacts produced for the prover and uploads them
+/// to the object store, so that third parties can re-prove or audit L1 batches without access to Postgres.
+///
+/// The exporter relies on the following data:
+///
+/// - Merkle paths uploaded by the metadata calculator (requires the tree to run in the full mode)
+/// - Storage reads uploaded by the basic witness input producer
+/// - Initial bootloader memory stored in Postgres
+#[derive(Debug)]
+pub struct WitnessBundleExporter {
+    pool: ConnectionPool,
+    object_store: Arc<dyn ObjectStore>,
+}
+
+impl WitnessBundleExporter {
+    pub fn new(pool: ConnectionPool, object_store: Arc<dyn ObjectStore>) -> Self {
+        Self { pool, object_store }
+    }
+
+    /// Creates a witness bundle for the specified L1 batch without uploading it.
+    pub async fn create_bundle(
+        &self,
+        l1_batch_number: L1BatchNumber,
+    ) -> anyhow::Result<L1BatchWitnessBundle> {
+        let mut storage = self.pool.access_storage_tagged("witness_bundle").await?;
+        let initial_bootloader_memory = storage
+            .blocks_dal()
+            .get_initial_bootloader_heap(l1_batch_number)
+            .await
+            .with_context(|| format!("get_initial_bootloader_heap({l1_batch_number})"))?
+            .with_context(|| format!("L1 batch #{l1_batch_number} is not present in Postgres"))?;
+        drop(storage);
+
+        let merkle_paths = self
+            .object_store
+            .get_witness_input(l1_batch_number)
+            .await
+            .with_context(|| {
+                format!("failed getting Merkle paths for L1 batch #{l1_batch_number}")
+            })?;
+        let storage_state: WitnessBlockState = self
+            .object_store
+            .get(l1_batch_number)
+            .await
+            .with_context(|| {
+                format!("failed getting storage reads for L1 batch #{l1_batch_number}")
+            })?;
+        Ok(L1BatchWitnessBundle::new(
+            l1_batch_number,
+            storage_state,
+            merkle_paths,
+            initial_bootloader_memory,
+        ))
+    }
+
+    /// Creates a witness bundle for the specified L1 batch and uploads it to the object store.
+    /// Returns the path to the uploaded bundle.
+    pub async fn export(&self, l1_batch_number: L1BatchNumber) -> anyhow::Result<String> {
+        let bundle = self.create_bundle(l1_batch_number).await?;
+        let path = self
+            .object_store
+            .put(l1_batch_number, &bundle)
+            .await
+            .with_context(|| {
+                format!("failed uploading witness bundle for L1 batch #{l1_batch_number}")
+            })?;
+        tracing::info!(
+            "Exported witness bundle for L1 batch #{l1_batch_number} with {} initial reads, {} initial writes \
+             and {} repeated writes to `{path}`",
+            bundle.initial_reads.len(),
+            bundle.initial_writes.len(),
+            bundle.repeated_writes.len()
+        );
+        Ok(path)
+    }
+}
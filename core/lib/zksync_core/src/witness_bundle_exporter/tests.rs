@@ -0,0 +1,88 @@
+//! Tests for witness bundle export.
+
+use std::collections::HashMap;
+
+use zksync_object_store::{ObjectStoreError, ObjectStoreFactory};
+use zksync_types::{
+    block::BlockGasCount, proofs::StorageLogMetadata, AccountTreeId, Address, L2ChainId,
+    StorageKey, H256, U256,
+};
+
+use super::*;
+use crate::{
+    genesis::{ensure_genesis_state, GenesisParams},
+    utils::testonly::create_l1_batch,
+};
+
+#[tokio::test]
+async fn exporting_witness_bundle() {
+    let pool = ConnectionPool::test_pool().await;
+    let mut storage = pool.access_storage().await.unwrap();
+    ensure_genesis_state(&mut storage, L2ChainId::default(), &GenesisParams::mock())
+        .await
+        .unwrap();
+    let bootloader_memory = vec![(0, U256::from(1)), (10, U256::from(2))];
+    storage
+        .blocks_dal()
+        .insert_l1_batch(
+            &create_l1_batch(1),
+            &bootloader_memory,
+            BlockGasCount::default(),
+            &[],
+            &[],
+            0,
+        )
+        .await
+        .unwrap();
+    drop(storage);
+
+    let object_store = ObjectStoreFactory::mock().create_store().await;
+    let exporter = WitnessBundleExporter::new(pool, object_store.clone());
+    // Artifacts for the batch are not uploaded yet.
+    exporter.export(L1BatchNumber(1)).await.unwrap_err();
+
+    let read_key = StorageKey::new(AccountTreeId::new(Address::repeat_byte(1)), H256::zero());
+    let storage_state = WitnessBlockState {
+        read_storage_key: HashMap::from([(read_key, H256::repeat_byte(1))]),
+        is_write_initial: HashMap::new(),
+    };
+    object_store
+        .put(L1BatchNumber(1), &storage_state)
+        .await
+        .unwrap();
+    let mut merkle_paths = PrepareBasicCircuitsJob::new(10);
+    merkle_paths.push_merkle_path(StorageLogMetadata {
+        root_hash: [1; 32],
+        is_write: true,
+        first_write: true,
+        merkle_paths: vec![[0; 32]; 256],
+        leaf_hashed_key: U256::from(123),
+        leaf_enumeration_index: 10,
+        value_written: [2; 32],
+        value_read: [0; 32],
+    });
+    object_store
+        .put(L1BatchNumber(1), &merkle_paths)
+        .await
+        .unwrap();
+
+    exporter.export(L1BatchNumber(1)).await.unwrap();
+    let bundle: L1BatchWitnessBundle = object_store.get(L1BatchNumber(1)).await.unwrap();
+    assert_eq!(bundle.l1_batch_number, L1BatchNumber(1));
+    assert_eq!(bundle.initial_reads, [(read_key, H256::repeat_byte(1))]);
+    assert_eq!(bundle.initial_writes.len(), 1);
+    assert_eq!(bundle.initial_writes[0].key, U256::from(123));
+    assert!(bundle.repeated_writes.is_empty());
+    assert_eq!(bundle.initial_bootloader_memory, bootloader_memory);
+
+    // Bundles cannot be exported for missing batches.
+    let err = exporter.export(L1BatchNumber(2)).await.unwrap_err();
+    assert!(format!("{err:#}").contains("not present"), "{err:#}");
+    let missing_bundle = object_store
+        .get::<L1BatchWitnessBundle>(L1BatchNumber(2))
+        .await;
+    assert!(matches!(
+        missing_bundle,
+        Err(ObjectStoreError::KeyNotFound(_))
+    ));
+}
@@ -26,7 +26,10 @@ const CONFIG_FILES = [
     'proof_data_handler.toml',
     'fri_witness_vector_generator.toml',
     'fri_prover_gateway.toml',
-    'fri_proof_compressor.toml'
+    'fri_proof_compressor.toml',
+    'da_dispatcher.toml',
+    'tx_event_stream.toml',
+    'token_fetcher.toml'
 ];
 
 function loadConfigFile(path: string) {